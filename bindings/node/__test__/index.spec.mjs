@@ -0,0 +1,35 @@
+import { test } from 'node:test'
+import assert from 'node:assert/strict'
+import { writeFileSync, rmSync } from 'node:fs'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+import { createRequire } from 'node:module'
+
+const require = createRequire(import.meta.url)
+const { WilliwNode } = require('../index.js')
+
+test('加载模型并推理', async () => {
+  const node = new WilliwNode()
+  const path = join(tmpdir(), `williw-node-model-${process.pid}.json`)
+  writeFileSync(path, JSON.stringify({ dim: 2, values: [0.5, 2.0], version: 1 }))
+  try {
+    const version = await node.loadModel(path)
+    assert.equal(node.modelVersion(), version)
+    assert.deepEqual(Array.from(await node.infer(new Float32Array([2, 1]))), [3])
+    assert.equal(node.status().state, 'Stopped')
+    assert.ok(node.unloadModel())
+  } finally {
+    rmSync(path, { force: true })
+  }
+})
+
+test('订阅状态推送', async () => {
+  const node = new WilliwNode()
+  const status = await new Promise((resolve) => {
+    const subscription = node.subscribeStats((status) => {
+      subscription.unsubscribe()
+      resolve(status)
+    }, 100)
+  })
+  assert.equal(status.state, 'Stopped')
+})
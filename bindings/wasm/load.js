@@ -0,0 +1,19 @@
+// 按页面环境加载合适的 WASM 构建
+//
+// 跨源隔离的页面加载多线程构建（pkg-threads，启用 wasm-threads 特性）并初始化 Worker 线程池，
+// 否则加载单线程构建（pkg）：共享内存的模块在非隔离页面中无法实例化。
+export async function loadWilliw() {
+  if (self.crossOriginIsolated) {
+    try {
+      const williw = await import('./pkg-threads/williw.js')
+      await williw.default()
+      const threads = await williw.initThreads()
+      return { williw, threads }
+    } catch (e) {
+      console.warn('[williw] 多线程构建加载失败，改用单线程构建', e)
+    }
+  }
+  const williw = await import('./pkg/williw.js')
+  await williw.default()
+  return { williw, threads: 1 }
+}
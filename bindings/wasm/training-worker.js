@@ -0,0 +1,46 @@
+// 浏览器训练 Worker：在后台线程运行 WasmTrainer
+//
+// 页面发送：
+//   { type: 'init', config, shardUrl }            初始化并下载模型分片
+//   { type: 'batch', inputs, targets }             训练一步（Float32Array，行主序）
+//   { type: 'gradient', bytes }                    其他访客的梯度（来自 WebRtcGradientTransport）
+// Worker 回复：
+//   { type: 'ready', backend }
+//   { type: 'step', step, loss, backend, mergedPeers, gradient }   gradient 需由页面广播
+//   { type: 'error', message }
+import init, { WasmTrainer } from './pkg/williw.js'
+
+let trainer = null
+
+self.onmessage = async ({ data }) => {
+  try {
+    switch (data.type) {
+      case 'init':
+        await init()
+        trainer = await WasmTrainer.create(data.config)
+        await trainer.loadShardFromUrl(data.shardUrl)
+        self.postMessage({ type: 'ready', backend: trainer.backend })
+        break
+      case 'batch': {
+        const result = await trainer.trainStep(data.inputs, data.targets)
+        self.postMessage(
+          {
+            type: 'step',
+            step: result.step,
+            loss: result.loss,
+            backend: result.backend,
+            mergedPeers: result.merged_peers,
+            gradient: result.gradient,
+          },
+          [result.gradient.buffer],
+        )
+        break
+      }
+      case 'gradient':
+        trainer?.receiveGradient(data.bytes)
+        break
+    }
+  } catch (e) {
+    self.postMessage({ type: 'error', message: String(e) })
+  }
+}
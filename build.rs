@@ -0,0 +1,110 @@
+//! 构建脚本：生成构建元数据
+//!
+//! 输出给 `src/build_info.rs` 使用的环境变量：
+//! - `WILLIW_GIT_COMMIT`：构建时的 git 提交（源码包构建时为 unknown）
+//! - `WILLIW_FEATURES`：启用的 cargo feature（排序后逗号分隔）
+//! - `WILLIW_TARGET`：目标三元组
+//! - `WILLIW_RUSTC_VERSION`：编译器版本
+//! - `WILLIW_SOURCE_HASH`：可复现构建哈希，只依赖源码、清单、feature 和目标，
+//!   相同输入在任何机器上得到相同结果
+//!
+//! 启用 `ios` 特性时还会用 cbindgen 生成 C 头文件 `include/williw.h`；
+//! 启用 `uniffi` 特性时从 `src/williw.udl` 生成绑定脚手架；启用 `nodejs` 特性时
+//! 设置 Node.js 原生插件所需的链接参数。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            out.push(path);
+        }
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 从通用 FFI 和 iOS 导出层生成 C 头文件
+#[cfg(feature = "cbindgen")]
+fn generate_header(manifest_dir: &Path) {
+    let config = cbindgen::Config::from_file(manifest_dir.join("cbindgen.toml")).unwrap_or_default();
+    match cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(manifest_dir.join("src/network/ffi.rs"))
+        .with_src(manifest_dir.join("src/ffi/ios/mod.rs"))
+        .with_src(manifest_dir.join("src/ffi/ios/callbacks.rs"))
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(manifest_dir.join("include/williw.h"));
+        }
+        Err(e) => println!("cargo:warning=生成 C 头文件失败: {}", e),
+    }
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
+
+fn main() {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase().replace('_', "-")))
+        .collect();
+    features.sort();
+    let features = features.join(",");
+    let target = std::env::var("TARGET").unwrap_or_default();
+
+    let mut sources = Vec::new();
+    collect_sources(&manifest_dir.join("src"), &mut sources);
+    sources.push(manifest_dir.join("Cargo.toml"));
+    sources.push(manifest_dir.join("Cargo.lock"));
+    sources.sort();
+
+    let mut hasher = blake3::Hasher::new();
+    for path in &sources {
+        let Ok(content) = std::fs::read(path) else {
+            continue;
+        };
+        let relative = path.strip_prefix(&manifest_dir).unwrap_or(path);
+        hasher.update(relative.to_string_lossy().replace('\\', "/").as_bytes());
+        hasher.update(&(content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+    hasher.update(features.as_bytes());
+    hasher.update(target.as_bytes());
+
+    let commit = command_output("git", &["rev-parse", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=WILLIW_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=WILLIW_FEATURES={}", features);
+    println!("cargo:rustc-env=WILLIW_TARGET={}", target);
+    println!("cargo:rustc-env=WILLIW_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=WILLIW_SOURCE_HASH={}", hasher.finalize().to_hex());
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+
+    #[cfg(feature = "cbindgen")]
+    generate_header(&manifest_dir);
+
+    #[cfg(feature = "uniffi")]
+    uniffi::generate_scaffolding("src/williw.udl").expect("生成 UniFFI 脚手架失败");
+
+    #[cfg(feature = "napi-build")]
+    napi_build::setup();
+}
@@ -1,8 +1,81 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_lang::system_program;
 use shared_types::*;
+use zk_verifier::program::ZkVerifier;
+use zk_verifier::{Groth16Proof, VerifyingKeyAccount};
+use oracle::{MetricAggregate, MetricKind};
+use node_management::NodeAccount;
+
+pub mod vrf;
 
 declare_id!("CONTRIBUTION_TRACKING_PROGRAM_ID");
 
+/// 纪元时长（秒）
+pub const EPOCH_DURATION_SECONDS: i64 = 86_400;
+
+/// 时间戳所在的纪元
+pub fn epoch_of(timestamp: i64) -> u64 {
+    (timestamp.max(0) / EPOCH_DURATION_SECONDS) as u64
+}
+
+/// 纪元是否已关闭：贡献只能记到当前或上一纪元，
+/// 因此纪元结束一个完整周期后其算力评分不会再变化
+pub fn epoch_closed(epoch: u64, now: i64) -> bool {
+    epoch_of(now) >= epoch.saturating_add(2)
+}
+
+/// 单次批量验证的最大贡献数
+pub const MAX_BATCH_VERIFY: usize = 10;
+
+/// 把验证通过的贡献算力计入纪元统计
+fn add_verified_compute(
+    epoch_compute: &mut EpochComputeAccount,
+    node_epoch_compute: &mut NodeEpochCompute,
+    compute_score: f64,
+) {
+    epoch_compute.total_compute_score += compute_score;
+    epoch_compute.contribution_count += 1;
+    node_epoch_compute.compute_score += compute_score;
+    node_epoch_compute.contribution_count += 1;
+}
+
+/// 贡献证明的公开输入承诺：把证明绑定到贡献ID、节点和算力评分，防止一份证明被套用到其他记录
+pub fn contribution_commitment(contribution_id: &str, node_id: &Pubkey, compute_score: f64) -> [u8; 32] {
+    zk_verifier::hash_to_scalar(&[
+        contribution_id.as_bytes(),
+        node_id.as_ref(),
+        &compute_score.to_le_bytes(),
+    ])
+}
+
+/// 证明的第一个公开输入须是本条贡献的承诺，节点端矩阵乘法电路把它作为公开输入绑定到证明上
+pub fn proof_is_bound(proof: &ContributionProof, contribution_id: &str, node_id: &Pubkey, compute_score: f64) -> bool {
+    proof.public_inputs.first() == Some(&contribution_commitment(contribution_id, node_id, compute_score))
+}
+
+/// 校验委员会抽签证明：种子须是 SlotHashes 中记录的区块哈希，VRF 证明须由成员公钥生成，
+/// 且按成员质押和本纪元委员会参数，VRF 输出须低于当选阈值
+///
+/// SlotHashes 只保留最近 512 个 slot，委员会须在种子区块出块后约 3 分钟内提交验证。
+pub fn check_committee_proof(
+    proof: &CommitteeProof,
+    epoch: u64,
+    slot_hashes: &[u8],
+    stake: u64,
+    config: &CommitteeConfig,
+) -> bool {
+    if proof.epoch != epoch || config.epoch != epoch {
+        return false;
+    }
+    if vrf::find_slot_hash(slot_hashes, proof.seed_slot) != Some(proof.seed_blockhash) {
+        return false;
+    }
+    let alpha = vrf::committee_alpha(&proof.seed_blockhash, proof.seed_slot, proof.epoch);
+    vrf::verify(&proof.member.to_bytes(), &alpha, &proof.vrf_proof)
+        .is_some_and(|output| vrf::is_selected(&output, stake, config.total_stake, config.committee_size))
+}
+
 /// 算力贡献账户
 #[account]
 pub struct ContributionAccount {
@@ -28,9 +101,108 @@ pub struct ContributionAccount {
     pub is_verified: bool,               // 是否已验证
     pub verified_by: Option<Pubkey>,      // 验证者
     pub verification_timestamp: Option<i64>, // 验证时间
+    pub proof_hash: Option<[u8; 32]>,     // 零知识计算证明摘要
+    pub committee_proof: Option<CommitteeProof>, // 验证者的委员会抽签证明
+    pub dataset_manifest_hash: Option<[u8; 32]>, // 训练数据集清单哈希
     pub bump: u8,                         // PDA bump
 }
 
+/// 验证委员会成员的 VRF 抽签证明，任何人可据此链下复核验证者确实当选
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CommitteeProof {
+    pub member: Pubkey,                   // 委员会成员（VRF 公钥）
+    pub epoch: u64,                       // 抽签纪元
+    pub seed_slot: u64,                   // 种子区块的 slot
+    pub seed_blockhash: [u8; 32],         // 种子区块哈希
+    pub vrf_proof: [u8; 80],              // ECVRF-EDWARDS25519-SHA512-TAI 证明
+}
+
+impl CommitteeProof {
+    pub const SPACE: usize = 32 + 8 + 8 + 32 + 80;
+}
+
+/// 纪元委员会参数，须在种子区块出块前公布；节点和合约按同一参数判定当选
+#[account]
+pub struct CommitteeConfig {
+    pub epoch: u64,                       // 纪元编号
+    pub committee_size: u32,              // 期望的委员会人数
+    pub total_stake: u64,                 // 参与抽签节点的总质押
+    pub bump: u8,                         // PDA bump
+}
+
+impl CommitteeConfig {
+    pub const SPACE: usize = 8 + 8 + 4 + 8 + 1;
+}
+
+/// 随贡献记录提交的零知识计算证明
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ContributionProof {
+    pub proof_hash: [u8; 32],             // 证明摘要（用于派生证明记录地址）
+    pub proof: Groth16Proof,              // Groth16 证明
+    pub public_inputs: Vec<[u8; 32]>,     // 公开输入（大端序标量）
+}
+
+/// 纪元算力汇总
+#[account]
+pub struct EpochComputeAccount {
+    pub epoch: u64,                       // 纪元编号
+    pub total_compute_score: f64,         // 纪元内总算力评分
+    pub contribution_count: u32,          // 纪元内贡献记录数
+    pub bump: u8,                         // PDA bump
+}
+
+impl EpochComputeAccount {
+    pub const SPACE: usize = 8 + 8 + 8 + 4 + 1;
+}
+
+/// 节点在某纪元内的算力汇总
+#[account]
+pub struct NodeEpochCompute {
+    pub epoch: u64,                       // 纪元编号
+    pub node_id: Pubkey,                  // 节点ID
+    pub compute_score: f64,               // 纪元内算力评分
+    pub contribution_count: u32,          // 纪元内贡献记录数
+    pub bump: u8,                         // PDA bump
+}
+
+impl NodeEpochCompute {
+    pub const SPACE: usize = 8 + 8 + 32 + 8 + 4 + 1;
+}
+
+/// 从预言机导入的节点纪元指标，同时防止同一汇总被重复计入
+#[account]
+pub struct OracleImport {
+    pub epoch: u64,                       // 纪元编号
+    pub node_id: Pubkey,                  // 节点ID
+    pub compute_score: f64,               // 计入的算力评分
+    pub uptime_bps: Option<u16>,          // 在线率（万分比）
+    pub imported_at: i64,                 // 导入时间
+    pub bump: u8,                         // PDA bump
+}
+
+impl OracleImport {
+    pub const SPACE: usize = 8 + 8 + 32 + 8 + (1 + 2) + 8 + 1;
+}
+
+/// 冗余执行验证发现结果不一致时发起的质疑
+#[account]
+pub struct ChallengeAccount {
+    pub node_id: Pubkey,                  // 被质疑的节点
+    pub task_id: String,                  // 任务ID
+    pub challenger: Pubkey,               // 发起者
+    pub evidence_hash: [u8; 32],          // 两份输出的证据摘要
+    pub opened_at: i64,                   // 发起时间
+    pub resolved: bool,                   // 是否已裁决
+    pub upheld: bool,                     // 质疑是否成立
+    pub resolved_at: Option<i64>,         // 裁决时间
+    pub bump: u8,                         // PDA bump
+}
+
+impl ChallengeAccount {
+    pub const MAX_TASK_ID_LEN: usize = 64;
+    pub const SPACE: usize = 8 + 32 + (4 + Self::MAX_TASK_ID_LEN) + 32 + 32 + 8 + 1 + 1 + (1 + 8) + 1;
+}
+
 /// 贡献跟踪全局状态
 #[account]
 pub struct ContributionTrackingState {
@@ -41,6 +213,13 @@ pub struct ContributionTrackingState {
     pub verification_required: bool,      // 是否需要验证
     pub min_quality_threshold: f32,       // 最低质量阈值
     pub bump: u8,                         // PDA bump
+    pub compute_verifying_key: Pubkey,    // 算力证明电路的验证密钥账户
+}
+
+impl ContributionTrackingState {
+    pub const SPACE: usize = Self::SPACE_V0 + 32;
+    /// 加入验证密钥前的布局
+    pub const SPACE_V0: usize = 8 + 32 + 4 + 8 + 8 + 1 + 4 + 1;
 }
 
 #[program]
@@ -62,6 +241,7 @@ pub mod contribution_tracking {
         state.verification_required = verification_required;
         state.min_quality_threshold = min_quality_threshold;
         state.bump = ctx.bumps.state;
+        state.compute_verifying_key = Pubkey::default();
 
         msg!("Contribution tracking contract initialized");
         Ok(())
@@ -88,6 +268,8 @@ pub mod contribution_tracking {
         batches_processed: u64,
         compute_score: f64,
         quality_score: f32,
+        proof: Option<ContributionProof>,
+        dataset_manifest_hash: Option<[u8; 32]>,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
@@ -95,6 +277,8 @@ pub mod contribution_tracking {
         // 验证时间戳
         require!(start_timestamp < end_timestamp, ErrorCode::InvalidContributionData);
         require!(end_timestamp <= current_time, ErrorCode::InvalidContributionData);
+        let epoch = epoch_of(end_timestamp);
+        require!(epoch + 1 >= epoch_of(current_time), ErrorCode::EpochClosed);
 
         // 验证质量分数
         let state = &ctx.accounts.state;
@@ -102,6 +286,43 @@ pub mod contribution_tracking {
             require!(quality_score >= state.min_quality_threshold, ErrorCode::QualityTooLow);
         }
 
+        // 需要验证时必须附带有效的计算证明，由 zk-verifier 合约校验
+        let proof_hash = if state.verification_required {
+            let proof = proof.ok_or(ErrorCode::ProofRequired)?;
+            let (Some(verifying_key), Some(proof_record), Some(zk_verifier_program)) = (
+                ctx.accounts.verifying_key.as_ref(),
+                ctx.accounts.proof_record.as_ref(),
+                ctx.accounts.zk_verifier_program.as_ref(),
+            ) else {
+                return err!(ErrorCode::ProofRequired);
+            };
+            // 只接受管理员指定的电路，且证明必须承诺本条贡献的字段
+            require!(
+                state.compute_verifying_key != Pubkey::default()
+                    && verifying_key.key() == state.compute_verifying_key,
+                ErrorCode::InvalidVerifyingKey
+            );
+            require!(
+                proof_is_bound(&proof, &contribution_id, &node_id, compute_score),
+                ErrorCode::ProofNotBound
+            );
+            let cpi_accounts = zk_verifier::cpi::accounts::VerifyProof {
+                verifying_key: verifying_key.to_account_info(),
+                proof_record: proof_record.to_account_info(),
+                payer: ctx.accounts.authority.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            };
+            zk_verifier::cpi::verify_proof(
+                CpiContext::new(zk_verifier_program.to_account_info(), cpi_accounts),
+                proof.proof_hash,
+                proof.proof,
+                proof.public_inputs,
+            )?;
+            Some(proof.proof_hash)
+        } else {
+            None
+        };
+
         // 计算奖励金额
         let reward_amount = calculate_reward_amount(
             compute_score,
@@ -136,41 +357,162 @@ pub mod contribution_tracking {
         contribution_account.compute_score = compute_score;
         contribution_account.quality_score = quality_score;
         contribution_account.reward_amount = reward_amount;
-        contribution_account.is_verified = !state.verification_required;
+        // 证明只说明计算结果正确，记录仍需管理员或验证委员会确认后才计入纪元算力
+        contribution_account.is_verified = false;
         contribution_account.verified_by = None;
         contribution_account.verification_timestamp = None;
+        contribution_account.proof_hash = proof_hash;
+        contribution_account.committee_proof = None;
+        contribution_account.dataset_manifest_hash = dataset_manifest_hash;
         contribution_account.bump = ctx.bumps.contribution_account;
 
         // 更新全局统计
         state.total_contributions += 1;
-        state.total_compute_score += compute_score;
+
+        // 预先创建纪元统计账户，算力评分在验证通过后才计入
+        let epoch_compute = &mut ctx.accounts.epoch_compute;
+        epoch_compute.epoch = epoch;
+        epoch_compute.bump = ctx.bumps.epoch_compute;
+
+        let node_epoch_compute = &mut ctx.accounts.node_epoch_compute;
+        node_epoch_compute.epoch = epoch;
+        node_epoch_compute.node_id = node_id;
+        node_epoch_compute.bump = ctx.bumps.node_epoch_compute;
 
         msg!("Contribution recorded: {} for node {}", contribution_id, node_id);
         Ok(())
     }
 
+    /// 查询已关闭纪元的总算力评分（供收益管理合约 CPI 调用）
+    pub fn get_epoch_compute(ctx: Context<GetEpochCompute>, epoch: u64) -> Result<f64> {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(epoch_closed(epoch, current_time), ErrorCode::EpochNotClosed);
+
+        Ok(ctx.accounts.epoch_compute.total_compute_score)
+    }
+
+    /// 导入预言机已定值的节点纪元指标（Workers 汇总、未逐条上链的算力）
+    ///
+    /// 只接受达到 M-of-N 一致的汇总，且必须在纪元关闭前导入，之后纪元算力不再变化。
+    pub fn import_oracle_compute(ctx: Context<ImportOracleCompute>, epoch: u64, node_id: Pubkey) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(!epoch_closed(epoch, current_time), ErrorCode::EpochClosed);
+
+        let report = &ctx.accounts.compute_report;
+        require!(
+            report.finalized
+                && report.kind == MetricKind::NodeComputeScore
+                && report.epoch == epoch
+                && report.subject == node_id,
+            ErrorCode::OracleReportInvalid
+        );
+        let compute_score = report.value as f64 / oracle::VALUE_SCALE as f64;
+
+        let uptime_bps = match &ctx.accounts.uptime_report {
+            Some(uptime) => {
+                require!(
+                    uptime.finalized
+                        && uptime.kind == MetricKind::NodeUptime
+                        && uptime.epoch == epoch
+                        && uptime.subject == node_id,
+                    ErrorCode::OracleReportInvalid
+                );
+                Some(uptime.value.min(oracle::MAX_UPTIME_BPS) as u16)
+            }
+            None => None,
+        };
+
+        let import = &mut ctx.accounts.oracle_import;
+        import.epoch = epoch;
+        import.node_id = node_id;
+        import.compute_score = compute_score;
+        import.uptime_bps = uptime_bps;
+        import.imported_at = current_time;
+        import.bump = ctx.bumps.oracle_import;
+
+        let state = &mut ctx.accounts.state;
+        state.total_compute_score += compute_score;
+
+        let epoch_compute = &mut ctx.accounts.epoch_compute;
+        epoch_compute.epoch = epoch;
+        epoch_compute.total_compute_score += compute_score;
+        epoch_compute.bump = ctx.bumps.epoch_compute;
+
+        let node_epoch_compute = &mut ctx.accounts.node_epoch_compute;
+        node_epoch_compute.epoch = epoch;
+        node_epoch_compute.node_id = node_id;
+        node_epoch_compute.compute_score += compute_score;
+        node_epoch_compute.bump = ctx.bumps.node_epoch_compute;
+
+        msg!("Oracle compute imported: epoch {} node {} score {}", epoch, node_id, compute_score);
+        Ok(())
+    }
+
     /// 验证贡献
     pub fn verify_contribution(
         ctx: Context<VerifyContribution>,
         contribution_id: String,
         is_valid: bool,
         verifier_notes: Option<String>,
+        committee_proof: Option<CommitteeProof>,
     ) -> Result<()> {
         let contribution_account = &mut ctx.accounts.contribution_account;
-        let state = &ctx.accounts.state;
+        let verifier = ctx.accounts.verifier.key();
 
-        // 只有管理员可以验证贡献
-        require!(ctx.accounts.verifier.key() == state.admin, ErrorCode::Unauthorized);
-        require!(!contribution_account.is_verified, ErrorCode::AlreadyVerified);
+        let clock = Clock::get()?;
+        let current_time = clock.unix_timestamp;
+        let epoch = epoch_of(contribution_account.end_timestamp);
 
-        let current_time = Clock::get()?.unix_timestamp;
+        // 没有委员会抽签证明时只有管理员可以验证
+        match &committee_proof {
+            None => require!(verifier == ctx.accounts.state.admin, ErrorCode::Unauthorized),
+            Some(proof) => {
+                let (Some(member_node), Some(slot_hashes), Some(committee_config)) = (
+                    ctx.accounts.member_node.as_ref(),
+                    ctx.accounts.slot_hashes.as_ref(),
+                    ctx.accounts.committee_config.as_ref(),
+                ) else {
+                    return err!(ErrorCode::InvalidCommitteeProof);
+                };
+                // 成员须是已注册的活跃节点，用节点公钥签名，且不能验证自己的贡献
+                require!(
+                    proof.member == verifier
+                        && member_node.node_id == verifier
+                        && member_node.status == NodeStatus::Active
+                        && !member_node.stake_info.is_slashed
+                        && verifier != contribution_account.node_id,
+                    ErrorCode::InvalidCommitteeProof
+                );
+                require!(
+                    check_committee_proof(
+                        proof,
+                        epoch,
+                        &slot_hashes.try_borrow_data()?,
+                        member_node.stake_info.amount,
+                        committee_config,
+                    ),
+                    ErrorCode::InvalidCommitteeProof
+                );
+            }
+        }
+        require!(contribution_account.verified_by.is_none(), ErrorCode::AlreadyVerified);
+        require!(!epoch_closed(epoch, current_time), ErrorCode::EpochClosed);
 
         contribution_account.is_verified = is_valid;
-        contribution_account.verified_by = Some(ctx.accounts.verifier.key());
+        contribution_account.verified_by = Some(verifier);
         contribution_account.verification_timestamp = Some(current_time);
+        contribution_account.committee_proof = committee_proof;
 
-        // 如果验证失败，将奖励金额设为0
-        if !is_valid {
+        if is_valid {
+            let compute_score = contribution_account.compute_score;
+            ctx.accounts.state.total_compute_score += compute_score;
+            add_verified_compute(
+                &mut ctx.accounts.epoch_compute,
+                &mut ctx.accounts.node_epoch_compute,
+                compute_score,
+            );
+        } else {
+            // 如果验证失败，将奖励金额设为0
             contribution_account.reward_amount = 0;
         }
 
@@ -179,38 +521,131 @@ pub mod contribution_tracking {
     }
 
     /// 批量验证贡献
-    pub fn batch_verify_contributions(
-        ctx: Context<BatchVerifyContributions>,
+    ///
+    /// 每条贡献在 remaining_accounts 中依次传入贡献账户、纪元算力账户和节点纪元算力账户；
+    /// 已验证过的贡献会被跳过。
+    pub fn batch_verify_contributions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchVerifyContributions<'info>>,
         contribution_ids: Vec<String>,
         verification_results: Vec<bool>,
     ) -> Result<()> {
-        let state = &ctx.accounts.state;
+        let verifier = ctx.accounts.verifier.key();
 
         // 只有管理员可以批量验证
-        require!(ctx.accounts.verifier.key() == state.admin, ErrorCode::Unauthorized);
+        require!(verifier == ctx.accounts.state.admin, ErrorCode::Unauthorized);
         require!(contribution_ids.len() == verification_results.len(), ErrorCode::MismatchedArrays);
+        require!(contribution_ids.len() <= MAX_BATCH_VERIFY, ErrorCode::MismatchedArrays);
+        require!(
+            ctx.remaining_accounts.len() == contribution_ids.len() * 3,
+            ErrorCode::MismatchedArrays
+        );
 
         let current_time = Clock::get()?.unix_timestamp;
 
-        for (i, contribution_id) in contribution_ids.iter().enumerate() {
-            let contribution_account = &mut ctx.accounts.contribution_accounts[i];
-            
-            if !contribution_account.is_verified {
-                contribution_account.is_verified = verification_results[i];
-                contribution_account.verified_by = Some(ctx.accounts.verifier.key());
-                contribution_account.verification_timestamp = Some(current_time);
+        for ((contribution_id, is_valid), accounts) in contribution_ids
+            .iter()
+            .zip(verification_results)
+            .zip(ctx.remaining_accounts.chunks(3))
+        {
+            let mut contribution_account = Account::<ContributionAccount>::try_from(&accounts[0])?;
+            require!(contribution_account.id == *contribution_id, ErrorCode::InvalidContributionData);
+            if contribution_account.verified_by.is_some() {
+                continue;
+            }
+            let epoch = epoch_of(contribution_account.end_timestamp);
+            require!(!epoch_closed(epoch, current_time), ErrorCode::EpochClosed);
+
+            contribution_account.is_verified = is_valid;
+            contribution_account.verified_by = Some(verifier);
+            contribution_account.verification_timestamp = Some(current_time);
 
+            if is_valid {
+                // 同一纪元的账户可能在批次中重复出现，逐条读取并立即写回
+                let mut epoch_compute = Account::<EpochComputeAccount>::try_from(&accounts[1])?;
+                let mut node_epoch_compute = Account::<NodeEpochCompute>::try_from(&accounts[2])?;
+                require!(
+                    epoch_compute.epoch == epoch
+                        && node_epoch_compute.epoch == epoch
+                        && node_epoch_compute.node_id == contribution_account.node_id,
+                    ErrorCode::InvalidContributionData
+                );
+                let compute_score = contribution_account.compute_score;
+                ctx.accounts.state.total_compute_score += compute_score;
+                add_verified_compute(&mut epoch_compute, &mut node_epoch_compute, compute_score);
+                epoch_compute.exit(ctx.program_id)?;
+                node_epoch_compute.exit(ctx.program_id)?;
+            } else {
                 // 如果验证失败，将奖励金额设为0
-                if !verification_results[i] {
-                    contribution_account.reward_amount = 0;
-                }
+                contribution_account.reward_amount = 0;
             }
+            contribution_account.exit(ctx.program_id)?;
         }
 
         msg!("Batch verified {} contributions", contribution_ids.len());
         Ok(())
     }
 
+    /// 指定算力证明使用的验证密钥（仅管理员），旧布局的状态账户会在此扩容
+    pub fn set_verifying_key(ctx: Context<SetVerifyingKey>, verifying_key: Pubkey) -> Result<()> {
+        let state_info = ctx.accounts.state.to_account_info();
+        let old_len = state_info.data_len();
+        {
+            let data = state_info.try_borrow_data()?;
+            require!(
+                old_len >= ContributionTrackingState::SPACE_V0
+                    && &data[..8] == ContributionTrackingState::DISCRIMINATOR,
+                ErrorCode::InvalidStateLayout
+            );
+            require!(data[8..40] == ctx.accounts.admin.key().to_bytes(), ErrorCode::Unauthorized);
+        }
+
+        if old_len < ContributionTrackingState::SPACE {
+            // 补足扩容后的租金
+            let rent = Rent::get()?.minimum_balance(ContributionTrackingState::SPACE);
+            let lamports = state_info.lamports();
+            if rent > lamports {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.admin.to_account_info(),
+                            to: state_info.clone(),
+                        },
+                    ),
+                    rent - lamports,
+                )?;
+            }
+            state_info.resize(ContributionTrackingState::SPACE)?;
+        }
+
+        let mut state = ContributionTrackingState::try_deserialize(&mut &state_info.try_borrow_data()?[..])?;
+        state.compute_verifying_key = verifying_key;
+        state.try_serialize(&mut &mut state_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Compute verifying key set to {}", verifying_key);
+        Ok(())
+    }
+
+    /// 公布纪元委员会参数（仅管理员），每个纪元只能设置一次
+    pub fn set_committee_config(
+        ctx: Context<SetCommitteeConfig>,
+        epoch: u64,
+        committee_size: u32,
+        total_stake: u64,
+    ) -> Result<()> {
+        require!(ctx.accounts.admin.key() == ctx.accounts.state.admin, ErrorCode::Unauthorized);
+        require!(committee_size > 0 && total_stake > 0, ErrorCode::InvalidCommitteeConfig);
+
+        let config = &mut ctx.accounts.committee_config;
+        config.epoch = epoch;
+        config.committee_size = committee_size;
+        config.total_stake = total_stake;
+        config.bump = ctx.bumps.committee_config;
+
+        msg!("Committee config for epoch {}: size {}, total stake {}", epoch, committee_size, total_stake);
+        Ok(())
+    }
+
     /// 更新基础奖励
     pub fn update_base_reward(
         ctx: Context<UpdateBaseReward>,
@@ -227,6 +662,45 @@ pub mod contribution_tracking {
         Ok(())
     }
 
+    /// 对节点的任务输出发起质疑（冗余执行验证发现不一致时由验证方提交），等待管理员裁决
+    pub fn open_challenge(
+        ctx: Context<OpenChallenge>,
+        node_id: Pubkey,
+        task_id: String,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(task_id.len() <= ChallengeAccount::MAX_TASK_ID_LEN, ErrorCode::InvalidContributionData);
+        require!(ctx.accounts.challenger.key() != node_id, ErrorCode::Unauthorized);
+
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.node_id = node_id;
+        challenge.task_id = task_id;
+        challenge.challenger = ctx.accounts.challenger.key();
+        challenge.evidence_hash = evidence_hash;
+        challenge.opened_at = Clock::get()?.unix_timestamp;
+        challenge.resolved = false;
+        challenge.upheld = false;
+        challenge.resolved_at = None;
+        challenge.bump = ctx.bumps.challenge;
+
+        msg!("Challenge opened against {} for task {}", node_id, challenge.task_id);
+        Ok(())
+    }
+
+    /// 管理员裁决质疑
+    pub fn resolve_challenge(ctx: Context<ResolveChallenge>, upheld: bool) -> Result<()> {
+        require!(ctx.accounts.authority.key() == ctx.accounts.state.admin, ErrorCode::Unauthorized);
+        let challenge = &mut ctx.accounts.challenge;
+        require!(!challenge.resolved, ErrorCode::ChallengeResolved);
+
+        challenge.resolved = true;
+        challenge.upheld = upheld;
+        challenge.resolved_at = Some(Clock::get()?.unix_timestamp);
+
+        msg!("Challenge against {} resolved: upheld={}", challenge.node_id, upheld);
+        Ok(())
+    }
+
     /// 更新验证要求
     pub fn update_verification_settings(
         ctx: Context<UpdateVerificationSettings>,
@@ -252,7 +726,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32 + 4 + 8 + 8 + 1 + 4 + 1, // 空间计算
+        space = ContributionTrackingState::SPACE,
         seeds = [b"contribution-tracking-state"],
         bump
     )]
@@ -265,49 +739,205 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(contribution_id: String)]
+#[instruction(
+    contribution_id: String,
+    node_id: Pubkey,
+    task_id: String,
+    task_type: TaskType,
+    model_info: ModelInfo,
+    start_timestamp: i64,
+    end_timestamp: i64
+)]
 pub struct RecordContribution<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + (4 + 36) + 32 + (4 + 36) + 8 + 8 + 8 + 4 + 8 + 4 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1, // 空间计算
+        space = 8 + (4 + 36) + 32 + (4 + 36) + 8 + 8 + 8 + 4 + 8 + 4 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + (1 + 32) + (1 + CommitteeProof::SPACE) + (1 + 32) + 1, // 空间计算
         seeds = [b"contribution", contribution_id.as_bytes()],
         bump
     )]
     pub contribution_account: Account<'info, ContributionAccount>,
 
-    #[account(mut)]
+    #[account(mut, seeds = [b"contribution-tracking-state"], bump = state.bump)]
     pub state: Account<'info, ContributionTrackingState>,
 
+    // 节点管理合约中的节点账户，只有节点所有者能为节点记录贡献
+    #[account(
+        seeds = [b"node", node_id.as_ref()],
+        bump = node_account.bump,
+        seeds::program = node_management::ID,
+        constraint = node_account.owner == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = EpochComputeAccount::SPACE,
+        seeds = [b"epoch-compute".as_ref(), &epoch_of(end_timestamp).to_le_bytes()],
+        bump
+    )]
+    pub epoch_compute: Account<'info, EpochComputeAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = NodeEpochCompute::SPACE,
+        seeds = [b"node-epoch-compute".as_ref(), &epoch_of(end_timestamp).to_le_bytes(), node_id.as_ref()],
+        bump
+    )]
+    pub node_epoch_compute: Account<'info, NodeEpochCompute>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    // 以下账户仅在需要验证时提供
+    pub verifying_key: Option<Account<'info, VerifyingKeyAccount>>,
+
+    /// CHECK: 由 zk-verifier 合约创建并校验地址
+    #[account(mut)]
+    pub proof_record: Option<UncheckedAccount<'info>>,
+
+    pub zk_verifier_program: Option<Program<'info, ZkVerifier>>,
 }
 
 #[derive(Accounts)]
-pub struct VerifyContribution<'info> {
+#[instruction(epoch: u64)]
+pub struct GetEpochCompute<'info> {
+    #[account(seeds = [b"epoch-compute".as_ref(), &epoch.to_le_bytes()], bump = epoch_compute.bump)]
+    pub epoch_compute: Account<'info, EpochComputeAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64, node_id: Pubkey)]
+pub struct ImportOracleCompute<'info> {
+    #[account(mut)]
+    pub state: Account<'info, ContributionTrackingState>,
+
+    // 账户归属由 Account 类型校验为预言机合约，内容在指令中校验
+    pub compute_report: Account<'info, MetricAggregate>,
+
+    pub uptime_report: Option<Account<'info, MetricAggregate>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = OracleImport::SPACE,
+        seeds = [b"oracle-import", compute_report.key().as_ref()],
+        bump
+    )]
+    pub oracle_import: Account<'info, OracleImport>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = EpochComputeAccount::SPACE,
+        seeds = [b"epoch-compute".as_ref(), &epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_compute: Account<'info, EpochComputeAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = NodeEpochCompute::SPACE,
+        seeds = [b"node-epoch-compute".as_ref(), &epoch.to_le_bytes(), node_id.as_ref()],
+        bump
+    )]
+    pub node_epoch_compute: Account<'info, NodeEpochCompute>,
+
     #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(contribution_id: String)]
+pub struct VerifyContribution<'info> {
+    #[account(mut, seeds = [b"contribution", contribution_id.as_bytes()], bump = contribution_account.bump)]
     pub contribution_account: Account<'info, ContributionAccount>,
 
+    #[account(mut, seeds = [b"contribution-tracking-state"], bump = state.bump)]
     pub state: Account<'info, ContributionTrackingState>,
 
+    #[account(
+        mut,
+        seeds = [b"epoch-compute".as_ref(), &epoch_of(contribution_account.end_timestamp).to_le_bytes()],
+        bump = epoch_compute.bump
+    )]
+    pub epoch_compute: Account<'info, EpochComputeAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"node-epoch-compute".as_ref(),
+            &epoch_of(contribution_account.end_timestamp).to_le_bytes(),
+            contribution_account.node_id.as_ref()
+        ],
+        bump = node_epoch_compute.bump
+    )]
+    pub node_epoch_compute: Account<'info, NodeEpochCompute>,
+
     pub verifier: Signer<'info>,
+
+    // 以下账户仅在委员会成员验证时提供
+    pub member_node: Option<Account<'info, NodeAccount>>,
+
+    /// CHECK: 地址限定为 SlotHashes 系统变量，数据在指令中解析
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"committee-config".as_ref(), &epoch_of(contribution_account.end_timestamp).to_le_bytes()],
+        bump = committee_config.bump
+    )]
+    pub committee_config: Option<Account<'info, CommitteeConfig>>,
 }
 
 #[derive(Accounts)]
-#[instruction(contribution_ids: Vec<String>)]
-pub struct BatchVerifyContributions<'info> {
-    #[account(mut)]
+#[instruction(epoch: u64)]
+pub struct SetCommitteeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = CommitteeConfig::SPACE,
+        seeds = [b"committee-config".as_ref(), &epoch.to_le_bytes()],
+        bump
+    )]
+    pub committee_config: Account<'info, CommitteeConfig>,
+
+    #[account(seeds = [b"contribution-tracking-state"], bump = state.bump)]
     pub state: Account<'info, ContributionTrackingState>,
 
-    // 最多支持10个贡献的批量验证
-    #[account(mut, constraint = contribution_accounts.len() <= 10)]
-    pub contribution_accounts: Vec<Account<'info, ContributionAccount>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BatchVerifyContributions<'info> {
+    #[account(mut, seeds = [b"contribution-tracking-state"], bump = state.bump)]
+    pub state: Account<'info, ContributionTrackingState>,
 
     pub verifier: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetVerifyingKey<'info> {
+    /// CHECK: 旧布局无法按新结构反序列化，判别符和管理员在指令中校验
+    #[account(mut, seeds = [b"contribution-tracking-state"], bump)]
+    pub state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateBaseReward<'info> {
     #[account(mut)]
@@ -324,6 +954,34 @@ pub struct UpdateVerificationSettings<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(node_id: Pubkey, task_id: String, evidence_hash: [u8; 32])]
+pub struct OpenChallenge<'info> {
+    #[account(
+        init,
+        payer = challenger,
+        space = ChallengeAccount::SPACE,
+        seeds = [b"challenge", node_id.as_ref(), evidence_hash.as_ref()],
+        bump
+    )]
+    pub challenge: Account<'info, ChallengeAccount>,
+
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveChallenge<'info> {
+    #[account(mut)]
+    pub challenge: Account<'info, ChallengeAccount>,
+
+    pub state: Account<'info, ContributionTrackingState>,
+
+    pub authority: Signer<'info>,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid contribution data")]
@@ -338,4 +996,244 @@ pub enum ErrorCode {
     MismatchedArrays,
     #[msg("Invalid quality threshold")]
     InvalidQualityThreshold,
+    #[msg("A valid compute proof is required")]
+    ProofRequired,
+    #[msg("Contribution epoch is already closed")]
+    EpochClosed,
+    #[msg("Epoch is not closed yet")]
+    EpochNotClosed,
+    #[msg("Invalid committee proof")]
+    InvalidCommitteeProof,
+    #[msg("Oracle report is not a finalized metric for this node and epoch")]
+    OracleReportInvalid,
+    #[msg("Challenge is already resolved")]
+    ChallengeResolved,
+    #[msg("Verifying key is not the configured compute circuit")]
+    InvalidVerifyingKey,
+    #[msg("Proof public inputs do not commit to this contribution")]
+    ProofNotBound,
+    #[msg("Invalid state account layout")]
+    InvalidStateLayout,
+    #[msg("Committee size and total stake must be positive")]
+    InvalidCommitteeConfig,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+    use curve25519_dalek::scalar::Scalar;
+    use sha2::{Digest, Sha512};
+
+    /// 按节点端 `crypto::vrf::prove` 的算法生成证明，返回成员公钥和证明
+    fn prove(secret: u64, alpha: &[u8]) -> (Pubkey, [u8; vrf::PROOF_LEN]) {
+        let x = Scalar::from(secret);
+        let public = (x * ED25519_BASEPOINT_POINT).compress();
+        let h = (0..=u8::MAX)
+            .find_map(|ctr| {
+                let hash = Sha512::new()
+                    .chain_update([0x03, 0x01])
+                    .chain_update(public.as_bytes())
+                    .chain_update(alpha)
+                    .chain_update([ctr, 0x00])
+                    .finalize();
+                CompressedEdwardsY(hash[..32].try_into().unwrap()).decompress()
+            })
+            .unwrap()
+            .mul_by_cofactor();
+        let gamma = x * h;
+        let k = Scalar::from(secret.wrapping_mul(31).wrapping_add(7));
+        let mut hasher = Sha512::new().chain_update([0x03, 0x02]).chain_update(public.as_bytes());
+        for point in [h, gamma, k * ED25519_BASEPOINT_POINT, k * h] {
+            hasher.update(point.compress().as_bytes());
+        }
+        hasher.update([0x00]);
+        let mut c = [0u8; 32];
+        c[..16].copy_from_slice(&hasher.finalize()[..16]);
+        let s = k + Scalar::from_bytes_mod_order(c) * x;
+
+        let mut proof = [0u8; vrf::PROOF_LEN];
+        proof[..32].copy_from_slice(gamma.compress().as_bytes());
+        proof[32..48].copy_from_slice(&c[..16]);
+        proof[48..].copy_from_slice(s.as_bytes());
+        (Pubkey::new_from_array(public.to_bytes()), proof)
+    }
+
+    fn slot_hashes(entries: &[(u64, [u8; 32])]) -> Vec<u8> {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash);
+        }
+        data
+    }
+
+    fn committee_proof(secret: u64, epoch: u64, seed_slot: u64, seed_blockhash: [u8; 32]) -> CommitteeProof {
+        let (member, vrf_proof) = prove(secret, &vrf::committee_alpha(&seed_blockhash, seed_slot, epoch));
+        CommitteeProof {
+            member,
+            epoch,
+            seed_slot,
+            seed_blockhash,
+            vrf_proof,
+        }
+    }
+
+    #[test]
+    fn test_find_slot_hash() {
+        let data = slot_hashes(&[(30, [3; 32]), (20, [2; 32]), (10, [1; 32])]);
+        assert_eq!(vrf::find_slot_hash(&data, 30), Some([3; 32]));
+        assert_eq!(vrf::find_slot_hash(&data, 20), Some([2; 32]));
+        assert_eq!(vrf::find_slot_hash(&data, 10), Some([1; 32]));
+        assert_eq!(vrf::find_slot_hash(&data, 15), None);
+        // 条目数超出数据长度
+        assert_eq!(vrf::find_slot_hash(&data[..data.len() - 1], 10), None);
+    }
+
+    fn hex_bytes<const N: usize>(hex: &str) -> [u8; N] {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        out
+    }
+
+    /// 节点端矩阵乘法电路生成的真实证明及其验证密钥
+    fn matmul_fixture() -> (VerifyingKeyAccount, ContributionProof) {
+        let mut fields: Vec<(&str, &str)> = Vec::new();
+        for line in include_str!("../tests/fixtures/matmul_proof.txt").lines() {
+            if let Some((key, value)) = line.split_once(' ').filter(|_| !line.starts_with('#')) {
+                fields.push((key, value));
+            }
+        }
+        let field = |name: &str| fields.iter().find(|(key, _)| *key == name).unwrap().1;
+        let all = |name: &str| -> Vec<&str> {
+            fields.iter().filter(|(key, _)| *key == name).map(|(_, value)| *value).collect()
+        };
+
+        let verifying_key = VerifyingKeyAccount {
+            authority: Pubkey::default(),
+            circuit_id: "matmul-1x2x1x1".to_string(),
+            alpha_g1: hex_bytes(field("alpha_g1")),
+            beta_g2: hex_bytes(field("beta_g2")),
+            gamma_g2: hex_bytes(field("gamma_g2")),
+            delta_g2: hex_bytes(field("delta_g2")),
+            ic: all("ic").into_iter().map(hex_bytes).collect(),
+            bump: 0,
+        };
+        let proof = ContributionProof {
+            proof_hash: [0; 32],
+            proof: Groth16Proof {
+                a: hex_bytes(field("a")),
+                b: hex_bytes(field("b")),
+                c: hex_bytes(field("c")),
+            },
+            public_inputs: all("input").into_iter().map(hex_bytes).collect(),
+        };
+        (verifying_key, proof)
+    }
+
+    #[test]
+    fn test_real_contribution_proof_accepted() {
+        let (verifying_key, proof) = matmul_fixture();
+        let node_id = Pubkey::new_from_array([3; 32]);
+        assert!(proof_is_bound(&proof, "contribution-1", &node_id, 12.5));
+        assert!(zk_verifier::verify_groth16(&verifying_key, &proof.proof, &proof.public_inputs).unwrap());
+
+        // 套用到其他记录时绑定检查不通过
+        assert!(!proof_is_bound(&proof, "contribution-2", &node_id, 12.5));
+        assert!(!proof_is_bound(&proof, "contribution-1", &node_id, 13.0));
+        // 改写记录承诺去匹配其他记录，证明本身失效
+        let mut rebound = proof.public_inputs.clone();
+        rebound[0] = contribution_commitment("contribution-2", &node_id, 12.5);
+        assert!(!zk_verifier::verify_groth16(&verifying_key, &proof.proof, &rebound).unwrap());
+    }
+
+    /// 每个节点都当选的委员会参数
+    fn everyone(epoch: u64) -> CommitteeConfig {
+        CommitteeConfig {
+            epoch,
+            committee_size: 10,
+            total_stake: 10,
+            bump: 0,
+        }
+    }
+
+    fn draw(proof: &CommitteeProof) -> u64 {
+        let alpha = vrf::committee_alpha(&proof.seed_blockhash, proof.seed_slot, proof.epoch);
+        let output = vrf::verify(&proof.member.to_bytes(), &alpha, &proof.vrf_proof).unwrap();
+        u64::from_le_bytes(output[..8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_selection_threshold() {
+        let mut output = [0u8; 64];
+        // 期望人数不小于总质押时必然当选，零质押从不当选
+        output[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(vrf::is_selected(&output, 5, 10, 2));
+        assert!(!vrf::is_selected(&output, 0, 10, 10));
+        assert!(!vrf::is_selected(&output, 5, 0, 2));
+
+        // 当选概率 1/4：阈值为 2^62
+        output[..8].copy_from_slice(&((1u64 << 62) - 1).to_le_bytes());
+        assert!(vrf::is_selected(&output, 1, 8, 2));
+        output[..8].copy_from_slice(&(1u64 << 62).to_le_bytes());
+        assert!(!vrf::is_selected(&output, 1, 8, 2));
+
+        // 总质押接近 u64 上限时不溢出
+        assert!(vrf::is_selected(&[0u8; 64], u64::MAX / 2, u64::MAX, u32::MAX));
+        assert!(!vrf::is_selected(&[0xff; 64], 1, u64::MAX, 1));
+    }
+
+    #[test]
+    fn test_committee_proof_accepted() {
+        let data = slot_hashes(&[(42, [7; 32]), (41, [6; 32])]);
+        let proof = committee_proof(12345, 3, 42, [7; 32]);
+        assert!(check_committee_proof(&proof, 3, &data, 1, &everyone(3)));
+    }
+
+    #[test]
+    fn test_committee_proof_above_threshold_rejected() {
+        let data = slot_hashes(&[(42, [7; 32]), (41, [6; 32])]);
+        let proof = committee_proof(12345, 3, 42, [7; 32]);
+        let config = CommitteeConfig {
+            epoch: 3,
+            committee_size: 1,
+            total_stake: 1 << 32,
+            bump: 0,
+        };
+        // 阈值为 stake * 2^32：取阈值恰好不超过输出的质押，证明有效但未当选
+        let stake = draw(&proof) >> 32;
+        assert!(!check_committee_proof(&proof, 3, &data, stake, &config));
+        // 再多一份质押，阈值越过输出
+        assert!(check_committee_proof(&proof, 3, &data, stake + 1, &config));
+        // 其他纪元的委员会参数不能使用
+        assert!(!check_committee_proof(&proof, 3, &data, 1, &everyone(4)));
+    }
+
+    #[test]
+    fn test_forged_committee_proof_rejected() {
+        let data = slot_hashes(&[(42, [7; 32]), (41, [6; 32])]);
+        let proof = committee_proof(12345, 3, 42, [7; 32]);
+
+        // 篡改证明字节
+        let mut forged = proof.clone();
+        forged.vrf_proof[40] ^= 1;
+        assert!(!check_committee_proof(&forged, 3, &data, 1, &everyone(3)));
+
+        // 冒用他人的证明
+        let mut forged = proof.clone();
+        forged.member = committee_proof(999, 3, 42, [7; 32]).member;
+        assert!(!check_committee_proof(&forged, 3, &data, 1, &everyone(3)));
+
+        // 自选种子：区块哈希与 SlotHashes 记录不符，或 slot 不在 SlotHashes 中
+        let forged = committee_proof(12345, 3, 42, [9; 32]);
+        assert!(!check_committee_proof(&forged, 3, &data, 1, &everyone(3)));
+        let forged = committee_proof(12345, 3, 43, [7; 32]);
+        assert!(!check_committee_proof(&forged, 3, &data, 1, &everyone(3)));
+
+        // 其他纪元的证明
+        assert!(!check_committee_proof(&proof, 4, &data, 1, &everyone(4)));
+    }
 }
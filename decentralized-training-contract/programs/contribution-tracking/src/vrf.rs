@@ -0,0 +1,145 @@
+//! 链上验证委员会抽签证明（ECVRF-EDWARDS25519-SHA512-TAI，RFC 9381）
+//!
+//! 与节点端 `crypto::vrf` 逐字节一致；点运算走 curve25519 系统调用，链下测试时回退到 curve25519-dalek。
+
+use sha2::{Digest, Sha512};
+use solana_curve25519::edwards::{
+    add_edwards, multiply_edwards, subtract_edwards, validate_edwards, PodEdwardsPoint,
+};
+use solana_curve25519::scalar::PodScalar;
+
+/// 证明长度：Gamma（32）+ c（16）+ s（32）
+pub const PROOF_LEN: usize = 80;
+
+const SUITE: u8 = 0x03;
+
+/// Ed25519 基点
+const BASEPOINT: PodEdwardsPoint = PodEdwardsPoint([
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+]);
+
+/// 单位元
+const IDENTITY: PodEdwardsPoint = PodEdwardsPoint([
+    1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+]);
+
+/// 余因子 8
+const COFACTOR: PodScalar = PodScalar([
+    8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+]);
+
+/// 委员会抽签的 VRF 输入，与节点端 `CommitteeSeed::alpha` 一致
+pub fn committee_alpha(seed_blockhash: &[u8; 32], seed_slot: u64, epoch: u64) -> Vec<u8> {
+    let mut alpha = b"williw-committee:".to_vec();
+    alpha.extend_from_slice(seed_blockhash);
+    alpha.extend_from_slice(&seed_slot.to_le_bytes());
+    alpha.extend_from_slice(&epoch.to_le_bytes());
+    alpha
+}
+
+/// 按质押判定是否当选委员会，与节点端 `consensus::sortition::is_selected` 一致
+///
+/// 当选概率为 `min(1, committee_size * stake / total_stake)`：VRF 输出前 8 字节（小端）
+/// 小于 `2^64 * committee_size * stake / total_stake` 时当选，全部用整数计算。
+pub fn is_selected(output: &[u8; 64], stake: u64, total_stake: u64, committee_size: u32) -> bool {
+    if stake == 0 || total_stake == 0 {
+        return false;
+    }
+    let expected = stake as u128 * committee_size as u128;
+    if expected >= total_stake as u128 {
+        return true;
+    }
+    let threshold = (expected << 64) / total_stake as u128;
+    let draw = u64::from_le_bytes(output[..8].try_into().expect("长度固定"));
+    (draw as u128) < threshold
+}
+
+/// 在 SlotHashes 系统变量数据中查找 slot 的区块哈希
+///
+/// 布局为 u64 条目数后接按 slot 降序排列的 `(slot, hash)`，只保留最近 512 个 slot。
+pub fn find_slot_hash(data: &[u8], slot: u64) -> Option<[u8; 32]> {
+    const ENTRY_LEN: usize = 8 + 32;
+    let count = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+    let entries = data.get(8..8 + count.checked_mul(ENTRY_LEN)?)?;
+    let slot_at = |index: usize| {
+        let start = index * ENTRY_LEN;
+        u64::from_le_bytes(entries[start..start + 8].try_into().expect("长度固定"))
+    };
+
+    let (mut low, mut high) = (0usize, count);
+    while low < high {
+        let mid = (low + high) / 2;
+        match slot_at(mid) {
+            found if found == slot => {
+                let start = mid * ENTRY_LEN + 8;
+                return entries[start..start + 32].try_into().ok();
+            }
+            found if found > slot => low = mid + 1,
+            _ => high = mid,
+        }
+    }
+    None
+}
+
+/// 用公钥验证证明，成功时返回 64 字节输出
+pub fn verify(public: &[u8; 32], alpha: &[u8], proof: &[u8; PROOF_LEN]) -> Option<[u8; 64]> {
+    let y = PodEdwardsPoint(*public);
+    if !validate_edwards(&y) || multiply_edwards(&COFACTOR, &y)? == IDENTITY {
+        return None;
+    }
+
+    // 重新压缩 Gamma，与节点端解压后再压缩的编码一致
+    let gamma = add_edwards(&PodEdwardsPoint(proof[..32].try_into().ok()?), &IDENTITY)?;
+    let mut c = PodScalar([0u8; 32]);
+    c.0[..16].copy_from_slice(&proof[32..48]);
+    // 非规范的 s 会被点乘拒绝
+    let s = PodScalar(proof[48..].try_into().ok()?);
+
+    let h = encode_to_curve(public, alpha)?;
+    let u = subtract_edwards(&multiply_edwards(&s, &BASEPOINT)?, &multiply_edwards(&c, &y)?)?;
+    let v = subtract_edwards(&multiply_edwards(&s, &h)?, &multiply_edwards(&c, &gamma)?)?;
+    (challenge(public, &h, &gamma, &u, &v) == c.0).then(|| proof_to_hash(&gamma)).flatten()
+}
+
+/// try-and-increment 把输入映射到素数阶子群上的点
+fn encode_to_curve(public: &[u8; 32], alpha: &[u8]) -> Option<PodEdwardsPoint> {
+    (0..=u8::MAX).find_map(|ctr| {
+        let hash = Sha512::new()
+            .chain_update([SUITE, 0x01])
+            .chain_update(public)
+            .chain_update(alpha)
+            .chain_update([ctr, 0x00])
+            .finalize();
+        let candidate = PodEdwardsPoint(hash[..32].try_into().ok()?);
+        validate_edwards(&candidate).then(|| multiply_edwards(&COFACTOR, &candidate)).flatten()
+    })
+}
+
+fn challenge(
+    public: &[u8; 32],
+    h: &PodEdwardsPoint,
+    gamma: &PodEdwardsPoint,
+    u: &PodEdwardsPoint,
+    v: &PodEdwardsPoint,
+) -> [u8; 32] {
+    let mut hasher = Sha512::new().chain_update([SUITE, 0x02]).chain_update(public);
+    for point in [h, gamma, u, v] {
+        hasher.update(point.0);
+    }
+    hasher.update([0x00]);
+    let mut c = [0u8; 32];
+    c[..16].copy_from_slice(&hasher.finalize()[..16]);
+    c
+}
+
+fn proof_to_hash(gamma: &PodEdwardsPoint) -> Option<[u8; 64]> {
+    let hash = Sha512::new()
+        .chain_update([SUITE, 0x03])
+        .chain_update(multiply_edwards(&COFACTOR, gamma)?.0)
+        .chain_update([0x00])
+        .finalize();
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&hash);
+    Some(out)
+}
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{get_return_data, invoke_signed};
 use shared_types::*;
+use node_management::program::NodeManagement;
 
 declare_id!("GOVERNANCE_PROGRAM_ID");
 
@@ -49,6 +52,18 @@ pub struct GovernanceProposal {
     pub bump: u8,                         // PDA bump
 }
 
+/// 投票记录（每个提案每个投票者一条，防止重复投票）
+#[account]
+pub struct VoterRecord {
+    pub proposal: Pubkey,                 // 提案账户
+    pub voter: Pubkey,                    // 投票者
+    pub node_account: Pubkey,             // 提供质押的节点账户
+    pub weight: u64,                      // 投票权重（质押数量）
+    pub vote: bool,                       // 赞成/反对
+    pub voted_at: i64,                    // 投票时间
+    pub bump: u8,                         // PDA bump
+}
+
 /// 治理全局状态
 #[account]
 pub struct GovernanceState {
@@ -56,8 +71,8 @@ pub struct GovernanceState {
     pub total_proposals: u64,             // 总提案数
     pub voting_period: u64,               // 投票周期（秒）
     pub execution_delay: u64,             // 执行延迟（秒）
-    pub min_voting_power: u64,            // 最小投票权
-    pub quorum: u64,                      // 法定人数
+    pub min_voting_power: u64,            // 最小投票权（质押数量）
+    pub quorum: u64,                      // 法定票数（按质押加权）
     pub is_active: bool,                  // 是否激活
     pub bump: u8,                         // PDA bump
 }
@@ -73,7 +88,7 @@ pub enum ProposalType {
 }
 
 /// 提案状态
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ProposalStatus {
     Pending,           // 待投票
     Active,            // 投票中
@@ -84,6 +99,52 @@ pub enum ProposalStatus {
     Expired,           // 已过期
 }
 
+impl MultisigTransaction {
+    /// 记录 `signer` 的批准，返回其在所有者列表中的序号
+    ///
+    /// `owners` 必须来自本交易所属的多签账户（由账户约束保证）。
+    pub fn approve(&mut self, owners: &[Pubkey], signer: &Pubkey) -> Result<usize> {
+        require!(!self.did_execute, ErrorCode::TransactionAlreadyExecuted);
+
+        let index = owners
+            .iter()
+            .position(|owner| owner == signer)
+            .ok_or(ErrorCode::Unauthorized)?;
+        let signed = self.signers.get_mut(index).ok_or(ErrorCode::Unauthorized)?;
+        require!(!*signed, ErrorCode::AlreadySigned);
+        *signed = true;
+        Ok(index)
+    }
+
+    /// 执行前检查：未执行过、执行者是所有者、批准数达到阈值
+    pub fn check_executable(&self, multisig: &MultisigAccount, executor: &Pubkey) -> Result<()> {
+        require!(!self.did_execute, ErrorCode::TransactionAlreadyExecuted);
+        require!(multisig.owners.contains(executor), ErrorCode::Unauthorized);
+
+        let signed_count = self.signers.iter().filter(|&&signed| signed).count() as u64;
+        require!(signed_count >= multisig.threshold, ErrorCode::InsufficientSignatures);
+        Ok(())
+    }
+}
+
+impl GovernanceProposal {
+    /// 投票结束后按法定票数和票数结果确定状态；未达法定票数的提案过期
+    pub fn finalize(&mut self, now: i64, quorum: u64) -> Result<()> {
+        require!(now > self.voting_end_at, ErrorCode::VotingNotEnded);
+        require!(self.status == ProposalStatus::Active, ErrorCode::ProposalNotActive);
+
+        let total_votes = self.votes_for.saturating_add(self.votes_against);
+        self.status = if total_votes < quorum {
+            ProposalStatus::Expired
+        } else if self.votes_for > self.votes_against {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        };
+        Ok(())
+    }
+}
+
 #[program]
 pub mod governance {
     use super::*;
@@ -171,44 +232,45 @@ pub mod governance {
 
     /// 批准多签交易
     pub fn approve_multisig_transaction(ctx: Context<ApproveMultisigTransaction>) -> Result<()> {
-        let multisig_transaction = &mut ctx.accounts.multisig_transaction;
-        let multisig_account = &ctx.accounts.multisig_account;
-
-        require!(!multisig_transaction.did_execute, ErrorCode::TransactionAlreadyExecuted);
-
-        // 查找签名者索引
-        let signer_index = multisig_account.owners.iter().position(|&owner| owner == ctx.accounts.signer.key());
-        require!(signer_index.is_some(), ErrorCode::Unauthorized);
-        let index = signer_index.unwrap();
-        require!(!multisig_transaction.signers[index], ErrorCode::AlreadySigned);
-
-        // 设置签名
-        multisig_transaction.signers[index] = true;
+        let index = ctx
+            .accounts
+            .multisig_transaction
+            .approve(&ctx.accounts.multisig_account.owners, &ctx.accounts.signer.key())?;
 
         msg!("Multisig transaction approved by signer {}", index);
         Ok(())
     }
 
     /// 执行多签交易
-    pub fn execute_multisig_transaction(ctx: Context<ExecuteMultisigTransaction>) -> Result<()> {
+    ///
+    /// 以多签 PDA（`[b"multisig-signer", multisig]`）为签名者 CPI 调用目标程序，
+    /// 目标程序和账户需通过 remaining_accounts 传入。执行者必须是多签所有者。
+    pub fn execute_multisig_transaction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteMultisigTransaction<'info>>,
+    ) -> Result<()> {
         let multisig_transaction = &mut ctx.accounts.multisig_transaction;
         let multisig_account = &ctx.accounts.multisig_account;
-
-        require!(!multisig_transaction.did_execute, ErrorCode::TransactionAlreadyExecuted);
-
-        // 检查签名数量是否达到阈值
-        let signed_count = multisig_transaction.signers.iter().filter(|&&signed| signed).count() as u64;
-        require!(signed_count >= multisig_account.threshold, ErrorCode::InsufficientSignatures);
+        multisig_transaction.check_executable(multisig_account, &ctx.accounts.executor.key())?;
 
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
 
-        // 这里应该执行实际的指令调用
-        // 为了简化，我们标记为已执行
+        let multisig_key = multisig_account.key();
+        let signer_seeds: &[&[u8]] = &[b"multisig-signer", multisig_key.as_ref(), &[ctx.bumps.multisig_signer]];
+        let (instruction, account_infos) = prepare_instruction(
+            multisig_transaction.program_id,
+            &multisig_transaction.accounts,
+            &multisig_transaction.data,
+            ctx.remaining_accounts,
+            ctx.accounts.multisig_signer.key(),
+        )?;
+
+        // 先标记已执行，CPI 失败时整笔交易回滚，标记也随之撤销
         multisig_transaction.did_execute = true;
         multisig_transaction.executed_at = Some(current_time);
+        invoke_signed(&instruction, &account_infos, &[signer_seeds])?;
 
-        msg!("Multisig transaction executed");
+        msg!("Multisig transaction executed on program {}", instruction.program_id);
         Ok(())
     }
 
@@ -264,21 +326,54 @@ pub mod governance {
         require!(current_time <= proposal.voting_end_at, ErrorCode::VotingEnded);
         require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalNotActive);
 
-        // 简化实现：每次投票算1票
-        // 实际应该根据投票者的代币数量或质押数量计算投票权
+        // 通过 CPI 从节点管理合约读取投票者节点的质押数量作为投票权
+        let cpi_accounts = node_management::cpi::accounts::GetVotingPower {
+            node_account: ctx.accounts.node_account.to_account_info(),
+            owner: ctx.accounts.voter.to_account_info(),
+        };
+        let weight = node_management::cpi::get_voting_power(CpiContext::new(
+            ctx.accounts.node_management_program.to_account_info(),
+            cpi_accounts,
+        ))?
+        .get();
+        require!(
+            weight > 0 && weight >= ctx.accounts.state.min_voting_power,
+            ErrorCode::InsufficientVotingPower
+        );
+
         if vote {
-            proposal.votes_for += 1;
+            proposal.votes_for = proposal.votes_for.checked_add(weight).ok_or(ErrorCode::VoteOverflow)?;
         } else {
-            proposal.votes_against += 1;
+            proposal.votes_against = proposal.votes_against.checked_add(weight).ok_or(ErrorCode::VoteOverflow)?;
         }
 
-        msg!("Vote cast on proposal {}: {}", proposal_id, if vote { "YES" } else { "NO" });
+        // 投票记录 PDA 只能创建一次，重复投票会在账户初始化时失败
+        let voter_record = &mut ctx.accounts.voter_record;
+        voter_record.proposal = proposal.key();
+        voter_record.voter = ctx.accounts.voter.key();
+        voter_record.node_account = ctx.accounts.node_account.key();
+        voter_record.weight = weight;
+        voter_record.vote = vote;
+        voter_record.voted_at = current_time;
+        voter_record.bump = ctx.bumps.voter_record;
+
+        msg!(
+            "Vote cast on proposal {}: {} (weight {})",
+            proposal_id,
+            if vote { "YES" } else { "NO" },
+            weight
+        );
         Ok(())
     }
 
     /// 执行通过的提案
-    pub fn execute_proposal(
-        ctx: Context<ExecuteProposal>,
+    ///
+    /// 以治理 PDA（`[b"governance-authority"]`）为签名者 CPI 调用 `target_program`，
+    /// 目标程序和 `target_accounts` 需通过 remaining_accounts 传入。
+    /// 调用前即可发现的问题（缺少账户、签名或写权限不符）会把提案标记为 `Failed`；
+    /// 目标程序自身执行失败会回滚整笔交易，提案保持 `Passed`，可修正账户后重试。
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
         proposal_id: String,
     ) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
@@ -290,10 +385,35 @@ pub mod governance {
         require!(proposal.status == ProposalStatus::Passed, ErrorCode::ProposalNotPassed);
         require!(current_time >= proposal.voting_end_at + state.execution_delay as i64, ErrorCode::ExecutionDelayNotMet);
 
-        // 这里应该执行实际的指令调用
-        // 简化实现，标记为已执行
+        let prepared = prepare_instruction(
+            proposal.target_program,
+            &proposal.target_accounts,
+            &proposal.instruction_data,
+            ctx.remaining_accounts,
+            ctx.accounts.governance_authority.key(),
+        );
+        let (instruction, account_infos) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                proposal.status = ProposalStatus::Failed;
+                proposal.execution_result = Some(truncate_result(format!("Failed: {}", e)));
+                msg!("Proposal execution failed: {} ({})", proposal_id, e);
+                return Ok(());
+            }
+        };
+
+        let signer_seeds: &[&[u8]] = &[b"governance-authority", &[ctx.bumps.governance_authority]];
+        invoke_signed(&instruction, &account_infos, &[signer_seeds])?;
+
+        // 记录目标程序的返回数据（如有）
+        let result = match get_return_data() {
+            Some((program_id, data)) if program_id == instruction.program_id && !data.is_empty() => {
+                format!("Executed successfully; return 0x{}", to_hex(&data))
+            }
+            _ => "Executed successfully".to_string(),
+        };
         proposal.status = ProposalStatus::Executed;
-        proposal.execution_result = Some("Executed successfully".to_string());
+        proposal.execution_result = Some(truncate_result(result));
 
         msg!("Proposal executed: {}", proposal_id);
         Ok(())
@@ -305,23 +425,8 @@ pub mod governance {
         proposal_id: String,
     ) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
-        let state = &ctx.accounts.state;
         let clock = Clock::get()?;
-        let current_time = clock.unix_timestamp;
-
-        // 验证投票时间已结束
-        require!(current_time > proposal.voting_end_at, ErrorCode::VotingNotEnded);
-        require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalNotActive);
-
-        // 检查是否达到法定人数
-        let total_votes = proposal.votes_for + proposal.votes_against;
-        if total_votes < state.quorum {
-            proposal.status = ProposalStatus::Expired;
-        } else if proposal.votes_for > proposal.votes_against {
-            proposal.status = ProposalStatus::Passed;
-        } else {
-            proposal.status = ProposalStatus::Rejected;
-        }
+        proposal.finalize(clock.unix_timestamp, ctx.accounts.state.quorum)?;
 
         msg!("Proposal finalized: {} -> {:?}", proposal_id, proposal.status);
         Ok(())
@@ -358,6 +463,70 @@ pub mod governance {
     }
 }
 
+/// 执行结果字段的最大长度（与提案账户空间一致）
+const MAX_EXECUTION_RESULT_LEN: usize = 100;
+
+/// 根据存储的指令构建 CPI，并从 remaining_accounts 中找出对应账户
+///
+/// `pda_signer` 为本程序将以 seeds 签名的 PDA，其余签名账户必须已在外层交易中签名。
+fn prepare_instruction<'info>(
+    program_id: Pubkey,
+    accounts: &[TransactionAccount],
+    data: &[u8],
+    remaining_accounts: &[AccountInfo<'info>],
+    pda_signer: Pubkey,
+) -> std::result::Result<(Instruction, Vec<AccountInfo<'info>>), ErrorCode> {
+    let find = |key: &Pubkey| remaining_accounts.iter().find(|info| info.key == key);
+
+    let program_info = find(&program_id).ok_or(ErrorCode::MissingTargetProgram)?;
+    if !program_info.executable {
+        return Err(ErrorCode::MissingTargetProgram);
+    }
+
+    let mut metas = Vec::with_capacity(accounts.len());
+    let mut account_infos = Vec::with_capacity(accounts.len() + 1);
+    for account in accounts {
+        let info = find(&account.pubkey).ok_or(ErrorCode::MissingTargetAccount)?;
+        if account.is_signer && account.pubkey != pda_signer && !info.is_signer {
+            return Err(ErrorCode::MissingRequiredSigner);
+        }
+        if account.is_writable && !info.is_writable {
+            return Err(ErrorCode::AccountNotWritable);
+        }
+        metas.push(if account.is_writable {
+            AccountMeta::new(account.pubkey, account.is_signer)
+        } else {
+            AccountMeta::new_readonly(account.pubkey, account.is_signer)
+        });
+        account_infos.push(info.clone());
+    }
+    account_infos.push(program_info.clone());
+
+    Ok((
+        Instruction {
+            program_id,
+            accounts: metas,
+            data: data.to_vec(),
+        },
+        account_infos,
+    ))
+}
+
+fn truncate_result(mut result: String) -> String {
+    if result.len() > MAX_EXECUTION_RESULT_LEN {
+        let mut end = MAX_EXECUTION_RESULT_LEN;
+        while !result.is_char_boundary(end) {
+            end -= 1;
+        }
+        result.truncate(end);
+    }
+    result
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().take(32).map(|b| format!("{:02x}", b)).collect()
+}
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
@@ -415,7 +584,10 @@ pub struct CreateMultisigTransaction<'info> {
 
 #[derive(Accounts)]
 pub struct ApproveMultisigTransaction<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = multisig_transaction.multisig == multisig_account.key() @ ErrorCode::Unauthorized
+    )]
     pub multisig_transaction: Account<'info, MultisigTransaction>,
 
     pub multisig_account: Account<'info, MultisigAccount>,
@@ -425,11 +597,18 @@ pub struct ApproveMultisigTransaction<'info> {
 
 #[derive(Accounts)]
 pub struct ExecuteMultisigTransaction<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = multisig_transaction.multisig == multisig_account.key() @ ErrorCode::Unauthorized
+    )]
     pub multisig_transaction: Account<'info, MultisigTransaction>,
 
     pub multisig_account: Account<'info, MultisigAccount>,
 
+    /// CHECK: 多签 PDA，仅作为 CPI 签名者
+    #[account(seeds = [b"multisig-signer", multisig_account.key().as_ref()], bump)]
+    pub multisig_signer: UncheckedAccount<'info>,
+
     pub executor: Signer<'info>,
 }
 
@@ -458,7 +637,26 @@ pub struct VoteOnProposal<'info> {
     #[account(mut)]
     pub proposal: Account<'info, GovernanceProposal>,
 
+    pub state: Account<'info, GovernanceState>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + 32 + 32 + 32 + 8 + 1 + 8 + 1,
+        seeds = [b"voter-record", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    /// CHECK: 由节点管理合约在 CPI 中校验所有权和所有者
+    pub node_account: UncheckedAccount<'info>,
+
+    pub node_management_program: Program<'info, NodeManagement>,
+
+    #[account(mut)]
     pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -466,6 +664,12 @@ pub struct ExecuteProposal<'info> {
     #[account(mut)]
     pub proposal: Account<'info, GovernanceProposal>,
 
+    pub state: Account<'info, GovernanceState>,
+
+    /// CHECK: 治理 PDA，仅作为 CPI 签名者
+    #[account(seeds = [b"governance-authority"], bump)]
+    pub governance_authority: UncheckedAccount<'info>,
+
     pub executor: Signer<'info>,
 }
 
@@ -474,6 +678,9 @@ pub struct FinalizeProposal<'info> {
     #[account(mut)]
     pub proposal: Account<'info, GovernanceProposal>,
 
+    #[account(seeds = [b"governance-state"], bump)]
+    pub state: Account<'info, GovernanceState>,
+
     pub authority: Signer<'info>,
 }
 
@@ -503,10 +710,261 @@ pub enum ErrorCode {
     VotingNotStarted,
     #[msg("Voting ended")]
     VotingEnded,
+    #[msg("Voting not ended")]
+    VotingNotEnded,
     #[msg("Proposal not active")]
     ProposalNotActive,
     #[msg("Proposal not passed")]
     ProposalNotPassed,
     #[msg("Execution delay not met")]
     ExecutionDelayNotMet,
+    #[msg("Insufficient voting power")]
+    InsufficientVotingPower,
+    #[msg("Vote tally overflow")]
+    VoteOverflow,
+    #[msg("Target program account missing or not executable")]
+    MissingTargetProgram,
+    #[msg("Target account missing from remaining accounts")]
+    MissingTargetAccount,
+    #[msg("Required signer did not sign")]
+    MissingRequiredSigner,
+    #[msg("Target account is not writable")]
+    AccountNotWritable,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// 账户约束测试用的本地账户
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+        is_signer: bool,
+    }
+
+    impl TestAccount {
+        fn program<T: AccountSerialize>(key: Pubkey, account: &T) -> Self {
+            let mut data = Vec::new();
+            account.try_serialize(&mut data).unwrap();
+            Self {
+                key,
+                owner: crate::ID,
+                lamports: 1_000_000,
+                data,
+                is_signer: false,
+            }
+        }
+
+        fn signer(key: Pubkey) -> Self {
+            Self {
+                key,
+                owner: anchor_lang::system_program::ID,
+                lamports: 1_000_000,
+                data: Vec::new(),
+                is_signer: true,
+            }
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                self.is_signer,
+                true,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
+
+    fn multisig(owners: Vec<Pubkey>, threshold: u64) -> MultisigAccount {
+        MultisigAccount {
+            owners,
+            threshold,
+            nonce: 0,
+            is_active: true,
+            created_at: 0,
+            bump: 0,
+        }
+    }
+
+    fn transaction(multisig: Pubkey, owners: usize) -> MultisigTransaction {
+        MultisigTransaction {
+            multisig,
+            program_id: Pubkey::new_unique(),
+            accounts: Vec::new(),
+            data: Vec::new(),
+            signers: vec![false; owners],
+            did_execute: false,
+            created_at: 0,
+            executed_at: None,
+            bump: 0,
+        }
+    }
+
+    fn approve_accounts(
+        transaction: &mut TestAccount,
+        multisig: &mut TestAccount,
+        signer: &mut TestAccount,
+    ) -> Result<()> {
+        let infos = [transaction.info(), multisig.info(), signer.info()];
+        ApproveMultisigTransaction::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &[],
+            &mut Default::default(),
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_approve_through_foreign_multisig_rejected() {
+        let (owner_a, owner_b, attacker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let victim_key = Pubkey::new_unique();
+        let victim = multisig(vec![owner_a, owner_b], 2);
+        // 攻击者自建的多签，所有者数量多于受害交易的签名位
+        let foreign = multisig(vec![Pubkey::new_unique(), Pubkey::new_unique(), attacker], 1);
+        let mut tx = transaction(victim_key, victim.owners.len());
+
+        let mut tx_account = TestAccount::program(Pubkey::new_unique(), &tx);
+        let mut foreign_account = TestAccount::program(Pubkey::new_unique(), &foreign);
+        let mut attacker_signer = TestAccount::signer(attacker);
+        assert_eq!(
+            approve_accounts(&mut tx_account, &mut foreign_account, &mut attacker_signer).unwrap_err(),
+            ErrorCode::Unauthorized.into()
+        );
+        // 即使绕过账户约束，越界的签名位也只会被拒绝而不会 panic
+        assert!(tx.approve(&foreign.owners, &attacker).is_err());
+
+        let mut victim_account = TestAccount::program(victim_key, &victim);
+        let mut owner_signer = TestAccount::signer(owner_b);
+        assert!(approve_accounts(&mut tx_account, &mut victim_account, &mut owner_signer).is_ok());
+        assert_eq!(tx.approve(&victim.owners, &owner_b).unwrap(), 1);
+        assert_eq!(tx.approve(&victim.owners, &owner_b).unwrap_err(), ErrorCode::AlreadySigned.into());
+        assert_eq!(tx.approve(&victim.owners, &attacker).unwrap_err(), ErrorCode::Unauthorized.into());
+    }
+
+    fn proposal(votes_for: u64, votes_against: u64) -> GovernanceProposal {
+        GovernanceProposal {
+            id: "p-1".to_string(),
+            proposer: Pubkey::new_unique(),
+            title: "title".to_string(),
+            description: String::new(),
+            proposal_type: ProposalType::ParameterUpdate,
+            target_program: Pubkey::new_unique(),
+            target_accounts: Vec::new(),
+            instruction_data: Vec::new(),
+            voting_start_at: 0,
+            voting_end_at: 100,
+            votes_for,
+            votes_against,
+            status: ProposalStatus::Active,
+            execution_result: None,
+            created_at: 0,
+            bump: 0,
+        }
+    }
+
+    fn execute_accounts(
+        transaction: &mut TestAccount,
+        multisig: &mut TestAccount,
+        multisig_signer: Pubkey,
+        executor: &mut TestAccount,
+    ) -> Result<()> {
+        let mut pda = TestAccount::signer(multisig_signer);
+        pda.is_signer = false;
+        let infos = [transaction.info(), multisig.info(), pda.info(), executor.info()];
+        ExecuteMultisigTransaction::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &[],
+            &mut Default::default(),
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+
+    fn multisig_signer(multisig: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"multisig-signer", multisig.as_ref()], &crate::ID).0
+    }
+
+    #[test]
+    fn test_execute_rejects_mismatched_multisig_and_signer_pda() {
+        let owner = Pubkey::new_unique();
+        let (victim_key, foreign_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut tx = transaction(victim_key, 1);
+        tx.signers[0] = true;
+
+        let mut tx_account = TestAccount::program(Pubkey::new_unique(), &tx);
+        let mut victim_account = TestAccount::program(victim_key, &multisig(vec![owner], 1));
+        let mut foreign_account = TestAccount::program(foreign_key, &multisig(vec![owner], 1));
+        let mut executor = TestAccount::signer(owner);
+
+        let victim_signer = multisig_signer(&victim_key);
+        assert!(execute_accounts(&mut tx_account, &mut victim_account, victim_signer, &mut executor).is_ok());
+        // 用其他多签的 PDA 签名
+        assert_eq!(
+            execute_accounts(&mut tx_account, &mut victim_account, multisig_signer(&foreign_key), &mut executor)
+                .unwrap_err(),
+            anchor_lang::error::ErrorCode::ConstraintSeeds.into()
+        );
+        // 交易不属于传入的多签
+        assert_eq!(
+            execute_accounts(&mut tx_account, &mut foreign_account, multisig_signer(&foreign_key), &mut executor)
+                .unwrap_err(),
+            ErrorCode::Unauthorized.into()
+        );
+    }
+
+    #[test]
+    fn test_execute_requires_owner_and_threshold() {
+        let owners = vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let account = multisig(owners.clone(), 2);
+        let mut tx = transaction(Pubkey::new_unique(), owners.len());
+        tx.approve(&owners, &owners[0]).unwrap();
+
+        // 未达阈值
+        assert_eq!(
+            tx.check_executable(&account, &owners[0]).unwrap_err(),
+            ErrorCode::InsufficientSignatures.into()
+        );
+        tx.approve(&owners, &owners[2]).unwrap();
+        // 非所有者不能执行
+        assert_eq!(tx.check_executable(&account, &Pubkey::new_unique()).unwrap_err(), ErrorCode::Unauthorized.into());
+        assert!(tx.check_executable(&account, &owners[1]).is_ok());
+
+        tx.did_execute = true;
+        assert_eq!(
+            tx.check_executable(&account, &owners[1]).unwrap_err(),
+            ErrorCode::TransactionAlreadyExecuted.into()
+        );
+    }
+
+    #[test]
+    fn test_finalize_by_stake_quorum() {
+        // 投票期内不能结束
+        let mut below = proposal(40, 10);
+        assert!(below.finalize(100, 100).is_err());
+
+        // 赞成票占多数但总质押未达法定票数，提案过期
+        assert!(below.finalize(101, 100).is_ok());
+        assert_eq!(below.status, ProposalStatus::Expired);
+        // 已结束的提案不能再次结束
+        assert!(below.finalize(102, 0).is_err());
+
+        let mut passed = proposal(80, 20);
+        passed.finalize(101, 100).unwrap();
+        assert_eq!(passed.status, ProposalStatus::Passed);
+
+        let mut rejected = proposal(50, 50);
+        rejected.finalize(101, 100).unwrap();
+        assert_eq!(rejected.status, ProposalStatus::Rejected);
+    }
 }
@@ -0,0 +1,830 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use zk_verifier::program::ZkVerifier;
+use zk_verifier::{Groth16Proof, VerifyingKeyAccount};
+
+declare_id!("JOB_ESCROW_PROGRAM_ID");
+
+/// 任务ID最大长度
+pub const MAX_JOB_ID_LEN: usize = 64;
+/// 争议理由最大长度
+pub const MAX_DISPUTE_REASON_LEN: usize = 200;
+/// 裁决比例的分母（万分比）
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// 争议裁决期限（秒）：逾期未裁决时任何人都可以把托管款退给发布者
+pub const DISPUTE_RESOLUTION_SECONDS: i64 = 14 * 24 * 3600;
+
+/// 任务托管状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JobStatus {
+    Open,     // 已托管，等待指派节点
+    Assigned, // 已指派节点，等待提交结果
+    Verified, // 结果证明已验证，处于争议期
+    Disputed, // 争议中，等待治理多签裁决
+    Released, // 已付款给节点
+    Refunded, // 超时未完成，已退款给发布者
+    Resolved, // 治理裁决后按比例结算
+}
+
+impl JobStatus {
+    pub fn is_settled(self) -> bool {
+        matches!(self, JobStatus::Released | JobStatus::Refunded | JobStatus::Resolved)
+    }
+}
+
+/// 托管合约全局状态
+#[account]
+pub struct EscrowState {
+    pub admin: Pubkey,                    // 管理员公钥
+    pub dispute_authority: Pubkey,        // 争议裁决者（治理多签签名 PDA）
+    pub dispute_window_seconds: i64,      // 结果验证后可发起争议的时长
+    pub min_deposit: u64,                 // 最低托管金额（lamports）
+    pub total_jobs: u64,                  // 累计任务数
+    pub total_escrowed: u64,              // 当前托管中的总额（lamports）
+    pub bump: u8,                         // PDA bump
+}
+
+impl EscrowState {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+}
+
+/// 单个任务的托管账户，托管的 lamports 存放在账户本身
+#[account]
+pub struct JobEscrow {
+    pub job_id: String,                   // 任务ID
+    pub requester: Pubkey,                // 发布者（付款方）
+    pub node: Option<Pubkey>,             // 指派的节点
+    pub amount: u64,                      // 托管金额（lamports，不含租金）
+    pub circuit_id: String,               // 结果证明使用的电路
+    pub verifying_key: Pubkey,            // 发布时固定的验证密钥账户
+    pub input_commitment: [u8; 32],       // 任务输入承诺，须为证明的第一个公开输入
+    pub result_hash: Option<[u8; 32]>,    // 结果摘要
+    pub proof_hash: Option<[u8; 32]>,     // 已验证的证明摘要
+    pub status: JobStatus,                // 托管状态
+    pub created_at: i64,                  // 创建时间
+    pub deadline: i64,                    // 提交结果期限
+    pub verified_at: Option<i64>,         // 结果验证时间
+    pub disputed_by: Option<Pubkey>,      // 争议发起方
+    pub dispute_reason: Option<String>,   // 争议理由
+    pub settled_at: Option<i64>,          // 结算时间
+    pub bump: u8,                         // PDA bump
+    pub dispute_deadline: Option<i64>,    // 争议裁决期限
+    pub disputed_from: Option<JobStatus>, // 发起争议前的状态
+}
+
+impl JobEscrow {
+    pub const SPACE: usize = 8
+        + (4 + MAX_JOB_ID_LEN)
+        + 32
+        + (1 + 32)
+        + 8
+        + (4 + zk_verifier::MAX_CIRCUIT_ID_LEN)
+        + 32
+        + 32
+        + (1 + 32)
+        + (1 + 32)
+        + 1
+        + 8
+        + 8
+        + (1 + 8)
+        + (1 + 32)
+        + (1 + 4 + MAX_DISPUTE_REASON_LEN)
+        + (1 + 8)
+        + 1
+        + (1 + 8)
+        + (1 + 1);
+
+    /// 争议期结束时间
+    pub fn dispute_window_end(&self, dispute_window_seconds: i64) -> Option<i64> {
+        self.verified_at.map(|t| t.saturating_add(dispute_window_seconds))
+    }
+
+    /// 结果证明的公开输入必须依次为任务输入承诺和结果摘要，不能挪用其他任务或其他结果的证明
+    pub fn check_result_binding(&self, result_hash: &[u8; 32], public_inputs: &[[u8; 32]]) -> Result<()> {
+        require!(
+            public_inputs.first() == Some(&self.input_commitment),
+            JobEscrowError::InputCommitmentMismatch
+        );
+        require!(public_inputs.get(1) == Some(result_hash), JobEscrowError::ResultHashMismatch);
+        Ok(())
+    }
+
+    /// 已验证且争议期已过时可以向节点付款
+    pub fn check_releasable(&self, dispute_window_seconds: i64, now: i64) -> Result<()> {
+        require!(self.status == JobStatus::Verified, JobEscrowError::InvalidStatus);
+        let window_end = self
+            .dispute_window_end(dispute_window_seconds)
+            .ok_or(JobEscrowError::InvalidStatus)?;
+        require!(now > window_end, JobEscrowError::DisputeWindowOpen);
+        Ok(())
+    }
+
+    /// 期限已过仍未验证结果时可以退款
+    pub fn check_refundable(&self, now: i64) -> Result<()> {
+        require!(
+            matches!(self.status, JobStatus::Open | JobStatus::Assigned),
+            JobEscrowError::InvalidStatus
+        );
+        require!(now > self.deadline, JobEscrowError::DeadlineNotReached);
+        Ok(())
+    }
+
+    /// 争议超过裁决期限仍未裁决时可以退款
+    pub fn check_dispute_expired(&self, now: i64) -> Result<()> {
+        require!(self.status == JobStatus::Disputed, JobEscrowError::InvalidStatus);
+        let dispute_deadline = self.dispute_deadline.ok_or(JobEscrowError::InvalidStatus)?;
+        require!(now > dispute_deadline, JobEscrowError::DisputeNotExpired);
+        Ok(())
+    }
+
+    /// 争议逾期未裁决时的结算：结果已验证的任务按原结果付款给节点，执行期间的争议退款给发布者，
+    /// 返回 (结算状态, 节点金额, 发布者金额)
+    pub fn expired_dispute_settlement(&self) -> (JobStatus, u64, u64) {
+        match (self.disputed_from, self.node) {
+            (Some(JobStatus::Verified), Some(_)) => (JobStatus::Released, self.amount, 0),
+            _ => (JobStatus::Refunded, 0, self.amount),
+        }
+    }
+
+    /// 按万分比裁决时节点分得的金额，其余退给发布者
+    pub fn node_share(&self, node_share_bps: u16) -> u64 {
+        (self.amount as u128 * node_share_bps.min(BPS_DENOMINATOR as u16) as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+/// 节点提交的结果证明
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ResultProof {
+    pub proof_hash: [u8; 32],             // 证明摘要（用于派生证明记录地址）
+    pub proof: Groth16Proof,              // Groth16 证明
+    pub public_inputs: Vec<[u8; 32]>,     // 公开输入（依次为任务输入承诺和结果摘要）
+}
+
+#[event]
+pub struct JobCreated {
+    pub job_id: String,
+    pub requester: Pubkey,
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct ResultVerified {
+    pub job_id: String,
+    pub node: Pubkey,
+    pub result_hash: [u8; 32],
+    pub proof_hash: [u8; 32],
+}
+
+#[event]
+pub struct JobSettled {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub node_amount: u64,
+    pub requester_amount: u64,
+}
+
+#[event]
+pub struct DisputeRaised {
+    pub job_id: String,
+    pub disputed_by: Pubkey,
+    pub reason: String,
+}
+
+#[program]
+pub mod job_escrow {
+    use super::*;
+
+    /// 初始化托管合约，争议裁决者默认为管理员
+    pub fn initialize(ctx: Context<Initialize>, dispute_window_seconds: i64, min_deposit: u64) -> Result<()> {
+        require!(dispute_window_seconds >= 0, JobEscrowError::InvalidDisputeWindow);
+
+        let state = &mut ctx.accounts.state;
+        state.admin = ctx.accounts.admin.key();
+        state.dispute_authority = ctx.accounts.admin.key();
+        state.dispute_window_seconds = dispute_window_seconds;
+        state.min_deposit = min_deposit;
+        state.total_jobs = 0;
+        state.total_escrowed = 0;
+        state.bump = ctx.bumps.state;
+
+        msg!("Job escrow initialized, dispute window {}s", dispute_window_seconds);
+        Ok(())
+    }
+
+    /// 发布任务并托管付款，结果证明只能用此时指定的验证密钥验证
+    pub fn create_job(
+        ctx: Context<CreateJob>,
+        job_id: String,
+        amount: u64,
+        circuit_id: String,
+        input_commitment: [u8; 32],
+        deadline: i64,
+    ) -> Result<()> {
+        require!(job_id.len() <= MAX_JOB_ID_LEN, JobEscrowError::JobIdTooLong);
+        require!(
+            circuit_id.len() <= zk_verifier::MAX_CIRCUIT_ID_LEN,
+            JobEscrowError::CircuitIdTooLong
+        );
+        require!(
+            ctx.accounts.verifying_key.circuit_id == circuit_id,
+            JobEscrowError::CircuitMismatch
+        );
+        require!(amount > 0 && amount >= ctx.accounts.state.min_deposit, JobEscrowError::DepositTooLow);
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(deadline > current_time, JobEscrowError::InvalidDeadline);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.requester.to_account_info(),
+                    to: ctx.accounts.job_escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let job = &mut ctx.accounts.job_escrow;
+        job.job_id = job_id.clone();
+        job.requester = ctx.accounts.requester.key();
+        job.node = None;
+        job.amount = amount;
+        job.circuit_id = circuit_id;
+        job.verifying_key = ctx.accounts.verifying_key.key();
+        job.input_commitment = input_commitment;
+        job.result_hash = None;
+        job.proof_hash = None;
+        job.status = JobStatus::Open;
+        job.created_at = current_time;
+        job.deadline = deadline;
+        job.verified_at = None;
+        job.disputed_by = None;
+        job.dispute_reason = None;
+        job.settled_at = None;
+        job.bump = ctx.bumps.job_escrow;
+        job.dispute_deadline = None;
+
+        let state = &mut ctx.accounts.state;
+        state.total_jobs += 1;
+        state.total_escrowed = state.total_escrowed.checked_add(amount).ok_or(JobEscrowError::Overflow)?;
+
+        emit!(JobCreated {
+            job_id: job_id.clone(),
+            requester: job.requester,
+            amount,
+            deadline,
+        });
+        msg!("Job {} created with {} lamports in escrow", job_id, amount);
+        Ok(())
+    }
+
+    /// 发布者指派执行节点（例如市场竞价的中标节点）
+    pub fn assign_node(ctx: Context<AssignNode>, node: Pubkey) -> Result<()> {
+        let job = &mut ctx.accounts.job_escrow;
+        require!(job.status == JobStatus::Open, JobEscrowError::InvalidStatus);
+        require!(Clock::get()?.unix_timestamp <= job.deadline, JobEscrowError::DeadlinePassed);
+
+        job.node = Some(node);
+        job.status = JobStatus::Assigned;
+
+        msg!("Job {} assigned to node {}", job.job_id, node);
+        Ok(())
+    }
+
+    /// 节点提交结果：通过 zk-verifier 验证证明，争议窗口为 0 时立即付款
+    ///
+    /// `result_hash` 须为标量域内的值（如 `zk_verifier::hash_to_scalar` 的输出），作为证明的第二个公开输入。
+    pub fn submit_result(ctx: Context<SubmitResult>, result_hash: [u8; 32], proof: ResultProof) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let job = &ctx.accounts.job_escrow;
+        require!(job.status == JobStatus::Assigned, JobEscrowError::InvalidStatus);
+        require!(current_time <= job.deadline, JobEscrowError::DeadlinePassed);
+        job.check_result_binding(&result_hash, &proof.public_inputs)?;
+
+        let cpi_accounts = zk_verifier::cpi::accounts::VerifyProof {
+            verifying_key: ctx.accounts.verifying_key.to_account_info(),
+            proof_record: ctx.accounts.proof_record.to_account_info(),
+            payer: ctx.accounts.node.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        zk_verifier::cpi::verify_proof(
+            CpiContext::new(ctx.accounts.zk_verifier_program.to_account_info(), cpi_accounts),
+            proof.proof_hash,
+            proof.proof,
+            proof.public_inputs,
+        )?;
+
+        let job = &mut ctx.accounts.job_escrow;
+        job.result_hash = Some(result_hash);
+        job.proof_hash = Some(proof.proof_hash);
+        job.verified_at = Some(current_time);
+        job.status = JobStatus::Verified;
+
+        emit!(ResultVerified {
+            job_id: job.job_id.clone(),
+            node: ctx.accounts.node.key(),
+            result_hash,
+            proof_hash: proof.proof_hash,
+        });
+        msg!("Result verified for job {}", job.job_id);
+
+        if ctx.accounts.state.dispute_window_seconds == 0 {
+            let amount = job.amount;
+            pay_out(&job.to_account_info(), &ctx.accounts.node.to_account_info(), amount)?;
+            settle(job, &mut ctx.accounts.state, JobStatus::Released, amount, 0, current_time);
+        }
+        Ok(())
+    }
+
+    /// 争议期结束且无争议时，任何人都可以触发向节点付款
+    pub fn release_payment(ctx: Context<ReleasePayment>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let job = &ctx.accounts.job_escrow;
+        job.check_releasable(ctx.accounts.state.dispute_window_seconds, current_time)?;
+
+        let amount = job.amount;
+        pay_out(&job.to_account_info(), &ctx.accounts.node, amount)?;
+        settle(&mut ctx.accounts.job_escrow, &mut ctx.accounts.state, JobStatus::Released, amount, 0, current_time);
+        Ok(())
+    }
+
+    /// 期限已过仍未验证结果时，任何人都可以触发退款给发布者
+    pub fn refund_expired(ctx: Context<RefundExpired>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let job = &ctx.accounts.job_escrow;
+        job.check_refundable(current_time)?;
+
+        let amount = job.amount;
+        pay_out(&job.to_account_info(), &ctx.accounts.requester, amount)?;
+        settle(&mut ctx.accounts.job_escrow, &mut ctx.accounts.state, JobStatus::Refunded, 0, amount, current_time);
+        Ok(())
+    }
+
+    /// 发布者发起争议，冻结付款和退款，交由治理多签在裁决期限内裁决：
+    /// 执行期间（期限前）或结果验证后的争议期内均可发起。
+    /// 节点不能发起争议，否则可借此无限期阻止发布者在期限后退款
+    pub fn raise_dispute(ctx: Context<RaiseDispute>, reason: String) -> Result<()> {
+        require!(reason.len() <= MAX_DISPUTE_REASON_LEN, JobEscrowError::ReasonTooLong);
+        let current_time = Clock::get()?.unix_timestamp;
+        let dispute_window_seconds = ctx.accounts.state.dispute_window_seconds;
+        let job = &mut ctx.accounts.job_escrow;
+        let signer = ctx.accounts.signer.key();
+        require!(signer == job.requester, JobEscrowError::Unauthorized);
+
+        match job.status {
+            JobStatus::Assigned => {
+                require!(current_time <= job.deadline, JobEscrowError::DeadlinePassed);
+            }
+            JobStatus::Verified => {
+                let window_end = job
+                    .dispute_window_end(dispute_window_seconds)
+                    .ok_or(JobEscrowError::InvalidStatus)?;
+                require!(current_time <= window_end, JobEscrowError::DisputeWindowClosed);
+            }
+            _ => return err!(JobEscrowError::InvalidStatus),
+        }
+
+        job.disputed_from = Some(job.status);
+        job.status = JobStatus::Disputed;
+        job.disputed_by = Some(signer);
+        job.dispute_reason = Some(reason.clone());
+        job.dispute_deadline = Some(current_time.saturating_add(DISPUTE_RESOLUTION_SECONDS));
+
+        emit!(DisputeRaised {
+            job_id: job.job_id.clone(),
+            disputed_by: signer,
+            reason,
+        });
+        msg!("Dispute raised on job {} by {}", job.job_id, signer);
+        Ok(())
+    }
+
+    /// 治理多签裁决争议：按万分比把托管金额分给节点，其余退给发布者
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, node_share_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.state.dispute_authority,
+            JobEscrowError::Unauthorized
+        );
+        require!(node_share_bps as u64 <= BPS_DENOMINATOR, JobEscrowError::InvalidShare);
+        let job = &ctx.accounts.job_escrow;
+        require!(job.status == JobStatus::Disputed, JobEscrowError::InvalidStatus);
+
+        let node_amount = match job.node {
+            Some(node) => {
+                require!(ctx.accounts.node.key() == node, JobEscrowError::WrongNode);
+                job.node_share(node_share_bps)
+            }
+            None => 0,
+        };
+        let requester_amount = job.amount - node_amount;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let escrow = job.to_account_info();
+        pay_out(&escrow, &ctx.accounts.node, node_amount)?;
+        pay_out(&escrow, &ctx.accounts.requester, requester_amount)?;
+        settle(
+            &mut ctx.accounts.job_escrow,
+            &mut ctx.accounts.state,
+            JobStatus::Resolved,
+            node_amount,
+            requester_amount,
+            current_time,
+        );
+        Ok(())
+    }
+
+    /// 争议逾期未裁决时，任何人都可以触发结算：结果已验证的任务全额付款给节点，
+    /// 执行期间发起的争议全额退给发布者。发布者不能靠拖延裁决收回已完成任务的托管款
+    pub fn settle_expired_dispute(ctx: Context<SettleExpiredDispute>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let job = &ctx.accounts.job_escrow;
+        job.check_dispute_expired(current_time)?;
+
+        let (status, node_amount, requester_amount) = job.expired_dispute_settlement();
+        if node_amount > 0 {
+            require!(Some(ctx.accounts.node.key()) == job.node, JobEscrowError::WrongNode);
+        }
+        let escrow = job.to_account_info();
+        pay_out(&escrow, &ctx.accounts.node, node_amount)?;
+        pay_out(&escrow, &ctx.accounts.requester, requester_amount)?;
+        settle(
+            &mut ctx.accounts.job_escrow,
+            &mut ctx.accounts.state,
+            status,
+            node_amount,
+            requester_amount,
+            current_time,
+        );
+        Ok(())
+    }
+
+    /// 结算后发布者关闭托管账户，取回租金
+    pub fn close_job(ctx: Context<CloseJob>) -> Result<()> {
+        require!(ctx.accounts.job_escrow.status.is_settled(), JobEscrowError::InvalidStatus);
+        msg!("Job {} closed", ctx.accounts.job_escrow.job_id);
+        Ok(())
+    }
+
+    /// 设置争议裁决者（通常为治理多签签名 PDA）和争议窗口（仅管理员）
+    pub fn set_dispute_authority(
+        ctx: Context<SetDisputeAuthority>,
+        dispute_authority: Pubkey,
+        dispute_window_seconds: i64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.admin.key() == state.admin, JobEscrowError::Unauthorized);
+        require!(dispute_window_seconds >= 0, JobEscrowError::InvalidDisputeWindow);
+
+        state.dispute_authority = dispute_authority;
+        state.dispute_window_seconds = dispute_window_seconds;
+
+        msg!("Dispute authority set to {}, dispute window {}s", dispute_authority, dispute_window_seconds);
+        Ok(())
+    }
+}
+
+/// 从托管账户转出 lamports（托管账户归本程序所有，可直接扣减）
+fn pay_out<'info>(escrow: &AccountInfo<'info>, recipient: &AccountInfo<'info>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let remaining = escrow.lamports().checked_sub(amount).ok_or(JobEscrowError::InsufficientEscrow)?;
+    let credited = recipient.lamports().checked_add(amount).ok_or(JobEscrowError::Overflow)?;
+    **escrow.try_borrow_mut_lamports()? = remaining;
+    **recipient.try_borrow_mut_lamports()? = credited;
+    Ok(())
+}
+
+/// 记录结算结果（付款由调用方完成）
+fn settle(
+    job: &mut JobEscrow,
+    state: &mut EscrowState,
+    status: JobStatus,
+    node_amount: u64,
+    requester_amount: u64,
+    current_time: i64,
+) {
+    state.total_escrowed = state.total_escrowed.saturating_sub(job.amount);
+    job.status = status;
+    job.settled_at = Some(current_time);
+
+    emit!(JobSettled {
+        job_id: job.job_id.clone(),
+        status,
+        node_amount,
+        requester_amount,
+    });
+    msg!("Job {} settled: {:?}", job.job_id, status);
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = EscrowState::SPACE,
+        seeds = [b"job-escrow-state"],
+        bump
+    )]
+    pub state: Account<'info, EscrowState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(job_id: String)]
+pub struct CreateJob<'info> {
+    #[account(
+        init,
+        payer = requester,
+        space = JobEscrow::SPACE,
+        seeds = [b"job-escrow", requester.key().as_ref(), job_id.as_bytes()],
+        bump
+    )]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(mut, seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    pub verifying_key: Account<'info, VerifyingKeyAccount>,
+
+    #[account(mut)]
+    pub requester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AssignNode<'info> {
+    #[account(mut, has_one = requester)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    pub requester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitResult<'info> {
+    #[account(mut, constraint = job_escrow.node == Some(node.key()) @ JobEscrowError::WrongNode)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(mut, seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    #[account(mut)]
+    pub node: Signer<'info>,
+
+    #[account(address = job_escrow.verifying_key @ JobEscrowError::CircuitMismatch)]
+    pub verifying_key: Account<'info, VerifyingKeyAccount>,
+
+    /// CHECK: 由 zk-verifier 合约创建并校验地址
+    #[account(mut)]
+    pub proof_record: UncheckedAccount<'info>,
+
+    pub zk_verifier_program: Program<'info, ZkVerifier>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleasePayment<'info> {
+    #[account(mut, constraint = job_escrow.node == Some(node.key()) @ JobEscrowError::WrongNode)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(mut, seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    /// CHECK: 收款节点，须为任务指派的节点
+    #[account(mut)]
+    pub node: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpired<'info> {
+    #[account(mut, has_one = requester)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(mut, seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    /// CHECK: 收款的发布者，须与托管账户记录一致
+    #[account(mut)]
+    pub requester: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleExpiredDispute<'info> {
+    #[account(mut, has_one = requester)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(mut, seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    /// CHECK: 收款的发布者，须与托管账户记录一致
+    #[account(mut)]
+    pub requester: AccountInfo<'info>,
+
+    /// CHECK: 指派的节点（只有结果已验证时才收款，此时须与托管账户记录一致）
+    #[account(mut)]
+    pub node: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    #[account(mut)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut, has_one = requester)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(mut, seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    /// CHECK: 发布者，须与托管账户记录一致
+    #[account(mut)]
+    pub requester: AccountInfo<'info>,
+
+    /// CHECK: 指派的节点（未指派时可传任意账户，不会收款）
+    #[account(mut)]
+    pub node: AccountInfo<'info>,
+
+    // 治理多签签名 PDA，通过 execute_multisig_transaction CPI 签名
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseJob<'info> {
+    #[account(mut, has_one = requester, close = requester)]
+    pub job_escrow: Account<'info, JobEscrow>,
+
+    #[account(mut)]
+    pub requester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDisputeAuthority<'info> {
+    #[account(mut, seeds = [b"job-escrow-state"], bump = state.bump)]
+    pub state: Account<'info, EscrowState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[error_code]
+pub enum JobEscrowError {
+    #[msg("Job id too long")]
+    JobIdTooLong,
+    #[msg("Circuit id too long")]
+    CircuitIdTooLong,
+    #[msg("Dispute reason too long")]
+    ReasonTooLong,
+    #[msg("Deposit below minimum")]
+    DepositTooLow,
+    #[msg("Deadline must be in the future")]
+    InvalidDeadline,
+    #[msg("Dispute window must not be negative")]
+    InvalidDisputeWindow,
+    #[msg("Node share exceeds 10000 bps")]
+    InvalidShare,
+    #[msg("Job is not in a valid status for this operation")]
+    InvalidStatus,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Account is not the assigned node")]
+    WrongNode,
+    #[msg("Job deadline has passed")]
+    DeadlinePassed,
+    #[msg("Job deadline has not been reached")]
+    DeadlineNotReached,
+    #[msg("Dispute window is still open")]
+    DisputeWindowOpen,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Dispute resolution deadline has not been reached")]
+    DisputeNotExpired,
+    #[msg("Verifying key circuit does not match the job")]
+    CircuitMismatch,
+    #[msg("Proof is not bound to the job input commitment")]
+    InputCommitmentMismatch,
+    #[msg("Proof is not bound to the submitted result hash")]
+    ResultHashMismatch,
+    #[msg("Escrow balance is insufficient")]
+    InsufficientEscrow,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(status: JobStatus) -> JobEscrow {
+        JobEscrow {
+            job_id: "job-1".to_string(),
+            requester: Pubkey::new_unique(),
+            node: Some(Pubkey::new_unique()),
+            amount: 1_000,
+            circuit_id: "matmul".to_string(),
+            verifying_key: Pubkey::new_unique(),
+            input_commitment: [1; 32],
+            result_hash: None,
+            proof_hash: None,
+            status,
+            created_at: 0,
+            deadline: 100,
+            verified_at: None,
+            disputed_by: None,
+            dispute_reason: None,
+            settled_at: None,
+            bump: 0,
+            dispute_deadline: None,
+            disputed_from: None,
+        }
+    }
+
+    #[test]
+    fn test_result_binding() {
+        let job = job(JobStatus::Assigned);
+        assert!(job.check_result_binding(&[2; 32], &[[1; 32], [2; 32]]).is_ok());
+        // 输入承诺或结果摘要不符、缺少结果摘要都不接受
+        assert!(job.check_result_binding(&[2; 32], &[[9; 32], [2; 32]]).is_err());
+        assert!(job.check_result_binding(&[3; 32], &[[1; 32], [2; 32]]).is_err());
+        assert!(job.check_result_binding(&[2; 32], &[[1; 32]]).is_err());
+    }
+
+    #[test]
+    fn test_release_after_dispute_window() {
+        let mut job = job(JobStatus::Verified);
+        job.verified_at = Some(50);
+        assert!(job.check_releasable(10, 60).is_err());
+        assert!(job.check_releasable(10, 61).is_ok());
+
+        // 争议中或已结算的任务不能付款
+        job.status = JobStatus::Disputed;
+        assert!(job.check_releasable(10, 61).is_err());
+        job.status = JobStatus::Released;
+        assert!(job.check_releasable(10, 61).is_err());
+    }
+
+    #[test]
+    fn test_refund_after_deadline() {
+        let mut job = job(JobStatus::Assigned);
+        assert!(job.check_refundable(100).is_err());
+        assert!(job.check_refundable(101).is_ok());
+
+        job.status = JobStatus::Open;
+        assert!(job.check_refundable(101).is_ok());
+        // 结果已验证后只能付款或走争议，不能退款
+        job.status = JobStatus::Verified;
+        assert!(job.check_refundable(101).is_err());
+    }
+
+    #[test]
+    fn test_refund_after_dispute_deadline() {
+        let mut job = job(JobStatus::Disputed);
+        job.dispute_deadline = Some(200);
+        assert!(job.check_dispute_expired(200).is_err());
+        assert!(job.check_dispute_expired(201).is_ok());
+
+        // 已裁决或未进入争议的任务不能按逾期退款
+        job.status = JobStatus::Resolved;
+        assert!(job.check_dispute_expired(201).is_err());
+        job.status = JobStatus::Assigned;
+        assert!(job.check_dispute_expired(201).is_err());
+        // 争议中的任务也不能按执行期限退款
+        job.status = JobStatus::Disputed;
+        assert!(job.check_refundable(201).is_err());
+    }
+
+    #[test]
+    fn test_expired_dispute_on_verified_job_pays_node() {
+        let mut job = job(JobStatus::Disputed);
+        job.dispute_deadline = Some(200);
+        job.disputed_from = Some(JobStatus::Verified);
+        assert!(job.check_dispute_expired(201).is_ok());
+        assert_eq!(job.expired_dispute_settlement(), (JobStatus::Released, 1_000, 0));
+
+        // 执行期间发起的争议逾期后退款给发布者
+        job.disputed_from = Some(JobStatus::Assigned);
+        assert_eq!(job.expired_dispute_settlement(), (JobStatus::Refunded, 0, 1_000));
+    }
+
+    #[test]
+    fn test_node_share() {
+        let job = job(JobStatus::Disputed);
+        assert_eq!(job.node_share(0), 0);
+        assert_eq!(job.node_share(2_500), 250);
+        assert_eq!(job.node_share(10_000), 1_000);
+        assert_eq!(job.node_share(u16::MAX), 1_000);
+    }
+}
@@ -1,8 +1,20 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use shared_types::*;
 
 declare_id!("NODE_MANAGEMENT_PROGRAM_ID");
 
+/// 默认申诉窗口（秒）
+pub const DEFAULT_APPEAL_WINDOW_SECONDS: i64 = 3 * 24 * 3600;
+/// 默认不活跃阈值（秒）：超过该时间没有心跳的节点可被标记为离线
+pub const DEFAULT_INACTIVE_THRESHOLD_SECONDS: i64 = 3600;
+/// 默认最小心跳间隔（秒）
+pub const DEFAULT_MIN_HEARTBEAT_INTERVAL_SECONDS: i64 = 60;
+/// 信誉分上限
+pub const MAX_REPUTATION_SCORE: u32 = 1000;
+/// 罚没原因最大长度
+pub const MAX_SLASH_REASON_LEN: usize = 200;
+
 /// 节点账户
 #[account]
 pub struct NodeAccount {
@@ -32,6 +44,171 @@ pub struct NodeManagementState {
     pub min_stake_amount: u64,            // 最小质押数量
     pub verification_fee: u64,            // 验证费用
     pub bump: u8,                         // PDA bump
+    pub slash_authority: Pubkey,          // 罚没裁决者（治理多签签名 PDA）
+    pub appeal_window_seconds: i64,       // 罚没申诉窗口（秒）
+    pub inactive_threshold_seconds: i64,  // 不活跃阈值（秒）
+    pub min_heartbeat_interval_seconds: i64, // 最小心跳间隔（秒）
+    pub treasury: Pubkey,                 // 罚没质押的接收地址
+}
+
+impl NodeManagementState {
+    /// 当前布局的账户大小
+    pub const SPACE: usize = Self::SPACE_V2 + 32;
+    /// 初版布局（到 bump 为止）
+    pub const SPACE_V0: usize = 8 + 32 + 4 + 4 + 8 + 8 + 1;
+    /// 加入罚没裁决者和申诉窗口后的布局
+    pub const SPACE_V1: usize = Self::SPACE_V0 + 32 + 8;
+    /// 加入活跃度设置后的布局
+    pub const SPACE_V2: usize = Self::SPACE_V1 + 8 + 8;
+
+    /// 按当前布局读取旧布局的账户数据，旧布局中没有的字段写入默认值
+    pub fn migrate(old_data: &[u8]) -> Result<Self> {
+        let old_len = old_data.len();
+        require!(
+            (Self::SPACE_V0..Self::SPACE).contains(&old_len) && old_data[..8] == *Self::DISCRIMINATOR,
+            ErrorCode::InvalidStateLayout
+        );
+
+        let mut data = old_data.to_vec();
+        data.resize(Self::SPACE, 0);
+        let mut state = Self::try_deserialize(&mut &data[..])?;
+        if old_len < Self::SPACE_V1 {
+            state.slash_authority = state.admin;
+            state.appeal_window_seconds = DEFAULT_APPEAL_WINDOW_SECONDS;
+        }
+        if old_len < Self::SPACE_V2 {
+            state.inactive_threshold_seconds = DEFAULT_INACTIVE_THRESHOLD_SECONDS;
+            state.min_heartbeat_interval_seconds = DEFAULT_MIN_HEARTBEAT_INTERVAL_SECONDS;
+        }
+        state.treasury = state.admin;
+        Ok(state)
+    }
+
+    /// 管理员和治理多签可以任意变更节点状态；节点所有者只能在非封禁状态之间切换，
+    /// 不能封禁节点，也不能解除封禁
+    pub fn check_status_change(
+        &self,
+        authority: &Pubkey,
+        owner: &Pubkey,
+        current: &NodeStatus,
+        new_status: &NodeStatus,
+    ) -> Result<()> {
+        if *authority == self.admin || *authority == self.slash_authority {
+            return Ok(());
+        }
+        require!(authority == owner, ErrorCode::Unauthorized);
+        require!(
+            *current != NodeStatus::Banned && *new_status != NodeStatus::Banned,
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    /// 按节点进入或离开 Active 更新活跃节点数
+    pub fn track_status_change(&mut self, current: &NodeStatus, new_status: &NodeStatus) {
+        match (*current == NodeStatus::Active, *new_status == NodeStatus::Active) {
+            (true, false) => self.active_nodes = self.active_nodes.saturating_sub(1),
+            (false, true) => self.active_nodes += 1,
+            _ => {}
+        }
+    }
+}
+
+#[event]
+pub struct ReputationUpdated {
+    pub node_id: Pubkey,
+    pub previous: u32,
+    pub reputation_score: u32,
+}
+
+#[event]
+pub struct NodeMarkedInactive {
+    pub node_id: Pubkey,
+    pub last_active_at: i64,
+    pub marked_by: Pubkey,
+}
+
+/// 罚没状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlashStatus {
+    Pending,                              // 申诉窗口内，等待申诉或到期
+    Appealed,                             // 节点所有者已申诉，等待治理裁决
+    Executed,                             // 罚没已执行
+    Overturned,                           // 申诉成功，罚没撤销
+}
+
+/// 罚没记录：证据上链，申诉窗口结束或治理裁决后才转移质押
+#[account]
+pub struct SlashRecord {
+    pub node_id: Pubkey,                  // 被罚没节点ID
+    pub node_account: Pubkey,             // 节点账户地址
+    pub reporter: Pubkey,                 // 发起者
+    pub evidence_hash: [u8; 32],          // 证据摘要
+    pub reason: String,                   // 罚没原因
+    pub slash_ratio: u32,                 // 罚没比例（基点）
+    pub created_at: i64,                  // 发起时间
+    pub appeal_deadline: i64,             // 申诉截止时间
+    pub status: SlashStatus,              // 状态
+    pub counter_evidence_hash: Option<[u8; 32]>, // 申诉反证摘要
+    pub appealed_at: Option<i64>,         // 申诉时间
+    pub resolved_by: Option<Pubkey>,      // 裁决者
+    pub resolved_at: Option<i64>,         // 裁决或执行时间
+    pub slashed_amount: u64,              // 实际罚没金额
+    pub bump: u8,                         // PDA bump
+}
+
+impl SlashRecord {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 32 + (4 + MAX_SLASH_REASON_LEN) + 4 + 8 + 8 + 1
+        + (1 + 32) + (1 + 8) + (1 + 32) + (1 + 8) + 8 + 1;
+
+    /// 只能在申诉窗口内对待执行的罚没提出申诉
+    pub fn check_appealable(&self, now: i64) -> Result<()> {
+        require!(self.status == SlashStatus::Pending, ErrorCode::SlashNotPending);
+        require!(now <= self.appeal_deadline, ErrorCode::AppealWindowClosed);
+        Ok(())
+    }
+
+    /// 已申诉的记录随时可裁决，未申诉的记录需等窗口结束
+    pub fn check_resolvable(&self, now: i64) -> Result<()> {
+        match self.status {
+            SlashStatus::Appealed => Ok(()),
+            SlashStatus::Pending => {
+                require!(now > self.appeal_deadline, ErrorCode::AppealWindowOpen);
+                Ok(())
+            }
+            _ => err!(ErrorCode::SlashNotPending),
+        }
+    }
+
+    /// 窗口结束且未被申诉、未被裁决时才能直接执行
+    pub fn check_finalizable(&self, now: i64) -> Result<()> {
+        require!(self.status == SlashStatus::Pending, ErrorCode::SlashNotPending);
+        require!(now > self.appeal_deadline, ErrorCode::AppealWindowOpen);
+        Ok(())
+    }
+}
+
+#[event]
+pub struct SlashProposed {
+    pub node_id: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub slash_ratio: u32,
+    pub appeal_deadline: i64,
+}
+
+#[event]
+pub struct SlashAppealed {
+    pub node_id: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub counter_evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct SlashResolved {
+    pub node_id: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub status: SlashStatus,
+    pub slashed_amount: u64,
 }
 
 #[program]
@@ -47,6 +224,11 @@ pub mod node_management {
         state.min_stake_amount = min_stake_amount;
         state.verification_fee = verification_fee;
         state.bump = ctx.bumps.state;
+        state.slash_authority = ctx.accounts.admin.key();
+        state.appeal_window_seconds = DEFAULT_APPEAL_WINDOW_SECONDS;
+        state.inactive_threshold_seconds = DEFAULT_INACTIVE_THRESHOLD_SECONDS;
+        state.min_heartbeat_interval_seconds = DEFAULT_MIN_HEARTBEAT_INTERVAL_SECONDS;
+        state.treasury = ctx.accounts.admin.key();
 
         msg!("Node management contract initialized");
         Ok(())
@@ -102,7 +284,7 @@ pub mod node_management {
         Ok(())
     }
 
-    /// 更新节点状态
+    /// 更新节点状态（节点所有者不能封禁或解封节点）
     pub fn update_node_status(
         ctx: Context<UpdateNodeStatus>,
         node_id: Pubkey,
@@ -111,28 +293,19 @@ pub mod node_management {
         let node_account = &mut ctx.accounts.node_account;
         let state = &mut ctx.accounts.state;
 
-        // 只有管理员或节点所有者可以更新状态
-        require!(
-            ctx.accounts.authority.key() == state.admin || 
-            ctx.accounts.authority.key() == node_account.owner,
-            ErrorCode::Unauthorized
-        );
-
-        // 更新活跃节点统计
-        match (node_account.status, new_status) {
-            (NodeStatus::Active, NodeStatus::Offline | NodeStatus::Paused | NodeStatus::Banned) => {
-                state.active_nodes -= 1;
-            }
-            (NodeStatus::Offline | NodeStatus::Paused, NodeStatus::Active) => {
-                state.active_nodes += 1;
-            }
-            _ => {}
-        }
+        // 封禁和解除封禁只能由管理员或治理多签执行
+        state.check_status_change(
+            &ctx.accounts.authority.key(),
+            &node_account.owner,
+            &node_account.status,
+            &new_status,
+        )?;
+        state.track_status_change(&node_account.status, &new_status);
 
         node_account.status = new_status;
         node_account.last_active_at = Clock::get()?.unix_timestamp;
 
-        msg!("Node status updated: {} -> {:?}", node_id, new_status);
+        msg!("Node status updated: {} -> {:?}", node_id, node_account.status);
         Ok(())
     }
 
@@ -156,36 +329,213 @@ pub mod node_management {
         Ok(())
     }
 
-    /// 罚没节点
+    /// 更新节点信誉分（仅管理员），分数由链下信誉引擎综合在线、验证、传输和举报记录得出
+    pub fn update_reputation(
+        ctx: Context<UpdateReputation>,
+        node_id: Pubkey,
+        reputation_score: u32,
+    ) -> Result<()> {
+        let node_account = &mut ctx.accounts.node_account;
+        let state = &ctx.accounts.state;
+
+        require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
+        require!(reputation_score <= MAX_REPUTATION_SCORE, ErrorCode::InvalidReputationScore);
+
+        let previous = node_account.reputation_score;
+        node_account.reputation_score = reputation_score;
+
+        emit!(ReputationUpdated {
+            node_id,
+            previous,
+            reputation_score,
+        });
+        msg!("Node reputation updated: {} {} -> {}", node_id, previous, reputation_score);
+        Ok(())
+    }
+
+    /// 发起罚没：记录证据并开启申诉窗口，此时不转移质押
     pub fn slash_node(
         ctx: Context<SlashNode>,
         node_id: Pubkey,
         slash_ratio: u32, // 罚没比例 (0-10000, 基点)
+        evidence_hash: [u8; 32],
+        reason: String,
     ) -> Result<()> {
-        let node_account = &mut ctx.accounts.node_account;
         let state = &ctx.accounts.state;
 
-        // 只有管理员可以罚没节点
+        // 只有管理员可以发起罚没
         require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
         require!(slash_ratio <= 10000, ErrorCode::InvalidSlashRatio);
+        require!(reason.len() <= MAX_SLASH_REASON_LEN, ErrorCode::ReasonTooLong);
 
-        // 计算罚没金额
-        let slash_amount = (node_account.stake_info.amount * slash_ratio as u64) / 10000;
-        
-        if slash_amount > 0 {
-            // 转移罚没金额到国库
-            **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += slash_amount;
-            **ctx.accounts.node_account.to_account_info().try_borrow_mut_lamports()? -= slash_amount;
-            
-            node_account.stake_info.amount -= slash_amount;
-            node_account.stake_info.is_slashed = true;
+        let current_time = Clock::get()?.unix_timestamp;
+        let slash_record = &mut ctx.accounts.slash_record;
+        slash_record.node_id = node_id;
+        slash_record.node_account = ctx.accounts.node_account.key();
+        slash_record.reporter = ctx.accounts.authority.key();
+        slash_record.evidence_hash = evidence_hash;
+        slash_record.reason = reason;
+        slash_record.slash_ratio = slash_ratio;
+        slash_record.created_at = current_time;
+        slash_record.appeal_deadline = current_time + state.appeal_window_seconds;
+        slash_record.status = SlashStatus::Pending;
+        slash_record.counter_evidence_hash = None;
+        slash_record.appealed_at = None;
+        slash_record.resolved_by = None;
+        slash_record.resolved_at = None;
+        slash_record.slashed_amount = 0;
+        slash_record.bump = ctx.bumps.slash_record;
+
+        emit!(SlashProposed {
+            node_id,
+            evidence_hash,
+            slash_ratio,
+            appeal_deadline: slash_record.appeal_deadline,
+        });
+        msg!("Slash proposed for node {}: appeal until {}", node_id, slash_record.appeal_deadline);
+        Ok(())
+    }
+
+    /// 节点所有者在申诉窗口内提交反证
+    pub fn appeal_slash(ctx: Context<AppealSlash>, counter_evidence_hash: [u8; 32]) -> Result<()> {
+        let slash_record = &mut ctx.accounts.slash_record;
+        require!(
+            ctx.accounts.owner.key() == ctx.accounts.node_account.owner,
+            ErrorCode::Unauthorized
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        slash_record.check_appealable(current_time)?;
+
+        slash_record.status = SlashStatus::Appealed;
+        slash_record.counter_evidence_hash = Some(counter_evidence_hash);
+        slash_record.appealed_at = Some(current_time);
+
+        emit!(SlashAppealed {
+            node_id: slash_record.node_id,
+            evidence_hash: slash_record.evidence_hash,
+            counter_evidence_hash,
+        });
+        msg!("Slash appealed by node {}", slash_record.node_id);
+        Ok(())
+    }
+
+    /// 治理多签裁决：已申诉的记录随时可裁决，未申诉的记录需等窗口结束
+    pub fn resolve_slash(ctx: Context<ResolveSlash>, uphold: bool) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.state.slash_authority,
+            ErrorCode::Unauthorized
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        ctx.accounts.slash_record.check_resolvable(current_time)?;
+
+        let resolver = ctx.accounts.authority.key();
+        if uphold {
+            execute_slash(
+                &mut ctx.accounts.slash_record,
+                &mut ctx.accounts.node_account,
+                &mut ctx.accounts.state,
+                &ctx.accounts.treasury,
+                resolver,
+                current_time,
+            )
+        } else {
+            let slash_record = &mut ctx.accounts.slash_record;
+            slash_record.status = SlashStatus::Overturned;
+            slash_record.resolved_by = Some(resolver);
+            slash_record.resolved_at = Some(current_time);
+
+            emit!(SlashResolved {
+                node_id: slash_record.node_id,
+                evidence_hash: slash_record.evidence_hash,
+                status: SlashStatus::Overturned,
+                slashed_amount: 0,
+            });
+            msg!("Slash overturned for node {}", slash_record.node_id);
+            Ok(())
+        }
+    }
+
+    /// 申诉窗口结束且未被申诉时，任何人都可以执行罚没，质押只会转入状态中登记的国库
+    pub fn finalize_slash(ctx: Context<FinalizeSlash>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        ctx.accounts.slash_record.check_finalizable(current_time)?;
+
+        let resolver = ctx.accounts.payer.key();
+        execute_slash(
+            &mut ctx.accounts.slash_record,
+            &mut ctx.accounts.node_account,
+            &mut ctx.accounts.state,
+            &ctx.accounts.treasury,
+            resolver,
+            current_time,
+        )
+    }
+
+    /// 设置罚没裁决者和申诉窗口（仅管理员）
+    pub fn set_slash_authority(
+        ctx: Context<SetSlashAuthority>,
+        slash_authority: Pubkey,
+        appeal_window_seconds: i64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
+        require!(appeal_window_seconds >= 0, ErrorCode::InvalidAppealWindow);
+
+        state.slash_authority = slash_authority;
+        state.appeal_window_seconds = appeal_window_seconds;
+
+        msg!("Slash authority set to {}, appeal window {}s", slash_authority, appeal_window_seconds);
+        Ok(())
+    }
+
+    /// 把旧布局的状态账户扩容到当前布局（仅管理员），旧布局中没有的字段写入默认值
+    ///
+    /// 合约升级后旧状态账户按新布局无法反序列化，其他读取状态的指令都会失败，需先执行本指令。
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let state_info = ctx.accounts.state.to_account_info();
+        let old_len = state_info.data_len();
+        require!(old_len < NodeManagementState::SPACE, ErrorCode::StateAlreadyMigrated);
+        {
+            let data = state_info.try_borrow_data()?;
+            require!(
+                old_len >= NodeManagementState::SPACE_V0
+                    && &data[..8] == NodeManagementState::DISCRIMINATOR,
+                ErrorCode::InvalidStateLayout
+            );
+            require!(data[8..40] == ctx.accounts.admin.key().to_bytes(), ErrorCode::Unauthorized);
         }
 
-        // 将节点状态设为禁用
-        node_account.status = NodeStatus::Banned;
-        state.active_nodes -= 1;
+        // 补足扩容后的租金
+        let rent = Rent::get()?.minimum_balance(NodeManagementState::SPACE);
+        let lamports = state_info.lamports();
+        if rent > lamports {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: state_info.clone(),
+                    },
+                ),
+                rent - lamports,
+            )?;
+        }
+        let state = NodeManagementState::migrate(&state_info.try_borrow_data()?[..old_len])?;
+        state_info.resize(NodeManagementState::SPACE)?;
+        state.try_serialize(&mut &mut state_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Node management state migrated: {} -> {} bytes", old_len, NodeManagementState::SPACE);
+        Ok(())
+    }
 
-        msg!("Node slashed: {} amount: {} lamports", node_id, slash_amount);
+    /// 设置罚没质押的接收国库（仅管理员）
+    pub fn set_treasury(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
+
+        state.treasury = treasury;
+
+        msg!("Slash treasury set to {}", treasury);
         Ok(())
     }
 
@@ -207,6 +557,135 @@ pub mod node_management {
         msg!("Node last active updated: {}", node_id);
         Ok(())
     }
+
+    /// 提交心跳；被标记为离线的节点在心跳后恢复活跃
+    pub fn submit_heartbeat(ctx: Context<SubmitHeartbeat>) -> Result<()> {
+        let node_account = &mut ctx.accounts.node_account;
+        let state = &mut ctx.accounts.state;
+
+        require!(ctx.accounts.owner.key() == node_account.owner, ErrorCode::Unauthorized);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time - node_account.last_active_at >= state.min_heartbeat_interval_seconds,
+            ErrorCode::HeartbeatTooFrequent
+        );
+
+        if node_account.status == NodeStatus::Offline {
+            node_account.status = NodeStatus::Active;
+            state.active_nodes += 1;
+        }
+        node_account.last_active_at = current_time;
+
+        msg!("Heartbeat from node {}", node_account.node_id);
+        Ok(())
+    }
+
+    /// 把心跳过期的活跃节点标记为离线（任何人都可以调用），离线节点不参与收益分配
+    pub fn mark_inactive(ctx: Context<MarkInactive>) -> Result<()> {
+        let node_account = &mut ctx.accounts.node_account;
+        let state = &mut ctx.accounts.state;
+
+        require!(node_account.status == NodeStatus::Active, ErrorCode::NodeNotActive);
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time - node_account.last_active_at > state.inactive_threshold_seconds,
+            ErrorCode::NodeStillActive
+        );
+
+        node_account.status = NodeStatus::Offline;
+        state.active_nodes -= 1;
+
+        emit!(NodeMarkedInactive {
+            node_id: node_account.node_id,
+            last_active_at: node_account.last_active_at,
+            marked_by: ctx.accounts.caller.key(),
+        });
+        msg!("Node marked inactive: {}", node_account.node_id);
+        Ok(())
+    }
+
+    /// 更新活跃度检测设置（仅管理员）
+    pub fn update_liveness_settings(
+        ctx: Context<UpdateLivenessSettings>,
+        inactive_threshold_seconds: i64,
+        min_heartbeat_interval_seconds: i64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
+        require!(
+            min_heartbeat_interval_seconds >= 0 && inactive_threshold_seconds > min_heartbeat_interval_seconds,
+            ErrorCode::InvalidLivenessSettings
+        );
+
+        state.inactive_threshold_seconds = inactive_threshold_seconds;
+        state.min_heartbeat_interval_seconds = min_heartbeat_interval_seconds;
+
+        msg!(
+            "Liveness settings updated: threshold={}s, interval={}s",
+            inactive_threshold_seconds,
+            min_heartbeat_interval_seconds
+        );
+        Ok(())
+    }
+
+    /// 查询节点所有者的投票权（质押数量），供治理合约通过 CPI 调用
+    ///
+    /// 被罚没或已禁用的节点没有投票权。
+    pub fn get_voting_power(ctx: Context<GetVotingPower>) -> Result<u64> {
+        let node_account = &ctx.accounts.node_account;
+        require!(
+            ctx.accounts.owner.key() == node_account.owner,
+            ErrorCode::Unauthorized
+        );
+
+        if node_account.stake_info.is_slashed || node_account.status == NodeStatus::Banned {
+            return Ok(0);
+        }
+        Ok(node_account.stake_info.amount)
+    }
+}
+
+/// 执行罚没：把质押转入国库并禁用节点
+fn execute_slash<'info>(
+    slash_record: &mut Account<'info, SlashRecord>,
+    node_account: &mut Account<'info, NodeAccount>,
+    state: &mut Account<'info, NodeManagementState>,
+    treasury: &AccountInfo<'info>,
+    resolver: Pubkey,
+    current_time: i64,
+) -> Result<()> {
+    // 计算罚没金额
+    let slash_amount = (node_account.stake_info.amount as u128 * slash_record.slash_ratio as u128 / 10000) as u64;
+
+    if slash_amount > 0 {
+        // 转移罚没金额到国库
+        **treasury.try_borrow_mut_lamports()? += slash_amount;
+        **node_account.to_account_info().try_borrow_mut_lamports()? -= slash_amount;
+
+        node_account.stake_info.amount -= slash_amount;
+        node_account.stake_info.is_slashed = true;
+    }
+
+    // 将节点状态设为禁用
+    if node_account.status == NodeStatus::Active {
+        state.active_nodes -= 1;
+    }
+    node_account.status = NodeStatus::Banned;
+
+    slash_record.status = SlashStatus::Executed;
+    slash_record.resolved_by = Some(resolver);
+    slash_record.resolved_at = Some(current_time);
+    slash_record.slashed_amount = slash_amount;
+
+    emit!(SlashResolved {
+        node_id: slash_record.node_id,
+        evidence_hash: slash_record.evidence_hash,
+        status: SlashStatus::Executed,
+        slashed_amount: slash_amount,
+    });
+    msg!("Node slashed: {} amount: {} lamports", slash_record.node_id, slash_amount);
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -214,7 +693,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32 + 4 + 4 + 8 + 8 + 1, // 空间计算
+        space = NodeManagementState::SPACE,
         seeds = [b"node-management-state"],
         bump
     )]
@@ -269,16 +748,112 @@ pub struct VerifyNode<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(node_id: Pubkey)]
+pub struct UpdateReputation<'info> {
+    #[account(mut, seeds = [b"node", node_id.as_ref()], bump = node_account.bump)]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(seeds = [b"node-management-state"], bump = state.bump)]
+    pub state: Account<'info, NodeManagementState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(node_id: Pubkey, slash_ratio: u32, evidence_hash: [u8; 32])]
 pub struct SlashNode<'info> {
+    #[account(seeds = [b"node", node_id.as_ref()], bump = node_account.bump)]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = SlashRecord::SPACE,
+        seeds = [b"slash-record", node_id.as_ref(), evidence_hash.as_ref()],
+        bump
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+
+    pub state: Account<'info, NodeManagementState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AppealSlash<'info> {
+    #[account(mut, has_one = node_account)]
+    pub slash_record: Account<'info, SlashRecord>,
+
+    pub node_account: Account<'info, NodeAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveSlash<'info> {
+    #[account(mut, has_one = node_account)]
+    pub slash_record: Account<'info, SlashRecord>,
+
     #[account(mut)]
     pub node_account: Account<'info, NodeAccount>,
 
+    #[account(mut, seeds = [b"node-management-state"], bump = state.bump)]
     pub state: Account<'info, NodeManagementState>,
 
-    /// CHECK: 国库地址
+    /// CHECK: 国库地址，必须与状态中登记的一致
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    // 治理多签签名 PDA，通过 execute_multisig_transaction CPI 签名
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSlash<'info> {
+    #[account(mut, has_one = node_account)]
+    pub slash_record: Account<'info, SlashRecord>,
+
     #[account(mut)]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(mut, seeds = [b"node-management-state"], bump = state.bump)]
+    pub state: Account<'info, NodeManagementState>,
+
+    /// CHECK: 国库地址，必须与状态中登记的一致
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
     pub treasury: AccountInfo<'info>,
 
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSlashAuthority<'info> {
+    #[account(mut, seeds = [b"node-management-state"], bump = state.bump)]
+    pub state: Account<'info, NodeManagementState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: 旧布局无法按 NodeManagementState 反序列化，判别符和管理员在指令中校验
+    #[account(mut, seeds = [b"node-management-state"], bump)]
+    pub state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    #[account(mut, seeds = [b"node-management-state"], bump = state.bump)]
+    pub state: Account<'info, NodeManagementState>,
+
     pub authority: Signer<'info>,
 }
 
@@ -290,6 +865,44 @@ pub struct UpdateLastActive<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SubmitHeartbeat<'info> {
+    #[account(mut)]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(mut, seeds = [b"node-management-state"], bump = state.bump)]
+    pub state: Account<'info, NodeManagementState>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarkInactive<'info> {
+    #[account(mut)]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(mut, seeds = [b"node-management-state"], bump = state.bump)]
+    pub state: Account<'info, NodeManagementState>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLivenessSettings<'info> {
+    #[account(mut, seeds = [b"node-management-state"], bump = state.bump)]
+    pub state: Account<'info, NodeManagementState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetVotingPower<'info> {
+    pub node_account: Account<'info, NodeAccount>,
+
+    /// CHECK: 只与节点所有者比较，不读取数据
+    pub owner: UncheckedAccount<'info>,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Node name is too long")]
@@ -304,4 +917,191 @@ pub enum ErrorCode {
     InvalidVerificationLevel,
     #[msg("Invalid slash ratio")]
     InvalidSlashRatio,
+    #[msg("Slash reason is too long")]
+    ReasonTooLong,
+    #[msg("Slash record is not pending")]
+    SlashNotPending,
+    #[msg("Appeal window has closed")]
+    AppealWindowClosed,
+    #[msg("Appeal window is still open")]
+    AppealWindowOpen,
+    #[msg("Invalid appeal window")]
+    InvalidAppealWindow,
+    #[msg("Heartbeat submitted too frequently")]
+    HeartbeatTooFrequent,
+    #[msg("Node is not active")]
+    NodeNotActive,
+    #[msg("Node heartbeat is not stale yet")]
+    NodeStillActive,
+    #[msg("Invalid liveness settings")]
+    InvalidLivenessSettings,
+    #[msg("Invalid reputation score")]
+    InvalidReputationScore,
+    #[msg("Treasury does not match the configured slash treasury")]
+    InvalidTreasury,
+    #[msg("State account already uses the current layout")]
+    StateAlreadyMigrated,
+    #[msg("State account has an unknown layout")]
+    InvalidStateLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 初版布局的状态账户数据
+    fn state_v0(admin: &Pubkey) -> Vec<u8> {
+        let mut data = NodeManagementState::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(admin.as_ref());
+        data.extend_from_slice(&12u32.to_le_bytes()); // total_nodes
+        data.extend_from_slice(&7u32.to_le_bytes()); // active_nodes
+        data.extend_from_slice(&1_000u64.to_le_bytes()); // min_stake_amount
+        data.extend_from_slice(&50u64.to_le_bytes()); // verification_fee
+        data.push(254); // bump
+        assert_eq!(data.len(), NodeManagementState::SPACE_V0);
+        data
+    }
+
+    fn assert_v0_fields_kept(state: &NodeManagementState, admin: &Pubkey) {
+        assert_eq!(state.admin, *admin);
+        assert_eq!((state.total_nodes, state.active_nodes), (12, 7));
+        assert_eq!((state.min_stake_amount, state.verification_fee, state.bump), (1_000, 50, 254));
+    }
+
+    #[test]
+    fn test_migrate_v0_state() {
+        let admin = Pubkey::new_unique();
+        let state = NodeManagementState::migrate(&state_v0(&admin)).unwrap();
+        assert_v0_fields_kept(&state, &admin);
+        assert_eq!(state.slash_authority, admin);
+        assert_eq!(state.appeal_window_seconds, DEFAULT_APPEAL_WINDOW_SECONDS);
+        assert_eq!(state.inactive_threshold_seconds, DEFAULT_INACTIVE_THRESHOLD_SECONDS);
+        assert_eq!(state.min_heartbeat_interval_seconds, DEFAULT_MIN_HEARTBEAT_INTERVAL_SECONDS);
+        assert_eq!(state.treasury, admin);
+    }
+
+    #[test]
+    fn test_migrate_v1_state() {
+        let admin = Pubkey::new_unique();
+        let slash_authority = Pubkey::new_unique();
+        let mut data = state_v0(&admin);
+        data.extend_from_slice(slash_authority.as_ref());
+        data.extend_from_slice(&3_600i64.to_le_bytes());
+        assert_eq!(data.len(), NodeManagementState::SPACE_V1);
+
+        let state = NodeManagementState::migrate(&data).unwrap();
+        assert_v0_fields_kept(&state, &admin);
+        // V1 已有的罚没设置保持不变，之后加入的字段取默认值
+        assert_eq!((state.slash_authority, state.appeal_window_seconds), (slash_authority, 3_600));
+        assert_eq!(state.inactive_threshold_seconds, DEFAULT_INACTIVE_THRESHOLD_SECONDS);
+        assert_eq!(state.min_heartbeat_interval_seconds, DEFAULT_MIN_HEARTBEAT_INTERVAL_SECONDS);
+        assert_eq!(state.treasury, admin);
+
+        // 新布局可以原样写回并读出
+        let mut migrated = Vec::new();
+        state.try_serialize(&mut migrated).unwrap();
+        assert_eq!(migrated.len(), NodeManagementState::SPACE);
+        let reread = NodeManagementState::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!(reread.slash_authority, slash_authority);
+    }
+
+    #[test]
+    fn test_migrate_rejects_invalid_layout() {
+        let admin = Pubkey::new_unique();
+        let mut data = state_v0(&admin);
+        data[0] ^= 1;
+        assert!(NodeManagementState::migrate(&data).is_err());
+        // 过短或已是当前布局的数据不迁移
+        assert!(NodeManagementState::migrate(&state_v0(&admin)[..NodeManagementState::SPACE_V0 - 1]).is_err());
+        let mut current = state_v0(&admin);
+        current.resize(NodeManagementState::SPACE, 0);
+        assert!(NodeManagementState::migrate(&current).is_err());
+    }
+
+    fn slash_record(status: SlashStatus) -> SlashRecord {
+        SlashRecord {
+            node_id: Pubkey::new_unique(),
+            node_account: Pubkey::new_unique(),
+            reporter: Pubkey::new_unique(),
+            evidence_hash: [1; 32],
+            reason: "double signing".to_string(),
+            slash_ratio: 5_000,
+            created_at: 0,
+            appeal_deadline: 100,
+            status,
+            counter_evidence_hash: None,
+            appealed_at: None,
+            resolved_by: None,
+            resolved_at: None,
+            slashed_amount: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_appeal_within_window() {
+        let mut record = slash_record(SlashStatus::Pending);
+        assert!(record.check_appealable(100).is_ok());
+        // 窗口结束后不能再申诉
+        assert!(record.check_appealable(101).is_err());
+
+        // 已申诉或已裁决的记录不能重复申诉
+        record.status = SlashStatus::Appealed;
+        assert!(record.check_appealable(50).is_err());
+        record.status = SlashStatus::Overturned;
+        assert!(record.check_appealable(50).is_err());
+    }
+
+    #[test]
+    fn test_finalize_after_window() {
+        let mut record = slash_record(SlashStatus::Pending);
+        assert!(record.check_finalizable(100).is_err());
+        assert!(record.check_finalizable(101).is_ok());
+
+        // 已申诉的记录只能由治理裁决
+        record.status = SlashStatus::Appealed;
+        assert!(record.check_finalizable(101).is_err());
+        assert!(record.check_resolvable(50).is_ok());
+    }
+
+    #[test]
+    fn test_resolved_slash_cannot_be_finalized() {
+        for status in [SlashStatus::Overturned, SlashStatus::Executed] {
+            let record = slash_record(status);
+            assert!(record.check_finalizable(101).is_err());
+            assert!(record.check_resolvable(101).is_err());
+            assert!(record.check_appealable(50).is_err());
+        }
+
+        // 未申诉的记录在窗口内不能裁决
+        let record = slash_record(SlashStatus::Pending);
+        assert!(record.check_resolvable(100).is_err());
+        assert!(record.check_resolvable(101).is_ok());
+    }
+
+    #[test]
+    fn test_owner_cannot_unban_node() {
+        let admin = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut state = NodeManagementState::migrate(&state_v0(&admin)).unwrap();
+
+        // 所有者不能解除封禁，也不能封禁自己的节点
+        assert!(state.check_status_change(&owner, &owner, &NodeStatus::Banned, &NodeStatus::Active).is_err());
+        assert!(state.check_status_change(&owner, &owner, &NodeStatus::Active, &NodeStatus::Banned).is_err());
+        assert!(state.check_status_change(&owner, &owner, &NodeStatus::Active, &NodeStatus::Paused).is_ok());
+        // 其他人不能变更状态，管理员或治理多签可以解除封禁
+        let stranger = Pubkey::new_unique();
+        assert!(state.check_status_change(&stranger, &owner, &NodeStatus::Paused, &NodeStatus::Active).is_err());
+        assert!(state.check_status_change(&admin, &owner, &NodeStatus::Banned, &NodeStatus::Active).is_ok());
+        state.slash_authority = Pubkey::new_unique();
+        let governance = state.slash_authority;
+        assert!(state.check_status_change(&governance, &owner, &NodeStatus::Banned, &NodeStatus::Active).is_ok());
+
+        // 解除封禁回到 Active 时计入活跃节点
+        state.track_status_change(&NodeStatus::Banned, &NodeStatus::Active);
+        assert_eq!(state.active_nodes, 8);
+        state.track_status_change(&NodeStatus::Active, &NodeStatus::Banned);
+        state.track_status_change(&NodeStatus::Banned, &NodeStatus::Offline);
+        assert_eq!(state.active_nodes, 7);
+    }
 }
@@ -0,0 +1,389 @@
+use anchor_lang::prelude::*;
+
+declare_id!("ORACLE_PROGRAM_ID");
+
+/// 上报者白名单上限
+pub const MAX_REPORTERS: usize = 16;
+/// 算力评分的定点缩放倍数（链上以整数保存，值 = 评分 × VALUE_SCALE）
+pub const VALUE_SCALE: u64 = 1_000_000;
+/// 在线率的满值（万分比）
+pub const MAX_UPTIME_BPS: u64 = 10_000;
+/// 纪元时长（秒），需与 contribution-tracking 的 `EPOCH_DURATION_SECONDS` 一致
+pub const EPOCH_DURATION_SECONDS: i64 = 86_400;
+
+/// 时间戳所在的纪元
+pub fn epoch_of(timestamp: i64) -> u64 {
+    (timestamp.max(0) / EPOCH_DURATION_SECONDS) as u64
+}
+
+/// 指标类型
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MetricKind {
+    EpochComputeScore, // 纪元总算力评分，subject 为默认公钥
+    NodeComputeScore,  // 节点纪元算力评分
+    NodeUptime,        // 节点纪元在线率（万分比）
+}
+
+impl MetricKind {
+    /// 该类型是否针对单个节点
+    pub fn per_node(self) -> bool {
+        !matches!(self, MetricKind::EpochComputeScore)
+    }
+}
+
+/// 预言机全局状态
+#[account]
+pub struct OracleState {
+    pub admin: Pubkey,                    // 管理员公钥（可设为治理多签签名 PDA）
+    pub reporters: Vec<Pubkey>,           // 上报者白名单
+    pub threshold: u8,                    // 定值所需的一致上报数（M-of-N 中的 M）
+    pub tolerance_bps: u16,               // 视为一致的最大相对偏差（万分比）
+    pub total_finalized: u64,             // 累计定值的指标数
+    pub bump: u8,                         // PDA bump
+}
+
+impl OracleState {
+    pub const SPACE: usize = 8 + 32 + (4 + 32 * MAX_REPORTERS) + 1 + 2 + 8 + 1;
+
+    pub fn is_reporter(&self, key: &Pubkey) -> bool {
+        self.reporters.contains(key)
+    }
+}
+
+/// 单个上报者提交的值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MetricSubmission {
+    pub reporter: Pubkey,                 // 上报者
+    pub value: u64,                       // 上报值
+}
+
+/// 某纪元某指标的汇总，达到阈值后定值且不再变化
+#[account]
+pub struct MetricAggregate {
+    pub kind: MetricKind,                 // 指标类型
+    pub epoch: u64,                       // 纪元编号
+    pub subject: Pubkey,                  // 指标所属节点（纪元总量为默认公钥）
+    pub submissions: Vec<MetricSubmission>, // 已收到的上报
+    pub finalized: bool,                  // 是否已定值
+    pub value: u64,                       // 定值（一致上报的中位数）
+    pub finalized_at: i64,                // 定值时间
+    pub bump: u8,                         // PDA bump
+}
+
+impl MetricAggregate {
+    pub const SPACE: usize = 8 + 1 + 8 + 32 + (4 + (32 + 8) * MAX_REPORTERS) + 1 + 8 + 8 + 1;
+
+    pub fn has_report_from(&self, reporter: &Pubkey) -> bool {
+        self.submissions.iter().any(|s| &s.reporter == reporter)
+    }
+}
+
+/// 一致值：仍在白名单中的上报者里，若有不少于 `threshold` 个值与某个上报值的相对偏差
+/// 都在容差内，取这组值的中位数；否则返回 None
+pub fn agreed_value(
+    submissions: &[MetricSubmission],
+    reporters: &[Pubkey],
+    threshold: u8,
+    tolerance_bps: u16,
+) -> Option<u64> {
+    let mut values: Vec<u64> = submissions
+        .iter()
+        .filter(|s| reporters.contains(&s.reporter))
+        .map(|s| s.value)
+        .collect();
+    values.sort_unstable();
+
+    for &anchor in &values {
+        let tolerance = (anchor as u128) * (tolerance_bps as u128) / 10_000;
+        let group: Vec<u64> = values
+            .iter()
+            .copied()
+            .filter(|&v| (v as i128 - anchor as i128).unsigned_abs() <= tolerance)
+            .collect();
+        if group.len() >= threshold.max(1) as usize {
+            return Some(group[group.len() / 2]);
+        }
+    }
+    None
+}
+
+#[event]
+pub struct MetricReported {
+    pub kind: MetricKind,
+    pub epoch: u64,
+    pub subject: Pubkey,
+    pub reporter: Pubkey,
+    pub value: u64,
+}
+
+#[event]
+pub struct MetricFinalized {
+    pub kind: MetricKind,
+    pub epoch: u64,
+    pub subject: Pubkey,
+    pub value: u64,
+    pub reports: u8,
+}
+
+#[program]
+pub mod oracle {
+    use super::*;
+
+    /// 初始化预言机
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        reporters: Vec<Pubkey>,
+        threshold: u8,
+        tolerance_bps: u16,
+    ) -> Result<()> {
+        require!(reporters.len() <= MAX_REPORTERS, OracleError::TooManyReporters);
+        require!(
+            threshold > 0 && threshold as usize <= reporters.len(),
+            OracleError::InvalidThreshold
+        );
+        require!(tolerance_bps as u64 <= MAX_UPTIME_BPS, OracleError::InvalidTolerance);
+        for (i, reporter) in reporters.iter().enumerate() {
+            require!(!reporters[..i].contains(reporter), OracleError::DuplicateReporter);
+        }
+
+        let state = &mut ctx.accounts.state;
+        state.admin = ctx.accounts.admin.key();
+        state.reporters = reporters;
+        state.threshold = threshold;
+        state.tolerance_bps = tolerance_bps;
+        state.total_finalized = 0;
+        state.bump = ctx.bumps.state;
+
+        msg!("Oracle initialized: {}-of-{}", threshold, state.reporters.len());
+        Ok(())
+    }
+
+    /// 添加上报者
+    pub fn add_reporter(ctx: Context<UpdateOracle>, reporter: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.admin.key() == state.admin, OracleError::Unauthorized);
+        require!(!state.is_reporter(&reporter), OracleError::DuplicateReporter);
+        require!(state.reporters.len() < MAX_REPORTERS, OracleError::TooManyReporters);
+
+        state.reporters.push(reporter);
+        msg!("Reporter added: {}", reporter);
+        Ok(())
+    }
+
+    /// 移除上报者，其尚未定值的上报不再计入一致性
+    pub fn remove_reporter(ctx: Context<UpdateOracle>, reporter: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.admin.key() == state.admin, OracleError::Unauthorized);
+        require!(state.is_reporter(&reporter), OracleError::UnknownReporter);
+        // 移除后剩余人数仍需满足阈值
+        require!(state.reporters.len() > state.threshold as usize, OracleError::InvalidThreshold);
+
+        state.reporters.retain(|r| r != &reporter);
+        msg!("Reporter removed: {}", reporter);
+        Ok(())
+    }
+
+    /// 更新阈值和容差
+    pub fn set_threshold(ctx: Context<UpdateOracle>, threshold: u8, tolerance_bps: u16) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.admin.key() == state.admin, OracleError::Unauthorized);
+        require!(
+            threshold > 0 && threshold as usize <= state.reporters.len(),
+            OracleError::InvalidThreshold
+        );
+        require!(tolerance_bps as u64 <= MAX_UPTIME_BPS, OracleError::InvalidTolerance);
+
+        state.threshold = threshold;
+        state.tolerance_bps = tolerance_bps;
+        msg!("Oracle threshold updated: {}, tolerance {} bps", threshold, tolerance_bps);
+        Ok(())
+    }
+
+    /// 上报已结束纪元的指标；达到阈值的一致上报后定值
+    pub fn submit_report(
+        ctx: Context<SubmitReport>,
+        kind: MetricKind,
+        epoch: u64,
+        subject: Pubkey,
+        value: u64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(epoch < epoch_of(current_time), OracleError::EpochNotEnded);
+        require!(kind.per_node() == (subject != Pubkey::default()), OracleError::InvalidSubject);
+        if kind == MetricKind::NodeUptime {
+            require!(value <= MAX_UPTIME_BPS, OracleError::InvalidValue);
+        }
+
+        let state = &mut ctx.accounts.state;
+        let reporter = ctx.accounts.reporter.key();
+        require!(state.is_reporter(&reporter), OracleError::UnknownReporter);
+
+        let aggregate = &mut ctx.accounts.aggregate;
+        require!(!aggregate.finalized, OracleError::AlreadyFinalized);
+        require!(!aggregate.has_report_from(&reporter), OracleError::AlreadyReported);
+        // 被移除的上报者留下的记录可能占满空间，仅保留白名单内的上报
+        aggregate.submissions.retain(|s| state.reporters.contains(&s.reporter));
+
+        aggregate.kind = kind;
+        aggregate.epoch = epoch;
+        aggregate.subject = subject;
+        aggregate.bump = ctx.bumps.aggregate;
+        aggregate.submissions.push(MetricSubmission { reporter, value });
+
+        emit!(MetricReported {
+            kind,
+            epoch,
+            subject,
+            reporter,
+            value,
+        });
+
+        if let Some(agreed) = agreed_value(
+            &aggregate.submissions,
+            &state.reporters,
+            state.threshold,
+            state.tolerance_bps,
+        ) {
+            aggregate.finalized = true;
+            aggregate.value = agreed;
+            aggregate.finalized_at = current_time;
+            state.total_finalized = state.total_finalized.checked_add(1).ok_or(OracleError::Overflow)?;
+
+            emit!(MetricFinalized {
+                kind,
+                epoch,
+                subject,
+                value: agreed,
+                reports: aggregate.submissions.len() as u8,
+            });
+            msg!("Metric finalized: {:?} epoch {} -> {}", kind, epoch, agreed);
+        }
+        Ok(())
+    }
+
+    /// 转移管理权（例如交给治理多签）
+    pub fn set_admin(ctx: Context<UpdateOracle>, new_admin: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.admin.key() == state.admin, OracleError::Unauthorized);
+
+        state.admin = new_admin;
+        msg!("Oracle admin set to {}", new_admin);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = OracleState::SPACE,
+        seeds = [b"oracle-state"],
+        bump
+    )]
+    pub state: Account<'info, OracleState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracle<'info> {
+    #[account(mut, seeds = [b"oracle-state"], bump = state.bump)]
+    pub state: Account<'info, OracleState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: MetricKind, epoch: u64, subject: Pubkey)]
+pub struct SubmitReport<'info> {
+    #[account(mut, seeds = [b"oracle-state"], bump = state.bump)]
+    pub state: Account<'info, OracleState>,
+
+    #[account(
+        init_if_needed,
+        payer = reporter,
+        space = MetricAggregate::SPACE,
+        seeds = [b"oracle-metric".as_ref(), &[kind as u8], &epoch.to_le_bytes(), subject.as_ref()],
+        bump
+    )]
+    pub aggregate: Account<'info, MetricAggregate>,
+
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[error_code]
+pub enum OracleError {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Too many reporters")]
+    TooManyReporters,
+    #[msg("Reporter is already whitelisted")]
+    DuplicateReporter,
+    #[msg("Signer is not a whitelisted reporter")]
+    UnknownReporter,
+    #[msg("Threshold must be between 1 and the number of reporters")]
+    InvalidThreshold,
+    #[msg("Tolerance exceeds 10000 bps")]
+    InvalidTolerance,
+    #[msg("Epoch has not ended yet")]
+    EpochNotEnded,
+    #[msg("Subject does not match the metric kind")]
+    InvalidSubject,
+    #[msg("Metric value out of range")]
+    InvalidValue,
+    #[msg("Metric is already finalized")]
+    AlreadyFinalized,
+    #[msg("Reporter already submitted this metric")]
+    AlreadyReported,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submissions(reporters: &[Pubkey], values: &[u64]) -> Vec<MetricSubmission> {
+        reporters
+            .iter()
+            .zip(values)
+            .map(|(reporter, value)| MetricSubmission { reporter: *reporter, value: *value })
+            .collect()
+    }
+
+    #[test]
+    fn test_agreed_value_m_of_n() {
+        let reporters: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+
+        // 3-of-4：三个值在 1% 容差内一致，离群值被排除，取一致组的中位数
+        let reports = submissions(&reporters, &[1_000, 1_005, 998, 5_000]);
+        assert_eq!(agreed_value(&reports, &reporters, 3, 100), Some(1_000));
+
+        // 不足门限时不定值
+        assert_eq!(agreed_value(&reports[..2], &reporters, 3, 100), None);
+        let scattered = submissions(&reporters, &[1_000, 1_100, 1_200, 1_300]);
+        assert_eq!(agreed_value(&scattered, &reporters, 3, 100), None);
+
+        // 零容差要求完全一致
+        let exact = submissions(&reporters, &[7, 7, 8]);
+        assert_eq!(agreed_value(&exact, &reporters, 2, 0), Some(7));
+        assert_eq!(agreed_value(&exact, &reporters, 3, 0), None);
+    }
+
+    #[test]
+    fn test_agreed_value_ignores_removed_reporters() {
+        let reporters: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let reports = submissions(&reporters, &[500, 500, 500]);
+
+        // 被移出白名单的上报者不再计入门限
+        assert_eq!(agreed_value(&reports, &reporters[..2], 3, 0), None);
+        assert_eq!(agreed_value(&reports, &reporters[..2], 2, 0), Some(500));
+    }
+}
@@ -1,30 +1,126 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
+use contribution_tracking::program::ContributionTracking;
+use contribution_tracking::{EpochComputeAccount, NodeEpochCompute};
+use node_management::NodeAccount;
 use shared_types::*;
 
 declare_id!("REWARD_MANAGEMENT_PROGRAM_ID");
 
+/// 奖励币种
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RewardCurrency {
+    Lamports,                             // 原生 SOL，直接修改账户余额
+    SplToken,                             // SPL 代币，通过 token 程序 CPI 转账
+}
+
 /// 收益分配账户
 #[account]
 pub struct RewardAccount {
     pub id: String,                       // 分配记录ID
     pub node_id: Pubkey,                  // 节点ID
     pub contribution_id: String,          // 贡献记录ID
-    pub amount_lamports: u64,             // 收益金额
+    pub amount_lamports: u64,             // 收益金额（单位随 reward_currency）
     pub distributed_at: i64,              // 分配时间戳
     pub status: RewardStatus,             // 状态
     pub bump: u8,                         // PDA bump
 }
 
+impl RewardAccount {
+    /// 记录ID形如 `batch_reward_<节点ID>_<时间戳>`
+    pub const MAX_ID_LEN: usize = 80;
+    pub const MAX_CONTRIBUTION_ID_LEN: usize = 36;
+    pub const SPACE: usize = 8 + (4 + Self::MAX_ID_LEN) + 32 + (4 + Self::MAX_CONTRIBUTION_ID_LEN) + 8 + 8 + 1 + 1;
+}
+
+/// 单次批量分配的最大记录数
+pub const MAX_BATCH_DISTRIBUTIONS: usize = 10;
+
+/// 算力评分换算为整数单位的倍数，按比例分配时只用整数运算
+pub const COMPUTE_UNITS_PER_SCORE: f64 = 1_000_000.0;
+
+/// 算力评分对应的整数单位（负数和 NaN 记为 0，超出 u64 时截断，保证与预算相乘不溢出 u128）
+fn compute_units(score: f64) -> u128 {
+    (score * COMPUTE_UNITS_PER_SCORE) as u64 as u128
+}
+
+/// 节点按纪元算力占比分得的预算：`budget * node_compute / epoch_compute` 向下取整，
+/// 并以尚未分出的预算为上限，因此各节点分得的总额不会超过纪元预算
+pub fn epoch_share(budget: u64, distributed: u64, node_compute: f64, epoch_compute: f64) -> Result<u64> {
+    let epoch_units = compute_units(epoch_compute);
+    require!(epoch_units > 0, ErrorCode::NoEpochCompute);
+    let share = budget as u128 * compute_units(node_compute) / epoch_units;
+    Ok(share.min(budget.saturating_sub(distributed) as u128) as u64)
+}
+
 /// 节点收益汇总账户
 #[account]
 pub struct NodeRewardSummary {
     pub node_id: Pubkey,                  // 节点ID
     pub total_earned: u64,               // 总收益
     pub total_distributed: u64,          // 已分配收益
-    pub pending_rewards: u64,             // 待分配收益
+    pub pending_rewards: u64,             // 待领取收益（含未归属部分）
     pub last_distribution_at: i64,       // 最后分配时间
     pub distribution_count: u32,         // 分配次数
     pub bump: u8,                         // PDA bump
+    pub claimable: u64,                   // 已归属、可领取的收益
+    pub vesting_amount: u64,              // 仍在线性归属中的收益
+    pub vesting_start: i64,               // 当前归属区间起点
+    pub vesting_end: i64,                 // 当前归属区间终点
+}
+
+impl NodeRewardSummary {
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8 + 8 + 4 + 1 + 8 + 8 + 8 + 8;
+
+    /// 把截至 `now` 已归属的部分转入可领取余额
+    pub fn settle_vesting(&mut self, now: i64) {
+        if self.vesting_amount == 0 || now <= self.vesting_start {
+            return;
+        }
+        let vested = if now >= self.vesting_end {
+            self.vesting_amount
+        } else {
+            let elapsed = (now - self.vesting_start) as u128;
+            let duration = (self.vesting_end - self.vesting_start) as u128;
+            (self.vesting_amount as u128 * elapsed / duration) as u64
+        };
+        self.vesting_amount -= vested;
+        self.claimable += vested;
+        self.vesting_start = now;
+    }
+
+    /// 累计一笔收益；归属期为 0 时立即可领取，
+    /// 否则与未归属余额合并，在 `max(原终点, now + 归属期)` 前线性归属
+    pub fn accrue(&mut self, amount: u64, now: i64, vesting_duration_seconds: u64) -> Result<()> {
+        self.settle_vesting(now);
+        self.total_earned = self.total_earned.checked_add(amount).ok_or(ErrorCode::RewardOverflow)?;
+        self.pending_rewards = self.pending_rewards.checked_add(amount).ok_or(ErrorCode::RewardOverflow)?;
+        if vesting_duration_seconds == 0 {
+            self.claimable = self.claimable.checked_add(amount).ok_or(ErrorCode::RewardOverflow)?;
+        } else {
+            self.vesting_amount = self.vesting_amount.checked_add(amount).ok_or(ErrorCode::RewardOverflow)?;
+            self.vesting_start = now;
+            let duration = i64::try_from(vesting_duration_seconds).unwrap_or(i64::MAX);
+            self.vesting_end = self.vesting_end.max(now.saturating_add(duration));
+        }
+        self.last_distribution_at = now;
+        self.distribution_count = self.distribution_count.saturating_add(1);
+        Ok(())
+    }
+
+    /// 结清截至 `now` 已归属的收益，返回本次可领取的金额（转账由调用方完成）
+    pub fn claim(&mut self, now: i64) -> Result<u64> {
+        self.settle_vesting(now);
+        let amount = self.claimable;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+
+        self.claimable = 0;
+        self.pending_rewards -= amount;
+        self.total_distributed += amount;
+        Ok(amount)
+    }
 }
 
 /// 收益管理全局状态
@@ -38,6 +134,92 @@ pub struct RewardManagementState {
     pub distribution_frequency: u64,       // 分配频率（秒）
     pub auto_distribution_enabled: bool,  // 是否启用自动分配
     pub bump: u8,                         // PDA bump
+    pub reward_currency: RewardCurrency,  // 奖励币种
+    pub reward_mint: Pubkey,              // 奖励代币 mint（Lamports 模式下为默认值）
+    pub vesting_duration_seconds: u64,    // 新收益的线性归属期（0 表示立即归属）
+    pub epoch_budget: u64,                // 每个纪元的奖励预算（0 表示按贡献逐笔分配）
+}
+
+impl RewardManagementState {
+    /// SPL 代币奖励必须指定 mint
+    pub fn check_reward_currency(reward_currency: RewardCurrency, reward_mint: &Pubkey) -> Result<()> {
+        require!(
+            reward_currency == RewardCurrency::Lamports || *reward_mint != Pubkey::default(),
+            ErrorCode::MissingRewardMint
+        );
+        Ok(())
+    }
+
+    /// 领取 SPL 代币时的账户校验：mint 须为配置的奖励代币，金库归状态 PDA 所有，收款账户归领取者所有。
+    /// `vault` 和 `destination` 为代币账户的 `(mint, owner)`
+    pub fn check_token_claim_accounts(
+        &self,
+        state_key: &Pubkey,
+        mint: &Pubkey,
+        vault: (&Pubkey, &Pubkey),
+        destination: (&Pubkey, &Pubkey),
+        owner: &Pubkey,
+    ) -> Result<()> {
+        require!(self.reward_currency == RewardCurrency::SplToken, ErrorCode::WrongRewardCurrency);
+        require!(*mint == self.reward_mint, ErrorCode::MissingTokenAccounts);
+        require!(vault == (&self.reward_mint, state_key), ErrorCode::MissingTokenAccounts);
+        require!(destination == (&self.reward_mint, owner), ErrorCode::MissingTokenAccounts);
+        Ok(())
+    }
+}
+
+/// 已结算的纪元
+#[account]
+pub struct EpochState {
+    pub epoch: u64,                       // 纪元编号
+    pub total_compute_score: f64,         // 纪元总算力评分（来自贡献跟踪合约）
+    pub budget: u64,                      // 从奖励池预留的纪元预算
+    pub distributed: u64,                 // 已按比例分给节点的金额
+    pub nodes_paid: u32,                  // 已分配的节点数
+    pub finalized_at: i64,                // 结算时间
+    pub bump: u8,                         // PDA bump
+}
+
+impl EpochState {
+    pub const SPACE: usize = 8 + 8 + 8 + 8 + 8 + 4 + 8 + 1;
+}
+
+/// 节点纪元奖励回执，防止同一纪元重复分配
+#[account]
+pub struct EpochRewardReceipt {
+    pub epoch: u64,                       // 纪元编号
+    pub node_id: Pubkey,                  // 节点ID
+    pub compute_score: f64,               // 节点纪元算力评分
+    pub amount: u64,                      // 分得的金额
+    pub bump: u8,                         // PDA bump
+}
+
+impl EpochRewardReceipt {
+    pub const SPACE: usize = 8 + 8 + 32 + 8 + 8 + 1;
+}
+
+#[event]
+pub struct EpochFinalized {
+    pub epoch: u64,
+    pub total_compute_score: f64,
+    pub budget: u64,
+}
+
+#[event]
+pub struct RewardAccrued {
+    pub node_id: Pubkey,
+    pub contribution_id: String,
+    pub amount: u64,
+    pub pending_rewards: u64,
+    pub vesting_end: i64,
+}
+
+#[event]
+pub struct RewardClaimed {
+    pub node_id: Pubkey,
+    pub amount: u64,
+    pub pending_rewards: u64,
+    pub currency: RewardCurrency,
 }
 
 #[program]
@@ -51,7 +233,11 @@ pub mod reward_management {
         min_distribution_amount: u64,
         distribution_frequency: u64,
         auto_distribution_enabled: bool,
+        reward_currency: RewardCurrency,
+        reward_mint: Pubkey,
     ) -> Result<()> {
+        RewardManagementState::check_reward_currency(reward_currency, &reward_mint)?;
+
         let state = &mut ctx.accounts.state;
         state.admin = ctx.accounts.admin.key();
         state.treasury = treasury;
@@ -61,12 +247,16 @@ pub mod reward_management {
         state.distribution_frequency = distribution_frequency;
         state.auto_distribution_enabled = auto_distribution_enabled;
         state.bump = ctx.bumps.state;
+        state.reward_currency = reward_currency;
+        state.reward_mint = reward_mint;
+        state.vesting_duration_seconds = 0;
+        state.epoch_budget = 0;
 
         msg!("Reward management contract initialized");
         Ok(())
     }
 
-    /// 分配收益到节点
+    /// 分配收益到节点：只累计到节点的待领取余额，由节点通过 `claim_rewards` 提取
     pub fn distribute_rewards(
         ctx: Context<DistributeRewards>,
         node_id: Pubkey,
@@ -77,85 +267,283 @@ pub mod reward_management {
         let node_summary = &mut ctx.accounts.node_reward_summary;
         let state = &mut ctx.accounts.state;
 
+        // 只有管理员可以分配收益
+        require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
+
         // 验证金额
+        require!(state.epoch_budget == 0, ErrorCode::EpochModeEnabled);
+        require!(ctx.accounts.node_account.status == NodeStatus::Active, ErrorCode::NodeNotEligible);
         require!(amount_lamports >= state.min_distribution_amount, ErrorCode::AmountTooLow);
         require!(state.reward_pool_balance >= amount_lamports, ErrorCode::InsufficientPoolBalance);
 
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
 
-        // 转移收益
-        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= amount_lamports;
-        **ctx.accounts.node_wallet.to_account_info().try_borrow_mut_lamports()? += amount_lamports;
-
         // 创建收益分配记录
         reward_account.id = format!("reward_{}_{}", node_id, current_time);
         reward_account.node_id = node_id;
-        reward_account.contribution_id = contribution_id;
+        reward_account.contribution_id = contribution_id.clone();
         reward_account.amount_lamports = amount_lamports;
         reward_account.distributed_at = current_time;
-        reward_account.status = RewardStatus::Completed;
+        reward_account.status = RewardStatus::Pending;
         reward_account.bump = ctx.bumps.reward_account;
 
-        // 更新节点收益汇总
-        node_summary.total_earned += amount_lamports;
-        node_summary.total_distributed += amount_lamports;
-        node_summary.last_distribution_at = current_time;
-        node_summary.distribution_count += 1;
+        // 累计到节点收益汇总
+        node_summary.node_id = node_id;
+        node_summary.bump = ctx.bumps.node_reward_summary;
+        node_summary.accrue(amount_lamports, current_time, state.vesting_duration_seconds)?;
 
-        // 更新全局状态
+        // 从奖励池中预留
         state.total_rewards_distributed += amount_lamports;
         state.reward_pool_balance -= amount_lamports;
 
-        msg!("Rewards distributed: {} lamports to node {}", amount_lamports, node_id);
+        emit!(RewardAccrued {
+            node_id,
+            contribution_id,
+            amount: amount_lamports,
+            pending_rewards: node_summary.pending_rewards,
+            vesting_end: node_summary.vesting_end,
+        });
+        msg!("Rewards accrued: {} to node {}", amount_lamports, node_id);
         Ok(())
     }
 
-    /// 批量分配收益
-    pub fn batch_distribute_rewards(
-        ctx: Context<BatchDistributeRewards>,
+    /// 批量分配收益（仅管理员，同样只累计，不转账）
+    ///
+    /// 每条分配在 remaining_accounts 中依次传入收益记录账户（由本指令创建）、
+    /// 已存在的节点收益汇总账户和节点管理合约中的节点账户。
+    pub fn batch_distribute_rewards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchDistributeRewards<'info>>,
         distributions: Vec<RewardDistribution>,
     ) -> Result<()> {
-        let state = &mut ctx.accounts.state;
-        let total_amount: u64 = distributions.iter().map(|d| d.amount_lamports).sum();
+        // 只有管理员可以批量分配
+        require!(ctx.accounts.authority.key() == ctx.accounts.state.admin, ErrorCode::Unauthorized);
+        require!(
+            distributions.len() <= MAX_BATCH_DISTRIBUTIONS
+                && ctx.remaining_accounts.len() == distributions.len() * 3,
+            ErrorCode::InvalidBatch
+        );
+
+        let total_amount = distributions
+            .iter()
+            .try_fold(0u64, |total, d| total.checked_add(d.amount_lamports))
+            .ok_or(ErrorCode::RewardOverflow)?;
 
         // 验证总金额
+        let state = &ctx.accounts.state;
+        require!(state.epoch_budget == 0, ErrorCode::EpochModeEnabled);
         require!(state.reward_pool_balance >= total_amount, ErrorCode::InsufficientPoolBalance);
 
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
+        let timestamp_bytes = current_time.to_le_bytes();
+        let record_rent = Rent::get()?.minimum_balance(RewardAccount::SPACE);
 
-        for (i, distribution) in distributions.iter().enumerate() {
-            // 验证单个金额
+        for (distribution, accounts) in distributions.iter().zip(ctx.remaining_accounts.chunks(3)) {
+            // 验证单个金额和节点活跃状态
             require!(distribution.amount_lamports >= state.min_distribution_amount, ErrorCode::AmountTooLow);
-
-            // 转移收益
-            **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= distribution.amount_lamports;
-            **ctx.accounts.node_wallets[i].to_account_info().try_borrow_mut_lamports()? += distribution.amount_lamports;
-
-            // 创建收益分配记录
-            let reward_account = &mut ctx.accounts.reward_accounts[i];
-            reward_account.id = format!("batch_reward_{}_{}", distribution.node_id, current_time);
-            reward_account.node_id = distribution.node_id;
-            reward_account.contribution_id = distribution.contribution_id.clone();
-            reward_account.amount_lamports = distribution.amount_lamports;
-            reward_account.distributed_at = current_time;
-            reward_account.status = RewardStatus::Completed;
-            reward_account.bump = ctx.bumps.reward_accounts[i];
-
-            // 更新节点收益汇总
-            let node_summary = &mut ctx.accounts.node_summaries[i];
-            node_summary.total_earned += distribution.amount_lamports;
-            node_summary.total_distributed += distribution.amount_lamports;
-            node_summary.last_distribution_at = current_time;
-            node_summary.distribution_count += 1;
+            require!(
+                distribution.contribution_id.len() <= RewardAccount::MAX_CONTRIBUTION_ID_LEN,
+                ErrorCode::InvalidBatch
+            );
+            let node_account = Account::<NodeAccount>::try_from(&accounts[2])?;
+            require!(
+                node_account.node_id == distribution.node_id && node_account.status == NodeStatus::Active,
+                ErrorCode::NodeNotEligible
+            );
+
+            // 创建收益分配记录，地址与单笔分配相同
+            let (record_address, record_bump) = Pubkey::find_program_address(
+                &[b"reward", distribution.node_id.as_ref(), &timestamp_bytes],
+                ctx.program_id,
+            );
+            require_keys_eq!(record_address, accounts[0].key(), ErrorCode::InvalidBatch);
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: accounts[0].clone(),
+                    },
+                    &[&[b"reward", distribution.node_id.as_ref(), &timestamp_bytes, &[record_bump]]],
+                ),
+                record_rent,
+                RewardAccount::SPACE as u64,
+                ctx.program_id,
+            )?;
+            let reward_account = RewardAccount {
+                id: format!("batch_reward_{}_{}", distribution.node_id, current_time),
+                node_id: distribution.node_id,
+                contribution_id: distribution.contribution_id.clone(),
+                amount_lamports: distribution.amount_lamports,
+                distributed_at: current_time,
+                status: RewardStatus::Pending,
+                bump: record_bump,
+            };
+            reward_account.try_serialize(&mut &mut accounts[0].try_borrow_mut_data()?[..])?;
+
+            // 累计到节点收益汇总
+            let mut node_summary = Account::<NodeRewardSummary>::try_from(&accounts[1])?;
+            require!(node_summary.node_id == distribution.node_id, ErrorCode::Unauthorized);
+            node_summary.accrue(distribution.amount_lamports, current_time, state.vesting_duration_seconds)?;
+            node_summary.exit(ctx.program_id)?;
+
+            emit!(RewardAccrued {
+                node_id: distribution.node_id,
+                contribution_id: distribution.contribution_id.clone(),
+                amount: distribution.amount_lamports,
+                pending_rewards: node_summary.pending_rewards,
+                vesting_end: node_summary.vesting_end,
+            });
         }
 
         // 更新全局状态
+        let state = &mut ctx.accounts.state;
         state.total_rewards_distributed += total_amount;
         state.reward_pool_balance -= total_amount;
 
-        msg!("Batch distributed rewards: {} lamports to {} nodes", total_amount, distributions.len());
+        msg!("Batch accrued rewards: {} to {} nodes", total_amount, distributions.len());
+        Ok(())
+    }
+
+    /// 结算已关闭的纪元：通过 CPI 读取纪元总算力评分（仅含验证通过的贡献和预言机定值的算力）并从奖励池预留纪元预算
+    pub fn finalize_epoch(ctx: Context<FinalizeEpoch>, epoch: u64) -> Result<()> {
+        let budget = ctx.accounts.state.epoch_budget;
+        require!(budget > 0, ErrorCode::EpochModeDisabled);
+
+        let cpi_accounts = contribution_tracking::cpi::accounts::GetEpochCompute {
+            epoch_compute: ctx.accounts.epoch_compute.to_account_info(),
+        };
+        let total_compute_score = contribution_tracking::cpi::get_epoch_compute(
+            CpiContext::new(ctx.accounts.contribution_tracking_program.to_account_info(), cpi_accounts),
+            epoch,
+        )?
+        .get();
+
+        // 纪元内没有贡献时不预留预算
+        let budget = if total_compute_score > 0.0 { budget } else { 0 };
+        let state = &mut ctx.accounts.state;
+        require!(state.reward_pool_balance >= budget, ErrorCode::InsufficientPoolBalance);
+        state.reward_pool_balance -= budget;
+
+        let epoch_state = &mut ctx.accounts.epoch_state;
+        epoch_state.epoch = epoch;
+        epoch_state.total_compute_score = total_compute_score;
+        epoch_state.budget = budget;
+        epoch_state.distributed = 0;
+        epoch_state.nodes_paid = 0;
+        epoch_state.finalized_at = Clock::get()?.unix_timestamp;
+        epoch_state.bump = ctx.bumps.epoch_state;
+
+        emit!(EpochFinalized {
+            epoch,
+            total_compute_score,
+            budget,
+        });
+        msg!("Epoch {} finalized: score {}, budget {}", epoch, total_compute_score, budget);
+        Ok(())
+    }
+
+    /// 按节点在纪元内的算力占比分配纪元预算，累计到节点待领取余额
+    pub fn distribute_epoch_reward(
+        ctx: Context<DistributeEpochReward>,
+        epoch: u64,
+        node_id: Pubkey,
+    ) -> Result<()> {
+        require!(ctx.accounts.node_account.status == NodeStatus::Active, ErrorCode::NodeNotEligible);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let epoch_state = &mut ctx.accounts.epoch_state;
+        let compute_score = ctx.accounts.node_epoch_compute.compute_score;
+
+        let amount = epoch_share(
+            epoch_state.budget,
+            epoch_state.distributed,
+            compute_score,
+            epoch_state.total_compute_score,
+        )?;
+        epoch_state.distributed += amount;
+        epoch_state.nodes_paid += 1;
+
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.epoch = epoch;
+        receipt.node_id = node_id;
+        receipt.compute_score = compute_score;
+        receipt.amount = amount;
+        receipt.bump = ctx.bumps.receipt;
+
+        let node_summary = &mut ctx.accounts.node_reward_summary;
+        node_summary.node_id = node_id;
+        node_summary.bump = ctx.bumps.node_reward_summary;
+        node_summary.accrue(amount, current_time, ctx.accounts.state.vesting_duration_seconds)?;
+
+        ctx.accounts.state.total_rewards_distributed += amount;
+
+        emit!(RewardAccrued {
+            node_id,
+            contribution_id: format!("epoch_{}", epoch),
+            amount,
+            pending_rewards: node_summary.pending_rewards,
+            vesting_end: node_summary.vesting_end,
+        });
+        msg!("Epoch {} reward: {} to node {}", epoch, amount, node_id);
+        Ok(())
+    }
+
+    /// 节点所有者领取已归属的收益，按配置的币种转账
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let node_summary = &mut ctx.accounts.node_reward_summary;
+        let amount = node_summary.claim(current_time)?;
+
+        let state = &ctx.accounts.state;
+        match state.reward_currency {
+            RewardCurrency::Lamports => {
+                **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= amount;
+                **ctx.accounts.owner.to_account_info().try_borrow_mut_lamports()? += amount;
+            }
+            RewardCurrency::SplToken => {
+                let (Some(reward_mint), Some(reward_vault), Some(owner_token_account), Some(token_program)) = (
+                    &ctx.accounts.reward_mint,
+                    &ctx.accounts.reward_vault,
+                    &ctx.accounts.owner_token_account,
+                    &ctx.accounts.token_program,
+                ) else {
+                    return err!(ErrorCode::MissingTokenAccounts);
+                };
+                state.check_token_claim_accounts(
+                    &state.key(),
+                    &reward_mint.key(),
+                    (&reward_vault.mint, &reward_vault.owner),
+                    (&owner_token_account.mint, &owner_token_account.owner),
+                    &ctx.accounts.owner.key(),
+                )?;
+
+                let signer_seeds: &[&[&[u8]]] = &[&[b"reward-management-state", &[state.bump]]];
+                token::transfer_checked(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        TransferChecked {
+                            from: reward_vault.to_account_info(),
+                            mint: reward_mint.to_account_info(),
+                            to: owner_token_account.to_account_info(),
+                            authority: state.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    amount,
+                    reward_mint.decimals,
+                )?;
+            }
+        }
+
+        emit!(RewardClaimed {
+            node_id: node_summary.node_id,
+            amount,
+            pending_rewards: node_summary.pending_rewards,
+            currency: state.reward_currency,
+        });
+        msg!("Node {} claimed {} rewards", node_summary.node_id, amount);
         Ok(())
     }
 
@@ -166,6 +554,11 @@ pub mod reward_management {
         amount: u64,
         lock_duration_seconds: u64,
     ) -> Result<()> {
+        require!(
+            ctx.accounts.state.reward_currency == RewardCurrency::Lamports,
+            ErrorCode::WrongRewardCurrency
+        );
+
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
         let lock_until = current_time + lock_duration_seconds as i64;
@@ -181,29 +574,13 @@ pub mod reward_management {
         Ok(())
     }
 
-    /// 解除质押代币
-    pub fn unstake_tokens(
-        ctx: Context<UnstakeTokens>,
-        node_id: Pubkey,
-        amount: u64,
-    ) -> Result<()> {
-        // 验证质押已到期且未被罚没
-        // 简化实现，实际应该从节点管理合约查询质押信息
-
-        // 转移代币从国库到用户
-        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.staker.to_account_info().try_borrow_mut_lamports()? += amount;
-
-        msg!("Unstaked {} lamports for node {}", amount, node_id);
-        Ok(())
-    }
-
     /// 增加奖励池余额
     pub fn add_to_reward_pool(
         ctx: Context<AddToRewardPool>,
         amount: u64,
     ) -> Result<()> {
         let state = &mut ctx.accounts.state;
+        require!(state.reward_currency == RewardCurrency::Lamports, ErrorCode::WrongRewardCurrency);
 
         // 转移代币到国库
         **ctx.accounts.funder.to_account_info().try_borrow_mut_lamports()? -= amount;
@@ -215,6 +592,70 @@ pub mod reward_management {
         Ok(())
     }
 
+    /// 质押 SPL 代币：从质押者代币账户转入奖励金库
+    pub fn stake_spl_tokens(
+        ctx: Context<StakeSplTokens>,
+        node_id: Pubkey,
+        amount: u64,
+        lock_duration_seconds: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.state.reward_currency == RewardCurrency::SplToken,
+            ErrorCode::WrongRewardCurrency
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let lock_until = current_time + lock_duration_seconds as i64;
+
+        token::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.staker_token_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.staker.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        msg!("Staked {} tokens for node {} until {}", amount, node_id, lock_until);
+        Ok(())
+    }
+
+    /// 向奖励金库注入 SPL 代币
+    pub fn add_tokens_to_reward_pool(
+        ctx: Context<AddTokensToRewardPool>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.state.reward_currency == RewardCurrency::SplToken,
+            ErrorCode::WrongRewardCurrency
+        );
+
+        token::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.funder_token_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        let state = &mut ctx.accounts.state;
+        state.reward_pool_balance += amount;
+
+        msg!("Added {} tokens to reward pool", amount);
+        Ok(())
+    }
+
     /// 更新分配设置
     pub fn update_distribution_settings(
         ctx: Context<UpdateDistributionSettings>,
@@ -235,6 +676,34 @@ pub mod reward_management {
         Ok(())
     }
 
+    /// 设置新收益的线性归属期（仅管理员，不影响已累计的收益）
+    pub fn set_vesting_duration(
+        ctx: Context<UpdateDistributionSettings>,
+        vesting_duration_seconds: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
+
+        state.vesting_duration_seconds = vesting_duration_seconds;
+
+        msg!("Vesting duration set to {} seconds", vesting_duration_seconds);
+        Ok(())
+    }
+
+    /// 设置纪元奖励预算（仅管理员）；非零时改为按纪元结算，逐笔分配被禁用
+    pub fn set_epoch_budget(
+        ctx: Context<UpdateDistributionSettings>,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(ctx.accounts.authority.key() == state.admin, ErrorCode::Unauthorized);
+
+        state.epoch_budget = epoch_budget;
+
+        msg!("Epoch budget set to {}", epoch_budget);
+        Ok(())
+    }
+
     /// 紧急提取（仅管理员）
     pub fn emergency_withdraw(
         ctx: Context<EmergencyWithdraw>,
@@ -268,7 +737,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1, // 空间计算
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 32 + 8 + 8, // 空间计算
         seeds = [b"reward-management-state"],
         bump
     )]
@@ -281,11 +750,12 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(node_id: Pubkey)]
 pub struct DistributeRewards<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + (4 + 36) + 32 + (4 + 36) + 8 + 8 + 1 + 1, // 空间计算
+        space = RewardAccount::SPACE,
         seeds = [b"reward", node_id.as_ref(), &Clock::get().unwrap().unix_timestamp.to_le_bytes()],
         bump
     )]
@@ -294,22 +764,33 @@ pub struct DistributeRewards<'info> {
     #[account(
         init_if_needed,
         payer = authority,
-        space = 8 + 32 + 8 + 8 + 8 + 8 + 4 + 1, // 空间计算
+        space = NodeRewardSummary::SPACE,
         seeds = [b"node-reward-summary", node_id.as_ref()],
         bump
     )]
     pub node_reward_summary: Account<'info, NodeRewardSummary>,
 
-    #[account(mut)]
+    // 节点管理合约中的节点账户，离线或被禁用的节点不参与分配
+    #[account(
+        seeds = [b"node", node_id.as_ref()],
+        bump = node_account.bump,
+        seeds::program = node_management::ID
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(mut, seeds = [b"reward-management-state"], bump = state.bump)]
     pub state: Account<'info, RewardManagementState>,
 
-    /// CHECK: 国库地址
     #[account(mut)]
-    pub treasury: AccountInfo<'info>,
+    pub authority: Signer<'info>,
 
-    /// CHECK: 节点钱包地址
-    #[account(mut)]
-    pub node_wallet: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BatchDistributeRewards<'info> {
+    #[account(mut, seeds = [b"reward-management-state"], bump = state.bump)]
+    pub state: Account<'info, RewardManagementState>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -318,31 +799,20 @@ pub struct DistributeRewards<'info> {
 }
 
 #[derive(Accounts)]
-pub struct BatchDistributeRewards<'info> {
+pub struct StakeTokens<'info> {
     #[account(mut)]
     pub state: Account<'info, RewardManagementState>,
 
-    // 最多支持10个节点的批量分配
-    #[account(mut, constraint = reward_accounts.len() <= 10)]
-    pub reward_accounts: Vec<Account<'info, RewardAccount>>,
-
-    #[account(mut, constraint = node_summaries.len() <= 10)]
-    pub node_summaries: Vec<Account<'info, NodeRewardSummary>>,
-
     /// CHECK: 国库地址
     #[account(mut)]
     pub treasury: AccountInfo<'info>,
 
-    /// CHECK: 节点钱包地址列表
-    #[account(mut, constraint = node_wallets.len() <= 10)]
-    pub node_wallets: Vec<AccountInfo<'info>>,
-
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub staker: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct StakeTokens<'info> {
+pub struct AddToRewardPool<'info> {
     #[account(mut)]
     pub state: Account<'info, RewardManagementState>,
 
@@ -351,33 +821,189 @@ pub struct StakeTokens<'info> {
     pub treasury: AccountInfo<'info>,
 
     #[account(mut)]
-    pub staker: Signer<'info>,
+    pub funder: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UnstakeTokens<'info> {
+#[instruction(epoch: u64)]
+pub struct FinalizeEpoch<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = EpochState::SPACE,
+        seeds = [b"epoch-state".as_ref(), &epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_state: Account<'info, EpochState>,
+
+    #[account(mut, seeds = [b"reward-management-state"], bump = state.bump)]
+    pub state: Account<'info, RewardManagementState>,
+
+    // 贡献跟踪合约的纪元算力汇总
+    #[account(
+        seeds = [b"epoch-compute".as_ref(), &epoch.to_le_bytes()],
+        bump = epoch_compute.bump,
+        seeds::program = contribution_tracking::ID
+    )]
+    pub epoch_compute: Account<'info, EpochComputeAccount>,
+
+    pub contribution_tracking_program: Program<'info, ContributionTracking>,
+
     #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64, node_id: Pubkey)]
+pub struct DistributeEpochReward<'info> {
+    #[account(mut, seeds = [b"epoch-state".as_ref(), &epoch.to_le_bytes()], bump = epoch_state.bump)]
+    pub epoch_state: Account<'info, EpochState>,
+
+    #[account(
+        seeds = [b"node-epoch-compute".as_ref(), &epoch.to_le_bytes(), node_id.as_ref()],
+        bump = node_epoch_compute.bump,
+        seeds::program = contribution_tracking::ID
+    )]
+    pub node_epoch_compute: Account<'info, NodeEpochCompute>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = EpochRewardReceipt::SPACE,
+        seeds = [b"epoch-reward".as_ref(), &epoch.to_le_bytes(), node_id.as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, EpochRewardReceipt>,
+
+    // 节点管理合约中的节点账户，离线或被禁用的节点不参与分配
+    #[account(
+        seeds = [b"node", node_id.as_ref()],
+        bump = node_account.bump,
+        seeds::program = node_management::ID
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = NodeRewardSummary::SPACE,
+        seeds = [b"node-reward-summary", node_id.as_ref()],
+        bump
+    )]
+    pub node_reward_summary: Account<'info, NodeRewardSummary>,
+
+    #[account(mut, seeds = [b"reward-management-state"], bump = state.bump)]
     pub state: Account<'info, RewardManagementState>,
 
-    /// CHECK: 国库地址
     #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    // 节点管理合约中的节点账户，收益只能由节点所有者领取
+    #[account(
+        seeds = [b"node", node_account.node_id.as_ref()],
+        bump = node_account.bump,
+        seeds::program = node_management::ID,
+        constraint = node_account.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"node-reward-summary", node_account.node_id.as_ref()],
+        bump = node_reward_summary.bump
+    )]
+    pub node_reward_summary: Account<'info, NodeRewardSummary>,
+
+    #[account(seeds = [b"reward-management-state"], bump = state.bump)]
+    pub state: Account<'info, RewardManagementState>,
+
+    /// CHECK: 国库地址（Lamports 模式下出账）
+    #[account(mut, address = state.treasury)]
     pub treasury: AccountInfo<'info>,
 
+    // 节点所有者
     #[account(mut)]
-    pub staker: Signer<'info>,
+    pub owner: Signer<'info>,
+
+    // 以下账户仅在 SplToken 模式下需要
+    pub reward_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub reward_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
-pub struct AddToRewardPool<'info> {
-    #[account(mut)]
+pub struct StakeSplTokens<'info> {
+    #[account(seeds = [b"reward-management-state"], bump = state.bump)]
     pub state: Account<'info, RewardManagementState>,
 
-    /// CHECK: 国库地址
+    #[account(address = state.reward_mint)]
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        associated_token::mint = reward_mint,
+        associated_token::authority = state
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = staker
+    )]
+    pub staker_token_account: Account<'info, TokenAccount>,
+
     #[account(mut)]
-    pub treasury: AccountInfo<'info>,
+    pub staker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddTokensToRewardPool<'info> {
+    #[account(mut, seeds = [b"reward-management-state"], bump = state.bump)]
+    pub state: Account<'info, RewardManagementState>,
+
+    #[account(address = state.reward_mint)]
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = funder,
+        associated_token::mint = reward_mint,
+        associated_token::authority = state
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = funder
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
 
     #[account(mut)]
     pub funder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -416,4 +1042,214 @@ pub enum ErrorCode {
     TokensStillLocked,
     #[msg("Tokens have been slashed")]
     TokensSlashed,
+    #[msg("Instruction does not match the configured reward currency")]
+    WrongRewardCurrency,
+    #[msg("SPL reward currency requires a reward mint")]
+    MissingRewardMint,
+    #[msg("No vested rewards to claim")]
+    NothingToClaim,
+    #[msg("Missing or mismatched token accounts for SPL claim")]
+    MissingTokenAccounts,
+    #[msg("Reward amount overflow")]
+    RewardOverflow,
+    #[msg("Per-contribution payouts are disabled while an epoch budget is set")]
+    EpochModeEnabled,
+    #[msg("Epoch budget is not configured")]
+    EpochModeDisabled,
+    #[msg("Node is not active and cannot receive rewards")]
+    NodeNotEligible,
+    #[msg("Batch accounts do not match the distributions")]
+    InvalidBatch,
+    #[msg("Epoch has no verified compute to distribute")]
+    NoEpochCompute,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_state(reward_mint: Pubkey) -> RewardManagementState {
+        RewardManagementState {
+            admin: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            total_rewards_distributed: 0,
+            reward_pool_balance: 0,
+            min_distribution_amount: 0,
+            distribution_frequency: 0,
+            auto_distribution_enabled: false,
+            bump: 0,
+            reward_currency: RewardCurrency::SplToken,
+            reward_mint,
+            vesting_duration_seconds: 0,
+            epoch_budget: 0,
+        }
+    }
+
+    #[test]
+    fn test_reward_currency_requires_mint() {
+        let mint = Pubkey::new_unique();
+        assert!(RewardManagementState::check_reward_currency(RewardCurrency::SplToken, &mint).is_ok());
+        assert!(RewardManagementState::check_reward_currency(RewardCurrency::Lamports, &Pubkey::default()).is_ok());
+        assert!(RewardManagementState::check_reward_currency(RewardCurrency::SplToken, &Pubkey::default()).is_err());
+    }
+
+    #[test]
+    fn test_token_claim_accounts() {
+        let mint = Pubkey::new_unique();
+        let state_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let state = token_state(mint);
+        assert!(state
+            .check_token_claim_accounts(&state_key, &mint, (&mint, &state_key), (&mint, &owner), &owner)
+            .is_ok());
+
+        // 收款账户不归领取者所有、金库不归状态 PDA 所有、mint 不是奖励代币
+        let other = Pubkey::new_unique();
+        assert!(state
+            .check_token_claim_accounts(&state_key, &mint, (&mint, &state_key), (&mint, &other), &owner)
+            .is_err());
+        assert!(state
+            .check_token_claim_accounts(&state_key, &mint, (&mint, &other), (&mint, &owner), &owner)
+            .is_err());
+        assert!(state
+            .check_token_claim_accounts(&state_key, &other, (&other, &state_key), (&other, &owner), &owner)
+            .is_err());
+
+        // 原生 SOL 奖励不走代币领取
+        let mut lamports_state = token_state(mint);
+        lamports_state.reward_currency = RewardCurrency::Lamports;
+        assert!(lamports_state
+            .check_token_claim_accounts(&state_key, &mint, (&mint, &state_key), (&mint, &owner), &owner)
+            .is_err());
+    }
+
+    fn summary() -> NodeRewardSummary {
+        NodeRewardSummary {
+            node_id: Pubkey::new_unique(),
+            total_earned: 0,
+            total_distributed: 0,
+            pending_rewards: 0,
+            last_distribution_at: 0,
+            distribution_count: 0,
+            bump: 0,
+            claimable: 0,
+            vesting_amount: 0,
+            vesting_start: 0,
+            vesting_end: 0,
+        }
+    }
+
+    #[test]
+    fn test_partial_vesting() {
+        let mut summary = summary();
+        summary.accrue(1_000, 100, 100).unwrap();
+        assert_eq!((summary.claimable, summary.vesting_amount, summary.vesting_end), (0, 1_000, 200));
+
+        // 未到起点时不归属
+        summary.settle_vesting(100);
+        assert_eq!(summary.claimable, 0);
+
+        summary.settle_vesting(125);
+        assert_eq!((summary.claimable, summary.vesting_amount), (250, 750));
+        // 剩余部分在剩余时长内继续线性归属
+        summary.settle_vesting(150);
+        assert_eq!((summary.claimable, summary.vesting_amount), (500, 500));
+        assert_eq!(summary.claimable + summary.vesting_amount, summary.pending_rewards);
+    }
+
+    #[test]
+    fn test_accrue_into_existing_schedule() {
+        let mut summary = summary();
+        summary.accrue(1_000, 0, 100).unwrap();
+        // 先结清已归属的 500，再与剩余 500 合并到 max(100, 50 + 30) 前归属
+        summary.accrue(300, 50, 30).unwrap();
+        assert_eq!((summary.claimable, summary.vesting_amount), (500, 800));
+        assert_eq!((summary.vesting_start, summary.vesting_end), (50, 100));
+        // 新的归属期更长时延后终点
+        summary.accrue(200, 60, 100).unwrap();
+        assert_eq!(summary.vesting_end, 160);
+        assert_eq!((summary.total_earned, summary.pending_rewards), (1_500, 1_500));
+        assert_eq!(summary.claimable + summary.vesting_amount, 1_500);
+        assert_eq!(summary.distribution_count, 3);
+    }
+
+    #[test]
+    fn test_zero_vesting_duration() {
+        let mut summary = summary();
+        summary.accrue(1_000, 100, 0).unwrap();
+        assert_eq!((summary.claimable, summary.vesting_amount), (1_000, 0));
+        assert_eq!(summary.claim(100).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_claim_after_full_vesting() {
+        let mut summary = summary();
+        summary.accrue(1_000, 0, 100).unwrap();
+        assert_eq!(summary.claim(40).unwrap(), 400);
+        assert_eq!(summary.claim(500).unwrap(), 600);
+        assert_eq!((summary.pending_rewards, summary.total_distributed, summary.vesting_amount), (0, 1_000, 0));
+        // 全部领取后不能重复领取
+        assert!(summary.claim(600).is_err());
+    }
+
+    #[test]
+    fn test_overflow_at_u64_bounds() {
+        let mut summary = summary();
+        summary.accrue(u64::MAX, 0, u64::MAX).unwrap();
+        // 归属期按 i64 上限截断，不会回绕到过去
+        assert_eq!(summary.vesting_end, i64::MAX);
+        summary.settle_vesting(i64::MAX / 2);
+        assert_eq!(summary.claimable + summary.vesting_amount, u64::MAX);
+        assert!(summary.claimable > 0);
+
+        assert!(summary.accrue(1, i64::MAX / 2, 0).is_err());
+        assert_eq!(summary.claim(i64::MAX).unwrap(), u64::MAX);
+    }
+
+    /// 依次为每个节点分配，返回各节点分得的金额
+    fn distribute(budget: u64, scores: &[f64]) -> Vec<u64> {
+        let epoch_compute: f64 = scores.iter().sum();
+        let mut distributed = 0u64;
+        scores
+            .iter()
+            .map(|score| {
+                let amount = epoch_share(budget, distributed, *score, epoch_compute).unwrap();
+                distributed += amount;
+                amount
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_epoch_share_never_exceeds_budget() {
+        assert_eq!(distribute(100, &[1.0, 1.0, 1.0]), vec![33, 33, 33]);
+        assert_eq!(distribute(1_000, &[1.0, 3.0]), vec![250, 750]);
+
+        let score_sets: [&[f64]; 5] = [
+            &[0.1, 0.2, 0.3],
+            &[1e-6, 2.5, 7.333333, 0.000001],
+            &[1e12, 1.0, 3.7],
+            &[1e18, 1e30, f64::MAX],
+            &[0.3; 7],
+        ];
+        for scores in score_sets {
+            for budget in [0, 1, 7, 999, 1_000_000_007, u64::MAX] {
+                let total: u128 = distribute(budget, scores).iter().map(|amount| *amount as u128).sum();
+                assert!(total <= budget as u128, "budget {} scores {:?}", budget, scores);
+            }
+        }
+
+        // 节点评分超过纪元总分时（浮点误差）也以剩余预算为上限
+        assert_eq!(epoch_share(100, 90, 2.0, 1.0).unwrap(), 10);
+        assert_eq!(epoch_share(100, 100, 1.0, 1.0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_epoch_share_rejects_zero_compute() {
+        assert!(epoch_share(100, 0, 1.0, 0.0).is_err());
+        assert!(epoch_share(100, 0, 1.0, f64::NAN).is_err());
+        assert!(epoch_share(100, 0, 0.0, -1.0).is_err());
+        // 节点评分为 0 时分不到预算
+        assert_eq!(epoch_share(100, 0, 0.0, 1.0).unwrap(), 0);
+    }
 }
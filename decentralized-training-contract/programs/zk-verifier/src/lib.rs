@@ -0,0 +1,423 @@
+use anchor_lang::prelude::*;
+use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing};
+use solana_sha256_hasher::hashv;
+
+declare_id!("ZK_VERIFIER_PROGRAM_ID");
+
+/// 单个电路最多支持的公开输入数量
+pub const MAX_PUBLIC_INPUTS: usize = 8;
+/// 电路ID最大长度
+pub const MAX_CIRCUIT_ID_LEN: usize = 64;
+
+/// BN254 基域模数 q（大端序）
+const BASE_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// BN254 标量域模数 r（大端序）
+const SCALAR_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// Groth16 证明（alt_bn128 系统调用格式，大端序）
+///
+/// G1 点为 `x || y`（64 字节），G2 点为 `x_im || x_re || y_im || y_re`（128 字节）。
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+/// 验证器全局配置：只有管理员可以注册验证密钥，防止抢注电路ID或注册平凡电路
+#[account]
+pub struct VerifierConfig {
+    pub admin: Pubkey,                    // 管理员公钥
+    pub bump: u8,                         // PDA bump
+}
+
+impl VerifierConfig {
+    pub const SPACE: usize = 8 + 32 + 1;
+}
+
+/// 电路验证密钥账户
+#[account]
+pub struct VerifyingKeyAccount {
+    pub authority: Pubkey,                // 注册者（管理员）
+    pub circuit_id: String,               // 电路ID，如 "matmul-1x2x1x1"
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    pub ic: Vec<[u8; 64]>,                // 公开输入的基点（长度 = 公开输入数 + 1）
+    pub bump: u8,                         // PDA bump
+}
+
+impl VerifyingKeyAccount {
+    pub const SPACE: usize = 8
+        + 32
+        + (4 + MAX_CIRCUIT_ID_LEN)
+        + 64
+        + 128 * 3
+        + (4 + 64 * (MAX_PUBLIC_INPUTS + 1))
+        + 1;
+}
+
+/// 已验证证明记录，PDA 由证明摘要派生，防止同一证明被重复提交
+#[account]
+pub struct ProofRecord {
+    pub circuit_id: String,               // 电路ID
+    pub proof_hash: [u8; 32],             // 证明和公开输入的摘要
+    pub submitter: Pubkey,                // 提交者
+    pub verified_at: i64,                 // 验证时间
+    pub bump: u8,                         // PDA bump
+}
+
+impl ProofRecord {
+    pub const SPACE: usize = 8 + (4 + MAX_CIRCUIT_ID_LEN) + 32 + 32 + 8 + 1;
+}
+
+#[event]
+pub struct ProofVerified {
+    pub circuit_id: String,
+    pub proof_hash: [u8; 32],
+    pub submitter: Pubkey,
+}
+
+#[program]
+pub mod zk_verifier {
+    use super::*;
+
+    /// 初始化验证器配置，调用者成为管理员
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.bump = ctx.bumps.config;
+
+        msg!("ZK verifier initialized");
+        Ok(())
+    }
+
+    /// 注册电路验证密钥（仅管理员）
+    pub fn register_verifying_key(
+        ctx: Context<RegisterVerifyingKey>,
+        circuit_id: String,
+        alpha_g1: [u8; 64],
+        beta_g2: [u8; 128],
+        gamma_g2: [u8; 128],
+        delta_g2: [u8; 128],
+        ic: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        check_verifying_key(&circuit_id, &ic)?;
+
+        let verifying_key = &mut ctx.accounts.verifying_key;
+        verifying_key.authority = ctx.accounts.authority.key();
+        verifying_key.circuit_id = circuit_id.clone();
+        verifying_key.alpha_g1 = alpha_g1;
+        verifying_key.beta_g2 = beta_g2;
+        verifying_key.gamma_g2 = gamma_g2;
+        verifying_key.delta_g2 = delta_g2;
+        verifying_key.ic = ic;
+        verifying_key.bump = ctx.bumps.verifying_key;
+
+        msg!("Verifying key registered: {}", circuit_id);
+        Ok(())
+    }
+
+    /// 验证 Groth16 证明并记录，证明无效时返回错误
+    pub fn verify_proof(
+        ctx: Context<VerifyProof>,
+        proof_hash: [u8; 32],
+        proof: Groth16Proof,
+        public_inputs: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            proof_hash == proof_digest(&proof, &public_inputs),
+            ZkVerifierError::ProofHashMismatch
+        );
+        let verifying_key = &ctx.accounts.verifying_key;
+        require!(
+            verify_groth16(verifying_key, &proof, &public_inputs)?,
+            ZkVerifierError::InvalidProof
+        );
+
+        let record = &mut ctx.accounts.proof_record;
+        record.circuit_id = verifying_key.circuit_id.clone();
+        record.proof_hash = proof_hash;
+        record.submitter = ctx.accounts.payer.key();
+        record.verified_at = Clock::get()?.unix_timestamp;
+        record.bump = ctx.bumps.proof_record;
+
+        emit!(ProofVerified {
+            circuit_id: verifying_key.circuit_id.clone(),
+            proof_hash,
+            submitter: record.submitter,
+        });
+        msg!("Proof verified for circuit {}", verifying_key.circuit_id);
+        Ok(())
+    }
+}
+
+/// 把任意数据哈希为 BN254 标量域内的公开输入：SHA-256 后清除最高 3 位（r 的最高字节为 0x30）
+///
+/// 调用方合约用它把证明绑定到具体的记录字段上。
+pub fn hash_to_scalar(parts: &[&[u8]]) -> [u8; 32] {
+    let mut scalar = hashv(parts).to_bytes();
+    scalar[0] &= 0x1f;
+    scalar
+}
+
+/// 证明和公开输入的摘要（客户端用它派生 `ProofRecord` 地址）
+pub fn proof_digest(proof: &Groth16Proof, public_inputs: &[[u8; 32]]) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> = vec![&proof.a, &proof.b, &proof.c];
+    parts.extend(public_inputs.iter().map(|input| input.as_slice()));
+    hashv(&parts).to_bytes()
+}
+
+/// 校验电路ID长度和公开输入基点数量
+fn check_verifying_key(circuit_id: &str, ic: &[[u8; 64]]) -> Result<()> {
+    require!(circuit_id.len() <= MAX_CIRCUIT_ID_LEN, ZkVerifierError::CircuitIdTooLong);
+    require!(
+        !ic.is_empty() && ic.len() <= MAX_PUBLIC_INPUTS + 1,
+        ZkVerifierError::InvalidVerifyingKey
+    );
+    Ok(())
+}
+
+/// 用 alt_bn128 系统调用验证 Groth16 证明：
+/// e(-A, B) · e(α, β) · e(vk_x, γ) · e(C, δ) == 1
+pub fn verify_groth16(
+    verifying_key: &VerifyingKeyAccount,
+    proof: &Groth16Proof,
+    public_inputs: &[[u8; 32]],
+) -> Result<bool> {
+    require!(
+        public_inputs.len() + 1 == verifying_key.ic.len(),
+        ZkVerifierError::InvalidPublicInputs
+    );
+
+    // vk_x = IC[0] + Σ input_i · IC[i]
+    let mut vk_x = verifying_key.ic[0];
+    for (input, base) in public_inputs.iter().zip(&verifying_key.ic[1..]) {
+        require!(is_canonical_scalar(input), ZkVerifierError::InvalidPublicInputs);
+        let mut mul_input = [0u8; 96];
+        mul_input[..64].copy_from_slice(base);
+        mul_input[64..].copy_from_slice(input);
+        let product = alt_bn128_multiplication(&mul_input).map_err(|_| error!(ZkVerifierError::CurveOperationFailed))?;
+
+        let mut add_input = [0u8; 128];
+        add_input[..64].copy_from_slice(&vk_x);
+        add_input[64..].copy_from_slice(&product);
+        let sum = alt_bn128_addition(&add_input).map_err(|_| error!(ZkVerifierError::CurveOperationFailed))?;
+        vk_x.copy_from_slice(&sum);
+    }
+
+    let mut pairing_input = Vec::with_capacity(192 * 4);
+    pairing_input.extend_from_slice(&negate_g1(&proof.a));
+    pairing_input.extend_from_slice(&proof.b);
+    pairing_input.extend_from_slice(&verifying_key.alpha_g1);
+    pairing_input.extend_from_slice(&verifying_key.beta_g2);
+    pairing_input.extend_from_slice(&vk_x);
+    pairing_input.extend_from_slice(&verifying_key.gamma_g2);
+    pairing_input.extend_from_slice(&proof.c);
+    pairing_input.extend_from_slice(&verifying_key.delta_g2);
+
+    let result = alt_bn128_pairing(&pairing_input).map_err(|_| error!(ZkVerifierError::CurveOperationFailed))?;
+    Ok(result.len() == 32 && result[..31].iter().all(|b| *b == 0) && result[31] == 1)
+}
+
+/// G1 点取负：(x, y) -> (x, q - y)
+fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut negated = *point;
+    let y = &point[32..];
+    if y.iter().all(|b| *b == 0) {
+        return negated;
+    }
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut diff = BASE_FIELD_MODULUS[i] as i16 - y[i] as i16 - borrow;
+        borrow = if diff < 0 {
+            diff += 256;
+            1
+        } else {
+            0
+        };
+        negated[32 + i] = diff as u8;
+    }
+    negated
+}
+
+/// 公开输入必须是标量域内的规范表示
+fn is_canonical_scalar(input: &[u8; 32]) -> bool {
+    input.as_slice() < SCALAR_FIELD_MODULUS.as_slice()
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = VerifierConfig::SPACE,
+        seeds = [b"zk-verifier-config"],
+        bump
+    )]
+    pub config: Account<'info, VerifierConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_id: String)]
+pub struct RegisterVerifyingKey<'info> {
+    #[account(
+        seeds = [b"zk-verifier-config"],
+        bump = config.bump,
+        constraint = config.admin == authority.key() @ ZkVerifierError::Unauthorized
+    )]
+    pub config: Account<'info, VerifierConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = VerifyingKeyAccount::SPACE,
+        seeds = [b"verifying-key", circuit_id.as_bytes()],
+        bump
+    )]
+    pub verifying_key: Account<'info, VerifyingKeyAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof_hash: [u8; 32])]
+pub struct VerifyProof<'info> {
+    pub verifying_key: Account<'info, VerifyingKeyAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ProofRecord::SPACE,
+        seeds = [b"proof-record", proof_hash.as_ref()],
+        bump
+    )]
+    pub proof_record: Account<'info, ProofRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[error_code]
+pub enum ZkVerifierError {
+    #[msg("Circuit id too long")]
+    CircuitIdTooLong,
+    #[msg("Invalid verifying key")]
+    InvalidVerifyingKey,
+    #[msg("Invalid public inputs")]
+    InvalidPublicInputs,
+    #[msg("Proof hash does not match proof data")]
+    ProofHashMismatch,
+    #[msg("alt_bn128 curve operation failed")]
+    CurveOperationFailed,
+    #[msg("Invalid Groth16 proof")]
+    InvalidProof,
+    #[msg("Only the verifier admin can register verifying keys")]
+    Unauthorized,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// G1 生成元 (1, 2)
+    fn g1() -> [u8; 64] {
+        let mut point = [0u8; 64];
+        point[31] = 1;
+        point[63] = 2;
+        point
+    }
+
+    /// G2 生成元（x_im || x_re || y_im || y_re）
+    fn g2() -> [u8; 128] {
+        let hex = concat!(
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+            "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+            "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        );
+        let mut point = [0u8; 128];
+        for (i, byte) in point.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        point
+    }
+
+    fn scalar(value: u64) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out[24..].copy_from_slice(&value.to_be_bytes());
+        out
+    }
+
+    fn g1_mul(value: u64) -> [u8; 64] {
+        let mut input = [0u8; 96];
+        input[..64].copy_from_slice(&g1());
+        input[64..].copy_from_slice(&scalar(value));
+        alt_bn128_multiplication(&input).unwrap().try_into().unwrap()
+    }
+
+    /// 所有 G2 元素都取生成元时，配对等式退化为 -a + α + vk_x + c = 0，
+    /// 取 α = C = IC[0] = IC[1] = G1 即可为输入 x 构造出 A = (3 + x)·G1 的有效证明
+    fn fixture(input: u64) -> (VerifyingKeyAccount, Groth16Proof) {
+        let verifying_key = VerifyingKeyAccount {
+            authority: Pubkey::default(),
+            circuit_id: "test".to_string(),
+            alpha_g1: g1(),
+            beta_g2: g2(),
+            gamma_g2: g2(),
+            delta_g2: g2(),
+            ic: vec![g1(), g1()],
+            bump: 0,
+        };
+        let proof = Groth16Proof { a: g1_mul(3 + input), b: g2(), c: g1() };
+        (verifying_key, proof)
+    }
+
+    #[test]
+    fn test_verify_groth16_accepts_valid_and_rejects_tampered() {
+        let (verifying_key, proof) = fixture(7);
+        assert!(verify_groth16(&verifying_key, &proof, &[scalar(7)]).unwrap());
+
+        // 公开输入或证明被篡改都不能通过
+        assert!(!verify_groth16(&verifying_key, &proof, &[scalar(8)]).unwrap());
+        let forged = Groth16Proof { a: g1_mul(11), ..proof.clone() };
+        assert!(!verify_groth16(&verifying_key, &forged, &[scalar(7)]).unwrap());
+
+        // 公开输入数量必须与密钥匹配，且必须是规范标量
+        assert!(verify_groth16(&verifying_key, &proof, &[]).is_err());
+        assert!(verify_groth16(&verifying_key, &proof, &[SCALAR_FIELD_MODULUS]).is_err());
+    }
+
+    #[test]
+    fn test_check_verifying_key() {
+        assert!(check_verifying_key("matmul", &[g1(), g1()]).is_ok());
+        assert!(check_verifying_key("matmul", &[]).is_err());
+        assert!(check_verifying_key("matmul", &vec![g1(); MAX_PUBLIC_INPUTS + 2]).is_err());
+        assert!(check_verifying_key(&"x".repeat(MAX_CIRCUIT_ID_LEN + 1), &[g1()]).is_err());
+    }
+
+    #[test]
+    fn test_hash_to_scalar_is_canonical() {
+        for i in 0u8..=255 {
+            assert!(is_canonical_scalar(&hash_to_scalar(&[&[i], b"contribution"])));
+        }
+    }
+}
@@ -1,5 +1,5 @@
 # 拆分后合约部署脚本 (PowerShell)
-# 部署顺序：共享类型 -> 节点管理 -> 贡献跟踪 -> 收益管理 -> 治理
+# 部署顺序：共享类型 -> 零知识证明验证 -> 节点管理 -> 贡献跟踪 -> 收益管理 -> 治理 -> 任务托管
 
 Write-Host "🚀 开始部署拆分后的智能合约..." -ForegroundColor Green
 
@@ -12,7 +12,16 @@ if ($LASTEXITCODE -ne 0) {
     exit 1
 }
 
-# 2. 部署节点管理合约
+# 2. 部署零知识证明验证合约（贡献跟踪合约通过 CPI 调用）
+Write-Host "🔐 部署零知识证明验证合约..." -ForegroundColor Blue
+anchor deploy zk-verifier --config Anchor-modular.toml
+
+if ($LASTEXITCODE -ne 0) {
+    Write-Host "❌ 零知识证明验证合约部署失败" -ForegroundColor Red
+    exit 1
+}
+
+# 3. 部署节点管理合约
 Write-Host "👤 部署节点管理合约..." -ForegroundColor Blue
 anchor deploy node-management --config Anchor-modular.toml
 
@@ -21,7 +30,7 @@ if ($LASTEXITCODE -ne 0) {
     exit 1
 }
 
-# 3. 部署贡献跟踪合约
+# 4. 部署贡献跟踪合约
 Write-Host "📊 部署贡献跟踪合约..." -ForegroundColor Blue
 anchor deploy contribution-tracking --config Anchor-modular.toml
 
@@ -30,7 +39,7 @@ if ($LASTEXITCODE -ne 0) {
     exit 1
 }
 
-# 4. 部署收益管理合约
+# 5. 部署收益管理合约
 Write-Host "💰 部署收益管理合约..." -ForegroundColor Blue
 anchor deploy reward-management --config Anchor-modular.toml
 
@@ -39,7 +48,7 @@ if ($LASTEXITCODE -ne 0) {
     exit 1
 }
 
-# 5. 部署治理合约
+# 6. 部署治理合约
 Write-Host "🏛️ 部署治理合约..." -ForegroundColor Blue
 anchor deploy governance --config Anchor-modular.toml
 
@@ -48,10 +57,28 @@ if ($LASTEXITCODE -ne 0) {
     exit 1
 }
 
+# 7. 部署任务托管付款合约（通过 CPI 调用零知识证明验证合约，争议由治理多签裁决）
+Write-Host "🤝 部署任务托管付款合约..." -ForegroundColor Blue
+anchor deploy job-escrow --config Anchor-modular.toml
+
+if ($LASTEXITCODE -ne 0) {
+    Write-Host "❌ 任务托管付款合约部署失败" -ForegroundColor Red
+    exit 1
+}
+
+# 8. 部署链下指标预言机合约（白名单上报者 M-of-N 一致后定值，供贡献跟踪合约导入）
+Write-Host "🔮 部署链下指标预言机合约..." -ForegroundColor Blue
+anchor deploy oracle --config Anchor-modular.toml
+
+if ($LASTEXITCODE -ne 0) {
+    Write-Host "❌ 链下指标预言机合约部署失败" -ForegroundColor Red
+    exit 1
+}
+
 Write-Host "✅ 所有合约部署完成！" -ForegroundColor Green
 
-# 6. 显示部署的程序ID
+# 9. 显示部署的程序ID
 Write-Host "📋 部署的程序ID：" -ForegroundColor Yellow
-solana program show --programs | Select-String "node_management|contribution_tracking|reward_management|governance"
+solana program show --programs | Select-String "node_management|contribution_tracking|reward_management|governance|zk_verifier|job_escrow|oracle"
 
 Write-Host "🎉 拆分后合约部署成功完成！" -ForegroundColor Green
@@ -1,7 +1,7 @@
 #!/bin/bash
 
 # 拆分后合约部署脚本
-# 部署顺序：共享类型 -> 节点管理 -> 贡献跟踪 -> 收益管理 -> 治理
+# 部署顺序：共享类型 -> 零知识证明验证 -> 节点管理 -> 贡献跟踪 -> 收益管理 -> 治理 -> 任务托管
 
 set -e
 
@@ -15,26 +15,38 @@ anchor build --config Anchor-modular.toml
 echo "🔧 部署共享类型库..."
 # 共享类型库通常不需要单独部署，作为依赖库使用
 
-# 3. 部署节点管理合约
+# 3. 部署零知识证明验证合约（贡献跟踪合约通过 CPI 调用）
+echo "🔐 部署零知识证明验证合约..."
+anchor deploy zk-verifier --config Anchor-modular.toml
+
+# 4. 部署节点管理合约
 echo "👤 部署节点管理合约..."
 anchor deploy node-management --config Anchor-modular.toml
 
-# 4. 部署贡献跟踪合约
+# 5. 部署贡献跟踪合约
 echo "📊 部署贡献跟踪合约..."
 anchor deploy contribution-tracking --config Anchor-modular.toml
 
-# 5. 部署收益管理合约
+# 6. 部署收益管理合约
 echo "💰 部署收益管理合约..."
 anchor deploy reward-management --config Anchor-modular.toml
 
-# 6. 部署治理合约
+# 7. 部署治理合约
 echo "🏛️ 部署治理合约..."
 anchor deploy governance --config Anchor-modular.toml
 
+# 8. 部署任务托管付款合约（通过 CPI 调用零知识证明验证合约，争议由治理多签裁决）
+echo "🤝 部署任务托管付款合约..."
+anchor deploy job-escrow --config Anchor-modular.toml
+
+# 9. 部署链下指标预言机合约（白名单上报者 M-of-N 一致后定值，供贡献跟踪合约导入）
+echo "🔮 部署链下指标预言机合约..."
+anchor deploy oracle --config Anchor-modular.toml
+
 echo "✅ 所有合约部署完成！"
 
-# 7. 显示部署的程序ID
+# 10. 显示部署的程序ID
 echo "📋 部署的程序ID："
-solana program show --programs | grep -E "(node_management|contribution_tracking|reward_management|governance)"
+solana program show --programs | grep -E "(node_management|contribution_tracking|reward_management|governance|zk_verifier|job_escrow|oracle)"
 
 echo "🎉 拆分后合约部署成功完成！"
@@ -0,0 +1,25 @@
+//! 线上消息解码模糊测试：`cargo +nightly fuzz run wire_decode`
+//!
+//! 任意输入都只能得到错误而不能 panic；包装消息和 gossip 帧解码成功后重新编码必须能再次解码。
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use williw::comms::transport::{decode_frame, decode_json, encode_frame, WireLimits, WrappedMessage};
+use williw::comms::p2p::FileTransferMessage;
+use williw::comms::AuthenticatedMessage;
+use williw::consensus::SignedGossip;
+
+fuzz_target!(|data: &[u8]| {
+    let limits = WireLimits::default();
+
+    let _ = decode_json::<FileTransferMessage>(data, &limits);
+    if let Ok(wrapped) = WrappedMessage::deserialize(data) {
+        WrappedMessage::deserialize(&wrapped.serialize().unwrap()).unwrap();
+    }
+    let _ = decode_json::<AuthenticatedMessage<SignedGossip>>(data, &limits);
+    if let Ok((topic, payload)) = decode_frame(data, &limits) {
+        let frame = encode_frame(topic, payload);
+        assert_eq!(decode_frame(&frame, &limits).unwrap(), (topic, payload));
+    }
+});
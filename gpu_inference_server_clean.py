@@ -8,7 +8,7 @@ import json
 import logging
 import time
 from pathlib import Path
-from typing import Dict, Any, Optional
+from typing import Dict, Any, List, Optional, Union
 
 import uvicorn
 from fastapi import FastAPI, HTTPException
@@ -17,7 +17,7 @@ from pydantic import BaseModel
 
 # 尝试导入transformers库
 try:
-    from transformers import AutoTokenizer, AutoModelForCausalLM, pipeline
+    from transformers import AutoTokenizer, AutoModel, AutoModelForCausalLM, pipeline
     import torch
     TRANSFORMERS_AVAILABLE = True
     print("✅ Transformers库已加载，支持真实GPU推理")
@@ -54,8 +54,38 @@ class InferenceResponse(BaseModel):
     processing_time: Optional[float] = None
     error: Optional[str] = None
 
+class EmbeddingRequest(BaseModel):
+    model_path: str
+    input: Union[str, List[str]]
+
+class EmbeddingResponse(BaseModel):
+    status: str
+    model: str
+    dimensions: int
+    embeddings: List[List[float]]
+    prompt_tokens: int
+    processing_time: float
+
 # 全局变量存储模型状态
 loaded_models: Dict[str, Any] = {}
+embedding_models: Dict[str, Any] = {}
+
+def load_embedding_model(model_path: str):
+    """加载（并缓存）向量模型，返回 (tokenizer, model, device)"""
+    if model_path not in embedding_models:
+        device = "cuda" if torch.cuda.is_available() else "cpu"
+        tokenizer = AutoTokenizer.from_pretrained(model_path)
+        model = AutoModel.from_pretrained(model_path).to(device).eval()
+        embedding_models[model_path] = (tokenizer, model, device)
+        loaded_models[model_path] = {
+            "path": model_path,
+            "loaded_at": time.time(),
+            "status": "loaded",
+            "type": "embedding",
+            "device": device,
+            "dimensions": model.config.hidden_size,
+        }
+    return embedding_models[model_path]
 
 @app.get("/")
 async def root():
@@ -118,6 +148,37 @@ async def inference(request: InferenceRequest):
             processing_time=processing_time
         )
 
+@app.post("/embeddings", response_model=EmbeddingResponse)
+async def embeddings(request: EmbeddingRequest):
+    """批量计算文本向量（mean pooling + L2 归一化）"""
+    if not TRANSFORMERS_AVAILABLE:
+        raise HTTPException(status_code=503, detail="transformers 未安装，无法计算向量")
+    texts = [request.input] if isinstance(request.input, str) else request.input
+    if not texts:
+        raise HTTPException(status_code=400, detail="input 不能为空")
+
+    start_time = time.time()
+    try:
+        tokenizer, model, device = load_embedding_model(request.model_path)
+        batch = tokenizer(texts, padding=True, truncation=True, return_tensors="pt").to(device)
+        with torch.no_grad():
+            hidden = model(**batch).last_hidden_state
+        mask = batch["attention_mask"].unsqueeze(-1).to(hidden.dtype)
+        pooled = (hidden * mask).sum(dim=1) / mask.sum(dim=1).clamp(min=1e-9)
+        pooled = torch.nn.functional.normalize(pooled, p=2, dim=1)
+    except Exception as e:
+        logger.error(f"向量计算失败: {str(e)}")
+        raise HTTPException(status_code=500, detail=f"向量计算失败: {str(e)}")
+
+    return EmbeddingResponse(
+        status="success",
+        model=request.model_path,
+        dimensions=pooled.shape[1],
+        embeddings=pooled.cpu().tolist(),
+        prompt_tokens=int(batch["attention_mask"].sum().item()),
+        processing_time=time.time() - start_time,
+    )
+
 @app.get("/models")
 async def list_models():
     """列出已加载的模型"""
@@ -130,7 +191,8 @@ async def list_models():
                 "loaded_at": info["loaded_at"],
                 "status": info["status"],
                 "type": info["type"],
-                "device": info.get("device", "unknown")
+                "device": info.get("device", "unknown"),
+                "dimensions": info.get("dimensions")
             }
             for model_id, info in loaded_models.items()
         ]
@@ -169,8 +231,9 @@ async def health_check():
 
 if __name__ == "__main__":
     print("启动真实GPU推理服务器...")
-    print("服务器将在 http://localhost:8000 运行")
+    print("服务器将在 http://127.0.0.1:8000 运行（外部请求请通过桌面端推理网关 :8010）")
     print("AI引擎: transformers + pytorch")
     print("按 Ctrl+C 停止服务器")
     
-    uvicorn.run(app, host="0.0.0.0", port=8000)
+    # 只监听本机，外部访问必须经过带 API 密钥校验的推理网关
+    uvicorn.run(app, host="127.0.0.1", port=8000)
@@ -0,0 +1,336 @@
+#ifndef WILLIW_H
+#define WILLIW_H
+
+/* 由 cbindgen 生成（cargo build --features ios），请勿手动修改 */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * FFI 错误代码
+ */
+typedef enum FfiError {
+  FfiError_Success = 0,
+  FfiError_InvalidArgument = 1,
+  FfiError_OutOfMemory = 2,
+  FfiError_NetworkError = 3,
+  FfiError_Unknown = 99,
+} FfiError;
+
+/**
+ * 训练状态
+ */
+typedef enum WilliwTrainingState {
+  WilliwTrainingState_Stopped = 0,
+  WilliwTrainingState_Running = 1,
+  /**
+   * 节点异常退出，原因见 `williw_ios_last_error`
+   */
+  WilliwTrainingState_Failed = 2,
+} WilliwTrainingState;
+
+/**
+ * 网络类型（与 `NetworkType` 一一对应，供 Swift 直接使用枚举）
+ */
+typedef enum WilliwNetworkType {
+  WilliwNetworkType_Unknown = 0,
+  WilliwNetworkType_WiFi = 1,
+  WilliwNetworkType_Cellular4G = 2,
+  WilliwNetworkType_Cellular5G = 3,
+} WilliwNetworkType;
+
+/**
+ * 节点句柄（不透明指针）
+ */
+typedef struct NodeHandle NodeHandle;
+
+/**
+ * iOS 节点句柄（不透明指针）
+ */
+typedef struct WilliwIosNode WilliwIosNode;
+
+/**
+ * 设备信息回调函数类型
+ *
+ * 移动端可以通过此回调函数向 Rust 层提供真实的设备信息
+ * 参数说明：
+ * - memory_mb: 输出参数，设备内存（MB）
+ * - cpu_cores: 输出参数，CPU 核心数
+ * - network_type: 输出参数，网络类型字符串（"wifi", "4g", "5g", "unknown"）
+ * - battery_level: 输出参数，电池电量（0.0-1.0），-1.0 表示无法检测
+ * - is_charging: 输出参数，是否正在充电（0=false, 1=true）
+ * 返回值：0 表示成功，非0表示失败
+ */
+typedef int (*DeviceInfoCallback)(uint32_t *memory_mb,
+                                  uint32_t *cpu_cores,
+                                  char *network_type,
+                                  size_t network_type_len,
+                                  float *battery_level,
+                                  int *is_charging);
+
+/**
+ * 读取电池状态：`level` 为 0.0-1.0（无法检测时填 -1.0），返回 0 表示成功
+ */
+typedef int (*WilliwBatteryHook)(void *context, float *level, int *is_charging);
+
+/**
+ * 读取当前网络类型（`WilliwNetworkType` 的取值），返回 0 表示成功
+ */
+typedef int (*WilliwNetworkHook)(void *context, int *network_type);
+
+/**
+ * 节点事件通知，`event_json` 仅在回调期间有效
+ */
+typedef void (*WilliwEventHook)(void *context, const char *event_json);
+
+/**
+ * Swift 侧注册的回调集合，未提供的回调传 NULL
+ */
+typedef struct WilliwIosCallbacks {
+  void *context;
+  WilliwBatteryHook battery;
+  WilliwNetworkHook network;
+  WilliwEventHook on_event;
+} WilliwIosCallbacks;
+
+#ifdef __cplusplus
+extern "C" {
+#endif  // __cplusplus
+
+/**
+ * 创建新的节点实例
+ *
+ * # Safety
+ * 返回的指针必须通过 `williw_node_destroy` 释放
+ */
+NodeHandle *williw_node_create(void);
+
+/**
+ * 设置设备信息回调函数
+ *
+ * 移动端可以通过此函数注册一个回调，用于向 Rust 层提供真实的设备信息
+ * 当 DeviceManager 需要刷新设备信息时，会调用此回调
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ * callback 必须是有效的函数指针，或者 NULL（表示清除回调）
+ */
+int williw_node_set_device_callback(NodeHandle *ptr, DeviceInfoCallback callback);
+
+/**
+ * 销毁节点实例
+ *
+ * # Safety
+ * ptr 必须是通过 `williw_node_create` 创建的有效指针
+ */
+void williw_node_destroy(NodeHandle *ptr);
+
+/**
+ * 获取设备能力信息（JSON 格式）
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ * 返回的字符串必须通过 `williw_string_free` 释放
+ */
+char *williw_node_get_capabilities(const NodeHandle *ptr);
+
+/**
+ * 更新网络类型
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ * network_type_str 必须是有效的 C 字符串
+ */
+int williw_node_update_network_type(NodeHandle *ptr, const char *network_type_str);
+
+/**
+ * 刷新设备信息（从回调函数获取）
+ *
+ * 如果已设置设备信息回调，会调用回调获取最新设备信息并更新
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ */
+int williw_node_refresh_device_info(NodeHandle *ptr);
+
+/**
+ * 更新电池状态
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ */
+int williw_node_update_battery(NodeHandle *ptr, float level, int is_charging);
+
+/**
+ * 释放由 FFI 函数返回的字符串
+ *
+ * # Safety
+ * ptr 必须是通过 FFI 函数返回的有效字符串指针
+ */
+void williw_string_free(char *ptr);
+
+/**
+ * 获取推荐的模型维度
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ */
+size_t williw_node_recommended_model_dim(const NodeHandle *ptr);
+
+/**
+ * 获取推荐的训练间隔（秒）
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ */
+uint64_t williw_node_recommended_tick_interval(const NodeHandle *ptr);
+
+/**
+ * 检查是否应该暂停训练
+ *
+ * # Safety
+ * ptr 必须是有效的节点句柄
+ */
+int williw_node_should_pause_training(const NodeHandle *ptr);
+
+/**
+ * 创建节点句柄
+ *
+ * `config_toml` 为 TOML 格式的完整配置，传 NULL 时按设备能力生成默认配置。
+ *
+ * # Safety
+ * `config_toml` 必须是 NULL 或有效的 C 字符串；返回的指针必须通过 `williw_ios_node_destroy` 释放
+ */
+WilliwIosNode *williw_ios_node_create(const char *config_toml);
+
+/**
+ * 销毁节点句柄（会先停止训练）
+ *
+ * # Safety
+ * `ptr` 必须是 `williw_ios_node_create` 返回的指针，且只能销毁一次
+ */
+void williw_ios_node_destroy(WilliwIosNode *ptr);
+
+/**
+ * 注册电池、网络和事件回调，传 NULL 清除
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄；`callbacks` 为 NULL 或指向有效的结构体（内容会被复制）
+ */
+int williw_ios_set_callbacks(WilliwIosNode *ptr, const WilliwIosCallbacks *callbacks);
+
+/**
+ * 立即通过回调刷新电池和网络状态
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄
+ */
+int williw_ios_refresh_device(WilliwIosNode *ptr);
+
+/**
+ * 推送电池状态（例如 `UIDevice.batteryStateDidChangeNotification`）
+ *
+ * `level` 为 0.0-1.0，超出范围表示无法检测。
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄
+ */
+int williw_ios_update_battery(WilliwIosNode *ptr, float level, int is_charging);
+
+/**
+ * 推送网络类型变化（例如 `NWPathMonitor`），取值见 `WilliwNetworkType`
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄
+ */
+int williw_ios_update_network(WilliwIosNode *ptr, int network_type);
+
+/**
+ * 更新内存和 CPU 信息
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄
+ */
+int williw_ios_update_hardware(WilliwIosNode *ptr, uint32_t memory_mb, uint32_t cpu_cores);
+
+/**
+ * 当前设备能力（JSON）
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄；返回的字符串必须通过 `williw_string_free` 释放
+ */
+char *williw_ios_capabilities_json(const WilliwIosNode *ptr);
+
+/**
+ * 启动训练节点（在句柄内部的运行时中后台运行）
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄
+ */
+int williw_ios_training_start(WilliwIosNode *ptr);
+
+/**
+ * 停止训练节点
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄
+ */
+int williw_ios_training_stop(WilliwIosNode *ptr);
+
+/**
+ * 训练状态
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄
+ */
+WilliwTrainingState williw_ios_training_state(const WilliwIosNode *ptr);
+
+/**
+ * 训练状态详情（JSON：状态、运行时长、模型版本、最近错误和训练统计）
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄；返回的字符串必须通过 `williw_string_free` 释放
+ */
+char *williw_ios_training_status_json(const WilliwIosNode *ptr);
+
+/**
+ * 从 JSON 张量快照加载推理模型，已加载模型时蓝绿切换
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄；`path` 必须是有效的 C 字符串
+ */
+int williw_ios_load_model(WilliwIosNode *ptr, const char *path);
+
+/**
+ * 执行一次推理
+ *
+ * 结果写入 `output`（容量 `output_capacity`），实际长度写入 `output_len`。
+ * 容量不足时返回 `OutOfMemory`，`output_len` 中为所需长度。
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄；`input` 指向 `input_len` 个 f32；
+ * `output` 指向 `output_capacity` 个 f32 的可写缓冲区；`output_len` 必须有效
+ */
+int williw_ios_infer(const WilliwIosNode *ptr,
+                     const float *input,
+                     size_t input_len,
+                     float *output,
+                     size_t output_capacity,
+                     size_t *output_len);
+
+/**
+ * 最近一次错误信息，没有错误时返回 NULL
+ *
+ * # Safety
+ * `ptr` 必须是有效的节点句柄；返回的字符串必须通过 `williw_string_free` 释放
+ */
+char *williw_ios_last_error(const WilliwIosNode *ptr);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WILLIW_H */
@@ -1,14 +1,24 @@
-use crate::state::{AppState, ModelConfig, TrainingStatus, DeviceInfo, AppSettings, ApiKeyEntry};
+use crate::state::{AppState, ModelConfig, TrainingStatus, DeviceInfo, AppSettings, ApiKeyEntry, LocalModel, LocalModelFile, SETTINGS_FILE};
+use crate::tray;
 use crate::api_client::TrainingConfigData;
-use tauri::State;
+use crate::events::{EventTopic, ModelDownloadEvent, ModelDownloadStatus};
+use model_downloader::{DownloadCancelled, DownloadConfig, DownloadResult, ModelDownloader, DEFAULT_CACHE_ROOT};
+use tauri::{AppHandle, Emitter, State};
 use williw::Node;  // 导入真实的Node
 use williw::config::AppConfig;
+use williw::stats::{ApiUsageTotals, ChainEvent, ChainRecord, ChainTable, PayoutCheck, PayoutStatus, StatsStore, SubmissionRecord, TickAggregate, TransferRecord, TransferTotals};
+use williw::training::{ApiKeyLimits, PriorityClass, SchedulerStats};
+use williw::device::ResourceCaps;
+use williw::model_cache::ModelCache;
+use williw::storage::DownloadPreflight;
+use std::collections::BTreeMap;
 use std::process::Command;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 // 确保 uuid 和 chrono 被导入
-use uuid::Uuid;
-use chrono::Utc;
 
 /// Start training node
 #[tauri::command]
@@ -27,11 +37,19 @@ pub async fn start_training(
     app_config.training.model_dim = model_config.dimensions;
     app_config.training.learning_rate = model_config.learning_rate;
     app_config.training.batch_size = model_config.batch_size;
+    app_config
+        .check()
+        .into_result()
+        .map_err(|e| format!("Invalid configuration: {}", e))?;
 
     // 创建并启动Node
-    let node = Node::new(app_config)
+    let mut node = Node::new(app_config)
         .await
         .map_err(|e| format!("Failed to create node: {}", e))?;
+    if let Some(store) = state.stats_store.clone() {
+        node = node.with_stats_store(store);
+    }
+    node = node.with_power_policy(state.power_policy.clone());
 
     let node_id = node.comms.node_id().to_string();
 
@@ -70,6 +88,34 @@ pub async fn stop_training(
     }
 }
 
+/// Subscribe to real-time event topics; returns the current subscriptions
+#[tauri::command]
+pub fn subscribe_events(
+    topics: Vec<EventTopic>,
+    state: State<'_, AppState>
+) -> Vec<EventTopic> {
+    state.event_subscriptions.subscribe(&topics);
+    state.event_subscriptions.topics()
+}
+
+/// Unsubscribe from real-time event topics; returns the current subscriptions
+#[tauri::command]
+pub fn unsubscribe_events(
+    topics: Vec<EventTopic>,
+    state: State<'_, AppState>
+) -> Vec<EventTopic> {
+    state.event_subscriptions.unsubscribe(&topics);
+    state.event_subscriptions.topics()
+}
+
+/// Get the currently subscribed event topics
+#[tauri::command]
+pub fn get_event_subscriptions(
+    state: State<'_, AppState>
+) -> Vec<EventTopic> {
+    state.event_subscriptions.topics()
+}
+
 /// Get current training status
 #[tauri::command]
 pub fn get_training_status(
@@ -105,6 +151,194 @@ pub fn get_available_models(
     state.available_models.lock().clone()
 }
 
+/// 下载进度事件的最小推送间隔
+const DOWNLOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Download a model from Hugging Face into the local model cache, emitting `model-download-progress` events
+#[tauri::command]
+pub async fn download_model(
+    model_name: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>
+) -> Result<DownloadResult, String> {
+    let cache = state.model_cache.clone().ok_or("Model cache is unavailable")?;
+    // 先确认保留空间，得知模型总大小后再按剩余字节预检
+    let download_dir = Path::new(DEFAULT_CACHE_ROOT).join(model_name.replace('/', "_"));
+    state.storage.preflight(&download_dir, 0).map_err(|e| e.to_string())?;
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut downloads = state.downloads.lock();
+        if downloads.contains_key(&model_name) {
+            return Err(format!("Model '{}' is already downloading", model_name));
+        }
+        downloads.insert(model_name.clone(), cancel.clone());
+    }
+
+    let hf_token = std::env::var("HF_TOKEN").ok();
+    let downloader = ModelDownloader::new(hf_token.clone());
+    let config = DownloadConfig {
+        model_name: model_name.clone(),
+        cache_dir: None,
+        hf_token,
+    };
+    let mut last_emit: Option<Instant> = None;
+    let mut preflight = DownloadPreflight::new(&state.storage, &download_dir, &cancel);
+    let result = downloader
+        .download_model_with_progress(config, &cancel, |progress| {
+            preflight.observe(progress.downloaded_bytes, progress.total_bytes);
+            // 限制推送频率，每个文件完成时总会推送一次
+            let file_done = progress.file_total.is_some_and(|total| progress.file_downloaded >= total);
+            if file_done || last_emit.is_none_or(|at| at.elapsed() >= DOWNLOAD_PROGRESS_INTERVAL) {
+                last_emit = Some(Instant::now());
+                let event = ModelDownloadEvent {
+                    progress: Some(progress.clone()),
+                    ..ModelDownloadEvent::new(&model_name, ModelDownloadStatus::Downloading)
+                };
+                let _ = app_handle.emit("model-download-progress", event);
+            }
+        })
+        .await;
+    // 因空间不足取消时报告原因
+    let result = match (result, preflight.into_error()) {
+        (Err(_), Some(e)) => Err(e),
+        (result, _) => result,
+    };
+
+    // 下载完成后把文件放入模型缓存（原位置改为硬链接），哈希大文件放到阻塞线程
+    let result = match result {
+        Ok(result) => {
+            let root = Path::new(&result.model_path).to_path_buf();
+            let names: Vec<String> = result.files_downloaded.clone();
+            let model = model_name.clone();
+            tokio::task::spawn_blocking(move || {
+                for file in names {
+                    cache.insert_file(&format!("{}/{}", model, file), &root.join(&file), true)?;
+                }
+                Ok::<_, anyhow::Error>(())
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|inserted| inserted)
+            .map(|_| result)
+        }
+        Err(e) => Err(e),
+    };
+    state.downloads.lock().remove(&model_name);
+
+    match result {
+        Ok(result) => {
+            let _ = app_handle.emit(
+                "model-download-progress",
+                ModelDownloadEvent::new(&model_name, ModelDownloadStatus::Completed),
+            );
+            Ok(result)
+        }
+        Err(e) => {
+            let status = if e.downcast_ref::<DownloadCancelled>().is_some() {
+                ModelDownloadStatus::Cancelled
+            } else {
+                ModelDownloadStatus::Failed
+            };
+            let _ = app_handle.emit(
+                "model-download-progress",
+                ModelDownloadEvent {
+                    error: Some(e.to_string()),
+                    ..ModelDownloadEvent::new(&model_name, status)
+                },
+            );
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Cancel an in-progress model download
+#[tauri::command]
+pub fn cancel_download(
+    model_name: String,
+    state: State<'_, AppState>
+) -> Result<(), String> {
+    let downloads = state.downloads.lock();
+    let cancel = downloads.get(&model_name)
+        .ok_or_else(|| format!("Model '{}' is not downloading", model_name))?;
+    cancel.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 按模型分组缓存中链接到下载目录的文件：缓存名为 `<模型名>/<文件>`，链接位置为 `<下载目录>/<模型目录>/<文件>`
+fn local_models(cache: &ModelCache) -> Vec<LocalModel> {
+    let root = Path::new(DEFAULT_CACHE_ROOT);
+    let mut models: BTreeMap<String, LocalModel> = BTreeMap::new();
+    for entry in cache.entries() {
+        for (name, link) in &entry.names {
+            let Some(rel) = link.as_deref().and_then(|link| link.strip_prefix(root).ok()) else {
+                continue;
+            };
+            let mut components = rel.components();
+            let Some(dir) = components.next() else {
+                continue;
+            };
+            let file = components.as_path().to_string_lossy().replace('\\', "/");
+            let Some(model_name) = name.strip_suffix(&format!("/{}", file)) else {
+                continue;
+            };
+            let model = models.entry(model_name.to_string()).or_insert_with(|| LocalModel {
+                name: model_name.to_string(),
+                path: root.join(dir).to_string_lossy().to_string(),
+                files: Vec::new(),
+                total_bytes: 0,
+                last_access: 0,
+            });
+            model.files.push(LocalModelFile {
+                name: file,
+                size: entry.size,
+                digest: entry.digest.clone(),
+                pinned: entry.pinned,
+            });
+            model.total_bytes += entry.size;
+            model.last_access = model.last_access.max(entry.last_access);
+        }
+    }
+    models.into_values().collect()
+}
+
+/// List models downloaded into the local model cache
+#[tauri::command]
+pub fn list_local_models(
+    state: State<'_, AppState>
+) -> Result<Vec<LocalModel>, String> {
+    let cache = state.model_cache.as_ref().ok_or("Model cache is unavailable")?;
+    Ok(local_models(cache))
+}
+
+/// Delete a locally downloaded model; files shared with other models stay in the cache
+#[tauri::command]
+pub fn delete_local_model(
+    model_name: String,
+    state: State<'_, AppState>
+) -> Result<LocalModel, String> {
+    if state.downloads.lock().contains_key(&model_name) {
+        return Err(format!("Model '{}' is still downloading", model_name));
+    }
+    let cache = state.model_cache.as_ref().ok_or("Model cache is unavailable")?;
+    let model = local_models(cache)
+        .into_iter()
+        .find(|model| model.name == model_name)
+        .ok_or_else(|| format!("Model '{}' not found", model_name))?;
+    if model.files.iter().any(|file| file.pinned) {
+        return Err(format!("Model '{}' has pinned files, unpin them first", model_name));
+    }
+    for file in &model.files {
+        cache.remove_name(&format!("{}/{}", model.name, file.name))
+            .map_err(|e| e.to_string())?;
+    }
+    // 清理下载目录中剩余的文件（未完成的 .part 等）
+    match std::fs::remove_dir_all(&model.path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+        _ => {}
+    }
+    Ok(model)
+}
+
 /// Get device information
 #[tauri::command]
 pub fn get_device_info(
@@ -123,16 +357,125 @@ pub fn get_training_stats(
     state.training_status.lock().clone()
 }
 
+fn stats_store(state: &State<'_, AppState>) -> Result<std::sync::Arc<StatsStore>, String> {
+    state.stats_store.clone().ok_or_else(|| "Stats database is not available".to_string())
+}
+
+/// Query per-tick training metrics aggregated into buckets of `bucket_secs` seconds
+#[tauri::command]
+pub fn query_tick_metrics(
+    from: i64,
+    to: i64,
+    bucket_secs: i64,
+    state: State<'_, AppState>
+) -> Result<Vec<TickAggregate>, String> {
+    stats_store(&state)?
+        .aggregate_ticks(from, to, bucket_secs)
+        .map_err(|e| e.to_string())
+}
+
+/// Get P2P transfer sessions started in the given time range
+#[tauri::command]
+pub fn get_transfer_history(
+    from: i64,
+    to: i64,
+    state: State<'_, AppState>
+) -> Result<Vec<TransferRecord>, String> {
+    stats_store(&state)?
+        .transfers_between(from, to)
+        .map_err(|e| e.to_string())
+}
+
+/// Get aggregated P2P transfer totals for the given time range
+#[tauri::command]
+pub fn get_transfer_totals(
+    from: i64,
+    to: i64,
+    state: State<'_, AppState>
+) -> Result<TransferTotals, String> {
+    stats_store(&state)?
+        .transfer_totals(from, to)
+        .map_err(|e| e.to_string())
+}
+
+/// Get on-chain submissions that have not been confirmed yet
+#[tauri::command]
+pub fn get_pending_submissions(
+    state: State<'_, AppState>
+) -> Result<Vec<SubmissionRecord>, String> {
+    stats_store(&state)?
+        .pending_submissions()
+        .map_err(|e| e.to_string())
+}
+
+/// Query on-chain accounts mirrored into the local stats database
+#[tauri::command]
+pub fn get_chain_records(
+    table: ChainTable,
+    node_id: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>
+) -> Result<Vec<ChainRecord>, String> {
+    stats_store(&state)?
+        .chain_records(table, node_id.as_deref(), limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+/// Query on-chain program events mirrored into the local stats database
+#[tauri::command]
+pub fn get_chain_events(
+    name: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>
+) -> Result<Vec<ChainEvent>, String> {
+    stats_store(&state)?
+        .chain_events(name.as_deref(), limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+/// Query reward payout verification results; `status` filters e.g. to "discrepancy" or "disputed"
+#[tauri::command]
+pub fn get_payout_checks(
+    status: Option<PayoutStatus>,
+    limit: Option<usize>,
+    state: State<'_, AppState>
+) -> Result<Vec<PayoutCheck>, String> {
+    stats_store(&state)?
+        .payout_checks(status, limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
 /// Update application settings
 #[tauri::command]
 pub fn update_settings(
-    new_settings: AppSettings,
+    mut new_settings: AppSettings,
     state: State<'_, AppState>
 ) -> Result<String, String> {
-    *state.settings.lock() = new_settings;
+    let mut settings = state.settings.lock();
+    // 资源上限只通过 set_resource_caps / 托盘修改
+    new_settings.resource_caps = settings.resource_caps.clone();
+    new_settings.save(SETTINGS_FILE).map_err(|e| e.to_string())?;
+    *settings = new_settings;
     Ok("Settings updated successfully".to_string())
 }
 
+/// Get the resource caps set from the tray (pause, CPU limit, Wi-Fi only)
+#[tauri::command]
+pub fn get_resource_caps(
+    state: State<'_, AppState>
+) -> ResourceCaps {
+    state.power_policy.caps()
+}
+
+/// Update resource caps; applied to the running node, persisted and mirrored in the tray menu
+#[tauri::command]
+pub fn set_resource_caps(
+    caps: ResourceCaps,
+    app_handle: AppHandle
+) -> Result<ResourceCaps, String> {
+    tray::apply_resource_caps(&app_handle, caps).map_err(|e| e.to_string())
+}
+
 /// Get current settings
 #[tauri::command]
 pub fn get_settings(
@@ -141,50 +484,79 @@ pub fn get_settings(
     state.settings.lock().clone()
 }
 
-/// Get all API keys
+/// Get all API keys (active keys only, secrets masked)
 #[tauri::command]
 pub fn get_api_keys(
     state: State<'_, AppState>
-) -> Vec<ApiKeyEntry> {
-    state.api_keys.lock().clone()
+) -> Result<Vec<ApiKeyEntry>, String> {
+    let records = state.api_keys.list().map_err(|e| e.to_string())?;
+    Ok(records
+        .iter()
+        .filter(|record| record.revoked_at.is_none())
+        .map(|record| ApiKeyEntry::from_record(record, format!("{}…", record.key_prefix)))
+        .collect())
 }
 
-/// Create new API key
+/// Create new API key; the full key is only returned here
 #[tauri::command]
 pub fn create_api_key(
     name: String,
+    limits: Option<ApiKeyLimits>,
     state: State<'_, AppState>
 ) -> Result<ApiKeyEntry, String> {
-    let new_key = format!("sk-williw-{}", Uuid::new_v4());
-    let entry = ApiKeyEntry {
-        id: Uuid::new_v4().to_string(),
-        name,
-        key: new_key.clone(),
-        created_at: Utc::now().to_rfc3339(),
-    };
-    
-    state.api_keys.lock().push(entry.clone());
-    
-    Ok(entry)
+    let issued = state
+        .api_keys
+        .issue(&name, limits.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    Ok(ApiKeyEntry::from_record(&issued.record, issued.secret))
 }
 
-/// Delete API key
+/// Delete (revoke) API key; usage records are kept for billing
 #[tauri::command]
 pub fn delete_api_key(
     id: String,
     state: State<'_, AppState>
 ) -> Result<String, String> {
-    let mut keys = state.api_keys.lock();
-    let initial_len = keys.len();
-    keys.retain(|k| k.id != id);
-    
-    if keys.len() < initial_len {
+    if state.api_keys.revoke(&id).map_err(|e| e.to_string())? {
         Ok("API key deleted successfully".to_string())
     } else {
         Err("API key not found".to_string())
     }
 }
 
+/// Change the scheduling priority class of an API key
+#[tauri::command]
+pub fn update_api_key_priority(
+    id: String,
+    priority: PriorityClass,
+    state: State<'_, AppState>
+) -> Result<String, String> {
+    if state.api_keys.set_priority(&id, priority).map_err(|e| e.to_string())? {
+        Ok("API key priority updated successfully".to_string())
+    } else {
+        Err("API key not found".to_string())
+    }
+}
+
+/// Inference queue depth and latency percentiles per priority class
+#[tauri::command]
+pub fn get_inference_scheduler_stats(
+    state: State<'_, AppState>
+) -> SchedulerStats {
+    state.inference_scheduler.stats()
+}
+
+/// Per-key inference usage in `[from, to)` (Unix seconds)
+#[tauri::command]
+pub fn get_api_usage(
+    from: i64,
+    to: i64,
+    state: State<'_, AppState>
+) -> Result<Vec<ApiUsageTotals>, String> {
+    let store = state.stats_store.as_ref().ok_or("统计数据库未启用")?;
+    store.api_usage_totals(from, to).map_err(|e| e.to_string())
+}
+
 /// Update API key name
 #[tauri::command]
 pub fn update_api_key_name(
@@ -192,10 +564,7 @@ pub fn update_api_key_name(
     new_name: String,
     state: State<'_, AppState>
 ) -> Result<String, String> {
-    let mut keys = state.api_keys.lock();
-    
-    if let Some(key) = keys.iter_mut().find(|k| k.id == id) {
-        key.name = new_name;
+    if state.api_keys.rename(&id, &new_name).map_err(|e| e.to_string())? {
         Ok("API key name updated successfully".to_string())
     } else {
         Err("API key not found".to_string())
@@ -1,30 +1,410 @@
-use tauri::{AppHandle, Emitter};
-use std::time::Duration;
+use crate::state::AppState;
+use model_downloader::DownloadProgress;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
 use tokio::time;
+use williw::comms::{get_global_event_manager, TransferEvent};
+use williw::stats::TrainingStats;
+
+/// Event topics the frontend can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+    /// `training-progress`
+    Training,
+    /// `transfer-progress`
+    Transfers,
+    /// `peer-joined` / `peer-left`
+    Peers,
+    /// `storage-warning`
+    Storage,
+}
+
+impl EventTopic {
+    pub const ALL: [EventTopic; 4] = [EventTopic::Training, EventTopic::Transfers, EventTopic::Peers, EventTopic::Storage];
+}
+
+/// 前端当前订阅的事件类别（默认全部订阅）
+#[derive(Clone)]
+pub struct EventSubscriptions(Arc<RwLock<BTreeSet<EventTopic>>>);
+
+impl Default for EventSubscriptions {
+    fn default() -> Self {
+        Self(Arc::new(RwLock::new(EventTopic::ALL.into_iter().collect())))
+    }
+}
+
+impl EventSubscriptions {
+    pub fn subscribe(&self, topics: &[EventTopic]) {
+        self.0.write().extend(topics.iter().copied());
+    }
+
+    pub fn unsubscribe(&self, topics: &[EventTopic]) {
+        let mut subscribed = self.0.write();
+        for topic in topics {
+            subscribed.remove(topic);
+        }
+    }
+
+    pub fn is_subscribed(&self, topic: EventTopic) -> bool {
+        self.0.read().contains(&topic)
+    }
+
+    pub fn topics(&self) -> Vec<EventTopic> {
+        self.0.read().iter().copied().collect()
+    }
+}
+
+/// Per-step training metrics
+#[derive(Debug, Clone, Serialize)]
+pub struct TrainingProgress {
+    pub step: u64,
+    pub epoch: u32,
+    pub loss: f64,
+    pub accuracy: f64,
+    pub samples_processed: u64,
+    /// 自上次推送以来的吞吐（样本/秒）
+    pub samples_per_sec: f64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerChange {
+    pub peer_id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelDownloadStatus {
+    Downloading,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// `model-download-progress` 事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDownloadEvent {
+    pub model_name: String,
+    pub status: ModelDownloadStatus,
+    pub progress: Option<DownloadProgress>,
+    pub error: Option<String>,
+}
+
+impl ModelDownloadEvent {
+    pub fn new(model_name: &str, status: ModelDownloadStatus) -> Self {
+        Self {
+            model_name: model_name.to_string(),
+            status,
+            progress: None,
+            error: None,
+        }
+    }
+}
+
+/// 事件推送目标；接收端已关闭时返回 false
+pub trait EventSink {
+    fn send<S: Serialize + Clone>(&self, event: &str, payload: S) -> bool;
+}
+
+impl EventSink for AppHandle {
+    fn send<S: Serialize + Clone>(&self, event: &str, payload: S) -> bool {
+        self.emit(event, payload).is_ok()
+    }
+}
+
+/// 由节点状态快照产生的前端事件
+#[derive(Debug, Clone)]
+pub enum NodeEvent {
+    Training(TrainingProgress),
+    PeerJoined(PeerChange),
+    PeerLeft(PeerChange),
+}
+
+impl NodeEvent {
+    /// 推送到前端；接收端已关闭时返回 false
+    pub fn emit(self, sink: &impl EventSink) -> bool {
+        match self {
+            NodeEvent::Training(progress) => sink.send("training-progress", progress),
+            NodeEvent::PeerJoined(change) => sink.send("peer-joined", change),
+            NodeEvent::PeerLeft(change) => sink.send("peer-left", change),
+        }
+    }
+}
+
+/// 比较相邻两次节点状态快照，只在有变化时产生事件
+#[derive(Default)]
+pub struct NodeEventTracker {
+    last_step: Option<(u64, u64, Instant)>,
+    known_peers: HashSet<String>,
+}
+
+impl NodeEventTracker {
+    /// `stats` 为 None 表示节点已停止，此时所有邻居视为离开
+    pub fn update(
+        &mut self,
+        stats: Option<&TrainingStats>,
+        peers: HashSet<String>,
+        epoch: u32,
+        now: Instant,
+        subs: &EventSubscriptions,
+    ) -> Vec<NodeEvent> {
+        let mut events = Vec::new();
+        if let Some(stats) = stats {
+            let advanced = self.last_step.is_none_or(|(step, _, _)| stats.tick_count > step);
+            if advanced && subs.is_subscribed(EventTopic::Training) {
+                let samples_per_sec = match self.last_step {
+                    Some((_, samples, at)) => {
+                        stats.samples_processed.saturating_sub(samples) as f64
+                            / now.duration_since(at).as_secs_f64().max(1e-3)
+                    }
+                    None => 0.0,
+                };
+                events.push(NodeEvent::Training(TrainingProgress {
+                    step: stats.tick_count,
+                    epoch,
+                    loss: stats.training_loss,
+                    accuracy: stats.training_accuracy,
+                    samples_processed: stats.samples_processed,
+                    samples_per_sec,
+                    timestamp: chrono::Utc::now().timestamp(),
+                }));
+            }
+            if advanced {
+                self.last_step = Some((stats.tick_count, stats.samples_processed, now));
+            }
+        } else {
+            self.last_step = None;
+        }
+
+        if subs.is_subscribed(EventTopic::Peers) {
+            let mut joined: Vec<String> = peers.difference(&self.known_peers).cloned().collect();
+            let mut left: Vec<String> = self.known_peers.difference(&peers).cloned().collect();
+            joined.sort();
+            left.sort();
+            events.extend(joined.into_iter().map(|peer_id| NodeEvent::PeerJoined(PeerChange { peer_id })));
+            events.extend(left.into_iter().map(|peer_id| NodeEvent::PeerLeft(PeerChange { peer_id })));
+        }
+        self.known_peers = peers;
+        events
+    }
+}
+
+/// 转发核心库的传输事件，事件源或前端任一端关闭时结束
+pub async fn forward_transfers(
+    mut events: mpsc::Receiver<TransferEvent>,
+    subs: EventSubscriptions,
+    sink: impl EventSink,
+) {
+    while let Some(event) = events.recv().await {
+        if subs.is_subscribed(EventTopic::Transfers) && !sink.send("transfer-progress", &event) {
+            break;
+        }
+    }
+}
 
 /// Setup event handlers for real-time updates
 pub fn setup_event_handlers(app_handle: AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    // Start background task to emit training status updates
-    tokio::spawn(async move {
+    let state = app_handle.state::<AppState>();
+    let node = state.node.clone();
+    let training_status = state.training_status.clone();
+    let subscriptions = state.event_subscriptions.clone();
+
+    // 训练指标与邻居变化：每秒读取一次节点状态，只在有变化时推送
+    let handle = app_handle.clone();
+    let subs = subscriptions.clone();
+    tauri::async_runtime::spawn(async move {
         let mut interval = time::interval(Duration::from_secs(1));
-        
+        let mut tracker = NodeEventTracker::default();
+
         loop {
             interval.tick().await;
-            
-            // Emit training status event
-            // In a real implementation, this would read from the actual node
-            // For now, we'll emit a placeholder event with timestamp only
-            
-            let _ = app_handle.emit("training-update", {
-                serde_json::json!({
-                    "timestamp": std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
+            let snapshot = {
+                let guard = node.lock();
+                guard.as_ref().map(|node| {
+                    let stats = node.stats.lock().ok().map(|stats| stats.get_stats().clone());
+                    let (primary, backup) = node.topology.neighbor_sets();
+                    (stats, primary.into_iter().chain(backup).collect::<HashSet<_>>())
                 })
-            });
+            };
+            let (stats, peers) = snapshot.unwrap_or_default();
+            let epoch = training_status.lock().current_epoch;
+            for event in tracker.update(stats.as_ref(), peers, epoch, Instant::now(), &subs) {
+                if !event.emit(&handle) {
+                    return;
+                }
+            }
         }
     });
 
+    // 磁盘空间：定期检查，可用空间不足时推送警告
+    let handle = app_handle.clone();
+    let storage = state.storage.clone();
+    let subs = subscriptions.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(storage.config().check_interval_secs.max(10)));
+        loop {
+            interval.tick().await;
+            let governor = storage.clone();
+            let Ok(warnings) = tauri::async_runtime::spawn_blocking(move || governor.check()).await else {
+                continue;
+            };
+            if subs.is_subscribed(EventTopic::Storage) {
+                for warning in warnings {
+                    let _ = handle.emit("storage-warning", &warning);
+                }
+            }
+        }
+    });
+
+    // P2P 传输进度：转发核心库的全局传输事件
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let events = get_global_event_manager().add_listener().await;
+        forward_transfers(events, subscriptions, handle).await;
+    });
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// 把推送的事件序列化成前端收到的 JSON
+    struct ChannelSink(mpsc::UnboundedSender<(String, Value)>);
+
+    impl EventSink for ChannelSink {
+        fn send<S: Serialize + Clone>(&self, event: &str, payload: S) -> bool {
+            let payload = serde_json::to_value(payload).unwrap();
+            self.0.send((event.to_string(), payload)).is_ok()
+        }
+    }
+
+    fn stats(step: u64, samples: u64) -> TrainingStats {
+        TrainingStats {
+            tick_count: step,
+            samples_processed: samples,
+            training_loss: 0.5,
+            training_accuracy: 0.75,
+            ..TrainingStats::default()
+        }
+    }
+
+    fn peers(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn emitted(events: Vec<NodeEvent>) -> Vec<(String, Value)> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sink = ChannelSink(tx);
+        for event in events {
+            assert!(event.emit(&sink));
+        }
+        drop(sink);
+        let mut out = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            out.push(event);
+        }
+        out
+    }
+
+    #[test]
+    fn test_node_snapshots_become_payloads() {
+        let subs = EventSubscriptions::default();
+        let mut tracker = NodeEventTracker::default();
+        let start = Instant::now();
+
+        let events = emitted(tracker.update(Some(&stats(3, 30)), peers(&["a", "b"]), 2, start, &subs));
+        assert_eq!(events.len(), 3);
+        let (name, progress) = &events[0];
+        assert_eq!(name, "training-progress");
+        assert_eq!(progress["step"], 3);
+        assert_eq!(progress["epoch"], 2);
+        assert_eq!(progress["loss"], 0.5);
+        assert_eq!(progress["accuracy"], 0.75);
+        assert_eq!(progress["samples_processed"], 30);
+        assert_eq!(progress["samples_per_sec"], 0.0);
+        assert_eq!(events[1], ("peer-joined".to_string(), json!({ "peer_id": "a" })));
+        assert_eq!(events[2], ("peer-joined".to_string(), json!({ "peer_id": "b" })));
+
+        // 步数未推进时不推送训练事件，只推送邻居变化
+        let events = emitted(tracker.update(
+            Some(&stats(3, 30)),
+            peers(&["b", "c"]),
+            2,
+            start + Duration::from_secs(2),
+            &subs,
+        ));
+        assert_eq!(
+            events,
+            vec![
+                ("peer-joined".to_string(), json!({ "peer_id": "c" })),
+                ("peer-left".to_string(), json!({ "peer_id": "a" })),
+            ]
+        );
+
+        let events = emitted(tracker.update(
+            Some(&stats(5, 50)),
+            peers(&["b", "c"]),
+            2,
+            start + Duration::from_secs(4),
+            &subs,
+        ));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].1["step"], 5);
+        assert_eq!(events[0].1["samples_per_sec"], 5.0);
+
+        // 取消订阅的类别不再推送；节点停止时邻居视为离开
+        subs.unsubscribe(&[EventTopic::Training]);
+        let events = emitted(tracker.update(None, HashSet::new(), 2, start + Duration::from_secs(5), &subs));
+        assert_eq!(
+            events,
+            vec![
+                ("peer-left".to_string(), json!({ "peer_id": "b" })),
+                ("peer-left".to_string(), json!({ "peer_id": "c" })),
+            ]
+        );
+        assert!(tracker.update(Some(&stats(6, 60)), HashSet::new(), 2, start, &subs).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_forwarding_ends_when_receiver_dropped() {
+        let (events_tx, events_rx) = mpsc::channel(8);
+        let (sink_tx, mut sink_rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(forward_transfers(events_rx, EventSubscriptions::default(), ChannelSink(sink_tx)));
+
+        let progress = TransferEvent::ProgressUpdate {
+            transfer_id: "t1".to_string(),
+            progress: 0.5,
+            speed_bps: 1024,
+        };
+        events_tx.send(progress.clone()).await.unwrap();
+        assert_eq!(
+            sink_rx.recv().await.unwrap(),
+            (
+                "transfer-progress".to_string(),
+                json!({ "ProgressUpdate": { "transfer_id": "t1", "progress": 0.5, "speed_bps": 1024 } })
+            )
+        );
+
+        // 前端接收端关闭后，下一个事件到达时转发任务结束
+        drop(sink_rx);
+        events_tx.send(progress).await.unwrap();
+        time::timeout(Duration::from_secs(1), task).await.unwrap().unwrap();
+        assert!(events_tx.is_closed());
+
+        // 事件源关闭时同样结束
+        let (events_tx, events_rx) = mpsc::channel::<TransferEvent>(8);
+        let (sink_tx, _sink_rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(forward_transfers(events_rx, EventSubscriptions::default(), ChannelSink(sink_tx)));
+        drop(events_tx);
+        time::timeout(Duration::from_secs(1), task).await.unwrap().unwrap();
+    }
+}
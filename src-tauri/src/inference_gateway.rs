@@ -0,0 +1,228 @@
+//! 对外推理网关
+//!
+//! 外部客户端携带 `Authorization: Bearer sk-williw-...` 访问 `POST /v1/infer` 或
+//! OpenAI 兼容的 `POST /v1/embeddings`，
+//! 网关校验密钥、限流和 token 配额后按密钥的优先级类别排队，再把请求转发给本机 GPU 推理服务
+//! （只监听 127.0.0.1），完成后按实际输入/输出记账。
+
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use williw::training::{estimate_tokens, Admission, ApiKeyError, ApiKeyManager, InferenceScheduler};
+
+pub const GATEWAY_ADDR: &str = "0.0.0.0:8010";
+/// 本机 GPU 推理服务
+pub const UPSTREAM_BASE: &str = "http://127.0.0.1:8000";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferRequest {
+    pub model_path: String,
+    pub input_text: String,
+    #[serde(default = "default_max_length")]
+    pub max_length: u64,
+}
+
+fn default_max_length() -> u64 {
+    100
+}
+
+/// 单条文本或一批文本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl EmbeddingInput {
+    fn texts(&self) -> Vec<&str> {
+        match self {
+            EmbeddingInput::One(text) => vec![text.as_str()],
+            EmbeddingInput::Many(texts) => texts.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// OpenAI 兼容的向量请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub input: EmbeddingInput,
+}
+
+/// GPU 推理服务 `/embeddings` 的响应
+#[derive(Debug, Deserialize)]
+struct UpstreamEmbeddings {
+    model: String,
+    dimensions: usize,
+    embeddings: Vec<Vec<f32>>,
+    prompt_tokens: u64,
+}
+
+#[derive(Clone)]
+struct GatewayState {
+    keys: Arc<ApiKeyManager>,
+    scheduler: InferenceScheduler,
+    upstream_base: String,
+    client: reqwest::Client,
+}
+
+pub async fn serve(
+    keys: Arc<ApiKeyManager>,
+    scheduler: InferenceScheduler,
+    addr: &str,
+    upstream_base: String,
+) -> anyhow::Result<()> {
+    let state = GatewayState {
+        keys,
+        scheduler,
+        upstream_base,
+        client: reqwest::Client::new(),
+    };
+    let app = Router::new()
+        .route("/v1/infer", post(infer))
+        .route("/v1/embeddings", post(embeddings))
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("推理网关监听 http://{}", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "status": "error", "message": message }))).into_response()
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// 认证并检查配额，失败时直接给出错误响应
+fn admit(state: &GatewayState, headers: &HeaderMap, estimated_tokens: u64) -> Result<Admission, Response> {
+    let Some(secret) = bearer_token(headers) else {
+        return Err(error_response(StatusCode::UNAUTHORIZED, ApiKeyError::Invalid.to_string()));
+    };
+    state.keys.admit(secret, estimated_tokens).map_err(|e| {
+        let status = StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = error_response(status, e.to_string());
+        if let ApiKeyError::RateLimited { retry_after_secs } = e {
+            response
+                .headers_mut()
+                .insert("retry-after", HeaderValue::from(retry_after_secs));
+        }
+        response
+    })
+}
+
+async fn infer(State(state): State<GatewayState>, headers: HeaderMap, Json(request): Json<InferRequest>) -> Response {
+    // 按输入长度加最大生成长度预估，避免一个请求冲过配额
+    let prompt_tokens = estimate_tokens(&request.input_text);
+    let admission = match admit(&state, &headers, prompt_tokens + request.max_length) {
+        Ok(admission) => admission,
+        Err(response) => return response,
+    };
+
+    // 上游推理服务一次只转发有限个请求，交互式密钥优先，同类别内各租户公平分配
+    let _permit = state.scheduler.acquire(&admission.key.id, admission.priority()).await;
+    let url = format!("{}/infer", state.upstream_base);
+    let upstream = match state.client.post(&url).json(&request).send().await {
+        Ok(response) => response,
+        Err(e) => return error_response(StatusCode::BAD_GATEWAY, format!("推理服务不可用: {}", e)),
+    };
+    let status = StatusCode::from_u16(upstream.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let body: serde_json::Value = match upstream.json().await {
+        Ok(body) => body,
+        Err(e) => return error_response(StatusCode::BAD_GATEWAY, format!("推理服务响应无效: {}", e)),
+    };
+
+    // 只对成功的推理记账
+    let mut tokens_remaining = admission.tokens_remaining;
+    if status.is_success() && body.get("status").and_then(|s| s.as_str()) == Some("success") {
+        let completion_tokens = body
+            .get("result")
+            .and_then(|r| r.as_str())
+            .map(estimate_tokens)
+            .unwrap_or(0);
+        match state
+            .keys
+            .record_usage(&admission, &request.model_path, prompt_tokens, completion_tokens)
+        {
+            Ok(usage) => {
+                tokens_remaining =
+                    tokens_remaining.map(|left| left.saturating_sub(usage.prompt_tokens + usage.completion_tokens));
+            }
+            Err(e) => eprintln!("推理用量记账失败: {}", e),
+        }
+    }
+
+    let mut response = (status, Json(body)).into_response();
+    if let Some(left) = tokens_remaining {
+        response
+            .headers_mut()
+            .insert("x-williw-tokens-remaining", HeaderValue::from(left));
+    }
+    response
+}
+
+async fn embeddings(
+    State(state): State<GatewayState>,
+    headers: HeaderMap,
+    Json(request): Json<EmbeddingsRequest>,
+) -> Response {
+    let texts = request.input.texts();
+    if texts.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "input 不能为空".to_string());
+    }
+    let estimated: u64 = texts.iter().map(|t| estimate_tokens(t)).sum();
+    let admission = match admit(&state, &headers, estimated) {
+        Ok(admission) => admission,
+        Err(response) => return response,
+    };
+
+    let _permit = state.scheduler.acquire(&admission.key.id, admission.priority()).await;
+    let url = format!("{}/embeddings", state.upstream_base);
+    let body = serde_json::json!({ "model_path": request.model, "input": texts });
+    let upstream = match state.client.post(&url).json(&body).send().await {
+        Ok(response) => response,
+        Err(e) => return error_response(StatusCode::BAD_GATEWAY, format!("推理服务不可用: {}", e)),
+    };
+    if !upstream.status().is_success() {
+        let status = StatusCode::from_u16(upstream.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+        let detail = upstream.text().await.unwrap_or_default();
+        return error_response(status, format!("向量计算失败: {}", detail));
+    }
+    let result: UpstreamEmbeddings = match upstream.json().await {
+        Ok(result) => result,
+        Err(e) => return error_response(StatusCode::BAD_GATEWAY, format!("推理服务响应无效: {}", e)),
+    };
+
+    if let Err(e) = state.keys.record_usage(&admission, &result.model, result.prompt_tokens, 0) {
+        eprintln!("向量用量记账失败: {}", e);
+    }
+    let data: Vec<_> = result
+        .embeddings
+        .into_iter()
+        .enumerate()
+        .map(|(index, embedding)| serde_json::json!({ "object": "embedding", "index": index, "embedding": embedding }))
+        .collect();
+    let mut response = Json(serde_json::json!({
+        "object": "list",
+        "data": data,
+        "model": result.model,
+        "usage": { "prompt_tokens": result.prompt_tokens, "total_tokens": result.prompt_tokens },
+    }))
+    .into_response();
+    response
+        .headers_mut()
+        .insert("x-williw-embedding-dimensions", HeaderValue::from(result.dimensions));
+    response
+}
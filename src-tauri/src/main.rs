@@ -5,6 +5,8 @@ mod commands;
 mod state;
 mod events;
 mod api_client;
+mod inference_gateway;
+mod tray;
 
 use tauri::Emitter;
 use state::AppState;
@@ -12,6 +14,8 @@ use state::AppState;
 #[tokio::main]
 async fn main() {
     let app_state = AppState::new().await;
+    let api_keys = app_state.api_keys.clone();
+    let inference_scheduler = app_state.inference_scheduler.clone();
 
     tauri::Builder::default()
         .manage(app_state)
@@ -22,14 +26,33 @@ async fn main() {
             commands::get_training_status,
             commands::select_model,
             commands::get_available_models,
+            commands::download_model,
+            commands::cancel_download,
+            commands::list_local_models,
+            commands::delete_local_model,
             commands::get_device_info,
             commands::get_training_stats,
+            commands::query_tick_metrics,
+            commands::get_transfer_history,
+            commands::get_transfer_totals,
+            commands::get_pending_submissions,
+            commands::get_chain_records,
+            commands::get_chain_events,
+            commands::get_payout_checks,
             commands::update_settings,
             commands::get_settings,
+            commands::get_resource_caps,
+            commands::set_resource_caps,
             commands::get_api_keys,
             commands::create_api_key,
             commands::delete_api_key,
             commands::update_api_key_name,
+            commands::get_api_usage,
+            commands::update_api_key_priority,
+            commands::get_inference_scheduler_stats,
+            commands::subscribe_events,
+            commands::unsubscribe_events,
+            commands::get_event_subscriptions,
             commands::get_node_info,
             commands::get_connected_peers,
             commands::upload_device_info_to_workers,
@@ -43,9 +66,29 @@ async fn main() {
             commands::check_gpu_server_status,
             commands::install_gpu_dependencies,
         ])
+        .on_window_event(|window, event| {
+            // 托盘模式：关闭主窗口只隐藏，训练在后台继续，从托盘菜单退出
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                let _ = window.hide();
+                api.prevent_close();
+            }
+        })
         .setup(|app| {
             // Initialize event handlers
             events::setup_event_handlers(app.handle().clone())?;
+            tray::setup_tray(app.handle())?;
+
+            // 对外的推理网关：认证 API 密钥、限流和配额后转发给本机 GPU 推理服务
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = inference_gateway::serve(
+                    api_keys,
+                    inference_scheduler,
+                    inference_gateway::GATEWAY_ADDR,
+                    inference_gateway::UPSTREAM_BASE.to_string(),
+                ).await {
+                    eprintln!("推理网关启动失败: {}", e);
+                }
+            });
 
             // Start background task to refresh device info every minute
             let app_handle = app.handle().clone();
@@ -1,7 +1,15 @@
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use williw::device::{PowerPolicy, ResourceCaps};
+use williw::model_cache::{ModelCache, ModelCacheConfig};
+use williw::storage::{StorageConfig, StorageGovernor};
+use williw::stats::{ApiKeyRecord, StatsStore};
+use williw::training::{ApiKeyManager, InferenceScheduler, SchedulerConfig, SubmissionQueueHook};
 use williw::Node;
+use crate::events::EventSubscriptions;
 
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +18,9 @@ pub struct AppSettings {
     pub bandwidth_budget: u32,  // MB/s
     pub network_config: NetworkConfig,
     pub checkpoint_settings: CheckpointSettings,
+    /// 托盘设置的资源上限（暂停、CPU 占用、仅 Wi-Fi）
+    #[serde(default)]
+    pub resource_caps: ResourceCaps,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,10 +52,34 @@ impl Default for AppSettings {
                 interval_minutes: 5,
                 max_checkpoints: 10,
             },
+            resource_caps: ResourceCaps::default(),
         }
     }
 }
 
+/// 设置文件，保存在工作目录下
+pub const SETTINGS_FILE: &str = "williw_settings.json";
+
+impl AppSettings {
+    /// 读取保存的设置，文件不存在或无法解析时使用默认值
+    pub fn load(path: &str) -> Self {
+        match std::fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                eprintln!("Failed to parse settings file {}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        let tmp = format!("{}.tmp", path);
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
 /// Available model configurations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
@@ -106,13 +141,55 @@ pub struct DeviceInfo {
     pub is_charging: Option<bool>,
 }
 
+/// A file of a locally downloaded model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalModelFile {
+    pub name: String,
+    pub size: u64,
+    /// 模型缓存中的内容哈希
+    pub digest: String,
+    pub pinned: bool,
+}
+
+/// A model downloaded into the local model cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalModel {
+    pub name: String,
+    pub path: String,
+    pub files: Vec<LocalModelFile>,
+    pub total_bytes: u64,
+    pub last_access: i64,
+}
+
 /// API Key entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKeyEntry {
     pub id: String,
     pub name: String,
+    /// 新签发时为完整密钥，列表中只显示前缀
     pub key: String,
     pub created_at: String,
+    pub requests_per_minute: u32,
+    pub token_quota: u64,
+    pub priority_class: String,
+    pub revoked: bool,
+}
+
+impl ApiKeyEntry {
+    pub fn from_record(record: &ApiKeyRecord, key: String) -> Self {
+        Self {
+            id: record.id.clone(),
+            name: record.name.clone(),
+            key,
+            created_at: chrono::DateTime::from_timestamp(record.created_at, 0)
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default(),
+            requests_per_minute: record.requests_per_minute,
+            token_quota: record.token_quota,
+            priority_class: record.priority_class.clone(),
+            revoked: record.revoked_at.is_some(),
+        }
+    }
 }
 
 /// Global application state
@@ -122,8 +199,23 @@ pub struct AppState {
     pub node: Arc<Mutex<Option<Node>>>,  // 使用真实的Node
     pub available_models: Arc<Mutex<Vec<ModelConfig>>>,
     pub device_info: Arc<Mutex<Option<DeviceInfo>>>,
-    pub api_keys: Arc<Mutex<Vec<ApiKeyEntry>>>,
+    /// 推理 API 密钥（哈希存储在统计数据库中）
+    pub api_keys: Arc<ApiKeyManager>,
+    /// 推理网关的优先级调度器
+    pub inference_scheduler: InferenceScheduler,
+    /// 前端订阅的实时事件类别
+    pub event_subscriptions: EventSubscriptions,
+    /// 本地模型缓存（打开失败时为 None）
+    pub model_cache: Option<Arc<ModelCache>>,
+    /// 下载前的磁盘空间预检和缓存自动淘汰
+    pub storage: Arc<StorageGovernor>,
+    /// 进行中的模型下载：模型名 -> 取消标记
+    pub downloads: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    /// 与训练节点共享的资源策略
+    pub power_policy: Arc<PowerPolicy>,
     pub api_client: crate::api_client::WorkersApiClient,
+    /// 本地统计数据库（打开失败时为 None）
+    pub stats_store: Option<Arc<StatsStore>>,
 }
 
 impl AppState {
@@ -199,16 +291,53 @@ impl AppState {
         // Get device info
         let device_info = Self::get_device_info_internal();
 
+        let settings = AppSettings::load(SETTINGS_FILE);
+        let power_policy = Arc::new(PowerPolicy::new(settings.resource_caps.clone()));
+
+        let stats_store = match StatsStore::open("williw_stats.db") {
+            Ok(store) => Some(Arc::new(store)),
+            Err(e) => {
+                eprintln!("Failed to open stats database: {}", e);
+                None
+            }
+        };
+
+        let model_cache = match ModelCache::open(ModelCacheConfig::default()) {
+            Ok(cache) => Some(Arc::new(cache)),
+            Err(e) => {
+                eprintln!("Failed to open model cache: {}", e);
+                None
+            }
+        };
+
+        let storage = Arc::new(StorageGovernor::new(StorageConfig::default(), model_cache.clone()));
+
+        // 统计数据库不可用时密钥只保存在内存中，重启后失效
+        let key_store = match &stats_store {
+            Some(store) => store.clone(),
+            None => Arc::new(StatsStore::open_in_memory().expect("内存数据库不可用")),
+        };
+        let api_keys = Arc::new(
+            ApiKeyManager::new(key_store.clone()).with_hook(Arc::new(SubmissionQueueHook::new(key_store))),
+        );
+
         Self {
-            settings: Arc::new(Mutex::new(AppSettings::default())),
+            settings: Arc::new(Mutex::new(settings)),
             training_status: Arc::new(Mutex::new(TrainingStatus::default())),
             node: Arc::new(Mutex::new(None)),  // 真实的Node，初始为None
             available_models: Arc::new(Mutex::new(models)),
             device_info: Arc::new(Mutex::new(Some(device_info))),
-            api_keys: Arc::new(Mutex::new(vec![])),
+            api_keys,
+            inference_scheduler: InferenceScheduler::new(SchedulerConfig::default()),
+            event_subscriptions: EventSubscriptions::default(),
+            model_cache,
+            storage,
+            downloads: Arc::new(Mutex::new(HashMap::new())),
+            power_policy,
             api_client: crate::api_client::WorkersApiClient::new(
                 "https://williw.sirazede725.workers.dev".to_string()
             ),
+            stats_store,
         }
     }
 
@@ -0,0 +1,123 @@
+use crate::state::{AppState, SETTINGS_FILE};
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use williw::device::ResourceCaps;
+
+/// 托盘菜单中可选的 CPU 占用上限
+const CPU_LIMITS: [u8; 4] = [100, 75, 50, 25];
+
+/// 需要与资源上限保持同步的托盘菜单项
+struct TrayMenu {
+    pause: CheckMenuItem<Wry>,
+    cpu_limits: Vec<(u8, CheckMenuItem<Wry>)>,
+    wifi_only: CheckMenuItem<Wry>,
+}
+
+impl TrayMenu {
+    fn sync(&self, caps: &ResourceCaps) -> tauri::Result<()> {
+        self.pause.set_checked(caps.paused)?;
+        for (percent, item) in &self.cpu_limits {
+            item.set_checked(*percent == caps.cpu_limit_percent)?;
+        }
+        self.wifi_only.set_checked(caps.wifi_only)
+    }
+}
+
+/// Create the system tray; closing the main window keeps the app running in the tray
+pub fn setup_tray(app: &AppHandle) -> tauri::Result<()> {
+    let caps = app.state::<AppState>().power_policy.caps();
+
+    let pause = CheckMenuItem::with_id(app, "pause", "暂停训练", true, caps.paused, None::<&str>)?;
+    let cpu_limits = CPU_LIMITS
+        .iter()
+        .map(|&percent| {
+            CheckMenuItem::with_id(
+                app,
+                format!("cpu_{}", percent),
+                format!("{}%", percent),
+                true,
+                percent == caps.cpu_limit_percent,
+                None::<&str>,
+            )
+            .map(|item| (percent, item))
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let cpu_items: Vec<&dyn IsMenuItem<Wry>> = cpu_limits.iter().map(|(_, item)| item as &dyn IsMenuItem<Wry>).collect();
+    let cpu_menu = Submenu::with_items(app, "CPU 占用上限", true, &cpu_items)?;
+    let wifi_only = CheckMenuItem::with_id(app, "wifi_only", "仅 Wi-Fi 同步", true, caps.wifi_only, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &pause,
+            &cpu_menu,
+            &wifi_only,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &quit,
+        ],
+    )?;
+
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip("Williw")
+        .menu(&menu)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+
+    app.manage(TrayMenu {
+        pause,
+        cpu_limits,
+        wifi_only,
+    });
+    Ok(())
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let mut caps = app.state::<AppState>().power_policy.caps();
+    match event.id().as_ref() {
+        "pause" => caps.paused = !caps.paused,
+        "wifi_only" => caps.wifi_only = !caps.wifi_only,
+        "show" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            return;
+        }
+        "quit" => {
+            app.exit(0);
+            return;
+        }
+        id => match id.strip_prefix("cpu_").and_then(|percent| percent.parse().ok()) {
+            Some(percent) => caps.cpu_limit_percent = percent,
+            None => return,
+        },
+    }
+    if let Err(e) = apply_resource_caps(app, caps) {
+        eprintln!("Failed to apply resource caps: {}", e);
+    }
+}
+
+/// 应用资源上限：更新共享的电源策略（运行中的节点下一轮 tick 生效）、保存设置、同步托盘菜单并通知前端
+pub fn apply_resource_caps(app: &AppHandle, caps: ResourceCaps) -> anyhow::Result<ResourceCaps> {
+    let state = app.state::<AppState>();
+    state.power_policy.set_caps(caps);
+    let caps = state.power_policy.caps();
+    {
+        let mut settings = state.settings.lock();
+        settings.resource_caps = caps.clone();
+        settings.save(SETTINGS_FILE)?;
+    }
+
+    // 勾选项点击时会自行切换，按实际状态重新同步
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        menu.sync(&caps)?;
+    }
+    let _ = app.emit("resource-caps-changed", &caps);
+    Ok(caps)
+}
@@ -0,0 +1,620 @@
+//! 节点运维管理接口
+//!
+//! 只监听本机地址的 REST 接口，供编排工具批量管理节点，不必通过桌面端：
+//! - `GET/PUT /admin/v1/config`：读取或修改可热更新的配置项（经 [`ConfigWatcher::apply`] 校验后生效）
+//! - `GET /admin/v1/peers`、`/transfers`、`/stake`：邻居、传输会话和质押状态
+//! - `GET/PUT /admin/v1/log-level`：调整日志级别
+//! - `GET /admin/v1/chain/{nodes|contributions|rewards|proposals}`、`/chain/events`：链上状态同步到本地统计库的记录
+//!
+//! 请求需携带 `Authorization: Bearer <令牌>`。配置中只保存令牌的 blake3 哈希，每个令牌绑定一个角色，
+//! 角色决定可执行的操作（只读 < 运维 < 管理员）。HTTP 服务需启用 `admin-api` 特性。
+
+use crate::comms::{StakeAttestation, TransferEvent};
+use crate::config_watch::ConfigWatcher;
+use crate::stats::{PeerSample, StatsStore};
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+/// 环境变量中的令牌拥有管理员角色，便于编排工具注入
+pub const ADMIN_TOKEN_ENV: &str = "WILLIW_ADMIN_TOKEN";
+
+/// 保留的已结束传输会话数
+const MAX_FINISHED_TRANSFERS: usize = 256;
+
+/// 管理接口角色，按权限从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// 只读：查看配置、邻居、传输和质押状态
+    Viewer,
+    /// 运维：额外可以调整日志级别
+    Operator,
+    /// 管理员：额外可以修改配置
+    Admin,
+}
+
+/// 管理操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ReadStatus,
+    ManageLogs,
+    WriteConfig,
+}
+
+impl AdminRole {
+    pub fn allows(self, permission: Permission) -> bool {
+        let required = match permission {
+            Permission::ReadStatus => AdminRole::Viewer,
+            Permission::ManageLogs => AdminRole::Operator,
+            Permission::WriteConfig => AdminRole::Admin,
+        };
+        self >= required
+    }
+}
+
+/// 一个管理令牌
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdminTokenConfig {
+    /// 用于审计日志的名称
+    pub name: String,
+    /// 令牌的 blake3 哈希（hex）
+    pub token_hash: String,
+    pub role: AdminRole,
+}
+
+/// 管理接口配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    pub enabled: bool,
+    /// 监听地址，必须是本机回环地址
+    pub bind: String,
+    pub tokens: Vec<AdminTokenConfig>,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1:9470".to_string(),
+            tokens: Vec::new(),
+        }
+    }
+}
+
+pub fn hash_token(token: &str) -> String {
+    blake3::hash(token.as_bytes()).to_hex().to_string()
+}
+
+/// 令牌认证
+#[derive(Debug, Clone, Default)]
+pub struct AdminAuth {
+    tokens: Vec<AdminTokenConfig>,
+}
+
+impl AdminAuth {
+    /// 配置中的令牌，加上环境变量 [`ADMIN_TOKEN_ENV`] 中的管理员令牌（如果设置了）
+    pub fn from_config(config: &AdminConfig) -> Self {
+        let mut tokens = config.tokens.clone();
+        if let Ok(token) = std::env::var(ADMIN_TOKEN_ENV) {
+            if !token.is_empty() {
+                tokens.push(AdminTokenConfig {
+                    name: ADMIN_TOKEN_ENV.to_string(),
+                    token_hash: hash_token(&token),
+                    role: AdminRole::Admin,
+                });
+            }
+        }
+        Self { tokens }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// 校验令牌，返回令牌名称和角色
+    pub fn authenticate(&self, token: &str) -> Option<(&str, AdminRole)> {
+        let hash = hash_token(token);
+        self.tokens
+            .iter()
+            .find(|entry| bool::from(entry.token_hash.to_ascii_lowercase().as_bytes().ct_eq(hash.as_bytes())))
+            .map(|entry| (entry.name.as_str(), entry.role))
+    }
+}
+
+/// 节点定期发布的状态快照，供管理接口读取（节点运行时持有自身，接口无法直接访问）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NodeStatus {
+    pub node_id: String,
+    pub tick: u64,
+    pub peers: Vec<PeerSample>,
+    pub stake: Option<StakeAttestation>,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Default)]
+pub struct NodeStatusBoard {
+    status: RwLock<NodeStatus>,
+}
+
+impl NodeStatusBoard {
+    pub fn publish(&self, status: NodeStatus) {
+        *self.status.write() = status;
+    }
+
+    pub fn snapshot(&self) -> NodeStatus {
+        self.status.read().clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferState {
+    Active,
+    Completed,
+    Failed,
+}
+
+/// 一个 P2P 传输会话
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransferSessionInfo {
+    pub transfer_id: String,
+    pub file_name: String,
+    pub peer_id: String,
+    pub state: TransferState,
+    pub progress: f32,
+    pub speed_bps: u64,
+    pub file_size: Option<u64>,
+    pub error: Option<String>,
+    pub updated_at: i64,
+}
+
+/// 根据全局传输事件维护的会话表
+#[derive(Debug, Default)]
+pub struct TransferSessions {
+    sessions: RwLock<BTreeMap<String, TransferSessionInfo>>,
+}
+
+impl TransferSessions {
+    pub fn record(&self, event: &TransferEvent) {
+        let now = chrono::Utc::now().timestamp();
+        let mut sessions = self.sessions.write();
+        let transfer_id = match event {
+            TransferEvent::TransferStarted {
+                transfer_id,
+                file_name,
+                peer_id,
+            } => {
+                sessions.insert(
+                    transfer_id.clone(),
+                    TransferSessionInfo {
+                        transfer_id: transfer_id.clone(),
+                        file_name: file_name.clone(),
+                        peer_id: peer_id.clone(),
+                        state: TransferState::Active,
+                        progress: 0.0,
+                        speed_bps: 0,
+                        file_size: None,
+                        error: None,
+                        updated_at: now,
+                    },
+                );
+                return;
+            }
+            TransferEvent::ProgressUpdate { transfer_id, .. }
+            | TransferEvent::TransferCompleted { transfer_id, .. }
+            | TransferEvent::TransferFailed { transfer_id, .. } => transfer_id,
+            TransferEvent::PeerConnectionChanged { .. } => return,
+        };
+        // 没收到开始事件的会话（接口启动前开始的传输）也记录下来
+        let session = sessions.entry(transfer_id.clone()).or_insert_with(|| TransferSessionInfo {
+            transfer_id: transfer_id.clone(),
+            file_name: String::new(),
+            peer_id: String::new(),
+            state: TransferState::Active,
+            progress: 0.0,
+            speed_bps: 0,
+            file_size: None,
+            error: None,
+            updated_at: now,
+        });
+        session.updated_at = now;
+        match event {
+            TransferEvent::ProgressUpdate { progress, speed_bps, .. } => {
+                session.progress = *progress;
+                session.speed_bps = *speed_bps;
+            }
+            TransferEvent::TransferCompleted { file_size, .. } => {
+                session.state = TransferState::Completed;
+                session.progress = 1.0;
+                session.file_size = Some(*file_size);
+            }
+            TransferEvent::TransferFailed { error, .. } => {
+                session.state = TransferState::Failed;
+                session.error = Some(error.clone());
+            }
+            _ => {}
+        }
+
+        // 只保留最近结束的会话
+        let mut finished: Vec<(i64, String)> = sessions
+            .values()
+            .filter(|session| session.state != TransferState::Active)
+            .map(|session| (session.updated_at, session.transfer_id.clone()))
+            .collect();
+        if finished.len() > MAX_FINISHED_TRANSFERS {
+            finished.sort();
+            for (_, id) in &finished[..finished.len() - MAX_FINISHED_TRANSFERS] {
+                sessions.remove(id);
+            }
+        }
+    }
+
+    /// 所有会话，进行中的在前，其余按更新时间倒序
+    pub fn list(&self) -> Vec<TransferSessionInfo> {
+        let mut sessions: Vec<TransferSessionInfo> = self.sessions.read().values().cloned().collect();
+        sessions.sort_by(|a, b| {
+            (b.state == TransferState::Active)
+                .cmp(&(a.state == TransferState::Active))
+                .then(b.updated_at.cmp(&a.updated_at))
+        });
+        sessions
+    }
+
+    /// 订阅全局传输事件并持续更新会话表
+    pub fn spawn(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let sessions = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut events = crate::comms::get_global_event_manager().add_listener().await;
+            while let Some(event) = events.recv().await {
+                sessions.record(&event);
+            }
+        })
+    }
+}
+
+/// 运行时调整日志级别（由二进制在初始化日志时提供具体实现）
+pub struct LogLevelControl {
+    current: RwLock<String>,
+    apply: Box<dyn Fn(&str) -> Result<()> + Send + Sync>,
+}
+
+impl LogLevelControl {
+    pub fn new(initial: impl Into<String>, apply: impl Fn(&str) -> Result<()> + Send + Sync + 'static) -> Self {
+        Self {
+            current: RwLock::new(initial.into()),
+            apply: Box::new(apply),
+        }
+    }
+
+    pub fn current(&self) -> String {
+        self.current.read().clone()
+    }
+
+    pub fn set(&self, level: &str) -> Result<()> {
+        (self.apply)(level)?;
+        *self.current.write() = level.to_string();
+        Ok(())
+    }
+}
+
+/// 管理接口依赖的节点句柄
+pub struct AdminContext {
+    pub auth: AdminAuth,
+    pub config: Arc<ConfigWatcher>,
+    pub status: Arc<NodeStatusBoard>,
+    pub transfers: Arc<TransferSessions>,
+    pub log_level: Arc<LogLevelControl>,
+    /// 本地统计库，未启用时链上记录接口返回错误
+    pub stats: Option<Arc<StatsStore>>,
+}
+
+impl AdminContext {
+    /// 校验 `Authorization` 头并检查角色权限，返回令牌名称
+    pub fn authorize(&self, authorization: Option<&str>, permission: Permission) -> Result<String, AdminError> {
+        let token = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(AdminError::Unauthorized)?;
+        let (name, role) = self.auth.authenticate(token.trim()).ok_or(AdminError::Unauthorized)?;
+        if !role.allows(permission) {
+            return Err(AdminError::Forbidden);
+        }
+        Ok(name.to_string())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdminError {
+    #[error("缺少或无效的管理令牌")]
+    Unauthorized,
+    #[error("当前角色无权执行该操作")]
+    Forbidden,
+    #[error("{0}")]
+    BadRequest(String),
+}
+
+/// 管理接口只允许监听回环地址
+pub fn parse_bind(bind: &str) -> Result<std::net::SocketAddr> {
+    let addr: std::net::SocketAddr = bind.parse().map_err(|e| anyhow!("无效的监听地址 {}: {}", bind, e))?;
+    if !addr.ip().is_loopback() {
+        return Err(anyhow!("管理接口只能监听本机回环地址，当前为 {}", addr));
+    }
+    Ok(addr)
+}
+
+#[cfg(feature = "admin-api")]
+pub use server::serve;
+
+#[cfg(feature = "admin-api")]
+mod server {
+    use super::{parse_bind, AdminContext, AdminError, Permission};
+    use crate::config_watch::RuntimeSettings;
+    use crate::stats::{ChainTable, StatsStore};
+    use axum::extract::{Path, Query, State};
+    use axum::http::{header, HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde::Deserialize;
+    use std::sync::Arc;
+
+    impl IntoResponse for AdminError {
+        fn into_response(self) -> Response {
+            let status = match self {
+                AdminError::Unauthorized => StatusCode::UNAUTHORIZED,
+                AdminError::Forbidden => StatusCode::FORBIDDEN,
+                AdminError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            };
+            (status, Json(serde_json::json!({ "error": self.to_string() }))).into_response()
+        }
+    }
+
+    type Ctx = State<Arc<AdminContext>>;
+
+    fn authorize(ctx: &AdminContext, headers: &HeaderMap, permission: Permission) -> Result<String, AdminError> {
+        let authorization = headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok());
+        ctx.authorize(authorization, permission)
+    }
+
+    async fn get_config(State(ctx): Ctx, headers: HeaderMap) -> Result<Json<RuntimeSettings>, AdminError> {
+        authorize(&ctx, &headers, Permission::ReadStatus)?;
+        Ok(Json(ctx.config.settings()))
+    }
+
+    async fn put_config(
+        State(ctx): Ctx,
+        headers: HeaderMap,
+        Json(settings): Json<RuntimeSettings>,
+    ) -> Result<Json<RuntimeSettings>, AdminError> {
+        let name = authorize(&ctx, &headers, Permission::WriteConfig)?;
+        let change = ctx
+            .config
+            .apply(settings)
+            .map_err(|e| AdminError::BadRequest(e.to_string()))?;
+        if change.is_some() {
+            log::info!("[管理接口] {} 修改了运行时配置", name);
+        }
+        Ok(Json(ctx.config.settings()))
+    }
+
+    async fn get_peers(State(ctx): Ctx, headers: HeaderMap) -> Result<impl IntoResponse, AdminError> {
+        authorize(&ctx, &headers, Permission::ReadStatus)?;
+        let status = ctx.status.snapshot();
+        Ok(Json(serde_json::json!({
+            "node_id": status.node_id,
+            "tick": status.tick,
+            "updated_at": status.updated_at,
+            "peers": status.peers,
+        })))
+    }
+
+    async fn get_transfers(State(ctx): Ctx, headers: HeaderMap) -> Result<impl IntoResponse, AdminError> {
+        authorize(&ctx, &headers, Permission::ReadStatus)?;
+        Ok(Json(ctx.transfers.list()))
+    }
+
+    async fn get_stake(State(ctx): Ctx, headers: HeaderMap) -> Result<impl IntoResponse, AdminError> {
+        authorize(&ctx, &headers, Permission::ReadStatus)?;
+        let status = ctx.status.snapshot();
+        let now = chrono::Utc::now().timestamp();
+        Ok(Json(serde_json::json!({
+            "node_id": status.node_id,
+            "attested": status.stake.is_some(),
+            "expired": status.stake.as_ref().is_some_and(|stake| stake.expires_at <= now),
+            "attestation": status.stake,
+        })))
+    }
+
+    #[derive(Deserialize)]
+    struct LogLevelRequest {
+        level: String,
+    }
+
+    async fn get_log_level(State(ctx): Ctx, headers: HeaderMap) -> Result<impl IntoResponse, AdminError> {
+        authorize(&ctx, &headers, Permission::ReadStatus)?;
+        Ok(Json(serde_json::json!({ "level": ctx.log_level.current() })))
+    }
+
+    async fn put_log_level(
+        State(ctx): Ctx,
+        headers: HeaderMap,
+        Json(request): Json<LogLevelRequest>,
+    ) -> Result<impl IntoResponse, AdminError> {
+        let name = authorize(&ctx, &headers, Permission::ManageLogs)?;
+        ctx.log_level
+            .set(&request.level)
+            .map_err(|e| AdminError::BadRequest(e.to_string()))?;
+        log::info!("[管理接口] {} 把日志级别调整为 {}", name, request.level);
+        Ok(Json(serde_json::json!({ "level": ctx.log_level.current() })))
+    }
+
+    /// 链上记录查询默认返回的条数
+    const DEFAULT_CHAIN_LIMIT: usize = 100;
+
+    /// 单次查询最多返回的条数，超过时截断
+    const MAX_RECORDS_PER_PAGE: usize = 1000;
+
+    #[derive(Deserialize)]
+    struct ChainQuery {
+        node_id: Option<String>,
+        name: Option<String>,
+        limit: Option<usize>,
+    }
+
+    impl ChainQuery {
+        fn limit(&self) -> usize {
+            self.limit.unwrap_or(DEFAULT_CHAIN_LIMIT).min(MAX_RECORDS_PER_PAGE)
+        }
+    }
+
+    fn stats_store(ctx: &AdminContext) -> Result<&StatsStore, AdminError> {
+        ctx.stats
+            .as_deref()
+            .ok_or_else(|| AdminError::BadRequest("未启用本地统计库".to_string()))
+    }
+
+    async fn get_chain_records(
+        State(ctx): Ctx,
+        headers: HeaderMap,
+        Path(table): Path<String>,
+        Query(query): Query<ChainQuery>,
+    ) -> Result<impl IntoResponse, AdminError> {
+        authorize(&ctx, &headers, Permission::ReadStatus)?;
+        let store = stats_store(&ctx)?;
+        let limit = query.limit();
+        // events 不是账户表，单独按事件名过滤
+        if table == "events" {
+            let events = store
+                .chain_events(query.name.as_deref(), limit)
+                .map_err(|e| AdminError::BadRequest(e.to_string()))?;
+            return Ok(Json(serde_json::json!(events)));
+        }
+        let table: ChainTable = serde_json::from_value(serde_json::Value::String(table.clone()))
+            .map_err(|_| AdminError::BadRequest(format!("未知的链上记录类别: {}", table)))?;
+        let records = store
+            .chain_records(table, query.node_id.as_deref(), limit)
+            .map_err(|e| AdminError::BadRequest(e.to_string()))?;
+        Ok(Json(serde_json::json!(records)))
+    }
+
+    /// 启动管理接口，直到监听失败才返回
+    pub async fn serve(ctx: Arc<AdminContext>, bind: &str) -> anyhow::Result<()> {
+        let addr = parse_bind(bind)?;
+        if ctx.auth.is_empty() {
+            return Err(anyhow::anyhow!("未配置任何管理令牌，管理接口不会启动"));
+        }
+        let app = Router::new()
+            .route("/admin/v1/config", get(get_config).put(put_config))
+            .route("/admin/v1/peers", get(get_peers))
+            .route("/admin/v1/transfers", get(get_transfers))
+            .route("/admin/v1/stake", get(get_stake))
+            .route("/admin/v1/log-level", get(get_log_level).put(put_log_level))
+            .route("/admin/v1/chain/:table", get(get_chain_records))
+            .with_state(ctx);
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        println!("[管理接口] 监听 http://{}", addr);
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn query(limit: Option<usize>) -> ChainQuery {
+            ChainQuery {
+                node_id: None,
+                name: None,
+                limit,
+            }
+        }
+
+        #[test]
+        fn test_chain_limit_is_clamped() {
+            assert_eq!(query(None).limit(), DEFAULT_CHAIN_LIMIT);
+            assert_eq!(query(Some(10)).limit(), 10);
+            assert_eq!(query(Some(MAX_RECORDS_PER_PAGE)).limit(), MAX_RECORDS_PER_PAGE);
+            assert_eq!(query(Some(MAX_RECORDS_PER_PAGE + 1)).limit(), MAX_RECORDS_PER_PAGE);
+            assert_eq!(query(Some(usize::MAX)).limit(), MAX_RECORDS_PER_PAGE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_roles_and_permissions() {
+        let config = AdminConfig {
+            tokens: vec![
+                AdminTokenConfig {
+                    name: "dashboard".to_string(),
+                    token_hash: hash_token("view-secret"),
+                    role: AdminRole::Viewer,
+                },
+                AdminTokenConfig {
+                    name: "ansible".to_string(),
+                    token_hash: hash_token("admin-secret"),
+                    role: AdminRole::Admin,
+                },
+            ],
+            ..Default::default()
+        };
+        let ctx = AdminContext {
+            auth: AdminAuth { tokens: config.tokens },
+            config: Arc::new(ConfigWatcher::detached(&crate::config::AppConfig::default())),
+            status: Arc::new(NodeStatusBoard::default()),
+            transfers: Arc::new(TransferSessions::default()),
+            log_level: Arc::new(LogLevelControl::new("info", |_| Ok(()))),
+            stats: None,
+        };
+
+        assert_eq!(ctx.authorize(Some("Bearer view-secret"), Permission::ReadStatus).unwrap(), "dashboard");
+        assert!(matches!(
+            ctx.authorize(Some("Bearer view-secret"), Permission::ManageLogs),
+            Err(AdminError::Forbidden)
+        ));
+        assert!(ctx.authorize(Some("Bearer admin-secret"), Permission::WriteConfig).is_ok());
+        assert!(matches!(ctx.authorize(Some("Bearer wrong"), Permission::ReadStatus), Err(AdminError::Unauthorized)));
+        assert!(matches!(ctx.authorize(None, Permission::ReadStatus), Err(AdminError::Unauthorized)));
+
+        assert!(parse_bind("127.0.0.1:9470").is_ok());
+        assert!(parse_bind("0.0.0.0:9470").is_err());
+    }
+
+    #[test]
+    fn test_transfer_sessions_follow_events() {
+        let sessions = TransferSessions::default();
+        sessions.record(&TransferEvent::TransferStarted {
+            transfer_id: "t1".to_string(),
+            file_name: "shard.bin".to_string(),
+            peer_id: "peer-a".to_string(),
+        });
+        sessions.record(&TransferEvent::ProgressUpdate {
+            transfer_id: "t1".to_string(),
+            progress: 0.5,
+            speed_bps: 1024,
+        });
+        sessions.record(&TransferEvent::TransferFailed {
+            transfer_id: "t2".to_string(),
+            error: "timeout".to_string(),
+        });
+
+        let list = sessions.list();
+        assert_eq!(list.len(), 2);
+        assert_eq!((list[0].transfer_id.as_str(), list[0].state, list[0].progress), ("t1", TransferState::Active, 0.5));
+        assert_eq!(list[1].error.as_deref(), Some("timeout"));
+
+        sessions.record(&TransferEvent::TransferCompleted {
+            transfer_id: "t1".to_string(),
+            file_size: 2048,
+            duration_secs: 2,
+        });
+        assert!(sessions.list().iter().all(|session| session.state != TransferState::Active));
+    }
+}
@@ -0,0 +1,287 @@
+//! 任务分配问题
+//!
+//! 把一组任务分配到算力不同的节点上。单目标优化只看完成时间（最慢节点的耗时）；
+//! 配置了节点的费率和功耗后，也可以同时权衡完成时间、费用和能耗（见 [`super::pareto`]）。
+
+use super::pareto::{MultiObjectiveProblem, ParetoPoint};
+use super::OptimizationProblem;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// 一种分配方案的各项目标值（均越小越好）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AllocationObjectives {
+    /// 完成时间（最慢节点的耗时）
+    pub completion_time: f64,
+    /// 费用：各节点耗时乘以其费率之和
+    pub cost: f64,
+    /// 能耗：各节点耗时乘以其功耗之和
+    pub energy: f64,
+}
+
+impl AllocationObjectives {
+    pub fn to_vec(self) -> Vec<f64> {
+        vec![self.completion_time, self.cost, self.energy]
+    }
+}
+
+/// 在帕累托前沿上选择折中点时各目标的权重
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TradeOffWeights {
+    pub completion_time: f64,
+    pub cost: f64,
+    pub energy: f64,
+}
+
+impl Default for TradeOffWeights {
+    fn default() -> Self {
+        Self {
+            completion_time: 1.0,
+            cost: 1.0,
+            energy: 1.0,
+        }
+    }
+}
+
+/// 帕累托前沿上的备选方案
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AllocationAlternative {
+    pub assignment: Vec<usize>,
+    pub objectives: AllocationObjectives,
+}
+
+/// 任务分配结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskAllocation {
+    /// 第 i 个任务分配到的节点下标
+    pub assignment: Vec<usize>,
+    /// 完成时间（最慢节点的耗时）
+    pub makespan: f64,
+    /// 所选方案的各项目标值
+    #[serde(default)]
+    pub objectives: AllocationObjectives,
+    /// 多目标优化时帕累托前沿上的其余方案，按完成时间排序
+    #[serde(default)]
+    pub alternatives: Vec<AllocationAlternative>,
+}
+
+/// 任务分配问题
+#[derive(Debug, Clone)]
+pub struct TaskAllocationProblem {
+    /// 各任务的计算量
+    pub task_costs: Vec<f64>,
+    /// 各节点的速度（单位时间完成的计算量）
+    pub node_speeds: Vec<f64>,
+    /// 各节点单位时间的费用（为空时费用记为 0）
+    pub node_cost_rates: Vec<f64>,
+    /// 各节点单位时间的能耗（为空时能耗记为 0）
+    pub node_power: Vec<f64>,
+}
+
+impl TaskAllocationProblem {
+    pub fn new(task_costs: Vec<f64>, node_speeds: Vec<f64>) -> Self {
+        Self {
+            task_costs,
+            node_speeds,
+            node_cost_rates: Vec::new(),
+            node_power: Vec::new(),
+        }
+    }
+
+    pub fn with_cost_rates(mut self, rates: Vec<f64>) -> Self {
+        self.node_cost_rates = rates;
+        self
+    }
+
+    pub fn with_power(mut self, power: Vec<f64>) -> Self {
+        self.node_power = power;
+        self
+    }
+
+    /// 各节点的耗时
+    pub fn node_times(&self, assignment: &[usize]) -> Vec<f64> {
+        let mut times = vec![0.0; self.node_speeds.len()];
+        for (task, node) in assignment.iter().enumerate() {
+            times[*node] += self.task_costs[task] / self.node_speeds[*node].max(f64::EPSILON);
+        }
+        times
+    }
+
+    pub fn makespan(&self, assignment: &[usize]) -> f64 {
+        self.node_times(assignment).into_iter().fold(0.0, f64::max)
+    }
+
+    pub fn objectives(&self, assignment: &[usize]) -> AllocationObjectives {
+        let times = self.node_times(assignment);
+        let weighted = |rates: &[f64]| times.iter().zip(rates).map(|(t, r)| t * r).sum();
+        AllocationObjectives {
+            completion_time: times.iter().copied().fold(0.0, f64::max),
+            cost: weighted(&self.node_cost_rates),
+            energy: weighted(&self.node_power),
+        }
+    }
+
+    pub fn to_allocation(&self, assignment: Vec<usize>) -> TaskAllocation {
+        let objectives = self.objectives(&assignment);
+        TaskAllocation {
+            makespan: objectives.completion_time,
+            objectives,
+            assignment,
+            alternatives: Vec::new(),
+        }
+    }
+
+    /// 从帕累托前沿中按权重选出折中方案，其余方案作为备选
+    ///
+    /// 各目标先按前沿上的取值范围归一化到 [0, 1]，再取加权和最小的方案。
+    pub fn choose_trade_off(&self, front: Vec<ParetoPoint<Vec<usize>>>, weights: TradeOffWeights) -> Option<TaskAllocation> {
+        let points: Vec<(Vec<usize>, AllocationObjectives)> = front
+            .into_iter()
+            .map(|point| {
+                let objectives = self.objectives(&point.solution);
+                (point.solution, objectives)
+            })
+            .collect();
+        let range = |get: fn(&AllocationObjectives) -> f64| {
+            let min = points.iter().map(|(_, o)| get(o)).fold(f64::INFINITY, f64::min);
+            let max = points.iter().map(|(_, o)| get(o)).fold(f64::NEG_INFINITY, f64::max);
+            (min, (max - min).max(f64::EPSILON))
+        };
+        let time_range = range(|o| o.completion_time);
+        let cost_range = range(|o| o.cost);
+        let energy_range = range(|o| o.energy);
+        let score = |o: &AllocationObjectives| {
+            weights.completion_time * (o.completion_time - time_range.0) / time_range.1
+                + weights.cost * (o.cost - cost_range.0) / cost_range.1
+                + weights.energy * (o.energy - energy_range.0) / energy_range.1
+        };
+
+        let chosen = (0..points.len()).min_by(|a, b| score(&points[*a].1).total_cmp(&score(&points[*b].1)))?;
+        let mut alternatives: Vec<AllocationAlternative> = points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != chosen)
+            .map(|(_, (assignment, objectives))| AllocationAlternative {
+                assignment: assignment.clone(),
+                objectives: *objectives,
+            })
+            .collect();
+        alternatives.sort_by(|a, b| a.objectives.completion_time.total_cmp(&b.objectives.completion_time));
+
+        let mut allocation = self.to_allocation(points[chosen].0.clone());
+        allocation.alternatives = alternatives;
+        Some(allocation)
+    }
+
+    fn random_node(&self, rng: &mut StdRng) -> usize {
+        rng.random_range(0..self.node_speeds.len().max(1))
+    }
+}
+
+impl MultiObjectiveProblem for TaskAllocationProblem {
+    type Solution = Vec<usize>;
+
+    fn evaluate(&self, solution: &Vec<usize>) -> Vec<f64> {
+        self.objectives(solution).to_vec()
+    }
+
+    fn random_solution(&self, rng: &mut StdRng) -> Vec<usize> {
+        (0..self.task_costs.len()).map(|_| self.random_node(rng)).collect()
+    }
+
+    /// 均匀交叉：每个任务随机继承一方的节点
+    fn crossover(&self, a: &Vec<usize>, b: &Vec<usize>, rng: &mut StdRng) -> Vec<usize> {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| if rng.random_bool(0.5) { *x } else { *y })
+            .collect()
+    }
+
+    fn mutate(&self, solution: &mut Vec<usize>, rate: f64, rng: &mut StdRng) {
+        for node in solution.iter_mut() {
+            if rng.random_bool(rate.clamp(0.0, 1.0)) {
+                *node = self.random_node(rng);
+            }
+        }
+    }
+}
+
+/// 任务分配的邻域移动
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AllocationMove {
+    /// 把任务移到另一个节点
+    Reassign { task: usize, node: usize },
+    /// 交换两个不同节点上的任务
+    Swap { a: usize, b: usize },
+}
+
+impl OptimizationProblem for TaskAllocationProblem {
+    type Solution = Vec<usize>;
+    type Move = AllocationMove;
+
+    fn size(&self) -> usize {
+        self.task_costs.len()
+    }
+
+    /// 贪心初始解：按计算量从大到小，每个任务放到完成后耗时最短的节点
+    fn initial_solution(&self, _rng: &mut StdRng) -> Vec<usize> {
+        let finish = |times: &[f64], task: usize, node: usize| {
+            times[node] + self.task_costs[task] / self.node_speeds[node].max(f64::EPSILON)
+        };
+        let mut order: Vec<usize> = (0..self.task_costs.len()).collect();
+        order.sort_by(|a, b| self.task_costs[*b].total_cmp(&self.task_costs[*a]));
+        let mut times = vec![0.0; self.node_speeds.len()];
+        let mut assignment = vec![0; self.task_costs.len()];
+        for task in order {
+            let node = (0..times.len())
+                .min_by(|a, b| finish(&times, task, *a).total_cmp(&finish(&times, task, *b)))
+                .unwrap_or(0);
+            times[node] = finish(&times, task, node);
+            assignment[task] = node;
+        }
+        assignment
+    }
+
+    fn cost(&self, solution: &Vec<usize>) -> f64 {
+        self.makespan(solution)
+    }
+
+    fn random_move(&self, solution: &Vec<usize>, rng: &mut StdRng) -> Option<AllocationMove> {
+        if solution.is_empty() || self.node_speeds.len() < 2 {
+            return None;
+        }
+        let task = rng.random_range(0..solution.len());
+        if solution.len() > 1 && rng.random_bool(0.5) {
+            let other = rng.random_range(0..solution.len());
+            if solution[other] != solution[task] {
+                return Some(AllocationMove::Swap { a: task, b: other });
+            }
+        }
+        // 从其余节点中随机选一个
+        let mut node = rng.random_range(0..self.node_speeds.len() - 1);
+        if node >= solution[task] {
+            node += 1;
+        }
+        Some(AllocationMove::Reassign { task, node })
+    }
+
+    fn apply_move(&self, solution: &Vec<usize>, mv: &AllocationMove) -> Vec<usize> {
+        let mut next = solution.clone();
+        match *mv {
+            AllocationMove::Reassign { task, node } => next[task] = node,
+            AllocationMove::Swap { a, b } => next.swap(a, b),
+        }
+        next
+    }
+
+    fn reverse_move(&self, solution: &Vec<usize>, mv: &AllocationMove) -> AllocationMove {
+        match *mv {
+            AllocationMove::Reassign { task, .. } => AllocationMove::Reassign {
+                task,
+                node: solution[task],
+            },
+            swap => swap,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! 模拟退火
+//!
+//! 每轮随机生成一个邻居，更优则接受，更差时按 `exp(-Δ/T)` 的概率接受以跳出局部最优。
+//! 温度按几何速率下降，降到下限后从当前最优解重新升温，直到用完迭代次数或时间预算。
+
+use super::{AlgorithmKind, OptimizationProblem, OptimizationResult, Optimizer};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// 估算初始温度时采样的邻居数
+const TEMPERATURE_SAMPLES: usize = 32;
+
+/// 模拟退火参数
+#[derive(Debug, Clone)]
+pub struct AnnealingConfig {
+    /// 初始温度；为 None 时根据随机邻居的代价差估算
+    pub initial_temperature: Option<f64>,
+    /// 每轮温度乘以该系数
+    pub cooling_rate: f64,
+    /// 温度下限，低于它时重新升温
+    pub min_temperature: f64,
+    pub max_iterations: u64,
+    pub time_budget: Duration,
+}
+
+impl Default for AnnealingConfig {
+    fn default() -> Self {
+        Self {
+            initial_temperature: None,
+            cooling_rate: 0.995,
+            min_temperature: 1e-6,
+            max_iterations: 1_000_000,
+            time_budget: Duration::from_millis(200),
+        }
+    }
+}
+
+/// 模拟退火优化器
+pub struct SimulatedAnnealing {
+    config: AnnealingConfig,
+}
+
+impl SimulatedAnnealing {
+    pub fn new(config: AnnealingConfig) -> Self {
+        Self { config }
+    }
+
+    /// 让平均代价上升的移动初始有约一半的接受概率
+    fn estimate_temperature<P: OptimizationProblem>(
+        problem: &P,
+        solution: &P::Solution,
+        cost: f64,
+        rng: &mut StdRng,
+    ) -> (f64, u64) {
+        let mut total = 0.0;
+        let mut uphill = 0;
+        let mut evaluations = 0;
+        for _ in 0..TEMPERATURE_SAMPLES {
+            let Some(mv) = problem.random_move(solution, rng) else {
+                break;
+            };
+            let delta = problem.cost(&problem.apply_move(solution, &mv)) - cost;
+            evaluations += 1;
+            if delta > 0.0 {
+                total += delta;
+                uphill += 1;
+            }
+        }
+        let mean = if uphill > 0 { total / uphill as f64 } else { 1.0 };
+        (mean / std::f64::consts::LN_2, evaluations)
+    }
+}
+
+impl Optimizer for SimulatedAnnealing {
+    fn kind(&self) -> AlgorithmKind {
+        AlgorithmKind::SimulatedAnnealing
+    }
+
+    fn optimize<P: OptimizationProblem>(&self, problem: &P, rng: &mut StdRng) -> OptimizationResult<P::Solution> {
+        let start = Instant::now();
+        let mut current = problem.initial_solution(rng);
+        let mut current_cost = problem.cost(&current);
+        let mut best = current.clone();
+        let mut best_cost = current_cost;
+        let mut evaluations = 1;
+
+        let initial_temperature = match self.config.initial_temperature {
+            Some(temperature) => temperature,
+            None => {
+                let (temperature, used) = Self::estimate_temperature(problem, &current, current_cost, rng);
+                evaluations += used;
+                temperature
+            }
+        }
+        .max(self.config.min_temperature);
+        let mut temperature = initial_temperature;
+
+        let mut iterations = 0;
+        while iterations < self.config.max_iterations && start.elapsed() < self.config.time_budget {
+            let Some(mv) = problem.random_move(&current, rng) else {
+                break;
+            };
+            iterations += 1;
+            let candidate = problem.apply_move(&current, &mv);
+            let candidate_cost = problem.cost(&candidate);
+            evaluations += 1;
+
+            let delta = candidate_cost - current_cost;
+            if delta <= 0.0 || rng.random::<f64>() < (-delta / temperature).exp() {
+                current = candidate;
+                current_cost = candidate_cost;
+                if current_cost < best_cost {
+                    best = current.clone();
+                    best_cost = current_cost;
+                }
+            }
+
+            temperature *= self.config.cooling_rate;
+            if temperature < self.config.min_temperature {
+                temperature = initial_temperature;
+                current = best.clone();
+                current_cost = best_cost;
+            }
+        }
+
+        OptimizationResult {
+            algorithm: self.kind(),
+            solution: best,
+            cost: best_cost,
+            iterations,
+            evaluations,
+            elapsed: start.elapsed(),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! 调度优化算法
+//!
+//! 任务分配等组合优化问题实现 [`OptimizationProblem`]，由各优化器在时间预算内搜索最小代价解：
+//! - 模拟退火：每轮只评估一个随机邻居，单次迭代开销小，适合规模大或预算紧的问题
+//! - 禁忌搜索：每轮评估一批邻居并禁止短期内撤销的移动，适合规模小、预算充足的问题
+//!
+//! [`AlgorithmManager`] 的自动模式按问题规模和时间预算选择算法。
+//! 需要同时权衡完成时间、费用和能耗时，用 NSGA-II 求帕累托前沿（见 [`pareto`]）。
+
+pub mod allocation;
+pub mod annealing;
+pub mod pareto;
+pub mod tabu;
+
+pub use allocation::{
+    AllocationAlternative, AllocationMove, AllocationObjectives, TaskAllocation, TaskAllocationProblem, TradeOffWeights,
+};
+pub use annealing::{AnnealingConfig, SimulatedAnnealing};
+pub use pareto::{dominates, nsga2, MultiObjectiveProblem, ParetoConfig, ParetoPoint};
+pub use tabu::{TabuConfig, TabuSearch};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+use std::time::Duration;
+
+/// 可由局部搜索求解的优化问题（最小化代价）
+pub trait OptimizationProblem {
+    type Solution: Clone;
+    /// 邻域移动，禁忌搜索按移动记录禁忌表
+    type Move: Clone + Eq + Hash;
+
+    /// 问题规模（例如待分配任务数），用于自动选择算法
+    fn size(&self) -> usize;
+
+    /// 初始解
+    fn initial_solution(&self, rng: &mut StdRng) -> Self::Solution;
+
+    /// 解的代价，越小越好
+    fn cost(&self, solution: &Self::Solution) -> f64;
+
+    /// 随机生成当前解的一个邻域移动（无可用移动时返回 None）
+    fn random_move(&self, solution: &Self::Solution, rng: &mut StdRng) -> Option<Self::Move>;
+
+    /// 应用移动得到新解
+    fn apply_move(&self, solution: &Self::Solution, mv: &Self::Move) -> Self::Solution;
+
+    /// 撤销该移动的移动（禁忌表中记录它，防止短期内回到原解）
+    fn reverse_move(&self, solution: &Self::Solution, mv: &Self::Move) -> Self::Move;
+}
+
+/// 优化结果
+#[derive(Debug, Clone)]
+pub struct OptimizationResult<S> {
+    pub algorithm: AlgorithmKind,
+    pub solution: S,
+    pub cost: f64,
+    pub iterations: u64,
+    /// 代价函数调用次数
+    pub evaluations: u64,
+    pub elapsed: Duration,
+}
+
+/// 优化器
+pub trait Optimizer {
+    fn kind(&self) -> AlgorithmKind;
+
+    fn optimize<P: OptimizationProblem>(&self, problem: &P, rng: &mut StdRng) -> OptimizationResult<P::Solution>;
+}
+
+/// 算法选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlgorithmKind {
+    SimulatedAnnealing,
+    TabuSearch,
+    /// 按问题规模和时间预算自动选择
+    Auto,
+}
+
+/// 自动模式下使用禁忌搜索的最大问题规模
+const TABU_MAX_SIZE: usize = 200;
+/// 禁忌搜索每个问题规模单位至少需要的时间预算
+const TABU_BUDGET_PER_ITEM: Duration = Duration::from_micros(500);
+
+/// 调度算法管理器
+#[derive(Debug, Clone)]
+pub struct AlgorithmManager {
+    pub algorithm: AlgorithmKind,
+    /// 单次优化的时间预算
+    pub time_budget: Duration,
+    /// 随机种子，固定后结果可复现
+    pub seed: Option<u64>,
+}
+
+impl Default for AlgorithmManager {
+    fn default() -> Self {
+        Self {
+            algorithm: AlgorithmKind::Auto,
+            time_budget: Duration::from_millis(200),
+            seed: None,
+        }
+    }
+}
+
+impl AlgorithmManager {
+    pub fn new(algorithm: AlgorithmKind, time_budget: Duration) -> Self {
+        Self {
+            algorithm,
+            time_budget,
+            seed: None,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// 实际使用的算法：小规模且预算充足时用禁忌搜索，否则用模拟退火
+    pub fn select(&self, problem_size: usize) -> AlgorithmKind {
+        match self.algorithm {
+            AlgorithmKind::Auto => {
+                let tabu_budget = TABU_BUDGET_PER_ITEM * problem_size.max(1) as u32;
+                if problem_size <= TABU_MAX_SIZE && self.time_budget >= tabu_budget {
+                    AlgorithmKind::TabuSearch
+                } else {
+                    AlgorithmKind::SimulatedAnnealing
+                }
+            }
+            kind => kind,
+        }
+    }
+
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        }
+    }
+
+    pub fn optimize<P: OptimizationProblem>(&self, problem: &P) -> OptimizationResult<P::Solution> {
+        let mut rng = self.rng();
+        match self.select(problem.size()) {
+            AlgorithmKind::TabuSearch => TabuSearch::new(TabuConfig {
+                time_budget: self.time_budget,
+                ..Default::default()
+            })
+            .optimize(problem, &mut rng),
+            _ => SimulatedAnnealing::new(AnnealingConfig {
+                time_budget: self.time_budget,
+                ..Default::default()
+            })
+            .optimize(problem, &mut rng),
+        }
+    }
+
+    /// 多目标任务分配：用 NSGA-II 求完成时间/费用/能耗的帕累托前沿，按权重选出折中方案，
+    /// 前沿上的其余方案放在 `alternatives` 中供调用方查看
+    pub fn allocate_pareto(&self, problem: &TaskAllocationProblem, weights: TradeOffWeights) -> Option<TaskAllocation> {
+        let config = ParetoConfig {
+            time_budget: self.time_budget,
+            ..Default::default()
+        };
+        let front = nsga2(problem, &config, &mut self.rng());
+        problem.choose_trade_off(front, weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem() -> TaskAllocationProblem {
+        // 贪心初始解为 7（3+2+2 / 3+2），最优为 6（3+3 / 2+2+2），需要交换移动才能到达
+        TaskAllocationProblem::new(vec![3.0, 3.0, 2.0, 2.0, 2.0], vec![1.0, 1.0])
+    }
+
+    #[test]
+    fn test_both_algorithms_balance_allocation() {
+        let problem = problem();
+        for algorithm in [AlgorithmKind::SimulatedAnnealing, AlgorithmKind::TabuSearch] {
+            let result = AlgorithmManager::new(algorithm, Duration::from_millis(50))
+                .with_seed(7)
+                .optimize(&problem);
+            assert_eq!(result.algorithm, algorithm);
+            assert!((result.cost - 6.0).abs() < 1e-9, "{:?} 得到 {}", algorithm, result.cost);
+        }
+    }
+
+    #[test]
+    fn test_auto_select_by_size_and_budget() {
+        let manager = AlgorithmManager::new(AlgorithmKind::Auto, Duration::from_millis(50));
+        assert_eq!(manager.select(20), AlgorithmKind::TabuSearch);
+        assert_eq!(manager.select(1000), AlgorithmKind::SimulatedAnnealing);
+        let tight = AlgorithmManager::new(AlgorithmKind::Auto, Duration::from_millis(1));
+        assert_eq!(tight.select(20), AlgorithmKind::SimulatedAnnealing);
+    }
+}
@@ -0,0 +1,233 @@
+//! NSGA-II 多目标优化
+//!
+//! 对多个相互冲突的目标（例如完成时间、费用、能耗）不再合成单一适应度，
+//! 而是用非支配排序加拥挤距离进化种群，返回互不支配的帕累托前沿，由调用方按需取舍。
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// 多目标优化问题（所有目标均为越小越好）
+pub trait MultiObjectiveProblem {
+    type Solution: Clone + PartialEq;
+
+    /// 各目标值，长度在同一问题内固定
+    fn evaluate(&self, solution: &Self::Solution) -> Vec<f64>;
+
+    fn random_solution(&self, rng: &mut StdRng) -> Self::Solution;
+
+    fn crossover(&self, a: &Self::Solution, b: &Self::Solution, rng: &mut StdRng) -> Self::Solution;
+
+    /// 按 `rate` 随机扰动解的各个分量
+    fn mutate(&self, solution: &mut Self::Solution, rate: f64, rng: &mut StdRng);
+}
+
+/// 前沿上的一个解
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParetoPoint<S> {
+    pub solution: S,
+    pub objectives: Vec<f64>,
+}
+
+/// NSGA-II 参数
+#[derive(Debug, Clone)]
+pub struct ParetoConfig {
+    pub population_size: usize,
+    pub generations: usize,
+    pub crossover_rate: f64,
+    /// 每个分量的变异概率
+    pub mutation_rate: f64,
+    pub time_budget: Duration,
+}
+
+impl Default for ParetoConfig {
+    fn default() -> Self {
+        Self {
+            population_size: 64,
+            generations: 200,
+            crossover_rate: 0.9,
+            mutation_rate: 0.05,
+            time_budget: Duration::from_millis(200),
+        }
+    }
+}
+
+/// `a` 是否支配 `b`：各目标都不差，且至少一个更好
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y) && a.iter().zip(b).any(|(x, y)| x < y)
+}
+
+/// 快速非支配排序，返回各层前沿的下标（第 0 层为帕累托前沿）
+fn non_dominated_sort(objectives: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated_by_me: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_count = vec![0usize; n];
+    for i in 0..n {
+        for j in 0..n {
+            if i != j && dominates(&objectives[i], &objectives[j]) {
+                dominated_by_me[i].push(j);
+            } else if i != j && dominates(&objectives[j], &objectives[i]) {
+                domination_count[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut current: Vec<usize> = (0..n).filter(|i| domination_count[*i] == 0).collect();
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &i in &current {
+            for &j in &dominated_by_me[i] {
+                domination_count[j] -= 1;
+                if domination_count[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        fronts.push(current);
+        current = next;
+    }
+    fronts
+}
+
+/// 同一层前沿内各解的拥挤距离，边界解为无穷大
+fn crowding_distance(front: &[usize], objectives: &[Vec<f64>]) -> Vec<f64> {
+    let mut distance = vec![0.0; front.len()];
+    let Some(dims) = front.first().map(|i| objectives[*i].len()) else {
+        return distance;
+    };
+    for m in 0..dims {
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|a, b| objectives[front[*a]][m].total_cmp(&objectives[front[*b]][m]));
+        let min = objectives[front[order[0]]][m];
+        let max = objectives[front[order[order.len() - 1]]][m];
+        distance[order[0]] = f64::INFINITY;
+        distance[order[order.len() - 1]] = f64::INFINITY;
+        if max - min <= f64::EPSILON {
+            continue;
+        }
+        for k in 1..order.len().saturating_sub(1) {
+            let gap = objectives[front[order[k + 1]]][m] - objectives[front[order[k - 1]]][m];
+            distance[order[k]] += gap / (max - min);
+        }
+    }
+    distance
+}
+
+/// NSGA-II 求解，返回去重后的帕累托前沿
+pub fn nsga2<P: MultiObjectiveProblem>(
+    problem: &P,
+    config: &ParetoConfig,
+    rng: &mut StdRng,
+) -> Vec<ParetoPoint<P::Solution>> {
+    let start = Instant::now();
+    let size = config.population_size.max(4);
+    let mut population: Vec<P::Solution> = (0..size).map(|_| problem.random_solution(rng)).collect();
+    let mut objectives: Vec<Vec<f64>> = population.iter().map(|s| problem.evaluate(s)).collect();
+    let (mut rank, mut crowding) = rank_population(&objectives);
+
+    for _ in 0..config.generations {
+        if start.elapsed() >= config.time_budget {
+            break;
+        }
+        // 二元锦标赛：先比层级，再比拥挤距离
+        let tournament = |rng: &mut StdRng| {
+            let a = rng.random_range(0..population.len());
+            let b = rng.random_range(0..population.len());
+            if rank[a] < rank[b] || (rank[a] == rank[b] && crowding[a] > crowding[b]) {
+                a
+            } else {
+                b
+            }
+        };
+        let mut offspring = Vec::with_capacity(size);
+        while offspring.len() < size {
+            let a = tournament(rng);
+            let b = tournament(rng);
+            let mut child = if rng.random_bool(config.crossover_rate.clamp(0.0, 1.0)) {
+                problem.crossover(&population[a], &population[b], rng)
+            } else {
+                population[a].clone()
+            };
+            problem.mutate(&mut child, config.mutation_rate, rng);
+            offspring.push(child);
+        }
+
+        // 父代与子代合并后按层级和拥挤距离保留前 size 个
+        let offspring_objectives: Vec<Vec<f64>> = offspring.iter().map(|s| problem.evaluate(s)).collect();
+        population.extend(offspring);
+        objectives.extend(offspring_objectives);
+        let mut survivors = Vec::with_capacity(size);
+        for front in non_dominated_sort(&objectives) {
+            if survivors.len() + front.len() <= size {
+                survivors.extend(front);
+                continue;
+            }
+            let distance = crowding_distance(&front, &objectives);
+            let mut order: Vec<usize> = (0..front.len()).collect();
+            order.sort_by(|a, b| distance[*b].total_cmp(&distance[*a]));
+            survivors.extend(order.into_iter().take(size - survivors.len()).map(|k| front[k]));
+            break;
+        }
+        population = survivors.iter().map(|i| population[*i].clone()).collect();
+        objectives = survivors.iter().map(|i| objectives[*i].clone()).collect();
+        (rank, crowding) = rank_population(&objectives);
+    }
+
+    let mut front: Vec<ParetoPoint<P::Solution>> = Vec::new();
+    for i in non_dominated_sort(&objectives).into_iter().next().unwrap_or_default() {
+        if !front.iter().any(|p| p.solution == population[i]) {
+            front.push(ParetoPoint {
+                solution: population[i].clone(),
+                objectives: objectives[i].clone(),
+            });
+        }
+    }
+    front
+}
+
+/// 每个个体的层级和拥挤距离
+fn rank_population(objectives: &[Vec<f64>]) -> (Vec<usize>, Vec<f64>) {
+    let mut rank = vec![0; objectives.len()];
+    let mut crowding = vec![0.0; objectives.len()];
+    for (level, front) in non_dominated_sort(objectives).into_iter().enumerate() {
+        for (k, distance) in crowding_distance(&front, objectives).into_iter().enumerate() {
+            rank[front[k]] = level;
+            crowding[front[k]] = distance;
+        }
+    }
+    (rank, crowding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{TaskAllocationProblem, TradeOffWeights};
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_front_spans_fast_expensive_and_slow_cheap_nodes() {
+        // 节点 0 快但贵且耗电，节点 1 慢但便宜省电
+        let problem = TaskAllocationProblem::new(vec![1.0; 6], vec![4.0, 1.0])
+            .with_cost_rates(vec![10.0, 1.0])
+            .with_power(vec![8.0, 1.0]);
+        let mut rng = StdRng::seed_from_u64(3);
+        let front = nsga2(&problem, &ParetoConfig::default(), &mut rng);
+        assert!(front.len() > 1);
+        for a in &front {
+            assert!(front.iter().all(|b| !dominates(&b.objectives, &a.objectives)));
+        }
+
+        let fastest = problem
+            .choose_trade_off(front.clone(), TradeOffWeights { completion_time: 1.0, cost: 0.0, energy: 0.0 })
+            .unwrap();
+        let cheapest = problem
+            .choose_trade_off(front, TradeOffWeights { completion_time: 0.0, cost: 1.0, energy: 0.0 })
+            .unwrap();
+        assert!(fastest.makespan < cheapest.makespan);
+        assert!(fastest.objectives.cost > cheapest.objectives.cost);
+        // 全部放在便宜节点上费用最低
+        assert_eq!(cheapest.assignment, vec![1; 6]);
+        assert!(!fastest.alternatives.is_empty());
+    }
+}
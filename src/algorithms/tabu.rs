@@ -0,0 +1,123 @@
+//! 禁忌搜索
+//!
+//! 每轮采样一批邻居并移动到其中最好的一个（即使比当前解更差），
+//! 同时把撤销该移动的移动放入禁忌表，在 `tenure` 轮内禁止执行，避免在局部最优附近打转。
+//! 被禁忌的移动若能得到优于历史最优的解仍然允许（特赦准则）。
+
+use super::{AlgorithmKind, OptimizationProblem, OptimizationResult, Optimizer};
+use rand::rngs::StdRng;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// 禁忌搜索参数
+#[derive(Debug, Clone)]
+pub struct TabuConfig {
+    /// 移动在禁忌表中保留的轮数
+    pub tenure: usize,
+    /// 每轮采样的邻居数
+    pub neighborhood_size: usize,
+    pub max_iterations: u64,
+    /// 连续多少轮没有改进历史最优就停止
+    pub max_stagnation: u64,
+    pub time_budget: Duration,
+}
+
+impl Default for TabuConfig {
+    fn default() -> Self {
+        Self {
+            tenure: 10,
+            neighborhood_size: 32,
+            max_iterations: 100_000,
+            max_stagnation: 2_000,
+            time_budget: Duration::from_millis(200),
+        }
+    }
+}
+
+/// 禁忌搜索优化器
+pub struct TabuSearch {
+    config: TabuConfig,
+}
+
+impl TabuSearch {
+    pub fn new(config: TabuConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Optimizer for TabuSearch {
+    fn kind(&self) -> AlgorithmKind {
+        AlgorithmKind::TabuSearch
+    }
+
+    fn optimize<P: OptimizationProblem>(&self, problem: &P, rng: &mut StdRng) -> OptimizationResult<P::Solution> {
+        let start = Instant::now();
+        let mut current = problem.initial_solution(rng);
+        let mut best = current.clone();
+        let mut best_cost = problem.cost(&current);
+        let mut evaluations = 1;
+
+        let mut tabu_order: VecDeque<P::Move> = VecDeque::new();
+        let mut tabu: HashSet<P::Move> = HashSet::new();
+
+        let mut iterations = 0;
+        let mut stagnation = 0;
+        while iterations < self.config.max_iterations
+            && stagnation < self.config.max_stagnation
+            && start.elapsed() < self.config.time_budget
+        {
+            iterations += 1;
+            let mut chosen: Option<(P::Move, P::Solution, f64)> = None;
+            for _ in 0..self.config.neighborhood_size.max(1) {
+                let Some(mv) = problem.random_move(&current, rng) else {
+                    break;
+                };
+                let candidate = problem.apply_move(&current, &mv);
+                let cost = problem.cost(&candidate);
+                evaluations += 1;
+                if tabu.contains(&mv) && cost >= best_cost {
+                    continue;
+                }
+                let better = match &chosen {
+                    Some((_, _, chosen_cost)) => cost < *chosen_cost,
+                    None => true,
+                };
+                if better {
+                    chosen = Some((mv, candidate, cost));
+                }
+            }
+            let Some((mv, candidate, cost)) = chosen else {
+                stagnation += 1;
+                continue;
+            };
+
+            let reverse = problem.reverse_move(&current, &mv);
+            if tabu.insert(reverse.clone()) {
+                tabu_order.push_back(reverse);
+            }
+            while tabu_order.len() > self.config.tenure {
+                if let Some(expired) = tabu_order.pop_front() {
+                    tabu.remove(&expired);
+                }
+            }
+
+            current = candidate;
+            if cost < best_cost {
+                best = current.clone();
+                best_cost = cost;
+                stagnation = 0;
+            } else {
+                stagnation += 1;
+            }
+        }
+
+        OptimizationResult {
+            algorithm: self.kind(),
+            solution: best,
+            cost: best_cost,
+            iterations,
+            evaluations,
+            elapsed: start.elapsed(),
+        }
+    }
+}
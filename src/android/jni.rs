@@ -318,8 +318,7 @@ pub unsafe extern "C" fn Java_com_williw_mobile_WilliwNode_nativeGetRecommendedM
     }
     
     let handle = &*(ptr as *mut NodeHandle);
-    let caps = handle.device_manager.get();
-    caps.recommended_model_dim() as jint
+    handle.device_manager.recommended_model_dim() as jint
 }
 
 /// 获取推荐的训练间隔（秒）
@@ -0,0 +1,200 @@
+//! Android Keystore 身份存储
+//!
+//! Java 侧 `com.williw.mobile.IdentityKeystore` 使用系统 Keystore 中的 AES 密钥加密身份数据，
+//! 提供静态方法 `load()[B` 和 `store([B)Z`；Rust 通过此后端读写节点身份。
+//! `com.williw.mobile.SecretVault` 以同样方式保存其他私钥（例如 Solana 支付者），注册为平台密钥库。
+
+#[cfg(feature = "android")]
+use crate::crypto::identity::{set_platform_keystore, IdentityKeystore};
+#[cfg(feature = "android")]
+use crate::crypto::vault::{set_platform_vault, KeyVault};
+#[cfg(feature = "android")]
+use zeroize::Zeroizing;
+
+#[cfg(feature = "android")]
+use jni::JNIEnv;
+#[cfg(feature = "android")]
+use jni::objects::{GlobalRef, JByteArray, JClass, JObject, JValue};
+#[cfg(feature = "android")]
+use jni::sys::jint;
+#[cfg(feature = "android")]
+use std::sync::Arc;
+
+/// 基于 Android Keystore 的身份存储
+#[cfg(feature = "android")]
+pub struct AndroidKeystore {
+    java_vm: jni::JavaVM,
+    keystore_class: GlobalRef,
+}
+
+#[cfg(feature = "android")]
+impl IdentityKeystore for AndroidKeystore {
+    fn load(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut env = self
+            .java_vm
+            .attach_current_thread()
+            .map_err(|e| anyhow::anyhow!("附加 JNI 线程失败: {:?}", e))?;
+        let class: &JClass = self.keystore_class.as_obj().into();
+        let result = env
+            .call_static_method(class, "load", "()[B", &[])
+            .and_then(|v| v.l())
+            .map_err(|e| anyhow::anyhow!("调用 IdentityKeystore.load 失败: {:?}", e))?;
+        if result.is_null() {
+            return Ok(None);
+        }
+        let bytes = env
+            .convert_byte_array(JByteArray::from(result))
+            .map_err(|e| anyhow::anyhow!("读取身份数据失败: {:?}", e))?;
+        Ok(Some(bytes))
+    }
+
+    fn store(&self, data: &[u8]) -> anyhow::Result<()> {
+        let mut env = self
+            .java_vm
+            .attach_current_thread()
+            .map_err(|e| anyhow::anyhow!("附加 JNI 线程失败: {:?}", e))?;
+        let payload: JObject = env
+            .byte_array_from_slice(data)
+            .map_err(|e| anyhow::anyhow!("创建 Java 字节数组失败: {:?}", e))?
+            .into();
+        let class: &JClass = self.keystore_class.as_obj().into();
+        let stored = env
+            .call_static_method(class, "store", "([B)Z", &[JValue::Object(&payload)])
+            .and_then(|v| v.z())
+            .map_err(|e| anyhow::anyhow!("调用 IdentityKeystore.store 失败: {:?}", e))?;
+        if stored {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Android Keystore 拒绝保存身份"))
+        }
+    }
+}
+
+/// 注册 Android Keystore 为身份存储后端（需在节点启动前调用）
+#[cfg(feature = "android")]
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_williw_mobile_IdentityKeystore_nativeRegister(
+    env: JNIEnv,
+    class: JClass,
+) -> jint {
+    let java_vm = match env.get_java_vm() {
+        Ok(vm) => vm,
+        Err(e) => {
+            log::error!("获取 JavaVM 失败: {:?}", e);
+            return -1;
+        }
+    };
+    let keystore_class = match env.new_global_ref(class) {
+        Ok(global) => global,
+        Err(e) => {
+            log::error!("创建 IdentityKeystore 全局引用失败: {:?}", e);
+            return -1;
+        }
+    };
+
+    set_platform_keystore(Arc::new(AndroidKeystore { java_vm, keystore_class }));
+    log::info!("已注册 Android Keystore 身份存储");
+    0
+}
+
+/// 基于 Android Keystore 的通用密钥库
+///
+/// Java 侧 `com.williw.mobile.SecretVault` 用 Keystore 中的 AES 密钥加密各条目，
+/// 提供静态方法 `load(String)[B`、`store(String, [B)Z` 和 `delete(String)Z`。
+#[cfg(feature = "android")]
+pub struct AndroidKeyVault {
+    java_vm: jni::JavaVM,
+    vault_class: GlobalRef,
+}
+
+#[cfg(feature = "android")]
+impl AndroidKeyVault {
+    fn call<T>(
+        &self,
+        method: &str,
+        signature: &str,
+        name: &str,
+        data: Option<&[u8]>,
+        convert: impl FnOnce(&mut JNIEnv, jni::objects::JValueOwned) -> jni::errors::Result<T>,
+    ) -> anyhow::Result<T> {
+        let mut env = self
+            .java_vm
+            .attach_current_thread()
+            .map_err(|e| anyhow::anyhow!("附加 JNI 线程失败: {:?}", e))?;
+        let name: JObject = env
+            .new_string(name)
+            .map_err(|e| anyhow::anyhow!("创建 Java 字符串失败: {:?}", e))?
+            .into();
+        let payload: Option<JObject> = match data {
+            Some(data) => Some(
+                env.byte_array_from_slice(data)
+                    .map_err(|e| anyhow::anyhow!("创建 Java 字节数组失败: {:?}", e))?
+                    .into(),
+            ),
+            None => None,
+        };
+        let mut args = vec![JValue::Object(&name)];
+        if let Some(payload) = &payload {
+            args.push(JValue::Object(payload));
+        }
+        let class: &JClass = self.vault_class.as_obj().into();
+        env.call_static_method(class, method, signature, &args)
+            .and_then(|value| convert(&mut env, value))
+            .map_err(|e| anyhow::anyhow!("调用 SecretVault.{} 失败: {:?}", method, e))
+    }
+}
+
+#[cfg(feature = "android")]
+impl KeyVault for AndroidKeyVault {
+    fn backend(&self) -> &'static str {
+        "android_keystore"
+    }
+
+    fn load(&self, name: &str) -> anyhow::Result<Option<Zeroizing<Vec<u8>>>> {
+        self.call("load", "(Ljava/lang/String;)[B", name, None, |env, value| {
+            let result = value.l()?;
+            if result.is_null() {
+                return Ok(None);
+            }
+            env.convert_byte_array(JByteArray::from(result)).map(|bytes| Some(Zeroizing::new(bytes)))
+        })
+    }
+
+    fn store(&self, name: &str, secret: &[u8]) -> anyhow::Result<()> {
+        let stored = self.call("store", "(Ljava/lang/String;[B)Z", name, Some(secret), |_, value| value.z())?;
+        if stored {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Android Keystore 拒绝保存条目 {}", name))
+        }
+    }
+
+    fn delete(&self, name: &str) -> anyhow::Result<()> {
+        self.call("delete", "(Ljava/lang/String;)Z", name, None, |_, value| value.z())?;
+        Ok(())
+    }
+}
+
+/// 注册 Android Keystore 为密钥库（Solana 支付者私钥等），需在节点启动前调用
+#[cfg(feature = "android")]
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_williw_mobile_SecretVault_nativeRegister(env: JNIEnv, class: JClass) -> jint {
+    let java_vm = match env.get_java_vm() {
+        Ok(vm) => vm,
+        Err(e) => {
+            log::error!("获取 JavaVM 失败: {:?}", e);
+            return -1;
+        }
+    };
+    let vault_class = match env.new_global_ref(class) {
+        Ok(global) => global,
+        Err(e) => {
+            log::error!("创建 SecretVault 全局引用失败: {:?}", e);
+            return -1;
+        }
+    };
+
+    set_platform_vault(Arc::new(AndroidKeyVault { java_vm, vault_class }));
+    log::info!("已注册 Android Keystore 密钥库");
+    0
+}
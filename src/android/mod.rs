@@ -12,6 +12,15 @@ pub mod callbacks;
 #[cfg(feature = "android")]
 pub mod utils;
 
+#[cfg(feature = "android")]
+pub mod proximity;
+
+#[cfg(feature = "android")]
+pub mod keystore;
+
+#[cfg(feature = "android")]
+pub mod system_events;
+
 // 重新导出公共接口
 #[cfg(feature = "android")]
 pub use jni::*;
@@ -0,0 +1,151 @@
+//! Android WiFi Direct 近场链路
+//!
+//! Java 侧 `com.williw.mobile.ProximityBridge` 负责 WiFi Direct 的发现和 socket 收发，
+//! 通过以下 JNI 钩子把事件转发给 Rust 的近场中枢；Rust 发送时回调其静态方法 `send(String, byte[])`。
+
+#[cfg(feature = "android")]
+use crate::network::transport::{proximity_hub, ProximityKind, ProximityLink};
+
+#[cfg(feature = "android")]
+use jni::JNIEnv;
+#[cfg(feature = "android")]
+use jni::objects::{GlobalRef, JByteArray, JClass, JString, JValue};
+#[cfg(feature = "android")]
+use jni::sys::jint;
+#[cfg(feature = "android")]
+use std::sync::Arc;
+
+/// WiFi Direct 链路：持有 JavaVM 和桥接类的全局引用
+#[cfg(feature = "android")]
+pub struct WifiDirectLink {
+    java_vm: jni::JavaVM,
+    bridge_class: GlobalRef,
+}
+
+#[cfg(feature = "android")]
+impl ProximityLink for WifiDirectLink {
+    fn kind(&self) -> ProximityKind {
+        ProximityKind::WifiDirect
+    }
+
+    fn send(&self, peer_id: &str, data: &[u8]) -> anyhow::Result<()> {
+        let mut env = self
+            .java_vm
+            .attach_current_thread()
+            .map_err(|e| anyhow::anyhow!("附加 JNI 线程失败: {:?}", e))?;
+        let peer = env
+            .new_string(peer_id)
+            .map_err(|e| anyhow::anyhow!("创建 Java 字符串失败: {:?}", e))?;
+        let payload = env
+            .byte_array_from_slice(data)
+            .map_err(|e| anyhow::anyhow!("创建 Java 字节数组失败: {:?}", e))?;
+        let class: &JClass = self.bridge_class.as_obj().into();
+
+        let sent = env
+            .call_static_method(
+                class,
+                "send",
+                "(Ljava/lang/String;[B)Z",
+                &[JValue::Object(&peer), JValue::Object(&payload)],
+            )
+            .and_then(|v| v.z())
+            .map_err(|e| anyhow::anyhow!("调用 ProximityBridge.send 失败: {:?}", e))?;
+
+        if sent {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("WiFi Direct 发送被拒绝: {}", peer_id))
+        }
+    }
+}
+
+/// WiFi Direct 组建立后注册链路
+#[cfg(feature = "android")]
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_williw_mobile_ProximityBridge_nativeRegister(
+    env: JNIEnv,
+    class: JClass,
+) -> jint {
+    let java_vm = match env.get_java_vm() {
+        Ok(vm) => vm,
+        Err(e) => {
+            log::error!("获取 JavaVM 失败: {:?}", e);
+            return -1;
+        }
+    };
+    let bridge_class = match env.new_global_ref(class) {
+        Ok(global) => global,
+        Err(e) => {
+            log::error!("创建 ProximityBridge 全局引用失败: {:?}", e);
+            return -1;
+        }
+    };
+
+    proximity_hub().set_link(Arc::new(WifiDirectLink { java_vm, bridge_class }));
+    0
+}
+
+/// WiFi Direct 组解散或被禁用
+#[cfg(feature = "android")]
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_williw_mobile_ProximityBridge_nativeUnregister(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    proximity_hub().clear_link();
+}
+
+/// 发现相邻节点
+#[cfg(feature = "android")]
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_williw_mobile_ProximityBridge_nativeOnPeerFound(
+    mut env: JNIEnv,
+    _class: JClass,
+    peer_id: JString,
+    address: JString,
+) {
+    let peer: String = match env.get_string(&peer_id) {
+        Ok(s) => s.into(),
+        Err(e) => {
+            log::error!("转换节点ID失败: {:?}", e);
+            return;
+        }
+    };
+    let address: String = env.get_string(&address).map(Into::into).unwrap_or_default();
+    proximity_hub().peer_found(&peer, &address);
+}
+
+/// 相邻节点离开
+#[cfg(feature = "android")]
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_williw_mobile_ProximityBridge_nativeOnPeerLost(
+    mut env: JNIEnv,
+    _class: JClass,
+    peer_id: JString,
+) {
+    if let Ok(peer) = env.get_string(&peer_id) {
+        proximity_hub().peer_lost(&String::from(peer));
+    }
+}
+
+/// 收到数据帧
+#[cfg(feature = "android")]
+#[no_mangle]
+pub unsafe extern "C" fn Java_com_williw_mobile_ProximityBridge_nativeOnFrame(
+    mut env: JNIEnv,
+    _class: JClass,
+    peer_id: JString,
+    data: JByteArray,
+) {
+    let peer: String = match env.get_string(&peer_id) {
+        Ok(s) => s.into(),
+        Err(e) => {
+            log::error!("转换节点ID失败: {:?}", e);
+            return;
+        }
+    };
+    match env.convert_byte_array(&data) {
+        Ok(frame) => proximity_hub().deliver(&peer, frame),
+        Err(e) => log::error!("读取近场数据帧失败: {:?}", e),
+    }
+}
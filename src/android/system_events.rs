@@ -0,0 +1,33 @@
+//! Android 系统事件
+//!
+//! Java 侧 `com.williw.mobile.SystemEventsBridge` 监听 Doze（`ACTION_DEVICE_IDLE_MODE_CHANGED`）
+//! 和 `ConnectivityManager.NetworkCallback`，通过以下 JNI 钩子转发给系统事件中枢。
+
+#[cfg(feature = "android")]
+use crate::device::{system_event_hub, SystemEvent};
+
+#[cfg(feature = "android")]
+use jni::objects::JClass;
+#[cfg(feature = "android")]
+use jni::JNIEnv;
+
+/// 设备进入 Doze 或即将休眠
+#[cfg(feature = "android")]
+#[no_mangle]
+pub extern "C" fn Java_com_williw_mobile_SystemEventsBridge_nativeOnSuspend(_env: JNIEnv, _class: JClass) {
+    system_event_hub().emit(SystemEvent::Suspend);
+}
+
+/// 设备退出 Doze
+#[cfg(feature = "android")]
+#[no_mangle]
+pub extern "C" fn Java_com_williw_mobile_SystemEventsBridge_nativeOnResume(_env: JNIEnv, _class: JClass) {
+    system_event_hub().emit(SystemEvent::Resume);
+}
+
+/// 默认网络可用、丢失或能力变化
+#[cfg(feature = "android")]
+#[no_mangle]
+pub extern "C" fn Java_com_williw_mobile_SystemEventsBridge_nativeOnNetworkChanged(_env: JNIEnv, _class: JClass) {
+    system_event_hub().emit(SystemEvent::NetworkChanged);
+}
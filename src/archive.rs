@@ -0,0 +1,372 @@
+//! 冷存储归档
+//!
+//! 已完成轮次的产物（checkpoint、证明等）超过保留期后：
+//! 1. zstd 压缩后上传到对象存储（S3 / Cloudflare R2），对象按内容哈希命名
+//! 2. 本地索引记录原路径、blake3 哈希和对象键，并同步一份到对象存储
+//! 3. 上传成功后删除本地文件释放空间，哈希保留在本地索引中用于完整性校验
+//!
+//! 需要时（例如争议处理）可按原路径透明取回，取回内容会先校验哈希。
+
+use anyhow::{anyhow, Result};
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+const INDEX_FILE: &str = "archive_index.json";
+
+/// 对象存储提供方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectStoreProvider {
+    S3,
+    /// Cloudflare R2（S3 兼容接口）
+    R2,
+}
+
+/// 对象存储配置，访问密钥从环境变量 `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` 读取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectStoreConfig {
+    pub provider: ObjectStoreProvider,
+    pub bucket: String,
+    /// S3 区域（R2 固定为 auto）
+    pub region: String,
+    /// 自定义端点（S3 兼容服务）
+    pub endpoint: Option<String>,
+    /// R2 账户 ID，用于拼接端点
+    pub account_id: Option<String>,
+    /// 对象键前缀
+    pub prefix: String,
+}
+
+impl Default for ObjectStoreConfig {
+    fn default() -> Self {
+        Self {
+            provider: ObjectStoreProvider::S3,
+            bucket: String::new(),
+            region: "us-east-1".to_string(),
+            endpoint: None,
+            account_id: None,
+            prefix: "williw-archive".to_string(),
+        }
+    }
+}
+
+impl ObjectStoreConfig {
+    /// 创建对象存储客户端
+    pub fn build(&self) -> Result<Arc<dyn ObjectStore>> {
+        if self.bucket.is_empty() {
+            return Err(anyhow!("未配置归档存储桶"));
+        }
+        let mut builder = object_store::aws::AmazonS3Builder::from_env().with_bucket_name(&self.bucket);
+        builder = match self.provider {
+            ObjectStoreProvider::S3 => builder.with_region(&self.region),
+            ObjectStoreProvider::R2 => {
+                let endpoint = match (&self.endpoint, &self.account_id) {
+                    (Some(endpoint), _) => endpoint.clone(),
+                    (None, Some(account)) => format!("https://{}.r2.cloudflarestorage.com", account),
+                    (None, None) => return Err(anyhow!("R2 需要配置 account_id 或 endpoint")),
+                };
+                builder.with_region("auto").with_endpoint(endpoint)
+            }
+        };
+        if let (ObjectStoreProvider::S3, Some(endpoint)) = (self.provider, &self.endpoint) {
+            builder = builder.with_endpoint(endpoint);
+        }
+        Ok(Arc::new(builder.build()?))
+    }
+}
+
+/// 归档策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveConfig {
+    pub enabled: bool,
+    /// 需要归档的产物目录（checkpoint、证明等）
+    pub artifact_dirs: Vec<PathBuf>,
+    /// 超过该天数的文件会被归档
+    pub max_age_days: u64,
+    /// zstd 压缩级别
+    pub compression_level: i32,
+    /// 扫描间隔（秒）
+    pub scan_interval_secs: u64,
+    /// 本地索引路径，默认放在第一个产物目录下
+    pub index_path: Option<PathBuf>,
+    pub store: ObjectStoreConfig,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            artifact_dirs: Vec::new(),
+            max_age_days: 30,
+            compression_level: 3,
+            scan_interval_secs: 3600,
+            index_path: None,
+            store: ObjectStoreConfig::default(),
+        }
+    }
+}
+
+/// 索引条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub original_path: PathBuf,
+    pub object_key: String,
+    /// 原始内容的 blake3 哈希
+    pub blake3: String,
+    pub original_size: u64,
+    pub compressed_size: u64,
+    pub archived_at: i64,
+}
+
+/// 归档索引（按原路径）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveIndex {
+    pub entries: BTreeMap<PathBuf, ArchiveEntry>,
+}
+
+/// 一次归档扫描的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveReport {
+    pub archived: usize,
+    pub failed: usize,
+    pub bytes_freed: u64,
+    pub bytes_uploaded: u64,
+}
+
+/// 冷存储归档器
+pub struct ColdArchiver {
+    config: ArchiveConfig,
+    store: Arc<dyn ObjectStore>,
+    index_path: PathBuf,
+    index: RwLock<ArchiveIndex>,
+}
+
+impl ColdArchiver {
+    /// 按配置创建对象存储客户端
+    pub fn new(config: ArchiveConfig) -> Result<Self> {
+        let store = config.store.build()?;
+        Self::with_store(config, store)
+    }
+
+    /// 使用指定的对象存储（便于测试或接入其它后端）
+    pub fn with_store(config: ArchiveConfig, store: Arc<dyn ObjectStore>) -> Result<Self> {
+        let index_path = match (&config.index_path, config.artifact_dirs.first()) {
+            (Some(path), _) => path.clone(),
+            (None, Some(dir)) => dir.join(INDEX_FILE),
+            (None, None) => return Err(anyhow!("未配置归档目录")),
+        };
+        let index = match std::fs::read(&index_path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ArchiveIndex::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            config,
+            store,
+            index_path,
+            index: RwLock::new(index),
+        })
+    }
+
+    pub fn config(&self) -> &ArchiveConfig {
+        &self.config
+    }
+
+    pub fn index(&self) -> ArchiveIndex {
+        self.index.read().clone()
+    }
+
+    fn object_path(&self, name: &str) -> ObjectPath {
+        ObjectPath::from(format!("{}/{}", self.config.store.prefix.trim_end_matches('/'), name))
+    }
+
+    /// 扫描产物目录，归档超过保留期的文件
+    pub async fn run_once(&self) -> Result<ArchiveReport> {
+        let max_age = Duration::from_secs(self.config.max_age_days * 24 * 3600);
+        let now = SystemTime::now();
+        let mut candidates = Vec::new();
+        for dir in &self.config.artifact_dirs {
+            collect_files(dir, &mut candidates);
+        }
+
+        let mut report = ArchiveReport::default();
+        for path in candidates {
+            if path == self.index_path || self.index.read().entries.contains_key(&path) {
+                continue;
+            }
+            let old_enough = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(|modified| now.duration_since(modified).unwrap_or_default() >= max_age)
+                .unwrap_or(false);
+            if !old_enough {
+                continue;
+            }
+            match self.archive_file(&path).await {
+                Ok(entry) => {
+                    report.archived += 1;
+                    report.bytes_freed += entry.original_size;
+                    report.bytes_uploaded += entry.compressed_size;
+                }
+                Err(e) => {
+                    log::warn!("[归档] {} 归档失败: {}", path.display(), e);
+                    report.failed += 1;
+                }
+            }
+        }
+
+        if report.archived > 0 {
+            self.persist_index().await?;
+            println!(
+                "[归档] 已归档 {} 个文件，释放 {} 字节（上传 {} 字节）",
+                report.archived, report.bytes_freed, report.bytes_uploaded
+            );
+        }
+        Ok(report)
+    }
+
+    /// 压缩、上传并删除单个文件
+    pub async fn archive_file(&self, path: &Path) -> Result<ArchiveEntry> {
+        let data = std::fs::read(path)?;
+        let hash = blake3::hash(&data).to_hex().to_string();
+        let compressed = zstd::encode_all(data.as_slice(), self.config.compression_level)?;
+        let object_key = self.object_path(&format!("objects/{}.zst", hash));
+
+        self.store.put(&object_key, PutPayload::from(compressed.clone())).await?;
+
+        let entry = ArchiveEntry {
+            original_path: path.to_path_buf(),
+            object_key: object_key.to_string(),
+            blake3: hash,
+            original_size: data.len() as u64,
+            compressed_size: compressed.len() as u64,
+            archived_at: chrono::Utc::now().timestamp(),
+        };
+        // 先写索引再删除本地文件，避免删除后丢失对象位置
+        self.index.write().entries.insert(path.to_path_buf(), entry.clone());
+        self.save_local_index()?;
+        std::fs::remove_file(path)?;
+        Ok(entry)
+    }
+
+    /// 取回已归档文件的内容（校验哈希）
+    pub async fn retrieve(&self, path: &Path) -> Result<Vec<u8>> {
+        let entry = self
+            .index
+            .read()
+            .entries
+            .get(path)
+            .cloned()
+            .ok_or_else(|| anyhow!("{} 未被归档", path.display()))?;
+        let compressed = self
+            .store
+            .get(&ObjectPath::from(entry.object_key.as_str()))
+            .await?
+            .bytes()
+            .await?;
+        let data = zstd::decode_all(compressed.as_ref())?;
+        let hash = blake3::hash(&data).to_hex().to_string();
+        if hash != entry.blake3 {
+            return Err(anyhow!("归档内容哈希不匹配: 期望 {}, 实际 {}", entry.blake3, hash));
+        }
+        Ok(data)
+    }
+
+    /// 确保文件在本地可用：已存在直接返回，已归档则取回并写回原路径
+    pub async fn ensure_local(&self, path: &Path) -> Result<PathBuf> {
+        if path.exists() {
+            return Ok(path.to_path_buf());
+        }
+        let data = self.retrieve(path).await?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data)?;
+        Ok(path.to_path_buf())
+    }
+
+    /// 校验本地文件与索引中记录的哈希一致
+    pub fn verify_local(&self, path: &Path) -> Result<bool> {
+        let expected = self
+            .index
+            .read()
+            .entries
+            .get(path)
+            .map(|e| e.blake3.clone())
+            .ok_or_else(|| anyhow!("{} 没有归档记录", path.display()))?;
+        let data = std::fs::read(path)?;
+        Ok(blake3::hash(&data).to_hex().to_string() == expected)
+    }
+
+    fn save_local_index(&self) -> Result<()> {
+        let data = serde_json::to_vec_pretty(&*self.index.read())?;
+        if let Some(parent) = self.index_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.index_path.with_extension("json.tmp");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &self.index_path)?;
+        Ok(())
+    }
+
+    /// 保存本地索引并同步一份到对象存储
+    async fn persist_index(&self) -> Result<()> {
+        self.save_local_index()?;
+        let data = serde_json::to_vec_pretty(&*self.index.read())?;
+        self.store.put(&self.object_path("index.json"), PutPayload::from(data)).await?;
+        Ok(())
+    }
+}
+
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, out);
+        } else if !path.to_string_lossy().ends_with(".tmp") {
+            out.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_archive_and_retrieve_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("williw-archive-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("round-1")).unwrap();
+        let artifact = dir.join("round-1").join("proof.bin");
+        std::fs::write(&artifact, vec![7u8; 4096]).unwrap();
+
+        let config = ArchiveConfig {
+            enabled: true,
+            artifact_dirs: vec![dir.clone()],
+            max_age_days: 0,
+            ..Default::default()
+        };
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let archiver = ColdArchiver::with_store(config.clone(), store.clone()).unwrap();
+
+        let report = archiver.run_once().await.unwrap();
+        assert_eq!(report.archived, 1);
+        assert!(!artifact.exists());
+        assert!(report.bytes_uploaded < report.bytes_freed);
+
+        // 重新加载索引后仍能取回
+        let archiver = ColdArchiver::with_store(config, store).unwrap();
+        assert_eq!(archiver.retrieve(&artifact).await.unwrap(), vec![7u8; 4096]);
+        archiver.ensure_local(&artifact).await.unwrap();
+        assert!(archiver.verify_local(&artifact).unwrap());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,156 +1,558 @@
 use crate::config::AppConfig;
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
-/// 解析命令行参数并返回配置
-pub fn parse_args_and_build_config() -> AppConfig {
-    // 解析命令行参数
-    let args: Vec<String> = std::env::args().collect();
-    let mut stats_output: Option<String> = None;
-    let mut node_id: Option<usize> = None;
-    let mut model_dim: Option<usize> = None;
-    let mut quic_port: Option<u16> = None;
-    let mut bootstrap_peers: Vec<String> = Vec::new();
-
-    let mut i = 1;
-    while i < args.len() {
-        match args[i].as_str() {
-            "--stats-output" => {
-                if i + 1 < args.len() {
-                    stats_output = Some(args[i + 1].clone());
-                    i += 2;
-                } else {
-                    i += 1;
-                }
+/// Williw 去中心化训练节点
+///
+/// 不带子命令时等同于 `run`，兼容旧的启动参数。
+#[derive(Parser, Debug)]
+#[command(name = "williw", version, about = "Williw 去中心化训练节点")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+impl Cli {
+    /// 实际要执行的子命令（未指定时为 `run`）
+    pub fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Run(self.run))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 启动训练节点
+    Run(RunArgs),
+    /// 从 Hugging Face 下载模型
+    DownloadModel(DownloadModelArgs),
+    /// 按切分方案切分模型
+    SplitModel(SplitModelArgs),
+    /// 测量本机算力并生成校准算力分
+    Benchmark(BenchmarkArgs),
+    /// 节点密钥管理
+    #[command(subcommand)]
+    Keys(KeysCommand),
+    /// 统计数据管理
+    #[command(subcommand)]
+    Stats(StatsCommand),
+    /// 配置文件管理
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// 本地模型缓存管理
+    #[command(subcommand)]
+    Cache(CacheCommand),
+    /// 数据集清单管理
+    #[command(subcommand)]
+    Dataset(DatasetCommand),
+    /// 在确定性模拟网络上跑 gossip 流量，注入延迟、丢包和分区
+    Simulate(SimulateArgs),
+    /// 终端仪表盘：读取运行中节点的统计数据库实时展示
+    #[cfg(feature = "tui")]
+    Top(TopArgs),
+    /// WASM 任务插件管理
+    #[cfg(feature = "plugins")]
+    #[command(subcommand)]
+    Plugins(PluginsCommand),
+}
+
+#[derive(Args, Debug, Default)]
+pub struct RunArgs {
+    /// TOML 配置文件，运行期间监视其变化
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// 定期导出统计数据（.ndjson/.jsonl 追加写入，其余覆盖为 JSON）
+    #[arg(long)]
+    pub stats_output: Option<PathBuf>,
+
+    /// 本地统计数据库，记录每个 tick 的指标和邻居快照（供 `top` 等读取）
+    #[arg(long)]
+    pub stats_db: Option<PathBuf>,
+
+    /// `benchmark --output` 生成的已签名算力分，用于推荐模型维度
+    #[arg(long)]
+    pub device_score: Option<PathBuf>,
+
+    /// 本地节点编号（多节点测试时自动分配端口和 bootstrap）
+    #[arg(long)]
+    pub node_id: Option<usize>,
+
+    /// 模型维度
+    #[arg(long)]
+    pub model_dim: Option<usize>,
+
+    /// QUIC 端口（未指定时读取 GGB_QUIC_PORT）
+    #[arg(long)]
+    pub quic_port: Option<u16>,
+
+    /// bootstrap 节点地址，可重复指定
+    #[arg(long)]
+    pub bootstrap: Vec<String>,
+
+    /// 观察模式：只同步网络状态，不训练、不接受任务、不签名
+    #[arg(long)]
+    pub watch_only: bool,
+
+    /// 故障注入脚本（JSON），按 tick 触发传输中断、分片损坏等故障
+    #[cfg(feature = "chaos")]
+    #[arg(long)]
+    pub chaos_script: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DownloadModelArgs {
+    /// 模型名称，如 `bert-base-uncased`
+    pub model: String,
+
+    /// 缓存目录（默认 ./models_cache/<模型名>）
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Hugging Face 访问令牌（未指定时读取 HF_TOKEN）
+    #[arg(long)]
+    pub hf_token: Option<String>,
+
+    /// TOML 配置文件，按其中的 work_schedule 等待允许下载的时段和网络，按 model_cache 放入缓存
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// 不放入本地模型缓存
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SplitModelArgs {
+    /// 模型名称
+    #[arg(long)]
+    pub model_name: String,
+
+    /// 模型文件或目录
+    #[arg(long)]
+    pub model_path: PathBuf,
+
+    /// 切分方案 JSON 文件（节点 ID -> 切分方案）
+    #[arg(long)]
+    pub plan: PathBuf,
+
+    /// 要切分的节点 ID（默认使用本节点身份）
+    #[arg(long)]
+    pub node_id: Option<String>,
+
+    /// 输出目录
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// 用节点身份签名分片清单
+    #[arg(long)]
+    pub sign: bool,
+
+    /// 身份文件路径
+    #[arg(long)]
+    pub identity: Option<PathBuf>,
+
+    /// 不把分片放入本地模型缓存
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchmarkArgs {
+    /// 每项测试的持续时间（秒）
+    #[arg(long, default_value_t = 3)]
+    pub seconds: u64,
+
+    /// 矩阵乘法测试的矩阵边长
+    #[arg(long, default_value_t = 256)]
+    pub matrix_size: usize,
+
+    /// 内存带宽测试的缓冲区大小（MB）
+    #[arg(long, default_value_t = 64)]
+    pub memory_mb: usize,
+
+    /// 用节点身份签名算力分并写入该文件
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// 身份文件路径
+    #[arg(long)]
+    pub identity: Option<PathBuf>,
+
+    /// 以 JSON 输出结果
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    /// 节点数（命名为 node-0、node-1 ...）
+    #[arg(long, default_value_t = 8)]
+    pub nodes: usize,
+
+    /// gossip 轮数
+    #[arg(long, default_value_t = 20)]
+    pub rounds: usize,
+
+    /// 每个节点每轮发送的目标数
+    #[arg(long, default_value_t = 3)]
+    pub fanout: usize,
+
+    /// 每轮间隔（虚拟毫秒）
+    #[arg(long, default_value_t = 100.0)]
+    pub interval_ms: f64,
+
+    /// 消息大小（字节）
+    #[arg(long, default_value_t = 1024)]
+    pub message_bytes: usize,
+
+    /// 延迟分布：`fixed:50`、`uniform:20:80`、`normal:50:10`、`pareto:20:2.5`
+    #[arg(long, default_value = "fixed:20")]
+    pub latency: String,
+
+    /// 抖动（毫秒）
+    #[arg(long, default_value_t = 0.0)]
+    pub jitter_ms: f64,
+
+    /// 丢包率（0~1）
+    #[arg(long, default_value_t = 0.0)]
+    pub loss: f64,
+
+    /// 随机种子，相同种子结果可复现
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// 分区，如 `node-0,node-1/node-2,node-3`，可重复指定
+    #[arg(long)]
+    pub partition: Vec<String>,
+
+    /// 模拟网络配置 JSON（含单条链路覆盖），指定时忽略上面的链路参数
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// 以 JSON 输出结果
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[cfg(feature = "tui")]
+#[derive(Args, Debug)]
+pub struct TopArgs {
+    /// 节点的统计数据库（节点需以 `--stats-db` 启动）
+    #[arg(long, default_value = "williw_stats.db")]
+    pub db: PathBuf,
+
+    /// 刷新间隔（毫秒）
+    #[arg(long, default_value_t = 1000)]
+    pub refresh_ms: u64,
+}
+
+#[cfg(feature = "plugins")]
+#[derive(Subcommand, Debug)]
+pub enum PluginsCommand {
+    /// 加载并校验插件目录下的所有插件
+    Check {
+        /// 插件根目录（每个子目录一个插件）
+        #[arg(long, default_value = "plugins")]
+        dir: PathBuf,
+    },
+    /// 用本地文件作为输入运行插件，便于插件开发调试
+    Run {
+        /// 插件目录
+        plugin: PathBuf,
+
+        /// 输入文件
+        #[arg(long)]
+        input: PathBuf,
+
+        /// 输出文件，未指定时写到标准输出
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KeysCommand {
+    /// 轮换节点密钥并输出需广播的轮换公告
+    Rotate {
+        /// 身份文件路径
+        #[arg(long)]
+        identity: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatsCommand {
+    /// 从本地统计数据库导出 tick 指标
+    Export {
+        /// 统计数据库路径
+        #[arg(long, default_value = "williw_stats.db")]
+        db: PathBuf,
+
+        /// 输出文件（.ndjson/.jsonl 为逐行格式，其余为 JSON 数组）
+        #[arg(long)]
+        output: PathBuf,
+
+        /// 起始时间（Unix 秒，默认不限）
+        #[arg(long)]
+        from: Option<i64>,
+
+        /// 结束时间（Unix 秒，默认当前时间）
+        #[arg(long)]
+        to: Option<i64>,
+
+        /// 按该时间桶长度（秒）聚合后导出
+        #[arg(long)]
+        bucket_secs: Option<i64>,
+    },
+}
+
+/// 定位模型缓存：`--dir` 优先，其次配置文件中的 `model_cache.dir`
+#[derive(Args, Debug, Default)]
+pub struct CacheLocation {
+    /// 缓存目录
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+
+    /// TOML 配置文件
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// 列出缓存对象及其引用名
+    List {
+        #[command(flatten)]
+        location: CacheLocation,
+
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 淘汰缓存对象：指定名字/哈希前缀淘汰单个对象，或按 `--max-bytes` 淘汰到容量以内
+    Evict {
+        /// 名字（如 `bert-base-uncased/tokenizer.json`）或至少 8 位的哈希前缀
+        key: Option<String>,
+
+        /// 淘汰到总大小不超过该字节数（固定和使用中的对象除外）
+        #[arg(long, conflicts_with = "key")]
+        max_bytes: Option<u64>,
+
+        #[command(flatten)]
+        location: CacheLocation,
+    },
+    /// 固定对象，使其不被淘汰
+    Pin {
+        key: String,
+
+        #[command(flatten)]
+        location: CacheLocation,
+    },
+    /// 取消固定
+    Unpin {
+        key: String,
+
+        #[command(flatten)]
+        location: CacheLocation,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DatasetCommand {
+    /// 为目录下的数据分块生成清单并用节点身份签名
+    Sign {
+        /// 数据目录
+        dir: PathBuf,
+
+        #[arg(long)]
+        name: String,
+
+        #[arg(long, default_value = "1")]
+        version: String,
+
+        /// 许可证标签（SPDX 标识）
+        #[arg(long)]
+        license: String,
+
+        /// 分块来源 URL 前缀，分块的相对路径拼接在其后
+        #[arg(long)]
+        source_url: Option<String>,
+
+        /// 身份文件路径
+        #[arg(long)]
+        identity: Option<PathBuf>,
+
+        /// 清单输出路径，默认为数据目录下的 `dataset-manifest.json`
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// 校验清单签名和本地分块
+    Verify {
+        manifest: PathBuf,
+
+        /// 分块所在目录，默认为清单所在目录
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// 信任的发布者节点 ID（可重复）
+        #[arg(long)]
+        publisher: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// 检查配置文件，列出错误和警告
+    Validate {
+        /// TOML 配置文件
+        path: PathBuf,
+
+        /// 同时检查运行环境（如端口是否被占用）
+        #[arg(long)]
+        runtime: bool,
+    },
+}
+
+impl RunArgs {
+    /// 根据启动参数和环境变量构建配置
+    pub fn build_config(&self) -> AppConfig {
+        let mut quic_port = self.quic_port;
+        let mut bootstrap_peers = self.bootstrap.clone();
+        let watch_only = self.watch_only
+            || std::env::var("GGB_WATCH_ONLY").map(|v| v == "1" || v == "true").unwrap_or(false);
+
+        // 如果没有通过命令行指定端口，尝试从环境变量读取
+        if quic_port.is_none() {
+            if let Ok(port_str) = std::env::var("GGB_QUIC_PORT") {
+                quic_port = port_str.parse().ok();
             }
-            "--node-id" => {
-                if i + 1 < args.len() {
-                    node_id = args[i + 1].parse().ok();
-                    i += 2;
-                } else {
-                    i += 1;
-                }
+        }
+
+        // 如果指定了node-id但没有指定端口，根据node-id自动分配端口
+        if quic_port.is_none() {
+            if let Some(id) = self.node_id {
+                quic_port = Some(9234 + id as u16);
             }
-            "--model-dim" => {
-                if i + 1 < args.len() {
-                    model_dim = args[i + 1].parse().ok();
-                    i += 2;
-                } else {
-                    i += 1;
+        }
+
+        // 根据 node-id 自动设置 bootstrap（连接其他节点）
+        if bootstrap_peers.is_empty() {
+            if let Some(id) = self.node_id {
+                // 自动连接到其他节点
+                for other_id in 0..3 {
+                    if other_id != id {
+                        let port = 9234 + other_id;
+                        bootstrap_peers.push(format!("127.0.0.1:{}", port));
+                    }
                 }
             }
-            "--quic-port" => {
-                if i + 1 < args.len() {
-                    quic_port = args[i + 1].parse().ok();
-                    i += 2;
-                } else {
-                    i += 1;
+        }
+
+        if let Some(id) = self.node_id {
+            println!("节点 ID: {}", id);
+        }
+
+        // 构建配置，支持自定义模型维度和端口
+        let mut config = match &self.config {
+            Some(path) => match AppConfig::from_toml_file(path) {
+                Ok(config) => {
+                    println!("使用配置文件: {}", path.display());
+                    config
                 }
-            }
-            "--bootstrap" => {
-                if i + 1 < args.len() {
-                    bootstrap_peers.push(args[i + 1].clone());
-                    i += 2;
-                } else {
-                    i += 1;
+                Err(e) => {
+                    eprintln!("加载配置文件 {} 失败，使用默认配置: {:?}", path.display(), e);
+                    AppConfig::default()
                 }
-            }
-            _ => i += 1,
+            },
+            None => AppConfig::default(),
+        };
+        if let Some(dim) = self.model_dim {
+            // config.inference.model_dim = dim; // 注释掉，因为AppConfig没有inference字段
+            println!("使用自定义模型维度: {}", dim);
         }
-    }
-
-    // 如果没有通过命令行指定端口，尝试从环境变量读取
-    if quic_port.is_none() {
-        if let Ok(port_str) = std::env::var("GGB_QUIC_PORT") {
-            quic_port = port_str.parse().ok();
+        if let Some(port) = quic_port {
+            config.comms.quic_bind = Some(std::net::SocketAddr::new(
+                std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+                port,
+            ));
         }
-    }
 
-    // 如果指定了node-id但没有指定端口，根据node-id自动分配端口
-    if quic_port.is_none() {
-        if let Some(id) = node_id {
-            quic_port = Some(9234 + id as u16);
+        // 从环境变量读取 checkpoint 目录
+        if let Ok(checkpoint_dir) = std::env::var("GGB_CHECKPOINT_DIR") {
+            // config.inference.checkpoint_dir = Some(PathBuf::from(checkpoint_dir)); // 注释掉
+            println!("使用checkpoint目录: {}", checkpoint_dir);
         }
-    }
 
-    // 根据 node-id 自动设置 bootstrap（连接其他节点）
-    if bootstrap_peers.is_empty() {
-        if let Some(id) = node_id {
-            // 自动连接到其他节点
-            for other_id in 0..3 {
-                if other_id != id {
-                    let port = 9234 + other_id;
-                    bootstrap_peers.push(format!("127.0.0.1:{}", port));
-                }
+        // 从环境变量读取学习率
+        if let Ok(lr_str) = std::env::var("GGB_LEARNING_RATE") {
+            if let Ok(lr) = lr_str.parse::<f32>() {
+                // config.inference.learning_rate = lr; // 注释掉
+                println!("使用自定义学习率: {}", lr);
             }
         }
-    }
 
-    if let Some(id) = node_id {
-        println!("节点 ID: {}", id);
-    }
+        // 从环境变量读取是否启用训练
+        if let Ok(use_training) = std::env::var("GGB_USE_TRAINING") {
+            println!("训练模式设置: {}", use_training);
+        }
 
-    // 构建配置，支持自定义模型维度和端口
-    let mut config = AppConfig::default();
-    if let Some(dim) = model_dim {
-        // config.inference.model_dim = dim; // 注释掉，因为AppConfig没有inference字段
-        println!("使用自定义模型维度: {}", dim);
-    }
-    if let Some(port) = quic_port {
-        config.comms.quic_bind = Some(std::net::SocketAddr::new(
-            std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
-            port,
-        ));
-    }
+        // 添加 bootstrap 节点
+        for peer in &bootstrap_peers {
+            if let Ok(addr) = peer.parse() {
+                config.comms.quic_bootstrap.push(addr);
+                println!("添加 Bootstrap 节点: {}", peer);
+            }
+        }
+        if let Some(port) = quic_port {
+            println!("使用 QUIC 端口: {}", port);
+        }
+        if watch_only {
+            config.node_mode = crate::config::NodeMode::WatchOnly;
+            println!("以观察模式运行（不训练、不接受任务、不签名）");
+        }
 
-    // 从环境变量读取 checkpoint 目录
-    if let Ok(checkpoint_dir) = std::env::var("GGB_CHECKPOINT_DIR") {
-        // config.inference.checkpoint_dir = Some(PathBuf::from(checkpoint_dir)); // 注释掉
-        println!("使用checkpoint目录: {}", checkpoint_dir);
+        config
     }
+}
 
-    // 从环境变量读取学习率
-    if let Ok(lr_str) = std::env::var("GGB_LEARNING_RATE") {
-        if let Ok(lr) = lr_str.parse::<f32>() {
-            // config.inference.learning_rate = lr; // 注释掉
-            println!("使用自定义学习率: {}", lr);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_without_subcommand_mean_run() {
+        let cli = Cli::parse_from(["williw", "--node-id", "1", "--bootstrap", "127.0.0.1:9234"]);
+        match cli.into_command() {
+            Command::Run(args) => {
+                assert_eq!(args.node_id, Some(1));
+                assert_eq!(args.bootstrap, vec!["127.0.0.1:9234".to_string()]);
+            }
+            other => panic!("unexpected command: {:?}", other),
         }
-    }
 
-    // 从环境变量读取是否启用训练
-    if let Ok(use_training) = std::env::var("GGB_USE_TRAINING") {
-        // if config.inference.use_training { // 注释掉
-        //     println!("启用训练模式");
-        // }
-        println!("训练模式设置: {}", use_training);
-    }
+        let cli = Cli::parse_from(["williw", "keys", "rotate"]);
+        assert!(matches!(cli.into_command(), Command::Keys(KeysCommand::Rotate { identity: None })));
 
-    // 添加 bootstrap 节点
-    for peer in &bootstrap_peers {
-        if let Ok(addr) = peer.parse() {
-            config.comms.quic_bootstrap.push(addr);
-            println!("添加 Bootstrap 节点: {}", peer);
+        let cli = Cli::parse_from(["williw", "cache", "evict", "--max-bytes", "1024", "--dir", "/tmp/cache"]);
+        match cli.into_command() {
+            Command::Cache(CacheCommand::Evict { key, max_bytes, location }) => {
+                assert_eq!((key, max_bytes), (None, Some(1024)));
+                assert_eq!(location.dir, Some(PathBuf::from("/tmp/cache")));
+            }
+            other => panic!("unexpected command: {:?}", other),
         }
-    }
-    if let Some(port) = quic_port {
-        println!("使用 QUIC 端口: {}", port);
-    }
-
-    config
-}
 
-/// 获取统计输出路径
-pub fn get_stats_output() -> Option<String> {
-    let args: Vec<String> = std::env::args().collect();
-    let mut stats_output: Option<String> = None;
-    let mut i = 1;
-    while i < args.len() {
-        if args[i] == "--stats-output" {
-            if i + 1 < args.len() {
-                stats_output = Some(args[i + 1].clone());
+        let cli = Cli::parse_from(["williw", "simulate", "--latency", "normal:50:10", "--partition", "node-0/node-1"]);
+        match cli.into_command() {
+            Command::Simulate(args) => {
+                assert_eq!(args.latency, "normal:50:10");
+                assert_eq!(args.partition, vec!["node-0/node-1".to_string()]);
             }
-            break;
+            other => panic!("unexpected command: {:?}", other),
         }
-        i += 1;
     }
-    stats_output
-}
\ No newline at end of file
+}
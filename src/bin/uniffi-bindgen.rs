@@ -0,0 +1,7 @@
+//! UniFFI 绑定生成器
+//!
+//! 用法：`cargo run --features uniffi --bin uniffi-bindgen generate src/williw.udl --language kotlin --out-dir bindings/kotlin`
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
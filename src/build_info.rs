@@ -0,0 +1,216 @@
+//! 构建元数据与来源校验
+//!
+//! 节点把构建信息（git 提交、feature 集合、目标三元组、可复现构建哈希）放进
+//! 签名的能力记录中广播。运营者可以配置策略，拒绝向运行未知或黑名单构建的节点分配任务。
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+use crate::device::DeviceCapabilities;
+
+/// 构建信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// crate 版本
+    pub version: String,
+    pub git_commit: String,
+    /// 启用的 cargo feature
+    pub features: Vec<String>,
+    pub target: String,
+    pub rustc_version: String,
+    /// 可复现构建哈希（源码 + 清单 + feature + 目标）
+    pub source_hash: String,
+}
+
+impl BuildInfo {
+    /// 当前二进制的构建信息
+    pub fn current() -> Self {
+        let features = option_env!("WILLIW_FEATURES").unwrap_or_default();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: option_env!("WILLIW_GIT_COMMIT").unwrap_or("unknown").to_string(),
+            features: features
+                .split(',')
+                .filter(|f| !f.is_empty())
+                .map(str::to_string)
+                .collect(),
+            target: option_env!("WILLIW_TARGET").unwrap_or("unknown").to_string(),
+            rustc_version: option_env!("WILLIW_RUSTC_VERSION").unwrap_or("unknown").to_string(),
+            source_hash: option_env!("WILLIW_SOURCE_HASH").unwrap_or("unknown").to_string(),
+        }
+    }
+
+    /// 简短描述，用于集群视图展示
+    pub fn short(&self) -> String {
+        let commit: String = self.git_commit.chars().take(8).collect();
+        let hash: String = self.source_hash.chars().take(12).collect();
+        format!("v{} {} {} [{}]", self.version, commit, hash, self.target)
+    }
+}
+
+/// 构建准入策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildPolicy {
+    /// 是否允许未公告构建信息的节点
+    pub allow_unknown: bool,
+    /// 受信任的构建哈希，非空时只接受列表内的构建
+    pub trusted_source_hashes: Vec<String>,
+    /// 黑名单构建哈希
+    pub blacklisted_source_hashes: Vec<String>,
+    /// 黑名单 git 提交（支持前缀匹配）
+    pub blacklisted_commits: Vec<String>,
+}
+
+impl Default for BuildPolicy {
+    fn default() -> Self {
+        Self {
+            allow_unknown: true,
+            trusted_source_hashes: Vec::new(),
+            blacklisted_source_hashes: Vec::new(),
+            blacklisted_commits: Vec::new(),
+        }
+    }
+}
+
+/// 策略判定结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuildVerdict {
+    Allowed,
+    /// 未收到构建信息
+    Unknown,
+    /// 不在受信任列表中
+    Untrusted,
+    Blacklisted { reason: String },
+}
+
+impl BuildVerdict {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, BuildVerdict::Allowed)
+    }
+}
+
+impl BuildPolicy {
+    pub fn evaluate(&self, build: Option<&BuildInfo>) -> BuildVerdict {
+        let Some(build) = build else {
+            return if self.allow_unknown {
+                BuildVerdict::Allowed
+            } else {
+                BuildVerdict::Unknown
+            };
+        };
+        if self.blacklisted_source_hashes.iter().any(|h| h == &build.source_hash) {
+            return BuildVerdict::Blacklisted {
+                reason: format!("构建哈希 {}", build.source_hash),
+            };
+        }
+        if self
+            .blacklisted_commits
+            .iter()
+            .any(|c| !c.is_empty() && build.git_commit.starts_with(c.as_str()))
+        {
+            return BuildVerdict::Blacklisted {
+                reason: format!("提交 {}", build.git_commit),
+            };
+        }
+        if !self.trusted_source_hashes.is_empty()
+            && !self.trusted_source_hashes.iter().any(|h| h == &build.source_hash)
+        {
+            return BuildVerdict::Untrusted;
+        }
+        BuildVerdict::Allowed
+    }
+}
+
+/// 节点能力记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityRecord {
+    pub node_id: String,
+    pub build: BuildInfo,
+    pub device_type: String,
+    pub cpu_cores: u32,
+    pub max_memory_mb: u64,
+    /// 是否为观察模式节点（不接受任务）
+    #[serde(default)]
+    pub watch_only: bool,
+    /// 生成时间（Unix 秒）
+    pub timestamp: i64,
+}
+
+impl CapabilityRecord {
+    pub fn new(node_id: String, capabilities: &DeviceCapabilities, watch_only: bool) -> Self {
+        Self {
+            node_id,
+            build: BuildInfo::current(),
+            device_type: format!("{:?}", capabilities.device_type),
+            cpu_cores: capabilities.cpu_cores,
+            max_memory_mb: capabilities.max_memory_mb,
+            watch_only,
+            timestamp: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// 由节点身份签名
+    pub fn sign(self, identity: &NodeIdentity) -> anyhow::Result<SignedCapabilityRecord> {
+        let signature = identity.sign_record(&self)?;
+        Ok(SignedCapabilityRecord {
+            record: self,
+            signature,
+        })
+    }
+}
+
+/// 带签名的能力记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedCapabilityRecord {
+    pub record: CapabilityRecord,
+    pub signature: SolSignature,
+}
+
+impl SignedCapabilityRecord {
+    /// 签名者必须是记录中的节点本身
+    pub fn verify(&self) -> bool {
+        if self.signature.pubkey != self.record.node_id {
+            return false;
+        }
+        match serde_json::to_vec(&self.record) {
+            Ok(bytes) => verify_signature(&bytes, &self.signature),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(hash: &str, commit: &str) -> BuildInfo {
+        BuildInfo {
+            source_hash: hash.to_string(),
+            git_commit: commit.to_string(),
+            ..BuildInfo::current()
+        }
+    }
+
+    #[test]
+    fn test_policy_rejects_unknown_and_blacklisted_builds() {
+        let policy = BuildPolicy {
+            allow_unknown: false,
+            trusted_source_hashes: vec!["good".to_string(), "bad".to_string()],
+            blacklisted_source_hashes: vec!["bad".to_string()],
+            blacklisted_commits: vec!["deadbeef".to_string()],
+        };
+        assert_eq!(policy.evaluate(None), BuildVerdict::Unknown);
+        assert!(policy.evaluate(Some(&build("good", "0123abcd"))).is_allowed());
+        assert_eq!(policy.evaluate(Some(&build("other", "0123abcd"))), BuildVerdict::Untrusted);
+        assert!(matches!(
+            policy.evaluate(Some(&build("bad", "0123abcd"))),
+            BuildVerdict::Blacklisted { .. }
+        ));
+        assert!(matches!(
+            policy.evaluate(Some(&build("good", "deadbeef00"))),
+            BuildVerdict::Blacklisted { .. }
+        ));
+        assert!(BuildPolicy::default().evaluate(None).is_allowed());
+    }
+}
@@ -0,0 +1,221 @@
+//! 故障注入（`chaos` 特性）
+//!
+//! 按脚本在指定 tick 触发故障，用于可复现地测试故障切换、重新复制和重试：
+//! - `kill_transport`：若干 tick 内不收发任何网络消息
+//! - `corrupt_shard`：按种子翻转分片文件中的若干字节
+//! - `delay_rpc`：若干 tick 内每次 Solana RPC 调用前额外等待
+//! - `oom`：训练 tick 以错误退出，模拟进程因内存耗尽被杀死
+//!
+//! 脚本示例：
+//!
+//! ```json
+//! { "seed": 7, "faults": [
+//!     { "at_tick": 50, "fault": "kill_transport", "ticks": 20 },
+//!     { "at_tick": 80, "fault": "corrupt_shard", "path": "shards/layer_0.bin" },
+//!     { "at_tick": 120, "fault": "delay_rpc", "delay_ms": 5000, "ticks": 30 },
+//!     { "at_tick": 200, "fault": "oom" } ] }
+//! ```
+
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn default_corrupt_bytes() -> usize {
+    16
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "fault", rename_all = "snake_case")]
+pub enum Fault {
+    KillTransport {
+        ticks: u64,
+    },
+    CorruptShard {
+        path: PathBuf,
+        /// 翻转的字节数
+        #[serde(default = "default_corrupt_bytes")]
+        bytes: usize,
+    },
+    DelayRpc {
+        delay_ms: u64,
+        ticks: u64,
+    },
+    Oom,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledFault {
+    pub at_tick: u64,
+    #[serde(flatten)]
+    pub fault: Fault,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultScript {
+    /// 损坏分片时选择字节位置的随机种子
+    #[serde(default)]
+    pub seed: u64,
+    pub faults: Vec<ScheduledFault>,
+}
+
+impl FaultScript {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+#[derive(Default)]
+struct InjectorState {
+    tick: u64,
+    /// 下一个待触发故障的下标
+    next: usize,
+    transport_down_until: u64,
+    rpc_delay: Option<(Duration, u64)>,
+    oom_pending: bool,
+    fired: Vec<ScheduledFault>,
+}
+
+/// 故障注入器，由节点每个 tick 推进
+pub struct FaultInjector {
+    seed: u64,
+    faults: Vec<ScheduledFault>,
+    state: Mutex<InjectorState>,
+}
+
+impl FaultInjector {
+    pub fn new(script: FaultScript) -> Self {
+        let mut faults = script.faults;
+        faults.sort_by_key(|fault| fault.at_tick);
+        Self {
+            seed: script.seed,
+            faults,
+            state: Mutex::new(InjectorState::default()),
+        }
+    }
+
+    /// 推进到指定 tick，触发所有到期的故障并返回
+    pub fn on_tick(&self, tick: u64) -> Vec<Fault> {
+        let mut state = self.state.lock();
+        state.tick = tick;
+        let mut triggered = Vec::new();
+        while let Some(scheduled) = self.faults.get(state.next).filter(|fault| fault.at_tick <= tick) {
+            state.next += 1;
+            println!("[故障注入] tick {}: {:?}", tick, scheduled.fault);
+            match &scheduled.fault {
+                Fault::KillTransport { ticks } => state.transport_down_until = tick + ticks,
+                Fault::CorruptShard { path, bytes } => {
+                    if let Err(e) = corrupt_file(path, *bytes, self.seed ^ scheduled.at_tick) {
+                        println!("[故障注入] 损坏分片 {} 失败: {}", path.display(), e);
+                    }
+                }
+                Fault::DelayRpc { delay_ms, ticks } => {
+                    state.rpc_delay = Some((Duration::from_millis(*delay_ms), tick + ticks));
+                }
+                Fault::Oom => state.oom_pending = true,
+            }
+            state.fired.push(scheduled.clone());
+            triggered.push(scheduled.fault.clone());
+        }
+        triggered
+    }
+
+    /// 传输当前是否被关闭
+    pub fn transport_down(&self) -> bool {
+        let state = self.state.lock();
+        state.tick < state.transport_down_until
+    }
+
+    /// 当前 RPC 调用的额外延迟
+    pub fn rpc_delay(&self) -> Option<Duration> {
+        let state = self.state.lock();
+        state.rpc_delay.filter(|(_, until)| state.tick < *until).map(|(delay, _)| delay)
+    }
+
+    /// 在 RPC 调用前调用，按当前注入的延迟等待
+    pub async fn delay_rpc(&self) {
+        if let Some(delay) = self.rpc_delay() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// 在训练步骤前调用；注入了内存耗尽时返回错误（只触发一次）
+    pub fn check_training(&self) -> Result<()> {
+        if std::mem::take(&mut self.state.lock().oom_pending) {
+            bail!("[故障注入] 训练循环内存耗尽");
+        }
+        Ok(())
+    }
+
+    /// 已触发的故障
+    pub fn fired(&self) -> Vec<ScheduledFault> {
+        self.state.lock().fired.clone()
+    }
+}
+
+/// 按种子翻转文件中 `bytes` 个位置的字节，返回实际翻转的字节数
+pub fn corrupt_file(path: &Path, bytes: usize, seed: u64) -> Result<usize> {
+    let mut data = std::fs::read(path)?;
+    if data.is_empty() {
+        return Ok(0);
+    }
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let count = bytes.min(data.len());
+    for _ in 0..count {
+        let index = rng.random_range(0..data.len());
+        data[index] ^= rng.random_range(1..=u8::MAX);
+    }
+    std::fs::write(path, &data)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faults_fire_on_schedule() {
+        let script: FaultScript = serde_json::from_str(
+            r#"{ "faults": [
+                { "at_tick": 5, "fault": "delay_rpc", "delay_ms": 250, "ticks": 3 },
+                { "at_tick": 2, "fault": "kill_transport", "ticks": 2 },
+                { "at_tick": 6, "fault": "oom" } ] }"#,
+        )
+        .unwrap();
+        let injector = FaultInjector::new(script);
+
+        assert!(injector.on_tick(1).is_empty());
+        assert_eq!(injector.on_tick(2), vec![Fault::KillTransport { ticks: 2 }]);
+        assert!(injector.transport_down());
+        injector.on_tick(4);
+        assert!(!injector.transport_down());
+
+        injector.on_tick(5);
+        assert_eq!(injector.rpc_delay(), Some(Duration::from_millis(250)));
+        injector.on_tick(6);
+        assert!(injector.check_training().is_err());
+        assert!(injector.check_training().is_ok());
+        injector.on_tick(8);
+        assert_eq!(injector.rpc_delay(), None);
+        assert_eq!(injector.fired().len(), 3);
+    }
+
+    #[test]
+    fn test_corrupt_file_is_reproducible() {
+        let dir = std::env::temp_dir().join(format!("williw-chaos-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let original: Vec<u8> = (0..=255).collect();
+        let (a, b) = (dir.join("a.bin"), dir.join("b.bin"));
+        std::fs::write(&a, &original).unwrap();
+        std::fs::write(&b, &original).unwrap();
+
+        assert_eq!(corrupt_file(&a, 8, 42).unwrap(), 8);
+        corrupt_file(&b, 8, 42).unwrap();
+        let corrupted = std::fs::read(&a).unwrap();
+        assert_ne!(corrupted, original);
+        assert_eq!(corrupted, std::fs::read(&b).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,702 @@
+//! 命令行子命令实现
+//!
+//! 各子命令直接复用库中的模块（节点、模型下载/切分、身份、统计数据库、配置检查），
+//! 便于在没有桌面端的服务器上运维节点。
+
+use crate::args::{
+    BenchmarkArgs, CacheCommand, CacheLocation, Command, ConfigCommand, DatasetCommand, DownloadModelArgs, KeysCommand,
+    RunArgs, SimulateArgs, SplitModelArgs, StatsCommand,
+};
+#[cfg(feature = "admin-api")]
+use crate::admin::{AdminAuth, AdminConfig, AdminContext, LogLevelControl, TransferSessions};
+use crate::admin::NodeStatusBoard;
+use crate::config::AppConfig;
+use crate::config_watch::{ConfigWatcher, RuntimeSettings};
+use crate::crypto::NodeIdentity;
+use crate::dataset_manifest::{DatasetConfig, DatasetManifest};
+use crate::device::{BenchmarkConfig, DeviceBenchmark, DeviceManager, SignedDeviceScore};
+use crate::fleet::{FleetAgent, FleetHandles};
+use crate::marketplace::MarketplaceAgent;
+use crate::model_cache::{ModelCache, ModelCacheConfig};
+use crate::storage::{path_size, DownloadPreflight, StorageConfig, StorageGovernor};
+use crate::node::Node;
+use crate::stats::{is_ndjson_path, EnergyModel, PowerProfile, StatsStore};
+use crate::work_schedule::{WorkKind, WorkScheduler};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use williw::network::transport::simulated::{run_gossip, GossipPlan, LinkProfile, SimulatedNetwork, SimulationConfig};
+
+pub async fn execute(command: Command) -> Result<()> {
+    match command {
+        Command::Run(args) => run(args).await,
+        Command::DownloadModel(args) => download_model(args).await,
+        Command::SplitModel(args) => split_model(args).await,
+        Command::Benchmark(args) => benchmark(args),
+        Command::Keys(KeysCommand::Rotate { identity }) => rotate_keys(identity.as_deref()),
+        Command::Stats(StatsCommand::Export {
+            db,
+            output,
+            from,
+            to,
+            bucket_secs,
+        }) => export_stats(&db, &output, from, to, bucket_secs),
+        Command::Config(ConfigCommand::Validate { path, runtime }) => validate_config(&path, runtime),
+        Command::Cache(command) => cache(command),
+        Command::Dataset(command) => dataset(command),
+        Command::Simulate(args) => simulate(args).await,
+        #[cfg(feature = "tui")]
+        Command::Top(args) => crate::tui::run(args),
+        #[cfg(feature = "plugins")]
+        Command::Plugins(command) => plugins(command),
+    }
+}
+
+/// 启动训练节点
+async fn run(args: RunArgs) -> Result<()> {
+    let config = args.build_config();
+
+    // 启动前检查配置，避免在子系统初始化深处才失败
+    let report = config.check_runtime();
+    if !report.is_clean() {
+        println!("[配置验证] 发现配置问题：");
+        print!("{}", report);
+    }
+    report.into_result()?;
+
+    let settings = RuntimeSettings::from_config(&config);
+    let startup_config = config.clone();
+    #[cfg(not(feature = "admin-api"))]
+    if config.admin.enabled {
+        eprintln!("[管理接口] 当前构建未启用 admin-api 特性，忽略 admin 配置");
+    }
+    let mut node = Node::new(config).await?;
+
+    if let Some(path) = &args.device_score {
+        let signed = SignedDeviceScore::load(path)?;
+        if !signed.verify() {
+            return Err(anyhow!("算力分签名无效或测试套件版本已过期: {}", path.display()));
+        }
+        if signed.score.node_id != node.identity.node_id() {
+            return Err(anyhow!("算力分不属于本节点: {}", path.display()));
+        }
+        println!("[设备] 使用校准算力分 {:.3}", signed.score.score);
+        node.device_manager.set_device_score(signed.score);
+    }
+
+    if let Some(db) = &args.stats_db {
+        node = node.with_stats_store(Arc::new(StatsStore::open(db)?));
+    }
+
+    #[cfg(feature = "chaos")]
+    if let Some(path) = &args.chaos_script {
+        let script = crate::chaos::FaultScript::load(path)?;
+        println!("[故障注入] 已加载 {} 个故障: {}", script.faults.len(), path.display());
+        node = node.with_fault_injector(Arc::new(crate::chaos::FaultInjector::new(script)));
+    }
+
+    // 指定了配置文件时监视其变化，可热更新的配置项在运行时生效
+    let mut watcher = None;
+    if let Some(config_path) = &args.config {
+        match ConfigWatcher::new(config_path, settings) {
+            Ok(file_watcher) => {
+                let file_watcher = Arc::new(file_watcher);
+                file_watcher.spawn();
+                watcher = Some(file_watcher);
+            }
+            Err(e) => eprintln!("无法监视配置文件 {}: {:?}", config_path.display(), e),
+        }
+    }
+
+    // 管理接口、集群控制器下发的配置修改与配置文件变化走同一条通道；没有配置文件时使用不关联文件的配置中心
+    let admin_enabled = cfg!(feature = "admin-api") && startup_config.admin.enabled;
+    if admin_enabled || startup_config.fleet.enabled {
+        let watcher = watcher
+            .get_or_insert_with(|| Arc::new(ConfigWatcher::detached(&startup_config)))
+            .clone();
+        let board = Arc::new(NodeStatusBoard::default());
+        node = node.with_status_board(board.clone());
+
+        #[cfg(feature = "admin-api")]
+        if admin_enabled {
+            start_admin_api(&startup_config.admin, watcher.clone(), board.clone(), node.stats_store.clone())?;
+        }
+        if startup_config.fleet.enabled {
+            let handles = FleetHandles {
+                identity: Arc::clone(&node.identity),
+                config: watcher,
+                power: Arc::clone(&node.power_policy),
+                status: board,
+                stats: Arc::clone(&node.stats),
+                devices: node.device_manager.clone(),
+            };
+            FleetAgent::new(startup_config.fleet.clone(), handles)?.spawn();
+            println!("[集群] 已启用集群托管，控制器 {}", startup_config.fleet.controller_url);
+        }
+    }
+
+    if startup_config.marketplace.enabled {
+        start_marketplace(&startup_config, &node)?;
+    }
+
+    if let Some(watcher) = &watcher {
+        node = node.with_config_updates(watcher.subscribe());
+    }
+
+    // 如果指定了统计输出文件，设置定期导出
+    if let Some(stats_path) = args.stats_output {
+        let stats_manager: Arc<std::sync::Mutex<crate::stats::TrainingStatsManager>> = Arc::clone(&node.stats);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(30));
+            loop {
+                interval.tick().await;
+                let manager = stats_manager.lock().unwrap();
+                let result = if is_ndjson_path(&stats_path) {
+                    manager.append_ndjson_to_file(&stats_path)
+                } else {
+                    manager.export_json_to_file(&stats_path)
+                };
+                if let Err(e) = result {
+                    eprintln!("导出统计数据失败: {:?}", e);
+                }
+            }
+        });
+    }
+
+    node.run().await
+}
+
+/// 用插件目录中的插件作为执行后端，启动任务市场代理
+#[cfg(feature = "plugins")]
+fn start_marketplace(config: &AppConfig, node: &Node) -> Result<()> {
+    let market = &config.marketplace;
+    let mut registry = crate::plugins::PluginRegistry::new();
+    for (path, e) in registry.load_dir(&market.plugin_dir)? {
+        eprintln!("[市场] 插件 {} 加载失败: {:#}", path.display(), e);
+    }
+    if registry.is_empty() {
+        return Err(anyhow!("插件目录 {} 中没有可用插件，无法执行市场任务", market.plugin_dir.display()));
+    }
+    let task_types: Vec<_> = registry.plugins().map(|p| p.manifest.task_type.clone()).collect();
+    let profile = config
+        .energy
+        .profile
+        .unwrap_or_else(|| PowerProfile::for_device(&node.device_manager.get()));
+    MarketplaceAgent::new(
+        market.clone(),
+        Arc::clone(&node.identity),
+        node.device_manager.clone(),
+        EnergyModel::new(profile),
+        Arc::new(registry),
+    )?
+    .spawn();
+    println!("[市场] 已启用任务市场，协调者 {}，可执行任务类型: {}", market.coordinator_url, task_types.join(", "));
+    Ok(())
+}
+
+#[cfg(not(feature = "plugins"))]
+fn start_marketplace(_config: &AppConfig, _node: &Node) -> Result<()> {
+    eprintln!("[市场] 当前构建未启用 plugins 特性，没有执行市场任务的后端，忽略 marketplace 配置");
+    Ok(())
+}
+
+/// 初始化可在运行时调整级别的日志（`log` 宏经 tracing 转发）
+#[cfg(feature = "admin-api")]
+fn init_reloadable_logging() -> Result<LogLevelControl> {
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::{fmt, reload, EnvFilter};
+
+    let initial = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let (filter, handle) = reload::Layer::new(EnvFilter::try_new(&initial)?);
+    tracing_subscriber::registry().with(filter).with(fmt::layer()).try_init()?;
+    // 由过滤器决定输出哪些日志，`log` 自身不再截断
+    log::set_max_level(log::LevelFilter::Trace);
+    Ok(LogLevelControl::new(initial, move |level| {
+        handle.reload(EnvFilter::try_new(level)?)?;
+        Ok(())
+    }))
+}
+
+#[cfg(feature = "admin-api")]
+fn start_admin_api(
+    config: &AdminConfig,
+    watcher: Arc<ConfigWatcher>,
+    board: Arc<NodeStatusBoard>,
+    stats: Option<Arc<StatsStore>>,
+) -> Result<()> {
+    let transfers = Arc::new(TransferSessions::default());
+    transfers.spawn();
+    let ctx = Arc::new(AdminContext {
+        auth: AdminAuth::from_config(config),
+        config: watcher,
+        status: board,
+        transfers,
+        log_level: Arc::new(init_reloadable_logging()?),
+        stats,
+    });
+    let bind = config.bind.clone();
+    tokio::spawn(async move {
+        if let Err(e) = crate::admin::serve(ctx, &bind).await {
+            eprintln!("[管理接口] 启动失败: {}", e);
+        }
+    });
+    Ok(())
+}
+
+async fn download_model(args: DownloadModelArgs) -> Result<()> {
+    let config: Option<AppConfig> = match &args.config {
+        Some(path) => Some(toml::from_str(&std::fs::read_to_string(path)?)?),
+        None => None,
+    };
+    if let Some(config) = &config {
+        let devices = DeviceManager::new();
+        // 没有运行节点时自行刷新网络类型，以便切到 Wi-Fi 后继续
+        let refresh_interval = Duration::from_secs(config.work_schedule.check_interval_secs.max(1));
+        let refresher = {
+            let devices = devices.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(refresh_interval).await;
+                    devices.refresh();
+                }
+            })
+        };
+        let scheduler = WorkScheduler::new(config.work_schedule.clone(), devices);
+        scheduler.wait_for_window(WorkKind::ModelDownload, &args.model).await;
+        refresher.abort();
+    }
+    let (cache_config, storage_config) = config.map(|config| (config.model_cache, config.storage)).unwrap_or_default();
+    let cache = cache_config.enabled.then(|| ModelCache::open(cache_config)).transpose()?.map(Arc::new);
+    let storage = StorageGovernor::new(storage_config, cache.clone());
+
+    // 先确认保留空间，得知模型总大小后再按剩余字节预检
+    let download_dir = args.cache_dir.clone().unwrap_or_else(|| {
+        PathBuf::from(model_downloader::DEFAULT_CACHE_ROOT).join(args.model.replace('/', "_"))
+    });
+    storage.preflight(&download_dir, 0)?;
+    let cancel = AtomicBool::new(false);
+    let mut preflight = DownloadPreflight::new(&storage, &download_dir, &cancel);
+
+    let hf_token = args.hf_token.or_else(|| std::env::var("HF_TOKEN").ok());
+    let downloader = model_downloader::ModelDownloader::new(hf_token.clone());
+    let result = downloader
+        .download_model_with_progress(
+            model_downloader::DownloadConfig {
+                model_name: args.model.clone(),
+                cache_dir: Some(download_dir.to_string_lossy().to_string()),
+                hf_token,
+            },
+            &cancel,
+            |progress| preflight.observe(progress.downloaded_bytes, progress.total_bytes),
+        )
+        .await;
+    let result = match (result, preflight.into_error()) {
+        (Err(_), Some(e)) => return Err(e),
+        (result, _) => result?,
+    };
+    println!(
+        "模型已下载到 {}（{} 个文件，{:.1} MB）",
+        result.model_path,
+        result.files_downloaded.len(),
+        result.total_size_mb
+    );
+
+    if let Some(cache) = cache.filter(|_| !args.no_cache) {
+        let root = Path::new(&result.model_path);
+        let files: Vec<(String, PathBuf)> = result
+            .files_downloaded
+            .iter()
+            .map(|file| (format!("{}/{}", args.model, file), root.join(file)))
+            .collect();
+        add_to_model_cache(&cache, &files)?;
+    }
+    Ok(())
+}
+
+/// 把文件放入模型缓存，原位置改为指向缓存对象的硬链接
+fn add_to_model_cache(cache: &ModelCache, files: &[(String, PathBuf)]) -> Result<()> {
+    for (name, path) in files.iter().filter(|(_, path)| path.is_file()) {
+        cache.insert_file(name, path, true)?;
+    }
+    let usage = cache.usage();
+    println!(
+        "已加入模型缓存 {}（共 {} 个对象，{:.1} MB，去重节省 {:.1} MB）",
+        cache.config().dir.display(),
+        usage.objects,
+        usage.total_bytes as f64 / 1024.0 / 1024.0,
+        usage.deduplicated_bytes as f64 / 1024.0 / 1024.0
+    );
+    Ok(())
+}
+
+async fn split_model(args: SplitModelArgs) -> Result<()> {
+    let plan: HashMap<String, model_splitter::SplitPlan> =
+        serde_json::from_str(&std::fs::read_to_string(&args.plan)?)?;
+
+    let identity = if args.sign || args.node_id.is_none() {
+        Some(NodeIdentity::load_or_create(args.identity.as_deref())?)
+    } else {
+        None
+    };
+    let node_id = match (&args.node_id, &identity) {
+        (Some(node_id), _) => node_id.clone(),
+        (None, Some(identity)) => identity.node_id(),
+        (None, None) => unreachable!("未指定节点 ID 时总会加载身份"),
+    };
+
+    // 分片总大小不超过模型本身，按模型大小预检输出目录
+    let cache = Arc::new(ModelCache::open(ModelCacheConfig::default())?);
+    let storage = StorageGovernor::new(StorageConfig::default(), Some(Arc::clone(&cache)));
+    let output_dir = args.output_dir.clone().unwrap_or_else(|| PathBuf::from("./model_shards").join(&node_id));
+    storage.preflight(&output_dir, path_size(&args.model_path))?;
+
+    let config = model_splitter::SplitConfig {
+        model_name: args.model_name.clone(),
+        model_path: args.model_path.to_string_lossy().to_string(),
+        split_plan: plan,
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+    };
+    let splitter = model_splitter::ModelSplitter::new();
+
+    let result = match identity.filter(|_| args.sign) {
+        Some(identity) => {
+            let signing_key = ed25519_dalek::SigningKey::from_bytes(&identity.secret_bytes());
+            let (result, manifest) = splitter.split_model_signed(config, &node_id, &signing_key).await?;
+            println!("分片清单已签名: {} 个张量", manifest.manifest.tensors.len());
+            result
+        }
+        None => splitter.split_model(config, &node_id).await?,
+    };
+    println!(
+        "节点 {} 的分片: {}（{} 层，{} 个参数，{:.1} MB）",
+        result.node_id,
+        result.shard_path,
+        result.layer_names.len(),
+        result.total_params,
+        result.shard_size_mb
+    );
+
+    if !args.no_cache {
+        let shard_path = PathBuf::from(&result.shard_path);
+        let file_name = shard_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let name = format!("{}/{}/{}", args.model_name, result.node_id, file_name);
+        add_to_model_cache(&cache, &[(name, shard_path)])?;
+    }
+    Ok(())
+}
+
+fn benchmark(args: BenchmarkArgs) -> Result<()> {
+    let capabilities = DeviceManager::new().get();
+    let identity = NodeIdentity::load_or_create(args.identity.as_deref())?;
+    let score = DeviceBenchmark::new(BenchmarkConfig {
+        duration: Duration::from_secs(args.seconds.max(1)),
+        matrix_size: args.matrix_size,
+        memory_buffer_mb: args.memory_mb,
+    })
+    .score(&identity.node_id(), &capabilities)?;
+    let recommended_model_dim = score.recommended_model_dim(&capabilities);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&score)?);
+    } else {
+        let result = &score.result;
+        println!("设备: {} 核心, {}MB 内存, GPU: {}", capabilities.cpu_cores, capabilities.max_memory_mb, capabilities.has_gpu);
+        println!("矩阵乘法 ({0}x{0}): {1:.2} GFLOPS", args.matrix_size, result.matmul_gflops);
+        if let (Some(gflops), Some(name)) = (result.gpu_gflops, &result.gpu_name) {
+            println!("GPU 矩阵乘法 ({}): {:.2} GFLOPS", name, gflops);
+        }
+        println!("内存带宽: {:.2} GB/s", result.memory_bandwidth_gbps);
+        println!("Blake3 哈希: {:.1} MB/s", result.blake3_mb_per_sec);
+        println!("Ed25519 签名: {:.0} 次/秒", result.ed25519_signs_per_sec);
+        println!("校准算力分: {:.3}（参考设备为 1.0）", score.score);
+        println!(
+            "推荐: tick {}ms, 模型维度 {}",
+            capabilities.recommended_tick_interval().as_millis(),
+            recommended_model_dim
+        );
+    }
+
+    if let Some(output) = &args.output {
+        score.sign(&identity)?.save(output)?;
+        println!("已签名的算力分已写入 {}", output.display());
+    }
+    Ok(())
+}
+
+/// 在模拟网络上跑 gossip 流量并输出投递统计
+async fn simulate(args: SimulateArgs) -> Result<()> {
+    let config = match &args.config {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => SimulationConfig {
+            seed: args.seed,
+            default_link: LinkProfile {
+                latency: args.latency.parse()?,
+                jitter_ms: args.jitter_ms,
+                loss_rate: args.loss.clamp(0.0, 1.0),
+            },
+            links: Vec::new(),
+        },
+    };
+    let network = SimulatedNetwork::new(config);
+    for spec in &args.partition {
+        let (a, b) = spec
+            .split_once('/')
+            .ok_or_else(|| anyhow!("分区格式应为 node-0,node-1/node-2,node-3: {}", spec))?;
+        let side = |s: &str| s.split(',').map(|peer| peer.trim().to_string()).collect::<Vec<_>>();
+        network.partition(&side(a), &side(b));
+    }
+
+    let plan = GossipPlan {
+        nodes: args.nodes,
+        rounds: args.rounds,
+        fanout: args.fanout,
+        round_interval_ms: args.interval_ms,
+        message_bytes: args.message_bytes,
+    };
+    let stats = run_gossip(&network, &plan).await;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        println!("节点 {}，{} 轮，每轮扇出 {}，虚拟时长 {:.1}ms", plan.nodes, plan.rounds, plan.fanout, stats.now_ms);
+        println!("发送 {}，送达 {}，丢包 {}，分区阻断 {}", stats.sent, stats.delivered, stats.lost, stats.blocked);
+        println!(
+            "延迟: 平均 {:.1}ms, p50 {:.1}ms, p99 {:.1}ms",
+            stats.mean_latency_ms, stats.p50_latency_ms, stats.p99_latency_ms
+        );
+    }
+    Ok(())
+}
+
+fn rotate_keys(identity_path: Option<&Path>) -> Result<()> {
+    let identity = NodeIdentity::load_or_create(identity_path)?;
+    let rotation = identity.rotate()?;
+    println!("{}", serde_json::to_string_pretty(&rotation)?);
+    println!("新身份在节点重启后生效；对端需收到上面的轮换公告才能把旧身份的记录迁移到新身份");
+    Ok(())
+}
+
+fn dataset(command: DatasetCommand) -> Result<()> {
+    match command {
+        DatasetCommand::Sign {
+            dir,
+            name,
+            version,
+            license,
+            source_url,
+            identity,
+            output,
+        } => {
+            let identity = NodeIdentity::load_or_create(identity.as_deref())?;
+            let output = output.unwrap_or_else(|| dir.join("dataset-manifest.json"));
+            let manifest =
+                DatasetManifest::from_dir(&dir, &name, &version, &license, source_url.as_deref(), &[output.clone()])?;
+            let signed = manifest.sign(&identity);
+            signed.save(&output)?;
+            println!(
+                "已生成 {} 个分块的清单: {}\n发布者: {}\n清单哈希: {}",
+                signed.manifest.chunks.len(),
+                output.display(),
+                signed.manifest.publisher_id,
+                signed.manifest.hash()
+            );
+        }
+        DatasetCommand::Verify {
+            manifest,
+            data_dir,
+            publisher,
+        } => {
+            let config = DatasetConfig {
+                manifest_path: Some(manifest),
+                data_dir,
+                trusted_publishers: publisher,
+                allowed_licenses: Vec::new(),
+            };
+            let verified = config.load_verified()?.expect("已设置清单路径");
+            println!(
+                "✓ {} v{}: {} 个分块, 许可证 {}\n清单哈希: {}",
+                verified.manifest.name,
+                verified.manifest.version,
+                verified.manifest.chunks.len(),
+                verified.manifest.licenses().join(", "),
+                verified.manifest_hash
+            );
+        }
+    }
+    Ok(())
+}
+
+fn export_stats(db: &Path, output: &Path, from: Option<i64>, to: Option<i64>, bucket_secs: Option<i64>) -> Result<()> {
+    if !db.exists() {
+        return Err(anyhow!("统计数据库不存在: {}", db.display()));
+    }
+    let store = StatsStore::open(db)?;
+    let from = from.unwrap_or(0);
+    let to = to.unwrap_or_else(|| chrono::Utc::now().timestamp() + 1);
+
+    let rows: Vec<serde_json::Value> = match bucket_secs {
+        Some(bucket_secs) => store
+            .aggregate_ticks(from, to, bucket_secs)?
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<serde_json::Result<_>>()?,
+        None => store
+            .ticks_between(from, to)?
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<serde_json::Result<_>>()?,
+    };
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(output)?);
+    if is_ndjson_path(output) {
+        for row in &rows {
+            serde_json::to_writer(&mut file, row)?;
+            file.write_all(b"\n")?;
+        }
+    } else {
+        serde_json::to_writer_pretty(&mut file, &rows)?;
+    }
+    file.flush()?;
+    println!("已导出 {} 条记录到 {}", rows.len(), output.display());
+    Ok(())
+}
+
+fn validate_config(path: &Path, runtime: bool) -> Result<()> {
+    let config: AppConfig = toml::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| anyhow!("无法解析 {}: {}", path.display(), e))?;
+    let report = if runtime { config.check_runtime() } else { config.check() };
+    if report.is_clean() {
+        println!("{}: 配置检查通过", path.display());
+        return Ok(());
+    }
+    println!(
+        "{}: {} 个错误，{} 个警告",
+        path.display(),
+        report.errors().count(),
+        report.warnings().count()
+    );
+    print!("{}", report);
+    report.into_result()
+}
+
+fn open_model_cache(location: &CacheLocation) -> Result<ModelCache> {
+    let mut config = match &location.config {
+        Some(path) => {
+            let config: AppConfig = toml::from_str(&std::fs::read_to_string(path)?)
+                .map_err(|e| anyhow!("无法解析 {}: {}", path.display(), e))?;
+            config.model_cache
+        }
+        None => ModelCacheConfig::default(),
+    };
+    if let Some(dir) = &location.dir {
+        config.dir = dir.clone();
+    }
+    ModelCache::open(config)
+}
+
+#[cfg(feature = "plugins")]
+fn plugins(command: crate::args::PluginsCommand) -> Result<()> {
+    use crate::args::PluginsCommand;
+    use crate::plugins::{Plugin, PluginRegistry};
+
+    match command {
+        PluginsCommand::Check { dir } => {
+            let mut registry = PluginRegistry::new();
+            let failures = registry.load_dir(&dir)?;
+            for plugin in registry.plugins() {
+                let manifest = &plugin.manifest;
+                println!(
+                    "✓ {} {}  任务类型: {}  权限: {:?}",
+                    manifest.name, manifest.version, manifest.task_type, manifest.permissions
+                );
+            }
+            for (path, e) in &failures {
+                println!("✗ {}: {:#}", path.display(), e);
+            }
+            if !failures.is_empty() {
+                return Err(anyhow!("{} 个插件校验失败", failures.len()));
+            }
+        }
+        PluginsCommand::Run { plugin, input, output } => {
+            let plugin = Plugin::load(&plugin)?;
+            let run = plugin.run(std::fs::read(&input)?, |progress| {
+                eprintln!("[插件] 进度 {:.0}%", progress * 100.0)
+            })?;
+            match output {
+                Some(path) => std::fs::write(&path, &run.output)?,
+                None => std::io::stdout().write_all(&run.output)?,
+            }
+            eprintln!("[插件] 完成，输出 {} 字节，消耗燃料 {}", run.output.len(), run.fuel_consumed);
+        }
+    }
+    Ok(())
+}
+
+fn cache(command: CacheCommand) -> Result<()> {
+    match command {
+        CacheCommand::List { location, json } => {
+            let cache = open_model_cache(&location)?;
+            let entries = cache.entries();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+                return Ok(());
+            }
+            for entry in &entries {
+                let last_access = chrono::DateTime::from_timestamp(entry.last_access, 0)
+                    .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{}  {:>10.1} MB  {}  {}{}",
+                    &entry.digest[..12],
+                    entry.size as f64 / 1024.0 / 1024.0,
+                    last_access,
+                    if entry.pinned { "[固定] " } else { "" },
+                    entry.names.keys().cloned().collect::<Vec<_>>().join(", ")
+                );
+            }
+            let usage = cache.usage();
+            println!(
+                "共 {} 个对象（{} 个名字），{:.1} MB，去重节省 {:.1} MB，固定 {:.1} MB",
+                usage.objects,
+                usage.names,
+                usage.total_bytes as f64 / 1024.0 / 1024.0,
+                usage.deduplicated_bytes as f64 / 1024.0 / 1024.0,
+                usage.pinned_bytes as f64 / 1024.0 / 1024.0
+            );
+        }
+        CacheCommand::Evict {
+            key,
+            max_bytes,
+            location,
+        } => {
+            let cache = open_model_cache(&location)?;
+            let evicted = match (key, max_bytes) {
+                (Some(key), _) => vec![cache.evict(&key)?],
+                (None, Some(max_bytes)) => cache.evict_to(max_bytes)?,
+                (None, None) => return Err(anyhow!("请指定要淘汰的缓存项或 --max-bytes")),
+            };
+            for entry in &evicted {
+                println!(
+                    "已淘汰 {}（{} 字节）: {}",
+                    &entry.digest[..12],
+                    entry.size,
+                    entry.names.keys().cloned().collect::<Vec<_>>().join(", ")
+                );
+            }
+            println!("共淘汰 {} 个对象", evicted.len());
+        }
+        CacheCommand::Pin { key, location } => {
+            let entry = open_model_cache(&location)?.set_pinned(&key, true)?;
+            println!("已固定 {}", &entry.digest[..12]);
+        }
+        CacheCommand::Unpin { key, location } => {
+            let entry = open_model_cache(&location)?.set_pinned(&key, false)?;
+            println!("已取消固定 {}", &entry.digest[..12]);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+//! 集群视图
+//!
+//! 汇总已知节点的能力记录和构建信息，供状态接口展示，
+//! 并按构建准入策略筛选可分配任务的节点。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::build_info::{BuildInfo, BuildPolicy, BuildVerdict, CapabilityRecord, SignedCapabilityRecord};
+
+/// 单个节点在集群视图中的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerView {
+    pub node_id: String,
+    pub build: Option<BuildInfo>,
+    pub verdict: BuildVerdict,
+    pub device_type: Option<String>,
+    pub watch_only: bool,
+    /// 最近一次收到能力记录的时间（Unix 秒）
+    pub last_seen: i64,
+}
+
+/// 集群视图
+pub struct ClusterView {
+    policy: BuildPolicy,
+    records: RwLock<HashMap<String, CapabilityRecord>>,
+}
+
+impl ClusterView {
+    pub fn new(policy: BuildPolicy) -> Self {
+        Self {
+            policy,
+            records: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &BuildPolicy {
+        &self.policy
+    }
+
+    /// 记录对端的能力记录，签名无效或比已有记录旧时忽略
+    pub fn record_capability(&self, signed: &SignedCapabilityRecord) -> Option<BuildVerdict> {
+        if !signed.verify() {
+            return None;
+        }
+        let mut records = self.records.write();
+        if let Some(existing) = records.get(&signed.record.node_id) {
+            if existing.timestamp > signed.record.timestamp {
+                return None;
+            }
+        }
+        records.insert(signed.record.node_id.clone(), signed.record.clone());
+        Some(self.policy.evaluate(Some(&signed.record.build)))
+    }
+
+    pub fn remove(&self, node_id: &str) {
+        self.records.write().remove(node_id);
+    }
+
+    /// 按策略判定节点的构建
+    pub fn verdict(&self, node_id: &str) -> BuildVerdict {
+        let records = self.records.read();
+        self.policy.evaluate(records.get(node_id).map(|r| &r.build))
+    }
+
+    /// 可以分配任务的节点：构建满足策略且不是观察模式
+    pub fn eligible_assignees(&self, candidates: &[String]) -> Vec<String> {
+        let records = self.records.read();
+        candidates
+            .iter()
+            .filter(|id| {
+                let record = records.get(id.as_str());
+                !record.is_some_and(|r| r.watch_only)
+                    && self.policy.evaluate(record.map(|r| &r.build)).is_allowed()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 状态接口使用的快照
+    pub fn snapshot(&self) -> Vec<PeerView> {
+        let records = self.records.read();
+        let mut peers: Vec<PeerView> = records
+            .values()
+            .map(|r| PeerView {
+                node_id: r.node_id.clone(),
+                build: Some(r.build.clone()),
+                verdict: self.policy.evaluate(Some(&r.build)),
+                device_type: Some(r.device_type.clone()),
+                watch_only: r.watch_only,
+                last_seen: r.timestamp,
+            })
+            .collect();
+        peers.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        peers
+    }
+
+    /// 文本形式的集群视图
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for peer in self.snapshot() {
+            let build = peer.build.as_ref().map(BuildInfo::short).unwrap_or_else(|| "未知构建".to_string());
+            let mode = if peer.watch_only { " (观察)" } else { "" };
+            out.push_str(&format!("{}{} {} {:?}\n", peer.node_id, mode, build, peer.verdict));
+        }
+        out
+    }
+}
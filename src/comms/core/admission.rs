@@ -0,0 +1,367 @@
+//! 基于质押的节点准入控制（抗女巫攻击）
+//!
+//! 新节点默认处于观察期，只能占用少量连接和 gossip 主题；出示由受信任证明者签发、
+//! 记录其链上质押和验证等级的 [`StakeAttestation`] 并达到阈值后才解除限制。
+//! 证明的校验结果按节点缓存，过期后重新校验；配置了 [`AttestationSource`] 时会主动查询未出示证明的节点。
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+
+/// 准入参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdmissionConfig {
+    /// 关闭时所有节点都视为已准入
+    pub enabled: bool,
+    /// 受信任的证明者节点 ID（读取链上状态并签发证明的服务）
+    pub trusted_attesters: Vec<String>,
+    /// 解除观察期所需的最低质押（lamports）
+    pub min_stake_lamports: u64,
+    /// 解除观察期所需的最低验证等级（0-5）
+    pub min_verification_level: u8,
+    pub probation_max_connections: usize,
+    pub probation_max_topics: usize,
+    pub admitted_max_connections: usize,
+    pub admitted_max_topics: usize,
+    /// 校验结果缓存时间（秒）
+    pub cache_ttl_secs: i64,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            trusted_attesters: Vec::new(),
+            min_stake_lamports: 1_000_000_000,
+            min_verification_level: 1,
+            probation_max_connections: 1,
+            probation_max_topics: 1,
+            admitted_max_connections: 8,
+            admitted_max_topics: 32,
+            cache_ttl_secs: 300,
+        }
+    }
+}
+
+/// 证明者签发的链上质押证明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakeAttestation {
+    /// 被证明的节点 ID
+    pub node_id: String,
+    pub stake_lamports: u64,
+    pub verification_level: u8,
+    /// 读取链上状态时的 slot
+    pub slot: u64,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    /// 证明者节点 ID（签名公钥）
+    pub attester: String,
+    pub signature: String,
+}
+
+impl StakeAttestation {
+    fn signing_bytes(&self) -> Vec<u8> {
+        format!(
+            "williw-stake-attestation:{}:{}:{}:{}:{}:{}",
+            self.node_id, self.stake_lamports, self.verification_level, self.slot, self.issued_at, self.expires_at
+        )
+        .into_bytes()
+    }
+
+    /// 由证明者身份签发，有效期 `ttl_secs`
+    pub fn issue(
+        attester: &NodeIdentity,
+        node_id: String,
+        stake_lamports: u64,
+        verification_level: u8,
+        slot: u64,
+        ttl_secs: i64,
+    ) -> Self {
+        let issued_at = chrono::Utc::now().timestamp();
+        let mut attestation = Self {
+            node_id,
+            stake_lamports,
+            verification_level,
+            slot,
+            issued_at,
+            expires_at: issued_at + ttl_secs,
+            attester: String::new(),
+            signature: String::new(),
+        };
+        let signature = attester.sign(&attestation.signing_bytes());
+        attestation.attester = signature.pubkey;
+        attestation.signature = signature.signature;
+        attestation
+    }
+
+    pub fn verify_signature(&self) -> bool {
+        verify_signature(
+            &self.signing_bytes(),
+            &SolSignature {
+                pubkey: self.attester.clone(),
+                signature: self.signature.clone(),
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdmissionLevel {
+    /// 观察期：连接和主题受限
+    Probation,
+    Admitted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AdmissionError {
+    #[error("证明者 {0} 不受信任")]
+    UntrustedAttester(String),
+
+    #[error("质押证明签名无效")]
+    BadSignature,
+
+    #[error("质押证明已过期")]
+    Expired,
+
+    #[error("质押不足: {stake} lamports，至少需要 {min}")]
+    InsufficientStake { stake: u64, min: u64 },
+
+    #[error("验证等级不足: {level}，至少需要 {min}")]
+    InsufficientVerification { level: u8, min: u8 },
+
+    #[error("节点 {peer} 连接数已达上限 {limit}")]
+    ConnectionLimit { peer: String, limit: usize },
+
+    #[error("节点 {peer} 订阅主题数已达上限 {limit}")]
+    TopicLimit { peer: String, limit: usize },
+}
+
+/// 主动查询节点质押证明（例如请求证明服务或直接读取链上账户）
+pub trait AttestationSource: Send + Sync {
+    fn fetch(&self, node_id: &str) -> Option<StakeAttestation>;
+}
+
+struct CachedAdmission {
+    level: AdmissionLevel,
+    /// 已准入时为证明的过期时间
+    expires_at: i64,
+    checked_at: i64,
+}
+
+#[derive(Default)]
+struct PeerUsage {
+    connections: usize,
+    topics: HashSet<String>,
+}
+
+/// 准入控制器，线程安全
+pub struct AdmissionController {
+    config: AdmissionConfig,
+    source: Option<Arc<dyn AttestationSource>>,
+    cache: RwLock<HashMap<String, CachedAdmission>>,
+    usage: Mutex<HashMap<String, PeerUsage>>,
+}
+
+impl AdmissionController {
+    pub fn new(config: AdmissionConfig) -> Self {
+        Self {
+            config,
+            source: None,
+            cache: RwLock::new(HashMap::new()),
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_source(mut self, source: Arc<dyn AttestationSource>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn config(&self) -> &AdmissionConfig {
+        &self.config
+    }
+
+    /// 校验证明（不改变缓存）
+    pub fn check(&self, attestation: &StakeAttestation, now: i64) -> Result<(), AdmissionError> {
+        if !self.config.trusted_attesters.contains(&attestation.attester) {
+            return Err(AdmissionError::UntrustedAttester(attestation.attester.clone()));
+        }
+        if !attestation.verify_signature() {
+            return Err(AdmissionError::BadSignature);
+        }
+        if now >= attestation.expires_at {
+            return Err(AdmissionError::Expired);
+        }
+        if attestation.stake_lamports < self.config.min_stake_lamports {
+            return Err(AdmissionError::InsufficientStake {
+                stake: attestation.stake_lamports,
+                min: self.config.min_stake_lamports,
+            });
+        }
+        if attestation.verification_level < self.config.min_verification_level {
+            return Err(AdmissionError::InsufficientVerification {
+                level: attestation.verification_level,
+                min: self.config.min_verification_level,
+            });
+        }
+        Ok(())
+    }
+
+    /// 节点出示证明，校验通过后解除观察期
+    pub fn present(&self, attestation: &StakeAttestation) -> Result<AdmissionLevel, AdmissionError> {
+        let now = chrono::Utc::now().timestamp();
+        let result = self.check(attestation, now);
+        let cached = match &result {
+            Ok(()) => CachedAdmission {
+                level: AdmissionLevel::Admitted,
+                expires_at: attestation.expires_at,
+                checked_at: now,
+            },
+            Err(_) => CachedAdmission {
+                level: AdmissionLevel::Probation,
+                expires_at: now,
+                checked_at: now,
+            },
+        };
+        let mut cache = self.cache.write();
+        // 无效证明不覆盖仍然有效的准入
+        let keep_existing = result.is_err()
+            && cache
+                .get(&attestation.node_id)
+                .is_some_and(|existing| existing.level == AdmissionLevel::Admitted && existing.expires_at > now);
+        if !keep_existing {
+            cache.insert(attestation.node_id.clone(), cached);
+        }
+        result.map(|()| AdmissionLevel::Admitted)
+    }
+
+    /// 节点当前的准入级别；缓存过期时重新查询
+    pub fn level(&self, peer: &str) -> AdmissionLevel {
+        if !self.config.enabled {
+            return AdmissionLevel::Admitted;
+        }
+        let now = chrono::Utc::now().timestamp();
+        if let Some(cached) = self.cache.read().get(peer) {
+            let fresh = now - cached.checked_at < self.config.cache_ttl_secs;
+            match cached.level {
+                AdmissionLevel::Admitted if now < cached.expires_at => return AdmissionLevel::Admitted,
+                AdmissionLevel::Probation if fresh => return AdmissionLevel::Probation,
+                _ => {}
+            }
+        }
+
+        match self.source.as_ref().and_then(|source| source.fetch(peer)) {
+            Some(attestation) if attestation.node_id == peer => {
+                self.present(&attestation).unwrap_or(AdmissionLevel::Probation)
+            }
+            _ => {
+                // 查询失败也缓存，避免每次连接都重新查询
+                self.cache.write().insert(
+                    peer.to_string(),
+                    CachedAdmission {
+                        level: AdmissionLevel::Probation,
+                        expires_at: now,
+                        checked_at: now,
+                    },
+                );
+                AdmissionLevel::Probation
+            }
+        }
+    }
+
+    /// 该级别允许的 (连接数, 主题数)
+    pub fn limits(&self, level: AdmissionLevel) -> (usize, usize) {
+        match level {
+            AdmissionLevel::Probation => (self.config.probation_max_connections, self.config.probation_max_topics),
+            AdmissionLevel::Admitted => (self.config.admitted_max_connections, self.config.admitted_max_topics),
+        }
+    }
+
+    /// 为节点占用一个连接名额
+    pub fn open_connection(&self, peer: &str) -> Result<(), AdmissionError> {
+        let (limit, _) = self.limits(self.level(peer));
+        let mut usage = self.usage.lock();
+        let entry = usage.entry(peer.to_string()).or_default();
+        if self.config.enabled && entry.connections >= limit {
+            return Err(AdmissionError::ConnectionLimit {
+                peer: peer.to_string(),
+                limit,
+            });
+        }
+        entry.connections += 1;
+        Ok(())
+    }
+
+    pub fn close_connection(&self, peer: &str) {
+        if let Some(entry) = self.usage.lock().get_mut(peer) {
+            entry.connections = entry.connections.saturating_sub(1);
+        }
+    }
+
+    /// 为节点登记一个 gossip 主题，重复登记同一主题不占名额
+    pub fn join_topic(&self, peer: &str, topic: &str) -> Result<(), AdmissionError> {
+        let (_, limit) = self.limits(self.level(peer));
+        let mut usage = self.usage.lock();
+        let entry = usage.entry(peer.to_string()).or_default();
+        if entry.topics.contains(topic) {
+            return Ok(());
+        }
+        if self.config.enabled && entry.topics.len() >= limit {
+            return Err(AdmissionError::TopicLimit {
+                peer: peer.to_string(),
+                limit,
+            });
+        }
+        entry.topics.insert(topic.to_string());
+        Ok(())
+    }
+
+    /// 节点离开时释放全部名额（保留证明缓存）
+    pub fn forget(&self, peer: &str) {
+        self.usage.lock().remove(peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::node::MemoryKeystore;
+
+    #[test]
+    fn test_probation_until_valid_attestation() {
+        let attester = NodeIdentity::with_keystore(Arc::new(MemoryKeystore::default())).unwrap();
+        let controller = AdmissionController::new(AdmissionConfig {
+            enabled: true,
+            trusted_attesters: vec![attester.node_id()],
+            ..AdmissionConfig::default()
+        });
+
+        // 观察期只允许一个连接和一个主题
+        assert_eq!(controller.level("peer"), AdmissionLevel::Probation);
+        controller.open_connection("peer").unwrap();
+        assert!(controller.open_connection("peer").is_err());
+        controller.join_topic("peer", "ggb-training").unwrap();
+        controller.join_topic("peer", "ggb-training").unwrap();
+        assert!(controller.join_topic("peer", "ggb-shards").is_err());
+
+        let low_stake = StakeAttestation::issue(&attester, "peer".into(), 1_000, 3, 10, 3600);
+        assert!(matches!(controller.present(&low_stake), Err(AdmissionError::InsufficientStake { .. })));
+        let mut forged = StakeAttestation::issue(&attester, "peer".into(), 1_000, 3, 10, 3600);
+        forged.stake_lamports = 5_000_000_000;
+        assert_eq!(controller.present(&forged), Err(AdmissionError::BadSignature));
+
+        let valid = StakeAttestation::issue(&attester, "peer".into(), 5_000_000_000, 3, 10, 3600);
+        assert_eq!(controller.present(&valid), Ok(AdmissionLevel::Admitted));
+        assert_eq!(controller.level("peer"), AdmissionLevel::Admitted);
+        controller.open_connection("peer").unwrap();
+        controller.join_topic("peer", "ggb-shards").unwrap();
+        // 之后出示的无效证明不会撤销有效准入
+        controller.present(&forged).unwrap_err();
+        assert_eq!(controller.level("peer"), AdmissionLevel::Admitted);
+    }
+}
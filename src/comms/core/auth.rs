@@ -0,0 +1,406 @@
+//! 消息认证与防重放模块
+//!
+//! 所有 gossip 和文件传输消息在发送前封装为 [`AuthenticatedMessage`]：
+//! - 由节点持久身份签名，发送者 ID 即签名公钥，接收方据此验证身份
+//! - 每条消息携带单调递增的 nonce 和时间戳，重复或过旧的消息直接丢弃
+//! - 被拒绝的消息按原因计数，汇总到 `CommsHandle` 的统计中
+//! - 突发的大量入站消息可在短窗口内收集后批量验签（[`MessageAuthenticator::open_batch`]）
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::crypto::identity::{verify_signature, verify_signatures_batch, NodeIdentity};
+use crate::crypto::SolSignature;
+
+/// 认证参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// 是否要求所有入站消息都经过认证
+    pub require_auth: bool,
+    /// 允许的时钟偏差（秒），超出视为过期
+    pub max_clock_skew_secs: i64,
+    /// 每个发送者保留的 nonce 窗口大小
+    pub replay_window: u64,
+    /// 入站 gossip 批量验签的收集窗口（毫秒），0 表示逐条验证
+    #[serde(default = "default_batch_window_ms")]
+    pub batch_window_ms: u64,
+    /// 单批最多验证的消息数
+    #[serde(default = "default_max_batch")]
+    pub max_batch: usize,
+}
+
+fn default_batch_window_ms() -> u64 {
+    5
+}
+
+fn default_max_batch() -> usize {
+    64
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            require_auth: true,
+            max_clock_skew_secs: 120,
+            replay_window: 1024,
+            batch_window_ms: default_batch_window_ms(),
+            max_batch: default_max_batch(),
+        }
+    }
+}
+
+/// 带认证信息的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthenticatedMessage<T> {
+    /// 发送者节点 ID（身份公钥 base58）
+    pub sender: String,
+    pub nonce: u64,
+    /// 发送时间（Unix 秒）
+    pub timestamp: i64,
+    pub body: T,
+    /// 对 `sender|nonce|timestamp|body` 的签名（base58）
+    pub signature: String,
+}
+
+impl<T: Serialize> AuthenticatedMessage<T> {
+    fn signing_bytes(sender: &str, nonce: u64, timestamp: i64, body: &T) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = format!("williw-msg:{}:{}:{}:", sender, nonce, timestamp).into_bytes();
+        bytes.extend_from_slice(&serde_json::to_vec(body)?);
+        Ok(bytes)
+    }
+}
+
+/// 拒绝原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthRejection {
+    #[error("消息签名无效")]
+    BadSignature,
+
+    #[error("发送者不匹配: 声称 {claimed}, 实际 {actual}")]
+    SenderMismatch { claimed: String, actual: String },
+
+    #[error("重复消息: nonce {nonce}")]
+    Replay { nonce: u64 },
+
+    #[error("消息时间戳超出允许范围: {timestamp}")]
+    Stale { timestamp: i64 },
+
+    #[error("消息格式错误")]
+    Malformed,
+}
+
+/// 认证统计（被拒绝的消息按原因计数）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthStats {
+    pub accepted: u64,
+    pub rejected_signature: u64,
+    pub rejected_sender: u64,
+    pub rejected_replay: u64,
+    pub rejected_stale: u64,
+    pub rejected_malformed: u64,
+    /// 批量验签的批次数
+    #[serde(default)]
+    pub batches: u64,
+    /// 批量验签失败、回退到逐条验证的批次数
+    #[serde(default)]
+    pub batch_fallbacks: u64,
+}
+
+impl AuthStats {
+    pub fn total_rejected(&self) -> u64 {
+        self.rejected_signature
+            + self.rejected_sender
+            + self.rejected_replay
+            + self.rejected_stale
+            + self.rejected_malformed
+    }
+}
+
+/// 单个发送者的 nonce 窗口
+#[derive(Default)]
+struct ReplayWindow {
+    highest: u64,
+    seen: BTreeSet<u64>,
+}
+
+impl ReplayWindow {
+    /// 记录 nonce，已见过或落在窗口之外时返回 false
+    fn accept(&mut self, nonce: u64, window: u64) -> bool {
+        if self.highest >= window && nonce <= self.highest - window {
+            return false;
+        }
+        if !self.seen.insert(nonce) {
+            return false;
+        }
+        if nonce > self.highest {
+            self.highest = nonce;
+            let floor = self.highest.saturating_sub(window);
+            self.seen = self.seen.split_off(&floor);
+        }
+        true
+    }
+}
+
+/// 消息认证器
+pub struct MessageAuthenticator {
+    identity: Arc<NodeIdentity>,
+    config: AuthConfig,
+    next_nonce: AtomicU64,
+    windows: Mutex<HashMap<String, ReplayWindow>>,
+    stats: RwLock<AuthStats>,
+}
+
+impl MessageAuthenticator {
+    pub fn new(identity: Arc<NodeIdentity>, config: AuthConfig) -> Self {
+        // 以纳秒时间戳作为 nonce 起点，重启后不会与旧消息冲突
+        let start = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default().max(1) as u64;
+        Self {
+            identity,
+            config,
+            next_nonce: AtomicU64::new(start),
+            windows: Mutex::new(HashMap::new()),
+            stats: RwLock::new(AuthStats::default()),
+        }
+    }
+
+    pub fn config(&self) -> &AuthConfig {
+        &self.config
+    }
+
+    pub fn node_id(&self) -> String {
+        self.identity.node_id()
+    }
+
+    /// 签名并封装消息
+    pub fn seal<T: Serialize>(&self, body: T) -> anyhow::Result<AuthenticatedMessage<T>> {
+        let sender = self.identity.node_id();
+        let nonce = self.next_nonce.fetch_add(1, Ordering::SeqCst);
+        let timestamp = chrono::Utc::now().timestamp();
+        let bytes = AuthenticatedMessage::signing_bytes(&sender, nonce, timestamp, &body)?;
+        let signature = self.identity.sign(&bytes).signature;
+        Ok(AuthenticatedMessage {
+            sender,
+            nonce,
+            timestamp,
+            body,
+            signature,
+        })
+    }
+
+    /// 验证并解封消息
+    ///
+    /// `claimed_sender` 为传输层给出的来源（若有），必须与签名者一致。
+    pub fn open<T: Serialize>(
+        &self,
+        message: AuthenticatedMessage<T>,
+        claimed_sender: Option<&str>,
+    ) -> Result<T, AuthRejection> {
+        let result = self.check(&message, claimed_sender);
+        self.record(&result);
+        result.map(|()| message.body)
+    }
+
+    /// 批量验证并解封消息，结果与输入顺序一致
+    ///
+    /// 签名一次性批量验证；批量验证失败时逐条验证以找出无效消息。nonce 按输入顺序记录。
+    pub fn open_batch<T: Serialize>(
+        &self,
+        messages: Vec<(AuthenticatedMessage<T>, Option<String>)>,
+    ) -> Vec<Result<T, AuthRejection>> {
+        let prechecked: Vec<Result<Vec<u8>, AuthRejection>> = messages
+            .iter()
+            .map(|(message, claimed)| self.precheck(message, claimed.as_deref()))
+            .collect();
+        let signatures: Vec<SolSignature> = messages
+            .iter()
+            .map(|(message, _)| SolSignature {
+                pubkey: message.sender.clone(),
+                signature: message.signature.clone(),
+            })
+            .collect();
+        let pending: Vec<usize> = (0..messages.len()).filter(|&i| prechecked[i].is_ok()).collect();
+        let items: Vec<(&[u8], &SolSignature)> = pending
+            .iter()
+            .map(|&i| (prechecked[i].as_deref().expect("已过滤"), &signatures[i]))
+            .collect();
+        let verified = verify_signatures_batch(&items);
+        if !items.is_empty() {
+            let mut stats = self.stats.write();
+            stats.batches += 1;
+            if verified.iter().any(|ok| !ok) {
+                stats.batch_fallbacks += 1;
+            }
+        }
+
+        let mut signature_ok = vec![false; messages.len()];
+        for (n, &i) in pending.iter().enumerate() {
+            signature_ok[i] = verified[n];
+        }
+        messages
+            .into_iter()
+            .zip(prechecked)
+            .zip(signature_ok)
+            .map(|(((message, _), prechecked), signature_ok)| {
+                let result = match prechecked {
+                    Err(rejection) => Err(rejection),
+                    Ok(_) if !signature_ok => Err(AuthRejection::BadSignature),
+                    Ok(_) => self.accept_nonce(&message),
+                };
+                self.record(&result);
+                result.map(|()| message.body)
+            })
+            .collect()
+    }
+
+    fn record(&self, result: &Result<(), AuthRejection>) {
+        let mut stats = self.stats.write();
+        match result {
+            Ok(()) => stats.accepted += 1,
+            Err(AuthRejection::BadSignature) => stats.rejected_signature += 1,
+            Err(AuthRejection::SenderMismatch { .. }) => stats.rejected_sender += 1,
+            Err(AuthRejection::Replay { .. }) => stats.rejected_replay += 1,
+            Err(AuthRejection::Stale { .. }) => stats.rejected_stale += 1,
+            Err(AuthRejection::Malformed) => stats.rejected_malformed += 1,
+        }
+    }
+
+    /// 记录无法解析的入站消息
+    pub fn record_malformed(&self) {
+        self.stats.write().rejected_malformed += 1;
+    }
+
+    fn check<T: Serialize>(
+        &self,
+        message: &AuthenticatedMessage<T>,
+        claimed_sender: Option<&str>,
+    ) -> Result<(), AuthRejection> {
+        let bytes = self.precheck(message, claimed_sender)?;
+        let signature = SolSignature {
+            pubkey: message.sender.clone(),
+            signature: message.signature.clone(),
+        };
+        if !verify_signature(&bytes, &signature) {
+            return Err(AuthRejection::BadSignature);
+        }
+        self.accept_nonce(message)
+    }
+
+    /// 签名之外的检查（来源、时间戳），通过时返回待验签的字节
+    fn precheck<T: Serialize>(
+        &self,
+        message: &AuthenticatedMessage<T>,
+        claimed_sender: Option<&str>,
+    ) -> Result<Vec<u8>, AuthRejection> {
+        if let Some(claimed) = claimed_sender {
+            if claimed != message.sender {
+                return Err(AuthRejection::SenderMismatch {
+                    claimed: claimed.to_string(),
+                    actual: message.sender.clone(),
+                });
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        if (now - message.timestamp).abs() > self.config.max_clock_skew_secs {
+            return Err(AuthRejection::Stale {
+                timestamp: message.timestamp,
+            });
+        }
+
+        AuthenticatedMessage::signing_bytes(&message.sender, message.nonce, message.timestamp, &message.body)
+            .map_err(|_| AuthRejection::Malformed)
+    }
+
+    /// 签名通过后才记录 nonce，避免伪造消息污染窗口
+    fn accept_nonce<T>(&self, message: &AuthenticatedMessage<T>) -> Result<(), AuthRejection> {
+        let mut windows = self.windows.lock();
+        let window = windows.entry(message.sender.clone()).or_default();
+        if !window.accept(message.nonce, self.config.replay_window) {
+            return Err(AuthRejection::Replay { nonce: message.nonce });
+        }
+        Ok(())
+    }
+
+    /// 节点离开后清理其 nonce 窗口
+    pub fn forget_peer(&self, peer_id: &str) {
+        self.windows.lock().remove(peer_id);
+    }
+
+    pub fn stats(&self) -> AuthStats {
+        self.stats.read().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::FileKeystore;
+
+    fn authenticator(name: &str) -> MessageAuthenticator {
+        let path = std::env::temp_dir().join(format!("williw-auth-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let identity = NodeIdentity::with_keystore(Arc::new(FileKeystore::new(path))).unwrap();
+        MessageAuthenticator::new(Arc::new(identity), AuthConfig::default())
+    }
+
+    #[test]
+    fn test_replay_and_tamper_are_rejected() {
+        let alice = authenticator("alice");
+        let bob = authenticator("bob");
+
+        let sealed = alice.seal("hello".to_string()).unwrap();
+        assert_eq!(bob.open(sealed.clone(), Some(&alice.node_id())).unwrap(), "hello");
+        assert_eq!(
+            bob.open(sealed.clone(), None).unwrap_err(),
+            AuthRejection::Replay { nonce: sealed.nonce }
+        );
+
+        let mut tampered = alice.seal("hello".to_string()).unwrap();
+        tampered.body = "evil".to_string();
+        assert_eq!(bob.open(tampered, None).unwrap_err(), AuthRejection::BadSignature);
+
+        let spoofed = alice.seal("hi".to_string()).unwrap();
+        assert!(matches!(
+            bob.open(spoofed, Some("someone-else")),
+            Err(AuthRejection::SenderMismatch { .. })
+        ));
+
+        let stats = bob.stats();
+        assert_eq!(stats.accepted, 1);
+        assert_eq!(stats.total_rejected(), 3);
+    }
+
+    #[test]
+    fn test_batch_open_isolates_bad_signature() {
+        let alice = authenticator("batch-alice");
+        let bob = authenticator("batch-bob");
+
+        let mut batch: Vec<_> = (0..5)
+            .map(|i| (alice.seal(format!("msg-{}", i)).unwrap(), Some(alice.node_id())))
+            .collect();
+        batch[2].0.body = "evil".to_string();
+        batch.push(batch[0].clone());
+
+        let results = bob.open_batch(batch);
+        assert_eq!(results[0].as_deref(), Ok("msg-0"));
+        assert_eq!(results[2], Err(AuthRejection::BadSignature));
+        assert_eq!(results[4].as_deref(), Ok("msg-4"));
+        assert!(matches!(results[5], Err(AuthRejection::Replay { .. })));
+
+        let stats = bob.stats();
+        assert_eq!((stats.accepted, stats.batches, stats.batch_fallbacks), (4, 1, 1));
+    }
+
+    #[test]
+    fn test_replay_window_drops_old_nonces() {
+        let mut window = ReplayWindow::default();
+        assert!(window.accept(10, 4));
+        assert!(window.accept(8, 4));
+        assert!(!window.accept(8, 4));
+        assert!(window.accept(20, 4));
+        assert!(!window.accept(12, 4));
+    }
+}
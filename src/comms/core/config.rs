@@ -19,6 +19,18 @@ pub struct CommsConfig {
     pub enable_dht: bool,
     pub bootstrap_peers_file: Option<PathBuf>,
     pub security: crate::config::SecurityConfig,
+    /// 应用层保活配置
+    #[serde(default)]
+    pub keepalive: super::keepalive::KeepaliveConfig,
+    /// 消息认证与防重放配置
+    #[serde(default)]
+    pub auth: super::auth::AuthConfig,
+    /// 基于质押证明的节点准入配置
+    #[serde(default)]
+    pub admission: super::admission::AdmissionConfig,
+    /// QUIC 会话恢复与 0-RTT 配置
+    #[serde(default)]
+    pub quic_resumption: crate::comms::transport::ResumptionConfig,
 }
 
 impl Default for CommsConfig {
@@ -32,12 +44,16 @@ impl Default for CommsConfig {
             enable_dht: true,
             bootstrap_peers_file: None,
             security: crate::config::SecurityConfig::default(),
+            keepalive: super::keepalive::KeepaliveConfig::default(),
+            auth: super::auth::AuthConfig::default(),
+            admission: super::admission::AdmissionConfig::default(),
+            quic_resumption: crate::comms::transport::ResumptionConfig::default(),
         }
     }
 }
 
 /// 带宽预算配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BandwidthBudgetConfig {
     pub sparse_per_window: u32,
     pub dense_bytes_per_window: usize,
@@ -80,6 +96,11 @@ impl BandwidthBudget {
         }
     }
 
+    /// 替换预算配置，当前窗口内已用额度保留
+    pub(crate) fn set_config(&mut self, config: BandwidthBudgetConfig) {
+        self.config = config;
+    }
+
     pub(crate) fn allow_sparse(&mut self) -> bool {
         self.rotate();
         if self.sparse_sent < self.config.sparse_per_window {
@@ -4,6 +4,7 @@
 
 use anyhow::{anyhow, Result};
 use parking_lot::RwLock;
+use std::collections::VecDeque;
 use std::sync::Arc;
 // Stub iroh types for compatibility
 #[derive(Clone)]
@@ -13,8 +14,13 @@ use tokio::sync::mpsc;
 use crate::consensus::SignedGossip;
 use crate::device::NetworkType;
 
-use super::config::{CommsConfig, BandwidthBudget};
+use super::admission::{AdmissionController, AdmissionError, AdmissionLevel};
+use super::auth::{AuthConfig, AuthStats, AuthenticatedMessage, MessageAuthenticator};
+use super::config::{BandwidthBudget, BandwidthBudgetConfig, CommsConfig};
+use super::keepalive::{KeepaliveMonitor, LivenessChange, PeerLivenessInfo};
+use crate::comms::transport::codec::{decode_json, encode_frame, WireLimits};
 use crate::comms::transport::iroh::QuicGateway;
+use crate::comms::transport::HandshakeStats;
 
 /// Topic 类型（用于发布/订阅）
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -92,9 +98,32 @@ pub struct CommsHandle {
     event_tx: mpsc::Sender<IrohEvent>,
     pub event_rx: mpsc::Receiver<IrohEvent>,
     quic: Option<Arc<QuicGateway>>,
+    /// QUIC 引导节点，系统唤醒或网络切换后重新连接
+    quic_bootstrap: Vec<std::net::SocketAddr>,
     bandwidth: RwLock<BandwidthBudget>,
     network_type: parking_lot::RwLock<NetworkType>,
     subscriptions: RwLock<Vec<PeerSubscription>>,
+    keepalive: RwLock<KeepaliveMonitor>,
+    auth_config: AuthConfig,
+    auth: Option<Arc<MessageAuthenticator>>,
+    admission: Arc<AdmissionController>,
+    /// 收集 gossip 批次时顺带取出的其他事件，按原顺序稍后交付
+    pending_events: VecDeque<IrohEvent>,
+}
+
+/// 通信统计
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CommsStats {
+    /// 已订阅节点数
+    pub subscribed_peers: usize,
+    /// 消息认证统计（含被拒绝消息计数）
+    pub auth: AuthStats,
+    /// 处于观察期的已订阅节点数
+    #[serde(default)]
+    pub probation_peers: usize,
+    /// QUIC 完整握手与会话恢复计数
+    #[serde(default)]
+    pub handshakes: HandshakeStats,
 }
 
 impl CommsHandle {
@@ -113,8 +142,9 @@ impl CommsHandle {
         // 初始化 QUIC 网关（用于实时通信）
         let quic: Option<Arc<QuicGateway>> = if let Some(bind) = config.quic_bind {
             let quic_bootstrap = config.quic_bootstrap.clone();
+            let resumption = config.quic_resumption.clone();
             match tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current().block_on(QuicGateway::new(bind))
+                tokio::runtime::Handle::current().block_on(QuicGateway::with_resumption(bind, resumption))
             }) {
                 Ok(gateway) => {
                     let gateway = Arc::new(gateway);
@@ -168,15 +198,195 @@ impl CommsHandle {
             event_tx,
             event_rx,
             quic,
+            quic_bootstrap: config.quic_bootstrap.clone(),
             bandwidth: RwLock::new(BandwidthBudget::new(config.bandwidth)),
             network_type: parking_lot::RwLock::new(NetworkType::Unknown),
             subscriptions: RwLock::new(Vec::new()),
+            keepalive: RwLock::new(KeepaliveMonitor::new(config.keepalive.clone())),
+            auth_config: config.auth.clone(),
+            auth: None,
+            admission: Arc::new(AdmissionController::new(config.admission.clone())),
+            pending_events: VecDeque::new(),
         })
     }
 
+    /// 启用消息认证：节点 ID 切换为身份公钥，所有收发消息均签名并校验 nonce
+    pub fn enable_authentication(&mut self, identity: Arc<crate::crypto::NodeIdentity>) -> Arc<MessageAuthenticator> {
+        let authenticator = Arc::new(MessageAuthenticator::new(identity, self.auth_config.clone()));
+        self.peer_id = authenticator.node_id();
+        self.auth = Some(authenticator.clone());
+        authenticator
+    }
+
+    /// 消息认证器（供文件传输等其他通道复用）
+    pub fn authenticator(&self) -> Option<Arc<MessageAuthenticator>> {
+        self.auth.clone()
+    }
+
+    /// 替换准入控制器（例如挂接了链上证明查询的实例）
+    pub fn set_admission(&mut self, admission: Arc<AdmissionController>) {
+        self.admission = admission;
+    }
+
+    pub fn admission(&self) -> Arc<AdmissionController> {
+        self.admission.clone()
+    }
+
+    /// 为新连接申请准入：占用一个连接名额并登记默认主题，超出节点所在级别的上限时拒绝
+    pub fn admit_connection(&self, peer: &str) -> Result<AdmissionLevel, AdmissionError> {
+        self.admission.open_connection(peer)?;
+        if let Err(e) = self.admission.join_topic(peer, self.topic.name()) {
+            self.admission.close_connection(peer);
+            return Err(e);
+        }
+        Ok(self.admission.level(peer))
+    }
+
+    pub fn release_connection(&self, peer: &str) {
+        self.admission.close_connection(peer);
+    }
+
+    /// 为已订阅节点追加 gossip 主题，受准入级别的主题数上限约束
+    pub fn subscribe_peer_topic(&self, peer: &str, topic: Topic) -> Result<(), AdmissionError> {
+        self.admission.join_topic(peer, topic.name())?;
+        let mut subscriptions = self.subscriptions.write();
+        if let Some(subscription) = subscriptions.iter_mut().find(|s| s.peer == peer) {
+            if !subscription.topics.contains(&topic) {
+                subscription.topics.push(topic);
+            }
+        }
+        Ok(())
+    }
+
+    /// 编码待发送的 gossip（启用认证时封装为带签名和 nonce 的消息）
+    fn encode_gossip(&self, signed: &SignedGossip) -> Result<Vec<u8>> {
+        match &self.auth {
+            Some(auth) => Ok(serde_json::to_vec(&auth.seal(signed)?)?),
+            None => Ok(serde_json::to_vec(signed)?),
+        }
+    }
+
+    /// 解码并认证收到的 gossip
+    ///
+    /// 签名无效、重复或过期的消息返回 `None` 并计入统计；超长或畸形的数据在解码前即被拒绝。
+    pub fn open_gossip(&self, data: &[u8]) -> Option<SignedGossip> {
+        let limits = WireLimits::default();
+        let Some(auth) = &self.auth else {
+            return decode_json(data, &limits).ok();
+        };
+        match decode_json::<AuthenticatedMessage<SignedGossip>>(data, &limits) {
+            // 外层签名者必须与 gossip 声明的签名身份一致
+            Ok(message) => match auth.open(message.clone(), message.body.signer.as_deref()) {
+                Ok(signed) => Some(signed),
+                Err(e) => {
+                    println!("[认证] 丢弃消息: {}", e);
+                    None
+                }
+            },
+            // 未认证的消息只在未强制认证时放行
+            Err(_) if !self.auth_config.require_auth => decode_json(data, &limits).ok(),
+            Err(_) => {
+                auth.record_malformed();
+                None
+            }
+        }
+    }
+
+    /// 批量解码并认证收到的 gossip，返回通过认证的 `(来源, 消息)`，顺序与输入一致
+    ///
+    /// 外层签名一次性批量验证，失败时逐条回退以剔除无效消息。
+    pub fn open_gossip_batch(&self, frames: Vec<(String, Vec<u8>)>) -> Vec<(String, SignedGossip)> {
+        let auth = match &self.auth {
+            Some(auth) if frames.len() > 1 => auth,
+            _ => {
+                return frames
+                    .into_iter()
+                    .filter_map(|(source, data)| Some((source, self.open_gossip(&data)?)))
+                    .collect()
+            }
+        };
+
+        let limits = WireLimits::default();
+        // None：未认证但允许放行的消息；Some(i)：批次中的第 i 条
+        let mut slots: Vec<(String, Result<Option<usize>, SignedGossip>)> = Vec::with_capacity(frames.len());
+        let mut batch = Vec::new();
+        for (source, data) in frames {
+            match decode_json::<AuthenticatedMessage<SignedGossip>>(&data, &limits) {
+                Ok(message) => {
+                    let signer = message.body.signer.clone();
+                    slots.push((source, Ok(Some(batch.len()))));
+                    batch.push((message, signer));
+                }
+                Err(_) if !self.auth_config.require_auth => match decode_json(&data, &limits) {
+                    Ok(signed) => slots.push((source, Err(signed))),
+                    Err(_) => slots.push((source, Ok(None))),
+                },
+                Err(_) => {
+                    auth.record_malformed();
+                    slots.push((source, Ok(None)));
+                }
+            }
+        }
+
+        let mut opened: Vec<Option<SignedGossip>> = auth
+            .open_batch(batch)
+            .into_iter()
+            .map(|result| {
+                result
+                    .map_err(|e| println!("[认证] 丢弃消息: {}", e))
+                    .ok()
+            })
+            .collect();
+        slots
+            .into_iter()
+            .filter_map(|(source, slot)| match slot {
+                Ok(Some(i)) => Some((source, opened[i].take()?)),
+                Ok(None) => None,
+                Err(unauthenticated) => Some((source, unauthenticated)),
+            })
+            .collect()
+    }
+
+    /// 以 `first` 开始收集一批 gossip：在批量窗口内继续读取事件，直到窗口结束或达到批次上限
+    ///
+    /// 期间读到的其他事件留待 [`next_event`](Self::next_event) 按原顺序交付。
+    pub async fn collect_gossip_batch(&mut self, source: String, data: Vec<u8>) -> Vec<(String, Vec<u8>)> {
+        let mut frames = vec![(source, data)];
+        if self.auth_config.batch_window_ms == 0 || !self.pending_events.is_empty() {
+            return frames;
+        }
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(self.auth_config.batch_window_ms);
+        while frames.len() < self.auth_config.max_batch.max(1) {
+            match tokio::time::timeout_at(deadline, self.event_rx.recv()).await {
+                Ok(Some(IrohEvent::Gossip { source, data })) => frames.push((source, data)),
+                Ok(Some(other)) => {
+                    // 保持事件顺序：遇到非 gossip 事件即结束本批
+                    self.pending_events.push_back(other);
+                    break;
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+        frames
+    }
+
+    /// 获取通信统计
+    pub fn stats(&self) -> CommsStats {
+        let subscriptions = self.subscriptions.read();
+        CommsStats {
+            subscribed_peers: subscriptions.len(),
+            auth: self.auth.as_ref().map(|auth| auth.stats()).unwrap_or_default(),
+            probation_peers: subscriptions
+                .iter()
+                .filter(|s| self.admission.level(&s.peer) == AdmissionLevel::Probation)
+                .count(),
+            handshakes: self.quic.as_ref().map(|quic| quic.handshake_stats()).unwrap_or_default(),
+        }
+    }
+
     /// 发布消息到 gossip 网络
     pub fn publish(&mut self, signed: &SignedGossip) -> Result<()> {
-        let data = serde_json::to_vec(signed)?;
+        let data = self.encode_gossip(signed)?;
 
         // 获取所有订阅的 peer
         let subscriptions = self.subscriptions.read();
@@ -193,11 +403,7 @@ impl CommsHandle {
         for subscription in subscriptions.iter() {
             if subscription.topics.contains(&self.topic) {
                 // 序列化消息: [topic_len:4][topic_data][message_data]
-                let topic_bytes = self.topic.name().as_bytes();
-                let mut message = Vec::with_capacity(4 + topic_bytes.len() + data.len());
-                message.extend_from_slice(&(topic_bytes.len() as u32).to_be_bytes());
-                message.extend_from_slice(topic_bytes);
-                message.extend_from_slice(&data);
+                let message = encode_frame(self.topic.name(), &data);
 
                 // 发送（这里简化实现，实际应该使用连接池）
                 if self.send_to_peer(&subscription.peer, &message).is_ok() {
@@ -222,10 +428,24 @@ impl CommsHandle {
         Ok(())
     }
 
+    /// 运行时调整带宽预算
+    pub fn set_bandwidth_budget(&self, config: BandwidthBudgetConfig) {
+        println!(
+            "[网络] 带宽预算更新: 稀疏 {}/窗口, 稠密 {} 字节/窗口, 窗口 {}s",
+            config.sparse_per_window, config.dense_bytes_per_window, config.window_secs
+        );
+        self.bandwidth.write().set_config(config);
+    }
+
     pub fn allow_sparse_update(&self) -> bool {
         self.bandwidth.write().allow_sparse()
     }
 
+    /// 出站 gossip 积压的消息数
+    pub fn outbound_backlog(&self) -> usize {
+        self.gossip_tx.max_capacity() - self.gossip_tx.capacity()
+    }
+
     pub fn allow_dense_snapshot(&self, bytes: usize) -> bool {
         let network_type = *self.network_type.read();
         if !network_type.allows_dense_snapshot() {
@@ -253,6 +473,7 @@ impl CommsHandle {
             });
             println!("[Iroh] 添加 peer 到订阅列表: {}", peer);
         }
+        self.keepalive.write().track_peer(&peer);
     }
 
     /// 从订阅列表中移除 peer
@@ -262,6 +483,65 @@ impl CommsHandle {
             subscriptions.remove(pos);
             println!("[Iroh] 从订阅列表中移除 peer: {}", peer);
         }
+        self.keepalive.write().untrack_peer(peer);
+        self.admission.forget(peer);
+    }
+
+    /// 获取到期需要发送的保活 ping，返回 (peer_id, nonce)
+    pub fn due_keepalive_pings(&self) -> Vec<(String, u64)> {
+        let network_type = self.network_type();
+        self.keepalive.write().due_pings(network_type)
+    }
+
+    /// 记录收到的 pong
+    pub fn record_pong(&self, peer: &str, nonce: u64) -> Option<std::time::Duration> {
+        self.keepalive.write().record_pong(peer, nonce)
+    }
+
+    /// 记录来自节点的任意有效流量
+    pub fn record_peer_activity(&self, peer: &str) {
+        self.keepalive.write().record_activity(peer);
+    }
+
+    /// 评估节点存活状态
+    ///
+    /// 判定死亡的节点会立即从订阅列表移除，并通过事件通道发出
+    /// `PeerExpired`，无需等待传输层超时。返回本轮发生的状态变化。
+    pub fn detect_dead_peers(&mut self) -> Vec<LivenessChange> {
+        let network_type = self.network_type();
+        let (changes, dead) = {
+            let mut keepalive = self.keepalive.write();
+            let changes = keepalive.evaluate(network_type);
+            (changes, keepalive.drain_dead())
+        };
+
+        for peer in dead {
+            println!("[保活] 节点无响应，判定离线: {}", peer);
+            self.remove_peer(&peer);
+            let _ = self.event_tx.try_send(IrohEvent::PeerExpired { peer });
+        }
+        changes
+    }
+
+    /// 系统唤醒或网络切换后恢复连接：保活重新计时，重新连接 QUIC 引导节点
+    ///
+    /// 旧连接绑定在睡眠前的地址上，可能已经失效但尚未超时；这里不等待超时，直接重新建立。
+    pub async fn reconnect(&self) {
+        self.keepalive.write().restart();
+        let Some(quic) = &self.quic else {
+            return;
+        };
+        for addr in &self.quic_bootstrap {
+            match quic.connect(*addr).await {
+                Ok(()) => println!("[网络] 已重新连接引导节点 {}", addr),
+                Err(e) => println!("[网络] 重新连接引导节点 {} 失败: {}", addr, e),
+            }
+        }
+    }
+
+    /// 获取所有节点的存活信息（用于集群视图）
+    pub fn peer_liveness(&self) -> Vec<PeerLivenessInfo> {
+        self.keepalive.read().snapshot()
     }
 
     /// 连接到中继节点
@@ -275,19 +555,30 @@ impl CommsHandle {
 
     /// 获取下一个事件
     pub async fn next_event(&mut self) -> Option<IrohEvent> {
+        if let Some(event) = self.pending_events.pop_front() {
+            return Some(event);
+        }
         self.event_rx.recv().await
     }
 
     pub async fn broadcast_realtime(&self, signed: &SignedGossip) -> bool {
         if let Some(quic) = &self.quic {
-            return quic.broadcast(signed).await;
+            return match self.encode_gossip(signed) {
+                Ok(data) => quic.broadcast_bytes(data).await,
+                Err(_) => false,
+            };
         }
         false
     }
 
     pub fn take_quic_messages(&self) -> Vec<SignedGossip> {
         if let Some(quic) = &self.quic {
-            return quic.take_received_messages();
+            let frames = quic
+                .take_received_messages()
+                .into_iter()
+                .map(|data| ("QUIC".to_string(), data))
+                .collect();
+            return self.open_gossip_batch(frames).into_iter().map(|(_, signed)| signed).collect();
         }
         Vec::new()
     }
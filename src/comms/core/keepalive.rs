@@ -0,0 +1,377 @@
+//! 应用层保活模块
+//!
+//! 在传输层超时之前通过 ping/pong 探测失联节点，
+//! 并按网络类型提供不同的探测间隔和丢失阈值。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::device::NetworkType;
+
+/// 单一网络类型下的保活参数
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KeepaliveTunables {
+    /// ping 发送间隔（秒）
+    pub ping_interval_secs: u64,
+    /// 连续丢失多少个 pong 后标记为可疑
+    pub suspect_after_misses: u32,
+    /// 连续丢失多少个 pong 后判定节点死亡
+    pub dead_after_misses: u32,
+}
+
+impl KeepaliveTunables {
+    pub fn ping_interval(&self) -> Duration {
+        Duration::from_secs(self.ping_interval_secs.max(1))
+    }
+}
+
+/// 保活配置（按网络类型区分）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeepaliveConfig {
+    /// 是否启用应用层保活
+    pub enabled: bool,
+    pub wifi: KeepaliveTunables,
+    pub cellular_5g: KeepaliveTunables,
+    pub cellular_4g: KeepaliveTunables,
+    pub unknown: KeepaliveTunables,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            wifi: KeepaliveTunables {
+                ping_interval_secs: 5,
+                suspect_after_misses: 2,
+                dead_after_misses: 4,
+            },
+            // 移动网络下降低探测频率以节省流量和电量，同时容忍更多丢包
+            cellular_5g: KeepaliveTunables {
+                ping_interval_secs: 10,
+                suspect_after_misses: 2,
+                dead_after_misses: 5,
+            },
+            cellular_4g: KeepaliveTunables {
+                ping_interval_secs: 15,
+                suspect_after_misses: 3,
+                dead_after_misses: 6,
+            },
+            unknown: KeepaliveTunables {
+                ping_interval_secs: 10,
+                suspect_after_misses: 3,
+                dead_after_misses: 6,
+            },
+        }
+    }
+}
+
+impl KeepaliveConfig {
+    /// 获取指定网络类型的保活参数
+    pub fn tunables_for(&self, network_type: NetworkType) -> KeepaliveTunables {
+        match network_type {
+            NetworkType::WiFi => self.wifi,
+            NetworkType::Cellular5G => self.cellular_5g,
+            NetworkType::Cellular4G => self.cellular_4g,
+            NetworkType::Unknown => self.unknown,
+        }
+    }
+}
+
+/// 节点存活状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PeerLiveness {
+    /// 正常响应
+    #[default]
+    Alive,
+    /// 丢失部分 pong，可能失联
+    Suspect,
+    /// 判定死亡
+    Dead,
+}
+
+/// 单个节点的存活信息（用于集群视图）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerLivenessInfo {
+    pub peer_id: String,
+    pub state: PeerLiveness,
+    /// 连续未响应的 ping 数
+    pub missed_pings: u32,
+    /// 最近一次往返时延（毫秒）
+    pub last_rtt_ms: Option<u64>,
+    /// 距离上次收到 pong 的秒数
+    pub secs_since_last_pong: u64,
+}
+
+struct PeerKeepalive {
+    state: PeerLiveness,
+    outstanding: Option<(u64, Instant)>,
+    missed_pings: u32,
+    last_pong: Instant,
+    last_ping: Option<Instant>,
+    last_rtt: Option<Duration>,
+}
+
+impl PeerKeepalive {
+    fn new(now: Instant) -> Self {
+        Self {
+            state: PeerLiveness::Alive,
+            outstanding: None,
+            missed_pings: 0,
+            last_pong: now,
+            last_ping: None,
+            last_rtt: None,
+        }
+    }
+}
+
+/// 状态变化通知
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LivenessChange {
+    pub peer_id: String,
+    pub from: PeerLiveness,
+    pub to: PeerLiveness,
+}
+
+/// 保活监视器
+///
+/// 只负责记账，不直接发送消息：调用方通过 [`KeepaliveMonitor::due_pings`]
+/// 获取需要发送的 ping，并在收到 pong 时调用 [`KeepaliveMonitor::record_pong`]。
+pub struct KeepaliveMonitor {
+    config: KeepaliveConfig,
+    peers: HashMap<String, PeerKeepalive>,
+    next_nonce: u64,
+}
+
+impl KeepaliveMonitor {
+    pub fn new(config: KeepaliveConfig) -> Self {
+        Self {
+            config,
+            peers: HashMap::new(),
+            next_nonce: 1,
+        }
+    }
+
+    pub fn config(&self) -> &KeepaliveConfig {
+        &self.config
+    }
+
+    /// 开始跟踪节点
+    pub fn track_peer(&mut self, peer_id: &str) {
+        self.peers
+            .entry(peer_id.to_string())
+            .or_insert_with(|| PeerKeepalive::new(Instant::now()));
+    }
+
+    /// 停止跟踪节点
+    pub fn untrack_peer(&mut self, peer_id: &str) {
+        self.peers.remove(peer_id);
+    }
+
+    /// 收集到期需要发送的 ping，返回 (peer_id, nonce)
+    ///
+    /// 上一个 ping 仍未响应时会计为一次丢失。
+    pub fn due_pings(&mut self, network_type: NetworkType) -> Vec<(String, u64)> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+        let tunables = self.config.tunables_for(network_type);
+        let interval = tunables.ping_interval();
+        let now = Instant::now();
+        let mut due = Vec::new();
+
+        for (peer_id, peer) in self.peers.iter_mut() {
+            if peer.state == PeerLiveness::Dead {
+                continue;
+            }
+            let is_due = peer
+                .last_ping
+                .map(|sent| now.duration_since(sent) >= interval)
+                .unwrap_or(true);
+            if !is_due {
+                continue;
+            }
+            if peer.outstanding.take().is_some() {
+                peer.missed_pings += 1;
+            }
+            let nonce = self.next_nonce;
+            self.next_nonce = self.next_nonce.wrapping_add(1);
+            peer.outstanding = Some((nonce, now));
+            peer.last_ping = Some(now);
+            due.push((peer_id.clone(), nonce));
+        }
+        due
+    }
+
+    /// 记录收到的 pong，返回往返时延
+    pub fn record_pong(&mut self, peer_id: &str, nonce: u64) -> Option<Duration> {
+        let peer = self.peers.get_mut(peer_id)?;
+        match peer.outstanding {
+            Some((expected, sent_at)) if expected == nonce => {
+                let now = Instant::now();
+                let rtt = now.duration_since(sent_at);
+                peer.outstanding = None;
+                peer.missed_pings = 0;
+                peer.last_pong = now;
+                peer.last_rtt = Some(rtt);
+                peer.state = PeerLiveness::Alive;
+                Some(rtt)
+            }
+            _ => None,
+        }
+    }
+
+    /// 任何来自该节点的有效流量都视为存活证明
+    pub fn record_activity(&mut self, peer_id: &str) {
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.missed_pings = 0;
+            peer.last_pong = Instant::now();
+            if peer.state != PeerLiveness::Dead {
+                peer.state = PeerLiveness::Alive;
+            }
+        }
+    }
+
+    /// 系统唤醒或网络切换后重新计时：睡眠期间未应答的 ping 不计入丢失，
+    /// 未死亡的节点在下次评估时恢复为存活
+    pub fn restart(&mut self) {
+        let now = Instant::now();
+        for peer in self.peers.values_mut().filter(|peer| peer.state != PeerLiveness::Dead) {
+            peer.outstanding = None;
+            peer.missed_pings = 0;
+            peer.last_ping = None;
+            peer.last_pong = now;
+        }
+    }
+
+    /// 根据丢失计数更新状态，返回发生变化的节点
+    pub fn evaluate(&mut self, network_type: NetworkType) -> Vec<LivenessChange> {
+        let tunables = self.config.tunables_for(network_type);
+        let mut changes = Vec::new();
+
+        for (peer_id, peer) in self.peers.iter_mut() {
+            let next = if peer.missed_pings >= tunables.dead_after_misses {
+                PeerLiveness::Dead
+            } else if peer.missed_pings >= tunables.suspect_after_misses {
+                PeerLiveness::Suspect
+            } else {
+                PeerLiveness::Alive
+            };
+            // 死亡状态只能通过重新跟踪恢复
+            if peer.state == PeerLiveness::Dead || next == peer.state {
+                continue;
+            }
+            changes.push(LivenessChange {
+                peer_id: peer_id.clone(),
+                from: peer.state,
+                to: next,
+            });
+            peer.state = next;
+        }
+        changes
+    }
+
+    /// 移除所有已死亡节点并返回其 ID
+    pub fn drain_dead(&mut self) -> Vec<String> {
+        let dead: Vec<String> = self
+            .peers
+            .iter()
+            .filter(|(_, peer)| peer.state == PeerLiveness::Dead)
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect();
+        for peer_id in &dead {
+            self.peers.remove(peer_id);
+        }
+        dead
+    }
+
+    pub fn state_of(&self, peer_id: &str) -> Option<PeerLiveness> {
+        self.peers.get(peer_id).map(|peer| peer.state)
+    }
+
+    /// 导出所有节点的存活信息
+    pub fn snapshot(&self) -> Vec<PeerLivenessInfo> {
+        let now = Instant::now();
+        let mut infos: Vec<PeerLivenessInfo> = self
+            .peers
+            .iter()
+            .map(|(peer_id, peer)| PeerLivenessInfo {
+                peer_id: peer_id.clone(),
+                state: peer.state,
+                missed_pings: peer.missed_pings,
+                last_rtt_ms: peer.last_rtt.map(|rtt| rtt.as_millis() as u64),
+                secs_since_last_pong: now.duration_since(peer.last_pong).as_secs(),
+            })
+            .collect();
+        infos.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        infos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_config() -> KeepaliveConfig {
+        let tunables = KeepaliveTunables {
+            ping_interval_secs: 0,
+            suspect_after_misses: 1,
+            dead_after_misses: 2,
+        };
+        KeepaliveConfig {
+            enabled: true,
+            wifi: tunables,
+            cellular_5g: tunables,
+            cellular_4g: tunables,
+            unknown: tunables,
+        }
+    }
+
+    #[test]
+    fn test_missed_pings_mark_peer_dead() {
+        let mut monitor = KeepaliveMonitor::new(fast_config());
+        monitor.track_peer("peer-a");
+
+        // interval 被钳制为 1 秒，这里手动清空 last_ping 模拟时间流逝
+        for _ in 0..3 {
+            monitor.peers.get_mut("peer-a").unwrap().last_ping = None;
+            monitor.due_pings(NetworkType::WiFi);
+        }
+        let changes = monitor.evaluate(NetworkType::WiFi);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to, PeerLiveness::Dead);
+        assert_eq!(monitor.drain_dead(), vec!["peer-a".to_string()]);
+        assert!(monitor.state_of("peer-a").is_none());
+    }
+
+    #[test]
+    fn test_pong_resets_misses() {
+        let mut monitor = KeepaliveMonitor::new(fast_config());
+        monitor.track_peer("peer-b");
+        let pings = monitor.due_pings(NetworkType::WiFi);
+        let (_, nonce) = pings[0].clone();
+
+        assert!(monitor.record_pong("peer-b", nonce + 1).is_none());
+        assert!(monitor.record_pong("peer-b", nonce).is_some());
+        assert!(monitor.evaluate(NetworkType::WiFi).is_empty());
+        assert_eq!(monitor.state_of("peer-b"), Some(PeerLiveness::Alive));
+    }
+
+    #[test]
+    fn test_restart_forgives_misses_during_sleep() {
+        let mut monitor = KeepaliveMonitor::new(fast_config());
+        monitor.track_peer("peer-c");
+        for _ in 0..2 {
+            monitor.peers.get_mut("peer-c").unwrap().last_ping = None;
+            monitor.due_pings(NetworkType::WiFi);
+        }
+        assert_eq!(monitor.evaluate(NetworkType::WiFi)[0].to, PeerLiveness::Suspect);
+
+        monitor.restart();
+        let changes = monitor.evaluate(NetworkType::WiFi);
+        assert_eq!(changes[0].to, PeerLiveness::Alive);
+        assert_eq!(monitor.due_pings(NetworkType::WiFi).len(), 1);
+    }
+}
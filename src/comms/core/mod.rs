@@ -6,7 +6,17 @@
 pub mod config;
 pub mod handle;
 pub mod routing;
+pub mod keepalive;
+pub mod auth;
+pub mod admission;
+pub mod signing;
 
 // 重新导出常用类型
 pub use config::{CommsConfig, BandwidthBudgetConfig};
-pub use handle::{CommsHandle, IrohEvent, Topic};
+pub use handle::{CommsHandle, CommsStats, IrohEvent, Topic};
+pub use keepalive::{KeepaliveConfig, KeepaliveTunables, KeepaliveMonitor, PeerLiveness, PeerLivenessInfo};
+pub use auth::{AuthConfig, AuthRejection, AuthStats, AuthenticatedMessage, MessageAuthenticator};
+pub use admission::{
+    AdmissionConfig, AdmissionController, AdmissionError, AdmissionLevel, AttestationSource, StakeAttestation,
+};
+pub use signing::{IncomingSignRequest, RemoteSigningRelay, SignRequest, SigningMessage};
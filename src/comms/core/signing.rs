@@ -0,0 +1,204 @@
+//! 远程签名中继
+//!
+//! 节点进程不持有私钥时，待签名的交易消息经 P2P 发给审批设备（如手机钱包），
+//! 审批设备展示请求、用户确认后返回签名。请求和响应随签名 Gossip 传输，
+//! 只接受请求目标设备发回的响应；签名是否与公钥匹配由调用方校验。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// 发给审批设备的签名请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignRequest {
+    pub request_id: u64,
+    /// 签名公钥（base58）
+    pub signer: String,
+    /// 待签名的消息
+    pub message: Vec<u8>,
+    /// 供审批界面展示的说明
+    pub summary: String,
+    /// 过期时间戳（秒）
+    pub expires_at: i64,
+}
+
+/// 中继消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SigningMessage {
+    Request(SignRequest),
+    /// 审批结果，拒绝时 `signature` 为 None
+    Response { request_id: u64, signature: Option<Vec<u8>> },
+}
+
+struct PendingRequest {
+    approver: String,
+    reply: oneshot::Sender<Option<Vec<u8>>>,
+}
+
+/// 收到的待审批请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncomingSignRequest {
+    /// 请求方节点 ID
+    pub requester: String,
+    pub request: SignRequest,
+}
+
+/// 远程签名中继：请求方等待审批结果，审批方暂存收到的请求等待用户处理
+#[derive(Default)]
+pub struct RemoteSigningRelay {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, PendingRequest>>,
+    incoming: Mutex<Vec<IncomingSignRequest>>,
+    /// 待发送的消息：(目标节点 ID, 消息)
+    outbound: Mutex<Vec<(String, SigningMessage)>>,
+}
+
+impl RemoteSigningRelay {
+    /// 请求审批设备签名，等待用户确认直到超时
+    pub async fn request(
+        &self,
+        approver: &str,
+        signer: &str,
+        message: Vec<u8>,
+        summary: String,
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let request_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (reply, response) = oneshot::channel();
+        self.pending.lock().insert(
+            request_id,
+            PendingRequest {
+                approver: approver.to_string(),
+                reply,
+            },
+        );
+        let request = SignRequest {
+            request_id,
+            signer: signer.to_string(),
+            message,
+            summary,
+            expires_at: chrono::Utc::now().timestamp() + timeout.as_secs() as i64,
+        };
+        self.outbound.lock().push((approver.to_string(), SigningMessage::Request(request)));
+
+        let result = tokio::time::timeout(timeout, response).await;
+        self.pending.lock().remove(&request_id);
+        match result {
+            Ok(Ok(Some(signature))) => Ok(signature),
+            Ok(Ok(None)) => Err(anyhow!("审批设备 {} 拒绝了签名请求", approver)),
+            Ok(Err(_)) => Err(anyhow!("签名请求已取消")),
+            Err(_) => Err(anyhow!("等待审批设备 {} 签名超时", approver)),
+        }
+    }
+
+    /// 处理发给本节点的中继消息
+    pub fn handle(&self, sender: &str, message: SigningMessage) {
+        match message {
+            SigningMessage::Request(request) => {
+                if request.expires_at <= chrono::Utc::now().timestamp() {
+                    return;
+                }
+                let mut incoming = self.incoming.lock();
+                if !incoming
+                    .iter()
+                    .any(|r| r.requester == sender && r.request.request_id == request.request_id)
+                {
+                    incoming.push(IncomingSignRequest {
+                        requester: sender.to_string(),
+                        request,
+                    });
+                }
+            }
+            SigningMessage::Response { request_id, signature } => {
+                let mut pending = self.pending.lock();
+                // 只接受请求目标发回的响应
+                if pending.get(&request_id).is_some_and(|p| p.approver == sender) {
+                    if let Some(request) = pending.remove(&request_id) {
+                        let _ = request.reply.send(signature);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 未过期的待审批请求
+    pub fn pending_approvals(&self) -> Vec<IncomingSignRequest> {
+        let now = chrono::Utc::now().timestamp();
+        let mut incoming = self.incoming.lock();
+        incoming.retain(|r| r.request.expires_at > now);
+        incoming.clone()
+    }
+
+    /// 审批方回复请求，`signature` 为 None 表示拒绝
+    pub fn respond(&self, requester: &str, request_id: u64, signature: Option<Vec<u8>>) -> Result<()> {
+        let mut incoming = self.incoming.lock();
+        let index = incoming
+            .iter()
+            .position(|r| r.requester == requester && r.request.request_id == request_id)
+            .ok_or_else(|| anyhow!("没有来自 {} 的签名请求 {}", requester, request_id))?;
+        incoming.remove(index);
+        self.outbound
+            .lock()
+            .push((requester.to_string(), SigningMessage::Response { request_id, signature }));
+        Ok(())
+    }
+
+    /// 取出待发送的消息
+    pub fn take_outbound(&self) -> Vec<(String, SigningMessage)> {
+        std::mem::take(&mut *self.outbound.lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_request_resolves_only_from_approver() {
+        let requester = Arc::new(RemoteSigningRelay::default());
+        let approver = RemoteSigningRelay::default();
+
+        let waiting = {
+            let requester = Arc::clone(&requester);
+            tokio::spawn(async move {
+                requester
+                    .request("phone", "payer", b"tx".to_vec(), "claim".to_string(), Duration::from_secs(5))
+                    .await
+            })
+        };
+        let request = loop {
+            if let Some((target, message)) = requester.take_outbound().pop() {
+                assert_eq!(target, "phone");
+                break message;
+            }
+            tokio::task::yield_now().await;
+        };
+        let SigningMessage::Request(sent) = &request else {
+            panic!("应为签名请求");
+        };
+
+        // 非审批设备的响应被忽略
+        requester.handle(
+            "attacker",
+            SigningMessage::Response {
+                request_id: sent.request_id,
+                signature: Some(vec![0; 64]),
+            },
+        );
+
+        approver.handle("node", request.clone());
+        assert_eq!(approver.pending_approvals().len(), 1);
+        approver.respond("node", sent.request_id, Some(vec![7; 64])).unwrap();
+        let (_, response) = approver.take_outbound().pop().unwrap();
+        requester.handle("phone", response);
+
+        assert_eq!(waiting.await.unwrap().unwrap(), vec![7; 64]);
+        assert!(approver.pending_approvals().is_empty());
+    }
+}
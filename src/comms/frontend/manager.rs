@@ -118,10 +118,18 @@ impl P2PFrontendManager {
     }
 
     /// 生成节点 ID
+    ///
+    /// 优先使用持久身份的公钥，身份加载失败时退回随机 ID。
     fn generate_node_id() -> String {
-        use uuid::Uuid;
-        let uuid = Uuid::new_v4();
-        format!("12D3KooW{}", uuid.to_string().replace("-", "")[..32].to_uppercase())
+        match crate::crypto::NodeIdentity::load_or_create(None) {
+            Ok(identity) => identity.node_id(),
+            Err(e) => {
+                warn!("加载节点身份失败，使用临时 ID: {}", e);
+                use uuid::Uuid;
+                let uuid = Uuid::new_v4();
+                format!("12D3KooW{}", uuid.to_string().replace("-", "")[..32].to_uppercase())
+            }
+        }
     }
 
     /// 获取本地节点 ID
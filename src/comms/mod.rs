@@ -19,8 +19,13 @@ pub mod integration;
 
 // 重新导出常用类型
 pub use core::{CommsConfig, BandwidthBudgetConfig, CommsHandle, IrohEvent, Topic};
+pub use core::{KeepaliveConfig, PeerLiveness, PeerLivenessInfo};
+pub use core::{AuthConfig, AuthStats, AuthenticatedMessage, MessageAuthenticator};
+pub use core::{AdmissionConfig, AdmissionController, AdmissionError, AdmissionLevel, StakeAttestation};
+pub use core::{IncomingSignRequest, RemoteSigningRelay, SignRequest, SigningMessage};
 pub use p2p::{P2PModelDistributor, TransferEvent, EventManager, get_global_event_manager};
 pub use transport::{IrohConnectionManager, IrohConnectionConfig, ConnectionStats, WrappedMessage};
+pub use transport::{HandshakeStats, ResumptionConfig};
 pub use monitoring::MonitoringDashboard;
 pub use frontend::{P2PFrontendManager, P2PFrontendStarter};
 pub use integration::{P2PAppIntegration, P2PAppFactory, P2PEnabledApp};
@@ -11,6 +11,7 @@ use tokio::sync::{mpsc, RwLock};
 use chrono::{DateTime, Utc};
 use tracing::{info, warn, error};
 
+use crate::comms::core::keepalive::{PeerLiveness, PeerLivenessInfo};
 use crate::comms::p2p::{TransferEvent, P2PModelDistributor, get_global_receiver};
 
 /// 监控统计数据
@@ -81,6 +82,12 @@ pub struct PeerInfo {
     pub connection_time: Option<DateTime<Utc>>,
     pub total_transfers: u64,
     pub last_activity: Option<DateTime<Utc>>,
+    /// 应用层保活判定的存活状态
+    #[serde(default)]
+    pub liveness: PeerLiveness,
+    /// 最近一次保活往返时延（毫秒）
+    #[serde(default)]
+    pub last_rtt_ms: Option<u64>,
 }
 
 /// 监控仪表板
@@ -165,6 +172,8 @@ impl MonitoringDashboard {
                                 connection_time: Some(Utc::now()),
                                 total_transfers: 0,
                                 last_activity: Some(Utc::now()),
+                                liveness: PeerLiveness::Alive,
+                                last_rtt_ms: None,
                             });
                             peer.total_transfers += 1;
                             peer.last_activity = Some(Utc::now());
@@ -259,6 +268,8 @@ impl MonitoringDashboard {
                                 connection_time: if connected { Some(Utc::now()) } else { None },
                                 total_transfers: 0,
                                 last_activity: Some(Utc::now()),
+                                liveness: PeerLiveness::Alive,
+                                last_rtt_ms: None,
                             });
                             peer.connected = connected;
                             peer.last_activity = Some(Utc::now());
@@ -310,6 +321,34 @@ impl MonitoringDashboard {
         peer_info.values().cloned().collect()
     }
     
+    /// 用保活监视器的结果更新节点存活状态
+    pub async fn apply_peer_liveness(&self, liveness: &[PeerLivenessInfo]) {
+        let mut peer_info = self.peer_info.write().await;
+        for info in liveness {
+            let peer = peer_info.entry(info.peer_id.clone()).or_insert_with(|| PeerInfo {
+                peer_id: info.peer_id.clone(),
+                address: "unknown".to_string(),
+                connected: true,
+                connection_time: Some(Utc::now()),
+                total_transfers: 0,
+                last_activity: None,
+                liveness: info.state,
+                last_rtt_ms: None,
+            });
+            peer.liveness = info.state;
+            peer.last_rtt_ms = info.last_rtt_ms;
+            if info.state == PeerLiveness::Dead {
+                peer.connected = false;
+                peer.connection_time = None;
+            }
+        }
+        drop(peer_info);
+
+        let mut stats = self.stats.write().await;
+        let peer_info = self.peer_info.read().await;
+        stats.active_connections = peer_info.values().filter(|p| p.connected).count();
+    }
+
     /// 获取活跃传输列表
     pub async fn get_active_transfers(&self) -> Vec<TransferHistory> {
         let history = self.transfer_history.read().await;
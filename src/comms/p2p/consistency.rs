@@ -0,0 +1,377 @@
+/**
+ * 分片一致性检查模块
+ * 节点错过模型提交后可能继续用旧版本的层提供推理。
+ * 后台定期把本地分片哈希与最新签名的模型版本清单比对，过期分片被标记后拒绝用于推理，
+ * 并自动拉取增量（或完整分片）刷新，校验通过后恢复服务。
+ */
+
+use anyhow::{anyhow, Result};
+use model_splitter::{apply_block_delta, SignedModelManifest};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// 一致性检查配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyConfig {
+    /// 检查间隔（秒）
+    pub check_interval_secs: u64,
+    /// 有增量时优先下载增量
+    pub prefer_delta: bool,
+}
+
+impl Default for ConsistencyConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 300,
+            prefer_delta: true,
+        }
+    }
+}
+
+/// 过期分片的刷新来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RefreshSource {
+    /// 相对本地版本的增量文件
+    Delta { delta_file: String, delta_blake3: String },
+    /// 完整分片文件
+    Full { shard_file: String },
+    /// 最新版本已不包含该分片
+    Retired,
+}
+
+/// 过期分片
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleShard {
+    pub shard_id: String,
+    pub local_blake3: String,
+    pub expected_blake3: Option<String>,
+    pub source: RefreshSource,
+}
+
+/// 一轮检查的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    /// 比对所用的清单版本
+    pub manifest_version: Option<u64>,
+    pub checked: usize,
+    pub stale: Vec<StaleShard>,
+    /// 已刷新的分片
+    pub refreshed: Vec<String>,
+    /// 刷新失败的分片及原因
+    pub failed: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct LocalShard {
+    path: PathBuf,
+    blake3: String,
+    stale: Option<StaleShard>,
+}
+
+/// 分片一致性检查器
+pub struct ShardConsistencyChecker {
+    config: ConsistencyConfig,
+    /// 受信任的清单签名者公钥（hex），为空时接受任意有效签名
+    trusted_signers: Vec<String>,
+    manifest: RwLock<Option<SignedModelManifest>>,
+    local: RwLock<HashMap<String, LocalShard>>,
+}
+
+impl ShardConsistencyChecker {
+    pub fn new(config: ConsistencyConfig, trusted_signers: Vec<String>) -> Self {
+        Self {
+            config,
+            trusted_signers,
+            manifest: RwLock::new(None),
+            local: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ConsistencyConfig {
+        &self.config
+    }
+
+    /// 登记本节点托管的分片
+    pub async fn register_local_shard(&self, shard_id: &str, path: PathBuf) -> Result<()> {
+        let blake3 = model_splitter::manifest::blake3_file(&path)?;
+        self.local.write().await.insert(
+            shard_id.to_string(),
+            LocalShard {
+                path,
+                blake3,
+                stale: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// 接收新的模型版本清单，签名无效或版本回退时拒绝；返回是否更新了清单
+    pub async fn update_manifest(&self, signed: SignedModelManifest) -> Result<bool> {
+        signed.verify_signature(&self.trusted_signers)?;
+        let mut current = self.manifest.write().await;
+        if let Some(existing) = current.as_ref() {
+            if existing.manifest.model_name != signed.manifest.model_name {
+                return Err(anyhow!(
+                    "清单模型不匹配: {} != {}",
+                    signed.manifest.model_name,
+                    existing.manifest.model_name
+                ));
+            }
+            if signed.manifest.version <= existing.manifest.version {
+                return Ok(false);
+            }
+        }
+        info!("收到模型版本清单 {} v{}", signed.manifest.model_name, signed.manifest.version);
+        *current = Some(signed);
+        Ok(true)
+    }
+
+    /// 当前清单版本
+    pub async fn manifest_version(&self) -> Option<u64> {
+        self.manifest.read().await.as_ref().map(|m| m.manifest.version)
+    }
+
+    /// 推理前检查：过期分片拒绝服务
+    pub async fn ensure_servable(&self, shard_id: &str) -> Result<()> {
+        let local = self.local.read().await;
+        let shard = local
+            .get(shard_id)
+            .ok_or_else(|| anyhow!("本地未托管分片: {}", shard_id))?;
+        match &shard.stale {
+            Some(stale) => Err(anyhow!(
+                "分片 {} 已过期（本地 {}，最新 {}），刷新前拒绝推理",
+                shard_id,
+                short(&stale.local_blake3),
+                stale.expected_blake3.as_deref().map(short).unwrap_or("已移除")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// 当前过期的分片
+    pub async fn stale_shards(&self) -> Vec<StaleShard> {
+        let mut stale: Vec<StaleShard> = self
+            .local
+            .read()
+            .await
+            .values()
+            .filter_map(|shard| shard.stale.clone())
+            .collect();
+        stale.sort_by(|a, b| a.shard_id.cmp(&b.shard_id));
+        stale
+    }
+
+    /// 比对本地分片与最新清单，标记过期分片
+    pub async fn check(&self) -> Result<ConsistencyReport> {
+        let manifest = self.manifest.read().await.clone();
+        let Some(manifest) = manifest else {
+            return Ok(ConsistencyReport::default());
+        };
+        let manifest = manifest.manifest;
+
+        let mut local = self.local.write().await;
+        let mut report = ConsistencyReport {
+            manifest_version: Some(manifest.version),
+            checked: local.len(),
+            ..Default::default()
+        };
+        for (shard_id, shard) in local.iter_mut() {
+            // 重新计算哈希，发现磁盘上被替换或损坏的分片
+            shard.blake3 = model_splitter::manifest::blake3_file(&shard.path)?;
+            let expected = manifest.shard(shard_id);
+            if expected.is_some_and(|e| e.blake3 == shard.blake3) {
+                shard.stale = None;
+                continue;
+            }
+
+            let source = match expected {
+                None => RefreshSource::Retired,
+                Some(expected) => match manifest.delta_from(shard_id, &shard.blake3) {
+                    Some(delta) if self.config.prefer_delta => RefreshSource::Delta {
+                        delta_file: delta.delta_file.clone(),
+                        delta_blake3: delta.delta_blake3.clone(),
+                    },
+                    _ => RefreshSource::Full {
+                        shard_file: expected.shard_file.clone(),
+                    },
+                },
+            };
+            let stale = StaleShard {
+                shard_id: shard_id.clone(),
+                local_blake3: shard.blake3.clone(),
+                expected_blake3: expected.map(|e| e.blake3.clone()),
+                source,
+            };
+            if shard.stale.is_none() {
+                warn!("分片 {} 已过期（清单 v{}），暂停用于推理", shard_id, manifest.version);
+            }
+            shard.stale = Some(stale.clone());
+            report.stale.push(stale);
+        }
+        report.stale.sort_by(|a, b| a.shard_id.cmp(&b.shard_id));
+        Ok(report)
+    }
+
+    /// 检查并刷新过期分片
+    ///
+    /// `fetch` 按文件名从持有者或存储获取分片/增量文件。
+    pub async fn run_once<F, Fut>(&self, fetch: F) -> Result<ConsistencyReport>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<Vec<u8>>>,
+    {
+        let mut report = self.check().await?;
+        for stale in report.stale.clone() {
+            match self.refresh(&stale, &fetch).await {
+                Ok(true) => report.refreshed.push(stale.shard_id.clone()),
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("分片 {} 刷新失败: {}", stale.shard_id, e);
+                    report.failed.push((stale.shard_id.clone(), e.to_string()));
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn refresh<F, Fut>(&self, stale: &StaleShard, fetch: &F) -> Result<bool>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<Vec<u8>>>,
+    {
+        let Some(expected) = stale.expected_blake3.clone() else {
+            return Ok(false);
+        };
+        let path = self
+            .local
+            .read()
+            .await
+            .get(&stale.shard_id)
+            .map(|shard| shard.path.clone())
+            .ok_or_else(|| anyhow!("本地未托管分片: {}", stale.shard_id))?;
+
+        let data = match &stale.source {
+            RefreshSource::Delta { delta_file, delta_blake3 } => {
+                let delta = fetch(delta_file.clone()).await?;
+                check_hash(delta_file, &delta, delta_blake3)?;
+                apply_block_delta(&std::fs::read(&path)?, &delta)?
+            }
+            RefreshSource::Full { shard_file } => fetch(shard_file.clone()).await?,
+            RefreshSource::Retired => return Ok(false),
+        };
+        check_hash(&stale.shard_id, &data, &expected)?;
+
+        // 先写临时文件再重命名，刷新过程中断不会留下半个分片
+        let tmp = path.with_extension("refresh.tmp");
+        std::fs::write(&tmp, &data)?;
+        std::fs::rename(&tmp, &path)?;
+
+        if let Some(shard) = self.local.write().await.get_mut(&stale.shard_id) {
+            shard.blake3 = expected;
+            shard.stale = None;
+        }
+        info!("分片 {} 已刷新到最新版本", stale.shard_id);
+        Ok(true)
+    }
+
+    /// 启动后台一致性检查
+    pub fn spawn<F, Fut>(self: Arc<Self>, fetch: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<u8>>> + Send + 'static,
+    {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(self.config.check_interval_secs.max(10)));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.run_once(&fetch).await {
+                    warn!("分片一致性检查失败: {}", e);
+                }
+            }
+        })
+    }
+}
+
+fn check_hash(name: &str, data: &[u8], expected: &str) -> Result<()> {
+    let actual = blake3::hash(data).to_hex().to_string();
+    if actual != expected {
+        return Err(anyhow!("{} 哈希不匹配: 期望 {}, 实际 {}", name, short(expected), short(&actual)));
+    }
+    Ok(())
+}
+
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use model_splitter::{compute_block_delta, ModelManifest, ShardDeltaRef, ShardVersion};
+
+    fn hash(data: &[u8]) -> String {
+        blake3::hash(data).to_hex().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_stale_shard_refused_until_refreshed_from_delta() {
+        let dir = std::env::temp_dir().join(format!("williw-consistency-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shard_a.pth");
+        let v1 = vec![1u8; 200_000];
+        let mut v2 = v1.clone();
+        v2[150_000] = 9;
+        std::fs::write(&path, &v1).unwrap();
+
+        let checker = ShardConsistencyChecker::new(ConsistencyConfig::default(), Vec::new());
+        checker.register_local_shard("layers-0-3", path.clone()).await.unwrap();
+        assert!(checker.ensure_servable("layers-0-3").await.is_ok());
+
+        let delta = compute_block_delta(&v1, &v2);
+        let key = SigningKey::from_bytes(&[5u8; 32]);
+        let manifest = ModelManifest {
+            model_name: "test".to_string(),
+            version: 2,
+            parent_version: Some(1),
+            committed_at: 0,
+            shards: vec![ShardVersion {
+                shard_id: "layers-0-3".to_string(),
+                shard_file: "shard_a.pth".to_string(),
+                blake3: hash(&v2),
+                size_bytes: v2.len() as u64,
+            }],
+            deltas: vec![ShardDeltaRef {
+                shard_id: "layers-0-3".to_string(),
+                from_blake3: hash(&v1),
+                delta_file: "shard_a.v1-v2.delta".to_string(),
+                delta_blake3: hash(&delta),
+            }],
+        };
+        assert!(checker.update_manifest(manifest.sign(&key).unwrap()).await.unwrap());
+
+        let report = checker.check().await.unwrap();
+        assert!(matches!(report.stale[0].source, RefreshSource::Delta { .. }));
+        assert!(checker.ensure_servable("layers-0-3").await.is_err());
+
+        let files = Arc::new(HashMap::from([("shard_a.v1-v2.delta".to_string(), delta)]));
+        let report = checker
+            .run_once(|name| {
+                let files = files.clone();
+                async move { files.get(&name).cloned().ok_or_else(|| anyhow!("缺少文件 {}", name)) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.refreshed, vec!["layers-0-3".to_string()]);
+        assert_eq!(std::fs::read(&path).unwrap(), v2);
+        assert!(checker.ensure_servable("layers-0-3").await.is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,204 @@
+//! 基于内容分块的增量传输（rsync 风格）
+//!
+//! 模型更新后大部分分片字节不变。发送端用 gear 滚动哈希按内容切块（块边界只取决于附近的字节，
+//! 插入/删除不会让后续块全部错位），并以 blake3 哈希列出块清单；接收端查本地块缓存，
+//! 只请求缺失的块，其余直接从缓存拼装。
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// 内容分块参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkingParams {
+    pub min_size: usize,
+    /// 期望块大小，需为 2 的幂
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+impl Default for ChunkingParams {
+    fn default() -> Self {
+        Self {
+            min_size: 16 * 1024,
+            avg_size: 64 * 1024,
+            max_size: 256 * 1024,
+        }
+    }
+}
+
+/// 块清单中的一项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkSpec {
+    /// blake3 哈希（hex）
+    pub hash: String,
+    pub offset: u64,
+    pub len: u32,
+}
+
+/// gear 表：由 splitmix64 生成的固定伪随机数，双方必须一致
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+pub fn chunk_hash(data: &[u8]) -> String {
+    blake3::hash(data).to_hex().to_string()
+}
+
+/// 按内容切块
+pub fn chunk_data(data: &[u8], params: &ChunkingParams) -> Vec<ChunkSpec> {
+    let mask = (params.avg_size.max(2).next_power_of_two() - 1) as u64;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let remaining = data.len() - start;
+        let mut len = remaining.min(params.max_size.max(1));
+        if remaining > params.min_size {
+            let mut hash = 0u64;
+            for (i, byte) in data[start..start + len].iter().enumerate() {
+                hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+                if i + 1 >= params.min_size && hash & mask == 0 {
+                    len = i + 1;
+                    break;
+                }
+            }
+        }
+        let chunk = &data[start..start + len];
+        chunks.push(ChunkSpec {
+            hash: chunk_hash(chunk),
+            offset: start as u64,
+            len: len as u32,
+        });
+        start += len;
+    }
+    chunks
+}
+
+/// 本地块缓存：每个块按哈希存成一个文件
+pub struct ChunkCache {
+    dir: PathBuf,
+    params: ChunkingParams,
+}
+
+impl ChunkCache {
+    pub async fn open(dir: impl Into<PathBuf>, params: ChunkingParams) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).await?;
+        Ok(Self { dir, params })
+    }
+
+    pub fn params(&self) -> &ChunkingParams {
+        &self.params
+    }
+
+    fn path_of(&self, hash: &str) -> Result<PathBuf> {
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(anyhow!("无效的块哈希: {}", hash));
+        }
+        Ok(self.dir.join(hash))
+    }
+
+    pub async fn contains(&self, hash: &str) -> bool {
+        match self.path_of(hash) {
+            Ok(path) => fs::try_exists(path).await.unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    /// 读取块，内容与哈希不符（缓存损坏）时删除并返回 None
+    pub async fn get(&self, hash: &str) -> Option<Vec<u8>> {
+        let path = self.path_of(hash).ok()?;
+        let data = fs::read(&path).await.ok()?;
+        if chunk_hash(&data) != hash {
+            let _ = fs::remove_file(&path).await;
+            return None;
+        }
+        Some(data)
+    }
+
+    pub async fn put(&self, data: &[u8]) -> Result<String> {
+        let hash = chunk_hash(data);
+        let path = self.path_of(&hash)?;
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            // 先写临时文件再改名，避免并发读到半个块
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, data).await?;
+            fs::rename(&tmp, &path).await?;
+        }
+        Ok(hash)
+    }
+
+    /// 把已有文件（例如旧版本分片）切块后放入缓存，返回块清单
+    pub async fn index_file(&self, path: &Path) -> Result<Vec<ChunkSpec>> {
+        let data = fs::read(path).await?;
+        let chunks = chunk_data(&data, &self.params);
+        for chunk in &chunks {
+            let start = chunk.offset as usize;
+            self.put(&data[start..start + chunk.len as usize]).await?;
+        }
+        Ok(chunks)
+    }
+
+    /// 清单中本地缺失的块序号
+    pub async fn missing(&self, chunks: &[ChunkSpec]) -> Vec<u32> {
+        let mut missing = Vec::new();
+        let mut present = HashSet::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            if present.contains(&chunk.hash) {
+                continue;
+            }
+            if self.contains(&chunk.hash).await {
+                present.insert(chunk.hash.clone());
+            } else {
+                missing.push(index as u32);
+            }
+        }
+        missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundaries_survive_insertion() {
+        let params = ChunkingParams {
+            min_size: 256,
+            avg_size: 1024,
+            max_size: 4096,
+        };
+        let mut state = 1u64;
+        let original: Vec<u8> = (0..64 * 1024)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect();
+        let mut edited = original.clone();
+        edited.splice(1000..1000, b"inserted bytes".iter().copied());
+
+        let before = chunk_data(&original, &params);
+        let after = chunk_data(&edited, &params);
+        assert_eq!(before.iter().map(|c| c.len as usize).sum::<usize>(), original.len());
+        assert!(before.iter().all(|c| c.len as usize <= params.max_size));
+
+        // 插入点之后的块大部分能复用
+        let old: HashSet<&str> = before.iter().map(|c| c.hash.as_str()).collect();
+        let reused = after.iter().filter(|c| old.contains(c.hash.as_str())).count();
+        assert!(reused + 2 >= after.len(), "reused {reused} of {}", after.len());
+    }
+}
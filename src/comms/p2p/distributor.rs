@@ -8,10 +8,20 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
 use tokio::sync::{mpsc, RwLock};
 use tracing::{info, warn, error, debug};
+use model_splitter::SignedShardManifest;
+
+use super::delta::{chunk_data, chunk_hash, ChunkCache, ChunkSpec, ChunkingParams};
+use super::sender::{ChunkScheduler, TransferWindowConfig};
+use crate::comms::core::auth::{AuthenticatedMessage, MessageAuthenticator};
+use crate::comms::transport::codec::{decode_json, WireLimits};
+use crate::consensus::{ReputationEngine, ReputationEvent};
+use crate::device::{system_event_hub, SystemEvent};
+use crate::stats::{StatsStore, TransferDirection};
 
 /// 文件传输消息类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +65,39 @@ pub enum FileTransferMessage {
         file_id: String,
         error: String,
     },
+    /// 增量传输的块清单（紧随 FileRequest 发送）
+    ChunkManifest {
+        file_id: String,
+        chunks: Vec<ChunkSpec>,
+    },
+    /// 接收端本地缺失、需要传输的块序号
+    ChunkRequest {
+        file_id: String,
+        chunk_indices: Vec<u32>,
+    },
+    /// 增量块数据，按清单中的 blake3 哈希校验
+    DeltaChunk {
+        file_id: String,
+        chunk_index: u32,
+        data: Vec<u8>,
+    },
+    /// 逐块确认：接收端校验并落盘后回复
+    ChunkAck {
+        file_id: String,
+        chunk_index: u32,
+    },
+    /// 续传：接收端已有的块序号（紧随 FileResponse 发送）
+    TransferResume {
+        file_id: String,
+        received_chunks: Vec<u32>,
+    },
+}
+
+/// 分块发送过程中来自接收端的反馈
+#[derive(Debug, Clone)]
+enum ChunkFeedback {
+    Ack(u32),
+    Resume(Vec<u32>),
 }
 
 /// 文件传输状态
@@ -80,6 +123,12 @@ pub struct TransferSession {
     pub chunks_received: HashMap<u32, Vec<u8>>,
     pub file_hash: String,
     pub created_at: std::time::Instant,
+    /// 增量传输的块清单，为空时按固定大小分块传输
+    pub chunk_manifest: Vec<ChunkSpec>,
+    /// 从本地块缓存复用的字节数
+    pub bytes_reused: u64,
+    /// 实际从对端下载的字节数
+    pub bytes_downloaded: u64,
 }
 
 impl TransferSession {
@@ -97,7 +146,25 @@ impl TransferSession {
             chunks_received: HashMap::new(),
             file_hash,
             created_at: std::time::Instant::now(),
+            chunk_manifest: Vec::new(),
+            bytes_reused: 0,
+            bytes_downloaded: 0,
+        }
+    }
+
+    /// 切换为增量传输，块数以清单为准
+    pub fn set_chunk_manifest(&mut self, chunks: Vec<ChunkSpec>) {
+        self.total_chunks = chunks.len() as u32;
+        self.chunks_received.clear();
+        self.chunk_manifest = chunks;
+    }
+
+    /// 复用字节占文件大小的比例
+    pub fn savings_ratio(&self) -> f64 {
+        if self.file_size == 0 {
+            return 0.0;
         }
+        self.bytes_reused as f64 / self.file_size as f64
     }
 
     pub fn add_chunk(&mut self, chunk_index: u32, data: Vec<u8>) -> Result<()> {
@@ -134,6 +201,24 @@ pub struct P2PModelDistributor {
     active_transfers: Arc<RwLock<HashMap<String, TransferSession>>>,
     message_tx: mpsc::Sender<(String, FileTransferMessage)>,
     message_rx: mpsc::Receiver<(String, FileTransferMessage)>,
+    /// 预期分片的签名清单（按文件名索引）
+    shard_manifests: Arc<RwLock<HashMap<String, SignedShardManifest>>>,
+    /// 受信任的清单签名者公钥（hex），为空时接受任意有效签名
+    trusted_manifest_signers: Vec<String>,
+    /// 消息认证器，设置后所有传输消息都会签名并校验防重放
+    authenticator: Option<Arc<MessageAuthenticator>>,
+    /// 观察模式下不向其它节点提供文件/分片
+    watch_only: bool,
+    /// 传输会话写入本地统计数据库（未启用时为 None）
+    stats_store: Option<Arc<StatsStore>>,
+    /// 按传输结果更新对端信誉（未启用时为 None）
+    reputation: Option<Arc<ReputationEngine>>,
+    /// 增量传输的本地块缓存（未启用时为 None）
+    chunk_cache: Option<Arc<ChunkCache>>,
+    /// 等待对端请求缺失块的增量发送：file_id -> (文件路径, 块清单)
+    outbound_deltas: Arc<RwLock<HashMap<String, (PathBuf, Vec<ChunkSpec>)>>>,
+    /// 进行中的窗口化发送：file_id -> 确认反馈通道
+    chunk_feedback: Arc<RwLock<HashMap<String, mpsc::UnboundedSender<ChunkFeedback>>>>,
 }
 
 impl P2PModelDistributor {
@@ -145,14 +230,137 @@ impl P2PModelDistributor {
             active_transfers: Arc::new(RwLock::new(HashMap::new())),
             message_tx,
             message_rx,
+            shard_manifests: Arc::new(RwLock::new(HashMap::new())),
+            trusted_manifest_signers: Vec::new(),
+            authenticator: None,
+            watch_only: false,
+            stats_store: None,
+            reputation: None,
+            chunk_cache: None,
+            outbound_deltas: Arc::new(RwLock::new(HashMap::new())),
+            chunk_feedback: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 设置块缓存，启用增量传输
+    pub fn set_chunk_cache(&mut self, cache: Arc<ChunkCache>) {
+        self.chunk_cache = Some(cache);
+    }
+
+    /// 切换观察模式
+    pub fn set_watch_only(&mut self, watch_only: bool) {
+        self.watch_only = watch_only;
+    }
+
+    /// 设置本地统计数据库，记录收发会话
+    pub fn set_stats_store(&mut self, store: Arc<StatsStore>) {
+        self.stats_store = Some(store);
+    }
+
+    /// 设置信誉引擎，传输成功或失败计入对端信誉
+    pub fn set_reputation(&mut self, reputation: Arc<ReputationEngine>) {
+        self.reputation = Some(reputation);
+    }
+
+    fn record_transfer_started(&self, file_id: &str, peer: Option<&str>, direction: TransferDirection,
+                               file_name: &str, bytes: u64) {
+        if let Some(store) = &self.stats_store {
+            let now = chrono::Utc::now().timestamp();
+            if let Err(e) = store.record_transfer_started(file_id, peer, direction, file_name, bytes, now) {
+                warn!("记录传输会话失败: {}", e);
+            }
+        }
+    }
+
+    fn record_transfer_finished(&self, file_id: &str, peer: &str, result: &Result<()>) {
+        self.record_peer_transfer(peer, result.is_ok());
+        if let Some(store) = &self.stats_store {
+            let error = result.as_ref().err().map(|e| e.to_string());
+            let now = chrono::Utc::now().timestamp();
+            if let Err(e) = store.record_transfer_finished(file_id, now, error.as_deref()) {
+                warn!("记录传输会话失败: {}", e);
+            }
+        }
+    }
+
+    fn record_peer_transfer(&self, peer: &str, succeeded: bool) {
+        if let Some(reputation) = &self.reputation {
+            reputation.record(peer, ReputationEvent::Transfer { succeeded });
+        }
+    }
+
+    /// 设置消息认证器（通常与 CommsHandle 共用）
+    pub fn set_authenticator(&mut self, authenticator: Arc<MessageAuthenticator>) {
+        self.authenticator = Some(authenticator);
+    }
+
+    /// 编码待发送的传输消息
+    pub fn encode_message(&self, message: &FileTransferMessage) -> Result<Vec<u8>> {
+        match &self.authenticator {
+            Some(auth) => Ok(serde_json::to_vec(&auth.seal(message)?)?),
+            None => Ok(serde_json::to_vec(message)?),
         }
     }
 
+    /// 解码并认证收到的传输消息，发送者必须与传输层来源一致
+    pub fn open_message(&self, sender_id: &str, data: &[u8]) -> Result<FileTransferMessage> {
+        let limits = WireLimits::default();
+        let Some(auth) = &self.authenticator else {
+            return Ok(decode_json(data, &limits)?);
+        };
+        let message: AuthenticatedMessage<FileTransferMessage> = match decode_json(data, &limits) {
+            Ok(message) => message,
+            Err(e) => {
+                auth.record_malformed();
+                return Err(anyhow!("无法解析传输消息: {}", e));
+            }
+        };
+        auth.open(message, Some(sender_id))
+            .map_err(|e| anyhow!("传输消息认证失败: {}", e))
+    }
+
+    /// 设置受信任的清单签名者
+    pub fn set_trusted_manifest_signers(&mut self, signers: Vec<String>) {
+        self.trusted_manifest_signers = signers;
+    }
+
+    /// 登记即将接收的分片清单
+    ///
+    /// 清单签名在登记时即校验；分片组装完成后会再按清单校验文件哈希，
+    /// 不匹配的分片会被删除，不会进入加载流程。
+    pub async fn expect_shard_manifest(&self, manifest: SignedShardManifest) -> Result<()> {
+        manifest.verify_signature(&self.trusted_manifest_signers)?;
+        let file_name = manifest.manifest.shard_file.clone();
+        self.shard_manifests.write().await.insert(file_name, manifest);
+        Ok(())
+    }
+
+    /// 按清单校验已组装的分片
+    async fn verify_against_manifest(&self, session: &TransferSession) -> Result<()> {
+        let manifest = {
+            let manifests = self.shard_manifests.read().await;
+            manifests.get(&session.file_name).cloned()
+        };
+        let Some(manifest) = manifest else {
+            return Ok(());
+        };
+
+        let data = fs::read(&session.file_path).await?;
+        manifest.verify_signature(&self.trusted_manifest_signers)?;
+        manifest.verify_shard_bytes(&data)?;
+        info!("分片清单校验通过: {} ({} 个张量)",
+              session.file_name, manifest.manifest.tensors.len());
+        Ok(())
+    }
+
     /// 发送文件到指定节点
     pub async fn send_file(&mut self, 
                           peer_id: String, 
                           file_path: &Path,
                           chunk_size: Option<usize>) -> Result<String> {
+        if self.watch_only {
+            return Err(anyhow!("观察模式下不提供文件分片"));
+        }
         let file_path = file_path.to_path_buf();
         
         // 检查文件是否存在
@@ -195,7 +403,10 @@ impl P2PModelDistributor {
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
         // 开始发送文件块
-        self.send_file_chunks(&peer_id, &file_path, &file_id, chunk_size).await?;
+        self.record_transfer_started(&file_id, Some(&peer_id), TransferDirection::Outbound, &file_name, file_size);
+        let result = self.send_file_chunks(&peer_id, &file_path, &file_id, chunk_size).await;
+        self.record_transfer_finished(&file_id, &peer_id, &result);
+        result?;
 
         Ok(file_id)
     }
@@ -249,6 +460,194 @@ impl P2PModelDistributor {
         Ok(())
     }
 
+    /// 多流分块发送：按拥塞窗口控制在途块数，逐块确认、超时重传。
+    /// 同一对端、同名且内容相同的文件使用固定的传输 ID，中断后再次发送只补齐未确认的块
+    pub async fn send_file_windowed(&mut self,
+                                    peer_id: String,
+                                    file_path: &Path,
+                                    chunk_size: usize,
+                                    window: TransferWindowConfig) -> Result<String> {
+        if self.watch_only {
+            return Err(anyhow!("观察模式下不提供文件分片"));
+        }
+        if chunk_size == 0 {
+            return Err(anyhow!("块大小不能为 0"));
+        }
+        let metadata = fs::metadata(file_path).await
+            .map_err(|e| anyhow!("文件不存在: {} ({})", file_path.display(), e))?;
+        let file_size = metadata.len();
+        let file_name = file_path.file_name()
+            .ok_or_else(|| anyhow!("无效的文件名"))?
+            .to_string_lossy()
+            .to_string();
+        let file_hash = self.calculate_file_hash(file_path).await?;
+        let file_id = resumable_transfer_id(&peer_id, &file_name, &file_hash);
+        let total_chunks = file_size.div_ceil(chunk_size as u64) as u32;
+
+        // 本地记录的已确认块，对端的 TransferResume 会再补充
+        let acked = match &self.stats_store {
+            Some(store) => store.transfer_chunks(&file_id).unwrap_or_else(|e| {
+                warn!("读取续传记录失败: {}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        if !acked.is_empty() {
+            info!("续传文件: {} (已确认 {}/{} 块)", file_name, acked.len(), total_chunks);
+        }
+
+        let (feedback_tx, mut feedback_rx) = mpsc::unbounded_channel();
+        self.chunk_feedback.write().await.insert(file_id.clone(), feedback_tx);
+
+        let request = FileTransferMessage::FileRequest {
+            file_id: file_id.clone(),
+            file_name: file_name.clone(),
+            file_size,
+            chunk_size,
+            file_hash,
+        };
+        let result = match self.send_message(&peer_id, request).await {
+            Ok(()) => {
+                self.record_transfer_started(&file_id, Some(&peer_id), TransferDirection::Outbound, &file_name, file_size);
+                let max_stalled_rounds = window.max_stalled_rounds;
+                let mut scheduler = ChunkScheduler::new(total_chunks, &acked, window);
+                let result = self.run_windowed_transfer(&peer_id, file_path, &file_id, chunk_size,
+                                                        max_stalled_rounds, &mut scheduler, &mut feedback_rx).await;
+                info!("文件 {} 分块发送结束: 确认 {}/{} 块, 重传 {} 次, 窗口 {}",
+                      file_id, scheduler.acked(), total_chunks, scheduler.retransmits(), scheduler.window());
+                self.record_transfer_finished(&file_id, &peer_id, &result);
+                result
+            }
+            Err(e) => Err(e),
+        };
+        self.chunk_feedback.write().await.remove(&file_id);
+        result?;
+
+        Ok(file_id)
+    }
+
+    async fn run_windowed_transfer(&self,
+                                   peer_id: &str,
+                                   file_path: &Path,
+                                   file_id: &str,
+                                   chunk_size: usize,
+                                   max_stalled_rounds: u32,
+                                   scheduler: &mut ChunkScheduler,
+                                   feedback_rx: &mut mpsc::UnboundedReceiver<ChunkFeedback>) -> Result<()> {
+        let mut file = fs::File::open(file_path).await?;
+        let streams = scheduler.streams();
+        let mut stalled_rounds = 0u32;
+        let system_events = system_event_hub();
+        let mut system_rx = system_events.subscribe();
+
+        while !scheduler.is_complete() {
+            let batch = scheduler.poll(Instant::now());
+            if !batch.is_empty() {
+                // 按分配的流分组，各流并发发送
+                let mut per_stream: Vec<Vec<FileTransferMessage>> = vec![Vec::new(); streams];
+                for (chunk_index, stream) in batch {
+                    file.seek(SeekFrom::Start(chunk_index as u64 * chunk_size as u64)).await?;
+                    let mut data = Vec::with_capacity(chunk_size);
+                    (&mut file).take(chunk_size as u64).read_to_end(&mut data).await?;
+                    let chunk_hash = self.calculate_chunk_hash(&data);
+                    per_stream[stream.min(streams - 1)].push(FileTransferMessage::FileChunk {
+                        file_id: file_id.to_string(),
+                        chunk_index,
+                        data,
+                        chunk_hash,
+                    });
+                }
+                futures::future::try_join_all(per_stream.into_iter().map(|messages| async move {
+                    for message in messages {
+                        self.send_message(peer_id, message).await?;
+                    }
+                    Ok::<_, anyhow::Error>(())
+                })).await?;
+            }
+
+            let wait = scheduler.next_deadline()
+                .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+                .unwrap_or_else(|| scheduler.rto());
+            let feedback = tokio::select! {
+                feedback = tokio::time::timeout(wait, feedback_rx.recv()) => feedback,
+                // 睡眠期间暂停，唤醒或网络切换后在途块全部重发，不计为停滞
+                Ok(event) = system_rx.recv() => {
+                    if event == SystemEvent::Suspend {
+                        info!("系统即将睡眠，暂停向 {} 传输 {}", peer_id, file_id);
+                        system_events.wait_until_awake(&mut system_rx).await;
+                    }
+                    let requeued = scheduler.requeue_in_flight();
+                    stalled_rounds = 0;
+                    info!("{}，继续传输 {}，重发 {} 个在途块", event, file_id, requeued);
+                    continue;
+                }
+            };
+            match feedback {
+                Ok(Some(ChunkFeedback::Ack(chunk_index))) => {
+                    scheduler.on_ack(chunk_index, Instant::now());
+                    stalled_rounds = 0;
+                    if let Some(store) = &self.stats_store {
+                        if let Err(e) = store.record_chunk_done(file_id, chunk_index) {
+                            warn!("记录已确认块失败: {}", e);
+                        }
+                    }
+                }
+                Ok(Some(ChunkFeedback::Resume(received))) => {
+                    debug!("对端已有 {} 块，跳过", received.len());
+                    scheduler.mark_acked(&received);
+                }
+                Ok(None) => return Err(anyhow!("传输反馈通道已关闭")),
+                Err(_) => {
+                    let expired = scheduler.on_timeout(Instant::now());
+                    if expired > 0 {
+                        stalled_rounds += 1;
+                        debug!("{} 块确认超时，窗口降为 {}", expired, scheduler.window());
+                        if stalled_rounds > max_stalled_rounds {
+                            return Err(anyhow!("连续 {} 轮未收到确认，传输中止（可续传）", stalled_rounds));
+                        }
+                    }
+                }
+            }
+        }
+
+        let complete_message = FileTransferMessage::FileComplete {
+            file_id: file_id.to_string(),
+            total_chunks: scheduler.acked() as u32,
+            final_hash: String::new(),
+        };
+        self.send_message(peer_id, complete_message).await?;
+        Ok(())
+    }
+
+    /// 处理接收端的逐块确认或续传信息，转交给对应的发送任务
+    pub async fn handle_chunk_feedback(&self, message: FileTransferMessage) -> Result<()> {
+        let (file_id, feedback) = match message {
+            FileTransferMessage::ChunkAck { file_id, chunk_index } => (file_id, ChunkFeedback::Ack(chunk_index)),
+            FileTransferMessage::TransferResume { file_id, received_chunks } => {
+                (file_id, ChunkFeedback::Resume(received_chunks))
+            }
+            _ => return Err(anyhow!("不是分块反馈消息")),
+        };
+        let feedback_channels = self.chunk_feedback.read().await;
+        match feedback_channels.get(&file_id) {
+            Some(tx) => {
+                let _ = tx.send(feedback);
+            }
+            None => debug!("忽略未知传输的反馈: {}", file_id),
+        }
+        Ok(())
+    }
+
+    /// 接收会话中已有的块（续传时告知发送端）
+    pub async fn received_chunks(&self, file_id: &str) -> Vec<u32> {
+        let transfers = self.active_transfers.read().await;
+        let mut received: Vec<u32> = transfers.get(file_id)
+            .map(|session| session.chunks_received.keys().copied().collect())
+            .unwrap_or_default();
+        received.sort_unstable();
+        received
+    }
+
     /// 接收文件
     pub async fn receive_file(&mut self, 
                              output_dir: &Path,
@@ -264,8 +663,9 @@ impl P2PModelDistributor {
             info!("接收文件请求: {} (大小: {} bytes)", file_name, file_size);
 
             // 创建传输会话
+            self.record_transfer_started(&file_id, None, TransferDirection::Inbound, &file_name, file_size);
             let output_path = output_dir.join(&file_name);
-            let session = TransferSession::new(
+            let mut session = TransferSession::new(
                 file_id.clone(),
                 file_name,
                 output_path,
@@ -273,6 +673,10 @@ impl P2PModelDistributor {
                 chunk_size,
                 file_hash,
             );
+            if let Err(e) = self.load_partial_chunks(&mut session).await {
+                warn!("读取未完成的传输失败，重新接收: {}", e);
+                session.chunks_received.clear();
+            }
 
             {
                 let mut transfers = self.active_transfers.write().await;
@@ -295,6 +699,49 @@ impl P2PModelDistributor {
         }
     }
 
+    /// 从 `.part` 文件恢复上次中断时已落盘的块
+    async fn load_partial_chunks(&self, session: &mut TransferSession) -> Result<()> {
+        let Some(store) = &self.stats_store else {
+            return Ok(());
+        };
+        let chunks = store.transfer_chunks(&session.file_id)?;
+        let part_path = partial_path(&session.file_path);
+        if chunks.is_empty() || !fs::try_exists(&part_path).await.unwrap_or(false) {
+            return Ok(());
+        }
+        let mut file = fs::File::open(&part_path).await?;
+        for chunk_index in chunks.into_iter().filter(|index| *index < session.total_chunks) {
+            let offset = chunk_index as u64 * session.chunk_size as u64;
+            let len = (session.file_size - offset).min(session.chunk_size as u64);
+            let mut data = vec![0u8; len as usize];
+            file.seek(SeekFrom::Start(offset)).await?;
+            file.read_exact(&mut data).await?;
+            session.add_chunk(chunk_index, data)?;
+        }
+        info!("恢复未完成的传输: {} (已有 {}/{} 块)",
+              session.file_name, session.chunks_received.len(), session.total_chunks);
+        Ok(())
+    }
+
+    /// 把块写入 `.part` 文件并记录，重启后可续传
+    async fn persist_chunk(&self, file_id: &str, file_path: &Path, chunk_index: u32, offset: u64,
+                           data: &[u8]) -> Result<()> {
+        let Some(store) = &self.stats_store else {
+            return Ok(());
+        };
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(partial_path(file_path))
+            .await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        file.write_all(data).await?;
+        file.flush().await?;
+        store.record_chunk_done(file_id, chunk_index)?;
+        Ok(())
+    }
+
     /// 处理接收到的文件块
     pub async fn handle_file_chunk(&mut self, 
                                    sender_id: String,
@@ -310,34 +757,70 @@ impl P2PModelDistributor {
             let calculated_hash = self.calculate_chunk_hash(&data);
             if calculated_hash != chunk_hash {
                 error!("块哈希验证失败: 文件 {} 块 {}", file_id, chunk_index);
+                self.record_peer_transfer(&sender_id, false);
                 return Err(anyhow!("块哈希验证失败"));
             }
 
+            // 启用统计数据库时块同时落盘，供续传使用
+            let spill = self.stats_store.as_ref().map(|_| data.clone());
+
             // 添加到传输会话
-            let should_assemble = {
+            let accepted = {
                 let mut transfers = self.active_transfers.write().await;
                 if let Some(session) = transfers.get_mut(&file_id) {
-                    session.add_chunk(chunk_index, data)?;
-                    
-                    let progress = session.get_progress();
-                    debug!("文件 {} 进度: {:.1}%", file_id, progress);
-
-                    // 检查是否完成
-                    if session.is_complete() {
-                        info!("文件 {} 接收完成，开始组装文件", file_id);
-                        true
+                    if matches!(session.status, TransferStatus::Completed) {
+                        // 确认丢失导致的重传，只需再次确认
+                        Some((false, None))
                     } else {
-                        false
+                        session.bytes_downloaded += data.len() as u64;
+                        session.add_chunk(chunk_index, data)?;
+
+                        let progress = session.get_progress();
+                        debug!("文件 {} 进度: {:.1}%", file_id, progress);
+
+                        // 检查是否完成
+                        let complete = session.is_complete();
+                        if complete {
+                            info!("文件 {} 接收完成，开始组装文件", file_id);
+                        }
+                        let offset = chunk_index as u64 * session.chunk_size as u64;
+                        Some((complete, Some((session.file_path.clone(), offset))))
                     }
                 } else {
                     warn!("未找到文件传输会话: {}", file_id);
-                    false
+                    None
                 }
             };
+            let Some((should_assemble, placement)) = accepted else {
+                return Ok(());
+            };
+
+            if let (Some(data), Some((file_path, offset))) = (spill, &placement) {
+                if let Err(e) = self.persist_chunk(&file_id, file_path, chunk_index, *offset, &data).await {
+                    warn!("块落盘失败: {}", e);
+                }
+            }
+
+            // 逐块确认，发送端据此调整窗口
+            self.send_message(&sender_id, FileTransferMessage::ChunkAck {
+                file_id: file_id.clone(),
+                chunk_index,
+            }).await?;
 
             // 在锁外组装文件
-            if should_assemble {
-                self.assemble_file(&file_id).await?;
+            if let (true, Some((file_path, _))) = (should_assemble, &placement) {
+                let result = self.assemble_file(&file_id).await;
+                if self.stats_store.is_some() {
+                    let _ = fs::remove_file(partial_path(file_path)).await;
+                    if result.is_err() {
+                        // 校验失败时已落盘的块不可信，下次整体重传
+                        if let Some(store) = &self.stats_store {
+                            let _ = store.clear_transfer_chunks(&file_id);
+                        }
+                    }
+                }
+                self.record_transfer_finished(&file_id, &sender_id, &result);
+                result?;
             }
         }
 
@@ -376,9 +859,31 @@ impl P2PModelDistributor {
                 return Err(anyhow!("文件哈希验证失败"));
             }
 
+            // 按签名清单校验分片，拒绝损坏或被篡改的分片
+            if let Err(e) = self.verify_against_manifest(&session).await {
+                error!("分片清单校验失败: {} - {}", session.file_name, e);
+                let _ = fs::remove_file(&session.file_path).await;
+                let mut transfers = self.active_transfers.write().await;
+                if let Some(session) = transfers.get_mut(file_id) {
+                    session.status = TransferStatus::Failed(format!("清单校验失败: {}", e));
+                }
+                return Err(anyhow!("分片清单校验失败: {}", e));
+            }
+
             info!("文件组装完成: {} (大小: {} bytes)", 
                   session.file_name, session.file_size);
 
+            // 新版本的块放入缓存，下次更新时复用
+            if let (Some(cache), false) = (&self.chunk_cache, session.chunk_manifest.is_empty()) {
+                for data in session.chunks_received.values() {
+                    if let Err(e) = cache.put(data).await {
+                        warn!("写入块缓存失败: {}", e);
+                    }
+                }
+                info!("增量传输: 复用 {} bytes, 下载 {} bytes ({:.1}% 节省)",
+                      session.bytes_reused, session.bytes_downloaded, session.savings_ratio() * 100.0);
+            }
+
             // 更新状态
             {
                 let mut transfers = self.active_transfers.write().await;
@@ -391,10 +896,187 @@ impl P2PModelDistributor {
         Ok(())
     }
 
+    /// 增量发送文件：先发送块清单，对端回复缺失块后再传输
+    pub async fn send_file_delta(&mut self, peer_id: String, file_path: &Path) -> Result<String> {
+        if self.watch_only {
+            return Err(anyhow!("观察模式下不提供文件分片"));
+        }
+        let data = fs::read(file_path).await?;
+        let file_name = file_path.file_name()
+            .ok_or_else(|| anyhow!("无效的文件名"))?
+            .to_string_lossy()
+            .to_string();
+        let params = self.chunk_cache.as_ref().map(|cache| *cache.params()).unwrap_or_default();
+        let chunks = chunk_data(&data, &params);
+        let file_id = uuid::Uuid::new_v4().to_string();
+
+        info!("开始增量发送文件: {} ({} 块)", file_name, chunks.len());
+        let request = FileTransferMessage::FileRequest {
+            file_id: file_id.clone(),
+            file_name: file_name.clone(),
+            file_size: data.len() as u64,
+            chunk_size: params.avg_size,
+            file_hash: self.calculate_file_hash(file_path).await?,
+        };
+        self.send_message(&peer_id, request).await?;
+        let manifest = FileTransferMessage::ChunkManifest {
+            file_id: file_id.clone(),
+            chunks: chunks.clone(),
+        };
+        self.send_message(&peer_id, manifest).await?;
+
+        self.record_transfer_started(&file_id, Some(&peer_id), TransferDirection::Outbound, &file_name, data.len() as u64);
+        self.outbound_deltas.write().await.insert(file_id.clone(), (file_path.to_path_buf(), chunks));
+        Ok(file_id)
+    }
+
+    /// 发送端：按对端请求发送缺失块
+    pub async fn handle_chunk_request(&mut self, peer_id: String, message: FileTransferMessage) -> Result<()> {
+        let FileTransferMessage::ChunkRequest { file_id, chunk_indices } = message else {
+            return Err(anyhow!("无效的块请求消息"));
+        };
+        let Some((file_path, chunks)) = self.outbound_deltas.write().await.remove(&file_id) else {
+            return Err(anyhow!("未找到增量发送会话: {}", file_id));
+        };
+
+        let result = self.send_delta_chunks(&peer_id, &file_id, &file_path, &chunks, &chunk_indices).await;
+        self.record_transfer_finished(&file_id, &peer_id, &result);
+        result
+    }
+
+    async fn send_delta_chunks(&mut self, peer_id: &str, file_id: &str, file_path: &Path,
+                               chunks: &[ChunkSpec], chunk_indices: &[u32]) -> Result<()> {
+        let data = fs::read(file_path).await?;
+        let mut sent_bytes = 0u64;
+        for &chunk_index in chunk_indices {
+            let chunk = chunks.get(chunk_index as usize)
+                .ok_or_else(|| anyhow!("块索引超出范围: {}", chunk_index))?;
+            let start = chunk.offset as usize;
+            let bytes = data.get(start..start + chunk.len as usize)
+                .ok_or_else(|| anyhow!("文件在传输期间被修改: {}", file_path.display()))?;
+            if chunk_hash(bytes) != chunk.hash {
+                return Err(anyhow!("文件在传输期间被修改: {}", file_path.display()));
+            }
+            sent_bytes += bytes.len() as u64;
+            let message = FileTransferMessage::DeltaChunk {
+                file_id: file_id.to_string(),
+                chunk_index,
+                data: bytes.to_vec(),
+            };
+            self.send_message(peer_id, message).await?;
+        }
+
+        let complete_message = FileTransferMessage::FileComplete {
+            file_id: file_id.to_string(),
+            total_chunks: chunks.len() as u32,
+            final_hash: String::new(),
+        };
+        self.send_message(peer_id, complete_message).await?;
+        info!("增量传输完成: {} (发送 {}/{} 块, {} bytes)",
+              file_id, chunk_indices.len(), chunks.len(), sent_bytes);
+        Ok(())
+    }
+
+    /// 接收端：处理块清单，从缓存填充已有的块并向发送端请求缺失块
+    pub async fn handle_chunk_manifest(&mut self, sender_id: String, message: FileTransferMessage) -> Result<FileTransferMessage> {
+        let FileTransferMessage::ChunkManifest { file_id, chunks } = message else {
+            return Err(anyhow!("无效的块清单消息"));
+        };
+        let file_path = {
+            let transfers = self.active_transfers.read().await;
+            transfers.get(&file_id)
+                .map(|session| session.file_path.clone())
+                .ok_or_else(|| anyhow!("未找到文件传输会话: {}", file_id))?
+        };
+
+        // 旧版本文件的块先放入缓存
+        let mut reused = HashMap::new();
+        let mut missing: Vec<u32> = (0..chunks.len() as u32).collect();
+        if let Some(cache) = &self.chunk_cache {
+            if fs::try_exists(&file_path).await.unwrap_or(false) {
+                if let Err(e) = cache.index_file(&file_path).await {
+                    warn!("索引旧版本文件失败: {}", e);
+                }
+            }
+            missing = cache.missing(&chunks).await;
+            let wanted: std::collections::HashSet<u32> = missing.iter().copied().collect();
+            for (index, chunk) in chunks.iter().enumerate() {
+                if wanted.contains(&(index as u32)) {
+                    continue;
+                }
+                match cache.get(&chunk.hash).await {
+                    Some(data) => {
+                        reused.insert(index as u32, data);
+                    }
+                    None => missing.push(index as u32),
+                }
+            }
+            missing.sort_unstable();
+        }
+
+        let should_assemble = {
+            let mut transfers = self.active_transfers.write().await;
+            let session = transfers.get_mut(&file_id)
+                .ok_or_else(|| anyhow!("未找到文件传输会话: {}", file_id))?;
+            session.set_chunk_manifest(chunks);
+            for (index, data) in reused {
+                session.bytes_reused += data.len() as u64;
+                session.add_chunk(index, data)?;
+            }
+            info!("增量传输 {}: 复用 {} 块, 请求 {} 块",
+                  file_id, session.chunks_received.len(), missing.len());
+            session.is_complete()
+        };
+
+        let request = FileTransferMessage::ChunkRequest {
+            file_id: file_id.clone(),
+            chunk_indices: missing,
+        };
+        if should_assemble {
+            let result = self.assemble_file(&file_id).await;
+            self.record_transfer_finished(&file_id, &sender_id, &result);
+            result?;
+        } else {
+            self.send_message(&sender_id, request.clone()).await?;
+        }
+        Ok(request)
+    }
+
+    /// 接收端：处理增量块
+    pub async fn handle_delta_chunk(&mut self, sender_id: String, message: FileTransferMessage) -> Result<()> {
+        let FileTransferMessage::DeltaChunk { file_id, chunk_index, data } = message else {
+            return Err(anyhow!("无效的增量块消息"));
+        };
+        let should_assemble = {
+            let mut transfers = self.active_transfers.write().await;
+            let session = transfers.get_mut(&file_id)
+                .ok_or_else(|| anyhow!("未找到文件传输会话: {}", file_id))?;
+            let expected = session.chunk_manifest.get(chunk_index as usize)
+                .ok_or_else(|| anyhow!("块索引超出范围: {}", chunk_index))?;
+            if chunk_hash(&data) != expected.hash {
+                drop(transfers);
+                error!("增量块哈希验证失败: 文件 {} 块 {}", file_id, chunk_index);
+                self.record_peer_transfer(&sender_id, false);
+                return Err(anyhow!("块哈希验证失败"));
+            }
+            session.bytes_downloaded += data.len() as u64;
+            session.add_chunk(chunk_index, data)?;
+            session.is_complete()
+        };
+
+        if should_assemble {
+            let result = self.assemble_file(&file_id).await;
+            self.record_transfer_finished(&file_id, &sender_id, &result);
+            result?;
+        }
+        Ok(())
+    }
+
     /// 发送消息
-    async fn send_message(&mut self, peer_id: &str, message: FileTransferMessage) -> Result<()> {
+    async fn send_message(&self, peer_id: &str, message: FileTransferMessage) -> Result<()> {
         // 这里应该通过iroh发送消息，目前简化实现
-        let _ = (peer_id, message);
+        let encoded = self.encode_message(&message)?;
+        let _ = (peer_id, encoded);
         
         // 模拟发送到消息队列
         // self.message_tx.send((peer_id.to_string(), message)).await?;
@@ -451,6 +1133,19 @@ impl P2PModelDistributor {
     }
 }
 
+/// 可续传的传输 ID：由对端、文件名和内容哈希决定，内容变化后自动换成新传输
+fn resumable_transfer_id(peer_id: &str, file_name: &str, file_hash: &str) -> String {
+    let digest = blake3::hash(format!("{}\n{}\n{}", peer_id, file_name, file_hash).as_bytes());
+    digest.to_hex()[..32].to_string()
+}
+
+/// 未完成接收的块暂存文件
+fn partial_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+    path.push(".part");
+    PathBuf::from(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -478,6 +1173,122 @@ mod tests {
         assert!(!session.is_complete());
     }
 
+    #[tokio::test]
+    async fn test_delta_transfer_reuses_cached_chunks() {
+        let temp_dir = tempdir().unwrap();
+        let params = ChunkingParams {
+            min_size: 256,
+            avg_size: 1024,
+            max_size: 4096,
+        };
+        let mut state = 7u64;
+        let v1: Vec<u8> = (0..32 * 1024)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect();
+        let mut v2 = v1.clone();
+        v2[20_000..20_016].copy_from_slice(b"updated weights!");
+
+        let source_dir = temp_dir.path().join("src");
+        let output_dir = temp_dir.path().join("out");
+        fs::create_dir_all(&source_dir).await.unwrap();
+        fs::create_dir_all(&output_dir).await.unwrap();
+        fs::write(source_dir.join("shard.bin"), &v2).await.unwrap();
+        // 接收端已有旧版本
+        fs::write(output_dir.join("shard.bin"), &v1).await.unwrap();
+
+        let cache = Arc::new(ChunkCache::open(temp_dir.path().join("cache"), params).await.unwrap());
+        let mut sender = P2PModelDistributor::new("sender".to_string());
+        sender.set_chunk_cache(cache.clone());
+        let mut receiver = P2PModelDistributor::new("receiver".to_string());
+        receiver.set_chunk_cache(cache);
+
+        let file_id = sender.send_file_delta("receiver".to_string(), &source_dir.join("shard.bin")).await.unwrap();
+        let chunks = chunk_data(&v2, &params);
+        let request = FileTransferMessage::FileRequest {
+            file_id: file_id.clone(),
+            file_name: "shard.bin".to_string(),
+            file_size: v2.len() as u64,
+            chunk_size: params.avg_size,
+            file_hash: sender.calculate_file_hash(&source_dir.join("shard.bin")).await.unwrap(),
+        };
+        receiver.receive_file(&output_dir, request).await.unwrap();
+        let manifest = FileTransferMessage::ChunkManifest { file_id: file_id.clone(), chunks: chunks.clone() };
+        let FileTransferMessage::ChunkRequest { chunk_indices, .. } =
+            receiver.handle_chunk_manifest("sender".to_string(), manifest).await.unwrap()
+        else {
+            panic!("应返回块请求");
+        };
+        assert!(!chunk_indices.is_empty() && chunk_indices.len() <= 2);
+
+        for index in chunk_indices {
+            let chunk = &chunks[index as usize];
+            let start = chunk.offset as usize;
+            let data = v2[start..start + chunk.len as usize].to_vec();
+            receiver
+                .handle_delta_chunk("sender".to_string(), FileTransferMessage::DeltaChunk { file_id: file_id.clone(), chunk_index: index, data })
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(receiver.get_transfer_status(&file_id).await, Some(TransferStatus::Completed)));
+        assert_eq!(fs::read(output_dir.join("shard.bin")).await.unwrap(), v2);
+        let session = receiver.active_transfers.read().await.get(&file_id).cloned().unwrap();
+        assert!(session.savings_ratio() > 0.8);
+        assert_eq!(session.bytes_reused + session.bytes_downloaded, v2.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_receive_resumes_from_partial_file() {
+        let temp_dir = tempdir().unwrap();
+        let data: Vec<u8> = (0..10u8).cycle().take(10_000).collect();
+        let sender = P2PModelDistributor::new("sender".to_string());
+        let source = temp_dir.path().join("shard.bin");
+        fs::write(&source, &data).await.unwrap();
+        let file_hash = sender.calculate_file_hash(&source).await.unwrap();
+        let file_id = resumable_transfer_id("receiver", "shard.bin", &file_hash);
+        let request = FileTransferMessage::FileRequest {
+            file_id: file_id.clone(),
+            file_name: "shard.bin".to_string(),
+            file_size: data.len() as u64,
+            chunk_size: 4096,
+            file_hash,
+        };
+        let chunk = |index: u32| {
+            let start = index as usize * 4096;
+            let bytes = data[start..(start + 4096).min(data.len())].to_vec();
+            FileTransferMessage::FileChunk {
+                file_id: file_id.clone(),
+                chunk_index: index,
+                chunk_hash: sender.calculate_chunk_hash(&bytes),
+                data: bytes,
+            }
+        };
+
+        let output_dir = temp_dir.path().join("out");
+        fs::create_dir_all(&output_dir).await.unwrap();
+        let store = Arc::new(StatsStore::open_in_memory().unwrap());
+        let mut receiver = P2PModelDistributor::new("receiver".to_string());
+        receiver.set_stats_store(store.clone());
+        receiver.receive_file(&output_dir, request.clone()).await.unwrap();
+        receiver.handle_file_chunk("sender".to_string(), chunk(0)).await.unwrap();
+        receiver.handle_file_chunk("sender".to_string(), chunk(2)).await.unwrap();
+
+        // 模拟进程重启：新的分发器从 .part 文件恢复已收到的块
+        let mut restarted = P2PModelDistributor::new("receiver".to_string());
+        restarted.set_stats_store(store.clone());
+        restarted.receive_file(&output_dir, request).await.unwrap();
+        assert_eq!(restarted.received_chunks(&file_id).await, vec![0, 2]);
+        restarted.handle_file_chunk("sender".to_string(), chunk(1)).await.unwrap();
+
+        assert!(matches!(restarted.get_transfer_status(&file_id).await, Some(TransferStatus::Completed)));
+        assert_eq!(fs::read(output_dir.join("shard.bin")).await.unwrap(), data);
+        assert!(!fs::try_exists(output_dir.join("shard.bin.part")).await.unwrap());
+        assert!(store.transfer_chunks(&file_id).unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_p2p_distributor_creation() {
         let distributor = P2PModelDistributor::new("test_node".to_string());
@@ -0,0 +1,376 @@
+/**
+ * 运行时层重平衡模块
+ * 统计分布式训练/推理中各节点的单步延迟，节点间负载失衡超过阈值且持续一段时间后，
+ * 提议把部分层从最慢的节点移到最快的节点；由源节点用 ModelSplitter 切出这些层并通过 P2P 发送。
+ * 触发阈值与解除阈值之间留有回差，并有冷却时间，避免在边界附近来回搬迁。
+ */
+
+use anyhow::{anyhow, Result};
+use model_splitter::{ModelSplitter, SplitConfig, SplitPlan};
+use parking_lot::Mutex as SyncMutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use super::distributor::P2PModelDistributor;
+use crate::work_schedule::{WorkKind, WorkScheduler};
+
+/// 重平衡配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceConfig {
+    /// 最慢节点与最快节点的平均单步延迟之比超过该值时视为失衡
+    pub trigger_ratio: f64,
+    /// 延迟比降到该值以下才解除失衡状态（需小于 trigger_ratio）
+    pub release_ratio: f64,
+    /// 连续多少次检查处于失衡状态才提议搬迁
+    pub sustain_checks: u32,
+    /// 每个节点保留的最近延迟样本数
+    pub window: usize,
+    /// 节点至少有多少个样本才参与比较
+    pub min_samples: usize,
+    /// 两次搬迁之间的最短间隔
+    pub cooldown: Duration,
+    /// 单次最多搬迁的层数
+    pub max_layers_per_move: usize,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            trigger_ratio: 1.5,
+            release_ratio: 1.2,
+            sustain_checks: 3,
+            window: 32,
+            min_samples: 8,
+            cooldown: Duration::from_secs(300),
+            max_layers_per_move: 4,
+        }
+    }
+}
+
+/// 层搬迁提议
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RebalanceProposal {
+    pub from: String,
+    pub to: String,
+    pub layers: Vec<String>,
+    /// 搬迁的算力需求
+    pub moved_compute: f64,
+    /// 当前两节点的延迟比
+    pub current_ratio: f64,
+    /// 按当前吞吐量估算的搬迁后延迟比
+    pub predicted_ratio: f64,
+}
+
+impl RebalanceProposal {
+    /// 把提议应用到切分方案
+    pub fn apply(&self, plans: &mut HashMap<String, SplitPlan>) -> Result<()> {
+        let from = plans
+            .get_mut(&self.from)
+            .ok_or_else(|| anyhow!("切分方案中没有节点 {}", self.from))?;
+        if !self.layers.iter().all(|layer| from.layer_names.contains(layer)) {
+            return Err(anyhow!("节点 {} 已不再持有待搬迁的层", self.from));
+        }
+        from.layer_names.retain(|layer| !self.layers.contains(layer));
+        from.total_compute = (from.total_compute - self.moved_compute).max(0.0);
+
+        let to = plans
+            .get_mut(&self.to)
+            .ok_or_else(|| anyhow!("切分方案中没有节点 {}", self.to))?;
+        to.layer_names.extend(self.layers.iter().cloned());
+        to.total_compute += self.moved_compute;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct BalancerState {
+    latencies: HashMap<String, VecDeque<Duration>>,
+    /// 连续处于失衡状态的检查次数
+    imbalanced_checks: u32,
+    last_move: Option<Instant>,
+}
+
+/// 层重平衡器
+pub struct LayerRebalancer {
+    node_id: String,
+    config: RebalanceConfig,
+    distributor: Arc<Mutex<P2PModelDistributor>>,
+    state: SyncMutex<BalancerState>,
+    /// 重任务调度窗口（未设置时搬迁立即执行）
+    work_schedule: Option<Arc<WorkScheduler>>,
+}
+
+impl LayerRebalancer {
+    pub fn new(node_id: String, config: RebalanceConfig, distributor: Arc<Mutex<P2PModelDistributor>>) -> Self {
+        Self {
+            node_id,
+            config,
+            distributor,
+            state: SyncMutex::new(BalancerState::default()),
+            work_schedule: None,
+        }
+    }
+
+    /// 分片搬迁在调度窗口外排队，窗口打开后继续
+    pub fn with_work_scheduler(mut self, scheduler: Arc<WorkScheduler>) -> Self {
+        self.work_schedule = Some(scheduler);
+        self
+    }
+
+    /// 记录某节点完成一步的耗时
+    pub fn record_step_latency(&self, node_id: &str, latency: Duration) {
+        let mut state = self.state.lock();
+        let window = state.latencies.entry(node_id.to_string()).or_default();
+        window.push_back(latency);
+        while window.len() > self.config.window.max(1) {
+            window.pop_front();
+        }
+    }
+
+    /// 各节点的平均单步延迟（样本不足的节点不计入）
+    pub fn mean_latencies(&self) -> HashMap<String, Duration> {
+        let state = self.state.lock();
+        state
+            .latencies
+            .iter()
+            .filter(|(_, samples)| !samples.is_empty() && samples.len() >= self.config.min_samples)
+            .map(|(node, samples)| (node.clone(), samples.iter().sum::<Duration>() / samples.len() as u32))
+            .collect()
+    }
+
+    /// 检查失衡情况，需要搬迁时返回提议
+    ///
+    /// `layer_compute` 为各层的算力需求，缺失的层按所在节点的平均值估算。
+    pub fn propose(
+        &self,
+        plans: &HashMap<String, SplitPlan>,
+        layer_compute: &HashMap<String, f64>,
+    ) -> Option<RebalanceProposal> {
+        let latencies: Vec<(String, f64)> = self
+            .mean_latencies()
+            .into_iter()
+            .filter(|(node, _)| plans.contains_key(node))
+            .map(|(node, latency)| (node, latency.as_secs_f64()))
+            .filter(|(_, latency)| *latency > 0.0)
+            .collect();
+        let (slow, slow_latency) = latencies.iter().max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))?.clone();
+        let (fast, fast_latency) = latencies.iter().min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))?.clone();
+        if slow == fast {
+            return None;
+        }
+        let ratio = slow_latency / fast_latency;
+
+        let mut state = self.state.lock();
+        if ratio >= self.config.trigger_ratio {
+            state.imbalanced_checks += 1;
+        } else if ratio <= self.config.release_ratio {
+            state.imbalanced_checks = 0;
+        }
+        if state.imbalanced_checks < self.config.sustain_checks {
+            return None;
+        }
+        if state.last_move.is_some_and(|at| at.elapsed() < self.config.cooldown) {
+            return None;
+        }
+
+        let proposal = plan_move(
+            &plans[&slow],
+            slow_latency,
+            &plans[&fast],
+            fast_latency,
+            layer_compute,
+            self.config.max_layers_per_move,
+        )?;
+        info!(
+            "层负载失衡 {} / {} = {:.2}，提议搬迁 {} 层（预计 {:.2}）",
+            slow,
+            fast,
+            ratio,
+            proposal.layers.len(),
+            proposal.predicted_ratio
+        );
+        state.imbalanced_checks = 0;
+        state.last_move = Some(Instant::now());
+        // 搬迁后旧的延迟样本不再反映新的分配
+        state.latencies.remove(&slow);
+        state.latencies.remove(&fast);
+        Some(RebalanceProposal { current_ratio: ratio, ..proposal })
+    }
+
+    /// 源节点执行搬迁：切出待搬迁的层并发送给目标节点，返回传输 ID
+    pub async fn execute(&self, proposal: &RebalanceProposal, model_name: &str, model_path: &Path) -> Result<String> {
+        if proposal.from != self.node_id {
+            return Err(anyhow!("只有源节点 {} 能执行该搬迁", proposal.from));
+        }
+        let shard_plan = SplitPlan {
+            node_id: proposal.to.clone(),
+            layer_names: proposal.layers.clone(),
+            total_compute: proposal.moved_compute,
+            compute_utilization: 0.0,
+            expert_assignments: Vec::new(),
+        };
+        let config = SplitConfig {
+            model_name: model_name.to_string(),
+            model_path: model_path.to_string_lossy().to_string(),
+            split_plan: HashMap::from([(proposal.to.clone(), shard_plan)]),
+            output_dir: None,
+        };
+        let shard = ModelSplitter::new().split_model(config, &proposal.to).await?;
+        if let Some(scheduler) = &self.work_schedule {
+            scheduler.wait_for_window(WorkKind::ShardTransfer, &shard.shard_path).await;
+        }
+        let file_id = self
+            .distributor
+            .lock()
+            .await
+            .send_file(proposal.to.clone(), Path::new(&shard.shard_path), None)
+            .await?;
+        info!("已发送搬迁分片 {} -> {}（{} 层）", proposal.from, proposal.to, proposal.layers.len());
+        Ok(file_id)
+    }
+}
+
+/// 从慢节点末尾依次挑选层移到快节点，直到继续搬迁不再降低两者中较大的预计延迟
+fn plan_move(
+    slow: &SplitPlan,
+    slow_latency: f64,
+    fast: &SplitPlan,
+    fast_latency: f64,
+    layer_compute: &HashMap<String, f64>,
+    max_layers: usize,
+) -> Option<RebalanceProposal> {
+    let slow_average = slow.total_compute / slow.layer_names.len().max(1) as f64;
+    let cost = |layer: &String| layer_compute.get(layer).copied().unwrap_or(slow_average);
+
+    // 吞吐量：每秒完成的算力需求；方案中没有算力信息时按各层估算值求和
+    let load = |plan: &SplitPlan| {
+        if plan.total_compute > 0.0 {
+            plan.total_compute
+        } else {
+            plan.layer_names.iter().map(cost).sum()
+        }
+    };
+    let mut slow_load = load(slow);
+    let mut fast_load = load(fast);
+    let slow_throughput = slow_load.max(f64::EPSILON) / slow_latency;
+    let fast_throughput = fast_load.max(f64::EPSILON) / fast_latency;
+
+    let mut layers = Vec::new();
+    let mut moved = 0.0;
+    // 至少给慢节点留一层
+    for layer in slow.layer_names.iter().rev().take(slow.layer_names.len().saturating_sub(1)) {
+        if layers.len() >= max_layers {
+            break;
+        }
+        let c = cost(layer);
+        let current = (slow_load / slow_throughput).max(fast_load / fast_throughput);
+        let next = ((slow_load - c) / slow_throughput).max((fast_load + c) / fast_throughput);
+        if next >= current {
+            break;
+        }
+        slow_load -= c;
+        fast_load += c;
+        moved += c;
+        layers.push(layer.clone());
+    }
+    if layers.is_empty() {
+        return None;
+    }
+    layers.reverse();
+
+    let slow_after = slow_load / slow_throughput;
+    let fast_after = fast_load / fast_throughput;
+    Some(RebalanceProposal {
+        from: slow.node_id.clone(),
+        to: fast.node_id.clone(),
+        layers,
+        moved_compute: moved,
+        current_ratio: slow_latency / fast_latency,
+        predicted_ratio: slow_after.max(fast_after) / slow_after.min(fast_after).max(f64::EPSILON),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(node: &str, layers: usize) -> SplitPlan {
+        SplitPlan {
+            node_id: node.to_string(),
+            layer_names: (0..layers).map(|i| format!("{}.layer{}", node, i)).collect(),
+            total_compute: layers as f64 * 10.0,
+            compute_utilization: 0.0,
+            expert_assignments: Vec::new(),
+        }
+    }
+
+    fn rebalancer() -> LayerRebalancer {
+        let distributor = Arc::new(Mutex::new(P2PModelDistributor::new("node-a".to_string())));
+        LayerRebalancer::new(
+            "node-a".to_string(),
+            RebalanceConfig {
+                sustain_checks: 2,
+                window: 2,
+                min_samples: 2,
+                cooldown: Duration::from_secs(3600),
+                ..Default::default()
+            },
+            distributor,
+        )
+    }
+
+    #[test]
+    fn test_sustained_imbalance_moves_layers_once() {
+        let balancer = rebalancer();
+        let mut plans = HashMap::from([("node-a".to_string(), plan("node-a", 6)), ("node-b".to_string(), plan("node-b", 6))]);
+        let record = |balancer: &LayerRebalancer| {
+            for _ in 0..2 {
+                balancer.record_step_latency("node-a", Duration::from_millis(300));
+                balancer.record_step_latency("node-b", Duration::from_millis(100));
+            }
+        };
+
+        record(&balancer);
+        // 第一次检查只计数
+        assert!(balancer.propose(&plans, &HashMap::new()).is_none());
+        let proposal = balancer.propose(&plans, &HashMap::new()).unwrap();
+        assert_eq!((proposal.from.as_str(), proposal.to.as_str()), ("node-a", "node-b"));
+        assert!(proposal.predicted_ratio < proposal.current_ratio);
+        assert_eq!(proposal.layers.last().unwrap(), "node-a.layer5");
+
+        proposal.apply(&mut plans).unwrap();
+        assert_eq!(plans["node-a"].layer_names.len() + plans["node-b"].layer_names.len(), 12);
+        assert!(plans["node-b"].layer_names.contains(&"node-a.layer5".to_string()));
+
+        // 冷却期内即使仍然失衡也不再搬迁
+        for _ in 0..3 {
+            record(&balancer);
+            assert!(balancer.propose(&plans, &HashMap::new()).is_none());
+        }
+    }
+
+    #[test]
+    fn test_ratio_inside_hysteresis_band_keeps_count() {
+        let balancer = rebalancer();
+        let plans = HashMap::from([("node-a".to_string(), plan("node-a", 4)), ("node-b".to_string(), plan("node-b", 4))]);
+        let record = |slow_ms: u64| {
+            for _ in 0..2 {
+                balancer.record_step_latency("node-a", Duration::from_millis(slow_ms));
+                balancer.record_step_latency("node-b", Duration::from_millis(100));
+            }
+        };
+
+        record(200);
+        assert!(balancer.propose(&plans, &HashMap::new()).is_none());
+        // 1.3 介于解除阈值和触发阈值之间：不增加也不清零
+        record(130);
+        assert!(balancer.propose(&plans, &HashMap::new()).is_none());
+        record(200);
+        assert!(balancer.propose(&plans, &HashMap::new()).is_some());
+    }
+}
@@ -155,8 +155,22 @@ impl P2PModelReceiver {
         Ok(())
     }
 
-    /// 接收消息（模拟实现）
+    /// 接收并认证消息，未通过认证的消息直接丢弃
     async fn receive_message(&self) -> Result<Option<(String, FileTransferMessage)>> {
+        let Some((sender_id, data)) = self.receive_raw().await? else {
+            return Ok(None);
+        };
+        match self.distributor.open_message(&sender_id, &data) {
+            Ok(message) => Ok(Some((sender_id, message))),
+            Err(e) => {
+                warn!("⚠️  丢弃来自 {} 的传输消息: {}", sender_id, e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// 接收原始消息（模拟实现）
+    async fn receive_raw(&self) -> Result<Option<(String, Vec<u8>)>> {
         // 这里应该通过 iroh 接收实际消息
         // 目前模拟实现，返回 None
         tokio::time::sleep(Duration::from_millis(1000)).await;
@@ -193,6 +207,10 @@ impl P2PModelReceiver {
             FileTransferMessage::TransferError { file_id, error } => {
                 self.handle_transfer_error(sender_id, file_id, error).await?;
             }
+            message @ (FileTransferMessage::ChunkAck { .. } | FileTransferMessage::TransferResume { .. }) => {
+                // 本节点同时作为发送端时，确认转交给对应的发送任务
+                self.distributor.handle_chunk_feedback(message).await?;
+            }
             _ => {
                 warn!("⚠️  收到未知类型的消息");
             }
@@ -264,6 +282,17 @@ impl P2PModelReceiver {
 
         self.send_message(&sender_id, response).await?;
 
+        // 续传：告知发送端已有的块
+        let received_chunks = self.distributor.received_chunks(&transfer_id).await;
+        if !received_chunks.is_empty() {
+            info!("⏩ 续传，已有 {} 块", received_chunks.len());
+            let resume = FileTransferMessage::TransferResume {
+                file_id: transfer_id,
+                received_chunks,
+            };
+            self.send_message(&sender_id, resume).await?;
+        }
+
         Ok(())
     }
 
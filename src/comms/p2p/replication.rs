@@ -0,0 +1,350 @@
+/**
+ * 分片副本管理模块
+ * 跟踪各节点持有的层分片，维持副本数，并在节点掉线时从存活副本重新分发
+ */
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+use super::distributor::P2PModelDistributor;
+use super::events::{send_global_event, TransferEvent};
+use crate::work_schedule::{WorkKind, WorkScheduler};
+
+/// 副本管理配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfig {
+    /// 每个分片期望的副本数
+    pub replication_factor: usize,
+    /// 单次健康检查最多发起的修复传输数
+    pub max_repairs_per_check: usize,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            replication_factor: 2,
+            max_repairs_per_check: 8,
+        }
+    }
+}
+
+/// 分片描述
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardInfo {
+    /// 分片ID（通常为 `<model>/<layer_range>`）
+    pub shard_id: String,
+    /// 本地文件路径（仅当本节点持有时有效）
+    pub local_path: Option<PathBuf>,
+    /// 分片大小（字节）
+    pub size_bytes: u64,
+    /// 持有该分片的节点
+    pub holders: BTreeSet<String>,
+}
+
+/// 修复任务：从存活副本复制分片到新节点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairTask {
+    pub shard_id: String,
+    pub source: String,
+    pub target: String,
+}
+
+/// 健康检查结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplicationReport {
+    /// 本轮判定丢失的节点
+    pub lost_nodes: Vec<String>,
+    /// 本节点直接发起的传输
+    pub started: Vec<RepairTask>,
+    /// 需要其他节点执行的修复（本节点不持有源分片）
+    pub delegated: Vec<RepairTask>,
+    /// 已无存活副本、无法修复的分片
+    pub unrecoverable: Vec<String>,
+}
+
+/// 分片副本管理器
+pub struct ReplicationManager {
+    node_id: String,
+    config: ReplicationConfig,
+    distributor: Arc<Mutex<P2PModelDistributor>>,
+    shards: Arc<RwLock<HashMap<String, ShardInfo>>>,
+    known_nodes: Arc<RwLock<BTreeSet<String>>>,
+    /// 重任务调度窗口（未设置时修复传输立即执行）
+    work_schedule: Option<Arc<WorkScheduler>>,
+}
+
+impl ReplicationManager {
+    pub fn new(
+        node_id: String,
+        config: ReplicationConfig,
+        distributor: Arc<Mutex<P2PModelDistributor>>,
+    ) -> Self {
+        let mut known_nodes = BTreeSet::new();
+        known_nodes.insert(node_id.clone());
+        Self {
+            node_id,
+            config,
+            distributor,
+            shards: Arc::new(RwLock::new(HashMap::new())),
+            known_nodes: Arc::new(RwLock::new(known_nodes)),
+            work_schedule: None,
+        }
+    }
+
+    /// 修复传输在调度窗口外排队，窗口打开后继续
+    pub fn with_work_scheduler(mut self, scheduler: Arc<WorkScheduler>) -> Self {
+        self.work_schedule = Some(scheduler);
+        self
+    }
+
+    /// 注册集群中的节点（可作为修复目标）
+    pub async fn register_node(&self, node_id: &str) {
+        self.known_nodes.write().await.insert(node_id.to_string());
+    }
+
+    /// 登记本节点持有的分片
+    pub async fn register_local_shard(&self, shard_id: &str, path: PathBuf, size_bytes: u64) {
+        let mut shards = self.shards.write().await;
+        let shard = shards.entry(shard_id.to_string()).or_insert_with(|| ShardInfo {
+            shard_id: shard_id.to_string(),
+            local_path: None,
+            size_bytes,
+            holders: BTreeSet::new(),
+        });
+        shard.local_path = Some(path);
+        shard.size_bytes = size_bytes;
+        shard.holders.insert(self.node_id.clone());
+    }
+
+    /// 记录远端节点持有某分片（来自分配计划或传输完成通知）
+    pub async fn record_holder(&self, shard_id: &str, node_id: &str) {
+        self.register_node(node_id).await;
+        let mut shards = self.shards.write().await;
+        let shard = shards.entry(shard_id.to_string()).or_insert_with(|| ShardInfo {
+            shard_id: shard_id.to_string(),
+            local_path: None,
+            size_bytes: 0,
+            holders: BTreeSet::new(),
+        });
+        shard.holders.insert(node_id.to_string());
+    }
+
+    /// 查询分片的持有者
+    pub async fn holders(&self, shard_id: &str) -> Vec<String> {
+        self.shards
+            .read()
+            .await
+            .get(shard_id)
+            .map(|shard| shard.holders.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 副本数不足的分片
+    pub async fn under_replicated(&self) -> Vec<String> {
+        self.shards
+            .read()
+            .await
+            .values()
+            .filter(|shard| shard.holders.len() < self.config.replication_factor)
+            .map(|shard| shard.shard_id.clone())
+            .collect()
+    }
+
+    /// 节点健康检查
+    ///
+    /// `alive_nodes` 为当前存活的节点集合（例如来自保活监视器或 Workers 健康检查）。
+    /// 未出现在其中的已知节点视为丢失，其持有的分片会被重新复制。
+    pub async fn check_node_health(&self, alive_nodes: &[String]) -> Result<ReplicationReport> {
+        let lost_nodes: Vec<String> = {
+            let known = self.known_nodes.read().await;
+            known
+                .iter()
+                .filter(|node| **node != self.node_id && !alive_nodes.contains(node))
+                .cloned()
+                .collect()
+        };
+
+        for node in alive_nodes {
+            self.register_node(node).await;
+        }
+
+        let mut report = self.handle_nodes_lost(&lost_nodes).await?;
+        report.lost_nodes = lost_nodes;
+        Ok(report)
+    }
+
+    /// 处理节点丢失：移除其副本并为不足的分片安排修复
+    pub async fn handle_nodes_lost(&self, lost_nodes: &[String]) -> Result<ReplicationReport> {
+        if !lost_nodes.is_empty() {
+            let mut known = self.known_nodes.write().await;
+            let mut shards = self.shards.write().await;
+            for node in lost_nodes {
+                known.remove(node);
+                for shard in shards.values_mut() {
+                    shard.holders.remove(node);
+                }
+                warn!("节点丢失，移除其分片副本: {}", node);
+            }
+        }
+
+        let plan = self.plan_repairs().await;
+        let mut report = ReplicationReport {
+            unrecoverable: plan.unrecoverable,
+            ..Default::default()
+        };
+
+        for task in plan.tasks {
+            if task.source == self.node_id {
+                match self.execute_repair(&task).await {
+                    Ok(()) => report.started.push(task),
+                    Err(e) => warn!("分片修复失败 {} -> {}: {}", task.shard_id, task.target, e),
+                }
+            } else {
+                report.delegated.push(task);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 生成修复计划（确定性：按分片ID和节点ID排序）
+    async fn plan_repairs(&self) -> RepairPlan {
+        let shards = self.shards.read().await;
+        let known = self.known_nodes.read().await;
+
+        // 目标选择时优先负载较轻的节点
+        let mut load: HashMap<&str, usize> = known.iter().map(|node| (node.as_str(), 0)).collect();
+        for shard in shards.values() {
+            for holder in &shard.holders {
+                *load.entry(holder.as_str()).or_insert(0) += 1;
+            }
+        }
+
+        let mut shard_ids: Vec<&String> = shards.keys().collect();
+        shard_ids.sort();
+
+        let mut plan = RepairPlan::default();
+        for shard_id in shard_ids {
+            if plan.tasks.len() >= self.config.max_repairs_per_check {
+                break;
+            }
+            let shard = &shards[shard_id];
+            let missing = self.config.replication_factor.saturating_sub(shard.holders.len());
+            if missing == 0 {
+                continue;
+            }
+            // 优先由本节点作为源，避免额外协调
+            let source = if shard.holders.contains(&self.node_id) {
+                self.node_id.clone()
+            } else if let Some(holder) = shard.holders.iter().next() {
+                holder.clone()
+            } else {
+                plan.unrecoverable.push(shard_id.clone());
+                continue;
+            };
+
+            let mut candidates: Vec<&String> = known
+                .iter()
+                .filter(|node| !shard.holders.contains(*node))
+                .collect();
+            candidates.sort_by_key(|node| (load.get(node.as_str()).copied().unwrap_or(0), (*node).clone()));
+
+            for target in candidates.into_iter().take(missing) {
+                *load.entry(target.as_str()).or_insert(0) += 1;
+                plan.tasks.push(RepairTask {
+                    shard_id: shard_id.clone(),
+                    source: source.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+        plan
+    }
+
+    /// 执行本地发起的修复传输
+    async fn execute_repair(&self, task: &RepairTask) -> Result<()> {
+        let path = {
+            let shards = self.shards.read().await;
+            shards
+                .get(&task.shard_id)
+                .and_then(|shard| shard.local_path.clone())
+                .ok_or_else(|| anyhow!("本地未持有分片: {}", task.shard_id))?
+        };
+
+        if let Some(scheduler) = &self.work_schedule {
+            scheduler.wait_for_window(WorkKind::ShardTransfer, &task.shard_id).await;
+        }
+        info!("重新复制分片 {} -> {}", task.shard_id, task.target);
+        let transfer_id = self
+            .distributor
+            .lock()
+            .await
+            .send_file(task.target.clone(), &path, None)
+            .await?;
+
+        let _ = send_global_event(TransferEvent::TransferStarted {
+            transfer_id,
+            file_name: task.shard_id.clone(),
+            peer_id: task.target.clone(),
+        })
+        .await;
+
+        self.record_holder(&task.shard_id, &task.target).await;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct RepairPlan {
+    tasks: Vec<RepairTask>,
+    unrecoverable: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(factor: usize) -> ReplicationManager {
+        let distributor = Arc::new(Mutex::new(P2PModelDistributor::new("local".to_string())));
+        ReplicationManager::new(
+            "local".to_string(),
+            ReplicationConfig {
+                replication_factor: factor,
+                max_repairs_per_check: 8,
+            },
+            distributor,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lost_node_triggers_delegated_repair() {
+        let manager = manager(2);
+        manager.record_holder("layer-0", "node-a").await;
+        manager.record_holder("layer-0", "node-b").await;
+        manager.register_node("node-c").await;
+
+        let report = manager
+            .check_node_health(&["node-a".to_string(), "node-c".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(report.lost_nodes, vec!["node-b".to_string()]);
+        assert_eq!(report.delegated.len(), 1);
+        assert_eq!(report.delegated[0].source, "node-a");
+        assert_ne!(report.delegated[0].target, "node-a");
+    }
+
+    #[tokio::test]
+    async fn test_shard_without_survivors_is_unrecoverable() {
+        let manager = manager(1);
+        manager.record_holder("layer-1", "node-a").await;
+
+        let report = manager.check_node_health(&[]).await.unwrap();
+        assert_eq!(report.unrecoverable, vec!["layer-1".to_string()]);
+    }
+}
@@ -5,9 +5,11 @@
 
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio;
 use tracing::{info, warn, error};
 use tracing_subscriber;
@@ -42,6 +44,283 @@ pub struct P2PSenderArgs {
     /// bootstrap 节点
     #[arg(long)]
     pub bootstrap: Option<String>,
+
+    /// 并行传输流数
+    #[arg(long, default_value = "4")]
+    pub streams: usize,
+
+    /// 拥塞窗口上限（在途块数）
+    #[arg(long, default_value = "64")]
+    pub max_window: usize,
+}
+
+/// 分块传输的窗口参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferWindowConfig {
+    /// 并行流数，在途块按负载分配到各流
+    pub streams: usize,
+    pub initial_window: usize,
+    pub min_window: usize,
+    pub max_window: usize,
+    /// 尚无 RTT 样本时的确认超时（毫秒）
+    pub initial_rto_ms: u64,
+    pub min_rto_ms: u64,
+    pub max_rto_ms: u64,
+    /// RTT 超过最小 RTT 的倍数时视为排队拥塞，停止增大窗口
+    pub queueing_factor: f64,
+    /// 连续多少轮超时没有任何确认后放弃（已确认的块保留，可续传）
+    pub max_stalled_rounds: u32,
+}
+
+impl Default for TransferWindowConfig {
+    fn default() -> Self {
+        Self {
+            streams: 4,
+            initial_window: 4,
+            min_window: 1,
+            max_window: 64,
+            initial_rto_ms: 1000,
+            min_rto_ms: 200,
+            max_rto_ms: 10_000,
+            queueing_factor: 2.0,
+            max_stalled_rounds: 8,
+        }
+    }
+}
+
+/// 拥塞窗口：慢启动 + 加性增、丢失（超时）时乘性减，RTT 明显高于基线时暂停增长
+#[derive(Debug, Clone)]
+pub struct CongestionWindow {
+    config: TransferWindowConfig,
+    cwnd: f64,
+    ssthresh: f64,
+    srtt: Option<Duration>,
+    rttvar: Duration,
+    min_rtt: Option<Duration>,
+}
+
+impl CongestionWindow {
+    pub fn new(config: TransferWindowConfig) -> Self {
+        Self {
+            cwnd: config.initial_window.max(1) as f64,
+            ssthresh: config.max_window as f64,
+            srtt: None,
+            rttvar: Duration::ZERO,
+            min_rtt: None,
+            config,
+        }
+    }
+
+    /// 当前允许的在途块数
+    pub fn window(&self) -> usize {
+        (self.cwnd as usize).clamp(self.config.min_window.max(1), self.config.max_window.max(1))
+    }
+
+    pub fn srtt(&self) -> Option<Duration> {
+        self.srtt
+    }
+
+    /// 按 RFC 6298 计算重传超时
+    pub fn rto(&self) -> Duration {
+        let rto = match self.srtt {
+            Some(srtt) => srtt + self.rttvar * 4,
+            None => Duration::from_millis(self.config.initial_rto_ms),
+        };
+        rto.clamp(
+            Duration::from_millis(self.config.min_rto_ms),
+            Duration::from_millis(self.config.max_rto_ms.max(self.config.min_rto_ms)),
+        )
+    }
+
+    pub fn on_ack(&mut self, rtt: Option<Duration>) {
+        let mut queueing = false;
+        if let Some(rtt) = rtt {
+            match self.srtt {
+                None => {
+                    self.srtt = Some(rtt);
+                    self.rttvar = rtt / 2;
+                }
+                Some(srtt) => {
+                    let delta = if srtt > rtt { srtt - rtt } else { rtt - srtt };
+                    self.rttvar = self.rttvar.mul_f64(0.75) + delta.mul_f64(0.25);
+                    self.srtt = Some(srtt.mul_f64(0.875) + rtt.mul_f64(0.125));
+                }
+            }
+            let min_rtt = self.min_rtt.map_or(rtt, |min| min.min(rtt));
+            self.min_rtt = Some(min_rtt);
+            queueing = rtt.as_secs_f64() > min_rtt.as_secs_f64() * self.config.queueing_factor;
+        }
+        if queueing {
+            return;
+        }
+        if self.cwnd < self.ssthresh {
+            self.cwnd += 1.0;
+        } else {
+            self.cwnd += 1.0 / self.cwnd;
+        }
+        self.cwnd = self.cwnd.min(self.config.max_window.max(1) as f64);
+    }
+
+    pub fn on_loss(&mut self) {
+        self.ssthresh = (self.cwnd / 2.0).max(self.config.min_window.max(1) as f64);
+        self.cwnd = self.ssthresh;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct InFlightChunk {
+    sent_at: Instant,
+    stream: usize,
+    retransmitted: bool,
+}
+
+/// 发送端的块调度：按窗口放行、分配流、逐块确认和超时重传
+#[derive(Debug)]
+pub struct ChunkScheduler {
+    total: u32,
+    acked: HashSet<u32>,
+    pending: VecDeque<u32>,
+    in_flight: HashMap<u32, InFlightChunk>,
+    /// 超时重新排队过的块
+    requeued: HashSet<u32>,
+    stream_load: Vec<usize>,
+    window: CongestionWindow,
+    retransmits: u64,
+}
+
+impl ChunkScheduler {
+    /// `already_acked` 为续传时对端已确认的块
+    pub fn new(total: u32, already_acked: &[u32], config: TransferWindowConfig) -> Self {
+        let acked: HashSet<u32> = already_acked.iter().copied().filter(|index| *index < total).collect();
+        Self {
+            total,
+            pending: (0..total).filter(|index| !acked.contains(index)).collect(),
+            acked,
+            in_flight: HashMap::new(),
+            requeued: HashSet::new(),
+            stream_load: vec![0; config.streams.max(1)],
+            window: CongestionWindow::new(config),
+            retransmits: 0,
+        }
+    }
+
+    /// 窗口内可以发送的块及其分配的流
+    pub fn poll(&mut self, now: Instant) -> Vec<(u32, usize)> {
+        let mut batch = Vec::new();
+        while self.in_flight.len() < self.window.window() {
+            let Some(index) = self.pending.pop_front() else {
+                break;
+            };
+            if self.acked.contains(&index) {
+                continue;
+            }
+            let stream = (0..self.stream_load.len())
+                .min_by_key(|stream| self.stream_load[*stream])
+                .unwrap_or(0);
+            self.stream_load[stream] += 1;
+            let retransmitted = self.requeued.contains(&index);
+            self.in_flight.insert(index, InFlightChunk { sent_at: now, stream, retransmitted });
+            batch.push((index, stream));
+        }
+        batch
+    }
+
+    pub fn on_ack(&mut self, index: u32, now: Instant) {
+        if !self.acked.insert(index) {
+            return;
+        }
+        match self.in_flight.remove(&index) {
+            Some(chunk) => {
+                self.stream_load[chunk.stream] = self.stream_load[chunk.stream].saturating_sub(1);
+                // Karn 算法：重传过的块不计 RTT
+                let rtt = (!chunk.retransmitted).then(|| now.saturating_duration_since(chunk.sent_at));
+                self.window.on_ack(rtt);
+            }
+            None => self.pending.retain(|pending| *pending != index),
+        }
+    }
+
+    /// 对端报告已有的块（续传）
+    pub fn mark_acked(&mut self, indices: &[u32]) {
+        for &index in indices.iter().filter(|index| **index < self.total) {
+            if self.acked.insert(index) {
+                if let Some(chunk) = self.in_flight.remove(&index) {
+                    self.stream_load[chunk.stream] = self.stream_load[chunk.stream].saturating_sub(1);
+                }
+                self.pending.retain(|pending| *pending != index);
+            }
+        }
+    }
+
+    /// 超时未确认的块重新排队，返回重传数；本轮有超时时窗口减半一次
+    pub fn on_timeout(&mut self, now: Instant) -> usize {
+        let rto = self.window.rto();
+        let mut expired: Vec<u32> = self
+            .in_flight
+            .iter()
+            .filter(|(_, chunk)| now.saturating_duration_since(chunk.sent_at) >= rto)
+            .map(|(index, _)| *index)
+            .collect();
+        expired.sort_unstable();
+        for index in expired.iter().rev() {
+            if let Some(chunk) = self.in_flight.remove(index) {
+                self.stream_load[chunk.stream] = self.stream_load[chunk.stream].saturating_sub(1);
+            }
+            self.requeued.insert(*index);
+            self.pending.push_front(*index);
+        }
+        if !expired.is_empty() {
+            self.window.on_loss();
+            self.retransmits += expired.len() as u64;
+        }
+        expired.len()
+    }
+
+    /// 系统睡眠或网络切换后，所有在途块重新排队；丢失与拥塞无关，不缩小窗口
+    pub fn requeue_in_flight(&mut self) -> usize {
+        let mut in_flight: Vec<u32> = self.in_flight.keys().copied().collect();
+        in_flight.sort_unstable();
+        for index in in_flight.iter().rev() {
+            if let Some(chunk) = self.in_flight.remove(index) {
+                self.stream_load[chunk.stream] = self.stream_load[chunk.stream].saturating_sub(1);
+            }
+            self.requeued.insert(*index);
+            self.pending.push_front(*index);
+        }
+        self.retransmits += in_flight.len() as u64;
+        in_flight.len()
+    }
+
+    /// 最早的确认截止时间
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let rto = self.window.rto();
+        self.in_flight.values().map(|chunk| chunk.sent_at + rto).min()
+    }
+
+    pub fn rto(&self) -> Duration {
+        self.window.rto()
+    }
+
+    pub fn streams(&self) -> usize {
+        self.stream_load.len()
+    }
+
+    pub fn window(&self) -> usize {
+        self.window.window()
+    }
+
+    pub fn acked(&self) -> usize {
+        self.acked.len()
+    }
+
+    pub fn retransmits(&self) -> u64 {
+        self.retransmits
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.acked.len() as u32 >= self.total
+    }
 }
 
 /// 发送端状态
@@ -206,11 +485,17 @@ impl P2PModelSender {
     async fn send_single_file(&mut self, file_path: &Path) -> Result<String> {
         info!("📤 开始发送文件: {}", file_path.display());
 
-        // 通过 P2P 分发器发送文件
-        let transfer_id = self.distributor.send_file(
+        // 通过 P2P 分发器多流分块发送文件，中断后可续传
+        let window = TransferWindowConfig {
+            streams: self.args.streams,
+            max_window: self.args.max_window,
+            ..TransferWindowConfig::default()
+        };
+        let transfer_id = self.distributor.send_file_windowed(
             self.args.target_peer.clone(),
             file_path,
-            Some(self.args.chunk_size),
+            self.args.chunk_size,
+            window,
         ).await?;
 
         info!("🔄 文件传输已启动，ID: {}", transfer_id);
@@ -310,10 +595,52 @@ mod tests {
             chunk_size: 1024,
             port: 9235,
             bootstrap: None,
+            streams: 4,
+            max_window: 64,
         };
 
         let sender = P2PModelSender::new(args);
         assert_eq!(sender.args.node_id, "test_sender");
         assert_eq!(sender.args.target_peer, "test_receiver");
     }
+
+    #[test]
+    fn test_chunk_scheduler_window_and_retransmit() {
+        let config = TransferWindowConfig {
+            streams: 2,
+            initial_window: 2,
+            max_window: 8,
+            ..TransferWindowConfig::default()
+        };
+        // 续传：块 0 已被对端确认
+        let mut scheduler = ChunkScheduler::new(10, &[0], config);
+        let start = Instant::now();
+        let batch = scheduler.poll(start);
+        assert_eq!(batch, vec![(1, 0), (2, 1)]);
+        assert!(scheduler.poll(start).is_empty());
+
+        // 确认后慢启动增大窗口
+        scheduler.on_ack(1, start + Duration::from_millis(50));
+        scheduler.on_ack(2, start + Duration::from_millis(50));
+        assert_eq!(scheduler.window(), 4);
+        let batch = scheduler.poll(start + Duration::from_millis(50));
+        assert_eq!(batch.len(), 4);
+
+        // 超时后重传并把窗口减半
+        let late = start + Duration::from_secs(5);
+        assert_eq!(scheduler.on_timeout(late), 4);
+        assert_eq!(scheduler.window(), 2);
+        assert_eq!(scheduler.retransmits(), 4);
+        let resent: Vec<u32> = scheduler.poll(late).into_iter().map(|(index, _)| index).collect();
+        assert_eq!(resent, vec![3, 4]);
+
+        // 唤醒后在途块全部重发，窗口不变
+        assert_eq!(scheduler.requeue_in_flight(), 2);
+        assert_eq!(scheduler.window(), 2);
+        let resent: Vec<u32> = scheduler.poll(late).into_iter().map(|(index, _)| index).collect();
+        assert_eq!(resent, vec![3, 4]);
+
+        scheduler.mark_acked(&(0..10).collect::<Vec<_>>());
+        assert!(scheduler.is_complete());
+    }
 }
@@ -0,0 +1,236 @@
+//! 线上消息解码
+//!
+//! 所有来自远端的字节（`WrappedMessage`、gossip 帧、文件传输消息）都先经过这里：
+//! 超过长度上限的帧直接拒绝，JSON 嵌套深度在反序列化前线性扫描检查，
+//! 因此解码占用的内存和栈都以帧长度为界，畸形数据只会得到错误而不会让节点崩溃。
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// 解码上限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WireLimits {
+    /// 单帧最大字节数（1MB 文件块按 JSON 数组编码约 4MB，再留出认证信封的余量）
+    pub max_frame_bytes: usize,
+    /// gossip 帧中 topic 的最大字节数
+    pub max_topic_bytes: usize,
+    /// JSON 最大嵌套深度
+    pub max_depth: usize,
+}
+
+impl Default for WireLimits {
+    fn default() -> Self {
+        Self {
+            max_frame_bytes: 16 * 1024 * 1024,
+            max_topic_bytes: 256,
+            max_depth: 64,
+        }
+    }
+}
+
+/// 解码错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WireError {
+    #[error("帧过大: {len} 字节，上限 {max}")]
+    TooLarge { len: usize, max: usize },
+
+    #[error("帧被截断")]
+    Truncated,
+
+    #[error("topic 无效")]
+    InvalidTopic,
+
+    #[error("嵌套超过 {max} 层")]
+    TooDeep { max: usize },
+
+    #[error("消息格式错误: {0}")]
+    Malformed(String),
+}
+
+/// 在限制内反序列化 JSON 消息
+pub fn decode_json<T: DeserializeOwned>(data: &[u8], limits: &WireLimits) -> Result<T, WireError> {
+    if data.len() > limits.max_frame_bytes {
+        return Err(WireError::TooLarge {
+            len: data.len(),
+            max: limits.max_frame_bytes,
+        });
+    }
+    check_depth(data, limits.max_depth)?;
+    serde_json::from_slice(data).map_err(|e| WireError::Malformed(e.to_string()))
+}
+
+/// 编码 gossip 帧：`[topic_len:4][topic][payload]`
+pub fn encode_frame(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(4 + topic.len() + payload.len());
+    frame.extend_from_slice(&(topic.len() as u32).to_be_bytes());
+    frame.extend_from_slice(topic.as_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// 解码 gossip 帧，返回 `(topic, payload)`，不复制数据
+pub fn decode_frame<'a>(frame: &'a [u8], limits: &WireLimits) -> Result<(&'a str, &'a [u8]), WireError> {
+    if frame.len() > limits.max_frame_bytes {
+        return Err(WireError::TooLarge {
+            len: frame.len(),
+            max: limits.max_frame_bytes,
+        });
+    }
+    let (len, rest) = frame.split_first_chunk::<4>().ok_or(WireError::Truncated)?;
+    let topic_len = u32::from_be_bytes(*len) as usize;
+    if topic_len > limits.max_topic_bytes {
+        return Err(WireError::InvalidTopic);
+    }
+    if topic_len > rest.len() {
+        return Err(WireError::Truncated);
+    }
+    let (topic, payload) = rest.split_at(topic_len);
+    let topic = std::str::from_utf8(topic).map_err(|_| WireError::InvalidTopic)?;
+    Ok((topic, payload))
+}
+
+/// 扫描 JSON 的括号嵌套深度（跳过字符串内容），超过上限即返回错误
+fn check_depth(data: &[u8], max_depth: usize) -> Result<(), WireError> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in data {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return Err(WireError::TooDeep { max: max_depth });
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comms::core::AuthenticatedMessage;
+    use crate::comms::p2p::distributor::FileTransferMessage;
+    use crate::comms::transport::WrappedMessage;
+    use crate::consensus::SignedGossip;
+    use proptest::prelude::*;
+
+    fn file_transfer_message() -> impl Strategy<Value = FileTransferMessage> {
+        let id = "[a-z0-9-]{0,24}";
+        prop_oneof![
+            (id, ".{0,32}", any::<u64>(), 1usize..=4 * 1024 * 1024, "[0-9a-f]{64}").prop_map(
+                |(file_id, file_name, file_size, chunk_size, file_hash)| FileTransferMessage::FileRequest {
+                    file_id,
+                    file_name,
+                    file_size,
+                    chunk_size,
+                    file_hash,
+                }
+            ),
+            (id, any::<bool>(), proptest::option::of(".{0,32}")).prop_map(|(file_id, accepted, reason)| {
+                FileTransferMessage::FileResponse {
+                    file_id,
+                    accepted,
+                    reason,
+                }
+            }),
+            (id, any::<u32>(), proptest::collection::vec(any::<u8>(), 0..2048), "[0-9a-f]{64}").prop_map(
+                |(file_id, chunk_index, data, chunk_hash)| FileTransferMessage::FileChunk {
+                    file_id,
+                    chunk_index,
+                    data,
+                    chunk_hash,
+                }
+            ),
+            (id, any::<u32>(), "[0-9a-f]{64}").prop_map(|(file_id, total_chunks, final_hash)| {
+                FileTransferMessage::FileComplete {
+                    file_id,
+                    total_chunks,
+                    final_hash,
+                }
+            }),
+            (id, any::<u32>(), any::<u32>(), 0.0f32..100.0).prop_map(
+                |(file_id, chunks_received, total_chunks, percentage)| FileTransferMessage::ProgressReport {
+                    file_id,
+                    chunks_received,
+                    total_chunks,
+                    percentage,
+                }
+            ),
+            (id, ".{0,64}").prop_map(|(file_id, error)| FileTransferMessage::TransferError { file_id, error }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_file_transfer_and_wrapped_roundtrip(message in file_transfer_message(), sender in ".{0,32}") {
+            let limits = WireLimits::default();
+            let encoded = serde_json::to_vec(&message).unwrap();
+            let decoded: FileTransferMessage = decode_json(&encoded, &limits).unwrap();
+            prop_assert_eq!(serde_json::to_vec(&decoded).unwrap(), encoded.clone());
+
+            let wrapped = WrappedMessage::new("file_transfer".to_string(), sender, encoded);
+            let bytes = wrapped.serialize().unwrap();
+            let unwrapped = WrappedMessage::deserialize(&bytes).unwrap();
+            prop_assert_eq!(unwrapped.serialize().unwrap(), bytes);
+        }
+
+        #[test]
+        fn prop_frame_roundtrip(topic in ".{0,64}", payload in proptest::collection::vec(any::<u8>(), 0..4096)) {
+            let frame = encode_frame(&topic, &payload);
+            let (decoded_topic, decoded_payload) = decode_frame(&frame, &WireLimits::default()).unwrap();
+            prop_assert_eq!(decoded_topic, topic.as_str());
+            prop_assert_eq!(decoded_payload, payload.as_slice());
+        }
+
+        /// 任意字节只会得到错误，不会 panic
+        #[test]
+        fn prop_arbitrary_bytes_never_panic(data in proptest::collection::vec(any::<u8>(), 0..1024)) {
+            let limits = WireLimits::default();
+            let _ = decode_json::<FileTransferMessage>(&data, &limits);
+            let _ = decode_json::<AuthenticatedMessage<SignedGossip>>(&data, &limits);
+            let _ = WrappedMessage::deserialize(&data);
+            let _ = decode_frame(&data, &limits);
+        }
+    }
+
+    #[test]
+    fn test_rejects_oversized_and_deeply_nested() {
+        let limits = WireLimits {
+            max_frame_bytes: 1024,
+            ..WireLimits::default()
+        };
+        let huge = vec![b' '; 2048];
+        assert_eq!(
+            decode_json::<FileTransferMessage>(&huge, &limits).unwrap_err(),
+            WireError::TooLarge { len: 2048, max: 1024 }
+        );
+
+        let nested = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        assert_eq!(
+            decode_json::<serde_json::Value>(nested.as_bytes(), &WireLimits::default()).unwrap_err(),
+            WireError::TooDeep { max: 64 }
+        );
+        // 字符串里的括号不计入深度
+        let quoted = format!("\"{}\"", "[".repeat(100));
+        assert!(decode_json::<String>(quoted.as_bytes(), &WireLimits::default()).is_ok());
+
+        // 声明的 topic 长度超过实际数据
+        let mut frame = encode_frame("topic", b"");
+        frame.truncate(6);
+        assert_eq!(decode_frame(&frame, &limits).unwrap_err(), WireError::Truncated);
+    }
+}
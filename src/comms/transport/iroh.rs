@@ -4,10 +4,10 @@
  */
 
 use anyhow::{anyhow, Result};
-use iroh::{Endpoint, endpoint::Connection, EndpointAddr, PublicKey};
+use iroh::{Endpoint, endpoint::{Connection, ConnectOptions}, EndpointAddr, PublicKey};
 use iroh::endpoint_info::EndpointIdExt;
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use tracing::{info, warn, error, debug};
@@ -16,6 +16,8 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 // 兼容原有的Gossip功能
 use crate::consensus::SignedGossip;
+use super::codec::{decode_json, WireLimits};
+use super::resumption::{HandshakeStats, ResumptionConfig, SessionResumption};
 
 /// Iroh连接配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +32,9 @@ pub struct IrohConnectionConfig {
     pub enable_relay: bool,
     /// 最大并发连接数
     pub max_connections: usize,
+    /// 会话恢复与 0-RTT 配置
+    #[serde(default)]
+    pub resumption: ResumptionConfig,
 }
 
 impl Default for IrohConnectionConfig {
@@ -40,6 +45,7 @@ impl Default for IrohConnectionConfig {
             bootstrap_nodes: vec![],
             enable_relay: true,
             max_connections: 50,
+            resumption: ResumptionConfig::default(),
         }
     }
 }
@@ -52,6 +58,9 @@ pub struct IrohConnectionManager {
     connections: Arc<Mutex<HashMap<String, Connection>>>,
     message_tx: mpsc::Sender<(String, Vec<u8>)>,
     node_id: String,
+    resumption: Arc<SessionResumption>,
+    /// 仍处于 0-RTT 阶段（握手未确认）的出站连接
+    early_peers: Arc<RwLock<HashSet<String>>>,
 }
 
 impl IrohConnectionManager {
@@ -60,9 +69,12 @@ impl IrohConnectionManager {
         info!("🔗 初始化 iroh 连接管理器");
         
         // 创建iroh端点 - 使用正确的API
+        // 票据缓存容量为 0 时 TLS 层不保存会话票据，每次重连都走完整握手
+        let max_tickets = if config.resumption.enabled { config.resumption.max_tickets } else { 0 };
         let endpoint = Endpoint::builder()
             .bind_addr_v4("0.0.0.0:0".parse().unwrap())
             .alpns(vec![b"williw-p2p".to_vec()])  // 设置ALPN协议
+            .max_tls_tickets(max_tickets)
             .bind()
             .await?;
         
@@ -75,6 +87,7 @@ impl IrohConnectionManager {
         
         let (message_tx, _message_rx) = mpsc::channel::<(String, Vec<u8>)>(1000);
         let connections = Arc::new(Mutex::new(HashMap::new()));
+        let resumption = Arc::new(SessionResumption::new(config.resumption.clone()));
         
         Ok(Self {
             endpoint,
@@ -82,6 +95,8 @@ impl IrohConnectionManager {
             connections,
             message_tx,
             node_id,
+            resumption,
+            early_peers: Arc::new(RwLock::new(HashSet::new())),
         })
     }
     
@@ -108,8 +123,55 @@ impl IrohConnectionManager {
             
         // 使用iroh 0.95的正确connect API
         // 需要提供EndpointAddr和ALPN协议
-        match self.endpoint.connect(endpoint_addr, b"williw-p2p").await {
+        let connecting = match self
+            .endpoint
+            .connect_with_opts(endpoint_addr, b"williw-p2p", ConnectOptions::default())
+            .await
+        {
+            Ok(connecting) => connecting,
+            Err(e) => {
+                error!("连接失败: {}", e);
+                return Err(anyhow!("无法连接到节点 {}: {}", peer_addr, e));
+            }
+        };
+
+        // 持有未过期票据的节点先尝试 0-RTT，握手确认前只发送幂等消息
+        let connecting = if self.resumption.should_attempt_0rtt(peer_addr) {
+            match connecting.into_0rtt() {
+                Ok((connection, accepted)) => {
+                    self.early_peers.write().insert(peer_addr.to_string());
+                    self.connections.lock().await.insert(peer_addr.to_string(), connection);
+                    info!("⚡ 已通过 0-RTT 连接到节点: {}", peer_addr);
+
+                    let peer = peer_addr.to_string();
+                    let resumption = self.resumption.clone();
+                    let early_peers = self.early_peers.clone();
+                    tokio::spawn(async move {
+                        let accepted = accepted.await;
+                        resumption.metrics().record_zero_rtt(accepted);
+                        if accepted {
+                            resumption.remember(&peer);
+                        } else {
+                            warn!("⚠️ 节点 {} 拒绝了 0-RTT，已回退到完整握手", peer);
+                        }
+                        early_peers.write().remove(&peer);
+                    });
+                    return Ok(());
+                }
+                // 票据已被 TLS 层淘汰
+                Err(connecting) => {
+                    self.resumption.forget(peer_addr);
+                    connecting
+                }
+            }
+        } else {
+            connecting
+        };
+
+        match connecting.await {
             Ok(connection) => {
+                self.resumption.metrics().record_full();
+                self.resumption.remember(peer_addr);
                 // 存储连接
                 let mut connections = self.connections.lock().await;
                 connections.insert(peer_addr.to_string(), connection);
@@ -117,6 +179,7 @@ impl IrohConnectionManager {
                 Ok(())
             }
             Err(e) => {
+                self.resumption.forget(peer_addr);
                 error!("连接失败: {}", e);
                 Err(anyhow!("无法连接到节点 {}: {}", peer_addr, e))
             }
@@ -127,6 +190,10 @@ impl IrohConnectionManager {
     pub async fn send_message(&self, peer_id: &str, message: Vec<u8>) -> Result<()> {
         debug!("📤 发送消息到 {}: {} bytes", peer_id, message.len());
         
+        // 点对点消息（如文件传输）不保证幂等，等待握手确认后再发送
+        if self.is_early(peer_id) {
+            return Err(anyhow!("到节点 {} 的连接仍处于 0-RTT 阶段", peer_id));
+        }
         let connections = self.connections.lock().await;
         if let Some(connection) = connections.get(peer_id) {
             // 使用iroh的uni流发送真实消息
@@ -162,6 +229,11 @@ impl IrohConnectionManager {
         let mut sent_count = 0;
         
         for (peer_id, connection) in connections.iter() {
+            // gossip 消息由接收端去重，是幂等的；超过早期数据上限时等握手确认后再发
+            if self.is_early(peer_id) && !self.resumption.allow_outgoing_early(true, message.len()) {
+                debug!("⏳ 跳过仍处于 0-RTT 阶段的节点 {}", peer_id);
+                continue;
+            }
             match self.send_via_uni_stream(connection, &message).await {
                 Ok(_) => {
                     sent_count += 1;
@@ -183,14 +255,33 @@ impl IrohConnectionManager {
         if let Some(incoming) = self.endpoint.accept().await {
             match incoming.accept() {
                 Ok(accepting) => {
-                    match accepting.await {
+                    // 客户端携带有效票据时接受 0-RTT，首条消息可能是早期数据
+                    let (connection, early) = match accepting.into_0rtt() {
+                        Ok((connection, _accepted)) => (Ok(connection), true),
+                        Err(accepting) => (accepting.await, false),
+                    };
+                    match connection {
                         Ok(connection) => {
-                            let peer_addr = "incoming_peer".to_string(); // 暂时使用固定字符串
+                            let peer_addr = connection
+                                .remote_id()
+                                .map(|id| id.to_z32())
+                                .unwrap_or_else(|_| "incoming_peer".to_string());
                             info!("🔗 接收到来自 {} 的连接", peer_addr);
+                            if early {
+                                self.resumption.metrics().record_zero_rtt(true);
+                            } else {
+                                self.resumption.metrics().record_full();
+                            }
                             
                             // 尝试从连接接收数据
                             match self.receive_from_connection(&connection).await {
                                 Ok(data) => {
+                                    if early {
+                                        if let Err(e) = self.resumption.check_early_data(&peer_addr, &data) {
+                                            warn!("⚠️ 丢弃来自 {} 的 0-RTT 数据: {}", peer_addr, e);
+                                            return Ok(None);
+                                        }
+                                    }
                                     if !data.is_empty() {
                                         info!("📨 成功接收到 {} 字节的数据", data.len());
                                         return Ok(Some((peer_addr, data)));
@@ -258,6 +349,15 @@ impl IrohConnectionManager {
     pub fn node_id(&self) -> String {
         self.node_id.clone()
     }
+
+    fn is_early(&self, peer_id: &str) -> bool {
+        self.early_peers.read().contains(peer_id)
+    }
+
+    /// 完整握手与会话恢复的计数
+    pub fn handshake_stats(&self) -> HandshakeStats {
+        self.resumption.metrics().snapshot()
+    }
     
     /// 获取连接统计
     pub async fn get_connection_stats(&self) -> ConnectionStats {
@@ -330,21 +430,29 @@ impl WrappedMessage {
         Ok(serde_json::to_vec(self)?)
     }
     
+    /// 按默认上限解码，超长或畸形的数据返回错误
     pub fn deserialize(data: &[u8]) -> Result<Self> {
-        Ok(serde_json::from_slice(data)?)
+        Ok(decode_json(data, &WireLimits::default())?)
     }
 }
 
 /// 兼容原有的QuicGateway接口
 pub struct QuicGateway {
     connection_manager: Arc<IrohConnectionManager>,
-    received_messages: Arc<RwLock<Vec<SignedGossip>>>,
+    /// 收到的原始 gossip 数据（由 CommsHandle 负责认证和解码）
+    received_messages: Arc<RwLock<Vec<Vec<u8>>>>,
 }
 
 impl QuicGateway {
     pub async fn new(bind: std::net::SocketAddr) -> Result<Self> {
+        Self::with_resumption(bind, ResumptionConfig::default()).await
+    }
+
+    /// 使用指定的会话恢复配置创建网关
+    pub async fn with_resumption(bind: std::net::SocketAddr, resumption: ResumptionConfig) -> Result<Self> {
         let config = IrohConnectionConfig {
             bind_addr: bind.to_string(),
+            resumption,
             ..Default::default()
         };
         
@@ -381,25 +489,33 @@ impl QuicGateway {
         None
     }
     
-    pub fn take_received_messages(&self) -> Vec<SignedGossip> {
+    /// 完整握手与会话恢复的计数
+    pub fn handshake_stats(&self) -> HandshakeStats {
+        self.connection_manager.handshake_stats()
+    }
+
+    pub fn take_received_messages(&self) -> Vec<Vec<u8>> {
         std::mem::take(&mut *self.received_messages.write())
     }
 
     pub async fn broadcast(&self, signed: &SignedGossip) -> bool {
         // 将SignedGossip序列化并通过iroh广播
         match serde_json::to_vec(signed) {
-            Ok(data) => {
-                let wrapped_message = WrappedMessage::new(
-                    GOSSIP_MESSAGE_TYPE.to_string(),
-                    self.connection_manager.node_id().to_string(),
-                    data,
-                );
-                
-                match self.connection_manager.broadcast_message(wrapped_message.serialize().unwrap_or_default()).await {
-                    Ok(count) => count > 0,
-                    Err(_) => false,
-                }
-            }
+            Ok(data) => self.broadcast_bytes(data).await,
+            Err(_) => false,
+        }
+    }
+
+    /// 广播已编码的 gossip 数据（例如带认证信息的消息）
+    pub async fn broadcast_bytes(&self, data: Vec<u8>) -> bool {
+        let wrapped_message = WrappedMessage::new(
+            GOSSIP_MESSAGE_TYPE.to_string(),
+            self.connection_manager.node_id().to_string(),
+            data,
+        );
+
+        match self.connection_manager.broadcast_message(wrapped_message.serialize().unwrap_or_default()).await {
+            Ok(count) => count > 0,
             Err(_) => false,
         }
     }
@@ -3,12 +3,16 @@
  * 包含iroh集成、传输协议等底层传输功能
  */
 
+pub mod codec;
 pub mod iroh;
 pub mod protocol;
+pub mod resumption;
 
 // 重新导出常用类型
 pub use iroh::{
     IrohConnectionManager, IrohConnectionConfig, ConnectionStats, WrappedMessage,
     QuicGateway, FILE_TRANSFER_MESSAGE_TYPE, GOSSIP_MESSAGE_TYPE
 };
+pub use codec::{decode_frame, decode_json, encode_frame, WireError, WireLimits};
+pub use resumption::{EarlyDataError, HandshakeMetrics, HandshakeStats, ResumptionConfig, SessionResumption};
 pub use protocol::{FileTransferProtocol, TransferProtocolConfig, FileIntegrity, ChecksumAlgorithm};
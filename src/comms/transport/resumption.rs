@@ -0,0 +1,299 @@
+//! QUIC 会话恢复与 0-RTT
+//!
+//! 与已知节点重连时复用 TLS 1.3 会话票据，跳过完整握手；握手完成前可以用 0-RTT 先发数据。
+//! 0-RTT 数据可被中间人原样重放，因此：
+//! - 发送端只在 0-RTT 阶段发送幂等的 gossip 消息，且单条不超过 `max_early_data_bytes`
+//! - 接收端对早期数据按摘要去重（重放窗口内只接受一次），并限制每个节点在窗口内的早期消息数
+//!
+//! 票据本身由 TLS 层保存，这里只记录哪些节点持有未过期的票据，用来决定是否尝试 0-RTT。
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResumptionConfig {
+    /// 是否缓存会话票据
+    pub enabled: bool,
+    /// 是否对持有票据的节点尝试 0-RTT
+    pub enable_0rtt: bool,
+    /// 最多缓存的票据数
+    pub max_tickets: usize,
+    /// 票据有效期（秒），与 TLS 层的票据寿命保持一致
+    pub ticket_ttl_secs: u64,
+    /// 单条 0-RTT 消息的最大字节数
+    pub max_early_data_bytes: usize,
+    /// 早期数据的防重放窗口（秒）
+    pub replay_window_secs: u64,
+    /// 窗口内每个节点最多接受的早期消息数
+    pub max_early_messages_per_peer: u32,
+}
+
+impl Default for ResumptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            enable_0rtt: true,
+            max_tickets: 256,
+            ticket_ttl_secs: 2 * 3600,
+            max_early_data_bytes: 16 * 1024,
+            replay_window_secs: 10,
+            max_early_messages_per_peer: 8,
+        }
+    }
+}
+
+/// 握手统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandshakeStats {
+    /// 完整握手次数
+    pub full_handshakes: u64,
+    /// 通过会话票据恢复的连接数
+    pub resumed_connections: u64,
+    /// 服务端接受的 0-RTT 连接数
+    pub zero_rtt_accepted: u64,
+    /// 服务端拒绝 0-RTT、回退到 1-RTT 的连接数
+    pub zero_rtt_rejected: u64,
+    /// 被防重放检查丢弃的早期消息数
+    pub early_data_rejected: u64,
+}
+
+/// 握手计数器，线程安全
+#[derive(Debug, Default)]
+pub struct HandshakeMetrics {
+    full_handshakes: AtomicU64,
+    resumed_connections: AtomicU64,
+    zero_rtt_accepted: AtomicU64,
+    zero_rtt_rejected: AtomicU64,
+    early_data_rejected: AtomicU64,
+}
+
+impl HandshakeMetrics {
+    pub fn record_full(&self) {
+        self.full_handshakes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次 0-RTT 尝试的结果；被拒绝时连接仍会完成 1-RTT 握手
+    pub fn record_zero_rtt(&self, accepted: bool) {
+        if accepted {
+            self.resumed_connections.fetch_add(1, Ordering::Relaxed);
+            self.zero_rtt_accepted.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.full_handshakes.fetch_add(1, Ordering::Relaxed);
+            self.zero_rtt_rejected.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_early_rejected(&self) {
+        self.early_data_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HandshakeStats {
+        HandshakeStats {
+            full_handshakes: self.full_handshakes.load(Ordering::Relaxed),
+            resumed_connections: self.resumed_connections.load(Ordering::Relaxed),
+            zero_rtt_accepted: self.zero_rtt_accepted.load(Ordering::Relaxed),
+            zero_rtt_rejected: self.zero_rtt_rejected.load(Ordering::Relaxed),
+            early_data_rejected: self.early_data_rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 早期数据被拒绝的原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EarlyDataError {
+    #[error("0-RTT 数据过大: {0} 字节")]
+    TooLarge(usize),
+    #[error("0-RTT 数据在重放窗口内重复出现")]
+    Replayed,
+    #[error("节点 {0} 的 0-RTT 消息数超过限制")]
+    RateLimited(String),
+}
+
+struct GuardState {
+    /// 摘要 -> 首次出现时间
+    seen: HashMap<[u8; 32], Instant>,
+    /// 按出现顺序排列的摘要，用于过期清理
+    order: VecDeque<([u8; 32], Instant)>,
+    /// 节点 -> 窗口内接受的早期消息时间
+    per_peer: HashMap<String, VecDeque<Instant>>,
+}
+
+/// 会话票据缓存与 0-RTT 防重放
+pub struct SessionResumption {
+    config: ResumptionConfig,
+    /// 节点 -> 最近一次完成握手（获得票据）的时间，按 LRU 淘汰
+    tickets: Mutex<HashMap<String, Instant>>,
+    guard: Mutex<GuardState>,
+    metrics: HandshakeMetrics,
+}
+
+impl SessionResumption {
+    pub fn new(config: ResumptionConfig) -> Self {
+        Self {
+            config,
+            tickets: Mutex::new(HashMap::new()),
+            guard: Mutex::new(GuardState {
+                seen: HashMap::new(),
+                order: VecDeque::new(),
+                per_peer: HashMap::new(),
+            }),
+            metrics: HandshakeMetrics::default(),
+        }
+    }
+
+    pub fn config(&self) -> &ResumptionConfig {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> &HandshakeMetrics {
+        &self.metrics
+    }
+
+    /// 握手完成后记录节点已持有票据
+    pub fn remember(&self, peer: &str) {
+        self.remember_at(peer, Instant::now());
+    }
+
+    pub fn remember_at(&self, peer: &str, now: Instant) {
+        if !self.config.enabled || self.config.max_tickets == 0 {
+            return;
+        }
+        let mut tickets = self.tickets.lock();
+        tickets.insert(peer.to_string(), now);
+        while tickets.len() > self.config.max_tickets {
+            let Some(oldest) = tickets.iter().min_by_key(|(_, at)| **at).map(|(peer, _)| peer.clone()) else {
+                break;
+            };
+            tickets.remove(&oldest);
+        }
+    }
+
+    /// 票据被服务端拒绝或连接失败时丢弃
+    pub fn forget(&self, peer: &str) {
+        self.tickets.lock().remove(peer);
+    }
+
+    /// 是否对该节点尝试 0-RTT
+    pub fn should_attempt_0rtt(&self, peer: &str) -> bool {
+        self.should_attempt_0rtt_at(peer, Instant::now())
+    }
+
+    pub fn should_attempt_0rtt_at(&self, peer: &str, now: Instant) -> bool {
+        if !self.config.enabled || !self.config.enable_0rtt {
+            return false;
+        }
+        let ttl = Duration::from_secs(self.config.ticket_ttl_secs);
+        let mut tickets = self.tickets.lock();
+        match tickets.get(peer) {
+            Some(at) if now.saturating_duration_since(*at) < ttl => true,
+            Some(_) => {
+                tickets.remove(peer);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// 发送端：0-RTT 阶段只允许发送幂等且不超限的消息
+    pub fn allow_outgoing_early(&self, idempotent: bool, len: usize) -> bool {
+        idempotent && len <= self.config.max_early_data_bytes
+    }
+
+    /// 接收端：检查一条可能为 0-RTT 的消息
+    pub fn check_early_data(&self, peer: &str, data: &[u8]) -> Result<(), EarlyDataError> {
+        self.check_early_data_at(peer, data, Instant::now())
+    }
+
+    pub fn check_early_data_at(&self, peer: &str, data: &[u8], now: Instant) -> Result<(), EarlyDataError> {
+        let result = self.check_inner(peer, data, now);
+        if result.is_err() {
+            self.metrics.record_early_rejected();
+        }
+        result
+    }
+
+    fn check_inner(&self, peer: &str, data: &[u8], now: Instant) -> Result<(), EarlyDataError> {
+        if data.len() > self.config.max_early_data_bytes {
+            return Err(EarlyDataError::TooLarge(data.len()));
+        }
+        let window = Duration::from_secs(self.config.replay_window_secs);
+        let mut guard = self.guard.lock();
+
+        // 清理窗口外的摘要
+        while let Some((digest, at)) = guard.order.front().copied() {
+            if now.saturating_duration_since(at) < window {
+                break;
+            }
+            guard.order.pop_front();
+            guard.seen.remove(&digest);
+        }
+
+        let digest: [u8; 32] = Sha256::digest(data).into();
+        if guard.seen.contains_key(&digest) {
+            return Err(EarlyDataError::Replayed);
+        }
+
+        let limit = self.config.max_early_messages_per_peer as usize;
+        let times = guard.per_peer.entry(peer.to_string()).or_default();
+        while times.front().is_some_and(|at| now.saturating_duration_since(*at) >= window) {
+            times.pop_front();
+        }
+        if times.len() >= limit {
+            return Err(EarlyDataError::RateLimited(peer.to_string()));
+        }
+        times.push_back(now);
+
+        guard.seen.insert(digest, now);
+        guard.order.push_back((digest, now));
+        Ok(())
+    }
+}
+
+impl Default for SessionResumption {
+    fn default() -> Self {
+        Self::new(ResumptionConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tickets_expire_and_early_data_is_replay_protected() {
+        let resumption = SessionResumption::new(ResumptionConfig {
+            max_tickets: 2,
+            max_early_messages_per_peer: 2,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        assert!(!resumption.should_attempt_0rtt_at("a", start));
+        resumption.remember_at("a", start);
+        resumption.remember_at("b", start + Duration::from_secs(1));
+        resumption.remember_at("c", start + Duration::from_secs(2));
+        // 超出容量时淘汰最早的票据
+        assert!(!resumption.should_attempt_0rtt_at("a", start + Duration::from_secs(3)));
+        assert!(resumption.should_attempt_0rtt_at("c", start + Duration::from_secs(3)));
+        assert!(!resumption.should_attempt_0rtt_at("c", start + Duration::from_secs(3 * 3600)));
+
+        assert!(!resumption.allow_outgoing_early(false, 10));
+        assert!(!resumption.allow_outgoing_early(true, 64 * 1024));
+
+        assert_eq!(resumption.check_early_data_at("b", b"m1", start), Ok(()));
+        assert_eq!(resumption.check_early_data_at("b", b"m1", start), Err(EarlyDataError::Replayed));
+        assert_eq!(resumption.check_early_data_at("b", b"m2", start), Ok(()));
+        assert!(matches!(
+            resumption.check_early_data_at("b", b"m3", start),
+            Err(EarlyDataError::RateLimited(_))
+        ));
+        // 窗口过后同一摘要可以再次出现
+        let later = start + Duration::from_secs(11);
+        assert_eq!(resumption.check_early_data_at("b", b"m1", later), Ok(()));
+        assert_eq!(resumption.metrics().snapshot().early_data_rejected, 2);
+    }
+}
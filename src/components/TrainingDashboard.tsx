@@ -139,13 +139,23 @@ export const TrainingDashboard: React.FC = () => {
       loadDeviceInfo();
     }, 60000);
 
-    let unlistenFn: any = null;
+    const unlistenFns: any[] = [];
     
     const setupEventListener = async () => {
       try {
-        unlistenFn = await listen('device_info_refresh', () => {
+        unlistenFns.push(await listen('device_info_refresh', () => {
           loadDeviceInfo();
-        });
+        }));
+        unlistenFns.push(await listen('training-progress', () => {
+          loadTrainingStatus();
+          loadNodeInfo();
+        }));
+        unlistenFns.push(await listen('peer-joined', () => {
+          loadConnectedPeers();
+        }));
+        unlistenFns.push(await listen('peer-left', () => {
+          loadConnectedPeers();
+        }));
       } catch (error) {
         console.warn('Event listener setup failed, using polling only:', error);
       }
@@ -158,9 +168,7 @@ export const TrainingDashboard: React.FC = () => {
       clearInterval(nodeInterval);
       clearInterval(peersInterval);
       clearInterval(deviceInterval);
-      if (unlistenFn) {
-        unlistenFn();
-      }
+      unlistenFns.forEach((unlisten) => unlisten());
     };
   }, []);
 
@@ -33,6 +33,23 @@ impl Default for TrainingConfig {
     }
 }
 
+/// 节点运行模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NodeMode {
+    /// 完整节点：参与训练、接受任务、提供分片
+    #[default]
+    Full,
+    /// 观察模式：只加入 gossip/发现并同步清单、集群视图和索引数据，
+    /// 不接受任务、不提供分片、不签名任何消息
+    WatchOnly,
+}
+
+impl NodeMode {
+    pub fn is_watch_only(&self) -> bool {
+        matches!(self, NodeMode::WatchOnly)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
     pub hide_ip: bool,
@@ -74,7 +91,7 @@ pub struct PrivacyPerformanceConfig {
 }
 
 /// 平衡模式枚举
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub enum BalanceMode {
     Performance,
     Balanced,
@@ -283,6 +300,73 @@ pub struct AppConfig {
     pub device_capabilities: DeviceCapabilities,
     pub security: SecurityConfig,
     pub training: TrainingConfig,
+    /// 实验性功能开关
+    #[serde(default)]
+    pub experiments: crate::experiments::ExperimentsConfig,
+    /// 自适应 tick 间隔控制
+    #[serde(default)]
+    pub tick_controller: crate::device::TickControllerConfig,
+    /// 运行模式（完整节点或观察模式）
+    #[serde(default)]
+    pub node_mode: NodeMode,
+    /// 构建准入策略（拒绝向未知或黑名单构建分配任务）
+    #[serde(default)]
+    pub build_policy: crate::build_info::BuildPolicy,
+    /// 冷存储归档策略
+    #[serde(default)]
+    pub archive: crate::archive::ArchiveConfig,
+    /// 逐节点信任配置（决定对各节点的加密强度）
+    #[serde(default)]
+    pub peer_trust: crate::crypto::TrustConfig,
+    /// 拓扑主邻居上限
+    #[serde(default = "default_max_peers")]
+    pub max_peers: usize,
+    /// 重任务的时间窗口和网络策略
+    #[serde(default)]
+    pub work_schedule: crate::work_schedule::WorkScheduleConfig,
+    /// 内容寻址的本地模型缓存
+    #[serde(default)]
+    pub model_cache: crate::model_cache::ModelCacheConfig,
+    /// 磁盘空间预检、低空间警告和缓存自动淘汰
+    #[serde(default)]
+    pub storage: crate::storage::StorageConfig,
+    /// 训练数据集的签名清单校验
+    #[serde(default)]
+    pub dataset: crate::dataset_manifest::DatasetConfig,
+    /// 训练数据预处理流水线
+    #[serde(default)]
+    pub pipeline: crate::training::PipelineConfig,
+    /// 留出集评估与提前停止
+    #[serde(default)]
+    pub evaluation: crate::training::EvalConfig,
+    /// 冗余执行验证（抽样复制任务并比较输出）
+    #[serde(default)]
+    pub redundancy: crate::consensus::RedundancyConfig,
+    /// 投机解码的目标/草稿模型搭配
+    #[serde(default)]
+    pub speculative: crate::training::SpeculativeConfig,
+    /// 本机运维管理接口
+    #[serde(default)]
+    pub admin: crate::admin::AdminConfig,
+    /// 集群控制器托管
+    #[serde(default)]
+    pub fleet: crate::fleet::FleetConfig,
+    /// 能耗估算与碳排放报告
+    #[serde(default)]
+    pub energy: crate::stats::EnergyConfig,
+    /// 设备资源上限（沙箱限额由此推导）
+    #[serde(default)]
+    pub device: crate::device::DeviceConfig,
+    /// 训练资源沙箱
+    #[serde(default)]
+    pub sandbox: crate::device::SandboxConfig,
+    /// 算力任务市场
+    #[serde(default)]
+    pub marketplace: crate::marketplace::MarketplaceConfig,
+}
+
+fn default_max_peers() -> usize {
+    crate::topology::TopologyConfig::default().max_neighbors
 }
 
 impl AppConfig {
@@ -316,6 +400,10 @@ impl AppConfig {
             enable_dht: true,
             bootstrap_peers_file: Some(std::path::PathBuf::from("bootstrap_peers.txt")),
             security: SecurityConfig::default(),
+            keepalive: crate::comms::KeepaliveConfig::default(),
+            auth: crate::comms::AuthConfig::default(),
+            admission: crate::comms::AdmissionConfig::default(),
+            quic_resumption: crate::comms::ResumptionConfig::default(),
         };
 
         Self {
@@ -325,6 +413,27 @@ impl AppConfig {
             device_capabilities: capabilities,
             security: SecurityConfig::default(),
             training: TrainingConfig::default(),
+            experiments: crate::experiments::ExperimentsConfig::default(),
+            tick_controller: crate::device::TickControllerConfig::default(),
+            node_mode: NodeMode::Full,
+            build_policy: crate::build_info::BuildPolicy::default(),
+            archive: crate::archive::ArchiveConfig::default(),
+            peer_trust: crate::crypto::TrustConfig::default(),
+            max_peers: default_max_peers(),
+            work_schedule: crate::work_schedule::WorkScheduleConfig::default(),
+            model_cache: crate::model_cache::ModelCacheConfig::default(),
+            storage: crate::storage::StorageConfig::default(),
+            dataset: crate::dataset_manifest::DatasetConfig::default(),
+            pipeline: crate::training::PipelineConfig::default(),
+            evaluation: crate::training::EvalConfig::default(),
+            redundancy: crate::consensus::RedundancyConfig::default(),
+            speculative: crate::training::SpeculativeConfig::default(),
+            admin: crate::admin::AdminConfig::default(),
+            fleet: crate::fleet::FleetConfig::default(),
+            energy: crate::stats::EnergyConfig::default(),
+            device: crate::device::DeviceConfig::default(),
+            sandbox: crate::device::SandboxConfig::default(),
+            marketplace: crate::marketplace::MarketplaceConfig::default(),
         }
     }
 }
@@ -341,6 +450,27 @@ impl Default for AppConfig {
             device_capabilities: capabilities,
             security: SecurityConfig::default(),
             training: TrainingConfig::default(),
+            experiments: crate::experiments::ExperimentsConfig::default(),
+            tick_controller: crate::device::TickControllerConfig::default(),
+            node_mode: NodeMode::Full,
+            build_policy: crate::build_info::BuildPolicy::default(),
+            archive: crate::archive::ArchiveConfig::default(),
+            peer_trust: crate::crypto::TrustConfig::default(),
+            max_peers: default_max_peers(),
+            work_schedule: crate::work_schedule::WorkScheduleConfig::default(),
+            model_cache: crate::model_cache::ModelCacheConfig::default(),
+            storage: crate::storage::StorageConfig::default(),
+            dataset: crate::dataset_manifest::DatasetConfig::default(),
+            pipeline: crate::training::PipelineConfig::default(),
+            evaluation: crate::training::EvalConfig::default(),
+            redundancy: crate::consensus::RedundancyConfig::default(),
+            speculative: crate::training::SpeculativeConfig::default(),
+            admin: crate::admin::AdminConfig::default(),
+            fleet: crate::fleet::FleetConfig::default(),
+            energy: crate::stats::EnergyConfig::default(),
+            device: crate::device::DeviceConfig::default(),
+            sandbox: crate::device::SandboxConfig::default(),
+            marketplace: crate::marketplace::MarketplaceConfig::default(),
         }
     }
 }
@@ -414,11 +544,10 @@ impl AppConfig {
         let config: AppConfig = toml::from_str(&content)?;
         
         // 验证配置
-        if let Err(errors) = config.security.validate() {
+        let report = config.check();
+        if !report.is_clean() {
             println!("[配置验证] 发现配置问题：");
-            for error in &errors {
-                println!("  - {}", error);
-            }
+            print!("{}", report);
         }
         
         // 显示隐私建议
@@ -485,27 +614,439 @@ impl AppConfig {
     }
     
     /// 验证整个应用配置
+    ///
+    /// 错误和警告都会返回，警告以“警告：”开头；需要区分级别时使用 [`AppConfig::check`]。
     pub fn validate(&self) -> Result<(), Vec<String>> {
-        let mut errors = Vec::new();
-        
-        // 验证安全配置
-        if let Err(security_errors) = self.security.validate() {
-            errors.extend(security_errors);
+        let report = self.check();
+        if report.is_clean() {
+            return Ok(());
         }
-        
-        // 验证通信配置一致性
+        Err(report
+            .issues
+            .iter()
+            .map(|issue| match issue.severity {
+                IssueSeverity::Error => format!("{}: {}", issue.field, issue.message),
+                IssueSeverity::Warning => format!("警告：{}: {}", issue.field, issue.message),
+            })
+            .collect())
+    }
+
+    /// 检查取值范围和字段间的一致性，返回结构化的错误和警告列表
+    ///
+    /// 只检查配置本身；端口占用等运行环境相关的检查见 [`AppConfig::check_runtime`]。
+    pub fn check(&self) -> ConfigReport {
+        let mut report = ConfigReport::default();
+
+        // 训练参数
+        let training = &self.training;
+        if training.batch_size == 0 {
+            report.error("training.batch_size", "批量大小必须大于 0", "常用取值为 8-64");
+        }
+        if !(training.learning_rate.is_finite() && training.learning_rate > 0.0) {
+            report.error(
+                "training.learning_rate",
+                format!("学习率必须为正数，当前值: {}", training.learning_rate),
+                "常用取值为 1e-5 到 1e-2",
+            );
+        } else if training.learning_rate > 1.0 {
+            report.warning(
+                "training.learning_rate",
+                format!("学习率过大: {}，训练很可能发散", training.learning_rate),
+                "常用取值为 1e-5 到 1e-2",
+            );
+        }
+        if training.model_dim == 0 {
+            report.error("training.model_dim", "模型维度必须大于 0", "与所选模型的隐藏层维度保持一致");
+        }
+        if training.epochs == 0 {
+            report.error("training.epochs", "训练轮数必须大于 0", "至少设置为 1");
+        }
+
+        // 带宽预算
+        let bandwidth = &self.comms.bandwidth;
+        if bandwidth.window_secs == 0 {
+            report.error("comms.bandwidth.window_secs", "带宽预算窗口必须大于 0", "默认值为 60 秒");
+        }
+        if bandwidth.sparse_per_window == 0 {
+            report.error(
+                "comms.bandwidth.sparse_per_window",
+                "每个窗口允许的稀疏更新数为 0，节点将无法发送任何更新",
+                "默认值为 12",
+            );
+        }
+        if bandwidth.dense_bytes_per_window == 0 {
+            report.warning(
+                "comms.bandwidth.dense_bytes_per_window",
+                "稠密快照预算为 0，节点不会广播完整快照",
+                "默认值为 262144（256KB）",
+            );
+        }
+
+        // 拓扑
+        if self.max_peers == 0 {
+            report.error("max_peers", "邻居上限必须大于 0", "默认值为 8");
+        } else if self.max_peers > 64 {
+            report.warning(
+                "max_peers",
+                format!("邻居上限过大: {}，gossip 流量会随之线性增长", self.max_peers),
+                "一般不超过 32",
+            );
+        }
+
+        // tick 控制
+        let tick = &self.tick_controller;
+        if tick.min_interval_ms == 0 {
+            report.error("tick_controller.min_interval_ms", "最小 tick 间隔必须大于 0", "默认值为 50 毫秒");
+        }
+        if tick.min_interval_ms > tick.max_interval_ms {
+            report.error(
+                "tick_controller.max_interval_ms",
+                format!("最大间隔 {}ms 小于最小间隔 {}ms", tick.max_interval_ms, tick.min_interval_ms),
+                "交换两个值或调大 max_interval_ms",
+            );
+        }
+        if !(tick.target_utilization > 0.0 && tick.target_utilization <= 1.0) {
+            report.error(
+                "tick_controller.target_utilization",
+                format!("目标占空比必须在 (0, 1] 之间，当前值: {}", tick.target_utilization),
+                "默认值为 0.6",
+            );
+        }
+
+        // 保活
+        let keepalive = &self.comms.keepalive;
+        if keepalive.enabled {
+            for (name, tunables) in [
+                ("wifi", &keepalive.wifi),
+                ("cellular_5g", &keepalive.cellular_5g),
+                ("cellular_4g", &keepalive.cellular_4g),
+                ("unknown", &keepalive.unknown),
+            ] {
+                if tunables.ping_interval_secs == 0 {
+                    report.error(
+                        format!("comms.keepalive.{}.ping_interval_secs", name),
+                        "ping 间隔必须大于 0",
+                        "或设置 comms.keepalive.enabled = false 关闭保活",
+                    );
+                }
+                if tunables.dead_after_misses <= tunables.suspect_after_misses {
+                    report.error(
+                        format!("comms.keepalive.{}.dead_after_misses", name),
+                        format!(
+                            "判定死亡的丢包数 ({}) 必须大于标记可疑的丢包数 ({})",
+                            tunables.dead_after_misses, tunables.suspect_after_misses
+                        ),
+                        "例如 suspect_after_misses = 2, dead_after_misses = 4",
+                    );
+                }
+            }
+        }
+
+        // 安全与隐私
+        self.check_security(&mut report);
+
+        // 字段间一致性
         if self.security.hide_ip && self.comms.enable_dht {
-            errors.push("启用IP隐藏时应禁用公共DHT (enable_dht = false)".to_string());
+            report.error(
+                "comms.enable_dht",
+                "启用IP隐藏时公共DHT会暴露节点地址",
+                "设置 enable_dht = false",
+            );
         }
-        
         if self.security.use_relay && self.comms.security.relay_nodes.is_empty() {
-            errors.push("启用中继但未配置中继节点".to_string());
+            report.error(
+                "comms.security.relay_nodes",
+                "启用中继但未配置中继节点",
+                "添加至少一个中继节点地址，或设置 security.use_relay = false",
+            );
         }
-        
-        if errors.is_empty() {
-            Ok(())
+        if let Some(bind) = self.comms.quic_bind {
+            if self.comms.quic_bootstrap.iter().any(|peer| {
+                peer.port() == bind.port() && (peer.ip().is_loopback() || peer.ip() == bind.ip())
+            }) {
+                report.warning(
+                    "comms.quic_bootstrap",
+                    format!("bootstrap 列表包含本节点自己的地址 (端口 {})", bind.port()),
+                    "从 quic_bootstrap 中移除本机地址",
+                );
+            }
+        }
+        if self.node_mode.is_watch_only() && self.archive.enabled {
+            report.warning(
+                "archive.enabled",
+                "观察模式不产生训练产物，归档不会有任何效果",
+                "观察模式下可关闭归档",
+            );
+        }
+
+        // 重任务调度窗口
+        let schedule = &self.work_schedule;
+        for (name, windows) in [("windows", &schedule.windows), ("quiet_hours", &schedule.quiet_hours)] {
+            for (i, window) in windows.iter().enumerate() {
+                if let Err(e) = window.bounds() {
+                    report.error(format!("work_schedule.{}[{}]", name, i), e, "使用 24 小时制 HH:MM，例如 22:30");
+                }
+            }
+        }
+        if schedule.enabled && schedule.check_interval_secs == 0 {
+            report.warning("work_schedule.check_interval_secs", "检查间隔为 0，将按 1 秒处理", "默认值为 60 秒");
+        }
+
+        if self.energy.enabled && self.energy.epoch_ticks == 0 {
+            report.error("energy.epoch_ticks", "每个 epoch 的 tick 数必须大于 0", "默认值为 100");
+        }
+        if self.energy.carbon_intensity_g_per_kwh < 0.0 {
+            report.error("energy.carbon_intensity_g_per_kwh", "碳排放强度不能为负数", "全球平均约为 475 gCO2e/kWh");
+        }
+
+        if self.sandbox.enabled {
+            if self.device.max_memory_mb == 0 || self.device.max_cpu_cores == 0 {
+                report.error("device", "启用资源沙箱时内存和 CPU 核心上限必须大于 0", "设置 device.max_memory_mb 和 device.max_cpu_cores");
+            }
+            if self.sandbox.io_bytes_per_sec.is_some() && self.sandbox.io_devices.is_empty() && cfg!(target_os = "linux") {
+                report.warning("sandbox.io_devices", "设置了 IO 上限但未指定块设备，IO 不受限制", "填写块设备号，例如 \"8:0\"");
+            }
+        }
+
+        // 集群托管
+        if self.fleet.enabled {
+            if self.fleet.controller_url.is_empty() {
+                report.error("fleet.controller_url", "启用集群托管但未配置控制器地址", "填写控制器地址，或设置 fleet.enabled = false");
+            }
+            if self.fleet.trusted_controllers.is_empty() {
+                report.error(
+                    "fleet.trusted_controllers",
+                    "未配置受信任控制器，所有管理指令都会被拒绝",
+                    "添加控制器的节点 ID（身份公钥 base58）",
+                );
+            }
+        }
+
+        // 算力任务市场
+        let market = &self.marketplace;
+        if market.enabled {
+            if market.coordinator_url.is_empty() {
+                report.error("marketplace.coordinator_url", "启用任务市场但未配置协调者地址", "填写 Workers 协调者地址，或设置 marketplace.enabled = false");
+            }
+            if market.coordinator_key.is_empty() {
+                report.error("marketplace.coordinator_key", "未配置协调者公钥，无法验证中标通知", "填写协调者签名公钥（base58）");
+            }
+            if market.max_concurrent_jobs == 0 {
+                report.error("marketplace.max_concurrent_jobs", "同时进行的任务上限必须大于 0", "默认值为 1");
+            }
+            if market.electricity_price_per_kwh <= 0.0 && market.min_price == 0 {
+                report.warning(
+                    "marketplace.electricity_price_per_kwh",
+                    "未设置电价和最低报价，将以 0 报价参与竞价",
+                    "填写当地电价（lamports/kWh）或 min_price",
+                );
+            }
+            if !cfg!(feature = "plugins") {
+                report.warning("marketplace", "当前构建未启用 plugins 特性，没有执行市场任务的后端", "使用 --features plugins 构建");
+            }
+        }
+
+        report
+    }
+
+    fn check_security(&self, report: &mut ConfigReport) {
+        let security = &self.security;
+        if security.hide_ip {
+            if security.use_relay && security.relay_nodes.is_empty() {
+                report.error(
+                    "security.relay_nodes",
+                    "启用IP隐藏和中继时，必须提供至少一个中继节点",
+                    "添加中继节点地址",
+                );
+            }
+            if !security.use_relay {
+                report.warning("security.use_relay", "启用IP隐藏但未使用中继，隐私保护可能不完整", "设置 use_relay = true");
+            }
+            if security.enable_dcutr {
+                report.warning("security.enable_dcutr", "启用IP隐藏时使用DCUtR可能暴露IP", "设置 enable_dcutr = false");
+            }
+        }
+        if security.max_hops == 0 || security.max_hops > 5 {
+            report.error(
+                "security.max_hops",
+                format!("中继跳数必须在1-5之间，当前值: {}", security.max_hops),
+                "默认值为 3",
+            );
+        }
+
+        let pp = &security.privacy_performance;
+        let field = |name: &str| format!("security.privacy_performance.{}", name);
+        if !(0.0..=1.0).contains(&pp.performance_weight) {
+            report.error(field("performance_weight"), format!("性能权重必须在0.0-1.0之间，当前值: {}", pp.performance_weight), "默认值为 0.6");
+        }
+        if pp.connection_pool_size == 0 {
+            report.error(field("connection_pool_size"), "连接池大小必须大于0", "默认值为 10");
+        } else if pp.connection_pool_size > 100 {
+            report.warning(field("connection_pool_size"), format!("连接池大小过大: {}", pp.connection_pool_size), "建议不超过100");
+        }
+        if pp.monitoring_interval_secs == 0 {
+            report.error(field("monitoring_interval_secs"), "性能监控间隔必须大于0秒", "默认值为 30 秒");
+        } else if pp.monitoring_interval_secs > 300 {
+            report.warning(field("monitoring_interval_secs"), format!("性能监控间隔过长: {}秒", pp.monitoring_interval_secs), "建议不超过300秒");
+        }
+        if !(0.0..=1.0).contains(&pp.min_privacy_score) {
+            report.error(field("min_privacy_score"), format!("最小隐私评分必须在0.0-1.0之间，当前值: {}", pp.min_privacy_score), "默认值为 0.7");
+        }
+        if !(0.0..=1.0).contains(&pp.min_performance_score) {
+            report.error(field("min_performance_score"), format!("最小性能评分必须在0.0-1.0之间，当前值: {}", pp.min_performance_score), "默认值为 0.8");
+        }
+
+        // 各模式的推荐取值只作为警告
+        match pp.mode {
+            BalanceMode::Adaptive if pp.performance_weight != 0.6 => {
+                report.warning(field("performance_weight"), "自适应模式下性能权重会被自动调整", "保持默认值 0.6");
+            }
+            BalanceMode::Privacy if pp.performance_weight > 0.4 => {
+                report.warning(field("performance_weight"), "隐私优先模式下性能权重偏高", "设置为 0.4 或更低");
+            }
+            BalanceMode::Performance => {
+                if pp.performance_weight < 0.7 {
+                    report.warning(field("performance_weight"), "性能优先模式下性能权重偏低", "设置为 0.7 或更高");
+                }
+                if !pp.enable_hardware_acceleration {
+                    report.warning(field("enable_hardware_acceleration"), "性能优先模式下应启用硬件加速", "设置为 true");
+                }
+                if !pp.enable_0rtt {
+                    report.warning(field("enable_0rtt"), "性能优先模式下应启用0-RTT连接", "设置为 true");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 在 [`AppConfig::check`] 基础上检查运行环境，如 QUIC 端口是否已被占用
+    pub fn check_runtime(&self) -> ConfigReport {
+        let mut report = self.check();
+        if let Some(bind) = self.comms.quic_bind {
+            if bind.port() != 0 {
+                if let Err(e) = std::net::UdpSocket::bind(bind) {
+                    report.error(
+                        "comms.quic_bind",
+                        format!("无法绑定 {}: {}", bind, e),
+                        "端口可能已被其他节点占用，使用 --quic-port 指定其他端口",
+                    );
+                }
+            }
+        }
+        report
+    }
+}
+
+/// 配置问题级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    /// 节点无法正常运行，必须修正
+    Error,
+    /// 可以运行，但很可能不是预期行为
+    Warning,
+}
+
+/// 单个配置问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    /// 配置项路径，如 `training.batch_size`
+    pub field: String,
+    pub message: String,
+    /// 修正建议
+    pub hint: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self.severity {
+            IssueSeverity::Error => "错误",
+            IssueSeverity::Warning => "警告",
+        };
+        write!(f, "[{}] {}: {}（建议：{}）", level, self.field, self.message, self.hint)
+    }
+}
+
+/// 配置检查结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    pub fn error(&mut self, field: impl Into<String>, message: impl Into<String>, hint: impl Into<String>) {
+        self.push(IssueSeverity::Error, field.into(), message.into(), hint.into());
+    }
+
+    pub fn warning(&mut self, field: impl Into<String>, message: impl Into<String>, hint: impl Into<String>) {
+        self.push(IssueSeverity::Warning, field.into(), message.into(), hint.into());
+    }
+
+    fn push(&mut self, severity: IssueSeverity, field: String, message: String, hint: String) {
+        self.issues.push(ConfigIssue { severity, field, message, hint });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// 既没有错误也没有警告
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// 有错误时转换为包含全部问题的错误
+    pub fn into_result(self) -> anyhow::Result<()> {
+        if self.has_errors() {
+            Err(anyhow::anyhow!("配置检查未通过：\n{}", self))
         } else {
-            Err(errors)
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "  - {}", issue)?;
         }
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_passes_check() {
+        let report = AppConfig::default().check();
+        assert!(!report.has_errors(), "{}", report);
+    }
+
+    #[test]
+    fn test_check_reports_ranges_and_cross_field_issues() {
+        let mut config = AppConfig::default();
+        config.training.batch_size = 0;
+        config.tick_controller.min_interval_ms = 500;
+        config.tick_controller.max_interval_ms = 100;
+        config.security.hide_ip = true;
+        config.comms.enable_dht = true;
+
+        let report = config.check();
+        let fields: Vec<_> = report.errors().map(|i| i.field.as_str()).collect();
+        assert!(fields.contains(&"training.batch_size"));
+        assert!(fields.contains(&"tick_controller.max_interval_ms"));
+        assert!(fields.contains(&"comms.enable_dht"));
+        assert!(report.warnings().any(|i| i.field == "security.use_relay"));
+        assert!(report.into_result().is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! 配置文件热更新
+//!
+//! 定期检查 TOML 配置文件的修改时间，文件变化后重新解析并校验。
+//! 只有可安全热更新的字段（带宽预算、隐私模式、邻居上限、重任务调度窗口）会在运行时生效，
+//! 其余改动会在 `ConfigChanged::restart_required` 中列出，需重启节点才能生效。
+//! 校验失败的配置会被整体拒绝，节点继续使用原有配置。
+//! 管理接口等外部来源通过 [`ConfigWatcher::apply`] 下发修改，与文件变化走同一条广播通道。
+
+use crate::comms::BandwidthBudgetConfig;
+use crate::config::{AppConfig, BalanceMode};
+use crate::work_schedule::WorkScheduleConfig;
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+
+/// 可在运行时调整的配置项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeSettings {
+    pub bandwidth: BandwidthBudgetConfig,
+    pub privacy_mode: BalanceMode,
+    pub max_peers: usize,
+    pub work_schedule: WorkScheduleConfig,
+}
+
+impl RuntimeSettings {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            bandwidth: config.comms.bandwidth.clone(),
+            privacy_mode: config.security.privacy_performance.mode.clone(),
+            max_peers: config.max_peers,
+            work_schedule: config.work_schedule.clone(),
+        }
+    }
+
+    /// 把这些配置项写回完整配置
+    pub fn apply_to(&self, config: &mut AppConfig) {
+        config.comms.bandwidth = self.bandwidth.clone();
+        config.security.privacy_performance.mode = self.privacy_mode.clone();
+        config.max_peers = self.max_peers;
+        config.work_schedule = self.work_schedule.clone();
+    }
+}
+
+/// 配置文件变化事件
+#[derive(Debug, Clone)]
+pub struct ConfigChanged {
+    pub previous: RuntimeSettings,
+    pub current: RuntimeSettings,
+    /// 文件中已修改但需要重启才能生效的配置段
+    pub restart_required: Vec<String>,
+}
+
+impl ConfigChanged {
+    pub fn bandwidth_changed(&self) -> bool {
+        self.previous.bandwidth != self.current.bandwidth
+    }
+
+    pub fn privacy_changed(&self) -> bool {
+        self.previous.privacy_mode != self.current.privacy_mode
+    }
+
+    pub fn max_peers_changed(&self) -> bool {
+        self.previous.max_peers != self.current.max_peers
+    }
+
+    pub fn work_schedule_changed(&self) -> bool {
+        self.previous.work_schedule != self.current.work_schedule
+    }
+}
+
+struct WatchState {
+    /// 上次成功加载的文件内容
+    file_config: AppConfig,
+    modified: Option<SystemTime>,
+    settings: RuntimeSettings,
+}
+
+/// 配置文件监视器
+pub struct ConfigWatcher {
+    /// 监视的文件（不关联文件时为 None）
+    path: Option<PathBuf>,
+    poll_interval: Duration,
+    state: Mutex<WatchState>,
+    events: broadcast::Sender<ConfigChanged>,
+}
+
+impl ConfigWatcher {
+    /// 以当前文件内容为基线；`settings` 为节点实际生效的配置项（可能被命令行参数覆盖过）
+    pub fn new(path: impl AsRef<Path>, settings: RuntimeSettings) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file_config = load(&path)?;
+        let (events, _) = broadcast::channel(16);
+        Ok(Self {
+            poll_interval: Duration::from_secs(2),
+            state: Mutex::new(WatchState {
+                file_config,
+                modified: modified_time(&path),
+                settings,
+            }),
+            path: Some(path),
+            events,
+        })
+    }
+
+    /// 不关联配置文件，只接收 [`ConfigWatcher::apply`] 下发的修改；`config` 为节点启动时的配置
+    pub fn detached(config: &AppConfig) -> Self {
+        let (events, _) = broadcast::channel(16);
+        Self {
+            poll_interval: Duration::from_secs(2),
+            state: Mutex::new(WatchState {
+                file_config: config.clone(),
+                modified: None,
+                settings: RuntimeSettings::from_config(config),
+            }),
+            path: None,
+            events,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 订阅配置变化事件
+    pub fn subscribe(&self) -> broadcast::Receiver<ConfigChanged> {
+        self.events.subscribe()
+    }
+
+    /// 当前生效的可热更新配置项
+    pub fn settings(&self) -> RuntimeSettings {
+        self.state.lock().settings.clone()
+    }
+
+    /// 以外部下发的配置项替换生效配置，按完整配置校验通过后广播变化事件
+    pub fn apply(&self, settings: RuntimeSettings) -> Result<Option<ConfigChanged>> {
+        let mut state = self.state.lock();
+        let mut candidate = state.file_config.clone();
+        settings.apply_to(&mut candidate);
+        let report = candidate.check();
+        if report.has_errors() {
+            return Err(anyhow!("配置校验失败，保留原配置：\n{}", report));
+        }
+        if settings == state.settings {
+            return Ok(None);
+        }
+        let previous = std::mem::replace(&mut state.settings, settings.clone());
+        drop(state);
+
+        let change = ConfigChanged {
+            previous,
+            current: settings,
+            restart_required: Vec::new(),
+        };
+        let _ = self.events.send(change.clone());
+        Ok(Some(change))
+    }
+
+    /// 检查文件是否变化；变化且校验通过时广播并返回变化事件
+    pub fn check(&self) -> Result<Option<ConfigChanged>> {
+        let Some(path) = &self.path else {
+            return Ok(None);
+        };
+        let modified = modified_time(path);
+        let mut state = self.state.lock();
+        if modified.is_some() && modified == state.modified {
+            return Ok(None);
+        }
+        state.modified = modified;
+
+        let new_config = load(path)?;
+        let report = new_config.check();
+        if report.has_errors() {
+            return Err(anyhow!("新配置校验失败，保留原配置：\n{}", report));
+        }
+
+        let restart_required = restart_required_sections(&state.file_config, &new_config)?;
+        let file_settings = RuntimeSettings::from_config(&new_config);
+        let previous_file_settings = RuntimeSettings::from_config(&state.file_config);
+        state.file_config = new_config;
+
+        // 只把文件中实际改动的项覆盖到生效配置上，保留命令行等来源的设置
+        let previous = state.settings.clone();
+        let mut current = previous.clone();
+        if file_settings.bandwidth != previous_file_settings.bandwidth {
+            current.bandwidth = file_settings.bandwidth;
+        }
+        if file_settings.privacy_mode != previous_file_settings.privacy_mode {
+            current.privacy_mode = file_settings.privacy_mode;
+        }
+        if file_settings.max_peers != previous_file_settings.max_peers {
+            current.max_peers = file_settings.max_peers;
+        }
+        if file_settings.work_schedule != previous_file_settings.work_schedule {
+            current.work_schedule = file_settings.work_schedule;
+        }
+
+        if current == previous && restart_required.is_empty() {
+            return Ok(None);
+        }
+        state.settings = current.clone();
+        drop(state);
+
+        let change = ConfigChanged {
+            previous,
+            current,
+            restart_required,
+        };
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.events.send(change.clone());
+        Ok(Some(change))
+    }
+
+    /// 启动后台轮询任务
+    pub fn spawn(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let watcher = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(watcher.poll_interval);
+            loop {
+                ticker.tick().await;
+                match watcher.check() {
+                    Ok(Some(change)) => {
+                        if let Some(path) = &watcher.path {
+                            println!("[配置] 检测到配置文件变化: {}", path.display());
+                        }
+                        if !change.restart_required.is_empty() {
+                            println!("[配置] 以下配置段需重启后生效: {}", change.restart_required.join(", "));
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("[配置] 重新加载失败: {}", e),
+                }
+            }
+        })
+    }
+}
+
+fn load(path: &Path) -> Result<AppConfig> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&content)?)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 比较两份配置中除可热更新项外的各顶层配置段
+fn restart_required_sections(old: &AppConfig, new: &AppConfig) -> Result<Vec<String>> {
+    let mut normalized = new.clone();
+    RuntimeSettings::from_config(old).apply_to(&mut normalized);
+    let old = toml::Value::try_from(old)?;
+    let new = toml::Value::try_from(&normalized)?;
+    let (Some(old), Some(new)) = (old.as_table(), new.as_table()) else {
+        return Ok(Vec::new());
+    };
+    let mut sections: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect();
+    sections.sort();
+    sections.dedup();
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_applies_safe_fields_and_rejects_invalid() {
+        let path = std::env::temp_dir().join(format!("williw-config-{}.toml", uuid::Uuid::new_v4()));
+        let mut config = AppConfig::default();
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+
+        let watcher = ConfigWatcher::new(&path, RuntimeSettings::from_config(&config)).unwrap();
+        let mut events = watcher.subscribe();
+
+        config.max_peers = 3;
+        config.security.privacy_performance.mode = BalanceMode::Adaptive;
+        config.training.batch_size += 1;
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        // 修改时间精度可能不足，强制视为已变化
+        watcher.state.lock().modified = None;
+        let change = watcher.check().unwrap().unwrap();
+        assert!(change.max_peers_changed() && change.privacy_changed() && !change.bandwidth_changed());
+        assert_eq!(change.restart_required, vec!["training".to_string()]);
+        assert_eq!(events.try_recv().unwrap().current.max_peers, 3);
+
+        config.max_peers = 0;
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        watcher.state.lock().modified = None;
+        assert!(watcher.check().is_err());
+        assert_eq!(watcher.settings().max_peers, 3);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_apply_validates_and_broadcasts() {
+        let config = AppConfig::default();
+        let watcher = ConfigWatcher::detached(&config);
+        let mut events = watcher.subscribe();
+        assert!(watcher.check().unwrap().is_none());
+
+        let mut settings = watcher.settings();
+        settings.max_peers = 0;
+        assert!(watcher.apply(settings.clone()).is_err());
+
+        settings.max_peers = 5;
+        assert!(watcher.apply(settings.clone()).unwrap().is_some_and(|change| change.max_peers_changed()));
+        assert_eq!(events.try_recv().unwrap().current.max_peers, 5);
+        assert!(watcher.apply(settings).unwrap().is_none());
+    }
+}
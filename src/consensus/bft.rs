@@ -0,0 +1,573 @@
+//! 计算分数的 BFT 共识轮
+//!
+//! 每个 epoch 由已验证节点组成的委员会对计算分数向量达成一致，结果附带法定数量的提交签名后才提交上链。
+//! 流程沿用 PBFT 的三个阶段：
+//! 1. 当前视图的领导者广播提案（分数向量）
+//! 2. 成员确认提案与本地观测一致后广播 prepare 投票，收到法定数量的 prepare 即“已准备”
+//! 3. 已准备的成员广播 commit 投票，收到法定数量的 commit 即达成决定，生成 [`CommitCertificate`]
+//!
+//! 领导者失效时成员超时发起视图切换并附带各自的已准备证书；新领导者收齐法定数量的视图切换消息后
+//! 必须重新提议其中视图最高的已准备向量，因此可能已被决定的值不会被推翻。
+//! 本模块只是状态机，消息的收发和超时驱动由调用方（节点或测试）负责。
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 一个 epoch 的计算分数向量（节点 ID -> 分数）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreVector {
+    pub epoch: u64,
+    pub scores: BTreeMap<String, f64>,
+}
+
+impl ScoreVector {
+    pub fn new(epoch: u64, scores: BTreeMap<String, f64>) -> Self {
+        Self { epoch, scores }
+    }
+
+    /// 向量摘要，投票只针对摘要
+    pub fn digest(&self) -> String {
+        let bytes = serde_json::to_vec(self).unwrap_or_default();
+        blake3::hash(&bytes).to_hex().to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VotePhase {
+    Prepare,
+    Commit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BftMessage {
+    Proposal {
+        epoch: u64,
+        view: u64,
+        vector: ScoreVector,
+    },
+    Vote {
+        epoch: u64,
+        view: u64,
+        phase: VotePhase,
+        digest: String,
+    },
+    ViewChange {
+        epoch: u64,
+        new_view: u64,
+        prepared: Option<PreparedCertificate>,
+    },
+    /// 新领导者进入视图时的提案，附带作为依据的视图切换消息
+    NewView {
+        epoch: u64,
+        view: u64,
+        vector: ScoreVector,
+        view_changes: Vec<SignedBftMessage>,
+    },
+}
+
+impl BftMessage {
+    pub fn epoch(&self) -> u64 {
+        match self {
+            Self::Proposal { epoch, .. }
+            | Self::Vote { epoch, .. }
+            | Self::ViewChange { epoch, .. }
+            | Self::NewView { epoch, .. } => *epoch,
+        }
+    }
+}
+
+/// 由成员身份签名的共识消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedBftMessage {
+    pub message: BftMessage,
+    /// 签名者节点 ID（身份公钥 base58）
+    pub signer: String,
+    pub signature: String,
+}
+
+impl SignedBftMessage {
+    fn signing_bytes(message: &BftMessage) -> Result<Vec<u8>> {
+        let mut bytes = b"williw-bft:".to_vec();
+        bytes.extend_from_slice(&serde_json::to_vec(message)?);
+        Ok(bytes)
+    }
+
+    pub fn sign(message: BftMessage, identity: &NodeIdentity) -> Result<Self> {
+        let signature = identity.sign(&Self::signing_bytes(&message)?);
+        Ok(Self {
+            message,
+            signer: signature.pubkey,
+            signature: signature.signature,
+        })
+    }
+
+    pub fn verify(&self) -> bool {
+        let Ok(bytes) = Self::signing_bytes(&self.message) else {
+            return false;
+        };
+        verify_signature(
+            &bytes,
+            &SolSignature {
+                pubkey: self.signer.clone(),
+                signature: self.signature.clone(),
+            },
+        )
+    }
+}
+
+/// 委员会，成员按节点 ID 排序，领导者按 `(epoch + view)` 轮换
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Committee {
+    members: Vec<String>,
+}
+
+impl Committee {
+    pub fn new(members: impl IntoIterator<Item = String>) -> Self {
+        let mut members: Vec<String> = members.into_iter().collect();
+        members.sort();
+        members.dedup();
+        Self { members }
+    }
+
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    pub fn contains(&self, node_id: &str) -> bool {
+        self.members.binary_search_by(|member| member.as_str().cmp(node_id)).is_ok()
+    }
+
+    /// 可容忍的拜占庭成员数 f
+    pub fn max_faulty(&self) -> usize {
+        self.members.len().saturating_sub(1) / 3
+    }
+
+    /// 法定数量：任意两个法定集合至少有一个诚实成员相交
+    pub fn quorum(&self) -> usize {
+        (self.members.len() + self.max_faulty()) / 2 + 1
+    }
+
+    pub fn leader(&self, epoch: u64, view: u64) -> &str {
+        let index = (epoch.wrapping_add(view) % self.members.len().max(1) as u64) as usize;
+        self.members.get(index).map(String::as_str).unwrap_or_default()
+    }
+}
+
+/// 检查一组投票是否构成对 `(epoch, view, phase, digest)` 的法定证书
+fn is_quorum_of_votes(
+    committee: &Committee,
+    votes: &[SignedBftMessage],
+    epoch: u64,
+    view: u64,
+    phase: VotePhase,
+    digest: &str,
+) -> bool {
+    let mut signers = HashSet::new();
+    for vote in votes {
+        let matches = matches!(
+            &vote.message,
+            BftMessage::Vote { epoch: e, view: v, phase: p, digest: d }
+                if *e == epoch && *v == view && *p == phase && d == digest
+        );
+        if matches && committee.contains(&vote.signer) && vote.verify() {
+            signers.insert(vote.signer.as_str());
+        }
+    }
+    signers.len() >= committee.quorum()
+}
+
+/// 已准备证书：某视图中法定数量的 prepare 投票
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreparedCertificate {
+    pub view: u64,
+    pub vector: ScoreVector,
+    pub votes: Vec<SignedBftMessage>,
+}
+
+impl PreparedCertificate {
+    pub fn verify(&self, committee: &Committee) -> bool {
+        let digest = self.vector.digest();
+        is_quorum_of_votes(committee, &self.votes, self.vector.epoch, self.view, VotePhase::Prepare, &digest)
+    }
+}
+
+/// 提交证书：委员会对分数向量的最终决定，上链前应先 [`verify`](Self::verify)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitCertificate {
+    pub view: u64,
+    pub vector: ScoreVector,
+    pub votes: Vec<SignedBftMessage>,
+}
+
+impl CommitCertificate {
+    pub fn verify(&self, committee: &Committee) -> bool {
+        let digest = self.vector.digest();
+        is_quorum_of_votes(committee, &self.votes, self.vector.epoch, self.view, VotePhase::Commit, &digest)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BftConfig {
+    /// 视图 0 的超时，之后每次视图切换翻倍
+    pub view_timeout: Duration,
+    /// 提案分数与本地观测的最大相对偏差，超出则不投票
+    pub max_score_deviation: f64,
+}
+
+impl Default for BftConfig {
+    fn default() -> Self {
+        Self {
+            view_timeout: Duration::from_secs(10),
+            max_score_deviation: 0.05,
+        }
+    }
+}
+
+/// 单个成员在一个 epoch 内的共识状态
+pub struct BftRound {
+    config: BftConfig,
+    committee: Committee,
+    identity: Arc<NodeIdentity>,
+    node_id: String,
+    /// 本节点观测到的分数向量
+    local: ScoreVector,
+    view: u64,
+    view_started: Instant,
+    /// 当前视图已接受的提案
+    proposal: Option<ScoreVector>,
+    voted: HashSet<(u64, VotePhase)>,
+    prepared: Option<PreparedCertificate>,
+    votes: HashMap<(u64, VotePhase, String), BTreeMap<String, SignedBftMessage>>,
+    view_changes: BTreeMap<u64, BTreeMap<String, SignedBftMessage>>,
+    /// 已请求切换到的最高视图
+    requested_view: u64,
+    new_view_sent: HashSet<u64>,
+    decided: Option<CommitCertificate>,
+}
+
+impl BftRound {
+    pub fn new(config: BftConfig, committee: Committee, identity: Arc<NodeIdentity>, local: ScoreVector) -> Self {
+        Self {
+            node_id: identity.node_id(),
+            config,
+            committee,
+            identity,
+            local,
+            view: 0,
+            view_started: Instant::now(),
+            proposal: None,
+            voted: HashSet::new(),
+            prepared: None,
+            votes: HashMap::new(),
+            view_changes: BTreeMap::new(),
+            requested_view: 0,
+            new_view_sent: HashSet::new(),
+            decided: None,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.local.epoch
+    }
+
+    pub fn view(&self) -> u64 {
+        self.view
+    }
+
+    pub fn committee(&self) -> &Committee {
+        &self.committee
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.committee.leader(self.epoch(), self.view) == self.node_id
+    }
+
+    pub fn decided(&self) -> Option<&CommitCertificate> {
+        self.decided.as_ref()
+    }
+
+    /// 开始本轮：视图 0 的领导者提议本地向量，返回需要广播的消息
+    pub fn start(&mut self) -> Result<Vec<SignedBftMessage>> {
+        self.view_started = Instant::now();
+        if self.view != 0 || !self.is_leader() || self.proposal.is_some() {
+            return Ok(Vec::new());
+        }
+        let proposal = self.sign(BftMessage::Proposal {
+            epoch: self.epoch(),
+            view: 0,
+            vector: self.local.clone(),
+        })?;
+        self.broadcast(proposal)
+    }
+
+    /// 当前视图是否已超时（超时时间随视图指数增长）
+    pub fn timed_out(&self, now: Instant) -> bool {
+        let timeout = self.config.view_timeout * 2u32.pow(self.view.min(6) as u32);
+        self.decided.is_none() && now.duration_since(self.view_started) >= timeout
+    }
+
+    /// 超时：请求切换到下一个视图
+    pub fn on_timeout(&mut self) -> Result<Vec<SignedBftMessage>> {
+        if self.decided.is_some() {
+            return Ok(Vec::new());
+        }
+        self.view_started = Instant::now();
+        let target = self.view.max(self.requested_view) + 1;
+        println!("[BFT] epoch {} 视图 {} 超时，请求切换到视图 {}", self.epoch(), self.view, target);
+        self.request_view_change(target)
+    }
+
+    /// 处理收到的消息，返回需要广播的消息
+    pub fn handle(&mut self, signed: SignedBftMessage) -> Result<Vec<SignedBftMessage>> {
+        if !self.committee.contains(&signed.signer) {
+            bail!("{} 不是委员会成员", signed.signer);
+        }
+        if signed.message.epoch() != self.epoch() {
+            bail!("消息属于 epoch {}，当前为 {}", signed.message.epoch(), self.epoch());
+        }
+        if !signed.verify() {
+            bail!("{} 的共识消息签名无效", signed.signer);
+        }
+        if self.decided.is_some() {
+            return Ok(Vec::new());
+        }
+
+        match signed.message.clone() {
+            BftMessage::Proposal { view, vector, .. } => {
+                if view != self.view || signed.signer != self.committee.leader(self.epoch(), view) {
+                    return Ok(Vec::new());
+                }
+                self.accept_proposal(vector, false)
+            }
+            BftMessage::Vote { view, phase, digest, .. } => {
+                self.votes
+                    .entry((view, phase, digest))
+                    .or_default()
+                    .insert(signed.signer.clone(), signed);
+                self.check_progress()
+            }
+            BftMessage::ViewChange { new_view, prepared, .. } => {
+                if new_view <= self.view || prepared.as_ref().is_some_and(|cert| !cert.verify(&self.committee)) {
+                    return Ok(Vec::new());
+                }
+                self.view_changes
+                    .entry(new_view)
+                    .or_default()
+                    .insert(signed.signer.clone(), signed);
+                self.on_view_change(new_view)
+            }
+            BftMessage::NewView {
+                view,
+                vector,
+                view_changes,
+                ..
+            } => {
+                if view <= self.view || signed.signer != self.committee.leader(self.epoch(), view) {
+                    return Ok(Vec::new());
+                }
+                let Some(highest) = self.justify_new_view(view, &view_changes) else {
+                    bail!("视图 {} 的切换依据无效", view);
+                };
+                if highest.as_ref().is_some_and(|cert| cert.vector.digest() != vector.digest()) {
+                    bail!("视图 {} 的新领导者没有重新提议最高的已准备向量", view);
+                }
+                self.enter_view(view);
+                self.accept_proposal(vector, highest.is_some())
+            }
+        }
+    }
+
+    /// 提案与本地观测一致：节点集合相同，每个分数的偏差在允许范围内
+    fn acceptable(&self, vector: &ScoreVector) -> bool {
+        vector.epoch == self.epoch()
+            && vector.scores.len() == self.local.scores.len()
+            && self.local.scores.iter().all(|(node, local)| {
+                vector
+                    .scores
+                    .get(node)
+                    .is_some_and(|score| (score - local).abs() <= self.config.max_score_deviation * local.abs().max(1.0))
+            })
+    }
+
+    /// `justified` 表示提案来自视图切换中已准备的向量，无需再与本地观测比较
+    fn accept_proposal(&mut self, vector: ScoreVector, justified: bool) -> Result<Vec<SignedBftMessage>> {
+        if let Some(current) = &self.proposal {
+            if current.digest() != vector.digest() {
+                println!("[BFT] epoch {} 视图 {} 的领导者发出了冲突提案，忽略", self.epoch(), self.view);
+            }
+            return Ok(Vec::new());
+        }
+        if !justified && !self.acceptable(&vector) {
+            println!("[BFT] epoch {} 视图 {} 的提案与本地观测不符，不投票", self.epoch(), self.view);
+            return Ok(Vec::new());
+        }
+        let digest = vector.digest();
+        self.proposal = Some(vector);
+        self.vote(VotePhase::Prepare, digest)
+    }
+
+    fn vote(&mut self, phase: VotePhase, digest: String) -> Result<Vec<SignedBftMessage>> {
+        // 已请求视图切换后不再参与旧视图
+        if self.requested_view > self.view || !self.voted.insert((self.view, phase)) {
+            return Ok(Vec::new());
+        }
+        let vote = self.sign(BftMessage::Vote {
+            epoch: self.epoch(),
+            view: self.view,
+            phase,
+            digest,
+        })?;
+        self.broadcast(vote)
+    }
+
+    fn collected(&self, phase: VotePhase, digest: &str) -> Vec<SignedBftMessage> {
+        self.votes
+            .get(&(self.view, phase, digest.to_string()))
+            .map(|votes| votes.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn check_progress(&mut self) -> Result<Vec<SignedBftMessage>> {
+        let Some(proposal) = self.proposal.clone() else {
+            return Ok(Vec::new());
+        };
+        let digest = proposal.digest();
+        let quorum = self.committee.quorum();
+        let mut out = Vec::new();
+
+        let prepares = self.collected(VotePhase::Prepare, &digest);
+        let already_prepared = self
+            .prepared
+            .as_ref()
+            .is_some_and(|cert| cert.view == self.view && cert.vector.digest() == digest);
+        if !already_prepared && prepares.len() >= quorum {
+            self.prepared = Some(PreparedCertificate {
+                view: self.view,
+                vector: proposal.clone(),
+                votes: prepares,
+            });
+            out.extend(self.vote(VotePhase::Commit, digest.clone())?);
+        }
+
+        let commits = self.collected(VotePhase::Commit, &digest);
+        if self.decided.is_none() && commits.len() >= quorum {
+            println!(
+                "[BFT] epoch {} 在视图 {} 达成一致: {} 个节点的分数 ({})",
+                self.epoch(),
+                self.view,
+                proposal.scores.len(),
+                &digest[..12]
+            );
+            self.decided = Some(CommitCertificate {
+                view: self.view,
+                vector: proposal,
+                votes: commits,
+            });
+        }
+        Ok(out)
+    }
+
+    fn request_view_change(&mut self, target: u64) -> Result<Vec<SignedBftMessage>> {
+        if target <= self.requested_view || target <= self.view {
+            return Ok(Vec::new());
+        }
+        self.requested_view = target;
+        let view_change = self.sign(BftMessage::ViewChange {
+            epoch: self.epoch(),
+            new_view: target,
+            prepared: self.prepared.clone(),
+        })?;
+        self.broadcast(view_change)
+    }
+
+    fn on_view_change(&mut self, new_view: u64) -> Result<Vec<SignedBftMessage>> {
+        let count = self.view_changes.get(&new_view).map_or(0, BTreeMap::len);
+        let mut out = Vec::new();
+        // 已有 f+1 个成员请求切换，说明至少一个诚实成员超时，跟随切换以免少数成员卡住
+        if count > self.committee.max_faulty() {
+            out.extend(self.request_view_change(new_view)?);
+        }
+
+        let count = self.view_changes.get(&new_view).map_or(0, BTreeMap::len);
+        let is_new_leader = self.committee.leader(self.epoch(), new_view) == self.node_id;
+        if count >= self.committee.quorum() && is_new_leader && self.new_view_sent.insert(new_view) {
+            let view_changes: Vec<SignedBftMessage> = self.view_changes[&new_view].values().cloned().collect();
+            let vector = highest_prepared(&view_changes).map_or_else(|| self.local.clone(), |cert| cert.vector);
+            let new_view_message = self.sign(BftMessage::NewView {
+                epoch: self.epoch(),
+                view: new_view,
+                vector,
+                view_changes,
+            })?;
+            out.extend(self.broadcast(new_view_message)?);
+        }
+        Ok(out)
+    }
+
+    /// 校验新视图的依据，成功时返回其中视图最高的已准备证书
+    fn justify_new_view(&self, view: u64, view_changes: &[SignedBftMessage]) -> Option<Option<PreparedCertificate>> {
+        let mut signers = HashSet::new();
+        for signed in view_changes {
+            let BftMessage::ViewChange { epoch, new_view, prepared } = &signed.message else {
+                return None;
+            };
+            let valid = *epoch == self.epoch()
+                && *new_view == view
+                && self.committee.contains(&signed.signer)
+                && signed.verify()
+                && prepared.as_ref().is_none_or(|cert| cert.verify(&self.committee));
+            if !valid {
+                return None;
+            }
+            signers.insert(signed.signer.as_str());
+        }
+        (signers.len() >= self.committee.quorum()).then(|| highest_prepared(view_changes))
+    }
+
+    fn enter_view(&mut self, view: u64) {
+        println!("[BFT] epoch {} 进入视图 {}（领导者 {}）", self.epoch(), view, self.committee.leader(self.epoch(), view));
+        self.view = view;
+        self.view_started = Instant::now();
+        self.proposal = None;
+    }
+
+    fn sign(&self, message: BftMessage) -> Result<SignedBftMessage> {
+        SignedBftMessage::sign(message, &self.identity)
+    }
+
+    /// 自己发出的消息也按收到处理，返回包括它在内的全部待广播消息
+    fn broadcast(&mut self, signed: SignedBftMessage) -> Result<Vec<SignedBftMessage>> {
+        let mut out = vec![signed.clone()];
+        out.extend(self.handle(signed)?);
+        Ok(out)
+    }
+}
+
+fn highest_prepared(view_changes: &[SignedBftMessage]) -> Option<PreparedCertificate> {
+    view_changes
+        .iter()
+        .filter_map(|signed| match &signed.message {
+            BftMessage::ViewChange { prepared, .. } => prepared.clone(),
+            _ => None,
+        })
+        .max_by_key(|cert| cert.view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quorum_sizes() {
+        let committee = |n: usize| Committee::new((0..n).map(|i| format!("node-{}", i)));
+        assert_eq!((committee(4).max_faulty(), committee(4).quorum()), (1, 3));
+        assert_eq!((committee(5).max_faulty(), committee(5).quorum()), (1, 4));
+        assert_eq!((committee(7).max_faulty(), committee(7).quorum()), (2, 5));
+        assert_eq!(committee(4).leader(1, 2), "node-3");
+    }
+}
@@ -0,0 +1,296 @@
+// Temporarily comment out to fix compilation
+// use crate::crypto::{CryptoSuite, SignatureBundle};
+use crate::crypto::identity::{verify_signature, verify_signatures_batch, KeyRotation, NodeIdentity};
+use crate::crypto::SolSignature;
+use crate::types::GgbMessage;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub mod bft;
+pub mod redundancy;
+pub mod reputation;
+pub mod sortition;
+
+pub use bft::{BftConfig, BftRound, CommitCertificate, Committee, ScoreVector, SignedBftMessage};
+pub use redundancy::{ChallengeSink, RedundancyConfig, RedundancyStats, RedundancyValidator, ReplicaAssignment, ValidationVerdict};
+pub use reputation::{ReputationConfig, ReputationEngine, ReputationEvent};
+pub use sortition::{select_committee, CommitteeParams, CommitteeSeed, CommitteeTicket, SelectedCommittee};
+
+// Temporary mock signature type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockSignature {
+    pub data: Vec<u8>,
+}
+
+#[cfg(feature = "blockchain")]
+use crate::blockchain::BlockchainClient;
+
+#[derive(Clone, Debug)]
+pub struct StakeRecord {
+    pub stake_eth: f64,
+    pub stake_sol: f64,
+    pub reputation: f64,
+    pub last_seen: Instant,
+}
+
+impl StakeRecord {
+    pub fn combined_weight(&self) -> f32 {
+        let stake_component = (self.stake_eth + self.stake_sol).ln_1p() as f32;
+        let rep_component = (self.reputation.max(0.0) as f32).ln_1p();
+        (stake_component + rep_component).clamp(0.0, 5.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedGossip {
+    pub payload: GgbMessage,
+    // pub signature: SignatureBundle,  // Temporarily commented
+    pub signature: Option<MockSignature>,  // Use mock signature temporarily
+    /// 签名者公钥（节点身份，base58）；为空表示未使用持久身份签名
+    #[serde(default)]
+    pub signer: Option<String>,
+    pub staking_score: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConsensusConfig {
+    pub heartbeat_timeout: Duration,
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+pub struct ConsensusEngine {
+    // crypto: Arc<CryptoSuite>,  // Temporarily commented
+    ledger: RwLock<HashMap<String, StakeRecord>>,
+    config: ConsensusConfig,
+    #[cfg(feature = "blockchain")]
+    blockchain_client: Option<Arc<dyn BlockchainClient>>,
+    _crypto_marker: Arc<()>,  // Placeholder to keep type signature compatible
+    identity: Option<Arc<NodeIdentity>>,
+    reputation: Arc<ReputationEngine>,
+}
+
+impl ConsensusEngine {
+    pub fn new(_crypto: Arc<()>, config: ConsensusConfig) -> Self {  // Temporarily use Arc<()> instead of CryptoSuite
+        Self {
+            // crypto,  // Temporarily commented
+            ledger: RwLock::new(HashMap::new()),
+            config,
+            #[cfg(feature = "blockchain")]
+            blockchain_client: None,
+            _crypto_marker: _crypto,
+            identity: None,
+            reputation: Arc::new(ReputationEngine::default()),
+        }
+    }
+
+    /// 使用节点持久身份签名消息
+    pub fn with_identity(mut self, identity: Arc<NodeIdentity>) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+    
+    #[cfg(feature = "blockchain")]
+    pub fn with_blockchain_client(mut self, client: Arc<dyn BlockchainClient>) -> Self {
+        self.blockchain_client = Some(client);
+        self
+    }
+    
+    #[cfg(feature = "blockchain")]
+    /// 从链上查询质押信息（如果配置了区块链客户端）
+    pub async fn query_stake_from_chain(&self, address: &str) -> Option<f64> {
+        if let Some(client) = &self.blockchain_client {
+            client.query_stake(address).await.ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn sign(&self, payload: GgbMessage) -> anyhow::Result<SignedGossip> {
+        let bytes = serde_json::to_vec(&payload)?;
+        // let signature = self.crypto.sign_bytes(&bytes)?;
+        let (signature, signer) = match &self.identity {
+            Some(identity) => {
+                let sig = identity.sign(&bytes);
+                let raw = bs58::decode(&sig.signature).into_vec()?;
+                (Some(MockSignature { data: raw }), Some(sig.pubkey))
+            }
+            // Use mock signature temporarily
+            None => (
+                Some(MockSignature {
+                    data: bytes.clone(), // Simple mock: use the payload bytes as signature
+                }),
+                None,
+            ),
+        };
+        let peer_id = match &payload {
+            GgbMessage::Heartbeat { peer, .. }
+            | GgbMessage::SimilarityProbe { sender: peer, .. }
+            | GgbMessage::SparseUpdate { sender: peer, .. }
+            | GgbMessage::DenseSnapshot { sender: peer, .. }
+            | GgbMessage::Ping { sender: peer, .. }
+            | GgbMessage::Pong { sender: peer, .. }
+            | GgbMessage::TaskAssignment { sender: peer, .. }
+            | GgbMessage::KeyRotation { sender: peer, .. }
+            | GgbMessage::Capability { sender: peer, .. }
+            | GgbMessage::ScoreConsensus { sender: peer, .. }
+            | GgbMessage::StakeAttestation { sender: peer, .. }
+            | GgbMessage::RemoteSigning { sender: peer, .. }
+            | GgbMessage::TaskResult { sender: peer, .. } => peer.clone(),
+        };
+        let staking_score = self
+            .ledger
+            .read()
+            .get(&peer_id)
+            .map(|record| record.combined_weight())
+            .unwrap_or(0.1);
+        Ok(SignedGossip {
+            payload,
+            signature,
+            signer,
+            staking_score,
+        })
+    }
+
+    pub fn verify(&self, msg: &SignedGossip) -> bool {
+        // return self.crypto.verify(&bytes, &msg.signature);
+        let Some(signer) = &msg.signer else {
+            // 未使用持久身份的旧节点：暂时放行
+            return true;
+        };
+        let (Some(signature), Ok(bytes)) = (&msg.signature, serde_json::to_vec(&msg.payload)) else {
+            return false;
+        };
+        verify_signature(
+            &bytes,
+            &SolSignature {
+                pubkey: signer.clone(),
+                signature: bs58::encode(&signature.data).into_string(),
+            },
+        )
+    }
+
+    /// 批量验证 gossip 签名，结果与输入顺序一致（规则同 [`verify`](Self::verify)）
+    pub fn verify_batch(&self, messages: &[&SignedGossip]) -> Vec<bool> {
+        // None：无需验签（旧节点放行）；Some(Err)：格式错误；Some(Ok)：待验证
+        let prepared: Vec<Option<Result<(Vec<u8>, SolSignature), ()>>> = messages
+            .iter()
+            .map(|msg| {
+                let signer = msg.signer.as_ref()?;
+                let (Some(signature), Ok(bytes)) = (&msg.signature, serde_json::to_vec(&msg.payload)) else {
+                    return Some(Err(()));
+                };
+                Some(Ok((
+                    bytes,
+                    SolSignature {
+                        pubkey: signer.clone(),
+                        signature: bs58::encode(&signature.data).into_string(),
+                    },
+                )))
+            })
+            .collect();
+        let items: Vec<(&[u8], &SolSignature)> = prepared
+            .iter()
+            .filter_map(|entry| match entry {
+                Some(Ok((bytes, signature))) => Some((bytes.as_slice(), signature)),
+                _ => None,
+            })
+            .collect();
+        let mut verified = verify_signatures_batch(&items).into_iter();
+        prepared
+            .iter()
+            .map(|entry| match entry {
+                None => true,
+                Some(Err(())) => false,
+                Some(Ok(_)) => verified.next().unwrap_or(false),
+            })
+            .collect()
+    }
+
+    /// 应用对端的密钥轮换：验证后把旧 ID 的信誉迁移到新 ID
+    pub fn apply_key_rotation(&self, rotation: &KeyRotation) -> bool {
+        if !rotation.verify() {
+            return false;
+        }
+        let mut ledger = self.ledger.write();
+        if let Some(mut record) = ledger.remove(&rotation.old_node_id) {
+            record.last_seen = Instant::now();
+            ledger.insert(rotation.new_node_id.clone(), record);
+        }
+        self.reputation.migrate(&rotation.old_node_id, &rotation.new_node_id);
+        true
+    }
+
+    pub fn update_stake(&self, peer: &str, delta_eth: f64, delta_sol: f64, reputation_delta: f64) {
+        let mut ledger = self.ledger.write();
+        let entry = ledger.entry(peer.to_string()).or_insert(StakeRecord {
+            stake_eth: 1.0,
+            stake_sol: 0.1,
+            reputation: 1.0,
+            last_seen: Instant::now(),
+        });
+        entry.stake_eth = (entry.stake_eth + delta_eth).max(0.0);
+        entry.stake_sol = (entry.stake_sol + delta_sol).max(0.0);
+        entry.reputation = (entry.reputation + reputation_delta).max(-1.0);
+        entry.last_seen = Instant::now();
+    }
+
+    pub fn prune_stale(&self) {
+        let mut ledger = self.ledger.write();
+        let deadline = Instant::now() - self.config.heartbeat_timeout;
+        ledger.retain(|_, record| record.last_seen >= deadline);
+    }
+
+    pub fn stake_weight(&self, peer: &str) -> f32 {
+        self.ledger
+            .read()
+            .get(peer)
+            .map(|record| record.combined_weight())
+            .unwrap_or(0.1)
+    }
+
+    /// 本地信誉引擎（心跳、验证结果、传输失败和举报）
+    pub fn reputation(&self) -> &Arc<ReputationEngine> {
+        &self.reputation
+    }
+
+    /// 委员会抽签的候选权重（账本中权重为正的节点）
+    pub fn committee_weights(&self) -> BTreeMap<String, f64> {
+        self.ledger
+            .read()
+            .iter()
+            .map(|(peer, record)| (peer.clone(), record.combined_weight() as f64))
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect()
+    }
+
+    /// 用节点身份为 epoch 抽签；未配置身份时返回 None
+    pub fn draw_committee_ticket(&self, seed: CommitteeSeed, epoch: u64) -> Option<CommitteeTicket> {
+        self.identity.as_ref().map(|identity| CommitteeTicket::draw(identity, seed, epoch))
+    }
+    
+    #[cfg(feature = "blockchain")]
+    /// 同步链上质押信息到内存账本
+    pub async fn sync_stake_from_chain(&self, peer: &str) {
+        if let Some(stake) = self.query_stake_from_chain(peer).await {
+            let mut ledger = self.ledger.write();
+            let entry = ledger.entry(peer.to_string()).or_insert(StakeRecord {
+                stake_eth: 0.0,
+                stake_sol: 0.0,
+                reputation: 1.0,
+                last_seen: Instant::now(),
+            });
+            entry.stake_eth = stake;
+            entry.last_seen = Instant::now();
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! 冗余执行验证
+//!
+//! 协调者按采样率把一部分任务悄悄复制给另一个节点执行，比较两份输出以发现作弊：
+//! - 是否复制由本地随机盐的带密钥哈希决定，被分配的节点无法预测
+//! - 副本使用由同一密钥派生的任务 ID 下发，外部无法把副本和原任务关联起来
+//! - 两份输出在容差内一致时双方的验证证据加分；不一致时双方都记一次验证失败
+//!   （无法单凭两份输出判断谁作弊），并可对原任务的执行者发起链上质疑，由管理员裁决
+//! - 验证方超时未提交输出时放弃本次比较，不影响信誉
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 冗余验证配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedundancyConfig {
+    pub enabled: bool,
+    /// 被复制的任务比例（0~1）
+    pub sample_rate: f64,
+    /// 输出逐项允许的相对偏差
+    pub tolerance: f32,
+    /// 等待两份输出的最长时间（秒）
+    pub timeout_secs: i64,
+    /// 不一致时是否发起链上质疑
+    pub challenge_on_mismatch: bool,
+}
+
+impl Default for RedundancyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: 0.05,
+            tolerance: 1e-3,
+            timeout_secs: 1800,
+            challenge_on_mismatch: true,
+        }
+    }
+}
+
+/// 下发的副本任务
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicaAssignment {
+    pub replica_task_id: String,
+    pub validator: String,
+}
+
+/// 一次比较的结论
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationVerdict {
+    /// 原任务 ID
+    pub task_id: String,
+    /// 原任务的执行者
+    pub primary: String,
+    /// 副本的执行者
+    pub validator: String,
+    pub matched: bool,
+    /// 最大相对偏差（长度不同时为无穷大）
+    pub max_deviation: f32,
+    /// 两份输出的证据摘要，用作链上质疑的索引
+    pub evidence_hash: [u8; 32],
+}
+
+/// 验证计数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedundancyStats {
+    pub replicated: u64,
+    pub matched: u64,
+    pub mismatched: u64,
+    pub expired: u64,
+}
+
+/// 链上质疑的接收方（如 Solana 提交器）
+///
+/// 在消息处理路径上同步调用，实现方不应阻塞。
+pub trait ChallengeSink: Send + Sync {
+    fn challenge(&self, verdict: &ValidationVerdict);
+}
+
+#[derive(Debug, Clone)]
+struct PendingValidation {
+    primary: String,
+    validator: String,
+    replica_task_id: String,
+    primary_output: Option<Vec<f32>>,
+    validator_output: Option<Vec<f32>>,
+    created_at: i64,
+}
+
+/// 冗余验证器，线程安全
+pub struct RedundancyValidator {
+    config: RedundancyConfig,
+    salt: [u8; 32],
+    /// 原任务 ID -> 等待中的比较
+    pending: Mutex<HashMap<String, PendingValidation>>,
+    /// 副本任务 ID -> 原任务 ID
+    replicas: Mutex<HashMap<String, String>>,
+    stats: RwLock<RedundancyStats>,
+    sinks: RwLock<Vec<Arc<dyn ChallengeSink>>>,
+}
+
+impl RedundancyValidator {
+    pub fn new(config: RedundancyConfig) -> Self {
+        Self {
+            config,
+            salt: rand::random(),
+            pending: Mutex::new(HashMap::new()),
+            replicas: Mutex::new(HashMap::new()),
+            stats: RwLock::new(RedundancyStats::default()),
+            sinks: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn config(&self) -> &RedundancyConfig {
+        &self.config
+    }
+
+    /// 注册链上质疑接收方
+    pub fn add_challenge_sink(&self, sink: Arc<dyn ChallengeSink>) {
+        self.sinks.write().push(sink);
+    }
+
+    fn keyed_hash(&self, domain: &[u8], task_id: &str) -> blake3::Hash {
+        let mut hasher = blake3::Hasher::new_keyed(&self.salt);
+        hasher.update(domain);
+        hasher.update(task_id.as_bytes());
+        hasher.finalize()
+    }
+
+    /// 该任务是否被抽中复制
+    pub fn should_replicate(&self, task_id: &str) -> bool {
+        let hash = self.keyed_hash(b"sample", task_id);
+        let draw = u64::from_le_bytes(hash.as_bytes()[..8].try_into().expect("哈希长度固定"));
+        (draw as f64 / u64::MAX as f64) < self.config.sample_rate
+    }
+
+    /// 为抽中的任务选择验证节点（不在原任务的执行者中）并开始跟踪
+    pub fn plan(&self, task_id: &str, assignees: &[String], candidates: &[String], now: i64) -> Option<ReplicaAssignment> {
+        let primary = match assignees {
+            [primary] => primary,
+            // 多个执行者的任务本身已有冗余，且输出归属不唯一
+            _ => return None,
+        };
+        if !self.should_replicate(task_id) {
+            return None;
+        }
+        let candidates: Vec<&String> = candidates.iter().filter(|c| !assignees.contains(*c)).collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let pick = self.keyed_hash(b"validator", task_id);
+        let index = u64::from_le_bytes(pick.as_bytes()[..8].try_into().expect("哈希长度固定")) as usize % candidates.len();
+        let validator = candidates[index].clone();
+        let replica_task_id = format!("task-{}", &self.keyed_hash(b"replica", task_id).to_hex()[..16]);
+
+        self.replicas.lock().insert(replica_task_id.clone(), task_id.to_string());
+        self.pending.lock().insert(
+            task_id.to_string(),
+            PendingValidation {
+                primary: primary.clone(),
+                validator: validator.clone(),
+                replica_task_id: replica_task_id.clone(),
+                primary_output: None,
+                validator_output: None,
+                created_at: now,
+            },
+        );
+        self.stats.write().replicated += 1;
+        Some(ReplicaAssignment {
+            replica_task_id,
+            validator,
+        })
+    }
+
+    /// 记录节点提交的任务输出；两份输出都到齐时返回比较结论，不相关的输出返回 None
+    pub fn record_output(&self, sender: &str, task_id: &str, output: &[f32]) -> Option<ValidationVerdict> {
+        let replica_of = self.replicas.lock().get(task_id).cloned();
+        let is_replica = replica_of.is_some();
+        let original = replica_of.as_deref().unwrap_or(task_id);
+        let mut pending = self.pending.lock();
+        let entry = pending.get_mut(original)?;
+        if is_replica && sender == entry.validator {
+            entry.validator_output = Some(output.to_vec());
+        } else if !is_replica && sender == entry.primary {
+            entry.primary_output = Some(output.to_vec());
+        } else {
+            // 非指定执行者提交的输出不计入
+            return None;
+        }
+        let (Some(primary_output), Some(validator_output)) = (&entry.primary_output, &entry.validator_output) else {
+            return None;
+        };
+        let max_deviation = max_deviation(primary_output, validator_output);
+        let matched = max_deviation <= self.config.tolerance;
+        let verdict = ValidationVerdict {
+            task_id: original.to_string(),
+            primary: entry.primary.clone(),
+            validator: entry.validator.clone(),
+            matched,
+            max_deviation,
+            evidence_hash: evidence_hash(original, primary_output, validator_output),
+        };
+        let finished = pending.remove(original).expect("条目存在");
+        self.replicas.lock().remove(&finished.replica_task_id);
+        drop(pending);
+
+        let mut stats = self.stats.write();
+        if matched {
+            stats.matched += 1;
+        } else {
+            stats.mismatched += 1;
+        }
+        drop(stats);
+        if !matched && self.config.challenge_on_mismatch {
+            for sink in self.sinks.read().iter() {
+                sink.challenge(&verdict);
+            }
+        }
+        Some(verdict)
+    }
+
+    /// 放弃超时的比较，返回被放弃的原任务 ID
+    pub fn expire(&self, now: i64) -> Vec<String> {
+        let mut pending = self.pending.lock();
+        let expired: Vec<String> = pending
+            .iter()
+            .filter(|(_, entry)| now - entry.created_at > self.config.timeout_secs)
+            .map(|(task_id, _)| task_id.clone())
+            .collect();
+        let mut replicas = self.replicas.lock();
+        for task_id in &expired {
+            if let Some(entry) = pending.remove(task_id) {
+                replicas.remove(&entry.replica_task_id);
+            }
+        }
+        self.stats.write().expired += expired.len() as u64;
+        expired
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().len()
+    }
+
+    pub fn stats(&self) -> RedundancyStats {
+        self.stats.read().clone()
+    }
+}
+
+/// 逐项相对偏差的最大值，分母至少为 1 避免接近 0 的值放大误差
+fn max_deviation(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return f32::INFINITY;
+    }
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| {
+            let deviation = (x - y).abs() / y.abs().max(1.0);
+            if deviation.is_nan() {
+                f32::INFINITY
+            } else {
+                deviation
+            }
+        })
+        .fold(0.0, f32::max)
+}
+
+fn evidence_hash(task_id: &str, primary: &[f32], validator: &[f32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(task_id.as_bytes());
+    for output in [primary, validator] {
+        hasher.update(&(output.len() as u64).to_le_bytes());
+        for value in output {
+            hasher.update(&value.to_le_bytes());
+        }
+    }
+    *hasher.finalize().as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder(Mutex<Vec<ValidationVerdict>>);
+
+    impl ChallengeSink for Recorder {
+        fn challenge(&self, verdict: &ValidationVerdict) {
+            self.0.lock().push(verdict.clone());
+        }
+    }
+
+    fn validator() -> RedundancyValidator {
+        RedundancyValidator::new(RedundancyConfig {
+            enabled: true,
+            sample_rate: 1.0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_replica_outputs_compared_within_tolerance() {
+        let validator = validator();
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        validator.add_challenge_sink(recorder.clone());
+        let candidates = vec!["a".to_string(), "b".to_string()];
+
+        let replica = validator.plan("t1", &["a".to_string()], &candidates, 0).unwrap();
+        assert_eq!(replica.validator, "b");
+        assert_ne!(replica.replica_task_id, "t1");
+
+        // 非指定节点的输出和只到一份的输出都不产生结论
+        assert!(validator.record_output("c", "t1", &[1.0]).is_none());
+        assert!(validator.record_output("a", "t1", &[1.0, 2.0]).is_none());
+        let verdict = validator
+            .record_output("b", &replica.replica_task_id, &[1.0, 2.0005])
+            .unwrap();
+        assert!(verdict.matched);
+        assert!(recorder.0.lock().is_empty());
+
+        let replica = validator.plan("t2", &["a".to_string()], &candidates, 0).unwrap();
+        validator.record_output("b", &replica.replica_task_id, &[1.0, 2.0]);
+        let verdict = validator.record_output("a", "t2", &[1.0, 9.0]).unwrap();
+        assert!(!verdict.matched);
+        assert_eq!(recorder.0.lock().as_slice(), &[verdict]);
+        assert_eq!(validator.pending_count(), 0);
+
+        let stats = validator.stats();
+        assert_eq!((stats.replicated, stats.matched, stats.mismatched), (2, 1, 1));
+    }
+
+    #[test]
+    fn test_sampling_and_expiry() {
+        let validator = RedundancyValidator::new(RedundancyConfig {
+            enabled: true,
+            sample_rate: 0.2,
+            timeout_secs: 60,
+            ..Default::default()
+        });
+        let sampled = (0..2000)
+            .filter(|i| validator.should_replicate(&format!("task-{}", i)))
+            .count();
+        assert!((300..500).contains(&sampled), "sampled {}", sampled);
+
+        let validator = self::validator();
+        let candidates = vec!["a".to_string(), "b".to_string()];
+        // 只有一个候选且就是执行者时无法复制
+        assert!(validator.plan("t1", &["a".to_string()], &candidates[..1], 0).is_none());
+        let replica = validator.plan("t1", &["a".to_string()], &candidates, 0).unwrap();
+        assert!(validator.expire(30).is_empty());
+        assert_eq!(validator.expire(100), vec!["t1".to_string()]);
+        assert!(validator.record_output("b", &replica.replica_task_id, &[1.0]).is_none());
+        assert_eq!(validator.stats().expired, 1);
+    }
+}
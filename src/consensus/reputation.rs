@@ -0,0 +1,264 @@
+//! 本地信誉引擎
+//!
+//! 汇总四类证据并给出 `[0, 1]` 的信誉分：
+//! - 在线：收到的心跳和按心跳间隔推算出的漏报
+//! - 验证：零知识证明或委员会验证的通过/失败
+//! - 传输：文件/分片传输的成功/失败
+//! - 举报：其他节点的指控，同一举报者只计一次，权重取举报者自己的信誉
+//!
+//! 前三类按 Beta 分布均值加权平均（无证据时为 0.5），举报作为乘性惩罚 `exp(-系数 × 权重和)`；
+//! 所有证据按半衰期指数衰减，节点可以通过持续的良好表现恢复信誉。
+//! 分数按 0-1000 导出到 node-management 合约的 `reputation_score`。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 链上信誉分上限
+pub const MAX_ON_CHAIN_SCORE: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReputationConfig {
+    /// 证据半衰期
+    pub half_life: Duration,
+    /// 预期心跳间隔，超出部分计为漏报
+    pub heartbeat_interval: Duration,
+    pub uptime_weight: f64,
+    pub verification_weight: f64,
+    pub transfer_weight: f64,
+    /// 举报惩罚系数
+    pub accusation_penalty: f64,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self {
+            half_life: Duration::from_secs(24 * 3600),
+            heartbeat_interval: Duration::from_secs(60),
+            uptime_weight: 0.35,
+            verification_weight: 0.45,
+            transfer_weight: 0.2,
+            accusation_penalty: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReputationEvent {
+    Heartbeat,
+    Verification { passed: bool },
+    Transfer { succeeded: bool },
+    /// 来自 `reporter` 的指控
+    Accusation { reporter: String },
+}
+
+/// 衰减计数的成功/失败对
+#[derive(Debug, Clone, Copy, Default)]
+struct Evidence {
+    good: f64,
+    bad: f64,
+}
+
+impl Evidence {
+    fn decay(&mut self, factor: f64) {
+        self.good *= factor;
+        self.bad *= factor;
+    }
+
+    fn record(&mut self, good: bool, amount: f64) {
+        if good {
+            self.good += amount;
+        } else {
+            self.bad += amount;
+        }
+    }
+
+    /// Beta(1, 1) 先验下的均值
+    fn mean(&self) -> f64 {
+        (self.good + 1.0) / (self.good + self.bad + 2.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PeerReputation {
+    uptime: Evidence,
+    verification: Evidence,
+    transfer: Evidence,
+    /// 举报者 -> 衰减后的指控权重
+    accusations: HashMap<String, f64>,
+    last_heartbeat: Option<Instant>,
+    updated_at: Instant,
+}
+
+impl PeerReputation {
+    fn new(now: Instant) -> Self {
+        Self {
+            uptime: Evidence::default(),
+            verification: Evidence::default(),
+            transfer: Evidence::default(),
+            accusations: HashMap::new(),
+            last_heartbeat: None,
+            updated_at: now,
+        }
+    }
+
+    fn decay_to(&mut self, now: Instant, half_life: Duration) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return;
+        }
+        let factor = 0.5f64.powf(elapsed / half_life.as_secs_f64().max(1.0));
+        self.uptime.decay(factor);
+        self.verification.decay(factor);
+        self.transfer.decay(factor);
+        self.accusations.values_mut().for_each(|weight| *weight *= factor);
+        self.accusations.retain(|_, weight| *weight > 1e-3);
+        self.updated_at = now;
+    }
+}
+
+/// 信誉引擎，线程安全
+pub struct ReputationEngine {
+    config: ReputationConfig,
+    peers: RwLock<HashMap<String, PeerReputation>>,
+}
+
+impl ReputationEngine {
+    pub fn new(config: ReputationConfig) -> Self {
+        Self {
+            config,
+            peers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, peer: &str, event: ReputationEvent) {
+        self.record_at(peer, event, Instant::now());
+    }
+
+    pub fn record_at(&self, peer: &str, event: ReputationEvent, now: Instant) {
+        // 举报权重取举报者当前的信誉，需在获取写锁之前计算
+        let reporter_weight = match &event {
+            ReputationEvent::Accusation { reporter } if reporter == peer => return,
+            ReputationEvent::Accusation { reporter } => self.score_at(reporter, now),
+            _ => 0.0,
+        };
+
+        let mut peers = self.peers.write();
+        let state = peers.entry(peer.to_string()).or_insert_with(|| PeerReputation::new(now));
+        state.decay_to(now, self.config.half_life);
+        match event {
+            ReputationEvent::Heartbeat => {
+                if let Some(last) = state.last_heartbeat {
+                    let gap = now.saturating_duration_since(last).as_secs_f64();
+                    let interval = self.config.heartbeat_interval.as_secs_f64().max(1.0);
+                    let missed = (gap / interval - 1.0).floor().max(0.0);
+                    state.uptime.record(false, missed);
+                }
+                state.uptime.record(true, 1.0);
+                state.last_heartbeat = Some(now);
+            }
+            ReputationEvent::Verification { passed } => state.verification.record(passed, 1.0),
+            ReputationEvent::Transfer { succeeded } => state.transfer.record(succeeded, 1.0),
+            ReputationEvent::Accusation { reporter } => {
+                // 同一举报者重复指控不累加
+                let weight = state.accusations.entry(reporter).or_insert(0.0);
+                *weight = weight.max(reporter_weight);
+            }
+        }
+    }
+
+    /// 当前信誉分，范围 `[0, 1]`；未知节点为 0.5
+    pub fn score(&self, peer: &str) -> f64 {
+        self.score_at(peer, Instant::now())
+    }
+
+    pub fn score_at(&self, peer: &str, now: Instant) -> f64 {
+        let peers = self.peers.read();
+        let Some(state) = peers.get(peer) else {
+            return 0.5;
+        };
+        let mut state = state.clone();
+        state.decay_to(now, self.config.half_life);
+
+        let config = &self.config;
+        let total = config.uptime_weight + config.verification_weight + config.transfer_weight;
+        let weighted = config.uptime_weight * state.uptime.mean()
+            + config.verification_weight * state.verification.mean()
+            + config.transfer_weight * state.transfer.mean();
+        let penalty = (-config.accusation_penalty * state.accusations.values().sum::<f64>()).exp();
+        (weighted / total.max(f64::EPSILON) * penalty).clamp(0.0, 1.0)
+    }
+
+    /// 导出到链上的分数（0-1000）
+    pub fn on_chain_score(&self, peer: &str) -> u32 {
+        (self.score(peer) * MAX_ON_CHAIN_SCORE as f64).round() as u32
+    }
+
+    /// 所有已跟踪节点的链上分数，按节点 ID 排序
+    pub fn snapshot(&self) -> Vec<(String, u32)> {
+        let mut peers: Vec<String> = self.peers.read().keys().cloned().collect();
+        peers.sort();
+        peers
+            .into_iter()
+            .map(|peer| {
+                let score = self.on_chain_score(&peer);
+                (peer, score)
+            })
+            .collect()
+    }
+
+    /// 密钥轮换后把旧 ID 的信誉迁移到新 ID
+    pub fn migrate(&self, old_peer: &str, new_peer: &str) {
+        let mut peers = self.peers.write();
+        if let Some(state) = peers.remove(old_peer) {
+            peers.insert(new_peer.to_string(), state);
+        }
+    }
+}
+
+impl Default for ReputationEngine {
+    fn default() -> Self {
+        Self::new(ReputationConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evidence_moves_score_and_decays() {
+        let engine = ReputationEngine::default();
+        let start = Instant::now();
+        assert_eq!(engine.on_chain_score("node-a"), 500);
+
+        for i in 0..10 {
+            let at = start + Duration::from_secs(60 * i);
+            engine.record_at("good", ReputationEvent::Heartbeat, at);
+            engine.record_at("good", ReputationEvent::Verification { passed: true }, at);
+            engine.record_at("bad", ReputationEvent::Verification { passed: false }, at);
+            engine.record_at("bad", ReputationEvent::Transfer { succeeded: false }, at);
+        }
+        // 心跳中断 30 分钟后恢复，计入漏报
+        engine.record_at("flaky", ReputationEvent::Heartbeat, start);
+        engine.record_at("flaky", ReputationEvent::Heartbeat, start + Duration::from_secs(1800));
+        let now = start + Duration::from_secs(600);
+        let (good, bad) = (engine.score_at("good", now), engine.score_at("bad", now));
+        assert!(good > 0.6 && bad < 0.4, "good={good}, bad={bad}");
+        assert!(engine.score_at("flaky", start + Duration::from_secs(1800)) < 0.5);
+
+        // 举报按举报者信誉计权，自我举报和重复举报无效
+        engine.record_at("good", ReputationEvent::Accusation { reporter: "good".into() }, now);
+        assert_eq!(engine.score_at("good", now), good);
+        engine.record_at("good", ReputationEvent::Accusation { reporter: "bad".into() }, now);
+        let accused = engine.score_at("good", now);
+        engine.record_at("good", ReputationEvent::Accusation { reporter: "bad".into() }, now);
+        assert!(accused < good);
+        assert_eq!(engine.score_at("good", now), accused);
+
+        // 很久之后证据衰减殆尽，回到中性分数
+        let later = now + Duration::from_secs(30 * 24 * 3600);
+        assert!((engine.score_at("bad", later) - 0.5).abs() < 0.01);
+    }
+}
@@ -0,0 +1,198 @@
+//! 基于 VRF 的验证委员会抽签
+//!
+//! 种子取自近期已最终确认的 Solana 区块哈希，出块前无法预知，出块后任何人都能按 slot 查到。
+//! 每个候选节点用身份密钥对 `(种子, epoch)` 计算 VRF 得到抽签票据，按质押独立判定是否当选：
+//! VRF 输出低于 `2^64 * committee_size * stake / total_stake` 即当选，期望人数为 `committee_size`。
+//! 在公布票据前没有人能预测委员会，拿到票据后任何人都能验证每张票并复算出同一个委员会；
+//! 成员的 VRF 证明随验证结果一起提交，合约按链上公布的同一组委员会参数复核当选。
+
+use super::bft::Committee;
+use crate::crypto::identity::NodeIdentity;
+use crate::crypto::vrf::{self, VrfProof};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// 抽签种子：Solana slot 及其区块哈希
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitteeSeed {
+    pub slot: u64,
+    pub blockhash: [u8; 32],
+}
+
+impl CommitteeSeed {
+    /// VRF 输入
+    pub fn alpha(&self, epoch: u64) -> Vec<u8> {
+        let mut alpha = b"williw-committee:".to_vec();
+        alpha.extend_from_slice(&self.blockhash);
+        alpha.extend_from_slice(&self.slot.to_le_bytes());
+        alpha.extend_from_slice(&epoch.to_le_bytes());
+        alpha
+    }
+}
+
+/// 候选节点的抽签票据
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitteeTicket {
+    pub node_id: String,
+    pub epoch: u64,
+    pub seed: CommitteeSeed,
+    /// VRF 证明（base58）
+    pub proof: String,
+}
+
+impl CommitteeTicket {
+    pub fn draw(identity: &NodeIdentity, seed: CommitteeSeed, epoch: u64) -> Self {
+        Self {
+            node_id: identity.node_id(),
+            epoch,
+            seed,
+            proof: vrf::prove(identity, &seed.alpha(epoch)).to_base58(),
+        }
+    }
+
+    pub fn vrf_proof(&self) -> Result<VrfProof> {
+        VrfProof::from_base58(&self.proof)
+    }
+
+    /// 验证票据，成功时返回 VRF 输出
+    pub fn verify(&self) -> Option<[u8; 64]> {
+        let proof = self.vrf_proof().ok()?;
+        vrf::verify(&self.node_id, &self.seed.alpha(self.epoch), &proof)
+    }
+}
+
+/// 纪元委员会参数，与链上 contribution-tracking 的 `CommitteeConfig` 一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitteeParams {
+    /// 期望的委员会人数
+    pub committee_size: u32,
+    /// 参与抽签节点的总质押
+    pub total_stake: u64,
+}
+
+/// VRF 输出前 8 字节（小端），当选判定和成员排序都用它
+fn draw_value(output: &[u8; 64]) -> u64 {
+    u64::from_le_bytes(output[..8].try_into().expect("长度固定"))
+}
+
+/// 按质押判定是否当选，与合约的 `vrf::is_selected` 逐位一致
+///
+/// 当选概率为 `min(1, committee_size * stake / total_stake)`，全部用整数计算。
+pub fn is_selected(output: &[u8; 64], stake: u64, params: &CommitteeParams) -> bool {
+    if stake == 0 || params.total_stake == 0 {
+        return false;
+    }
+    let expected = stake as u128 * params.committee_size as u128;
+    if expected >= params.total_stake as u128 {
+        return true;
+    }
+    let threshold = (expected << 64) / params.total_stake as u128;
+    (draw_value(output) as u128) < threshold
+}
+
+/// 抽签结果
+#[derive(Debug, Clone)]
+pub struct SelectedCommittee {
+    pub epoch: u64,
+    pub seed: CommitteeSeed,
+    pub committee: Committee,
+    /// 当选成员的票据，按 VRF 输出排序
+    pub tickets: Vec<CommitteeTicket>,
+}
+
+impl SelectedCommittee {
+    pub fn ticket_of(&self, node_id: &str) -> Option<&CommitteeTicket> {
+        self.tickets.iter().find(|ticket| ticket.node_id == node_id)
+    }
+}
+
+/// 从公布的票据中选出委员会
+///
+/// 只接受种子和 epoch 匹配、证明有效且按 `stakes` 中的质押当选的候选节点，同一节点只计一次。
+/// 所有节点对同一组票据调用得到相同结果，与票据顺序无关。
+pub fn select_committee(
+    seed: &CommitteeSeed,
+    epoch: u64,
+    stakes: &BTreeMap<String, u64>,
+    params: &CommitteeParams,
+    tickets: &[CommitteeTicket],
+) -> SelectedCommittee {
+    let mut seen = HashSet::new();
+    let mut ranked: Vec<(u64, &CommitteeTicket)> = tickets
+        .iter()
+        .filter(|ticket| ticket.epoch == epoch && &ticket.seed == seed)
+        .filter_map(|ticket| {
+            let stake = stakes.get(&ticket.node_id).copied()?;
+            let output = ticket.verify()?;
+            (is_selected(&output, stake, params) && seen.insert(ticket.node_id.as_str()))
+                .then(|| (draw_value(&output), ticket))
+        })
+        .collect();
+    ranked.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.node_id.cmp(&b.1.node_id)));
+
+    let tickets: Vec<CommitteeTicket> = ranked.into_iter().map(|(_, ticket)| ticket.clone()).collect();
+    SelectedCommittee {
+        epoch,
+        seed: *seed,
+        committee: Committee::new(tickets.iter().map(|ticket| ticket.node_id.clone())),
+        tickets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::node::MemoryKeystore;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_selection_is_verifiable_and_order_independent() {
+        let seed = CommitteeSeed {
+            slot: 42,
+            blockhash: [7u8; 32],
+        };
+        let identities: Vec<NodeIdentity> = (0..8)
+            .map(|_| NodeIdentity::with_keystore(Arc::new(MemoryKeystore::default())).unwrap())
+            .collect();
+        let stakes: BTreeMap<String, u64> = identities.iter().map(|identity| (identity.node_id(), 1)).collect();
+        let mut tickets: Vec<CommitteeTicket> =
+            identities.iter().map(|identity| CommitteeTicket::draw(identity, seed, 3)).collect();
+
+        // 伪造的票据（证明属于另一个节点）被忽略
+        let mut forged = tickets[1].clone();
+        forged.node_id = tickets[0].node_id.clone();
+        tickets.insert(0, forged);
+
+        // 期望人数不小于总质押时全部当选
+        let everyone = CommitteeParams {
+            committee_size: 8,
+            total_stake: 8,
+        };
+        assert_eq!(select_committee(&seed, 3, &stakes, &everyone, &tickets).committee.members().len(), 8);
+
+        // 期望选出一半：当选与否只取决于各自的 VRF 输出
+        let params = CommitteeParams {
+            committee_size: 4,
+            total_stake: 8,
+        };
+        let selected = select_committee(&seed, 3, &stakes, &params, &tickets);
+        for ticket in &tickets[1..] {
+            let chosen = is_selected(&ticket.verify().unwrap(), 1, &params);
+            assert_eq!(selected.ticket_of(&ticket.node_id).is_some(), chosen);
+        }
+        tickets.reverse();
+        assert_eq!(select_committee(&seed, 3, &stakes, &params, &tickets).committee, selected.committee);
+
+        // 没有质押记录的节点不参与
+        let unstaked = BTreeMap::new();
+        assert!(select_committee(&seed, 3, &unstaked, &everyone, &tickets).tickets.is_empty());
+
+        // 换一个区块哈希，票据不再有效
+        let other_seed = CommitteeSeed {
+            blockhash: [8u8; 32],
+            ..seed
+        };
+        assert!(select_committee(&other_seed, 3, &stakes, &everyone, &tickets).tickets.is_empty());
+    }
+}
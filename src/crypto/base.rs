@@ -21,12 +21,20 @@ pub struct SolSignature {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CryptoConfig {
     pub sol_bs58_seed: Option<String>,
+    /// 节点身份文件路径，为空时使用默认配置目录
+    #[serde(default)]
+    pub identity_path: Option<std::path::PathBuf>,
+    /// 密钥库配置（节点身份和 Solana 支付者私钥）
+    #[serde(default)]
+    pub vault: super::vault::VaultConfig,
 }
 
 impl Default for CryptoConfig {
     fn default() -> Self {
         Self {
             sol_bs58_seed: None,
+            identity_path: None,
+            vault: super::vault::VaultConfig::default(),
         }
     }
 }
@@ -169,6 +177,7 @@ mod tests {
 
         let config = CryptoConfig {
             sol_bs58_seed: Some(sol_seed.to_string()),
+            ..Default::default()
         };
 
         let suite = SolanaCryptoSuite::new(config).unwrap();
@@ -176,6 +185,7 @@ mod tests {
         // 使用相同种子应该生成相同的地址
         let suite2 = SolanaCryptoSuite::new(CryptoConfig {
             sol_bs58_seed: Some(sol_seed.to_string()),
+            ..Default::default()
         }).unwrap();
 
         assert_eq!(suite.sol_address(), suite2.sol_address());
@@ -11,7 +11,7 @@ use crate::crypto::EncryptionAlgorithm;
 impl HighPerformanceCrypto {
     /// 批量加密（并行处理）
     pub fn encrypt_batch(&self, data_chunks: &[&[u8]], key: &[u8], algorithm: EncryptionAlgorithm) -> Result<Vec<Vec<u8>>> {
-        if self.config.enable_parallel_processing && data_chunks.len() > 1 {
+        if self.config.enable_parallel_processing && data_chunks.len() > 1 && crate::parallel::available() {
             self.encrypt_batch_parallel(data_chunks, key, algorithm)
         } else {
             self.encrypt_batch_sequential(data_chunks, key, algorithm)
@@ -20,7 +20,7 @@ impl HighPerformanceCrypto {
     
     /// 批量解密（并行处理）
     pub fn decrypt_batch(&self, encrypted_chunks: &[&[u8]], key: &[u8], algorithm: EncryptionAlgorithm) -> Result<Vec<Vec<u8>>> {
-        if self.config.enable_parallel_processing && encrypted_chunks.len() > 1 {
+        if self.config.enable_parallel_processing && encrypted_chunks.len() > 1 && crate::parallel::available() {
             self.decrypt_batch_parallel(encrypted_chunks, key, algorithm)
         } else {
             self.decrypt_batch_sequential(encrypted_chunks, key, algorithm)
@@ -75,6 +75,7 @@ impl HighPerformanceCrypto {
                 match algorithm {
                     EncryptionAlgorithm::ChaCha20Poly1305 => self.encrypt_chacha20(chunk, &key),
                     EncryptionAlgorithm::Aes256Cbc => self.encrypt_aes256(chunk, &key),
+                    EncryptionAlgorithm::Aes256Gcm => self.encrypt_aes256_gcm(chunk, &key),
                     EncryptionAlgorithm::Blake3 => self.encrypt_blake3(chunk, &key),
                 }
             })
@@ -92,6 +93,7 @@ impl HighPerformanceCrypto {
                 match algorithm {
                     EncryptionAlgorithm::ChaCha20Poly1305 => self.decrypt_chacha20(chunk, &key),
                     EncryptionAlgorithm::Aes256Cbc => self.decrypt_aes256(chunk, &key),
+                    EncryptionAlgorithm::Aes256Gcm => self.decrypt_aes256_gcm(chunk, &key),
                     EncryptionAlgorithm::Blake3 => self.decrypt_blake3(chunk, &key),
                 }
             })
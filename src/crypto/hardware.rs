@@ -1,14 +1,383 @@
 //! 硬件加速加密模块
-//! 
-//! 检测和利用硬件加速功能（如AES-NI、AVX2等）。
+//!
+//! 运行时检测 CPU 加密特性（AES-NI/PCLMULQDQ、AVX2/AVX-512、ARMv8 Crypto Extensions、NEON），
+//! 为 AEAD 选择最快的后端：
+//! - AES-256-GCM：有 AES + 无进位乘法指令时走 ring 的汇编实现，否则走 RustCrypto 的常数时间软件实现
+//! - ChaCha20-Poly1305：aarch64 NEON 走 ring 的汇编实现，x86 AVX2 走 RustCrypto 的 AVX2 后端，其余走可移植实现
+//!
+//! 各后端输出格式相同（`nonce(12) || 密文 || tag(16)`），不同设备之间可以互通。
+//! [`CryptoProfile::measure`] 在本机实测各算法吞吐，结果写入 [`PerformanceMetrics`]，
+//! 自适应模式据此选择隐私级别和优先的 AEAD。
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use rand::RngCore;
+use std::time::{Duration, Instant};
 
-use crate::crypto::{EncryptionAlgorithm, HighPerformanceCrypto};
+use crate::crypto::{EncryptionAlgorithm, HighPerformanceCrypto, PerformanceMetrics, PrivacyLevel, PrivacyPerformanceMetrics};
+
+/// AEAD nonce 长度
+pub const AEAD_NONCE_LEN: usize = 12;
+/// AEAD 认证标签长度
+pub const AEAD_TAG_LEN: usize = 16;
+
+/// 加密吞吐达到该值（MB/s）时视为不构成传输瓶颈
+const REFERENCE_THROUGHPUT_MBPS: f64 = 100.0;
+
+/// 运行时检测到的 CPU 加密特性
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CpuFeatures {
+    /// AES 指令（x86 AES-NI 或 ARMv8 AES）
+    pub aes: bool,
+    /// 无进位乘法（x86 PCLMULQDQ 或 ARMv8 PMULL），GHASH 依赖
+    pub clmul: bool,
+    pub avx2: bool,
+    pub avx512: bool,
+    pub neon: bool,
+}
+
+impl CpuFeatures {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    pub fn detect() -> Self {
+        Self {
+            aes: is_x86_feature_detected!("aes"),
+            clmul: is_x86_feature_detected!("pclmulqdq"),
+            avx2: is_x86_feature_detected!("avx2"),
+            avx512: is_x86_feature_detected!("avx512f"),
+            neon: false,
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    pub fn detect() -> Self {
+        Self {
+            aes: std::arch::is_aarch64_feature_detected!("aes"),
+            clmul: std::arch::is_aarch64_feature_detected!("pmull"),
+            avx2: false,
+            avx512: false,
+            neon: std::arch::is_aarch64_feature_detected!("neon"),
+        }
+    }
+
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    pub fn detect() -> Self {
+        Self::default()
+    }
+
+    /// AES-GCM 是否有完整的硬件支持
+    pub fn has_aes_gcm(&self) -> bool {
+        self.aes && self.clmul
+    }
+
+    /// ChaCha20 是否有向量化实现
+    pub fn has_simd_chacha(&self) -> bool {
+        self.avx2 || self.avx512 || self.neon
+    }
+}
+
+/// AEAD 实现后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum AeadBackend {
+    /// AES-NI / ARMv8 Crypto Extensions（ring）
+    AesHardware,
+    /// 常数时间软件 AES（RustCrypto）
+    AesSoftware,
+    /// AVX2 ChaCha20（RustCrypto，运行时检测）
+    ChaChaAvx2,
+    /// NEON ChaCha20（ring）
+    ChaChaNeon,
+    /// 可移植 ChaCha20（RustCrypto）
+    ChaChaPortable,
+}
+
+/// 按 CPU 特性选定后端的 AEAD
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AeadCipher {
+    algorithm: EncryptionAlgorithm,
+    backend: AeadBackend,
+}
+
+impl AeadCipher {
+    /// 为算法选择当前 CPU 上最快的后端，非 AEAD 算法返回错误
+    pub fn select(features: &CpuFeatures, algorithm: EncryptionAlgorithm) -> Result<Self> {
+        let backend = match algorithm {
+            EncryptionAlgorithm::Aes256Gcm if features.has_aes_gcm() => AeadBackend::AesHardware,
+            EncryptionAlgorithm::Aes256Gcm => AeadBackend::AesSoftware,
+            EncryptionAlgorithm::ChaCha20Poly1305 if features.neon => AeadBackend::ChaChaNeon,
+            EncryptionAlgorithm::ChaCha20Poly1305 if features.avx2 || features.avx512 => AeadBackend::ChaChaAvx2,
+            EncryptionAlgorithm::ChaCha20Poly1305 => AeadBackend::ChaChaPortable,
+            other => return Err(anyhow!("{:?} 不是 AEAD 算法", other)),
+        };
+        Ok(Self { algorithm, backend })
+    }
+
+    /// 检测本机特性后选择后端
+    pub fn detect(algorithm: EncryptionAlgorithm) -> Result<Self> {
+        Self::select(&CpuFeatures::detect(), algorithm)
+    }
+
+    pub fn algorithm(&self) -> EncryptionAlgorithm {
+        self.algorithm
+    }
+
+    pub fn backend(&self) -> AeadBackend {
+        self.backend
+    }
+
+    /// 用随机 nonce 加密，输出 `nonce || 密文 || tag`
+    pub fn seal(&self, key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; AEAD_NONCE_LEN];
+        rand::rng().fill_bytes(&mut nonce);
+        let mut out = Vec::with_capacity(AEAD_NONCE_LEN + plaintext.len() + AEAD_TAG_LEN);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(plaintext);
+
+        match self.backend {
+            AeadBackend::AesHardware | AeadBackend::ChaChaNeon => {
+                let mut body = out.split_off(AEAD_NONCE_LEN);
+                self.ring_key(key)?
+                    .seal_in_place_append_tag(
+                        ring::aead::Nonce::assume_unique_for_key(nonce),
+                        ring::aead::Aad::empty(),
+                        &mut body,
+                    )
+                    .map_err(|_| anyhow!("{:?} 加密失败", self.algorithm))?;
+                out.extend_from_slice(&body);
+            }
+            AeadBackend::AesSoftware => {
+                use aes_gcm::aead::{Aead, KeyInit};
+                let cipher = aes_gcm::Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("AES-256-GCM 密钥必须为 32 字节"))?;
+                let sealed = cipher
+                    .encrypt(aes_gcm::Nonce::from_slice(&nonce), plaintext)
+                    .map_err(|e| anyhow!("AES-256-GCM 加密失败: {}", e))?;
+                out.truncate(AEAD_NONCE_LEN);
+                out.extend_from_slice(&sealed);
+            }
+            AeadBackend::ChaChaAvx2 | AeadBackend::ChaChaPortable => {
+                use chacha20poly1305::aead::{Aead, KeyInit};
+                let cipher = chacha20poly1305::ChaCha20Poly1305::new_from_slice(key)
+                    .map_err(|_| anyhow!("ChaCha20-Poly1305 密钥必须为 32 字节"))?;
+                let sealed = cipher
+                    .encrypt(chacha20poly1305::Nonce::from_slice(&nonce), plaintext)
+                    .map_err(|e| anyhow!("ChaCha20加密失败: {}", e))?;
+                out.truncate(AEAD_NONCE_LEN);
+                out.extend_from_slice(&sealed);
+            }
+        }
+        Ok(out)
+    }
+
+    /// 解密 [`seal`](Self::seal) 的输出
+    pub fn open(&self, key: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < AEAD_NONCE_LEN + AEAD_TAG_LEN {
+            return Err(anyhow!("加密数据太短"));
+        }
+        let (nonce, body) = sealed.split_at(AEAD_NONCE_LEN);
+        match self.backend {
+            AeadBackend::AesHardware | AeadBackend::ChaChaNeon => {
+                let nonce = ring::aead::Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("nonce 长度错误"))?;
+                let mut buffer = body.to_vec();
+                let len = self
+                    .ring_key(key)?
+                    .open_in_place(nonce, ring::aead::Aad::empty(), &mut buffer)
+                    .map_err(|_| anyhow!("{:?} 解密失败", self.algorithm))?
+                    .len();
+                buffer.truncate(len);
+                Ok(buffer)
+            }
+            AeadBackend::AesSoftware => {
+                use aes_gcm::aead::{Aead, KeyInit};
+                let cipher = aes_gcm::Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("AES-256-GCM 密钥必须为 32 字节"))?;
+                cipher
+                    .decrypt(aes_gcm::Nonce::from_slice(nonce), body)
+                    .map_err(|e| anyhow!("AES-256-GCM 解密失败: {}", e))
+            }
+            AeadBackend::ChaChaAvx2 | AeadBackend::ChaChaPortable => {
+                use chacha20poly1305::aead::{Aead, KeyInit};
+                let cipher = chacha20poly1305::ChaCha20Poly1305::new_from_slice(key)
+                    .map_err(|_| anyhow!("ChaCha20-Poly1305 密钥必须为 32 字节"))?;
+                cipher
+                    .decrypt(chacha20poly1305::Nonce::from_slice(nonce), body)
+                    .map_err(|e| anyhow!("ChaCha20解密失败: {}", e))
+            }
+        }
+    }
+
+    fn ring_key(&self, key: &[u8]) -> Result<ring::aead::LessSafeKey> {
+        let algorithm = match self.algorithm {
+            EncryptionAlgorithm::Aes256Gcm => &ring::aead::AES_256_GCM,
+            _ => &ring::aead::CHACHA20_POLY1305,
+        };
+        let key = ring::aead::UnboundKey::new(algorithm, key).map_err(|_| anyhow!("{:?} 密钥必须为 32 字节", self.algorithm))?;
+        Ok(ring::aead::LessSafeKey::new(key))
+    }
+}
+
+/// 单个算法的实测结果
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CryptoBenchmark {
+    pub algorithm: EncryptionAlgorithm,
+    /// AEAD 使用的后端（非 AEAD 算法为 None）
+    pub backend: Option<AeadBackend>,
+    pub payload_bytes: usize,
+    /// 单次加密平均耗时（毫秒）
+    pub encrypt_latency_ms: f64,
+    /// 单次解密平均耗时（毫秒）
+    pub decrypt_latency_ms: f64,
+    /// 加密吞吐（MB/s）
+    pub throughput_mbps: f64,
+}
+
+impl CryptoBenchmark {
+    pub fn to_metrics(&self) -> PerformanceMetrics {
+        PerformanceMetrics {
+            encryption_latency_ms: self.encrypt_latency_ms,
+            decryption_latency_ms: self.decrypt_latency_ms,
+            throughput_mbps: self.throughput_mbps,
+            cpu_usage_percent: 100.0, // 基准测试期间占满一个核心
+            memory_usage_mb: (self.payload_bytes * 2) as f64 / (1024.0 * 1024.0),
+        }
+    }
+}
+
+/// 本机加密性能画像
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CryptoProfile {
+    pub features: CpuFeatures,
+    pub results: Vec<CryptoBenchmark>,
+}
+
+impl CryptoProfile {
+    /// 对各算法实测 `budget` 时间（平均分配），每次处理 `payload_bytes` 字节
+    pub fn measure(payload_bytes: usize, budget: Duration) -> Self {
+        let features = CpuFeatures::detect();
+        let algorithms = [
+            EncryptionAlgorithm::ChaCha20Poly1305,
+            EncryptionAlgorithm::Aes256Gcm,
+            EncryptionAlgorithm::Blake3,
+        ];
+        let per_algorithm = budget / algorithms.len() as u32;
+        let results = algorithms
+            .into_iter()
+            .filter_map(|algorithm| benchmark(&features, algorithm, payload_bytes.max(1), per_algorithm).ok())
+            .collect();
+        Self { features, results }
+    }
+
+    /// 只检测 CPU 特性、不做实测（禁用硬件加速时使用），各级别开销取内置估计值
+    pub fn unmeasured() -> Self {
+        Self {
+            features: CpuFeatures::detect(),
+            results: Vec::new(),
+        }
+    }
+
+    pub fn result(&self, algorithm: EncryptionAlgorithm) -> Option<&CryptoBenchmark> {
+        self.results.iter().find(|result| result.algorithm == algorithm)
+    }
+
+    pub fn performance_metrics(&self, algorithm: EncryptionAlgorithm) -> Option<PerformanceMetrics> {
+        self.result(algorithm).map(CryptoBenchmark::to_metrics)
+    }
+
+    /// 实测最快的 AEAD，未测量时默认 ChaCha20-Poly1305
+    pub fn best_aead(&self) -> EncryptionAlgorithm {
+        self.results
+            .iter()
+            .filter(|result| result.backend.is_some())
+            .max_by(|a, b| a.throughput_mbps.total_cmp(&b.throughput_mbps))
+            .map(|result| result.algorithm)
+            .unwrap_or(EncryptionAlgorithm::ChaCha20Poly1305)
+    }
+
+    /// 按实测吞吐估算各隐私级别的开销
+    ///
+    /// 性能级别只做 Blake3 完整性保护；平衡级别使用最快的 AEAD；
+    /// 最高级别额外填充到 1KB 并经 2–3 跳中继，每跳各加密一次。
+    pub fn privacy_metrics(&self, level: PrivacyLevel) -> PrivacyPerformanceMetrics {
+        let (algorithm, passes, padding, privacy_score) = match level {
+            PrivacyLevel::Performance => (EncryptionAlgorithm::Blake3, 1.0, 1.0, 0.6),
+            PrivacyLevel::Balanced => (self.best_aead(), 1.0, 1.0, 0.8),
+            PrivacyLevel::Maximum => (self.best_aead(), 3.0, 1.3, 0.95),
+        };
+        let Some(result) = self.result(algorithm) else {
+            return HighPerformanceCrypto::with_default_config().get_privacy_performance_metrics(level);
+        };
+        let effective_mbps = result.throughput_mbps / (passes * padding);
+        PrivacyPerformanceMetrics {
+            encryption_overhead_ms: result.encrypt_latency_ms * passes * padding,
+            bandwidth_overhead_percent: (padding - 1.0) * 100.0
+                + (AEAD_NONCE_LEN + AEAD_TAG_LEN) as f64 * passes * 100.0 / result.payload_bytes as f64,
+            privacy_score,
+            performance_score: (effective_mbps / REFERENCE_THROUGHPUT_MBPS).clamp(0.0, 1.0),
+            relay_hops: if level == PrivacyLevel::Maximum { 2 } else { 0 },
+            relay_latency_overhead_ms: 0.0,
+        }
+    }
+
+    /// 满足最低性能评分的最高隐私级别
+    pub fn adaptive_level(&self, min_performance_score: f64) -> PrivacyLevel {
+        [PrivacyLevel::Maximum, PrivacyLevel::Balanced]
+            .into_iter()
+            .find(|level| self.privacy_metrics(*level).performance_score >= min_performance_score)
+            .unwrap_or(PrivacyLevel::Performance)
+    }
+}
+
+fn benchmark(
+    features: &CpuFeatures,
+    algorithm: EncryptionAlgorithm,
+    payload_bytes: usize,
+    budget: Duration,
+) -> Result<CryptoBenchmark> {
+    let crypto = HighPerformanceCrypto::with_default_config();
+    let key = crypto.generate_key(algorithm)?;
+    let payload = vec![0x5au8; payload_bytes];
+    let aead = AeadCipher::select(features, algorithm).ok();
+
+    let encrypt = |data: &[u8]| match &aead {
+        Some(aead) => aead.seal(&key, data),
+        None => crypto.encrypt(data, &key, algorithm),
+    };
+    let decrypt = |data: &[u8]| match &aead {
+        Some(aead) => aead.open(&key, data),
+        None => crypto.decrypt(data, &key, algorithm),
+    };
+
+    // 预热一次，同时确认往返正确
+    let sealed = encrypt(&payload)?;
+    if decrypt(&sealed)? != payload {
+        return Err(anyhow!("{:?} 往返校验失败", algorithm));
+    }
+
+    let start = Instant::now();
+    let mut iterations = 0u32;
+    while iterations == 0 || start.elapsed() < budget {
+        std::hint::black_box(encrypt(&payload)?);
+        iterations += 1;
+    }
+    let encrypt_elapsed = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(decrypt(&sealed)?);
+    }
+    let decrypt_elapsed = start.elapsed();
+
+    let encrypt_secs = encrypt_elapsed.as_secs_f64().max(f64::EPSILON);
+    Ok(CryptoBenchmark {
+        algorithm,
+        backend: aead.map(|aead| aead.backend()),
+        payload_bytes,
+        encrypt_latency_ms: encrypt_elapsed.as_secs_f64() * 1000.0 / iterations as f64,
+        decrypt_latency_ms: decrypt_elapsed.as_secs_f64() * 1000.0 / iterations as f64,
+        throughput_mbps: (payload_bytes as f64 * iterations as f64) / encrypt_secs / 1_000_000.0,
+    })
+}
 
 /// 硬件加速加密检测和支持
 pub struct HardwareAcceleratedCrypto {
     supported_algorithms: Vec<EncryptionAlgorithm>,
+    features: CpuFeatures,
     has_aes_ni: bool,
     has_avx2: bool,
     has_avx512: bool,
@@ -20,89 +389,133 @@ pub struct HardwareInfo {
     pub has_aes_ni: bool,
     pub has_avx2: bool,
     pub has_avx512: bool,
+    /// 完整的 CPU 加密特性（含 ARM）
+    #[serde(default)]
+    pub features: CpuFeatures,
     pub supported_algorithms: Vec<EncryptionAlgorithm>,
 }
 
 impl HardwareAcceleratedCrypto {
     /// 检测系统硬件加速支持
     pub fn detect() -> Self {
+        let features = CpuFeatures::detect();
+
         #[cfg(target_arch = "x86_64")]
-        let has_aes_ni = is_x86_feature_detected!("aes");
-        #[cfg(target_arch = "x86_64")]
-        let has_avx2 = is_x86_feature_detected!("avx2");
-        #[cfg(target_arch = "x86_64")]
-        let has_avx512 = is_x86_feature_detected!("avx512f");
-        
+        let has_aes_ni = features.aes;
         #[cfg(not(target_arch = "x86_64"))]
         let has_aes_ni = false;
-        #[cfg(not(target_arch = "x86_64"))]
-        let has_avx2 = false;
-        #[cfg(not(target_arch = "x86_64"))]
-        let has_avx512 = false;
-        
-        let mut supported_algorithms = vec![EncryptionAlgorithm::ChaCha20Poly1305];
-        
-        if has_aes_ni {
+
+        let mut supported_algorithms = vec![EncryptionAlgorithm::ChaCha20Poly1305, EncryptionAlgorithm::Aes256Gcm];
+
+        if features.aes {
             supported_algorithms.push(EncryptionAlgorithm::Aes256Cbc);
         }
-        
+
         // Blake3 通常有硬件加速优化
         supported_algorithms.push(EncryptionAlgorithm::Blake3);
-        
+
         Self {
             supported_algorithms,
+            features,
             has_aes_ni,
-            has_avx2,
-            has_avx512,
+            has_avx2: features.avx2,
+            has_avx512: features.avx512,
         }
     }
-    
+
     /// 检查算法是否支持硬件加速
     pub fn is_hardware_accelerated(&self, algorithm: EncryptionAlgorithm) -> bool {
         match algorithm {
-            EncryptionAlgorithm::Aes256Cbc => self.has_aes_ni,
-            EncryptionAlgorithm::ChaCha20Poly1305 => self.has_avx2 || self.has_avx512,
-            EncryptionAlgorithm::Blake3 => self.has_avx2 || self.has_avx512,
+            EncryptionAlgorithm::Aes256Cbc => self.features.aes,
+            EncryptionAlgorithm::Aes256Gcm => self.features.has_aes_gcm(),
+            EncryptionAlgorithm::ChaCha20Poly1305 => self.features.has_simd_chacha(),
+            EncryptionAlgorithm::Blake3 => self.features.has_simd_chacha(),
         }
     }
-    
+
     /// 获取支持硬件加速的算法列表
     pub fn get_supported_algorithms(&self) -> &[EncryptionAlgorithm] {
         &self.supported_algorithms
     }
-    
+
+    pub fn features(&self) -> CpuFeatures {
+        self.features
+    }
+
     /// 获取硬件加速信息
     pub fn get_hardware_info(&self) -> HardwareInfo {
         HardwareInfo {
             has_aes_ni: self.has_aes_ni,
             has_avx2: self.has_avx2,
             has_avx512: self.has_avx512,
+            features: self.features,
             supported_algorithms: self.supported_algorithms.clone(),
         }
     }
-    
+
     /// 使用硬件加速加密（如果可用）
+    ///
+    /// AEAD 算法按 CPU 特性分派到对应后端，输出带随机 nonce；其他算法使用软件实现。
     pub fn encrypt_with_hardware(&self, data: &[u8], key: &[u8], algorithm: EncryptionAlgorithm) -> Result<Vec<u8>> {
-        if self.is_hardware_accelerated(algorithm) {
-            // 这里可以调用硬件加速的加密实现
-            // 目前我们使用软件实现，但标记为硬件加速可用
-            let crypto = HighPerformanceCrypto::with_default_config();
-            crypto.encrypt(data, key, algorithm)
-        } else {
-            // 回退到软件实现
-            let crypto = HighPerformanceCrypto::with_default_config();
-            crypto.encrypt(data, key, algorithm)
+        match AeadCipher::select(&self.features, algorithm) {
+            Ok(aead) => aead.seal(key, data),
+            Err(_) => HighPerformanceCrypto::with_default_config().encrypt(data, key, algorithm),
         }
     }
-    
+
     /// 使用硬件加速解密（如果可用）
     pub fn decrypt_with_hardware(&self, encrypted: &[u8], key: &[u8], algorithm: EncryptionAlgorithm) -> Result<Vec<u8>> {
-        if self.is_hardware_accelerated(algorithm) {
-            let crypto = HighPerformanceCrypto::with_default_config();
-            crypto.decrypt(encrypted, key, algorithm)
-        } else {
-            let crypto = HighPerformanceCrypto::with_default_config();
-            crypto.decrypt(encrypted, key, algorithm)
+        match AeadCipher::select(&self.features, algorithm) {
+            Ok(aead) => aead.open(key, encrypted),
+            Err(_) => HighPerformanceCrypto::with_default_config().decrypt(encrypted, key, algorithm),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_interoperate() {
+        let key = [7u8; 32];
+        let plaintext = b"gradient shard".repeat(100);
+        let all = CpuFeatures {
+            aes: true,
+            clmul: true,
+            avx2: false,
+            avx512: false,
+            neon: true,
+        };
+        let none = CpuFeatures::default();
+
+        for algorithm in [EncryptionAlgorithm::Aes256Gcm, EncryptionAlgorithm::ChaCha20Poly1305] {
+            // 硬件后端加密的数据可由软件后端解密，反之亦然
+            let fast = AeadCipher::select(&all, algorithm).unwrap();
+            let portable = AeadCipher::select(&none, algorithm).unwrap();
+            assert_ne!(fast.backend(), portable.backend());
+            assert_eq!(portable.open(&key, &fast.seal(&key, &plaintext).unwrap()).unwrap(), plaintext);
+            let mut sealed = portable.seal(&key, &plaintext).unwrap();
+            assert_eq!(fast.open(&key, &sealed).unwrap(), plaintext);
+            sealed[20] ^= 1;
+            assert!(fast.open(&key, &sealed).is_err());
+        }
+        assert!(AeadCipher::select(&all, EncryptionAlgorithm::Blake3).is_err());
+    }
+
+    #[test]
+    fn test_profile_drives_privacy_level() {
+        let profile = CryptoProfile::measure(4096, Duration::from_millis(30));
+        assert_eq!(profile.results.len(), 3);
+        assert!(profile.results.iter().all(|result| result.throughput_mbps > 0.0));
+        let best = profile.best_aead();
+        assert!(matches!(best, EncryptionAlgorithm::Aes256Gcm | EncryptionAlgorithm::ChaCha20Poly1305));
+
+        // 最高级别的开销不低于平衡级别；性能要求为 0 时总能选到最高级别
+        let balanced = profile.privacy_metrics(PrivacyLevel::Balanced);
+        let maximum = profile.privacy_metrics(PrivacyLevel::Maximum);
+        assert!(maximum.performance_score <= balanced.performance_score);
+        assert_eq!(profile.adaptive_level(0.0), PrivacyLevel::Maximum);
+        assert_eq!(profile.adaptive_level(2.0), PrivacyLevel::Performance);
+    }
+}
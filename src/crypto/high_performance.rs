@@ -18,6 +18,7 @@ use rand::RngCore;
 use aes::cipher::KeyIvInit;
 use aes::cipher::{BlockEncryptMut, BlockDecryptMut};
 
+use crate::crypto::hardware::AeadCipher;
 use crate::crypto::{EncryptionAlgorithm, PrivacyLevel, PerformanceMetrics, PrivacyPerformanceMetrics};
 
 /// 高性能加密引擎配置
@@ -87,6 +88,7 @@ impl HighPerformanceCrypto {
         let key_size = match algorithm {
             EncryptionAlgorithm::ChaCha20Poly1305 => 32, // 256位
             EncryptionAlgorithm::Aes256Cbc => 32,        // 256位
+            EncryptionAlgorithm::Aes256Gcm => 32,        // 256位
             EncryptionAlgorithm::Blake3 => 32,           // 256位
         };
 
@@ -103,6 +105,7 @@ impl HighPerformanceCrypto {
         let result = match algorithm {
             EncryptionAlgorithm::ChaCha20Poly1305 => self.encrypt_chacha20(data, key),
             EncryptionAlgorithm::Aes256Cbc => self.encrypt_aes256(data, key),
+            EncryptionAlgorithm::Aes256Gcm => self.encrypt_aes256_gcm(data, key),
             EncryptionAlgorithm::Blake3 => self.encrypt_blake3(data, key),
         };
         
@@ -119,6 +122,7 @@ impl HighPerformanceCrypto {
         let result = match algorithm {
             EncryptionAlgorithm::ChaCha20Poly1305 => self.decrypt_chacha20(encrypted, key),
             EncryptionAlgorithm::Aes256Cbc => self.decrypt_aes256(encrypted, key),
+            EncryptionAlgorithm::Aes256Gcm => self.decrypt_aes256_gcm(encrypted, key),
             EncryptionAlgorithm::Blake3 => self.decrypt_blake3(encrypted, key),
         };
         
@@ -161,18 +165,24 @@ impl HighPerformanceCrypto {
                 bandwidth_overhead_percent: 1.5,
                 privacy_score: 0.6,
                 performance_score: 0.95,
+                relay_hops: 0,
+                relay_latency_overhead_ms: 0.0,
             },
             PrivacyLevel::Balanced => PrivacyPerformanceMetrics {
                 encryption_overhead_ms: 1.2,
                 bandwidth_overhead_percent: 3.0,
                 privacy_score: 0.8,
                 performance_score: 0.85,
+                relay_hops: 0,
+                relay_latency_overhead_ms: 0.0,
             },
             PrivacyLevel::Maximum => PrivacyPerformanceMetrics {
                 encryption_overhead_ms: 2.5,
                 bandwidth_overhead_percent: 5.0,
                 privacy_score: 0.95,
                 performance_score: 0.75,
+                relay_hops: 0,
+                relay_latency_overhead_ms: 0.0,
             },
         }
     }
@@ -223,6 +233,15 @@ impl HighPerformanceCrypto {
         Ok(buffer)
     }
     
+    /// AES-256-GCM，按 CPU 特性选择硬件或软件实现，输出带随机 nonce
+    pub fn encrypt_aes256_gcm(&self, data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+        AeadCipher::detect(EncryptionAlgorithm::Aes256Gcm)?.seal(key, data)
+    }
+
+    pub fn decrypt_aes256_gcm(&self, encrypted: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+        AeadCipher::detect(EncryptionAlgorithm::Aes256Gcm)?.open(key, encrypted)
+    }
+
     pub fn encrypt_blake3(&self, data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
         let keyed_key: [u8; 32] = key.try_into()
             .map_err(|_| anyhow!("Blake3 key must be 32 bytes"))?;
@@ -0,0 +1,399 @@
+//! 节点持久身份模块
+//!
+//! 节点 ID 由持久化的 Ed25519 公钥派生（base58），重启后保持不变：
+//! - 桌面端存放在配置目录下的 `identity.json`
+//! - Android 端可通过 FFI 注册系统 Keystore 作为存储后端
+//! - 配置了密钥库（见 [`super::vault`]）时加密保存在系统钥匙串或 age 文件中
+//!
+//! 身份用于签名 P2P 消息和算力贡献记录，并提供显式的密钥轮换：
+//! 新公钥由旧私钥签名后广播，对端据此把旧 ID 的信誉迁移到新 ID。
+
+use anyhow::{anyhow, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use parking_lot::RwLock;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use super::base::SolSignature;
+
+/// 身份存储后端
+pub trait IdentityKeystore: Send + Sync {
+    /// 读取已保存的身份（不存在时返回 None）
+    fn load(&self) -> Result<Option<Vec<u8>>>;
+
+    /// 保存身份
+    fn store(&self, data: &[u8]) -> Result<()>;
+}
+
+/// 文件存储后端
+pub struct FileKeystore {
+    path: PathBuf,
+}
+
+impl FileKeystore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl IdentityKeystore for FileKeystore {
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        match std::fs::read(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn store(&self, data: &[u8]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // 先写临时文件再重命名，避免轮换过程中断导致身份丢失
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, data)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+static PLATFORM_KEYSTORE: OnceLock<RwLock<Option<Arc<dyn IdentityKeystore>>>> = OnceLock::new();
+
+fn platform_slot() -> &'static RwLock<Option<Arc<dyn IdentityKeystore>>> {
+    PLATFORM_KEYSTORE.get_or_init(|| RwLock::new(None))
+}
+
+/// 注册平台存储后端（例如 Android Keystore），优先于文件存储
+pub fn set_platform_keystore(keystore: Arc<dyn IdentityKeystore>) {
+    *platform_slot().write() = Some(keystore);
+}
+
+/// 获取已注册的平台存储后端
+pub fn platform_keystore() -> Option<Arc<dyn IdentityKeystore>> {
+    platform_slot().read().clone()
+}
+
+/// 默认身份文件路径：`$WILLIW_CONFIG_DIR` 或 `~/.williw`
+pub fn default_identity_path() -> PathBuf {
+    let base = std::env::var_os("WILLIW_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".williw"))
+        })
+        .unwrap_or_else(|| PathBuf::from(".williw"));
+    base.join("identity.json")
+}
+
+/// 密钥轮换公告（新公钥由旧私钥签名）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotation {
+    /// 旧节点 ID（旧公钥 base58）
+    pub old_node_id: String,
+    /// 新节点 ID（新公钥 base58）
+    pub new_node_id: String,
+    /// 轮换时间（Unix 秒）
+    pub rotated_at: i64,
+    /// 旧私钥对 `rotation_payload` 的签名（base58）
+    pub signature: String,
+}
+
+impl KeyRotation {
+    fn payload(old_node_id: &str, new_node_id: &str, rotated_at: i64) -> Vec<u8> {
+        format!("williw-key-rotation:{}:{}:{}", old_node_id, new_node_id, rotated_at).into_bytes()
+    }
+
+    /// 验证公告确实由旧密钥签发
+    pub fn verify(&self) -> bool {
+        let Ok(old_key) = decode_public_key(&self.old_node_id) else {
+            return false;
+        };
+        let Some(signature) = decode_signature(&self.signature) else {
+            return false;
+        };
+        let payload = Self::payload(&self.old_node_id, &self.new_node_id, self.rotated_at);
+        old_key.verify_strict(&payload, &signature).is_ok()
+    }
+}
+
+/// 持久化格式
+#[derive(Serialize, Deserialize)]
+struct StoredIdentity {
+    /// 当前私钥（base58）
+    secret: String,
+    created_at: i64,
+    /// 历史轮换记录，便于离线节点追溯
+    #[serde(default)]
+    rotations: Vec<KeyRotation>,
+}
+
+/// 节点身份
+pub struct NodeIdentity {
+    keystore: Arc<dyn IdentityKeystore>,
+    state: RwLock<IdentityState>,
+}
+
+struct IdentityState {
+    signing_key: SigningKey,
+    node_id: String,
+    created_at: i64,
+    rotations: Vec<KeyRotation>,
+}
+
+impl IdentityState {
+    fn new(signing_key: SigningKey, created_at: i64, rotations: Vec<KeyRotation>) -> Self {
+        let node_id = encode_public_key(&signing_key.verifying_key());
+        Self {
+            signing_key,
+            node_id,
+            created_at,
+            rotations,
+        }
+    }
+
+    fn to_stored(&self) -> StoredIdentity {
+        StoredIdentity {
+            secret: bs58::encode(self.signing_key.to_bytes()).into_string(),
+            created_at: self.created_at,
+            rotations: self.rotations.clone(),
+        }
+    }
+}
+
+impl NodeIdentity {
+    /// 加载身份，不存在时生成并保存
+    ///
+    /// 若注册了平台存储后端则优先使用，否则使用 `path`（默认见 [`default_identity_path`]）。
+    pub fn load_or_create(path: Option<&Path>) -> Result<Self> {
+        let keystore: Arc<dyn IdentityKeystore> = match platform_keystore() {
+            Some(keystore) => keystore,
+            None => Arc::new(FileKeystore::new(
+                path.map(Path::to_path_buf).unwrap_or_else(default_identity_path),
+            )),
+        };
+        Self::with_keystore(keystore)
+    }
+
+    /// 按加密配置加载身份，配置了密钥库时首次加载会迁移旧的明文身份文件
+    pub fn load_from_config(config: &super::base::CryptoConfig) -> Result<Self> {
+        Self::with_keystore(super::vault::identity_keystore(config)?)
+    }
+
+    /// 使用指定存储后端加载或创建身份
+    pub fn with_keystore(keystore: Arc<dyn IdentityKeystore>) -> Result<Self> {
+        let state = match keystore.load()? {
+            Some(data) => {
+                let stored: StoredIdentity = serde_json::from_slice(&data)?;
+                let secret = decode_secret(&stored.secret)?;
+                IdentityState::new(SigningKey::from_bytes(&secret), stored.created_at, stored.rotations)
+            }
+            None => {
+                let state = IdentityState::new(random_signing_key(), chrono::Utc::now().timestamp(), Vec::new());
+                keystore.store(&serde_json::to_vec_pretty(&state.to_stored())?)?;
+                println!("[身份] 已生成新节点身份: {}", state.node_id);
+                state
+            }
+        };
+        Ok(Self {
+            keystore,
+            state: RwLock::new(state),
+        })
+    }
+
+    /// 节点 ID（当前公钥 base58）
+    pub fn node_id(&self) -> String {
+        self.state.read().node_id.clone()
+    }
+
+    /// 历史轮换记录
+    pub fn rotations(&self) -> Vec<KeyRotation> {
+        self.state.read().rotations.clone()
+    }
+
+    /// 当前私钥种子（仅供证书生成等需要原始密钥的场景）
+    pub(crate) fn secret_bytes(&self) -> zeroize::Zeroizing<[u8; 32]> {
+        zeroize::Zeroizing::new(self.state.read().signing_key.to_bytes())
+    }
+
+    /// 签名任意数据
+    pub fn sign(&self, payload: &[u8]) -> SolSignature {
+        let state = self.state.read();
+        SolSignature {
+            pubkey: state.node_id.clone(),
+            signature: bs58::encode(state.signing_key.sign(payload).to_bytes()).into_string(),
+        }
+    }
+
+    /// 签名可序列化的记录（例如算力贡献记录）
+    pub fn sign_record<T: Serialize>(&self, record: &T) -> Result<SolSignature> {
+        Ok(self.sign(&serde_json::to_vec(record)?))
+    }
+
+    /// 轮换密钥：生成新密钥、持久化并返回需广播给对端的公告
+    pub fn rotate(&self) -> Result<KeyRotation> {
+        let mut state = self.state.write();
+        let new_key = random_signing_key();
+        let new_node_id = encode_public_key(&new_key.verifying_key());
+        let rotated_at = chrono::Utc::now().timestamp();
+        let payload = KeyRotation::payload(&state.node_id, &new_node_id, rotated_at);
+        let rotation = KeyRotation {
+            old_node_id: state.node_id.clone(),
+            new_node_id,
+            rotated_at,
+            signature: bs58::encode(state.signing_key.sign(&payload).to_bytes()).into_string(),
+        };
+
+        let mut rotations = state.rotations.clone();
+        rotations.push(rotation.clone());
+        let next = IdentityState::new(new_key, rotated_at, rotations);
+        // 先落盘再切换，保存失败时保留旧身份
+        self.keystore.store(&serde_json::to_vec_pretty(&next.to_stored())?)?;
+        *state = next;
+
+        println!("[身份] 密钥已轮换: {} -> {}", rotation.old_node_id, rotation.new_node_id);
+        Ok(rotation)
+    }
+}
+
+/// 使用签名中的公钥验证签名（严格模式，拒绝小阶公钥和可延展的签名）
+pub fn verify_signature(payload: &[u8], sig: &SolSignature) -> bool {
+    let Ok(key) = decode_public_key(&sig.pubkey) else {
+        return false;
+    };
+    decode_signature(&sig.signature)
+        .map(|signature| key.verify_strict(payload, &signature).is_ok())
+        .unwrap_or(false)
+}
+
+/// 批量验证签名，返回与输入一一对应的结果，与逐条调用 [`verify_signature`] 的结论一致
+///
+/// 小阶公钥直接判为无效，其余格式正确的签名先用 Ed25519 批量验证一次性校验；
+/// 批量失败时逐条严格验证，找出无效的签名。全部有效时比逐条验证快约一倍。
+pub fn verify_signatures_batch(items: &[(&[u8], &SolSignature)]) -> Vec<bool> {
+    let decoded: Vec<Option<(VerifyingKey, Signature)>> = items
+        .iter()
+        .map(|(_, sig)| Some((decode_public_key(&sig.pubkey).ok()?, decode_signature(&sig.signature)?)))
+        .map(|entry| entry.filter(|(key, _)| !key.is_weak()))
+        .collect();
+    let candidates: Vec<usize> = (0..items.len()).filter(|&i| decoded[i].is_some()).collect();
+
+    let mut results = vec![false; items.len()];
+    let (messages, (signatures, keys)): (Vec<&[u8]>, (Vec<Signature>, Vec<VerifyingKey>)) = candidates
+        .iter()
+        .map(|&i| {
+            let (key, signature) = decoded[i].expect("已过滤");
+            (items[i].0, (signature, key))
+        })
+        .unzip();
+    if candidates.len() > 1 && ed25519_dalek::verify_batch(&messages, &signatures, &keys).is_ok() {
+        candidates.iter().for_each(|&i| results[i] = true);
+        return results;
+    }
+    for (n, &i) in candidates.iter().enumerate() {
+        results[i] = keys[n].verify_strict(messages[n], &signatures[n]).is_ok();
+    }
+    results
+}
+
+fn random_signing_key() -> SigningKey {
+    let mut secret = [0u8; 32];
+    rand::rng().fill_bytes(&mut secret);
+    SigningKey::from_bytes(&secret)
+}
+
+fn encode_public_key(key: &VerifyingKey) -> String {
+    bs58::encode(key.as_bytes()).into_string()
+}
+
+fn decode_public_key(node_id: &str) -> Result<VerifyingKey> {
+    let bytes: [u8; 32] = bs58::decode(node_id)
+        .into_vec()?
+        .try_into()
+        .map_err(|_| anyhow!("公钥长度必须为 32 字节"))?;
+    Ok(VerifyingKey::from_bytes(&bytes)?)
+}
+
+fn decode_secret(secret: &str) -> Result<[u8; 32]> {
+    bs58::decode(secret)
+        .into_vec()?
+        .try_into()
+        .map_err(|_| anyhow!("私钥长度必须为 32 字节"))
+}
+
+fn decode_signature(signature: &str) -> Option<Signature> {
+    let bytes = bs58::decode(signature).into_vec().ok()?;
+    Signature::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_persists_and_rotates() {
+        let dir = std::env::temp_dir().join(format!("williw-identity-{}", std::process::id()));
+        let path = dir.join("identity.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let identity = NodeIdentity::with_keystore(Arc::new(FileKeystore::new(&path))).unwrap();
+        let original = identity.node_id();
+        let reloaded = NodeIdentity::with_keystore(Arc::new(FileKeystore::new(&path))).unwrap();
+        assert_eq!(reloaded.node_id(), original);
+
+        let rotation = identity.rotate().unwrap();
+        assert!(rotation.verify());
+        assert_eq!(rotation.old_node_id, original);
+        assert_ne!(identity.node_id(), original);
+
+        let sig = identity.sign(b"gossip");
+        assert!(verify_signature(b"gossip", &sig));
+        assert!(!verify_signature(b"tampered", &sig));
+
+        let reloaded = NodeIdentity::with_keystore(Arc::new(FileKeystore::new(&path))).unwrap();
+        assert_eq!(reloaded.node_id(), identity.node_id());
+        assert_eq!(reloaded.rotations().len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_batch_verify_matches_strict_verification() {
+        let signer = SigningKey::from_bytes(&[7; 32]);
+        let sign = |payload: &[u8]| SolSignature {
+            pubkey: encode_public_key(&signer.verifying_key()),
+            signature: bs58::encode(signer.sign(payload).to_bytes()).into_string(),
+        };
+        let valid = sign(b"a");
+        let other = sign(b"b");
+        // 单位元公钥配 R = 单位元、s = 0 的签名能通过非严格验证，严格验证必须拒绝
+        let mut identity_point = [0u8; 32];
+        identity_point[0] = 1;
+        let mut weak_signature = [0u8; 64];
+        weak_signature[0] = 1;
+        let weak = SolSignature {
+            pubkey: bs58::encode(identity_point).into_string(),
+            signature: bs58::encode(weak_signature).into_string(),
+        };
+
+        let items: Vec<(&[u8], &SolSignature)> =
+            vec![(b"a", &valid), (b"b", &other), (b"x", &valid), (b"weak", &weak)];
+        let results = verify_signatures_batch(&items);
+        assert_eq!(results, vec![true, true, false, false]);
+        let single: Vec<bool> = items.iter().map(|(payload, sig)| verify_signature(payload, sig)).collect();
+        assert_eq!(results, single);
+
+        // 全部有效时走批量路径
+        assert_eq!(verify_signatures_batch(&items[..2]), vec![true, true]);
+    }
+}
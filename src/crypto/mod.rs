@@ -6,6 +6,10 @@
 //! 3. 硬件加速加密
 //! 4. 批量加密
 //! 5. 零拷贝加密
+//! 6. 节点持久身份（Ed25519）
+//! 7. 基于信任级别的逐节点加密策略
+//! 8. 可验证随机函数（委员会抽签）
+//! 9. 静态加密的密钥库（系统钥匙串 / age 文件）
 
 // 导出子模块
 pub mod base;
@@ -13,6 +17,10 @@ pub mod high_performance;
 pub mod batch;
 pub mod hardware;
 pub mod zero_copy;
+pub mod identity;
+pub mod trust;
+pub mod vrf;
+pub mod vault;
 
 // 重新导出常用类型
 pub use base::*;
@@ -20,6 +28,10 @@ pub use high_performance::*;
 pub use batch::*;
 pub use hardware::*;
 pub use zero_copy::*;
+pub use identity::{KeyRotation, NodeIdentity, IdentityKeystore, FileKeystore};
+pub use vrf::VrfProof;
+pub use vault::{KeyVault, VaultBackend, VaultConfig, VaultKeystore};
+pub use trust::{OwnerAttestation, PeerEncryptionPolicy, TrustConfig, TrustLevel, TrustPolicyEngine};
 
 /// 隐私级别枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -39,6 +51,8 @@ pub enum EncryptionAlgorithm {
     ChaCha20Poly1305,
     /// AES-256-CBC (硬件加速)
     Aes256Cbc,
+    /// AES-256-GCM (AES-NI / ARMv8 Crypto Extensions)
+    Aes256Gcm,
     /// Blake3 哈希加密
     Blake3,
 }
@@ -69,6 +83,12 @@ pub struct PrivacyPerformanceMetrics {
     pub privacy_score: f64,
     /// 性能评分（0-1）
     pub performance_score: f64,
+    /// 洋葱路由中继跳数（0 表示直连）
+    #[serde(default)]
+    pub relay_hops: u8,
+    /// 洋葱路由带来的延迟开销（毫秒）
+    #[serde(default)]
+    pub relay_latency_overhead_ms: f64,
 }
 
 /// 性能比较结构体
@@ -0,0 +1,316 @@
+//! 基于信任级别的逐节点加密策略
+//!
+//! 同一运营者名下两台机器之间的局域网流量没必要做完整加密和混淆，
+//! 在弱手机上这只是白白消耗 CPU。这里按对端的信任级别选择加密强度：
+//! - `SameOwner`：配置中列出的本人节点，或持有本人所有者密钥签发的归属证明
+//! - `Verified`：配置中显式信任的节点，或由受信任所有者签发归属证明的节点
+//! - `Unknown`：其他所有节点
+//!
+//! 全局 `PrivacyLevel` 是上限：信任级别只会降低加密强度，不会超过全局设置。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::base::SolSignature;
+use super::identity::{verify_signature, NodeIdentity};
+use super::{EncryptionAlgorithm, PrivacyLevel};
+
+/// 对端信任级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TrustLevel {
+    /// 同一所有者的节点
+    SameOwner,
+    /// 已验证的节点
+    Verified,
+    /// 未知节点
+    Unknown,
+}
+
+/// 信任配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrustConfig {
+    /// 本人所有者公钥（base58），用于校验同一所有者的归属证明
+    pub owner_pubkey: Option<String>,
+    /// 本人名下的节点 ID
+    pub same_owner_peers: Vec<String>,
+    /// 显式信任的节点 ID
+    pub verified_peers: Vec<String>,
+    /// 受信任的其他所有者公钥，其签发归属证明的节点视为已验证
+    pub trusted_owner_keys: Vec<String>,
+    /// 同一所有者的节点只在局域网内降级加密，跨公网时按已验证处理
+    pub same_owner_lan_only: bool,
+}
+
+impl Default for TrustConfig {
+    fn default() -> Self {
+        Self {
+            owner_pubkey: None,
+            same_owner_peers: Vec::new(),
+            verified_peers: Vec::new(),
+            trusted_owner_keys: Vec::new(),
+            same_owner_lan_only: true,
+        }
+    }
+}
+
+/// 节点归属证明：所有者密钥对节点 ID 的签名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnerAttestation {
+    pub node_id: String,
+    pub owner: SolSignature,
+}
+
+impl OwnerAttestation {
+    fn payload(node_id: &str) -> Vec<u8> {
+        format!("williw-owner-attestation:{}", node_id).into_bytes()
+    }
+
+    /// 用所有者身份为节点签发归属证明
+    pub fn issue(owner: &NodeIdentity, node_id: &str) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            owner: owner.sign(&Self::payload(node_id)),
+        }
+    }
+
+    /// 签名有效时返回所有者公钥
+    pub fn verify(&self) -> Option<&str> {
+        verify_signature(&Self::payload(&self.node_id), &self.owner).then_some(self.owner.pubkey.as_str())
+    }
+}
+
+/// 对某个节点实际使用的加密策略
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerEncryptionPolicy {
+    pub trust: TrustLevel,
+    /// 生效的隐私级别（不超过全局级别）
+    pub level: PrivacyLevel,
+    pub algorithm: EncryptionAlgorithm,
+    /// 是否做流量混淆（填充到固定长度）
+    pub obfuscation: bool,
+    /// 填充后的块大小（字节），0 表示不填充
+    pub pad_to: usize,
+    /// 是否经洋葱路由转发
+    pub onion_routing: bool,
+}
+
+/// 由配置中的平衡模式推导全局隐私级别
+pub fn global_privacy_level(mode: &crate::config::BalanceMode) -> PrivacyLevel {
+    match mode {
+        crate::config::BalanceMode::Performance => PrivacyLevel::Performance,
+        crate::config::BalanceMode::Balanced | crate::config::BalanceMode::Adaptive => PrivacyLevel::Balanced,
+        crate::config::BalanceMode::Privacy => PrivacyLevel::Maximum,
+    }
+}
+
+/// 自适应模式下按本机实测加密性能选择隐私级别，其他模式同 [`global_privacy_level`]
+pub fn measured_privacy_level(
+    mode: &crate::config::BalanceMode,
+    min_performance_score: f32,
+    profile: &super::hardware::CryptoProfile,
+) -> PrivacyLevel {
+    match mode {
+        crate::config::BalanceMode::Adaptive => profile.adaptive_level(min_performance_score as f64),
+        other => global_privacy_level(other),
+    }
+}
+
+/// 地址是否位于局域网（私有、回环或链路本地地址）
+pub fn is_lan_addr(addr: &str) -> bool {
+    let ip = match addr.parse::<std::net::SocketAddr>() {
+        Ok(socket) => socket.ip(),
+        Err(_) => match addr.parse::<std::net::IpAddr>() {
+            Ok(ip) => ip,
+            Err(_) => return false,
+        },
+    };
+    match ip {
+        std::net::IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local(),
+        std::net::IpAddr::V6(v6) => {
+            v6.is_loopback() || (v6.segments()[0] & 0xfe00) == 0xfc00 || (v6.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+fn rank(level: PrivacyLevel) -> u8 {
+    match level {
+        PrivacyLevel::Performance => 0,
+        PrivacyLevel::Balanced => 1,
+        PrivacyLevel::Maximum => 2,
+    }
+}
+
+fn cap(level: PrivacyLevel, ceiling: PrivacyLevel) -> PrivacyLevel {
+    if rank(level) > rank(ceiling) {
+        ceiling
+    } else {
+        level
+    }
+}
+
+/// 信任策略引擎
+pub struct TrustPolicyEngine {
+    config: TrustConfig,
+    /// 全局隐私级别（配置热更新时可调整）
+    global_level: RwLock<PrivacyLevel>,
+    /// 已校验的归属证明：节点 ID -> 所有者公钥
+    attested: RwLock<HashMap<String, String>>,
+    /// 最近为每个节点选择的策略
+    policies: RwLock<HashMap<String, PeerEncryptionPolicy>>,
+    /// 完整加密使用的 AEAD（按本机实测吞吐选择）
+    preferred_aead: RwLock<EncryptionAlgorithm>,
+}
+
+impl TrustPolicyEngine {
+    pub fn new(config: TrustConfig, global_level: PrivacyLevel) -> Self {
+        Self {
+            config,
+            global_level: RwLock::new(global_level),
+            attested: RwLock::new(HashMap::new()),
+            policies: RwLock::new(HashMap::new()),
+            preferred_aead: RwLock::new(EncryptionAlgorithm::ChaCha20Poly1305),
+        }
+    }
+
+    pub fn global_level(&self) -> PrivacyLevel {
+        *self.global_level.read()
+    }
+
+    /// 调整全局隐私级别；已记录的逐节点策略会清空，下次协商时按新级别重新选择
+    pub fn set_global_level(&self, level: PrivacyLevel) {
+        *self.global_level.write() = level;
+        self.policies.write().clear();
+    }
+
+    /// 设置完整加密使用的 AEAD，已记录的逐节点策略会清空
+    pub fn set_preferred_aead(&self, algorithm: EncryptionAlgorithm) {
+        *self.preferred_aead.write() = algorithm;
+        self.policies.write().clear();
+    }
+
+    /// 接受对端出示的归属证明，签名无效时返回 false
+    pub fn accept_attestation(&self, attestation: &OwnerAttestation) -> bool {
+        let Some(owner) = attestation.verify() else {
+            return false;
+        };
+        self.attested
+            .write()
+            .insert(attestation.node_id.clone(), owner.to_string());
+        // 信任级别可能变化，丢弃缓存的策略
+        self.policies.write().remove(&attestation.node_id);
+        true
+    }
+
+    /// 由配置和归属证明推导信任级别
+    pub fn classify(&self, peer: &str) -> TrustLevel {
+        let attested_owner = self.attested.read().get(peer).cloned();
+        let same_owner = self.config.same_owner_peers.iter().any(|p| p == peer)
+            || matches!((&attested_owner, &self.config.owner_pubkey), (Some(a), Some(o)) if a == o);
+        if same_owner {
+            return TrustLevel::SameOwner;
+        }
+        let verified = self.config.verified_peers.iter().any(|p| p == peer)
+            || attested_owner.is_some_and(|a| self.config.trusted_owner_keys.contains(&a));
+        if verified {
+            TrustLevel::Verified
+        } else {
+            TrustLevel::Unknown
+        }
+    }
+
+    /// 为对端选择加密策略并记录
+    pub fn policy_for(&self, peer: &str, on_lan: bool) -> PeerEncryptionPolicy {
+        let trust = match self.classify(peer) {
+            TrustLevel::SameOwner if self.config.same_owner_lan_only && !on_lan => TrustLevel::Verified,
+            trust => trust,
+        };
+        let policy = self.policy_for_trust(trust);
+        self.policies.write().insert(peer.to_string(), policy.clone());
+        policy
+    }
+
+    /// 某信任级别在当前全局级别下的策略
+    pub fn policy_for_trust(&self, trust: TrustLevel) -> PeerEncryptionPolicy {
+        let global_level = self.global_level();
+        let level = match trust {
+            TrustLevel::SameOwner => PrivacyLevel::Performance,
+            TrustLevel::Verified => cap(PrivacyLevel::Balanced, global_level),
+            TrustLevel::Unknown => global_level,
+        };
+        let maximum = level == PrivacyLevel::Maximum;
+        PeerEncryptionPolicy {
+            trust,
+            level,
+            algorithm: match level {
+                // 同一所有者局域网内只做轻量的 Blake3 流加密
+                PrivacyLevel::Performance if trust == TrustLevel::SameOwner => EncryptionAlgorithm::Blake3,
+                _ => *self.preferred_aead.read(),
+            },
+            obfuscation: maximum,
+            pad_to: if maximum { 1024 } else { 0 },
+            onion_routing: maximum,
+        }
+    }
+
+    /// 最近为各节点选择的策略
+    pub fn policies(&self) -> HashMap<String, PeerEncryptionPolicy> {
+        self.policies.read().clone()
+    }
+
+    /// 节点离线后丢弃其策略
+    pub fn forget(&self, peer: &str) {
+        self.policies.write().remove(peer);
+    }
+
+    /// 各信任级别的节点数
+    pub fn trust_counts(&self) -> HashMap<TrustLevel, usize> {
+        let mut counts = HashMap::new();
+        for policy in self.policies.read().values() {
+            *counts.entry(policy.trust).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_follows_trust_within_global_level() {
+        let path = std::env::temp_dir().join(format!("williw-trust-owner-{}.json", std::process::id()));
+        let owner = NodeIdentity::load_or_create(Some(path.as_path())).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let config = TrustConfig {
+            owner_pubkey: Some(owner.node_id()),
+            verified_peers: vec!["friend".to_string()],
+            ..Default::default()
+        };
+        let engine = TrustPolicyEngine::new(config, PrivacyLevel::Maximum);
+
+        assert!(engine.accept_attestation(&OwnerAttestation::issue(&owner, "phone")));
+        let mut forged = OwnerAttestation::issue(&owner, "laptop");
+        forged.node_id = "stranger".to_string();
+        assert!(!engine.accept_attestation(&forged));
+
+        let lan = engine.policy_for("phone", true);
+        assert_eq!(lan.trust, TrustLevel::SameOwner);
+        assert_eq!(lan.level, PrivacyLevel::Performance);
+        assert_eq!(lan.algorithm, EncryptionAlgorithm::Blake3);
+        // 跨公网时同一所有者按已验证处理
+        assert_eq!(engine.policy_for("phone", false).trust, TrustLevel::Verified);
+
+        assert_eq!(engine.policy_for("friend", true).level, PrivacyLevel::Balanced);
+        let unknown = engine.policy_for("stranger", true);
+        assert_eq!(unknown.level, PrivacyLevel::Maximum);
+        assert!(unknown.onion_routing && unknown.obfuscation);
+
+        assert!(is_lan_addr("192.168.1.20:4433") && !is_lan_addr("8.8.8.8:4433"));
+
+        // 全局为性能优先时，任何信任级别都不会超过它
+        let relaxed = TrustPolicyEngine::new(TrustConfig::default(), PrivacyLevel::Performance);
+        assert_eq!(relaxed.policy_for("stranger", false).level, PrivacyLevel::Performance);
+    }
+}
@@ -0,0 +1,350 @@
+//! 密钥库：静态加密保存节点私钥和 Solana 支付者私钥
+//!
+//! 后端按配置选择：
+//! - `keychain`：系统钥匙串（Windows DPAPI 凭据、macOS Keychain、Linux libsecret），需启用 `keychain` feature
+//! - `age_file`：口令加密的 age 文件（scrypt），口令从环境变量读取，不写入配置
+//! - `plain`：旧的明文文件，保持兼容
+//!
+//! 移动端通过 FFI 注册平台密钥库（例如 Android Keystore），优先于配置的后端。
+//! 从明文迁移时，密钥写入密钥库后删除明文副本。
+
+use anyhow::{anyhow, Context, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use zeroize::Zeroizing;
+
+use super::base::CryptoConfig;
+use super::identity::{default_identity_path, platform_keystore, FileKeystore, IdentityKeystore};
+
+/// 节点身份在密钥库中的条目名
+pub const IDENTITY_ENTRY: &str = "node-identity";
+
+/// 密钥库后端
+pub trait KeyVault: Send + Sync {
+    /// 后端名称（用于日志）
+    fn backend(&self) -> &'static str;
+
+    /// 读取条目（不存在时返回 None）
+    fn load(&self, name: &str) -> Result<Option<Zeroizing<Vec<u8>>>>;
+
+    fn store(&self, name: &str, secret: &[u8]) -> Result<()>;
+
+    /// 删除条目，不存在时不报错
+    fn delete(&self, name: &str) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultBackend {
+    Plain,
+    Keychain,
+    AgeFile,
+}
+
+/// 密钥库配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    pub backend: VaultBackend,
+    /// 钥匙串服务名
+    pub service: String,
+    /// age 文件目录，为空时使用身份文件所在目录下的 `vault/`
+    pub dir: Option<PathBuf>,
+    /// 保存 age 口令的环境变量名
+    pub passphrase_env: String,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            backend: VaultBackend::Plain,
+            service: "williw".into(),
+            dir: None,
+            passphrase_env: "WILLIW_VAULT_PASSPHRASE".into(),
+        }
+    }
+}
+
+/// 系统钥匙串后端
+#[cfg(feature = "keychain")]
+pub struct KeychainVault {
+    service: String,
+}
+
+#[cfg(feature = "keychain")]
+impl KeychainVault {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self { service: service.into() }
+    }
+
+    fn entry(&self, name: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new(&self.service, name).map_err(|e| anyhow!("打开钥匙串条目 {} 失败: {}", name, e))
+    }
+}
+
+#[cfg(feature = "keychain")]
+impl KeyVault for KeychainVault {
+    fn backend(&self) -> &'static str {
+        "keychain"
+    }
+
+    fn load(&self, name: &str) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        match self.entry(name)?.get_secret() {
+            Ok(secret) => Ok(Some(Zeroizing::new(secret))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(anyhow!("读取钥匙串条目 {} 失败: {}", name, e)),
+        }
+    }
+
+    fn store(&self, name: &str, secret: &[u8]) -> Result<()> {
+        self.entry(name)?
+            .set_secret(secret)
+            .map_err(|e| anyhow!("写入钥匙串条目 {} 失败: {}", name, e))
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(anyhow!("删除钥匙串条目 {} 失败: {}", name, e)),
+        }
+    }
+}
+
+/// 口令加密的 age 文件后端，每个条目一个 `<name>.age` 文件
+pub struct AgeFileVault {
+    dir: PathBuf,
+    passphrase: age::secrecy::SecretString,
+}
+
+impl AgeFileVault {
+    pub fn new(dir: impl Into<PathBuf>, passphrase: String) -> Self {
+        Self {
+            dir: dir.into(),
+            passphrase: age::secrecy::SecretString::from(passphrase),
+        }
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(anyhow!("无效的密钥库条目名: {}", name));
+        }
+        Ok(self.dir.join(format!("{}.age", name)))
+    }
+}
+
+impl KeyVault for AgeFileVault {
+    fn backend(&self) -> &'static str {
+        "age_file"
+    }
+
+    fn load(&self, name: &str) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        let data = match std::fs::read(self.path(name)?) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let identity = age::scrypt::Identity::new(self.passphrase.clone());
+        let mut reader = age::Decryptor::new(&data[..])?
+            .decrypt(std::iter::once(&identity as &dyn age::Identity))
+            .map_err(|e| anyhow!("解密密钥库条目 {} 失败（口令错误？）: {}", name, e))?;
+        let mut secret = Zeroizing::new(Vec::new());
+        reader.read_to_end(&mut secret)?;
+        Ok(Some(secret))
+    }
+
+    fn store(&self, name: &str, secret: &[u8]) -> Result<()> {
+        let path = self.path(name)?;
+        let mut encrypted = Vec::new();
+        let mut writer = age::Encryptor::with_user_passphrase(self.passphrase.clone()).wrap_output(&mut encrypted)?;
+        writer.write_all(secret)?;
+        writer.finish()?;
+        write_private(&path, &encrypted)
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        remove_if_exists(&self.path(name)?)
+    }
+}
+
+/// 明文文件后端（兼容旧版本，不建议在生产环境使用）
+pub struct PlainFileVault {
+    dir: PathBuf,
+}
+
+impl PlainFileVault {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl KeyVault for PlainFileVault {
+    fn backend(&self) -> &'static str {
+        "plain"
+    }
+
+    fn load(&self, name: &str) -> Result<Option<Zeroizing<Vec<u8>>>> {
+        match std::fs::read(self.dir.join(name)) {
+            Ok(data) => Ok(Some(Zeroizing::new(data))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn store(&self, name: &str, secret: &[u8]) -> Result<()> {
+        write_private(&self.dir.join(name), secret)
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        remove_if_exists(&self.dir.join(name))
+    }
+}
+
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+static PLATFORM_VAULT: OnceLock<RwLock<Option<Arc<dyn KeyVault>>>> = OnceLock::new();
+
+fn platform_slot() -> &'static RwLock<Option<Arc<dyn KeyVault>>> {
+    PLATFORM_VAULT.get_or_init(|| RwLock::new(None))
+}
+
+/// 注册平台密钥库（例如 Android Keystore），优先于配置的后端
+pub fn set_platform_vault(vault: Arc<dyn KeyVault>) {
+    *platform_slot().write() = Some(vault);
+}
+
+pub fn platform_vault() -> Option<Arc<dyn KeyVault>> {
+    platform_slot().read().clone()
+}
+
+/// 按配置打开密钥库；`plain` 后端且未注册平台密钥库时返回 None（沿用明文文件）
+pub fn open_vault(config: &VaultConfig) -> Result<Option<Arc<dyn KeyVault>>> {
+    if let Some(vault) = platform_vault() {
+        return Ok(Some(vault));
+    }
+    match config.backend {
+        VaultBackend::Plain => Ok(None),
+        #[cfg(feature = "keychain")]
+        VaultBackend::Keychain => Ok(Some(Arc::new(KeychainVault::new(config.service.clone())))),
+        #[cfg(not(feature = "keychain"))]
+        VaultBackend::Keychain => Err(anyhow!("系统钥匙串后端需要启用 keychain feature")),
+        VaultBackend::AgeFile => {
+            let passphrase = std::env::var(&config.passphrase_env)
+                .with_context(|| format!("age 密钥库需要在环境变量 {} 中提供口令", config.passphrase_env))?;
+            let dir = config.dir.clone().unwrap_or_else(|| {
+                let identity_path = default_identity_path();
+                identity_path.parent().unwrap_or(Path::new(".")).join("vault")
+            });
+            Ok(Some(Arc::new(AgeFileVault::new(dir, passphrase))))
+        }
+    }
+}
+
+/// 把身份保存在密钥库条目中的存储后端
+pub struct VaultKeystore {
+    vault: Arc<dyn KeyVault>,
+    name: String,
+}
+
+impl VaultKeystore {
+    pub fn new(vault: Arc<dyn KeyVault>, name: impl Into<String>) -> Self {
+        Self {
+            vault,
+            name: name.into(),
+        }
+    }
+
+    /// 密钥库中还没有身份时，从旧的明文身份文件迁入并删除明文文件；返回是否发生迁移
+    pub fn migrate_from(&self, legacy: &FileKeystore) -> Result<bool> {
+        if self.vault.load(&self.name)?.is_some() {
+            return Ok(false);
+        }
+        let Some(data) = legacy.load()?.map(Zeroizing::new) else {
+            return Ok(false);
+        };
+        self.vault.store(&self.name, &data)?;
+        // 确认可以读回后再删除明文
+        if self.vault.load(&self.name)?.as_deref() != Some(data.as_slice()) {
+            return Err(anyhow!("身份迁移到 {} 后校验失败，保留明文文件", self.vault.backend()));
+        }
+        remove_if_exists(legacy.path())?;
+        println!("[密钥库] 身份已从 {} 迁移到 {}", legacy.path().display(), self.vault.backend());
+        Ok(true)
+    }
+}
+
+impl IdentityKeystore for VaultKeystore {
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        Ok(self.vault.load(&self.name)?.map(|secret| secret.to_vec()))
+    }
+
+    fn store(&self, data: &[u8]) -> Result<()> {
+        self.vault.store(&self.name, data)
+    }
+}
+
+/// 按加密配置选择身份存储：平台身份存储 > 配置的密钥库（自动迁移明文身份）> 明文文件
+pub fn identity_keystore(config: &CryptoConfig) -> Result<Arc<dyn IdentityKeystore>> {
+    if let Some(keystore) = platform_keystore() {
+        return Ok(keystore);
+    }
+    let legacy = FileKeystore::new(config.identity_path.clone().unwrap_or_else(default_identity_path));
+    match open_vault(&config.vault)? {
+        Some(vault) => {
+            let keystore = VaultKeystore::new(vault, IDENTITY_ENTRY);
+            keystore.migrate_from(&legacy)?;
+            Ok(Arc::new(keystore))
+        }
+        None => Ok(Arc::new(legacy)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age_vault_roundtrip_and_migration() {
+        let dir = std::env::temp_dir().join(format!("williw-vault-{}", rand::random::<u64>()));
+        let vault = Arc::new(AgeFileVault::new(dir.join("vault"), "correct horse".into()));
+        assert!(vault.load("payer").unwrap().is_none());
+        vault.store("payer", b"secret-key").unwrap();
+        assert_eq!(vault.load("payer").unwrap().as_deref(), Some(&b"secret-key"[..]));
+        // 密文中不含明文，错误口令无法解密
+        let raw = std::fs::read(dir.join("vault/payer.age")).unwrap();
+        assert!(!raw.windows(10).any(|w| w == b"secret-key"));
+        assert!(AgeFileVault::new(dir.join("vault"), "wrong".into()).load("payer").is_err());
+        assert!(vault.store("../escape", b"x").is_err());
+
+        let legacy = FileKeystore::new(dir.join("identity.json"));
+        legacy.store(b"{\"secret\":\"...\"}").unwrap();
+        let keystore = VaultKeystore::new(vault.clone(), IDENTITY_ENTRY);
+        assert!(keystore.migrate_from(&legacy).unwrap());
+        assert!(!legacy.path().exists());
+        assert_eq!(keystore.load().unwrap().unwrap(), b"{\"secret\":\"...\"}");
+        assert!(!keystore.migrate_from(&legacy).unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
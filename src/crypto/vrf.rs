@@ -0,0 +1,154 @@
+//! 可验证随机函数（ECVRF-EDWARDS25519-SHA512-TAI，RFC 9381）
+//!
+//! 复用节点的 Ed25519 身份密钥：只有私钥持有者能为输入计算出输出，
+//! 任何人都可以用节点 ID（公钥）和 80 字节证明验证输出，且同一输入只有唯一的合法输出。
+
+use anyhow::{anyhow, Result};
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::{clamp_integer, Scalar};
+use sha2::{Digest, Sha512};
+
+use super::identity::NodeIdentity;
+
+/// 证明长度：Gamma（32）+ c（16）+ s（32）
+pub const PROOF_LEN: usize = 80;
+
+const SUITE: u8 = 0x03;
+
+/// VRF 证明
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VrfProof(pub [u8; PROOF_LEN]);
+
+impl VrfProof {
+    pub fn to_base58(&self) -> String {
+        bs58::encode(self.0).into_string()
+    }
+
+    pub fn from_base58(encoded: &str) -> Result<Self> {
+        let bytes: [u8; PROOF_LEN] = bs58::decode(encoded)
+            .into_vec()?
+            .try_into()
+            .map_err(|_| anyhow!("VRF 证明长度必须为 {} 字节", PROOF_LEN))?;
+        Ok(Self(bytes))
+    }
+
+    /// 证明对应的 64 字节输出（不做验证，验证请用 [`verify`]）
+    pub fn output(&self) -> Option<[u8; 64]> {
+        let gamma = CompressedEdwardsY(self.0[..32].try_into().ok()?).decompress()?;
+        Some(proof_to_hash(&gamma))
+    }
+}
+
+/// 用节点身份为 `alpha` 生成证明
+pub fn prove(identity: &NodeIdentity, alpha: &[u8]) -> VrfProof {
+    let hashed = Sha512::digest(identity.secret_bytes().as_slice());
+    let x = Scalar::from_bytes_mod_order(clamp_integer(hashed[..32].try_into().expect("长度固定")));
+    let public = EdwardsPoint::mul_base(&x).compress();
+
+    let h = encode_to_curve(public.as_bytes(), alpha);
+    let gamma = x * h;
+    let k = Scalar::from_bytes_mod_order_wide(&wide(
+        Sha512::new().chain_update(&hashed[32..]).chain_update(h.compress().as_bytes()),
+    ));
+    let c = challenge(&public, &h, &gamma, &EdwardsPoint::mul_base(&k), &(k * h));
+    let s = k + c * x;
+
+    let mut proof = [0u8; PROOF_LEN];
+    proof[..32].copy_from_slice(gamma.compress().as_bytes());
+    proof[32..48].copy_from_slice(&c.as_bytes()[..16]);
+    proof[48..].copy_from_slice(s.as_bytes());
+    VrfProof(proof)
+}
+
+/// 用节点 ID（公钥 base58）验证证明，成功时返回 64 字节输出
+pub fn verify(node_id: &str, alpha: &[u8], proof: &VrfProof) -> Option<[u8; 64]> {
+    let public_bytes: [u8; 32] = bs58::decode(node_id).into_vec().ok()?.try_into().ok()?;
+    let public = CompressedEdwardsY(public_bytes);
+    let y = public.decompress().filter(|point| !point.is_small_order())?;
+
+    let gamma = CompressedEdwardsY(proof.0[..32].try_into().ok()?).decompress()?;
+    let mut c_bytes = [0u8; 32];
+    c_bytes[..16].copy_from_slice(&proof.0[32..48]);
+    let c = Scalar::from_bytes_mod_order(c_bytes);
+    let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.0[48..].try_into().ok()?))?;
+
+    let h = encode_to_curve(&public_bytes, alpha);
+    let u = EdwardsPoint::mul_base(&s) - c * y;
+    let v = s * h - c * gamma;
+    (challenge(&public, &h, &gamma, &u, &v) == c).then(|| proof_to_hash(&gamma))
+}
+
+/// try-and-increment 把输入映射到素数阶子群上的点
+fn encode_to_curve(public: &[u8; 32], alpha: &[u8]) -> EdwardsPoint {
+    (0..=u8::MAX)
+        .find_map(|ctr| {
+            let hash = Sha512::new()
+                .chain_update([SUITE, 0x01])
+                .chain_update(public)
+                .chain_update(alpha)
+                .chain_update([ctr, 0x00])
+                .finalize();
+            CompressedEdwardsY(hash[..32].try_into().expect("长度固定")).decompress()
+        })
+        .map(|point| point.mul_by_cofactor())
+        // 256 次都失败的概率约为 2^-256
+        .expect("encode_to_curve 失败")
+}
+
+fn challenge(
+    public: &CompressedEdwardsY,
+    h: &EdwardsPoint,
+    gamma: &EdwardsPoint,
+    u: &EdwardsPoint,
+    v: &EdwardsPoint,
+) -> Scalar {
+    let mut hasher = Sha512::new().chain_update([SUITE, 0x02]).chain_update(public.as_bytes());
+    for point in [h, gamma, u, v] {
+        hasher.update(point.compress().as_bytes());
+    }
+    hasher.update([0x00]);
+    let mut c = [0u8; 32];
+    c[..16].copy_from_slice(&hasher.finalize()[..16]);
+    Scalar::from_bytes_mod_order(c)
+}
+
+fn proof_to_hash(gamma: &EdwardsPoint) -> [u8; 64] {
+    wide(
+        Sha512::new()
+            .chain_update([SUITE, 0x03])
+            .chain_update(gamma.mul_by_cofactor().compress().as_bytes())
+            .chain_update([0x00]),
+    )
+}
+
+fn wide(hasher: Sha512) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&hasher.finalize());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::node::MemoryKeystore;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_prove_and_verify() {
+        let identity = NodeIdentity::with_keystore(Arc::new(MemoryKeystore::default())).unwrap();
+        let other = NodeIdentity::with_keystore(Arc::new(MemoryKeystore::default())).unwrap();
+        let proof = prove(&identity, b"epoch-1");
+
+        // 确定性：同一输入的证明和输出相同
+        assert_eq!(proof, prove(&identity, b"epoch-1"));
+        let output = verify(&identity.node_id(), b"epoch-1", &proof).unwrap();
+        assert_eq!(Some(output), proof.output());
+        assert_eq!(VrfProof::from_base58(&proof.to_base58()).unwrap(), proof);
+
+        assert!(verify(&identity.node_id(), b"epoch-2", &proof).is_none());
+        assert!(verify(&other.node_id(), b"epoch-1", &proof).is_none());
+        let mut tampered = proof;
+        tampered.0[40] ^= 1;
+        assert!(verify(&identity.node_id(), b"epoch-1", &tampered).is_none());
+    }
+}
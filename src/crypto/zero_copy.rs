@@ -41,6 +41,7 @@ impl ZeroCopyEncryption {
         match algorithm {
             EncryptionAlgorithm::ChaCha20Poly1305 => self.encrypt_chacha20_in_place(data, key),
             EncryptionAlgorithm::Aes256Cbc => self.encrypt_aes256_in_place(data, key),
+            EncryptionAlgorithm::Aes256Gcm => Err(anyhow!("AES-256-GCM 需要额外的 nonce 和认证标签空间，不支持原地加密")),
             EncryptionAlgorithm::Blake3 => self.encrypt_blake3_in_place(data, key),
         }
     }
@@ -54,6 +55,7 @@ impl ZeroCopyEncryption {
         match algorithm {
             EncryptionAlgorithm::ChaCha20Poly1305 => self.decrypt_chacha20_in_place(data, key),
             EncryptionAlgorithm::Aes256Cbc => self.decrypt_aes256_in_place(data, key),
+            EncryptionAlgorithm::Aes256Gcm => Err(anyhow!("AES-256-GCM 需要额外的 nonce 和认证标签空间，不支持原地解密")),
             EncryptionAlgorithm::Blake3 => self.decrypt_blake3_in_place(data, key),
         }
     }
@@ -0,0 +1,81 @@
+//! Groth16 证明和验证密钥转换为 Solana alt_bn128 系统调用格式（zk-verifier 合约使用）
+//!
+//! G1 点为 `x || y`（64 字节），G2 点为 `x_im || x_re || y_im || y_re`（128 字节），坐标均为大端序；
+//! 无穷远点编码为全零。
+
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+
+/// alt_bn128 格式的 Groth16 证明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltBn128Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+impl From<&Proof<Bn254>> for AltBn128Proof {
+    fn from(proof: &Proof<Bn254>) -> Self {
+        Self {
+            a: g1_bytes(&proof.a),
+            b: g2_bytes(&proof.b),
+            c: g1_bytes(&proof.c),
+        }
+    }
+}
+
+/// alt_bn128 格式的验证密钥，字段与 zk-verifier 的 `register_verifying_key` 参数一一对应
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltBn128VerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    pub ic: Vec<[u8; 64]>,
+}
+
+impl From<&VerifyingKey<Bn254>> for AltBn128VerifyingKey {
+    fn from(vk: &VerifyingKey<Bn254>) -> Self {
+        Self {
+            alpha_g1: g1_bytes(&vk.alpha_g1),
+            beta_g2: g2_bytes(&vk.beta_g2),
+            gamma_g2: g2_bytes(&vk.gamma_g2),
+            delta_g2: g2_bytes(&vk.delta_g2),
+            ic: vk.gamma_abc_g1.iter().map(g1_bytes).collect(),
+        }
+    }
+}
+
+pub fn g1_bytes(point: &G1Affine) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&fq_bytes(x));
+        bytes[32..].copy_from_slice(&fq_bytes(y));
+    }
+    bytes
+}
+
+pub fn g2_bytes(point: &G2Affine) -> [u8; 128] {
+    let mut bytes = [0u8; 128];
+    if let Some((x, y)) = point.xy() {
+        bytes[..64].copy_from_slice(&fq2_bytes(x));
+        bytes[64..].copy_from_slice(&fq2_bytes(y));
+    }
+    bytes
+}
+
+fn fq_bytes(value: &Fq) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&value.into_bigint().to_bytes_be());
+    bytes
+}
+
+/// 虚部在前
+fn fq2_bytes(value: &Fq2) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(&fq_bytes(&value.c1));
+    bytes[32..].copy_from_slice(&fq_bytes(&value.c0));
+    bytes
+}
@@ -1,9 +1,24 @@
 //! 零知识证明模块
 //!
-//! 使用 nori 作为零知识证明库
+//! 矩阵乘法工作量证明使用 arkworks Groth16（BN254），电路见 `zk_proof::circuits::matmul`
 
-// 使用 nori 库进行零知识证明
-// 注意：这里根据实际 nori 库的 API 进行调整
+pub mod alt_bn128;
+pub mod zk_proof;
+
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use alt_bn128::AltBn128Proof;
+use zk_proof::circuits::matmul::{
+    field_from_hex, field_to_be_bytes, poseidon_config, MatMulBatch, MatMulCircuit, MatMulShape, MatMulStatement,
+};
 
 /// 零知识证明配置
 #[derive(Debug, Clone)]
@@ -32,31 +47,141 @@ impl Default for ZkConfig {
 }
 
 /// 零知识证明生成器
+///
+/// 基于 Groth16 的矩阵乘法工作量证明。每种矩阵形状对应一套电路密钥，
+/// 生产环境应加载可信设置产生的密钥（`load_keys`），`setup_keys` 只用于开发和测试。
 pub struct ZkProver {
     config: ZkConfig,
+    poseidon: PoseidonConfig<Fr>,
+    keys: RwLock<HashMap<MatMulShape, Arc<CircuitKeys>>>,
+}
+
+/// 单个电路形状的证明/验证密钥
+pub struct CircuitKeys {
+    pub proving_key: ProvingKey<Bn254>,
+    pub verifying_key: VerifyingKey<Bn254>,
 }
 
 impl ZkProver {
     /// 创建新的零知识证明生成器
     pub fn new(config: ZkConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            poseidon: poseidon_config(),
+            keys: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 加载某个形状的证明密钥（压缩格式）
+    pub fn load_keys(&self, shape: MatMulShape, proving_key: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let proving_key = ProvingKey::<Bn254>::deserialize_compressed(proving_key)?;
+        let verifying_key = proving_key.vk.clone();
+        self.keys.write().insert(
+            shape,
+            Arc::new(CircuitKeys {
+                proving_key,
+                verifying_key,
+            }),
+        );
+        Ok(())
     }
-    
+
+    /// 本地生成某个形状的密钥（生成者掌握陷门，仅限开发和测试）
+    pub fn setup_keys(&self, shape: MatMulShape) -> Result<Arc<CircuitKeys>, Box<dyn std::error::Error>> {
+        let mut rng = ark_rng();
+        let (proving_key, verifying_key) =
+            Groth16::<Bn254>::circuit_specific_setup(MatMulCircuit::for_setup(shape), &mut rng)?;
+        let keys = Arc::new(CircuitKeys {
+            proving_key,
+            verifying_key,
+        });
+        self.keys.write().insert(shape, keys.clone());
+        Ok(keys)
+    }
+
+    fn keys_for(&self, shape: &MatMulShape) -> Result<Arc<CircuitKeys>, Box<dyn std::error::Error>> {
+        self.keys
+            .read()
+            .get(shape)
+            .cloned()
+            .ok_or_else(|| format!("缺少形状 {:?} 的电路密钥", shape).into())
+    }
+
     /// 生成零知识证明
+    ///
+    /// `statement` 为 JSON 编码的 `MatMulStatement`，`witness` 为 JSON 编码的 `MatMulBatch`。
+    /// 见证的输入/输出承诺必须与陈述一致，返回压缩格式的 Groth16 证明。
     pub fn generate_proof(&self, statement: &[u8], witness: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        // 使用 nori 库的实际功能来生成零知识证明
-        // 这是一个占位实现，实际使用时需要替换为 nori 的真实 API
-        Ok(vec![])
+        let statement: MatMulStatement = serde_json::from_slice(statement)?;
+        let batch: MatMulBatch = serde_json::from_slice(witness)?;
+        let proof = self.prove(batch, &statement)?;
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes)?;
+        if bytes.len() > self.config.max_proof_size {
+            return Err(format!("证明大小 {} 超过上限 {}", bytes.len(), self.config.max_proof_size).into());
+        }
+        Ok(bytes)
+    }
+
+    /// 为一条链上贡献记录生成证明，返回 alt_bn128 格式的证明和 zk-verifier 使用的公开输入
+    ///
+    /// `record_commitment` 为合约 `contribution_commitment` 的值，作为第一个公开输入绑定到证明上。
+    pub fn prove_contribution(
+        &self,
+        batch: MatMulBatch,
+        record_commitment: &[u8; 32],
+    ) -> Result<(AltBn128Proof, Vec<[u8; 32]>), Box<dyn std::error::Error>> {
+        let statement = batch.statement(&self.poseidon, record_commitment);
+        let proof = self.prove(batch, &statement)?;
+        Ok((AltBn128Proof::from(&proof), statement.scalar_inputs()?))
+    }
+
+    fn prove(
+        &self,
+        batch: MatMulBatch,
+        statement: &MatMulStatement,
+    ) -> Result<Proof<Bn254>, Box<dyn std::error::Error>> {
+        batch.validate()?;
+        let record_commitment = field_from_hex(&statement.record_commitment)?;
+        if batch.statement(&self.poseidon, &field_to_be_bytes(&record_commitment)) != *statement {
+            return Err("见证与承诺的输入/输出哈希不一致".into());
+        }
+
+        let keys = self.keys_for(&statement.shape)?;
+        let mut rng = ark_rng();
+        Ok(Groth16::<Bn254>::prove(
+            &keys.proving_key,
+            MatMulCircuit::for_proving(batch, self.poseidon.clone(), record_commitment),
+            &mut rng,
+        )?)
     }
-    
+
     /// 验证零知识证明
     pub fn verify_proof(&self, statement: &[u8], proof: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
-        // 使用 nori 库的实际功能来验证零知识证明
-        // 这是一个占位实现，实际使用时需要替换为 nori 的真实 API
-        Ok(true)
+        if proof.len() > self.config.max_proof_size {
+            return Ok(false);
+        }
+        let statement: MatMulStatement = serde_json::from_slice(statement)?;
+        let keys = self.keys_for(&statement.shape)?;
+        let proof = match Proof::<Bn254>::deserialize_compressed(proof) {
+            Ok(proof) => proof,
+            Err(_) => return Ok(false),
+        };
+        Ok(Groth16::<Bn254>::verify(
+            &keys.verifying_key,
+            &statement.public_inputs()?,
+            &proof,
+        )?)
     }
 }
 
+fn ark_rng() -> StdRng {
+    let mut seed = [0u8; 32];
+    rand::RngCore::fill_bytes(&mut rand::rng(), &mut seed);
+    StdRng::from_seed(seed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +192,29 @@ mod tests {
         let prover = ZkProver::new(config);
         assert!(true); // 简单测试，确保构造函数能正常工作
     }
+
+    #[test]
+    fn test_matmul_proof_roundtrip() {
+        let prover = ZkProver::new(ZkConfig::default());
+        let shape = MatMulShape { m: 2, n: 2, p: 2, batch: 1 };
+        prover.setup_keys(shape).unwrap();
+
+        let batch = MatMulBatch::execute(shape, vec![(vec![1, 2, 3, 4], vec![5, 6, 7, 8])]).unwrap();
+        let statement = serde_json::to_vec(&batch.statement(&poseidon_config(), &[1; 32])).unwrap();
+        let witness = serde_json::to_vec(&batch).unwrap();
+
+        let proof = prover.generate_proof(&statement, &witness).unwrap();
+        assert!(prover.verify_proof(&statement, &proof).unwrap());
+
+        // 换一个贡献记录承诺，同一证明不再有效
+        let rebound = serde_json::to_vec(&batch.statement(&poseidon_config(), &[2; 32])).unwrap();
+        assert!(!prover.verify_proof(&rebound, &proof).unwrap());
+
+        // 换一个输出承诺，同一证明不再有效
+        let mut other = batch.clone();
+        other.instances[0].c[0] += 1;
+        let forged = serde_json::to_vec(&other.statement(&poseidon_config(), &[1; 32])).unwrap();
+        assert!(!prover.verify_proof(&forged, &proof).unwrap());
+        assert!(prover.generate_proof(&forged, &witness).is_err());
+    }
 }
\ No newline at end of file
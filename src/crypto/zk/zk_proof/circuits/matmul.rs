@@ -0,0 +1,377 @@
+//! 矩阵乘法工作量证明电路（Groth16 / BN254）
+//!
+//! 证明节点对一批矩阵执行了 C = A × B，且：
+//! - 输入承诺 = Poseidon(形状, 所有 A、B 元素)
+//! - 输出承诺 = Poseidon(形状, 所有 C 元素)
+//!
+//! 公开输入依次为贡献记录承诺、输入承诺和输出承诺，矩阵本身作为私有见证，不会随证明泄露。
+//! 记录承诺即合约的 `contribution_commitment`（SHA-256 截到 253 位的大端序标量），
+//! 把证明绑定到一条链上贡献记录，同一证明不能套用到其他记录。
+//! 矩阵元素为量化后的整数（i32），输出在 i64 范围内精确计算。
+
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge};
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+
+/// Poseidon 参数（t = 3, α = 5, 8 个完整轮 + 57 个部分轮）
+const POSEIDON_RATE: usize = 2;
+const POSEIDON_FULL_ROUNDS: usize = 8;
+const POSEIDON_PARTIAL_ROUNDS: usize = 57;
+const POSEIDON_ALPHA: u64 = 5;
+
+/// 承诺的域分隔标签
+const INPUT_DOMAIN: u64 = 0x6d6d_696e; // "mmin"
+const OUTPUT_DOMAIN: u64 = 0x6d6d_6f75; // "mmou"
+
+/// 电路使用的 Poseidon 配置
+pub fn poseidon_config() -> PoseidonConfig<Fr> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+        Fr::MODULUS_BIT_SIZE as u64,
+        POSEIDON_RATE,
+        POSEIDON_FULL_ROUNDS as u64,
+        POSEIDON_PARTIAL_ROUNDS as u64,
+        0,
+    );
+    PoseidonConfig::new(
+        POSEIDON_FULL_ROUNDS,
+        POSEIDON_PARTIAL_ROUNDS,
+        POSEIDON_ALPHA,
+        mds,
+        ark,
+        POSEIDON_RATE,
+        1,
+    )
+}
+
+/// 一批矩阵乘法的形状：batch 个 (m × n) · (n × p)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MatMulShape {
+    pub m: usize,
+    pub n: usize,
+    pub p: usize,
+    pub batch: usize,
+}
+
+impl MatMulShape {
+    fn header(&self) -> [Fr; 4] {
+        [
+            Fr::from(self.m as u64),
+            Fr::from(self.n as u64),
+            Fr::from(self.p as u64),
+            Fr::from(self.batch as u64),
+        ]
+    }
+}
+
+/// 单次矩阵乘法（行优先存储）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatMulInstance {
+    pub a: Vec<i32>,
+    pub b: Vec<i32>,
+    pub c: Vec<i64>,
+}
+
+/// 一批矩阵乘法及其结果（证明的私有见证）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatMulBatch {
+    pub shape: MatMulShape,
+    pub instances: Vec<MatMulInstance>,
+}
+
+impl MatMulBatch {
+    /// 执行一批矩阵乘法
+    pub fn execute(shape: MatMulShape, inputs: Vec<(Vec<i32>, Vec<i32>)>) -> anyhow::Result<Self> {
+        if inputs.len() != shape.batch {
+            return Err(anyhow::anyhow!("批大小不匹配: 期望 {}, 实际 {}", shape.batch, inputs.len()));
+        }
+        let instances = inputs
+            .into_iter()
+            .map(|(a, b)| {
+                if a.len() != shape.m * shape.n || b.len() != shape.n * shape.p {
+                    return Err(anyhow::anyhow!("矩阵尺寸与形状不匹配"));
+                }
+                let mut c = vec![0i64; shape.m * shape.p];
+                for i in 0..shape.m {
+                    for j in 0..shape.p {
+                        let mut acc = 0i64;
+                        for k in 0..shape.n {
+                            acc = acc
+                                .checked_add(a[i * shape.n + k] as i64 * b[k * shape.p + j] as i64)
+                                .ok_or_else(|| anyhow::anyhow!("矩阵乘法结果溢出 i64"))?;
+                        }
+                        c[i * shape.p + j] = acc;
+                    }
+                }
+                Ok(MatMulInstance { a, b, c })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { shape, instances })
+    }
+
+    /// 检查形状并重新验算结果
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let inputs = self.instances.iter().map(|i| (i.a.clone(), i.b.clone())).collect();
+        let expected = Self::execute(self.shape, inputs)?;
+        if expected.instances != self.instances {
+            return Err(anyhow::anyhow!("矩阵乘法结果与输入不一致"));
+        }
+        Ok(())
+    }
+
+    /// 输入承诺
+    pub fn input_commitment(&self, config: &PoseidonConfig<Fr>) -> Fr {
+        let mut elements = vec![Fr::from(INPUT_DOMAIN)];
+        elements.extend(self.shape.header());
+        for instance in &self.instances {
+            elements.extend(instance.a.iter().map(|&v| i64_to_field(v as i64)));
+            elements.extend(instance.b.iter().map(|&v| i64_to_field(v as i64)));
+        }
+        poseidon_hash(config, &elements)
+    }
+
+    /// 输出承诺
+    pub fn output_commitment(&self, config: &PoseidonConfig<Fr>) -> Fr {
+        let mut elements = vec![Fr::from(OUTPUT_DOMAIN)];
+        elements.extend(self.shape.header());
+        for instance in &self.instances {
+            elements.extend(instance.c.iter().map(|&v| i64_to_field(v)));
+        }
+        poseidon_hash(config, &elements)
+    }
+
+    /// 证明对应的公开陈述，`record_commitment` 为要绑定的贡献记录承诺
+    pub fn statement(&self, config: &PoseidonConfig<Fr>, record_commitment: &[u8; 32]) -> MatMulStatement {
+        MatMulStatement {
+            shape: self.shape,
+            record_commitment: field_to_hex(&record_commitment_field(record_commitment)),
+            input_commitment: field_to_hex(&self.input_commitment(config)),
+            output_commitment: field_to_hex(&self.output_commitment(config)),
+        }
+    }
+}
+
+/// 公开陈述：形状、贡献记录承诺和输入/输出承诺
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatMulStatement {
+    pub shape: MatMulShape,
+    pub record_commitment: String,
+    pub input_commitment: String,
+    pub output_commitment: String,
+}
+
+impl MatMulStatement {
+    /// Groth16 验证使用的公开输入
+    pub fn public_inputs(&self) -> anyhow::Result<Vec<Fr>> {
+        Ok(vec![
+            field_from_hex(&self.record_commitment)?,
+            field_from_hex(&self.input_commitment)?,
+            field_from_hex(&self.output_commitment)?,
+        ])
+    }
+
+    /// 链上 zk-verifier 使用的公开输入（大端序标量），第一个即贡献记录承诺
+    pub fn scalar_inputs(&self) -> anyhow::Result<Vec<[u8; 32]>> {
+        Ok(self.public_inputs()?.iter().map(field_to_be_bytes).collect())
+    }
+}
+
+/// 矩阵乘法电路
+///
+/// `witness` 为 None 时只用于生成密钥（形状决定电路结构）。
+pub struct MatMulCircuit {
+    pub shape: MatMulShape,
+    pub config: PoseidonConfig<Fr>,
+    pub witness: Option<MatMulBatch>,
+    pub record_commitment: Option<Fr>,
+}
+
+impl MatMulCircuit {
+    /// 用于密钥生成的空电路
+    pub fn for_setup(shape: MatMulShape) -> Self {
+        Self {
+            shape,
+            config: poseidon_config(),
+            witness: None,
+            record_commitment: None,
+        }
+    }
+
+    /// 用于证明的电路
+    pub fn for_proving(batch: MatMulBatch, config: PoseidonConfig<Fr>, record_commitment: Fr) -> Self {
+        Self {
+            shape: batch.shape,
+            config,
+            witness: Some(batch),
+            record_commitment: Some(record_commitment),
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for MatMulCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let shape = self.shape;
+        let witness = self.witness.as_ref();
+        if let Some(batch) = witness {
+            if batch.shape != shape || batch.instances.len() != shape.batch {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
+
+        // 公开输入；记录承诺不参与计算，只随公开输入绑定到证明上
+        let _record_commitment = FpVar::new_input(cs.clone(), || {
+            self.record_commitment.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let input_commitment = FpVar::new_input(cs.clone(), || {
+            witness
+                .map(|b| b.input_commitment(&self.config))
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let output_commitment = FpVar::new_input(cs.clone(), || {
+            witness
+                .map(|b| b.output_commitment(&self.config))
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let header: Vec<FpVar<Fr>> = shape.header().iter().map(|v| FpVar::constant(*v)).collect();
+        let mut input_elements = vec![FpVar::constant(Fr::from(INPUT_DOMAIN))];
+        input_elements.extend(header.iter().cloned());
+        let mut output_elements = vec![FpVar::constant(Fr::from(OUTPUT_DOMAIN))];
+        output_elements.extend(header);
+
+        for index in 0..shape.batch {
+            let instance = witness.map(|b| &b.instances[index]);
+            let a = alloc_matrix(&cs, shape.m * shape.n, instance.map(|i| i.a.as_slice()))?;
+            let b = alloc_matrix(&cs, shape.n * shape.p, instance.map(|i| i.b.as_slice()))?;
+
+            // C[i][j] = Σ A[i][k] · B[k][j]，每个乘积一个约束
+            for i in 0..shape.m {
+                for j in 0..shape.p {
+                    let mut acc = FpVar::zero();
+                    for k in 0..shape.n {
+                        acc += &a[i * shape.n + k] * &b[k * shape.p + j];
+                    }
+                    output_elements.push(acc);
+                }
+            }
+            input_elements.extend(a);
+            input_elements.extend(b);
+        }
+
+        let mut sponge = PoseidonSpongeVar::new(cs.clone(), &self.config);
+        sponge.absorb(&input_elements)?;
+        sponge.squeeze_field_elements(1)?[0].enforce_equal(&input_commitment)?;
+
+        let mut sponge = PoseidonSpongeVar::new(cs, &self.config);
+        sponge.absorb(&output_elements)?;
+        sponge.squeeze_field_elements(1)?[0].enforce_equal(&output_commitment)?;
+
+        Ok(())
+    }
+}
+
+fn alloc_matrix(
+    cs: &ConstraintSystemRef<Fr>,
+    len: usize,
+    values: Option<&[i32]>,
+) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+    (0..len)
+        .map(|idx| {
+            FpVar::new_witness(cs.clone(), || {
+                values
+                    .and_then(|v| v.get(idx))
+                    .map(|&v| i64_to_field(v as i64))
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect()
+}
+
+fn poseidon_hash(config: &PoseidonConfig<Fr>, elements: &[Fr]) -> Fr {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&elements.to_vec());
+    sponge.squeeze_field_elements::<Fr>(1)[0]
+}
+
+/// 有符号整数映射到域元素（负数取加法逆元）
+pub fn i64_to_field(value: i64) -> Fr {
+    if value < 0 {
+        -Fr::from(value.unsigned_abs())
+    } else {
+        Fr::from(value as u64)
+    }
+}
+
+pub fn field_to_hex(value: &Fr) -> String {
+    let mut bytes = Vec::new();
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("域元素序列化不会失败");
+    hex::encode(bytes)
+}
+
+/// 贡献记录承诺（大端序）映射到域元素；合约生成的承诺小于 2^253，不会被约减
+pub fn record_commitment_field(bytes: &[u8; 32]) -> Fr {
+    Fr::from_be_bytes_mod_order(bytes)
+}
+
+/// 域元素的 32 字节大端序表示（alt_bn128 系统调用格式）
+pub fn field_to_be_bytes(value: &Fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&value.into_bigint().to_bytes_be());
+    bytes
+}
+
+pub fn field_from_hex(s: &str) -> anyhow::Result<Fr> {
+    let bytes = hex::decode(s)?;
+    Fr::deserialize_compressed(bytes.as_slice()).map_err(|e| anyhow::anyhow!("无效的域元素: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn sample_batch() -> MatMulBatch {
+        let shape = MatMulShape { m: 2, n: 3, p: 2, batch: 2 };
+        MatMulBatch::execute(
+            shape,
+            vec![
+                (vec![1, 2, 3, 4, 5, 6], vec![7, 8, 9, 10, 11, 12]),
+                (vec![-1, 0, 2, 3, -4, 1], vec![5, -6, 7, 8, 0, -2]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_matmul_circuit_is_satisfied_only_by_correct_outputs() {
+        let config = poseidon_config();
+        let batch = sample_batch();
+        assert_eq!(batch.instances[0].c, vec![58, 64, 139, 154]);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        MatMulCircuit::for_proving(batch.clone(), config.clone(), Fr::from(7u64))
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // 篡改输出后（承诺按篡改后的结果计算）电路不再满足
+        let mut forged = batch;
+        forged.instances[1].c[0] += 1;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        MatMulCircuit::for_proving(forged, config, Fr::from(7u64))
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
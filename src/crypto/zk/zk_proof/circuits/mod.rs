@@ -2,7 +2,11 @@
 //! 
 //! 定义用于算力验证的零知识证明电路
 
-use crate::zk_proof::{ComputeTask, TaskResult};
+use super::{ComputeTask, TaskResult};
+
+pub mod matmul;
+
+pub use matmul::{MatMulBatch, MatMulCircuit, MatMulInstance, MatMulShape, MatMulStatement};
 
 /// 计算电路接口
 pub trait ComputeCircuit {
@@ -0,0 +1,269 @@
+//! Groth16 证明聚合
+//!
+//! 同一电路（同一验证密钥）的 N 个证明用随机线性组合合并为一次多重配对检查：
+//!
+//! Π e(rᵢ·Aᵢ, Bᵢ) · e(-(Σrᵢ)·α, β) · e(-Σrᵢ·ICᵢ, γ) · e(-Σrᵢ·Cᵢ, δ) = 1
+//!
+//! 只需 N + 3 次 Miller loop 和一次最终幂运算，而逐个验证需要 3N 次配对。
+//! 系数 rᵢ 由所有证明和公开输入的 Fiat-Shamir 哈希导出，验证过程是确定性的，
+//! 可以在链上或 Workers 中重放。检查失败时可用 `locate_invalid` 找出具体的无效证明。
+
+use anyhow::{anyhow, Result};
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::zk::zk_proof::circuits::MatMulStatement;
+use crate::crypto::zk::zk_proof::ComputeProof;
+
+const TRANSCRIPT_DOMAIN: &str = "williw groth16 aggregation v1";
+
+/// 聚合后的证明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatedProof {
+    pub proof_ids: Vec<String>,
+    /// 各证明的公开陈述（与 `proofs` 一一对应）
+    pub statements: Vec<MatMulStatement>,
+    /// 压缩格式的 Groth16 证明
+    pub proofs: Vec<Vec<u8>>,
+    /// Fiat-Shamir 挑战（十六进制），用于核对聚合输入未被替换
+    pub challenge: String,
+}
+
+impl AggregatedProof {
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+}
+
+/// 聚合验证结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateVerification {
+    pub valid: bool,
+    pub total_proofs: usize,
+    /// 聚合检查失败时逐个定位出的无效证明
+    pub invalid_proof_ids: Vec<String>,
+}
+
+struct DecodedItem {
+    proof: Proof<Bn254>,
+    public_inputs: Vec<Fr>,
+}
+
+/// 把一组计算证明聚合为一次验证
+///
+/// `ComputeProof::public_inputs` 为 JSON 编码的 `MatMulStatement`，
+/// `proof_data` 为压缩格式的 Groth16 证明。
+pub fn aggregate_proofs(proofs: &[ComputeProof]) -> Result<AggregatedProof> {
+    if proofs.is_empty() {
+        return Err(anyhow!("没有可聚合的证明"));
+    }
+    let statements = proofs
+        .iter()
+        .map(|p| serde_json::from_slice::<MatMulStatement>(&p.public_inputs))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if statements.iter().any(|s| s.shape != statements[0].shape) {
+        return Err(anyhow!("只能聚合同一电路形状的证明"));
+    }
+
+    let raw: Vec<Vec<u8>> = proofs.iter().map(|p| p.proof_data.clone()).collect();
+    let items = decode(&statements, &raw)?;
+    Ok(AggregatedProof {
+        proof_ids: proofs.iter().map(|p| p.proof_id.clone()).collect(),
+        statements,
+        proofs: raw,
+        challenge: hex::encode(transcript(&items)?),
+    })
+}
+
+/// 一次多重配对验证整个聚合
+pub fn verify_aggregated(vk: &VerifyingKey<Bn254>, aggregated: &AggregatedProof) -> Result<bool> {
+    if aggregated.is_empty() || aggregated.statements.len() != aggregated.proofs.len() {
+        return Ok(false);
+    }
+    let items = decode(&aggregated.statements, &aggregated.proofs)?;
+    let challenge = transcript(&items)?;
+    if hex::encode(challenge) != aggregated.challenge {
+        return Ok(false);
+    }
+    batch_verify(vk, &items, &challenge)
+}
+
+/// 聚合验证；失败时逐个验证定位无效证明
+pub fn verify_aggregated_or_locate(
+    vk: &VerifyingKey<Bn254>,
+    aggregated: &AggregatedProof,
+) -> Result<AggregateVerification> {
+    let valid = verify_aggregated(vk, aggregated)?;
+    let invalid_proof_ids = if valid {
+        Vec::new()
+    } else {
+        locate_invalid(vk, aggregated)?
+    };
+    Ok(AggregateVerification {
+        valid,
+        total_proofs: aggregated.len(),
+        invalid_proof_ids,
+    })
+}
+
+/// 逐个验证，返回无效证明的 ID
+pub fn locate_invalid(vk: &VerifyingKey<Bn254>, aggregated: &AggregatedProof) -> Result<Vec<String>> {
+    let prepared = Groth16::<Bn254>::process_vk(vk)?;
+    let items = decode(&aggregated.statements, &aggregated.proofs)?;
+    let mut invalid = Vec::new();
+    for (id, item) in aggregated.proof_ids.iter().zip(items.iter()) {
+        if !Groth16::<Bn254>::verify_with_processed_vk(&prepared, &item.public_inputs, &item.proof)? {
+            invalid.push(id.clone());
+        }
+    }
+    Ok(invalid)
+}
+
+fn decode(statements: &[MatMulStatement], proofs: &[Vec<u8>]) -> Result<Vec<DecodedItem>> {
+    statements
+        .iter()
+        .zip(proofs)
+        .map(|(statement, bytes)| {
+            Ok(DecodedItem {
+                proof: Proof::<Bn254>::deserialize_compressed(bytes.as_slice())
+                    .map_err(|e| anyhow!("无效的 Groth16 证明: {}", e))?,
+                public_inputs: statement.public_inputs()?,
+            })
+        })
+        .collect()
+}
+
+fn transcript(items: &[DecodedItem]) -> Result<[u8; 32]> {
+    let mut hasher = blake3::Hasher::new_derive_key(TRANSCRIPT_DOMAIN);
+    hasher.update(&(items.len() as u64).to_le_bytes());
+    let mut buf = Vec::new();
+    for item in items {
+        buf.clear();
+        item.proof.serialize_compressed(&mut buf)?;
+        item.public_inputs.serialize_compressed(&mut buf)?;
+        hasher.update(&buf);
+    }
+    Ok(*hasher.finalize().as_bytes())
+}
+
+fn coefficient(challenge: &[u8; 32], index: usize) -> Fr {
+    let mut hasher = blake3::Hasher::new_keyed(challenge);
+    hasher.update(&(index as u64).to_le_bytes());
+    Fr::from_le_bytes_mod_order(hasher.finalize().as_bytes())
+}
+
+fn batch_verify(vk: &VerifyingKey<Bn254>, items: &[DecodedItem], challenge: &[u8; 32]) -> Result<bool> {
+    let mut g1 = Vec::with_capacity(items.len() + 3);
+    let mut g2 = Vec::with_capacity(items.len() + 3);
+    let mut r_sum = Fr::zero();
+    let mut ic_acc = G1Projective::zero();
+    let mut c_acc = G1Projective::zero();
+
+    for (index, item) in items.iter().enumerate() {
+        if item.public_inputs.len() + 1 != vk.gamma_abc_g1.len() {
+            return Err(anyhow!("公开输入数量与验证密钥不匹配"));
+        }
+        let r = coefficient(challenge, index);
+        let mut ic = vk.gamma_abc_g1[0].into_group();
+        for (input, base) in item.public_inputs.iter().zip(&vk.gamma_abc_g1[1..]) {
+            ic += *base * *input;
+        }
+
+        g1.push((item.proof.a * r).into_affine());
+        g2.push(item.proof.b);
+        ic_acc += ic * r;
+        c_acc += item.proof.c * r;
+        r_sum += r;
+    }
+
+    g1.push((vk.alpha_g1 * -r_sum).into_affine());
+    g2.push(vk.beta_g2);
+    g1.push((-ic_acc).into_affine());
+    g2.push(vk.gamma_g2);
+    g1.push((-c_acc).into_affine());
+    g2.push(vk.delta_g2);
+
+    Ok(Bn254::multi_pairing(g1, g2).is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::zk::zk_proof::circuits::matmul::poseidon_config;
+    use crate::crypto::zk::zk_proof::circuits::{MatMulBatch, MatMulShape};
+    use crate::crypto::zk::{ZkConfig, ZkProver};
+    use std::time::Instant;
+
+    fn compute_proofs(count: usize) -> (VerifyingKey<Bn254>, Vec<ComputeProof>) {
+        let prover = ZkProver::new(ZkConfig::default());
+        let shape = MatMulShape { m: 1, n: 2, p: 1, batch: 1 };
+        let keys = prover.setup_keys(shape).unwrap();
+        let proofs = (0..count)
+            .map(|i| {
+                let batch = MatMulBatch::execute(shape, vec![(vec![i as i32, 2], vec![3, -(i as i32)])]).unwrap();
+                let statement = serde_json::to_vec(&batch.statement(&poseidon_config(), &[i as u8; 32])).unwrap();
+                let witness = serde_json::to_vec(&batch).unwrap();
+                ComputeProof {
+                    proof_id: format!("proof-{}", i),
+                    task_id: "task".to_string(),
+                    node_id: "node".to_string(),
+                    proof_data: prover.generate_proof(&statement, &witness).unwrap(),
+                    public_inputs: statement,
+                    timestamp: 0,
+                    valid: true,
+                }
+            })
+            .collect();
+        (keys.verifying_key.clone(), proofs)
+    }
+
+    #[test]
+    fn test_aggregated_verification_detects_invalid_proof() {
+        let (vk, mut proofs) = compute_proofs(4);
+        let aggregated = aggregate_proofs(&proofs).unwrap();
+        assert!(verify_aggregated(&vk, &aggregated).unwrap());
+
+        // 把第 2 个证明换成第 3 个的证明数据
+        proofs[2].proof_data = proofs[3].proof_data.clone();
+        let aggregated = aggregate_proofs(&proofs).unwrap();
+        let result = verify_aggregated_or_locate(&vk, &aggregated).unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.invalid_proof_ids, vec!["proof-2".to_string()]);
+    }
+
+    /// 基准：cargo test --release --features zk_proof bench_aggregation -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_aggregation_vs_individual() {
+        for count in [16usize, 64, 256] {
+            let (vk, proofs) = compute_proofs(count);
+            let aggregated = aggregate_proofs(&proofs).unwrap();
+
+            let started = Instant::now();
+            assert!(locate_invalid(&vk, &aggregated).unwrap().is_empty());
+            let individual = started.elapsed();
+
+            let started = Instant::now();
+            assert!(verify_aggregated(&vk, &aggregated).unwrap());
+            let batched = started.elapsed();
+
+            println!(
+                "[基准] {} 个证明: 逐个验证 {:?}, 聚合验证 {:?} ({:.1}x)",
+                count,
+                individual,
+                batched,
+                individual.as_secs_f64() / batched.as_secs_f64()
+            );
+        }
+    }
+}
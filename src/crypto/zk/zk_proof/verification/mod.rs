@@ -5,6 +5,10 @@
 use crate::zk_proof::{ZKConfig, ComputeProof, ZKVerifier};
 use anyhow::Result;
 
+pub mod aggregation;
+
+pub use aggregation::{aggregate_proofs, verify_aggregated, AggregateVerification, AggregatedProof};
+
 /// 验证结果
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VerificationResult {
@@ -0,0 +1,406 @@
+//! 数据集完整性与来源清单
+//!
+//! 数据发布者为数据集生成清单并用身份密钥签名，清单列出每个分块的哈希、大小、来源 URL 和许可证：
+//! - 节点训练前加载清单，校验发布者签名、许可证白名单，并逐块核对本地数据
+//! - 任何分块缺失、大小或哈希不符都拒绝训练
+//! - 验证通过的清单哈希写入算力贡献记录，链上可追溯训练所用的数据
+
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+
+/// 数据集清单配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatasetConfig {
+    /// 签名清单文件；未设置时不做数据集校验
+    pub manifest_path: Option<PathBuf>,
+    /// 分块所在目录，默认为清单文件所在目录
+    pub data_dir: Option<PathBuf>,
+    /// 信任的发布者节点 ID，为空时接受任意有效签名
+    pub trusted_publishers: Vec<String>,
+    /// 允许的许可证标签，为空时不限制
+    pub allowed_licenses: Vec<String>,
+}
+
+impl DatasetConfig {
+    /// 加载并完整校验配置的清单（签名、许可证和全部分块）；未配置清单时返回 None
+    pub fn load_verified(&self) -> anyhow::Result<Option<VerifiedDataset>> {
+        let Some(path) = &self.manifest_path else {
+            return Ok(None);
+        };
+        let verified = SignedDatasetManifest::load(path)?.verify(self)?;
+        verified.verify_chunks(&self.resolved_data_dir().unwrap_or_default())?;
+        Ok(Some(verified))
+    }
+
+    /// 分块目录
+    pub fn resolved_data_dir(&self) -> Option<PathBuf> {
+        self.data_dir.clone().or_else(|| {
+            self.manifest_path
+                .as_ref()
+                .map(|path| path.parent().map(Path::to_path_buf).unwrap_or_default())
+        })
+    }
+}
+
+/// 数据分块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetChunk {
+    /// 相对数据目录的路径
+    pub path: String,
+    /// 内容哈希（blake3 十六进制）
+    pub hash: String,
+    pub size: u64,
+    /// 数据来源
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// 许可证标签（SPDX 标识，如 `CC-BY-4.0`）
+    pub license: String,
+}
+
+impl DatasetChunk {
+    /// 读取本地文件生成分块记录
+    pub fn from_file(
+        data_dir: &Path,
+        path: &str,
+        source_url: Option<String>,
+        license: &str,
+    ) -> Result<Self, DatasetRejection> {
+        let bytes = read_chunk(data_dir, path)?;
+        Ok(Self {
+            path: path.to_string(),
+            hash: blake3::hash(&bytes).to_hex().to_string(),
+            size: bytes.len() as u64,
+            source_url,
+            license: license.to_string(),
+        })
+    }
+
+    /// 核对分块内容
+    pub fn check(&self, bytes: &[u8]) -> Result<(), DatasetRejection> {
+        if bytes.len() as u64 != self.size {
+            return Err(DatasetRejection::SizeMismatch {
+                path: self.path.clone(),
+                expected: self.size,
+                actual: bytes.len() as u64,
+            });
+        }
+        let actual = blake3::hash(bytes).to_hex().to_string();
+        if actual != self.hash {
+            return Err(DatasetRejection::HashMismatch {
+                path: self.path.clone(),
+                expected: self.hash.clone(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 数据集清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    pub name: String,
+    pub version: String,
+    pub chunks: Vec<DatasetChunk>,
+    /// 生成时间（Unix 秒）
+    pub created_at: i64,
+    /// 签名的发布者
+    pub publisher_id: String,
+}
+
+impl DatasetManifest {
+    /// 为目录下的全部文件生成清单（按路径排序），`source_url` 为分块 URL 前缀，`exclude` 中的文件不计入
+    pub fn from_dir(
+        data_dir: &Path,
+        name: &str,
+        version: &str,
+        license: &str,
+        source_url: Option<&str>,
+        exclude: &[PathBuf],
+    ) -> anyhow::Result<Self> {
+        let mut files = Vec::new();
+        collect_files(data_dir, data_dir, &mut files)?;
+        files.retain(|(full, _)| !exclude.iter().any(|excluded| excluded == full));
+        files.sort_by(|a, b| a.1.cmp(&b.1));
+        let chunks = files
+            .iter()
+            .map(|(_, path)| {
+                let url = source_url.map(|base| format!("{}/{}", base.trim_end_matches('/'), path));
+                DatasetChunk::from_file(data_dir, path, url, license)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            chunks,
+            created_at: chrono::Utc::now().timestamp(),
+            publisher_id: String::new(),
+        })
+    }
+
+    /// 规范化字节（签名和哈希的输入）
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// 清单哈希（blake3 十六进制）
+    pub fn hash(&self) -> String {
+        blake3::hash(&self.canonical_bytes()).to_hex().to_string()
+    }
+
+    /// 清单涉及的许可证（去重、排序）
+    pub fn licenses(&self) -> Vec<String> {
+        let mut licenses: Vec<String> = self.chunks.iter().map(|chunk| chunk.license.clone()).collect();
+        licenses.sort();
+        licenses.dedup();
+        licenses
+    }
+
+    /// 使用发布者身份签名
+    pub fn sign(mut self, identity: &NodeIdentity) -> SignedDatasetManifest {
+        self.publisher_id = identity.node_id();
+        let signature = identity.sign(&self.canonical_bytes());
+        SignedDatasetManifest {
+            manifest: self,
+            signature: Some(signature),
+        }
+    }
+}
+
+/// 带签名的数据集清单（以 JSON 文件分发）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedDatasetManifest {
+    pub manifest: DatasetManifest,
+    #[serde(default)]
+    pub signature: Option<SolSignature>,
+}
+
+impl SignedDatasetManifest {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 校验签名、发布者和许可证
+    pub fn verify(&self, config: &DatasetConfig) -> Result<VerifiedDataset, DatasetRejection> {
+        let manifest = &self.manifest;
+        let signature = self.signature.as_ref().ok_or(DatasetRejection::Unsigned)?;
+        if signature.pubkey != manifest.publisher_id {
+            return Err(DatasetRejection::BadSignature);
+        }
+        if !config.trusted_publishers.is_empty() && !config.trusted_publishers.contains(&manifest.publisher_id) {
+            return Err(DatasetRejection::UntrustedPublisher {
+                publisher: manifest.publisher_id.clone(),
+            });
+        }
+        if !verify_signature(&manifest.canonical_bytes(), signature) {
+            return Err(DatasetRejection::BadSignature);
+        }
+        if !config.allowed_licenses.is_empty() {
+            if let Some(license) = manifest
+                .licenses()
+                .into_iter()
+                .find(|license| !config.allowed_licenses.contains(license))
+            {
+                return Err(DatasetRejection::LicenseNotAllowed { license });
+            }
+        }
+        Ok(VerifiedDataset {
+            manifest: manifest.clone(),
+            manifest_hash: manifest.hash(),
+        })
+    }
+}
+
+/// 签名验证通过的清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifiedDataset {
+    pub manifest: DatasetManifest,
+    pub manifest_hash: String,
+}
+
+impl VerifiedDataset {
+    /// 逐块核对本地数据
+    pub fn verify_chunks(&self, data_dir: &Path) -> Result<(), DatasetRejection> {
+        for chunk in &self.manifest.chunks {
+            chunk.check(&read_chunk(data_dir, &chunk.path)?)?;
+        }
+        Ok(())
+    }
+
+    /// 查找本地文件对应的分块，文件不在数据目录下或不在清单中时返回 None
+    pub fn chunk_for_file(&self, data_dir: &Path, file: &Path) -> Option<&DatasetChunk> {
+        let relative = file.strip_prefix(data_dir).ok()?;
+        self.manifest
+            .chunks
+            .iter()
+            .find(|chunk| Path::new(&chunk.path).components().eq(relative.components()))
+    }
+}
+
+/// 拒绝原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DatasetRejection {
+    #[error("数据集清单未签名")]
+    Unsigned,
+
+    #[error("发布者 {publisher} 不在信任列表中")]
+    UntrustedPublisher { publisher: String },
+
+    #[error("数据集清单签名无效")]
+    BadSignature,
+
+    #[error("许可证 {license} 不在允许列表中")]
+    LicenseNotAllowed { license: String },
+
+    #[error("分块路径 {path} 超出数据目录")]
+    InvalidPath { path: String },
+
+    #[error("读取分块 {path} 失败: {reason}")]
+    MissingChunk { path: String, reason: String },
+
+    #[error("分块 {path} 大小不符: 期望 {expected}，实际 {actual}")]
+    SizeMismatch { path: String, expected: u64, actual: u64 },
+
+    #[error("分块 {path} 哈希不符: 期望 {expected}，实际 {actual}")]
+    HashMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// 递归收集文件，返回 (完整路径, 以 `/` 分隔的相对路径)
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, String)>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((path.clone(), relative));
+        }
+    }
+    Ok(())
+}
+
+/// 读取分块，拒绝绝对路径和 `..`，避免清单读取数据目录之外的文件
+fn read_chunk(data_dir: &Path, path: &str) -> Result<Vec<u8>, DatasetRejection> {
+    let relative = Path::new(path);
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(DatasetRejection::InvalidPath { path: path.to_string() });
+    }
+    std::fs::read(data_dir.join(relative)).map_err(|e| DatasetRejection::MissingChunk {
+        path: path.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::FileKeystore;
+    use std::sync::Arc;
+
+    fn identity(name: &str) -> NodeIdentity {
+        let path = std::env::temp_dir().join(format!("williw-dataset-{}-{}.json", name, uuid::Uuid::new_v4()));
+        NodeIdentity::with_keystore(Arc::new(FileKeystore::new(path))).unwrap()
+    }
+
+    fn manifest(dir: &Path) -> DatasetManifest {
+        std::fs::write(dir.join("a.jsonl"), b"{\"text\": \"hello\"}\n").unwrap();
+        std::fs::write(dir.join("b.jsonl"), b"{\"text\": \"world\"}\n").unwrap();
+        let chunks = ["a.jsonl", "b.jsonl"]
+            .iter()
+            .map(|path| DatasetChunk::from_file(dir, path, Some(format!("https://example.org/{}", path)), "CC-BY-4.0"))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        DatasetManifest {
+            name: "demo".to_string(),
+            version: "1".to_string(),
+            chunks,
+            created_at: 100,
+            publisher_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_detects_tampered_chunks() {
+        let dir = std::env::temp_dir().join(format!("williw-dataset-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let publisher = identity("publisher");
+        let signed = manifest(&dir).sign(&publisher);
+        let path = dir.join("manifest.json");
+        signed.save(&path).unwrap();
+
+        let loaded = SignedDatasetManifest::load(&path).unwrap();
+        let verified = loaded.verify(&DatasetConfig::default()).unwrap();
+        assert_eq!(verified.manifest_hash, signed.manifest.hash());
+        verified.verify_chunks(&dir).unwrap();
+        assert!(verified.chunk_for_file(&dir, &dir.join("a.jsonl")).is_some());
+        assert!(verified.chunk_for_file(&dir, &dir.join("c.jsonl")).is_none());
+
+        std::fs::write(dir.join("b.jsonl"), b"{\"text\": \"w0rld\"}\n").unwrap();
+        assert!(matches!(
+            verified.verify_chunks(&dir),
+            Err(DatasetRejection::HashMismatch { path, .. }) if path == "b.jsonl"
+        ));
+        assert!(matches!(
+            read_chunk(&dir, "../escape"),
+            Err(DatasetRejection::InvalidPath { .. })
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rejects_untrusted_publisher_and_license() {
+        let dir = std::env::temp_dir().join(format!("williw-dataset-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let publisher = identity("publisher");
+        let signed = manifest(&dir).sign(&publisher);
+
+        let config = DatasetConfig {
+            trusted_publishers: vec![identity("other").node_id()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            signed.verify(&config),
+            Err(DatasetRejection::UntrustedPublisher { .. })
+        ));
+
+        let config = DatasetConfig {
+            trusted_publishers: vec![publisher.node_id()],
+            allowed_licenses: vec!["MIT".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            signed.verify(&config).unwrap_err(),
+            DatasetRejection::LicenseNotAllowed {
+                license: "CC-BY-4.0".to_string()
+            }
+        );
+
+        let mut tampered = signed.clone();
+        tampered.manifest.chunks[0].license = "MIT".to_string();
+        assert_eq!(
+            tampered.verify(&DatasetConfig::default()).unwrap_err(),
+            DatasetRejection::BadSignature
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,319 @@
+//! 设备基准测试
+//!
+//! 用统一的微基准（矩阵乘法 FLOPS、内存带宽、哈希/签名吞吐）测量设备实际算力，
+//! 按参考设备校准为 `DeviceScore`（参考设备为 1.0），并用节点身份签名。
+//! 校准分用于推荐模型维度、切分方案中的节点算力，以及链上贡献评分，
+//! 取代仅凭硬件参数估算的 `performance_score`。
+//!
+//! 本 crate 没有直接的 GPU 计算后端，GPU 测试通过平台注册的 [`GpuProbe`] 完成
+//! （例如移动端通过 FFI 提供），未注册时只测 CPU。
+
+use crate::crypto::identity::verify_signature;
+use crate::crypto::{NodeIdentity, SolSignature};
+use crate::device::capabilities::DeviceCapabilities;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 测试套件版本，测试项或校准参数变化时递增，不同版本的分数不可比较
+pub const BENCHMARK_SUITE_VERSION: u32 = 1;
+
+// 参考设备（约为 8 核桌面 CPU）的测量值，校准分 1.0
+const REFERENCE_MATMUL_GFLOPS: f64 = 50.0;
+const REFERENCE_MEMORY_GBPS: f64 = 10.0;
+const REFERENCE_BLAKE3_MB_PER_SEC: f64 = 1000.0;
+const REFERENCE_ED25519_SIGNS_PER_SEC: f64 = 20000.0;
+
+// 各项在校准分中的权重（加权几何平均）
+const COMPUTE_WEIGHT: f64 = 0.6;
+const MEMORY_WEIGHT: f64 = 0.25;
+const CRYPTO_WEIGHT: f64 = 0.15;
+
+/// 平台提供的 GPU 测试
+pub trait GpuProbe: Send + Sync {
+    /// GPU 名称
+    fn name(&self) -> String;
+
+    /// 在 `duration` 内反复执行 `size x size` 的 f32 矩阵乘法，返回 GFLOPS
+    fn matmul_gflops(&self, size: usize, duration: Duration) -> Result<f64>;
+}
+
+/// 基准测试参数
+#[derive(Debug, Clone)]
+pub struct BenchmarkConfig {
+    /// 每项测试的持续时间
+    pub duration: Duration,
+    /// 矩阵乘法测试的矩阵边长
+    pub matrix_size: usize,
+    /// 内存带宽测试的缓冲区大小（MB）
+    pub memory_buffer_mb: usize,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(3),
+            matrix_size: 256,
+            memory_buffer_mb: 64,
+        }
+    }
+}
+
+/// 各项原始测量值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub matmul_gflops: f64,
+    pub memory_bandwidth_gbps: f64,
+    pub blake3_mb_per_sec: f64,
+    pub ed25519_signs_per_sec: f64,
+    /// GPU 矩阵乘法（未注册 GpuProbe 或测试失败时为 None）
+    pub gpu_gflops: Option<f64>,
+    pub gpu_name: Option<String>,
+}
+
+impl BenchmarkResult {
+    /// 可用于训练的有效算力（CPU 与 GPU 取较大者，GFLOPS）
+    pub fn effective_gflops(&self) -> f64 {
+        self.gpu_gflops.unwrap_or(0.0).max(self.matmul_gflops)
+    }
+
+    /// 按参考设备校准后的分数
+    pub fn calibrated_score(&self) -> f64 {
+        let ratio = |value: f64, reference: f64| (value.max(1e-9) / reference).ln();
+        let crypto = (ratio(self.blake3_mb_per_sec, REFERENCE_BLAKE3_MB_PER_SEC)
+            + ratio(self.ed25519_signs_per_sec, REFERENCE_ED25519_SIGNS_PER_SEC))
+            / 2.0;
+        (COMPUTE_WEIGHT * ratio(self.effective_gflops(), REFERENCE_MATMUL_GFLOPS)
+            + MEMORY_WEIGHT * ratio(self.memory_bandwidth_gbps, REFERENCE_MEMORY_GBPS)
+            + CRYPTO_WEIGHT * crypto)
+            .exp()
+    }
+}
+
+/// 校准后的设备算力分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceScore {
+    pub node_id: String,
+    pub suite_version: u32,
+    /// 测量时间（Unix 秒）
+    pub measured_at: i64,
+    pub cpu_cores: u32,
+    pub max_memory_mb: u64,
+    pub result: BenchmarkResult,
+    /// 校准分（参考设备为 1.0）
+    pub score: f64,
+}
+
+impl DeviceScore {
+    /// 按实测算力推荐模型维度，不超过内存允许的上限
+    pub fn recommended_model_dim(&self, capabilities: &DeviceCapabilities) -> usize {
+        let by_compute = if self.score >= 1.0 {
+            1024
+        } else if self.score >= 0.5 {
+            512
+        } else if self.score >= 0.25 {
+            256
+        } else {
+            128
+        };
+        by_compute.min(capabilities.recommended_model_dim())
+    }
+
+    /// 切分方案中使用的节点算力（有效 GFLOPS）
+    pub fn split_capacity(&self) -> f64 {
+        self.result.effective_gflops()
+    }
+
+    /// 用节点身份签名
+    pub fn sign(self, identity: &NodeIdentity) -> Result<SignedDeviceScore> {
+        if self.node_id != identity.node_id() {
+            return Err(anyhow!("分数的节点 ID 与签名身份不一致"));
+        }
+        let signature = identity.sign_record(&self)?;
+        Ok(SignedDeviceScore { score: self, signature })
+    }
+}
+
+/// 带节点签名的算力分，可随贡献记录或切分协商发给其他节点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedDeviceScore {
+    pub score: DeviceScore,
+    pub signature: SolSignature,
+}
+
+impl SignedDeviceScore {
+    /// 验证签名由分数所属节点签发，且套件版本一致
+    pub fn verify(&self) -> bool {
+        if self.signature.pubkey != self.score.node_id || self.score.suite_version != BENCHMARK_SUITE_VERSION {
+            return false;
+        }
+        serde_json::to_vec(&self.score)
+            .map(|payload| verify_signature(&payload, &self.signature))
+            .unwrap_or(false)
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
+
+/// 从已验证的分数生成切分方案使用的节点算力表（可直接传给 `model_splitter::assign_experts`），
+/// 签名无效的分数被忽略
+pub fn split_capacities(scores: &[SignedDeviceScore]) -> HashMap<String, f64> {
+    scores
+        .iter()
+        .filter(|s| s.verify())
+        .map(|s| (s.score.node_id.clone(), s.score.split_capacity()))
+        .collect()
+}
+
+/// 设备基准测试
+pub struct DeviceBenchmark {
+    config: BenchmarkConfig,
+    gpu: Option<Arc<dyn GpuProbe>>,
+}
+
+impl DeviceBenchmark {
+    pub fn new(config: BenchmarkConfig) -> Self {
+        Self { config, gpu: None }
+    }
+
+    pub fn with_gpu_probe(mut self, gpu: Arc<dyn GpuProbe>) -> Self {
+        self.gpu = Some(gpu);
+        self
+    }
+
+    /// 运行全部测试项
+    pub fn run(&self) -> Result<BenchmarkResult> {
+        if self.config.matrix_size == 0 || self.config.memory_buffer_mb == 0 {
+            return Err(anyhow!("矩阵边长和内存缓冲区大小必须大于 0"));
+        }
+        let duration = self.config.duration;
+
+        let n = self.config.matrix_size;
+        let a = ndarray::Array2::<f32>::from_elem((n, n), 1.0);
+        let b = ndarray::Array2::<f32>::from_elem((n, n), 0.5);
+        let matmul_per_sec = ops_per_second(duration, || {
+            std::hint::black_box(a.dot(&b));
+        });
+
+        // 每次复制读写各一遍缓冲区
+        let buffer_bytes = self.config.memory_buffer_mb * 1024 * 1024;
+        let source = vec![0x5Au8; buffer_bytes];
+        let mut target = vec![0u8; buffer_bytes];
+        let copies_per_sec = ops_per_second(duration, || {
+            target.copy_from_slice(std::hint::black_box(&source));
+            std::hint::black_box(&mut target);
+        });
+
+        let buffer = vec![0xA5u8; 1024 * 1024];
+        let hashes_per_sec = ops_per_second(duration, || {
+            std::hint::black_box(blake3::hash(&buffer));
+        });
+
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let message = [0u8; 256];
+        let signs_per_sec = ops_per_second(duration, || {
+            use ed25519_dalek::Signer;
+            std::hint::black_box(signing_key.sign(&message));
+        });
+
+        let (gpu_gflops, gpu_name) = match &self.gpu {
+            Some(gpu) => match gpu.matmul_gflops(n, duration) {
+                Ok(gflops) => (Some(gflops), Some(gpu.name())),
+                Err(e) => {
+                    println!("[基准测试] GPU 测试失败，仅使用 CPU 结果: {:?}", e);
+                    (None, Some(gpu.name()))
+                }
+            },
+            None => (None, None),
+        };
+
+        Ok(BenchmarkResult {
+            matmul_gflops: matmul_per_sec * 2.0 * (n as f64).powi(3) / 1e9,
+            memory_bandwidth_gbps: copies_per_sec * 2.0 * buffer_bytes as f64 / 1e9,
+            blake3_mb_per_sec: hashes_per_sec,
+            ed25519_signs_per_sec: signs_per_sec,
+            gpu_gflops,
+            gpu_name,
+        })
+    }
+
+    /// 运行测试并生成本节点的校准分
+    pub fn score(&self, node_id: &str, capabilities: &DeviceCapabilities) -> Result<DeviceScore> {
+        let result = self.run()?;
+        Ok(DeviceScore {
+            node_id: node_id.to_string(),
+            suite_version: BENCHMARK_SUITE_VERSION,
+            measured_at: chrono::Utc::now().timestamp(),
+            cpu_cores: capabilities.cpu_cores,
+            max_memory_mb: capabilities.max_memory_mb,
+            score: result.calibrated_score(),
+            result,
+        })
+    }
+}
+
+/// 在 `duration` 内反复执行 `f`，返回每秒执行次数（至少执行一次）
+fn ops_per_second(duration: Duration, mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    let mut iterations = 0u64;
+    while iterations == 0 || start.elapsed() < duration {
+        f();
+        iterations += 1;
+    }
+    iterations as f64 / start.elapsed().as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_device_scores_one() {
+        let result = BenchmarkResult {
+            matmul_gflops: REFERENCE_MATMUL_GFLOPS,
+            memory_bandwidth_gbps: REFERENCE_MEMORY_GBPS,
+            blake3_mb_per_sec: REFERENCE_BLAKE3_MB_PER_SEC,
+            ed25519_signs_per_sec: REFERENCE_ED25519_SIGNS_PER_SEC,
+            gpu_gflops: Some(REFERENCE_MATMUL_GFLOPS * 4.0),
+            gpu_name: Some("test".to_string()),
+        };
+        // GPU 4 倍算力：0.6 * ln(4) 的提升
+        assert!((result.calibrated_score() - 4f64.powf(COMPUTE_WEIGHT)).abs() < 1e-9);
+
+        let cpu_only = BenchmarkResult { gpu_gflops: None, ..result };
+        assert!((cpu_only.calibrated_score() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_signed_score_verifies_and_rejects_tampering() {
+        let path = std::env::temp_dir().join(format!("williw-identity-{}.json", uuid::Uuid::new_v4()));
+        let identity = NodeIdentity::load_or_create(Some(path.as_path())).unwrap();
+        let benchmark = DeviceBenchmark::new(BenchmarkConfig {
+            duration: Duration::from_millis(10),
+            matrix_size: 8,
+            memory_buffer_mb: 1,
+        });
+        let capabilities = DeviceCapabilities::default();
+        let score = benchmark.score(&identity.node_id(), &capabilities).unwrap();
+        assert!(score.recommended_model_dim(&capabilities) <= capabilities.recommended_model_dim());
+
+        let signed = score.sign(&identity).unwrap();
+        assert!(signed.verify());
+        assert_eq!(split_capacities(std::slice::from_ref(&signed)).len(), 1);
+
+        let mut tampered = signed.clone();
+        tampered.score.score *= 10.0;
+        assert!(!tampered.verify());
+        assert!(split_capacities(&[tampered]).is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,12 +1,15 @@
+use crate::device::benchmark::DeviceScore;
 use crate::device::capabilities::DeviceCapabilities;
 use crate::device::detection::DeviceDetector;
-use crate::device::types::NetworkType;
+use crate::device::types::{NetworkType, ThermalState};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
 /// 设备能力管理器（支持运行时更新）
 pub struct DeviceManager {
     capabilities: Arc<RwLock<DeviceCapabilities>>,
+    thermal_state: Arc<RwLock<ThermalState>>,
+    score: Arc<RwLock<Option<DeviceScore>>>,
 }
 
 impl Clone for DeviceManager {
@@ -14,6 +17,8 @@ impl Clone for DeviceManager {
         // 仅克隆Arc指针，不复制内部数据
         Self {
             capabilities: Arc::clone(&self.capabilities),
+            thermal_state: Arc::clone(&self.thermal_state),
+            score: Arc::clone(&self.score),
         }
     }
 }
@@ -23,12 +28,16 @@ impl DeviceManager {
         let caps = DeviceDetector::detect();
         Self {
             capabilities: Arc::new(RwLock::new(caps)),
+            thermal_state: Arc::new(RwLock::new(ThermalState::default())),
+            score: Arc::new(RwLock::new(None)),
         }
     }
 
     pub fn with_capabilities(capabilities: DeviceCapabilities) -> Self {
         Self {
             capabilities: Arc::new(RwLock::new(capabilities)),
+            thermal_state: Arc::new(RwLock::new(ThermalState::default())),
+            score: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -55,6 +64,38 @@ impl DeviceManager {
         caps.cpu_cores = cpu_cores as u32;
     }
 
+    /// 整体替换设备能力（浏览器等由平台层自行检测的环境）
+    pub fn set_capabilities(&self, capabilities: DeviceCapabilities) {
+        *self.capabilities.write() = capabilities;
+    }
+
+    /// 更新温控状态（由平台层回调）
+    pub fn update_thermal_state(&self, state: ThermalState) {
+        *self.thermal_state.write() = state;
+    }
+
+    pub fn thermal_state(&self) -> ThermalState {
+        *self.thermal_state.read()
+    }
+
+    /// 设置基准测试得到的校准算力分
+    pub fn set_device_score(&self, score: DeviceScore) {
+        *self.score.write() = Some(score);
+    }
+
+    pub fn device_score(&self) -> Option<DeviceScore> {
+        self.score.read().clone()
+    }
+
+    /// 推荐的模型维度：有校准分时按实测算力，否则按内存估算
+    pub fn recommended_model_dim(&self) -> usize {
+        let caps = self.capabilities.read();
+        match &*self.score.read() {
+            Some(score) => score.recommended_model_dim(&caps),
+            None => caps.recommended_model_dim(),
+        }
+    }
+
     pub fn refresh(&self) {
         let mut caps = self.capabilities.write();
         *caps = DeviceDetector::detect();
@@ -5,12 +5,19 @@
 //! - 网络类型检测（WiFi、4G、5G）
 //! - 电池状态检测
 //! - 设备能力管理和运行时更新
+//! - 基准测试与校准算力分
+//! - 系统睡眠/唤醒与网络变化事件
 
 pub mod detection;
 pub mod capabilities;
 pub mod manager;
 pub mod platform;
 pub mod types;
+pub mod tick_controller;
+pub mod benchmark;
+pub mod power_policy;
+pub mod sandbox;
+pub mod system_events;
 
 // 重新导出公共接口
 pub use detection::*;
@@ -18,9 +25,19 @@ pub use capabilities::*;
 pub use manager::*;
 pub use types::*;
 pub use platform::*;
+pub use tick_controller::{TickAdaptation, TickController, TickControllerConfig, TickFeedback};
+pub use power_policy::{PauseReason, PowerPolicy, ResourceCaps};
+pub use sandbox::{
+    ResourceLimits, ResourceSandbox, ResourceUsage, SandboxConfig, SandboxIncident, SandboxViolation,
+};
+pub use system_events::{system_event_hub, SystemEvent, SystemEventHub};
+pub use benchmark::{
+    split_capacities, BenchmarkConfig, BenchmarkResult, DeviceBenchmark, DeviceScore, GpuProbe, SignedDeviceScore,
+};
 
 /// 设备配置
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct DeviceConfig {
     /// 是否启用 GPU 加速
     pub enable_gpu_acceleration: bool,
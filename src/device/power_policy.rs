@@ -0,0 +1,133 @@
+//! 电源与资源策略
+//!
+//! 把用户手动设置的资源上限（暂停训练、CPU 占用上限、仅 Wi-Fi 同步）与设备状态（低电量）
+//! 合成训练循环的运行决策。策略对象在节点和桌面端之间共享，修改后下一轮 tick 即生效。
+
+use super::{DeviceCapabilities, NetworkType};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// 用户设置的资源上限
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceCaps {
+    /// 手动暂停训练
+    pub paused: bool,
+    /// 训练循环的 CPU 占空比上限（1-100）
+    pub cpu_limit_percent: u8,
+    /// 只在 Wi-Fi 下同步梯度
+    pub wifi_only: bool,
+}
+
+impl Default for ResourceCaps {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            cpu_limit_percent: 100,
+            wifi_only: false,
+        }
+    }
+}
+
+/// 训练暂停的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    Manual,
+    LowBattery,
+}
+
+impl fmt::Display for PauseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PauseReason::Manual => write!(f, "用户手动暂停"),
+            PauseReason::LowBattery => write!(f, "电量过低"),
+        }
+    }
+}
+
+/// 共享的电源策略
+#[derive(Debug, Default)]
+pub struct PowerPolicy {
+    caps: RwLock<ResourceCaps>,
+}
+
+impl PowerPolicy {
+    pub fn new(caps: ResourceCaps) -> Self {
+        Self {
+            caps: RwLock::new(caps),
+        }
+    }
+
+    pub fn caps(&self) -> ResourceCaps {
+        self.caps.read().clone()
+    }
+
+    pub fn set_caps(&self, mut caps: ResourceCaps) {
+        caps.cpu_limit_percent = caps.cpu_limit_percent.clamp(1, 100);
+        *self.caps.write() = caps;
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.caps.write().paused = paused;
+    }
+
+    pub fn set_cpu_limit(&self, percent: u8) {
+        self.caps.write().cpu_limit_percent = percent.clamp(1, 100);
+    }
+
+    pub fn set_wifi_only(&self, wifi_only: bool) {
+        self.caps.write().wifi_only = wifi_only;
+    }
+
+    /// 当前是否应暂停训练，手动暂停优先于低电量保护
+    pub fn pause_reason(&self, device: &DeviceCapabilities) -> Option<PauseReason> {
+        if self.caps.read().paused {
+            Some(PauseReason::Manual)
+        } else if device.should_pause_training() {
+            Some(PauseReason::LowBattery)
+        } else {
+            None
+        }
+    }
+
+    /// 当前网络下是否允许同步梯度
+    pub fn network_allowed(&self, network: NetworkType) -> bool {
+        !self.caps.read().wifi_only || network == NetworkType::WiFi
+    }
+
+    /// 一轮计算耗时 `busy` 后需要空闲的时长，使占空比不超过 CPU 上限
+    pub fn throttle_delay(&self, busy: Duration) -> Duration {
+        let limit = u32::from(self.caps.read().cpu_limit_percent.clamp(1, 100));
+        busy * (100 - limit) / limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caps_drive_pause_network_and_throttle() {
+        let policy = PowerPolicy::default();
+        let mut device = DeviceCapabilities::default();
+        assert_eq!(policy.pause_reason(&device), None);
+        assert_eq!(policy.throttle_delay(Duration::from_millis(100)), Duration::ZERO);
+
+        device.battery_level = Some(0.1);
+        device.is_charging = Some(false);
+        assert_eq!(policy.pause_reason(&device), Some(PauseReason::LowBattery));
+        policy.set_paused(true);
+        assert_eq!(policy.pause_reason(&device), Some(PauseReason::Manual));
+
+        policy.set_cpu_limit(25);
+        assert_eq!(policy.throttle_delay(Duration::from_millis(100)), Duration::from_millis(300));
+        policy.set_cpu_limit(0);
+        assert_eq!(policy.caps().cpu_limit_percent, 1);
+
+        policy.set_wifi_only(true);
+        assert!(policy.network_allowed(NetworkType::WiFi));
+        assert!(!policy.network_allowed(NetworkType::Unknown));
+    }
+}
@@ -0,0 +1,88 @@
+//! Linux cgroups v2 后端
+
+use super::{ResourceLimits, ResourceUsage, SandboxBackend, SandboxConfig};
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// cpu.max 的调度周期（微秒）
+const CPU_PERIOD_US: u64 = 100_000;
+
+pub struct CgroupSandbox {
+    path: PathBuf,
+}
+
+impl CgroupSandbox {
+    /// 在父 cgroup 下创建本节点的 cgroup，写入上限后把当前进程移入
+    pub fn enter(config: &SandboxConfig, limits: &ResourceLimits) -> Result<Self> {
+        let parent = &config.cgroup_parent;
+        if !parent.join("cgroup.controllers").exists() {
+            return Err(anyhow!("{} 不是 cgroups v2 目录", parent.display()));
+        }
+        // 父 cgroup 可能已开启这些控制器，失败时由下面的写入报告具体缺失项
+        let _ = fs::write(parent.join("cgroup.subtree_control"), "+cpu +memory +io");
+
+        let path = parent.join(format!("node-{}", std::process::id()));
+        fs::create_dir_all(&path).with_context(|| format!("无法创建 cgroup {}", path.display()))?;
+
+        let quota = limits.cpu_percent as u64 * CPU_PERIOD_US / 100;
+        write(&path, "cpu.max", &format!("{} {}", quota, CPU_PERIOD_US))?;
+        let memory_bytes = limits.memory_mb * 1024 * 1024;
+        // 先在 90% 处回收内存施压，看门狗有机会在内核 OOM 之前中止任务
+        write(&path, "memory.high", &(memory_bytes / 10 * 9).to_string())?;
+        write(&path, "memory.max", &memory_bytes.to_string())?;
+        if let Some(bps) = limits.io_bytes_per_sec {
+            if config.io_devices.is_empty() {
+                log::warn!("[沙箱] 设置了 IO 上限但未指定 io_devices，IO 不受限制");
+            }
+            for device in &config.io_devices {
+                write(&path, "io.max", &format!("{} rbps={} wbps={}", device, bps, bps))?;
+            }
+        }
+        write(&path, "cgroup.procs", &std::process::id().to_string())?;
+        Ok(Self { path })
+    }
+
+    fn read(&self, file: &str) -> Option<String> {
+        fs::read_to_string(self.path.join(file)).ok()
+    }
+}
+
+fn write(cgroup: &Path, file: &str, value: &str) -> Result<()> {
+    fs::write(cgroup.join(file), value).with_context(|| format!("写入 {}/{} 失败", cgroup.display(), file))
+}
+
+/// 在 `key value` 形式的统计文件中查找数值
+fn stat_value(content: &str, key: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let (k, v) = line.split_once(' ')?;
+        (k == key).then(|| v.trim().parse().ok()).flatten()
+    })
+}
+
+impl SandboxBackend for CgroupSandbox {
+    fn name(&self) -> &'static str {
+        "cgroup-v2"
+    }
+
+    fn usage(&self) -> Option<ResourceUsage> {
+        let memory = self.read("memory.current")?.trim().parse::<u64>().ok()?;
+        let oom_kills = self
+            .read("memory.events")
+            .and_then(|events| stat_value(&events, "oom_kill"))
+            .unwrap_or(0);
+        // cpu.max 是硬上限，CPU 不会越界，这里不采样
+        Some(ResourceUsage {
+            memory_mb: memory / (1024 * 1024),
+            cpu_percent: 0.0,
+            oom_kills,
+        })
+    }
+}
+
+impl Drop for CgroupSandbox {
+    fn drop(&mut self) {
+        // 进程仍在 cgroup 内时删除会失败，退出后由父 cgroup 的管理者清理
+        let _ = fs::remove_dir(&self.path);
+    }
+}
@@ -0,0 +1,28 @@
+//! macOS task policy 后端
+//!
+//! macOS 没有面向普通进程的 CPU/内存硬上限。CPU 上限低于全部核心时把进程降为后台级别
+//! （CPU 和磁盘 IO 让位于前台应用），内存由看门狗把关。
+
+use super::{ResourceLimits, SandboxBackend};
+use anyhow::{anyhow, Result};
+
+pub struct TaskPolicySandbox;
+
+impl TaskPolicySandbox {
+    pub fn enter(limits: &ResourceLimits) -> Result<Self> {
+        let cores = std::thread::available_parallelism().map(|n| n.get() as u32).unwrap_or(1);
+        if limits.cpu_percent < cores * 100 || limits.io_bytes_per_sec.is_some() {
+            let ret = unsafe { libc::setpriority(libc::PRIO_DARWIN_PROCESS, 0, libc::PRIO_DARWIN_BG) };
+            if ret != 0 {
+                return Err(anyhow!("设置后台 task policy 失败: {}", std::io::Error::last_os_error()));
+            }
+        }
+        Ok(Self)
+    }
+}
+
+impl SandboxBackend for TaskPolicySandbox {
+    fn name(&self) -> &'static str {
+        "task-policy"
+    }
+}
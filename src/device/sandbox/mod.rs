@@ -0,0 +1,310 @@
+//! 训练资源沙箱
+//!
+//! 训练任务可能占满宿主机资源。这里按 [`DeviceConfig`] 推导 CPU / 内存 / IO 上限，用平台机制约束节点进程：
+//! - Linux：cgroups v2（`cpu.max`、`memory.high` / `memory.max`、`io.max`）
+//! - Windows：Job Object（CPU 硬上限、进程内存上限）
+//! - macOS：task policy 后台级别（降低 CPU 和 IO 优先级，内存只能由看门狗把关）
+//!
+//! 看门狗定期采样资源占用，连续超限时判定当前训练任务失控，由节点中止该任务并把结构化错误记入统计。
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use super::{DeviceCapabilities, DeviceConfig};
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 沙箱配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub enabled: bool,
+    /// 每个块设备的读写带宽上限（字节/秒），不设置时不限制 IO
+    pub io_bytes_per_sec: Option<u64>,
+    /// 需要限制 IO 的块设备（Linux `MAJ:MIN`，例如 `8:0`）
+    pub io_devices: Vec<String>,
+    /// Linux 下节点 cgroup 的父目录，需要对其有写权限（例如 systemd `Delegate=yes`）
+    pub cgroup_parent: PathBuf,
+    /// 看门狗采样间隔（秒）
+    pub check_interval_secs: u64,
+    /// 连续超限多少次判定为失控
+    pub max_strikes: u32,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            io_bytes_per_sec: None,
+            io_devices: Vec::new(),
+            cgroup_parent: PathBuf::from("/sys/fs/cgroup/williw"),
+            check_interval_secs: 5,
+            max_strikes: 3,
+        }
+    }
+}
+
+/// 资源上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// CPU 上限，100 表示一个核心
+    pub cpu_percent: u32,
+    pub memory_mb: u64,
+    pub io_bytes_per_sec: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// 按设备配置推导上限，不超过设备实际的核心数和内存
+    pub fn from_device_config(device: &DeviceConfig, caps: &DeviceCapabilities, io_bytes_per_sec: Option<u64>) -> Self {
+        let cores = match caps.cpu_cores {
+            0 => device.max_cpu_cores,
+            available => device.max_cpu_cores.min(available),
+        };
+        let memory_mb = match caps.max_memory_mb {
+            0 => device.max_memory_mb,
+            available => device.max_memory_mb.min(available),
+        };
+        Self {
+            cpu_percent: cores.max(1) * 100,
+            memory_mb: memory_mb.max(1),
+            io_bytes_per_sec,
+        }
+    }
+}
+
+/// 资源占用采样
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub memory_mb: u64,
+    /// CPU 占用，100 表示一个核心
+    pub cpu_percent: f32,
+    /// 沙箱内累计的 OOM 终止次数（平台不支持时为 0）
+    pub oom_kills: u64,
+}
+
+/// 资源越界
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SandboxViolation {
+    #[error("内存占用 {used_mb}MB 逼近上限 {limit_mb}MB")]
+    MemoryExceeded { used_mb: u64, limit_mb: u64 },
+
+    #[error("CPU 占用 {used_percent:.0}% 持续超出上限 {limit_percent}%")]
+    CpuExceeded { used_percent: f32, limit_percent: u32 },
+
+    #[error("沙箱内发生 {count} 次 OOM 终止")]
+    OomKilled { count: u64 },
+}
+
+/// 因资源越界被中止的任务，记入训练统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandboxIncident {
+    /// 被中止的任务（越界时没有进行中的任务则为空）
+    pub task_id: Option<String>,
+    pub violation: SandboxViolation,
+    pub usage: ResourceUsage,
+    pub limits: ResourceLimits,
+    /// 发生时间（Unix 秒）
+    pub occurred_at: i64,
+}
+
+/// 平台资源控制后端
+pub trait SandboxBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// 平台提供的资源占用（例如 cgroup 统计），不支持时返回 None，由进程采样代替
+    fn usage(&self) -> Option<ResourceUsage> {
+        None
+    }
+}
+
+/// 只靠看门狗把关的后端（平台机制不可用时）
+struct WatchdogOnly;
+
+impl SandboxBackend for WatchdogOnly {
+    fn name(&self) -> &'static str {
+        "watchdog"
+    }
+}
+
+fn platform_backend(config: &SandboxConfig, limits: &ResourceLimits) -> Result<Box<dyn SandboxBackend>> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(linux::CgroupSandbox::enter(config, limits)?))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = config;
+        Ok(Box::new(windows::JobObjectSandbox::enter(limits)?))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let _ = config;
+        Ok(Box::new(macos::TaskPolicySandbox::enter(limits)?))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        let _ = (config, limits);
+        Ok(Box::new(WatchdogOnly))
+    }
+}
+
+/// 连续超限计数，超过容忍次数时报告越界
+#[derive(Debug)]
+pub struct SandboxWatchdog {
+    limits: ResourceLimits,
+    max_strikes: u32,
+    strikes: u32,
+    oom_kills: u64,
+}
+
+impl SandboxWatchdog {
+    pub fn new(limits: ResourceLimits, max_strikes: u32) -> Self {
+        Self {
+            limits,
+            max_strikes: max_strikes.max(1),
+            strikes: 0,
+            oom_kills: 0,
+        }
+    }
+
+    pub fn observe(&mut self, usage: &ResourceUsage) -> Option<SandboxViolation> {
+        // OOM 终止说明已经失控，无需等待
+        if usage.oom_kills > self.oom_kills {
+            let count = usage.oom_kills - self.oom_kills;
+            self.oom_kills = usage.oom_kills;
+            self.strikes = 0;
+            return Some(SandboxViolation::OomKilled { count });
+        }
+
+        // 与 cgroup 的 memory.high 一致，在上限的 90% 处判定，赶在内核 OOM 之前中止任务
+        let violation = if usage.memory_mb > self.limits.memory_mb / 10 * 9 {
+            SandboxViolation::MemoryExceeded {
+                used_mb: usage.memory_mb,
+                limit_mb: self.limits.memory_mb,
+            }
+        } else if usage.cpu_percent > self.limits.cpu_percent as f32 {
+            SandboxViolation::CpuExceeded {
+                used_percent: usage.cpu_percent,
+                limit_percent: self.limits.cpu_percent,
+            }
+        } else {
+            self.strikes = 0;
+            return None;
+        };
+        self.strikes += 1;
+        if self.strikes < self.max_strikes {
+            return None;
+        }
+        self.strikes = 0;
+        Some(violation)
+    }
+}
+
+/// 资源沙箱：平台后端 + 看门狗
+pub struct ResourceSandbox {
+    limits: ResourceLimits,
+    backend: Box<dyn SandboxBackend>,
+    watchdog: Mutex<SandboxWatchdog>,
+    system: Mutex<sysinfo::System>,
+}
+
+impl ResourceSandbox {
+    /// 把当前进程放入平台沙箱；平台机制不可用时退化为只用看门狗
+    pub fn enter(config: &SandboxConfig, limits: ResourceLimits) -> Self {
+        let backend = platform_backend(config, &limits).unwrap_or_else(|e| {
+            log::warn!("[沙箱] 平台资源控制不可用，仅启用看门狗: {}", e);
+            Box::new(WatchdogOnly)
+        });
+        Self {
+            limits,
+            backend,
+            watchdog: Mutex::new(SandboxWatchdog::new(limits, config.max_strikes)),
+            system: Mutex::new(sysinfo::System::new()),
+        }
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub fn limits(&self) -> ResourceLimits {
+        self.limits
+    }
+
+    /// 当前资源占用，优先使用平台统计
+    pub fn usage(&self) -> ResourceUsage {
+        if let Some(usage) = self.backend.usage() {
+            return usage;
+        }
+        let Ok(pid) = sysinfo::get_current_pid() else {
+            return ResourceUsage::default();
+        };
+        let mut system = self.system.lock();
+        system.refresh_processes_specifics(
+            sysinfo::ProcessesToUpdate::Some(&[pid]),
+            true,
+            sysinfo::ProcessRefreshKind::nothing().with_memory().with_cpu(),
+        );
+        system
+            .process(pid)
+            .map(|process| ResourceUsage {
+                memory_mb: process.memory() / (1024 * 1024),
+                cpu_percent: process.cpu_usage(),
+                oom_kills: 0,
+            })
+            .unwrap_or_default()
+    }
+
+    /// 采样一次，连续超限时返回越界原因和当时的占用
+    pub fn check(&self) -> Option<(SandboxViolation, ResourceUsage)> {
+        let usage = self.usage();
+        self.watchdog.lock().observe(&usage).map(|violation| (violation, usage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_follow_device_config_and_watchdog_needs_strikes() {
+        let device = DeviceConfig {
+            max_cpu_cores: 8,
+            max_memory_mb: 4096,
+            ..Default::default()
+        };
+        let caps = DeviceCapabilities {
+            cpu_cores: 4,
+            max_memory_mb: 16384,
+            ..Default::default()
+        };
+        let limits = ResourceLimits::from_device_config(&device, &caps, None);
+        assert_eq!((limits.cpu_percent, limits.memory_mb), (400, 4096));
+
+        let mut watchdog = SandboxWatchdog::new(limits, 2);
+        let over = ResourceUsage {
+            memory_mb: 5000,
+            ..Default::default()
+        };
+        assert_eq!(watchdog.observe(&over), None);
+        assert!(matches!(
+            watchdog.observe(&over),
+            Some(SandboxViolation::MemoryExceeded { used_mb: 5000, limit_mb: 4096 })
+        ));
+        assert_eq!(watchdog.observe(&ResourceUsage::default()), None);
+
+        let oom = ResourceUsage {
+            oom_kills: 1,
+            ..Default::default()
+        };
+        assert_eq!(watchdog.observe(&oom), Some(SandboxViolation::OomKilled { count: 1 }));
+        assert_eq!(watchdog.observe(&oom), None);
+    }
+}
@@ -0,0 +1,80 @@
+//! Windows Job Object 后端
+
+use super::{ResourceLimits, SandboxBackend};
+use anyhow::{anyhow, Result};
+use std::ffi::c_void;
+use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+use windows_sys::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation, JobObjectExtendedLimitInformation,
+    SetInformationJobObject, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+    JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP, JOB_OBJECT_LIMIT_PROCESS_MEMORY,
+};
+use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+pub struct JobObjectSandbox {
+    job: HANDLE,
+}
+
+// Job Object 句柄只在创建和释放时使用
+unsafe impl Send for JobObjectSandbox {}
+unsafe impl Sync for JobObjectSandbox {}
+
+impl JobObjectSandbox {
+    pub fn enter(limits: &ResourceLimits) -> Result<Self> {
+        let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if job.is_null() {
+            return Err(anyhow!("创建 Job Object 失败: {}", std::io::Error::last_os_error()));
+        }
+        let sandbox = Self { job };
+
+        let mut memory: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        memory.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+        memory.ProcessMemoryLimit = (limits.memory_mb * 1024 * 1024) as usize;
+        sandbox.set(JobObjectExtendedLimitInformation, &memory)?;
+
+        // CpuRate 以整机的万分之一为单位
+        let cores = std::thread::available_parallelism().map(|n| n.get() as u32).unwrap_or(1);
+        let rate = (limits.cpu_percent * 100 / cores).clamp(1, 10_000);
+        let mut cpu: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = unsafe { std::mem::zeroed() };
+        cpu.ControlFlags = JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+        cpu.Anonymous.CpuRate = rate;
+        sandbox.set(JobObjectCpuRateControlInformation, &cpu)?;
+
+        if limits.io_bytes_per_sec.is_some() {
+            log::warn!("[沙箱] Job Object 后端暂不支持 IO 上限");
+        }
+        if unsafe { AssignProcessToJobObject(job, GetCurrentProcess()) } == 0 {
+            return Err(anyhow!("无法把进程加入 Job Object: {}", std::io::Error::last_os_error()));
+        }
+        Ok(sandbox)
+    }
+
+    fn set<T>(&self, class: i32, info: &T) -> Result<()> {
+        let ok = unsafe {
+            SetInformationJobObject(
+                self.job,
+                class,
+                info as *const T as *const c_void,
+                std::mem::size_of::<T>() as u32,
+            )
+        };
+        if ok == 0 {
+            return Err(anyhow!("设置 Job Object 限制失败: {}", std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+impl SandboxBackend for JobObjectSandbox {
+    fn name(&self) -> &'static str {
+        "job-object"
+    }
+}
+
+impl Drop for JobObjectSandbox {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.job);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Linux：logind 睡眠信号和 netlink 网络变化
+//!
+//! `gdbus monitor` 订阅 logind 的 `PrepareForSleep` 信号（参数 true 为即将睡眠，false 为已唤醒）。
+//! 这里只观察信号、不持有延迟锁，系统不会等待节点保存完状态。
+//! `ip monitor` 输出内核 netlink 的链路、地址和路由变化，每行视为一次网络变化。
+
+use super::{spawn_line_monitor, SystemEvent};
+
+pub fn spawn_listeners() {
+    spawn_line_monitor(
+        "logind",
+        "gdbus",
+        &[
+            "monitor",
+            "--system",
+            "--dest",
+            "org.freedesktop.login1",
+            "--object-path",
+            "/org/freedesktop/login1",
+        ],
+        parse_logind,
+    );
+    spawn_line_monitor("netlink", "ip", &["monitor", "link", "address", "route"], |_| {
+        Some(SystemEvent::NetworkChanged)
+    });
+}
+
+/// 解析 `gdbus monitor` 输出，例如
+/// `/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)`
+fn parse_logind(line: &str) -> Option<SystemEvent> {
+    if !line.contains("PrepareForSleep") {
+        None
+    } else if line.contains("(true") {
+        Some(SystemEvent::Suspend)
+    } else if line.contains("(false") {
+        Some(SystemEvent::Resume)
+    } else {
+        None
+    }
+}
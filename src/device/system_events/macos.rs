@@ -0,0 +1,85 @@
+//! macOS：IOKit 电源通知和路由变化
+//!
+//! `IORegisterForSystemPower` 的回调在专用线程的 RunLoop 上执行；睡眠类消息必须调用
+//! `IOAllowPowerChange` 应答，否则系统会等待 30 秒超时。网络变化由 `route -n monitor` 输出驱动。
+
+use super::{spawn_line_monitor, system_event_hub, SystemEvent};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+type IoConnect = u32;
+type IoObject = u32;
+type IoNotificationPortRef = *mut c_void;
+type CfRunLoopRef = *mut c_void;
+type CfRunLoopSourceRef = *mut c_void;
+type CfStringRef = *const c_void;
+type IoServiceInterestCallback =
+    extern "C" fn(refcon: *mut c_void, service: IoObject, message_type: u32, message_argument: *mut c_void);
+
+const MSG_CAN_SYSTEM_SLEEP: u32 = 0xe000_0270;
+const MSG_SYSTEM_WILL_SLEEP: u32 = 0xe000_0280;
+const MSG_SYSTEM_HAS_POWERED_ON: u32 = 0xe000_0300;
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IORegisterForSystemPower(
+        refcon: *mut c_void,
+        port: *mut IoNotificationPortRef,
+        callback: IoServiceInterestCallback,
+        notifier: *mut IoObject,
+    ) -> IoConnect;
+    fn IONotificationPortGetRunLoopSource(port: IoNotificationPortRef) -> CfRunLoopSourceRef;
+    fn IOAllowPowerChange(kernel_port: IoConnect, notification_id: isize) -> i32;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFRunLoopCommonModes: CfStringRef;
+    fn CFRunLoopGetCurrent() -> CfRunLoopRef;
+    fn CFRunLoopAddSource(run_loop: CfRunLoopRef, source: CfRunLoopSourceRef, mode: CfStringRef);
+    fn CFRunLoopRun();
+}
+
+/// `IORegisterForSystemPower` 返回的根电源域连接，应答睡眠消息时使用
+static ROOT_PORT: AtomicU32 = AtomicU32::new(0);
+
+extern "C" fn on_power_message(_refcon: *mut c_void, _service: IoObject, message_type: u32, argument: *mut c_void) {
+    match message_type {
+        MSG_SYSTEM_WILL_SLEEP => system_event_hub().emit(SystemEvent::Suspend),
+        MSG_SYSTEM_HAS_POWERED_ON => system_event_hub().emit(SystemEvent::Resume),
+        _ => {}
+    }
+    if matches!(message_type, MSG_CAN_SYSTEM_SLEEP | MSG_SYSTEM_WILL_SLEEP) {
+        unsafe {
+            IOAllowPowerChange(ROOT_PORT.load(Ordering::SeqCst), argument as isize);
+        }
+    }
+}
+
+pub fn spawn_listeners() {
+    let spawned = std::thread::Builder::new()
+        .name("system-events-iokit".to_string())
+        .spawn(|| unsafe {
+            let mut port: IoNotificationPortRef = std::ptr::null_mut();
+            let mut notifier: IoObject = 0;
+            let root = IORegisterForSystemPower(std::ptr::null_mut(), &mut port, on_power_message, &mut notifier);
+            if root == 0 {
+                log::warn!("[系统事件] IORegisterForSystemPower 失败，依靠看门狗检测睡眠");
+                return;
+            }
+            ROOT_PORT.store(root, Ordering::SeqCst);
+            CFRunLoopAddSource(
+                CFRunLoopGetCurrent(),
+                IONotificationPortGetRunLoopSource(port),
+                kCFRunLoopCommonModes,
+            );
+            CFRunLoopRun();
+        });
+    if let Err(e) = spawned {
+        log::warn!("[系统事件] 启动 IOKit 监听失败: {}", e);
+    }
+
+    spawn_line_monitor("route", "route", &["-n", "monitor"], |line| {
+        (!line.trim().is_empty()).then_some(SystemEvent::NetworkChanged)
+    });
+}
@@ -0,0 +1,314 @@
+//! 系统睡眠/唤醒与网络变化事件
+//!
+//! 笔记本合盖睡眠或切换 WiFi 时，训练 tick 和 P2P 传输会在没有任何错误的情况下卡住。
+//! 这里把各平台的通知统一成 [`SystemEvent`]，经 [`SystemEventHub`] 广播给节点和传输任务：
+//! - Windows：`PowerRegisterSuspendResumeNotification` 电源广播、`NotifyIpInterfaceChange`
+//! - macOS：IOKit `IORegisterForSystemPower`、`route -n monitor` 路由变化
+//! - Linux：logind `PrepareForSleep` 信号（`gdbus monitor`）、netlink 链路/地址/路由变化（`ip monitor`）
+//! - Android：Java 侧的屏幕/Doze 广播和 `NetworkCallback` 经 JNI 转发（见 `android::system_events`）
+//!
+//! 平台监听不可用时，看门狗根据墙上时钟的跳变推断进程曾被冻结，补发一次睡眠/唤醒。
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::broadcast;
+
+/// 看门狗采样间隔
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+/// 墙上时钟比预期多走这么久，判定进程曾被冻结（系统睡眠）
+const FREEZE_THRESHOLD: Duration = Duration::from_secs(15);
+/// 网络变化通知静默这么久后才广播（一次切换会连续收到多条通知）
+const NETWORK_SETTLE: Duration = Duration::from_secs(2);
+/// 收到睡眠通知后仍正常运行超过这么久，视为睡眠被取消
+const SUSPEND_GRACE: Duration = Duration::from_secs(60);
+/// 广播通道容量
+const CHANNEL_CAPACITY: usize = 16;
+
+/// 系统事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SystemEvent {
+    /// 系统即将睡眠
+    Suspend,
+    /// 系统已唤醒
+    Resume,
+    /// 网络接口、地址或路由发生变化（例如切换 WiFi）
+    NetworkChanged,
+}
+
+impl fmt::Display for SystemEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemEvent::Suspend => write!(f, "系统即将睡眠"),
+            SystemEvent::Resume => write!(f, "系统已唤醒"),
+            SystemEvent::NetworkChanged => write!(f, "网络已变化"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct HubState {
+    /// 收到睡眠通知的时间，唤醒后清除
+    suspended_at: Option<Instant>,
+    /// 最近一次网络变化通知的时间，静默后广播
+    network_pending_since: Option<Instant>,
+}
+
+/// 系统事件中枢：平台监听写入，节点和传输任务订阅
+pub struct SystemEventHub {
+    sender: broadcast::Sender<SystemEvent>,
+    state: Mutex<HubState>,
+    listening: AtomicBool,
+}
+
+impl Default for SystemEventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemEventHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            state: Mutex::new(HubState::default()),
+            listening: AtomicBool::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SystemEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 是否处于睡眠中（已收到睡眠通知、尚未唤醒）
+    pub fn is_suspended(&self) -> bool {
+        self.state.lock().suspended_at.is_some()
+    }
+
+    /// 上报平台事件：重复的睡眠/唤醒被忽略，网络变化先合并，静默后由看门狗广播
+    pub fn emit(&self, event: SystemEvent) {
+        self.emit_at(event, Instant::now());
+    }
+
+    fn emit_at(&self, event: SystemEvent, now: Instant) {
+        let mut state = self.state.lock();
+        match event {
+            SystemEvent::Suspend if state.suspended_at.is_none() => state.suspended_at = Some(now),
+            SystemEvent::Resume if state.suspended_at.is_some() => state.suspended_at = None,
+            SystemEvent::NetworkChanged => {
+                state.network_pending_since = Some(now);
+                return;
+            }
+            _ => return,
+        }
+        drop(state);
+        self.broadcast(event);
+    }
+
+    /// 看门狗周期调用：广播已静默的网络变化，清除长时间未兑现的睡眠状态
+    fn poll(&self, now: Instant) {
+        let mut state = self.state.lock();
+        let network_settled = state
+            .network_pending_since
+            .is_some_and(|since| now.duration_since(since) >= NETWORK_SETTLE);
+        if network_settled {
+            state.network_pending_since = None;
+        }
+        drop(state);
+        if network_settled {
+            self.broadcast(SystemEvent::NetworkChanged);
+        }
+
+        let stale = self
+            .state
+            .lock()
+            .suspended_at
+            .is_some_and(|since| now.duration_since(since) >= SUSPEND_GRACE);
+        if stale {
+            log::warn!("[系统事件] 睡眠通知后 {:?} 仍在运行，视为睡眠已取消", SUSPEND_GRACE);
+            self.emit_at(SystemEvent::Resume, now);
+        }
+    }
+
+    /// 看门狗发现进程曾被冻结：补发睡眠（若平台未通知）和唤醒
+    fn on_frozen(&self, gap: Duration) {
+        log::info!("[系统事件] 进程约 {} 秒未运行，按睡眠唤醒处理", gap.as_secs());
+        self.emit(SystemEvent::Suspend);
+        self.emit(SystemEvent::Resume);
+    }
+
+    /// 等待唤醒：收到唤醒事件、通道关闭或睡眠状态已清除时返回
+    pub async fn wait_until_awake(&self, events: &mut broadcast::Receiver<SystemEvent>) {
+        loop {
+            match tokio::time::timeout(WATCHDOG_INTERVAL, events.recv()).await {
+                Ok(Ok(SystemEvent::Resume)) | Ok(Err(broadcast::error::RecvError::Closed)) => return,
+                Ok(Ok(_)) => {}
+                // 丢失事件或超时：以当前状态为准
+                Ok(Err(broadcast::error::RecvError::Lagged(_))) | Err(_) => {
+                    if !self.is_suspended() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn broadcast(&self, event: SystemEvent) {
+        log::info!("[系统事件] {}", event);
+        // 没有订阅者时丢弃
+        let _ = self.sender.send(event);
+    }
+}
+
+static SYSTEM_EVENTS: OnceLock<Arc<SystemEventHub>> = OnceLock::new();
+
+/// 全局系统事件中枢
+pub fn system_event_hub() -> Arc<SystemEventHub> {
+    Arc::clone(SYSTEM_EVENTS.get_or_init(|| Arc::new(SystemEventHub::new())))
+}
+
+/// 启动平台监听和看门狗，重复调用只启动一次
+pub fn start_listeners() {
+    let hub = system_event_hub();
+    if hub.listening.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    linux::spawn_listeners();
+    #[cfg(target_os = "macos")]
+    macos::spawn_listeners();
+    #[cfg(target_os = "windows")]
+    windows::spawn_listeners();
+
+    let spawned = std::thread::Builder::new()
+        .name("system-events-watchdog".to_string())
+        .spawn(move || {
+            let mut last = SystemTime::now();
+            loop {
+                std::thread::sleep(WATCHDOG_INTERVAL);
+                let now = SystemTime::now();
+                // 手动回拨时钟时 duration_since 失败，按正常间隔处理
+                let elapsed = now.duration_since(last).unwrap_or(WATCHDOG_INTERVAL);
+                last = now;
+                if let Some(gap) = frozen_for(elapsed) {
+                    hub.on_frozen(gap);
+                }
+                hub.poll(Instant::now());
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("[系统事件] 启动看门狗失败: {}", e);
+    }
+}
+
+/// 一个采样间隔内墙上时钟走过 `elapsed`，超出阈值时返回进程被冻结的时长
+///
+/// 单调时钟在部分平台上睡眠期间不前进，所以用墙上时钟；时钟被手动调快也会触发，
+/// 代价只是多一次重连。
+fn frozen_for(elapsed: Duration) -> Option<Duration> {
+    elapsed
+        .checked_sub(WATCHDOG_INTERVAL)
+        .filter(|gap| *gap >= FREEZE_THRESHOLD)
+}
+
+/// 启动命令行监视器，按行解析输出为事件；命令退出后稍后重启，命令不存在时放弃
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn spawn_line_monitor(
+    name: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+    parse: fn(&str) -> Option<SystemEvent>,
+) {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+    let spawned = std::thread::Builder::new()
+        .name(format!("system-events-{}", name))
+        .spawn(move || loop {
+            let mut child = match Command::new(program)
+                .args(args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+            {
+                Ok(child) => child,
+                Err(e) => {
+                    log::info!("[系统事件] 无法启动 {}，不监听{}事件: {}", program, name, e);
+                    return;
+                }
+            };
+            if let Some(stdout) = child.stdout.take() {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if let Some(event) = parse(&line) {
+                        system_event_hub().emit(event);
+                    }
+                }
+            }
+            let _ = child.wait();
+            log::debug!("[系统事件] {} 已退出，{:?} 后重启", program, RESTART_DELAY);
+            std::thread::sleep(RESTART_DELAY);
+        });
+    if let Err(e) = spawned {
+        log::warn!("[系统事件] 启动{}监听失败: {}", name, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_power_events_are_ignored() {
+        let hub = SystemEventHub::new();
+        let mut events = hub.subscribe();
+        let now = Instant::now();
+
+        hub.emit_at(SystemEvent::Resume, now);
+        hub.emit_at(SystemEvent::Suspend, now);
+        hub.emit_at(SystemEvent::Suspend, now);
+        assert!(hub.is_suspended());
+        hub.emit_at(SystemEvent::Resume, now);
+        hub.emit_at(SystemEvent::Resume, now);
+
+        assert_eq!(events.try_recv().unwrap(), SystemEvent::Suspend);
+        assert_eq!(events.try_recv().unwrap(), SystemEvent::Resume);
+        assert!(events.try_recv().is_err());
+
+        // 睡眠通知后长时间仍在运行，自动恢复
+        hub.emit_at(SystemEvent::Suspend, now);
+        hub.poll(now + SUSPEND_GRACE);
+        assert!(!hub.is_suspended());
+        assert_eq!(frozen_for(WATCHDOG_INTERVAL), None);
+        assert!(frozen_for(WATCHDOG_INTERVAL + FREEZE_THRESHOLD).is_some());
+    }
+
+    #[test]
+    fn test_network_changes_are_debounced() {
+        let hub = SystemEventHub::new();
+        let mut events = hub.subscribe();
+        let start = Instant::now();
+
+        hub.emit_at(SystemEvent::NetworkChanged, start);
+        hub.emit_at(SystemEvent::NetworkChanged, start + Duration::from_secs(1));
+        hub.poll(start + NETWORK_SETTLE);
+        assert!(events.try_recv().is_err());
+
+        hub.poll(start + Duration::from_secs(1) + NETWORK_SETTLE);
+        assert_eq!(events.try_recv().unwrap(), SystemEvent::NetworkChanged);
+        hub.poll(start + Duration::from_secs(10));
+        assert!(events.try_recv().is_err());
+    }
+}
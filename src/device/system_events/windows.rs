@@ -0,0 +1,84 @@
+//! Windows：电源广播和 IP 接口变化
+//!
+//! `PowerRegisterSuspendResumeNotification` 以回调方式接收 `PBT_APMSUSPEND` / `PBT_APMRESUME*`，
+//! 无需窗口消息循环；`NotifyIpInterfaceChange` 在接口增删、连接状态和参数变化时回调。
+//! 两个注册都持续到进程退出，不注销。
+
+use super::{system_event_hub, SystemEvent};
+use std::ffi::c_void;
+use windows_sys::Win32::Foundation::HANDLE;
+
+const DEVICE_NOTIFY_CALLBACK: u32 = 2;
+const PBT_APMSUSPEND: u32 = 0x4;
+const PBT_APMRESUMESUSPEND: u32 = 0x7;
+const PBT_APMRESUMEAUTOMATIC: u32 = 0x12;
+const AF_UNSPEC: u16 = 0;
+const NO_ERROR: u32 = 0;
+
+type PowerCallback = unsafe extern "system" fn(context: *const c_void, kind: u32, setting: *const c_void) -> u32;
+type InterfaceChangeCallback = unsafe extern "system" fn(context: *const c_void, row: *const c_void, kind: i32);
+
+/// `DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS`
+#[repr(C)]
+struct DeviceNotifySubscribeParameters {
+    callback: PowerCallback,
+    context: *mut c_void,
+}
+
+#[link(name = "powrprof")]
+extern "system" {
+    fn PowerRegisterSuspendResumeNotification(
+        flags: u32,
+        recipient: HANDLE,
+        registration: *mut *mut c_void,
+    ) -> u32;
+}
+
+#[link(name = "iphlpapi")]
+extern "system" {
+    fn NotifyIpInterfaceChange(
+        family: u16,
+        callback: InterfaceChangeCallback,
+        context: *const c_void,
+        initial_notification: u8,
+        handle: *mut HANDLE,
+    ) -> u32;
+}
+
+unsafe extern "system" fn on_power_broadcast(_context: *const c_void, kind: u32, _setting: *const c_void) -> u32 {
+    match kind {
+        PBT_APMSUSPEND => system_event_hub().emit(SystemEvent::Suspend),
+        PBT_APMRESUMESUSPEND | PBT_APMRESUMEAUTOMATIC => system_event_hub().emit(SystemEvent::Resume),
+        _ => {}
+    }
+    0
+}
+
+unsafe extern "system" fn on_interface_change(_context: *const c_void, _row: *const c_void, _kind: i32) {
+    system_event_hub().emit(SystemEvent::NetworkChanged);
+}
+
+pub fn spawn_listeners() {
+    // 注册期间系统持有参数指针，需在进程生命周期内有效
+    let parameters = Box::leak(Box::new(DeviceNotifySubscribeParameters {
+        callback: on_power_broadcast,
+        context: std::ptr::null_mut(),
+    }));
+    let mut registration: *mut c_void = std::ptr::null_mut();
+    let status = unsafe {
+        PowerRegisterSuspendResumeNotification(
+            DEVICE_NOTIFY_CALLBACK,
+            parameters as *mut DeviceNotifySubscribeParameters as HANDLE,
+            &mut registration,
+        )
+    };
+    if status != NO_ERROR {
+        log::warn!("[系统事件] 注册电源通知失败（错误码 {}），依靠看门狗检测睡眠", status);
+    }
+
+    let mut handle: HANDLE = std::ptr::null_mut();
+    let status = unsafe { NotifyIpInterfaceChange(AF_UNSPEC, on_interface_change, std::ptr::null(), 0, &mut handle) };
+    if status != NO_ERROR {
+        log::warn!("[系统事件] 注册网络接口变化通知失败（错误码 {}）", status);
+    }
+}
@@ -0,0 +1,237 @@
+//! 自适应 tick 间隔控制器
+//!
+//! `recommended_tick_interval` 只按设备类型给出静态初值；
+//! 控制器在运行时根据以下反馈调整训练 tick 间隔，并受配置上下限约束：
+//! - 实测的轮次完成时间（目标是让每轮只占用间隔的一部分）
+//! - 设备温控状态
+//! - 电池放电速率
+//! - 网络出站积压
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use super::types::ThermalState;
+
+/// 控制器配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickControllerConfig {
+    /// 是否启用自适应调整，关闭时保持初始间隔
+    pub enabled: bool,
+    /// 最小间隔（毫秒）
+    pub min_interval_ms: u64,
+    /// 最大间隔（毫秒）
+    pub max_interval_ms: u64,
+    /// 目标占空比：轮次耗时 / tick 间隔
+    pub target_utilization: f64,
+    /// 单次调整的最大幅度（相对当前间隔）
+    pub max_step_ratio: f64,
+    /// 放电速率超过该值（每小时电量比例）时放慢训练
+    pub battery_drain_threshold_per_hour: f32,
+    /// 出站积压超过该值时放慢训练
+    pub backlog_threshold: usize,
+    /// 小于该比例的变化不生效，避免抖动
+    pub min_change_ratio: f64,
+}
+
+impl Default for TickControllerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_interval_ms: 50,
+            max_interval_ms: 10_000,
+            target_utilization: 0.6,
+            max_step_ratio: 0.25,
+            battery_drain_threshold_per_hour: 0.15,
+            backlog_threshold: 64,
+            min_change_ratio: 0.05,
+        }
+    }
+}
+
+/// 单轮反馈
+#[derive(Debug, Clone)]
+pub struct TickFeedback {
+    /// 本轮实际耗时
+    pub round_duration: Duration,
+    pub thermal: ThermalState,
+    /// 电池电量（0-1）
+    pub battery_level: Option<f32>,
+    pub is_charging: bool,
+    /// 待发送的网络消息数
+    pub network_backlog: usize,
+}
+
+/// 一次间隔调整记录（写入统计以便调参）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickAdaptation {
+    pub timestamp: i64,
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub round_ms: u64,
+    pub thermal: ThermalState,
+    pub battery_drain_per_hour: Option<f32>,
+    pub network_backlog: usize,
+    pub reason: String,
+}
+
+/// 自适应 tick 控制器
+pub struct TickController {
+    config: TickControllerConfig,
+    current: Duration,
+    /// 平滑后的轮次耗时（毫秒）
+    smoothed_round_ms: Option<f64>,
+    last_battery: Option<(Instant, f32)>,
+    drain_per_hour: Option<f32>,
+}
+
+impl TickController {
+    pub fn new(config: TickControllerConfig, initial: Duration) -> Self {
+        let current = clamp_interval(&config, initial);
+        Self {
+            config,
+            current,
+            smoothed_round_ms: None,
+            last_battery: None,
+            drain_per_hour: None,
+        }
+    }
+
+    /// 当前 tick 间隔
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// 最近估算的放电速率（每小时电量比例）
+    pub fn battery_drain_per_hour(&self) -> Option<f32> {
+        self.drain_per_hour
+    }
+
+    /// 输入一轮反馈，间隔发生变化时返回调整记录
+    pub fn observe(&mut self, feedback: &TickFeedback) -> Option<TickAdaptation> {
+        self.update_battery(feedback.battery_level, feedback.is_charging);
+
+        let round_ms = feedback.round_duration.as_secs_f64() * 1000.0;
+        let smoothed = match self.smoothed_round_ms {
+            Some(prev) => prev * 0.7 + round_ms * 0.3,
+            None => round_ms,
+        };
+        self.smoothed_round_ms = Some(smoothed);
+
+        if !self.config.enabled {
+            return None;
+        }
+
+        let mut reasons = Vec::new();
+        let mut desired = smoothed / self.config.target_utilization.clamp(0.05, 1.0);
+        reasons.push(format!("轮次 {:.0}ms", smoothed));
+
+        let thermal_factor = feedback.thermal.slowdown_factor();
+        if thermal_factor > 1.0 {
+            desired *= thermal_factor;
+            reasons.push(format!("温控 {:?}", feedback.thermal));
+        }
+
+        if let Some(drain) = self.drain_per_hour {
+            if !feedback.is_charging && drain > self.config.battery_drain_threshold_per_hour {
+                desired *= 1.5;
+                reasons.push(format!("放电 {:.0}%/h", drain * 100.0));
+            }
+        }
+
+        if self.config.backlog_threshold > 0 && feedback.network_backlog > self.config.backlog_threshold {
+            let factor = (feedback.network_backlog as f64 / self.config.backlog_threshold as f64).min(2.0);
+            desired *= factor;
+            reasons.push(format!("积压 {}", feedback.network_backlog));
+        }
+
+        // 限制单次调整幅度
+        let current_ms = self.current.as_secs_f64() * 1000.0;
+        let step = self.config.max_step_ratio.max(0.0);
+        let bounded = desired.clamp(current_ms * (1.0 - step), current_ms * (1.0 + step));
+        let next = clamp_interval(&self.config, Duration::from_secs_f64(bounded / 1000.0));
+
+        let next_ms = next.as_secs_f64() * 1000.0;
+        if (next_ms - current_ms).abs() < current_ms * self.config.min_change_ratio {
+            return None;
+        }
+
+        let adaptation = TickAdaptation {
+            timestamp: chrono::Utc::now().timestamp(),
+            from_ms: self.current.as_millis() as u64,
+            to_ms: next.as_millis() as u64,
+            round_ms: round_ms as u64,
+            thermal: feedback.thermal,
+            battery_drain_per_hour: self.drain_per_hour,
+            network_backlog: feedback.network_backlog,
+            reason: reasons.join(", "),
+        };
+        self.current = next;
+        Some(adaptation)
+    }
+
+    fn update_battery(&mut self, level: Option<f32>, is_charging: bool) {
+        let Some(level) = level else {
+            self.last_battery = None;
+            self.drain_per_hour = None;
+            return;
+        };
+        if is_charging {
+            self.last_battery = Some((Instant::now(), level));
+            self.drain_per_hour = None;
+            return;
+        }
+        match self.last_battery {
+            // 至少间隔一分钟再估算，避免电量读数粒度导致的噪声
+            Some((at, prev)) if at.elapsed() >= Duration::from_secs(60) => {
+                let hours = at.elapsed().as_secs_f32() / 3600.0;
+                self.drain_per_hour = Some(((prev - level) / hours).max(0.0));
+                self.last_battery = Some((Instant::now(), level));
+            }
+            Some(_) => {}
+            None => self.last_battery = Some((Instant::now(), level)),
+        }
+    }
+}
+
+fn clamp_interval(config: &TickControllerConfig, interval: Duration) -> Duration {
+    let min = Duration::from_millis(config.min_interval_ms);
+    let max = Duration::from_millis(config.max_interval_ms.max(config.min_interval_ms));
+    interval.clamp(min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feedback(round_ms: u64, thermal: ThermalState, backlog: usize) -> TickFeedback {
+        TickFeedback {
+            round_duration: Duration::from_millis(round_ms),
+            thermal,
+            battery_level: None,
+            is_charging: false,
+            network_backlog: backlog,
+        }
+    }
+
+    #[test]
+    fn test_slow_rounds_and_heat_stretch_interval_within_bounds() {
+        let mut controller = TickController::new(TickControllerConfig::default(), Duration::from_millis(100));
+
+        let adaptation = controller.observe(&feedback(300, ThermalState::Nominal, 0)).unwrap();
+        assert_eq!(adaptation.to_ms, 125);
+
+        for _ in 0..50 {
+            controller.observe(&feedback(20_000, ThermalState::Critical, 1000));
+        }
+        assert_eq!(controller.current(), Duration::from_millis(10_000));
+    }
+
+    #[test]
+    fn test_fast_rounds_shrink_interval() {
+        let mut controller = TickController::new(TickControllerConfig::default(), Duration::from_millis(1000));
+        for _ in 0..20 {
+            controller.observe(&feedback(30, ThermalState::Nominal, 0));
+        }
+        assert_eq!(controller.current(), Duration::from_millis(50));
+    }
+}
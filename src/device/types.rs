@@ -35,6 +35,28 @@ pub enum DeviceType {
     Unknown,
 }
 
+/// 设备温控状态（对应 Android PowerManager / iOS ProcessInfo.thermalState）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThermalState {
+    #[default]
+    Nominal,
+    Fair,
+    Serious,
+    Critical,
+}
+
+impl ThermalState {
+    /// 温控导致的训练降速系数
+    pub fn slowdown_factor(&self) -> f64 {
+        match self {
+            ThermalState::Nominal => 1.0,
+            ThermalState::Fair => 1.25,
+            ThermalState::Serious => 2.0,
+            ThermalState::Critical => 4.0,
+        }
+    }
+}
+
 /// GPU 计算 API 枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Ord, PartialOrd)]
 pub enum GpuComputeApi {
@@ -43,6 +65,7 @@ pub enum GpuComputeApi {
     Metal,     // Apple Metal
     Vulkan,    // Vulkan API
     DirectX,   // Windows DirectX 12
+    WebGPU,    // 浏览器 WebGPU
 }
 
 /// GPU 使用率信息
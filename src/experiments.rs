@@ -0,0 +1,263 @@
+//! 实验性功能框架
+//!
+//! 未完成的子系统（如 zk、workers）通过注册表挂在运行时开关之后：
+//! - 单一总开关即可关闭所有实验（生产环境）
+//! - 实验内部的 panic 和错误被隔离，不会影响核心循环
+//! - 实验产生的指标和日志带有 `experiment.<name>` 前缀，与核心遥测分开
+//! - 连续失败超过阈值的实验自动熔断
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use futures::FutureExt;
+
+/// 实验配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentsConfig {
+    /// 总开关，关闭后所有实验均不运行
+    pub enabled: bool,
+    /// 允许运行的实验列表，为空表示允许所有已注册实验
+    pub allow: Vec<String>,
+    /// 连续失败多少次后自动熔断
+    pub max_consecutive_failures: u32,
+}
+
+impl Default for ExperimentsConfig {
+    fn default() -> Self {
+        Self {
+            // 默认关闭，需显式开启
+            enabled: false,
+            allow: Vec::new(),
+            max_consecutive_failures: 3,
+        }
+    }
+}
+
+/// 实验状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExperimentState {
+    /// 运行中
+    Active,
+    /// 被配置关闭
+    Disabled,
+    /// 因连续失败被熔断
+    Tripped,
+}
+
+/// 单个实验的运行记录（同时作为隔离的遥测输出）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentStatus {
+    pub name: String,
+    pub description: String,
+    pub state: ExperimentState,
+    pub invocations: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// 带实验前缀的指标，键为 `experiment.<name>.<metric>`
+    pub metrics: HashMap<String, f64>,
+}
+
+/// 实验注册表
+pub struct ExperimentRegistry {
+    config: RwLock<ExperimentsConfig>,
+    experiments: RwLock<HashMap<String, ExperimentStatus>>,
+}
+
+impl ExperimentRegistry {
+    pub fn new(config: ExperimentsConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            experiments: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 注册实验，返回其初始状态
+    pub fn register(&self, name: &str, description: &str) -> ExperimentState {
+        let state = self.configured_state(name);
+        let mut experiments = self.experiments.write();
+        let entry = experiments.entry(name.to_string()).or_insert_with(|| ExperimentStatus {
+            name: name.to_string(),
+            description: description.to_string(),
+            state,
+            invocations: 0,
+            failures: 0,
+            consecutive_failures: 0,
+            last_error: None,
+            metrics: HashMap::new(),
+        });
+        if entry.state != ExperimentState::Tripped {
+            entry.state = state;
+        }
+        tracing::info!(experiment = name, state = ?entry.state, "[实验] 已注册");
+        entry.state
+    }
+
+    /// 更新配置（例如热加载），重新计算所有实验的开关状态
+    pub fn update_config(&self, config: ExperimentsConfig) {
+        *self.config.write() = config;
+        let names: Vec<String> = self.experiments.read().keys().cloned().collect();
+        for name in names {
+            let state = self.configured_state(&name);
+            if let Some(status) = self.experiments.write().get_mut(&name) {
+                if status.state != ExperimentState::Tripped {
+                    status.state = state;
+                }
+            }
+        }
+    }
+
+    /// 实验当前是否可运行
+    pub fn is_active(&self, name: &str) -> bool {
+        self.experiments
+            .read()
+            .get(name)
+            .map(|status| status.state == ExperimentState::Active)
+            .unwrap_or(false)
+    }
+
+    /// 在隔离环境中运行同步实验代码
+    ///
+    /// 实验未启用时返回 `None`；实验返回错误或 panic 时记录失败并返回 `None`。
+    pub fn guard<T, F>(&self, name: &str, f: F) -> Option<T>
+    where
+        F: FnOnce() -> anyhow::Result<T>,
+    {
+        if !self.is_active(name) {
+            return None;
+        }
+        let outcome = match catch_unwind(AssertUnwindSafe(f)) {
+            Ok(result) => result,
+            Err(panic) => Err(anyhow::anyhow!("panic: {}", panic_message(&panic))),
+        };
+        self.finish(name, outcome)
+    }
+
+    /// 在隔离环境中运行异步实验代码
+    pub async fn guard_async<T, Fut>(&self, name: &str, fut: Fut) -> Option<T>
+    where
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        if !self.is_active(name) {
+            return None;
+        }
+        let outcome = match AssertUnwindSafe(fut).catch_unwind().await {
+            Ok(result) => result,
+            Err(panic) => Err(anyhow::anyhow!("panic: {}", panic_message(&panic))),
+        };
+        self.finish(name, outcome)
+    }
+
+    /// 记录实验指标（自动加上实验前缀，不进入核心统计）
+    pub fn record_metric(&self, name: &str, metric: &str, value: f64) {
+        if let Some(status) = self.experiments.write().get_mut(name) {
+            status
+                .metrics
+                .insert(format!("experiment.{}.{}", name, metric), value);
+        }
+    }
+
+    /// 手动重置熔断
+    pub fn reset(&self, name: &str) {
+        let state = self.configured_state(name);
+        if let Some(status) = self.experiments.write().get_mut(name) {
+            status.consecutive_failures = 0;
+            status.state = state;
+        }
+    }
+
+    /// 获取所有实验状态
+    pub fn statuses(&self) -> Vec<ExperimentStatus> {
+        let mut statuses: Vec<_> = self.experiments.read().values().cloned().collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+
+    fn configured_state(&self, name: &str) -> ExperimentState {
+        let config = self.config.read();
+        if config.enabled && (config.allow.is_empty() || config.allow.iter().any(|n| n == name)) {
+            ExperimentState::Active
+        } else {
+            ExperimentState::Disabled
+        }
+    }
+
+    fn finish<T>(&self, name: &str, outcome: anyhow::Result<T>) -> Option<T> {
+        let max_failures = self.config.read().max_consecutive_failures;
+        let mut experiments = self.experiments.write();
+        let status = experiments.get_mut(name)?;
+        status.invocations += 1;
+
+        match outcome {
+            Ok(value) => {
+                status.consecutive_failures = 0;
+                Some(value)
+            }
+            Err(e) => {
+                status.failures += 1;
+                status.consecutive_failures += 1;
+                status.last_error = Some(e.to_string());
+                tracing::warn!(experiment = name, error = %e, "[实验] 执行失败，已隔离");
+                if status.consecutive_failures >= max_failures {
+                    status.state = ExperimentState::Tripped;
+                    tracing::error!(experiment = name, "[实验] 连续失败，已熔断");
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Default for ExperimentRegistry {
+    fn default() -> Self {
+        Self::new(ExperimentsConfig::default())
+    }
+}
+
+fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> ExperimentsConfig {
+        ExperimentsConfig {
+            enabled: true,
+            allow: Vec::new(),
+            max_consecutive_failures: 2,
+        }
+    }
+
+    #[test]
+    fn test_master_switch_disables_all() {
+        let registry = ExperimentRegistry::new(ExperimentsConfig::default());
+        registry.register("zk", "zk proofs");
+        assert_eq!(registry.guard("zk", || Ok(1)), None);
+    }
+
+    #[test]
+    fn test_panics_are_isolated_and_trip() {
+        let registry = ExperimentRegistry::new(enabled());
+        registry.register("workers", "edge workers");
+
+        let first: Option<()> = registry.guard("workers", || panic!("boom"));
+        let second: Option<()> = registry.guard("workers", || Err(anyhow::anyhow!("fail")));
+        assert!(first.is_none() && second.is_none());
+
+        let status = &registry.statuses()[0];
+        assert_eq!(status.state, ExperimentState::Tripped);
+        assert_eq!(status.failures, 2);
+        assert_eq!(registry.guard("workers", || Ok(1)), None);
+    }
+}
@@ -0,0 +1,105 @@
+//! iOS 原生回调
+//!
+//! 与 Android 端的 `DeviceInfoProvider` 对应：Swift 侧注册一组 C 函数指针，
+//! Rust 定期通过它们拉取电池和网络状态，并把节点事件（训练启动、停止、失败）推送回去。
+//! 所有回调都可能在后台线程调用，`context` 指向的对象必须线程安全
+//! （Swift 中通常用 `Unmanaged.passRetained(self).toOpaque()` 传入）。
+
+use crate::device::{DeviceManager, NetworkType};
+use crate::ffi::runtime::{NodeEvent, PlatformHooks};
+use anyhow::{anyhow, Result};
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+
+/// 网络类型（与 `NetworkType` 一一对应，供 Swift 直接使用枚举）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WilliwNetworkType {
+    Unknown = 0,
+    WiFi = 1,
+    Cellular4G = 2,
+    Cellular5G = 3,
+}
+
+impl From<WilliwNetworkType> for NetworkType {
+    fn from(value: WilliwNetworkType) -> Self {
+        match value {
+            WilliwNetworkType::WiFi => NetworkType::WiFi,
+            WilliwNetworkType::Cellular4G => NetworkType::Cellular4G,
+            WilliwNetworkType::Cellular5G => NetworkType::Cellular5G,
+            WilliwNetworkType::Unknown => NetworkType::Unknown,
+        }
+    }
+}
+
+impl WilliwNetworkType {
+    /// 解析回调返回的原始值，未知取值按 Unknown 处理
+    pub fn from_raw(value: c_int) -> Self {
+        match value {
+            1 => WilliwNetworkType::WiFi,
+            2 => WilliwNetworkType::Cellular4G,
+            3 => WilliwNetworkType::Cellular5G,
+            _ => WilliwNetworkType::Unknown,
+        }
+    }
+}
+
+/// 读取电池状态：`level` 为 0.0-1.0（无法检测时填 -1.0），返回 0 表示成功
+pub type WilliwBatteryHook = extern "C" fn(context: *mut c_void, level: *mut f32, is_charging: *mut c_int) -> c_int;
+
+/// 读取当前网络类型（`WilliwNetworkType` 的取值），返回 0 表示成功
+pub type WilliwNetworkHook = extern "C" fn(context: *mut c_void, network_type: *mut c_int) -> c_int;
+
+/// 节点事件通知，`event_json` 仅在回调期间有效
+pub type WilliwEventHook = extern "C" fn(context: *mut c_void, event_json: *const c_char);
+
+/// Swift 侧注册的回调集合，未提供的回调传 NULL
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct WilliwIosCallbacks {
+    pub context: *mut c_void,
+    pub battery: Option<WilliwBatteryHook>,
+    pub network: Option<WilliwNetworkHook>,
+    pub on_event: Option<WilliwEventHook>,
+}
+
+// 调用方保证 context 可以跨线程使用
+unsafe impl Send for WilliwIosCallbacks {}
+unsafe impl Sync for WilliwIosCallbacks {}
+
+impl PlatformHooks for WilliwIosCallbacks {
+    fn refresh_device(&self, devices: &DeviceManager) -> Result<()> {
+        if let Some(battery) = self.battery {
+            let mut level: f32 = -1.0;
+            let mut is_charging: c_int = 0;
+            let result = battery(self.context, &mut level, &mut is_charging);
+            if result != 0 {
+                return Err(anyhow!("电池回调返回错误 {}", result));
+            }
+            let level = (0.0..=1.0).contains(&level).then_some(level);
+            devices.update_battery(level, is_charging != 0);
+        }
+
+        if let Some(network) = self.network {
+            let mut network_type: c_int = 0;
+            let result = network(self.context, &mut network_type);
+            if result != 0 {
+                return Err(anyhow!("网络回调返回错误 {}", result));
+            }
+            devices.update_network_type(WilliwNetworkType::from_raw(network_type).into());
+        }
+        Ok(())
+    }
+
+    fn on_event(&self, event: &NodeEvent) {
+        let Some(on_event) = self.on_event else {
+            return;
+        };
+        let Ok(json) = serde_json::to_string(event) else {
+            return;
+        };
+        if let Ok(json) = CString::new(json) {
+            on_event(self.context, json.as_ptr());
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//! iOS C 接口
+//!
+//! 面向 Swift 的 C ABI：节点创建/销毁、设备状态更新、训练控制和推理。
+//! 头文件由 cbindgen 生成（`cargo build --features ios` 时写入 `include/williw.h`），
+//! 配合 `include/module.modulemap` 可在 Swift 中直接 `import Williw`。
+//!
+//! 约定：
+//! - 返回 `c_int` 的函数以 `FfiError` 表示结果，失败详情可通过 `williw_ios_last_error` 读取
+//! - 返回 `*mut c_char` 的字符串都需要用 `williw_string_free` 释放
+//! - 句柄内部持有独立的 tokio 运行时，所有函数都可以在任意线程调用，但不能并发销毁句柄
+
+pub mod callbacks;
+
+pub use callbacks::{WilliwBatteryHook, WilliwEventHook, WilliwIosCallbacks, WilliwNetworkHook, WilliwNetworkType};
+
+use super::runtime::{NodeRuntime, PlatformHooks, TrainingState};
+use crate::network::ffi::FfiError;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::sync::Arc;
+
+/// 训练状态
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WilliwTrainingState {
+    Stopped = 0,
+    Running = 1,
+    /// 节点异常退出，原因见 `williw_ios_last_error`
+    Failed = 2,
+}
+
+impl From<TrainingState> for WilliwTrainingState {
+    fn from(state: TrainingState) -> Self {
+        match state {
+            TrainingState::Stopped => WilliwTrainingState::Stopped,
+            TrainingState::Running => WilliwTrainingState::Running,
+            TrainingState::Failed => WilliwTrainingState::Failed,
+        }
+    }
+}
+
+/// iOS 节点句柄（不透明指针）
+pub struct WilliwIosNode {
+    node: NodeRuntime,
+}
+
+impl WilliwIosNode {
+    /// 记录错误并转换为错误码
+    fn fail(&self, code: FfiError, error: impl ToString) -> c_int {
+        let message = error.to_string();
+        log::warn!("[iOS] {}", message);
+        self.node.set_last_error(message);
+        code as c_int
+    }
+}
+
+fn into_c_string(value: String) -> *mut c_char {
+    CString::new(value).map(CString::into_raw).unwrap_or(std::ptr::null_mut())
+}
+
+/// 创建节点句柄
+///
+/// `config_toml` 为 TOML 格式的完整配置，传 NULL 时按设备能力生成默认配置。
+///
+/// # Safety
+/// `config_toml` 必须是 NULL 或有效的 C 字符串；返回的指针必须通过 `williw_ios_node_destroy` 释放
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_node_create(config_toml: *const c_char) -> *mut WilliwIosNode {
+    let config_toml = if config_toml.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(config_toml).to_str() {
+            Ok(toml_str) => Some(toml_str),
+            Err(e) => {
+                log::error!("[iOS] 配置不是有效的 UTF-8: {}", e);
+                return std::ptr::null_mut();
+            }
+        }
+    };
+    match NodeRuntime::from_toml(config_toml) {
+        Ok(node) => Box::into_raw(Box::new(WilliwIosNode { node })),
+        Err(e) => {
+            log::error!("[iOS] 创建节点失败: {}", e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// 销毁节点句柄（会先停止训练）
+///
+/// # Safety
+/// `ptr` 必须是 `williw_ios_node_create` 返回的指针，且只能销毁一次
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_node_destroy(ptr: *mut WilliwIosNode) {
+    if !ptr.is_null() {
+        let _ = Box::from_raw(ptr);
+    }
+}
+
+/// 注册电池、网络和事件回调，传 NULL 清除
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄；`callbacks` 为 NULL 或指向有效的结构体（内容会被复制）
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_set_callbacks(ptr: *mut WilliwIosNode, callbacks: *const WilliwIosCallbacks) -> c_int {
+    if ptr.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    let hooks = callbacks
+        .as_ref()
+        .map(|callbacks| Arc::new(*callbacks) as Arc<dyn PlatformHooks>);
+    handle.node.set_hooks(hooks);
+    FfiError::Success as c_int
+}
+
+/// 立即通过回调刷新电池和网络状态
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_refresh_device(ptr: *mut WilliwIosNode) -> c_int {
+    if ptr.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    match handle.node.refresh_device() {
+        Ok(()) => FfiError::Success as c_int,
+        Err(e) => handle.fail(FfiError::Unknown, e),
+    }
+}
+
+/// 推送电池状态（例如 `UIDevice.batteryStateDidChangeNotification`）
+///
+/// `level` 为 0.0-1.0，超出范围表示无法检测。
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_update_battery(ptr: *mut WilliwIosNode, level: f32, is_charging: c_int) -> c_int {
+    if ptr.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    let level = (0.0..=1.0).contains(&level).then_some(level);
+    handle.node.devices().update_battery(level, is_charging != 0);
+    FfiError::Success as c_int
+}
+
+/// 推送网络类型变化（例如 `NWPathMonitor`），取值见 `WilliwNetworkType`
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_update_network(ptr: *mut WilliwIosNode, network_type: c_int) -> c_int {
+    if ptr.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    handle.node.devices().update_network_type(WilliwNetworkType::from_raw(network_type).into());
+    FfiError::Success as c_int
+}
+
+/// 更新内存和 CPU 信息
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_update_hardware(ptr: *mut WilliwIosNode, memory_mb: u32, cpu_cores: u32) -> c_int {
+    if ptr.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    handle.node.devices().update_hardware(memory_mb as usize, cpu_cores as usize);
+    FfiError::Success as c_int
+}
+
+/// 当前设备能力（JSON）
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄；返回的字符串必须通过 `williw_string_free` 释放
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_capabilities_json(ptr: *const WilliwIosNode) -> *mut c_char {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    let handle = &*ptr;
+    match serde_json::to_string(&handle.node.devices().get()) {
+        Ok(json) => into_c_string(json),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// 启动训练节点（在句柄内部的运行时中后台运行）
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_training_start(ptr: *mut WilliwIosNode) -> c_int {
+    if ptr.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    match handle.node.start_training() {
+        Ok(()) => FfiError::Success as c_int,
+        Err(e) => handle.fail(FfiError::NetworkError, e),
+    }
+}
+
+/// 停止训练节点
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_training_stop(ptr: *mut WilliwIosNode) -> c_int {
+    if ptr.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    if handle.node.stop_training() {
+        FfiError::Success as c_int
+    } else {
+        handle.fail(FfiError::InvalidArgument, "训练未在运行")
+    }
+}
+
+/// 训练状态
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_training_state(ptr: *const WilliwIosNode) -> WilliwTrainingState {
+    if ptr.is_null() {
+        return WilliwTrainingState::Stopped;
+    }
+    (*ptr).node.training_state().into()
+}
+
+/// 训练状态详情（JSON：状态、运行时长、模型版本、最近错误和训练统计）
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄；返回的字符串必须通过 `williw_string_free` 释放
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_training_status_json(ptr: *const WilliwIosNode) -> *mut c_char {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    into_c_string((*ptr).node.status_json().to_string())
+}
+
+/// 从 JSON 张量快照加载推理模型，已加载模型时蓝绿切换
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄；`path` 必须是有效的 C 字符串
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_load_model(ptr: *mut WilliwIosNode, path: *const c_char) -> c_int {
+    if ptr.is_null() || path.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return handle.fail(FfiError::InvalidArgument, "模型路径不是有效的 UTF-8");
+    };
+    match handle.node.load_model(path) {
+        Ok(_) => FfiError::Success as c_int,
+        Err(e) => handle.fail(FfiError::InvalidArgument, e),
+    }
+}
+
+/// 执行一次推理
+///
+/// 结果写入 `output`（容量 `output_capacity`），实际长度写入 `output_len`。
+/// 容量不足时返回 `OutOfMemory`，`output_len` 中为所需长度。
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄；`input` 指向 `input_len` 个 f32；
+/// `output` 指向 `output_capacity` 个 f32 的可写缓冲区；`output_len` 必须有效
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_infer(
+    ptr: *const WilliwIosNode,
+    input: *const f32,
+    input_len: usize,
+    output: *mut f32,
+    output_capacity: usize,
+    output_len: *mut usize,
+) -> c_int {
+    if ptr.is_null() || input.is_null() || output.is_null() || output_len.is_null() {
+        return FfiError::InvalidArgument as c_int;
+    }
+    let handle = &*ptr;
+    let input = std::slice::from_raw_parts(input, input_len);
+    match handle.node.infer(input) {
+        Ok(values) => {
+            *output_len = values.len();
+            if values.len() > output_capacity {
+                return handle.fail(FfiError::OutOfMemory, format!("输出缓冲区需要 {} 个元素", values.len()));
+            }
+            std::ptr::copy_nonoverlapping(values.as_ptr(), output, values.len());
+            FfiError::Success as c_int
+        }
+        Err(e) => handle.fail(FfiError::InvalidArgument, e),
+    }
+}
+
+/// 最近一次错误信息，没有错误时返回 NULL
+///
+/// # Safety
+/// `ptr` 必须是有效的节点句柄；返回的字符串必须通过 `williw_string_free` 释放
+#[no_mangle]
+pub unsafe extern "C" fn williw_ios_last_error(ptr: *const WilliwIosNode) -> *mut c_char {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    match (*ptr).node.last_error() {
+        Some(message) => into_c_string(message),
+        None => std::ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::ffi::williw_string_free;
+    use crate::types::TensorSnapshot;
+    use std::os::raw::c_void;
+
+    #[test]
+    fn test_device_hooks_update_capabilities() {
+        extern "C" fn battery(_context: *mut c_void, level: *mut f32, is_charging: *mut c_int) -> c_int {
+            unsafe {
+                *level = 0.42;
+                *is_charging = 1;
+            }
+            0
+        }
+        extern "C" fn network(_context: *mut c_void, network_type: *mut c_int) -> c_int {
+            unsafe { *network_type = WilliwNetworkType::Cellular5G as c_int };
+            0
+        }
+
+        unsafe {
+            let ptr = williw_ios_node_create(std::ptr::null());
+            assert!(!ptr.is_null());
+            let callbacks = WilliwIosCallbacks {
+                context: std::ptr::null_mut(),
+                battery: Some(battery),
+                network: Some(network),
+                on_event: None,
+            };
+            assert_eq!(williw_ios_set_callbacks(ptr, &callbacks), FfiError::Success as c_int);
+            assert_eq!(williw_ios_refresh_device(ptr), FfiError::Success as c_int);
+
+            let json_ptr = williw_ios_capabilities_json(ptr);
+            let json = CStr::from_ptr(json_ptr).to_str().unwrap().to_string();
+            williw_string_free(json_ptr);
+            assert!(json.contains("\"network_type\":\"Cellular5G\""));
+            assert!(json.contains("\"is_charging\":true"));
+
+            assert_eq!(williw_ios_update_network(ptr, WilliwNetworkType::WiFi as c_int), FfiError::Success as c_int);
+            assert_eq!((*ptr).node.devices().get().network_type, crate::device::NetworkType::WiFi);
+            assert_eq!(williw_ios_training_state(ptr), WilliwTrainingState::Stopped);
+            williw_ios_node_destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn test_load_model_and_infer() {
+        let path = std::env::temp_dir().join(format!("williw-ios-model-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, serde_json::to_string(&TensorSnapshot::new(vec![1.0, 2.0, 3.0], 1)).unwrap()).unwrap();
+        let c_path = CString::new(path.to_string_lossy().to_string()).unwrap();
+
+        unsafe {
+            let ptr = williw_ios_node_create(std::ptr::null());
+            let mut output = [0.0f32; 1];
+            let mut output_len = 0;
+            let input = [1.0f32, 1.0, 1.0];
+            // 未加载模型时推理失败
+            assert_ne!(
+                williw_ios_infer(ptr, input.as_ptr(), input.len(), output.as_mut_ptr(), 1, &mut output_len),
+                FfiError::Success as c_int
+            );
+
+            assert_eq!(williw_ios_load_model(ptr, c_path.as_ptr()), FfiError::Success as c_int);
+            assert_eq!(
+                williw_ios_infer(ptr, input.as_ptr(), input.len(), output.as_mut_ptr(), 1, &mut output_len),
+                FfiError::Success as c_int
+            );
+            assert_eq!(output_len, 1);
+            assert!((output[0] - 6.0).abs() < 1e-6);
+            williw_ios_node_destroy(ptr);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,290 @@
+//! UniFFI 绑定
+//!
+//! 接口定义在 `src/williw.udl`，这里是对应的 Rust 实现。Kotlin 和 Swift 代码都由同一份
+//! 定义生成，替代逐个手写 JNI / C 函数；底层与 iOS C 接口共用 [`NodeRuntime`]。
+
+use super::runtime::{NodeRuntime, PlatformHooks};
+use crate::device::DeviceManager;
+use anyhow::Result;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+pub use super::runtime::{NodeEvent, TrainingState, TrainingStatus};
+pub use crate::device::NetworkType;
+
+/// 设备能力摘要
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceInfo {
+    pub max_memory_mb: u64,
+    pub cpu_cores: u32,
+    pub has_gpu: bool,
+    pub cpu_architecture: String,
+    pub network_type: NetworkType,
+    pub battery_level: Option<f32>,
+    pub is_charging: Option<bool>,
+    pub recommended_model_dim: u32,
+    pub recommended_tick_secs: u64,
+    pub should_pause_training: bool,
+}
+
+impl DeviceInfo {
+    fn from_manager(devices: &DeviceManager) -> Self {
+        let caps = devices.get();
+        Self {
+            max_memory_mb: caps.max_memory_mb,
+            cpu_cores: caps.cpu_cores,
+            has_gpu: caps.has_gpu,
+            cpu_architecture: caps.cpu_architecture.clone(),
+            network_type: caps.network_type,
+            battery_level: caps.battery_level,
+            is_charging: caps.is_charging,
+            recommended_model_dim: devices.recommended_model_dim() as u32,
+            recommended_tick_secs: caps.recommended_tick_interval().as_secs(),
+            should_pause_training: caps.should_pause_training(),
+        }
+    }
+}
+
+/// 可供选择的训练模型
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelOption {
+    pub id: String,
+    pub name: String,
+    pub dimensions: u32,
+    pub learning_rate: f64,
+    pub batch_size: u32,
+    /// 训练该模型至少需要的内存（MB）
+    pub min_memory_mb: u64,
+}
+
+/// 模型及其是否适合本机
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelAvailability {
+    pub model: ModelOption,
+    pub fits_device: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryState {
+    pub level: f32,
+    pub is_charging: bool,
+}
+
+/// 平台设备状态提供方（Kotlin / Swift 实现）
+pub trait DeviceProvider: Send + Sync {
+    fn battery(&self) -> Option<BatteryState>;
+    fn network_type(&self) -> NetworkType;
+}
+
+/// 节点事件监听方（Kotlin / Swift 实现）
+pub trait NodeListener: Send + Sync {
+    fn on_event(&self, event: NodeEvent);
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WilliwError {
+    #[error("配置无效: {0}")]
+    InvalidConfig(String),
+    #[error("训练控制失败: {0}")]
+    Training(String),
+    #[error("未知模型: {0}")]
+    UnknownModel(String),
+    #[error("模型 {id} 需要 {required_mb}MB 内存，设备只有 {available_mb}MB")]
+    ModelTooLarge {
+        id: String,
+        required_mb: u64,
+        available_mb: u64,
+    },
+}
+
+/// 把外部回调接到节点运行时上
+#[derive(Default)]
+struct MobileHooks {
+    provider: RwLock<Option<Box<dyn DeviceProvider>>>,
+    listener: RwLock<Option<Box<dyn NodeListener>>>,
+}
+
+impl PlatformHooks for MobileHooks {
+    fn refresh_device(&self, devices: &DeviceManager) -> Result<()> {
+        if let Some(provider) = self.provider.read().as_ref() {
+            if let Some(battery) = provider.battery() {
+                devices.update_battery(Some(battery.level.clamp(0.0, 1.0)), battery.is_charging);
+            }
+            devices.update_network_type(provider.network_type());
+        }
+        Ok(())
+    }
+
+    fn on_event(&self, event: &NodeEvent) {
+        if let Some(listener) = self.listener.read().as_ref() {
+            listener.on_event(event.clone());
+        }
+    }
+}
+
+pub fn detect_device() -> DeviceInfo {
+    DeviceInfo::from_manager(&DeviceManager::new())
+}
+
+/// 移动端节点
+pub struct WilliwNode {
+    node: NodeRuntime,
+    hooks: Arc<MobileHooks>,
+    catalog: RwLock<Vec<ModelOption>>,
+    selected: RwLock<Option<ModelOption>>,
+}
+
+impl WilliwNode {
+    pub fn new(config_toml: Option<String>) -> Result<Self, WilliwError> {
+        let node =
+            NodeRuntime::from_toml(config_toml.as_deref()).map_err(|e| WilliwError::InvalidConfig(e.to_string()))?;
+        let hooks = Arc::new(MobileHooks::default());
+        node.set_hooks(Some(hooks.clone()));
+        Ok(Self {
+            node,
+            hooks,
+            catalog: RwLock::new(Vec::new()),
+            selected: RwLock::new(None),
+        })
+    }
+
+    pub fn device_info(&self) -> DeviceInfo {
+        DeviceInfo::from_manager(self.node.devices())
+    }
+
+    pub fn update_battery(&self, level: Option<f32>, is_charging: bool) {
+        let level = level.filter(|l| (0.0..=1.0).contains(l));
+        self.node.devices().update_battery(level, is_charging);
+    }
+
+    pub fn update_network(&self, network_type: NetworkType) {
+        self.node.devices().update_network_type(network_type);
+    }
+
+    pub fn set_device_provider(&self, provider: Box<dyn DeviceProvider>) {
+        *self.hooks.provider.write() = Some(provider);
+    }
+
+    pub fn set_listener(&self, listener: Box<dyn NodeListener>) {
+        *self.hooks.listener.write() = Some(listener);
+    }
+
+    pub fn refresh_device(&self) -> Result<(), WilliwError> {
+        self.node.refresh_device().map_err(|e| WilliwError::Training(e.to_string()))
+    }
+
+    pub fn start_training(&self) -> Result<(), WilliwError> {
+        self.node.start_training().map_err(|e| WilliwError::Training(e.to_string()))
+    }
+
+    pub fn stop_training(&self) -> bool {
+        self.node.stop_training()
+    }
+
+    pub fn training_status(&self) -> TrainingStatus {
+        self.node.training_status()
+    }
+
+    /// 由应用提供可选模型列表（例如从协调者拉取）
+    pub fn set_model_catalog(&self, models: Vec<ModelOption>) {
+        *self.catalog.write() = models;
+    }
+
+    pub fn available_models(&self) -> Vec<ModelAvailability> {
+        let available_mb = self.node.devices().get().max_memory_mb;
+        self.catalog
+            .read()
+            .iter()
+            .map(|model| ModelAvailability {
+                fits_device: model.min_memory_mb <= available_mb,
+                model: model.clone(),
+            })
+            .collect()
+    }
+
+    /// 选择训练模型，下次启动训练时按它的维度、学习率和批量大小运行
+    pub fn select_model(&self, model_id: String) -> Result<(), WilliwError> {
+        let model = self
+            .catalog
+            .read()
+            .iter()
+            .find(|m| m.id == model_id)
+            .cloned()
+            .ok_or(WilliwError::UnknownModel(model_id))?;
+        let available_mb = self.node.devices().get().max_memory_mb;
+        if model.min_memory_mb > available_mb {
+            return Err(WilliwError::ModelTooLarge {
+                id: model.id,
+                required_mb: model.min_memory_mb,
+                available_mb,
+            });
+        }
+        self.node.update_config(|config| {
+            config.training.model_dim = model.dimensions as usize;
+            config.training.learning_rate = model.learning_rate;
+            config.training.batch_size = model.batch_size as usize;
+        });
+        *self.selected.write() = Some(model);
+        Ok(())
+    }
+
+    pub fn selected_model(&self) -> Option<ModelOption> {
+        self.selected.read().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str, min_memory_mb: u64) -> ModelOption {
+        ModelOption {
+            id: id.to_string(),
+            name: id.to_string(),
+            dimensions: 768,
+            learning_rate: 2e-5,
+            batch_size: 16,
+            min_memory_mb,
+        }
+    }
+
+    #[test]
+    fn test_select_model_checks_memory_and_updates_config() {
+        let node = WilliwNode::new(None).unwrap();
+        let memory = node.device_info().max_memory_mb;
+        node.set_model_catalog(vec![model("small", 1), model("huge", memory + 1)]);
+
+        let fits: Vec<bool> = node.available_models().iter().map(|m| m.fits_device).collect();
+        assert_eq!(fits, vec![true, false]);
+        assert!(matches!(node.select_model("huge".into()), Err(WilliwError::ModelTooLarge { .. })));
+        assert!(matches!(node.select_model("missing".into()), Err(WilliwError::UnknownModel(_))));
+
+        node.select_model("small".into()).unwrap();
+        assert_eq!(node.selected_model().unwrap().id, "small");
+        let config = node.node.config();
+        assert_eq!(config.training.model_dim, 768);
+        assert_eq!(config.training.batch_size, 16);
+    }
+
+    #[test]
+    fn test_device_provider_feeds_device_info() {
+        struct OnCellular;
+        impl DeviceProvider for OnCellular {
+            fn battery(&self) -> Option<BatteryState> {
+                Some(BatteryState { level: 0.3, is_charging: false })
+            }
+            fn network_type(&self) -> NetworkType {
+                NetworkType::Cellular4G
+            }
+        }
+
+        let node = WilliwNode::new(None).unwrap();
+        node.set_device_provider(Box::new(OnCellular));
+        node.refresh_device().unwrap();
+        let info = node.device_info();
+        assert_eq!(info.network_type, NetworkType::Cellular4G);
+        assert_eq!(info.battery_level, Some(0.3));
+        assert_eq!(info.is_charging, Some(false));
+        assert_eq!(node.training_status().state, TrainingState::Stopped);
+    }
+}
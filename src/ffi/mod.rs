@@ -0,0 +1,21 @@
+//! 平台原生绑定
+//!
+//! 通用的 C 接口（设备信息回调、能力查询、字符串释放）位于 `crate::network::ffi`，
+//! 这里放各平台专用的导出层：
+//! - `ios`：面向 Swift 的手写 C 接口
+//! - `mobile`：UniFFI 绑定，从 `src/williw.udl` 同时生成 Kotlin 和 Swift 代码
+//! - `node`：Node.js 原生插件（napi-rs），供 Web 后端嵌入节点
+//!
+//! Android 原有的 JNI 接口（`crate::android`）保留给旧版调用方。
+
+#[cfg(any(feature = "ios", feature = "uniffi", feature = "nodejs"))]
+pub mod runtime;
+
+#[cfg(feature = "ios")]
+pub mod ios;
+
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+
+#[cfg(feature = "nodejs")]
+pub mod node;
@@ -0,0 +1,233 @@
+//! Node.js 绑定（napi-rs）
+//!
+//! 供 Web 后端在进程内嵌入节点：训练启动/停止、状态订阅、推理请求和模型管理。
+//! 预编译的原生插件由 `bindings/node` 打包发布，JS 侧用法见其中的 README。
+//!
+//! 约定：
+//! - 节点事件和状态推送都以普通 JS 对象传入回调，字段与 iOS 接口的 JSON 一致
+//! - 推理和加载模型在 libuv 线程池执行并返回 Promise，不阻塞事件循环
+//! - 回调不会阻止 Node.js 进程退出
+
+use super::runtime::{NodeEvent, NodeRuntime, PlatformHooks};
+use crate::device::DeviceManager;
+use napi::bindgen_prelude::{AsyncTask, Float32Array};
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, JsFunction, Task};
+use napi_derive::napi;
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 状态推送的默认间隔
+const DEFAULT_STATS_INTERVAL_MS: u32 = 1000;
+/// 状态推送的最小间隔，避免回调挤满事件循环
+const MIN_STATS_INTERVAL_MS: u32 = 100;
+
+type JsCallback = ThreadsafeFunction<serde_json::Value, ErrorStrategy::Fatal>;
+
+fn to_napi(error: impl std::fmt::Display) -> napi::Error {
+    napi::Error::from_reason(error.to_string())
+}
+
+/// 把 JS 函数包装为可在任意线程调用的回调
+fn js_callback(env: &Env, callback: JsFunction) -> napi::Result<JsCallback> {
+    let mut callback: JsCallback = callback
+        .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<serde_json::Value>| Ok(vec![ctx.value]))?;
+    callback.unref(env)?;
+    Ok(callback)
+}
+
+/// 服务端进程直接读取本机设备状态，事件转发给已注册的 JS 监听器
+#[derive(Default)]
+struct NodeHooks {
+    listeners: RwLock<Vec<JsCallback>>,
+}
+
+impl PlatformHooks for NodeHooks {
+    fn refresh_device(&self, devices: &DeviceManager) -> anyhow::Result<()> {
+        devices.refresh();
+        Ok(())
+    }
+
+    fn on_event(&self, event: &NodeEvent) {
+        let Ok(value) = serde_json::to_value(event) else {
+            return;
+        };
+        for listener in self.listeners.read().iter() {
+            listener.call(value.clone(), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
+}
+
+pub struct InferTask {
+    node: Arc<NodeRuntime>,
+    input: Vec<f32>,
+}
+
+impl Task for InferTask {
+    type Output = Vec<f32>;
+    type JsValue = Float32Array;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        self.node.infer(&self.input).map_err(to_napi)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(Float32Array::new(output))
+    }
+}
+
+pub struct LoadModelTask {
+    node: Arc<NodeRuntime>,
+    path: String,
+}
+
+impl Task for LoadModelTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        self.node.load_model(&self.path).map_err(to_napi)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// 状态订阅句柄，调用 `unsubscribe()` 停止推送
+#[napi]
+pub struct StatsSubscription {
+    stopped: Arc<AtomicBool>,
+}
+
+#[napi]
+impl StatsSubscription {
+    #[napi]
+    pub fn unsubscribe(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 嵌入式 williw 节点
+#[napi(js_name = "WilliwNode")]
+pub struct JsWilliwNode {
+    node: Arc<NodeRuntime>,
+    hooks: Arc<NodeHooks>,
+}
+
+#[napi]
+impl JsWilliwNode {
+    /// `configToml` 为 TOML 格式的完整配置，省略时按本机设备能力生成默认配置
+    #[napi(constructor)]
+    pub fn new(config_toml: Option<String>) -> napi::Result<Self> {
+        let node = NodeRuntime::from_toml(config_toml.as_deref()).map_err(to_napi)?;
+        let hooks = Arc::new(NodeHooks::default());
+        node.set_hooks(Some(hooks.clone()));
+        Ok(Self {
+            node: Arc::new(node),
+            hooks,
+        })
+    }
+
+    /// 启动训练节点
+    #[napi]
+    pub fn start(&self) -> napi::Result<()> {
+        self.node.start_training().map_err(to_napi)
+    }
+
+    /// 停止训练节点，未在运行时返回 false
+    #[napi]
+    pub fn stop(&self) -> bool {
+        self.node.stop_training()
+    }
+
+    /// 训练状态、统计和当前模型版本
+    #[napi]
+    pub fn status(&self) -> serde_json::Value {
+        self.node.status_json()
+    }
+
+    #[napi]
+    pub fn device_info(&self) -> napi::Result<serde_json::Value> {
+        serde_json::to_value(self.node.devices().get()).map_err(to_napi)
+    }
+
+    /// 注册节点事件监听器（训练启动、停止、失败和模型加载）
+    #[napi]
+    pub fn on_event(&self, env: Env, callback: JsFunction) -> napi::Result<()> {
+        let callback = js_callback(&env, callback)?;
+        self.hooks.listeners.write().push(callback);
+        Ok(())
+    }
+
+    /// 按固定间隔推送 `status()` 的结果，默认每秒一次
+    #[napi]
+    pub fn subscribe_stats(
+        &self,
+        env: Env,
+        callback: JsFunction,
+        interval_ms: Option<u32>,
+    ) -> napi::Result<StatsSubscription> {
+        let callback = js_callback(&env, callback)?;
+        let interval = Duration::from_millis(
+            interval_ms
+                .unwrap_or(DEFAULT_STATS_INTERVAL_MS)
+                .max(MIN_STATS_INTERVAL_MS) as u64,
+        );
+        let stopped = Arc::new(AtomicBool::new(false));
+        let flag = stopped.clone();
+        // 只持有弱引用：JS 侧释放节点后订阅自动结束。
+        // 推送在独立线程中进行，避免最后一个引用在节点自己的运行时里被释放
+        let node = Arc::downgrade(&self.node);
+        std::thread::Builder::new()
+            .name("williw-stats".to_string())
+            .spawn(move || {
+                while !flag.load(Ordering::Relaxed) {
+                    let Some(node) = node.upgrade() else {
+                        break;
+                    };
+                    callback.call(node.status_json(), ThreadsafeFunctionCallMode::NonBlocking);
+                    drop(node);
+                    std::thread::sleep(interval);
+                }
+            })
+            .map_err(to_napi)?;
+        Ok(StatsSubscription { stopped })
+    }
+
+    /// 执行一次推理
+    #[napi(ts_return_type = "Promise<Float32Array>")]
+    pub fn infer(&self, input: Float32Array) -> AsyncTask<InferTask> {
+        AsyncTask::new(InferTask {
+            node: self.node.clone(),
+            input: input.to_vec(),
+        })
+    }
+
+    /// 从 JSON 张量快照加载推理模型，已加载模型时蓝绿切换；返回新模型版本
+    #[napi(ts_return_type = "Promise<string>")]
+    pub fn load_model(&self, path: String) -> AsyncTask<LoadModelTask> {
+        AsyncTask::new(LoadModelTask {
+            node: self.node.clone(),
+            path,
+        })
+    }
+
+    /// 卸载推理模型，未加载时返回 false
+    #[napi]
+    pub fn unload_model(&self) -> bool {
+        self.node.unload_model()
+    }
+
+    #[napi]
+    pub fn model_version(&self) -> Option<String> {
+        self.node.model_version()
+    }
+
+    #[napi]
+    pub fn last_error(&self) -> Option<String> {
+        self.node.last_error()
+    }
+}
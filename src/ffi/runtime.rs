@@ -0,0 +1,368 @@
+//! 移动端绑定共用的节点运行时
+//!
+//! 句柄内部持有独立的 tokio 运行时，训练节点在后台任务中运行；
+//! 平台层通过 [`PlatformHooks`] 提供设备状态并接收节点事件。
+//! iOS C 接口、UniFFI 绑定和 Node.js 绑定都基于它实现，只在类型转换上不同。
+
+use crate::config::AppConfig;
+use crate::device::DeviceManager;
+use crate::node::Node;
+use crate::stats::TrainingStatsManager;
+use crate::training::{ModelRouter, ServableModel, SwapConfig, SwapOutcome};
+use crate::types::TensorSnapshot;
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// 训练运行期间拉取平台设备状态的间隔
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 节点事件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NodeEvent {
+    TrainingStarted,
+    TrainingStopped,
+    TrainingFailed { error: String },
+    ModelLoaded { version: String },
+}
+
+/// 训练状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TrainingState {
+    Stopped,
+    Running,
+    /// 节点异常退出，原因见 `last_error`
+    Failed,
+}
+
+/// 训练状态详情
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrainingStatus {
+    pub state: TrainingState,
+    /// 本次训练已运行的秒数（未启动时为 None）
+    pub uptime_secs: Option<u64>,
+    pub last_error: Option<String>,
+    /// 训练统计（JSON）
+    pub stats_json: Option<String>,
+}
+
+/// 平台层回调，可能在后台线程调用
+pub trait PlatformHooks: Send + Sync {
+    /// 拉取平台的电池、网络等状态并写入设备管理器
+    fn refresh_device(&self, _devices: &DeviceManager) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_event(&self, _event: &NodeEvent) {}
+}
+
+/// 按张量快照做线性推理的模型（输出为参数与输入的内积）
+pub struct SnapshotModel {
+    snapshot: TensorSnapshot,
+    version: String,
+}
+
+impl SnapshotModel {
+    pub fn new(snapshot: TensorSnapshot) -> Self {
+        let hash = snapshot.hash();
+        let version = format!("v{}-{}", snapshot.version, &hash[2..hash.len().min(12)]);
+        Self { snapshot, version }
+    }
+}
+
+impl ServableModel for SnapshotModel {
+    fn version(&self) -> String {
+        self.version.clone()
+    }
+
+    fn memory_mb(&self) -> usize {
+        (self.snapshot.values.len() * std::mem::size_of::<f32>()).div_ceil(1024 * 1024)
+    }
+
+    fn infer(&self, input: &[f32]) -> Result<Vec<f32>> {
+        if input.len() != self.snapshot.values.len() {
+            return Err(anyhow!(
+                "输入维度 {} 与模型维度 {} 不一致",
+                input.len(),
+                self.snapshot.values.len()
+            ));
+        }
+        Ok(vec![self.snapshot.values.iter().zip(input).map(|(w, x)| w * x).sum()])
+    }
+}
+
+type HookSlot = Arc<RwLock<Option<Arc<dyn PlatformHooks>>>>;
+
+fn emit(hooks: &HookSlot, event: NodeEvent) {
+    let hooks = hooks.read().clone();
+    if let Some(hooks) = hooks {
+        hooks.on_event(&event);
+    }
+}
+
+fn refresh(hooks: &HookSlot, devices: &DeviceManager) -> Result<()> {
+    let hooks = hooks.read().clone();
+    match hooks {
+        Some(hooks) => hooks.refresh_device(devices),
+        None => Ok(()),
+    }
+}
+
+struct TrainingSession {
+    node_task: JoinHandle<()>,
+    device_task: JoinHandle<()>,
+    stats: Arc<std::sync::Mutex<TrainingStatsManager>>,
+    started_at: Instant,
+}
+
+/// 移动端节点运行时
+pub struct NodeRuntime {
+    runtime: tokio::runtime::Runtime,
+    config: RwLock<AppConfig>,
+    devices: DeviceManager,
+    hooks: HookSlot,
+    training: Mutex<Option<TrainingSession>>,
+    last_error: Arc<Mutex<Option<String>>>,
+    model: RwLock<Option<ModelRouter<SnapshotModel>>>,
+}
+
+impl NodeRuntime {
+    pub fn new(config: AppConfig) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("williw-mobile")
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            runtime,
+            devices: DeviceManager::with_capabilities(config.device_capabilities.clone()),
+            config: RwLock::new(config),
+            hooks: Arc::new(RwLock::new(None)),
+            training: Mutex::new(None),
+            last_error: Arc::new(Mutex::new(None)),
+            model: RwLock::new(None),
+        })
+    }
+
+    /// TOML 配置，为 None 时按本机设备能力生成默认配置
+    pub fn from_toml(config_toml: Option<&str>) -> Result<Self> {
+        let config = match config_toml {
+            Some(toml_str) => toml::from_str(toml_str)?,
+            None => AppConfig::from_device_capabilities(DeviceManager::new().get()),
+        };
+        Self::new(config)
+    }
+
+    pub fn runtime(&self) -> &tokio::runtime::Runtime {
+        &self.runtime
+    }
+
+    pub fn devices(&self) -> &DeviceManager {
+        &self.devices
+    }
+
+    pub fn config(&self) -> AppConfig {
+        self.config.read().clone()
+    }
+
+    /// 修改配置，下次启动训练时生效
+    pub fn update_config(&self, update: impl FnOnce(&mut AppConfig)) {
+        update(&mut self.config.write());
+    }
+
+    pub fn set_hooks(&self, hooks: Option<Arc<dyn PlatformHooks>>) {
+        *self.hooks.write() = hooks;
+    }
+
+    pub fn refresh_device(&self) -> Result<()> {
+        refresh(&self.hooks, &self.devices)
+    }
+
+    pub fn emit(&self, event: NodeEvent) {
+        emit(&self.hooks, event);
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().clone()
+    }
+
+    pub fn set_last_error(&self, message: String) {
+        *self.last_error.lock() = Some(message);
+    }
+
+    pub fn training_state(&self) -> TrainingState {
+        match self.training.lock().as_ref() {
+            Some(session) if !session.node_task.is_finished() => TrainingState::Running,
+            Some(_) if self.last_error.lock().is_some() => TrainingState::Failed,
+            _ => TrainingState::Stopped,
+        }
+    }
+
+    pub fn start_training(&self) -> Result<()> {
+        let mut training = self.training.lock();
+        if training.as_ref().is_some_and(|s| !s.node_task.is_finished()) {
+            return Err(anyhow!("训练已在运行"));
+        }
+        *self.last_error.lock() = None;
+        if let Err(e) = self.refresh_device() {
+            log::warn!("[移动端] 刷新设备状态失败: {}", e);
+        }
+
+        let mut config = self.config();
+        config.device_capabilities = self.devices.get();
+        let node = self
+            .runtime
+            .block_on(Node::new(config))?
+            .with_device_manager(self.devices.clone());
+        let stats = node.stats.clone();
+
+        let hooks = self.hooks.clone();
+        let last_error = self.last_error.clone();
+        let node_task = self.runtime.spawn(async move {
+            emit(&hooks, NodeEvent::TrainingStarted);
+            match node.run().await {
+                Ok(()) => emit(&hooks, NodeEvent::TrainingStopped),
+                Err(e) => {
+                    let error = e.to_string();
+                    *last_error.lock() = Some(error.clone());
+                    emit(&hooks, NodeEvent::TrainingFailed { error });
+                }
+            }
+        });
+
+        let devices = self.devices.clone();
+        let hooks = self.hooks.clone();
+        let device_task = self.runtime.spawn(async move {
+            let mut poll = tokio::time::interval(DEVICE_POLL_INTERVAL);
+            loop {
+                poll.tick().await;
+                if let Err(e) = refresh(&hooks, &devices) {
+                    log::warn!("[移动端] 刷新设备状态失败: {}", e);
+                }
+            }
+        });
+
+        *training = Some(TrainingSession {
+            node_task,
+            device_task,
+            stats,
+            started_at: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// 停止训练，未在运行时返回 false
+    pub fn stop_training(&self) -> bool {
+        let Some(session) = self.training.lock().take() else {
+            return false;
+        };
+        session.node_task.abort();
+        session.device_task.abort();
+        self.emit(NodeEvent::TrainingStopped);
+        true
+    }
+
+    pub fn training_status(&self) -> TrainingStatus {
+        let state = self.training_state();
+        let training = self.training.lock();
+        TrainingStatus {
+            state,
+            uptime_secs: training.as_ref().map(|s| s.started_at.elapsed().as_secs()),
+            last_error: self.last_error(),
+            stats_json: training
+                .as_ref()
+                .and_then(|s| s.stats.lock().ok().and_then(|stats| stats.export_json().ok())),
+        }
+    }
+}
+
+impl NodeRuntime {
+    /// 训练状态、统计和当前模型版本，供各绑定直接序列化返回
+    pub fn status_json(&self) -> serde_json::Value {
+        let status = self.training_status();
+        let stats = status
+            .stats_json
+            .as_deref()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok());
+        json!({
+            "state": status.state,
+            "uptime_secs": status.uptime_secs,
+            "model_version": self.model_version(),
+            "last_error": status.last_error,
+            "stats": stats,
+        })
+    }
+
+    /// 从 JSON 张量快照加载推理模型；已有模型时按蓝绿方式切换
+    pub fn load_model(&self, path: &str) -> Result<String> {
+        let snapshot: TensorSnapshot = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let model = SnapshotModel::new(snapshot);
+        let version = model.version();
+        let router = self.model.read();
+        match router.as_ref() {
+            Some(router) => match self.runtime.block_on(router.swap(model))? {
+                SwapOutcome::Switched { .. } => {}
+                SwapOutcome::InsufficientMemory { required_mb, budget_mb } => {
+                    return Err(anyhow!("内存不足以切换模型：需要 {}MB，预算 {}MB", required_mb, budget_mb));
+                }
+            },
+            None => {
+                drop(router);
+                *self.model.write() = Some(ModelRouter::new(SwapConfig::default(), model));
+            }
+        }
+        self.emit(NodeEvent::ModelLoaded {
+            version: version.clone(),
+        });
+        Ok(version)
+    }
+
+    /// 卸载推理模型，未加载时返回 false
+    pub fn unload_model(&self) -> bool {
+        self.model.write().take().is_some()
+    }
+
+    pub fn model_version(&self) -> Option<String> {
+        self.model.read().as_ref().map(|m| m.active_version())
+    }
+
+    pub fn infer(&self, input: &[f32]) -> Result<Vec<f32>> {
+        match self.model.read().as_ref() {
+            Some(router) => router.infer(input),
+            None => Err(anyhow!("尚未加载模型")),
+        }
+    }
+}
+
+impl Drop for NodeRuntime {
+    fn drop(&mut self) {
+        self.stop_training();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_load_status_and_unload() {
+        let path = std::env::temp_dir().join(format!("williw-runtime-model-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, serde_json::to_string(&TensorSnapshot::new(vec![0.5, 2.0], 3)).unwrap()).unwrap();
+
+        let node = NodeRuntime::from_toml(None).unwrap();
+        assert!(node.infer(&[1.0, 1.0]).is_err());
+        let version = node.load_model(path.to_str().unwrap()).unwrap();
+        assert_eq!(node.status_json()["model_version"], version);
+        assert_eq!(node.infer(&[2.0, 1.0]).unwrap(), vec![3.0]);
+
+        assert!(node.unload_model());
+        assert!(!node.unload_model());
+        assert!(node.model_version().is_none());
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,526 @@
+//! 集群托管（fleet）代理
+//!
+//! 企业批量部署贡献节点时，由集群控制器统一管理。开启后节点主动连接控制器（无需开放入站端口）：
+//! - 定期上报签名的节点清单（构建、设备、资源上限、训练进度）
+//! - 在心跳响应中领取管理指令：修改运行时配置、开始/停止训练、收集诊断包
+//! - 指令必须由受信任控制器的身份密钥签名，并校验目标节点、有效期和序号防重放；
+//!   每个控制器已执行的最大序号持久化在身份文件旁，重启后仍能拒绝重放
+
+use crate::admin::{NodeStatus, NodeStatusBoard};
+use crate::build_info::BuildInfo;
+use crate::config_watch::{ConfigWatcher, RuntimeSettings};
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+use crate::device::{DeviceCapabilities, DeviceManager, PowerPolicy, ResourceCaps};
+use crate::stats::{TrainingStats, TrainingStatsManager};
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 心跳失败后的最长重试间隔
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// 集群托管配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FleetConfig {
+    pub enabled: bool,
+    /// 控制器地址，例如 `https://fleet.example.com`
+    pub controller_url: String,
+    /// 受信任控制器的节点 ID（身份公钥 base58）
+    pub trusted_controllers: Vec<String>,
+    /// 心跳间隔（秒）
+    pub poll_interval_secs: u64,
+    /// 上报给控制器的自定义标签（机房、部门等）
+    pub labels: BTreeMap<String, String>,
+    /// 已执行指令序号的状态文件，默认为身份文件所在目录下的 `fleet_state.json`
+    pub state_path: Option<PathBuf>,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            controller_url: String::new(),
+            trusted_controllers: Vec::new(),
+            poll_interval_secs: 15,
+            labels: BTreeMap::new(),
+            state_path: None,
+        }
+    }
+}
+
+impl FleetConfig {
+    /// 实际使用的状态文件路径
+    pub fn state_path(&self) -> PathBuf {
+        self.state_path.clone().unwrap_or_else(|| {
+            crate::crypto::identity::default_identity_path()
+                .parent()
+                .map(|dir| dir.join("fleet_state.json"))
+                .unwrap_or_else(|| PathBuf::from("fleet_state.json"))
+        })
+    }
+}
+
+/// 管理指令内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FleetAction {
+    /// 替换可热更新的配置项
+    UpdateConfig { settings: RuntimeSettings },
+    StartTraining,
+    StopTraining,
+    /// 收集诊断包并随执行结果返回
+    CollectDiagnostics,
+}
+
+/// 控制器下发的管理指令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FleetCommand {
+    pub command_id: String,
+    /// 每个控制器为每个节点单调递增的序号，用于防重放
+    pub seq: u64,
+    /// 目标节点 ID，`*` 表示所有节点
+    pub target: String,
+    /// 签发时间（Unix 秒）
+    pub issued_at: i64,
+    /// 过期时间（Unix 秒）
+    pub expires_at: i64,
+    /// 签发的控制器
+    pub controller_id: String,
+    pub action: FleetAction,
+}
+
+impl FleetCommand {
+    /// 规范化字节（签名输入）
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// 使用控制器身份签名
+    pub fn sign(self, identity: &NodeIdentity) -> SignedFleetCommand {
+        let signature = identity.sign(&self.canonical_bytes());
+        SignedFleetCommand {
+            command: self,
+            signature: Some(signature),
+        }
+    }
+}
+
+/// 带签名的管理指令（网络上传输的形式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedFleetCommand {
+    pub command: FleetCommand,
+    /// 控制器签名；未签名的指令会被拒绝
+    #[serde(default)]
+    pub signature: Option<SolSignature>,
+}
+
+/// 指令拒绝原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CommandRejection {
+    #[error("管理指令未签名")]
+    Unsigned,
+
+    #[error("签名者 {signer} 不是受信任的控制器")]
+    UntrustedController { signer: String },
+
+    #[error("指令签名无效")]
+    BadSignature,
+
+    #[error("指令目标 {target} 不是本节点")]
+    WrongTarget { target: String },
+
+    #[error("指令已过期: expires_at={expires_at}")]
+    Expired { expires_at: i64 },
+
+    #[error("指令序号 {seq} 不大于已执行的序号 {last_seq}，疑似重放")]
+    Replayed { seq: u64, last_seq: u64 },
+
+    #[error("无法保存指令序号: {0}")]
+    StateUnavailable(String),
+}
+
+/// 管理指令验证器
+pub struct CommandVerifier {
+    node_id: String,
+    trusted: HashSet<String>,
+    state_path: PathBuf,
+    /// 每个控制器已执行的最大序号
+    last_seq: Mutex<HashMap<String, u64>>,
+}
+
+impl CommandVerifier {
+    /// 打开验证器，从 `state_path` 恢复各控制器已执行的序号
+    pub fn open(
+        node_id: impl Into<String>,
+        trusted: impl IntoIterator<Item = String>,
+        state_path: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let state_path = state_path.as_ref().to_path_buf();
+        let last_seq = if state_path.exists() {
+            serde_json::from_slice(&std::fs::read(&state_path)?)?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            node_id: node_id.into(),
+            trusted: trusted.into_iter().collect(),
+            state_path,
+            last_seq: Mutex::new(last_seq),
+        })
+    }
+
+    /// 验证指令，通过后记录该控制器的序号，同一序号之后不再接受
+    ///
+    /// 序号写入状态文件后才放行指令；写入失败时拒绝，避免重启后被重放。
+    pub fn verify(&self, signed: &SignedFleetCommand, now: i64) -> Result<FleetCommand, CommandRejection> {
+        let command = &signed.command;
+        let signature = signed.signature.as_ref().ok_or(CommandRejection::Unsigned)?;
+        if !self.trusted.contains(&signature.pubkey) || command.controller_id != signature.pubkey {
+            return Err(CommandRejection::UntrustedController {
+                signer: signature.pubkey.clone(),
+            });
+        }
+        if !verify_signature(&command.canonical_bytes(), signature) {
+            return Err(CommandRejection::BadSignature);
+        }
+        if command.target != "*" && command.target != self.node_id {
+            return Err(CommandRejection::WrongTarget {
+                target: command.target.clone(),
+            });
+        }
+        if command.expires_at <= now {
+            return Err(CommandRejection::Expired {
+                expires_at: command.expires_at,
+            });
+        }
+
+        let mut last_seq = self.last_seq.lock();
+        if let Some(&last) = last_seq.get(&command.controller_id) {
+            if command.seq <= last {
+                return Err(CommandRejection::Replayed {
+                    seq: command.seq,
+                    last_seq: last,
+                });
+            }
+        }
+        let mut updated = last_seq.clone();
+        updated.insert(command.controller_id.clone(), command.seq);
+        self.persist(&updated).map_err(|e| CommandRejection::StateUnavailable(e.to_string()))?;
+        *last_seq = updated;
+        Ok(command.clone())
+    }
+
+    fn persist(&self, last_seq: &HashMap<String, u64>) -> Result<()> {
+        if let Some(dir) = self.state_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.state_path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(last_seq)?)?;
+        std::fs::rename(&tmp, &self.state_path)?;
+        Ok(())
+    }
+}
+
+/// 节点清单（每次心跳上报）
+#[derive(Debug, Clone, Serialize)]
+pub struct Inventory {
+    pub node_id: String,
+    pub labels: BTreeMap<String, String>,
+    pub build: BuildInfo,
+    pub device: DeviceCapabilities,
+    pub resource_caps: ResourceCaps,
+    pub tick: u64,
+    pub peers: usize,
+    pub uptime_secs: u64,
+    /// 上报时间（Unix 秒）
+    pub reported_at: i64,
+}
+
+/// 诊断包
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    pub inventory: Inventory,
+    pub settings: RuntimeSettings,
+    pub stats: Option<TrainingStats>,
+    pub status: NodeStatus,
+}
+
+/// 指令执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandResult {
+    pub command_id: String,
+    pub ok: bool,
+    pub message: String,
+    pub diagnostics: Option<DiagnosticsBundle>,
+}
+
+/// 节点签名的上报内容，控制器据此确认上报来源
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedReport<T> {
+    pub report: T,
+    pub signature: SolSignature,
+}
+
+impl<T: Serialize> SignedReport<T> {
+    pub fn new(report: T, identity: &NodeIdentity) -> Result<Self> {
+        let signature = identity.sign_record(&report)?;
+        Ok(Self { report, signature })
+    }
+}
+
+/// 心跳响应
+#[derive(Debug, Default, Deserialize)]
+struct HeartbeatResponse {
+    #[serde(default)]
+    commands: Vec<SignedFleetCommand>,
+}
+
+/// 代理需要读取或控制的节点共享状态（节点运行时持有自身，代理无法直接访问）
+#[derive(Clone)]
+pub struct FleetHandles {
+    pub identity: Arc<NodeIdentity>,
+    pub config: Arc<ConfigWatcher>,
+    pub power: Arc<PowerPolicy>,
+    pub status: Arc<NodeStatusBoard>,
+    pub stats: Arc<std::sync::Mutex<TrainingStatsManager>>,
+    pub devices: DeviceManager,
+}
+
+/// 集群托管代理
+pub struct FleetAgent {
+    config: FleetConfig,
+    handles: FleetHandles,
+    verifier: CommandVerifier,
+    client: reqwest::Client,
+    node_id: String,
+    started: Instant,
+}
+
+impl FleetAgent {
+    pub fn new(config: FleetConfig, handles: FleetHandles) -> Result<Self> {
+        let node_id = handles.identity.node_id();
+        let verifier = CommandVerifier::open(node_id.clone(), config.trusted_controllers.clone(), config.state_path())?;
+        Ok(Self {
+            verifier,
+            config,
+            handles,
+            client: reqwest::Client::new(),
+            node_id,
+            started: Instant::now(),
+        })
+    }
+
+    /// 在后台运行代理
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    /// 心跳循环，失败时指数退避
+    pub async fn run(self) {
+        let interval = Duration::from_secs(self.config.poll_interval_secs.max(1));
+        let mut delay = interval;
+        loop {
+            match self.heartbeat().await {
+                Ok(()) => delay = interval,
+                Err(e) => {
+                    log::warn!("[集群] 心跳失败: {}", e);
+                    delay = (delay * 2).min(MAX_BACKOFF);
+                }
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!(
+            "{}/v1/fleet/nodes/{}/{}",
+            self.config.controller_url.trim_end_matches('/'),
+            self.node_id,
+            path
+        )
+    }
+
+    async fn heartbeat(&self) -> Result<()> {
+        let report = SignedReport::new(self.inventory(), &self.handles.identity)?;
+        let response = self
+            .client
+            .post(self.endpoint("heartbeat"))
+            .json(&report)
+            .send()
+            .await?
+            .error_for_status()?;
+        let HeartbeatResponse { commands } = response.json().await?;
+
+        for signed in commands {
+            let command_id = signed.command.command_id.clone();
+            let result = match self.verifier.verify(&signed, chrono::Utc::now().timestamp()) {
+                Ok(command) => self.execute(command),
+                Err(rejection) => {
+                    log::warn!("[集群] 拒绝指令 {}: {}", command_id, rejection);
+                    CommandResult {
+                        command_id: command_id.clone(),
+                        ok: false,
+                        message: rejection.to_string(),
+                        diagnostics: None,
+                    }
+                }
+            };
+            let report = SignedReport::new(result, &self.handles.identity)?;
+            self.client
+                .post(self.endpoint(&format!("commands/{}/result", command_id)))
+                .json(&report)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        Ok(())
+    }
+
+    fn execute(&self, command: FleetCommand) -> CommandResult {
+        log::info!("[集群] 执行指令 {} ({:?})", command.command_id, command.action);
+        let mut diagnostics = None;
+        let outcome = match command.action {
+            FleetAction::UpdateConfig { settings } => self.handles.config.apply(settings).map(|change| match change {
+                Some(_) => "配置已更新".to_string(),
+                None => "配置未变化".to_string(),
+            }),
+            FleetAction::StartTraining => {
+                self.handles.power.set_paused(false);
+                Ok("训练已恢复".to_string())
+            }
+            FleetAction::StopTraining => {
+                self.handles.power.set_paused(true);
+                Ok("训练已暂停".to_string())
+            }
+            FleetAction::CollectDiagnostics => {
+                diagnostics = Some(self.diagnostics());
+                Ok("诊断包已生成".to_string())
+            }
+        };
+        let (ok, message) = match outcome {
+            Ok(message) => (true, message),
+            Err(e) => (false, e.to_string()),
+        };
+        CommandResult {
+            command_id: command.command_id,
+            ok,
+            message,
+            diagnostics,
+        }
+    }
+
+    fn inventory(&self) -> Inventory {
+        let status = self.handles.status.snapshot();
+        Inventory {
+            node_id: self.node_id.clone(),
+            labels: self.config.labels.clone(),
+            build: BuildInfo::current(),
+            device: self.handles.devices.get(),
+            resource_caps: self.handles.power.caps(),
+            tick: status.tick,
+            peers: status.peers.len(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            reported_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn diagnostics(&self) -> DiagnosticsBundle {
+        DiagnosticsBundle {
+            inventory: self.inventory(),
+            settings: self.handles.config.settings(),
+            stats: self.handles.stats.lock().ok().map(|stats| stats.get_stats().clone()),
+            status: self.handles.status.snapshot(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::FileKeystore;
+
+    fn identity(name: &str) -> NodeIdentity {
+        let path = std::env::temp_dir().join(format!("williw-fleet-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        NodeIdentity::with_keystore(Arc::new(FileKeystore::new(path))).unwrap()
+    }
+
+    fn state_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("williw-fleet-state-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn command(seq: u64, target: &str, controller_id: String) -> FleetCommand {
+        FleetCommand {
+            command_id: format!("cmd-{}", seq),
+            seq,
+            target: target.to_string(),
+            issued_at: 100,
+            expires_at: 200,
+            controller_id,
+            action: FleetAction::StopTraining,
+        }
+    }
+
+    #[test]
+    fn test_rejects_untrusted_stale_and_replayed_commands() {
+        let controller = identity("controller");
+        let attacker = identity("attacker");
+        let verifier = CommandVerifier::open("node-a", [controller.node_id()], state_path("rejects")).unwrap();
+
+        let unsigned = SignedFleetCommand {
+            command: command(1, "node-a", controller.node_id()),
+            signature: None,
+        };
+        assert_eq!(verifier.verify(&unsigned, 150).unwrap_err(), CommandRejection::Unsigned);
+        let spoofed = command(1, "node-a", attacker.node_id()).sign(&attacker);
+        assert!(matches!(
+            verifier.verify(&spoofed, 150),
+            Err(CommandRejection::UntrustedController { .. })
+        ));
+        let mut tampered = command(1, "node-a", controller.node_id()).sign(&controller);
+        tampered.command.action = FleetAction::CollectDiagnostics;
+        assert_eq!(verifier.verify(&tampered, 150).unwrap_err(), CommandRejection::BadSignature);
+
+        let other = command(1, "node-b", controller.node_id()).sign(&controller);
+        assert!(matches!(verifier.verify(&other, 150), Err(CommandRejection::WrongTarget { .. })));
+        let signed = command(1, "*", controller.node_id()).sign(&controller);
+        assert!(matches!(verifier.verify(&signed, 250), Err(CommandRejection::Expired { .. })));
+
+        assert!(verifier.verify(&signed, 150).is_ok());
+        assert!(matches!(verifier.verify(&signed, 150), Err(CommandRejection::Replayed { .. })));
+        let next = command(2, "node-a", controller.node_id()).sign(&controller);
+        assert_eq!(verifier.verify(&next, 150).unwrap().seq, 2);
+    }
+
+    #[test]
+    fn test_replay_rejected_after_restart() {
+        let first = identity("restart-first");
+        let second = identity("restart-second");
+        let trusted = [first.node_id(), second.node_id()];
+        let path = state_path("restart");
+
+        let verifier = CommandVerifier::open("node-a", trusted.clone(), &path).unwrap();
+        let signed = command(5, "node-a", first.node_id()).sign(&first);
+        assert!(verifier.verify(&signed, 150).is_ok());
+        drop(verifier);
+
+        let restarted = CommandVerifier::open("node-a", trusted, &path).unwrap();
+        assert_eq!(
+            restarted.verify(&signed, 150).unwrap_err(),
+            CommandRejection::Replayed { seq: 5, last_seq: 5 }
+        );
+        // 序号按控制器分别记录，另一个控制器从自己的序号开始
+        let other = command(1, "node-a", second.node_id()).sign(&second);
+        assert_eq!(restarted.verify(&other, 150).unwrap().seq, 1);
+        let next = command(6, "node-a", first.node_id()).sign(&first);
+        assert_eq!(restarted.verify(&next, 150).unwrap().seq, 6);
+    }
+}
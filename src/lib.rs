@@ -17,6 +17,18 @@ pub mod consensus;
 #[cfg(feature = "workers")]
 pub mod workers;
 
+// 多节点端到端测试套件
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+
+// 脚本化故障注入
+#[cfg(feature = "chaos")]
+pub mod chaos;
+
+// WASM 任务插件
+#[cfg(feature = "plugins")]
+pub mod plugins;
+
 // Android JNI 集成
 #[cfg(feature = "android")]
 pub mod android;
@@ -30,11 +42,52 @@ pub mod training;
 // 拓扑模块
 pub mod topology;
 
+// 调度优化算法
+pub mod algorithms;
+
 // 统计模块
 pub mod stats;
 
 // 配置模块
 pub mod config;
+pub mod config_watch;
+
+// 实验性功能框架
+pub mod experiments;
+
+// 协调者签名的任务清单
+pub mod task_manifest;
+
+// 数据集完整性与来源清单
+pub mod dataset_manifest;
+
+// 构建元数据与集群视图
+pub mod build_info;
+pub mod cluster;
+
+// 已完成轮次产物的冷存储归档
+pub mod archive;
+
+// 重任务的时间窗口与网络策略
+pub mod work_schedule;
+
+// 内容寻址的本地模型缓存
+pub mod model_cache;
+
+// 磁盘空间预检与缓存配额
+pub mod storage;
+
+// 节点运维管理接口（令牌认证 + 角色权限）
+pub mod admin;
+
+// 集群控制器托管代理
+pub mod fleet;
+
+// 算力任务市场客户端
+pub mod marketplace;
+
+// 并行执行开关（浏览器中需先初始化线程池）
+pub mod parallel;
 
 // 通讯模块 - 使用 iroh
 pub mod comms;
@@ -49,6 +102,24 @@ pub mod zk;
 // 网络模块（包含FFI接口）
 pub mod network;
 
+// 平台原生绑定（iOS C 接口、UniFFI 移动端绑定、Node.js 插件）
+#[cfg(any(feature = "ffi", feature = "uniffi", feature = "nodejs"))]
+pub mod ffi;
+
+#[cfg(feature = "uniffi")]
+pub use ffi::mobile::*;
+
+// UniFFI 脚手架需要在 crate 根展开，以便找到 udl 中引用的类型
+#[cfg(feature = "uniffi")]
+uniffi::include_scaffolding!("williw");
+
+// 浏览器端绑定（WASM）
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// QUIC 隐私覆盖层（洋葱路由）
+pub mod quic;
+
 // 重新导出常用类型
 pub use device::{DeviceConfig, DeviceCapabilities, DeviceManager};
 pub use consensus::{ConsensusConfig, ConsensusEngine};
@@ -107,6 +178,7 @@ impl WilliwApp {
             config: self.config.clone(),
             device_capabilities: self.device_manager.get(), // 使用正确的 get 方法
             network_connected: self.network.is_some(),
+            node_mode: self.config.node_mode,
         }
     }
 }
@@ -117,4 +189,6 @@ pub struct AppStatus {
     pub config: config::AppConfig,
     pub device_capabilities: DeviceCapabilities,
     pub network_connected: bool,
+    /// 运行模式，观察模式的节点不参与训练和任务
+    pub node_mode: config::NodeMode,
 }
\ No newline at end of file
@@ -1,45 +1,47 @@
+mod admin;
+mod archive;
 mod args;
+mod build_info;
+#[cfg(feature = "chaos")]
+mod chaos;
+mod cli;
+mod cluster;
 mod comms;
 mod config;
+mod config_watch;
 mod consensus;
 mod crypto;
+mod dataset_manifest;
 mod device;
-#[cfg(feature = "ffi")]
-mod ffi;
+mod experiments;
+mod fleet;
+mod marketplace;
+mod model_cache;
 mod node;
+mod parallel;
+#[cfg(feature = "plugins")]
+mod plugins;
 mod stats;
+mod storage;
+mod task_manifest;
+// 单元测试共用的 testkit 夹具（内存身份存储等）
+#[cfg(test)]
+mod testkit {
+    pub mod node;
+}
 mod topology;
 mod training;
+#[cfg(feature = "tui")]
+mod tui;
 mod types;
+mod work_schedule;
 
-use crate::args::{get_stats_output, parse_args_and_build_config};
-use crate::node::Node;
+use crate::args::Cli;
 use anyhow::Result;
-use std::sync::Arc;
-use tokio::time::Duration;
-
-
+use clap::Parser;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let config = parse_args_and_build_config();
-    let node = Node::new(config).await?;
-
-    // 如果指定了统计输出文件，设置定期导出
-    if let Some(output_path) = get_stats_output() {
-        let stats_path = std::path::PathBuf::from(&output_path);
-        let stats_manager: Arc<std::sync::Mutex<crate::stats::TrainingStatsManager>> = Arc::clone(&node.stats);
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(30));
-            loop {
-                interval.tick().await;
-                if let Err(e) = stats_manager.lock().unwrap().export_json_to_file(&stats_path) {
-                    eprintln!("导出统计数据失败: {:?}", e);
-                }
-            }
-        });
-    }
-
-    node.run().await
+    let cli = Cli::parse();
+    cli::execute(cli.into_command()).await
 }
-
@@ -0,0 +1,751 @@
+//! 算力任务市场客户端
+//!
+//! 节点从 Workers 协调者（`/api/market/*`）浏览公开的计算任务，按本机校准算力分和电价估算成本后自动出价；
+//! 中标后确认接受、执行任务并提交签名的执行证明。每个任务的进度由 [`JobBook`] 状态机驱动并持久化在本地，
+//! 节点重启后从中断处继续。
+//!
+//! | 接口 | 说明 |
+//! | --- | --- |
+//! | `GET jobs` | 开放竞价的任务 |
+//! | `POST jobs/{id}/bids` | 出价（节点签名） |
+//! | `GET nodes/{node}/awards` | 本节点的中标通知（协调者签名） |
+//! | `POST jobs/{id}/accept` | 确认接受（节点签名） |
+//! | `POST jobs/{id}/output` | 上传执行结果 |
+//! | `POST jobs/{id}/proof` | 提交执行证明（节点签名） |
+//! | `POST jobs/{id}/abandon` | 放弃已接受的任务，协调者可重新授标（节点签名） |
+
+mod state;
+
+pub use state::{JobBook, JobPhase, JobRecord, TransitionError};
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+use crate::device::{DeviceManager, DeviceScore};
+use crate::fleet::SignedReport;
+use crate::stats::EnergyModel;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 轮询失败后的最长重试间隔
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+/// 竞价截止后等待授标的时间，超过仍未中标视为落标
+const AWARD_WAIT_SECS: i64 = 600;
+
+/// 任务市场配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketplaceConfig {
+    pub enabled: bool,
+    /// 协调者地址，例如 `https://williw-edge.example.workers.dev`
+    pub coordinator_url: String,
+    /// 协调者签发中标通知的公钥（base58）
+    pub coordinator_key: String,
+    /// 轮询间隔（秒）
+    pub poll_interval_secs: u64,
+    /// 关闭时只处理已有任务，不再出价
+    pub auto_bid: bool,
+    /// 电价，单位与任务报价相同（lamports/kWh）
+    pub electricity_price_per_kwh: f64,
+    /// 在电费成本上的加价比例
+    pub margin: f64,
+    /// 单个任务的最低报价（lamports）
+    pub min_price: u64,
+    /// 同时进行的任务上限（包括等待授标的出价）
+    pub max_concurrent_jobs: usize,
+    /// 执行任务的插件目录（需启用 `plugins` 特性）
+    pub plugin_dir: PathBuf,
+    /// 任务状态文件
+    pub state_path: PathBuf,
+    /// 执行结果目录
+    pub output_dir: PathBuf,
+}
+
+impl Default for MarketplaceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            coordinator_url: String::new(),
+            coordinator_key: String::new(),
+            poll_interval_secs: 30,
+            auto_bid: true,
+            electricity_price_per_kwh: 0.0,
+            margin: 0.2,
+            min_price: 0,
+            max_concurrent_jobs: 1,
+            plugin_dir: PathBuf::from("plugins"),
+            state_path: PathBuf::from("marketplace_jobs.json"),
+            output_dir: PathBuf::from("marketplace_outputs"),
+        }
+    }
+}
+
+/// 协调者公开的计算任务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketJob {
+    pub job_id: String,
+    /// 任务类型，由同名插件执行
+    pub task_type: String,
+    /// 发布任务的账户
+    pub requester: String,
+    /// 输入数据地址
+    pub input_url: String,
+    /// 输入数据的 blake3 哈希（十六进制）
+    pub input_hash: String,
+    /// 预估计算量（GFLOP）
+    pub work_gflop: f64,
+    /// 愿意支付的最高价（lamports）
+    pub max_price: u64,
+    /// 要求的最低校准算力分
+    #[serde(default)]
+    pub min_score: f64,
+    /// 竞价截止时间（Unix 秒）
+    pub bid_closes_at: i64,
+    /// 必须提交证明的期限（Unix 秒）
+    pub deadline: i64,
+}
+
+impl MarketJob {
+    /// 任务 ID 用于文件名和 URL，只接受字母、数字和 -_.
+    pub fn has_valid_id(&self) -> bool {
+        !self.job_id.is_empty()
+            && self.job_id.len() <= 128
+            && self.job_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && !self.job_id.starts_with('.')
+    }
+}
+
+/// 出价
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bid {
+    pub job_id: String,
+    pub node_id: String,
+    /// 报价（lamports）
+    pub price: u64,
+    /// 预计执行时长（秒）
+    pub estimated_secs: u64,
+    /// 出价时的校准算力分
+    pub device_score: f64,
+    /// 出价时间（Unix 秒）
+    pub placed_at: i64,
+}
+
+/// 中标通知
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Award {
+    pub job_id: String,
+    pub node_id: String,
+    /// 成交价（lamports），不低于出价
+    pub price: u64,
+    pub awarded_at: i64,
+    /// 必须在此之前确认接受（Unix 秒）
+    pub accept_by: i64,
+}
+
+/// 协调者签名的中标通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedAward {
+    pub award: Award,
+    pub signature: SolSignature,
+}
+
+/// 拒绝中标通知的原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AwardRejection {
+    #[error("中标通知不是由配置的协调者签发")]
+    UntrustedCoordinator,
+
+    #[error("中标通知签名无效")]
+    BadSignature,
+
+    #[error("中标通知发给了其他节点 {0}")]
+    WrongNode(String),
+
+    #[error("成交价 {awarded} 低于出价 {bid}")]
+    PriceBelowBid { awarded: u64, bid: u64 },
+}
+
+impl SignedAward {
+    /// 验证签名来自协调者且通知发给本节点
+    pub fn verify(&self, coordinator_key: &str, node_id: &str) -> Result<&Award, AwardRejection> {
+        if self.signature.pubkey != coordinator_key {
+            return Err(AwardRejection::UntrustedCoordinator);
+        }
+        let payload = serde_json::to_vec(&self.award).map_err(|_| AwardRejection::BadSignature)?;
+        if !verify_signature(&payload, &self.signature) {
+            return Err(AwardRejection::BadSignature);
+        }
+        if self.award.node_id != node_id {
+            return Err(AwardRejection::WrongNode(self.award.node_id.clone()));
+        }
+        Ok(&self.award)
+    }
+}
+
+/// 确认接受
+#[derive(Debug, Clone, Serialize)]
+pub struct Acceptance {
+    pub job_id: String,
+    pub node_id: String,
+    pub price: u64,
+    pub accepted_at: i64,
+}
+
+/// 放弃已接受的任务
+#[derive(Debug, Clone, Serialize)]
+pub struct Abandonment {
+    pub job_id: String,
+    pub node_id: String,
+    pub reason: String,
+}
+
+/// 执行证明：把输入、输出和成交价绑定到节点身份
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionProof {
+    pub job_id: String,
+    pub node_id: String,
+    pub task_type: String,
+    pub input_hash: String,
+    /// 执行结果的 blake3 哈希（十六进制）
+    pub output_hash: String,
+    pub output_bytes: u64,
+    pub price: u64,
+    pub started_at: i64,
+    pub finished_at: i64,
+}
+
+/// 节点签名的执行证明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedProof {
+    pub proof: ExecutionProof,
+    pub signature: SolSignature,
+}
+
+impl SignedProof {
+    pub fn new(proof: ExecutionProof, identity: &NodeIdentity) -> Result<Self> {
+        let signature = identity.sign_record(&proof)?;
+        Ok(Self { proof, signature })
+    }
+
+    /// 验证证明由执行节点签发
+    pub fn verify(&self) -> bool {
+        self.signature.pubkey == self.proof.node_id
+            && serde_json::to_vec(&self.proof)
+                .map(|payload| verify_signature(&payload, &self.signature))
+                .unwrap_or(false)
+    }
+}
+
+/// 估价结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    pub price: u64,
+    pub estimated_secs: u64,
+    pub energy_wh: f64,
+}
+
+/// 不出价的原因
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BidSkip {
+    #[error("竞价已截止")]
+    BiddingClosed,
+
+    #[error("算力分 {actual:.3} 低于要求的 {required:.3}")]
+    ScoreTooLow { required: f64, actual: f64 },
+
+    #[error("预计用时 {estimated_secs}s，距期限只剩 {remaining_secs}s")]
+    TooSlow { estimated_secs: u64, remaining_secs: i64 },
+
+    #[error("报价 {price} 超出任务最高价 {max_price}")]
+    Unprofitable { price: u64, max_price: u64 },
+}
+
+/// 按算力和电价估价
+#[derive(Debug, Clone)]
+pub struct BidStrategy {
+    energy: EnergyModel,
+    electricity_price_per_kwh: f64,
+    margin: f64,
+    min_price: u64,
+}
+
+impl BidStrategy {
+    pub fn new(config: &MarketplaceConfig, energy: EnergyModel) -> Self {
+        Self {
+            energy,
+            electricity_price_per_kwh: config.electricity_price_per_kwh.max(0.0),
+            margin: config.margin.max(0.0),
+            min_price: config.min_price,
+        }
+    }
+
+    /// 估算用时和电费，报价为电费加价后与最低报价取较大者
+    pub fn quote(&self, job: &MarketJob, score: &DeviceScore, now: i64) -> Result<Quote, BidSkip> {
+        if now >= job.bid_closes_at {
+            return Err(BidSkip::BiddingClosed);
+        }
+        if score.score < job.min_score {
+            return Err(BidSkip::ScoreTooLow {
+                required: job.min_score,
+                actual: score.score,
+            });
+        }
+
+        let gflops = score.split_capacity().max(1e-3);
+        let estimated_secs = (job.work_gflop.max(0.0) / gflops).ceil() as u64;
+        let remaining_secs = job.deadline - now;
+        if estimated_secs as i64 >= remaining_secs {
+            return Err(BidSkip::TooSlow {
+                estimated_secs,
+                remaining_secs,
+            });
+        }
+
+        // GPU 算力更高时任务跑在 GPU 上，CPU 只负责调度
+        let on_gpu = score.result.gpu_gflops.is_some_and(|gpu| gpu > score.result.matmul_gflops);
+        let (cpu, gpu) = if on_gpu { (0.25, 1.0) } else { (1.0, 0.0) };
+        let energy_wh = self.energy.power_watts(cpu, gpu) * estimated_secs as f64 / 3600.0;
+        let cost = energy_wh / 1000.0 * self.electricity_price_per_kwh;
+        let price = ((cost * (1.0 + self.margin)).ceil() as u64).max(self.min_price);
+        if price > job.max_price {
+            return Err(BidSkip::Unprofitable {
+                price,
+                max_price: job.max_price,
+            });
+        }
+        Ok(Quote {
+            price,
+            estimated_secs,
+            energy_wh,
+        })
+    }
+}
+
+/// 执行市场任务的后端
+pub trait JobExecutor: Send + Sync {
+    fn supports(&self, task_type: &str) -> bool;
+
+    /// 同步执行，在阻塞线程池中调用
+    fn execute(&self, job: &MarketJob, input: Vec<u8>) -> Result<Vec<u8>>;
+}
+
+#[cfg(feature = "plugins")]
+impl JobExecutor for crate::plugins::PluginRegistry {
+    fn supports(&self, task_type: &str) -> bool {
+        self.get(task_type).is_some()
+    }
+
+    fn execute(&self, job: &MarketJob, input: Vec<u8>) -> Result<Vec<u8>> {
+        let plugin = self
+            .get(&job.task_type)
+            .ok_or_else(|| anyhow!("没有处理任务类型 {} 的插件", job.task_type))?;
+        let job_id = job.job_id.clone();
+        let run = plugin.run(input, move |progress| {
+            log::debug!("[市场] 任务 {} 进度 {:.0}%", job_id, progress * 100.0)
+        })?;
+        Ok(run.output)
+    }
+}
+
+/// 任务市场代理
+pub struct MarketplaceAgent {
+    config: MarketplaceConfig,
+    identity: Arc<NodeIdentity>,
+    devices: DeviceManager,
+    strategy: BidStrategy,
+    executor: Arc<dyn JobExecutor>,
+    book: JobBook,
+    client: reqwest::Client,
+    node_id: String,
+}
+
+impl MarketplaceAgent {
+    pub fn new(
+        config: MarketplaceConfig,
+        identity: Arc<NodeIdentity>,
+        devices: DeviceManager,
+        energy: EnergyModel,
+        executor: Arc<dyn JobExecutor>,
+    ) -> Result<Self> {
+        std::fs::create_dir_all(&config.output_dir)?;
+        Ok(Self {
+            book: JobBook::open(&config.state_path)?,
+            strategy: BidStrategy::new(&config, energy),
+            node_id: identity.node_id(),
+            config,
+            identity,
+            devices,
+            executor,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    pub fn book(&self) -> &JobBook {
+        &self.book
+    }
+
+    /// 在后台运行代理
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    /// 轮询循环，失败时指数退避
+    pub async fn run(self) {
+        let interval = Duration::from_secs(self.config.poll_interval_secs.max(1));
+        let mut delay = interval;
+        loop {
+            match self.poll().await {
+                Ok(()) => delay = interval,
+                Err(e) => {
+                    log::warn!("[市场] 轮询失败: {}", e);
+                    delay = (delay * 2).min(MAX_BACKOFF);
+                }
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/api/market/{}", self.config.coordinator_url.trim_end_matches('/'), path)
+    }
+
+    /// 推进一轮：领取中标、清理过期、出价，再执行和提交
+    async fn poll(&self) -> Result<()> {
+        let now = Utc::now().timestamp();
+        self.book.prune(now)?;
+        self.collect_awards(now).await?;
+        self.expire(now)?;
+        if self.config.auto_bid {
+            self.place_bids(now).await?;
+        }
+        for record in self.book.in_phase(JobPhase::Accepted) {
+            let job_id = record.job.job_id.clone();
+            if let Err(e) = self.execute(record).await {
+                self.book.fail(&job_id, format!("执行失败: {:#}", e))?;
+                self.abandon(&job_id, &e.to_string()).await;
+            }
+        }
+        for record in self.book.in_phase(JobPhase::Executed) {
+            self.submit(record).await?;
+        }
+        Ok(())
+    }
+
+    async fn collect_awards(&self, now: i64) -> Result<()> {
+        let awards: Vec<SignedAward> = self
+            .client
+            .get(self.endpoint(&format!("nodes/{}/awards", self.node_id)))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        for signed in awards {
+            let Some(record) = self.book.get(&signed.award.job_id).filter(|r| r.phase == JobPhase::Bid) else {
+                continue;
+            };
+            let job_id = record.job.job_id.clone();
+            let award = match signed.verify(&self.config.coordinator_key, &self.node_id).and_then(|award| {
+                if award.price < record.bid.price {
+                    return Err(AwardRejection::PriceBelowBid {
+                        awarded: award.price,
+                        bid: record.bid.price,
+                    });
+                }
+                Ok(award.clone())
+            }) {
+                Ok(award) => award,
+                Err(rejection) => {
+                    log::warn!("[市场] 忽略任务 {} 的中标通知: {}", job_id, rejection);
+                    continue;
+                }
+            };
+            log::info!("[市场] 中标任务 {}，成交价 {}", job_id, award.price);
+            self.book
+                .transition(&job_id, JobPhase::Awarded, |r| r.award = Some(award.clone()))?;
+
+            if now > award.accept_by {
+                self.book.fail(&job_id, "错过接受期限")?;
+                continue;
+            }
+            let acceptance = Acceptance {
+                job_id: job_id.clone(),
+                node_id: self.node_id.clone(),
+                price: award.price,
+                accepted_at: now,
+            };
+            self.client
+                .post(self.endpoint(&format!("jobs/{}/accept", job_id)))
+                .json(&SignedReport::new(acceptance, &self.identity)?)
+                .send()
+                .await?
+                .error_for_status()?;
+            self.book.transition(&job_id, JobPhase::Accepted, |_| {})?;
+        }
+        Ok(())
+    }
+
+    /// 竞价截止仍未中标的记为落标，错过接受期限或提交期限的记为失败
+    fn expire(&self, now: i64) -> Result<()> {
+        for record in self.book.records() {
+            let job = &record.job;
+            match record.phase {
+                JobPhase::Bid if now > job.bid_closes_at + AWARD_WAIT_SECS => self.book.transition(&job.job_id, JobPhase::Lost, |_| {})?,
+                JobPhase::Awarded if record.award.as_ref().is_some_and(|a| now > a.accept_by) => {
+                    self.book.fail(&job.job_id, "错过接受期限")?
+                }
+                JobPhase::Accepted | JobPhase::Executed if now > job.deadline => {
+                    self.book.fail(&job.job_id, "超过提交期限")?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn place_bids(&self, now: i64) -> Result<()> {
+        let Some(score) = self.devices.device_score() else {
+            log::debug!("[市场] 没有校准算力分，跳过出价（运行 benchmark --output 后用 --device-score 启动）");
+            return Ok(());
+        };
+        let jobs: Vec<MarketJob> = self
+            .client
+            .get(self.endpoint("jobs"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        for job in jobs {
+            if self.book.active() >= self.config.max_concurrent_jobs {
+                break;
+            }
+            if !job.has_valid_id() || self.book.contains(&job.job_id) || !self.executor.supports(&job.task_type) {
+                continue;
+            }
+            let quote = match self.strategy.quote(&job, &score, now) {
+                Ok(quote) => quote,
+                Err(skip) => {
+                    log::debug!("[市场] 不对任务 {} 出价: {}", job.job_id, skip);
+                    continue;
+                }
+            };
+            let bid = Bid {
+                job_id: job.job_id.clone(),
+                node_id: self.node_id.clone(),
+                price: quote.price,
+                estimated_secs: quote.estimated_secs,
+                device_score: score.score,
+                placed_at: now,
+            };
+            self.client
+                .post(self.endpoint(&format!("jobs/{}/bids", job.job_id)))
+                .json(&SignedReport::new(bid.clone(), &self.identity)?)
+                .send()
+                .await?
+                .error_for_status()?;
+            log::info!(
+                "[市场] 对任务 {} 出价 {}（预计 {}s，{:.2} Wh）",
+                job.job_id,
+                quote.price,
+                quote.estimated_secs,
+                quote.energy_wh
+            );
+            self.book.insert_bid(job, bid)?;
+        }
+        Ok(())
+    }
+
+    /// 下载并校验输入、执行任务、写出结果并生成签名证明
+    async fn execute(&self, record: JobRecord) -> Result<()> {
+        let job = record.job;
+        let price = record.award.as_ref().map(|a| a.price).unwrap_or(record.bid.price);
+        self.book.transition(&job.job_id, JobPhase::Executing, |_| {})?;
+        let started_at = Utc::now().timestamp();
+
+        let input = self
+            .client
+            .get(&job.input_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?
+            .to_vec();
+        let input_hash = blake3::hash(&input).to_hex().to_string();
+        if !input_hash.eq_ignore_ascii_case(&job.input_hash) {
+            return Err(anyhow!("输入哈希不匹配: 任务 {}, 实际 {}", job.input_hash, input_hash));
+        }
+
+        let executor = self.executor.clone();
+        let task = job.clone();
+        let output = tokio::task::spawn_blocking(move || executor.execute(&task, input)).await??;
+
+        let output_path = self.config.output_dir.join(format!("{}.out", job.job_id));
+        std::fs::write(&output_path, &output)?;
+        let proof = SignedProof::new(
+            ExecutionProof {
+                job_id: job.job_id.clone(),
+                node_id: self.node_id.clone(),
+                task_type: job.task_type.clone(),
+                input_hash,
+                output_hash: blake3::hash(&output).to_hex().to_string(),
+                output_bytes: output.len() as u64,
+                price,
+                started_at,
+                finished_at: Utc::now().timestamp(),
+            },
+            &self.identity,
+        )?;
+        log::info!("[市场] 任务 {} 执行完成，输出 {} 字节", job.job_id, output.len());
+        self.book.transition(&job.job_id, JobPhase::Executed, |r| {
+            r.output_path = Some(output_path);
+            r.proof = Some(proof);
+        })
+    }
+
+    /// 上传结果并提交证明，失败时留在 `Executed` 下轮重试
+    async fn submit(&self, record: JobRecord) -> Result<()> {
+        let job_id = record.job.job_id.clone();
+        let (Some(output_path), Some(proof)) = (&record.output_path, &record.proof) else {
+            return self.book.fail(&job_id, "执行记录缺少结果或证明");
+        };
+        let result: Result<()> = async {
+            self.client
+                .post(self.endpoint(&format!("jobs/{}/output", job_id)))
+                .header("Content-Type", "application/octet-stream")
+                .body(std::fs::read(output_path)?)
+                .send()
+                .await?
+                .error_for_status()?;
+            self.client
+                .post(self.endpoint(&format!("jobs/{}/proof", job_id)))
+                .json(proof)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                log::info!("[市场] 任务 {} 的执行证明已提交", job_id);
+                self.book.transition(&job_id, JobPhase::Submitted, |_| {})
+            }
+            Err(e) => {
+                let attempts = self.book.record_failure(&job_id, e.to_string())?;
+                log::warn!("[市场] 提交任务 {} 失败（第 {} 次）: {}", job_id, attempts, e);
+                Ok(())
+            }
+        }
+    }
+
+    /// 通知协调者放弃任务（尽力而为）
+    async fn abandon(&self, job_id: &str, reason: &str) {
+        let abandonment = Abandonment {
+            job_id: job_id.to_string(),
+            node_id: self.node_id.clone(),
+            reason: reason.to_string(),
+        };
+        let result = match SignedReport::new(abandonment, &self.identity) {
+            Ok(report) => self
+                .client
+                .post(self.endpoint(&format!("jobs/{}/abandon", job_id)))
+                .json(&report)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            log::warn!("[市场] 无法通知协调者放弃任务 {}: {}", job_id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::BenchmarkResult;
+    use crate::stats::PowerProfile;
+
+    fn score(matmul_gflops: f64, score: f64) -> DeviceScore {
+        DeviceScore {
+            node_id: "node-a".to_string(),
+            suite_version: 1,
+            measured_at: 0,
+            cpu_cores: 8,
+            max_memory_mb: 16384,
+            result: BenchmarkResult {
+                matmul_gflops,
+                memory_bandwidth_gbps: 20.0,
+                blake3_mb_per_sec: 1000.0,
+                ed25519_signs_per_sec: 10000.0,
+                gpu_gflops: None,
+                gpu_name: None,
+            },
+            score,
+        }
+    }
+
+    fn job(work_gflop: f64, max_price: u64) -> MarketJob {
+        MarketJob {
+            job_id: "job-1".to_string(),
+            task_type: "upper".to_string(),
+            requester: "requester".to_string(),
+            input_url: "https://example.com/input".to_string(),
+            input_hash: String::new(),
+            work_gflop,
+            max_price,
+            min_score: 0.5,
+            bid_closes_at: 1_000,
+            deadline: 10_000,
+        }
+    }
+
+    #[test]
+    fn test_quote_covers_electricity_and_skips_unsuitable_jobs() {
+        let config = MarketplaceConfig {
+            electricity_price_per_kwh: 1_000_000.0,
+            margin: 0.5,
+            ..Default::default()
+        };
+        let profile = PowerProfile {
+            cpu_idle_watts: 10.0,
+            cpu_max_watts: 110.0,
+            gpu_idle_watts: 0.0,
+            gpu_max_watts: 0.0,
+        };
+        let strategy = BidStrategy::new(&config, EnergyModel::new(profile));
+
+        // 3600 秒 × 100 瓦 = 100 Wh = 0.1 kWh，电费 100000，加价 50%
+        let quote = strategy.quote(&job(360_000.0, 200_000), &score(100.0, 1.0), 0).unwrap();
+        assert_eq!(quote.estimated_secs, 3600);
+        assert!((quote.energy_wh - 100.0).abs() < 1e-9);
+        assert_eq!(quote.price, 150_000);
+
+        assert!(matches!(
+            strategy.quote(&job(360_000.0, 100_000), &score(100.0, 1.0), 0),
+            Err(BidSkip::Unprofitable { price: 150_000, .. })
+        ));
+        assert!(matches!(
+            strategy.quote(&job(360_000.0, 200_000), &score(100.0, 0.2), 0),
+            Err(BidSkip::ScoreTooLow { .. })
+        ));
+        assert!(matches!(
+            strategy.quote(&job(360_000.0, 200_000), &score(10.0, 1.0), 0),
+            Err(BidSkip::TooSlow { estimated_secs: 36_000, .. })
+        ));
+        assert_eq!(
+            strategy.quote(&job(1.0, 200_000), &score(100.0, 1.0), 1_000),
+            Err(BidSkip::BiddingClosed)
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! 任务生命周期状态机
+//!
+//! ```text
+//! Bid ──> Awarded ──> Accepted ──> Executing ──> Executed ──> Submitted
+//!  │         │           │            │             │
+//!  └> Lost   └───────────┴────────────┴─────────────┴──> Failed
+//! ```
+//!
+//! 每次状态变化立即写入本地文件；重启时中断在 `Executing` 的任务退回 `Accepted` 重新执行，
+//! `Executed` 的任务保留证明，继续提交。
+
+use super::{Award, Bid, MarketJob, SignedProof};
+use anyhow::Result;
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 已结束的任务在本地保留的时长
+const HISTORY_RETENTION_SECS: i64 = 7 * 24 * 3600;
+
+/// 任务所处阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPhase {
+    /// 已出价，等待协调者授标
+    Bid,
+    /// 中标，尚未接受
+    Awarded,
+    /// 已向协调者确认接受
+    Accepted,
+    Executing,
+    /// 已执行并生成证明，等待提交
+    Executed,
+    /// 证明已提交
+    Submitted,
+    /// 未中标或竞价已关闭
+    Lost,
+    Failed,
+}
+
+impl JobPhase {
+    pub fn is_terminal(self) -> bool {
+        matches!(self, JobPhase::Submitted | JobPhase::Lost | JobPhase::Failed)
+    }
+
+    pub fn can_transition(self, to: JobPhase) -> bool {
+        use JobPhase::*;
+        match (self, to) {
+            (Bid, Awarded | Lost) => true,
+            (Awarded, Accepted) => true,
+            (Accepted, Executing) => true,
+            (Executing, Executed | Accepted) => true,
+            (Executed, Submitted) => true,
+            (from, Failed) => !from.is_terminal(),
+            _ => false,
+        }
+    }
+}
+
+/// 非法的状态变化
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TransitionError {
+    #[error("未知任务 {0}")]
+    UnknownJob(String),
+
+    #[error("任务 {job_id} 不能从 {from:?} 进入 {to:?}")]
+    Invalid { job_id: String, from: JobPhase, to: JobPhase },
+}
+
+/// 一个任务的本地记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub job: MarketJob,
+    pub bid: Bid,
+    pub phase: JobPhase,
+    #[serde(default)]
+    pub award: Option<Award>,
+    /// 执行结果文件
+    #[serde(default)]
+    pub output_path: Option<PathBuf>,
+    #[serde(default)]
+    pub proof: Option<SignedProof>,
+    /// 提交失败次数
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// 最近一次状态变化（Unix 秒）
+    pub updated_at: i64,
+}
+
+/// 持久化的任务簿
+pub struct JobBook {
+    path: PathBuf,
+    records: Mutex<BTreeMap<String, JobRecord>>,
+}
+
+impl JobBook {
+    /// 打开任务簿；上次中断在执行中的任务退回已接受
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut records: BTreeMap<String, JobRecord> = if path.exists() {
+            serde_json::from_slice(&std::fs::read(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+        let mut interrupted = 0;
+        for record in records.values_mut().filter(|r| r.phase == JobPhase::Executing) {
+            record.phase = JobPhase::Accepted;
+            interrupted += 1;
+        }
+        if interrupted > 0 {
+            log::info!("[市场] {} 个任务上次执行中断，将重新执行", interrupted);
+        }
+        let book = Self {
+            path,
+            records: Mutex::new(records),
+        };
+        book.persist(&book.records.lock())?;
+        Ok(book)
+    }
+
+    /// 记录新出价
+    pub fn insert_bid(&self, job: MarketJob, bid: Bid) -> Result<()> {
+        let mut records = self.records.lock();
+        records.insert(
+            job.job_id.clone(),
+            JobRecord {
+                job,
+                bid,
+                phase: JobPhase::Bid,
+                award: None,
+                output_path: None,
+                proof: None,
+                attempts: 0,
+                last_error: None,
+                updated_at: Utc::now().timestamp(),
+            },
+        );
+        self.persist(&records)
+    }
+
+    /// 推进状态，`update` 在同一次写入中修改记录的其他字段
+    pub fn transition(&self, job_id: &str, to: JobPhase, update: impl FnOnce(&mut JobRecord)) -> Result<()> {
+        let mut records = self.records.lock();
+        let record = records
+            .get_mut(job_id)
+            .ok_or_else(|| TransitionError::UnknownJob(job_id.to_string()))?;
+        if !record.phase.can_transition(to) {
+            return Err(TransitionError::Invalid {
+                job_id: job_id.to_string(),
+                from: record.phase,
+                to,
+            }
+            .into());
+        }
+        record.phase = to;
+        record.updated_at = Utc::now().timestamp();
+        update(record);
+        self.persist(&records)
+    }
+
+    /// 标记失败
+    pub fn fail(&self, job_id: &str, reason: impl Into<String>) -> Result<()> {
+        let reason = reason.into();
+        log::warn!("[市场] 任务 {} 失败: {}", job_id, reason);
+        self.transition(job_id, JobPhase::Failed, |record| record.last_error = Some(reason))
+    }
+
+    /// 记录一次提交失败，返回累计失败次数（不改变阶段）
+    pub fn record_failure(&self, job_id: &str, error: String) -> Result<u32> {
+        let mut records = self.records.lock();
+        let attempts = match records.get_mut(job_id) {
+            Some(record) => {
+                record.attempts += 1;
+                record.last_error = Some(error);
+                record.attempts
+            }
+            None => 0,
+        };
+        self.persist(&records)?;
+        Ok(attempts)
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<JobRecord> {
+        self.records.lock().get(job_id).cloned()
+    }
+
+    pub fn contains(&self, job_id: &str) -> bool {
+        self.records.lock().contains_key(job_id)
+    }
+
+    /// 处于某一阶段的全部任务
+    pub fn in_phase(&self, phase: JobPhase) -> Vec<JobRecord> {
+        self.records.lock().values().filter(|r| r.phase == phase).cloned().collect()
+    }
+
+    /// 未结束的任务数（包括等待授标的出价，它们随时可能中标）
+    pub fn active(&self) -> usize {
+        self.records.lock().values().filter(|r| !r.phase.is_terminal()).count()
+    }
+
+    pub fn records(&self) -> Vec<JobRecord> {
+        self.records.lock().values().cloned().collect()
+    }
+
+    /// 清理结束超过保留期的任务
+    pub fn prune(&self, now: i64) -> Result<usize> {
+        let mut records = self.records.lock();
+        let before = records.len();
+        records.retain(|_, r| !r.phase.is_terminal() || now - r.updated_at < HISTORY_RETENTION_SECS);
+        let removed = before - records.len();
+        if removed > 0 {
+            self.persist(&records)?;
+        }
+        Ok(removed)
+    }
+
+    /// 写入临时文件后替换，避免中途崩溃留下半截文件
+    fn persist(&self, records: &BTreeMap<String, JobRecord>) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(records)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid(job_id: &str) -> (MarketJob, Bid) {
+        let job = MarketJob {
+            job_id: job_id.to_string(),
+            task_type: "upper".to_string(),
+            requester: "requester".to_string(),
+            input_url: "https://example.com/input".to_string(),
+            input_hash: String::new(),
+            work_gflop: 1.0,
+            max_price: 100,
+            min_score: 0.0,
+            bid_closes_at: 100,
+            deadline: 1_000,
+        };
+        let bid = Bid {
+            job_id: job_id.to_string(),
+            node_id: "node-a".to_string(),
+            price: 10,
+            estimated_secs: 1,
+            device_score: 1.0,
+            placed_at: 0,
+        };
+        (job, bid)
+    }
+
+    #[test]
+    fn test_lifecycle_survives_restart_and_rejects_skipped_phases() {
+        let path = std::env::temp_dir().join(format!("williw-market-{}.json", uuid::Uuid::new_v4()));
+        let book = JobBook::open(&path).unwrap();
+        let (job, first) = bid("job-1");
+        book.insert_bid(job, first).unwrap();
+
+        let err = book.transition("job-1", JobPhase::Executing, |_| {}).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TransitionError>(),
+            Some(TransitionError::Invalid {
+                from: JobPhase::Bid,
+                to: JobPhase::Executing,
+                ..
+            })
+        ));
+        book.transition("job-1", JobPhase::Awarded, |_| {}).unwrap();
+        book.transition("job-1", JobPhase::Accepted, |_| {}).unwrap();
+        book.transition("job-1", JobPhase::Executing, |_| {}).unwrap();
+        let (job, second) = bid("job-2");
+        book.insert_bid(job, second).unwrap();
+        book.transition("job-2", JobPhase::Lost, |_| {}).unwrap();
+        drop(book);
+
+        // 执行中断的任务重启后退回已接受，结束的任务不能再变化
+        let book = JobBook::open(&path).unwrap();
+        assert_eq!(book.get("job-1").unwrap().phase, JobPhase::Accepted);
+        assert_eq!(book.active(), 1);
+        assert!(book.fail("job-2", "late").is_err());
+        assert_eq!(book.prune(Utc::now().timestamp() + HISTORY_RETENTION_SECS).unwrap(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,497 @@
+//! 内容寻址的本地模型缓存
+//!
+//! 下载的模型文件和切分出的分片按 blake3 哈希存放在 `objects/<哈希>`，索引记录每个对象被哪些名字
+//! （如 `bert-base-uncased/tokenizer.json`）引用以及这些名字链接到的位置：
+//! - 不同模型共用的文件（分词器等）只存一份，原位置替换为指向对象的硬链接
+//! - 超出容量时按 LRU 或“最大优先”淘汰，淘汰时一并删除链接出去的文件，真正释放磁盘
+//! - 固定（持久化）或租用中（运行中的节点正在使用）的对象不会被淘汰
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const INDEX_FILE: &str = "cache_index.json";
+
+/// 淘汰顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// 最久未访问的先淘汰
+    #[default]
+    Lru,
+    /// 最大的先淘汰（尽量少删文件）
+    LargestFirst,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelCacheConfig {
+    pub enabled: bool,
+    /// 缓存目录
+    pub dir: PathBuf,
+    /// 容量上限（字节），0 表示不限
+    pub max_bytes: u64,
+    pub policy: EvictionPolicy,
+}
+
+impl Default for ModelCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: PathBuf::from("./model_cache"),
+            max_bytes: 20 * 1024 * 1024 * 1024,
+            policy: EvictionPolicy::Lru,
+        }
+    }
+}
+
+/// 缓存中的一个对象
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// 内容的 blake3 哈希（hex）
+    pub digest: String,
+    pub size: u64,
+    /// 引用该对象的名字 -> 链接到的位置（没有链接出去时为 None）
+    pub names: BTreeMap<String, Option<PathBuf>>,
+    pub added_at: i64,
+    pub last_access: i64,
+    /// 访问序号，LRU 按它排序（时间戳精度不够区分同一秒内的访问）
+    pub access_seq: u64,
+    /// 固定后不会被淘汰
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    entries: BTreeMap<String, CacheEntry>,
+    next_seq: u64,
+}
+
+impl CacheIndex {
+    fn touch(&mut self, digest: &str, now: i64) {
+        self.next_seq += 1;
+        let seq = self.next_seq;
+        if let Some(entry) = self.entries.get_mut(digest) {
+            entry.last_access = now;
+            entry.access_seq = seq;
+        }
+    }
+
+    fn digest_of_name(&self, name: &str) -> Option<String> {
+        self.entries
+            .values()
+            .find(|entry| entry.names.contains_key(name))
+            .map(|entry| entry.digest.clone())
+    }
+}
+
+/// 缓存占用概况
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheUsage {
+    pub objects: usize,
+    pub names: usize,
+    pub total_bytes: u64,
+    /// 去重节省的字节数（同一对象被多个名字引用）
+    pub deduplicated_bytes: u64,
+    pub pinned_bytes: u64,
+}
+
+/// 内容寻址的模型缓存
+pub struct ModelCache {
+    config: ModelCacheConfig,
+    index: Mutex<CacheIndex>,
+    /// 运行中租用的对象（哈希 -> 租用数），不持久化
+    leases: Mutex<HashMap<String, usize>>,
+}
+
+impl ModelCache {
+    pub fn open(config: ModelCacheConfig) -> Result<Self> {
+        std::fs::create_dir_all(config.dir.join("objects"))?;
+        let index = match std::fs::read(config.dir.join(INDEX_FILE)) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CacheIndex::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            config,
+            index: Mutex::new(index),
+            leases: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn config(&self) -> &ModelCacheConfig {
+        &self.config
+    }
+
+    pub fn object_path(&self, digest: &str) -> PathBuf {
+        self.config.dir.join("objects").join(digest)
+    }
+
+    /// 把文件放入缓存，返回内容哈希。
+    /// `link` 为 true 时原位置改为指向缓存对象的硬链接（内容相同的文件只占一份空间），
+    /// 否则只复制一份进缓存
+    pub fn insert_file(&self, name: &str, path: &Path, link: bool) -> Result<String> {
+        let (digest, size) = hash_file(path)?;
+        let object = self.object_path(&digest);
+        if !object.exists() {
+            let tmp = object.with_extension("tmp");
+            if !link || std::fs::hard_link(path, &tmp).is_err() {
+                std::fs::copy(path, &tmp)?;
+            }
+            std::fs::rename(&tmp, &object)?;
+        } else if link {
+            relink(&object, path)?;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        {
+            let mut index = self.index.lock();
+            // 同名文件内容变化时，先解除旧对象上的引用
+            if let Some(old) = index.digest_of_name(name).filter(|old| *old != digest) {
+                if let Some(entry) = index.entries.get_mut(&old) {
+                    entry.names.remove(name);
+                }
+            }
+            let entry = index.entries.entry(digest.clone()).or_insert_with(|| CacheEntry {
+                digest: digest.clone(),
+                size,
+                names: BTreeMap::new(),
+                added_at: now,
+                last_access: now,
+                access_seq: 0,
+                pinned: false,
+            });
+            entry.names.insert(name.to_string(), link.then(|| path.to_path_buf()));
+            index.touch(&digest, now);
+        }
+        self.save_index()?;
+        if self.config.max_bytes > 0 {
+            self.evict_to(self.config.max_bytes)?;
+        }
+        Ok(digest)
+    }
+
+    /// 按名字或哈希（至少 8 位前缀）查找对象路径，并记一次访问
+    pub fn get(&self, key: &str) -> Option<PathBuf> {
+        let digest = self.resolve(key).ok()?;
+        let path = self.object_path(&digest);
+        if !path.exists() {
+            return None;
+        }
+        self.index.lock().touch(&digest, chrono::Utc::now().timestamp());
+        let _ = self.save_index();
+        Some(path)
+    }
+
+    /// 把名字或哈希前缀解析为完整哈希
+    pub fn resolve(&self, key: &str) -> Result<String> {
+        let index = self.index.lock();
+        if let Some(digest) = index.digest_of_name(key) {
+            return Ok(digest);
+        }
+        if key.len() < 8 {
+            return Err(anyhow!("未找到缓存项: {}", key));
+        }
+        let mut matches = index.entries.keys().filter(|digest| digest.starts_with(key));
+        match (matches.next(), matches.next()) {
+            (Some(digest), None) => Ok(digest.clone()),
+            (Some(_), Some(_)) => Err(anyhow!("哈希前缀 {} 对应多个缓存项", key)),
+            _ => Err(anyhow!("未找到缓存项: {}", key)),
+        }
+    }
+
+    pub fn set_pinned(&self, key: &str, pinned: bool) -> Result<CacheEntry> {
+        let digest = self.resolve(key)?;
+        let entry = {
+            let mut index = self.index.lock();
+            let entry = index.entries.get_mut(&digest).ok_or_else(|| anyhow!("未找到缓存项: {}", key))?;
+            entry.pinned = pinned;
+            entry.clone()
+        };
+        self.save_index()?;
+        Ok(entry)
+    }
+
+    /// 租用对象：租约存续期间不会被淘汰（用于节点正在加载或使用的分片）
+    pub fn lease(self: &Arc<Self>, key: &str) -> Result<CacheLease> {
+        let digest = self.resolve(key)?;
+        *self.leases.lock().entry(digest.clone()).or_insert(0) += 1;
+        self.index.lock().touch(&digest, chrono::Utc::now().timestamp());
+        Ok(CacheLease {
+            cache: Arc::clone(self),
+            digest,
+        })
+    }
+
+    fn is_protected(&self, entry: &CacheEntry) -> bool {
+        entry.pinned || self.leases.lock().get(&entry.digest).is_some_and(|count| *count > 0)
+    }
+
+    /// 按名字或哈希淘汰单个对象（连同所有引用它的名字）
+    pub fn evict(&self, key: &str) -> Result<CacheEntry> {
+        let digest = self.resolve(key)?;
+        let entry = self
+            .index
+            .lock()
+            .entries
+            .get(&digest)
+            .cloned()
+            .ok_or_else(|| anyhow!("未找到缓存项: {}", key))?;
+        if self.is_protected(&entry) {
+            return Err(anyhow!("缓存项 {} 已固定或正在使用", key));
+        }
+        self.remove_entry(&entry)?;
+        self.save_index()?;
+        Ok(entry)
+    }
+
+    /// 解除一个名字对对象的引用并删除它链接出去的文件；对象不再被任何名字引用时一并删除。
+    /// 返回对象是否被删除（其他模型共用的文件保留）
+    pub fn remove_name(&self, name: &str) -> Result<bool> {
+        let entry = {
+            let index = self.index.lock();
+            index
+                .digest_of_name(name)
+                .and_then(|digest| index.entries.get(&digest).cloned())
+                .ok_or_else(|| anyhow!("未找到缓存项: {}", name))?
+        };
+        if self.is_protected(&entry) {
+            return Err(anyhow!("缓存项 {} 已固定或正在使用", name));
+        }
+        if entry.names.len() == 1 {
+            self.remove_entry(&entry)?;
+            self.save_index()?;
+            return Ok(true);
+        }
+        if let Some(Some(path)) = entry.names.get(name) {
+            if hash_file(path).is_ok_and(|(digest, _)| digest == entry.digest) {
+                std::fs::remove_file(path)?;
+            }
+        }
+        if let Some(entry) = self.index.lock().entries.get_mut(&entry.digest) {
+            entry.names.remove(name);
+        }
+        self.save_index()?;
+        Ok(false)
+    }
+
+    /// 按淘汰策略删除对象，直到总大小不超过 `max_bytes`，返回被淘汰的对象
+    pub fn evict_to(&self, max_bytes: u64) -> Result<Vec<CacheEntry>> {
+        let mut candidates = self.entries();
+        let mut total: u64 = candidates.iter().map(|entry| entry.size).sum();
+        if total <= max_bytes {
+            return Ok(Vec::new());
+        }
+        match self.config.policy {
+            EvictionPolicy::Lru => candidates.sort_by_key(|entry| entry.access_seq),
+            EvictionPolicy::LargestFirst => candidates.sort_by(|a, b| b.size.cmp(&a.size)),
+        }
+
+        let mut evicted = Vec::new();
+        for entry in candidates {
+            if total <= max_bytes {
+                break;
+            }
+            if self.is_protected(&entry) {
+                continue;
+            }
+            self.remove_entry(&entry)?;
+            total -= entry.size;
+            evicted.push(entry);
+        }
+        if !evicted.is_empty() {
+            self.save_index()?;
+            log::info!(
+                "[模型缓存] 淘汰 {} 个对象，释放 {} 字节",
+                evicted.len(),
+                evicted.iter().map(|entry| entry.size).sum::<u64>()
+            );
+        }
+        Ok(evicted)
+    }
+
+    fn remove_entry(&self, entry: &CacheEntry) -> Result<()> {
+        for path in entry.names.values().flatten() {
+            // 只删除仍指向该对象内容的链接，用户替换过的文件保留
+            if hash_file(path).is_ok_and(|(digest, _)| digest == entry.digest) {
+                std::fs::remove_file(path)?;
+            }
+        }
+        match std::fs::remove_file(self.object_path(&entry.digest)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        self.index.lock().entries.remove(&entry.digest);
+        Ok(())
+    }
+
+    /// 所有对象，按最近访问排序（最近的在前）
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        let mut entries: Vec<CacheEntry> = self.index.lock().entries.values().cloned().collect();
+        entries.sort_by(|a, b| b.access_seq.cmp(&a.access_seq));
+        entries
+    }
+
+    pub fn usage(&self) -> CacheUsage {
+        let index = self.index.lock();
+        let mut usage = CacheUsage::default();
+        for entry in index.entries.values() {
+            usage.objects += 1;
+            usage.names += entry.names.len();
+            usage.total_bytes += entry.size;
+            usage.deduplicated_bytes += entry.size * entry.names.len().saturating_sub(1) as u64;
+            if entry.pinned {
+                usage.pinned_bytes += entry.size;
+            }
+        }
+        usage
+    }
+
+    fn save_index(&self) -> Result<()> {
+        let data = serde_json::to_vec_pretty(&*self.index.lock())?;
+        let path = self.config.dir.join(INDEX_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// 缓存对象的租约，释放时解除淘汰保护
+pub struct CacheLease {
+    cache: Arc<ModelCache>,
+    digest: String,
+}
+
+impl CacheLease {
+    pub fn digest(&self) -> &str {
+        &self.digest
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.cache.object_path(&self.digest)
+    }
+}
+
+impl Drop for CacheLease {
+    fn drop(&mut self) {
+        let mut leases = self.cache.leases.lock();
+        if let Some(count) = leases.get_mut(&self.digest) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                leases.remove(&self.digest);
+            }
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> Result<(String, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((hasher.finalize().to_hex().to_string(), size))
+}
+
+/// 用指向缓存对象的硬链接替换 `path`（先链接到临时文件再改名，失败时保留原文件）
+fn relink(object: &Path, path: &Path) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".relink");
+    let tmp = PathBuf::from(tmp);
+    if std::fs::hard_link(object, &tmp).is_err() {
+        // 跨文件系统无法链接时保留原文件
+        return Ok(());
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedupe_pin_and_lru_eviction() {
+        let dir = tempfile::tempdir().unwrap();
+        let models = dir.path().join("models");
+        std::fs::create_dir_all(models.join("a")).unwrap();
+        std::fs::create_dir_all(models.join("b")).unwrap();
+        std::fs::write(models.join("a/tokenizer.json"), b"shared tokenizer").unwrap();
+        std::fs::write(models.join("b/tokenizer.json"), b"shared tokenizer").unwrap();
+        std::fs::write(models.join("a/weights.bin"), vec![1u8; 100]).unwrap();
+        std::fs::write(models.join("b/weights.bin"), vec![2u8; 100]).unwrap();
+
+        let cache = Arc::new(
+            ModelCache::open(ModelCacheConfig {
+                dir: dir.path().join("cache"),
+                max_bytes: 0,
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let shared = cache.insert_file("a/tokenizer.json", &models.join("a/tokenizer.json"), true).unwrap();
+        assert_eq!(cache.insert_file("b/tokenizer.json", &models.join("b/tokenizer.json"), true).unwrap(), shared);
+        cache.insert_file("a/weights.bin", &models.join("a/weights.bin"), true).unwrap();
+        cache.insert_file("b/weights.bin", &models.join("b/weights.bin"), true).unwrap();
+        let usage = cache.usage();
+        assert_eq!((usage.objects, usage.names), (3, 4));
+        assert_eq!(usage.deduplicated_bytes, 16);
+
+        // a 的权重固定，b 的权重正被使用；再访问分词器让它成为最近使用
+        cache.set_pinned("a/weights.bin", true).unwrap();
+        let lease = cache.lease("b/weights.bin").unwrap();
+        assert!(cache.get(&shared[..8]).is_some());
+        assert!(cache.evict_to(0).unwrap().iter().any(|entry| entry.digest == shared));
+        assert!(!models.join("b/tokenizer.json").exists());
+        assert_eq!(cache.entries().len(), 2);
+
+        drop(lease);
+        let evicted = cache.evict_to(100).unwrap();
+        assert_eq!(evicted.len(), 1);
+        assert!(evicted[0].names.contains_key("b/weights.bin"));
+        assert!(cache.evict("a/weights.bin").is_err());
+
+        // 索引持久化
+        let reopened = ModelCache::open(cache.config().clone()).unwrap();
+        assert_eq!(reopened.entries().len(), 1);
+        assert!(reopened.entries()[0].pinned);
+    }
+
+    #[test]
+    fn test_remove_name_keeps_shared_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let models = dir.path().join("models");
+        std::fs::create_dir_all(&models).unwrap();
+        std::fs::write(models.join("a.json"), b"shared").unwrap();
+        std::fs::write(models.join("b.json"), b"shared").unwrap();
+
+        let cache = ModelCache::open(ModelCacheConfig {
+            dir: dir.path().join("cache"),
+            max_bytes: 0,
+            ..Default::default()
+        })
+        .unwrap();
+        let digest = cache.insert_file("a/config.json", &models.join("a.json"), true).unwrap();
+        cache.insert_file("b/config.json", &models.join("b.json"), true).unwrap();
+
+        assert!(!cache.remove_name("a/config.json").unwrap());
+        assert!(!models.join("a.json").exists());
+        assert!(models.join("b.json").exists() && cache.object_path(&digest).exists());
+
+        assert!(cache.remove_name("b/config.json").unwrap());
+        assert!(!cache.object_path(&digest).exists());
+        assert!(cache.remove_name("b/config.json").is_err());
+    }
+}
@@ -276,8 +276,7 @@ pub unsafe extern "C" fn williw_node_recommended_model_dim(
     }
     
     let handle = &*ptr;
-    let caps = handle.device_manager.get();
-    caps.recommended_model_dim()
+    handle.device_manager.recommended_model_dim()
 }
 
 /// 获取推荐的训练间隔（秒）
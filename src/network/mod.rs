@@ -48,7 +48,7 @@ impl Default for BandwidthBudgetConfig {
 
 /// 网络句柄
 pub struct NetworkHandle {
-    transport: transport::IrohTransport,
+    transport: transport::MultipathTransport<transport::ProximityTransport<transport::IrohTransport>>,
     router: routing::SimpleRouter,
     config: NetworkConfig,
 }
@@ -56,7 +56,47 @@ pub struct NetworkHandle {
 impl NetworkHandle {
     /// 创建新的网络句柄
     pub async fn new(config: NetworkConfig) -> anyhow::Result<Self> {
-        let transport = transport::create_transport(&config.transport).await?;
+        Self::build(config, None).await
+    }
+
+    /// 创建绑定节点身份的网络句柄，连接按配置使用 TLS 1.3 或 Noise XX 加密
+    pub async fn with_identity(
+        config: NetworkConfig,
+        identity: std::sync::Arc<crate::crypto::identity::NodeIdentity>,
+    ) -> anyhow::Result<Self> {
+        Self::build(config, Some(identity)).await
+    }
+
+    async fn build(
+        config: NetworkConfig,
+        identity: Option<std::sync::Arc<crate::crypto::identity::NodeIdentity>>,
+    ) -> anyhow::Result<Self> {
+        let mut base = transport::create_transport(&config.transport).await?;
+        if let Some(identity) = identity {
+            base.enable_channel_security(identity);
+        }
+        let enable_proximity = config.transport.enable_proximity
+            || config.transport.transport_type == TransportType::Proximity;
+        let transport = transport::ProximityTransport::new(
+            base,
+            transport::proximity_hub(),
+            enable_proximity,
+            transport::proximity::local_lan_addrs(),
+        )
+        .await;
+        let selector = routing::PrivacyPathSelector::new(crate::config::PrivacyPerformanceConfig::default());
+        let multipath_config = routing::MultiPathConfig {
+            enabled: config.routing.enable_multipath,
+            max_paths: config.routing.max_paths,
+            load_balance_strategy: config.routing.load_balance,
+            ..selector.multipath_config().clone()
+        };
+        let transport = transport::MultipathTransport::new(
+            transport,
+            std::sync::Arc::new(selector.with_multipath_config(multipath_config)),
+            config.routing.enable_multipath,
+            config.routing.multipath.clone(),
+        );
         let router = routing::create_router(&config.routing).await?;
         
         Ok(Self {
@@ -79,6 +119,26 @@ impl NetworkHandle {
         self.transport.send(&transport_route, message).await
     }
     
+    /// 本节点的近场能力提示（随握手发送给对端）
+    pub fn proximity_hint(&self) -> transport::ProximityHint {
+        self.transport.inner().local_hint()
+    }
+
+    /// 记录对端的近场能力提示，后续发送会据此协商近场链路
+    pub fn register_peer_proximity(&self, peer_id: &str, hint: transport::ProximityHint) {
+        self.transport.inner().register_peer_hint(peer_id, hint);
+    }
+
+    /// 路径选择器：登记到目标节点的各条路径，并接收传输层回报的路径质量
+    pub fn path_selector(&self) -> &std::sync::Arc<routing::PrivacyPathSelector> {
+        self.transport.selector()
+    }
+
+    /// 多路径发送与重排统计
+    pub fn multipath_stats(&self) -> transport::MultipathStats {
+        self.transport.multipath_stats()
+    }
+
     /// 接收消息
     pub async fn receive(&self) -> anyhow::Result<(String, Vec<u8>)> {
         self.transport.receive().await
@@ -30,6 +30,9 @@ pub struct RoutingConfig {
     pub enable_multipath: bool,
     /// 最大路径数
     pub max_paths: usize,
+    /// 多路径发送参数（条带/复制、重排超时），`enable_multipath` 为 true 时生效
+    #[serde(default)]
+    pub multipath: crate::network::transport::MultipathTransportConfig,
 }
 
 impl Default for RoutingConfig {
@@ -39,6 +42,28 @@ impl Default for RoutingConfig {
             load_balance: LoadBalanceStrategy::Weighted,
             enable_multipath: false,
             max_paths: 3,
+            multipath: Default::default(),
+        }
+    }
+}
+
+impl RoutingConfig {
+    /// 验证路由配置
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.max_paths == 0 {
+            errors.push("最大路径数 (max_paths) 必须大于 0".to_string());
+        }
+        if self.enable_multipath && self.max_paths < 2 {
+            errors.push(format!(
+                "启用多路径时最大路径数 (max_paths) 至少为 2，当前值: {}",
+                self.max_paths
+            ));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
     }
 }
@@ -71,6 +96,9 @@ pub struct RoutingStats {
 
 /// 创建路由实例
 pub async fn create_router(config: &RoutingConfig) -> Result<SimpleRouter> {
+    config
+        .validate()
+        .map_err(|errors| anyhow::anyhow!("路由配置无效: {}", errors.join("; ")))?;
     // 暂时返回一个简单的路由器实现
     // TODO: 实现完整的路由器逻辑
     Ok(SimpleRouter::new(config.clone()))
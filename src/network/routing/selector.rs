@@ -164,6 +164,33 @@ impl PrivacyPathSelector {
         }
     }
     
+    /// 替换多路径配置
+    pub fn with_multipath_config(mut self, multipath_config: MultiPathConfig) -> Self {
+        self.multipath_config = multipath_config;
+        self
+    }
+
+    pub fn multipath_config(&self) -> &MultiPathConfig {
+        &self.multipath_config
+    }
+
+    /// 传输层回报路径的实测质量；可靠性低于故障转移阈值的路径标记为不健康
+    pub fn report_path_quality(&self, target: &str, path_id: &str, quality: ConnectionQuality) {
+        let healthy = quality.reliability >= self.multipath_config.failover_threshold;
+        {
+            let mut paths = self.available_paths.write();
+            if let Some(path) = paths
+                .get_mut(target)
+                .and_then(|target_paths| target_paths.iter_mut().find(|p| p.path_id == path_id))
+            {
+                path.quality = quality;
+                path.usage_count += 1;
+                path.last_used = Instant::now();
+            }
+        }
+        self.update_path_health(path_id, healthy);
+    }
+
     /// 添加可用路径
     pub fn add_path(&self, target: &str, path: PathInfo) {
         let mut paths = self.available_paths.write();
@@ -383,10 +410,10 @@ impl PrivacyPathSelector {
             return Err(PathSelectionError::NoPathsAvailable);
         }
         
-        // 计算所有路径的评分
+        // 计算所有健康路径的评分
         let mut scored_paths: Vec<(PathInfo, PathScore)> = target_paths
             .iter()
-            .filter(|p| p.is_active)
+            .filter(|p| p.is_active && self.check_path_health(&p.path_id))
             .map(|path| {
                 let score = self.calculate_path_score(path);
                 (path.clone(), score)
@@ -12,11 +12,14 @@ pub struct Endpoint;
 #[derive(Clone)]
 pub struct Connection;
 use parking_lot::RwLock;
-use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
 
+use super::pool::{ConnectionPool, PoolConfig, PoolConnector, PooledConnection};
+use super::security::{ChannelSecurity, ChannelSecurityContext};
+use super::pool::PoolStats;
 use super::{RouteInfo, TransportStats};
+use crate::crypto::identity::NodeIdentity;
 
 /// Iroh传输配置
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -29,6 +32,31 @@ pub struct IrohConfig {
     pub enable_tls: bool,
     /// 是否启用压缩
     pub enable_compression: bool,
+    /// 通道加密方式
+    #[serde(default)]
+    pub channel_security: ChannelSecurity,
+    /// 连接池配置
+    #[serde(default)]
+    pub pool: PoolConfig,
+}
+
+/// 通过 iroh 端点建立和探测连接
+pub struct IrohConnector {
+    endpoint: Endpoint,
+}
+
+impl PoolConnector for IrohConnector {
+    type Connection = Connection;
+
+    async fn connect(&self, _peer: &str) -> Result<Connection> {
+        // Stub implementation
+        Ok(Connection)
+    }
+
+    async fn probe(&self, _connection: &Connection) -> Result<()> {
+        // Stub implementation
+        Ok(())
+    }
 }
 
 /// Iroh传输实现
@@ -36,24 +64,69 @@ pub struct IrohTransport {
     endpoint: Endpoint,
     config: IrohConfig,
     stats: Arc<RwLock<TransportStats>>,
-    connections: Arc<Mutex<HashMap<String, Connection>>>,
+    pool: Arc<ConnectionPool<IrohConnector>>,
+    security: Option<Arc<ChannelSecurityContext>>,
 }
 
 impl IrohTransport {
     pub async fn new(_config: IrohConfig) -> Result<Self> {
         // Stub implementation
+        let endpoint = Endpoint;
+        let pool = Arc::new(ConnectionPool::new(
+            IrohConnector {
+                endpoint: endpoint.clone(),
+            },
+            _config.pool.clone(),
+        ));
+        Self::spawn_pool_maintenance(&pool);
         Ok(Self {
-            endpoint: Endpoint,
+            endpoint,
             config: _config,
             stats: Arc::new(RwLock::new(TransportStats::default())),
-            connections: Arc::new(Mutex::new(HashMap::new())),
+            pool,
+            security: None,
         })
     }
 
-    /// 连接到远程节点
-    pub async fn connect(&self, _node_addr: &str) -> Result<Connection> {
-        // Stub implementation
-        Ok(Connection)
+    /// 按保活间隔维护连接池，传输实例释放后自动停止
+    fn spawn_pool_maintenance(pool: &Arc<ConnectionPool<IrohConnector>>) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let period = Duration::from_secs(pool.config().keepalive_interval_secs.max(1));
+        let pool = Arc::downgrade(pool);
+        runtime.spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+                pool.maintain().await;
+            }
+        });
+    }
+
+    /// 绑定节点身份，按配置启用 TLS 1.3 证书固定或 Noise XX
+    pub fn enable_channel_security(&mut self, identity: Arc<NodeIdentity>) {
+        if self.config.channel_security == ChannelSecurity::None {
+            log::warn!("[传输] 通道加密已关闭，连接将以明文传输");
+            return;
+        }
+        self.security = Some(Arc::new(ChannelSecurityContext::new(self.config.channel_security, identity)));
+    }
+
+    pub fn security(&self) -> Option<&Arc<ChannelSecurityContext>> {
+        self.security.as_ref()
+    }
+
+    /// 从连接池借出到远程节点的连接
+    pub async fn connect(&self, node_addr: &str) -> Result<PooledConnection<Connection>> {
+        self.pool.acquire(node_addr).await
+    }
+
+    pub fn pool(&self) -> &Arc<ConnectionPool<IrohConnector>> {
+        &self.pool
     }
 }
 
@@ -69,8 +142,12 @@ impl super::Transport for IrohTransport {
     }
 
     fn get_stats(&self) -> super::TransportStats {
-        // Stub implementation
-        super::TransportStats::default()
+        let pool = self.pool.stats();
+        super::TransportStats {
+            active_connections: pool.open_connections,
+            pool,
+            ..self.stats.read().clone()
+        }
     }
 }
 
@@ -82,6 +159,7 @@ impl Default for TransportStats {
             active_connections: 0,
             failed_sends: 0,
             average_latency_ms: 0.0,
+            pool: PoolStats::default(),
         }
     }
 }
\ No newline at end of file
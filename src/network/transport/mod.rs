@@ -1,17 +1,30 @@
 //! 传输层模块
 //!
-//! 基于 iroh 提供统一的传输接口
+//! 基于 iroh 提供统一的传输接口，并可叠加近场链路（WiFi Direct / AWDL）；
+//! 调试共识和路由时可换用确定性的模拟传输
 
 mod iroh;
+pub mod multipath;
+pub mod pool;
+pub mod proximity;
+pub mod security;
+pub mod simulated;
 
 // 重新导出公共接口
 pub use iroh::*;
+pub use multipath::{MultipathMode, MultipathStats, MultipathTransport, MultipathTransportConfig, PathMeter};
+pub use pool::{ConnectionPool, PoolConfig, PoolConnector, PoolStats, PooledConnection};
+pub use proximity::{proximity_hub, ProximityHint, ProximityHub, ProximityKind, ProximityLink, ProximityTransport};
+pub use security::{ChannelSecurity, ChannelSecurityContext, NodeCertificate, NoiseChannel, NoiseHandshake, PeerVerificationError};
+pub use simulated::{LatencyDistribution, LinkProfile, SimulatedNetwork, SimulatedTransport, SimulationConfig};
 
 /// 传输协议类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum TransportType {
     /// Iroh 协议
     Iroh,
+    /// 近场链路（WiFi Direct / AWDL），以 Iroh 作为回退路径
+    Proximity,
 }
 
 /// 传输配置
@@ -27,6 +40,26 @@ pub struct TransportConfig {
     pub enable_tls: bool,
     /// 是否启用压缩
     pub enable_compression: bool,
+    /// 是否在相邻节点间启用近场传输
+    #[serde(default)]
+    pub enable_proximity: bool,
+    /// 通道加密方式（TLS 1.3 或 Noise XX），`enable_tls` 为 false 时不生效
+    #[serde(default)]
+    pub channel_security: ChannelSecurity,
+    /// 连接池配置，总连接数上限取 `max_connections`
+    #[serde(default)]
+    pub pool: PoolConfig,
+}
+
+impl TransportConfig {
+    /// 实际生效的通道加密方式
+    pub fn effective_security(&self) -> ChannelSecurity {
+        if self.enable_tls {
+            self.channel_security
+        } else {
+            ChannelSecurity::None
+        }
+    }
 }
 
 impl Default for TransportConfig {
@@ -37,6 +70,9 @@ impl Default for TransportConfig {
             max_connections: 100,
             enable_tls: true,
             enable_compression: true,
+            enable_proximity: false,
+            channel_security: ChannelSecurity::Tls13,
+            pool: PoolConfig::default(),
         }
     }
 }
@@ -70,17 +106,26 @@ pub struct TransportStats {
     pub active_connections: usize,
     pub failed_sends: u64,
     pub average_latency_ms: f64,
+    /// 连接池统计
+    #[serde(default)]
+    pub pool: PoolStats,
 }
 
 /// 创建传输实例
 pub async fn create_transport(config: &TransportConfig) -> anyhow::Result<IrohTransport> {
     match config.transport_type {
-        TransportType::Iroh => {
+        // 近场传输叠加在 Iroh 之上，基础传输始终是 Iroh
+        TransportType::Iroh | TransportType::Proximity => {
             let iroh_config = IrohConfig {
                 listen_addr: config.listen_addr.clone(),
                 max_connections: config.max_connections,
                 enable_tls: config.enable_tls,
                 enable_compression: config.enable_compression,
+                channel_security: config.effective_security(),
+                pool: PoolConfig {
+                    max_total: config.max_connections,
+                    ..config.pool.clone()
+                },
             };
             Ok(IrohTransport::new(iroh_config).await?)
         }
@@ -0,0 +1,643 @@
+//! 多路径传输
+//!
+//! 在同一底层传输上沿多条路径（直连、中继等，由 [`PrivacyPathSelector`] 给出）并行发送：
+//! - 条带模式：消息切成分片，按负载均衡策略算出的权重做平滑加权轮询分配到各路径
+//! - 复制模式：整条消息在权重最高的若干路径上各发一份，接收端去重
+//!
+//! 每帧带有发送会话号和序号，接收端按来源重组分片并按序号交付；缺口超过重排超时或重排缓冲满时跳过。
+//! 每次发送的耗时和成败按路径统计，换算成 [`ConnectionQuality`] 回报给路径选择器，
+//! 质量低于故障转移阈值的路径会被标记为不健康，后续不再参与多路径选择。
+
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{RouteInfo, Transport, TransportStats};
+use crate::network::routing::quality::ConnectionQuality;
+use crate::network::routing::selector::{PathInfo, PrivacyPathSelector};
+use crate::network::routing::LoadBalanceStrategy;
+
+const FRAME_MAGIC: &[u8; 4] = b"WMP1";
+/// magic(4) + 会话(4) + 序号(8) + 分片序号(2) + 分片数(2)
+const FRAME_HEADER_LEN: usize = 20;
+
+/// 多路径发送方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MultipathMode {
+    /// 分片后分散到各路径，提高吞吐
+    Stripe,
+    /// 每条路径发送完整副本，降低尾延迟和丢包影响
+    Replicate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultipathTransportConfig {
+    pub mode: MultipathMode,
+    /// 条带分片大小（字节）
+    pub stripe_size: usize,
+    /// 复制模式下的副本数
+    pub replicas: usize,
+    /// 接收端等待缺失序号的最长时间（毫秒）
+    pub reorder_timeout_ms: u64,
+    /// 每个来源最多缓存的待排序消息数
+    pub max_reorder_messages: usize,
+    /// 路径质量统计的 EWMA 系数
+    pub quality_alpha: f32,
+}
+
+impl Default for MultipathTransportConfig {
+    fn default() -> Self {
+        Self {
+            mode: MultipathMode::Stripe,
+            stripe_size: 16 * 1024,
+            replicas: 2,
+            reorder_timeout_ms: 200,
+            max_reorder_messages: 256,
+            quality_alpha: 0.2,
+        }
+    }
+}
+
+/// 多路径帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Frame {
+    session: u32,
+    seq: u64,
+    index: u16,
+    count: u16,
+    payload: Vec<u8>,
+}
+
+impl Frame {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(FRAME_HEADER_LEN + self.payload.len());
+        out.extend_from_slice(FRAME_MAGIC);
+        out.extend_from_slice(&self.session.to_le_bytes());
+        out.extend_from_slice(&self.seq.to_le_bytes());
+        out.extend_from_slice(&self.index.to_le_bytes());
+        out.extend_from_slice(&self.count.to_le_bytes());
+        out.extend_from_slice(&self.payload);
+        out
+    }
+
+    /// 不是多路径帧时返回 None，由调用方按普通消息处理
+    fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < FRAME_HEADER_LEN || &data[..4] != FRAME_MAGIC {
+            return None;
+        }
+        let frame = Self {
+            session: u32::from_le_bytes(data[4..8].try_into().ok()?),
+            seq: u64::from_le_bytes(data[8..16].try_into().ok()?),
+            index: u16::from_le_bytes(data[16..18].try_into().ok()?),
+            count: u16::from_le_bytes(data[18..20].try_into().ok()?),
+            payload: data[FRAME_HEADER_LEN..].to_vec(),
+        };
+        (frame.count > 0 && frame.index < frame.count).then_some(frame)
+    }
+}
+
+/// 单条路径的发送质量（EWMA）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathMeter {
+    pub path_id: String,
+    pub sent_frames: u64,
+    pub failed_frames: u64,
+    pub sent_bytes: u64,
+    pub latency_ms: f32,
+    pub jitter_ms: f32,
+    pub bandwidth_mbps: f32,
+    pub loss_rate: f32,
+}
+
+impl PathMeter {
+    fn record(&mut self, alpha: f32, bytes: usize, elapsed: Duration, ok: bool) {
+        let first = self.sent_frames + self.failed_frames == 0;
+        let blend = |old: f32, new: f32| if first { new } else { old + alpha * (new - old) };
+        self.loss_rate = blend(self.loss_rate, if ok { 0.0 } else { 1.0 });
+        if !ok {
+            self.failed_frames += 1;
+            return;
+        }
+        let latency = elapsed.as_secs_f32() * 1000.0;
+        self.jitter_ms = blend(self.jitter_ms, (latency - self.latency_ms).abs());
+        self.latency_ms = blend(self.latency_ms, latency);
+        let mbps = bytes as f32 * 8.0 / 1_000_000.0 / elapsed.as_secs_f32().max(1e-4);
+        self.bandwidth_mbps = blend(self.bandwidth_mbps, mbps);
+        self.sent_frames += 1;
+        self.sent_bytes += bytes as u64;
+    }
+
+    pub fn quality(&self) -> ConnectionQuality {
+        let reliability = (1.0 - self.loss_rate).clamp(0.0, 1.0);
+        ConnectionQuality {
+            latency_ms: self.latency_ms,
+            bandwidth_mbps: self.bandwidth_mbps,
+            packet_loss_percent: self.loss_rate * 100.0,
+            jitter_ms: self.jitter_ms,
+            reliability,
+            stability: (1.0 - self.jitter_ms / self.latency_ms.max(1.0)).clamp(0.0, 1.0),
+            last_updated: Instant::now(),
+        }
+    }
+}
+
+/// 多路径统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MultipathStats {
+    pub messages_sent: u64,
+    pub frames_sent: u64,
+    pub frames_received: u64,
+    /// 乱序到达、经缓冲后才交付的消息数
+    pub reordered: u64,
+    /// 重复的副本或分片
+    pub duplicates: u64,
+    /// 因超时或缓冲满而跳过的缺失序号数
+    pub gaps_skipped: u64,
+    /// 分片在首选路径失败后改走其他路径的次数
+    pub failovers: u64,
+    pub paths: Vec<PathMeter>,
+}
+
+/// 未收齐的消息
+struct Partial {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// 单个来源（按发送会话区分）的重组状态
+struct SourceState {
+    session: u32,
+    next_seq: u64,
+    partial: HashMap<u64, Partial>,
+    /// 已收齐但序号之前还有缺口的消息及其收齐时间
+    complete: BTreeMap<u64, (Vec<u8>, Instant)>,
+}
+
+impl SourceState {
+    fn new(session: u32) -> Self {
+        Self {
+            session,
+            next_seq: 0,
+            partial: HashMap::new(),
+            complete: BTreeMap::new(),
+        }
+    }
+
+    /// 跳过缺口，直到最早一条已收齐的消息
+    fn skip_gap(&mut self, stats: &mut MultipathStats) {
+        if let Some(&first) = self.complete.keys().next() {
+            stats.gaps_skipped += first - self.next_seq;
+            self.partial.retain(|seq, _| *seq > first);
+            self.next_seq = first;
+        }
+    }
+
+    fn drain_ready(&mut self, ready: &mut VecDeque<Vec<u8>>) {
+        while let Some((data, _)) = self.complete.remove(&self.next_seq) {
+            ready.push_back(data);
+            self.next_seq += 1;
+        }
+    }
+}
+
+struct ReceiveState {
+    sources: HashMap<String, SourceState>,
+    ready: VecDeque<(String, Vec<u8>)>,
+}
+
+/// 多路径传输：包装常规传输
+pub struct MultipathTransport<T: Transport> {
+    inner: T,
+    selector: Arc<PrivacyPathSelector>,
+    enabled: bool,
+    config: MultipathTransportConfig,
+    session: u32,
+    /// 目标 -> 下一个序号
+    next_seq: Mutex<HashMap<String, u64>>,
+    /// 平滑加权轮询的当前值：路径 ID -> 累计权重
+    wrr: Mutex<HashMap<String, f64>>,
+    meters: RwLock<HashMap<String, PathMeter>>,
+    receive_state: Mutex<ReceiveState>,
+    stats: Mutex<MultipathStats>,
+    messages_sent: AtomicU64,
+}
+
+impl<T: Transport> MultipathTransport<T> {
+    /// `enabled` 为 false 时完全等价于内层传输
+    pub fn new(inner: T, selector: Arc<PrivacyPathSelector>, enabled: bool, config: MultipathTransportConfig) -> Self {
+        Self {
+            inner,
+            selector,
+            enabled,
+            config,
+            session: rand::random(),
+            next_seq: Mutex::new(HashMap::new()),
+            wrr: Mutex::new(HashMap::new()),
+            meters: RwLock::new(HashMap::new()),
+            receive_state: Mutex::new(ReceiveState {
+                sources: HashMap::new(),
+                ready: VecDeque::new(),
+            }),
+            stats: Mutex::new(MultipathStats::default()),
+            messages_sent: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn selector(&self) -> &Arc<PrivacyPathSelector> {
+        &self.selector
+    }
+
+    pub fn multipath_stats(&self) -> MultipathStats {
+        let mut stats = self.stats.lock().clone();
+        stats.messages_sent = self.messages_sent.load(Ordering::Relaxed);
+        let mut paths: Vec<PathMeter> = self.meters.read().values().cloned().collect();
+        paths.sort_by(|a, b| a.path_id.cmp(&b.path_id));
+        stats.paths = paths;
+        stats
+    }
+
+    /// 按负载均衡策略计算路径权重
+    fn path_weight(&self, path: &PathInfo) -> f64 {
+        let weight = match self.selector.multipath_config().load_balance_strategy {
+            LoadBalanceStrategy::RoundRobin | LoadBalanceStrategy::LeastConnections => 1.0,
+            LoadBalanceStrategy::Weighted => path.performance_level * path.quality.reliability.max(0.05),
+            LoadBalanceStrategy::LatencyBased => 1.0 / (path.quality.latency_ms + 1.0),
+            LoadBalanceStrategy::BandwidthBased => path.quality.bandwidth_mbps,
+            LoadBalanceStrategy::PrivacyAware => path.privacy_level,
+        };
+        (weight as f64).max(1e-3)
+    }
+
+    /// 平滑加权轮询：每次选出累计值最大的路径，再减去总权重
+    fn next_path<'a>(&self, paths: &'a [(PathInfo, f64)]) -> &'a PathInfo {
+        let total: f64 = paths.iter().map(|(_, weight)| weight).sum();
+        let mut wrr = self.wrr.lock();
+        let mut best = 0;
+        let mut best_value = f64::MIN;
+        for (i, (path, weight)) in paths.iter().enumerate() {
+            let value = wrr.entry(path.path_id.clone()).or_insert(0.0);
+            *value += weight;
+            if *value > best_value {
+                best_value = *value;
+                best = i;
+            }
+        }
+        if let Some(value) = wrr.get_mut(&paths[best].0.path_id) {
+            *value -= total;
+        }
+        &paths[best].0
+    }
+
+    fn route_for(route: &RouteInfo, path: &PathInfo) -> RouteInfo {
+        RouteInfo {
+            address: path.hops.first().cloned().unwrap_or_else(|| path.target.clone()),
+            quality_score: path.performance_level as f64,
+            ..route.clone()
+        }
+    }
+
+    /// 在指定路径上发送一帧并回报路径质量
+    async fn send_on(&self, route: &RouteInfo, path: &PathInfo, frame: &[u8]) -> Result<()> {
+        let started = Instant::now();
+        let result = self.inner.send(&Self::route_for(route, path), frame).await;
+        let quality = {
+            let mut meters = self.meters.write();
+            let meter = meters.entry(path.path_id.clone()).or_insert_with(|| PathMeter {
+                path_id: path.path_id.clone(),
+                ..PathMeter::default()
+            });
+            meter.record(self.config.quality_alpha, frame.len(), started.elapsed(), result.is_ok());
+            meter.quality()
+        };
+        self.selector.report_path_quality(&route.destination, &path.path_id, quality);
+        result
+    }
+
+    /// 首选路径失败时依次尝试其余路径
+    async fn send_with_failover(&self, route: &RouteInfo, preferred: &PathInfo, paths: &[(PathInfo, f64)], frame: &[u8]) -> Result<()> {
+        let Err(first_error) = self.send_on(route, preferred, frame).await else {
+            return Ok(());
+        };
+        for (path, _) in paths.iter().filter(|(path, _)| path.path_id != preferred.path_id) {
+            if self.send_on(route, path, frame).await.is_ok() {
+                self.stats.lock().failovers += 1;
+                return Ok(());
+            }
+        }
+        Err(first_error)
+    }
+
+    fn frames_for(&self, seq: u64, message: &[u8]) -> Result<Vec<Frame>> {
+        let count = match self.config.mode {
+            MultipathMode::Stripe => message.len().div_ceil(self.config.stripe_size.max(1)).max(1),
+            MultipathMode::Replicate => 1,
+        };
+        let count = u16::try_from(count).map_err(|_| anyhow!("消息过大，分片数超过 {}", u16::MAX))?;
+        let chunk = message.len().div_ceil(count as usize).max(1);
+        let mut frames: Vec<Frame> = message
+            .chunks(chunk)
+            .enumerate()
+            .map(|(index, payload)| Frame {
+                session: self.session,
+                seq,
+                index: index as u16,
+                count,
+                payload: payload.to_vec(),
+            })
+            .collect();
+        if frames.is_empty() {
+            frames.push(Frame {
+                session: self.session,
+                seq,
+                index: 0,
+                count: 1,
+                payload: Vec::new(),
+            });
+        }
+        Ok(frames)
+    }
+
+    /// 处理一帧，收齐并按序的消息放入交付队列
+    fn accept_frame(&self, source: &str, frame: Frame) {
+        let now = Instant::now();
+        let reorder_timeout = Duration::from_millis(self.config.reorder_timeout_ms);
+        let mut state = self.receive_state.lock();
+        let mut stats = self.stats.lock();
+        stats.frames_received += 1;
+
+        let ReceiveState { sources, ready } = &mut *state;
+        // 对端重启后会话号变化，重新开始计序
+        let entry = sources.entry(source.to_string()).or_insert_with(|| SourceState::new(frame.session));
+        if entry.session != frame.session {
+            *entry = SourceState::new(frame.session);
+        }
+
+        if frame.seq < entry.next_seq || entry.complete.contains_key(&frame.seq) {
+            stats.duplicates += 1;
+            return;
+        }
+        let partial = entry.partial.entry(frame.seq).or_insert_with(|| Partial {
+            chunks: vec![None; frame.count as usize],
+            received: 0,
+        });
+        let Some(slot) = partial.chunks.get_mut(frame.index as usize) else {
+            return;
+        };
+        if slot.is_some() {
+            stats.duplicates += 1;
+            return;
+        }
+        *slot = Some(frame.payload);
+        partial.received += 1;
+        if partial.received == partial.chunks.len() {
+            let partial = entry.partial.remove(&frame.seq).expect("刚刚插入");
+            let data: Vec<u8> = partial.chunks.into_iter().flatten().flatten().collect();
+            if frame.seq != entry.next_seq {
+                stats.reordered += 1;
+            }
+            entry.complete.insert(frame.seq, (data, now));
+        }
+
+        let stalled = entry
+            .complete
+            .values()
+            .next()
+            .is_some_and(|(_, at)| now.saturating_duration_since(*at) >= reorder_timeout);
+        if !entry.complete.contains_key(&entry.next_seq)
+            && (stalled || entry.complete.len() > self.config.max_reorder_messages)
+        {
+            entry.skip_gap(&mut stats);
+        }
+        let mut delivered = VecDeque::new();
+        entry.drain_ready(&mut delivered);
+        ready.extend(delivered.into_iter().map(|data| (source.to_string(), data)));
+    }
+
+    /// 重排超时后跳过所有来源的缺口
+    fn flush_stalled(&self) {
+        let now = Instant::now();
+        let reorder_timeout = Duration::from_millis(self.config.reorder_timeout_ms);
+        let mut state = self.receive_state.lock();
+        let mut stats = self.stats.lock();
+        let ReceiveState { sources, ready } = &mut *state;
+        for (source, entry) in sources.iter_mut() {
+            let stalled = entry
+                .complete
+                .values()
+                .next()
+                .is_some_and(|(_, at)| now.saturating_duration_since(*at) >= reorder_timeout);
+            if stalled {
+                entry.skip_gap(&mut stats);
+                let mut delivered = VecDeque::new();
+                entry.drain_ready(&mut delivered);
+                ready.extend(delivered.into_iter().map(|data| (source.clone(), data)));
+            }
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        self.receive_state.lock().sources.values().any(|entry| !entry.complete.is_empty())
+    }
+}
+
+impl<T: Transport> Transport for MultipathTransport<T> {
+    async fn send(&self, route: &RouteInfo, message: &[u8]) -> Result<()> {
+        if !self.enabled {
+            return self.inner.send(route, message).await;
+        }
+
+        let seq = {
+            let mut next_seq = self.next_seq.lock();
+            let seq = next_seq.entry(route.destination.clone()).or_insert(0);
+            *seq += 1;
+            *seq - 1
+        };
+        let frames = self.frames_for(seq, message)?;
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.stats.lock().frames_sent += frames.len() as u64;
+
+        // 没有已知路径时仍然加帧头沿原路由发送，保证接收端的序号连续
+        let paths: Vec<(PathInfo, f64)> = self
+            .selector
+            .select_multipaths(&route.destination)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| {
+                let weight = self.path_weight(&path);
+                (path, weight)
+            })
+            .collect();
+        if paths.is_empty() {
+            for frame in &frames {
+                self.inner.send(route, &frame.encode()).await?;
+            }
+            return Ok(());
+        }
+
+        let sends: Vec<_> = match self.config.mode {
+            MultipathMode::Stripe => frames
+                .iter()
+                .map(|frame| (self.next_path(&paths).clone(), frame.encode()))
+                .collect(),
+            MultipathMode::Replicate => {
+                let mut ranked = paths.clone();
+                ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+                let encoded = frames[0].encode();
+                ranked
+                    .into_iter()
+                    .take(self.config.replicas.max(1))
+                    .map(|(path, _)| (path, encoded.clone()))
+                    .collect()
+            }
+        };
+
+        let results = futures::future::join_all(
+            sends
+                .iter()
+                .map(|(path, frame)| self.send_with_failover(route, path, &paths, frame)),
+        )
+        .await;
+        match self.config.mode {
+            // 复制模式只要有一份送达即可
+            MultipathMode::Replicate if results.iter().any(|result| result.is_ok()) => Ok(()),
+            _ => results.into_iter().collect::<Result<Vec<()>>>().map(|_| ()),
+        }
+    }
+
+    async fn receive(&self) -> Result<(String, Vec<u8>)> {
+        loop {
+            if let Some(message) = self.receive_state.lock().ready.pop_front() {
+                return Ok(message);
+            }
+            // 有消息在等待缺口时，最多等待一个重排超时
+            let (source, data) = if self.has_pending() {
+                match tokio::time::timeout(Duration::from_millis(self.config.reorder_timeout_ms), self.inner.receive()).await {
+                    Ok(result) => result?,
+                    Err(_) => {
+                        self.flush_stalled();
+                        continue;
+                    }
+                }
+            } else {
+                self.inner.receive().await?
+            };
+            match Frame::decode(&data) {
+                Some(frame) => self.accept_frame(&source, frame),
+                // 未启用多路径的对端直接交付
+                None => return Ok((source, data)),
+            }
+        }
+    }
+
+    fn get_stats(&self) -> TransportStats {
+        self.inner.get_stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PrivacyPerformanceConfig;
+    use crate::network::routing::selector::{MultiPathConfig, PathType};
+    use crate::network::transport::{LatencyDistribution, LinkProfile, SimulatedNetwork, SimulationConfig, TransportType};
+
+    fn path(id: &str, latency_ms: f32) -> PathInfo {
+        PathInfo {
+            path_id: id.to_string(),
+            path_type: PathType::Direct,
+            target: "b".to_string(),
+            hops: vec![format!("relay-{id}")],
+            quality: ConnectionQuality {
+                latency_ms,
+                bandwidth_mbps: 50.0,
+                packet_loss_percent: 0.0,
+                jitter_ms: 1.0,
+                reliability: 0.99,
+                stability: 0.99,
+                last_updated: Instant::now(),
+            },
+            privacy_level: 0.5,
+            performance_level: 0.9,
+            established_at: Instant::now(),
+            usage_count: 0,
+            last_used: Instant::now(),
+            is_active: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stripes_across_paths_and_reorders() {
+        let network = SimulatedNetwork::new(SimulationConfig {
+            seed: 5,
+            default_link: LinkProfile {
+                latency: LatencyDistribution::Uniform { min_ms: 5.0, max_ms: 60.0 },
+                jitter_ms: 10.0,
+                loss_rate: 0.0,
+            },
+            ..SimulationConfig::default()
+        });
+        let selector = Arc::new(PrivacyPathSelector::new(PrivacyPerformanceConfig::default()).with_multipath_config(
+            MultiPathConfig {
+                enabled: true,
+                max_paths: 3,
+                min_privacy_level: 0.0,
+                min_performance_level: 0.0,
+                load_balance_strategy: LoadBalanceStrategy::RoundRobin,
+                failover_enabled: true,
+                failover_threshold: 0.3,
+                health_check_interval_secs: 30,
+            },
+        ));
+        for (id, latency) in [("p1", 10.0), ("p2", 30.0), ("p3", 80.0)] {
+            selector.add_path("b", path(id, latency));
+        }
+        let config = MultipathTransportConfig {
+            stripe_size: 8,
+            ..MultipathTransportConfig::default()
+        };
+        let sender = MultipathTransport::new(network.endpoint("a"), selector.clone(), true, config.clone());
+        let receiver = MultipathTransport::new(network.endpoint("b"), selector.clone(), true, config);
+        let route = RouteInfo {
+            destination: "b".to_string(),
+            transport_type: TransportType::Iroh,
+            address: "b".to_string(),
+            quality_score: 1.0,
+        };
+
+        let messages: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 40]).collect();
+        for message in &messages {
+            sender.send(&route, message).await.unwrap();
+        }
+        for message in &messages {
+            assert_eq!(receiver.receive().await.unwrap(), ("a".to_string(), message.clone()));
+        }
+
+        let stats = sender.multipath_stats();
+        assert_eq!(stats.frames_sent, 25);
+        assert_eq!(stats.paths.len(), 3);
+        assert!(stats.paths.iter().all(|meter| meter.sent_frames > 0));
+        assert_eq!(receiver.multipath_stats().frames_received, 25);
+    }
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let frame = Frame {
+            session: 7,
+            seq: 42,
+            index: 1,
+            count: 3,
+            payload: b"chunk".to_vec(),
+        };
+        assert_eq!(Frame::decode(&frame.encode()), Some(frame));
+        assert_eq!(Frame::decode(b"plain message without header"), None);
+    }
+}
@@ -0,0 +1,418 @@
+//! 连接池
+//!
+//! 统一管理到各节点的连接：
+//! - 每个节点最多 `max_per_peer` 条连接，总数不超过 `max_total`，超出上限时复用负载最低的连接
+//! - 空闲超过 `keepalive_interval` 的连接发送保活探测，连续失败 `max_probe_failures` 次后驱逐
+//! - 空闲超过 `idle_timeout` 的连接回收
+//! - 按使用频率对最常访问的目标预建连接，首条消息不必等待握手
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 连接的建立与探测，由具体传输实现
+pub trait PoolConnector: Send + Sync {
+    type Connection: Clone + Send + Sync;
+
+    fn connect(&self, peer: &str) -> impl Future<Output = Result<Self::Connection>> + Send;
+
+    /// 保活探测
+    fn probe(&self, connection: &Self::Connection) -> impl Future<Output = Result<()>> + Send;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoolConfig {
+    /// 每个节点的最大连接数
+    pub max_per_peer: usize,
+    /// 连接总数上限
+    pub max_total: usize,
+    /// 空闲多久后发送保活探测（秒）
+    pub keepalive_interval_secs: u64,
+    /// 空闲多久后回收连接（秒）
+    pub idle_timeout_secs: u64,
+    /// 连续探测失败多少次后驱逐
+    pub max_probe_failures: u32,
+    /// 预建连接的目标数
+    pub prewarm_peers: usize,
+    /// 目标至少被使用多少次才参与预建
+    pub prewarm_min_uses: u64,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_per_peer: 4,
+            max_total: 100,
+            keepalive_interval_secs: 15,
+            idle_timeout_secs: 300,
+            max_probe_failures: 2,
+            prewarm_peers: 3,
+            prewarm_min_uses: 5,
+        }
+    }
+}
+
+/// 连接池统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolStats {
+    pub open_connections: usize,
+    pub idle_connections: usize,
+    pub pooled_peers: usize,
+    pub created: u64,
+    pub reused: u64,
+    pub prewarmed: u64,
+    pub probes: u64,
+    pub probe_failures: u64,
+    /// 因探测失败或被标记损坏而驱逐的连接数
+    pub evicted_unhealthy: u64,
+    /// 因长时间空闲而回收的连接数
+    pub reclaimed_idle: u64,
+}
+
+struct PoolEntry<C> {
+    id: u64,
+    connection: C,
+    leases: Arc<AtomicUsize>,
+    broken: Arc<AtomicBool>,
+    last_used: Arc<Mutex<Instant>>,
+    probe_failures: u32,
+}
+
+/// 借出的连接，释放时归还连接池
+pub struct PooledConnection<C> {
+    connection: C,
+    leases: Arc<AtomicUsize>,
+    broken: Arc<AtomicBool>,
+    last_used: Arc<Mutex<Instant>>,
+}
+
+impl<C> PooledConnection<C> {
+    pub fn connection(&self) -> &C {
+        &self.connection
+    }
+
+    /// 发送失败时标记连接损坏，下次维护或借出时驱逐
+    pub fn mark_broken(&self) {
+        self.broken.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<C> Drop for PooledConnection<C> {
+    fn drop(&mut self) {
+        *self.last_used.lock() = Instant::now();
+        self.leases.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct PoolState<C> {
+    entries: HashMap<String, Vec<PoolEntry<C>>>,
+    /// 目标 -> 累计借出次数
+    usage: HashMap<String, u64>,
+    next_id: u64,
+    stats: PoolStats,
+}
+
+impl<C> PoolState<C> {
+    fn total(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    fn lease(&mut self, peer: &str, index: usize) -> Option<PooledConnection<C>>
+    where
+        C: Clone,
+    {
+        let entry = self.entries.get(peer)?.get(index)?;
+        entry.leases.fetch_add(1, Ordering::Relaxed);
+        *entry.last_used.lock() = Instant::now();
+        Some(PooledConnection {
+            connection: entry.connection.clone(),
+            leases: entry.leases.clone(),
+            broken: entry.broken.clone(),
+            last_used: entry.last_used.clone(),
+        })
+    }
+}
+
+pub struct ConnectionPool<P: PoolConnector> {
+    connector: P,
+    config: PoolConfig,
+    state: Mutex<PoolState<P::Connection>>,
+}
+
+impl<P: PoolConnector> ConnectionPool<P> {
+    pub fn new(connector: P, config: PoolConfig) -> Self {
+        Self {
+            connector,
+            config,
+            state: Mutex::new(PoolState {
+                entries: HashMap::new(),
+                usage: HashMap::new(),
+                next_id: 0,
+                stats: PoolStats::default(),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    /// 借出到 `peer` 的连接：优先复用空闲连接，未达上限时新建，否则复用负载最低的连接
+    pub async fn acquire(&self, peer: &str) -> Result<PooledConnection<P::Connection>> {
+        {
+            let mut state = self.state.lock();
+            *state.usage.entry(peer.to_string()).or_insert(0) += 1;
+            let evicted = state.entries.get_mut(peer).map_or(0, |entries| {
+                let before = entries.len();
+                entries.retain(|entry| !entry.broken.load(Ordering::Relaxed));
+                before - entries.len()
+            });
+            state.stats.evicted_unhealthy += evicted as u64;
+
+            let peer_count = state.entries.get(peer).map_or(0, Vec::len);
+            let least_loaded = state.entries.get(peer).and_then(|entries| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, entry)| entry.leases.load(Ordering::Relaxed))
+                    .map(|(index, entry)| (index, entry.leases.load(Ordering::Relaxed)))
+            });
+            let at_capacity = peer_count >= self.config.max_per_peer.max(1) || state.total() >= self.config.max_total;
+            if let Some((index, leases)) = least_loaded {
+                if leases == 0 || at_capacity {
+                    state.stats.reused += 1;
+                    return state.lease(peer, index).ok_or_else(|| anyhow!("连接池条目已失效"));
+                }
+            } else if state.total() >= self.config.max_total {
+                return Err(anyhow!("连接池已满（{} 条连接）", self.config.max_total));
+            }
+        }
+
+        let connection = self.connector.connect(peer).await?;
+        let mut state = self.state.lock();
+        state.stats.created += 1;
+        let index = Self::insert(&mut state, peer, connection);
+        state.lease(peer, index).ok_or_else(|| anyhow!("连接池条目已失效"))
+    }
+
+    fn insert(state: &mut PoolState<P::Connection>, peer: &str, connection: P::Connection) -> usize {
+        let id = state.next_id;
+        state.next_id += 1;
+        let entries = state.entries.entry(peer.to_string()).or_default();
+        entries.push(PoolEntry {
+            id,
+            connection,
+            leases: Arc::new(AtomicUsize::new(0)),
+            broken: Arc::new(AtomicBool::new(false)),
+            last_used: Arc::new(Mutex::new(Instant::now())),
+            probe_failures: 0,
+        });
+        entries.len() - 1
+    }
+
+    /// 主动关闭到某节点的全部连接
+    pub fn remove_peer(&self, peer: &str) {
+        self.state.lock().entries.remove(peer);
+    }
+
+    /// 周期维护：探测空闲连接、驱逐失效连接、回收长时间空闲的连接并预建常用目标的连接
+    pub async fn maintain(&self) {
+        self.maintain_at(Instant::now()).await
+    }
+
+    pub async fn maintain_at(&self, now: Instant) {
+        let keepalive = Duration::from_secs(self.config.keepalive_interval_secs);
+        let idle_timeout = Duration::from_secs(self.config.idle_timeout_secs);
+
+        // 回收并挑出需要探测的连接；探测期间不持有锁
+        let to_probe: Vec<(String, u64, P::Connection)> = {
+            let mut state = self.state.lock();
+            let mut reclaimed = 0;
+            let mut evicted = 0;
+            for entries in state.entries.values_mut() {
+                entries.retain(|entry| {
+                    let idle = entry.leases.load(Ordering::Relaxed) == 0;
+                    if entry.broken.load(Ordering::Relaxed) {
+                        evicted += 1;
+                        false
+                    } else if idle && now.saturating_duration_since(*entry.last_used.lock()) >= idle_timeout {
+                        reclaimed += 1;
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+            state.entries.retain(|_, entries| !entries.is_empty());
+            state.stats.reclaimed_idle += reclaimed;
+            state.stats.evicted_unhealthy += evicted;
+            state
+                .entries
+                .iter()
+                .flat_map(|(peer, entries)| {
+                    entries
+                        .iter()
+                        .filter(|entry| {
+                            entry.leases.load(Ordering::Relaxed) == 0
+                                && now.saturating_duration_since(*entry.last_used.lock()) >= keepalive
+                        })
+                        .map(|entry| (peer.clone(), entry.id, entry.connection.clone()))
+                })
+                .collect()
+        };
+
+        for (peer, id, connection) in to_probe {
+            let ok = self.connector.probe(&connection).await.is_ok();
+            let mut state = self.state.lock();
+            state.stats.probes += 1;
+            if !ok {
+                state.stats.probe_failures += 1;
+            }
+            let max_failures = self.config.max_probe_failures.max(1);
+            let mut evicted = false;
+            if let Some(entries) = state.entries.get_mut(&peer) {
+                if let Some(position) = entries.iter().position(|entry| entry.id == id) {
+                    let entry = &mut entries[position];
+                    if ok {
+                        entry.probe_failures = 0;
+                        // 探测成功视为一次活动，推迟下一次探测
+                        *entry.last_used.lock() = now;
+                    } else {
+                        entry.probe_failures += 1;
+                        if entry.probe_failures >= max_failures {
+                            entries.remove(position);
+                            evicted = true;
+                        }
+                    }
+                }
+            }
+            if evicted {
+                state.stats.evicted_unhealthy += 1;
+                state.entries.retain(|_, entries| !entries.is_empty());
+            }
+        }
+
+        self.prewarm().await;
+    }
+
+    /// 对使用最频繁但当前没有连接的目标预建连接
+    async fn prewarm(&self) {
+        let targets: Vec<String> = {
+            let state = self.state.lock();
+            let mut frequent: Vec<(&String, &u64)> = state
+                .usage
+                .iter()
+                .filter(|(_, uses)| **uses >= self.config.prewarm_min_uses)
+                .collect();
+            frequent.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            let room = self.config.max_total.saturating_sub(state.total());
+            frequent
+                .into_iter()
+                .take(self.config.prewarm_peers)
+                .filter(|(peer, _)| !state.entries.contains_key(*peer))
+                .take(room)
+                .map(|(peer, _)| peer.clone())
+                .collect()
+        };
+
+        for peer in targets {
+            match self.connector.connect(&peer).await {
+                Ok(connection) => {
+                    let mut state = self.state.lock();
+                    Self::insert(&mut state, &peer, connection);
+                    state.stats.prewarmed += 1;
+                }
+                Err(e) => log::debug!("[连接池] 预建到 {} 的连接失败: {}", peer, e),
+            }
+        }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let state = self.state.lock();
+        let mut stats = state.stats.clone();
+        stats.open_connections = state.total();
+        stats.idle_connections = state
+            .entries
+            .values()
+            .flatten()
+            .filter(|entry| entry.leases.load(Ordering::Relaxed) == 0)
+            .count();
+        stats.pooled_peers = state.entries.len();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeConnector {
+        connects: AtomicUsize,
+        healthy: AtomicBool,
+    }
+
+    impl PoolConnector for Arc<FakeConnector> {
+        type Connection = usize;
+
+        async fn connect(&self, _peer: &str) -> Result<usize> {
+            Ok(self.connects.fetch_add(1, Ordering::Relaxed))
+        }
+
+        async fn probe(&self, _connection: &usize) -> Result<()> {
+            if self.healthy.load(Ordering::Relaxed) {
+                Ok(())
+            } else {
+                Err(anyhow!("no pong"))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caps_probes_reclaims_and_prewarms() {
+        let connector = Arc::new(FakeConnector::default());
+        connector.healthy.store(true, Ordering::Relaxed);
+        let pool = ConnectionPool::new(
+            connector.clone(),
+            PoolConfig {
+                max_per_peer: 2,
+                prewarm_min_uses: 3,
+                ..PoolConfig::default()
+            },
+        );
+
+        // 并发借出时新建连接，达到上限后复用
+        let a = pool.acquire("peer-a").await.unwrap();
+        let b = pool.acquire("peer-a").await.unwrap();
+        let c = pool.acquire("peer-a").await.unwrap();
+        assert_ne!(a.connection(), b.connection());
+        assert_eq!(pool.stats().open_connections, 2);
+        drop((a, b, c));
+        // 归还后再借出复用空闲连接
+        drop(pool.acquire("peer-a").await.unwrap());
+        assert_eq!(connector.connects.load(Ordering::Relaxed), 2);
+
+        // 探测失败两次后驱逐
+        connector.healthy.store(false, Ordering::Relaxed);
+        let later = Instant::now() + Duration::from_secs(20);
+        pool.maintain_at(later).await;
+        assert_eq!(pool.stats().probe_failures, 2);
+        pool.maintain_at(later + Duration::from_secs(20)).await;
+        let stats = pool.stats();
+        assert_eq!(stats.evicted_unhealthy, 2);
+        // peer-a 被频繁使用，驱逐后重新预建
+        assert_eq!(stats.prewarmed, 1);
+        assert_eq!(stats.open_connections, 1);
+
+        // 长时间空闲的连接被回收（随后又因使用频繁而预建）
+        connector.healthy.store(true, Ordering::Relaxed);
+        pool.maintain_at(later + Duration::from_secs(3600)).await;
+        assert_eq!(pool.stats().reclaimed_idle, 1);
+    }
+}
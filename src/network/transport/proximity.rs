@@ -0,0 +1,397 @@
+//! 近场传输实现
+//!
+//! 同一局域网或物理相邻的设备之间直接传输分片，不经过互联网：
+//! - Android：WiFi Direct，通过 JNI 钩子（见 `android::proximity`）
+//! - Apple 平台：AWDL/Bonjour，通过 C 接口由 Swift 侧驱动
+//!
+//! 平台层只负责发现节点和收发字节帧，统一汇入 [`ProximityHub`]；
+//! [`ProximityTransport`] 包装常规传输，协商成功时走近场链路，失败时自动回退。
+
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::sync::{mpsc, Mutex};
+
+use super::{RouteInfo, Transport, TransportStats, TransportType};
+
+/// 近场链路类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ProximityKind {
+    /// Android WiFi Direct
+    WifiDirect,
+    /// Apple AWDL / Bonjour
+    Awdl,
+}
+
+/// 平台近场链路（由 JNI 或 Swift 侧实现发送）
+pub trait ProximityLink: Send + Sync {
+    fn kind(&self) -> ProximityKind;
+
+    /// 向相邻节点发送一帧数据
+    fn send(&self, peer_id: &str, data: &[u8]) -> Result<()>;
+}
+
+/// 平台层报告的相邻节点
+#[derive(Debug, Clone)]
+pub struct NearbyPeer {
+    pub peer_id: String,
+    /// 平台地址（WiFi Direct 的 MAC/IP 或 Bonjour 服务名）
+    pub address: String,
+    pub discovered_at: Instant,
+}
+
+/// 近场中枢：平台回调的汇聚点
+pub struct ProximityHub {
+    link: RwLock<Option<Arc<dyn ProximityLink>>>,
+    nearby: RwLock<HashMap<String, NearbyPeer>>,
+    inbound_tx: mpsc::UnboundedSender<(String, Vec<u8>)>,
+    inbound_rx: Mutex<Option<mpsc::UnboundedReceiver<(String, Vec<u8>)>>>,
+}
+
+impl ProximityHub {
+    pub fn new() -> Self {
+        let (inbound_tx, inbound_rx) = mpsc::unbounded_channel();
+        Self {
+            link: RwLock::new(None),
+            nearby: RwLock::new(HashMap::new()),
+            inbound_tx,
+            inbound_rx: Mutex::new(Some(inbound_rx)),
+        }
+    }
+
+    /// 注册平台链路
+    pub fn set_link(&self, link: Arc<dyn ProximityLink>) {
+        log::info!("[近场] 注册链路: {:?}", link.kind());
+        *self.link.write() = Some(link);
+    }
+
+    /// 注销平台链路（例如 WiFi Direct 组解散）
+    pub fn clear_link(&self) {
+        *self.link.write() = None;
+        self.nearby.write().clear();
+    }
+
+    pub fn link(&self) -> Option<Arc<dyn ProximityLink>> {
+        self.link.read().clone()
+    }
+
+    pub fn peer_found(&self, peer_id: &str, address: &str) {
+        self.nearby.write().insert(
+            peer_id.to_string(),
+            NearbyPeer {
+                peer_id: peer_id.to_string(),
+                address: address.to_string(),
+                discovered_at: Instant::now(),
+            },
+        );
+    }
+
+    pub fn peer_lost(&self, peer_id: &str) {
+        self.nearby.write().remove(peer_id);
+    }
+
+    pub fn is_nearby(&self, peer_id: &str) -> bool {
+        self.nearby.read().contains_key(peer_id)
+    }
+
+    pub fn nearby_peers(&self) -> Vec<NearbyPeer> {
+        self.nearby.read().values().cloned().collect()
+    }
+
+    /// 平台层收到数据帧
+    pub fn deliver(&self, peer_id: &str, data: Vec<u8>) {
+        let _ = self.inbound_tx.send((peer_id.to_string(), data));
+    }
+
+    /// 取走入站接收端（只能被一个传输实例持有）
+    async fn take_inbound(&self) -> Option<mpsc::UnboundedReceiver<(String, Vec<u8>)>> {
+        self.inbound_rx.lock().await.take()
+    }
+}
+
+impl Default for ProximityHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static PROXIMITY_HUB: OnceLock<Arc<ProximityHub>> = OnceLock::new();
+
+/// 获取全局近场中枢（平台回调入口）
+pub fn proximity_hub() -> Arc<ProximityHub> {
+    PROXIMITY_HUB.get_or_init(|| Arc::new(ProximityHub::new())).clone()
+}
+
+/// 节点交换的近场能力提示
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ProximityHint {
+    /// 是否具备近场链路
+    pub capable: bool,
+    /// 本地局域网地址
+    pub lan_addrs: Vec<IpAddr>,
+}
+
+/// 协商是否走近场链路：双方都具备能力，且已被平台发现或处于同一局域网
+pub fn negotiate(local: &ProximityHint, remote: &ProximityHint, discovered_nearby: bool) -> bool {
+    if !local.capable || !remote.capable {
+        return false;
+    }
+    discovered_nearby
+        || local
+            .lan_addrs
+            .iter()
+            .any(|a| remote.lan_addrs.iter().any(|b| same_subnet(a, b)))
+}
+
+/// 判断两个地址是否在同一子网（IPv4 /24，IPv6 /64）
+fn same_subnet(a: &IpAddr, b: &IpAddr) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            (a.is_private() || a.is_link_local()) && a.octets()[..3] == b.octets()[..3]
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => a.segments()[..4] == b.segments()[..4],
+        _ => false,
+    }
+}
+
+/// 探测本机的局域网地址
+///
+/// 通过 UDP "连接" 公网地址让系统选择出口网卡，不会真正发送数据。
+pub fn local_lan_addrs() -> Vec<IpAddr> {
+    ["8.8.8.8:80", "[2001:4860:4860::8888]:80"]
+        .iter()
+        .filter_map(|target| {
+            let bind = if target.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" };
+            let socket = std::net::UdpSocket::bind(bind).ok()?;
+            socket.connect(target).ok()?;
+            socket.local_addr().ok().map(|addr| addr.ip())
+        })
+        .filter(|ip| !ip.is_unspecified() && !ip.is_loopback())
+        .collect()
+}
+
+/// 近场传输：在常规传输之上叠加近场链路
+pub struct ProximityTransport<T: Transport> {
+    fallback: T,
+    hub: Arc<ProximityHub>,
+    enabled: bool,
+    local_hint: ProximityHint,
+    peer_hints: RwLock<HashMap<String, ProximityHint>>,
+    inbound: Mutex<Option<mpsc::UnboundedReceiver<(String, Vec<u8>)>>>,
+    stats: RwLock<ProximityStats>,
+}
+
+/// 近场传输统计
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ProximityStats {
+    pub proximity_sent_bytes: u64,
+    pub proximity_received_bytes: u64,
+    pub fallback_sends: u64,
+    pub proximity_failures: u64,
+}
+
+impl<T: Transport> ProximityTransport<T> {
+    /// `enabled` 为 false 时完全等价于回退传输
+    pub async fn new(fallback: T, hub: Arc<ProximityHub>, enabled: bool, local_lan_addrs: Vec<IpAddr>) -> Self {
+        let inbound = if enabled { hub.take_inbound().await } else { None };
+        let local_hint = ProximityHint {
+            capable: enabled && hub.link().is_some(),
+            lan_addrs: local_lan_addrs,
+        };
+        Self {
+            fallback,
+            hub,
+            enabled,
+            local_hint,
+            peer_hints: RwLock::new(HashMap::new()),
+            inbound: Mutex::new(inbound),
+            stats: RwLock::new(ProximityStats::default()),
+        }
+    }
+
+    /// 本节点的能力提示（随握手发送给对端）
+    pub fn local_hint(&self) -> ProximityHint {
+        ProximityHint {
+            capable: self.enabled && self.hub.link().is_some(),
+            ..self.local_hint.clone()
+        }
+    }
+
+    /// 记录对端的能力提示
+    pub fn register_peer_hint(&self, peer_id: &str, hint: ProximityHint) {
+        self.peer_hints.write().insert(peer_id.to_string(), hint);
+    }
+
+    /// 是否对该节点使用近场链路
+    pub fn uses_proximity(&self, peer_id: &str) -> bool {
+        let Some(remote) = self.peer_hints.read().get(peer_id).cloned() else {
+            return false;
+        };
+        negotiate(&self.local_hint(), &remote, self.hub.is_nearby(peer_id))
+    }
+
+    pub fn proximity_stats(&self) -> ProximityStats {
+        self.stats.read().clone()
+    }
+
+    fn try_proximity_send(&self, peer_id: &str, message: &[u8]) -> Result<()> {
+        let link = self.hub.link().ok_or_else(|| anyhow!("近场链路不可用"))?;
+        link.send(peer_id, message)?;
+        self.stats.write().proximity_sent_bytes += message.len() as u64;
+        Ok(())
+    }
+}
+
+impl<T: Transport> Transport for ProximityTransport<T> {
+    async fn send(&self, route: &RouteInfo, message: &[u8]) -> Result<()> {
+        if self.uses_proximity(&route.destination) {
+            match self.try_proximity_send(&route.destination, message) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    log::warn!("[近场] 发送到 {} 失败，回退常规路径: {}", route.destination, e);
+                    self.stats.write().proximity_failures += 1;
+                    self.hub.peer_lost(&route.destination);
+                }
+            }
+        }
+        self.stats.write().fallback_sends += 1;
+        let fallback_route = RouteInfo {
+            transport_type: TransportType::Iroh,
+            ..route.clone()
+        };
+        self.fallback.send(&fallback_route, message).await
+    }
+
+    async fn receive(&self) -> Result<(String, Vec<u8>)> {
+        let mut inbound = self.inbound.lock().await;
+        let Some(rx) = inbound.as_mut() else {
+            drop(inbound);
+            return self.fallback.receive().await;
+        };
+
+        let proximity = Box::pin(rx.recv());
+        let fallback = Box::pin(self.fallback.receive());
+        match futures::future::select(proximity, fallback).await {
+            futures::future::Either::Left((Some((peer, data)), _)) => {
+                self.stats.write().proximity_received_bytes += data.len() as u64;
+                Ok((peer, data))
+            }
+            futures::future::Either::Left((None, fallback)) => fallback.await,
+            futures::future::Either::Right((result, _)) => result,
+        }
+    }
+
+    fn get_stats(&self) -> TransportStats {
+        let mut stats = self.fallback.get_stats();
+        let proximity = self.stats.read();
+        stats.total_sent_bytes += proximity.proximity_sent_bytes;
+        stats.total_received_bytes += proximity.proximity_received_bytes;
+        stats.failed_sends += proximity.proximity_failures;
+        stats
+    }
+}
+
+/// Apple 平台 C 接口（由 Swift 的 Network.framework / Bonjour 层调用）
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub mod apple {
+    use super::*;
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_int};
+
+    /// Swift 侧发送回调：返回 0 表示成功
+    pub type AwdlSendFn = extern "C" fn(peer_id: *const c_char, data: *const u8, len: usize) -> c_int;
+
+    struct AwdlLink {
+        send_fn: AwdlSendFn,
+    }
+
+    impl ProximityLink for AwdlLink {
+        fn kind(&self) -> ProximityKind {
+            ProximityKind::Awdl
+        }
+
+        fn send(&self, peer_id: &str, data: &[u8]) -> Result<()> {
+            let peer = std::ffi::CString::new(peer_id)?;
+            match (self.send_fn)(peer.as_ptr(), data.as_ptr(), data.len()) {
+                0 => Ok(()),
+                code => Err(anyhow!("AWDL 发送失败: {}", code)),
+            }
+        }
+    }
+
+    unsafe fn c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+        if ptr.is_null() {
+            return None;
+        }
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+
+    /// 注册 AWDL 发送回调
+    #[no_mangle]
+    pub extern "C" fn williw_proximity_register_awdl(send_fn: AwdlSendFn) {
+        proximity_hub().set_link(Arc::new(AwdlLink { send_fn }));
+    }
+
+    /// 注销 AWDL 链路
+    #[no_mangle]
+    pub extern "C" fn williw_proximity_unregister() {
+        proximity_hub().clear_link();
+    }
+
+    /// Bonjour 发现节点
+    #[no_mangle]
+    pub unsafe extern "C" fn williw_proximity_peer_found(peer_id: *const c_char, address: *const c_char) {
+        if let (Some(peer), Some(addr)) = (c_str(peer_id), c_str(address)) {
+            proximity_hub().peer_found(peer, addr);
+        }
+    }
+
+    /// 节点离开
+    #[no_mangle]
+    pub unsafe extern "C" fn williw_proximity_peer_lost(peer_id: *const c_char) {
+        if let Some(peer) = c_str(peer_id) {
+            proximity_hub().peer_lost(peer);
+        }
+    }
+
+    /// 收到数据帧
+    #[no_mangle]
+    pub unsafe extern "C" fn williw_proximity_on_frame(peer_id: *const c_char, data: *const u8, len: usize) {
+        if data.is_null() {
+            return;
+        }
+        if let Some(peer) = c_str(peer_id) {
+            let frame = std::slice::from_raw_parts(data, len).to_vec();
+            proximity_hub().deliver(peer, frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_same_lan() {
+        let local = ProximityHint {
+            capable: true,
+            lan_addrs: vec!["192.168.1.10".parse().unwrap()],
+        };
+        let remote = ProximityHint {
+            capable: true,
+            lan_addrs: vec!["192.168.1.22".parse().unwrap()],
+        };
+        let far = ProximityHint {
+            capable: true,
+            lan_addrs: vec!["10.0.5.3".parse().unwrap()],
+        };
+
+        assert!(negotiate(&local, &remote, false));
+        assert!(!negotiate(&local, &far, false));
+        assert!(negotiate(&local, &far, true));
+        assert!(!negotiate(&ProximityHint::default(), &remote, true));
+    }
+}
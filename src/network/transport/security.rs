@@ -0,0 +1,520 @@
+//! 传输通道加密模块
+//!
+//! 为 QUIC 网关提供与节点身份绑定的加密通道：
+//! - TLS 1.3：自签名证书直接使用节点身份的 Ed25519 密钥，对端按宣告的节点 ID 固定（pin）证书公钥，
+//!   不依赖任何 CA
+//! - Noise XX：可选替代方案，握手载荷中携带身份密钥对 Noise 静态公钥的签名，完成双向身份绑定
+//!
+//! 节点 ID 即身份公钥的 base58 编码，因此证书校验只需比对证书公钥与节点 ID。
+
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::{ClientConfig, DigitallySignedStruct, DistinguishedName, ServerConfig, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+
+/// 证书 SAN 后缀：`<node_id>.williw`
+const CERT_NAME_SUFFIX: &str = "williw";
+/// Ed25519 私钥 PKCS#8 v1 编码前缀（后接 32 字节种子）
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+/// Noise 单条消息上限
+pub const NOISE_MAX_MESSAGE_LEN: usize = 65535;
+const NOISE_TAG_LEN: usize = 16;
+
+/// 通道加密方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ChannelSecurity {
+    /// 不加密（仅用于本地调试）
+    None,
+    /// TLS 1.3，证书固定到节点身份
+    #[default]
+    Tls13,
+    /// Noise XX 握手
+    NoiseXX,
+}
+
+/// 对端身份校验失败原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PeerVerificationError {
+    #[error("证书解析失败: {0}")]
+    InvalidCertificate(String),
+
+    #[error("证书公钥不是 Ed25519")]
+    UnsupportedKey,
+
+    #[error("证书已过期或尚未生效")]
+    Expired,
+
+    #[error("节点 ID 不匹配: 期望 {expected}, 实际 {actual}")]
+    NodeIdMismatch { expected: String, actual: String },
+
+    #[error("Noise 身份签名无效")]
+    BadIdentityBinding,
+}
+
+/// 与节点身份绑定的自签名证书
+#[derive(Clone)]
+pub struct NodeCertificate {
+    pub node_id: String,
+    cert_der: CertificateDer<'static>,
+    key_der: Vec<u8>,
+}
+
+impl NodeCertificate {
+    /// 使用节点身份密钥生成自签名证书
+    pub fn generate(identity: &NodeIdentity) -> Result<Self> {
+        let node_id = identity.node_id();
+        let secret = identity.secret_bytes();
+        let mut key_der = ED25519_PKCS8_PREFIX.to_vec();
+        key_der.extend_from_slice(secret.as_slice());
+
+        let key_pair = rcgen::KeyPair::try_from(key_der.as_slice())?;
+        let mut params = rcgen::CertificateParams::new(vec![format!("{}.{}", node_id, CERT_NAME_SUFFIX)])?;
+        params.distinguished_name.push(rcgen::DnType::CommonName, node_id.clone());
+        let cert = params.self_signed(&key_pair)?;
+
+        Ok(Self {
+            node_id,
+            cert_der: cert.der().clone(),
+            key_der,
+        })
+    }
+
+    pub fn cert_der(&self) -> &CertificateDer<'static> {
+        &self.cert_der
+    }
+
+    fn private_key(&self) -> PrivateKeyDer<'static> {
+        PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(self.key_der.clone()))
+    }
+}
+
+impl Drop for NodeCertificate {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.key_der.zeroize();
+    }
+}
+
+/// 从证书中提取节点 ID（校验自签名和有效期）
+pub fn node_id_from_certificate(der: &[u8]) -> Result<String, PeerVerificationError> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der)
+        .map_err(|e| PeerVerificationError::InvalidCertificate(e.to_string()))?;
+    let spki = cert.public_key();
+    if spki.algorithm.algorithm != x509_parser::oid_registry::OID_SIG_ED25519 {
+        return Err(PeerVerificationError::UnsupportedKey);
+    }
+    cert.verify_signature(None)
+        .map_err(|e| PeerVerificationError::InvalidCertificate(e.to_string()))?;
+    if !cert.validity().is_valid() {
+        return Err(PeerVerificationError::Expired);
+    }
+    Ok(bs58::encode(spki.subject_public_key.data.as_ref()).into_string())
+}
+
+/// 校验证书属于期望的节点
+pub fn verify_peer_certificate(der: &[u8], expected_node_id: &str) -> Result<(), PeerVerificationError> {
+    let actual = node_id_from_certificate(der)?;
+    if actual != expected_node_id {
+        return Err(PeerVerificationError::NodeIdMismatch {
+            expected: expected_node_id.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn to_tls_error(err: PeerVerificationError) -> rustls::Error {
+    rustls::Error::General(err.to_string())
+}
+
+/// 客户端侧：服务端证书必须属于宣告的节点 ID
+#[derive(Debug)]
+struct PinnedServerVerifier {
+    expected_node_id: String,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        verify_peer_certificate(end_entity, &self.expected_node_id).map_err(to_tls_error)?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+}
+
+/// 服务端侧：连接前无法得知对端 ID，只校验证书本身，
+/// 握手完成后由调用方通过 [`peer_node_id`] 取得对端节点 ID
+#[derive(Debug)]
+struct NodeClientVerifier {
+    provider: Arc<CryptoProvider>,
+}
+
+impl ClientCertVerifier for NodeClientVerifier {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        node_id_from_certificate(end_entity).map_err(to_tls_error)?;
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+}
+
+/// 从 TLS 连接的对端证书链中取出节点 ID
+pub fn peer_node_id(certs: Option<&[CertificateDer<'_>]>) -> Result<String, PeerVerificationError> {
+    let end_entity = certs
+        .and_then(|certs| certs.first())
+        .ok_or_else(|| PeerVerificationError::InvalidCertificate("对端未提供证书".to_string()))?;
+    node_id_from_certificate(end_entity)
+}
+
+/// Noise 握手中的身份载荷：身份密钥对 Noise 静态公钥的签名
+#[derive(Serialize, Deserialize)]
+struct NoiseIdentityPayload {
+    node_id: String,
+    signature: String,
+}
+
+fn noise_binding_bytes(static_key: &[u8]) -> Vec<u8> {
+    let mut bytes = b"williw-noise-static:".to_vec();
+    bytes.extend_from_slice(static_key);
+    bytes
+}
+
+/// Noise XX 握手
+///
+/// 消息顺序：发起方写 → 响应方写 → 发起方写。
+/// 双方的身份载荷都在已加密的握手消息中发送，不会暴露节点 ID。
+pub struct NoiseHandshake {
+    state: snow::HandshakeState,
+    local_payload: Vec<u8>,
+    writes: usize,
+    remote_node_id: Option<String>,
+}
+
+impl NoiseHandshake {
+    pub fn initiator(identity: &NodeIdentity) -> Result<Self> {
+        Self::build(identity, true)
+    }
+
+    pub fn responder(identity: &NodeIdentity) -> Result<Self> {
+        Self::build(identity, false)
+    }
+
+    fn build(identity: &NodeIdentity, initiator: bool) -> Result<Self> {
+        let builder = snow::Builder::new(NOISE_PARAMS.parse()?);
+        // 每次握手使用新的静态密钥，身份由签名绑定
+        let keypair = builder.generate_keypair()?;
+        let signature = identity.sign(&noise_binding_bytes(&keypair.public));
+        let local_payload = serde_json::to_vec(&NoiseIdentityPayload {
+            node_id: signature.pubkey,
+            signature: signature.signature,
+        })?;
+        let builder = builder.local_private_key(&keypair.private);
+        let state = if initiator {
+            builder.build_initiator()?
+        } else {
+            builder.build_responder()?
+        };
+        Ok(Self {
+            state,
+            local_payload,
+            writes: 0,
+            remote_node_id: None,
+        })
+    }
+
+    /// 生成下一条握手消息
+    pub fn write_message(&mut self) -> Result<Vec<u8>> {
+        // 发起方的第一条消息尚未加密，不携带身份
+        let carries_identity = if self.state.is_initiator() { self.writes == 1 } else { self.writes == 0 };
+        let payload: &[u8] = if carries_identity { &self.local_payload } else { &[] };
+        let mut buf = vec![0u8; NOISE_MAX_MESSAGE_LEN];
+        let len = self.state.write_message(payload, &mut buf)?;
+        buf.truncate(len);
+        self.writes += 1;
+        Ok(buf)
+    }
+
+    /// 处理对端的握手消息
+    pub fn read_message(&mut self, message: &[u8]) -> Result<()> {
+        let mut buf = vec![0u8; NOISE_MAX_MESSAGE_LEN];
+        let len = self.state.read_message(message, &mut buf)?;
+        if len == 0 {
+            return Ok(());
+        }
+
+        let payload: NoiseIdentityPayload = serde_json::from_slice(&buf[..len])?;
+        let remote_static = self
+            .state
+            .get_remote_static()
+            .ok_or_else(|| anyhow!("对端未发送 Noise 静态公钥"))?;
+        let signature = SolSignature {
+            pubkey: payload.node_id.clone(),
+            signature: payload.signature,
+        };
+        if !verify_signature(&noise_binding_bytes(remote_static), &signature) {
+            return Err(PeerVerificationError::BadIdentityBinding.into());
+        }
+        self.remote_node_id = Some(payload.node_id);
+        Ok(())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.is_handshake_finished()
+    }
+
+    /// 已验证的对端节点 ID
+    pub fn remote_node_id(&self) -> Option<&str> {
+        self.remote_node_id.as_deref()
+    }
+
+    /// 握手完成后转换为加密通道；`expected_node_id` 为宣告的对端 ID（若已知）
+    pub fn into_channel(self, expected_node_id: Option<&str>) -> Result<NoiseChannel> {
+        let remote_node_id = self
+            .remote_node_id
+            .ok_or_else(|| anyhow!("Noise 握手未完成身份验证"))?;
+        if let Some(expected) = expected_node_id {
+            if expected != remote_node_id {
+                return Err(PeerVerificationError::NodeIdMismatch {
+                    expected: expected.to_string(),
+                    actual: remote_node_id,
+                }
+                .into());
+            }
+        }
+        Ok(NoiseChannel {
+            transport: self.state.into_transport_mode()?,
+            remote_node_id,
+        })
+    }
+}
+
+/// Noise 加密通道
+pub struct NoiseChannel {
+    transport: snow::TransportState,
+    remote_node_id: String,
+}
+
+impl NoiseChannel {
+    pub fn remote_node_id(&self) -> &str {
+        &self.remote_node_id
+    }
+
+    /// 加密单条消息（明文不超过 `NOISE_MAX_MESSAGE_LEN - 16` 字节）
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        if plaintext.len() > NOISE_MAX_MESSAGE_LEN - NOISE_TAG_LEN {
+            return Err(anyhow!("消息过大: {} 字节", plaintext.len()));
+        }
+        let mut buf = vec![0u8; plaintext.len() + NOISE_TAG_LEN];
+        let len = self.transport.write_message(plaintext, &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    pub fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let mut buf = vec![0u8; ciphertext.len()];
+        let len = self.transport.read_message(ciphertext, &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+}
+
+/// 通道加密上下文
+///
+/// 证书按需从当前身份生成；密钥轮换后节点 ID 变化，证书会自动重新生成。
+pub struct ChannelSecurityContext {
+    mode: ChannelSecurity,
+    identity: Arc<NodeIdentity>,
+    certificate: RwLock<Option<NodeCertificate>>,
+}
+
+impl ChannelSecurityContext {
+    pub fn new(mode: ChannelSecurity, identity: Arc<NodeIdentity>) -> Self {
+        Self {
+            mode,
+            identity,
+            certificate: RwLock::new(None),
+        }
+    }
+
+    pub fn mode(&self) -> ChannelSecurity {
+        self.mode
+    }
+
+    pub fn node_id(&self) -> String {
+        self.identity.node_id()
+    }
+
+    /// 当前身份对应的证书
+    pub fn certificate(&self) -> Result<NodeCertificate> {
+        let node_id = self.identity.node_id();
+        if let Some(cert) = self.certificate.read().as_ref().filter(|cert| cert.node_id == node_id) {
+            return Ok(cert.clone());
+        }
+        let cert = NodeCertificate::generate(&self.identity)?;
+        *self.certificate.write() = Some(cert.clone());
+        Ok(cert)
+    }
+
+    /// TLS 1.3 服务端配置（要求客户端出示节点证书）
+    pub fn tls_server_config(&self) -> Result<Arc<ServerConfig>> {
+        let cert = self.certificate()?;
+        let provider = crypto_provider();
+        let verifier = Arc::new(NodeClientVerifier {
+            provider: provider.clone(),
+        });
+        let config = ServerConfig::builder_with_provider(provider)
+            .with_protocol_versions(&[&rustls::version::TLS13])?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(vec![cert.cert_der().clone()], cert.private_key())?;
+        Ok(Arc::new(config))
+    }
+
+    /// TLS 1.3 客户端配置，服务端证书固定到 `expected_node_id`
+    pub fn tls_client_config(&self, expected_node_id: &str) -> Result<Arc<ClientConfig>> {
+        let cert = self.certificate()?;
+        let provider = crypto_provider();
+        let verifier = Arc::new(PinnedServerVerifier {
+            expected_node_id: expected_node_id.to_string(),
+            provider: provider.clone(),
+        });
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_protocol_versions(&[&rustls::version::TLS13])?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
+            .with_client_auth_cert(vec![cert.cert_der().clone()], cert.private_key())?;
+        Ok(Arc::new(config))
+    }
+
+    /// 连接对端时使用的 TLS 服务器名
+    pub fn server_name(node_id: &str) -> Result<ServerName<'static>> {
+        ServerName::try_from(format!("{}.{}", node_id, CERT_NAME_SUFFIX))
+            .map_err(|e| anyhow!("无效的服务器名: {}", e))
+    }
+
+    pub fn noise_initiator(&self) -> Result<NoiseHandshake> {
+        NoiseHandshake::initiator(&self.identity)
+    }
+
+    pub fn noise_responder(&self) -> Result<NoiseHandshake> {
+        NoiseHandshake::responder(&self.identity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::FileKeystore;
+
+    fn identity(name: &str) -> Arc<NodeIdentity> {
+        let path = std::env::temp_dir().join(format!("williw-security-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Arc::new(NodeIdentity::with_keystore(Arc::new(FileKeystore::new(path))).unwrap())
+    }
+
+    #[test]
+    fn test_certificate_is_pinned_to_node_id() {
+        let alice = identity("alice");
+        let cert = NodeCertificate::generate(&alice).unwrap();
+        assert_eq!(node_id_from_certificate(cert.cert_der()).unwrap(), alice.node_id());
+        assert!(verify_peer_certificate(cert.cert_der(), &alice.node_id()).is_ok());
+        assert!(matches!(
+            verify_peer_certificate(cert.cert_der(), &identity("mallory").node_id()),
+            Err(PeerVerificationError::NodeIdMismatch { .. })
+        ));
+
+        let ctx = ChannelSecurityContext::new(ChannelSecurity::Tls13, alice.clone());
+        assert!(ctx.tls_server_config().is_ok());
+        assert!(ctx.tls_client_config(&alice.node_id()).is_ok());
+    }
+
+    #[test]
+    fn test_noise_xx_binds_identities() {
+        let alice = identity("noise-alice");
+        let bob = identity("noise-bob");
+        let mut initiator = NoiseHandshake::initiator(&alice).unwrap();
+        let mut responder = NoiseHandshake::responder(&bob).unwrap();
+
+        responder.read_message(&initiator.write_message().unwrap()).unwrap();
+        initiator.read_message(&responder.write_message().unwrap()).unwrap();
+        responder.read_message(&initiator.write_message().unwrap()).unwrap();
+        assert!(initiator.is_finished() && responder.is_finished());
+
+        let mut a = initiator.into_channel(Some(&bob.node_id())).unwrap();
+        let mut b = responder.into_channel(None).unwrap();
+        assert_eq!(b.remote_node_id(), alice.node_id());
+        let ciphertext = a.encrypt(b"gradient").unwrap();
+        assert_eq!(b.decrypt(&ciphertext).unwrap(), b"gradient");
+    }
+}
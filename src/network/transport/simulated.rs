@@ -0,0 +1,572 @@
+//! 确定性网络模拟传输
+//!
+//! 所有模拟节点共享一个 [`SimulatedNetwork`]：消息按虚拟时钟投递，延迟、抖动和丢包都由固定种子的
+//! 随机数生成器决定，相同的种子和发送顺序得到完全相同的结果，便于复现共识和路由问题。
+//! 命名节点之间可以设置分区，分区期间的发送直接失败，在途消息在投递时被丢弃。
+
+use super::{RouteInfo, Transport, TransportStats, TransportType};
+use anyhow::{anyhow, bail, Result};
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// 单向链路的延迟分布（毫秒）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LatencyDistribution {
+    Fixed { ms: f64 },
+    Uniform { min_ms: f64, max_ms: f64 },
+    Normal { mean_ms: f64, std_dev_ms: f64 },
+    /// 帕累托分布，模拟长尾延迟
+    Pareto { scale_ms: f64, shape: f64 },
+}
+
+impl LatencyDistribution {
+    pub fn sample(&self, rng: &mut impl Rng) -> f64 {
+        let ms = match *self {
+            Self::Fixed { ms } => ms,
+            Self::Uniform { min_ms, max_ms } if max_ms > min_ms => rng.random_range(min_ms..max_ms),
+            Self::Uniform { min_ms, .. } => min_ms,
+            Self::Normal { mean_ms, std_dev_ms } => {
+                // Box-Muller 变换
+                let u1: f64 = rng.random_range(f64::EPSILON..1.0);
+                let u2: f64 = rng.random();
+                mean_ms + std_dev_ms * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+            }
+            Self::Pareto { scale_ms, shape } => {
+                let u: f64 = rng.random_range(f64::EPSILON..1.0);
+                scale_ms / u.powf(1.0 / shape.max(f64::EPSILON))
+            }
+        };
+        ms.max(0.0)
+    }
+}
+
+impl FromStr for LatencyDistribution {
+    type Err = anyhow::Error;
+
+    /// 命令行格式：`fixed:50`、`uniform:20:80`、`normal:50:10`、`pareto:20:2.5`
+    fn from_str(spec: &str) -> Result<Self> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let num = |index: usize| -> Result<f64> {
+            parts
+                .get(index)
+                .ok_or_else(|| anyhow!("延迟分布 {} 缺少参数", spec))?
+                .trim()
+                .parse()
+                .map_err(|e| anyhow!("延迟分布 {} 参数无效: {}", spec, e))
+        };
+        let distribution = match parts[0].trim() {
+            "fixed" => Self::Fixed { ms: num(1)? },
+            "uniform" => Self::Uniform {
+                min_ms: num(1)?,
+                max_ms: num(2)?,
+            },
+            "normal" => Self::Normal {
+                mean_ms: num(1)?,
+                std_dev_ms: num(2)?,
+            },
+            "pareto" => Self::Pareto {
+                scale_ms: num(1)?,
+                shape: num(2)?,
+            },
+            other => bail!("未知的延迟分布 {}（可选 fixed / uniform / normal / pareto）", other),
+        };
+        Ok(distribution)
+    }
+}
+
+/// 单向链路特性
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkProfile {
+    pub latency: LatencyDistribution,
+    /// 在延迟基础上叠加 ±jitter_ms 的均匀抖动
+    #[serde(default)]
+    pub jitter_ms: f64,
+    /// 丢包率（0~1），丢包对发送方不可见
+    #[serde(default)]
+    pub loss_rate: f64,
+}
+
+impl Default for LinkProfile {
+    fn default() -> Self {
+        Self {
+            latency: LatencyDistribution::Fixed { ms: 20.0 },
+            jitter_ms: 0.0,
+            loss_rate: 0.0,
+        }
+    }
+}
+
+/// 指定节点之间的链路覆盖（单向）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkOverride {
+    pub from: String,
+    pub to: String,
+    #[serde(flatten)]
+    pub profile: LinkProfile,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    #[serde(default)]
+    pub default_link: LinkProfile,
+    #[serde(default)]
+    pub links: Vec<LinkOverride>,
+}
+
+/// 整个模拟网络的统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationStats {
+    /// 虚拟时钟（毫秒）
+    pub now_ms: f64,
+    pub sent: u64,
+    pub delivered: u64,
+    /// 随机丢包
+    pub lost: u64,
+    /// 因分区丢弃或发送失败
+    pub blocked: u64,
+    pub in_flight: usize,
+    pub mean_latency_ms: f64,
+    pub p50_latency_ms: f64,
+    pub p99_latency_ms: f64,
+}
+
+struct InFlight {
+    from: String,
+    to: String,
+    sent_at_us: u64,
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct PeerState {
+    inbox: VecDeque<(String, Vec<u8>)>,
+    stats: TransportStats,
+    contacted: HashSet<String>,
+    latency_total_ms: f64,
+    latency_samples: u64,
+}
+
+struct SimState {
+    config: SimulationConfig,
+    rng: StdRng,
+    now_us: u64,
+    seq: u64,
+    peers: BTreeMap<String, PeerState>,
+    /// 无序节点对，两个方向都不通
+    partitions: HashSet<(String, String)>,
+    /// (投递时间, 序号) -> 消息，序号保证同一时刻的投递顺序固定
+    in_flight: BTreeMap<(u64, u64), InFlight>,
+    latencies_ms: Vec<f64>,
+    stats: SimulationStats,
+}
+
+fn pair(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+impl SimState {
+    fn link(&self, from: &str, to: &str) -> &LinkProfile {
+        self.config
+            .links
+            .iter()
+            .find(|link| link.from == from && link.to == to)
+            .map(|link| &link.profile)
+            .unwrap_or(&self.config.default_link)
+    }
+
+    fn enqueue(&mut self, from: &str, to: &str, data: &[u8]) -> Result<()> {
+        if !self.peers.contains_key(to) {
+            bail!("模拟网络中没有节点 {}", to);
+        }
+        self.stats.sent += 1;
+        let partitioned = self.partitions.contains(&pair(from, to));
+        let sender = self.peers.entry(from.to_string()).or_default();
+        sender.stats.total_sent_bytes += data.len() as u64;
+        sender.contacted.insert(to.to_string());
+        sender.stats.active_connections = sender.contacted.len();
+        if partitioned {
+            sender.stats.failed_sends += 1;
+            self.stats.blocked += 1;
+            bail!("{} 与 {} 之间网络分区", from, to);
+        }
+
+        let profile = self.link(from, to).clone();
+        if self.rng.random::<f64>() < profile.loss_rate {
+            self.stats.lost += 1;
+            return Ok(());
+        }
+        let mut delay_ms = profile.latency.sample(&mut self.rng);
+        if profile.jitter_ms > 0.0 {
+            delay_ms += self.rng.random_range(-profile.jitter_ms..profile.jitter_ms);
+        }
+        let deliver_at = self.now_us + (delay_ms.max(0.0) * 1000.0) as u64;
+        self.seq += 1;
+        self.in_flight.insert(
+            (deliver_at, self.seq),
+            InFlight {
+                from: from.to_string(),
+                to: to.to_string(),
+                sent_at_us: self.now_us,
+                data: data.to_vec(),
+            },
+        );
+        Ok(())
+    }
+
+    /// 投递 `until_us` 之前到期的消息并把时钟推进到 `until_us`，返回投递数
+    fn deliver_until(&mut self, until_us: u64) -> usize {
+        let mut delivered = 0;
+        while let Some(entry) = self.in_flight.first_entry() {
+            if entry.key().0 > until_us {
+                break;
+            }
+            let ((at, _), message) = entry.remove_entry();
+            self.now_us = self.now_us.max(at);
+            if self.partitions.contains(&pair(&message.from, &message.to)) {
+                self.stats.blocked += 1;
+                continue;
+            }
+            let latency_ms = (at - message.sent_at_us) as f64 / 1000.0;
+            let Some(receiver) = self.peers.get_mut(&message.to) else {
+                continue;
+            };
+            receiver.stats.total_received_bytes += message.data.len() as u64;
+            receiver.latency_total_ms += latency_ms;
+            receiver.latency_samples += 1;
+            receiver.inbox.push_back((message.from, message.data));
+            self.latencies_ms.push(latency_ms);
+            self.stats.delivered += 1;
+            delivered += 1;
+        }
+        self.now_us = self.now_us.max(until_us);
+        delivered
+    }
+
+    fn next_delivery_for(&self, peer: &str) -> Option<u64> {
+        self.in_flight
+            .iter()
+            .find(|(_, message)| message.to == peer)
+            .map(|((at, _), _)| *at)
+    }
+}
+
+/// 共享的模拟网络，克隆后指向同一状态
+#[derive(Clone)]
+pub struct SimulatedNetwork {
+    state: Arc<Mutex<SimState>>,
+    delivered: Arc<Notify>,
+}
+
+impl SimulatedNetwork {
+    pub fn new(config: SimulationConfig) -> Self {
+        let state = SimState {
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+            now_us: 0,
+            seq: 0,
+            peers: BTreeMap::new(),
+            partitions: HashSet::new(),
+            in_flight: BTreeMap::new(),
+            latencies_ms: Vec::new(),
+            stats: SimulationStats::default(),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            delivered: Arc::new(Notify::new()),
+        }
+    }
+
+    /// 加入一个命名节点并返回它的传输端点
+    pub fn endpoint(&self, peer: &str) -> SimulatedTransport {
+        self.state.lock().peers.entry(peer.to_string()).or_default();
+        SimulatedTransport {
+            local: peer.to_string(),
+            network: self.clone(),
+        }
+    }
+
+    pub fn peers(&self) -> Vec<String> {
+        self.state.lock().peers.keys().cloned().collect()
+    }
+
+    /// 切断两组节点之间的所有链路（双向）
+    pub fn partition(&self, side_a: &[String], side_b: &[String]) {
+        let mut state = self.state.lock();
+        for a in side_a {
+            for b in side_b {
+                if a != b {
+                    state.partitions.insert(pair(a, b));
+                }
+            }
+        }
+    }
+
+    /// 恢复两组节点之间的链路
+    pub fn heal(&self, side_a: &[String], side_b: &[String]) {
+        let mut state = self.state.lock();
+        for a in side_a {
+            for b in side_b {
+                state.partitions.remove(&pair(a, b));
+            }
+        }
+    }
+
+    pub fn heal_all(&self) {
+        self.state.lock().partitions.clear();
+    }
+
+    pub fn is_partitioned(&self, a: &str, b: &str) -> bool {
+        self.state.lock().partitions.contains(&pair(a, b))
+    }
+
+    pub fn now_ms(&self) -> f64 {
+        self.state.lock().now_us as f64 / 1000.0
+    }
+
+    /// 推进虚拟时钟，投递期间到期的消息，返回投递数
+    pub fn advance(&self, ms: f64) -> usize {
+        let delivered = {
+            let mut state = self.state.lock();
+            let until = state.now_us + (ms.max(0.0) * 1000.0) as u64;
+            state.deliver_until(until)
+        };
+        if delivered > 0 {
+            self.delivered.notify_waiters();
+        }
+        delivered
+    }
+
+    /// 投递全部在途消息
+    pub fn run_until_idle(&self) -> usize {
+        let delivered = {
+            let mut state = self.state.lock();
+            let until = state.in_flight.keys().next_back().map_or(state.now_us, |(at, _)| *at);
+            state.deliver_until(until)
+        };
+        if delivered > 0 {
+            self.delivered.notify_waiters();
+        }
+        delivered
+    }
+
+    /// 用网络的随机数生成器为 `from` 挑选至多 `count` 个其他节点（结果同样可复现）
+    pub fn choose_peers(&self, from: &str, count: usize) -> Vec<String> {
+        let mut state = self.state.lock();
+        let others: Vec<String> = state.peers.keys().filter(|peer| *peer != from).cloned().collect();
+        others.choose_multiple(&mut state.rng, count).cloned().collect()
+    }
+
+    pub fn stats(&self) -> SimulationStats {
+        let state = self.state.lock();
+        let mut stats = state.stats.clone();
+        stats.now_ms = state.now_us as f64 / 1000.0;
+        stats.in_flight = state.in_flight.len();
+        if !state.latencies_ms.is_empty() {
+            let mut sorted = state.latencies_ms.clone();
+            sorted.sort_by(f64::total_cmp);
+            let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+            stats.mean_latency_ms = sorted.iter().sum::<f64>() / sorted.len() as f64;
+            stats.p50_latency_ms = percentile(0.5);
+            stats.p99_latency_ms = percentile(0.99);
+        }
+        stats
+    }
+}
+
+/// 单个节点的模拟传输端点
+pub struct SimulatedTransport {
+    local: String,
+    network: SimulatedNetwork,
+}
+
+impl SimulatedTransport {
+    pub fn local_peer(&self) -> &str {
+        &self.local
+    }
+
+    /// 非阻塞地取出一条已投递的消息
+    pub fn try_receive(&self) -> Option<(String, Vec<u8>)> {
+        self.network
+            .state
+            .lock()
+            .peers
+            .get_mut(&self.local)
+            .and_then(|peer| peer.inbox.pop_front())
+    }
+}
+
+impl Transport for SimulatedTransport {
+    async fn send(&self, route: &RouteInfo, message: &[u8]) -> Result<()> {
+        self.network.state.lock().enqueue(&self.local, &route.destination, message)
+    }
+
+    /// 收件箱为空但有发往本节点的在途消息时，把虚拟时钟直接推进到最早那条的投递时间；
+    /// 没有在途消息则等待其他节点推进时钟
+    async fn receive(&self) -> Result<(String, Vec<u8>)> {
+        loop {
+            // 先登记等待再检查，避免检查与等待之间的投递通知丢失
+            let notified = self.network.delivered.notified();
+            let delivered = {
+                let mut state = self.network.state.lock();
+                if let Some(message) = state.peers.get_mut(&self.local).and_then(|peer| peer.inbox.pop_front()) {
+                    return Ok(message);
+                }
+                state.next_delivery_for(&self.local).map(|at| state.deliver_until(at))
+            };
+            match delivered {
+                Some(0) => {}
+                Some(_) => self.network.delivered.notify_waiters(),
+                None => notified.await,
+            }
+        }
+    }
+
+    fn get_stats(&self) -> TransportStats {
+        let state = self.network.state.lock();
+        let Some(peer) = state.peers.get(&self.local) else {
+            return TransportStats::default();
+        };
+        let mut stats = peer.stats.clone();
+        if peer.latency_samples > 0 {
+            stats.average_latency_ms = peer.latency_total_ms / peer.latency_samples as f64;
+        }
+        stats
+    }
+}
+
+/// gossip 流量模拟参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GossipPlan {
+    pub nodes: usize,
+    pub rounds: usize,
+    /// 每个节点每轮发送的目标数
+    pub fanout: usize,
+    pub round_interval_ms: f64,
+    pub message_bytes: usize,
+}
+
+impl Default for GossipPlan {
+    fn default() -> Self {
+        Self {
+            nodes: 8,
+            rounds: 20,
+            fanout: 3,
+            round_interval_ms: 100.0,
+            message_bytes: 1024,
+        }
+    }
+}
+
+/// 节点命名约定：`node-0`、`node-1` ...
+pub fn sim_peer_name(index: usize) -> String {
+    format!("node-{}", index)
+}
+
+/// 按计划在模拟网络上跑 gossip 流量，结束时投递全部在途消息并返回统计
+pub async fn run_gossip(network: &SimulatedNetwork, plan: &GossipPlan) -> SimulationStats {
+    let endpoints: Vec<SimulatedTransport> =
+        (0..plan.nodes).map(|index| network.endpoint(&sim_peer_name(index))).collect();
+    let payload = vec![0u8; plan.message_bytes];
+    for _ in 0..plan.rounds {
+        for endpoint in &endpoints {
+            for target in network.choose_peers(endpoint.local_peer(), plan.fanout) {
+                let route = RouteInfo {
+                    destination: target.clone(),
+                    transport_type: TransportType::Iroh,
+                    address: target,
+                    quality_score: 1.0,
+                };
+                // 分区导致的失败已计入统计
+                let _ = endpoint.send(&route, &payload).await;
+            }
+        }
+        network.advance(plan.round_interval_ms);
+        for endpoint in &endpoints {
+            while endpoint.try_receive().is_some() {}
+        }
+    }
+    network.run_until_idle();
+    network.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(to: &str) -> RouteInfo {
+        RouteInfo {
+            destination: to.to_string(),
+            transport_type: TransportType::Iroh,
+            address: to.to_string(),
+            quality_score: 1.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_same_seed_same_run() {
+        let config = SimulationConfig {
+            seed: 11,
+            default_link: LinkProfile {
+                latency: "normal:40:15".parse().unwrap(),
+                jitter_ms: 5.0,
+                loss_rate: 0.1,
+            },
+            links: Vec::new(),
+        };
+        let plan = GossipPlan::default();
+        let first = run_gossip(&SimulatedNetwork::new(config.clone()), &plan).await;
+        let second = run_gossip(&SimulatedNetwork::new(config), &plan).await;
+
+        assert_eq!(first, second);
+        assert!(first.lost > 0);
+        assert_eq!(first.delivered + first.lost, first.sent);
+    }
+
+    #[tokio::test]
+    async fn test_latency_and_partition() {
+        let network = SimulatedNetwork::new(SimulationConfig {
+            links: vec![LinkOverride {
+                from: "a".to_string(),
+                to: "b".to_string(),
+                profile: LinkProfile {
+                    latency: LatencyDistribution::Fixed { ms: 75.0 },
+                    ..LinkProfile::default()
+                },
+            }],
+            ..SimulationConfig::default()
+        });
+        let a = network.endpoint("a");
+        let b = network.endpoint("b");
+
+        a.send(&route("b"), b"hello").await.unwrap();
+        assert_eq!(network.advance(50.0), 0);
+        assert!(b.try_receive().is_none());
+        // receive 把虚拟时钟推进到消息到达时刻
+        assert_eq!(b.receive().await.unwrap(), ("a".to_string(), b"hello".to_vec()));
+        assert_eq!(network.now_ms(), 75.0);
+
+        b.send(&route("a"), b"in flight").await.unwrap();
+        network.partition(&["a".to_string()], &["b".to_string()]);
+        assert!(a.send(&route("b"), b"blocked").await.is_err());
+        network.run_until_idle();
+        assert!(a.try_receive().is_none());
+        assert_eq!(network.stats().blocked, 2);
+
+        network.heal_all();
+        a.send(&route("b"), b"again").await.unwrap();
+        assert_eq!(b.receive().await.unwrap().1, b"again".to_vec());
+    }
+}
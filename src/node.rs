@@ -1,12 +1,29 @@
-use crate::comms::{CommsHandle, IrohEvent};
-use crate::config::AppConfig;
-use crate::consensus::{ConsensusEngine, SignedGossip};
-use crate::crypto::CryptoConfig;
-use crate::device::DeviceManager;
-use crate::stats::TrainingStatsManager;
+use crate::admin::{NodeStatus, NodeStatusBoard};
+use crate::archive::ColdArchiver;
+use crate::build_info::CapabilityRecord;
+use crate::cluster::ClusterView;
+use crate::comms::{CommsHandle, IrohEvent, RemoteSigningRelay, StakeAttestation};
+use crate::config::{AppConfig, NodeMode};
+use crate::config_watch::ConfigChanged;
+use crate::consensus::{
+    BftConfig, BftRound, CommitCertificate, Committee, ConsensusEngine, RedundancyValidator, ReputationEvent, ScoreVector,
+    SignedBftMessage, SignedGossip, ValidationVerdict,
+};
+use crate::crypto::{CryptoConfig, CryptoProfile, KeyRotation, NodeIdentity, TrustPolicyEngine};
+use crate::dataset_manifest::VerifiedDataset;
+use crate::device::{
+    system_event_hub, system_events, DeviceDetector, DeviceManager, PauseReason, PowerPolicy, ResourceLimits,
+    ResourceSandbox, ResourceUsage, SandboxIncident, SandboxViolation, SystemEvent, TickController, TickFeedback,
+};
+use crate::experiments::ExperimentRegistry;
+use crate::model_cache::ModelCache;
+use crate::storage::StorageGovernor;
+use crate::stats::{EnergyModel, PeerSample, PowerProfile, StatsStore, TickMetrics, TrainingStatsManager};
+use crate::task_manifest::{ElectionRecord, SignedTaskManifest, TaskManifest, TaskManifestVerifier, VerifiedManifest};
 use crate::topology::TopologySelector;
-use crate::training::TrainingEngine;
+use crate::training::{Evaluator, Pipeline, SourceConfig, TaskCompletion, TrainingEngine, TransformRegistry};
 use crate::types::{GeoPoint, GgbMessage};
+use crate::work_schedule::{WorkKind, WorkScheduler};
 use anyhow::Result;
 use futures::StreamExt;
 use rand::{Rng, SeedableRng};
@@ -24,8 +41,81 @@ pub struct Node {
     pub tick_counter: u64,
     pub checkpoint_dir: Option<PathBuf>,
     pub checkpoint_interval: u64, // 每 N 个 tick 保存一次 checkpoint
+    pub experiments: Arc<ExperimentRegistry>,
+    pub identity: Arc<NodeIdentity>,
+    pub task_manifests: TaskManifestVerifier,
+    pub tick_controller: TickController,
+    /// 已接受、分配给本节点的任务
+    pub assigned_tasks: Vec<VerifiedManifest>,
+    pub mode: NodeMode,
+    /// 已知节点的能力记录和构建信息
+    pub cluster: ClusterView,
+    /// 冷存储归档器（未启用时为 None）
+    pub archiver: Option<Arc<ColdArchiver>>,
+    /// 磁盘空间检查与模型缓存自动淘汰（未启用时为 None）
+    pub storage: Option<Arc<StorageGovernor>>,
+    /// 已校验的训练数据集清单（未配置或校验失败时为 None）
+    pub dataset: Option<VerifiedDataset>,
+    /// 训练数据预处理流水线（未启用时为 None）
+    pub pipeline: Option<Pipeline>,
+    /// 留出集评估器（未启用时为 None）
+    pub evaluator: Option<Evaluator>,
+    /// 冗余执行验证（未启用时为 None）
+    pub redundancy: Option<Arc<RedundancyValidator>>,
+    /// 已完成、尚未广播输出的任务
+    pub completed_outputs: Vec<(String, Vec<f32>)>,
+    /// 按信任级别为各节点选择加密策略
+    pub trust: Arc<TrustPolicyEngine>,
+    /// 本地统计数据库（未启用时为 None）
+    pub stats_store: Option<Arc<StatsStore>>,
+    /// 配置热更新事件（未启用监视时为 None）
+    pub config_updates: Option<tokio::sync::broadcast::Receiver<ConfigChanged>>,
+    /// 重任务的时间窗口与网络策略
+    pub work_schedule: Arc<WorkScheduler>,
+    /// 手动暂停、CPU 上限和仅 Wi-Fi 等资源策略（桌面端托盘可实时修改）
+    pub power_policy: Arc<PowerPolicy>,
+    /// 设备状态由平台层推送（移动端原生回调），不再用本地检测覆盖
+    pub platform_devices: bool,
+    /// 故障注入器（未启用时为 None）
+    #[cfg(feature = "chaos")]
+    pub chaos: Option<Arc<crate::chaos::FaultInjector>>,
+    /// 当前 epoch 的计算分数共识轮（未参与时为 None）
+    pub score_round: Option<BftRound>,
+    /// 本节点的链上质押证明，随能力记录定期广播（未配置时为 None）
+    pub stake_attestation: Option<StakeAttestation>,
+    /// 每个 tick 发布状态快照供管理接口读取（未启用时为 None）
+    pub status_board: Option<Arc<NodeStatusBoard>>,
+    /// 本机加密性能画像（启动时实测）
+    pub crypto_profile: Arc<CryptoProfile>,
+    /// 自适应隐私模式要求的最低性能评分
+    pub min_performance_score: f32,
+    /// 训练能耗估算模型（未启用时为 None）
+    pub energy_model: Option<EnergyModel>,
+    /// 能耗按 epoch 汇总时每个 epoch 的 tick 数
+    pub energy_epoch_ticks: u64,
+    /// 最近一次采样的 GPU 平均利用率（0-1）
+    pub gpu_utilization: f64,
+    /// 训练资源沙箱（未启用时为 None）
+    pub sandbox: Option<Arc<ResourceSandbox>>,
+    /// 沙箱看门狗采样间隔
+    pub sandbox_check_interval: Duration,
+    /// 远程签名中继（交易经 P2P 发给审批设备签名）
+    pub signing_relay: Arc<RemoteSigningRelay>,
 }
 
+/// 每隔多少个 tick 重新广播能力记录
+const CAPABILITY_BROADCAST_TICKS: u64 = 60;
+
+/// 启动时加密基准测试的单次负载和总时长
+const CRYPTO_BENCHMARK_PAYLOAD: usize = 16 * 1024;
+const CRYPTO_BENCHMARK_BUDGET: Duration = Duration::from_millis(150);
+
+/// 每隔多少个 tick 把邻居快照写入统计数据库
+const PEER_SNAPSHOT_TICKS: u64 = 10;
+
+/// 观察模式的 tick 间隔下限，只做清单清理和集群视图维护
+const WATCH_ONLY_MIN_TICK: Duration = Duration::from_secs(5);
+
 impl Node {
     pub async fn new(config: AppConfig) -> Result<Self> {
         let mut rng = rand::rngs::StdRng::from_seed([
@@ -35,20 +125,56 @@ impl Node {
         let geo = GeoPoint::random(&mut rng);
         let capabilities = config.device_capabilities.clone();
 
+        // 加载持久身份，节点 ID 由身份公钥派生
+        let identity = Arc::new(NodeIdentity::load_from_config(&config.crypto)?);
+
         // 创建通信句柄
-        let comms = CommsHandle::new(config.comms.clone()).await?;
+        let mut comms = CommsHandle::new(config.comms.clone()).await?;
+        comms.enable_authentication(identity.clone());
         
         // 创建训练引擎
-        let training = TrainingEngine::new(config.clone())?;
+        let mut training = TrainingEngine::new(config.clone())?;
+
+        // 校验数据集清单，通过后清单哈希写入之后的贡献记录
+        let dataset = match config.dataset.load_verified() {
+            Ok(dataset) => dataset,
+            Err(e) => {
+                println!("[数据集] 清单校验失败，不使用该数据集训练: {}", e);
+                None
+            }
+        };
+        if let Some(dataset) = &dataset {
+            println!(
+                "[数据集] {} v{} 校验通过: {} 个分块, 许可证 {}, 清单哈希 {}",
+                dataset.manifest.name,
+                dataset.manifest.version,
+                dataset.manifest.chunks.len(),
+                dataset.manifest.licenses().join(", "),
+                dataset.manifest_hash
+            );
+        }
+        training.set_dataset_manifest(dataset.as_ref().map(|dataset| dataset.manifest_hash.clone()));
         
         // 创建拓扑选择器
-        let topology = TopologySelector::new(geo.clone(), crate::topology::TopologyConfig::default());
+        let topology = TopologySelector::new(
+            geo.clone(),
+            crate::topology::TopologyConfig {
+                max_neighbors: config.max_peers,
+                ..Default::default()
+            },
+        );
         
         // 创建共识引擎
-        let consensus = ConsensusEngine::new(Arc::new(()), config.consensus.clone());
+        let consensus = ConsensusEngine::new(Arc::new(()), config.consensus.clone())
+            .with_identity(identity.clone());
         
         // 创建设备管理器
         let device_manager = DeviceManager::new();
+
+        // 注册实验性子系统（默认关闭，由 config.experiments 控制）
+        let experiments = Arc::new(ExperimentRegistry::new(config.experiments.clone()));
+        experiments.register("zk_proof", "零知识计算证明");
+        experiments.register("workers", "Cloudflare Workers 边缘集成");
         
         // 初始化统计管理器
         let stats = Arc::new(Mutex::new(TrainingStatsManager::new_with_model(
@@ -62,6 +188,9 @@ impl Node {
             geo.lat,
             geo.lon
         );
+        if config.node_mode.is_watch_only() {
+            println!("[观察模式] 只同步网络状态，不训练、不接受任务、不签名");
+        }
         println!("模型维度: {}", training.model_dim());
         println!(
             "设备能力: {}MB 内存, {} 核心, 网络: {:?}, 电池: {:?}",
@@ -74,6 +203,120 @@ impl Node {
                 .unwrap_or_else(|| "N/A".to_string())
         );
 
+        stats.lock().unwrap().set_node_mode(config.node_mode);
+
+        let mut tick_config = config.tick_controller.clone();
+        let mut initial_tick = capabilities.recommended_tick_interval();
+        if config.node_mode.is_watch_only() {
+            tick_config.enabled = false;
+            initial_tick = initial_tick.max(WATCH_ONLY_MIN_TICK);
+            tick_config.max_interval_ms = tick_config.max_interval_ms.max(initial_tick.as_millis() as u64);
+        }
+
+        let archiver = if config.archive.enabled {
+            match ColdArchiver::new(config.archive.clone()) {
+                Ok(archiver) => Some(Arc::new(archiver)),
+                Err(e) => {
+                    println!("[归档] 初始化失败，归档已禁用: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let storage = config.storage.enabled.then(|| {
+            let cache = if config.model_cache.enabled {
+                match ModelCache::open(config.model_cache.clone()) {
+                    Ok(cache) => Some(Arc::new(cache)),
+                    Err(e) => {
+                        println!("[存储] 打开模型缓存失败，不自动淘汰: {}", e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            Arc::new(StorageGovernor::new(config.storage.clone(), cache))
+        });
+
+        let pipeline = if config.pipeline.enabled {
+            let started = check_pipeline_dataset(&config, dataset.as_ref())
+                .and_then(|_| Pipeline::start(&config.pipeline, &TransformRegistry::default()));
+            match started {
+                Ok(pipeline) => Some(pipeline),
+                Err(e) => {
+                    println!("[流水线] 启动失败: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let evaluator = if config.evaluation.enabled {
+            match Evaluator::new(config.evaluation.clone(), &config.pipeline.transforms, &TransformRegistry::default()) {
+                Ok(evaluator) => {
+                    println!("[评估] 载入 {} 个留出样本", evaluator.sample_count());
+                    Some(evaluator)
+                }
+                Err(e) => {
+                    println!("[评估] 初始化失败: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let redundancy = config.redundancy.enabled.then(|| {
+            println!("[冗余验证] 已启用，采样率 {:.1}%", config.redundancy.sample_rate * 100.0);
+            Arc::new(RedundancyValidator::new(config.redundancy.clone()))
+        });
+
+        let work_schedule = Arc::new(WorkScheduler::new(config.work_schedule.clone(), device_manager.clone()));
+
+        let sandbox = config.sandbox.enabled.then(|| {
+            let limits =
+                ResourceLimits::from_device_config(&config.device, &capabilities, config.sandbox.io_bytes_per_sec);
+            let sandbox = ResourceSandbox::enter(&config.sandbox, limits);
+            println!(
+                "[沙箱] {} 后端: CPU {}%, 内存 {}MB",
+                sandbox.backend_name(),
+                limits.cpu_percent,
+                limits.memory_mb
+            );
+            Arc::new(sandbox)
+        });
+
+        let energy_model = config
+            .energy
+            .enabled
+            .then(|| EnergyModel::new(config.energy.profile.unwrap_or_else(|| PowerProfile::for_device(&capabilities))));
+
+        // 实测本机加密吞吐，自适应模式据此选择隐私级别
+        let privacy_performance = &config.security.privacy_performance;
+        let crypto_profile = Arc::new(if privacy_performance.enable_hardware_acceleration {
+            CryptoProfile::measure(CRYPTO_BENCHMARK_PAYLOAD, CRYPTO_BENCHMARK_BUDGET)
+        } else {
+            CryptoProfile::unmeasured()
+        });
+        let trust = Arc::new(TrustPolicyEngine::new(
+            config.peer_trust.clone(),
+            crate::crypto::trust::measured_privacy_level(
+                &privacy_performance.mode,
+                privacy_performance.min_performance_score,
+                &crypto_profile,
+            ),
+        ));
+        trust.set_preferred_aead(crypto_profile.best_aead());
+        for result in &crypto_profile.results {
+            println!(
+                "[加密] {:?} ({:?}): {:.1} MB/s",
+                result.algorithm, result.backend, result.throughput_mbps
+            );
+        }
+
         Ok(Self {
             comms,
             training,
@@ -84,28 +327,172 @@ impl Node {
             tick_counter: 0,
             checkpoint_dir: None,
             checkpoint_interval: 100,
+            experiments,
+            identity,
+            task_manifests: TaskManifestVerifier::new(),
+            tick_controller: TickController::new(tick_config, initial_tick),
+            assigned_tasks: Vec::new(),
+            mode: config.node_mode,
+            cluster: ClusterView::new(config.build_policy.clone()),
+            archiver,
+            storage,
+            dataset,
+            pipeline,
+            evaluator,
+            redundancy,
+            completed_outputs: Vec::new(),
+            trust,
+            stats_store: None,
+            config_updates: None,
+            work_schedule,
+            power_policy: Arc::new(PowerPolicy::default()),
+            platform_devices: false,
+            #[cfg(feature = "chaos")]
+            chaos: None,
+            score_round: None,
+            stake_attestation: None,
+            status_board: None,
+            crypto_profile,
+            min_performance_score: config.security.privacy_performance.min_performance_score,
+            energy_model,
+            energy_epoch_ticks: config.energy.epoch_ticks.max(1),
+            gpu_utilization: 0.0,
+            sandbox,
+            sandbox_check_interval: Duration::from_secs(config.sandbox.check_interval_secs.max(1)),
+            signing_relay: Arc::new(RemoteSigningRelay::default()),
         })
     }
 
+    /// 订阅配置文件变化，在 tick 中应用可热更新的配置项
+    pub fn with_config_updates(mut self, updates: tokio::sync::broadcast::Receiver<ConfigChanged>) -> Self {
+        self.config_updates = Some(updates);
+        self
+    }
+
+    /// 每个 tick 的训练指标写入本地统计数据库
+    pub fn with_stats_store(mut self, store: Arc<StatsStore>) -> Self {
+        self.stats_store = Some(store);
+        self
+    }
+
+    /// 每个 tick 把邻居和质押状态发布到状态板（供管理接口读取）
+    pub fn with_status_board(mut self, board: Arc<NodeStatusBoard>) -> Self {
+        self.status_board = Some(board);
+        self
+    }
+
+    /// 与外部共享资源策略，外部修改后下一轮 tick 生效
+    pub fn with_power_policy(mut self, policy: Arc<PowerPolicy>) -> Self {
+        self.power_policy = policy;
+        self
+    }
+
+    /// 使用平台层维护的设备管理器（网络类型、电池等由原生回调更新）
+    pub fn with_device_manager(mut self, devices: DeviceManager) -> Self {
+        self.work_schedule = Arc::new(WorkScheduler::new(self.work_schedule.config(), devices.clone()));
+        self.device_manager = devices;
+        self.platform_devices = true;
+        self
+    }
+
+    /// 按脚本注入故障（传输中断、分片损坏、训练内存耗尽）
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(mut self, injector: Arc<crate::chaos::FaultInjector>) -> Self {
+        self.chaos = Some(injector);
+        self
+    }
+
+    /// 传输是否被故障注入关闭，关闭期间丢弃所有收发的消息
+    fn transport_killed(&self) -> bool {
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.chaos {
+            return chaos.transport_down();
+        }
+        false
+    }
+
     pub async fn run(mut self) -> Result<()> {
-        let capabilities = self.device_manager.get();
-        let mut tick_interval = capabilities.recommended_tick_interval();
+        let mut tick_interval = self.tick_controller.current();
         let mut ticker = interval(tick_interval);
         let mut device_refresh = interval(Duration::from_secs(60)); // 每分钟刷新设备状态
+        let mut sandbox_check = interval(self.sandbox_check_interval);
+
+        // 监听系统睡眠/唤醒和网络变化
+        system_events::start_listeners();
+        let mut system_rx = system_event_hub().subscribe();
 
         println!("训练频率: {:?}ms", tick_interval);
 
+        // 后台定期归档过期产物
+        if let Some(archiver) = self.archiver.clone() {
+            tokio::spawn(async move {
+                let mut sweep = interval(Duration::from_secs(archiver.config().scan_interval_secs.max(60)));
+                loop {
+                    sweep.tick().await;
+                    if let Err(e) = archiver.run_once().await {
+                        println!("[归档] 扫描失败: {}", e);
+                    }
+                }
+            });
+        }
+
+        // 后台定期检查磁盘空间，低空间警告记入统计
+        if let Some(storage) = self.storage.clone() {
+            let stats = Arc::clone(&self.stats);
+            tokio::spawn(async move {
+                let mut check = interval(Duration::from_secs(storage.config().check_interval_secs.max(10)));
+                loop {
+                    check.tick().await;
+                    let governor = Arc::clone(&storage);
+                    let warnings = match tokio::task::spawn_blocking(move || governor.check()).await {
+                        Ok(warnings) => warnings,
+                        Err(e) => {
+                            println!("[存储] 空间检查失败: {}", e);
+                            continue;
+                        }
+                    };
+                    for warning in warnings {
+                        println!(
+                            "[存储] 磁盘空间不足: {}（可用 {:.1} MB）",
+                            warning.path.display(),
+                            warning.available_bytes as f64 / 1024.0 / 1024.0
+                        );
+                        stats.lock().unwrap().record_storage_warning(warning);
+                    }
+                }
+            });
+        }
+
+        let mut manually_paused = false;
+        // 上一轮 tick 的开始时间，两轮之间的间隔作为估算能耗的时间窗口（暂停后重新计时）
+        let mut last_tick: Option<std::time::Instant> = None;
         loop {
-            // 检查是否应该暂停训练（低电量）
-            let should_pause = {
-                let caps = self.device_manager.get();
-                caps.should_pause_training()
-            };
+            // 检查是否应该暂停训练（手动暂停或低电量）
+            let pause = self.power_policy.pause_reason(&self.device_manager.get());
+            if pause != Some(PauseReason::Manual) && manually_paused {
+                println!("[资源策略] 恢复训练");
+                manually_paused = false;
+            }
 
-            if should_pause {
-                println!("[电池保护] 电量过低，暂停训练");
-                tokio::time::sleep(Duration::from_secs(60)).await;
-                continue;
+            if !self.mode.is_watch_only() {
+                match pause {
+                    Some(PauseReason::Manual) => {
+                        if !manually_paused {
+                            println!("[资源策略] 用户手动暂停训练");
+                            manually_paused = true;
+                        }
+                        last_tick = None;
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                    Some(PauseReason::LowBattery) => {
+                        println!("[电池保护] 电量过低，暂停训练");
+                        last_tick = None;
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        continue;
+                    }
+                    None => {}
+                }
             }
 
             tokio::select! {
@@ -115,19 +502,54 @@ impl Node {
                     }
                 }
                 _ = ticker.tick() => {
-                    // 动态调整 tick 间隔（如果电池状态变化）
+                    let started = std::time::Instant::now();
+                    self.on_tick().await?;
+                    let window = last_tick.replace(started).map(|previous| started - previous);
+                    self.record_tick_energy(started.elapsed(), window);
+
+                    // 根据本轮耗时、温控、放电速率和网络积压调整 tick 间隔
                     let caps = self.device_manager.get();
-                    let new_interval = caps.recommended_tick_interval();
-                    if new_interval != tick_interval {
-                        tick_interval = new_interval;
+                    let feedback = TickFeedback {
+                        round_duration: started.elapsed(),
+                        thermal: self.device_manager.thermal_state(),
+                        battery_level: caps.battery_level,
+                        is_charging: caps.is_charging.unwrap_or(false),
+                        network_backlog: self.comms.outbound_backlog(),
+                    };
+                    if let Some(adaptation) = self.tick_controller.observe(&feedback) {
+                        println!(
+                            "[自适应] 调整训练频率: {}ms -> {}ms ({})",
+                            adaptation.from_ms, adaptation.to_ms, adaptation.reason
+                        );
+                        self.stats.lock().unwrap().record_tick_adaptation(adaptation);
+                        tick_interval = self.tick_controller.current();
                         ticker = interval(tick_interval);
-                        println!("[自适应] 调整训练频率: {:?}ms", tick_interval);
                     }
-                    self.on_tick().await?;
+
+                    // CPU 上限：按本轮耗时补足空闲，并重置节拍避免补发积压的 tick
+                    let idle = self.power_policy.throttle_delay(started.elapsed());
+                    if !idle.is_zero() {
+                        tokio::time::sleep(idle).await;
+                        ticker.reset();
+                    }
+                }
+                Ok(event) = system_rx.recv() => {
+                    self.handle_system_event(event, &mut system_rx).await?;
+                    // 睡眠期间错过的 tick 不补发，能耗窗口重新计时
+                    last_tick = None;
+                    ticker.reset();
+                }
+                _ = sandbox_check.tick(), if self.sandbox.is_some() => {
+                    let violation = self.sandbox.as_ref().and_then(|sandbox| sandbox.check());
+                    if let Some((violation, usage)) = violation {
+                        self.abort_runaway_task(violation, usage);
+                    }
                 }
                 _ = device_refresh.tick() => {
                     // 定期刷新设备状态（网络类型、电池等）
-                    self.device_manager.refresh();
+                    if !self.platform_devices {
+                        self.device_manager.refresh();
+                    }
                     let caps = self.device_manager.get();
 
                     // 更新网络类型
@@ -159,28 +581,121 @@ impl Node {
                         caps.max_memory_mb as usize, 
                         caps.cpu_cores as usize
                     );
+
+                    // 采样 GPU 利用率供能耗估算使用
+                    if self.energy_model.is_some() && caps.has_gpu {
+                        let gpus = DeviceDetector::detect_gpu_usage();
+                        if !gpus.is_empty() {
+                            let total: f64 = gpus.iter().map(|g| g.usage_percent as f64).sum();
+                            self.gpu_utilization = total / gpus.len() as f64 / 100.0;
+                        }
+                    }
                 }
             }
         }
     }
 
+    /// 资源越界：中止当前训练任务并把事件记入统计
+    fn abort_runaway_task(&mut self, violation: SandboxViolation, usage: ResourceUsage) {
+        let reason = violation.to_string();
+        let task_id = self.training.abort_task(&reason);
+        match &task_id {
+            Some(task_id) => {
+                println!("[沙箱] 中止失控任务 {}: {}", task_id, reason);
+                self.assigned_tasks.retain(|t| &t.manifest.task_id != task_id);
+            }
+            None => println!("[沙箱] 资源越界（无进行中的任务）: {}", reason),
+        }
+        let limits = match &self.sandbox {
+            Some(sandbox) => sandbox.limits(),
+            None => return,
+        };
+        self.stats.lock().unwrap().record_sandbox_incident(SandboxIncident {
+            task_id,
+            violation,
+            usage,
+            limits,
+            occurred_at: chrono::Utc::now().timestamp(),
+        });
+        self.start_next_task();
+    }
+
+    /// 按本轮计算占空比和最近的 GPU 利用率估算能耗，计入 epoch 汇总和当前任务
+    fn record_tick_energy(&mut self, busy: Duration, window: Option<Duration>) {
+        let Some(model) = &self.energy_model else {
+            return;
+        };
+        if self.mode.is_watch_only() {
+            return;
+        }
+        let window = window.unwrap_or(busy).max(busy);
+        if window.is_zero() {
+            return;
+        }
+        let duty = busy.as_secs_f64() / window.as_secs_f64();
+        let watt_hours = model.watt_hours(duty, self.gpu_utilization, window);
+        let epoch = self.tick_counter / self.energy_epoch_ticks;
+        self.stats.lock().unwrap().record_energy(epoch, watt_hours, busy);
+        self.training.record_energy(watt_hours);
+    }
+
     async fn on_tick(&mut self) -> Result<()> {
         self.tick_counter = self.tick_counter.wrapping_add(1);
         self.stats.lock().unwrap().increment_tick();
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.chaos {
+            chaos.on_tick(self.tick_counter);
+        }
+        self.apply_config_updates();
 
         // 处理通过 QUIC 接收到的消息
-        let quic_messages = self.comms.take_quic_messages();
-        for signed in quic_messages {
-            if self.consensus.verify(&signed) {
+        let mut quic_messages = self.comms.take_quic_messages();
+        if self.transport_killed() {
+            quic_messages.clear();
+        }
+        let verdicts = self.consensus.verify_batch(&quic_messages.iter().collect::<Vec<_>>());
+        for (signed, valid) in quic_messages.into_iter().zip(verdicts) {
+            if valid {
                 self.handle_signed_message(signed, "QUIC".to_string()).await?;
             }
         }
 
+        if self.tick_counter % 100 == 0 {
+            let view = self.cluster.render();
+            if !view.is_empty() {
+                println!("[集群视图]\n{}", view.trim_end());
+            }
+        }
+
+        if self.mode.is_watch_only() {
+            // 观察模式只维护清单缓存和集群视图
+            self.consensus.prune_stale();
+            self.task_manifests.prune(chrono::Utc::now().timestamp());
+            let (primary, _backups) = self.topology.neighbor_sets();
+            self.stats.lock().unwrap().update_connected_peers(primary.len() as u64);
+            self.check_topology_health();
+            return Ok(());
+        }
+
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = &self.chaos {
+            chaos.check_training()?;
+        }
+
+        if let Some(round) = self.score_round.as_mut().filter(|round| round.timed_out(std::time::Instant::now())) {
+            let messages = round.on_timeout()?;
+            self.publish_score_messages(messages).await?;
+        }
+
         // 暂时注释掉inference相关代码
         // let hash = self.inference.tensor_hash();
         // let version = self.inference.tensor_snapshot().version;
         // self.stats.update_model(hash.clone(), version);
 
+        if self.tick_counter % CAPABILITY_BROADCAST_TICKS == 1 {
+            self.broadcast_capability().await?;
+        }
+
         let heartbeat = GgbMessage::Heartbeat {
             peer: self.comms.node_id().to_string(),
             model_hash: self.training.tensor_hash(),
@@ -199,7 +714,18 @@ impl Node {
         // self.stats.record_probe_sent();
 
         // self.inference.local_train_step();
+        self.feed_pipeline_batch()?;
+        self.run_evaluation();
+        self.publish_task_outputs().await?;
+        if self.tick_counter % 100 == 0 {
+            if let Some(redundancy) = &self.redundancy {
+                for task_id in redundancy.expire(chrono::Utc::now().timestamp()) {
+                    println!("[冗余验证] 任务 {} 的比较超时，已放弃", task_id);
+                }
+            }
+        }
         self.consensus.prune_stale();
+        self.task_manifests.prune(chrono::Utc::now().timestamp());
         if self.tick_counter % 12 == 0 {
             self.maybe_broadcast_dense().await?;
         }
@@ -239,17 +765,351 @@ impl Node {
             }
         }
 
+        self.run_keepalive().await?;
+        self.flush_remote_signing().await?;
         self.check_topology_health();
+        self.persist_tick_metrics();
+        self.publish_status();
         Ok(())
     }
 
+    fn publish_status(&self) {
+        if let Some(board) = &self.status_board {
+            board.publish(NodeStatus {
+                node_id: self.comms.node_id().to_string(),
+                tick: self.tick_counter,
+                peers: self.peer_samples(),
+                stake: self.stake_attestation.clone(),
+                updated_at: chrono::Utc::now().timestamp(),
+            });
+        }
+    }
+
+    /// 应用积压的配置变化事件
+    fn apply_config_updates(&mut self) {
+        use tokio::sync::broadcast::error::TryRecvError;
+        let Some(updates) = self.config_updates.as_mut() else {
+            return;
+        };
+        let mut changes = Vec::new();
+        loop {
+            match updates.try_recv() {
+                Ok(change) => changes.push(change),
+                Err(TryRecvError::Lagged(skipped)) => {
+                    println!("[配置] 跳过 {} 个过期的配置变化事件", skipped);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => {
+                    self.config_updates = None;
+                    break;
+                }
+            }
+        }
+
+        for change in changes {
+            if change.bandwidth_changed() {
+                self.comms.set_bandwidth_budget(change.current.bandwidth.clone());
+            }
+            if change.privacy_changed() {
+                let level = crate::crypto::trust::measured_privacy_level(
+                    &change.current.privacy_mode,
+                    self.min_performance_score,
+                    &self.crypto_profile,
+                );
+                self.trust.set_global_level(level);
+                println!("[配置] 隐私模式更新: {:?} -> {:?}", change.previous.privacy_mode, change.current.privacy_mode);
+            }
+            if change.max_peers_changed() {
+                self.topology.set_max_neighbors(change.current.max_peers);
+                println!("[配置] 邻居上限更新: {} -> {}", change.previous.max_peers, change.current.max_peers);
+            }
+            if change.work_schedule_changed() {
+                self.work_schedule.set_config(change.current.work_schedule.clone());
+                println!("[配置] 重任务调度窗口已更新");
+            }
+        }
+    }
+
+    fn peer_samples(&self) -> Vec<PeerSample> {
+        let policies = self.trust.policies();
+        let (primary, backups) = self.topology.neighbor_sets();
+        primary
+            .into_iter()
+            .map(|peer| (peer, "primary"))
+            .chain(backups.into_iter().map(|peer| (peer, "backup")))
+            .map(|(peer, role)| PeerSample {
+                similarity: self.topology.peer_snapshot(&peer).map(|s| s.similarity).unwrap_or(0.0),
+                trust: policies.get(&peer).map(|p| format!("{:?}", p.trust)),
+                role: role.to_string(),
+                peer_id: peer,
+            })
+            .collect()
+    }
+
+    fn persist_tick_metrics(&self) {
+        if let Some(store) = &self.stats_store {
+            let metrics = TickMetrics::from_stats(self.stats.lock().unwrap().get_stats());
+            if let Err(e) = store.record_tick(&metrics) {
+                eprintln!("[统计] 写入 tick 指标失败: {:?}", e);
+            }
+            if self.tick_counter % PEER_SNAPSHOT_TICKS == 0 {
+                if let Err(e) = store.record_peers(metrics.timestamp, &self.peer_samples()) {
+                    eprintln!("[统计] 写入邻居快照失败: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// 发送到期的保活 ping，并立即处理判定死亡的节点
+    async fn run_keepalive(&mut self) -> Result<()> {
+        for (target, nonce) in self.comms.due_keepalive_pings() {
+            let ping = GgbMessage::Ping {
+                sender: self.comms.node_id().to_string(),
+                target,
+                nonce,
+            };
+            self.publish_signed(ping).await?;
+        }
+
+        for change in self.comms.detect_dead_peers() {
+            println!(
+                "[保活] 节点状态变化: {} {:?} -> {:?}",
+                change.peer_id, change.from, change.to
+            );
+        }
+        Ok(())
+    }
+
+    /// 发出远程签名请求和审批结果
+    async fn flush_remote_signing(&mut self) -> Result<()> {
+        for (target, message) in self.signing_relay.take_outbound() {
+            let payload = GgbMessage::RemoteSigning {
+                sender: self.comms.node_id(),
+                target,
+                message,
+            };
+            self.publish_signed(payload).await?;
+        }
+        Ok(())
+    }
+
+    /// 处理系统事件：睡眠前保存检查点并等待唤醒；唤醒或网络切换后刷新网络状态、
+    /// 重新建立连接并重新广播能力记录
+    async fn handle_system_event(
+        &mut self,
+        event: SystemEvent,
+        system_rx: &mut tokio::sync::broadcast::Receiver<SystemEvent>,
+    ) -> Result<()> {
+        if event == SystemEvent::Suspend {
+            println!("[系统] 即将睡眠，暂停训练");
+            if let Some(ref checkpoint_dir) = self.checkpoint_dir {
+                let checkpoint_path = checkpoint_dir.join("checkpoint_suspend.json");
+                match self.training.save_checkpoint_structured(&checkpoint_path) {
+                    Ok(_) => println!("[Checkpoint] 已保存睡眠前 checkpoint: {:?}", checkpoint_path),
+                    Err(e) => eprintln!("[Checkpoint] 保存失败: {:?}", e),
+                }
+            }
+            system_event_hub().wait_until_awake(system_rx).await;
+            println!("[系统] 已唤醒，恢复训练");
+        } else {
+            println!("[系统] {}", event);
+        }
+
+        // 唤醒后网络可能已经切换，不等下一次定期刷新
+        if !self.platform_devices {
+            self.device_manager.refresh();
+        }
+        let network_type = self.device_manager.get().network_type;
+        if network_type != self.comms.network_type() {
+            self.comms.update_network_type(network_type);
+            self.device_manager.update_network_type(network_type);
+        }
+        self.comms.reconnect().await;
+        self.broadcast_capability().await
+    }
+
+    /// 广播签名的能力记录（含本节点构建信息）
+    async fn broadcast_capability(&mut self) -> Result<()> {
+        let record = CapabilityRecord::new(self.comms.node_id(), &self.device_manager.get(), self.mode.is_watch_only());
+        let message = GgbMessage::Capability {
+            sender: record.node_id.clone(),
+            record: record.sign(&self.identity)?,
+        };
+        self.publish_signed(message).await?;
+
+        if let Some(attestation) = self.stake_attestation.clone() {
+            let message = GgbMessage::StakeAttestation {
+                sender: self.comms.node_id(),
+                attestation,
+            };
+            self.publish_signed(message).await?;
+        }
+        Ok(())
+    }
+
+    /// 设置本节点的质押证明（由证明服务按链上状态签发），下次广播能力记录时一并发出
+    pub fn set_stake_attestation(&mut self, attestation: StakeAttestation) -> Result<()> {
+        if attestation.node_id != self.identity.node_id() {
+            return Err(anyhow::anyhow!("质押证明属于其他节点: {}", attestation.node_id));
+        }
+        self.stake_attestation = Some(attestation);
+        Ok(())
+    }
+
+    /// 作为协调者下发任务：按构建准入策略剔除不合格的节点后签名广播
+    pub async fn assign_task(&mut self, mut manifest: TaskManifest) -> Result<SignedTaskManifest> {
+        if self.mode.is_watch_only() {
+            return Err(anyhow::anyhow!("观察模式下不能下发任务"));
+        }
+        let eligible = self.cluster.eligible_assignees(&manifest.assignees);
+        for refused in manifest.assignees.iter().filter(|a| !eligible.contains(a)) {
+            println!("[任务] 拒绝向 {} 分配任务: {:?}", refused, self.cluster.verdict(refused));
+        }
+        if eligible.is_empty() {
+            return Err(anyhow::anyhow!("任务 {} 没有满足构建策略的节点", manifest.task_id));
+        }
+        manifest.assignees = eligible;
+        manifest.coordinator_id = self.identity.node_id();
+        let signed = manifest.clone().sign(&self.identity);
+        let message = GgbMessage::TaskAssignment {
+            sender: self.comms.node_id(),
+            manifest: signed.clone(),
+        };
+        self.publish_signed(message).await?;
+        self.maybe_replicate(&manifest).await?;
+        Ok(signed)
+    }
+
+    /// 抽中的任务以另一个任务 ID 复制给一个验证节点执行，执行者无法察觉
+    async fn maybe_replicate(&mut self, manifest: &TaskManifest) -> Result<()> {
+        let Some(redundancy) = self.redundancy.clone() else {
+            return Ok(());
+        };
+        let (primary, backups) = self.topology.neighbor_sets();
+        let candidates = self.cluster.eligible_assignees(&[primary, backups].concat());
+        let now = chrono::Utc::now().timestamp();
+        let Some(replica) = redundancy.plan(&manifest.task_id, &manifest.assignees, &candidates, now) else {
+            return Ok(());
+        };
+        let mut replica_manifest = manifest.clone();
+        replica_manifest.task_id = replica.replica_task_id;
+        replica_manifest.assignees = vec![replica.validator.clone()];
+        let message = GgbMessage::TaskAssignment {
+            sender: self.comms.node_id(),
+            manifest: replica_manifest.sign(&self.identity),
+        };
+        self.publish_signed(message).await?;
+        println!("[冗余验证] 任务 {} 已复制给 {}", manifest.task_id, replica.validator);
+        Ok(())
+    }
+
+    /// 训练引擎空闲时开始下一个已接受的任务
+    fn start_next_task(&mut self) {
+        if self.training.active_task_id().is_some() {
+            return;
+        }
+        if let Some(next) = self.assigned_tasks.first() {
+            if let Err(e) = self.training.start_task(&next.manifest.task_id, Some(&next.manifest_hash)) {
+                eprintln!("[任务] 无法开始任务 {}: {}", next.manifest.task_id, e);
+            }
+        }
+    }
+
+    /// 完成当前训练任务：通知训练引擎的接收方（如链上贡献提交），并开始下一个任务
+    pub fn complete_current_task(&mut self, samples_processed: u64, batches_processed: u64) -> Result<TaskCompletion> {
+        let completion = self.training.complete_task(samples_processed, batches_processed)?;
+        self.assigned_tasks.retain(|t| t.manifest.task_id != completion.task_id);
+        // 模拟：以训练后的 embedding 作为任务输出，供协调者做冗余比较
+        self.completed_outputs.push((completion.task_id.clone(), self.training.embedding()));
+        println!(
+            "[任务] 完成任务 {}: 用时 {}s, 样本 {}, 批次 {}",
+            completion.task_id,
+            completion.duration_seconds(),
+            completion.samples_processed,
+            completion.batches_processed
+        );
+        self.start_next_task();
+        Ok(completion)
+    }
+
+    /// 记录协调者选举结果，之后该轮的任务清单必须由此协调者签名
+    pub fn record_election(&self, record: ElectionRecord) {
+        println!("[任务] 轮次 {} 协调者: {}", record.round, record.coordinator_id);
+        self.task_manifests.record_election(record);
+    }
+
+    /// 读取轮次产物，本地已被归档时从冷存储透明取回（用于争议处理）
+    pub async fn load_artifact(&self, path: &std::path::Path) -> Result<Vec<u8>> {
+        if path.exists() {
+            return Ok(std::fs::read(path)?);
+        }
+        match &self.archiver {
+            Some(archiver) => archiver.retrieve(path).await,
+            None => Err(anyhow::anyhow!("{} 不存在且未启用归档", path.display())),
+        }
+    }
+
+    /// 以委员会成员身份开始本 epoch 的计算分数共识轮，`local` 为本节点观测到的分数
+    pub async fn start_score_round(&mut self, committee: Committee, local: ScoreVector) -> Result<()> {
+        if !committee.contains(&self.identity.node_id()) {
+            return Err(anyhow::anyhow!("本节点不在 epoch {} 的分数委员会中", local.epoch));
+        }
+        let mut round = BftRound::new(BftConfig::default(), committee, self.identity.clone(), local);
+        let messages = round.start()?;
+        self.score_round = Some(round);
+        self.publish_score_messages(messages).await
+    }
+
+    /// 取出已达成一致的分数证书（用于提交上链），未决定时返回 None
+    pub fn take_score_certificate(&mut self) -> Option<CommitCertificate> {
+        let certificate = self.score_round.as_ref()?.decided()?.clone();
+        self.score_round = None;
+        Some(certificate)
+    }
+
+    async fn publish_score_messages(&mut self, messages: Vec<SignedBftMessage>) -> Result<()> {
+        for message in messages {
+            let payload = GgbMessage::ScoreConsensus {
+                sender: self.comms.node_id().to_string(),
+                message,
+            };
+            self.publish_signed(payload).await?;
+        }
+        Ok(())
+    }
+
+    /// 轮换节点密钥并向对端广播公告
+    ///
+    /// 公告中的新公钥由旧密钥签名，消息外层则已使用新密钥签名。
+    pub async fn rotate_identity(&mut self) -> Result<KeyRotation> {
+        if self.mode.is_watch_only() {
+            return Err(anyhow::anyhow!("观察模式下不签名，无法轮换密钥"));
+        }
+        let rotation = self.identity.rotate()?;
+        let announcement = GgbMessage::KeyRotation {
+            sender: rotation.old_node_id.clone(),
+            rotation: rotation.clone(),
+        };
+        self.publish_signed(announcement).await?;
+        self.comms.peer_id = rotation.new_node_id.clone();
+        Ok(rotation)
+    }
+
     async fn handle_network_event(&mut self, event: IrohEvent) -> Result<()> {
+        if self.transport_killed() {
+            return Ok(());
+        }
         match event {
             IrohEvent::Gossip { source, data } => {
-                if let Ok(signed) = serde_json::from_slice::<SignedGossip>(&data) {
-                    if self.consensus.verify(&signed) {
-                        self.handle_signed_message(signed, source.to_string())
-                            .await?;
+                // 在短窗口内收集突发的 gossip，外层认证和消息签名都批量验证
+                let frames = self.comms.collect_gossip_batch(source, data).await;
+                let opened = self.comms.open_gossip_batch(frames);
+                let verdicts = self
+                    .consensus
+                    .verify_batch(&opened.iter().map(|(_, signed)| signed).collect::<Vec<_>>());
+                for ((source, signed), valid) in opened.into_iter().zip(verdicts) {
+                    if valid {
+                        self.handle_signed_message(signed, source).await?;
                     } else {
                         eprintln!("签名验证失败，来自 {:?}", source);
                     }
@@ -257,6 +1117,9 @@ impl Node {
             }
             IrohEvent::PeerDiscovered { peer, addr } => {
                 println!("[Iroh] 发现节点 {} @ {}", peer, addr);
+                let policy = self.trust.policy_for(&peer, crate::crypto::trust::is_lan_addr(&addr));
+                println!("[信任] 节点 {} 信任级别 {:?}，加密级别 {:?}", peer, policy.trust, policy.level);
+                self.stats.lock().unwrap().record_peer_policy(&peer, Some(policy));
                 // 将发现的节点添加到订阅列表
                 self.comms.add_peer(peer);
                 
@@ -268,12 +1131,26 @@ impl Node {
             }
             IrohEvent::PeerExpired { peer } => {
                 println!("[Iroh] 节点离线 {}", peer);
+                self.trust.forget(&peer);
+                self.stats.lock().unwrap().record_peer_policy(&peer, None);
                 self.comms.remove_peer(&peer);
+                self.cluster.remove(&peer);
+                // 立即从拓扑中移除，避免继续向失联节点路由
+                self.topology.mark_unreachable(&peer);
             }
             IrohEvent::ConnectionEstablished { peer } => {
                 println!("[Iroh] 连接建立: {}", peer);
-                // 连接建立后，可以添加到订阅列表
-                self.comms.add_peer(peer);
+                // 未出示质押证明的节点处于观察期，超出连接/主题上限时不加入订阅列表
+                match self.comms.admit_connection(&peer) {
+                    Ok(level) => {
+                        println!("[准入] 节点 {} 准入级别 {:?}", peer, level);
+                        self.comms.add_peer(peer);
+                    }
+                    Err(e) => {
+                        println!("[准入] 拒绝连接: {}", e);
+                        return Ok(());
+                    }
+                }
                 
                 // 当连接建立时，尝试获取节点地址并测量网络距离
                 // 注意：这里我们没有直接的地址，需要通过其他方式获取
@@ -281,6 +1158,7 @@ impl Node {
             }
             IrohEvent::ConnectionClosed { peer } => {
                 println!("[Iroh] 连接断开: {}", peer);
+                self.comms.release_connection(&peer);
                 self.comms.remove_peer(&peer);
             }
         }
@@ -288,6 +1166,10 @@ impl Node {
     }
 
     async fn publish_signed(&mut self, payload: GgbMessage) -> Result<()> {
+        // 观察模式从不签名或发布消息
+        if self.mode.is_watch_only() || self.transport_killed() {
+            return Ok(());
+        }
         let signed = self.consensus.sign(payload)?;
         self.comms.publish(&signed)?;
         if !self.comms.broadcast_realtime(&signed).await {
@@ -298,8 +1180,104 @@ impl Node {
 
     async fn handle_signed_message(&mut self, signed: SignedGossip, source: String) -> Result<()> {
         match &signed.payload {
+            GgbMessage::Ping { sender, target, nonce } => {
+                if target == &self.comms.node_id() {
+                    self.comms.record_peer_activity(sender);
+                    let pong = GgbMessage::Pong {
+                        sender: self.comms.node_id().to_string(),
+                        target: sender.clone(),
+                        nonce: *nonce,
+                    };
+                    self.publish_signed(pong).await?;
+                }
+            }
+            GgbMessage::Pong { sender, target, nonce } => {
+                if target == &self.comms.node_id() {
+                    if let Some(rtt) = self.comms.record_pong(sender, *nonce) {
+                        println!("[保活] {} pong, RTT {:?}", sender, rtt);
+                    }
+                }
+            }
+            GgbMessage::TaskAssignment { sender, manifest } => {
+                let now = chrono::Utc::now().timestamp();
+                match self.task_manifests.verify(manifest, now) {
+                    Ok(verified) if self.mode.is_watch_only() => {
+                        println!(
+                            "[观察模式] 同步任务清单 {} (轮次 {})",
+                            verified.manifest.task_id, verified.manifest.round
+                        );
+                    }
+                    Ok(verified) => {
+                        let node_id = self.comms.node_id();
+                        if verified.manifest.assignees.iter().any(|a| a == &node_id)
+                            && !self.assigned_tasks.iter().any(|t| t.manifest.task_id == verified.manifest.task_id)
+                        {
+                            println!(
+                                "[任务] 接受任务 {} (轮次 {}, 清单 {})",
+                                verified.manifest.task_id, verified.manifest.round, verified.manifest_hash
+                            );
+                            self.assigned_tasks.push(verified);
+                            self.start_next_task();
+                        }
+                    }
+                    Err(e) => eprintln!("[任务] 拒绝来自 {} 的任务分配: {}", sender, e),
+                }
+            }
+            GgbMessage::KeyRotation { sender, rotation } => {
+                if sender == &rotation.old_node_id && self.consensus.apply_key_rotation(rotation) {
+                    println!("[身份] 节点密钥轮换: {} -> {}", rotation.old_node_id, rotation.new_node_id);
+                    self.comms.remove_peer(sender);
+                    self.comms.add_peer(rotation.new_node_id.clone());
+                } else {
+                    eprintln!("[身份] 无效的密钥轮换公告，来自 {}", sender);
+                }
+            }
+            GgbMessage::Capability { sender, record } => {
+                if sender != &record.record.node_id {
+                    eprintln!("[集群] 能力记录发送者不匹配: {}", sender);
+                } else if let Some(verdict) = self.cluster.record_capability(record) {
+                    if !verdict.is_allowed() {
+                        println!(
+                            "[集群] 节点 {} 的构建不满足策略 ({:?}): {}",
+                            sender,
+                            verdict,
+                            record.record.build.short()
+                        );
+                    }
+                }
+            }
+            GgbMessage::StakeAttestation { sender, attestation } => {
+                if sender != &attestation.node_id {
+                    eprintln!("[准入] 质押证明发送者不匹配: {}", sender);
+                } else {
+                    match self.comms.admission().present(attestation) {
+                        Ok(level) => println!("[准入] 节点 {} 出示质押证明，准入级别 {:?}", sender, level),
+                        Err(e) => eprintln!("[准入] 拒绝节点 {} 的质押证明: {}", sender, e),
+                    }
+                }
+            }
+            GgbMessage::RemoteSigning { sender, target, message } => {
+                if target == &self.comms.node_id() {
+                    self.signing_relay.handle(sender, message.clone());
+                }
+            }
+            GgbMessage::ScoreConsensus { sender, message } => {
+                let Some(round) = self.score_round.as_mut() else {
+                    return Ok(());
+                };
+                if sender != &message.signer {
+                    eprintln!("[BFT] 共识消息发送者不匹配: {}", sender);
+                    return Ok(());
+                }
+                match round.handle(message.clone()) {
+                    Ok(messages) => self.publish_score_messages(messages).await?,
+                    Err(e) => eprintln!("[BFT] 拒绝来自 {} 的共识消息: {}", sender, e),
+                }
+            }
             GgbMessage::Heartbeat { peer, .. } => {
+                self.comms.record_peer_activity(peer);
                 self.consensus.update_stake(peer, 0.0, 0.0, 0.05);
+                self.consensus.reputation().record(peer, ReputationEvent::Heartbeat);
                 // self.stats.record_heartbeat_received(peer);
                 println!("收到 {} 的心跳 (via {source})", peer);
             }
@@ -345,7 +1323,11 @@ impl Node {
                         snapshot.position.lon
                     );
                 }
-                if self.should_send_sparse_update(sender) {
+                if !self.mode.is_watch_only()
+                    && self.should_send_sparse_update(sender)
+                    && self.work_schedule.is_allowed(WorkKind::GradientSync)
+                    && self.power_policy.network_allowed(self.comms.network_type())
+                {
                     if self.comms.allow_sparse_update() {
                         // let update = self.inference.make_sparse_update(16);
                         let update = crate::types::SparseUpdate {
@@ -364,6 +1346,7 @@ impl Node {
                     }
                 }
             }
+            GgbMessage::SparseUpdate { .. } | GgbMessage::DenseSnapshot { .. } if self.mode.is_watch_only() => {}
             GgbMessage::SparseUpdate { sender, update } => {
                 // self.stats.record_sparse_update_received(sender);
                 self.training.apply_sparse_update(update);
@@ -372,10 +1355,58 @@ impl Node {
                 // self.stats.record_dense_snapshot_received(sender);
                 self.training.apply_dense_snapshot(snapshot);
             }
+            GgbMessage::TaskResult { sender, task_id, output } => {
+                let verdict = self
+                    .redundancy
+                    .as_ref()
+                    .and_then(|redundancy| redundancy.record_output(sender, task_id, output));
+                if let Some(verdict) = verdict {
+                    self.handle_validation_verdict(&verdict);
+                }
+            }
         }
         Ok(())
     }
 
+    /// 广播已完成任务的输出
+    async fn publish_task_outputs(&mut self) -> Result<()> {
+        for (task_id, output) in std::mem::take(&mut self.completed_outputs) {
+            let message = GgbMessage::TaskResult {
+                sender: self.comms.node_id(),
+                task_id,
+                output,
+            };
+            self.publish_signed(message).await?;
+        }
+        Ok(())
+    }
+
+    /// 冗余比较的结论计入双方信誉；不一致时由验证器通知链上质疑接收方
+    fn handle_validation_verdict(&self, verdict: &ValidationVerdict) {
+        let event = ReputationEvent::Verification {
+            passed: verdict.matched,
+        };
+        self.consensus.reputation().record(&verdict.primary, event.clone());
+        self.consensus.reputation().record(&verdict.validator, event);
+        if verdict.matched {
+            println!(
+                "[冗余验证] 任务 {} 输出一致 ({} / {}, 最大偏差 {:.2e})",
+                verdict.task_id, verdict.primary, verdict.validator, verdict.max_deviation
+            );
+        } else {
+            println!(
+                "[冗余验证] 任务 {} 输出不一致 ({} / {}, 最大偏差 {:.2e})",
+                verdict.task_id, verdict.primary, verdict.validator, verdict.max_deviation
+            );
+        }
+        if let Some(redundancy) = &self.redundancy {
+            let stats = redundancy.stats();
+            let mut node_stats = self.stats.lock().unwrap();
+            node_stats.add_custom_metric("redundancy_matched".to_string(), stats.matched as f64);
+            node_stats.add_custom_metric("redundancy_mismatched".to_string(), stats.mismatched as f64);
+        }
+    }
+
     fn should_send_sparse_update(&self, target: &str) -> bool {
         let primary = self.topology.select_neighbors();
         if primary.iter().any(|peer| peer == target) {
@@ -385,6 +1416,61 @@ impl Node {
         false
     }
 
+    /// 每个 tick 从流水线取一个就绪的批次喂给训练引擎，并定期记录各阶段吞吐
+    fn feed_pipeline_batch(&mut self) -> Result<()> {
+        let Some(pipeline) = &self.pipeline else {
+            return Ok(());
+        };
+        if let Some(batch) = pipeline.try_next_batch() {
+            self.training.feed_batch(&batch)?;
+        }
+        if self.tick_counter % 10 == 0 {
+            let mut stats = self.stats.lock().unwrap();
+            for stage in pipeline.metrics() {
+                stats.add_custom_metric(format!("pipeline_{}_per_sec", stage.name), stage.throughput_per_sec);
+            }
+        }
+        Ok(())
+    }
+
+    /// 定期在留出集上评估：更新质量评分和统计，损失长期不改善时停止喂入训练数据
+    fn run_evaluation(&mut self) {
+        let Some(evaluator) = self.evaluator.as_mut() else {
+            return;
+        };
+        if evaluator.stopped() || self.tick_counter % evaluator.config().interval_ticks.max(1) != 0 {
+            return;
+        }
+        let patience = evaluator.config().patience;
+        let outcome = match evaluator.run(&self.training) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("[评估] 评估失败: {}", e);
+                return;
+            }
+        };
+        let metrics = &outcome.metrics;
+        println!(
+            "[评估] 第 {} 次: 损失 {:.4}, 准确率 {:.1}%{}",
+            metrics.evaluation,
+            metrics.loss,
+            metrics.accuracy * 100.0,
+            metrics
+                .perplexity
+                .map(|perplexity| format!(", 困惑度 {:.2}", perplexity))
+                .unwrap_or_default()
+        );
+        if let Some(path) = &outcome.checkpoint {
+            println!("[评估] 已保存最佳 checkpoint: {}", path.display());
+        }
+        self.training.set_quality_score(metrics.quality_score());
+        self.stats.lock().unwrap().record_evaluation(outcome.metrics.clone());
+        if outcome.stop {
+            println!("[评估] 连续 {} 次评估未改善，提前停止训练", patience);
+            self.pipeline = None;
+        }
+    }
+
     fn check_topology_health(&self) {
         let (primary, backups) = self.topology.neighbor_sets();
         if primary.len() < self.topology.max_neighbors() && !backups.is_empty() {
@@ -409,6 +1495,9 @@ impl Node {
             // 移动网络下跳过密集快照
             return Ok(());
         }
+        if !self.work_schedule.is_allowed(WorkKind::GradientSync) || !self.power_policy.network_allowed(network_type) {
+            return Ok(());
+        }
 
         let snapshot = self.training.tensor_snapshot();
         let msg = GgbMessage::DenseSnapshot {
@@ -418,4 +1507,19 @@ impl Node {
         self.publish_signed(msg).await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// 配置了数据集清单时，流水线只能读取清单校验过的分块
+fn check_pipeline_dataset(config: &AppConfig, dataset: Option<&VerifiedDataset>) -> Result<()> {
+    if config.dataset.manifest_path.is_none() {
+        return Ok(());
+    }
+    let dataset = dataset.ok_or_else(|| anyhow::anyhow!("数据集清单未通过校验"))?;
+    if let SourceConfig::Jsonl { path } = &config.pipeline.source {
+        let data_dir = config.dataset.resolved_data_dir().unwrap_or_default();
+        if dataset.chunk_for_file(&data_dir, path).is_none() {
+            return Err(anyhow::anyhow!("数据源 {} 不在数据集清单中", path.display()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! 并行执行开关
+//!
+//! 原生平台上 rayon 的全局线程池随时可用；浏览器中只有启用 `wasm-threads` 特性、
+//! 页面处于跨源隔离状态并成功初始化 Worker 线程池后才能并行，否则 rayon 会因无法创建线程而崩溃。
+//! 矩阵运算和批量加密通过这里判断走并行还是顺序路径。
+
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static THREAD_POOL_READY: AtomicBool = AtomicBool::new(false);
+
+/// 当前是否可以使用 rayon 并行
+pub fn available() -> bool {
+    cfg!(not(target_arch = "wasm32")) || THREAD_POOL_READY.load(Ordering::Acquire)
+}
+
+/// 浏览器线程池初始化完成后调用
+pub fn mark_thread_pool_ready() {
+    THREAD_POOL_READY.store(true, Ordering::Release);
+}
+
+/// 按行处理矩阵：`data` 由长度为 `row_len` 的行组成，`f(行号, 行)`，可并行时按行并行
+pub fn for_each_row<F>(data: &mut [f32], row_len: usize, f: F)
+where
+    F: Fn(usize, &mut [f32]) + Send + Sync,
+{
+    if row_len == 0 {
+        return;
+    }
+    if available() {
+        data.par_chunks_mut(row_len).enumerate().for_each(|(i, row)| f(i, row));
+    } else {
+        data.chunks_mut(row_len).enumerate().for_each(|(i, row)| f(i, row));
+    }
+}
+
+/// 矩阵乘法 `a (m×k) · b (k×n)`，行主序
+pub fn matmul(a: &[f32], b: &[f32], m: usize, k: usize, n: usize) -> Vec<f32> {
+    assert_eq!(a.len(), m * k, "矩阵 a 的大小与 m×k 不一致");
+    assert_eq!(b.len(), k * n, "矩阵 b 的大小与 k×n 不一致");
+    let mut out = vec![0.0; m * n];
+    for_each_row(&mut out, n, |i, row| {
+        for (p, a_ip) in a[i * k..(i + 1) * k].iter().enumerate() {
+            for (o, b_pj) in row.iter_mut().zip(&b[p * n..(p + 1) * n]) {
+                *o += a_ip * b_pj;
+            }
+        }
+    });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matmul_matches_naive() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let b = [7.0, 8.0, 9.0, 10.0, 11.0, 12.0];
+        assert_eq!(matmul(&a, &b, 2, 3, 2), vec![58.0, 64.0, 139.0, 154.0]);
+        assert!(available());
+    }
+}
@@ -0,0 +1,228 @@
+//! 插件宿主 API（wasmi）
+//!
+//! 模块从 `williw` 命名空间导入以下函数，每个函数需要清单中申请对应权限：
+//!
+//! | 函数 | 签名 | 权限 |
+//! |------|------|------|
+//! | `input_len` | `() -> i64` | `read_input` |
+//! | `read_input` | `(offset: i64, ptr: i32, len: i32) -> i32`，返回读取的字节数 | `read_input` |
+//! | `write_output` | `(ptr: i32, len: i32) -> i32`，成功返回 0，超出输出上限返回 -1 | `write_output` |
+//! | `report_progress` | `(permille: i32)`，0-1000 | `report_progress` |
+//! | `log` | `(level: i32, ptr: i32, len: i32)`，level 0-4 对应 error..trace | `log` |
+//!
+//! 内存越界等错误让插件陷入 trap，不会影响宿主。
+
+use super::{PluginError, PluginManifest, PluginPermission};
+use wasmi::core::TrapCode;
+use wasmi::{Caller, Config, Engine, Extern, ExternType, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// 宿主函数所在的导入命名空间
+pub const HOST_MODULE: &str = "williw";
+
+/// 宿主函数及其所需权限
+const HOST_FUNCTIONS: [(&str, PluginPermission); 5] = [
+    ("input_len", PluginPermission::ReadInput),
+    ("read_input", PluginPermission::ReadInput),
+    ("write_output", PluginPermission::WriteOutput),
+    ("report_progress", PluginPermission::ReportProgress),
+    ("log", PluginPermission::Log),
+];
+
+/// 一次运行的结果
+#[derive(Debug, Clone, Default)]
+pub struct PluginRun {
+    pub output: Vec<u8>,
+    /// 最后上报的进度（0-1）
+    pub progress: f32,
+    pub fuel_consumed: u64,
+}
+
+/// 运行期间的宿主状态
+struct HostState {
+    plugin: String,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    max_output_bytes: usize,
+    output_overflow: bool,
+    progress: f32,
+    on_progress: Box<dyn FnMut(f32) + Send>,
+    limits: StoreLimits,
+}
+
+/// 已编译并通过导入检查的模块
+#[derive(Debug, Clone)]
+pub(super) struct ValidatedModule {
+    engine: Engine,
+    module: Module,
+}
+
+impl ValidatedModule {
+    pub(super) fn new(manifest: &PluginManifest, bytes: Vec<u8>) -> Result<Self, PluginError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &bytes).map_err(|e| PluginError::InvalidModule(e.to_string()))?;
+
+        for import in module.imports() {
+            let known = HOST_FUNCTIONS.iter().find(|(name, _)| *name == import.name());
+            let (name, permission) = match known {
+                Some(entry) if import.module() == HOST_MODULE && matches!(import.ty(), ExternType::Func(_)) => entry,
+                _ => {
+                    return Err(PluginError::UnknownImport {
+                        module: import.module().to_string(),
+                        name: import.name().to_string(),
+                    })
+                }
+            };
+            if !manifest.allows(*permission) {
+                return Err(PluginError::PermissionDenied {
+                    name: name.to_string(),
+                    permission: *permission,
+                });
+            }
+        }
+
+        let exports: Vec<_> = module.exports().map(|e| (e.name().to_string(), e.ty().clone())).collect();
+        if !exports.iter().any(|(name, ty)| name == "memory" && matches!(ty, ExternType::Memory(_))) {
+            return Err(PluginError::MissingExport("memory"));
+        }
+        if !exports.iter().any(|(name, ty)| name == "run" && matches!(ty, ExternType::Func(_))) {
+            return Err(PluginError::MissingExport("run"));
+        }
+        Ok(Self { engine, module })
+    }
+
+    pub(super) fn run(
+        &self,
+        manifest: &PluginManifest,
+        input: Vec<u8>,
+        on_progress: impl FnMut(f32) + Send + 'static,
+    ) -> Result<PluginRun, PluginError> {
+        let limits = &manifest.limits;
+        let state = HostState {
+            plugin: manifest.name.clone(),
+            input,
+            output: Vec::new(),
+            max_output_bytes: limits.max_output_bytes,
+            output_overflow: false,
+            progress: 0.0,
+            on_progress: Box::new(on_progress),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(limits.max_memory_mb as usize * 1024 * 1024)
+                .instances(1)
+                .build(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(limits.fuel).map_err(|e| PluginError::Trap(e.to_string()))?;
+
+        let linker = host_linker(&self.engine).map_err(|e| PluginError::InvalidModule(e.to_string()))?;
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| PluginError::InvalidModule(e.to_string()))?;
+        let run = instance
+            .get_typed_func::<(), i32>(&store, "run")
+            .map_err(|_| PluginError::MissingExport("run"))?;
+
+        let result = run.call(&mut store, ());
+        let fuel_consumed = limits.fuel - store.get_fuel().unwrap_or(0);
+        let code = result.map_err(|e| match e.as_trap_code() {
+            Some(TrapCode::OutOfFuel) => PluginError::OutOfFuel(limits.fuel),
+            _ => PluginError::Trap(e.to_string()),
+        })?;
+
+        let state = store.into_data();
+        if state.output_overflow {
+            return Err(PluginError::OutputTooLarge(state.max_output_bytes));
+        }
+        if code != 0 {
+            return Err(PluginError::Exit(code));
+        }
+        Ok(PluginRun {
+            output: state.output,
+            progress: state.progress,
+            fuel_consumed,
+        })
+    }
+}
+
+fn memory(caller: &Caller<'_, HostState>) -> Result<wasmi::Memory, wasmi::Error> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("插件未导出 memory"))
+}
+
+fn host_linker(engine: &Engine) -> Result<Linker<HostState>, wasmi::Error> {
+    let mut linker = Linker::new(engine);
+    linker.func_wrap(HOST_MODULE, "input_len", |caller: Caller<'_, HostState>| -> i64 {
+        caller.data().input.len() as i64
+    })?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "read_input",
+        |mut caller: Caller<'_, HostState>, offset: i64, ptr: i32, len: i32| -> Result<i32, wasmi::Error> {
+            let memory = memory(&caller)?;
+            let (data, state) = memory.data_and_store_mut(&mut caller);
+            let start = (offset.max(0) as usize).min(state.input.len());
+            let end = start.saturating_add(len.max(0) as usize).min(state.input.len());
+            let dest = data
+                .get_mut(ptr as u32 as usize..)
+                .and_then(|dest| dest.get_mut(..end - start))
+                .ok_or_else(|| wasmi::Error::new("read_input 越界"))?;
+            dest.copy_from_slice(&state.input[start..end]);
+            Ok((end - start) as i32)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "write_output",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<i32, wasmi::Error> {
+            let memory = memory(&caller)?;
+            let (data, state) = memory.data_and_store_mut(&mut caller);
+            let src = data
+                .get(ptr as u32 as usize..)
+                .and_then(|src| src.get(..len.max(0) as usize))
+                .ok_or_else(|| wasmi::Error::new("write_output 越界"))?;
+            if state.output.len() + src.len() > state.max_output_bytes {
+                state.output_overflow = true;
+                return Ok(-1);
+            }
+            state.output.extend_from_slice(src);
+            Ok(0)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "report_progress",
+        |mut caller: Caller<'_, HostState>, permille: i32| {
+            let state = caller.data_mut();
+            state.progress = permille.clamp(0, 1000) as f32 / 1000.0;
+            let progress = state.progress;
+            (state.on_progress)(progress);
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            let memory = memory(&caller)?;
+            let message = memory
+                .data(&caller)
+                .get(ptr as u32 as usize..)
+                .and_then(|bytes| bytes.get(..len.max(0) as usize))
+                .ok_or_else(|| wasmi::Error::new("log 越界"))?;
+            let level = match level {
+                0 => log::Level::Error,
+                1 => log::Level::Warn,
+                2 => log::Level::Info,
+                3 => log::Level::Debug,
+                _ => log::Level::Trace,
+            };
+            log::log!(level, "[插件 {}] {}", caller.data().plugin, String::from_utf8_lossy(message));
+            Ok(())
+        },
+    )?;
+    Ok(linker)
+}
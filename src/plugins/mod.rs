@@ -0,0 +1,319 @@
+//! WASM 任务插件
+//!
+//! 第三方可以把新的任务类型（数据预处理、自定义评测等）打包成 WASM 模块分发。每个插件目录包含：
+//! - `plugin.toml`：名称、版本、任务类型、模块文件、模块哈希、申请的能力权限和资源上限
+//! - WASM 模块：从 `williw` 命名空间导入宿主函数，导出 `memory` 和 `run() -> i32`
+//!
+//! 加载时校验清单、模块哈希，并检查模块导入的每个宿主函数都在已申请的权限之内；
+//! 运行在 wasmi 解释器中，受燃料（指令数）、线性内存和输出大小限制。
+
+mod host;
+
+pub use host::{PluginRun, HOST_MODULE};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// 插件目录中的清单文件名
+pub const MANIFEST_FILE: &str = "plugin.toml";
+
+/// 插件可申请的能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPermission {
+    /// 读取输入分块（`input_len`、`read_input`）
+    ReadInput,
+    /// 写出结果（`write_output`）
+    WriteOutput,
+    /// 上报进度（`report_progress`）
+    ReportProgress,
+    /// 写节点日志（`log`）
+    Log,
+}
+
+/// 插件资源上限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginLimits {
+    /// 燃料上限（约等于执行的指令数）
+    pub fuel: u64,
+    /// 线性内存上限（MB）
+    pub max_memory_mb: u32,
+    /// 输出上限（字节）
+    pub max_output_bytes: usize,
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 10_000_000_000,
+            max_memory_mb: 64,
+            max_output_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// 插件清单（`plugin.toml`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    /// 插件处理的任务类型，全局唯一
+    pub task_type: String,
+    #[serde(default)]
+    pub description: String,
+    /// 模块文件（相对插件目录）
+    pub module: PathBuf,
+    /// 模块的 blake3 哈希（十六进制）
+    pub module_hash: String,
+    #[serde(default)]
+    pub permissions: Vec<PluginPermission>,
+    #[serde(default)]
+    pub limits: PluginLimits,
+}
+
+impl PluginManifest {
+    /// 检查字段合法性；模块路径不能逃出插件目录
+    pub fn validate(&self) -> Result<(), PluginError> {
+        let valid_id = |s: &str| {
+            !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        if !valid_id(&self.name) {
+            return Err(PluginError::InvalidManifest(format!("插件名 {:?} 只能包含字母、数字和 -_.", self.name)));
+        }
+        if !valid_id(&self.task_type) {
+            return Err(PluginError::InvalidManifest(format!(
+                "任务类型 {:?} 只能包含字母、数字和 -_.",
+                self.task_type
+            )));
+        }
+        if self.module.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(PluginError::InvalidManifest(format!(
+                "模块路径 {} 必须是插件目录内的相对路径",
+                self.module.display()
+            )));
+        }
+        if self.limits.fuel == 0 || self.limits.max_memory_mb == 0 {
+            return Err(PluginError::InvalidManifest("燃料和内存上限必须大于 0".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn allows(&self, permission: PluginPermission) -> bool {
+        self.permissions.contains(&permission)
+    }
+}
+
+/// 插件错误
+#[derive(Debug, thiserror::Error)]
+pub enum PluginError {
+    #[error("插件清单无效: {0}")]
+    InvalidManifest(String),
+
+    #[error("模块哈希不匹配: 清单 {expected}, 实际 {actual}")]
+    HashMismatch { expected: String, actual: String },
+
+    #[error("模块无效: {0}")]
+    InvalidModule(String),
+
+    #[error("模块导入 {module}::{name} 不是宿主提供的函数")]
+    UnknownImport { module: String, name: String },
+
+    #[error("模块导入 {name} 需要权限 {permission:?}，清单未申请")]
+    PermissionDenied { name: String, permission: PluginPermission },
+
+    #[error("模块缺少导出 {0}")]
+    MissingExport(&'static str),
+
+    #[error("任务类型 {task_type} 已由插件 {existing} 注册")]
+    DuplicateTaskType { task_type: String, existing: String },
+
+    #[error("插件燃料耗尽（上限 {0}）")]
+    OutOfFuel(u64),
+
+    #[error("插件输出超出上限 {0} 字节")]
+    OutputTooLarge(usize),
+
+    #[error("插件执行失败: {0}")]
+    Trap(String),
+
+    #[error("插件返回错误码 {0}")]
+    Exit(i32),
+}
+
+/// 已加载并通过校验的插件
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub manifest: PluginManifest,
+    pub dir: PathBuf,
+    module: host::ValidatedModule,
+}
+
+impl Plugin {
+    /// 从插件目录加载：读取清单、校验哈希和导入权限
+    pub fn load(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest: PluginManifest = toml::from_str(
+            &std::fs::read_to_string(&manifest_path).with_context(|| format!("读取 {} 失败", manifest_path.display()))?,
+        )
+        .with_context(|| format!("解析 {} 失败", manifest_path.display()))?;
+        manifest.validate()?;
+
+        let module_path = dir.join(&manifest.module);
+        let bytes = std::fs::read(&module_path).with_context(|| format!("读取 {} 失败", module_path.display()))?;
+        let actual = blake3::hash(&bytes).to_hex().to_string();
+        if !actual.eq_ignore_ascii_case(&manifest.module_hash) {
+            return Err(PluginError::HashMismatch {
+                expected: manifest.module_hash.clone(),
+                actual,
+            }
+            .into());
+        }
+        let module = host::ValidatedModule::new(&manifest, bytes)?;
+        Ok(Self { manifest, dir, module })
+    }
+
+    /// 用一个输入分块运行插件，`on_progress` 收到 0-1 的进度
+    pub fn run(&self, input: Vec<u8>, on_progress: impl FnMut(f32) + Send + 'static) -> Result<PluginRun, PluginError> {
+        self.module.run(&self.manifest, input, on_progress)
+    }
+}
+
+/// 按任务类型索引的插件注册表
+#[derive(Debug, Default)]
+pub struct PluginRegistry {
+    plugins: BTreeMap<String, Plugin>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, plugin: Plugin) -> Result<(), PluginError> {
+        if let Some(existing) = self.plugins.get(&plugin.manifest.task_type) {
+            return Err(PluginError::DuplicateTaskType {
+                task_type: plugin.manifest.task_type.clone(),
+                existing: existing.manifest.name.clone(),
+            });
+        }
+        self.plugins.insert(plugin.manifest.task_type.clone(), plugin);
+        Ok(())
+    }
+
+    /// 加载目录下每个包含 `plugin.toml` 的子目录，返回加载失败的插件及原因
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> anyhow::Result<Vec<(PathBuf, anyhow::Error)>> {
+        let mut failures = Vec::new();
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(dir.as_ref())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.join(MANIFEST_FILE).is_file())
+            .collect();
+        entries.sort();
+        for path in entries {
+            if let Err(e) = Plugin::load(&path).and_then(|plugin| Ok(self.register(plugin)?)) {
+                failures.push((path, e));
+            }
+        }
+        Ok(failures)
+    }
+
+    pub fn get(&self, task_type: &str) -> Option<&Plugin> {
+        self.plugins.get(task_type)
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = &Plugin> {
+        self.plugins.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UPPERCASE: &str = r#"
+        (module
+          (import "williw" "input_len" (func $input_len (result i64)))
+          (import "williw" "read_input" (func $read_input (param i64 i32 i32) (result i32)))
+          (import "williw" "write_output" (func $write_output (param i32 i32) (result i32)))
+          (import "williw" "report_progress" (func $report_progress (param i32)))
+          (memory (export "memory") 1)
+          (func (export "run") (result i32)
+            (local $n i32) (local $i i32)
+            (local.set $n (call $read_input (i64.const 0) (i32.const 0) (i32.wrap_i64 (call $input_len))))
+            (block $done
+              (loop $next
+                (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+                (i32.store8 (local.get $i) (i32.sub (i32.load8_u (local.get $i)) (i32.const 32)))
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br $next)))
+            (call $report_progress (i32.const 1000))
+            (call $write_output (i32.const 0) (local.get $n))))
+    "#;
+
+    /// 安装到独立的插件根目录，返回插件目录
+    fn install(name: &str, wat: &str, permissions: &[PluginPermission]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("williw-plugins-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        let bytes = wat::parse_str(wat).unwrap();
+        std::fs::write(dir.join("plugin.wasm"), &bytes).unwrap();
+        let manifest = PluginManifest {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            task_type: name.to_string(),
+            description: String::new(),
+            module: PathBuf::from("plugin.wasm"),
+            module_hash: blake3::hash(&bytes).to_hex().to_string(),
+            permissions: permissions.to_vec(),
+            limits: PluginLimits::default(),
+        };
+        std::fs::write(dir.join(MANIFEST_FILE), toml::to_string(&manifest).unwrap()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_runs_plugin_with_granted_permissions() {
+        use PluginPermission::*;
+        let dir = install("upper", UPPERCASE, &[ReadInput, WriteOutput, ReportProgress]);
+        let mut registry = PluginRegistry::new();
+        assert!(registry.load_dir(dir.parent().unwrap()).unwrap().is_empty());
+        let plugin = registry.get("upper").unwrap();
+
+        let progress = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = progress.clone();
+        let run = plugin.run(b"williw".to_vec(), move |p| seen.lock().push(p)).unwrap();
+        assert_eq!(run.output, b"WILLIW");
+        assert_eq!(*progress.lock(), vec![1.0]);
+        assert!(run.fuel_consumed > 0);
+    }
+
+    #[test]
+    fn test_rejects_missing_permission_and_escaping_module_path() {
+        let dir = install("no-progress", UPPERCASE, &[PluginPermission::ReadInput, PluginPermission::WriteOutput]);
+        let err = Plugin::load(&dir).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PluginError>(),
+            Some(PluginError::PermissionDenied {
+                permission: PluginPermission::ReportProgress,
+                ..
+            })
+        ));
+
+        let mut manifest: PluginManifest =
+            toml::from_str(&std::fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap()).unwrap();
+        manifest.module = PathBuf::from("../other/plugin.wasm");
+        assert!(matches!(manifest.validate(), Err(PluginError::InvalidManifest(_))));
+    }
+}
@@ -0,0 +1,7 @@
+//! QUIC 隐私增强模块
+//!
+//! 在基础传输之上提供按消息选择的隐私保护
+
+pub mod privacy_overlay;
+
+pub use privacy_overlay::{OnionConfig, OutboundPacket, OverlayStats, PrivacyOverlay, RelayAction, RelayDescriptor};
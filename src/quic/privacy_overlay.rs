@@ -0,0 +1,499 @@
+//! 洋葱路由隐私覆盖层
+//!
+//! `PrivacyLevel::Maximum` 的消息不再直连目标节点，而是经 2–3 个自愿中继逐跳转发：
+//! - 发送方为路径上每个中继各加一层加密（X25519 临时密钥 + ChaCha20-Poly1305），
+//!   每个中继只能解开自己那一层，得知下一跳，无法同时看到来源和目标
+//! - 每层明文按固定块长补齐，避免通过包长关联各跳
+//! - 路径经过的中继延迟会计入 `PrivacyPerformanceMetrics`，便于权衡隐私与性能
+//!
+//! 其余隐私级别保持直连，加密由传输层负责。
+
+use anyhow::{anyhow, Result};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use parking_lot::RwLock;
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::config::SecurityConfig;
+use crate::crypto::{PrivacyLevel, PrivacyPerformanceMetrics};
+use crate::network::transport::{RouteInfo, Transport, TransportType};
+
+/// 洋葱包头魔数
+const ONION_MAGIC: &[u8; 4] = b"WONI";
+const ONION_VERSION: u8 = 1;
+const KEY_CONTEXT: &str = "williw onion layer v1";
+/// 每层明文补齐的块长
+const PAD_BLOCK: usize = 256;
+const HEADER_LEN: usize = 4 + 1 + 32 + 12;
+
+const TAG_FORWARD: u8 = 1;
+const TAG_DELIVER: u8 = 2;
+
+/// 覆盖层配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnionConfig {
+    /// 是否启用洋葱路由
+    pub enabled: bool,
+    /// 中继跳数（2–3）
+    pub hops: u8,
+    /// 本节点是否作为自愿中继
+    pub volunteer_relay: bool,
+    /// 可用中继不足时是否回退直连
+    pub fallback_to_direct: bool,
+}
+
+impl Default for OnionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hops: 3,
+            volunteer_relay: false,
+            fallback_to_direct: false,
+        }
+    }
+}
+
+impl OnionConfig {
+    /// 从安全配置派生：`use_relay` 表示愿意为他人中继，`max_hops` 决定跳数
+    pub fn from_security(security: &SecurityConfig) -> Self {
+        Self {
+            enabled: true,
+            hops: security.max_hops.clamp(2, 3),
+            volunteer_relay: security.use_relay,
+            fallback_to_direct: security.privacy_performance.fallback_to_direct,
+        }
+    }
+}
+
+/// 中继节点公告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayDescriptor {
+    pub node_id: String,
+    /// X25519 洋葱公钥
+    pub onion_key: [u8; 32],
+    /// 最近测得的到该中继的单程延迟（毫秒）
+    #[serde(default)]
+    pub latency_ms: f64,
+}
+
+/// 中继收到洋葱包后的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayAction {
+    /// 转发给下一跳
+    Forward { next_hop: String, packet: Vec<u8> },
+    /// 本节点是出口中继，把载荷交给目标节点
+    Deliver { destination: String, payload: Vec<u8> },
+}
+
+/// 已封装好的出站包
+#[derive(Debug, Clone)]
+pub struct OutboundPacket {
+    /// 实际发送对象（直连时为目标本身）
+    pub first_hop: String,
+    pub packet: Vec<u8>,
+    pub hops: usize,
+    /// 路径上中继的预估延迟开销（毫秒）
+    pub estimated_overhead_ms: f64,
+}
+
+/// 覆盖层统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OverlayStats {
+    pub onion_sent: u64,
+    pub direct_sent: u64,
+    pub relayed: u64,
+    pub delivered: u64,
+    pub rejected: u64,
+    /// 洋葱路径延迟开销的滑动平均（毫秒）
+    pub avg_latency_overhead_ms: f64,
+    pub last_hops: u8,
+}
+
+/// 洋葱路由隐私覆盖层
+pub struct PrivacyOverlay {
+    config: OnionConfig,
+    local_node_id: String,
+    onion_secret: Option<StaticSecret>,
+    relays: RwLock<HashMap<String, RelayDescriptor>>,
+    stats: RwLock<OverlayStats>,
+}
+
+impl PrivacyOverlay {
+    pub fn new(config: OnionConfig, local_node_id: String) -> Self {
+        let onion_secret = config.volunteer_relay.then(random_secret);
+        Self {
+            config,
+            local_node_id,
+            onion_secret,
+            relays: RwLock::new(HashMap::new()),
+            stats: RwLock::new(OverlayStats::default()),
+        }
+    }
+
+    pub fn config(&self) -> &OnionConfig {
+        &self.config
+    }
+
+    /// 本节点的中继公告（未自愿中继时为 None）
+    pub fn relay_descriptor(&self) -> Option<RelayDescriptor> {
+        self.onion_secret.as_ref().map(|secret| RelayDescriptor {
+            node_id: self.local_node_id.clone(),
+            onion_key: PublicKey::from(secret).to_bytes(),
+            latency_ms: 0.0,
+        })
+    }
+
+    /// 记录其他节点的中继公告
+    pub fn register_relay(&self, descriptor: RelayDescriptor) {
+        if descriptor.node_id != self.local_node_id {
+            self.relays.write().insert(descriptor.node_id.clone(), descriptor);
+        }
+    }
+
+    pub fn remove_relay(&self, node_id: &str) {
+        self.relays.write().remove(node_id);
+    }
+
+    /// 更新到中继的延迟测量
+    pub fn observe_relay_latency(&self, node_id: &str, latency_ms: f64) {
+        if let Some(relay) = self.relays.write().get_mut(node_id) {
+            relay.latency_ms = if relay.latency_ms > 0.0 {
+                relay.latency_ms * 0.8 + latency_ms * 0.2
+            } else {
+                latency_ms
+            };
+        }
+    }
+
+    pub fn relay_count(&self) -> usize {
+        self.relays.read().len()
+    }
+
+    /// 某隐私级别使用的中继跳数（0 表示直连）
+    pub fn hops_for(&self, level: PrivacyLevel) -> usize {
+        match level {
+            PrivacyLevel::Maximum if self.config.enabled => self.config.hops.clamp(2, 3) as usize,
+            _ => 0,
+        }
+    }
+
+    /// 随机选择中继路径（排除目标节点）
+    fn select_path(&self, destination: &str, hops: usize) -> Option<Vec<RelayDescriptor>> {
+        let mut candidates: Vec<RelayDescriptor> = self
+            .relays
+            .read()
+            .values()
+            .filter(|r| r.node_id != destination)
+            .cloned()
+            .collect();
+        if candidates.len() < hops {
+            return None;
+        }
+        candidates.shuffle(&mut rand::rng());
+        candidates.truncate(hops);
+        Some(candidates)
+    }
+
+    /// 按隐私级别封装消息
+    pub fn wrap(&self, destination: &str, payload: &[u8], level: PrivacyLevel) -> Result<OutboundPacket> {
+        let hops = self.hops_for(level);
+        if hops == 0 {
+            self.stats.write().direct_sent += 1;
+            return Ok(OutboundPacket {
+                first_hop: destination.to_string(),
+                packet: payload.to_vec(),
+                hops: 0,
+                estimated_overhead_ms: 0.0,
+            });
+        }
+
+        let Some(path) = self.select_path(destination, hops) else {
+            if self.config.fallback_to_direct {
+                log::warn!("[隐私] 可用中继不足 {} 个，回退直连", hops);
+                self.stats.write().direct_sent += 1;
+                return Ok(OutboundPacket {
+                    first_hop: destination.to_string(),
+                    packet: payload.to_vec(),
+                    hops: 0,
+                    estimated_overhead_ms: 0.0,
+                });
+            }
+            return Err(anyhow!("可用中继不足 {} 个，拒绝以直连发送最高隐私级别消息", hops));
+        };
+
+        // 从出口中继开始由内向外加密
+        let exit = path.last().expect("path is non-empty");
+        let mut packet = seal_layer(&exit.onion_key, TAG_DELIVER, destination, payload)?;
+        for window in path.windows(2).rev() {
+            packet = seal_layer(&window[0].onion_key, TAG_FORWARD, &window[1].node_id, &packet)?;
+        }
+
+        // 最后一跳到目标的延迟无法预知，开销只计入经过的中继
+        let estimated_overhead_ms = path.iter().map(|r| r.latency_ms).sum();
+        let mut stats = self.stats.write();
+        stats.onion_sent += 1;
+        stats.last_hops = hops as u8;
+        stats.avg_latency_overhead_ms = if stats.onion_sent == 1 {
+            estimated_overhead_ms
+        } else {
+            stats.avg_latency_overhead_ms * 0.9 + estimated_overhead_ms * 0.1
+        };
+
+        Ok(OutboundPacket {
+            first_hop: path[0].node_id.clone(),
+            packet,
+            hops,
+            estimated_overhead_ms,
+        })
+    }
+
+    /// 作为中继解开一层
+    pub fn unwrap_layer(&self, packet: &[u8]) -> Result<RelayAction> {
+        let result = self.open(packet);
+        let mut stats = self.stats.write();
+        match &result {
+            Ok(RelayAction::Forward { .. }) => stats.relayed += 1,
+            Ok(RelayAction::Deliver { .. }) => stats.delivered += 1,
+            Err(_) => stats.rejected += 1,
+        }
+        result
+    }
+
+    fn open(&self, packet: &[u8]) -> Result<RelayAction> {
+        let secret = self
+            .onion_secret
+            .as_ref()
+            .ok_or_else(|| anyhow!("本节点未启用中继"))?;
+        if !is_onion_packet(packet) || packet.len() < HEADER_LEN {
+            return Err(anyhow!("不是有效的洋葱包"));
+        }
+        let eph_public: [u8; 32] = packet[5..37].try_into()?;
+        let nonce = &packet[37..HEADER_LEN];
+        let relay_public = PublicKey::from(secret).to_bytes();
+        let key = layer_key(&secret.diffie_hellman(&PublicKey::from(eph_public)).to_bytes(), &eph_public, &relay_public);
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(Nonce::from_slice(nonce), &packet[HEADER_LEN..])
+            .map_err(|_| anyhow!("洋葱层解密失败"))?;
+        decode_layer(&plaintext)
+    }
+
+    /// 通过指定传输发送（按隐私级别决定是否走洋葱路径）
+    pub async fn send_via<T: Transport>(
+        &self,
+        transport: &T,
+        destination: &str,
+        payload: &[u8],
+        level: PrivacyLevel,
+    ) -> Result<OutboundPacket> {
+        let outbound = self.wrap(destination, payload, level)?;
+        transport.send(&route_to(&outbound.first_hop), &outbound.packet).await?;
+        Ok(outbound)
+    }
+
+    /// 处理入站数据：洋葱包解开一层后转发或投递，其它数据原样返回给调用方
+    pub async fn handle_inbound<T: Transport>(&self, transport: &T, data: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        if !is_onion_packet(&data) {
+            return Ok(Some(data));
+        }
+        match self.unwrap_layer(&data)? {
+            RelayAction::Forward { next_hop, packet } => {
+                transport.send(&route_to(&next_hop), &packet).await?;
+            }
+            RelayAction::Deliver { destination, payload } => {
+                if destination == self.local_node_id {
+                    return Ok(Some(payload));
+                }
+                transport.send(&route_to(&destination), &payload).await?;
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn stats(&self) -> OverlayStats {
+        self.stats.read().clone()
+    }
+
+    /// 在基础加密指标上叠加洋葱路由的延迟开销
+    pub fn performance_metrics(&self, level: PrivacyLevel, base: PrivacyPerformanceMetrics) -> PrivacyPerformanceMetrics {
+        let hops = self.hops_for(level);
+        if hops == 0 {
+            return base;
+        }
+        let stats = self.stats.read();
+        let overhead = if stats.onion_sent > 0 {
+            stats.avg_latency_overhead_ms
+        } else {
+            // 尚无发送记录时用当前中继的平均延迟估算
+            let relays = self.relays.read();
+            let avg = relays.values().map(|r| r.latency_ms).sum::<f64>() / relays.len().max(1) as f64;
+            avg * hops as f64
+        };
+        // 每跳额外的包头和补齐开销
+        let per_hop_bytes = (HEADER_LEN + 16 + PAD_BLOCK / 2) as f64;
+        PrivacyPerformanceMetrics {
+            relay_hops: hops as u8,
+            relay_latency_overhead_ms: overhead,
+            bandwidth_overhead_percent: base.bandwidth_overhead_percent + per_hop_bytes * hops as f64 / 1024.0 * 100.0,
+            ..base
+        }
+    }
+}
+
+/// 是否为洋葱包
+pub fn is_onion_packet(data: &[u8]) -> bool {
+    data.len() > 5 && &data[..4] == ONION_MAGIC && data[4] == ONION_VERSION
+}
+
+fn route_to(node_id: &str) -> RouteInfo {
+    RouteInfo {
+        destination: node_id.to_string(),
+        transport_type: TransportType::Iroh,
+        address: node_id.to_string(),
+        quality_score: 1.0,
+    }
+}
+
+fn random_secret() -> StaticSecret {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    StaticSecret::from(bytes)
+}
+
+fn layer_key(shared: &[u8; 32], eph_public: &[u8; 32], relay_public: &[u8; 32]) -> [u8; 32] {
+    let mut material = Vec::with_capacity(96);
+    material.extend_from_slice(shared);
+    material.extend_from_slice(eph_public);
+    material.extend_from_slice(relay_public);
+    blake3::derive_key(KEY_CONTEXT, &material)
+}
+
+fn seal_layer(relay_key: &[u8; 32], tag: u8, target: &str, inner: &[u8]) -> Result<Vec<u8>> {
+    let eph_secret = random_secret();
+    let eph_public = PublicKey::from(&eph_secret).to_bytes();
+    let key = layer_key(
+        &eph_secret.diffie_hellman(&PublicKey::from(*relay_key)).to_bytes(),
+        &eph_public,
+        relay_key,
+    );
+    let mut nonce = [0u8; 12];
+    rand::rng().fill_bytes(&mut nonce);
+
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(Nonce::from_slice(&nonce), encode_layer(tag, target, inner)?.as_slice())
+        .map_err(|_| anyhow!("洋葱层加密失败"))?;
+
+    let mut packet = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    packet.extend_from_slice(ONION_MAGIC);
+    packet.push(ONION_VERSION);
+    packet.extend_from_slice(&eph_public);
+    packet.extend_from_slice(&nonce);
+    packet.extend_from_slice(&ciphertext);
+    Ok(packet)
+}
+
+/// 层明文：`tag | u16 目标长度 | 目标 | u32 内容长度 | 内容 | 补零`
+fn encode_layer(tag: u8, target: &str, inner: &[u8]) -> Result<Vec<u8>> {
+    let target_len = u16::try_from(target.len()).map_err(|_| anyhow!("节点 ID 过长"))?;
+    let inner_len = u32::try_from(inner.len()).map_err(|_| anyhow!("载荷过大"))?;
+    let mut out = Vec::with_capacity(7 + target.len() + inner.len() + PAD_BLOCK);
+    out.push(tag);
+    out.extend_from_slice(&target_len.to_be_bytes());
+    out.extend_from_slice(target.as_bytes());
+    out.extend_from_slice(&inner_len.to_be_bytes());
+    out.extend_from_slice(inner);
+    let padded = out.len().div_ceil(PAD_BLOCK) * PAD_BLOCK;
+    out.resize(padded, 0);
+    Ok(out)
+}
+
+fn decode_layer(data: &[u8]) -> Result<RelayAction> {
+    let malformed = || anyhow!("洋葱层格式错误");
+    let (&tag, rest) = data.split_first().ok_or_else(malformed)?;
+    let target_len = u16::from_be_bytes(rest.get(..2).ok_or_else(malformed)?.try_into()?) as usize;
+    let target = rest.get(2..2 + target_len).ok_or_else(malformed)?;
+    let target = String::from_utf8(target.to_vec())?;
+    let rest = &rest[2 + target_len..];
+    let inner_len = u32::from_be_bytes(rest.get(..4).ok_or_else(malformed)?.try_into()?) as usize;
+    let inner = rest.get(4..4 + inner_len).ok_or_else(malformed)?.to_vec();
+    match tag {
+        TAG_FORWARD => Ok(RelayAction::Forward {
+            next_hop: target,
+            packet: inner,
+        }),
+        TAG_DELIVER => Ok(RelayAction::Deliver {
+            destination: target,
+            payload: inner,
+        }),
+        _ => Err(malformed()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relay(id: &str) -> PrivacyOverlay {
+        let config = OnionConfig {
+            volunteer_relay: true,
+            ..Default::default()
+        };
+        PrivacyOverlay::new(config, id.to_string())
+    }
+
+    #[test]
+    fn test_three_hop_onion_unwraps_layer_by_layer() {
+        let relays: Vec<PrivacyOverlay> = ["r1", "r2", "r3"].iter().map(|id| relay(id)).collect();
+        let sender = PrivacyOverlay::new(OnionConfig::default(), "sender".to_string());
+        for r in &relays {
+            let mut descriptor = r.relay_descriptor().unwrap();
+            descriptor.latency_ms = 10.0;
+            sender.register_relay(descriptor);
+        }
+
+        let direct = sender.wrap("dest", b"grad", PrivacyLevel::Balanced).unwrap();
+        assert_eq!((direct.first_hop.as_str(), direct.hops), ("dest", 0));
+
+        let outbound = sender.wrap("dest", b"grad", PrivacyLevel::Maximum).unwrap();
+        assert_eq!(outbound.hops, 3);
+        assert!((outbound.estimated_overhead_ms - 30.0).abs() < 1e-9);
+
+        let by_id = |id: &str| relays.iter().find(|r| r.local_node_id == id).unwrap();
+        let mut hop = outbound.first_hop.clone();
+        let mut packet = outbound.packet;
+        let mut visited = Vec::new();
+        loop {
+            visited.push(hop.clone());
+            match by_id(&hop).unwrap_layer(&packet).unwrap() {
+                RelayAction::Forward { next_hop, packet: next } => {
+                    assert_eq!(next.len() % PAD_BLOCK, (HEADER_LEN + 16) % PAD_BLOCK);
+                    hop = next_hop;
+                    packet = next;
+                }
+                RelayAction::Deliver { destination, payload } => {
+                    assert_eq!(destination, "dest");
+                    assert_eq!(payload, b"grad");
+                    break;
+                }
+            }
+        }
+        assert_eq!(visited.len(), 3);
+
+        let metrics = sender.performance_metrics(
+            PrivacyLevel::Maximum,
+            crate::crypto::HighPerformanceCrypto::with_default_config().get_privacy_performance_metrics(PrivacyLevel::Maximum),
+        );
+        assert_eq!(metrics.relay_hops, 3);
+        assert!(metrics.relay_latency_overhead_ms > 0.0);
+    }
+
+    #[test]
+    fn test_maximum_level_refuses_direct_without_relays() {
+        let sender = PrivacyOverlay::new(OnionConfig::default(), "sender".to_string());
+        assert!(sender.wrap("dest", b"x", PrivacyLevel::Maximum).is_err());
+        assert!(relay("r1").unwrap_layer(b"WONI\x01garbage").is_err());
+    }
+}
@@ -6,6 +6,10 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod moe;
+
+pub use moe::{detect_moe_blocks, ExpertBlock, MoeBlock};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayerMetadata {
     pub name: String,
@@ -27,6 +31,15 @@ pub struct ModelMetadata {
     pub total_layers: usize,
     pub generated_at: f64,
     pub node_id: Option<String>,
+    /// 稀疏专家混合块（非 MoE 模型为空）
+    #[serde(default)]
+    pub moe_blocks: Vec<MoeBlock>,
+}
+
+impl ModelMetadata {
+    pub fn is_moe(&self) -> bool {
+        !self.moe_blocks.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,9 +101,14 @@ impl MetadataGenerator {
 
         // 解析 JSON 输出
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let metadata: ModelMetadata = serde_json::from_str(&stdout)
+        let mut metadata: ModelMetadata = serde_json::from_str(&stdout)
             .context("Failed to parse metadata JSON")?;
 
+        // 按参数名识别专家块，供切分时按专家分配节点
+        if metadata.moe_blocks.is_empty() {
+            metadata.moe_blocks = detect_moe_blocks(&metadata.layers);
+        }
+
         Ok(metadata)
     }
 
@@ -0,0 +1,133 @@
+/**
+ * 稀疏专家混合（MoE）结构识别
+ * 根据参数名识别专家块和门控层，例如：
+ * - Mixtral: model.layers.0.block_sparse_moe.experts.3.w1.weight / block_sparse_moe.gate.weight
+ * - Qwen / DeepSeek: model.layers.0.mlp.experts.3.up_proj.weight / mlp.gate.weight
+ * - Switch: encoder.block.1.layer.1.mlp.experts.expert_3.wi.weight / mlp.router.classifier.weight
+ */
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::LayerMetadata;
+
+/// 单个专家
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpertBlock {
+    pub index: usize,
+    pub layer_names: Vec<String>,
+    pub num_params: usize,
+    pub compute_required: f64,
+}
+
+/// 一个 MoE 块（同一门控下的所有专家）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoeBlock {
+    /// 块前缀，如 `model.layers.0.block_sparse_moe`
+    pub name: String,
+    /// 门控（路由）层
+    pub router_layers: Vec<String>,
+    pub experts: Vec<ExpertBlock>,
+}
+
+impl MoeBlock {
+    pub fn num_experts(&self) -> usize {
+        self.experts.len()
+    }
+}
+
+/// 门控层在块前缀后的名字
+const ROUTER_SEGMENTS: [&str; 3] = ["gate", "router", "gating"];
+
+/// 从参数名中解析 (块前缀, 专家序号)
+fn parse_expert(name: &str) -> Option<(String, usize)> {
+    let segments: Vec<&str> = name.split('.').collect();
+    let pos = segments.iter().position(|s| *s == "experts")?;
+    let index_segment = segments.get(pos + 1)?;
+    let index = index_segment
+        .strip_prefix("expert_")
+        .unwrap_or(index_segment)
+        .parse()
+        .ok()?;
+    Some((segments[..pos].join("."), index))
+}
+
+/// 识别模型中的 MoE 块，没有专家层时返回空列表
+pub fn detect_moe_blocks(layers: &[LayerMetadata]) -> Vec<MoeBlock> {
+    let mut blocks: BTreeMap<String, BTreeMap<usize, ExpertBlock>> = BTreeMap::new();
+    for layer in layers {
+        let Some((block, index)) = parse_expert(&layer.name) else {
+            continue;
+        };
+        let expert = blocks.entry(block).or_default().entry(index).or_insert_with(|| ExpertBlock {
+            index,
+            layer_names: Vec::new(),
+            num_params: 0,
+            compute_required: 0.0,
+        });
+        expert.layer_names.push(layer.name.clone());
+        expert.num_params += layer.num_params;
+        expert.compute_required += layer.compute_required;
+    }
+
+    blocks
+        .into_iter()
+        .map(|(name, experts)| {
+            let router_layers = layers
+                .iter()
+                .filter(|l| {
+                    l.name
+                        .strip_prefix(name.as_str())
+                        .and_then(|rest| rest.strip_prefix('.'))
+                        .and_then(|rest| rest.split('.').next())
+                        .is_some_and(|segment| ROUTER_SEGMENTS.contains(&segment))
+                })
+                .map(|l| l.name.clone())
+                .collect();
+            MoeBlock {
+                name,
+                router_layers,
+                experts: experts.into_values().collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(name: &str) -> LayerMetadata {
+        LayerMetadata {
+            name: name.to_string(),
+            shape: vec![4, 4],
+            num_params: 16,
+            compute_required: 32.0,
+            layer_type: "linear".to_string(),
+            dtype: "float16".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_detect_mixtral_and_switch_experts() {
+        let layers = vec![
+            layer("model.layers.0.self_attn.q_proj.weight"),
+            layer("model.layers.0.block_sparse_moe.gate.weight"),
+            layer("model.layers.0.block_sparse_moe.experts.0.w1.weight"),
+            layer("model.layers.0.block_sparse_moe.experts.0.w2.weight"),
+            layer("model.layers.0.block_sparse_moe.experts.1.w1.weight"),
+            layer("encoder.block.1.layer.1.mlp.router.classifier.weight"),
+            layer("encoder.block.1.layer.1.mlp.experts.expert_7.wi.weight"),
+        ];
+        let blocks = detect_moe_blocks(&layers);
+        assert_eq!(blocks.len(), 2);
+
+        let mixtral = blocks.iter().find(|b| b.name == "model.layers.0.block_sparse_moe").unwrap();
+        assert_eq!(mixtral.num_experts(), 2);
+        assert_eq!(mixtral.router_layers, vec!["model.layers.0.block_sparse_moe.gate.weight".to_string()]);
+        assert_eq!(mixtral.experts[0].num_params, 32);
+
+        let switch = blocks.iter().find(|b| b.name == "encoder.block.1.layer.1.mlp").unwrap();
+        assert_eq!(switch.experts[0].index, 7);
+        assert_eq!(switch.router_layers.len(), 1);
+    }
+}
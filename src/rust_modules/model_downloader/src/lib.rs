@@ -6,6 +6,7 @@ use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
@@ -23,6 +24,31 @@ pub struct DownloadResult {
     pub total_size_mb: f64,
 }
 
+/// 未指定缓存目录时，模型下载到 `DEFAULT_CACHE_ROOT/<模型名（/ 替换为 _）>`
+pub const DEFAULT_CACHE_ROOT: &str = "./models_cache";
+
+/// 下载进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub model_name: String,
+    /// 当前文件
+    pub file_name: String,
+    pub file_downloaded: u64,
+    /// 当前文件大小（服务端未返回时为 None）
+    pub file_total: Option<u64>,
+    /// 已下载（含已存在而跳过的文件）
+    pub downloaded_bytes: u64,
+    /// 全部文件大小（模型信息中缺少任一文件大小时为 None）
+    pub total_bytes: Option<u64>,
+    pub files_completed: usize,
+    pub files_total: usize,
+}
+
+/// 下载被调用方取消
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("模型 {0} 的下载已取消")]
+pub struct DownloadCancelled(pub String);
+
 pub struct ModelDownloader {
     client: Client,
     hf_token: Option<String>,
@@ -43,10 +69,24 @@ impl ModelDownloader {
 
     /// 下载模型文件
     pub async fn download_model(&self, config: DownloadConfig) -> Result<DownloadResult> {
+        self.download_model_with_progress(config, &AtomicBool::new(false), |_| {}).await
+    }
+
+    /// 下载模型文件，每收到一块数据回调一次进度；`cancel` 置位后在下一块数据处中止并返回 [`DownloadCancelled`]。
+    /// 文件先写入 `.part` 临时文件，完整下载后才改名，取消或失败不会在目标位置留下残缺文件
+    pub async fn download_model_with_progress<F>(
+        &self,
+        config: DownloadConfig,
+        cancel: &AtomicBool,
+        mut on_progress: F,
+    ) -> Result<DownloadResult>
+    where
+        F: FnMut(&DownloadProgress),
+    {
         let model_name = config.model_name;
         let cache_dir = config.cache_dir
             .map(PathBuf::from)
-            .unwrap_or_else(|| PathBuf::from("./models_cache").join(model_name.replace("/", "_")));
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_ROOT).join(model_name.replace("/", "_")));
         
         fs::create_dir_all(&cache_dir)
             .await
@@ -54,9 +94,9 @@ impl ModelDownloader {
 
         println!("下载模型: {} 到 {}", model_name, cache_dir.display());
 
-        // 构建 Hugging Face API URL
+        // 构建 Hugging Face API URL（blobs=true 时文件列表带大小，用于计算总进度）
         let api_base = "https://huggingface.co/api/models";
-        let model_url = format!("{}/{}", api_base, model_name);
+        let model_url = format!("{}/{}?blobs=true", api_base, model_name);
 
         // 获取模型文件列表
         let mut headers = reqwest::header::HeaderMap::new();
@@ -85,6 +125,7 @@ impl ModelDownloader {
             .context("No files found in model info")?;
 
         let mut files_to_download = Vec::new();
+        let mut total_bytes = Some(0u64);
         for file in files {
             let file_name = file["rfilename"]
                 .as_str()
@@ -97,20 +138,37 @@ impl ModelDownloader {
                 || file_name == "tokenizer.json"
                 || file_name == "tokenizer_config.json" {
                 files_to_download.push(file_name.to_string());
+                total_bytes = total_bytes.zip(file["size"].as_u64()).map(|(total, size)| total + size);
             }
         }
 
         // 下载文件
         let mut downloaded_files = Vec::new();
         let mut total_size = 0u64;
+        let mut progress = DownloadProgress {
+            model_name: model_name.clone(),
+            file_name: String::new(),
+            file_downloaded: 0,
+            file_total: None,
+            downloaded_bytes: 0,
+            total_bytes,
+            files_completed: 0,
+            files_total: files_to_download.len(),
+        };
 
         for file_name in &files_to_download {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(DownloadCancelled(model_name).into());
+            }
+
             let file_url = format!(
                 "https://huggingface.co/{}/resolve/main/{}",
                 model_name, file_name
             );
 
             let file_path = cache_dir.join(file_name);
+            progress.file_name = file_name.clone();
+            progress.file_downloaded = 0;
             
             // 如果文件已存在，跳过
             if file_path.exists() {
@@ -118,39 +176,68 @@ impl ModelDownloader {
                 total_size += metadata.len();
                 downloaded_files.push(file_name.clone());
                 println!("  文件已存在，跳过: {}", file_name);
+                progress.file_downloaded = metadata.len();
+                progress.file_total = Some(metadata.len());
+                progress.downloaded_bytes += metadata.len();
+                progress.files_completed += 1;
+                on_progress(&progress);
                 continue;
             }
 
             println!("  下载: {}", file_name);
 
-            let response = self.client
+            let mut response = self.client
                 .get(&file_url)
                 .headers(headers.clone())
                 .send()
                 .await
+                .and_then(|response| response.error_for_status())
                 .context(format!("Failed to download {}", file_name))?;
-
-            let content = response
-                .bytes()
-                .await
-                .context(format!("Failed to read content of {}", file_name))?;
+            progress.file_total = response.content_length();
 
             // 确保父目录存在
             if let Some(parent) = file_path.parent() {
                 fs::create_dir_all(parent).await?;
             }
 
-            // 写入文件
-            let mut file = fs::File::create(&file_path)
+            // 边下载边写入临时文件
+            let part_path = part_path(&file_path);
+            let mut file = fs::File::create(&part_path)
                 .await
-                .context(format!("Failed to create file {}", file_path.display()))?;
-            
-            file.write_all(&content)
+                .context(format!("Failed to create file {}", part_path.display()))?;
+
+            loop {
+                if cancel.load(Ordering::Relaxed) {
+                    drop(file);
+                    let _ = fs::remove_file(&part_path).await;
+                    return Err(DownloadCancelled(model_name).into());
+                }
+                let chunk = match response.chunk().await {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => break,
+                    Err(e) => {
+                        drop(file);
+                        let _ = fs::remove_file(&part_path).await;
+                        return Err(e).context(format!("Failed to read content of {}", file_name));
+                    }
+                };
+                file.write_all(&chunk)
+                    .await
+                    .context(format!("Failed to write file {}", part_path.display()))?;
+                progress.file_downloaded += chunk.len() as u64;
+                progress.downloaded_bytes += chunk.len() as u64;
+                on_progress(&progress);
+            }
+            file.flush().await?;
+            drop(file);
+            fs::rename(&part_path, &file_path)
                 .await
                 .context(format!("Failed to write file {}", file_path.display()))?;
 
-            total_size += content.len() as u64;
+            total_size += progress.file_downloaded;
             downloaded_files.push(file_name.clone());
+            progress.files_completed += 1;
+            on_progress(&progress);
         }
 
         Ok(DownloadResult {
@@ -161,6 +248,12 @@ impl ModelDownloader {
     }
 }
 
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
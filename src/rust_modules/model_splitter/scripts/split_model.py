@@ -7,6 +7,7 @@ import json
 import sys
 import argparse
 import torch
+import blake3
 from transformers import AutoModel
 from pathlib import Path
 from typing import Dict, Any
@@ -34,6 +35,18 @@ def split_model(model_name: str, model_path: str,
     shard_path = output_path / f"shard_{node_id}.pth"
     torch.save(my_shard, shard_path)
     
+    # 每个张量的完整性记录（原始字节的 blake3）
+    tensors = []
+    for name in layer_names:
+        tensor = my_shard[name].detach().cpu().contiguous()
+        raw = tensor.view(-1).view(torch.uint8).numpy().tobytes()
+        tensors.append({
+            "name": name,
+            "shape": list(tensor.shape),
+            "dtype": str(tensor.dtype).replace("torch.", ""),
+            "blake3": blake3.blake3(raw).hexdigest(),
+        })
+    
     total_params = sum(p.numel() for p in my_shard.values())
     shard_size_mb = sum(p.numel() * 4 for p in my_shard.values()) / (1024 * 1024)
     
@@ -42,7 +55,8 @@ def split_model(model_name: str, model_path: str,
         "shard_path": str(shard_path),
         "layer_names": layer_names,
         "total_params": total_params,
-        "shard_size_mb": shard_size_mb
+        "shard_size_mb": shard_size_mb,
+        "tensors": tensors
     }
     
     return result
@@ -7,12 +7,23 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+pub mod lineage;
+pub mod manifest;
+pub mod moe;
+
+pub use lineage::{apply_block_delta, compute_block_delta, ModelManifest, ShardDeltaRef, ShardVersion, SignedModelManifest};
+pub use manifest::{ManifestError, ShardManifest, SignedShardManifest, TensorEntry};
+pub use moe::{assign_experts, expert_hosts, ExpertAssignment};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SplitPlan {
     pub node_id: String,
     pub layer_names: Vec<String>,
     pub total_compute: f64,
     pub compute_utilization: f64,
+    /// 本节点托管的 MoE 专家
+    #[serde(default)]
+    pub expert_assignments: Vec<ExpertAssignment>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +41,9 @@ pub struct SplitResult {
     pub layer_names: Vec<String>,
     pub total_params: usize,
     pub shard_size_mb: f64,
+    /// 每个张量的形状、类型和哈希（由切分脚本计算）
+    #[serde(default)]
+    pub tensors: Vec<TensorEntry>,
 }
 
 pub struct ModelSplitter;
@@ -107,6 +121,57 @@ impl ModelSplitter {
         Ok(result)
     }
 
+    /// 切分模型并生成签名清单
+    ///
+    /// 清单保存在分片旁边（`<shard>.manifest.json`），随分片一起分发，
+    /// 接收端据此在加载前拒绝损坏或被篡改的分片。
+    pub async fn split_model_signed(
+        &self,
+        config: SplitConfig,
+        node_id: &str,
+        signing_key: &ed25519_dalek::SigningKey,
+    ) -> Result<(SplitResult, SignedShardManifest)> {
+        let model_name = config.model_name.clone();
+        let result = self.split_model(config, node_id).await?;
+        let manifest = self.build_manifest(&model_name, &result)?.sign(signing_key)?;
+
+        let manifest_path = Self::manifest_path(Path::new(&result.shard_path));
+        manifest.save(&manifest_path)?;
+
+        Ok((result, manifest))
+    }
+
+    /// 根据切分结果构建（未签名的）清单
+    pub fn build_manifest(&self, model_name: &str, result: &SplitResult) -> Result<ShardManifest> {
+        let shard_path = Path::new(&result.shard_path);
+        if result.tensors.len() != result.layer_names.len() {
+            anyhow::bail!(
+                "切分结果缺少张量元数据: {} 个层, {} 条记录",
+                result.layer_names.len(),
+                result.tensors.len()
+            );
+        }
+
+        Ok(ShardManifest {
+            model_name: model_name.to_string(),
+            node_id: result.node_id.clone(),
+            shard_file: shard_path
+                .file_name()
+                .context("无效的分片路径")?
+                .to_string_lossy()
+                .to_string(),
+            shard_blake3: manifest::blake3_file(shard_path)?,
+            tensors: result.tensors.clone(),
+        })
+    }
+
+    /// 分片对应的清单路径
+    pub fn manifest_path(shard_path: &Path) -> PathBuf {
+        let mut name = shard_path.as_os_str().to_owned();
+        name.push(".manifest.json");
+        PathBuf::from(name)
+    }
+
     /// 验证切分方案（检查所有层是否都被分配）
     pub fn validate_split_plan(
         &self,
@@ -163,6 +228,7 @@ mod tests {
                 layer_names: vec!["layer1".to_string(), "layer2".to_string()],
                 total_compute: 100.0,
                 compute_utilization: 0.5,
+                expert_assignments: Vec::new(),
             },
         );
         split_plan.insert(
@@ -172,10 +238,55 @@ mod tests {
                 layer_names: vec!["layer3".to_string()],
                 total_compute: 50.0,
                 compute_utilization: 0.3,
+                expert_assignments: Vec::new(),
             },
         );
         
         let result = splitter.validate_split_plan(&all_layers, &split_plan);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_assign_experts_spreads_across_nodes() {
+        use metadata_generator::{ExpertBlock, MoeBlock};
+
+        let block = MoeBlock {
+            name: "model.layers.0.block_sparse_moe".to_string(),
+            router_layers: vec!["model.layers.0.block_sparse_moe.gate.weight".to_string()],
+            experts: (0..4)
+                .map(|i| ExpertBlock {
+                    index: i,
+                    layer_names: vec![format!("model.layers.0.block_sparse_moe.experts.{}.w1.weight", i)],
+                    num_params: 16,
+                    compute_required: 10.0,
+                })
+                .collect(),
+        };
+        let all_layers: Vec<String> = std::iter::once(block.router_layers[0].clone())
+            .chain(block.experts.iter().map(|e| e.layer_names[0].clone()))
+            .collect();
+
+        // 原方案把整个块放在 node1
+        let mut split_plan = HashMap::new();
+        for (node, layers) in [("node1", all_layers.clone()), ("node2", Vec::new())] {
+            split_plan.insert(
+                node.to_string(),
+                SplitPlan {
+                    node_id: node.to_string(),
+                    layer_names: layers,
+                    total_compute: if node == "node1" { 45.0 } else { 0.0 },
+                    compute_utilization: 0.0,
+                    expert_assignments: Vec::new(),
+                },
+            );
+        }
+        let capacities = HashMap::from([("node1".to_string(), 100.0), ("node2".to_string(), 100.0)]);
+        assign_experts(&mut split_plan, &[block], &capacities).unwrap();
+
+        assert_eq!(split_plan["node1"].expert_assignments.len(), 2);
+        assert_eq!(split_plan["node2"].expert_assignments.len(), 2);
+        assert!(split_plan["node1"].layer_names.contains(&all_layers[0]));
+        assert!(ModelSplitter::new().validate_split_plan(&all_layers, &split_plan).is_ok());
+        assert_eq!(expert_hosts(&split_plan).len(), 4);
+    }
 }
@@ -0,0 +1,219 @@
+/**
+ * 模型版本谱系清单
+ * 每次提交新模型版本时发布一份签名清单，记录各分片的最新哈希，
+ * 以及相对上一版本的分片增量（只包含变化的数据块），
+ * 节点据此发现本地分片是否过期，并优先用增量刷新。
+ */
+use anyhow::{anyhow, Result};
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::{verify_hex_signature, ManifestError};
+
+/// 增量使用的块大小
+pub const DELTA_BLOCK_SIZE: usize = 64 * 1024;
+
+const DELTA_MAGIC: &[u8; 4] = b"WDLT";
+
+/// 某版本中的分片
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardVersion {
+    pub shard_id: String,
+    /// 分片文件名（不含目录）
+    pub shard_file: String,
+    /// 分片文件的 blake3 哈希（hex）
+    pub blake3: String,
+    pub size_bytes: u64,
+}
+
+/// 分片相对上一版本的增量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardDeltaRef {
+    pub shard_id: String,
+    /// 增量适用的旧分片哈希
+    pub from_blake3: String,
+    /// 增量文件名
+    pub delta_file: String,
+    /// 增量文件的 blake3 哈希（hex）
+    pub delta_blake3: String,
+}
+
+/// 模型版本清单
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub model_name: String,
+    pub version: u64,
+    /// 上一版本（首个版本为 None）
+    pub parent_version: Option<u64>,
+    pub committed_at: i64,
+    pub shards: Vec<ShardVersion>,
+    #[serde(default)]
+    pub deltas: Vec<ShardDeltaRef>,
+}
+
+impl ModelManifest {
+    /// 用于签名的规范字节（字段顺序固定的紧凑 JSON）
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    pub fn sign(self, key: &SigningKey) -> Result<SignedModelManifest> {
+        let signature = key.sign(&self.canonical_bytes()?);
+        Ok(SignedModelManifest {
+            manifest: self,
+            signer: hex::encode(key.verifying_key().to_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+        })
+    }
+
+    pub fn shard(&self, shard_id: &str) -> Option<&ShardVersion> {
+        self.shards.iter().find(|s| s.shard_id == shard_id)
+    }
+
+    /// 从指定旧哈希升级到本版本的增量
+    pub fn delta_from(&self, shard_id: &str, from_blake3: &str) -> Option<&ShardDeltaRef> {
+        self.deltas
+            .iter()
+            .find(|d| d.shard_id == shard_id && d.from_blake3 == from_blake3)
+    }
+}
+
+/// 已签名的模型版本清单
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedModelManifest {
+    pub manifest: ModelManifest,
+    /// 签名者 ed25519 公钥（hex）
+    pub signer: String,
+    /// ed25519 签名（hex）
+    pub signature: String,
+}
+
+impl SignedModelManifest {
+    /// 校验签名；`trusted_signers` 非空时签名者必须在其中
+    pub fn verify_signature(&self, trusted_signers: &[String]) -> std::result::Result<(), ManifestError> {
+        let bytes = self
+            .manifest
+            .canonical_bytes()
+            .map_err(|e| ManifestError::Malformed(e.to_string()))?;
+        verify_hex_signature(&self.signer, &self.signature, &bytes, trusted_signers)
+    }
+}
+
+/// 计算块级增量：只保留与旧版本不同的数据块
+///
+/// 格式：`WDLT | new_len(u64) | block_size(u32) | count(u32) | (index(u64) | len(u32) | data)*`
+pub fn compute_block_delta(old: &[u8], new: &[u8]) -> Vec<u8> {
+    let mut changed = Vec::new();
+    for (index, block) in new.chunks(DELTA_BLOCK_SIZE).enumerate() {
+        let start = index * DELTA_BLOCK_SIZE;
+        let old_block = old.get(start..(start + block.len()).min(old.len()));
+        if old_block != Some(block) {
+            changed.push((index as u64, block));
+        }
+    }
+
+    let mut out = Vec::with_capacity(20 + changed.iter().map(|(_, b)| 12 + b.len()).sum::<usize>());
+    out.extend_from_slice(DELTA_MAGIC);
+    out.extend_from_slice(&(new.len() as u64).to_le_bytes());
+    out.extend_from_slice(&(DELTA_BLOCK_SIZE as u32).to_le_bytes());
+    out.extend_from_slice(&(changed.len() as u32).to_le_bytes());
+    for (index, block) in changed {
+        out.extend_from_slice(&index.to_le_bytes());
+        out.extend_from_slice(&(block.len() as u32).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out
+}
+
+/// 把块级增量应用到旧分片
+pub fn apply_block_delta(old: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+    let mut reader = DeltaReader { data: delta, pos: 0 };
+    if reader.take(4)? != DELTA_MAGIC {
+        return Err(anyhow!("不是有效的分片增量"));
+    }
+    let new_len = reader.u64()? as usize;
+    let block_size = reader.u32()? as usize;
+    let count = reader.u32()?;
+    if block_size == 0 {
+        return Err(anyhow!("增量块大小无效"));
+    }
+
+    let mut out = old.to_vec();
+    out.resize(new_len, 0);
+    for _ in 0..count {
+        let start = (reader.u64()? as usize)
+            .checked_mul(block_size)
+            .ok_or_else(|| anyhow!("增量块索引溢出"))?;
+        let len = reader.u32()? as usize;
+        let block = reader.take(len)?;
+        let end = start.checked_add(len).filter(|end| *end <= new_len);
+        let Some(end) = end else {
+            return Err(anyhow!("增量块超出分片长度"));
+        };
+        out[start..end].copy_from_slice(block);
+    }
+    Ok(out)
+}
+
+struct DeltaReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> DeltaReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| anyhow!("分片增量被截断"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_delta_roundtrip_and_manifest_signature() {
+        let old: Vec<u8> = (0..DELTA_BLOCK_SIZE * 3).map(|i| (i % 251) as u8).collect();
+        let mut new = old.clone();
+        new[DELTA_BLOCK_SIZE + 5] ^= 0xff;
+        new.extend_from_slice(b"tail");
+
+        let delta = compute_block_delta(&old, &new);
+        // 只包含变化的第 2 块和新增的尾部
+        assert!(delta.len() < DELTA_BLOCK_SIZE + 64);
+        assert_eq!(apply_block_delta(&old, &delta).unwrap(), new);
+        assert!(apply_block_delta(&old, &delta[..delta.len() - 1]).is_err());
+
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let mut signed = ModelManifest {
+            model_name: "test".to_string(),
+            version: 2,
+            parent_version: Some(1),
+            committed_at: 0,
+            shards: vec![ShardVersion {
+                shard_id: "layers-0-3".to_string(),
+                shard_file: "shard_a.pth".to_string(),
+                blake3: blake3::hash(&new).to_hex().to_string(),
+                size_bytes: new.len() as u64,
+            }],
+            deltas: Vec::new(),
+        }
+        .sign(&key)
+        .unwrap();
+        assert!(signed.verify_signature(&[]).is_ok());
+        signed.manifest.version = 3;
+        assert!(signed.verify_signature(&[]).is_err());
+    }
+}
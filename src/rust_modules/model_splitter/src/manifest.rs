@@ -0,0 +1,211 @@
+/**
+ * 分片完整性清单
+ * 记录每个张量的形状、数据类型和 blake3 哈希，并由切分节点签名
+ */
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 单个张量的完整性记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TensorEntry {
+    pub name: String,
+    pub shape: Vec<usize>,
+    pub dtype: String,
+    /// 张量原始字节的 blake3 哈希（hex）
+    pub blake3: String,
+}
+
+/// 分片清单
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardManifest {
+    pub model_name: String,
+    pub node_id: String,
+    /// 分片文件名（不含目录）
+    pub shard_file: String,
+    /// 整个分片文件的 blake3 哈希（hex）
+    pub shard_blake3: String,
+    pub tensors: Vec<TensorEntry>,
+}
+
+impl ShardManifest {
+    /// 用于签名的规范字节（字段顺序固定的紧凑 JSON）
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// 签名清单
+    pub fn sign(self, key: &SigningKey) -> Result<SignedShardManifest> {
+        let signature = key.sign(&self.canonical_bytes()?);
+        Ok(SignedShardManifest {
+            manifest: self,
+            signer: hex::encode(key.verifying_key().to_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+        })
+    }
+
+    pub fn tensor(&self, name: &str) -> Option<&TensorEntry> {
+        self.tensors.iter().find(|t| t.name == name)
+    }
+}
+
+/// 已签名的分片清单
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedShardManifest {
+    pub manifest: ShardManifest,
+    /// 签名者 ed25519 公钥（hex）
+    pub signer: String,
+    /// ed25519 签名（hex）
+    pub signature: String,
+}
+
+/// 清单校验错误
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    #[error("清单签名无效")]
+    InvalidSignature,
+    #[error("签名者不受信任: {0}")]
+    UntrustedSigner(String),
+    #[error("分片文件哈希不匹配: 期望 {expected}, 实际 {actual}")]
+    ShardHashMismatch { expected: String, actual: String },
+    #[error("张量 {0} 不在清单中")]
+    UnknownTensor(String),
+    #[error("张量 {name} 哈希不匹配")]
+    TensorHashMismatch { name: String },
+    #[error("清单格式错误: {0}")]
+    Malformed(String),
+}
+
+impl SignedShardManifest {
+    /// 校验签名；`trusted_signers` 非空时签名者必须在其中
+    pub fn verify_signature(&self, trusted_signers: &[String]) -> std::result::Result<(), ManifestError> {
+        let bytes = self
+            .manifest
+            .canonical_bytes()
+            .map_err(|e| ManifestError::Malformed(e.to_string()))?;
+        verify_hex_signature(&self.signer, &self.signature, &bytes, trusted_signers)
+    }
+
+    /// 校验分片文件的整体哈希
+    pub fn verify_shard_bytes(&self, data: &[u8]) -> std::result::Result<(), ManifestError> {
+        let actual = blake3::hash(data).to_hex().to_string();
+        if actual != self.manifest.shard_blake3 {
+            return Err(ManifestError::ShardHashMismatch {
+                expected: self.manifest.shard_blake3.clone(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// 校验单个张量的原始字节（加载时逐层校验）
+    pub fn verify_tensor(&self, name: &str, data: &[u8]) -> std::result::Result<(), ManifestError> {
+        let entry = self
+            .manifest
+            .tensor(name)
+            .ok_or_else(|| ManifestError::UnknownTensor(name.to_string()))?;
+        if blake3::hash(data).to_hex().as_str() != entry.blake3 {
+            return Err(ManifestError::TensorHashMismatch {
+                name: name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// 读取分片文件并完成签名和哈希校验
+    pub fn verify_shard_file(&self, path: &Path, trusted_signers: &[String]) -> Result<()> {
+        self.verify_signature(trusted_signers)?;
+        let data = std::fs::read(path)
+            .with_context(|| format!("读取分片失败: {}", path.display()))?;
+        self.verify_shard_bytes(&data)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("读取清单失败: {}", path.display()))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// 校验 hex 编码的 ed25519 签名；`trusted_signers` 非空时签名者必须在其中
+pub(crate) fn verify_hex_signature(
+    signer: &str,
+    signature: &str,
+    bytes: &[u8],
+    trusted_signers: &[String],
+) -> std::result::Result<(), ManifestError> {
+    if !trusted_signers.is_empty() && !trusted_signers.iter().any(|s| s == signer) {
+        return Err(ManifestError::UntrustedSigner(signer.to_string()));
+    }
+
+    let key_bytes: [u8; 32] = hex::decode(signer)
+        .map_err(|e| ManifestError::Malformed(e.to_string()))?
+        .try_into()
+        .map_err(|_| ManifestError::Malformed("公钥长度错误".to_string()))?;
+    let key = VerifyingKey::from_bytes(&key_bytes)
+        .map_err(|e| ManifestError::Malformed(e.to_string()))?;
+
+    let sig_bytes: [u8; 64] = hex::decode(signature)
+        .map_err(|e| ManifestError::Malformed(e.to_string()))?
+        .try_into()
+        .map_err(|_| ManifestError::Malformed("签名长度错误".to_string()))?;
+    let signature = Signature::from_bytes(&sig_bytes);
+
+    key.verify(bytes, &signature)
+        .map_err(|_| ManifestError::InvalidSignature)
+}
+
+/// 计算文件的 blake3 哈希（流式读取）
+pub fn blake3_file(path: &Path) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    let mut file = std::fs::File::open(path)?;
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (SigningKey, SignedShardManifest) {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let manifest = ShardManifest {
+            model_name: "test".to_string(),
+            node_id: "node1".to_string(),
+            shard_file: "shard_node1.pth".to_string(),
+            shard_blake3: blake3::hash(b"shard").to_hex().to_string(),
+            tensors: vec![TensorEntry {
+                name: "layer1.weight".to_string(),
+                shape: vec![2, 2],
+                dtype: "float32".to_string(),
+                blake3: blake3::hash(b"tensor").to_hex().to_string(),
+            }],
+        };
+        let signed = manifest.sign(&key).unwrap();
+        (key, signed)
+    }
+
+    #[test]
+    fn test_signed_manifest_verification() {
+        let (key, mut signed) = sample();
+        let signer = hex::encode(key.verifying_key().to_bytes());
+
+        assert!(signed.verify_signature(&[signer.clone()]).is_ok());
+        assert!(signed.verify_shard_bytes(b"shard").is_ok());
+        assert!(signed.verify_tensor("layer1.weight", b"tensor").is_ok());
+        assert!(signed.verify_tensor("layer1.weight", b"tampered").is_err());
+
+        signed.manifest.tensors[0].shape = vec![4];
+        assert!(matches!(
+            signed.verify_signature(&[signer]),
+            Err(ManifestError::InvalidSignature)
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+/**
+ * MoE 感知的切分方案
+ * 按层切分会把同一块的所有专家塞到一个节点上，而每个 token 只会激活其中少数几个。
+ * 这里把专家分散到多个节点（按算力均衡），门控层留在该块稠密层所在的节点，
+ * 推理时门控结果决定 token 只发往托管被选中专家的节点。
+ */
+use anyhow::Result;
+use metadata_generator::MoeBlock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::SplitPlan;
+
+/// 专家到节点的分配
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExpertAssignment {
+    /// MoE 块前缀
+    pub moe_block: String,
+    pub expert_index: usize,
+}
+
+/// 在已有方案上重新分配专家层
+///
+/// `capacities` 为各节点的算力，专家按算力需求从大到小依次分给当前利用率最低的节点，
+/// 每个专家只分配给一个节点。
+pub fn assign_experts(
+    plans: &mut HashMap<String, SplitPlan>,
+    blocks: &[MoeBlock],
+    capacities: &HashMap<String, f64>,
+) -> Result<()> {
+    if plans.is_empty() {
+        anyhow::bail!("切分方案为空");
+    }
+    let expert_layers: HashSet<&String> = blocks
+        .iter()
+        .flat_map(|b| b.experts.iter().flat_map(|e| e.layer_names.iter()))
+        .collect();
+
+    // 先从原方案中移除专家层（及其算力）
+    let mut layer_compute: HashMap<&String, f64> = HashMap::new();
+    for block in blocks {
+        for expert in &block.experts {
+            let per_layer = expert.compute_required / expert.layer_names.len().max(1) as f64;
+            for layer in &expert.layer_names {
+                layer_compute.insert(layer, per_layer);
+            }
+        }
+    }
+    for plan in plans.values_mut() {
+        let removed: f64 = plan
+            .layer_names
+            .iter()
+            .filter_map(|l| layer_compute.get(l))
+            .sum();
+        plan.layer_names.retain(|l| !expert_layers.contains(l));
+        plan.total_compute = (plan.total_compute - removed).max(0.0);
+        plan.expert_assignments.clear();
+    }
+
+    let mut experts: Vec<(&MoeBlock, &metadata_generator::ExpertBlock)> = blocks
+        .iter()
+        .flat_map(|b| b.experts.iter().map(move |e| (b, e)))
+        .collect();
+    experts.sort_by(|a, b| b.1.compute_required.total_cmp(&a.1.compute_required));
+
+    let capacity_of = |node: &str| capacities.get(node).copied().unwrap_or(1.0).max(f64::EPSILON);
+    for (block, expert) in experts {
+        let node = plans
+            .keys()
+            .min_by(|a, b| {
+                let ua = plans[*a].total_compute / capacity_of(a);
+                let ub = plans[*b].total_compute / capacity_of(b);
+                ua.total_cmp(&ub).then_with(|| a.cmp(b))
+            })
+            .cloned()
+            .expect("方案非空");
+        let plan = plans.get_mut(&node).expect("节点来自方案本身");
+        plan.layer_names.extend(expert.layer_names.iter().cloned());
+        plan.total_compute += expert.compute_required;
+        plan.expert_assignments.push(ExpertAssignment {
+            moe_block: block.name.clone(),
+            expert_index: expert.index,
+        });
+    }
+
+    for (node, plan) in plans.iter_mut() {
+        plan.compute_utilization = plan.total_compute / capacity_of(node);
+    }
+    Ok(())
+}
+
+/// 每个专家由哪些节点托管（手工调整的方案中同一专家可能有多个副本）
+pub fn expert_hosts(plans: &HashMap<String, SplitPlan>) -> HashMap<ExpertAssignment, Vec<String>> {
+    let mut hosts: HashMap<ExpertAssignment, Vec<String>> = HashMap::new();
+    for plan in plans.values() {
+        for assignment in &plan.expert_assignments {
+            hosts.entry(assignment.clone()).or_default().push(plan.node_id.clone());
+        }
+    }
+    for nodes in hosts.values_mut() {
+        nodes.sort();
+    }
+    hosts
+}
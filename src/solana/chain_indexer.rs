@@ -0,0 +1,310 @@
+//! 链上状态同步
+//!
+//! 对每个合约通过 WebSocket 订阅账户变化（`programSubscribe`）和交易日志（`logsSubscribe`），
+//! 用 IDL 解码后写入统计数据库的 `chain_*` 表，桌面端和运维接口查询历史时不必每次请求 RPC：
+//!
+//! | 账户 | 表 |
+//! |------|----|
+//! | `NodeAccount` | `chain_nodes` |
+//! | `ContributionAccount` | `chain_contributions` |
+//! | `RewardAccount` | `chain_rewards` |
+//! | `GovernanceProposal` | `chain_proposals` |
+//!
+//! 合约事件写入 `chain_events`。每次（重新）连接时先订阅，再用 RPC 拉取全部程序账户快照，
+//! 并补齐上次同步之后的交易日志，断线期间的变化不会丢失；较旧 slot 的账户状态不会覆盖较新的。
+
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig, RpcTransactionLogsConfig,
+    RpcTransactionLogsFilter,
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::idl::{program_events, Decoded, Idl};
+use super::SolanaConfig;
+use crate::stats::{ChainEvent, ChainRecord, ChainTable, StatsStore};
+
+/// 重连等待时间上限
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+
+/// 每次补齐最多回溯的交易数
+const MAX_BACKFILL_SIGNATURES: usize = 1000;
+
+/// 账户类型与本地表的对应关系：(账户名, 表, 节点字段, 时间字段)
+const ACCOUNT_TABLES: [(&str, ChainTable, &str, &str); 4] = [
+    ("NodeAccount", ChainTable::Nodes, "node_id", "last_active_at"),
+    ("ContributionAccount", ChainTable::Contributions, "node_id", "end_timestamp"),
+    ("RewardAccount", ChainTable::Rewards, "node_id", "distributed_at"),
+    ("GovernanceProposal", ChainTable::Proposals, "proposer", "created_at"),
+];
+
+/// 需要同步的合约
+pub struct IndexedProgram {
+    pub program_id: Pubkey,
+    pub idl: Idl,
+}
+
+impl IndexedProgram {
+    /// 从 `anchor build` 生成的 IDL 文件加载
+    pub fn load(program_id: Pubkey, idl_path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            program_id,
+            idl: Idl::load(idl_path)?,
+        })
+    }
+}
+
+/// 解码后的账户对应的本地记录；不需要镜像的账户类型返回 None
+pub fn chain_record(decoded: &Decoded, address: &Pubkey, slot: u64) -> Option<(ChainTable, ChainRecord)> {
+    let (_, table, node_field, time_field) = ACCOUNT_TABLES.iter().find(|(name, ..)| *name == decoded.name)?;
+    Some((
+        *table,
+        ChainRecord {
+            address: address.to_string(),
+            node_id: decoded.value[node_field].as_str().map(str::to_string),
+            timestamp: decoded.value[time_field].as_i64().unwrap_or_default(),
+            slot,
+            data: decoded.value.clone(),
+        },
+    ))
+}
+
+/// 链上状态同步任务
+pub struct ChainIndexer {
+    rpc_url: String,
+    ws_url: String,
+    programs: Vec<Arc<IndexedProgram>>,
+    store: Arc<StatsStore>,
+}
+
+impl ChainIndexer {
+    pub fn new(config: &SolanaConfig, programs: Vec<IndexedProgram>, store: Arc<StatsStore>) -> Result<Self> {
+        let ws_url = config
+            .ws_url
+            .clone()
+            .ok_or_else(|| anyhow!("链上状态同步需要配置 WebSocket 端点 ws_url"))?;
+        Ok(Self {
+            rpc_url: config.rpc_url.clone(),
+            ws_url,
+            programs: programs.into_iter().map(Arc::new).collect(),
+            store,
+        })
+    }
+
+    /// 为每个合约启动一个同步任务，断线后指数退避重连
+    pub fn spawn(self: &Arc<Self>) -> Vec<tokio::task::JoinHandle<()>> {
+        self.programs
+            .iter()
+            .map(|program| {
+                let indexer = Arc::clone(self);
+                let program = Arc::clone(program);
+                tokio::spawn(async move {
+                    let mut backoff = Duration::from_secs(1);
+                    loop {
+                        if let Err(e) = indexer.sync(&program).await {
+                            log::warn!("[链上同步] {} 订阅中断: {}，{:?} 后重连", program.idl.name, e, backoff);
+                        }
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// 订阅并持续同步，直到连接断开
+    async fn sync(&self, program: &IndexedProgram) -> Result<()> {
+        let commitment = CommitmentConfig::confirmed();
+        let pubsub = PubsubClient::new(&self.ws_url).await?;
+        let (mut accounts, _unsubscribe_accounts) = pubsub
+            .program_subscribe(
+                &program.program_id,
+                Some(RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        commitment: Some(commitment),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        let (mut logs, _unsubscribe_logs) = pubsub
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![program.program_id.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(commitment),
+                },
+            )
+            .await?;
+
+        // 先订阅再补齐，补齐期间发生的变化由订阅收到
+        self.backfill(program).await?;
+        log::info!("[链上同步] {} 已同步，开始接收实时更新", program.idl.name);
+
+        loop {
+            tokio::select! {
+                Some(update) = accounts.next() => {
+                    let slot = update.context.slot;
+                    let keyed = update.value;
+                    match (keyed.pubkey.parse::<Pubkey>(), keyed.account.decode::<Account>()) {
+                        (Ok(address), Some(account)) => self.apply_account(program, &address, &account.data, slot),
+                        _ => log::debug!("[链上同步] 无法解析账户更新 {}", keyed.pubkey),
+                    }
+                }
+                Some(update) = logs.next() => {
+                    // 失败交易的事件已随交易回滚
+                    if update.value.err.is_none() {
+                        self.apply_logs(program, &update.value.signature, &update.value.logs, update.context.slot);
+                    }
+                }
+                else => return Err(anyhow!("订阅已断开")),
+            }
+        }
+    }
+
+    /// 拉取全部程序账户快照，并补齐上次同步之后的交易日志
+    async fn backfill(&self, program: &IndexedProgram) -> Result<()> {
+        let rpc = RpcClient::new_with_commitment(self.rpc_url.clone(), CommitmentConfig::confirmed());
+        // 快照不带 slot，取拉取前的 slot，之后订阅收到的更新一定不比它旧
+        let slot = rpc.get_slot().await?;
+        let accounts = rpc
+            .get_program_accounts_with_config(
+                &program.program_id,
+                RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .await?;
+        for (address, account) in &accounts {
+            self.apply_account(program, address, &account.data, slot);
+        }
+
+        let program_key = program.program_id.to_string();
+        let since = self.store.chain_sync_slot(&program_key)?.unwrap_or(0);
+        let signatures = rpc
+            .get_signatures_for_address_with_config(
+                &program.program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    limit: Some(MAX_BACKFILL_SIGNATURES),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        // 接口按时间倒序返回，按发生顺序回放
+        for entry in signatures.iter().rev().filter(|s| s.slot > since && s.err.is_none()) {
+            let signature: Signature = entry.signature.parse()?;
+            let transaction = rpc
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Json),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await?;
+            let logs: Option<Vec<String>> = transaction.transaction.meta.and_then(|meta| meta.log_messages.into());
+            if let Some(logs) = logs {
+                self.apply_logs(program, &entry.signature, &logs, entry.slot);
+            }
+        }
+        self.store.set_chain_sync_slot(&program_key, slot)?;
+        log::info!(
+            "[链上同步] {} 快照 {} 个账户，补齐 {} 笔交易",
+            program.idl.name,
+            accounts.len(),
+            signatures.iter().filter(|s| s.slot > since).count()
+        );
+        Ok(())
+    }
+
+    fn apply_account(&self, program: &IndexedProgram, address: &Pubkey, data: &[u8], slot: u64) {
+        let decoded = match program.idl.decode_account(data) {
+            Ok(Some(decoded)) => decoded,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("[链上同步] 无法解码 {} 的账户 {}: {}", program.idl.name, address, e);
+                return;
+            }
+        };
+        if let Some((table, record)) = chain_record(&decoded, address, slot) {
+            if let Err(e) = self.store.upsert_chain_record(table, &record) {
+                log::warn!("[链上同步] 写入 {} 失败: {}", address, e);
+            }
+        }
+    }
+
+    fn apply_logs(&self, program: &IndexedProgram, signature: &str, logs: &[String], slot: u64) {
+        let program_key = program.program_id.to_string();
+        for (index, data) in program_events(logs, &program_key).iter().enumerate() {
+            let decoded = match program.idl.decode_event(data) {
+                Ok(Some(decoded)) => decoded,
+                Ok(None) => continue,
+                Err(e) => {
+                    log::warn!("[链上同步] 无法解码交易 {} 中的事件: {}", signature, e);
+                    continue;
+                }
+            };
+            let event = ChainEvent {
+                signature: signature.to_string(),
+                index: index as u32,
+                program: program.idl.name.clone(),
+                name: decoded.name,
+                slot,
+                data: decoded.value,
+            };
+            if let Err(e) = self.store.record_chain_event(&event) {
+                log::warn!("[链上同步] 写入事件失败: {}", e);
+            }
+        }
+        if let Err(e) = self.store.set_chain_sync_slot(&program_key, slot) {
+            log::warn!("[链上同步] 更新同步进度失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accounts_map_to_local_tables() {
+        let address = Pubkey::new_unique();
+        let node = Decoded {
+            name: "NodeAccount".to_string(),
+            value: serde_json::json!({ "node_id": "node-a", "last_active_at": 1_700_000_000, "status": "Active" }),
+        };
+        let (table, record) = chain_record(&node, &address, 42).unwrap();
+        assert_eq!(table, ChainTable::Nodes);
+        assert_eq!(record.node_id.as_deref(), Some("node-a"));
+        assert_eq!(record.timestamp, 1_700_000_000);
+        assert_eq!(record.slot, 42);
+
+        let proposal = Decoded {
+            name: "GovernanceProposal".to_string(),
+            value: serde_json::json!({ "proposer": "voter-a", "created_at": 5 }),
+        };
+        assert_eq!(chain_record(&proposal, &address, 1).unwrap().1.node_id.as_deref(), Some("voter-a"));
+
+        // 全局状态等账户不镜像
+        let state = Decoded {
+            name: "NodeManagementState".to_string(),
+            value: serde_json::json!({}),
+        };
+        assert!(chain_record(&state, &address, 1).is_none());
+    }
+}
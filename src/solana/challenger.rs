@@ -0,0 +1,49 @@
+//! 冗余执行不一致时的链上质疑
+//!
+//! 冗余验证器发现两份任务输出不一致时，对原任务的执行者发起链上质疑，
+//! 以证据摘要作为质疑账户的索引，由管理员裁决。
+
+use std::sync::Arc;
+
+use solana_sdk::pubkey::Pubkey;
+use tokio::runtime::Handle;
+
+use super::client::SolanaClient;
+use crate::consensus::{ChallengeSink, ValidationVerdict};
+
+/// 把冗余验证的不一致结论提交为链上质疑
+pub struct ChallengeSubmitter {
+    client: Arc<SolanaClient>,
+    runtime: Handle,
+}
+
+impl ChallengeSubmitter {
+    /// 需在 tokio 运行时内创建，质疑交易在该运行时上异步发送
+    pub fn new(client: Arc<SolanaClient>) -> Self {
+        Self {
+            client,
+            runtime: Handle::current(),
+        }
+    }
+}
+
+impl ChallengeSink for ChallengeSubmitter {
+    fn challenge(&self, verdict: &ValidationVerdict) {
+        let Ok(node) = verdict.primary.parse::<Pubkey>() else {
+            log::warn!("节点 ID {} 不是有效的 Solana 地址，跳过链上质疑", verdict.primary);
+            return;
+        };
+        let client = self.client.clone();
+        let task_id = verdict.task_id.clone();
+        let evidence_hash = verdict.evidence_hash;
+        self.runtime.spawn(async move {
+            match client.open_challenge(&node, &task_id, evidence_hash).await {
+                Ok(result) if result.success => {
+                    log::info!("已对 {} 的任务 {} 发起质疑: {}", node, task_id, result.signature)
+                }
+                Ok(result) => log::warn!("任务 {} 的质疑交易失败: {:?}", task_id, result.error),
+                Err(e) => log::warn!("任务 {} 的质疑提交失败: {}", task_id, e),
+            }
+        });
+    }
+}
@@ -6,68 +6,178 @@ use anyhow::{anyhow, Result};
 use chrono::Utc;
 use std::sync::Arc;
 use parking_lot::RwLock;
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
-    transaction::Transaction,
+    signature::{Keypair, Signature},
 };
 
 use super::types::*;
 use super::SolanaConfig;
+use super::rpc_pool::{EndpointStatus, RpcPool};
+use super::tx_pipeline::{LandingStats, PipelineConfig, TxPipeline};
+use super::payout_verifier::PayoutVerifier;
+use super::wallet::{KeypairSigner, WalletSigner};
+use crate::crypto::vault::KeyVault;
 use super::compute::{ComputeTracker, ComputeCalculator, ContributionLevel};
 use super::rewards::{RewardManager, RewardSettler};
 use super::accounts::*;
 use super::instruction::*;
+use super::indexer::LocalIndexer;
+use super::programs::{self, CommitteeProofArgs, ContributionHeader, ContributionProofArgs, MetricKind, ProgramIds, RecordContributionArgs, TransactionAccountArgs};
+use super::oracle::{MetricAggregate, MetricReport};
+use crate::consensus::{CommitteeSeed, CommitteeTicket, ReputationEngine};
+
+/// 优先费设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriorityFee {
+    /// 每个计算单元的价格（micro-lamports），0 表示按近期优先费估算
+    pub compute_unit_price_micro_lamports: u64,
+    /// 计算单元上限，None 表示按指令类型设置
+    pub compute_unit_limit: Option<u32>,
+}
+
+impl PriorityFee {
+    /// 需要前置到交易中的 ComputeBudget 指令
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        if let Some(limit) = self.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+        }
+        if self.compute_unit_price_micro_lamports > 0 {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                self.compute_unit_price_micro_lamports,
+            ));
+        }
+        instructions
+    }
+}
 
 /// Solana 客户端
 pub struct SolanaClient {
     /// 配置
     config: SolanaConfig,
-    /// RPC 客户端
-    rpc_client: RpcClient,
-    /// 支付者密钥对
-    payer_keypair: Option<Keypair>,
+    /// RPC 端点池（按优先级故障切换）
+    rpc: Arc<RpcPool>,
+    /// 支付者签名器（本地密钥对、硬件钱包或远程审批）
+    payer: Option<Arc<dyn WalletSigner>>,
     /// 算力跟踪器
     compute_tracker: Arc<RwLock<ComputeTracker>>,
     /// 收益管理器
     reward_manager: Arc<RwLock<RewardManager>>,
     /// 收益结算器
     reward_settler: Arc<RwLock<RewardSettler>>,
+    /// 本地记录索引（用于收益报表导出）
+    indexer: Option<Arc<LocalIndexer>>,
+    /// 拆分合约的程序 ID（未设置时使用旧版单合约指令）
+    program_ids: Option<ProgramIds>,
+    /// 优先费设置（显式设置的值优先于流水线估算）
+    priority_fee: PriorityFee,
+    /// 交易提交流水线
+    pipeline: TxPipeline,
 }
 
 impl SolanaClient {
-    /// 创建新的 Solana 客户端
+    /// 创建新的 Solana 客户端（支付者私钥从已注册的平台密钥库或明文配置读取）
     pub fn new(config: SolanaConfig, node_id: String) -> Result<Self> {
+        let vault = crate::crypto::vault::platform_vault();
+        Self::with_vault(config, node_id, vault.as_deref())
+    }
+
+    /// 创建客户端，支付者私钥从指定密钥库读取
+    pub fn with_vault(config: SolanaConfig, node_id: String, vault: Option<&dyn KeyVault>) -> Result<Self> {
         let commitment = CommitmentConfig::confirmed();
-        let rpc_client = RpcClient::new_with_commitment(&config.rpc_url, commitment);
+        let rpc = Arc::new(RpcPool::new(&config.rpc_endpoints(), commitment)?);
         
         // 解析程序 ID
         let _program_id = config.program_id.parse::<Pubkey>()
             .map_err(|e| anyhow!("Invalid program ID: {}", e))?;
         
         // 创建支付者密钥对
-        let payer_keypair = if let Some(keypair_base58) = &config.payer_keypair_base58 {
-            Some(Keypair::from_base58_string(keypair_base58)
-                .map_err(|e| anyhow!("Invalid keypair: {}", e))?)
+        let payer: Option<Arc<dyn WalletSigner>> = if let Some(keypair_base58) = config.payer_secret(vault)? {
+            let keypair = Keypair::from_base58_string(&keypair_base58)
+                .map_err(|e| anyhow!("Invalid keypair: {}", e))?;
+            Some(Arc::new(KeypairSigner::new(keypair)))
         } else {
             None
         };
         
         Ok(Self {
             config,
-            rpc_client,
-            payer_keypair,
+            payer,
             compute_tracker: Arc::new(RwLock::new(ComputeTracker::new(node_id))),
             reward_manager: Arc::new(RwLock::new(RewardManager::with_defaults())),
             reward_settler: Arc::new(RwLock::new(RewardSettler::with_defaults())),
+            indexer: None,
+            program_ids: None,
+            priority_fee: PriorityFee::default(),
+            pipeline: TxPipeline::new(Arc::clone(&rpc), PipelineConfig::default()),
+            rpc,
         })
     }
+
+    /// 设置本地记录索引，成功上报的贡献和收益会写入索引
+    pub fn with_indexer(mut self, indexer: Arc<LocalIndexer>) -> Self {
+        self.indexer = Some(indexer);
+        self
+    }
+
+    /// 设置支付者签名器（如硬件钱包或远程审批），替换配置中的支付者私钥
+    pub fn with_signer(mut self, signer: Arc<dyn WalletSigner>) -> Self {
+        log::info!("Solana 支付者使用 {} 签名器: {}", signer.kind(), signer.pubkey());
+        self.payer = Some(signer);
+        self
+    }
+
+    /// 设置拆分合约的程序 ID，之后节点、贡献和收益操作走 Anchor 指令
+    pub fn with_program_ids(mut self, program_ids: ProgramIds) -> Self {
+        self.program_ids = Some(program_ids);
+        self
+    }
+
+    /// 设置交易优先费
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// 设置交易提交流水线的优先费和重发策略
+    pub fn with_pipeline_config(mut self, config: PipelineConfig) -> Self {
+        self.pipeline.set_config(config);
+        self
+    }
+
+    /// 设置故障注入器，注入的 RPC 延迟作用于每次交易尝试
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(mut self, injector: Arc<crate::chaos::FaultInjector>) -> Self {
+        self.pipeline.set_fault_injector(injector);
+        self
+    }
+
+    fn index_contribution(&self, contribution: &ComputeContribution) {
+        if let Some(indexer) = &self.indexer {
+            if let Err(e) = indexer.record_contribution(contribution) {
+                log::warn!("写入本地贡献索引失败: {}", e);
+            }
+        }
+    }
+
+    fn index_reward(&self, mut distribution: RewardDistribution, signature: &str) {
+        if let Some(indexer) = &self.indexer {
+            distribution.transaction_signature = signature.to_string();
+            distribution.status = RewardStatus::Confirmed;
+            if let Err(e) = indexer.record_reward(&distribution) {
+                log::warn!("写入本地收益索引失败: {}", e);
+            }
+        }
+    }
     
     /// 检查连接状态
     pub async fn check_connection(&self) -> Result<bool> {
-        match self.rpc_client.get_version() {
+        match self.rpc.call(|rpc| rpc.get_version()) {
             Ok(_) => Ok(true),
             Err(e) => {
                 log::warn!("Failed to connect to Solana RPC: {}", e);
@@ -102,14 +212,49 @@ impl SolanaClient {
         &self.config
     }
 
+    /// 启动 RPC 端点健康探测
+    pub fn spawn_rpc_health_probe(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        self.rpc.spawn_health_probe(interval)
+    }
+
+    /// 各 RPC 端点的健康状态
+    pub fn rpc_status(&self) -> Vec<EndpointStatus> {
+        self.rpc.status()
+    }
+
+    /// 交易落地延迟、重发次数和优先费统计
+    pub fn landing_stats(&self) -> LandingStats {
+        self.pipeline.stats()
+    }
+
     // ============ 节点管理 ============
 
     /// 注册节点到区块链
     pub async fn register_node(&self, node_info: NodeInfo) -> Result<TransactionResult> {
         log::info!("注册节点到区块链: {}", node_info.node_id);
 
+        if let (Some(ids), Some(payer)) = (&self.program_ids, &self.payer) {
+            let node_id = self.get_program_account(&node_info.node_id).await?;
+            // NodeInfo 不含地理位置，注册时留空
+            let location = Location {
+                latitude: 0,
+                longitude: 0,
+                country: String::new(),
+                region: String::new(),
+            };
+            let instruction = programs::register_node(
+                ids,
+                &payer.pubkey(),
+                &node_id,
+                &node_info.name,
+                &node_info.device_type,
+                &location,
+            );
+            return self.send_instructions(vec![instruction], &[]).await;
+        }
+
         // 如果有支付者密钥，使用真实的智能合约调用
-        if let Some(payer) = &self.payer_keypair {
+        if self.payer.is_some() {
             let program_id = self.get_program_account(&self.config.program_id).await?;
             let node_id = self.get_program_account(&node_info.node_id).await?;
             let owner = self.get_program_account(&node_info.owner_address).await?;
@@ -129,14 +274,8 @@ impl SolanaClient {
                 node_info.device_type.clone(),
             )?;
 
-            // 创建交易
-            let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
-            let recent_blockhash = self.rpc_client.get_latest_blockhash()
-                .map_err(|e| anyhow!("Failed to get recent blockhash: {}", e))?;
-            transaction.sign(&[payer], recent_blockhash);
-
             // 发送交易
-            match self.send_transaction_with_retry(&transaction, 3).await {
+            match self.send_with_retry(vec![instruction], &[]).await {
                 Ok(signature) => Ok(TransactionResult {
                     signature: signature.to_string(),
                     success: true,
@@ -166,7 +305,11 @@ impl SolanaClient {
     ) -> Result<TransactionResult> {
         log::info!("更新节点状态: {} -> {:?}", node_id, status);
 
-        // TODO: 实现实际的链上状态更新逻辑
+        if let Some(ids) = &self.program_ids {
+            let node_pubkey = self.get_program_account(node_id).await?;
+            let instruction = programs::update_node_status(ids, &self.payer_pubkey(), &node_pubkey, status);
+            return self.send_instructions(vec![instruction], &[]).await;
+        }
 
         Ok(TransactionResult {
             signature: format!("mock_status_update_{}", node_id),
@@ -206,8 +349,17 @@ impl SolanaClient {
             contribution.compute_score
         );
 
+        if let Some(ids) = &self.program_ids {
+            let args = contribution_args(&contribution)?;
+            let result = self.record_contribution_with(ids, &args).await?;
+            if result.success {
+                self.index_contribution(&contribution);
+            }
+            return Ok(result);
+        }
+
         // 如果有支付者密钥，使用真实的智能合约调用
-        if let Some(payer) = &self.payer_keypair {
+        if let Some(payer) = &self.payer {
             let program_id = self.get_program_account(&self.config.program_id).await?;
             let node_id = self.get_program_account(&contribution.node_id).await?;
 
@@ -239,16 +391,11 @@ impl SolanaClient {
                 contribution.compute_score,
             )?;
 
-            // 创建交易
-            let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
-            let recent_blockhash = self.rpc_client.get_latest_blockhash()
-                .map_err(|e| anyhow!("Failed to get recent blockhash: {}", e))?;
-            transaction.sign(&[payer], recent_blockhash);
-
                 // 发送交易
-                match self.send_transaction_with_retry(&transaction, 3).await {
+                match self.send_with_retry(vec![instruction], &[]).await {
                     Ok(signature) => {
                         log::info!("Node registration successful: {}", signature);
+                        self.index_contribution(&contribution);
                         Ok(TransactionResult {
                             signature: signature.to_string(),
                             success: true,
@@ -266,6 +413,7 @@ impl SolanaClient {
                 }
         } else {
             // 模拟实现
+            self.index_contribution(&contribution);
             Ok(TransactionResult {
                 signature: format!("mock_contribution_{}", contribution.id),
                 success: true,
@@ -333,8 +481,30 @@ impl SolanaClient {
 
         let mut results = Vec::new();
 
+        if let Some(ids) = &self.program_ids {
+            for distribution in distributions {
+                let node_id = self.get_program_account(&distribution.node_id).await?;
+                // 收益记录只保存任务 ID，作为合约中的贡献引用；
+                // 收益记录 PDA 由合约按链上时间派生，这里按本地时间估计
+                let instruction = programs::distribute_rewards(
+                    ids,
+                    &self.payer_pubkey(),
+                    &node_id,
+                    &distribution.task_id,
+                    distribution.amount_lamports,
+                    Utc::now().timestamp(),
+                );
+                let result = self.send_instructions(vec![instruction], &[]).await?;
+                if result.success {
+                    self.index_reward(distribution, &result.signature);
+                }
+                results.push(result);
+            }
+            return Ok(results);
+        }
+
         // 如果有支付者密钥，使用真实的智能合约调用
-        if let Some(payer) = &self.payer_keypair {
+        if let Some(payer) = &self.payer {
             let program_id = self.get_program_account(&self.config.program_id).await?;
             let (global_state_pda, _) = find_global_state_pda(&program_id);
 
@@ -361,19 +531,16 @@ impl SolanaClient {
                     &payer.pubkey(),
                 )?;
 
-                // 创建交易
-                let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
-                let recent_blockhash = self.rpc_client.get_latest_blockhash()
-                    .map_err(|e| anyhow!("Failed to get recent blockhash: {}", e))?;
-                transaction.sign(&[payer], recent_blockhash);
-
                 // 发送交易
-                match self.send_transaction_with_retry(&transaction, 3).await {
-                    Ok(signature) => results.push(TransactionResult {
-                        signature: signature.to_string(),
-                        success: true,
-                        error: None,
-                    }),
+                match self.send_with_retry(vec![instruction], &[]).await {
+                    Ok(signature) => {
+                        self.index_reward(distribution, &signature.to_string());
+                        results.push(TransactionResult {
+                            signature: signature.to_string(),
+                            success: true,
+                            error: None,
+                        })
+                    }
                     Err(e) => results.push(TransactionResult {
                         signature: "".to_string(),
                         success: false,
@@ -437,11 +604,11 @@ impl SolanaClient {
         log::info!("查询合约状态");
 
         // 如果有程序客户端，查询真实状态
-        if self.payer_keypair.is_some() {
+        if self.payer.is_some() {
             let global_state_pda = self.get_global_state_pda().await?;
             
             // 查询全局状态账户
-            match self.rpc_client.get_account(&global_state_pda) {
+            match self.rpc.call(|rpc| rpc.get_account(&global_state_pda)) {
                 Ok(account) => {
                     // 简化解析，实际应该使用 Anchor 的反序列化
                     Ok(ContractState {
@@ -490,7 +657,343 @@ impl SolanaClient {
         let state = self.get_contract_state().await?;
         Ok(state.base_reward_per_compute_lamports)
     }
+
+    /// 委员会抽签种子：最近一个已最终确认区块的 slot 及其区块哈希，任何人可按 slot 查询复核
+    pub async fn committee_seed(&self) -> Result<CommitteeSeed> {
+        let finalized = CommitmentConfig::finalized();
+        let latest = self.rpc.call(|rpc| rpc.get_slot_with_commitment(finalized))?;
+        // 跳过的 slot 没有区块，取最近一个实际出块的 slot
+        let slot = self
+            .rpc
+            .call(|rpc| rpc.get_blocks_with_commitment(latest.saturating_sub(64), Some(latest), finalized))?
+            .last()
+            .copied()
+            .ok_or_else(|| anyhow!("slot {} 之前没有已确认的区块", latest))?;
+        let blockhash: Hash = self.rpc.call(|rpc| rpc.get_block(slot))?.blockhash.parse()?;
+        Ok(CommitteeSeed {
+            slot,
+            blockhash: blockhash.to_bytes(),
+        })
+    }
     
+    // ============ 拆分合约指令 ============
+
+    /// 提交节点心跳
+    pub async fn submit_heartbeat(&self, node_id: &Pubkey) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::submit_heartbeat(ids, &self.payer_pubkey(), node_id);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 将超过不活跃阈值的节点标记为离线
+    pub async fn mark_inactive(&self, node_id: &Pubkey) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::mark_inactive(ids, &self.payer_pubkey(), node_id);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 提交罚没提案
+    pub async fn slash_node(
+        &self,
+        node_id: &Pubkey,
+        slash_ratio: u32,
+        evidence_hash: [u8; 32],
+        reason: &str,
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::slash_node(ids, &self.payer_pubkey(), node_id, slash_ratio, evidence_hash, reason);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 对罚没提出申诉
+    pub async fn appeal_slash(
+        &self,
+        node_id: &Pubkey,
+        evidence_hash: &[u8; 32],
+        counter_evidence_hash: [u8; 32],
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction =
+            programs::appeal_slash(ids, &self.payer_pubkey(), node_id, evidence_hash, counter_evidence_hash);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 记录算力贡献（可附带零知识证明）
+    pub async fn record_contribution(&self, args: &RecordContributionArgs) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        self.record_contribution_with(ids, args).await
+    }
+
+    /// 上报附带零知识证明的算力贡献
+    ///
+    /// 证明的第一个公开输入须承诺本条贡献（`RecordContributionArgs::commitment`），
+    /// 可用 `ZkProver::prove_contribution` 生成；合约开启验证时只接受这样绑定的证明。
+    pub async fn report_compute_contribution_with_proof(
+        &self,
+        contribution: ComputeContribution,
+        circuit_id: &str,
+        proof: ContributionProofArgs,
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let mut args = contribution_args(&contribution)?;
+        if proof.public_inputs.first() != Some(&args.commitment()) {
+            return Err(anyhow!("Proof is not bound to contribution {}", contribution.id));
+        }
+        args.proof = Some((circuit_id.to_string(), proof));
+        let result = self.record_contribution_with(ids, &args).await?;
+        if result.success {
+            self.index_contribution(&contribution);
+        }
+        Ok(result)
+    }
+
+    async fn record_contribution_with(
+        &self,
+        ids: &ProgramIds,
+        args: &RecordContributionArgs,
+    ) -> Result<TransactionResult> {
+        let instruction = programs::record_contribution(ids, &self.payer_pubkey(), args);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 把本地信誉引擎的分数导出到 node-management 合约
+    ///
+    /// 只导出节点 ID 为合法公钥的节点；单个节点失败不影响其余节点，返回成功提交的结果。
+    pub async fn export_reputation(&self, engine: &ReputationEngine) -> Result<Vec<TransactionResult>> {
+        let ids = self.program_ids()?;
+        let mut results = Vec::new();
+        for (node_id, score) in engine.snapshot() {
+            let Ok(node_pubkey) = node_id.parse::<Pubkey>() else {
+                continue;
+            };
+            let instruction = programs::update_reputation(ids, &self.payer_pubkey(), &node_pubkey, score);
+            match self.send_instructions(vec![instruction], &[]).await {
+                Ok(result) => results.push(result),
+                Err(e) => log::warn!("导出节点 {} 的信誉分失败: {}", node_id, e),
+            }
+        }
+        Ok(results)
+    }
+
+    /// 提交贡献验证结果，附带验证者的委员会抽签票据
+    pub async fn verify_contribution(
+        &self,
+        contribution_id: &str,
+        is_valid: bool,
+        verifier_notes: Option<String>,
+        ticket: Option<&CommitteeTicket>,
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let contribution = self.get_contribution_header(contribution_id).await?;
+        let committee_proof = ticket.map(CommitteeProofArgs::from_ticket).transpose()?;
+        let instruction = programs::verify_contribution(
+            ids,
+            &self.payer_pubkey(),
+            &contribution,
+            is_valid,
+            verifier_notes,
+            committee_proof,
+        );
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 读取链上贡献记录的节点和时间字段
+    pub async fn get_contribution_header(&self, contribution_id: &str) -> Result<ContributionHeader> {
+        let ids = self.program_ids()?;
+        let account = self
+            .rpc
+            .call(|rpc| rpc.get_account(&programs::contribution_pda(ids, contribution_id)))
+            .map_err(|e| anyhow!("Failed to fetch contribution {}: {}", contribution_id, e))?;
+        ContributionHeader::decode(&account.data)
+    }
+
+    /// 对节点的任务输出发起链上质疑，等待管理员裁决
+    pub async fn open_challenge(
+        &self,
+        node_id: &Pubkey,
+        task_id: &str,
+        evidence_hash: [u8; 32],
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::open_challenge(ids, &self.payer_pubkey(), node_id, task_id, evidence_hash);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 裁决质疑（管理员）
+    pub async fn resolve_challenge(
+        &self,
+        node_id: &Pubkey,
+        evidence_hash: &[u8; 32],
+        upheld: bool,
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::resolve_challenge(ids, &self.payer_pubkey(), node_id, evidence_hash, upheld);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 结算已关闭的纪元
+    pub async fn finalize_epoch(&self, epoch: u64) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::finalize_epoch(ids, &self.payer_pubkey(), epoch);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 按纪元算力比例为节点计入收益
+    pub async fn distribute_epoch_reward(&self, epoch: u64, node_id: &Pubkey) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::distribute_epoch_reward(ids, &self.payer_pubkey(), epoch, node_id);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 领取节点已归属的收益（支付者须为节点所有者）
+    pub async fn claim_rewards(&self, node_id: &Pubkey) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let (treasury, reward_mint) = self.reward_treasury_and_mint(ids)?;
+        let instruction =
+            programs::claim_rewards(ids, &self.payer_pubkey(), node_id, &treasury, reward_mint.as_ref());
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 收益到账校验器，按收益合约配置的币种核对钱包入账
+    pub fn payout_verifier(&self) -> Result<PayoutVerifier> {
+        let ids = self.program_ids()?;
+        let (_, reward_mint) = self.reward_treasury_and_mint(ids)?;
+        Ok(PayoutVerifier::new(Arc::clone(&self.rpc), ids.reward_management, reward_mint))
+    }
+
+    /// 质押 lamports
+    pub async fn stake_tokens(
+        &self,
+        node_id: &Pubkey,
+        amount: u64,
+        lock_duration_seconds: u64,
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let (treasury, _) = self.reward_treasury_and_mint(ids)?;
+        let instruction =
+            programs::stake_tokens(ids, &self.payer_pubkey(), &treasury, node_id, amount, lock_duration_seconds);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 创建治理提案
+    pub async fn create_proposal(
+        &self,
+        proposal_id: &str,
+        title: &str,
+        description: &str,
+        proposal_type: programs::ProposalType,
+        target: &Instruction,
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::create_proposal(
+            ids,
+            &self.payer_pubkey(),
+            proposal_id,
+            title,
+            description,
+            proposal_type,
+            &target.program_id,
+            transaction_accounts(target),
+            target.data.clone(),
+        );
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 以节点质押为权重对提案投票
+    pub async fn vote_on_proposal(&self, node_id: &Pubkey, proposal_id: &str, vote: bool) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::vote_on_proposal(ids, &self.payer_pubkey(), node_id, proposal_id, vote);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 结束提案投票
+    pub async fn finalize_proposal(&self, proposal_id: &str) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::finalize_proposal(ids, &self.payer_pubkey(), proposal_id);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 执行已通过的提案；`target` 须与创建提案时的目标指令一致
+    pub async fn execute_proposal(&self, proposal_id: &str, target: &Instruction) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::execute_proposal(
+            ids,
+            &self.payer_pubkey(),
+            proposal_id,
+            &target.program_id,
+            &transaction_accounts(target),
+        );
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 批准多签交易
+    pub async fn approve_multisig_transaction(
+        &self,
+        multisig: &Pubkey,
+        transaction: &Pubkey,
+    ) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::approve_multisig_transaction(ids, &self.payer_pubkey(), multisig, transaction);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 从收益合约状态读取国库地址和奖励代币 mint（lamports 模式下 mint 为 None）
+    /// 以支付者身份向预言机上报一项指标（支付者须在上报者白名单中）
+    pub async fn submit_metric_report(&self, report: &MetricReport) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::submit_metric_report(
+            ids,
+            &self.payer_pubkey(),
+            report.kind,
+            report.epoch,
+            &report.subject,
+            report.value,
+        );
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    /// 查询预言机指标汇总，尚无人上报时返回 None
+    pub async fn get_oracle_metric(&self, kind: MetricKind, epoch: u64, subject: &Pubkey) -> Result<Option<MetricAggregate>> {
+        let ids = self.program_ids()?;
+        let address = programs::oracle_metric_pda(ids, kind, epoch, subject);
+        match self.rpc.call(|rpc| rpc.get_account_with_commitment(&address, CommitmentConfig::confirmed()))?.value {
+            Some(account) => Ok(Some(MetricAggregate::decode(&account.data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 节点的预言机算力是否已导入 contribution-tracking
+    pub async fn oracle_compute_imported(&self, epoch: u64, node_id: &Pubkey) -> Result<bool> {
+        let ids = self.program_ids()?;
+        let compute_report = programs::oracle_metric_pda(ids, MetricKind::NodeComputeScore, epoch, node_id);
+        self.account_exists(&programs::oracle_import_pda(ids, &compute_report)).await
+    }
+
+    /// 把预言机已定值的节点算力导入 contribution-tracking
+    pub async fn import_oracle_compute(&self, epoch: u64, node_id: &Pubkey, with_uptime: bool) -> Result<TransactionResult> {
+        let ids = self.program_ids()?;
+        let instruction = programs::import_oracle_compute(ids, &self.payer_pubkey(), epoch, node_id, with_uptime);
+        self.send_instructions(vec![instruction], &[]).await
+    }
+
+    fn reward_treasury_and_mint(&self, ids: &ProgramIds) -> Result<(Pubkey, Option<Pubkey>)> {
+        let account = self
+            .rpc
+            .call(|rpc| rpc.get_account(&programs::reward_state_pda(ids)))
+            .map_err(|e| anyhow!("Failed to fetch reward state: {}", e))?;
+        // 判别符(8) + admin(32) + treasury(32) + 4 个 u64 + 两个 bool/u8 + reward_currency(1) + reward_mint(32)
+        let data = &account.data;
+        if data.len() < 139 {
+            return Err(anyhow!("Reward state account too small"));
+        }
+        let treasury = Pubkey::try_from(&data[40..72]).map_err(|e| anyhow!("Invalid treasury: {}", e))?;
+        let reward_mint = match data[106] {
+            0 => None,
+            _ => Some(Pubkey::try_from(&data[107..139]).map_err(|e| anyhow!("Invalid reward mint: {}", e))?),
+        };
+        Ok((treasury, reward_mint))
+    }
+
     // ============ 辅助函数 ============
     
     /// 获取全局状态 PDA
@@ -520,7 +1023,7 @@ impl SolanaClient {
         let signature = signature.parse()
             .map_err(|e| anyhow!("Invalid signature: {}", e))?;
         
-        match self.rpc_client.confirm_transaction(&signature) {
+        match self.rpc.call(|rpc| rpc.confirm_transaction(&signature)) {
             Ok(confirmation) => Ok(confirmation.value.is_some()),
             Err(e) => {
                 log::warn!("Failed to confirm transaction {}: {}", signature, e);
@@ -529,38 +1032,69 @@ impl SolanaClient {
         }
     }
     
-    /// 发送交易并确认（带重试）
-    async fn send_transaction_with_retry(
+    /// 经提交流水线发送指令：估算优先费、按指令类型设置计算单元上限，blockhash 过期时提高优先费重发
+    async fn send_with_retry(
         &self,
-        transaction: &Transaction,
-        max_retries: u32,
-    ) -> Result<solana_sdk::signature::Signature> {
-        let mut retries = 0;
-        
-        loop {
-            match self.rpc_client.send_and_confirm_transaction(transaction) {
-                Ok(signature) => {
-                    log::info!("Transaction sent successfully: {}", signature);
-                    return Ok(signature);
-                }
-                Err(e) => {
-                    retries += 1;
-                    if retries >= max_retries {
-                        return Err(anyhow!("Transaction failed after {} retries: {}", max_retries, e));
-                    }
-                    
-                    log::warn!("Transaction failed (attempt {}/{}): {}, retrying...", retries, max_retries, e);
-                    
-                    // 等待一段时间后重试
-                    tokio::time::sleep(tokio::time::Duration::from_millis(1000 * retries as u64)).await;
-                }
+        instructions: Vec<Instruction>,
+        extra_signers: &[&Keypair],
+    ) -> Result<Signature> {
+        let payer = self
+            .payer
+            .as_deref()
+            .ok_or_else(|| anyhow!("No payer signer configured"))?;
+        let landed = self
+            .pipeline
+            .submit(payer, &instructions, extra_signers, self.priority_fee)
+            .await?;
+        Ok(landed.signature)
+    }
+
+    /// 发送任意指令（可由 `programs` 中的构建函数生成），未配置支付者时返回模拟结果
+    pub async fn send_instructions(
+        &self,
+        instructions: Vec<Instruction>,
+        extra_signers: &[&Keypair],
+    ) -> Result<TransactionResult> {
+        if self.payer.is_none() {
+            return Ok(TransactionResult {
+                signature: format!("mock_tx_{}", Utc::now().timestamp_millis()),
+                success: true,
+                error: None,
+            });
+        }
+
+        match self.send_with_retry(instructions, extra_signers).await {
+            Ok(signature) => Ok(TransactionResult {
+                signature: signature.to_string(),
+                success: true,
+                error: None,
+            }),
+            Err(e) => {
+                log::error!("Transaction failed: {}", e);
+                Ok(TransactionResult {
+                    signature: "".to_string(),
+                    success: false,
+                    error: Some(format!("Transaction failed: {}", e)),
+                })
             }
         }
     }
+
+    /// 支付者公钥（未配置时为默认公钥，仅用于构建模拟交易）
+    pub(super) fn payer_pubkey(&self) -> Pubkey {
+        self.payer.as_ref().map(|signer| signer.pubkey()).unwrap_or_default()
+    }
+
+    /// 拆分合约的程序 ID
+    fn program_ids(&self) -> Result<&ProgramIds> {
+        self.program_ids
+            .as_ref()
+            .ok_or_else(|| anyhow!("Program ids not configured, call with_program_ids first"))
+    }
     
     /// 获取账户租金豁免最低余额
     pub async fn get_rent_exemption_minimum(&self, data_size: usize) -> Result<u64> {
-        match self.rpc_client.get_minimum_balance_for_rent_exemption(data_size) {
+        match self.rpc.call(|rpc| rpc.get_minimum_balance_for_rent_exemption(data_size)) {
             Ok(balance) => Ok(balance),
             Err(e) => {
                 log::warn!("Failed to get rent exemption: {}", e);
@@ -572,7 +1106,7 @@ impl SolanaClient {
     
     /// 检查账户是否存在
     pub async fn account_exists(&self, pubkey: &Pubkey) -> Result<bool> {
-        match self.rpc_client.get_account(pubkey) {
+        match self.rpc.call(|rpc| rpc.get_account(pubkey)) {
             Ok(_) => Ok(true),
             Err(solana_client::client_error::ClientError::AccountNotFound) => Ok(false),
             Err(e) => {
@@ -583,6 +1117,59 @@ impl SolanaClient {
     }
 }
 
+/// 旧版贡献记录转换为合约参数（旧记录不含任务类型和模型信息，使用默认值；证明由调用方附加）
+fn contribution_args(contribution: &ComputeContribution) -> Result<RecordContributionArgs> {
+    let node_id = contribution
+        .node_id
+        .parse::<Pubkey>()
+        .map_err(|e| anyhow!("Invalid node id: {}", e))?;
+    Ok(RecordContributionArgs {
+        contribution_id: contribution.id.clone(),
+        node_id,
+        task_id: contribution.task_id.clone(),
+        task_type: TaskType::Training,
+        model_info: ModelInfo {
+            model_id: String::new(),
+            version: String::new(),
+            parameters_hash: contribution.manifest_hash.clone().unwrap_or_default(),
+            size_mb: 0,
+        },
+        start_timestamp: contribution.start_timestamp,
+        end_timestamp: contribution.end_timestamp,
+        duration_seconds: contribution.duration_seconds,
+        avg_gpu_usage_percent: contribution.avg_gpu_usage_percent,
+        gpu_memory_used_mb: contribution.gpu_memory_used_mb,
+        avg_cpu_usage_percent: contribution.avg_cpu_usage_percent,
+        memory_used_mb: contribution.memory_used_mb,
+        network_upload_mb: contribution.network_upload_mb,
+        network_download_mb: contribution.network_download_mb,
+        samples_processed: contribution.samples_processed,
+        batches_processed: contribution.batches_processed,
+        compute_score: contribution.compute_score,
+        quality_score: contribution.quality_score.unwrap_or(1.0),
+        proof: None,
+        dataset_manifest_hash: contribution
+            .dataset_manifest_hash
+            .as_deref()
+            .map(|hash| blake3::Hash::from_hex(hash).map(|hash| *hash.as_bytes()))
+            .transpose()
+            .map_err(|e| anyhow!("Invalid dataset manifest hash: {}", e))?,
+    })
+}
+
+/// 指令账户转换为提案/多签中保存的账户列表
+fn transaction_accounts(instruction: &Instruction) -> Vec<TransactionAccountArgs> {
+    instruction
+        .accounts
+        .iter()
+        .map(|meta| TransactionAccountArgs {
+            pubkey: meta.pubkey,
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        })
+        .collect()
+}
+
 // ============ PDA 查找函数 ============
 
 /// 查找全局状态 PDA
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::device::benchmark::SignedDeviceScore;
 use crate::device::types::GpuUsageInfo;
 use super::types::*;
 
@@ -17,12 +18,16 @@ pub struct ComputeTracker {
     node_id: String,
     /// 当前任务 ID
     current_task_id: Option<String>,
+    /// 当前任务的清单哈希
+    current_manifest_hash: Option<String>,
     /// 任务开始时间
     task_start_time: Option<chrono::DateTime<chrono::Utc>>,
     /// 任务开始时的资源使用快照
     start_snapshot: Option<ResourceSnapshot>,
     /// 累计统计
     accumulated_stats: ComputeStats,
+    /// 基准测试得到的校准算力分（参考设备为 1.0）
+    device_score: Option<f64>,
 }
 
 /// 资源使用快照
@@ -42,6 +47,7 @@ impl ComputeTracker {
         Self {
             node_id: node_id.clone(),  // Clone to avoid move
             current_task_id: None,
+            current_manifest_hash: None,
             task_start_time: None,
             start_snapshot: None,
             accumulated_stats: ComputeStats {
@@ -54,9 +60,19 @@ impl ComputeTracker {
                 total_network_mb: 0,
                 contribution_count: 0,
             },
+            device_score: None,
         }
     }
 
+    /// 设置已签名的校准算力分，之后的贡献评分按实测算力加权
+    pub fn set_device_score(&mut self, score: &SignedDeviceScore) -> Result<()> {
+        if !score.verify() {
+            return Err(anyhow!("算力分签名无效"));
+        }
+        self.device_score = Some(score.score.score);
+        Ok(())
+    }
+
     /// 开始一个新的计算任务
     pub fn start_task(&mut self, task_id: String) -> Result<()> {
         if self.current_task_id.is_some() {
@@ -71,6 +87,13 @@ impl ComputeTracker {
         Ok(())
     }
 
+    /// 开始由协调者清单分配的任务，清单哈希会写入贡献记录
+    pub fn start_manifest_task(&mut self, verified: &crate::task_manifest::VerifiedManifest) -> Result<()> {
+        self.start_task(verified.manifest.task_id.clone())?;
+        self.current_manifest_hash = Some(verified.manifest_hash.clone());
+        Ok(())
+    }
+
     /// 完成当前计算任务并生成贡献记录
     pub fn complete_task(
         &mut self,
@@ -144,6 +167,10 @@ impl ComputeTracker {
             samples_processed,
             batches_processed,
             compute_score,
+            manifest_hash: self.current_manifest_hash.take(),
+            dataset_manifest_hash: None,
+            quality_score: None,
+            energy_wh: None,
         };
 
         // 更新累计统计
@@ -166,6 +193,7 @@ impl ComputeTracker {
         }
 
         let task_id = self.current_task_id.take().unwrap();
+        self.current_manifest_hash = None;
         self.task_start_time = None;
         self.start_snapshot = None;
 
@@ -236,11 +264,17 @@ impl ComputeTracker {
         let network_score = (network_mb as f64).ln_1p() / 10.0;
 
         // 加权总分
-        TIME_WEIGHT * time_score +
-        SAMPLE_WEIGHT * sample_score +
-        GPU_WEIGHT * gpu_score +
-        CPU_WEIGHT * cpu_score +
-        NETWORK_WEIGHT * network_score
+        let score = TIME_WEIGHT * time_score +
+            SAMPLE_WEIGHT * sample_score +
+            GPU_WEIGHT * gpu_score +
+            CPU_WEIGHT * cpu_score +
+            NETWORK_WEIGHT * network_score;
+
+        // 同样的使用率在实测算力更高的设备上代表更多计算量
+        match self.device_score {
+            Some(device_score) => score * device_score.clamp(0.1, 10.0),
+            None => score,
+        }
     }
 
     /// 更新累计统计
@@ -281,6 +315,27 @@ impl ComputeCalculator {
         total_reward as u64
     }
 
+    /// 能效倍率：每瓦时样本数相对参考能效的比值，限制在 0.5-1.5，未上报能耗时为 1
+    pub fn energy_efficiency_multiplier(contribution: &ComputeContribution, reference_samples_per_wh: f64) -> f64 {
+        match contribution.energy_wh {
+            Some(energy_wh) if energy_wh > 0.0 && reference_samples_per_wh > 0.0 => {
+                let samples_per_wh = contribution.samples_processed as f64 / energy_wh;
+                (samples_per_wh / reference_samples_per_wh).clamp(0.5, 1.5)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// 按能效调整后的预估收益
+    pub fn calculate_energy_adjusted_reward(
+        contribution: &ComputeContribution,
+        base_reward_per_compute_lamports: u64,
+        reference_samples_per_wh: f64,
+    ) -> u64 {
+        let reward = Self::calculate_reward(contribution, base_reward_per_compute_lamports) as f64;
+        (reward * Self::energy_efficiency_multiplier(contribution, reference_samples_per_wh)) as u64
+    }
+
     /// 批量计算多个贡献的总收益
     pub fn calculate_total_reward(
         contributions: &[ComputeContribution],
@@ -0,0 +1,502 @@
+//! 历史收益导出
+//!
+//! 为运营者生成年度收益报表（供报税/记账使用）：
+//! 1. 从本地索引读取本节点所有密钥（含轮换前的旧密钥）的收益和算力贡献记录
+//! 2. 按收益发放日期查询 SOL/USD 历史价格（价格来源可替换）
+//! 3. 按月分组并汇总，输出 CSV 或 JSON
+//!
+//! 只有已确认/已完成的收益计入收入，待确认和失败的记录不会出现在报表中。
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::indexer::{LocalIndexer, RecordIndexer};
+use super::types::{RewardDistribution, RewardStatus};
+use crate::crypto::identity::NodeIdentity;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// SOL/USD 历史价格来源
+pub trait PriceSource: Send + Sync {
+    /// 指定日期（UTC）的 SOL/USD 价格
+    async fn sol_usd(&self, date: NaiveDate) -> Result<f64>;
+}
+
+/// CoinGecko 历史价格
+pub struct CoinGeckoPriceSource {
+    base_url: String,
+    client: reqwest::Client,
+    cache: RwLock<HashMap<NaiveDate, f64>>,
+}
+
+impl CoinGeckoPriceSource {
+    pub fn new() -> Self {
+        Self::with_base_url("https://api.coingecko.com/api/v3")
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            client: reqwest::Client::new(),
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for CoinGeckoPriceSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PriceSource for CoinGeckoPriceSource {
+    async fn sol_usd(&self, date: NaiveDate) -> Result<f64> {
+        if let Some(price) = self.cache.read().get(&date) {
+            return Ok(*price);
+        }
+        let url = format!(
+            "{}/coins/solana/history?date={}&localization=false",
+            self.base_url,
+            date.format("%d-%m-%Y")
+        );
+        let body: serde_json::Value = self.client.get(&url).send().await?.error_for_status()?.json().await?;
+        let price = body["market_data"]["current_price"]["usd"]
+            .as_f64()
+            .ok_or_else(|| anyhow!("{} 没有 SOL/USD 价格数据", date))?;
+        self.cache.write().insert(date, price);
+        Ok(price)
+    }
+}
+
+/// 从 CSV 文件（`date,price`，日期格式 `YYYY-MM-DD`）读取价格，便于离线或使用会计指定的价格表
+pub struct CsvPriceSource {
+    prices: HashMap<NaiveDate, f64>,
+}
+
+impl CsvPriceSource {
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut prices = HashMap::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((date, price)) = line.split_once(',') else {
+                return Err(anyhow!("价格表第 {} 行格式错误: {}", index + 1, line));
+            };
+            // 跳过表头
+            let Ok(date) = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") else {
+                if index == 0 {
+                    continue;
+                }
+                return Err(anyhow!("价格表第 {} 行日期无效: {}", index + 1, date));
+            };
+            prices.insert(date, price.trim().parse()?);
+        }
+        Ok(Self { prices })
+    }
+}
+
+impl PriceSource for CsvPriceSource {
+    async fn sol_usd(&self, date: NaiveDate) -> Result<f64> {
+        self.prices
+            .get(&date)
+            .copied()
+            .ok_or_else(|| anyhow!("价格表中没有 {} 的价格", date))
+    }
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// 单笔收益
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardLine {
+    pub date: NaiveDate,
+    pub node_id: String,
+    pub task_id: String,
+    pub transaction_signature: String,
+    pub amount_lamports: u64,
+    pub amount_sol: f64,
+    /// 当日 SOL/USD 价格（缺失时为 None）
+    pub sol_usd_price: Option<f64>,
+    pub amount_usd: Option<f64>,
+}
+
+/// 月度汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonthlySummary {
+    /// `YYYY-MM`
+    pub month: String,
+    pub rewards: Vec<RewardLine>,
+    pub total_sol: f64,
+    pub total_usd: f64,
+    pub contribution_count: u32,
+    pub compute_seconds: u64,
+    pub compute_score: f64,
+}
+
+/// 收益报表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarningsReport {
+    pub node_ids: Vec<String>,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub generated_at: i64,
+    pub months: Vec<MonthlySummary>,
+    pub total_sol: f64,
+    pub total_usd: f64,
+    pub contribution_count: u32,
+    pub compute_seconds: u64,
+    /// 缺少价格、未计入美元合计的收益笔数
+    pub missing_prices: usize,
+}
+
+impl EarningsReport {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// 每笔收益一行，每月末附月度合计行，最后一行为总计
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "month,date,type,node_id,task_id,transaction,amount_sol,sol_usd_price,amount_usd,contributions,compute_seconds\n",
+        );
+        for month in &self.months {
+            for line in &month.rewards {
+                out.push_str(&csv_row(&[
+                    month.month.clone(),
+                    line.date.to_string(),
+                    "reward".to_string(),
+                    line.node_id.clone(),
+                    line.task_id.clone(),
+                    line.transaction_signature.clone(),
+                    format!("{:.9}", line.amount_sol),
+                    line.sol_usd_price.map(|p| format!("{:.4}", p)).unwrap_or_default(),
+                    line.amount_usd.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+                    String::new(),
+                    String::new(),
+                ]));
+            }
+            out.push_str(&csv_row(&[
+                month.month.clone(),
+                String::new(),
+                "month_total".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                format!("{:.9}", month.total_sol),
+                String::new(),
+                format!("{:.2}", month.total_usd),
+                month.contribution_count.to_string(),
+                month.compute_seconds.to_string(),
+            ]));
+        }
+        out.push_str(&csv_row(&[
+            format!("{}..{}", self.period_start, self.period_end),
+            String::new(),
+            "total".to_string(),
+            self.node_ids.join(" "),
+            String::new(),
+            String::new(),
+            format!("{:.9}", self.total_sol),
+            String::new(),
+            format!("{:.2}", self.total_usd),
+            self.contribution_count.to_string(),
+            self.compute_seconds.to_string(),
+        ]));
+        out
+    }
+
+    pub fn render(&self, format: ExportFormat) -> Result<String> {
+        match format {
+            ExportFormat::Csv => Ok(self.to_csv()),
+            ExportFormat::Json => self.to_json(),
+        }
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let escaped: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.clone()
+            }
+        })
+        .collect();
+    format!("{}\n", escaped.join(","))
+}
+
+fn timestamp_date(ts: i64) -> NaiveDate {
+    Utc.timestamp_opt(ts, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+        .unwrap_or_default()
+}
+
+fn month_key(date: NaiveDate) -> String {
+    format!("{:04}-{:02}", date.year(), date.month())
+}
+
+/// 本节点的全部密钥 ID（当前 ID 与轮换前的旧 ID）
+pub fn identity_node_ids(identity: &NodeIdentity) -> Vec<String> {
+    let mut ids: Vec<String> = identity.rotations().into_iter().map(|r| r.old_node_id).collect();
+    ids.push(identity.node_id());
+    ids.dedup();
+    ids
+}
+
+/// 收益导出器
+pub struct EarningsExporter<I: RecordIndexer, P: PriceSource> {
+    indexer: I,
+    prices: P,
+}
+
+impl<I: RecordIndexer, P: PriceSource> EarningsExporter<I, P> {
+    pub fn new(indexer: I, prices: P) -> Self {
+        Self { indexer, prices }
+    }
+
+    /// 生成某一年度的报表
+    pub async fn yearly_report(&self, node_ids: &[String], year: i32) -> Result<EarningsReport> {
+        let start = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| anyhow!("无效年份: {}", year))?;
+        let end = NaiveDate::from_ymd_opt(year, 12, 31).ok_or_else(|| anyhow!("无效年份: {}", year))?;
+        self.report(node_ids, start, end).await
+    }
+
+    /// 生成 `[start, end]`（含两端，UTC 日期）区间的报表
+    pub async fn report(&self, node_ids: &[String], start: NaiveDate, end: NaiveDate) -> Result<EarningsReport> {
+        let in_range = |ts: i64| {
+            let date = timestamp_date(ts);
+            date >= start && date <= end
+        };
+
+        let mut months: BTreeMap<String, MonthlySummary> = BTreeMap::new();
+        let mut missing_prices = 0;
+
+        let mut rewards: Vec<RewardDistribution> = self
+            .indexer
+            .rewards(node_ids)?
+            .into_iter()
+            .filter(|r| matches!(r.status, RewardStatus::Confirmed | RewardStatus::Completed))
+            .filter(|r| in_range(r.distributed_at))
+            .collect();
+        rewards.sort_by_key(|r| r.distributed_at);
+        // 同一笔收益可能因重试被记录多次
+        rewards.dedup_by(|a, b| a.id == b.id);
+
+        for reward in rewards {
+            let date = timestamp_date(reward.distributed_at);
+            let amount_sol = reward.amount_lamports as f64 / LAMPORTS_PER_SOL;
+            let sol_usd_price = match self.prices.sol_usd(date).await {
+                Ok(price) => Some(price),
+                Err(e) => {
+                    log::warn!("获取 {} 的 SOL/USD 价格失败: {}", date, e);
+                    missing_prices += 1;
+                    None
+                }
+            };
+            let amount_usd = sol_usd_price.map(|p| amount_sol * p);
+
+            let month = months.entry(month_key(date)).or_insert_with(|| MonthlySummary {
+                month: month_key(date),
+                ..Default::default()
+            });
+            month.total_sol += amount_sol;
+            month.total_usd += amount_usd.unwrap_or(0.0);
+            month.rewards.push(RewardLine {
+                date,
+                node_id: reward.node_id,
+                task_id: reward.task_id,
+                transaction_signature: reward.transaction_signature,
+                amount_lamports: reward.amount_lamports,
+                amount_sol,
+                sol_usd_price,
+                amount_usd,
+            });
+        }
+
+        for contribution in self.indexer.contributions(node_ids)? {
+            if !in_range(contribution.end_timestamp) {
+                continue;
+            }
+            let key = month_key(timestamp_date(contribution.end_timestamp));
+            let month = months.entry(key.clone()).or_insert_with(|| MonthlySummary {
+                month: key,
+                ..Default::default()
+            });
+            month.contribution_count += 1;
+            month.compute_seconds += contribution.duration_seconds;
+            month.compute_score += contribution.compute_score;
+        }
+
+        let months: Vec<MonthlySummary> = months.into_values().collect();
+        Ok(EarningsReport {
+            node_ids: node_ids.to_vec(),
+            period_start: start,
+            period_end: end,
+            generated_at: Utc::now().timestamp(),
+            total_sol: months.iter().map(|m| m.total_sol).sum(),
+            total_usd: months.iter().map(|m| m.total_usd).sum(),
+            contribution_count: months.iter().map(|m| m.contribution_count).sum(),
+            compute_seconds: months.iter().map(|m| m.compute_seconds).sum(),
+            months,
+            missing_prices,
+        })
+    }
+}
+
+/// 命令行可选的价格来源
+pub enum CliPriceSource {
+    CoinGecko(CoinGeckoPriceSource),
+    Csv(CsvPriceSource),
+}
+
+impl PriceSource for CliPriceSource {
+    async fn sol_usd(&self, date: NaiveDate) -> Result<f64> {
+        match self {
+            CliPriceSource::CoinGecko(source) => source.sol_usd(date).await,
+            CliPriceSource::Csv(source) => source.sol_usd(date).await,
+        }
+    }
+}
+
+/// 导出命令
+///
+/// `export-earnings --year 2025 [--format csv|json] [--output 文件] [--records 目录] [--prices 价格表.csv] [--identity 身份文件]`
+pub async fn run_export_command(args: &[String]) -> Result<()> {
+    let mut year = Utc::now().year() - 1;
+    let mut format = ExportFormat::Csv;
+    let mut output: Option<PathBuf> = None;
+    let mut records_dir = LocalIndexer::default_dir();
+    let mut prices_path: Option<PathBuf> = None;
+    let mut identity_path: Option<PathBuf> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).cloned();
+        match (args[i].as_str(), value) {
+            ("--year", Some(v)) => year = v.parse().map_err(|_| anyhow!("无效年份: {}", v))?,
+            ("--format", Some(v)) => {
+                format = match v.as_str() {
+                    "csv" => ExportFormat::Csv,
+                    "json" => ExportFormat::Json,
+                    other => return Err(anyhow!("不支持的导出格式: {}", other)),
+                }
+            }
+            ("--output", Some(v)) => output = Some(PathBuf::from(v)),
+            ("--records", Some(v)) => records_dir = PathBuf::from(v),
+            ("--prices", Some(v)) => prices_path = Some(PathBuf::from(v)),
+            ("--identity", Some(v)) => identity_path = Some(PathBuf::from(v)),
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+
+    let identity = NodeIdentity::load_or_create(identity_path.as_deref())?;
+    let node_ids = identity_node_ids(&identity);
+    let prices = match prices_path {
+        Some(path) => CliPriceSource::Csv(CsvPriceSource::load(&path)?),
+        None => CliPriceSource::CoinGecko(CoinGeckoPriceSource::new()),
+    };
+    let exporter = EarningsExporter::new(LocalIndexer::open(records_dir)?, prices);
+    let report = exporter.yearly_report(&node_ids, year).await?;
+    if report.missing_prices > 0 {
+        eprintln!("警告: {} 笔收益缺少价格，未计入美元合计", report.missing_prices);
+    }
+
+    let rendered = report.render(format)?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            println!("已导出 {} 年收益报表: {}", year, path.display());
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana::types::ComputeContribution;
+
+    struct MemoryIndexer {
+        rewards: Vec<RewardDistribution>,
+        contributions: Vec<ComputeContribution>,
+    }
+
+    impl RecordIndexer for MemoryIndexer {
+        fn contributions(&self, _node_ids: &[String]) -> Result<Vec<ComputeContribution>> {
+            Ok(self.contributions.clone())
+        }
+
+        fn rewards(&self, _node_ids: &[String]) -> Result<Vec<RewardDistribution>> {
+            Ok(self.rewards.clone())
+        }
+    }
+
+    fn reward(id: &str, date: &str, lamports: u64, status: RewardStatus) -> RewardDistribution {
+        let ts = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp();
+        RewardDistribution {
+            id: id.to_string(),
+            node_id: "node".to_string(),
+            task_id: "task".to_string(),
+            amount_lamports: lamports,
+            distributed_at: ts,
+            transaction_signature: format!("sig-{}", id),
+            status,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_groups_by_month_with_usd_totals() {
+        let indexer = MemoryIndexer {
+            rewards: vec![
+                reward("a", "2025-01-10", 1_000_000_000, RewardStatus::Confirmed),
+                reward("b", "2025-01-20", 500_000_000, RewardStatus::Completed),
+                reward("c", "2025-02-01", 2_000_000_000, RewardStatus::Confirmed),
+                reward("d", "2025-02-02", 9_000_000_000, RewardStatus::Failed),
+                reward("e", "2024-12-31", 9_000_000_000, RewardStatus::Confirmed),
+            ],
+            contributions: Vec::new(),
+        };
+        let prices = CsvPriceSource::parse("date,price\n2025-01-10,100\n2025-01-20,200\n2025-02-01,150\n").unwrap();
+        let exporter = EarningsExporter::new(indexer, prices);
+
+        let report = exporter.yearly_report(&["node".to_string()], 2025).await.unwrap();
+        assert_eq!(report.months.len(), 2);
+        assert_eq!(report.months[0].month, "2025-01");
+        assert!((report.months[0].total_usd - 200.0).abs() < 1e-9);
+        assert!((report.total_sol - 3.5).abs() < 1e-9);
+        assert!((report.total_usd - 500.0).abs() < 1e-9);
+        assert_eq!(report.missing_prices, 0);
+
+        let csv = report.to_csv();
+        assert_eq!(csv.lines().count(), 1 + 3 + 2 + 1);
+        assert!(csv.contains("month_total"));
+    }
+}
@@ -0,0 +1,400 @@
+//! Anchor IDL 解码
+//!
+//! 读取 `anchor build` 生成的 IDL（`target/idl/<program>.json`，0.30 及以后的格式），
+//! 按 IDL 中的判别符识别账户和事件，把 Borsh 数据解码成 JSON：
+//! 公钥编码为 base58，`u8` 数组和 `bytes` 编码为 hex，`u128`/`i128` 编码为字符串，
+//! 无字段的枚举变体编码为变体名，带字段的编码为 `{ 变体名: 字段 }`。
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 嵌套类型的最大深度，防止畸形 IDL 导致无限递归
+const MAX_DEPTH: usize = 32;
+
+/// 日志中事件数据的前缀
+const EVENT_LOG_PREFIX: &str = "Program data: ";
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlMetadata {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlDiscriminated {
+    name: String,
+    discriminator: [u8; 8],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IdlDefined {
+    Name(String),
+    Object { name: String },
+}
+
+impl IdlDefined {
+    fn name(&self) -> &str {
+        match self {
+            IdlDefined::Name(name) | IdlDefined::Object { name } => name,
+        }
+    }
+}
+
+/// 字段类型
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IdlType {
+    Primitive(String),
+    Option { option: Box<IdlType> },
+    Vec { vec: Box<IdlType> },
+    Array { array: (Box<IdlType>, usize) },
+    Defined { defined: IdlDefined },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlField {
+    name: String,
+    #[serde(rename = "type")]
+    ty: IdlType,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IdlFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+impl Default for IdlFields {
+    fn default() -> Self {
+        IdlFields::Named(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlVariant {
+    name: String,
+    #[serde(default)]
+    fields: Option<IdlFields>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum IdlTypeDefBody {
+    Struct {
+        #[serde(default)]
+        fields: IdlFields,
+    },
+    Enum {
+        variants: Vec<IdlVariant>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlTypeDef {
+    name: String,
+    #[serde(rename = "type")]
+    body: IdlTypeDefBody,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlFile {
+    #[serde(default)]
+    address: String,
+    metadata: IdlMetadata,
+    #[serde(default)]
+    accounts: Vec<IdlDiscriminated>,
+    #[serde(default)]
+    events: Vec<IdlDiscriminated>,
+    #[serde(default)]
+    types: Vec<IdlTypeDef>,
+}
+
+/// 解码结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    /// 账户或事件类型名
+    pub name: String,
+    pub value: Value,
+}
+
+/// 已加载的程序 IDL
+#[derive(Debug, Clone)]
+pub struct Idl {
+    /// IDL 中记录的程序地址
+    pub address: String,
+    /// 程序名，例如 `node_management`
+    pub name: String,
+    accounts: Vec<IdlDiscriminated>,
+    events: Vec<IdlDiscriminated>,
+    types: HashMap<String, IdlTypeDefBody>,
+}
+
+impl Idl {
+    pub fn parse(json: &str) -> Result<Self> {
+        let file: IdlFile = serde_json::from_str(json)?;
+        Ok(Self {
+            address: file.address,
+            name: file.metadata.name,
+            accounts: file.accounts,
+            events: file.events,
+            types: file.types.into_iter().map(|t| (t.name, t.body)).collect(),
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        Self::parse(&std::fs::read_to_string(path)?).map_err(|e| anyhow!("无法解析 IDL {}: {}", path.display(), e))
+    }
+
+    /// 解码账户数据；判别符不属于 IDL 中任何账户时返回 None
+    pub fn decode_account(&self, data: &[u8]) -> Result<Option<Decoded>> {
+        self.decode_with(&self.accounts, data)
+    }
+
+    /// 解码事件数据（日志中 `Program data:` 之后的内容，已 base64 解码）
+    pub fn decode_event(&self, data: &[u8]) -> Result<Option<Decoded>> {
+        self.decode_with(&self.events, data)
+    }
+
+    fn decode_with(&self, entries: &[IdlDiscriminated], data: &[u8]) -> Result<Option<Decoded>> {
+        let Some(discriminator) = data.get(..8) else {
+            return Ok(None);
+        };
+        let Some(entry) = entries.iter().find(|e| e.discriminator == discriminator) else {
+            return Ok(None);
+        };
+        // 账户尾部可能有未使用的空间，只要求按布局读完字段
+        let mut reader = Reader(&data[8..]);
+        let value = self.decode_defined(&entry.name, &mut reader, 0)?;
+        Ok(Some(Decoded {
+            name: entry.name.clone(),
+            value,
+        }))
+    }
+
+    fn decode_defined(&self, name: &str, reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+        let body = self.types.get(name).ok_or_else(|| anyhow!("IDL 中没有类型 {}", name))?;
+        match body {
+            IdlTypeDefBody::Struct { fields } => self.decode_fields(fields, reader, depth + 1),
+            IdlTypeDefBody::Enum { variants } => {
+                let index = reader.take(1)?[0] as usize;
+                let variant = variants
+                    .get(index)
+                    .ok_or_else(|| anyhow!("枚举 {} 没有第 {} 个变体", name, index))?;
+                match &variant.fields {
+                    None => Ok(Value::String(variant.name.clone())),
+                    Some(fields) => {
+                        let mut object = Map::new();
+                        object.insert(variant.name.clone(), self.decode_fields(fields, reader, depth + 1)?);
+                        Ok(Value::Object(object))
+                    }
+                }
+            }
+        }
+    }
+
+    fn decode_fields(&self, fields: &IdlFields, reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+        match fields {
+            IdlFields::Named(fields) => {
+                let mut object = Map::new();
+                for field in fields {
+                    object.insert(field.name.clone(), self.decode_type(&field.ty, reader, depth)?);
+                }
+                Ok(Value::Object(object))
+            }
+            IdlFields::Tuple(types) => types
+                .iter()
+                .map(|ty| self.decode_type(ty, reader, depth))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array),
+        }
+    }
+
+    fn decode_type(&self, ty: &IdlType, reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(anyhow!("IDL 类型嵌套过深"));
+        }
+        Ok(match ty {
+            IdlType::Primitive(name) => decode_primitive(name, reader)?,
+            IdlType::Option { option } => match reader.take(1)?[0] {
+                0 => Value::Null,
+                _ => self.decode_type(option, reader, depth + 1)?,
+            },
+            IdlType::Vec { vec } => {
+                let len = reader.len_prefix()?;
+                self.decode_sequence(vec, len, reader, depth)?
+            }
+            IdlType::Array { array: (item, len) } => self.decode_sequence(item, *len, reader, depth)?,
+            IdlType::Defined { defined } => self.decode_defined(defined.name(), reader, depth + 1)?,
+        })
+    }
+
+    fn decode_sequence(&self, item: &IdlType, len: usize, reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+        if matches!(item, IdlType::Primitive(name) if name == "u8") {
+            return Ok(Value::String(hex::encode(reader.take(len)?)));
+        }
+        // 每个元素至少占一个字节，长度超过剩余数据必然是畸形数据
+        if len > reader.0.len() {
+            return Err(anyhow!("序列长度 {} 超出剩余数据", len));
+        }
+        (0..len)
+            .map(|_| self.decode_type(item, reader, depth + 1))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array)
+    }
+}
+
+/// 顺序读取 Borsh 数据
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(anyhow!("数据不足：需要 {} 字节，剩余 {}", n, self.0.len()));
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("长度已检查"))
+    }
+
+    fn len_prefix(&mut self) -> Result<usize> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+}
+
+fn decode_primitive(name: &str, reader: &mut Reader<'_>) -> Result<Value> {
+    Ok(match name {
+        "bool" => Value::Bool(reader.take(1)?[0] != 0),
+        "u8" => reader.take(1)?[0].into(),
+        "i8" => (reader.take(1)?[0] as i8).into(),
+        "u16" => u16::from_le_bytes(reader.array()?).into(),
+        "i16" => i16::from_le_bytes(reader.array()?).into(),
+        "u32" => u32::from_le_bytes(reader.array()?).into(),
+        "i32" => i32::from_le_bytes(reader.array()?).into(),
+        "u64" => u64::from_le_bytes(reader.array()?).into(),
+        "i64" => i64::from_le_bytes(reader.array()?).into(),
+        "u128" => u128::from_le_bytes(reader.array()?).to_string().into(),
+        "i128" => i128::from_le_bytes(reader.array()?).to_string().into(),
+        "f32" => serde_json::Number::from_f64(f32::from_le_bytes(reader.array()?) as f64).map_or(Value::Null, Value::Number),
+        "f64" => serde_json::Number::from_f64(f64::from_le_bytes(reader.array()?)).map_or(Value::Null, Value::Number),
+        "string" => {
+            let len = reader.len_prefix()?;
+            String::from_utf8(reader.take(len)?.to_vec())?.into()
+        }
+        "bytes" => {
+            let len = reader.len_prefix()?;
+            hex::encode(reader.take(len)?).into()
+        }
+        "pubkey" | "publicKey" => bs58::encode(reader.take(32)?).into_string().into(),
+        other => return Err(anyhow!("不支持的 IDL 类型 {}", other)),
+    })
+}
+
+/// 从交易日志中取出由 `program_id` 自身发出的事件数据
+///
+/// 日志包含交易中所有程序的输出，按 `invoke` / `success` / `failed` 跟踪当前执行的程序，
+/// 只保留该程序栈顶时打印的 `Program data:`，避免把 CPI 调用方或被调用方的事件算到它头上。
+pub fn program_events(logs: &[String], program_id: &str) -> Vec<Vec<u8>> {
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        if let Some(data) = line.strip_prefix(EVENT_LOG_PREFIX) {
+            if stack.last() == Some(&program_id) {
+                match base64::engine::general_purpose::STANDARD.decode(data.trim()) {
+                    Ok(bytes) => events.push(bytes),
+                    Err(e) => log::debug!("跳过无法解码的事件日志: {}", e),
+                }
+            }
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        let mut parts = rest.split_whitespace();
+        let (Some(id), Some(action)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        match action {
+            "invoke" => stack.push(id),
+            "success" | "failed:" if stack.last() == Some(&id) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDL: &str = r#"{
+        "address": "Node111",
+        "metadata": { "name": "node_management", "version": "0.1.0", "spec": "0.1.0" },
+        "accounts": [{ "name": "Sample", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+        "events": [],
+        "types": [
+            { "name": "Sample", "type": { "kind": "struct", "fields": [
+                { "name": "owner", "type": "pubkey" },
+                { "name": "name", "type": "string" },
+                { "name": "status", "type": { "defined": { "name": "Status" } } },
+                { "name": "hash", "type": { "option": { "array": ["u8", 2] } } },
+                { "name": "scores", "type": { "vec": "u32" } }
+            ] } },
+            { "name": "Status", "type": { "kind": "enum", "variants": [
+                { "name": "Active" },
+                { "name": "Slashed", "fields": ["u64"] }
+            ] } }
+        ]
+    }"#;
+
+    #[test]
+    fn test_decode_account_from_idl() {
+        let idl = Idl::parse(IDL).unwrap();
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&[9; 32]);
+        data.extend_from_slice(&[2, 0, 0, 0, b'n', b'1']);
+        data.extend_from_slice(&[1, 5, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(&[1, 0xab, 0xcd]);
+        data.extend_from_slice(&[1, 0, 0, 0, 7, 0, 0, 0]);
+        data.extend_from_slice(&[0; 16]);
+
+        let decoded = idl.decode_account(&data).unwrap().unwrap();
+        assert_eq!(decoded.name, "Sample");
+        assert_eq!(decoded.value["owner"], bs58::encode([9u8; 32]).into_string());
+        assert_eq!(decoded.value["name"], "n1");
+        assert_eq!(decoded.value["status"]["Slashed"][0], 5);
+        assert_eq!(decoded.value["hash"], "abcd");
+        assert_eq!(decoded.value["scores"][0], 7);
+
+        // 未知判别符不是错误，截断的数据是
+        assert!(idl.decode_account(&[0; 16]).unwrap().is_none());
+        assert!(idl.decode_account(&data[..20]).is_err());
+    }
+
+    #[test]
+    fn test_program_events_follow_invoke_stack() {
+        let logs = [
+            "Program Node111 invoke [1]",
+            "Program data: AQI=",
+            "Program Zk111 invoke [2]",
+            "Program data: AwQ=",
+            "Program Zk111 success",
+            "Program data: BQY=",
+            "Program Node111 success",
+        ]
+        .map(String::from);
+        assert_eq!(program_events(&logs, "Node111"), vec![vec![1, 2], vec![5, 6]]);
+        assert_eq!(program_events(&logs, "Zk111"), vec![vec![3, 4]]);
+    }
+}
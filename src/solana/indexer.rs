@@ -0,0 +1,113 @@
+//! 本地记录索引
+//!
+//! 把本节点上报的算力贡献和收到的收益按行追加到 NDJSON 文件，
+//! 供收益报表导出等离线查询使用，不依赖链上历史查询。
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use super::types::{ComputeContribution, RewardDistribution};
+
+const CONTRIBUTIONS_FILE: &str = "contributions.ndjson";
+const REWARDS_FILE: &str = "rewards.ndjson";
+
+/// 历史记录查询接口
+pub trait RecordIndexer: Send + Sync {
+    /// 指定节点的全部算力贡献记录
+    fn contributions(&self, node_ids: &[String]) -> Result<Vec<ComputeContribution>>;
+
+    /// 指定节点的全部收益记录
+    fn rewards(&self, node_ids: &[String]) -> Result<Vec<RewardDistribution>>;
+}
+
+/// 基于本地 NDJSON 文件的索引
+pub struct LocalIndexer {
+    dir: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl LocalIndexer {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// 默认目录：身份文件所在目录下的 `records/`
+    pub fn default_dir() -> PathBuf {
+        let identity_path = crate::crypto::identity::default_identity_path();
+        identity_path
+            .parent()
+            .map(|dir| dir.join("records"))
+            .unwrap_or_else(|| PathBuf::from("records"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn record_contribution(&self, contribution: &ComputeContribution) -> Result<()> {
+        self.append(CONTRIBUTIONS_FILE, contribution)
+    }
+
+    pub fn record_reward(&self, reward: &RewardDistribution) -> Result<()> {
+        self.append(REWARDS_FILE, reward)
+    }
+
+    fn append<T: Serialize>(&self, file: &str, record: &T) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let mut out = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(file))?;
+        writeln!(out, "{}", serde_json::to_string(record)?)?;
+        Ok(())
+    }
+
+    fn read_all<T: DeserializeOwned>(&self, file: &str) -> Result<Vec<T>> {
+        let path = self.dir.join(file);
+        let reader = match std::fs::File::open(&path) {
+            Ok(f) => BufReader::new(f),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut records = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // 写入中断可能留下半行，跳过而不是让整个导出失败
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => log::warn!("跳过损坏的记录 {}:{}: {}", path.display(), index + 1, e),
+            }
+        }
+        Ok(records)
+    }
+}
+
+impl RecordIndexer for LocalIndexer {
+    fn contributions(&self, node_ids: &[String]) -> Result<Vec<ComputeContribution>> {
+        Ok(self
+            .read_all::<ComputeContribution>(CONTRIBUTIONS_FILE)?
+            .into_iter()
+            .filter(|c| node_ids.contains(&c.node_id))
+            .collect())
+    }
+
+    fn rewards(&self, node_ids: &[String]) -> Result<Vec<RewardDistribution>> {
+        Ok(self
+            .read_all::<RewardDistribution>(REWARDS_FILE)?
+            .into_iter()
+            .filter(|r| node_ids.contains(&r.node_id))
+            .collect())
+    }
+}
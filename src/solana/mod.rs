@@ -9,6 +9,9 @@
 use anyhow::{anyhow, Result};
 use std::sync::Arc;
 use parking_lot::RwLock;
+use zeroize::Zeroizing;
+
+use crate::crypto::vault::KeyVault;
 
 // 子模块
 pub mod client;
@@ -17,6 +20,18 @@ pub mod compute;
 pub mod rewards;
 pub mod accounts;
 pub mod instruction;
+pub mod indexer;
+pub mod export;
+pub mod programs;
+pub mod submitter;
+pub mod oracle;
+pub mod idl;
+pub mod chain_indexer;
+pub mod rpc_pool;
+pub mod wallet;
+pub mod tx_pipeline;
+pub mod payout_verifier;
+pub mod challenger;
 
 // 重新导出常用类型
 pub use client::*;
@@ -25,18 +40,41 @@ pub use compute::*;
 pub use rewards::*;
 pub use accounts::*;
 pub use instruction::*;
+pub use indexer::{LocalIndexer, RecordIndexer};
+pub use export::{EarningsExporter, EarningsReport, ExportFormat, PriceSource};
+pub use programs::ProgramIds;
+pub use submitter::{ContributionOutbox, ContributionSubmitter, SubmitterConfig};
+pub use oracle::{EpochMetrics, MetricReport, OracleReporter};
+pub use idl::Idl;
+pub use chain_indexer::{ChainIndexer, IndexedProgram};
+pub use rpc_pool::{EndpointStatus, RpcEndpointConfig, RpcPool};
+pub use wallet::{KeypairSigner, RemoteSigner, WalletSigner};
+pub use tx_pipeline::{LandingStats, PipelineConfig, TxPipeline};
+pub use payout_verifier::PayoutVerifier;
+pub use challenger::ChallengeSubmitter;
+#[cfg(feature = "ledger")]
+pub use wallet::LedgerSigner;
 
 /// Solana 配置
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SolanaConfig {
-    /// RPC 端点 URL
+    /// RPC 端点 URL（最高优先级）
     pub rpc_url: String,
+    /// 主端点每秒请求上限，None 表示不限
+    #[serde(default)]
+    pub rpc_requests_per_second: Option<f64>,
+    /// 备用 RPC 端点，按优先级排列，主端点故障或超出限额时依次切换
+    #[serde(default)]
+    pub fallback_rpc_endpoints: Vec<RpcEndpointConfig>,
     /// WebSocket 端点 URL（可选）
     pub ws_url: Option<String>,
     /// 程序 ID（智能合约地址）
     pub program_id: String,
-    /// 支付者的私钥（base58 编码）
+    /// 支付者的私钥（base58 编码，明文，已弃用：请迁移到密钥库）
     pub payer_keypair_base58: Option<String>,
+    /// 支付者私钥在密钥库中的条目名
+    #[serde(default)]
+    pub payer_vault_entry: Option<String>,
     /// 网络环境
     pub network: SolanaNetwork,
 }
@@ -55,13 +93,20 @@ pub enum SolanaNetwork {
 }
 
 impl SolanaConfig {
+    /// 默认的支付者密钥库条目名
+    pub const DEFAULT_PAYER_ENTRY: &'static str = "solana-payer";
+
     /// 创建默认配置（使用 devnet）
     pub fn devnet(program_id: &str) -> Self {
         Self {
             rpc_url: "https://api.devnet.solana.com".to_string(),
+            // 公共端点限额为每 10 秒 100 次
+            rpc_requests_per_second: Some(10.0),
+            fallback_rpc_endpoints: Vec::new(),
             ws_url: Some("wss://api.devnet.solana.com".to_string()),
             program_id: program_id.to_string(),
             payer_keypair_base58: None,
+            payer_vault_entry: None,
             network: SolanaNetwork::Devnet,
         }
     }
@@ -70,9 +115,12 @@ impl SolanaConfig {
     pub fn localnet(program_id: &str) -> Self {
         Self {
             rpc_url: "http://localhost:8899".to_string(),
+            rpc_requests_per_second: None,
+            fallback_rpc_endpoints: Vec::new(),
             ws_url: Some("ws://localhost:8900".to_string()),
             program_id: program_id.to_string(),
             payer_keypair_base58: None,
+            payer_vault_entry: None,
             network: SolanaNetwork::Localnet,
         }
     }
@@ -81,12 +129,62 @@ impl SolanaConfig {
     pub fn mainnet(program_id: &str) -> Self {
         Self {
             rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+            rpc_requests_per_second: Some(10.0),
+            fallback_rpc_endpoints: Vec::new(),
             ws_url: Some("wss://api.mainnet-beta.solana.com".to_string()),
             program_id: program_id.to_string(),
             payer_keypair_base58: None,
+            payer_vault_entry: None,
             network: SolanaNetwork::Mainnet,
         }
     }
+
+    /// 按优先级排列的全部 RPC 端点
+    pub fn rpc_endpoints(&self) -> Vec<RpcEndpointConfig> {
+        let primary = RpcEndpointConfig {
+            url: self.rpc_url.clone(),
+            requests_per_second: self.rpc_requests_per_second,
+        };
+        std::iter::once(primary)
+            .chain(self.fallback_rpc_endpoints.iter().cloned())
+            .collect()
+    }
+
+    /// 读取支付者私钥（base58）：优先密钥库条目，其次明文配置
+    pub fn payer_secret(&self, vault: Option<&dyn KeyVault>) -> Result<Option<Zeroizing<String>>> {
+        if let Some(entry) = &self.payer_vault_entry {
+            let vault = vault.ok_or_else(|| anyhow!("支付者私钥保存在密钥库条目 {} 中，但未配置密钥库", entry))?;
+            let secret = vault
+                .load(entry)?
+                .ok_or_else(|| anyhow!("密钥库中没有支付者私钥条目 {}", entry))?;
+            let secret = String::from_utf8(secret.to_vec()).map_err(|_| anyhow!("支付者私钥条目 {} 不是有效的 base58", entry))?;
+            return Ok(Some(Zeroizing::new(secret)));
+        }
+        if self.payer_keypair_base58.is_some() {
+            if self.network == SolanaNetwork::Mainnet {
+                return Err(anyhow!("主网不允许在配置中保存明文支付者私钥，请迁移到密钥库或使用硬件钱包/远程签名"));
+            }
+            log::warn!("Solana 支付者私钥以明文保存在配置中，建议迁移到密钥库");
+        }
+        Ok(self.payer_keypair_base58.clone().map(Zeroizing::new))
+    }
+
+    /// 把明文支付者私钥迁入密钥库并从配置中清除，调用方需随后保存配置；返回是否发生迁移
+    pub fn migrate_payer_to_vault(&mut self, vault: &dyn KeyVault) -> Result<bool> {
+        let Some(secret) = self.payer_keypair_base58.take().map(Zeroizing::new) else {
+            return Ok(false);
+        };
+        let entry = self
+            .payer_vault_entry
+            .get_or_insert_with(|| Self::DEFAULT_PAYER_ENTRY.to_string())
+            .clone();
+        if let Err(e) = vault.store(&entry, secret.as_bytes()) {
+            self.payer_keypair_base58 = Some(secret.to_string());
+            return Err(e);
+        }
+        log::info!("Solana 支付者私钥已迁移到 {} 密钥库条目 {}", vault.backend(), entry);
+        Ok(true)
+    }
 }
 
 impl Default for SolanaConfig {
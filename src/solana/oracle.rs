@@ -0,0 +1,286 @@
+//! 链下指标预言机上报
+//!
+//! Workers 协调者按纪元汇总各节点的算力评分和在线率（`GET {metrics_url}/api/metrics/epochs/{epoch}`），
+//! 上报节点以支付者密钥签名，把汇总写入 oracle 合约（支付者须在合约的上报者白名单中）。
+//! 不少于阈值个上报者的值在容差内一致后合约定值，之后任何人都可以把节点算力导入
+//! contribution-tracking；导入须在纪元关闭前完成，因此上报在纪元结束后尽快进行。
+
+use anyhow::{anyhow, Result};
+use borsh::BorshDeserialize;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::client::SolanaClient;
+use super::programs::{contribution_epoch, MetricKind};
+
+/// 算力评分的定点缩放倍数，需与合约中的 `VALUE_SCALE` 一致
+pub const VALUE_SCALE: u64 = 1_000_000;
+/// 在线率的满值（万分比）
+pub const MAX_UPTIME_BPS: u64 = 10_000;
+
+/// 协调者汇总的单个节点纪元指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeEpochMetrics {
+    /// 节点 ID（钱包公钥 base58）
+    pub node_id: String,
+    pub compute_score: f64,
+    /// 在线率（0-1）
+    pub uptime: f64,
+}
+
+/// 协调者汇总的纪元指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochMetrics {
+    pub epoch: u64,
+    pub total_compute_score: f64,
+    pub nodes: Vec<NodeEpochMetrics>,
+}
+
+/// 一项待上报的指标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricReport {
+    pub kind: MetricKind,
+    pub epoch: u64,
+    /// 指标所属节点，纪元总量为默认公钥
+    pub subject: Pubkey,
+    pub value: u64,
+}
+
+/// 算力评分转为链上定点值
+pub fn scale_compute_score(score: f64) -> u64 {
+    (score.max(0.0) * VALUE_SCALE as f64).round() as u64
+}
+
+/// 在线率转为万分比
+pub fn uptime_bps(uptime: f64) -> u64 {
+    (uptime.clamp(0.0, 1.0) * MAX_UPTIME_BPS as f64).round() as u64
+}
+
+impl EpochMetrics {
+    /// 转为链上指标；节点 ID 不是合法公钥的节点无法上链，直接跳过
+    pub fn reports(&self) -> Vec<MetricReport> {
+        let mut reports = vec![MetricReport {
+            kind: MetricKind::EpochComputeScore,
+            epoch: self.epoch,
+            subject: Pubkey::default(),
+            value: scale_compute_score(self.total_compute_score),
+        }];
+        for node in &self.nodes {
+            let Ok(subject) = node.node_id.parse::<Pubkey>() else {
+                log::debug!("[预言机] 跳过无效节点 ID: {}", node.node_id);
+                continue;
+            };
+            reports.push(MetricReport {
+                kind: MetricKind::NodeComputeScore,
+                epoch: self.epoch,
+                subject,
+                value: scale_compute_score(node.compute_score),
+            });
+            reports.push(MetricReport {
+                kind: MetricKind::NodeUptime,
+                epoch: self.epoch,
+                subject,
+                value: uptime_bps(node.uptime),
+            });
+        }
+        reports
+    }
+}
+
+/// 链上的单个上报
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize)]
+pub struct MetricSubmission {
+    pub reporter: Pubkey,
+    pub value: u64,
+}
+
+/// 链上的指标汇总账户（与合约中的 `MetricAggregate` 布局一致）
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct MetricAggregate {
+    pub kind: MetricKind,
+    pub epoch: u64,
+    pub subject: Pubkey,
+    pub submissions: Vec<MetricSubmission>,
+    pub finalized: bool,
+    pub value: u64,
+    pub finalized_at: i64,
+    pub bump: u8,
+}
+
+impl MetricAggregate {
+    /// 解析账户数据（跳过 8 字节 Anchor 账户判别符，忽略尾部未用空间）
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut body = data.get(8..).ok_or_else(|| anyhow!("指标汇总账户数据过短"))?;
+        Ok(Self::deserialize(&mut body)?)
+    }
+
+    pub fn has_report_from(&self, reporter: &Pubkey) -> bool {
+        self.submissions.iter().any(|s| &s.reporter == reporter)
+    }
+}
+
+/// 一轮上报的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportSummary {
+    pub submitted: usize,
+    /// 已定值或本节点已上报过
+    pub skipped: usize,
+    pub failed: usize,
+    /// 导入 contribution-tracking 的节点数
+    pub imported: usize,
+}
+
+/// 预言机上报客户端
+pub struct OracleReporter {
+    client: Arc<SolanaClient>,
+    http: reqwest::Client,
+    metrics_url: String,
+}
+
+impl OracleReporter {
+    /// `metrics_url` 为 Workers 协调者地址，例如 `https://coordinator.example.com`
+    pub fn new(client: Arc<SolanaClient>, metrics_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            http: reqwest::Client::new(),
+            metrics_url: metrics_url.into(),
+        }
+    }
+
+    /// 拉取协调者汇总的纪元指标
+    pub async fn fetch_epoch(&self, epoch: u64) -> Result<EpochMetrics> {
+        let url = format!("{}/api/metrics/epochs/{}", self.metrics_url.trim_end_matches('/'), epoch);
+        let metrics: EpochMetrics = self
+            .http
+            .get(&url)
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if metrics.epoch != epoch {
+            return Err(anyhow!("协调者返回了纪元 {} 的指标，请求的是 {}", metrics.epoch, epoch));
+        }
+        Ok(metrics)
+    }
+
+    /// 上报一个已结束纪元的全部指标，再导入已定值的节点算力
+    pub async fn report_epoch(&self, epoch: u64) -> Result<ReportSummary> {
+        let metrics = self.fetch_epoch(epoch).await?;
+        let reporter = self.client.payer_pubkey();
+        let mut summary = ReportSummary::default();
+
+        for report in metrics.reports() {
+            let existing = self.client.get_oracle_metric(report.kind, epoch, &report.subject).await?;
+            if existing.is_some_and(|a| a.finalized || a.has_report_from(&reporter)) {
+                summary.skipped += 1;
+                continue;
+            }
+            match self.client.submit_metric_report(&report).await {
+                Ok(result) if result.success => summary.submitted += 1,
+                Ok(result) => {
+                    summary.failed += 1;
+                    log::warn!("[预言机] 上报 {:?} {} 失败: {:?}", report.kind, report.subject, result.error);
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    log::warn!("[预言机] 上报 {:?} {} 失败: {}", report.kind, report.subject, e);
+                }
+            }
+        }
+
+        summary.imported = self.import_finalized(&metrics).await?;
+        Ok(summary)
+    }
+
+    /// 把已定值但尚未导入的节点算力导入 contribution-tracking，返回导入的节点数
+    pub async fn import_finalized(&self, metrics: &EpochMetrics) -> Result<usize> {
+        let mut imported = 0;
+        for node in &metrics.nodes {
+            let Ok(node_id) = node.node_id.parse::<Pubkey>() else {
+                continue;
+            };
+            let finalized = |a: &Option<MetricAggregate>| a.as_ref().is_some_and(|a| a.finalized);
+            let compute = self.client.get_oracle_metric(MetricKind::NodeComputeScore, metrics.epoch, &node_id).await?;
+            if !finalized(&compute) || self.client.oracle_compute_imported(metrics.epoch, &node_id).await? {
+                continue;
+            }
+            let uptime = self.client.get_oracle_metric(MetricKind::NodeUptime, metrics.epoch, &node_id).await?;
+            match self.client.import_oracle_compute(metrics.epoch, &node_id, finalized(&uptime)).await {
+                Ok(result) if result.success => imported += 1,
+                Ok(result) => log::warn!("[预言机] 导入节点 {} 的算力失败: {:?}", node_id, result.error),
+                Err(e) => log::warn!("[预言机] 导入节点 {} 的算力失败: {}", node_id, e),
+            }
+        }
+        Ok(imported)
+    }
+
+    /// 启动后台任务：每隔 `interval` 上报刚结束的纪元
+    pub fn spawn(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let reporter = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let epoch = contribution_epoch(Utc::now().timestamp()).saturating_sub(1);
+                match reporter.report_epoch(epoch).await {
+                    Ok(summary) => log::info!("[预言机] 纪元 {} 上报完成: {:?}", epoch, summary),
+                    Err(e) => log::warn!("[预言机] 纪元 {} 上报失败: {}", epoch, e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_reports_scale_values_and_decode_aggregate() {
+        let node = Pubkey::new_unique();
+        let metrics = EpochMetrics {
+            epoch: 7,
+            total_compute_score: 1.5,
+            nodes: vec![
+                NodeEpochMetrics {
+                    node_id: node.to_string(),
+                    compute_score: 1.25,
+                    uptime: 1.2,
+                },
+                NodeEpochMetrics {
+                    node_id: "browser-node".to_string(),
+                    compute_score: 0.25,
+                    uptime: 0.5,
+                },
+            ],
+        };
+        let reports = metrics.reports();
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0].value, 1_500_000);
+        assert_eq!(reports[1].subject, node);
+        assert_eq!(reports[1].value, 1_250_000);
+        assert_eq!(reports[2].value, MAX_UPTIME_BPS);
+
+        // 判别符 + 汇总数据 + 尾部未用空间
+        let mut data = vec![0u8; 8];
+        MetricKind::NodeComputeScore.serialize(&mut data).unwrap();
+        7u64.serialize(&mut data).unwrap();
+        node.serialize(&mut data).unwrap();
+        vec![(node, 1_250_000u64)].serialize(&mut data).unwrap();
+        true.serialize(&mut data).unwrap();
+        1_250_000u64.serialize(&mut data).unwrap();
+        100i64.serialize(&mut data).unwrap();
+        255u8.serialize(&mut data).unwrap();
+        data.extend_from_slice(&[0; 64]);
+
+        let aggregate = MetricAggregate::decode(&data).unwrap();
+        assert!(aggregate.finalized);
+        assert!(aggregate.has_report_from(&node));
+        assert!(!aggregate.has_report_from(&Pubkey::new_unique()));
+    }
+}
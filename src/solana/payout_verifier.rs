@@ -0,0 +1,398 @@
+//! 收益到账的轻客户端校验
+//!
+//! 节点不再直接相信单个 RPC 的响应。收益交易分别向每个健康端点查询，每份响应在本地独立核对：
+//! - 交易的全部 ed25519 签名在本地校验，且首个签名就是被查询的交易签名
+//! - 交易所在 slot 已最终确认，按签名列表取回的区块包含该交易（包含性证明）
+//! - 钱包在交易前后的余额变化（lamports 或奖励代币）与预期金额一致；未给出预期金额时
+//!   取收益合约 `RewardClaimed` 事件中的金额
+//!
+//! 达到法定数量的端点给出完全一致（含区块哈希）的观察才会得出结论，结论写入统计数据库供桌面端展示；
+//! 与多数矛盾的端点被暂停使用，到账不符的分配交易计入费用支付者的信誉。
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcBlockConfig, RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, hash::hash, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{TransactionDetails, UiTransactionEncoding, UiTransactionTokenBalance};
+use std::sync::Arc;
+
+use super::idl::program_events;
+use super::rpc_pool::RpcPool;
+use crate::consensus::{ReputationEngine, ReputationEvent};
+use crate::stats::{PayoutCheck, PayoutStatus, StatsStore};
+
+/// 默认需要给出一致结果的端点数
+const DEFAULT_QUORUM: usize = 2;
+
+/// 单个端点对交易的观察，端点之间按整体是否相等判断是否一致
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    pub slot: u64,
+    /// 交易所在区块的哈希
+    pub blockhash: String,
+    /// 区块的签名列表包含该交易
+    pub included: bool,
+    /// 全部签名在本地校验通过
+    pub signatures_valid: bool,
+    /// 交易执行成功
+    pub succeeded: bool,
+    /// 费用支付者
+    pub payer: String,
+    /// 钱包实际入账（钱包支付的手续费已加回）
+    pub credited: i64,
+    /// 收益合约 `RewardClaimed` 事件中的金额
+    pub claimed: Option<u64>,
+}
+
+/// 按多数观察得出的结论
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    pub status: PayoutStatus,
+    pub detail: Option<String>,
+    /// 达到法定数量的多数观察
+    pub majority: Option<Observation>,
+    pub agreeing: usize,
+    /// 与多数矛盾的端点
+    pub dissenting: Vec<String>,
+}
+
+/// 汇总各端点的观察：多数观察达到 `quorum` 且没有同样多的对立观察时才下结论
+pub fn evaluate(expected: Option<u64>, observations: &[(String, Observation)], quorum: usize) -> Verdict {
+    let mut groups: Vec<(&Observation, Vec<&str>)> = Vec::new();
+    for (url, observation) in observations {
+        match groups.iter_mut().find(|(seen, _)| *seen == observation) {
+            Some((_, urls)) => urls.push(url),
+            None => groups.push((observation, vec![url])),
+        }
+    }
+    groups.sort_by_key(|(_, urls)| std::cmp::Reverse(urls.len()));
+
+    let agreeing = groups.first().map_or(0, |(_, urls)| urls.len());
+    let tied = groups.get(1).is_some_and(|(_, urls)| urls.len() == agreeing);
+    if agreeing < quorum.max(1) || tied {
+        let status = if groups.len() > 1 {
+            PayoutStatus::Disputed
+        } else {
+            PayoutStatus::Unconfirmed
+        };
+        return Verdict {
+            status,
+            detail: Some(format!(
+                "{} 个端点给出 {} 种结果，最多 {} 个一致，需要 {} 个",
+                observations.len(),
+                groups.len(),
+                agreeing,
+                quorum
+            )),
+            majority: None,
+            agreeing,
+            dissenting: Vec::new(),
+        };
+    }
+
+    let majority = groups[0].0.clone();
+    let dissenting = groups[1..]
+        .iter()
+        .flat_map(|(_, urls)| urls.iter().map(|url| url.to_string()))
+        .collect();
+    let problem = if !majority.signatures_valid {
+        Some("交易签名校验失败".to_string())
+    } else if !majority.included {
+        Some(format!("slot {} 的区块中不包含该交易", majority.slot))
+    } else if !majority.succeeded {
+        Some("交易执行失败".to_string())
+    } else {
+        match expected.or(majority.claimed) {
+            None => Some("交易中没有 RewardClaimed 事件，无法确定应入账金额".to_string()),
+            Some(amount) if majority.credited != amount as i64 => {
+                Some(format!("应入账 {}，实际入账 {}", amount, majority.credited))
+            }
+            Some(_) => None,
+        }
+    };
+    Verdict {
+        status: if problem.is_some() {
+            PayoutStatus::Discrepancy
+        } else {
+            PayoutStatus::Verified
+        },
+        detail: problem,
+        majority: Some(majority),
+        agreeing,
+        dissenting,
+    }
+}
+
+/// Anchor 事件判别符
+fn event_discriminator(name: &str) -> [u8; 8] {
+    let digest = hash(format!("event:{}", name).as_bytes());
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&digest.to_bytes()[..8]);
+    out
+}
+
+/// 日志中 `RewardClaimed` 事件的金额之和（布局：判别符 + node_id + amount）
+fn claimed_amount(logs: &[String], reward_program: &Pubkey) -> Option<u64> {
+    let discriminator = event_discriminator("RewardClaimed");
+    program_events(logs, &reward_program.to_string())
+        .iter()
+        .filter(|data| data.starts_with(&discriminator))
+        .filter_map(|data| <[u8; 8]>::try_from(data.get(40..48)?).ok().map(u64::from_le_bytes))
+        .reduce(|a, b| a.saturating_add(b))
+}
+
+/// 钱包持有的指定代币余额之和
+fn token_balance(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, owner: &str, mint: &str) -> i64 {
+    let OptionSerializer::Some(balances) = balances else {
+        return 0;
+    };
+    balances
+        .iter()
+        .filter(|b| b.mint == mint && matches!(&b.owner, OptionSerializer::Some(o) if o == owner))
+        .filter_map(|b| b.ui_token_amount.amount.parse::<i64>().ok())
+        .sum()
+}
+
+fn invalid(message: impl Into<String>) -> ClientError {
+    ClientErrorKind::Custom(message.into()).into()
+}
+
+/// 向单个端点取回已最终确认的交易和所在区块，在本地核对
+fn observe(
+    rpc: &RpcClient,
+    signature: &Signature,
+    wallet: &Pubkey,
+    reward_mint: Option<&Pubkey>,
+    reward_program: &Pubkey,
+) -> ClientResult<Observation> {
+    let confirmed = rpc.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::finalized()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let meta = confirmed
+        .transaction
+        .meta
+        .ok_or_else(|| invalid("交易缺少执行结果"))?;
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| invalid("无法解码交易"))?;
+    let signatures_valid = transaction.signatures.first() == Some(signature)
+        && transaction.verify_with_results().iter().all(|valid| *valid);
+
+    // 余额数组按静态账户和地址表加载的账户（先可写后只读）依次排列
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        for key in loaded.writable.iter().chain(&loaded.readonly) {
+            keys.push(key.parse().map_err(|e| invalid(format!("无效的地址表账户 {}: {}", key, e)))?);
+        }
+    }
+    let credited = match reward_mint {
+        Some(mint) => {
+            let (owner, mint) = (wallet.to_string(), mint.to_string());
+            token_balance(&meta.post_token_balances, &owner, &mint)
+                - token_balance(&meta.pre_token_balances, &owner, &mint)
+        }
+        None => match keys.iter().position(|key| key == wallet) {
+            Some(index) => {
+                let pre = meta.pre_balances.get(index).copied().unwrap_or_default() as i64;
+                let post = meta.post_balances.get(index).copied().unwrap_or_default() as i64;
+                // 钱包自己领取时手续费由钱包支付
+                let fee = if index == 0 { meta.fee as i64 } else { 0 };
+                post - pre + fee
+            }
+            None => 0,
+        },
+    };
+    let logs: Option<Vec<String>> = meta.log_messages.into();
+
+    let block = rpc.get_block_with_config(
+        confirmed.slot,
+        RpcBlockConfig {
+            encoding: None,
+            transaction_details: Some(TransactionDetails::Signatures),
+            rewards: Some(false),
+            commitment: Some(CommitmentConfig::finalized()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let signature = signature.to_string();
+    Ok(Observation {
+        slot: confirmed.slot,
+        included: block.signatures.is_some_and(|signatures| signatures.contains(&signature)),
+        blockhash: block.blockhash,
+        signatures_valid,
+        succeeded: meta.err.is_none(),
+        payer: keys.first().map(|key| key.to_string()).unwrap_or_default(),
+        credited,
+        claimed: claimed_amount(&logs.unwrap_or_default(), reward_program),
+    })
+}
+
+/// 收益到账校验器
+pub struct PayoutVerifier {
+    rpc: Arc<RpcPool>,
+    reward_program: Pubkey,
+    /// 奖励代币，None 表示以 lamports 发放
+    reward_mint: Option<Pubkey>,
+    quorum: usize,
+    store: Option<Arc<StatsStore>>,
+}
+
+impl PayoutVerifier {
+    /// 默认要求两个端点一致，只配置了一个端点时退化为单端点的本地核对
+    pub fn new(rpc: Arc<RpcPool>, reward_program: Pubkey, reward_mint: Option<Pubkey>) -> Self {
+        Self {
+            quorum: rpc.endpoint_count().min(DEFAULT_QUORUM),
+            rpc,
+            reward_program,
+            reward_mint,
+            store: None,
+        }
+    }
+
+    /// 设置需要给出一致结果的端点数
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.quorum = quorum.max(1);
+        self
+    }
+
+    /// 校验结果写入统计数据库
+    pub fn with_store(mut self, store: Arc<StatsStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// 校验交易是否向 `wallet` 入账 `expected`（None 表示按 `RewardClaimed` 事件的金额）
+    pub async fn verify(&self, signature: &str, wallet: &Pubkey, expected: Option<u64>) -> Result<PayoutCheck> {
+        let parsed: Signature = signature
+            .parse()
+            .map_err(|e| anyhow!("Invalid signature: {}", e))?;
+        let (rpc, owner, mint, program) = (Arc::clone(&self.rpc), *wallet, self.reward_mint, self.reward_program);
+        let responses = tokio::task::spawn_blocking(move || {
+            rpc.call_each(|client| observe(client, &parsed, &owner, mint.as_ref(), &program))
+        })
+        .await?;
+
+        let queried = responses.len();
+        let observations: Vec<(String, Observation)> = responses
+            .into_iter()
+            .filter_map(|(url, result)| match result {
+                Ok(observation) => Some((url, observation)),
+                Err(e) => {
+                    log::debug!("[到账校验] {} 未返回交易 {}: {}", url, signature, e);
+                    None
+                }
+            })
+            .collect();
+        let verdict = evaluate(expected, &observations, self.quorum);
+        for url in &verdict.dissenting {
+            self.rpc.penalize(url);
+        }
+
+        let majority = verdict.majority.as_ref();
+        let check = PayoutCheck {
+            signature: signature.to_string(),
+            wallet: wallet.to_string(),
+            payer: majority.map(|o| o.payer.clone()),
+            slot: majority.map(|o| o.slot),
+            expected_amount: expected.or_else(|| majority.and_then(|o| o.claimed)),
+            credited_amount: majority.map(|o| o.credited),
+            status: verdict.status,
+            detail: verdict.detail,
+            endpoints_agreeing: verdict.agreeing as u32,
+            endpoints_queried: queried as u32,
+            checked_at: Utc::now().timestamp(),
+        };
+        match check.status {
+            PayoutStatus::Verified => log::info!("[到账校验] 交易 {} 已到账", signature),
+            PayoutStatus::Unconfirmed => log::debug!("[到账校验] 交易 {} 尚无法确认", signature),
+            _ => log::warn!(
+                "[到账校验] 交易 {} 异常: {}",
+                signature,
+                check.detail.as_deref().unwrap_or_default()
+            ),
+        }
+        if let Some(store) = &self.store {
+            if let Err(e) = store.record_payout_check(&check) {
+                log::warn!("[到账校验] 写入校验结果失败: {}", e);
+            }
+        }
+        Ok(check)
+    }
+}
+
+/// 把校验结论计入分配者（费用支付者）的信誉：到账相符为验证通过，不符为验证失败；钱包自己领取的交易不计
+pub fn record_reputation(check: &PayoutCheck, engine: &ReputationEngine) {
+    let Some(payer) = check.payer.as_deref().filter(|payer| *payer != check.wallet) else {
+        return;
+    };
+    match check.status {
+        PayoutStatus::Verified => engine.record(payer, ReputationEvent::Verification { passed: true }),
+        PayoutStatus::Discrepancy => engine.record(payer, ReputationEvent::Verification { passed: false }),
+        PayoutStatus::Disputed | PayoutStatus::Unconfirmed => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(blockhash: &str, credited: i64) -> Observation {
+        Observation {
+            slot: 42,
+            blockhash: blockhash.to_string(),
+            included: true,
+            signatures_valid: true,
+            succeeded: true,
+            payer: "distributor".to_string(),
+            credited,
+            claimed: Some(500),
+        }
+    }
+
+    #[test]
+    fn test_majority_verifies_and_flags_dissenting_endpoint() {
+        let observations = vec![
+            ("a".to_string(), observation("hash", 500)),
+            ("b".to_string(), observation("hash", 500)),
+            ("c".to_string(), observation("forged", 500)),
+        ];
+        let verdict = evaluate(None, &observations, 2);
+        assert_eq!(verdict.status, PayoutStatus::Verified);
+        assert_eq!(verdict.agreeing, 2);
+        assert_eq!(verdict.dissenting, vec!["c".to_string()]);
+
+        // 实际入账少于预期
+        let short = vec![
+            ("a".to_string(), observation("hash", 300)),
+            ("b".to_string(), observation("hash", 300)),
+        ];
+        let verdict = evaluate(Some(500), &short, 2);
+        assert_eq!(verdict.status, PayoutStatus::Discrepancy);
+        assert!(verdict.dissenting.is_empty());
+    }
+
+    #[test]
+    fn test_split_or_missing_responses_do_not_conclude() {
+        let split = vec![
+            ("a".to_string(), observation("hash", 500)),
+            ("b".to_string(), observation("forged", 0)),
+        ];
+        let verdict = evaluate(None, &split, 2);
+        assert_eq!(verdict.status, PayoutStatus::Disputed);
+        assert!(verdict.majority.is_none() && verdict.dissenting.is_empty());
+
+        let single = vec![("a".to_string(), observation("hash", 500))];
+        assert_eq!(evaluate(None, &single, 2).status, PayoutStatus::Unconfirmed);
+        assert_eq!(evaluate(None, &[], 1).status, PayoutStatus::Unconfirmed);
+    }
+}
@@ -0,0 +1,1640 @@
+//! 拆分后 Anchor 合约的指令构建
+//!
+//! 为 node-management、contribution-tracking、zk-verifier、reward-management、governance 和 oracle
+//! 六个合约的每条指令提供类型化构建函数和 PDA 推导函数。
+//! 指令数据采用 Anchor 格式：`sha256("global:<指令名>")[..8]` 判别符 + Borsh 编码的参数。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    hash::{hash, hashv},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use super::types::{Location, ModelInfo, NodeStatus, TaskType};
+
+/// 贡献跟踪合约的纪元时长（秒），需与合约中的 `EPOCH_DURATION_SECONDS` 一致
+pub const CONTRIBUTION_EPOCH_SECONDS: i64 = 86_400;
+
+/// SPL Token 程序
+pub const TOKEN_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// 各合约的程序 ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramIds {
+    pub node_management: Pubkey,
+    pub contribution_tracking: Pubkey,
+    pub reward_management: Pubkey,
+    pub governance: Pubkey,
+    pub zk_verifier: Pubkey,
+    pub oracle: Pubkey,
+}
+
+/// 时间戳所在的贡献纪元
+pub fn contribution_epoch(timestamp: i64) -> u64 {
+    (timestamp.max(0) / CONTRIBUTION_EPOCH_SECONDS) as u64
+}
+
+/// 贡献证明第一个公开输入应承诺的值，需与合约中的 `contribution_commitment` 一致
+pub fn contribution_commitment(contribution_id: &str, node_id: &Pubkey, compute_score: f64) -> [u8; 32] {
+    let mut scalar = hashv(&[contribution_id.as_bytes(), node_id.as_ref(), &compute_score.to_le_bytes()]).to_bytes();
+    // 截到 BN254 标量域内
+    scalar[0] &= 0x1f;
+    scalar
+}
+
+/// Anchor 指令判别符
+pub fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let digest = hash(format!("global:{}", name).as_bytes());
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&digest.to_bytes()[..8]);
+    out
+}
+
+/// 按顺序写入指令参数
+struct ArgWriter(Vec<u8>);
+
+impl ArgWriter {
+    fn new(name: &str) -> Self {
+        Self(anchor_discriminator(name).to_vec())
+    }
+
+    fn arg<T: BorshSerialize>(mut self, value: &T) -> Self {
+        value.serialize(&mut self.0).expect("写入内存缓冲不会失败");
+        self
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+fn writable(pubkey: Pubkey) -> AccountMeta {
+    AccountMeta::new(pubkey, false)
+}
+
+fn readonly(pubkey: Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(pubkey, false)
+}
+
+fn signer(pubkey: Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(pubkey, true)
+}
+
+fn payer(pubkey: Pubkey) -> AccountMeta {
+    AccountMeta::new(pubkey, true)
+}
+
+/// Anchor 可选账户：未提供时以被调用程序 ID 占位
+fn optional(pubkey: Option<Pubkey>, program_id: &Pubkey, is_writable: bool) -> AccountMeta {
+    match pubkey {
+        Some(pubkey) if is_writable => writable(pubkey),
+        Some(pubkey) => readonly(pubkey),
+        None => readonly(*program_id),
+    }
+}
+
+// ============ PDA 推导 ============
+
+pub fn node_management_state_pda(ids: &ProgramIds) -> Pubkey {
+    Pubkey::find_program_address(&[b"node-management-state"], &ids.node_management).0
+}
+
+pub fn node_pda(ids: &ProgramIds, node_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"node", node_id.as_ref()], &ids.node_management).0
+}
+
+pub fn slash_record_pda(ids: &ProgramIds, node_id: &Pubkey, evidence_hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"slash-record", node_id.as_ref(), evidence_hash], &ids.node_management).0
+}
+
+pub fn contribution_state_pda(ids: &ProgramIds) -> Pubkey {
+    Pubkey::find_program_address(&[b"contribution-tracking-state"], &ids.contribution_tracking).0
+}
+
+pub fn contribution_pda(ids: &ProgramIds, contribution_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"contribution", contribution_id.as_bytes()], &ids.contribution_tracking).0
+}
+
+pub fn challenge_pda(ids: &ProgramIds, node_id: &Pubkey, evidence_hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"challenge", node_id.as_ref(), evidence_hash], &ids.contribution_tracking).0
+}
+
+pub fn epoch_compute_pda(ids: &ProgramIds, epoch: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"epoch-compute", &epoch.to_le_bytes()], &ids.contribution_tracking).0
+}
+
+pub fn committee_config_pda(ids: &ProgramIds, epoch: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"committee-config", &epoch.to_le_bytes()], &ids.contribution_tracking).0
+}
+
+pub fn node_epoch_compute_pda(ids: &ProgramIds, epoch: u64, node_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"node-epoch-compute", &epoch.to_le_bytes(), node_id.as_ref()],
+        &ids.contribution_tracking,
+    )
+    .0
+}
+
+pub fn verifier_config_pda(ids: &ProgramIds) -> Pubkey {
+    Pubkey::find_program_address(&[b"zk-verifier-config"], &ids.zk_verifier).0
+}
+
+pub fn verifying_key_pda(ids: &ProgramIds, circuit_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"verifying-key", circuit_id.as_bytes()], &ids.zk_verifier).0
+}
+
+pub fn proof_record_pda(ids: &ProgramIds, proof_hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"proof-record", proof_hash], &ids.zk_verifier).0
+}
+
+pub fn reward_state_pda(ids: &ProgramIds) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward-management-state"], &ids.reward_management).0
+}
+
+/// 收益记录 PDA（合约用分配时的链上时间戳派生，客户端只能按本地时间估计）
+pub fn reward_record_pda(ids: &ProgramIds, node_id: &Pubkey, timestamp: i64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"reward", node_id.as_ref(), &timestamp.to_le_bytes()],
+        &ids.reward_management,
+    )
+    .0
+}
+
+pub fn node_reward_summary_pda(ids: &ProgramIds, node_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"node-reward-summary", node_id.as_ref()], &ids.reward_management).0
+}
+
+pub fn epoch_state_pda(ids: &ProgramIds, epoch: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"epoch-state", &epoch.to_le_bytes()], &ids.reward_management).0
+}
+
+pub fn epoch_reward_receipt_pda(ids: &ProgramIds, epoch: u64, node_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"epoch-reward", &epoch.to_le_bytes(), node_id.as_ref()],
+        &ids.reward_management,
+    )
+    .0
+}
+
+pub fn oracle_import_pda(ids: &ProgramIds, compute_report: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"oracle-import", compute_report.as_ref()], &ids.contribution_tracking).0
+}
+
+pub fn governance_state_pda(ids: &ProgramIds) -> Pubkey {
+    Pubkey::find_program_address(&[b"governance-state"], &ids.governance).0
+}
+
+pub fn proposal_pda(ids: &ProgramIds, proposal_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"proposal", proposal_id.as_bytes()], &ids.governance).0
+}
+
+pub fn voter_record_pda(ids: &ProgramIds, proposal: &Pubkey, voter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"voter-record", proposal.as_ref(), voter.as_ref()], &ids.governance).0
+}
+
+pub fn governance_authority_pda(ids: &ProgramIds) -> Pubkey {
+    Pubkey::find_program_address(&[b"governance-authority"], &ids.governance).0
+}
+
+pub fn multisig_pda(ids: &ProgramIds, creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"multisig", creator.as_ref()], &ids.governance).0
+}
+
+pub fn multisig_transaction_pda(ids: &ProgramIds, multisig: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"multisig-tx", multisig.as_ref(), &nonce.to_le_bytes()], &ids.governance).0
+}
+
+pub fn multisig_signer_pda(ids: &ProgramIds, multisig: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"multisig-signer", multisig.as_ref()], &ids.governance).0
+}
+
+pub fn oracle_state_pda(ids: &ProgramIds) -> Pubkey {
+    Pubkey::find_program_address(&[b"oracle-state"], &ids.oracle).0
+}
+
+/// 指标汇总 PDA（纪元总量的 subject 为默认公钥）
+pub fn oracle_metric_pda(ids: &ProgramIds, kind: MetricKind, epoch: u64, subject: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"oracle-metric", &[kind as u8], &epoch.to_le_bytes(), subject.as_ref()],
+        &ids.oracle,
+    )
+    .0
+}
+
+/// 关联代币账户地址
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let associated_token_program = solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &associated_token_program,
+    )
+    .0
+}
+
+// ============ 合约参数类型（与链上布局一致） ============
+
+/// 随贡献提交的 Groth16 证明
+#[derive(Debug, Clone, BorshSerialize)]
+pub struct ContributionProofArgs {
+    pub proof_hash: [u8; 32],
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+    pub public_inputs: Vec<[u8; 32]>,
+}
+
+impl ContributionProofArgs {
+    /// 由 alt_bn128 格式的证明和公开输入组装，摘要与 zk-verifier 的 `proof_digest` 一致
+    pub fn new(a: [u8; 64], b: [u8; 128], c: [u8; 64], public_inputs: Vec<[u8; 32]>) -> Self {
+        let mut parts: Vec<&[u8]> = vec![&a, &b, &c];
+        parts.extend(public_inputs.iter().map(|input| input.as_slice()));
+        Self {
+            proof_hash: hashv(&parts).to_bytes(),
+            a,
+            b,
+            c,
+            public_inputs,
+        }
+    }
+}
+
+/// 验证者的委员会抽签证明
+#[derive(Debug, Clone, BorshSerialize)]
+pub struct CommitteeProofArgs {
+    pub member: Pubkey,
+    pub epoch: u64,
+    pub seed_slot: u64,
+    pub seed_blockhash: [u8; 32],
+    pub vrf_proof: [u8; 80],
+}
+
+impl CommitteeProofArgs {
+    pub fn from_ticket(ticket: &crate::consensus::CommitteeTicket) -> anyhow::Result<Self> {
+        Ok(Self {
+            member: ticket.node_id.parse()?,
+            epoch: ticket.epoch,
+            seed_slot: ticket.seed.slot,
+            seed_blockhash: ticket.seed.blockhash,
+            vrf_proof: ticket.vrf_proof()?.0,
+        })
+    }
+}
+
+/// 记录贡献的参数
+#[derive(Debug, Clone)]
+pub struct RecordContributionArgs {
+    pub contribution_id: String,
+    pub node_id: Pubkey,
+    pub task_id: String,
+    pub task_type: TaskType,
+    pub model_info: ModelInfo,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub duration_seconds: u64,
+    pub avg_gpu_usage_percent: f32,
+    pub gpu_memory_used_mb: u64,
+    pub avg_cpu_usage_percent: f32,
+    pub memory_used_mb: u64,
+    pub network_upload_mb: u64,
+    pub network_download_mb: u64,
+    pub samples_processed: u64,
+    pub batches_processed: u64,
+    pub compute_score: f64,
+    pub quality_score: f32,
+    /// 需要验证时附带的证明及其电路 ID
+    pub proof: Option<(String, ContributionProofArgs)>,
+    /// 训练所用数据集的清单哈希
+    pub dataset_manifest_hash: Option<[u8; 32]>,
+}
+
+impl RecordContributionArgs {
+    /// 证明须作为第一个公开输入承诺的值
+    pub fn commitment(&self) -> [u8; 32] {
+        contribution_commitment(&self.contribution_id, &self.node_id, self.compute_score)
+    }
+}
+
+/// 贡献账户开头的固定字段，验证贡献时据此推导纪元统计账户
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct ContributionHeader {
+    pub id: String,
+    pub node_id: Pubkey,
+    pub task_id: String,
+    pub task_type: TaskType,
+    pub model_info: ModelInfo,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+}
+
+impl ContributionHeader {
+    /// 解析账户数据（跳过 8 字节 Anchor 账户判别符，忽略其余字段）
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        let mut body = data.get(8..).ok_or_else(|| anyhow::anyhow!("贡献账户数据过短"))?;
+        Ok(Self::deserialize(&mut body)?)
+    }
+
+    pub fn epoch(&self) -> u64 {
+        contribution_epoch(self.end_timestamp)
+    }
+}
+
+/// 提案类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize)]
+pub enum ProposalType {
+    ParameterUpdate,
+    ContractUpgrade,
+    TreasuryManagement,
+    NodeManagement,
+    Other,
+}
+
+/// 提案或多签交易中的账户元数据
+#[derive(Debug, Clone, BorshSerialize)]
+pub struct TransactionAccountArgs {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// 批量分配中的单条记录
+#[derive(Debug, Clone, BorshSerialize)]
+pub struct RewardDistributionArgs {
+    pub node_id: Pubkey,
+    pub contribution_id: String,
+    pub amount_lamports: u64,
+}
+
+/// 奖励币种
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize)]
+pub enum RewardCurrency {
+    Lamports,
+    SplToken,
+}
+
+/// 预言机指标类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum MetricKind {
+    /// 纪元总算力评分
+    EpochComputeScore,
+    /// 节点纪元算力评分
+    NodeComputeScore,
+    /// 节点纪元在线率（万分比）
+    NodeUptime,
+}
+
+// ============ node-management ============
+
+pub fn node_initialize(ids: &ProgramIds, admin: &Pubkey, min_stake_amount: u64, verification_fee: u64) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_management_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("initialize").arg(&min_stake_amount).arg(&verification_fee).finish(),
+    }
+}
+
+pub fn register_node(
+    ids: &ProgramIds,
+    owner: &Pubkey,
+    node_id: &Pubkey,
+    name: &str,
+    device_type: &str,
+    location: &Location,
+) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_pda(ids, node_id)),
+            writable(node_management_state_pda(ids)),
+            payer(*owner),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("register_node")
+            .arg(node_id)
+            .arg(&name.to_string())
+            .arg(&device_type.to_string())
+            .arg(location)
+            .finish(),
+    }
+}
+
+pub fn update_node_status(ids: &ProgramIds, authority: &Pubkey, node_id: &Pubkey, status: NodeStatus) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_pda(ids, node_id)),
+            writable(node_management_state_pda(ids)),
+            signer(*authority),
+        ],
+        data: ArgWriter::new("update_node_status").arg(node_id).arg(&status).finish(),
+    }
+}
+
+pub fn verify_node(ids: &ProgramIds, verifier: &Pubkey, node_id: &Pubkey, verification_level: u8) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_pda(ids, node_id)),
+            readonly(node_management_state_pda(ids)),
+            signer(*verifier),
+        ],
+        data: ArgWriter::new("verify_node").arg(node_id).arg(&verification_level).finish(),
+    }
+}
+
+/// 上报节点信誉分（0-1000），由本地信誉引擎计算
+pub fn update_reputation(ids: &ProgramIds, authority: &Pubkey, node_id: &Pubkey, reputation_score: u32) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_pda(ids, node_id)),
+            readonly(node_management_state_pda(ids)),
+            signer(*authority),
+        ],
+        data: ArgWriter::new("update_reputation").arg(node_id).arg(&reputation_score).finish(),
+    }
+}
+
+pub fn slash_node(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    node_id: &Pubkey,
+    slash_ratio: u32,
+    evidence_hash: [u8; 32],
+    reason: &str,
+) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            readonly(node_pda(ids, node_id)),
+            writable(slash_record_pda(ids, node_id, &evidence_hash)),
+            readonly(node_management_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("slash_node")
+            .arg(node_id)
+            .arg(&slash_ratio)
+            .arg(&evidence_hash)
+            .arg(&reason.to_string())
+            .finish(),
+    }
+}
+
+pub fn appeal_slash(
+    ids: &ProgramIds,
+    owner: &Pubkey,
+    node_id: &Pubkey,
+    evidence_hash: &[u8; 32],
+    counter_evidence_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(slash_record_pda(ids, node_id, evidence_hash)),
+            readonly(node_pda(ids, node_id)),
+            signer(*owner),
+        ],
+        data: ArgWriter::new("appeal_slash").arg(&counter_evidence_hash).finish(),
+    }
+}
+
+pub fn resolve_slash(
+    ids: &ProgramIds,
+    slash_authority: &Pubkey,
+    node_id: &Pubkey,
+    evidence_hash: &[u8; 32],
+    treasury: &Pubkey,
+    uphold: bool,
+) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(slash_record_pda(ids, node_id, evidence_hash)),
+            writable(node_pda(ids, node_id)),
+            writable(node_management_state_pda(ids)),
+            writable(*treasury),
+            signer(*slash_authority),
+        ],
+        data: ArgWriter::new("resolve_slash").arg(&uphold).finish(),
+    }
+}
+
+pub fn finalize_slash(
+    ids: &ProgramIds,
+    caller: &Pubkey,
+    node_id: &Pubkey,
+    evidence_hash: &[u8; 32],
+    treasury: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(slash_record_pda(ids, node_id, evidence_hash)),
+            writable(node_pda(ids, node_id)),
+            writable(node_management_state_pda(ids)),
+            writable(*treasury),
+            signer(*caller),
+        ],
+        data: ArgWriter::new("finalize_slash").finish(),
+    }
+}
+
+pub fn set_slash_authority(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    slash_authority: &Pubkey,
+    appeal_window_seconds: i64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![writable(node_management_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("set_slash_authority")
+            .arg(slash_authority)
+            .arg(&appeal_window_seconds)
+            .finish(),
+    }
+}
+
+/// 合约升级后把旧布局的状态账户扩容到当前布局
+pub fn migrate_node_state(ids: &ProgramIds, admin: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_management_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("migrate_state").finish(),
+    }
+}
+
+pub fn set_treasury(ids: &ProgramIds, admin: &Pubkey, treasury: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![writable(node_management_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("set_treasury").arg(treasury).finish(),
+    }
+}
+
+pub fn update_last_active(ids: &ProgramIds, owner: &Pubkey, node_id: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![writable(node_pda(ids, node_id)), signer(*owner)],
+        data: ArgWriter::new("update_last_active").arg(node_id).finish(),
+    }
+}
+
+pub fn submit_heartbeat(ids: &ProgramIds, owner: &Pubkey, node_id: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_pda(ids, node_id)),
+            writable(node_management_state_pda(ids)),
+            signer(*owner),
+        ],
+        data: ArgWriter::new("submit_heartbeat").finish(),
+    }
+}
+
+pub fn mark_inactive(ids: &ProgramIds, caller: &Pubkey, node_id: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![
+            writable(node_pda(ids, node_id)),
+            writable(node_management_state_pda(ids)),
+            signer(*caller),
+        ],
+        data: ArgWriter::new("mark_inactive").finish(),
+    }
+}
+
+pub fn update_liveness_settings(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    inactive_threshold_seconds: i64,
+    min_heartbeat_interval_seconds: i64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.node_management,
+        accounts: vec![writable(node_management_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("update_liveness_settings")
+            .arg(&inactive_threshold_seconds)
+            .arg(&min_heartbeat_interval_seconds)
+            .finish(),
+    }
+}
+
+// ============ contribution-tracking ============
+
+pub fn contribution_initialize(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    base_reward_per_compute: u64,
+    verification_required: bool,
+    min_quality_threshold: f32,
+) -> Instruction {
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![
+            writable(contribution_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("initialize")
+            .arg(&base_reward_per_compute)
+            .arg(&verification_required)
+            .arg(&min_quality_threshold)
+            .finish(),
+    }
+}
+
+pub fn record_contribution(ids: &ProgramIds, authority: &Pubkey, args: &RecordContributionArgs) -> Instruction {
+    let epoch = contribution_epoch(args.end_timestamp);
+    let program_id = ids.contribution_tracking;
+    let (verifying_key, proof_record, zk_verifier, proof) = match &args.proof {
+        Some((circuit_id, proof)) => (
+            Some(verifying_key_pda(ids, circuit_id)),
+            Some(proof_record_pda(ids, &proof.proof_hash)),
+            Some(ids.zk_verifier),
+            Some(proof.clone()),
+        ),
+        None => (None, None, None, None),
+    };
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            writable(contribution_pda(ids, &args.contribution_id)),
+            writable(contribution_state_pda(ids)),
+            readonly(node_pda(ids, &args.node_id)),
+            writable(epoch_compute_pda(ids, epoch)),
+            writable(node_epoch_compute_pda(ids, epoch, &args.node_id)),
+            payer(*authority),
+            readonly(system_program::id()),
+            optional(verifying_key, &program_id, false),
+            optional(proof_record, &program_id, true),
+            optional(zk_verifier, &program_id, false),
+        ],
+        data: ArgWriter::new("record_contribution")
+            .arg(&args.contribution_id)
+            .arg(&args.node_id)
+            .arg(&args.task_id)
+            .arg(&args.task_type)
+            .arg(&args.model_info)
+            .arg(&args.start_timestamp)
+            .arg(&args.end_timestamp)
+            .arg(&args.duration_seconds)
+            .arg(&args.avg_gpu_usage_percent)
+            .arg(&args.gpu_memory_used_mb)
+            .arg(&args.avg_cpu_usage_percent)
+            .arg(&args.memory_used_mb)
+            .arg(&args.network_upload_mb)
+            .arg(&args.network_download_mb)
+            .arg(&args.samples_processed)
+            .arg(&args.batches_processed)
+            .arg(&args.compute_score)
+            .arg(&args.quality_score)
+            .arg(&proof)
+            .arg(&args.dataset_manifest_hash)
+            .finish(),
+    }
+}
+
+/// 验证贡献，通过后其算力计入所在纪元；非管理员须附带本纪元的委员会抽签证明，由链上复核 VRF 和种子区块哈希
+pub fn verify_contribution(
+    ids: &ProgramIds,
+    verifier: &Pubkey,
+    contribution: &ContributionHeader,
+    is_valid: bool,
+    verifier_notes: Option<String>,
+    committee_proof: Option<CommitteeProofArgs>,
+) -> Instruction {
+    let epoch = contribution.epoch();
+    // 委员会成员须附带自己的节点账户、SlotHashes 系统变量和本纪元委员会参数，供链上复核抽签证明
+    let member_node = committee_proof.as_ref().map(|proof| node_pda(ids, &proof.member));
+    let slot_hashes = committee_proof.as_ref().map(|_| sysvar::slot_hashes::id());
+    let committee_config = committee_proof.as_ref().map(|_| committee_config_pda(ids, epoch));
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![
+            writable(contribution_pda(ids, &contribution.id)),
+            writable(contribution_state_pda(ids)),
+            writable(epoch_compute_pda(ids, epoch)),
+            writable(node_epoch_compute_pda(ids, epoch, &contribution.node_id)),
+            signer(*verifier),
+            optional(member_node, &ids.contribution_tracking, false),
+            optional(slot_hashes, &ids.contribution_tracking, false),
+            optional(committee_config, &ids.contribution_tracking, false),
+        ],
+        data: ArgWriter::new("verify_contribution")
+            .arg(&contribution.id)
+            .arg(&is_valid)
+            .arg(&verifier_notes)
+            .arg(&committee_proof)
+            .finish(),
+    }
+}
+
+pub fn open_challenge(
+    ids: &ProgramIds,
+    challenger: &Pubkey,
+    node_id: &Pubkey,
+    task_id: &str,
+    evidence_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![
+            writable(challenge_pda(ids, node_id, &evidence_hash)),
+            payer(*challenger),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("open_challenge")
+            .arg(node_id)
+            .arg(&task_id.to_string())
+            .arg(&evidence_hash)
+            .finish(),
+    }
+}
+
+pub fn resolve_challenge(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    node_id: &Pubkey,
+    evidence_hash: &[u8; 32],
+    upheld: bool,
+) -> Instruction {
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![
+            writable(challenge_pda(ids, node_id, evidence_hash)),
+            readonly(contribution_state_pda(ids)),
+            signer(*admin),
+        ],
+        data: ArgWriter::new("resolve_challenge").arg(&upheld).finish(),
+    }
+}
+
+/// 批量验证（仅管理员），每条贡献依次附带贡献账户和两个纪元统计账户
+pub fn batch_verify_contributions(
+    ids: &ProgramIds,
+    verifier: &Pubkey,
+    contributions: &[ContributionHeader],
+    verification_results: Vec<bool>,
+) -> Instruction {
+    let mut accounts = vec![writable(contribution_state_pda(ids)), signer(*verifier)];
+    for contribution in contributions {
+        let epoch = contribution.epoch();
+        accounts.push(writable(contribution_pda(ids, &contribution.id)));
+        accounts.push(writable(epoch_compute_pda(ids, epoch)));
+        accounts.push(writable(node_epoch_compute_pda(ids, epoch, &contribution.node_id)));
+    }
+    let contribution_ids: Vec<String> = contributions.iter().map(|c| c.id.clone()).collect();
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts,
+        data: ArgWriter::new("batch_verify_contributions")
+            .arg(&contribution_ids)
+            .arg(&verification_results)
+            .finish(),
+    }
+}
+
+pub fn update_base_reward(ids: &ProgramIds, admin: &Pubkey, new_base_reward: u64) -> Instruction {
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![writable(contribution_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("update_base_reward").arg(&new_base_reward).finish(),
+    }
+}
+
+pub fn update_verification_settings(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    verification_required: bool,
+    min_quality_threshold: f32,
+) -> Instruction {
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![writable(contribution_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("update_verification_settings")
+            .arg(&verification_required)
+            .arg(&min_quality_threshold)
+            .finish(),
+    }
+}
+
+/// 公布纪元委员会参数（仅管理员），节点和合约按同一参数判定抽签是否当选
+pub fn set_committee_config(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    epoch: u64,
+    committee_size: u32,
+    total_stake: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![
+            writable(committee_config_pda(ids, epoch)),
+            readonly(contribution_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("set_committee_config")
+            .arg(&epoch)
+            .arg(&committee_size)
+            .arg(&total_stake)
+            .finish(),
+    }
+}
+
+/// 指定算力证明电路（仅管理员），旧布局的状态账户会随之扩容
+pub fn set_verifying_key(ids: &ProgramIds, admin: &Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: ids.contribution_tracking,
+        accounts: vec![
+            writable(contribution_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("set_verifying_key")
+            .arg(&verifying_key_pda(ids, circuit_id))
+            .finish(),
+    }
+}
+
+// ============ zk-verifier ============
+
+pub fn zk_verifier_initialize(ids: &ProgramIds, admin: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.zk_verifier,
+        accounts: vec![
+            writable(verifier_config_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("initialize").finish(),
+    }
+}
+
+pub fn register_verifying_key(
+    ids: &ProgramIds,
+    authority: &Pubkey,
+    circuit_id: &str,
+    alpha_g1: [u8; 64],
+    beta_g2: [u8; 128],
+    gamma_g2: [u8; 128],
+    delta_g2: [u8; 128],
+    ic: Vec<[u8; 64]>,
+) -> Instruction {
+    Instruction {
+        program_id: ids.zk_verifier,
+        accounts: vec![
+            readonly(verifier_config_pda(ids)),
+            writable(verifying_key_pda(ids, circuit_id)),
+            payer(*authority),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("register_verifying_key")
+            .arg(&circuit_id.to_string())
+            .arg(&alpha_g1)
+            .arg(&beta_g2)
+            .arg(&gamma_g2)
+            .arg(&delta_g2)
+            .arg(&ic)
+            .finish(),
+    }
+}
+
+/// 单独验证证明（不随贡献记录提交）
+pub fn verify_proof(ids: &ProgramIds, payer_key: &Pubkey, circuit_id: &str, proof: &ContributionProofArgs) -> Instruction {
+    Instruction {
+        program_id: ids.zk_verifier,
+        accounts: vec![
+            readonly(verifying_key_pda(ids, circuit_id)),
+            writable(proof_record_pda(ids, &proof.proof_hash)),
+            payer(*payer_key),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("verify_proof")
+            .arg(&proof.proof_hash)
+            .arg(&proof.a)
+            .arg(&proof.b)
+            .arg(&proof.c)
+            .arg(&proof.public_inputs)
+            .finish(),
+    }
+}
+
+// ============ reward-management ============
+
+pub fn reward_initialize(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    min_distribution_amount: u64,
+    distribution_frequency: u64,
+    auto_distribution_enabled: bool,
+    reward_currency: RewardCurrency,
+    reward_mint: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![
+            writable(reward_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("initialize")
+            .arg(treasury)
+            .arg(&min_distribution_amount)
+            .arg(&distribution_frequency)
+            .arg(&auto_distribution_enabled)
+            .arg(&reward_currency)
+            .arg(reward_mint)
+            .finish(),
+    }
+}
+
+pub fn distribute_rewards(
+    ids: &ProgramIds,
+    authority: &Pubkey,
+    node_id: &Pubkey,
+    contribution_id: &str,
+    amount_lamports: u64,
+    timestamp: i64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![
+            writable(reward_record_pda(ids, node_id, timestamp)),
+            writable(node_reward_summary_pda(ids, node_id)),
+            readonly(node_pda(ids, node_id)),
+            writable(reward_state_pda(ids)),
+            payer(*authority),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("distribute_rewards")
+            .arg(node_id)
+            .arg(&contribution_id.to_string())
+            .arg(&amount_lamports)
+            .finish(),
+    }
+}
+
+/// 批量分配（仅管理员）；收益记录由合约按链上时间创建，这里按 `timestamp` 估计其地址，
+/// 节点收益汇总账户需已存在
+pub fn batch_distribute_rewards(
+    ids: &ProgramIds,
+    authority: &Pubkey,
+    distributions: Vec<RewardDistributionArgs>,
+    timestamp: i64,
+) -> Instruction {
+    let mut accounts = vec![
+        writable(reward_state_pda(ids)),
+        payer(*authority),
+        readonly(system_program::id()),
+    ];
+    for distribution in &distributions {
+        accounts.push(writable(reward_record_pda(ids, &distribution.node_id, timestamp)));
+        accounts.push(writable(node_reward_summary_pda(ids, &distribution.node_id)));
+        accounts.push(readonly(node_pda(ids, &distribution.node_id)));
+    }
+    Instruction {
+        program_id: ids.reward_management,
+        accounts,
+        data: ArgWriter::new("batch_distribute_rewards").arg(&distributions).finish(),
+    }
+}
+
+pub fn finalize_epoch(ids: &ProgramIds, payer_key: &Pubkey, epoch: u64) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![
+            writable(epoch_state_pda(ids, epoch)),
+            writable(reward_state_pda(ids)),
+            readonly(epoch_compute_pda(ids, epoch)),
+            readonly(ids.contribution_tracking),
+            payer(*payer_key),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("finalize_epoch").arg(&epoch).finish(),
+    }
+}
+
+pub fn distribute_epoch_reward(ids: &ProgramIds, payer_key: &Pubkey, epoch: u64, node_id: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![
+            writable(epoch_state_pda(ids, epoch)),
+            readonly(node_epoch_compute_pda(ids, epoch, node_id)),
+            writable(epoch_reward_receipt_pda(ids, epoch, node_id)),
+            readonly(node_pda(ids, node_id)),
+            writable(node_reward_summary_pda(ids, node_id)),
+            writable(reward_state_pda(ids)),
+            payer(*payer_key),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("distribute_epoch_reward").arg(&epoch).arg(node_id).finish(),
+    }
+}
+
+/// 领取收益；SPL 模式下需提供奖励 mint
+pub fn claim_rewards(
+    ids: &ProgramIds,
+    owner: &Pubkey,
+    node_id: &Pubkey,
+    treasury: &Pubkey,
+    reward_mint: Option<&Pubkey>,
+) -> Instruction {
+    let program_id = ids.reward_management;
+    let state = reward_state_pda(ids);
+    let mint = reward_mint.copied();
+    Instruction {
+        program_id,
+        accounts: vec![
+            readonly(node_pda(ids, node_id)),
+            writable(node_reward_summary_pda(ids, node_id)),
+            readonly(state),
+            writable(*treasury),
+            payer(*owner),
+            optional(mint, &program_id, false),
+            optional(mint.map(|m| associated_token_address(&state, &m)), &program_id, true),
+            optional(mint.map(|m| associated_token_address(owner, &m)), &program_id, true),
+            optional(mint.map(|_| TOKEN_PROGRAM_ID), &program_id, false),
+        ],
+        data: ArgWriter::new("claim_rewards").finish(),
+    }
+}
+
+pub fn stake_tokens(
+    ids: &ProgramIds,
+    staker: &Pubkey,
+    treasury: &Pubkey,
+    node_id: &Pubkey,
+    amount: u64,
+    lock_duration_seconds: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![writable(reward_state_pda(ids)), writable(*treasury), payer(*staker)],
+        data: ArgWriter::new("stake_tokens")
+            .arg(node_id)
+            .arg(&amount)
+            .arg(&lock_duration_seconds)
+            .finish(),
+    }
+}
+
+pub fn add_to_reward_pool(ids: &ProgramIds, funder: &Pubkey, treasury: &Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![writable(reward_state_pda(ids)), writable(*treasury), payer(*funder)],
+        data: ArgWriter::new("add_to_reward_pool").arg(&amount).finish(),
+    }
+}
+
+fn token_pool_accounts(ids: &ProgramIds, owner: &Pubkey, mint: &Pubkey) -> Vec<AccountMeta> {
+    let state = reward_state_pda(ids);
+    vec![
+        readonly(*mint),
+        writable(associated_token_address(&state, mint)),
+        writable(associated_token_address(owner, mint)),
+        payer(*owner),
+        readonly(TOKEN_PROGRAM_ID),
+        readonly(solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")),
+        readonly(system_program::id()),
+    ]
+}
+
+pub fn stake_spl_tokens(
+    ids: &ProgramIds,
+    staker: &Pubkey,
+    reward_mint: &Pubkey,
+    node_id: &Pubkey,
+    amount: u64,
+    lock_duration_seconds: u64,
+) -> Instruction {
+    let mut accounts = vec![readonly(reward_state_pda(ids))];
+    accounts.extend(token_pool_accounts(ids, staker, reward_mint));
+    Instruction {
+        program_id: ids.reward_management,
+        accounts,
+        data: ArgWriter::new("stake_spl_tokens")
+            .arg(node_id)
+            .arg(&amount)
+            .arg(&lock_duration_seconds)
+            .finish(),
+    }
+}
+
+pub fn add_tokens_to_reward_pool(ids: &ProgramIds, funder: &Pubkey, reward_mint: &Pubkey, amount: u64) -> Instruction {
+    let mut accounts = vec![writable(reward_state_pda(ids))];
+    accounts.extend(token_pool_accounts(ids, funder, reward_mint));
+    Instruction {
+        program_id: ids.reward_management,
+        accounts,
+        data: ArgWriter::new("add_tokens_to_reward_pool").arg(&amount).finish(),
+    }
+}
+
+pub fn update_distribution_settings(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    min_distribution_amount: u64,
+    distribution_frequency: u64,
+    auto_distribution_enabled: bool,
+) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![writable(reward_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("update_distribution_settings")
+            .arg(&min_distribution_amount)
+            .arg(&distribution_frequency)
+            .arg(&auto_distribution_enabled)
+            .finish(),
+    }
+}
+
+pub fn set_vesting_duration(ids: &ProgramIds, admin: &Pubkey, vesting_duration_seconds: u64) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![writable(reward_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("set_vesting_duration").arg(&vesting_duration_seconds).finish(),
+    }
+}
+
+pub fn set_epoch_budget(ids: &ProgramIds, admin: &Pubkey, epoch_budget: u64) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![writable(reward_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("set_epoch_budget").arg(&epoch_budget).finish(),
+    }
+}
+
+pub fn emergency_withdraw(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.reward_management,
+        accounts: vec![
+            writable(reward_state_pda(ids)),
+            writable(*treasury),
+            writable(*recipient),
+            signer(*admin),
+        ],
+        data: ArgWriter::new("emergency_withdraw").arg(&amount).finish(),
+    }
+}
+
+// ============ governance ============
+
+pub fn governance_initialize(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    voting_period: u64,
+    execution_delay: u64,
+    min_voting_power: u64,
+    quorum: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![
+            writable(governance_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("initialize")
+            .arg(&voting_period)
+            .arg(&execution_delay)
+            .arg(&min_voting_power)
+            .arg(&quorum)
+            .finish(),
+    }
+}
+
+pub fn create_proposal(
+    ids: &ProgramIds,
+    proposer: &Pubkey,
+    proposal_id: &str,
+    title: &str,
+    description: &str,
+    proposal_type: ProposalType,
+    target_program: &Pubkey,
+    target_accounts: Vec<TransactionAccountArgs>,
+    instruction_data: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![
+            writable(proposal_pda(ids, proposal_id)),
+            readonly(governance_state_pda(ids)),
+            payer(*proposer),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("create_proposal")
+            .arg(&proposal_id.to_string())
+            .arg(&title.to_string())
+            .arg(&description.to_string())
+            .arg(&proposal_type)
+            .arg(target_program)
+            .arg(&target_accounts)
+            .arg(&instruction_data)
+            .finish(),
+    }
+}
+
+/// 按节点质押投票；投票者须为 `node_id` 对应节点的所有者
+pub fn vote_on_proposal(
+    ids: &ProgramIds,
+    voter: &Pubkey,
+    node_id: &Pubkey,
+    proposal_id: &str,
+    vote: bool,
+) -> Instruction {
+    let proposal = proposal_pda(ids, proposal_id);
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![
+            writable(proposal),
+            readonly(governance_state_pda(ids)),
+            writable(voter_record_pda(ids, &proposal, voter)),
+            readonly(node_pda(ids, node_id)),
+            readonly(ids.node_management),
+            payer(*voter),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("vote_on_proposal")
+            .arg(&proposal_id.to_string())
+            .arg(&vote)
+            .finish(),
+    }
+}
+
+/// 目标指令的账户（目标程序 + 目标账户）作为剩余账户传入；
+/// 由治理 PDA 代签的账户在这里不能标记为签名者
+fn target_accounts(target_program: &Pubkey, accounts: &[TransactionAccountArgs], pda_signer: &Pubkey) -> Vec<AccountMeta> {
+    let mut metas = vec![readonly(*target_program)];
+    metas.extend(accounts.iter().map(|a| {
+        let is_signer = a.is_signer && a.pubkey != *pda_signer;
+        if a.is_writable {
+            AccountMeta::new(a.pubkey, is_signer)
+        } else {
+            AccountMeta::new_readonly(a.pubkey, is_signer)
+        }
+    }));
+    metas
+}
+
+pub fn execute_proposal(
+    ids: &ProgramIds,
+    executor: &Pubkey,
+    proposal_id: &str,
+    target_program: &Pubkey,
+    accounts: &[TransactionAccountArgs],
+) -> Instruction {
+    let authority = governance_authority_pda(ids);
+    let mut metas = vec![
+        writable(proposal_pda(ids, proposal_id)),
+        readonly(governance_state_pda(ids)),
+        readonly(authority),
+        signer(*executor),
+    ];
+    metas.extend(target_accounts(target_program, accounts, &authority));
+    Instruction {
+        program_id: ids.governance,
+        accounts: metas,
+        data: ArgWriter::new("execute_proposal").arg(&proposal_id.to_string()).finish(),
+    }
+}
+
+pub fn finalize_proposal(ids: &ProgramIds, authority: &Pubkey, proposal_id: &str) -> Instruction {
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![
+            writable(proposal_pda(ids, proposal_id)),
+            readonly(governance_state_pda(ids)),
+            signer(*authority),
+        ],
+        data: ArgWriter::new("finalize_proposal").arg(&proposal_id.to_string()).finish(),
+    }
+}
+
+pub fn update_governance_params(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    voting_period: Option<u64>,
+    execution_delay: Option<u64>,
+    min_voting_power: Option<u64>,
+    quorum: Option<u64>,
+) -> Instruction {
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![writable(governance_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("update_governance_params")
+            .arg(&voting_period)
+            .arg(&execution_delay)
+            .arg(&min_voting_power)
+            .arg(&quorum)
+            .finish(),
+    }
+}
+
+pub fn create_multisig(ids: &ProgramIds, creator: &Pubkey, owners: Vec<Pubkey>, threshold: u64) -> Instruction {
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![
+            writable(multisig_pda(ids, creator)),
+            payer(*creator),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("create_multisig").arg(&owners).arg(&threshold).finish(),
+    }
+}
+
+/// 创建多签交易；`nonce` 为多签账户当前的 nonce
+pub fn create_multisig_transaction(
+    ids: &ProgramIds,
+    creator: &Pubkey,
+    multisig: &Pubkey,
+    nonce: u64,
+    target_program: &Pubkey,
+    accounts: Vec<TransactionAccountArgs>,
+    data: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![
+            writable(multisig_transaction_pda(ids, multisig, nonce)),
+            writable(*multisig),
+            payer(*creator),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("create_multisig_transaction")
+            .arg(target_program)
+            .arg(&accounts)
+            .arg(&data)
+            .finish(),
+    }
+}
+
+pub fn approve_multisig_transaction(
+    ids: &ProgramIds,
+    owner: &Pubkey,
+    multisig: &Pubkey,
+    transaction: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ids.governance,
+        accounts: vec![writable(*transaction), readonly(*multisig), signer(*owner)],
+        data: ArgWriter::new("approve_multisig_transaction").finish(),
+    }
+}
+
+/// 执行者须为多签所有者
+pub fn execute_multisig_transaction(
+    ids: &ProgramIds,
+    executor: &Pubkey,
+    multisig: &Pubkey,
+    transaction: &Pubkey,
+    target_program: &Pubkey,
+    accounts: &[TransactionAccountArgs],
+) -> Instruction {
+    let multisig_signer = multisig_signer_pda(ids, multisig);
+    let mut metas = vec![
+        writable(*transaction),
+        readonly(*multisig),
+        readonly(multisig_signer),
+        signer(*executor),
+    ];
+    metas.extend(target_accounts(target_program, accounts, &multisig_signer));
+    Instruction {
+        program_id: ids.governance,
+        accounts: metas,
+        data: ArgWriter::new("execute_multisig_transaction").finish(),
+    }
+}
+
+// ============ oracle ============
+
+pub fn oracle_initialize(
+    ids: &ProgramIds,
+    admin: &Pubkey,
+    reporters: Vec<Pubkey>,
+    threshold: u8,
+    tolerance_bps: u16,
+) -> Instruction {
+    Instruction {
+        program_id: ids.oracle,
+        accounts: vec![
+            writable(oracle_state_pda(ids)),
+            payer(*admin),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("initialize")
+            .arg(&reporters)
+            .arg(&threshold)
+            .arg(&tolerance_bps)
+            .finish(),
+    }
+}
+
+pub fn add_oracle_reporter(ids: &ProgramIds, admin: &Pubkey, reporter: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.oracle,
+        accounts: vec![writable(oracle_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("add_reporter").arg(reporter).finish(),
+    }
+}
+
+pub fn remove_oracle_reporter(ids: &ProgramIds, admin: &Pubkey, reporter: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ids.oracle,
+        accounts: vec![writable(oracle_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("remove_reporter").arg(reporter).finish(),
+    }
+}
+
+pub fn set_oracle_threshold(ids: &ProgramIds, admin: &Pubkey, threshold: u8, tolerance_bps: u16) -> Instruction {
+    Instruction {
+        program_id: ids.oracle,
+        accounts: vec![writable(oracle_state_pda(ids)), signer(*admin)],
+        data: ArgWriter::new("set_threshold").arg(&threshold).arg(&tolerance_bps).finish(),
+    }
+}
+
+pub fn submit_metric_report(
+    ids: &ProgramIds,
+    reporter: &Pubkey,
+    kind: MetricKind,
+    epoch: u64,
+    subject: &Pubkey,
+    value: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ids.oracle,
+        accounts: vec![
+            writable(oracle_state_pda(ids)),
+            writable(oracle_metric_pda(ids, kind, epoch, subject)),
+            payer(*reporter),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("submit_report")
+            .arg(&kind)
+            .arg(&epoch)
+            .arg(subject)
+            .arg(&value)
+            .finish(),
+    }
+}
+
+/// 把已定值的节点算力（及可选的在线率）导入 contribution-tracking
+pub fn import_oracle_compute(
+    ids: &ProgramIds,
+    payer_key: &Pubkey,
+    epoch: u64,
+    node_id: &Pubkey,
+    with_uptime: bool,
+) -> Instruction {
+    let program_id = ids.contribution_tracking;
+    let compute_report = oracle_metric_pda(ids, MetricKind::NodeComputeScore, epoch, node_id);
+    let uptime_report = with_uptime.then(|| oracle_metric_pda(ids, MetricKind::NodeUptime, epoch, node_id));
+    Instruction {
+        program_id,
+        accounts: vec![
+            writable(contribution_state_pda(ids)),
+            readonly(compute_report),
+            optional(uptime_report, &program_id, false),
+            writable(oracle_import_pda(ids, &compute_report)),
+            writable(epoch_compute_pda(ids, epoch)),
+            writable(node_epoch_compute_pda(ids, epoch, node_id)),
+            payer(*payer_key),
+            readonly(system_program::id()),
+        ],
+        data: ArgWriter::new("import_oracle_compute").arg(&epoch).arg(node_id).finish(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids() -> ProgramIds {
+        ProgramIds {
+            node_management: Pubkey::new_unique(),
+            contribution_tracking: Pubkey::new_unique(),
+            reward_management: Pubkey::new_unique(),
+            governance: Pubkey::new_unique(),
+            zk_verifier: Pubkey::new_unique(),
+            oracle: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_anchor_instruction_layout() {
+        let ids = ids();
+        let owner = Pubkey::new_unique();
+        let node_id = Pubkey::new_unique();
+
+        let ix = submit_heartbeat(&ids, &owner, &node_id);
+        assert_eq!(ix.data, anchor_discriminator("submit_heartbeat").to_vec());
+        assert_eq!(ix.accounts[0].pubkey, node_pda(&ids, &node_id));
+        assert!(ix.accounts[2].is_signer);
+
+        // 判别符后紧跟 Borsh 编码的参数
+        let ix = vote_on_proposal(&ids, &owner, &node_id, "p1", true);
+        assert_eq!(&ix.data[8..], &[2, 0, 0, 0, b'p', b'1', 1]);
+
+        // 未附带证明时可选账户以程序 ID 占位，proof 和数据集哈希参数编码为 None
+        let args = RecordContributionArgs {
+            contribution_id: "c1".to_string(),
+            node_id,
+            task_id: "t1".to_string(),
+            task_type: TaskType::Training,
+            model_info: ModelInfo {
+                model_id: "m".to_string(),
+                version: "1".to_string(),
+                parameters_hash: "h".to_string(),
+                size_mb: 1,
+            },
+            start_timestamp: 0,
+            end_timestamp: CONTRIBUTION_EPOCH_SECONDS * 3 + 5,
+            duration_seconds: 1,
+            avg_gpu_usage_percent: 0.0,
+            gpu_memory_used_mb: 0,
+            avg_cpu_usage_percent: 0.0,
+            memory_used_mb: 0,
+            network_upload_mb: 0,
+            network_download_mb: 0,
+            samples_processed: 0,
+            batches_processed: 0,
+            compute_score: 1.0,
+            quality_score: 1.0,
+            proof: None,
+            dataset_manifest_hash: None,
+        };
+        let ix = record_contribution(&ids, &owner, &args);
+        assert_eq!(ix.accounts[2].pubkey, node_pda(&ids, &node_id));
+        assert_eq!(ix.accounts[3].pubkey, epoch_compute_pda(&ids, 3));
+        assert_eq!(ix.accounts[7].pubkey, ids.contribution_tracking);
+        assert_eq!(*ix.data.last().unwrap(), 0);
+
+        // 验证指令按链上贡献记录推导纪元统计账户，账户尾部的其余字段被忽略
+        let mut data = vec![0u8; 8];
+        (&args.contribution_id, &node_id, &args.task_id, &args.task_type, &args.model_info)
+            .serialize(&mut data)
+            .unwrap();
+        (args.start_timestamp, args.end_timestamp, 1.0f64).serialize(&mut data).unwrap();
+        let header = ContributionHeader::decode(&data).unwrap();
+        assert_eq!(header.epoch(), 3);
+        let ix = verify_contribution(&ids, &owner, &header, true, None, None);
+        assert_eq!(ix.accounts[0].pubkey, contribution_pda(&ids, "c1"));
+        assert_eq!(ix.accounts[3].pubkey, node_epoch_compute_pda(&ids, 3, &node_id));
+        assert_eq!(ix.accounts[5].pubkey, ids.contribution_tracking);
+
+        // 委员会成员附带自己的节点账户、SlotHashes 和本纪元委员会参数
+        let proof = CommitteeProofArgs {
+            member: owner,
+            epoch: 3,
+            seed_slot: 42,
+            seed_blockhash: [7; 32],
+            vrf_proof: [0; 80],
+        };
+        let ix = verify_contribution(&ids, &owner, &header, true, None, Some(proof));
+        assert_eq!(ix.accounts[5].pubkey, node_pda(&ids, &owner));
+        assert_eq!(ix.accounts[6].pubkey, sysvar::slot_hashes::id());
+        assert_eq!(ix.accounts[7].pubkey, committee_config_pda(&ids, 3));
+
+        // 未导入在线率时同样以程序 ID 占位；汇总地址按指标类型区分
+        let ix = import_oracle_compute(&ids, &owner, 3, &node_id, false);
+        assert_eq!(ix.accounts[1].pubkey, oracle_metric_pda(&ids, MetricKind::NodeComputeScore, 3, &node_id));
+        assert_eq!(ix.accounts[2].pubkey, ids.contribution_tracking);
+        assert_ne!(
+            oracle_metric_pda(&ids, MetricKind::NodeComputeScore, 3, &node_id),
+            oracle_metric_pda(&ids, MetricKind::NodeUptime, 3, &node_id)
+        );
+    }
+
+    #[test]
+    fn test_contribution_proof_binding() {
+        // 与 contribution-tracking 测试夹具中真实证明的第一个公开输入一致
+        let node_id = Pubkey::new_from_array([3; 32]);
+        assert_eq!(
+            hex::encode(contribution_commitment("contribution-1", &node_id, 12.5)),
+            "06e3ba8f2e6e6a8b6c7825b913231472979b2fef6b05852be22657387362353e"
+        );
+
+        let proof = ContributionProofArgs::new([1; 64], [2; 128], [3; 64], vec![[4; 32]]);
+        assert_eq!(proof.proof_hash, hashv(&[&[1; 64][..], &[2; 128], &[3; 64], &[4; 32]]).to_bytes());
+    }
+}
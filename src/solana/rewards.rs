@@ -9,6 +9,13 @@ use uuid::Uuid;
 use super::compute::ComputeCalculator;
 use super::types::*;
 
+pub mod homomorphic;
+
+pub use homomorphic::{
+    DecryptedReward, EncryptedScoreSubmission, GovernanceKey, GovernanceKeyShare, HomomorphicRewardAggregator,
+    PaillierPublicKey, RewardComputationMode,
+};
+
 /// 收益分配管理器
 pub struct RewardManager {
     /// 基础每次计算的奖励（lamports）
@@ -61,6 +68,28 @@ impl RewardManager {
         }
     }
 
+    /// 由治理多签解密后的同态聚合结果创建收益分配记录
+    pub fn create_decrypted_reward_distributions(
+        &self,
+        task_id: String,
+        rewards: &[DecryptedReward],
+        transaction_signatures: Vec<String>,
+    ) -> Vec<RewardDistribution> {
+        rewards
+            .iter()
+            .zip(transaction_signatures)
+            .map(|(reward, signature)| RewardDistribution {
+                id: Uuid::new_v4().to_string(),
+                node_id: reward.node_id.clone(),
+                task_id: task_id.clone(),
+                amount_lamports: reward.amount_lamports,
+                distributed_at: Utc::now().timestamp(),
+                transaction_signature: signature,
+                status: RewardStatus::Pending,
+            })
+            .collect()
+    }
+
     /// 创建批量收益分配记录
     pub fn create_batch_reward_distributions(
         &self,
@@ -0,0 +1,451 @@
+//! 同态加密收益计算（可选）
+//!
+//! 面向不信任协调者的运营者：节点提交 Paillier 加密的奖励单位，协调者只能在密文上做
+//! 加法聚合和乘以基础奖励，看不到任何单个节点的评分。
+//!
+//! Paillier 私钥（λ）在生成时用 Shamir 秘密共享拆分给多签治理成员，协调者不持有私钥；
+//! 只有凑齐门限数量的治理成员份额才能解密聚合结果。
+
+use anyhow::{anyhow, Result};
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::solana::types::ComputeContribution;
+
+/// 奖励单位的定点精度（评分乘数 × 1e6）
+pub const REWARD_UNIT_SCALE: u64 = 1_000_000;
+
+/// 默认 Paillier 模数位数
+pub const DEFAULT_PAILLIER_BITS: u64 = 2048;
+
+/// Miller-Rabin 轮数
+const MILLER_RABIN_ROUNDS: usize = 40;
+
+mod biguint_hex {
+    use num_bigint::BigUint;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_str_radix(16))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigUint, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        BigUint::parse_bytes(s.as_bytes(), 16).ok_or_else(|| serde::de::Error::custom("无效的十六进制大整数"))
+    }
+}
+
+/// 收益计算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RewardComputationMode {
+    /// 协调者明文计算（默认）
+    #[default]
+    Plaintext,
+    /// 评分加密提交，同态聚合，治理多签解密
+    Homomorphic,
+}
+
+/// Paillier 公钥（g = n + 1）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaillierPublicKey {
+    #[serde(with = "biguint_hex")]
+    pub n: BigUint,
+}
+
+/// Paillier 密文
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ciphertext(#[serde(with = "biguint_hex")] pub BigUint);
+
+impl PaillierPublicKey {
+    fn n_squared(&self) -> BigUint {
+        &self.n * &self.n
+    }
+
+    /// 加密明文 m（m < n）
+    pub fn encrypt(&self, m: u64) -> Ciphertext {
+        let n_squared = self.n_squared();
+        let r = loop {
+            let r = random_below(&self.n);
+            if !r.is_zero() && r.gcd(&self.n).is_one() {
+                break r;
+            }
+        };
+        // g^m = (1 + n)^m = 1 + m·n (mod n²)
+        let gm = (BigUint::one() + BigUint::from(m) * &self.n) % &n_squared;
+        Ciphertext(gm * r.modpow(&self.n, &n_squared) % &n_squared)
+    }
+
+    /// 密文相加：E(a) · E(b) = E(a + b)
+    pub fn add(&self, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
+        Ciphertext(&a.0 * &b.0 % self.n_squared())
+    }
+
+    /// 密文乘以明文常数：E(a)^k = E(k · a)
+    pub fn mul_scalar(&self, c: &Ciphertext, k: u64) -> Ciphertext {
+        Ciphertext(c.0.modpow(&BigUint::from(k), &self.n_squared()))
+    }
+
+    /// 0 的密文，作为聚合初值
+    pub fn zero(&self) -> Ciphertext {
+        Ciphertext(BigUint::one())
+    }
+}
+
+/// 治理成员持有的私钥份额
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceKeyShare {
+    /// 治理成员公钥（多签成员）
+    pub member: String,
+    /// Shamir 横坐标（从 1 开始）
+    pub index: u32,
+    #[serde(with = "biguint_hex")]
+    pub value: BigUint,
+}
+
+/// 治理多签解密密钥的公开部分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceKey {
+    pub public_key: PaillierPublicKey,
+    /// 多签成员
+    pub members: Vec<String>,
+    /// 解密所需的最少份额
+    pub threshold: usize,
+    /// Shamir 共享使用的素数域
+    #[serde(with = "biguint_hex")]
+    pub share_modulus: BigUint,
+}
+
+impl GovernanceKey {
+    /// 生成 Paillier 密钥并把私钥拆分给治理成员，返回公开部分和各成员份额
+    pub fn generate(bits: u64, members: Vec<String>, threshold: usize) -> Result<(Self, Vec<GovernanceKeyShare>)> {
+        if threshold == 0 || threshold > members.len() {
+            return Err(anyhow!("门限 {} 无效（成员 {} 个）", threshold, members.len()));
+        }
+        let prime_bits = bits / 2;
+        let (p, q) = loop {
+            let p = random_prime(prime_bits);
+            let q = random_prime(prime_bits);
+            if p != q {
+                break (p, q);
+            }
+        };
+        let n = &p * &q;
+        let lambda = (&p - 1u32).lcm(&(&q - 1u32));
+
+        // λ < n，素数域比 n 多 64 位即可容纳
+        let share_modulus = random_prime(bits + 64);
+        let mut coefficients = vec![lambda];
+        for _ in 1..threshold {
+            coefficients.push(random_below(&share_modulus));
+        }
+        let shares = members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| {
+                let x = BigUint::from(i as u32 + 1);
+                let value = coefficients
+                    .iter()
+                    .rev()
+                    .fold(BigUint::zero(), |acc, c| (acc * &x + c) % &share_modulus);
+                GovernanceKeyShare {
+                    member: member.clone(),
+                    index: i as u32 + 1,
+                    value,
+                }
+            })
+            .collect();
+
+        Ok((
+            Self {
+                public_key: PaillierPublicKey { n },
+                members,
+                threshold,
+                share_modulus,
+            },
+            shares,
+        ))
+    }
+
+    /// 用治理成员份额恢复私钥并解密
+    pub fn decrypt(&self, ciphertexts: &[Ciphertext], shares: &[GovernanceKeyShare]) -> Result<Vec<u128>> {
+        let lambda = self.recover_lambda(shares)?;
+        let n = &self.public_key.n;
+        let n_squared = self.public_key.n_squared();
+        // g = n + 1 时 L(g^λ mod n²) = λ mod n
+        let mu = (&lambda % n)
+            .modinv(n)
+            .ok_or_else(|| anyhow!("治理份额无法恢复有效私钥"))?;
+
+        ciphertexts
+            .iter()
+            .map(|c| {
+                let u = c.0.modpow(&lambda, &n_squared);
+                let l = (u - 1u32) / n;
+                let m = l * &mu % n;
+                u128::try_from(m).map_err(|_| anyhow!("解密结果超出范围，份额可能有误"))
+            })
+            .collect()
+    }
+
+    fn recover_lambda(&self, shares: &[GovernanceKeyShare]) -> Result<BigUint> {
+        let mut seen = HashSet::new();
+        let valid: Vec<&GovernanceKeyShare> = shares
+            .iter()
+            .filter(|s| {
+                s.index >= 1
+                    && self.members.get(s.index as usize - 1) == Some(&s.member)
+                    && seen.insert(s.index)
+            })
+            .collect();
+        if valid.len() < self.threshold {
+            return Err(anyhow!(
+                "治理份额不足：需要 {}，有效 {}",
+                self.threshold,
+                valid.len()
+            ));
+        }
+
+        // 拉格朗日插值求 f(0)
+        let p = &self.share_modulus;
+        let used = &valid[..self.threshold];
+        let mut secret = BigUint::zero();
+        for share in used {
+            let xi = BigUint::from(share.index);
+            let mut numerator = BigUint::one();
+            let mut denominator = BigUint::one();
+            for other in used {
+                if other.index == share.index {
+                    continue;
+                }
+                let xj = BigUint::from(other.index);
+                numerator = numerator * (p - &xj) % p;
+                denominator = denominator * ((&xi + p - &xj) % p) % p;
+            }
+            let inverse = denominator
+                .modinv(p)
+                .ok_or_else(|| anyhow!("治理份额横坐标重复"))?;
+            secret = (secret + &share.value * numerator % p * inverse) % p;
+        }
+        Ok(secret)
+    }
+}
+
+/// 节点提交的加密评分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedScoreSubmission {
+    pub contribution_id: String,
+    pub node_id: String,
+    pub task_id: String,
+    /// 加密的奖励单位：(1 + 算力评分) × (1 + 0.05 × 小时数) × REWARD_UNIT_SCALE
+    pub encrypted_units: Ciphertext,
+}
+
+impl EncryptedScoreSubmission {
+    /// 节点侧：用治理公钥加密本次贡献的奖励单位
+    pub fn encrypt(public_key: &PaillierPublicKey, contribution: &ComputeContribution) -> Self {
+        Self {
+            contribution_id: contribution.id.clone(),
+            node_id: contribution.node_id.clone(),
+            task_id: contribution.task_id.clone(),
+            encrypted_units: public_key.encrypt(reward_units(contribution)),
+        }
+    }
+}
+
+/// 与 `ComputeCalculator::calculate_reward` 相同的乘数，以定点整数表示
+pub fn reward_units(contribution: &ComputeContribution) -> u64 {
+    let score_multiplier = 1.0 + contribution.compute_score.max(0.0);
+    let hours = contribution.duration_seconds as f64 / 3600.0;
+    let duration_multiplier = 1.0 + hours * 0.05;
+    (score_multiplier * duration_multiplier * REWARD_UNIT_SCALE as f64).round() as u64
+}
+
+/// 解密后的节点奖励
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecryptedReward {
+    pub node_id: String,
+    pub amount_lamports: u64,
+    pub contributions: usize,
+}
+
+/// 协调者侧的同态聚合器，只接触密文
+pub struct HomomorphicRewardAggregator {
+    key: GovernanceKey,
+    totals: BTreeMap<String, (Ciphertext, usize)>,
+    seen: HashSet<String>,
+}
+
+impl HomomorphicRewardAggregator {
+    pub fn new(key: GovernanceKey) -> Self {
+        Self {
+            key,
+            totals: BTreeMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    pub fn public_key(&self) -> &PaillierPublicKey {
+        &self.key.public_key
+    }
+
+    /// 累加一条加密提交，同一贡献记录只计一次
+    pub fn submit(&mut self, submission: &EncryptedScoreSubmission) -> bool {
+        if !self.seen.insert(submission.contribution_id.clone()) {
+            return false;
+        }
+        let pk = &self.key.public_key;
+        let entry = self
+            .totals
+            .entry(submission.node_id.clone())
+            .or_insert_with(|| (pk.zero(), 0));
+        entry.0 = pk.add(&entry.0, &submission.encrypted_units);
+        entry.1 += 1;
+        true
+    }
+
+    /// 各节点的加密奖励（已在密文上乘以基础奖励），交给治理多签解密
+    pub fn encrypted_rewards(&self, base_reward_per_compute_lamports: u64) -> Vec<(String, Ciphertext)> {
+        let pk = &self.key.public_key;
+        self.totals
+            .iter()
+            .map(|(node_id, (total, _))| (node_id.clone(), pk.mul_scalar(total, base_reward_per_compute_lamports)))
+            .collect()
+    }
+
+    /// 治理成员提交份额后解密每个节点的奖励
+    pub fn finalize(
+        &self,
+        base_reward_per_compute_lamports: u64,
+        shares: &[GovernanceKeyShare],
+    ) -> Result<Vec<DecryptedReward>> {
+        let encrypted = self.encrypted_rewards(base_reward_per_compute_lamports);
+        let ciphertexts: Vec<Ciphertext> = encrypted.iter().map(|(_, c)| c.clone()).collect();
+        let plaintexts = self.key.decrypt(&ciphertexts, shares)?;
+
+        Ok(encrypted
+            .into_iter()
+            .zip(plaintexts)
+            .map(|((node_id, _), scaled)| {
+                let contributions = self.totals.get(&node_id).map(|(_, count)| *count).unwrap_or(0);
+                DecryptedReward {
+                    node_id,
+                    amount_lamports: (scaled / REWARD_UNIT_SCALE as u128) as u64,
+                    contributions,
+                }
+            })
+            .collect())
+    }
+}
+
+fn random_below(bound: &BigUint) -> BigUint {
+    let bytes = (bound.bits() as usize).div_ceil(8) + 8;
+    let mut buf = vec![0u8; bytes];
+    rand::rng().fill_bytes(&mut buf);
+    BigUint::from_bytes_be(&buf) % bound
+}
+
+fn random_prime(bits: u64) -> BigUint {
+    loop {
+        let mut buf = vec![0u8; (bits as usize).div_ceil(8)];
+        rand::rng().fill_bytes(&mut buf);
+        let mut candidate = BigUint::from_bytes_be(&buf) >> (buf.len() as u64 * 8 - bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+fn is_probable_prime(n: &BigUint) -> bool {
+    const SMALL_PRIMES: [u32; 15] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+    if *n < BigUint::from(2u32) {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if *n == BigUint::from(p) {
+            return true;
+        }
+        if (n % p).is_zero() {
+            return false;
+        }
+    }
+
+    let one = BigUint::one();
+    let n_minus_one = n - &one;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    let range = n - 3u32;
+
+    'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+        let a = random_below(&range) + 2u32;
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&BigUint::from(2u32), n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contribution(id: &str, node_id: &str, score: f64) -> ComputeContribution {
+        ComputeContribution {
+            id: id.to_string(),
+            node_id: node_id.to_string(),
+            task_id: "task".to_string(),
+            start_timestamp: 0,
+            end_timestamp: 3600,
+            duration_seconds: 3600,
+            avg_gpu_usage_percent: 0.0,
+            gpu_memory_used_mb: 0,
+            avg_cpu_usage_percent: 50.0,
+            memory_used_mb: 512,
+            network_upload_mb: 0,
+            network_download_mb: 0,
+            samples_processed: 100,
+            batches_processed: 10,
+            compute_score: score,
+            manifest_hash: None,
+            dataset_manifest_hash: None,
+            quality_score: None,
+            energy_wh: None,
+        }
+    }
+
+    #[test]
+    fn test_encrypted_aggregation_requires_governance_threshold() {
+        let members: Vec<String> = ["gov-a", "gov-b", "gov-c"].iter().map(|s| s.to_string()).collect();
+        let (key, shares) = GovernanceKey::generate(256, members, 2).unwrap();
+        let mut aggregator = HomomorphicRewardAggregator::new(key.clone());
+
+        for c in [contribution("1", "node-a", 0.5), contribution("2", "node-a", 1.0), contribution("3", "node-b", 0.0)] {
+            let submission = EncryptedScoreSubmission::encrypt(aggregator.public_key(), &c);
+            assert!(aggregator.submit(&submission));
+            assert!(!aggregator.submit(&submission));
+        }
+
+        // 单个成员（例如协调者拿到的一份）无法解密
+        assert!(aggregator.finalize(1_000_000, &shares[..1]).is_err());
+
+        let rewards = aggregator.finalize(1_000_000, &shares[1..]).unwrap();
+        let expected_a = (1.5 * 1.05 * 1e6 + 2.0 * 1.05 * 1e6) as u64;
+        assert_eq!(rewards[0].node_id, "node-a");
+        assert_eq!(rewards[0].amount_lamports, expected_a);
+        assert_eq!(rewards[0].contributions, 2);
+        assert_eq!(rewards[1].amount_lamports, 1_050_000);
+    }
+}
@@ -0,0 +1,439 @@
+//! 多 RPC 端点故障切换
+//!
+//! 按优先级排列的 RPC 端点池：
+//! - 每个端点有独立的令牌桶限额，超出限额的端点本次跳过，全部超限时等待最早补充的令牌
+//! - 连接失败、5xx、节点落后等端点故障连续达到阈值后端点进入冷却，返回 429 的立即冷却；
+//!   冷却结束后重新参与选择，请求本身的错误（账户不存在、交易失败等）不切换端点
+//! - 后台探测定期查询各端点的 slot，落后最高 slot 过多的端点视为不健康
+//! - 获取 blockhash 等对延迟敏感的调用可以对冲：首选端点在对冲延迟内未返回时并行请求下一个端点，取先成功的结果
+//! - 需要交叉核对的查询可以分别请求每个健康端点，给出与多数矛盾结果的端点可以被调用方停用
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcError;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 连续失败多少次后进入冷却
+const FAILURE_THRESHOLD: u32 = 3;
+/// 故障冷却时间
+const FAILURE_COOLDOWN: Duration = Duration::from_secs(30);
+/// 给出与其他端点矛盾结果后的冷却时间
+const DISPUTED_COOLDOWN: Duration = Duration::from_secs(600);
+/// 被限流（429）后的冷却时间
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(10);
+/// 全部端点超出限额时最多等待的时间
+const MAX_BUDGET_WAIT: Duration = Duration::from_secs(2);
+/// 落后最高 slot 超过该值的端点视为不健康
+const MAX_SLOT_LAG: u64 = 150;
+/// 默认对冲延迟
+const DEFAULT_HEDGE_DELAY: Duration = Duration::from_millis(250);
+/// 对冲请求同时进行的最大端点数
+const HEDGE_FANOUT: usize = 2;
+/// Solana 节点落后或不健康时返回的 JSON-RPC 错误码
+const NODE_UNHEALTHY_CODE: i64 = -32005;
+
+/// RPC 端点配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcEndpointConfig {
+    pub url: String,
+    /// 每秒请求上限，None 表示不限
+    #[serde(default)]
+    pub requests_per_second: Option<f64>,
+}
+
+impl RpcEndpointConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            requests_per_second: None,
+        }
+    }
+}
+
+/// 端点状态快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// 成功请求的平滑延迟
+    pub latency_ms: Option<f64>,
+    /// 最近一次探测到的 slot
+    pub slot: Option<u64>,
+}
+
+/// 错误的责任方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// 请求本身的错误，换端点也一样
+    Request,
+    /// 端点故障，切换到下一个端点
+    Endpoint,
+    /// 端点限流
+    RateLimited,
+}
+
+fn classify(error: &ClientError) -> Failure {
+    match error.kind() {
+        ClientErrorKind::Io(_) => Failure::Endpoint,
+        ClientErrorKind::Reqwest(e) => match e.status().map(|status| status.as_u16()) {
+            Some(429) => Failure::RateLimited,
+            Some(status) if status < 500 => Failure::Request,
+            _ => Failure::Endpoint,
+        },
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => Failure::Endpoint,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) if *code == NODE_UNHEALTHY_CODE => {
+            Failure::Endpoint
+        }
+        _ => Failure::Request,
+    }
+}
+
+struct EndpointState {
+    tokens: f64,
+    refilled_at: Instant,
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    latency_ms: Option<f64>,
+    slot: Option<u64>,
+}
+
+struct Endpoint {
+    url: String,
+    requests_per_second: Option<f64>,
+    client: RpcClient,
+    state: Mutex<EndpointState>,
+}
+
+impl Endpoint {
+    fn new(config: &RpcEndpointConfig, commitment: CommitmentConfig) -> Self {
+        Self {
+            url: config.url.clone(),
+            requests_per_second: config.requests_per_second.filter(|rps| *rps > 0.0),
+            client: RpcClient::new_with_commitment(config.url.clone(), commitment),
+            state: Mutex::new(EndpointState {
+                tokens: config.requests_per_second.unwrap_or_default().max(1.0),
+                refilled_at: Instant::now(),
+                consecutive_failures: 0,
+                cooldown_until: None,
+                latency_ms: None,
+                slot: None,
+            }),
+        }
+    }
+
+    /// 消耗一个令牌，不足时返回需要等待的时间（桶容量为一秒的请求数）
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let Some(rps) = self.requests_per_second else {
+            return Ok(());
+        };
+        let mut state = self.state.lock();
+        let elapsed = now.saturating_duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rps).min(rps.max(1.0));
+        state.refilled_at = now;
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - state.tokens) / rps))
+        }
+    }
+
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.state.lock().cooldown_until.map_or(true, |until| until <= now)
+    }
+
+    fn record(&self, failure: Option<Failure>, latency: Duration) {
+        let mut state = self.state.lock();
+        match failure {
+            None | Some(Failure::Request) => {
+                let latency_ms = latency.as_secs_f64() * 1000.0;
+                state.latency_ms = Some(state.latency_ms.map_or(latency_ms, |avg| avg * 0.8 + latency_ms * 0.2));
+                state.consecutive_failures = 0;
+                state.cooldown_until = None;
+            }
+            Some(Failure::Endpoint) => {
+                state.consecutive_failures += 1;
+                if state.consecutive_failures >= FAILURE_THRESHOLD {
+                    state.cooldown_until = Some(Instant::now() + FAILURE_COOLDOWN);
+                }
+            }
+            Some(Failure::RateLimited) => {
+                state.cooldown_until = Some(Instant::now() + RATE_LIMIT_COOLDOWN);
+            }
+        }
+    }
+
+    fn call<T, F: Fn(&RpcClient) -> ClientResult<T> + ?Sized>(&self, f: &F) -> ClientResult<T> {
+        let started = Instant::now();
+        let result = f(&self.client);
+        self.record(result.as_ref().err().map(classify), started.elapsed());
+        if let Err(e) = &result {
+            log::debug!("[RPC] {} 请求失败: {}", self.url, e);
+        }
+        result
+    }
+}
+
+/// 按优先级排列的 RPC 端点池
+pub struct RpcPool {
+    endpoints: Vec<Arc<Endpoint>>,
+    hedge_delay: Duration,
+}
+
+impl RpcPool {
+    /// `endpoints` 按优先级排列，至少一个
+    pub fn new(endpoints: &[RpcEndpointConfig], commitment: CommitmentConfig) -> anyhow::Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow::anyhow!("至少需要配置一个 RPC 端点"));
+        }
+        Ok(Self {
+            endpoints: endpoints
+                .iter()
+                .map(|config| Arc::new(Endpoint::new(config, commitment)))
+                .collect(),
+            hedge_delay: DEFAULT_HEDGE_DELAY,
+        })
+    }
+
+    /// 设置对冲延迟
+    pub fn with_hedge_delay(mut self, hedge_delay: Duration) -> Self {
+        self.hedge_delay = hedge_delay;
+        self
+    }
+
+    /// 首选端点的 URL
+    pub fn primary_url(&self) -> &str {
+        &self.endpoints[0].url
+    }
+
+    /// 端点数量
+    pub fn endpoint_count(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    /// 候选顺序：健康端点按优先级在前，冷却中的端点按冷却结束时间排在最后作为兜底
+    fn candidates(&self) -> Vec<Arc<Endpoint>> {
+        let now = Instant::now();
+        let (mut healthy, mut cooling): (Vec<_>, Vec<_>) =
+            self.endpoints.iter().cloned().partition(|endpoint| endpoint.is_healthy(now));
+        cooling.sort_by_key(|endpoint| endpoint.state.lock().cooldown_until);
+        healthy.append(&mut cooling);
+        healthy
+    }
+
+    /// 按优先级依次尝试，端点故障时切换到下一个；健康端点都超出限额时短暂等待令牌补充，不动用冷却中的端点
+    pub fn call<T>(&self, f: impl Fn(&RpcClient) -> ClientResult<T>) -> ClientResult<T> {
+        let deadline = Instant::now() + MAX_BUDGET_WAIT;
+        let mut failed: Vec<Arc<Endpoint>> = Vec::new();
+        let mut last_error = None;
+        loop {
+            let now = Instant::now();
+            let mut wait: Option<Duration> = None;
+            for endpoint in self.candidates() {
+                if failed.iter().any(|f| Arc::ptr_eq(f, &endpoint)) {
+                    continue;
+                }
+                // 冷却中的端点排在最后，只在没有健康端点可等时兜底
+                if wait.is_some() && !endpoint.is_healthy(now) {
+                    break;
+                }
+                if let Err(retry_after) = endpoint.try_acquire(now) {
+                    wait = Some(wait.map_or(retry_after, |w| w.min(retry_after)));
+                    continue;
+                }
+                match endpoint.call(&f) {
+                    Ok(value) => return Ok(value),
+                    Err(e) if classify(&e) == Failure::Request => return Err(e),
+                    Err(e) => {
+                        failed.push(endpoint);
+                        last_error = Some(e);
+                    }
+                }
+            }
+            match wait {
+                Some(wait) if Instant::now() + wait <= deadline => std::thread::sleep(wait),
+                _ => return Err(last_error.unwrap_or_else(exhausted)),
+            }
+        }
+    }
+
+    /// 分别请求每个健康且未超出限额的端点，返回 (URL, 结果)，用于交叉核对
+    pub fn call_each<T>(&self, f: impl Fn(&RpcClient) -> ClientResult<T>) -> Vec<(String, ClientResult<T>)> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_healthy(now) && endpoint.try_acquire(now).is_ok())
+            .map(|endpoint| (endpoint.url.clone(), endpoint.call(&f)))
+            .collect()
+    }
+
+    /// 停用给出矛盾结果的端点一段时间
+    pub fn penalize(&self, url: &str) {
+        if let Some(endpoint) = self.endpoints.iter().find(|endpoint| endpoint.url == url) {
+            log::warn!("[RPC] {} 返回的结果与其他端点矛盾，暂停使用", url);
+            endpoint.state.lock().cooldown_until = Some(Instant::now() + DISPUTED_COOLDOWN);
+        }
+    }
+
+    /// 对冲请求：首选端点在对冲延迟内未返回时并行请求下一个端点，取先成功的结果
+    pub async fn hedged<T, F>(&self, f: F) -> ClientResult<T>
+    where
+        T: Send + 'static,
+        F: Fn(&RpcClient) -> ClientResult<T> + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let mut candidates = self
+            .candidates()
+            .into_iter()
+            .filter(|endpoint| endpoint.try_acquire(Instant::now()).is_ok());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut pending = 0;
+        let mut last_error = None;
+        loop {
+            if pending < HEDGE_FANOUT {
+                if let Some(endpoint) = candidates.next() {
+                    let f = Arc::clone(&f);
+                    let tx = tx.clone();
+                    pending += 1;
+                    tokio::task::spawn_blocking(move || {
+                        let _ = tx.send(endpoint.call(f.as_ref()));
+                    });
+                }
+            }
+            if pending == 0 {
+                return Err(last_error.unwrap_or_else(exhausted));
+            }
+            match tokio::time::timeout(self.hedge_delay, rx.recv()).await {
+                Ok(Some(Ok(value))) => return Ok(value),
+                Ok(Some(Err(e))) => {
+                    pending -= 1;
+                    if classify(&e) == Failure::Request {
+                        return Err(e);
+                    }
+                    last_error = Some(e);
+                }
+                Ok(None) => return Err(last_error.unwrap_or_else(exhausted)),
+                // 对冲延迟已过，下一轮启动下一个端点
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// 探测一轮各端点的 slot，落后最高 slot 过多的端点进入冷却
+    pub fn probe(&self) {
+        let slots: Vec<Option<u64>> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                endpoint.try_acquire(Instant::now()).ok()?;
+                let slot = endpoint.call(&|rpc: &RpcClient| rpc.get_slot()).ok()?;
+                endpoint.state.lock().slot = Some(slot);
+                Some(slot)
+            })
+            .collect();
+        let Some(highest) = slots.iter().flatten().max().copied() else {
+            return;
+        };
+        for (endpoint, slot) in self.endpoints.iter().zip(slots) {
+            if slot.is_some_and(|slot| highest.saturating_sub(slot) > MAX_SLOT_LAG) {
+                log::warn!("[RPC] {} 落后最高 slot {} 个，暂停使用", endpoint.url, highest - slot.unwrap_or_default());
+                endpoint.state.lock().cooldown_until = Some(Instant::now() + FAILURE_COOLDOWN);
+            }
+        }
+    }
+
+    /// 启动后台健康探测
+    pub fn spawn_health_probe(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let pool = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let probe = Arc::clone(&pool);
+                if let Err(e) = tokio::task::spawn_blocking(move || probe.probe()).await {
+                    log::warn!("[RPC] 健康探测失败: {}", e);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    /// 各端点的状态
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let state = endpoint.state.lock();
+                EndpointStatus {
+                    url: endpoint.url.clone(),
+                    healthy: state.cooldown_until.map_or(true, |until| until <= now),
+                    consecutive_failures: state.consecutive_failures,
+                    latency_ms: state.latency_ms,
+                    slot: state.slot,
+                }
+            })
+            .collect()
+    }
+}
+
+fn exhausted() -> ClientError {
+    ClientErrorKind::Custom("所有 RPC 端点都不可用或已超出限额".to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn io_error() -> ClientError {
+        ClientErrorKind::Io(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused")).into()
+    }
+
+    #[test]
+    fn test_fails_over_and_cools_down_failing_endpoint() {
+        let endpoints = [
+            RpcEndpointConfig::new("http://primary.invalid"),
+            RpcEndpointConfig::new("http://backup.invalid"),
+        ];
+        let pool = RpcPool::new(&endpoints, CommitmentConfig::confirmed()).unwrap();
+        let primary_calls = std::cell::Cell::new(0);
+        let down = |rpc: &RpcClient| match rpc.url().as_str() {
+            "http://primary.invalid" => {
+                primary_calls.set(primary_calls.get() + 1);
+                Err(io_error())
+            }
+            url => Ok(url.to_string()),
+        };
+        for _ in 0..FAILURE_THRESHOLD {
+            assert_eq!(pool.call(down).unwrap(), "http://backup.invalid");
+        }
+        assert!(!pool.status()[0].healthy);
+
+        // 冷却中的首选端点不再被请求
+        assert_eq!(pool.call(down).unwrap(), "http://backup.invalid");
+        assert_eq!(primary_calls.get(), FAILURE_THRESHOLD);
+    }
+
+    #[test]
+    fn test_rate_limit_waits_and_request_errors_do_not_fail_over() {
+        let endpoints = [RpcEndpointConfig {
+            url: "http://limited.invalid".to_string(),
+            requests_per_second: Some(4.0),
+        }];
+        let pool = RpcPool::new(&endpoints, CommitmentConfig::confirmed()).unwrap();
+        let started = Instant::now();
+        for _ in 0..5 {
+            pool.call(|_| Ok(())).unwrap();
+        }
+        // 桶容量 4 个，第 5 次等待约 250ms 的补充
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        let result: ClientResult<()> = pool.call(|_| Err(ClientErrorKind::Custom("account not found".to_string()).into()));
+        assert!(result.is_err());
+        assert!(pool.status()[0].healthy);
+        assert_eq!(pool.status()[0].consecutive_failures, 0);
+    }
+}
@@ -0,0 +1,353 @@
+//! 训练任务贡献的自动上链
+//!
+//! 训练引擎完成任务后，由算力跟踪器根据任务统计生成贡献记录并写入本地发件箱，
+//! 后台任务按顺序经 `SolanaClient` 签名提交，失败时指数退避重试。
+//! 发件箱持久化到文件，离线期间积压的记录在重启后继续提交。
+//! 设置统计数据库后，入队、失败和上链结果会同步记录，供桌面端查询。
+
+use anyhow::Result;
+use chrono::Utc;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use super::client::SolanaClient;
+use super::compute::ComputeTracker;
+use super::types::ComputeContribution;
+use crate::stats::StatsStore;
+use crate::training::{TaskCompletion, TaskCompletionSink};
+
+/// 提交器配置
+#[derive(Debug, Clone)]
+pub struct SubmitterConfig {
+    /// 发件箱文件（每行一条 JSON 记录）
+    pub outbox_path: PathBuf,
+    /// 首次重试的等待时间
+    pub initial_backoff: Duration,
+    /// 重试等待时间上限
+    pub max_backoff: Duration,
+}
+
+impl Default for SubmitterConfig {
+    fn default() -> Self {
+        Self {
+            outbox_path: PathBuf::from("solana_outbox.jsonl"),
+            initial_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(300),
+        }
+    }
+}
+
+impl SubmitterConfig {
+    /// 第 `attempts` 次失败后的等待时间
+    pub fn backoff_delay(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// 发件箱中待提交的贡献
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub contribution: ComputeContribution,
+    /// 已失败的提交次数
+    pub attempts: u32,
+    /// 入队时间（Unix 秒）
+    pub queued_at: i64,
+    /// 最近一次失败原因
+    pub last_error: Option<String>,
+}
+
+/// 持久化的贡献发件箱
+pub struct ContributionOutbox {
+    path: PathBuf,
+    entries: Mutex<VecDeque<OutboxEntry>>,
+}
+
+impl ContributionOutbox {
+    /// 打开发件箱，载入上次未提交的记录
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = VecDeque::new();
+        if path.exists() {
+            for line in std::fs::read_to_string(&path)?.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str::<OutboxEntry>(line) {
+                    Ok(entry) => entries.push_back(entry),
+                    Err(e) => log::warn!("跳过无法解析的发件箱记录: {}", e),
+                }
+            }
+        }
+        if !entries.is_empty() {
+            log::info!("发件箱中有 {} 条待提交的贡献", entries.len());
+        }
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// 追加贡献记录
+    pub fn push(&self, contribution: ComputeContribution) -> Result<()> {
+        let mut entries = self.entries.lock();
+        entries.push_back(OutboxEntry {
+            contribution,
+            attempts: 0,
+            queued_at: Utc::now().timestamp(),
+            last_error: None,
+        });
+        self.persist(&entries)
+    }
+
+    /// 队首记录
+    pub fn front(&self) -> Option<OutboxEntry> {
+        self.entries.lock().front().cloned()
+    }
+
+    /// 移除已提交的记录
+    pub fn remove(&self, contribution_id: &str) -> Result<()> {
+        let mut entries = self.entries.lock();
+        entries.retain(|e| e.contribution.id != contribution_id);
+        self.persist(&entries)
+    }
+
+    /// 记录队首提交失败，返回累计失败次数
+    pub fn record_failure(&self, contribution_id: &str, error: String) -> Result<u32> {
+        let mut entries = self.entries.lock();
+        let attempts = match entries.iter_mut().find(|e| e.contribution.id == contribution_id) {
+            Some(entry) => {
+                entry.attempts += 1;
+                entry.last_error = Some(error);
+                entry.attempts
+            }
+            None => 0,
+        };
+        self.persist(&entries)?;
+        Ok(attempts)
+    }
+
+    /// 全部待提交记录的快照
+    pub fn entries(&self) -> Vec<OutboxEntry> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// 写入临时文件后替换，避免中途崩溃留下半截文件
+    fn persist(&self, entries: &VecDeque<OutboxEntry>) -> Result<()> {
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// 训练任务完成后自动提交贡献的接收方
+pub struct ContributionSubmitter {
+    client: Arc<SolanaClient>,
+    tracker: Arc<RwLock<ComputeTracker>>,
+    outbox: ContributionOutbox,
+    wake: Notify,
+    config: SubmitterConfig,
+    stats_store: Option<Arc<StatsStore>>,
+}
+
+/// 统计数据库中贡献提交的类型标识
+const SUBMISSION_KIND: &str = "contribution";
+
+impl ContributionSubmitter {
+    pub fn new(client: Arc<SolanaClient>, config: SubmitterConfig) -> Result<Self> {
+        let outbox = ContributionOutbox::open(&config.outbox_path)?;
+        Ok(Self {
+            tracker: client.get_compute_tracker(),
+            client,
+            outbox,
+            wake: Notify::new(),
+            config,
+            stats_store: None,
+        })
+    }
+
+    /// 同步记录提交状态到本地统计数据库
+    pub fn with_stats_store(self, store: Arc<StatsStore>) -> Self {
+        // 发件箱中已有的记录补录到数据库（已存在的记录不受影响）
+        for entry in self.outbox.entries() {
+            if let Err(e) = store.enqueue_submission(&entry.contribution.id, SUBMISSION_KIND, &entry.contribution, entry.queued_at) {
+                log::warn!("更新统计数据库失败: {}", e);
+            }
+        }
+        Self {
+            stats_store: Some(store),
+            ..self
+        }
+    }
+
+    fn with_store(&self, action: impl FnOnce(&StatsStore) -> Result<()>) {
+        if let Some(store) = &self.stats_store {
+            if let Err(e) = action(store) {
+                log::warn!("更新统计数据库失败: {}", e);
+            }
+        }
+    }
+
+    /// 发件箱
+    pub fn outbox(&self) -> &ContributionOutbox {
+        &self.outbox
+    }
+
+    /// 启动后台提交任务
+    pub fn spawn(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let submitter = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match submitter.drain().await {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => submitter.wake.notified().await,
+                }
+            }
+        })
+    }
+
+    /// 按顺序提交发件箱中的记录；遇到失败时返回退避等待时间，全部提交后返回 None
+    async fn drain(&self) -> Option<Duration> {
+        while let Some(entry) = self.outbox.front() {
+            let id = entry.contribution.id.clone();
+            let error = match self.client.report_compute_contribution(entry.contribution).await {
+                Ok(result) if result.success => {
+                    log::info!("贡献 {} 已上链: {}", id, result.signature);
+                    self.with_store(|store| store.mark_submitted(&id, &result.signature, Utc::now().timestamp()));
+                    if let Err(e) = self.outbox.remove(&id) {
+                        log::warn!("更新发件箱失败: {}", e);
+                    }
+                    continue;
+                }
+                Ok(result) => result.error.unwrap_or_else(|| "unknown error".to_string()),
+                Err(e) => e.to_string(),
+            };
+
+            let attempts = self.outbox.record_failure(&id, error.clone()).unwrap_or_else(|e| {
+                log::warn!("更新发件箱失败: {}", e);
+                entry.attempts + 1
+            });
+            self.with_store(|store| store.record_submission_failure(&id, &error));
+            let delay = self.config.backoff_delay(attempts);
+            log::warn!("贡献 {} 提交失败（第 {} 次）: {}，{:?} 后重试", id, attempts, error, delay);
+            return Some(delay);
+        }
+        None
+    }
+}
+
+impl TaskCompletionSink for ContributionSubmitter {
+    fn task_started(&self, task_id: &str, _manifest_hash: Option<&str>) {
+        let mut tracker = self.tracker.write();
+        if tracker.is_task_active() {
+            let _ = tracker.cancel_task();
+        }
+        if let Err(e) = tracker.start_task(task_id.to_string()) {
+            log::warn!("无法开始跟踪任务 {}: {}", task_id, e);
+        }
+    }
+
+    fn task_aborted(&self, task_id: &str, reason: &str) {
+        let mut tracker = self.tracker.write();
+        if tracker.get_current_task_id().map(String::as_str) == Some(task_id) {
+            let _ = tracker.cancel_task();
+        }
+        log::warn!("任务 {} 已中止，不提交贡献: {}", task_id, reason);
+    }
+
+    fn task_completed(&self, completion: &TaskCompletion) {
+        let contribution = self.tracker.write().complete_task(
+            completion.samples_processed,
+            completion.batches_processed,
+            0,
+            0,
+        );
+        match contribution {
+            Ok(mut contribution) => {
+                if contribution.manifest_hash.is_none() {
+                    contribution.manifest_hash = completion.manifest_hash.clone();
+                }
+                contribution.dataset_manifest_hash = completion.dataset_manifest_hash.clone();
+                contribution.quality_score = completion.quality_score.map(|score| score as f32);
+                contribution.energy_wh = completion.energy_wh;
+                self.with_store(|store| {
+                    store.enqueue_submission(&contribution.id, SUBMISSION_KIND, &contribution, Utc::now().timestamp())
+                });
+                match self.outbox.push(contribution) {
+                    Ok(()) => self.wake.notify_one(),
+                    Err(e) => log::error!("贡献写入发件箱失败: {}", e),
+                }
+            }
+            Err(e) => log::warn!("任务 {} 未生成贡献记录: {}", completion.task_id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contribution(id: &str) -> ComputeContribution {
+        ComputeContribution {
+            id: id.to_string(),
+            node_id: "node".to_string(),
+            task_id: "task".to_string(),
+            start_timestamp: 0,
+            end_timestamp: 60,
+            duration_seconds: 60,
+            avg_gpu_usage_percent: 0.0,
+            gpu_memory_used_mb: 0,
+            avg_cpu_usage_percent: 30.0,
+            memory_used_mb: 0,
+            network_upload_mb: 0,
+            network_download_mb: 0,
+            samples_processed: 100,
+            batches_processed: 4,
+            compute_score: 0.5,
+            manifest_hash: None,
+            dataset_manifest_hash: None,
+            quality_score: None,
+            energy_wh: None,
+        }
+    }
+
+    #[test]
+    fn test_outbox_survives_restart_and_backoff_grows() {
+        let path = std::env::temp_dir().join(format!("williw-outbox-{}.jsonl", uuid::Uuid::new_v4()));
+        let outbox = ContributionOutbox::open(&path).unwrap();
+        outbox.push(contribution("a")).unwrap();
+        outbox.push(contribution("b")).unwrap();
+        assert_eq!(outbox.record_failure("a", "offline".to_string()).unwrap(), 1);
+        drop(outbox);
+
+        let reopened = ContributionOutbox::open(&path).unwrap();
+        assert_eq!(reopened.len(), 2);
+        let front = reopened.front().unwrap();
+        assert_eq!(front.contribution.id, "a");
+        assert_eq!(front.attempts, 1);
+        reopened.remove("a").unwrap();
+        assert_eq!(reopened.front().unwrap().contribution.id, "b");
+        let _ = std::fs::remove_file(&path);
+
+        let config = SubmitterConfig::default();
+        assert_eq!(config.backoff_delay(1), Duration::from_secs(2));
+        assert_eq!(config.backoff_delay(3), Duration::from_secs(8));
+        assert_eq!(config.backoff_delay(30), Duration::from_secs(300));
+    }
+}
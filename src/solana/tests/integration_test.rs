@@ -123,6 +123,10 @@ mod tests {
                 samples_processed: 10000,
                 batches_processed: 50,
                 compute_score: 2.5,
+                manifest_hash: None,
+                dataset_manifest_hash: None,
+                quality_score: None,
+                energy_wh: None,
             };
             
             match client.report_compute_contribution(contribution).await {
@@ -202,6 +202,10 @@ mod tests {
             samples_processed: 10000,
             batches_processed: 50,
             compute_score: 2.5,
+            manifest_hash: None,
+            dataset_manifest_hash: None,
+            quality_score: None,
+            energy_wh: None,
         };
         
         // 验证数据完整性
@@ -233,9 +237,12 @@ mod tests {
         
         let config = SolanaConfig {
             rpc_url: "invalid_url".to_string(),
+            rpc_requests_per_second: None,
+            fallback_rpc_endpoints: Vec::new(),
             ws_url: None,
             program_id: "invalid_program_id".to_string(),
             payer_keypair_base58: None,
+            payer_vault_entry: None,
             network: SolanaNetwork::Localnet,
         };
         
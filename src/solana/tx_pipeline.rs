@@ -0,0 +1,416 @@
+//! 交易提交流水线
+//!
+//! 所有上链交易经此提交：
+//! 1. 按指令类型设置计算单元上限（见 [`COMPUTE_UNIT_BUDGETS`]），未知指令使用运行时默认值
+//! 2. 用 `getRecentPrioritizationFees` 查询交易写入账户的近期优先费，取分位数作为计算单元价格
+//! 3. 发送后轮询签名状态；blockhash 过期仍未落地时以更高的优先费、新的 blockhash 重新签名发送。
+//!    只有确认区块高度已超过 blockhash 有效期时才判定过期，避免同一操作落地两次
+//! 4. 统计落地延迟、尝试次数和优先费，供监控读取
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::client::PriorityFee;
+use super::programs::anchor_discriminator;
+use super::rpc_pool::RpcPool;
+use super::wallet::{sign_transaction, WalletSigner};
+
+/// 各 Anchor 指令的计算单元上限
+pub const COMPUTE_UNIT_BUDGETS: [(&str, u32); 20] = [
+    ("register_node", 40_000),
+    ("submit_heartbeat", 10_000),
+    ("mark_inactive", 15_000),
+    ("update_reputation", 15_000),
+    ("slash_node", 40_000),
+    ("appeal_slash", 30_000),
+    ("record_contribution", 60_000),
+    ("verify_contribution", 80_000),
+    ("finalize_epoch", 30_000),
+    ("distribute_epoch_reward", 40_000),
+    ("claim_rewards", 60_000),
+    ("stake_tokens", 40_000),
+    ("create_proposal", 60_000),
+    ("vote_on_proposal", 30_000),
+    ("finalize_proposal", 30_000),
+    ("execute_proposal", 300_000),
+    ("approve_multisig_transaction", 30_000),
+    ("submit_report", 40_000),
+    ("import_oracle_compute", 60_000),
+    ("verify_proof", 400_000),
+];
+
+/// 未登记指令的计算单元上限（与运行时默认值一致）
+const DEFAULT_INSTRUCTION_UNITS: u32 = 200_000;
+/// 单笔交易的计算单元上限
+const MAX_TRANSACTION_UNITS: u32 = 1_400_000;
+/// 计算单元上限的余量（百分比）
+const UNIT_MARGIN_PERCENT: u32 = 10;
+/// 优先费查询最多携带的账户数
+const MAX_FEE_ACCOUNTS: usize = 128;
+/// 落地统计保留的最近交易数
+const STATS_WINDOW: usize = 256;
+
+/// 流水线配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    /// 近期优先费的分位数（0-100）
+    pub fee_percentile: u8,
+    /// 计算单元价格下限（micro-lamports）
+    pub min_unit_price: u64,
+    /// 计算单元价格上限（micro-lamports）
+    pub max_unit_price: u64,
+    /// 每次过期重发时价格上调的百分比
+    pub escalation_percent: u64,
+    /// 最多发送次数
+    pub max_attempts: u32,
+    /// 签名状态轮询间隔（毫秒）
+    pub poll_interval_ms: u64,
+    /// 单次发送等待落地或过期的最长时间（秒），超时视为状态未知，不再重发
+    pub confirm_timeout_secs: u64,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            fee_percentile: 75,
+            min_unit_price: 1_000,
+            max_unit_price: 2_000_000,
+            escalation_percent: 50,
+            max_attempts: 4,
+            poll_interval_ms: 500,
+            confirm_timeout_secs: 90,
+        }
+    }
+}
+
+/// 落地的交易
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Landed {
+    pub signature: Signature,
+    pub slot: u64,
+    pub attempts: u32,
+    /// 最终使用的计算单元价格（micro-lamports）
+    pub unit_price: u64,
+    /// 从首次发送到确认的时间
+    pub latency: Duration,
+}
+
+/// 落地统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LandingStats {
+    pub submitted: u64,
+    pub landed: u64,
+    /// 执行失败的交易
+    pub failed: u64,
+    /// 因 blockhash 过期而重发的次数
+    pub expired_attempts: u64,
+    /// 以下为最近落地交易的统计
+    pub p50_latency_ms: u64,
+    pub p95_latency_ms: u64,
+    pub mean_attempts: f64,
+    pub median_unit_price: u64,
+}
+
+#[derive(Default)]
+struct StatsWindow {
+    totals: LandingStats,
+    recent: VecDeque<(u64, u32, u64)>,
+}
+
+/// 单次发送的结果
+enum Outcome {
+    Landed(u64),
+    Failed(String),
+    Expired,
+}
+
+/// 按最近邻秩取分位数，空输入返回 0
+pub fn percentile(values: &[u64], pct: u8) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let rank = (pct.min(100) as usize * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// 按指令类型累加计算单元上限（含余量）
+pub fn compute_unit_limit(instructions: &[Instruction]) -> u32 {
+    let units: u32 = instructions
+        .iter()
+        .map(|instruction| {
+            let discriminator = instruction.data.get(..8);
+            COMPUTE_UNIT_BUDGETS
+                .iter()
+                .find(|(name, _)| discriminator == Some(&anchor_discriminator(name)[..]))
+                .map_or(DEFAULT_INSTRUCTION_UNITS, |(_, units)| *units)
+        })
+        .sum();
+    (units + units * UNIT_MARGIN_PERCENT / 100).min(MAX_TRANSACTION_UNITS)
+}
+
+/// 交易提交流水线
+pub struct TxPipeline {
+    rpc: Arc<RpcPool>,
+    config: PipelineConfig,
+    stats: Mutex<StatsWindow>,
+    /// 故障注入器（注入 RPC 延迟）
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<crate::chaos::FaultInjector>>,
+}
+
+impl TxPipeline {
+    pub fn new(rpc: Arc<RpcPool>, config: PipelineConfig) -> Self {
+        Self {
+            rpc,
+            config,
+            stats: Mutex::new(StatsWindow::default()),
+            #[cfg(feature = "chaos")]
+            chaos: None,
+        }
+    }
+
+    /// 设置故障注入器，注入的 RPC 延迟作用于每次发送
+    #[cfg(feature = "chaos")]
+    pub fn set_fault_injector(&mut self, injector: Arc<crate::chaos::FaultInjector>) {
+        self.chaos = Some(injector);
+    }
+
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: PipelineConfig) {
+        self.config = config;
+    }
+
+    /// 按交易写入账户的近期优先费估算计算单元价格
+    pub fn estimate_unit_price(&self, instructions: &[Instruction]) -> Result<u64> {
+        let mut accounts: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|instruction| instruction.accounts.iter())
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        accounts.sort();
+        accounts.dedup();
+        accounts.truncate(MAX_FEE_ACCOUNTS);
+        let fees: Vec<u64> = self
+            .rpc
+            .call(|rpc| rpc.get_recent_prioritization_fees(&accounts))?
+            .iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        Ok(percentile(&fees, self.config.fee_percentile).clamp(self.config.min_unit_price, self.config.max_unit_price))
+    }
+
+    fn escalate(&self, price: u64) -> u64 {
+        let raised = price + price * self.config.escalation_percent / 100;
+        raised.max(price + 1).min(self.config.max_unit_price)
+    }
+
+    /// 提交交易直到落地；`fee` 中显式设置的价格或上限优先于估算值
+    pub async fn submit(
+        &self,
+        payer: &dyn WalletSigner,
+        instructions: &[Instruction],
+        extra_signers: &[&Keypair],
+        fee: PriorityFee,
+    ) -> Result<Landed> {
+        let unit_limit = fee.compute_unit_limit.unwrap_or_else(|| compute_unit_limit(instructions));
+        let mut unit_price = match fee.compute_unit_price_micro_lamports {
+            0 => self.estimate_unit_price(instructions).unwrap_or_else(|e| {
+                log::warn!("估算优先费失败，使用下限: {}", e);
+                self.config.min_unit_price
+            }),
+            price => price,
+        };
+        self.stats.lock().totals.submitted += 1;
+        let started = Instant::now();
+
+        for attempt in 1..=self.config.max_attempts.max(1) {
+            #[cfg(feature = "chaos")]
+            if let Some(chaos) = &self.chaos {
+                chaos.delay_rpc().await;
+            }
+            let (blockhash, last_valid_height) = self
+                .rpc
+                .hedged(|rpc| rpc.get_latest_blockhash_with_commitment(CommitmentConfig::confirmed()))
+                .await
+                .map_err(|e| anyhow!("Failed to get recent blockhash: {}", e))?;
+
+            let budget = PriorityFee {
+                compute_unit_price_micro_lamports: unit_price,
+                compute_unit_limit: Some(unit_limit),
+            };
+            let mut all_instructions = budget.instructions();
+            all_instructions.extend(instructions.iter().cloned());
+            // 硬件钱包和远程审批需要用户确认，每次发送都对新的 blockhash 重新签名
+            let transaction = sign_transaction(payer, &all_instructions, extra_signers, blockhash).await?;
+            // 预检失败（程序错误）不会重试
+            let signature = self.rpc.call(|rpc| {
+                rpc.send_transaction_with_config(
+                    &transaction,
+                    RpcSendTransactionConfig {
+                        preflight_commitment: Some(CommitmentConfig::confirmed().commitment),
+                        ..Default::default()
+                    },
+                )
+            })?;
+
+            match self.await_outcome(&signature, last_valid_height).await? {
+                Outcome::Landed(slot) => {
+                    let landed = Landed {
+                        signature,
+                        slot,
+                        attempts: attempt,
+                        unit_price,
+                        latency: started.elapsed(),
+                    };
+                    self.record_landed(&landed);
+                    log::info!(
+                        "交易 {} 已落地: 第 {} 次发送，优先费 {} micro-lamports/CU，耗时 {:?}",
+                        signature,
+                        attempt,
+                        unit_price,
+                        landed.latency
+                    );
+                    return Ok(landed);
+                }
+                Outcome::Failed(error) => {
+                    self.stats.lock().totals.failed += 1;
+                    return Err(anyhow!("Transaction {} failed: {}", signature, error));
+                }
+                Outcome::Expired => {
+                    self.stats.lock().totals.expired_attempts += 1;
+                    let raised = self.escalate(unit_price);
+                    log::warn!(
+                        "交易 {} 的 blockhash 已过期未落地（第 {}/{} 次），优先费 {} -> {} micro-lamports/CU 后重发",
+                        signature,
+                        attempt,
+                        self.config.max_attempts,
+                        unit_price,
+                        raised
+                    );
+                    unit_price = raised;
+                }
+            }
+        }
+        self.stats.lock().totals.failed += 1;
+        Err(anyhow!("Transaction expired after {} attempts", self.config.max_attempts))
+    }
+
+    /// 轮询签名状态，直到确认、执行失败或 blockhash 确定过期
+    async fn await_outcome(&self, signature: &Signature, last_valid_height: u64) -> Result<Outcome> {
+        let deadline = Instant::now() + Duration::from_secs(self.config.confirm_timeout_secs);
+        let poll = Duration::from_millis(self.config.poll_interval_ms.max(50));
+        let status = || self.rpc.call(|rpc| rpc.get_signature_statuses(&[*signature]));
+        loop {
+            tokio::time::sleep(poll).await;
+            match status() {
+                Ok(response) => match response.value.into_iter().next().flatten() {
+                    Some(status) if status.err.is_some() => {
+                        return Ok(Outcome::Failed(format!("{:?}", status.err)));
+                    }
+                    Some(status) if status.satisfies_commitment(CommitmentConfig::confirmed()) => {
+                        return Ok(Outcome::Landed(status.slot));
+                    }
+                    // 已处理但尚未确认，继续等待
+                    Some(_) => continue,
+                    None => {}
+                },
+                Err(e) => log::debug!("查询交易 {} 状态失败: {}", signature, e),
+            }
+            if let Ok(height) = self.rpc.call(|rpc| rpc.get_block_height()) {
+                if height > last_valid_height {
+                    // 过期判定前再确认一次，避免错过刚落地的交易
+                    let landed = status()?.value.into_iter().next().flatten();
+                    return Ok(match landed {
+                        Some(status) if status.err.is_some() => Outcome::Failed(format!("{:?}", status.err)),
+                        Some(status) => Outcome::Landed(status.slot),
+                        None => Outcome::Expired,
+                    });
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!("交易 {} 状态未知：等待确认超时，不再重发", signature));
+            }
+        }
+    }
+
+    fn record_landed(&self, landed: &Landed) {
+        let mut stats = self.stats.lock();
+        stats.totals.landed += 1;
+        if stats.recent.len() == STATS_WINDOW {
+            stats.recent.pop_front();
+        }
+        stats
+            .recent
+            .push_back((landed.latency.as_millis() as u64, landed.attempts, landed.unit_price));
+    }
+
+    /// 落地统计
+    pub fn stats(&self) -> LandingStats {
+        let stats = self.stats.lock();
+        let latencies: Vec<u64> = stats.recent.iter().map(|(latency, ..)| *latency).collect();
+        let prices: Vec<u64> = stats.recent.iter().map(|(.., price)| *price).collect();
+        let attempts: u32 = stats.recent.iter().map(|(_, attempts, _)| *attempts).sum();
+        LandingStats {
+            p50_latency_ms: percentile(&latencies, 50),
+            p95_latency_ms: percentile(&latencies, 95),
+            mean_attempts: if stats.recent.is_empty() {
+                0.0
+            } else {
+                attempts as f64 / stats.recent.len() as f64
+            },
+            median_unit_price: percentile(&prices, 50),
+            ..stats.totals.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    #[test]
+    fn test_percentile_and_escalation() {
+        assert_eq!(percentile(&[], 75), 0);
+        assert_eq!(percentile(&[5, 1, 3, 2, 4], 50), 3);
+        assert_eq!(percentile(&[0, 0, 0, 100], 75), 0);
+        assert_eq!(percentile(&[0, 0, 0, 100], 100), 100);
+
+        let endpoints = [super::super::RpcEndpointConfig::new("http://localhost:8899")];
+        let pool = Arc::new(RpcPool::new(&endpoints, CommitmentConfig::confirmed()).unwrap());
+        let pipeline = TxPipeline::new(pool, PipelineConfig::default());
+        assert_eq!(pipeline.escalate(10_000), 15_000);
+        assert_eq!(pipeline.escalate(0), 1);
+        assert_eq!(pipeline.escalate(1_900_000), PipelineConfig::default().max_unit_price);
+    }
+
+    #[test]
+    fn test_compute_unit_limit_by_instruction_type() {
+        let program = Pubkey::new_unique();
+        let anchor = |name: &str| Instruction::new_with_bytes(program, &anchor_discriminator(name), vec![]);
+        let heartbeat = anchor("submit_heartbeat");
+        let unknown = Instruction::new_with_bytes(program, &[1, 2], vec![AccountMeta::new(program, false)]);
+
+        assert_eq!(compute_unit_limit(&[heartbeat.clone()]), 11_000);
+        assert_eq!(compute_unit_limit(&[heartbeat, unknown]), 231_000);
+        let many = vec![anchor("verify_proof"); 4];
+        assert_eq!(compute_unit_limit(&many), MAX_TRANSACTION_UNITS);
+    }
+}
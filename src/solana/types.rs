@@ -1,5 +1,6 @@
 //! Solana 相关类型定义
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -23,7 +24,7 @@ pub struct NodeInfo {
 }
 
 /// 节点状态
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub enum NodeStatus {
     /// 活跃
     Active,
@@ -36,7 +37,7 @@ pub enum NodeStatus {
 }
 
 /// 任务类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub enum TaskType {
     /// 训练
     Training,
@@ -49,7 +50,7 @@ pub enum TaskType {
 }
 
 /// 节点地理位置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct Location {
     pub latitude: i32,  // 纬度 * 1000000
     pub longitude: i32, // 经度 * 1000000
@@ -58,7 +59,7 @@ pub struct Location {
 }
 
 /// 模型信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct ModelInfo {
     pub model_id: String,
     pub version: String,
@@ -108,6 +109,18 @@ pub struct ComputeContribution {
     pub batches_processed: u64,
     /// 算力评分（基于上述指标计算）
     pub compute_score: f64,
+    /// 协调者签发的任务清单哈希（用于追溯任务来源）
+    #[serde(default)]
+    pub manifest_hash: Option<String>,
+    /// 训练所用数据集的清单哈希（blake3 十六进制）
+    #[serde(default)]
+    pub dataset_manifest_hash: Option<String>,
+    /// 留出集评估折算的质量评分（0~1，未评估时为空）
+    #[serde(default)]
+    pub quality_score: Option<f32>,
+    /// 任务期间的能耗估计（瓦时，未上报时为空）
+    #[serde(default)]
+    pub energy_wh: Option<f64>,
 }
 
 /// 算力贡献统计
@@ -0,0 +1,380 @@
+//! 交易签名器
+//!
+//! 支付者私钥不必进入节点进程：
+//! - [`KeypairSigner`]：本地密钥对（密钥库或开发网明文配置）
+//! - `LedgerSigner`：Ledger 硬件钱包（USB HID，Solana 应用），需启用 `ledger` 特性
+//! - [`RemoteSigner`]：经 P2P 把交易发给审批设备（如手机钱包），用户确认后返回签名
+//!
+//! 领取收益、治理投票等交易通过 [`SolanaClient::with_signer`](super::SolanaClient::with_signer) 使用这些签名器。
+
+use anyhow::{anyhow, Result};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::comms::RemoteSigningRelay;
+
+/// 交易签名器
+#[async_trait::async_trait]
+pub trait WalletSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    /// 签名器类型，用于日志
+    fn kind(&self) -> &'static str;
+
+    /// 对交易消息签名（硬件钱包和远程审批会等待用户确认）
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+}
+
+/// 本地密钥对签名器
+pub struct KeypairSigner(Keypair);
+
+impl KeypairSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self(keypair)
+    }
+}
+
+#[async_trait::async_trait]
+impl WalletSigner for KeypairSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.0.pubkey()
+    }
+
+    fn kind(&self) -> &'static str {
+        "keypair"
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.0.sign_message(message))
+    }
+}
+
+/// 远程审批签名器：交易消息经 P2P 发给审批设备，等待用户确认
+pub struct RemoteSigner {
+    pubkey: Pubkey,
+    /// 审批设备的节点 ID
+    approver: String,
+    relay: Arc<RemoteSigningRelay>,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    /// 默认等待审批的时间
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+    pub fn new(pubkey: Pubkey, approver: impl Into<String>, relay: Arc<RemoteSigningRelay>) -> Self {
+        Self {
+            pubkey,
+            approver: approver.into(),
+            relay,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// 设置等待审批的超时时间
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl WalletSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn kind(&self) -> &'static str {
+        "remote"
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let summary = format!("Solana 交易，签名账户 {}", self.pubkey);
+        let bytes = self
+            .relay
+            .request(&self.approver, &self.pubkey.to_string(), message.to_vec(), summary, self.timeout)
+            .await?;
+        let signature = Signature::try_from(bytes.as_slice()).map_err(|_| anyhow!("审批设备返回的签名长度无效"))?;
+        // 审批设备不可信：签名必须对应声明的公钥和本次消息
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(anyhow!("审批设备 {} 返回的签名无效", self.approver));
+        }
+        Ok(signature)
+    }
+}
+
+/// 用签名器为交易签名：附加签名者（如新建账户的密钥对）本地签名，支付者由签名器签名
+pub async fn sign_transaction(
+    payer: &dyn WalletSigner,
+    instructions: &[Instruction],
+    extra_signers: &[&Keypair],
+    blockhash: Hash,
+) -> Result<Transaction> {
+    let payer_pubkey = payer.pubkey();
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer_pubkey));
+    transaction.try_partial_sign(extra_signers, blockhash)?;
+    let signature = payer.sign_message(&transaction.message_data()).await?;
+    let position = transaction.get_signing_keypair_positions(&[payer_pubkey])?[0]
+        .ok_or_else(|| anyhow!("支付者 {} 不在交易签名者中", payer_pubkey))?;
+    transaction.signatures[position] = signature;
+    transaction.verify()?;
+    Ok(transaction)
+}
+
+// ============ Ledger ============
+
+/// Ledger 的 USB 厂商 ID
+pub const LEDGER_VENDOR_ID: u16 = 0x2c97;
+
+const APDU_CLA: u8 = 0xe0;
+const INS_GET_PUBKEY: u8 = 0x05;
+const INS_SIGN_MESSAGE: u8 = 0x06;
+const P1_NON_CONFIRM: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P2_EXTEND: u8 = 0x01;
+const P2_MORE: u8 = 0x02;
+/// 单个 APDU 的最大数据长度
+const MAX_CHUNK_SIZE: usize = 255;
+/// HID 报文长度
+const HID_PACKET_SIZE: usize = 64;
+const HID_CHANNEL: [u8; 2] = [0x01, 0x01];
+const HID_TAG_APDU: u8 = 0x05;
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+
+/// Solana 账户的派生路径 m/44'/501'/{account}'/0'，按 Ledger 格式序列化（个数 + 大端 u32）
+pub fn ledger_derivation_path(account: u32) -> Vec<u8> {
+    let hardened = [44, 501, account, 0].map(|index| index | 0x8000_0000);
+    let mut path = vec![hardened.len() as u8];
+    for index in hardened {
+        path.extend_from_slice(&index.to_be_bytes());
+    }
+    path
+}
+
+fn apdu(ins: u8, p1: u8, p2: u8, data: &[u8]) -> Vec<u8> {
+    let mut apdu = vec![APDU_CLA, ins, p1, p2];
+    apdu.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    apdu.extend_from_slice(data);
+    apdu
+}
+
+/// 签名消息的 APDU 序列：首个包含签名者个数和派生路径，超长消息分块续传
+fn sign_message_apdus(path: &[u8], message: &[u8]) -> Vec<Vec<u8>> {
+    let mut first = vec![1u8];
+    first.extend_from_slice(path);
+    let (head, rest) = message.split_at(message.len().min(MAX_CHUNK_SIZE - first.len()));
+    first.extend_from_slice(head);
+
+    let chunks: Vec<&[u8]> = rest.chunks(MAX_CHUNK_SIZE).collect();
+    let mut apdus = vec![apdu(
+        INS_SIGN_MESSAGE,
+        P1_CONFIRM,
+        if chunks.is_empty() { 0 } else { P2_MORE },
+        &first,
+    )];
+    for (i, chunk) in chunks.iter().enumerate() {
+        let p2 = if i + 1 < chunks.len() { P2_EXTEND | P2_MORE } else { P2_EXTEND };
+        apdus.push(apdu(INS_SIGN_MESSAGE, P1_CONFIRM, p2, chunk));
+    }
+    apdus
+}
+
+/// 按 Ledger HID 传输格式分帧：通道 + 标签 + 序号，首帧带总长度
+fn ledger_frames(payload: &[u8]) -> Vec<[u8; HID_PACKET_SIZE]> {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    let mut sequence: u16 = 0;
+    while sequence == 0 || offset < payload.len() {
+        let mut frame = [0u8; HID_PACKET_SIZE];
+        frame[..2].copy_from_slice(&HID_CHANNEL);
+        frame[2] = HID_TAG_APDU;
+        frame[3..5].copy_from_slice(&sequence.to_be_bytes());
+        let mut header = 5;
+        if sequence == 0 {
+            frame[5..7].copy_from_slice(&(payload.len() as u16).to_be_bytes());
+            header = 7;
+        }
+        let size = (HID_PACKET_SIZE - header).min(payload.len() - offset);
+        frame[header..header + size].copy_from_slice(&payload[offset..offset + size]);
+        frames.push(frame);
+        offset += size;
+        sequence += 1;
+    }
+    frames
+}
+
+/// 读取并拼接响应帧，检查状态字后返回数据
+fn read_ledger_response(mut next_frame: impl FnMut() -> Result<Vec<u8>>) -> Result<Vec<u8>> {
+    let mut response = Vec::new();
+    let mut expected = None;
+    let mut sequence: u16 = 0;
+    while expected.map_or(true, |len| response.len() < len) {
+        let frame = next_frame()?;
+        if frame.len() < 5 || frame[..2] != HID_CHANNEL || frame[2] != HID_TAG_APDU {
+            return Err(anyhow!("Ledger 响应帧格式无效"));
+        }
+        if u16::from_be_bytes([frame[3], frame[4]]) != sequence {
+            return Err(anyhow!("Ledger 响应帧序号不连续"));
+        }
+        let mut body = &frame[5..];
+        if sequence == 0 {
+            if body.len() < 2 {
+                return Err(anyhow!("Ledger 响应帧格式无效"));
+            }
+            expected = Some(u16::from_be_bytes([body[0], body[1]]) as usize);
+            body = &body[2..];
+        }
+        let remaining = expected.unwrap_or_default() - response.len();
+        response.extend_from_slice(&body[..body.len().min(remaining)]);
+        sequence += 1;
+    }
+    if response.len() < 2 {
+        return Err(anyhow!("Ledger 响应缺少状态字"));
+    }
+    let status_at = response.len() - 2;
+    match u16::from_be_bytes([response[status_at], response[status_at + 1]]) {
+        SW_OK => {
+            response.truncate(status_at);
+            Ok(response)
+        }
+        SW_USER_REJECTED => Err(anyhow!("用户在 Ledger 上拒绝了签名")),
+        status => Err(anyhow!("Ledger 返回错误状态 0x{:04x}，请确认已打开 Solana 应用", status)),
+    }
+}
+
+#[cfg(feature = "ledger")]
+pub use ledger::LedgerSigner;
+
+#[cfg(feature = "ledger")]
+mod ledger {
+    use super::*;
+    use parking_lot::Mutex;
+
+    /// 等待用户在设备上确认的最长时间
+    const CONFIRM_TIMEOUT_MS: i32 = 120_000;
+
+    /// Ledger 硬件钱包签名器（Solana 应用）
+    pub struct LedgerSigner {
+        device: Arc<Mutex<hidapi::HidDevice>>,
+        path: Vec<u8>,
+        pubkey: Pubkey,
+    }
+
+    impl LedgerSigner {
+        /// 连接第一台 Ledger，读取派生路径 m/44'/501'/{account}'/0' 的公钥
+        pub fn connect(account: u32) -> Result<Self> {
+            let api = hidapi::HidApi::new()?;
+            let info = api
+                .device_list()
+                .find(|d| d.vendor_id() == LEDGER_VENDOR_ID && (d.usage_page() == 0xffa0 || d.interface_number() == 0))
+                .ok_or_else(|| anyhow!("未找到 Ledger 设备，请连接并解锁"))?;
+            let device = info.open_device(&api)?;
+            let path = ledger_derivation_path(account);
+            let response = exchange(&device, &apdu(INS_GET_PUBKEY, P1_NON_CONFIRM, 0, &path))?;
+            let pubkey = Pubkey::try_from(response.as_slice()).map_err(|_| anyhow!("Ledger 返回的公钥无效"))?;
+            log::info!("已连接 Ledger，账户 {} 公钥 {}", account, pubkey);
+            Ok(Self {
+                device: Arc::new(Mutex::new(device)),
+                path,
+                pubkey,
+            })
+        }
+    }
+
+    fn exchange(device: &hidapi::HidDevice, apdu: &[u8]) -> Result<Vec<u8>> {
+        for frame in ledger_frames(apdu) {
+            // 首字节为 HID 报告 ID
+            let mut report = vec![0u8];
+            report.extend_from_slice(&frame);
+            device.write(&report)?;
+        }
+        read_ledger_response(|| {
+            let mut buffer = [0u8; HID_PACKET_SIZE];
+            match device.read_timeout(&mut buffer, CONFIRM_TIMEOUT_MS)? {
+                0 => Err(anyhow!("等待 Ledger 响应超时")),
+                len => Ok(buffer[..len].to_vec()),
+            }
+        })
+    }
+
+    #[async_trait::async_trait]
+    impl WalletSigner for LedgerSigner {
+        fn pubkey(&self) -> Pubkey {
+            self.pubkey
+        }
+
+        fn kind(&self) -> &'static str {
+            "ledger"
+        }
+
+        async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+            let device = Arc::clone(&self.device);
+            let apdus = sign_message_apdus(&self.path, message);
+            // 等待用户在设备上确认，放到阻塞线程中执行
+            let response = tokio::task::spawn_blocking(move || {
+                let device = device.lock();
+                let mut response = Vec::new();
+                for apdu in &apdus {
+                    response = exchange(&device, apdu)?;
+                }
+                Ok::<_, anyhow::Error>(response)
+            })
+            .await??;
+            let signature = Signature::try_from(response.as_slice()).map_err(|_| anyhow!("Ledger 返回的签名无效"))?;
+            if !signature.verify(self.pubkey.as_ref(), message) {
+                return Err(anyhow!("Ledger 返回的签名与公钥不匹配"));
+            }
+            Ok(signature)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction;
+
+    #[tokio::test]
+    async fn test_signer_and_extra_signers_produce_valid_transaction() {
+        let payer = KeypairSigner::new(Keypair::new());
+        let new_account = Keypair::new();
+        let instruction =
+            system_instruction::create_account(&payer.pubkey(), &new_account.pubkey(), 1_000_000, 0, &Pubkey::new_unique());
+        let transaction = sign_transaction(&payer, &[instruction], &[&new_account], Hash::new_unique())
+            .await
+            .unwrap();
+        assert!(transaction.is_signed());
+        assert_eq!(transaction.message.account_keys[0], payer.pubkey());
+    }
+
+    #[test]
+    fn test_ledger_apdus_and_hid_framing() {
+        let path = ledger_derivation_path(2);
+        assert_eq!(path.len(), 17);
+        assert_eq!(&path[9..13], &(2u32 | 0x8000_0000).to_be_bytes());
+
+        // 超长消息分为首块 + 续传块，最后一块清除 MORE 位
+        let apdus = sign_message_apdus(&path, &[7u8; 600]);
+        assert_eq!(apdus.len(), 3);
+        assert_eq!((apdus[0][3], apdus[1][3], apdus[2][3]), (P2_MORE, P2_EXTEND | P2_MORE, P2_EXTEND));
+        let sent: usize = apdus.iter().map(|a| a.len() - 6).sum();
+        assert_eq!(sent, 600 + 1 + path.len());
+
+        // 响应与请求使用同样的分帧格式
+        let mut response = vec![9u8; 64];
+        response.extend_from_slice(&SW_OK.to_be_bytes());
+        let mut frames = ledger_frames(&response).into_iter().map(|f| f.to_vec());
+        assert_eq!(read_ledger_response(|| Ok(frames.next().unwrap())).unwrap(), vec![9u8; 64]);
+
+        let mut frames = ledger_frames(&SW_USER_REJECTED.to_be_bytes()).into_iter().map(|f| f.to_vec());
+        assert!(read_ledger_response(|| Ok(frames.next().unwrap())).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! 能耗估算与碳排放报告
+//!
+//! 没有可靠的功率计时，按设备类型的 TDP 档位和训练期间的 CPU/GPU 利用率估算功耗：
+//! 只计入训练带来的增量功耗（满载功耗与空闲功耗之差乘以利用率），
+//! 按 epoch 汇总电量，并可写入算力贡献记录，让奖励计算考虑能效。
+
+use crate::device::{DeviceCapabilities, DeviceType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// 设备功耗档位（瓦）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PowerProfile {
+    pub cpu_idle_watts: f64,
+    pub cpu_max_watts: f64,
+    pub gpu_idle_watts: f64,
+    pub gpu_max_watts: f64,
+}
+
+impl PowerProfile {
+    /// 按设备类型选择典型 TDP，没有 GPU 时不计 GPU 功耗
+    pub fn for_device(caps: &DeviceCapabilities) -> Self {
+        let (cpu_idle_watts, cpu_max_watts, gpu_idle_watts, gpu_max_watts) = match caps.device_type {
+            DeviceType::Phone => (0.5, 5.0, 0.2, 3.0),
+            DeviceType::Tablet => (1.0, 8.0, 0.5, 5.0),
+            DeviceType::Desktop | DeviceType::Unknown => (10.0, 65.0, 15.0, 200.0),
+        };
+        Self {
+            cpu_idle_watts,
+            cpu_max_watts,
+            gpu_idle_watts: if caps.has_gpu { gpu_idle_watts } else { 0.0 },
+            gpu_max_watts: if caps.has_gpu { gpu_max_watts } else { 0.0 },
+        }
+    }
+}
+
+/// 能耗统计配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnergyConfig {
+    pub enabled: bool,
+    /// 自定义功耗档位，未设置时按设备类型推断
+    pub profile: Option<PowerProfile>,
+    /// 电网碳排放强度（gCO2e/kWh）
+    pub carbon_intensity_g_per_kwh: f64,
+    /// 每个 epoch 包含的 tick 数
+    pub epoch_ticks: u64,
+    /// 是否把任务能耗写入算力贡献记录
+    pub include_in_contributions: bool,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            profile: None,
+            carbon_intensity_g_per_kwh: 475.0,
+            epoch_ticks: 100,
+            include_in_contributions: false,
+        }
+    }
+}
+
+/// 能耗模型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnergyModel {
+    pub profile: PowerProfile,
+}
+
+impl EnergyModel {
+    pub fn new(profile: PowerProfile) -> Self {
+        Self { profile }
+    }
+
+    /// 训练带来的增量功率（瓦），利用率取值 0-1
+    pub fn power_watts(&self, cpu_utilization: f64, gpu_utilization: f64) -> f64 {
+        let p = &self.profile;
+        (p.cpu_max_watts - p.cpu_idle_watts).max(0.0) * cpu_utilization.clamp(0.0, 1.0)
+            + (p.gpu_max_watts - p.gpu_idle_watts).max(0.0) * gpu_utilization.clamp(0.0, 1.0)
+    }
+
+    /// 一段时间内消耗的电量（瓦时）
+    pub fn watt_hours(&self, cpu_utilization: f64, gpu_utilization: f64, duration: Duration) -> f64 {
+        self.power_watts(cpu_utilization, gpu_utilization) * duration.as_secs_f64() / 3600.0
+    }
+}
+
+/// 单个 epoch 的能耗
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EpochEnergy {
+    pub epoch: u64,
+    pub watt_hours: f64,
+    /// 计算占用的时长（秒）
+    pub busy_secs: f64,
+    pub ticks: u64,
+}
+
+/// 按 epoch 汇总的能耗账本
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnergyLedger {
+    pub epochs: BTreeMap<u64, EpochEnergy>,
+    pub total_watt_hours: f64,
+}
+
+impl EnergyLedger {
+    /// 只保留最近的 epoch，总量不受影响
+    const MAX_EPOCHS: usize = 500;
+
+    pub fn record(&mut self, epoch: u64, watt_hours: f64, busy: Duration) {
+        let entry = self.epochs.entry(epoch).or_insert_with(|| EpochEnergy {
+            epoch,
+            ..Default::default()
+        });
+        entry.watt_hours += watt_hours;
+        entry.busy_secs += busy.as_secs_f64();
+        entry.ticks += 1;
+        self.total_watt_hours += watt_hours;
+        while self.epochs.len() > Self::MAX_EPOCHS {
+            self.epochs.pop_first();
+        }
+    }
+
+    /// 生成碳排放报告
+    pub fn report(&self, carbon_intensity_g_per_kwh: f64, samples_processed: u64) -> CarbonReport {
+        let total_kwh = self.total_watt_hours / 1000.0;
+        CarbonReport {
+            total_watt_hours: self.total_watt_hours,
+            co2e_grams: total_kwh * carbon_intensity_g_per_kwh,
+            carbon_intensity_g_per_kwh,
+            samples_per_watt_hour: (self.total_watt_hours > 0.0)
+                .then(|| samples_processed as f64 / self.total_watt_hours),
+            epochs: self.epochs.values().cloned().collect(),
+        }
+    }
+}
+
+/// 碳排放报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonReport {
+    pub total_watt_hours: f64,
+    pub co2e_grams: f64,
+    pub carbon_intensity_g_per_kwh: f64,
+    /// 能效：每瓦时处理的样本数
+    pub samples_per_watt_hour: Option<f64>,
+    pub epochs: Vec<EpochEnergy>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimates_incremental_energy_and_carbon() {
+        let caps = DeviceCapabilities {
+            device_type: DeviceType::Phone,
+            has_gpu: false,
+            ..Default::default()
+        };
+        let model = EnergyModel::new(PowerProfile::for_device(&caps));
+        assert_eq!(model.power_watts(0.0, 1.0), 0.0);
+        // 手机 CPU 增量 4.5W，半载运行 2 小时 = 4.5Wh
+        let wh = model.watt_hours(0.5, 0.0, Duration::from_secs(7200));
+        assert!((wh - 4.5).abs() < 1e-9);
+
+        let mut ledger = EnergyLedger::default();
+        ledger.record(0, wh, Duration::from_secs(3600));
+        ledger.record(1, 5.5, Duration::from_secs(10));
+        ledger.record(1, 0.0, Duration::from_secs(10));
+        let report = ledger.report(400.0, 1000);
+        assert!((report.total_watt_hours - 10.0).abs() < 1e-9);
+        assert!((report.co2e_grams - 4.0).abs() < 1e-9);
+        assert_eq!(report.samples_per_watt_hour, Some(100.0));
+        assert_eq!(report.epochs[1].ticks, 2);
+    }
+}
@@ -0,0 +1,478 @@
+//! 统计数据管理模块
+//! 
+//! 管理训练统计数据和导出功能
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use anyhow::{anyhow, Result};
+
+pub mod energy;
+pub mod store;
+
+pub use energy::{CarbonReport, EnergyConfig, EnergyLedger, EnergyModel, EpochEnergy, PowerProfile};
+
+pub use store::{
+    ApiKeyRecord, ApiUsage, ApiUsageTotals, ChainEvent, ChainRecord, ChainTable, PayoutCheck, PayoutStatus, PeerSample,
+    StatsStore, SubmissionRecord, TickAggregate, TickMetrics, TransferDirection, TransferOutcome, TransferRecord, TransferTotals,
+};
+
+/// 流式读取时单行记录的默认上限（字节），避免异常文件撑爆内存
+pub const DEFAULT_MAX_RECORD_BYTES: usize = 1024 * 1024;
+
+/// 训练统计数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingStats {
+    pub tick_count: u64,
+    pub start_time: DateTime<Utc>,
+    pub last_update: DateTime<Utc>,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub connected_peers: u64,
+    pub training_accuracy: f64,
+    pub training_loss: f64,
+    pub samples_processed: u64,
+    pub custom_metrics: HashMap<String, f64>,
+    /// 最近的 tick 间隔调整记录
+    #[serde(default)]
+    pub tick_adaptations: Vec<crate::device::TickAdaptation>,
+    /// 节点运行模式（观察模式下不会产生训练数据）
+    #[serde(default)]
+    pub node_mode: crate::config::NodeMode,
+    /// 对各节点生效的加密策略
+    #[serde(default)]
+    pub peer_policies: HashMap<String, crate::crypto::PeerEncryptionPolicy>,
+    /// 按 epoch 汇总的训练能耗估计
+    #[serde(default)]
+    pub energy: EnergyLedger,
+    /// 因资源越界被中止的任务
+    #[serde(default)]
+    pub sandbox_incidents: Vec<crate::device::SandboxIncident>,
+    /// 磁盘可用空间不足的警告
+    #[serde(default)]
+    pub storage_warnings: Vec<crate::storage::StorageWarning>,
+    /// 最近的留出集评估结果
+    #[serde(default)]
+    pub evaluations: Vec<crate::training::EvalMetrics>,
+}
+
+impl Default for TrainingStats {
+    fn default() -> Self {
+        let now = Utc::now();
+        Self {
+            tick_count: 0,
+            start_time: now,
+            last_update: now,
+            messages_sent: 0,
+            messages_received: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            connected_peers: 0,
+            training_accuracy: 0.0,
+            training_loss: 1.0,
+            samples_processed: 0,
+            custom_metrics: HashMap::new(),
+            tick_adaptations: Vec::new(),
+            node_mode: crate::config::NodeMode::Full,
+            peer_policies: HashMap::new(),
+            energy: EnergyLedger::default(),
+            sandbox_incidents: Vec::new(),
+            storage_warnings: Vec::new(),
+            evaluations: Vec::new(),
+        }
+    }
+}
+
+/// 统计数据管理器
+#[derive(Debug)]
+pub struct TrainingStatsManager {
+    stats: TrainingStats,
+}
+
+impl TrainingStatsManager {
+    /// 创建新的统计管理器
+    pub fn new() -> Self {
+        Self {
+            stats: TrainingStats::default(),
+        }
+    }
+    
+    /// 创建带模型信息的统计管理器
+    pub fn new_with_model(_model_hash: String, _model_version: u32) -> Self {
+        Self::new()
+    }
+    
+    /// 增加tick计数
+    pub fn increment_tick(&mut self) {
+        self.stats.tick_count += 1;
+        self.stats.last_update = Utc::now();
+    }
+    
+    /// 更新消息统计
+    pub fn update_message_stats(&mut self, sent: u64, received: u64, bytes_sent: u64, bytes_received: u64) {
+        self.stats.messages_sent += sent;
+        self.stats.messages_received += received;
+        self.stats.bytes_sent += bytes_sent;
+        self.stats.bytes_received += bytes_received;
+        self.stats.last_update = Utc::now();
+    }
+    
+    /// 更新连接节点数
+    pub fn update_connected_peers(&mut self, count: u64) {
+        self.stats.connected_peers = count;
+        self.stats.last_update = Utc::now();
+    }
+    
+    /// 更新训练指标
+    pub fn update_training_metrics(&mut self, accuracy: f64, loss: f64, samples: u64) {
+        self.stats.training_accuracy = accuracy;
+        self.stats.training_loss = loss;
+        self.stats.samples_processed = samples;
+        self.stats.last_update = Utc::now();
+    }
+    
+    /// 添加自定义指标
+    pub fn add_custom_metric(&mut self, name: String, value: f64) {
+        self.stats.custom_metrics.insert(name, value);
+        self.stats.last_update = Utc::now();
+    }
+    
+    /// 设置节点运行模式
+    pub fn set_node_mode(&mut self, mode: crate::config::NodeMode) {
+        self.stats.node_mode = mode;
+        self.stats.last_update = Utc::now();
+    }
+
+    /// 记录 tick 间隔调整（只保留最近的记录）
+    pub fn record_tick_adaptation(&mut self, adaptation: crate::device::TickAdaptation) {
+        const MAX_TICK_ADAPTATIONS: usize = 200;
+        self.stats
+            .custom_metrics
+            .insert("tick_interval_ms".to_string(), adaptation.to_ms as f64);
+        self.stats.tick_adaptations.push(adaptation);
+        if self.stats.tick_adaptations.len() > MAX_TICK_ADAPTATIONS {
+            let excess = self.stats.tick_adaptations.len() - MAX_TICK_ADAPTATIONS;
+            self.stats.tick_adaptations.drain(..excess);
+        }
+        self.stats.last_update = Utc::now();
+    }
+
+    /// 记录投机解码的累计效果
+    pub fn record_speculative(&mut self, stats: &crate::training::SpeculativeStats) {
+        let metrics = &mut self.stats.custom_metrics;
+        metrics.insert("speculative_acceptance_rate".to_string(), stats.acceptance_rate());
+        metrics.insert("speculative_tokens_per_pass".to_string(), stats.tokens_per_target_pass());
+        metrics.insert("speculative_speedup".to_string(), stats.speedup());
+        self.stats.last_update = Utc::now();
+    }
+
+    /// 记录对某节点选择的加密策略（`None` 表示节点已离线）
+    pub fn record_peer_policy(&mut self, peer: &str, policy: Option<crate::crypto::PeerEncryptionPolicy>) {
+        match policy {
+            Some(policy) => {
+                self.stats.peer_policies.insert(peer.to_string(), policy);
+            }
+            None => {
+                self.stats.peer_policies.remove(peer);
+            }
+        }
+        self.stats.last_update = Utc::now();
+    }
+
+    /// 记录一轮计算的能耗估计
+    pub fn record_energy(&mut self, epoch: u64, watt_hours: f64, busy: std::time::Duration) {
+        self.stats.energy.record(epoch, watt_hours, busy);
+        self.stats
+            .custom_metrics
+            .insert("energy_wh_total".to_string(), self.stats.energy.total_watt_hours);
+        self.stats.last_update = Utc::now();
+    }
+
+    /// 记录资源越界事件（只保留最近的记录）
+    pub fn record_sandbox_incident(&mut self, incident: crate::device::SandboxIncident) {
+        const MAX_SANDBOX_INCIDENTS: usize = 100;
+        self.stats.sandbox_incidents.push(incident);
+        if self.stats.sandbox_incidents.len() > MAX_SANDBOX_INCIDENTS {
+            let excess = self.stats.sandbox_incidents.len() - MAX_SANDBOX_INCIDENTS;
+            self.stats.sandbox_incidents.drain(..excess);
+        }
+        *self
+            .stats
+            .custom_metrics
+            .entry("sandbox_incidents_total".to_string())
+            .or_insert(0.0) += 1.0;
+        self.stats.last_update = Utc::now();
+    }
+
+    /// 记录低空间警告（只保留最近的记录）
+    pub fn record_storage_warning(&mut self, warning: crate::storage::StorageWarning) {
+        const MAX_STORAGE_WARNINGS: usize = 100;
+        let metrics = &mut self.stats.custom_metrics;
+        metrics.insert("disk_available_bytes".to_string(), warning.available_bytes as f64);
+        *metrics.entry("storage_pruned_bytes_total".to_string()).or_insert(0.0) += warning.pruned_bytes as f64;
+        self.stats.storage_warnings.push(warning);
+        if self.stats.storage_warnings.len() > MAX_STORAGE_WARNINGS {
+            let excess = self.stats.storage_warnings.len() - MAX_STORAGE_WARNINGS;
+            self.stats.storage_warnings.drain(..excess);
+        }
+        self.stats.last_update = Utc::now();
+    }
+
+    pub fn record_evaluation(&mut self, metrics: crate::training::EvalMetrics) {
+        const MAX_EVALUATIONS: usize = 100;
+        let custom = &mut self.stats.custom_metrics;
+        custom.insert("eval_loss".to_string(), metrics.loss);
+        custom.insert("eval_accuracy".to_string(), metrics.accuracy);
+        custom.insert("quality_score".to_string(), metrics.quality_score());
+        if let Some(perplexity) = metrics.perplexity {
+            custom.insert("eval_perplexity".to_string(), perplexity);
+        }
+        self.stats.evaluations.push(metrics);
+        if self.stats.evaluations.len() > MAX_EVALUATIONS {
+            let excess = self.stats.evaluations.len() - MAX_EVALUATIONS;
+            self.stats.evaluations.drain(..excess);
+        }
+        self.stats.last_update = Utc::now();
+    }
+
+    /// 按电网碳排放强度生成碳排放报告
+    pub fn carbon_report(&self, carbon_intensity_g_per_kwh: f64) -> CarbonReport {
+        self.stats
+            .energy
+            .report(carbon_intensity_g_per_kwh, self.stats.samples_processed)
+    }
+
+    /// 获取统计数据引用
+    pub fn get_stats(&self) -> &TrainingStats {
+        &self.stats
+    }
+    
+    /// 导出为JSON字符串
+    pub fn export_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.stats)?)
+    }
+    
+    /// 导出到文件
+    ///
+    /// 直接序列化到带缓冲的文件写入器，不在内存中构造完整的 JSON 字符串
+    pub fn export_json_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, &self.stats)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// 以 NDJSON 形式追加一条统计快照到文件
+    ///
+    /// 每次调用只写入一行，历史记录保存在磁盘上而不是内存中
+    pub fn append_ndjson_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = StatsStreamWriter::open(path)?;
+        writer.write_record(&self.stats)?;
+        writer.flush()
+    }
+    
+    /// 重置统计数据
+    pub fn reset(&mut self) {
+        self.stats = TrainingStats::default();
+    }
+    
+    /// 获取运行时间
+    pub fn get_runtime(&self) -> chrono::Duration {
+        Utc::now() - self.stats.start_time
+    }
+    
+    /// 获取平均消息速率（消息/秒）
+    pub fn get_message_rate(&self) -> f64 {
+        let runtime_secs = self.get_runtime().num_seconds() as f64;
+        if runtime_secs > 0.0 {
+            (self.stats.messages_sent + self.stats.messages_received) as f64 / runtime_secs
+        } else {
+            0.0
+        }
+    }
+    
+    /// 获取网络吞吐量（字节/秒）
+    pub fn get_throughput(&self) -> f64 {
+        let runtime_secs = self.get_runtime().num_seconds() as f64;
+        if runtime_secs > 0.0 {
+            (self.stats.bytes_sent + self.stats.bytes_received) as f64 / runtime_secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 判断路径是否应使用 NDJSON 流式格式（`.ndjson` / `.jsonl`）
+pub fn is_ndjson_path<P: AsRef<Path>>(path: P) -> bool {
+    matches!(
+        path.as_ref().extension().and_then(|ext| ext.to_str()),
+        Some("ndjson") | Some("jsonl")
+    )
+}
+
+/// NDJSON 统计流写入器
+///
+/// 以追加模式打开文件，每条记录序列化为单独一行。
+/// 内存占用只取决于单条记录大小，与历史长度无关。
+pub struct StatsStreamWriter {
+    writer: BufWriter<File>,
+    records_written: u64,
+}
+
+impl StatsStreamWriter {
+    /// 以追加模式打开（不存在则创建）
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            records_written: 0,
+        })
+    }
+
+    /// 写入一条记录
+    pub fn write_record<T: Serialize>(&mut self, record: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        self.records_written += 1;
+        Ok(())
+    }
+
+    /// 已写入的记录数
+    pub fn records_written(&self) -> u64 {
+        self.records_written
+    }
+
+    /// 刷新缓冲区
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// NDJSON 统计流读取器
+///
+/// 逐行读取并反序列化，单行超过 `max_record_bytes` 时返回错误而不是继续分配内存。
+pub struct StatsStreamReader<R: BufRead> {
+    reader: R,
+    max_record_bytes: usize,
+    line: Vec<u8>,
+    line_number: u64,
+}
+
+impl StatsStreamReader<BufReader<File>> {
+    /// 打开 NDJSON 文件
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> StatsStreamReader<R> {
+    /// 从任意缓冲读取器创建
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            max_record_bytes: DEFAULT_MAX_RECORD_BYTES,
+            line: Vec::new(),
+            line_number: 0,
+        }
+    }
+
+    /// 设置单行记录上限
+    pub fn with_max_record_bytes(mut self, max_record_bytes: usize) -> Self {
+        self.max_record_bytes = max_record_bytes;
+        self
+    }
+
+    /// 读取下一条记录，文件结束时返回 `None`
+    pub fn next_record<T: serde::de::DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        loop {
+            self.line.clear();
+            let limit = self.max_record_bytes as u64 + 1;
+            let read = (&mut self.reader).take(limit).read_until(b'\n', &mut self.line)?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+
+            if self.line.last() != Some(&b'\n') && self.line.len() > self.max_record_bytes {
+                return Err(anyhow!(
+                    "第 {} 行记录超过上限 {} 字节",
+                    self.line_number,
+                    self.max_record_bytes
+                ));
+            }
+
+            let trimmed = self.line.trim_ascii();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let record = serde_json::from_slice(trimmed)
+                .map_err(|e| anyhow!("第 {} 行解析失败: {}", self.line_number, e))?;
+            return Ok(Some(record));
+        }
+    }
+
+    /// 逐条回调处理所有记录，返回处理的记录数
+    pub fn for_each<T, F>(&mut self, mut f: F) -> Result<u64>
+    where
+        T: serde::de::DeserializeOwned,
+        F: FnMut(T),
+    {
+        let mut count = 0;
+        while let Some(record) = self.next_record::<T>()? {
+            f(record);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ndjson_roundtrip() {
+        let path = std::env::temp_dir().join(format!("williw_stats_{}.ndjson", uuid::Uuid::new_v4()));
+        let mut manager = TrainingStatsManager::new();
+        for _ in 0..3 {
+            manager.increment_tick();
+            manager.append_ndjson_to_file(&path).unwrap();
+        }
+
+        let mut reader = StatsStreamReader::open(&path).unwrap();
+        let mut ticks = Vec::new();
+        let count = reader.for_each(|stats: TrainingStats| ticks.push(stats.tick_count)).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(count, 3);
+        assert_eq!(ticks, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_node_mode_is_reported() {
+        let mut manager = TrainingStatsManager::new();
+        manager.set_node_mode(crate::config::NodeMode::WatchOnly);
+        let json = manager.export_json().unwrap();
+        assert!(json.contains("WatchOnly"));
+
+        // 旧版本导出的记录没有该字段，按完整节点处理
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("node_mode");
+        let stats: TrainingStats = serde_json::from_value(value).unwrap();
+        assert_eq!(stats.node_mode, crate::config::NodeMode::Full);
+    }
+
+    #[test]
+    fn test_reader_rejects_oversized_record() {
+        let data = format!("{}\n", "x".repeat(64));
+        let mut reader = StatsStreamReader::new(data.as_bytes()).with_max_record_bytes(16);
+        assert!(reader.next_record::<serde_json::Value>().is_err());
+    }
+}
@@ -0,0 +1,1334 @@
+//! 本地统计数据库
+//!
+//! 用 SQLite 持久化每个 tick 的训练指标、邻居快照、P2P 传输会话、待上链的提交记录、
+//! 从链上同步的合约状态和收益到账的校验结果，并提供按时间范围查询和按时间桶聚合的接口，供桌面端命令使用。
+//! 时间均为 Unix 秒，范围查询为 `[from, to)`。
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::TrainingStats;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS tick_metrics (
+    timestamp INTEGER NOT NULL,
+    tick INTEGER NOT NULL,
+    loss REAL NOT NULL,
+    accuracy REAL NOT NULL,
+    samples_processed INTEGER NOT NULL,
+    connected_peers INTEGER NOT NULL,
+    messages_sent INTEGER NOT NULL,
+    messages_received INTEGER NOT NULL,
+    bytes_sent INTEGER NOT NULL,
+    bytes_received INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_tick_metrics_ts ON tick_metrics(timestamp);
+
+CREATE TABLE IF NOT EXISTS transfer_sessions (
+    id TEXT PRIMARY KEY,
+    peer TEXT,
+    direction TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    bytes INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    finished_at INTEGER,
+    outcome TEXT NOT NULL,
+    error TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_transfer_sessions_started ON transfer_sessions(started_at);
+
+CREATE TABLE IF NOT EXISTS transfer_chunks (
+    transfer_id TEXT NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    PRIMARY KEY (transfer_id, chunk_index)
+);
+
+CREATE TABLE IF NOT EXISTS chain_submissions (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    queued_at INTEGER NOT NULL,
+    last_error TEXT,
+    submitted_at INTEGER,
+    signature TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_chain_submissions_queued ON chain_submissions(queued_at);
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    key_prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    created_at INTEGER NOT NULL,
+    revoked_at INTEGER,
+    requests_per_minute INTEGER NOT NULL,
+    token_quota INTEGER NOT NULL,
+    quota_period_secs INTEGER NOT NULL,
+    priority_class TEXT NOT NULL DEFAULT 'standard'
+);
+
+CREATE TABLE IF NOT EXISTS api_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    model TEXT NOT NULL,
+    prompt_tokens INTEGER NOT NULL,
+    completion_tokens INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_api_usage_key_ts ON api_usage(key_id, timestamp);
+
+CREATE TABLE IF NOT EXISTS peer_snapshot (
+    peer_id TEXT PRIMARY KEY,
+    role TEXT NOT NULL,
+    similarity REAL NOT NULL,
+    trust TEXT,
+    recorded_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS chain_nodes (
+    address TEXT PRIMARY KEY,
+    node_id TEXT,
+    timestamp INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chain_nodes_node ON chain_nodes(node_id, timestamp);
+
+CREATE TABLE IF NOT EXISTS chain_contributions (
+    address TEXT PRIMARY KEY,
+    node_id TEXT,
+    timestamp INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chain_contributions_node ON chain_contributions(node_id, timestamp);
+
+CREATE TABLE IF NOT EXISTS chain_rewards (
+    address TEXT PRIMARY KEY,
+    node_id TEXT,
+    timestamp INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chain_rewards_node ON chain_rewards(node_id, timestamp);
+
+CREATE TABLE IF NOT EXISTS chain_proposals (
+    address TEXT PRIMARY KEY,
+    node_id TEXT,
+    timestamp INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chain_proposals_node ON chain_proposals(node_id, timestamp);
+
+CREATE TABLE IF NOT EXISTS chain_events (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    program TEXT NOT NULL,
+    name TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS idx_chain_events_slot ON chain_events(slot);
+
+CREATE TABLE IF NOT EXISTS chain_sync (
+    program TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS payout_checks (
+    signature TEXT PRIMARY KEY,
+    wallet TEXT NOT NULL,
+    payer TEXT,
+    slot INTEGER,
+    expected_amount INTEGER,
+    credited_amount INTEGER,
+    status TEXT NOT NULL,
+    detail TEXT,
+    endpoints_agreeing INTEGER NOT NULL,
+    endpoints_queried INTEGER NOT NULL,
+    checked_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_payout_checks_checked ON payout_checks(checked_at);
+";
+
+/// 单个 tick 的训练指标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickMetrics {
+    pub timestamp: i64,
+    pub tick: u64,
+    pub loss: f64,
+    pub accuracy: f64,
+    /// 累计处理样本数
+    pub samples_processed: u64,
+    pub connected_peers: u64,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl TickMetrics {
+    pub fn from_stats(stats: &TrainingStats) -> Self {
+        Self {
+            timestamp: stats.last_update.timestamp(),
+            tick: stats.tick_count,
+            loss: stats.training_loss,
+            accuracy: stats.training_accuracy,
+            samples_processed: stats.samples_processed,
+            connected_peers: stats.connected_peers,
+            messages_sent: stats.messages_sent,
+            messages_received: stats.messages_received,
+            bytes_sent: stats.bytes_sent,
+            bytes_received: stats.bytes_received,
+        }
+    }
+}
+
+/// 按时间桶聚合的训练指标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickAggregate {
+    pub bucket_start: i64,
+    pub ticks: u64,
+    pub avg_loss: f64,
+    pub min_loss: f64,
+    pub avg_accuracy: f64,
+    pub max_connected_peers: u64,
+    /// 桶内新处理的样本数
+    pub samples_processed: u64,
+}
+
+/// 邻居快照中的单个节点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerSample {
+    pub peer_id: String,
+    /// "primary" 或 "backup"
+    pub role: String,
+    pub similarity: f32,
+    /// 信任级别（未协商加密策略时为 None）
+    pub trust: Option<String>,
+}
+
+/// 传输方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferDirection {
+    Inbound,
+    Outbound,
+}
+
+impl TransferDirection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TransferDirection::Inbound => "inbound",
+            TransferDirection::Outbound => "outbound",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "inbound" => Ok(TransferDirection::Inbound),
+            "outbound" => Ok(TransferDirection::Outbound),
+            other => Err(anyhow!("未知的传输方向: {}", other)),
+        }
+    }
+}
+
+/// 传输结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferOutcome {
+    InProgress,
+    Completed,
+    Failed,
+}
+
+impl TransferOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TransferOutcome::InProgress => "in_progress",
+            TransferOutcome::Completed => "completed",
+            TransferOutcome::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "in_progress" => Ok(TransferOutcome::InProgress),
+            "completed" => Ok(TransferOutcome::Completed),
+            "failed" => Ok(TransferOutcome::Failed),
+            other => Err(anyhow!("未知的传输结果: {}", other)),
+        }
+    }
+}
+
+/// P2P 传输会话记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferRecord {
+    pub id: String,
+    /// 对端节点（接收方在收到请求时可能尚不知道发送方）
+    pub peer: Option<String>,
+    pub direction: TransferDirection,
+    pub file_name: String,
+    pub bytes: u64,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub outcome: TransferOutcome,
+    pub error: Option<String>,
+}
+
+/// 时间范围内的传输汇总
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransferTotals {
+    pub sessions: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub bytes_inbound: u64,
+    pub bytes_outbound: u64,
+}
+
+/// 上链提交记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmissionRecord {
+    pub id: String,
+    /// 提交类型，如 "contribution"
+    pub kind: String,
+    /// JSON 编码的提交内容
+    pub payload: String,
+    pub attempts: u32,
+    pub queued_at: i64,
+    pub last_error: Option<String>,
+    pub submitted_at: Option<i64>,
+    pub signature: Option<String>,
+}
+
+/// 从链上同步的账户类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainTable {
+    Nodes,
+    Contributions,
+    Rewards,
+    Proposals,
+}
+
+impl ChainTable {
+    fn table(&self) -> &'static str {
+        match self {
+            ChainTable::Nodes => "chain_nodes",
+            ChainTable::Contributions => "chain_contributions",
+            ChainTable::Rewards => "chain_rewards",
+            ChainTable::Proposals => "chain_proposals",
+        }
+    }
+}
+
+/// 链上账户的本地镜像
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainRecord {
+    /// 账户地址
+    pub address: String,
+    /// 关联节点（提案为提案者）
+    pub node_id: Option<String>,
+    /// 记录自身的时间（最后活跃、贡献结束、分配或创建时间）
+    pub timestamp: i64,
+    /// 观察到该状态的 slot，较旧的更新会被忽略
+    pub slot: u64,
+    /// 按 IDL 解码的账户内容
+    pub data: serde_json::Value,
+}
+
+/// 链上合约事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainEvent {
+    pub signature: String,
+    /// 事件在交易中的序号
+    pub index: u32,
+    pub program: String,
+    pub name: String,
+    pub slot: u64,
+    pub data: serde_json::Value,
+}
+
+/// 收益到账校验结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutStatus {
+    /// 足够多的端点一致确认交易已最终确认且钱包入账与合约事件相符
+    Verified,
+    /// 端点一致，但交易未成功、签名无效、不在区块中或入账金额不符
+    Discrepancy,
+    /// 端点给出相互矛盾的结果
+    Disputed,
+    /// 尚未最终确认或可用端点不足，稍后重试
+    Unconfirmed,
+}
+
+impl PayoutStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayoutStatus::Verified => "verified",
+            PayoutStatus::Discrepancy => "discrepancy",
+            PayoutStatus::Disputed => "disputed",
+            PayoutStatus::Unconfirmed => "unconfirmed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "verified" => Ok(PayoutStatus::Verified),
+            "discrepancy" => Ok(PayoutStatus::Discrepancy),
+            "disputed" => Ok(PayoutStatus::Disputed),
+            "unconfirmed" => Ok(PayoutStatus::Unconfirmed),
+            other => Err(anyhow!("未知的到账校验结论: {}", other)),
+        }
+    }
+}
+
+/// 一笔收益交易的到账校验结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayoutCheck {
+    pub signature: String,
+    /// 应入账的钱包
+    pub wallet: String,
+    /// 交易的费用支付者（分配收益的节点）
+    pub payer: Option<String>,
+    pub slot: Option<u64>,
+    /// 预期金额（未指定时取合约 `RewardClaimed` 事件中的金额）
+    pub expected_amount: Option<u64>,
+    /// 钱包实际入账（已扣除由钱包支付的手续费的影响）
+    pub credited_amount: Option<i64>,
+    pub status: PayoutStatus,
+    pub detail: Option<String>,
+    /// 给出多数结果的端点数
+    pub endpoints_agreeing: u32,
+    pub endpoints_queried: u32,
+    pub checked_at: i64,
+}
+
+/// 推理 API 密钥（只保存哈希，明文仅在签发时返回一次）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub id: String,
+    pub name: String,
+    /// 明文前几位，便于用户辨认
+    pub key_prefix: String,
+    /// 明文的 SHA-256（hex）
+    pub key_hash: String,
+    pub created_at: i64,
+    pub revoked_at: Option<i64>,
+    /// 每分钟请求数上限，0 表示不限
+    pub requests_per_minute: u32,
+    /// 每个配额周期的 token 上限，0 表示不限
+    pub token_quota: u64,
+    pub quota_period_secs: u64,
+    /// 推理调度优先级类别（interactive / standard / batch）
+    pub priority_class: String,
+}
+
+/// 单次推理请求的用量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiUsage {
+    pub key_id: String,
+    pub timestamp: i64,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// 时间范围内按密钥汇总的用量
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiUsageTotals {
+    pub key_id: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// 本地统计数据库
+pub struct StatsStore {
+    conn: Mutex<Connection>,
+}
+
+impl StatsStore {
+    /// 打开（或创建）数据库文件
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// 内存数据库（测试和临时会话使用）
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    // ============ 训练指标 ============
+
+    pub fn record_tick(&self, metrics: &TickMetrics) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT INTO tick_metrics (timestamp, tick, loss, accuracy, samples_processed, connected_peers,
+                messages_sent, messages_received, bytes_sent, bytes_received)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                metrics.timestamp,
+                metrics.tick as i64,
+                metrics.loss,
+                metrics.accuracy,
+                metrics.samples_processed as i64,
+                metrics.connected_peers as i64,
+                metrics.messages_sent as i64,
+                metrics.messages_received as i64,
+                metrics.bytes_sent as i64,
+                metrics.bytes_received as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn ticks_between(&self, from: i64, to: i64) -> Result<Vec<TickMetrics>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, tick, loss, accuracy, samples_processed, connected_peers,
+                messages_sent, messages_received, bytes_sent, bytes_received
+             FROM tick_metrics WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp, tick",
+        )?;
+        let rows = stmt.query_map(params![from, to], tick_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 按 `bucket_secs` 秒的时间桶聚合训练指标
+    pub fn aggregate_ticks(&self, from: i64, to: i64, bucket_secs: i64) -> Result<Vec<TickAggregate>> {
+        if bucket_secs <= 0 {
+            return Err(anyhow!("时间桶长度必须为正数"));
+        }
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT (timestamp / ?3) * ?3 AS bucket, COUNT(*), AVG(loss), MIN(loss), AVG(accuracy),
+                MAX(connected_peers), MAX(samples_processed) - MIN(samples_processed)
+             FROM tick_metrics WHERE timestamp >= ?1 AND timestamp < ?2
+             GROUP BY bucket ORDER BY bucket",
+        )?;
+        let rows = stmt.query_map(params![from, to, bucket_secs], |row| {
+            Ok(TickAggregate {
+                bucket_start: row.get(0)?,
+                ticks: row.get::<_, i64>(1)? as u64,
+                avg_loss: row.get(2)?,
+                min_loss: row.get(3)?,
+                avg_accuracy: row.get(4)?,
+                max_connected_peers: row.get::<_, i64>(5)? as u64,
+                samples_processed: row.get::<_, i64>(6)?.max(0) as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 最近的 `limit` 个 tick，按时间升序
+    pub fn recent_ticks(&self, limit: usize) -> Result<Vec<TickMetrics>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, tick, loss, accuracy, samples_processed, connected_peers,
+                messages_sent, messages_received, bytes_sent, bytes_received
+             FROM tick_metrics ORDER BY timestamp DESC, tick DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], tick_from_row)?;
+        let mut ticks = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        ticks.reverse();
+        Ok(ticks)
+    }
+
+    // ============ 邻居快照 ============
+
+    /// 用当前邻居列表替换上一次的快照
+    pub fn record_peers(&self, recorded_at: i64, peers: &[PeerSample]) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM peer_snapshot", [])?;
+        for peer in peers {
+            tx.execute(
+                "INSERT OR REPLACE INTO peer_snapshot (peer_id, role, similarity, trust, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![peer.peer_id, peer.role, peer.similarity as f64, peer.trust, recorded_at],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 最近一次邻居快照及其记录时间
+    pub fn latest_peers(&self) -> Result<(Option<i64>, Vec<PeerSample>)> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT peer_id, role, similarity, trust, recorded_at FROM peer_snapshot ORDER BY role DESC, similarity DESC",
+        )?;
+        let mut recorded_at = None;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                PeerSample {
+                    peer_id: row.get(0)?,
+                    role: row.get(1)?,
+                    similarity: row.get::<_, f64>(2)? as f32,
+                    trust: row.get(3)?,
+                },
+                row.get::<_, i64>(4)?,
+            ))
+        })?;
+        let mut peers = Vec::new();
+        for row in rows {
+            let (peer, at) = row?;
+            recorded_at = Some(at);
+            peers.push(peer);
+        }
+        Ok((recorded_at, peers))
+    }
+
+    // ============ 传输会话 ============
+
+    pub fn record_transfer_started(
+        &self,
+        id: &str,
+        peer: Option<&str>,
+        direction: TransferDirection,
+        file_name: &str,
+        bytes: u64,
+        started_at: i64,
+    ) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT OR REPLACE INTO transfer_sessions (id, peer, direction, file_name, bytes, started_at, outcome)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                peer,
+                direction.as_str(),
+                file_name,
+                bytes as i64,
+                started_at,
+                TransferOutcome::InProgress.as_str(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 结束传输会话；`error` 为 None 表示成功。成功后不再需要续传，清掉已完成块的记录
+    pub fn record_transfer_finished(&self, id: &str, finished_at: i64, error: Option<&str>) -> Result<()> {
+        let outcome = if error.is_some() {
+            TransferOutcome::Failed
+        } else {
+            TransferOutcome::Completed
+        };
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE transfer_sessions SET finished_at = ?2, outcome = ?3, error = ?4 WHERE id = ?1",
+            params![id, finished_at, outcome.as_str(), error],
+        )?;
+        if error.is_none() {
+            conn.execute("DELETE FROM transfer_chunks WHERE transfer_id = ?1", params![id])?;
+        }
+        Ok(())
+    }
+
+    /// 记录分块传输中已确认（发送端）或已落盘（接收端）的块
+    pub fn record_chunk_done(&self, transfer_id: &str, chunk_index: u32) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT OR IGNORE INTO transfer_chunks (transfer_id, chunk_index) VALUES (?1, ?2)",
+            params![transfer_id, chunk_index],
+        )?;
+        Ok(())
+    }
+
+    /// 未完成传输已记录的块，用于断点续传
+    pub fn transfer_chunks(&self, transfer_id: &str) -> Result<Vec<u32>> {
+        let conn = self.conn.lock();
+        let mut stmt =
+            conn.prepare("SELECT chunk_index FROM transfer_chunks WHERE transfer_id = ?1 ORDER BY chunk_index")?;
+        let rows = stmt.query_map(params![transfer_id], |row| row.get::<_, i64>(0))?;
+        Ok(rows
+            .map(|row| row.map(|index| index as u32))
+            .collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 丢弃传输的块记录（例如校验失败需要整体重传）
+    pub fn clear_transfer_chunks(&self, transfer_id: &str) -> Result<()> {
+        self.conn
+            .lock()
+            .execute("DELETE FROM transfer_chunks WHERE transfer_id = ?1", params![transfer_id])?;
+        Ok(())
+    }
+
+    pub fn transfers_between(&self, from: i64, to: i64) -> Result<Vec<TransferRecord>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, peer, direction, file_name, bytes, started_at, finished_at, outcome, error
+             FROM transfer_sessions WHERE started_at >= ?1 AND started_at < ?2 ORDER BY started_at",
+        )?;
+        let rows = stmt.query_map(params![from, to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, i64>(5)?,
+                row.get::<_, Option<i64>>(6)?,
+                row.get::<_, String>(7)?,
+                row.get::<_, Option<String>>(8)?,
+            ))
+        })?;
+        rows.map(|row| -> Result<TransferRecord> {
+            let (id, peer, direction, file_name, bytes, started_at, finished_at, outcome, error) = row?;
+            Ok(TransferRecord {
+                id,
+                peer,
+                direction: TransferDirection::parse(&direction)?,
+                file_name,
+                bytes: bytes as u64,
+                started_at,
+                finished_at,
+                outcome: TransferOutcome::parse(&outcome)?,
+                error,
+            })
+        })
+        .collect()
+    }
+
+    pub fn transfer_totals(&self, from: i64, to: i64) -> Result<TransferTotals> {
+        let conn = self.conn.lock();
+        let totals = conn.query_row(
+            "SELECT COUNT(*),
+                COALESCE(SUM(outcome = 'completed'), 0),
+                COALESCE(SUM(outcome = 'failed'), 0),
+                COALESCE(SUM(CASE WHEN direction = 'inbound' AND outcome = 'completed' THEN bytes END), 0),
+                COALESCE(SUM(CASE WHEN direction = 'outbound' AND outcome = 'completed' THEN bytes END), 0)
+             FROM transfer_sessions WHERE started_at >= ?1 AND started_at < ?2",
+            params![from, to],
+            |row| {
+                Ok(TransferTotals {
+                    sessions: row.get::<_, i64>(0)? as u64,
+                    completed: row.get::<_, i64>(1)? as u64,
+                    failed: row.get::<_, i64>(2)? as u64,
+                    bytes_inbound: row.get::<_, i64>(3)? as u64,
+                    bytes_outbound: row.get::<_, i64>(4)? as u64,
+                })
+            },
+        )?;
+        Ok(totals)
+    }
+
+    // ============ 上链提交 ============
+
+    /// 记录待上链的提交（同一 ID 重复入队时保留原有记录）
+    pub fn enqueue_submission<T: Serialize>(&self, id: &str, kind: &str, payload: &T, queued_at: i64) -> Result<()> {
+        let payload = serde_json::to_string(payload)?;
+        self.conn.lock().execute(
+            "INSERT OR IGNORE INTO chain_submissions (id, kind, payload, queued_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, kind, payload, queued_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_submission_failure(&self, id: &str, error: &str) -> Result<()> {
+        self.conn.lock().execute(
+            "UPDATE chain_submissions SET attempts = attempts + 1, last_error = ?2 WHERE id = ?1",
+            params![id, error],
+        )?;
+        Ok(())
+    }
+
+    pub fn mark_submitted(&self, id: &str, signature: &str, submitted_at: i64) -> Result<()> {
+        self.conn.lock().execute(
+            "UPDATE chain_submissions SET submitted_at = ?2, signature = ?3 WHERE id = ?1",
+            params![id, submitted_at, signature],
+        )?;
+        Ok(())
+    }
+
+    /// 尚未上链的提交，按入队时间排序
+    pub fn pending_submissions(&self) -> Result<Vec<SubmissionRecord>> {
+        self.query_submissions(
+            "SELECT id, kind, payload, attempts, queued_at, last_error, submitted_at, signature
+             FROM chain_submissions WHERE submitted_at IS NULL ORDER BY queued_at",
+            params![],
+        )
+    }
+
+    /// 最近上链成功的提交，按上链时间倒序
+    pub fn recent_submitted(&self, limit: usize) -> Result<Vec<SubmissionRecord>> {
+        self.query_submissions(
+            "SELECT id, kind, payload, attempts, queued_at, last_error, submitted_at, signature
+             FROM chain_submissions WHERE submitted_at IS NOT NULL ORDER BY submitted_at DESC LIMIT ?1",
+            params![limit as i64],
+        )
+    }
+
+    pub fn submissions_between(&self, from: i64, to: i64) -> Result<Vec<SubmissionRecord>> {
+        self.query_submissions(
+            "SELECT id, kind, payload, attempts, queued_at, last_error, submitted_at, signature
+             FROM chain_submissions WHERE queued_at >= ?1 AND queued_at < ?2 ORDER BY queued_at",
+            params![from, to],
+        )
+    }
+
+    pub fn submission(&self, id: &str) -> Result<Option<SubmissionRecord>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row(
+                "SELECT id, kind, payload, attempts, queued_at, last_error, submitted_at, signature
+                 FROM chain_submissions WHERE id = ?1",
+                params![id],
+                submission_from_row,
+            )
+            .optional()?)
+    }
+
+    fn query_submissions(&self, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<SubmissionRecord>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, submission_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    // ============ 推理 API 密钥与用量 ============
+
+    pub fn insert_api_key(&self, key: &ApiKeyRecord) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT INTO api_keys (id, name, key_prefix, key_hash, created_at, revoked_at, requests_per_minute,
+                token_quota, quota_period_secs, priority_class)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                key.id,
+                key.name,
+                key.key_prefix,
+                key.key_hash,
+                key.created_at,
+                key.revoked_at,
+                key.requests_per_minute,
+                key.token_quota as i64,
+                key.quota_period_secs as i64,
+                key.priority_class,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn api_key(&self, id: &str) -> Result<Option<ApiKeyRecord>> {
+        self.query_api_key("WHERE id = ?1", id)
+    }
+
+    pub fn api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKeyRecord>> {
+        self.query_api_key("WHERE key_hash = ?1", key_hash)
+    }
+
+    fn query_api_key(&self, filter: &str, value: &str) -> Result<Option<ApiKeyRecord>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row(
+                &format!(
+                    "SELECT id, name, key_prefix, key_hash, created_at, revoked_at, requests_per_minute, token_quota,
+                        quota_period_secs, priority_class FROM api_keys {}",
+                    filter
+                ),
+                params![value],
+                api_key_from_row,
+            )
+            .optional()?)
+    }
+
+    /// 所有密钥（含已吊销），按签发时间排序
+    pub fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, key_prefix, key_hash, created_at, revoked_at, requests_per_minute, token_quota,
+                quota_period_secs, priority_class FROM api_keys ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map([], api_key_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 返回是否找到该密钥
+    pub fn rename_api_key(&self, id: &str, name: &str) -> Result<bool> {
+        let updated = self
+            .conn
+            .lock()
+            .execute("UPDATE api_keys SET name = ?2 WHERE id = ?1", params![id, name])?;
+        Ok(updated > 0)
+    }
+
+    /// 返回是否找到该密钥
+    pub fn set_api_key_priority(&self, id: &str, priority_class: &str) -> Result<bool> {
+        let updated = self.conn.lock().execute(
+            "UPDATE api_keys SET priority_class = ?2 WHERE id = ?1",
+            params![id, priority_class],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// 吊销密钥（用量记录保留用于结算），返回是否找到未吊销的该密钥
+    pub fn revoke_api_key(&self, id: &str, revoked_at: i64) -> Result<bool> {
+        let updated = self.conn.lock().execute(
+            "UPDATE api_keys SET revoked_at = ?2 WHERE id = ?1 AND revoked_at IS NULL",
+            params![id, revoked_at],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn record_api_usage(&self, usage: &ApiUsage) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT INTO api_usage (key_id, timestamp, model, prompt_tokens, completion_tokens)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                usage.key_id,
+                usage.timestamp,
+                usage.model,
+                usage.prompt_tokens as i64,
+                usage.completion_tokens as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 密钥自 `from` 起消耗的 token 数（输入 + 输出）
+    pub fn api_tokens_since(&self, key_id: &str, from: i64) -> Result<u64> {
+        let tokens: i64 = self.conn.lock().query_row(
+            "SELECT COALESCE(SUM(prompt_tokens + completion_tokens), 0) FROM api_usage
+             WHERE key_id = ?1 AND timestamp >= ?2",
+            params![key_id, from],
+            |row| row.get(0),
+        )?;
+        Ok(tokens as u64)
+    }
+
+    pub fn api_usage_totals(&self, from: i64, to: i64) -> Result<Vec<ApiUsageTotals>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT key_id, COUNT(*), COALESCE(SUM(prompt_tokens), 0), COALESCE(SUM(completion_tokens), 0)
+             FROM api_usage WHERE timestamp >= ?1 AND timestamp < ?2 GROUP BY key_id ORDER BY key_id",
+        )?;
+        let rows = stmt.query_map(params![from, to], |row| {
+            Ok(ApiUsageTotals {
+                key_id: row.get(0)?,
+                requests: row.get::<_, i64>(1)? as u64,
+                prompt_tokens: row.get::<_, i64>(2)? as u64,
+                completion_tokens: row.get::<_, i64>(3)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    // ============ 链上状态镜像 ============
+
+    /// 写入账户状态；已有更新 slot 的记录时忽略，返回是否写入
+    pub fn upsert_chain_record(&self, table: ChainTable, record: &ChainRecord) -> Result<bool> {
+        let sql = format!(
+            "INSERT INTO {table} (address, node_id, timestamp, slot, data) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(address) DO UPDATE SET node_id = excluded.node_id, timestamp = excluded.timestamp,
+                slot = excluded.slot, data = excluded.data
+             WHERE excluded.slot >= {table}.slot",
+            table = table.table()
+        );
+        let changed = self.conn.lock().execute(
+            &sql,
+            params![
+                record.address,
+                record.node_id,
+                record.timestamp,
+                record.slot as i64,
+                serde_json::to_string(&record.data)?,
+            ],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// 按时间倒序查询账户镜像，可按节点过滤
+    pub fn chain_records(&self, table: ChainTable, node_id: Option<&str>, limit: usize) -> Result<Vec<ChainRecord>> {
+        let sql = format!(
+            "SELECT address, node_id, timestamp, slot, data FROM {}
+             WHERE ?1 IS NULL OR node_id = ?1 ORDER BY timestamp DESC LIMIT ?2",
+            table.table()
+        );
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![node_id, limit as i64], |row| {
+            Ok((
+                ChainRecord {
+                    address: row.get(0)?,
+                    node_id: row.get(1)?,
+                    timestamp: row.get(2)?,
+                    slot: row.get::<_, i64>(3)? as u64,
+                    data: serde_json::Value::Null,
+                },
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+        rows.map(|row| {
+            let (mut record, data) = row?;
+            record.data = serde_json::from_str(&data)?;
+            Ok(record)
+        })
+        .collect()
+    }
+
+    /// 记录事件（同一交易中的同一事件重复收到时忽略），返回是否写入
+    pub fn record_chain_event(&self, event: &ChainEvent) -> Result<bool> {
+        let changed = self.conn.lock().execute(
+            "INSERT OR IGNORE INTO chain_events (signature, event_index, program, name, slot, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                event.signature,
+                event.index,
+                event.program,
+                event.name,
+                event.slot as i64,
+                serde_json::to_string(&event.data)?,
+            ],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// 最近的事件，按 slot 倒序，可按事件名过滤
+    pub fn chain_events(&self, name: Option<&str>, limit: usize) -> Result<Vec<ChainEvent>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT signature, event_index, program, name, slot, data FROM chain_events
+             WHERE ?1 IS NULL OR name = ?1 ORDER BY slot DESC, signature, event_index LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![name, limit as i64], |row| {
+            Ok((
+                ChainEvent {
+                    signature: row.get(0)?,
+                    index: row.get::<_, i64>(1)? as u32,
+                    program: row.get(2)?,
+                    name: row.get(3)?,
+                    slot: row.get::<_, i64>(4)? as u64,
+                    data: serde_json::Value::Null,
+                },
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+        rows.map(|row| {
+            let (mut event, data) = row?;
+            event.data = serde_json::from_str(&data)?;
+            Ok(event)
+        })
+        .collect()
+    }
+
+    /// 程序最近一次完整同步时的 slot
+    pub fn chain_sync_slot(&self, program: &str) -> Result<Option<u64>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row("SELECT slot FROM chain_sync WHERE program = ?1", params![program], |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()?
+            .map(|slot| slot as u64))
+    }
+
+    pub fn set_chain_sync_slot(&self, program: &str, slot: u64) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT INTO chain_sync (program, slot) VALUES (?1, ?2)
+             ON CONFLICT(program) DO UPDATE SET slot = MAX(slot, excluded.slot)",
+            params![program, slot as i64],
+        )?;
+        Ok(())
+    }
+
+    // ============ 收益到账校验 ============
+
+    /// 写入校验结果，同一交易重复校验时覆盖旧结果
+    pub fn record_payout_check(&self, check: &PayoutCheck) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT OR REPLACE INTO payout_checks (signature, wallet, payer, slot, expected_amount, credited_amount,
+                status, detail, endpoints_agreeing, endpoints_queried, checked_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                check.signature,
+                check.wallet,
+                check.payer,
+                check.slot.map(|slot| slot as i64),
+                check.expected_amount.map(|amount| amount as i64),
+                check.credited_amount,
+                check.status.as_str(),
+                check.detail,
+                check.endpoints_agreeing,
+                check.endpoints_queried,
+                check.checked_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 最近的校验结果，按校验时间倒序，可按结论过滤
+    pub fn payout_checks(&self, status: Option<PayoutStatus>, limit: usize) -> Result<Vec<PayoutCheck>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT signature, wallet, payer, slot, expected_amount, credited_amount, status, detail,
+                endpoints_agreeing, endpoints_queried, checked_at
+             FROM payout_checks WHERE ?1 IS NULL OR status = ?1 ORDER BY checked_at DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![status.map(|s| s.as_str()), limit as i64], |row| {
+            Ok((
+                PayoutCheck {
+                    signature: row.get(0)?,
+                    wallet: row.get(1)?,
+                    payer: row.get(2)?,
+                    slot: row.get::<_, Option<i64>>(3)?.map(|slot| slot as u64),
+                    expected_amount: row.get::<_, Option<i64>>(4)?.map(|amount| amount as u64),
+                    credited_amount: row.get(5)?,
+                    status: PayoutStatus::Unconfirmed,
+                    detail: row.get(7)?,
+                    endpoints_agreeing: row.get(8)?,
+                    endpoints_queried: row.get(9)?,
+                    checked_at: row.get(10)?,
+                },
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+        rows.map(|row| {
+            let (mut check, status) = row?;
+            check.status = PayoutStatus::parse(&status)?;
+            Ok(check)
+        })
+        .collect()
+    }
+
+    // ============ 维护 ============
+
+    /// 删除早于 `before` 的指标、已结束的传输和已上链的提交，返回删除的行数
+    pub fn prune_before(&self, before: i64) -> Result<usize> {
+        let conn = self.conn.lock();
+        let mut removed = conn.execute("DELETE FROM tick_metrics WHERE timestamp < ?1", params![before])?;
+        removed += conn.execute(
+            "DELETE FROM transfer_sessions WHERE started_at < ?1 AND finished_at IS NOT NULL",
+            params![before],
+        )?;
+        removed += conn.execute(
+            "DELETE FROM transfer_chunks WHERE transfer_id NOT IN (SELECT id FROM transfer_sessions)",
+            [],
+        )?;
+        removed += conn.execute(
+            "DELETE FROM chain_submissions WHERE queued_at < ?1 AND submitted_at IS NOT NULL",
+            params![before],
+        )?;
+        Ok(removed)
+    }
+}
+
+fn tick_from_row(row: &Row<'_>) -> rusqlite::Result<TickMetrics> {
+    Ok(TickMetrics {
+        timestamp: row.get(0)?,
+        tick: row.get::<_, i64>(1)? as u64,
+        loss: row.get(2)?,
+        accuracy: row.get(3)?,
+        samples_processed: row.get::<_, i64>(4)? as u64,
+        connected_peers: row.get::<_, i64>(5)? as u64,
+        messages_sent: row.get::<_, i64>(6)? as u64,
+        messages_received: row.get::<_, i64>(7)? as u64,
+        bytes_sent: row.get::<_, i64>(8)? as u64,
+        bytes_received: row.get::<_, i64>(9)? as u64,
+    })
+}
+
+fn api_key_from_row(row: &Row<'_>) -> rusqlite::Result<ApiKeyRecord> {
+    Ok(ApiKeyRecord {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        key_prefix: row.get(2)?,
+        key_hash: row.get(3)?,
+        created_at: row.get(4)?,
+        revoked_at: row.get(5)?,
+        requests_per_minute: row.get(6)?,
+        token_quota: row.get::<_, i64>(7)? as u64,
+        quota_period_secs: row.get::<_, i64>(8)? as u64,
+        priority_class: row.get(9)?,
+    })
+}
+
+fn submission_from_row(row: &Row<'_>) -> rusqlite::Result<SubmissionRecord> {
+    Ok(SubmissionRecord {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        payload: row.get(2)?,
+        attempts: row.get::<_, i64>(3)? as u32,
+        queued_at: row.get(4)?,
+        last_error: row.get(5)?,
+        submitted_at: row.get(6)?,
+        signature: row.get(7)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_queries_and_aggregates() {
+        let store = StatsStore::open_in_memory().unwrap();
+        for (i, ts) in [0i64, 30, 70, 90].iter().enumerate() {
+            store
+                .record_tick(&TickMetrics {
+                    timestamp: *ts,
+                    tick: i as u64,
+                    loss: 1.0 - i as f64 * 0.1,
+                    accuracy: 0.5,
+                    samples_processed: i as u64 * 100,
+                    connected_peers: i as u64,
+                    messages_sent: 0,
+                    messages_received: 0,
+                    bytes_sent: 0,
+                    bytes_received: 0,
+                })
+                .unwrap();
+        }
+        assert_eq!(store.ticks_between(30, 90).unwrap().len(), 2);
+        let buckets = store.aggregate_ticks(0, 120, 60).unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[1].bucket_start, 60);
+        assert_eq!(buckets[1].samples_processed, 100);
+        assert!((buckets[0].min_loss - 0.9).abs() < 1e-9);
+
+        store
+            .record_transfer_started("t1", Some("peer"), TransferDirection::Outbound, "shard.bin", 512, 10)
+            .unwrap();
+        store
+            .record_transfer_started("t2", None, TransferDirection::Inbound, "shard.bin", 256, 20)
+            .unwrap();
+        store.record_transfer_finished("t1", 15, None).unwrap();
+        store.record_transfer_finished("t2", 25, Some("hash mismatch")).unwrap();
+        let totals = store.transfer_totals(0, 100).unwrap();
+        assert_eq!((totals.sessions, totals.completed, totals.failed), (2, 1, 1));
+        assert_eq!((totals.bytes_outbound, totals.bytes_inbound), (512, 0));
+
+        store.enqueue_submission("c1", "contribution", &"payload", 5).unwrap();
+        store.record_submission_failure("c1", "offline").unwrap();
+        assert_eq!(store.pending_submissions().unwrap()[0].attempts, 1);
+        store.mark_submitted("c1", "sig", 50).unwrap();
+        assert!(store.pending_submissions().unwrap().is_empty());
+        assert_eq!(store.prune_before(100).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_chain_mirror_ignores_stale_updates() {
+        let store = StatsStore::open_in_memory().unwrap();
+        let record = |slot: u64, status: &str| ChainRecord {
+            address: "node-pda".to_string(),
+            node_id: Some("node-a".to_string()),
+            timestamp: 100,
+            slot,
+            data: serde_json::json!({ "status": status }),
+        };
+        assert!(store.upsert_chain_record(ChainTable::Nodes, &record(10, "Active")).unwrap());
+        // 重连补齐时可能先收到旧快照
+        assert!(!store.upsert_chain_record(ChainTable::Nodes, &record(8, "Inactive")).unwrap());
+        assert!(store.upsert_chain_record(ChainTable::Nodes, &record(12, "Slashed")).unwrap());
+        let nodes = store.chain_records(ChainTable::Nodes, Some("node-a"), 10).unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].data["status"], "Slashed");
+        assert!(store.chain_records(ChainTable::Rewards, None, 10).unwrap().is_empty());
+
+        let event = ChainEvent {
+            signature: "sig".to_string(),
+            index: 0,
+            program: "node_management".to_string(),
+            name: "ReputationUpdated".to_string(),
+            slot: 12,
+            data: serde_json::json!({ "reputation_score": 700 }),
+        };
+        assert!(store.record_chain_event(&event).unwrap());
+        assert!(!store.record_chain_event(&event).unwrap());
+        assert_eq!(store.chain_events(Some("ReputationUpdated"), 10).unwrap(), vec![event]);
+
+        store.set_chain_sync_slot("node_management", 12).unwrap();
+        store.set_chain_sync_slot("node_management", 9).unwrap();
+        assert_eq!(store.chain_sync_slot("node_management").unwrap(), Some(12));
+    }
+
+    #[test]
+    fn test_payout_checks_are_replaced_on_recheck() {
+        let store = StatsStore::open_in_memory().unwrap();
+        let mut check = PayoutCheck {
+            signature: "sig".to_string(),
+            wallet: "wallet".to_string(),
+            payer: Some("distributor".to_string()),
+            slot: None,
+            expected_amount: None,
+            credited_amount: None,
+            status: PayoutStatus::Unconfirmed,
+            detail: None,
+            endpoints_agreeing: 0,
+            endpoints_queried: 2,
+            checked_at: 10,
+        };
+        store.record_payout_check(&check).unwrap();
+        check.slot = Some(42);
+        check.expected_amount = Some(500);
+        check.credited_amount = Some(300);
+        check.status = PayoutStatus::Discrepancy;
+        check.checked_at = 20;
+        store.record_payout_check(&check).unwrap();
+
+        assert_eq!(store.payout_checks(None, 10).unwrap(), vec![check.clone()]);
+        assert_eq!(store.payout_checks(Some(PayoutStatus::Discrepancy), 10).unwrap(), vec![check]);
+        assert!(store.payout_checks(Some(PayoutStatus::Verified), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_transfer_chunks_for_resume() {
+        let store = StatsStore::open_in_memory().unwrap();
+        store
+            .record_transfer_started("t1", Some("peer"), TransferDirection::Outbound, "shard.bin", 512, 10)
+            .unwrap();
+        store.record_chunk_done("t1", 3).unwrap();
+        store.record_chunk_done("t1", 1).unwrap();
+        store.record_chunk_done("t1", 3).unwrap();
+        // 失败的传输保留块记录，重启后可续传
+        store.record_transfer_finished("t1", 12, Some("timeout")).unwrap();
+        assert_eq!(store.transfer_chunks("t1").unwrap(), vec![1, 3]);
+
+        store
+            .record_transfer_started("t1", Some("peer"), TransferDirection::Outbound, "shard.bin", 512, 20)
+            .unwrap();
+        store.record_transfer_finished("t1", 25, None).unwrap();
+        assert!(store.transfer_chunks("t1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recent_ticks_and_peer_snapshot() {
+        let store = StatsStore::open_in_memory().unwrap();
+        for tick in 0..5u64 {
+            store
+                .record_tick(&TickMetrics {
+                    timestamp: tick as i64,
+                    tick,
+                    loss: 0.0,
+                    accuracy: 0.0,
+                    samples_processed: 0,
+                    connected_peers: 0,
+                    messages_sent: 0,
+                    messages_received: 0,
+                    bytes_sent: 0,
+                    bytes_received: 0,
+                })
+                .unwrap();
+        }
+        let recent: Vec<u64> = store.recent_ticks(3).unwrap().iter().map(|t| t.tick).collect();
+        assert_eq!(recent, vec![2, 3, 4]);
+
+        let peer = |id: &str, role: &str| PeerSample {
+            peer_id: id.to_string(),
+            role: role.to_string(),
+            similarity: 0.5,
+            trust: None,
+        };
+        store.record_peers(10, &[peer("a", "primary"), peer("b", "backup")]).unwrap();
+        store.record_peers(20, &[peer("c", "primary")]).unwrap();
+        let (recorded_at, peers) = store.latest_peers().unwrap();
+        assert_eq!(recorded_at, Some(20));
+        assert_eq!(peers, vec![peer("c", "primary")]);
+    }
+}
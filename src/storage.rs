@@ -0,0 +1,341 @@
+//! 磁盘空间管理
+//!
+//! 模型下载和分片切分可能写满磁盘。存储管理器负责：
+//! - 下载、切分前的空间预检：写入后仍需保留 `min_free_bytes`，不足时先淘汰模型缓存，仍不足则拒绝
+//! - 周期检查：按 `model_cache.max_bytes` 执行缓存配额，可用空间低于阈值时生成警告（记入统计），
+//!   低于保留值时自动淘汰可淘汰的缓存对象（未固定、未租用）
+
+use crate::model_cache::ModelCache;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const MB: f64 = 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub enabled: bool,
+    /// 下载和切分完成后至少保留的可用空间（字节），低于该值时自动淘汰缓存
+    pub min_free_bytes: u64,
+    /// 可用空间低于该值时发出警告（字节）
+    pub warn_free_bytes: u64,
+    /// 周期检查间隔（秒）
+    pub check_interval_secs: u64,
+    /// 除模型缓存目录外需要监视的目录（checkpoint、统计数据库等）
+    pub watch_dirs: Vec<PathBuf>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_free_bytes: 2 * 1024 * 1024 * 1024,
+            warn_free_bytes: 10 * 1024 * 1024 * 1024,
+            check_interval_secs: 300,
+            watch_dirs: Vec::new(),
+        }
+    }
+}
+
+/// 目录所在磁盘的空间
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskSpace {
+    pub mount_point: PathBuf,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// 查询目录所在磁盘的空间；目录尚不存在时按最近的已存在上级目录查询
+pub fn disk_space(path: &Path) -> Result<DiskSpace> {
+    let absolute = std::path::absolute(path)?;
+    let existing = absolute
+        .ancestors()
+        .find(|dir| dir.exists())
+        .ok_or_else(|| anyhow!("无法定位 {} 所在的磁盘", path.display()))?;
+    let resolved = existing.canonicalize()?;
+
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| resolved.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| DiskSpace {
+            mount_point: disk.mount_point().to_path_buf(),
+            total_bytes: disk.total_space(),
+            available_bytes: disk.available_space(),
+        })
+        .ok_or_else(|| anyhow!("无法定位 {} 所在的磁盘", path.display()))
+}
+
+/// 文件或目录（递归）占用的字节数，读取失败的项按 0 计
+pub fn path_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| path_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// 可用空间告警级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageLevel {
+    /// 低于警告阈值
+    Low,
+    /// 低于保留值，已自动淘汰缓存
+    Critical,
+}
+
+/// 低空间警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageWarning {
+    /// 触发警告的监视目录
+    pub path: PathBuf,
+    pub mount_point: PathBuf,
+    pub available_bytes: u64,
+    pub level: StorageLevel,
+    /// 本次自动淘汰释放的缓存字节数
+    pub pruned_bytes: u64,
+    pub occurred_at: i64,
+}
+
+/// 存储管理器
+pub struct StorageGovernor {
+    config: StorageConfig,
+    cache: Option<Arc<ModelCache>>,
+}
+
+impl StorageGovernor {
+    /// `cache` 为可淘汰的模型缓存，未启用缓存时为 None
+    pub fn new(config: StorageConfig, cache: Option<Arc<ModelCache>>) -> Self {
+        Self { config, cache }
+    }
+
+    pub fn config(&self) -> &StorageConfig {
+        &self.config
+    }
+
+    /// 写入 `needed_bytes` 到 `dir` 前预检：写入后仍需保留 `min_free_bytes`。
+    /// 不足时先淘汰同一磁盘上的缓存对象，仍不足则返回错误
+    pub fn preflight(&self, dir: &Path, needed_bytes: u64) -> Result<DiskSpace> {
+        let mut space = disk_space(dir)?;
+        if !self.config.enabled {
+            return Ok(space);
+        }
+        let required = needed_bytes.saturating_add(self.config.min_free_bytes);
+        if space.available_bytes < required && self.prune_on(&space, required - space.available_bytes)? > 0 {
+            space = disk_space(dir)?;
+        }
+        if space.available_bytes < required {
+            return Err(anyhow!(
+                "{} 所在磁盘空间不足：需要 {:.1} MB（含保留 {:.1} MB），可用 {:.1} MB",
+                dir.display(),
+                required as f64 / MB,
+                self.config.min_free_bytes as f64 / MB,
+                space.available_bytes as f64 / MB
+            ));
+        }
+        Ok(space)
+    }
+
+    /// 按淘汰策略删除未固定、未租用的缓存对象，直到释放 `bytes`，返回实际释放的字节数
+    pub fn prune(&self, bytes: u64) -> Result<u64> {
+        let Some(cache) = &self.cache else {
+            return Ok(0);
+        };
+        let total = cache.usage().total_bytes;
+        let evicted = cache.evict_to(total.saturating_sub(bytes))?;
+        Ok(evicted.iter().map(|entry| entry.size).sum())
+    }
+
+    /// 只有缓存与目标在同一磁盘时，淘汰缓存才能腾出空间
+    fn prune_on(&self, space: &DiskSpace, bytes: u64) -> Result<u64> {
+        let Some(cache) = &self.cache else {
+            return Ok(0);
+        };
+        if disk_space(&cache.config().dir)?.mount_point != space.mount_point {
+            return Ok(0);
+        }
+        self.prune(bytes)
+    }
+
+    /// 可用空间对应的告警级别
+    pub fn level(&self, available_bytes: u64) -> Option<StorageLevel> {
+        if available_bytes < self.config.min_free_bytes {
+            Some(StorageLevel::Critical)
+        } else if available_bytes < self.config.warn_free_bytes {
+            Some(StorageLevel::Low)
+        } else {
+            None
+        }
+    }
+
+    /// 周期检查：执行缓存配额，为空间不足的磁盘生成警告（同一磁盘只报一次），
+    /// 低于保留值时淘汰缓存直到回到警告阈值以上
+    pub fn check(&self) -> Vec<StorageWarning> {
+        if let Some(cache) = &self.cache {
+            let quota = cache.config().max_bytes;
+            if quota > 0 {
+                if let Err(e) = cache.evict_to(quota) {
+                    log::warn!("[存储] 执行缓存配额失败: {}", e);
+                }
+            }
+        }
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        let mut seen = HashSet::new();
+        let mut warnings = Vec::new();
+        for dir in self.watched_dirs() {
+            let space = match disk_space(&dir) {
+                Ok(space) => space,
+                Err(e) => {
+                    log::debug!("[存储] 查询 {} 的磁盘空间失败: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            if !seen.insert(space.mount_point.clone()) {
+                continue;
+            }
+            let Some(level) = self.level(space.available_bytes) else {
+                continue;
+            };
+            let mut pruned_bytes = 0;
+            if level == StorageLevel::Critical {
+                let target = self.config.warn_free_bytes.saturating_sub(space.available_bytes);
+                pruned_bytes = self.prune_on(&space, target).unwrap_or_else(|e| {
+                    log::warn!("[存储] 淘汰缓存失败: {}", e);
+                    0
+                });
+            }
+            log::warn!(
+                "[存储] {} 所在磁盘可用空间 {:.1} MB，已淘汰缓存 {:.1} MB",
+                dir.display(),
+                space.available_bytes as f64 / MB,
+                pruned_bytes as f64 / MB
+            );
+            warnings.push(StorageWarning {
+                path: dir,
+                mount_point: space.mount_point,
+                available_bytes: space.available_bytes,
+                level,
+                pruned_bytes,
+                occurred_at: chrono::Utc::now().timestamp(),
+            });
+        }
+        warnings
+    }
+
+    fn watched_dirs(&self) -> Vec<PathBuf> {
+        let cache_dir = self.cache.as_ref().map(|cache| cache.config().dir.clone());
+        cache_dir.into_iter().chain(self.config.watch_dirs.iter().cloned()).collect()
+    }
+}
+
+/// 下载过程中的空间预检：首次得知总大小时按剩余字节预检，空间不足时置位取消标志
+pub struct DownloadPreflight<'a> {
+    governor: &'a StorageGovernor,
+    dir: PathBuf,
+    cancel: &'a AtomicBool,
+    checked: bool,
+    error: Option<anyhow::Error>,
+}
+
+impl<'a> DownloadPreflight<'a> {
+    pub fn new(governor: &'a StorageGovernor, dir: impl Into<PathBuf>, cancel: &'a AtomicBool) -> Self {
+        Self {
+            governor,
+            dir: dir.into(),
+            cancel,
+            checked: false,
+            error: None,
+        }
+    }
+
+    /// 在下载进度回调中调用；总大小未知时不预检
+    pub fn observe(&mut self, downloaded_bytes: u64, total_bytes: Option<u64>) {
+        let Some(total) = total_bytes.filter(|_| !self.checked) else {
+            return;
+        };
+        self.checked = true;
+        if let Err(e) = self.governor.preflight(&self.dir, total.saturating_sub(downloaded_bytes)) {
+            self.error = Some(e);
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// 因空间不足而取消时返回原因
+    pub fn into_error(self) -> Option<anyhow::Error> {
+        self.error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_cache::ModelCacheConfig;
+
+    #[test]
+    fn test_levels_and_preflight() {
+        let dir = tempfile::tempdir().unwrap();
+        let governor = StorageGovernor::new(
+            StorageConfig {
+                min_free_bytes: 0,
+                warn_free_bytes: 100,
+                ..Default::default()
+            },
+            None,
+        );
+        assert_eq!(governor.level(50), Some(StorageLevel::Low));
+        assert_eq!(governor.level(100), None);
+
+        // 尚不存在的目录按上级目录所在磁盘查询
+        let space = governor.preflight(&dir.path().join("models/new"), 0).unwrap();
+        assert!(space.available_bytes > 0);
+        assert!(governor.preflight(dir.path(), u64::MAX / 2).is_err());
+        std::fs::create_dir_all(dir.path().join("models")).unwrap();
+        std::fs::write(dir.path().join("models/weights.bin"), vec![0u8; 64]).unwrap();
+        assert_eq!(path_size(dir.path()), 64);
+
+        let cancel = AtomicBool::new(false);
+        let mut download = DownloadPreflight::new(&governor, dir.path(), &cancel);
+        download.observe(0, None);
+        assert!(!cancel.load(Ordering::Relaxed));
+        download.observe(0, Some(u64::MAX / 2));
+        assert!(cancel.load(Ordering::Relaxed));
+        assert!(download.into_error().is_some());
+    }
+
+    #[test]
+    fn test_prune_skips_pinned_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![1u8; 100]).unwrap();
+        std::fs::write(dir.path().join("b.bin"), vec![2u8; 100]).unwrap();
+        let cache = Arc::new(
+            ModelCache::open(ModelCacheConfig {
+                dir: dir.path().join("cache"),
+                max_bytes: 0,
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        cache.insert_file("m/a.bin", &dir.path().join("a.bin"), false).unwrap();
+        cache.insert_file("m/b.bin", &dir.path().join("b.bin"), false).unwrap();
+        cache.set_pinned("m/a.bin", true).unwrap();
+
+        let governor = StorageGovernor::new(StorageConfig::default(), Some(Arc::clone(&cache)));
+        assert_eq!(governor.prune(150).unwrap(), 100);
+        assert_eq!(cache.entries().len(), 1);
+        assert!(cache.entries()[0].pinned);
+    }
+}
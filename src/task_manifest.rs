@@ -0,0 +1,294 @@
+//! 协调者签名的任务清单
+//!
+//! 轮次/任务分配必须以清单形式下发，并由该轮当选协调者的身份密钥签名：
+//! - 签名者需与选举记录中的协调者一致
+//! - 未签名、签名无效、过期或被新轮次取代的分配一律拒绝
+//! - 验证通过的清单会被缓存，清单哈希写入算力贡献记录以便追溯
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+
+/// 协调者选举记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElectionRecord {
+    /// 轮次
+    pub round: u64,
+    /// 当选协调者的节点 ID（身份公钥 base58）
+    pub coordinator_id: String,
+    /// 当选时间（Unix 秒）
+    pub elected_at: i64,
+}
+
+/// 任务清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskManifest {
+    pub round: u64,
+    pub task_id: String,
+    /// 被分配的节点
+    pub assignees: Vec<String>,
+    /// 任务基于的模型版本哈希
+    pub model_hash: String,
+    /// 签发时间（Unix 秒）
+    pub issued_at: i64,
+    /// 过期时间（Unix 秒）
+    pub expires_at: i64,
+    /// 签发的协调者
+    pub coordinator_id: String,
+}
+
+impl TaskManifest {
+    /// 规范化字节（签名和哈希的输入）
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// 清单哈希（blake3 十六进制）
+    pub fn hash(&self) -> String {
+        blake3::hash(&self.canonical_bytes()).to_hex().to_string()
+    }
+
+    /// 使用协调者身份签名
+    pub fn sign(self, identity: &NodeIdentity) -> SignedTaskManifest {
+        let signature = identity.sign(&self.canonical_bytes());
+        SignedTaskManifest {
+            manifest: self,
+            signature: Some(signature),
+        }
+    }
+}
+
+/// 带签名的任务清单（网络上传输的形式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTaskManifest {
+    pub manifest: TaskManifest,
+    /// 协调者签名；为空的分配会被拒绝
+    #[serde(default)]
+    pub signature: Option<SolSignature>,
+}
+
+/// 验证通过的清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifiedManifest {
+    pub manifest: TaskManifest,
+    pub manifest_hash: String,
+}
+
+/// 拒绝原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ManifestRejection {
+    #[error("任务分配未签名")]
+    Unsigned,
+
+    #[error("轮次 {round} 没有选举记录")]
+    UnknownRound { round: u64 },
+
+    #[error("签名者 {signer} 不是轮次协调者 {expected}")]
+    WrongCoordinator { signer: String, expected: String },
+
+    #[error("清单签名无效")]
+    BadSignature,
+
+    #[error("清单已过期: expires_at={expires_at}")]
+    Expired { expires_at: i64 },
+
+    #[error("轮次 {round} 已被更新的轮次 {latest} 取代")]
+    Stale { round: u64, latest: u64 },
+}
+
+/// 拒绝计数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestStats {
+    pub accepted: u64,
+    pub rejected_unsigned: u64,
+    pub rejected_coordinator: u64,
+    pub rejected_signature: u64,
+    pub rejected_stale: u64,
+}
+
+/// 任务清单验证器
+pub struct TaskManifestVerifier {
+    elections: RwLock<HashMap<u64, ElectionRecord>>,
+    verified: RwLock<HashMap<String, VerifiedManifest>>,
+    stats: RwLock<ManifestStats>,
+}
+
+impl TaskManifestVerifier {
+    pub fn new() -> Self {
+        Self {
+            elections: RwLock::new(HashMap::new()),
+            verified: RwLock::new(HashMap::new()),
+            stats: RwLock::new(ManifestStats::default()),
+        }
+    }
+
+    /// 记录选举结果
+    pub fn record_election(&self, record: ElectionRecord) {
+        self.elections.write().insert(record.round, record);
+    }
+
+    /// 最新轮次
+    pub fn latest_round(&self) -> Option<u64> {
+        self.elections.read().keys().max().copied()
+    }
+
+    /// 验证清单，成功后缓存
+    pub fn verify(&self, signed: &SignedTaskManifest, now: i64) -> Result<VerifiedManifest, ManifestRejection> {
+        let result = self.check(signed, now);
+        let mut stats = self.stats.write();
+        match &result {
+            Ok(verified) => {
+                stats.accepted += 1;
+                self.verified
+                    .write()
+                    .insert(verified.manifest.task_id.clone(), verified.clone());
+            }
+            Err(ManifestRejection::Unsigned) => stats.rejected_unsigned += 1,
+            Err(ManifestRejection::UnknownRound { .. } | ManifestRejection::WrongCoordinator { .. }) => {
+                stats.rejected_coordinator += 1
+            }
+            Err(ManifestRejection::BadSignature) => stats.rejected_signature += 1,
+            Err(ManifestRejection::Expired { .. } | ManifestRejection::Stale { .. }) => stats.rejected_stale += 1,
+        }
+        result
+    }
+
+    fn check(&self, signed: &SignedTaskManifest, now: i64) -> Result<VerifiedManifest, ManifestRejection> {
+        let manifest = &signed.manifest;
+
+        // 已缓存且内容一致的清单无需重复验签
+        if let Some(cached) = self.verified.read().get(&manifest.task_id) {
+            if &cached.manifest == manifest && manifest.expires_at > now {
+                return Ok(cached.clone());
+            }
+        }
+
+        let signature = signed.signature.as_ref().ok_or(ManifestRejection::Unsigned)?;
+
+        let elections = self.elections.read();
+        let election = elections
+            .get(&manifest.round)
+            .ok_or(ManifestRejection::UnknownRound { round: manifest.round })?;
+        if signature.pubkey != election.coordinator_id || manifest.coordinator_id != election.coordinator_id {
+            return Err(ManifestRejection::WrongCoordinator {
+                signer: signature.pubkey.clone(),
+                expected: election.coordinator_id.clone(),
+            });
+        }
+        if !verify_signature(&manifest.canonical_bytes(), signature) {
+            return Err(ManifestRejection::BadSignature);
+        }
+
+        if manifest.expires_at <= now {
+            return Err(ManifestRejection::Expired {
+                expires_at: manifest.expires_at,
+            });
+        }
+        if let Some(latest) = elections.keys().max().copied() {
+            if manifest.round < latest {
+                return Err(ManifestRejection::Stale {
+                    round: manifest.round,
+                    latest,
+                });
+            }
+        }
+
+        Ok(VerifiedManifest {
+            manifest: manifest.clone(),
+            manifest_hash: manifest.hash(),
+        })
+    }
+
+    /// 查询已验证的清单
+    pub fn get(&self, task_id: &str) -> Option<VerifiedManifest> {
+        self.verified.read().get(task_id).cloned()
+    }
+
+    /// 清理过期清单和旧轮次的选举记录
+    pub fn prune(&self, now: i64) {
+        self.verified.write().retain(|_, v| v.manifest.expires_at > now);
+        if let Some(latest) = self.latest_round() {
+            self.elections.write().retain(|round, _| *round + 1 >= latest);
+        }
+    }
+
+    pub fn stats(&self) -> ManifestStats {
+        self.stats.read().clone()
+    }
+}
+
+impl Default for TaskManifestVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::FileKeystore;
+    use std::sync::Arc;
+
+    fn identity(name: &str) -> NodeIdentity {
+        let path = std::env::temp_dir().join(format!("williw-manifest-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        NodeIdentity::with_keystore(Arc::new(FileKeystore::new(path))).unwrap()
+    }
+
+    fn manifest(round: u64, coordinator_id: String) -> TaskManifest {
+        TaskManifest {
+            round,
+            task_id: format!("task-{}", round),
+            assignees: vec!["node-a".to_string()],
+            model_hash: "abc".to_string(),
+            issued_at: 100,
+            expires_at: 200,
+            coordinator_id,
+        }
+    }
+
+    #[test]
+    fn test_rejects_spoofed_and_stale_assignments() {
+        let coordinator = identity("coordinator");
+        let attacker = identity("attacker");
+        let verifier = TaskManifestVerifier::new();
+        verifier.record_election(ElectionRecord {
+            round: 1,
+            coordinator_id: coordinator.node_id(),
+            elected_at: 0,
+        });
+
+        let signed = manifest(1, coordinator.node_id()).sign(&coordinator);
+        let verified = verifier.verify(&signed, 150).unwrap();
+        assert_eq!(verified.manifest_hash, signed.manifest.hash());
+
+        let spoofed = manifest(1, coordinator.node_id()).sign(&attacker);
+        assert!(matches!(
+            verifier.verify(&spoofed, 150),
+            Err(ManifestRejection::WrongCoordinator { .. })
+        ));
+
+        let mut unsigned = SignedTaskManifest {
+            manifest: manifest(1, coordinator.node_id()),
+            signature: None,
+        };
+        unsigned.manifest.task_id = "other".to_string();
+        assert_eq!(verifier.verify(&unsigned, 150).unwrap_err(), ManifestRejection::Unsigned);
+
+        verifier.record_election(ElectionRecord {
+            round: 2,
+            coordinator_id: coordinator.node_id(),
+            elected_at: 120,
+        });
+        let mut old = manifest(1, coordinator.node_id());
+        old.task_id = "late".to_string();
+        assert!(matches!(
+            verifier.verify(&old.sign(&coordinator), 150),
+            Err(ManifestRejection::Stale { .. })
+        ));
+        assert_eq!(verifier.stats().accepted, 1);
+    }
+}
@@ -0,0 +1,122 @@
+//! 模拟 Workers 协调者
+//!
+//! 在内存中复现边缘注册表的语义：节点注册并定期心跳，空闲节点领取任务，
+//! 心跳超时的节点被移除，其未完成的任务重新排队。时间同样以 tick 计。
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+#[derive(Debug, Clone)]
+struct NodeEntry {
+    last_heartbeat: u64,
+}
+
+#[derive(Debug)]
+pub struct MockCoordinator {
+    heartbeat_ttl: u64,
+    nodes: HashMap<String, NodeEntry>,
+    pending: VecDeque<String>,
+    /// 任务 -> 执行节点
+    running: BTreeMap<String, String>,
+    completed: Vec<String>,
+    reassigned: u64,
+}
+
+impl MockCoordinator {
+    pub fn new(heartbeat_ttl: u64) -> Self {
+        Self {
+            heartbeat_ttl,
+            nodes: HashMap::new(),
+            pending: VecDeque::new(),
+            running: BTreeMap::new(),
+            completed: Vec::new(),
+            reassigned: 0,
+        }
+    }
+
+    /// 注册节点；重复注册视为节点重启，它名下未完成的任务重新排队
+    pub fn register(&mut self, node_id: &str, now: u64) {
+        self.requeue_tasks_of(node_id);
+        self.nodes.insert(node_id.to_string(), NodeEntry { last_heartbeat: now });
+    }
+
+    /// 心跳，未注册（或已被清理）的节点返回 false，需要重新注册
+    pub fn heartbeat(&mut self, node_id: &str, now: u64) -> bool {
+        match self.nodes.get_mut(node_id) {
+            Some(entry) => {
+                entry.last_heartbeat = now;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn submit(&mut self, task_id: impl Into<String>) {
+        self.pending.push_back(task_id.into());
+    }
+
+    /// 已注册且没有任务在执行的节点领取下一个任务
+    pub fn poll(&mut self, node_id: &str) -> Option<String> {
+        if !self.nodes.contains_key(node_id) || self.running.values().any(|owner| owner == node_id) {
+            return None;
+        }
+        let task_id = self.pending.pop_front()?;
+        self.running.insert(task_id.clone(), node_id.to_string());
+        Some(task_id)
+    }
+
+    /// 完成任务；任务已被重新分配给其他节点时返回 false
+    pub fn complete(&mut self, node_id: &str, task_id: &str) -> bool {
+        if self.running.get(task_id).map(String::as_str) != Some(node_id) {
+            return false;
+        }
+        self.running.remove(task_id);
+        self.completed.push(task_id.to_string());
+        true
+    }
+
+    /// 移除心跳超时的节点并把它们的任务放回队首，返回移除的节点数
+    pub fn expire(&mut self, now: u64) -> usize {
+        let ttl = self.heartbeat_ttl;
+        let expired: Vec<String> = self
+            .nodes
+            .iter()
+            .filter(|(_, entry)| now.saturating_sub(entry.last_heartbeat) > ttl)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for node_id in &expired {
+            self.nodes.remove(node_id);
+            self.requeue_tasks_of(node_id);
+        }
+        expired.len()
+    }
+
+    fn requeue_tasks_of(&mut self, node_id: &str) {
+        let orphaned: Vec<String> = self
+            .running
+            .iter()
+            .filter(|(_, owner)| *owner == node_id)
+            .map(|(task, _)| task.clone())
+            .collect();
+        for task_id in orphaned {
+            self.running.remove(&task_id);
+            self.pending.push_front(task_id);
+            self.reassigned += 1;
+        }
+    }
+
+    pub fn registered(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn completed(&self) -> &[String] {
+        &self.completed
+    }
+
+    pub fn reassigned(&self) -> u64 {
+        self.reassigned
+    }
+}
@@ -0,0 +1,24 @@
+//! 多节点端到端测试套件（`testkit` 特性）
+//!
+//! - [`network`]：按 tick 投递的进程内网络，支持上下线、分区和慢节点
+//! - [`node`]：使用真实身份与共识签名的模拟训练节点
+//! - [`coordinator`]：复现边缘注册表语义的内存协调者
+//! - [`cluster`]：把以上组件组合成可按脚本运行的集群
+//! - [`scenario`]：节点流失、网络分区、慢节点等场景脚本
+//! - [`solana`]：本地 `solana-test-validator` 包装
+//!
+//! 集成测试见 `tests/multi_node_sim.rs`，运行 `cargo test --features testkit --test multi_node_sim`。
+
+pub mod cluster;
+pub mod coordinator;
+pub mod network;
+pub mod node;
+pub mod scenario;
+pub mod solana;
+
+pub use cluster::{ScenarioReport, SimCluster, SimClusterConfig};
+pub use coordinator::MockCoordinator;
+pub use network::{InMemoryNetwork, NetworkStats};
+pub use node::SimNode;
+pub use scenario::{ScenarioAction, ScenarioScript, ScenarioStep};
+pub use solana::SolanaTestValidator;
@@ -0,0 +1,183 @@
+//! 进程内消息网络
+//!
+//! 以逻辑 tick 为时间单位投递消息，完全确定、不依赖真实时钟，便于在 CI 中复现。
+//! 支持节点上下线、网络分区和为单个节点设置额外延迟（慢节点）。
+
+use crate::consensus::SignedGossip;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+struct Envelope {
+    from: String,
+    to: String,
+    deliver_at: u64,
+    message: SignedGossip,
+}
+
+/// 网络统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkStats {
+    pub sent: u64,
+    pub delivered: u64,
+    /// 因分区或目标离线被丢弃的消息
+    pub dropped: u64,
+}
+
+#[derive(Default)]
+struct NetworkState {
+    online: HashSet<String>,
+    /// 分区：节点所在的组号，不同组之间不通；为空表示没有分区
+    partition: HashMap<String, usize>,
+    /// 节点的额外延迟（tick）
+    delays: HashMap<String, u64>,
+    in_flight: Vec<Envelope>,
+    inboxes: HashMap<String, VecDeque<(String, SignedGossip)>>,
+    now: u64,
+    stats: NetworkStats,
+}
+
+impl NetworkState {
+    fn reachable(&self, from: &str, to: &str) -> bool {
+        if !self.online.contains(from) || !self.online.contains(to) {
+            return false;
+        }
+        match (self.partition.get(from), self.partition.get(to)) {
+            (Some(a), Some(b)) => a == b,
+            // 未列入任何分区组的节点与所有人隔离，避免漏配时静默连通
+            (None, None) => self.partition.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// 进程内网络，克隆后共享同一状态
+#[derive(Clone, Default)]
+pub struct InMemoryNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+impl InMemoryNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 节点上线
+    pub fn join(&self, peer: &str) {
+        let mut state = self.state.lock();
+        state.online.insert(peer.to_string());
+        state.inboxes.entry(peer.to_string()).or_default();
+    }
+
+    /// 节点下线，未读消息和发给它的在途消息一并丢弃
+    pub fn leave(&self, peer: &str) {
+        let mut state = self.state.lock();
+        state.online.remove(peer);
+        let unread = state.inboxes.remove(peer).map_or(0, |inbox| inbox.len() as u64);
+        let before = state.in_flight.len();
+        state.in_flight.retain(|envelope| envelope.to != peer);
+        let dropped = unread + (before - state.in_flight.len()) as u64;
+        state.stats.dropped += dropped;
+    }
+
+    pub fn is_online(&self, peer: &str) -> bool {
+        self.state.lock().online.contains(peer)
+    }
+
+    pub fn online_peers(&self) -> Vec<String> {
+        let mut peers: Vec<String> = self.state.lock().online.iter().cloned().collect();
+        peers.sort();
+        peers
+    }
+
+    /// 按组划分网络，组之间的消息被丢弃
+    pub fn partition(&self, groups: &[Vec<String>]) {
+        let mut state = self.state.lock();
+        state.partition = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(index, group)| group.iter().map(move |peer| (peer.clone(), index)))
+            .collect();
+    }
+
+    /// 恢复全连通
+    pub fn heal(&self) {
+        self.state.lock().partition.clear();
+    }
+
+    /// 设置节点收发消息的额外延迟（慢节点），0 表示恢复正常
+    pub fn set_delay(&self, peer: &str, ticks: u64) {
+        let mut state = self.state.lock();
+        if ticks == 0 {
+            state.delays.remove(peer);
+        } else {
+            state.delays.insert(peer.to_string(), ticks);
+        }
+    }
+
+    pub fn send(&self, from: &str, to: &str, message: SignedGossip) {
+        let mut state = self.state.lock();
+        state.stats.sent += 1;
+        if !state.reachable(from, to) {
+            state.stats.dropped += 1;
+            return;
+        }
+        let delay = 1 + state.delays.get(from).copied().unwrap_or(0) + state.delays.get(to).copied().unwrap_or(0);
+        let deliver_at = state.now + delay;
+        state.in_flight.push(Envelope {
+            from: from.to_string(),
+            to: to.to_string(),
+            deliver_at,
+            message,
+        });
+    }
+
+    /// 广播给除自己以外的所有在线节点
+    pub fn broadcast(&self, from: &str, message: &SignedGossip) {
+        for peer in self.online_peers() {
+            if peer != from {
+                self.send(from, &peer, message.clone());
+            }
+        }
+    }
+
+    /// 推进一个 tick，投递到期的消息；投递时分区状态变化的消息会被丢弃
+    pub fn advance(&self) {
+        let mut state = self.state.lock();
+        state.now += 1;
+        let now = state.now;
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut state.in_flight)
+            .into_iter()
+            .partition(|envelope| envelope.deliver_at <= now);
+        state.in_flight = pending;
+        for envelope in due {
+            if state.reachable(&envelope.from, &envelope.to) {
+                state.stats.delivered += 1;
+                if let Some(inbox) = state.inboxes.get_mut(&envelope.to) {
+                    inbox.push_back((envelope.from, envelope.message));
+                }
+            } else {
+                state.stats.dropped += 1;
+            }
+        }
+    }
+
+    /// 取出节点收到的全部消息 `(发送者, 消息)`
+    pub fn drain(&self, peer: &str) -> Vec<(String, SignedGossip)> {
+        self.state
+            .lock()
+            .inboxes
+            .get_mut(peer)
+            .map(|inbox| inbox.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn now(&self) -> u64 {
+        self.state.lock().now
+    }
+
+    pub fn stats(&self) -> NetworkStats {
+        self.state.lock().stats.clone()
+    }
+}
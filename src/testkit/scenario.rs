@@ -0,0 +1,127 @@
+//! 场景脚本
+//!
+//! 脚本是按 tick 排列的网络事件，可以用内置构造函数生成，也可以从 JSON 文件加载
+//! （见 `tests/scenarios/`）。节点用启动顺序的下标引用。
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScenarioAction {
+    /// 节点下线
+    Leave { node: usize },
+    /// 节点重新上线（保持原身份）
+    Join { node: usize },
+    /// 网络分区，未列出的节点与所有人隔离
+    Partition { groups: Vec<Vec<usize>> },
+    /// 恢复全连通
+    Heal,
+    /// 节点变慢：收发消息额外延迟若干 tick，0 表示恢复
+    SlowPeer { node: usize, delay: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioStep {
+    pub at: u64,
+    #[serde(flatten)]
+    pub action: ScenarioAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioScript {
+    pub name: String,
+    pub nodes: usize,
+    /// 运行的总 tick 数
+    pub ticks: u64,
+    /// 开始时提交给协调者的任务数
+    #[serde(default)]
+    pub tasks: usize,
+    #[serde(default)]
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl ScenarioScript {
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let script: Self = serde_json::from_str(json)?;
+        for step in &script.steps {
+            let nodes: Vec<usize> = match &step.action {
+                ScenarioAction::Leave { node } | ScenarioAction::Join { node } | ScenarioAction::SlowPeer { node, .. } => {
+                    vec![*node]
+                }
+                ScenarioAction::Partition { groups } => groups.iter().flatten().copied().collect(),
+                ScenarioAction::Heal => Vec::new(),
+            };
+            if let Some(node) = nodes.into_iter().find(|node| *node >= script.nodes) {
+                anyhow::bail!("场景 {} 第 {} tick 引用了不存在的节点 {}", script.name, step.at, node);
+            }
+        }
+        Ok(script)
+    }
+
+    /// 节点轮流下线再上线
+    pub fn node_churn(nodes: usize, ticks: u64) -> Self {
+        let period = (ticks / (nodes as u64 + 1)).max(4);
+        let steps = (0..nodes.saturating_sub(1))
+            .flat_map(|node| {
+                let at = period * (node as u64 + 1) / 2;
+                [
+                    ScenarioStep {
+                        at,
+                        action: ScenarioAction::Leave { node },
+                    },
+                    ScenarioStep {
+                        at: at + period,
+                        action: ScenarioAction::Join { node },
+                    },
+                ]
+            })
+            .collect();
+        Self {
+            name: "node_churn".to_string(),
+            nodes,
+            ticks,
+            tasks: nodes * 3,
+            steps,
+        }
+    }
+
+    /// 前三分之一时间后一分为二，三分之二处恢复
+    pub fn network_partition(nodes: usize, ticks: u64) -> Self {
+        let half = nodes / 2;
+        Self {
+            name: "network_partition".to_string(),
+            nodes,
+            ticks,
+            tasks: nodes * 2,
+            steps: vec![
+                ScenarioStep {
+                    at: ticks / 3,
+                    action: ScenarioAction::Partition {
+                        groups: vec![(0..half).collect(), (half..nodes).collect()],
+                    },
+                },
+                ScenarioStep {
+                    at: ticks * 2 / 3,
+                    action: ScenarioAction::Heal,
+                },
+            ],
+        }
+    }
+
+    /// 一个节点全程比其他节点慢
+    pub fn slow_peer(nodes: usize, ticks: u64, delay: u64) -> Self {
+        Self {
+            name: "slow_peer".to_string(),
+            nodes,
+            ticks,
+            tasks: nodes * 2,
+            steps: vec![ScenarioStep {
+                at: 0,
+                action: ScenarioAction::SlowPeer {
+                    node: nodes - 1,
+                    delay,
+                },
+            }],
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! 本地 Solana 测试验证器
+//!
+//! 启动 `solana-test-validator`（需已安装 Solana CLI），使用临时账本目录，
+//! 等待 RPC 端口可连接后返回，析构时结束进程并删除账本。未安装时 [`SolanaTestValidator::start`]
+//! 返回 `None`，依赖链上环境的测试应据此跳过。
+
+use anyhow::{bail, Result};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// 验证器可执行文件，可用 `SOLANA_TEST_VALIDATOR` 覆盖路径
+const VALIDATOR_ENV: &str = "SOLANA_TEST_VALIDATOR";
+const DEFAULT_BINARY: &str = "solana-test-validator";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct SolanaTestValidator {
+    child: Child,
+    ledger: PathBuf,
+    rpc_port: u16,
+}
+
+impl SolanaTestValidator {
+    /// 在指定 RPC 端口启动验证器；找不到可执行文件时返回 `Ok(None)`
+    pub fn start(rpc_port: u16) -> Result<Option<Self>> {
+        let binary = std::env::var(VALIDATOR_ENV).unwrap_or_else(|_| DEFAULT_BINARY.to_string());
+        let ledger = std::env::temp_dir().join(format!("williw-validator-{}-{}", std::process::id(), rpc_port));
+        let spawned = Command::new(&binary)
+            .arg("--ledger")
+            .arg(&ledger)
+            .arg("--rpc-port")
+            .arg(rpc_port.to_string())
+            .arg("--reset")
+            .arg("--quiet")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let child = match spawned {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                println!("[测试套件] 未找到 {}，跳过链上测试", binary);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mut validator = Self {
+            child,
+            ledger,
+            rpc_port,
+        };
+        validator.wait_ready()?;
+        Ok(Some(validator))
+    }
+
+    pub fn rpc_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.rpc_port)
+    }
+
+    fn wait_ready(&mut self) -> Result<()> {
+        let addr = SocketAddr::from(([127, 0, 0, 1], self.rpc_port));
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Some(status) = self.child.try_wait()? {
+                bail!("solana-test-validator 启动失败: {}", status);
+            }
+            if TcpStream::connect_timeout(&addr, Duration::from_millis(200)).is_ok() {
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+        bail!("solana-test-validator 在 {:?} 内未就绪", STARTUP_TIMEOUT)
+    }
+}
+
+impl Drop for SolanaTestValidator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.ledger);
+    }
+}
@@ -149,6 +149,11 @@ impl TopologySelector {
         self.config.max_neighbors
     }
 
+    /// 运行时调整主邻居上限，下一次选择邻居时生效
+    pub fn set_max_neighbors(&mut self, max_neighbors: usize) {
+        self.config.max_neighbors = max_neighbors;
+    }
+
     pub fn failover_pool(&self) -> usize {
         self.config.failover_pool
     }
@@ -0,0 +1,317 @@
+//! 推理 API 密钥、限流与配额
+//!
+//! - 签发：明文 `sk-williw-<hex>` 只在签发时返回一次，本地数据库只保存 SHA-256
+//! - 准入：每个密钥按令牌桶限制每分钟请求数，并按滑动配额周期累计 token 用量（从本地数据库统计）
+//! - 记账：每次请求的用量写入本地数据库，再交给 [`UsageHook`] 上报到奖励/计费流水线
+
+use crate::stats::{ApiKeyRecord, ApiUsage, StatsStore};
+use crate::training::scheduler::PriorityClass;
+use anyhow::Result;
+use parking_lot::Mutex;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 明文密钥前缀
+pub const KEY_PREFIX: &str = "sk-williw-";
+
+/// 签发密钥时指定的限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiKeyLimits {
+    /// 每分钟请求数上限，0 表示不限
+    pub requests_per_minute: u32,
+    /// 每个配额周期的 token 上限，0 表示不限
+    pub token_quota: u64,
+    pub quota_period_secs: u64,
+    /// 推理调度优先级
+    pub priority: PriorityClass,
+}
+
+impl Default for ApiKeyLimits {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 60,
+            token_quota: 1_000_000,
+            quota_period_secs: 24 * 3600,
+            priority: PriorityClass::Standard,
+        }
+    }
+}
+
+/// 新签发的密钥，`secret` 只在此处出现一次
+#[derive(Debug, Clone)]
+pub struct IssuedApiKey {
+    pub record: ApiKeyRecord,
+    pub secret: String,
+}
+
+/// 准入失败原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApiKeyError {
+    #[error("API 密钥无效")]
+    Invalid,
+    #[error("API 密钥已吊销")]
+    Revoked,
+    #[error("请求过于频繁，请 {retry_after_secs} 秒后重试")]
+    RateLimited { retry_after_secs: u64 },
+    #[error("token 配额已用完: {used}/{quota}")]
+    QuotaExceeded { used: u64, quota: u64 },
+    #[error("读取密钥数据失败: {0}")]
+    Store(String),
+}
+
+impl ApiKeyError {
+    /// 对应的 HTTP 状态码
+    pub fn status_code(&self) -> u16 {
+        match self {
+            ApiKeyError::Invalid | ApiKeyError::Revoked => 401,
+            ApiKeyError::RateLimited { .. } | ApiKeyError::QuotaExceeded { .. } => 429,
+            ApiKeyError::Store(_) => 500,
+        }
+    }
+}
+
+/// 准入通过的请求，处理完后交回 [`ApiKeyManager::record_usage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Admission {
+    pub key: ApiKeyRecord,
+    /// 本配额周期内剩余的 token 数（不限时为 None）
+    pub tokens_remaining: Option<u64>,
+}
+
+impl Admission {
+    /// 密钥配置的调度优先级，无法识别时按标准处理
+    pub fn priority(&self) -> PriorityClass {
+        PriorityClass::parse(&self.key.priority_class).unwrap_or_default()
+    }
+}
+
+/// 用量上报钩子
+pub trait UsageHook: Send + Sync {
+    fn on_usage(&self, key: &ApiKeyRecord, usage: &ApiUsage);
+}
+
+/// 把每次用量作为 `inference_usage` 提交排入上链队列，由奖励结算流程统一处理
+pub struct SubmissionQueueHook {
+    store: Arc<StatsStore>,
+}
+
+impl SubmissionQueueHook {
+    pub fn new(store: Arc<StatsStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl UsageHook for SubmissionQueueHook {
+    fn on_usage(&self, _key: &ApiKeyRecord, usage: &ApiUsage) {
+        let id = uuid::Uuid::new_v4().to_string();
+        if let Err(e) = self.store.enqueue_submission(&id, "inference_usage", usage, usage.timestamp) {
+            log::warn!("[API 密钥] 用量排队上报失败: {}", e);
+        }
+    }
+}
+
+struct RateBucket {
+    tokens: f64,
+    last: Instant,
+}
+
+/// API 密钥的签发、准入和用量记账
+pub struct ApiKeyManager {
+    store: Arc<StatsStore>,
+    hooks: Vec<Arc<dyn UsageHook>>,
+    buckets: Mutex<HashMap<String, RateBucket>>,
+}
+
+impl ApiKeyManager {
+    pub fn new(store: Arc<StatsStore>) -> Self {
+        Self {
+            store,
+            hooks: Vec::new(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 添加用量上报钩子
+    pub fn with_hook(mut self, hook: Arc<dyn UsageHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    pub fn issue(&self, name: &str, limits: ApiKeyLimits) -> Result<IssuedApiKey> {
+        let mut bytes = [0u8; 24];
+        rand::rng().fill_bytes(&mut bytes);
+        let secret = format!("{}{}", KEY_PREFIX, hex::encode(bytes));
+        let record = ApiKeyRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            key_prefix: secret[..KEY_PREFIX.len() + 6].to_string(),
+            key_hash: hash_secret(&secret),
+            created_at: chrono::Utc::now().timestamp(),
+            revoked_at: None,
+            requests_per_minute: limits.requests_per_minute,
+            token_quota: limits.token_quota,
+            quota_period_secs: limits.quota_period_secs,
+            priority_class: limits.priority.as_str().to_string(),
+        };
+        self.store.insert_api_key(&record)?;
+        Ok(IssuedApiKey { record, secret })
+    }
+
+    pub fn list(&self) -> Result<Vec<ApiKeyRecord>> {
+        self.store.api_keys()
+    }
+
+    pub fn rename(&self, id: &str, name: &str) -> Result<bool> {
+        self.store.rename_api_key(id, name)
+    }
+
+    pub fn set_priority(&self, id: &str, priority: PriorityClass) -> Result<bool> {
+        self.store.set_api_key_priority(id, priority.as_str())
+    }
+
+    pub fn revoke(&self, id: &str) -> Result<bool> {
+        self.buckets.lock().remove(id);
+        self.store.revoke_api_key(id, chrono::Utc::now().timestamp())
+    }
+
+    pub fn authenticate(&self, secret: &str) -> Result<ApiKeyRecord, ApiKeyError> {
+        if !secret.starts_with(KEY_PREFIX) {
+            return Err(ApiKeyError::Invalid);
+        }
+        let key = self
+            .store
+            .api_key_by_hash(&hash_secret(secret))
+            .map_err(|e| ApiKeyError::Store(e.to_string()))?
+            .ok_or(ApiKeyError::Invalid)?;
+        if key.revoked_at.is_some() {
+            return Err(ApiKeyError::Revoked);
+        }
+        Ok(key)
+    }
+
+    /// 认证并检查配额和限流；`estimated_tokens` 为请求预计消耗的 token 数
+    pub fn admit(&self, secret: &str, estimated_tokens: u64) -> Result<Admission, ApiKeyError> {
+        self.admit_at(secret, estimated_tokens, Instant::now(), chrono::Utc::now().timestamp())
+    }
+
+    pub fn admit_at(&self, secret: &str, estimated_tokens: u64, now: Instant, unix_now: i64) -> Result<Admission, ApiKeyError> {
+        let key = self.authenticate(secret)?;
+
+        // 先查配额（不消耗），再扣限流令牌
+        let tokens_remaining = if key.token_quota > 0 {
+            let since = unix_now - key.quota_period_secs as i64;
+            let used = self
+                .store
+                .api_tokens_since(&key.id, since)
+                .map_err(|e| ApiKeyError::Store(e.to_string()))?;
+            if used + estimated_tokens > key.token_quota {
+                return Err(ApiKeyError::QuotaExceeded {
+                    used,
+                    quota: key.token_quota,
+                });
+            }
+            Some(key.token_quota - used)
+        } else {
+            None
+        };
+
+        if key.requests_per_minute > 0 {
+            let capacity = key.requests_per_minute as f64;
+            let per_sec = capacity / 60.0;
+            let mut buckets = self.buckets.lock();
+            let bucket = buckets.entry(key.id.clone()).or_insert(RateBucket {
+                tokens: capacity,
+                last: now,
+            });
+            bucket.tokens = (bucket.tokens + now.saturating_duration_since(bucket.last).as_secs_f64() * per_sec).min(capacity);
+            bucket.last = now;
+            if bucket.tokens < 1.0 {
+                let retry_after_secs = ((1.0 - bucket.tokens) * 60.0 / capacity).ceil() as u64;
+                return Err(ApiKeyError::RateLimited { retry_after_secs });
+            }
+            bucket.tokens -= 1.0;
+        }
+
+        Ok(Admission { key, tokens_remaining })
+    }
+
+    /// 记录请求的实际用量并上报
+    pub fn record_usage(&self, admission: &Admission, model: &str, prompt_tokens: u64, completion_tokens: u64) -> Result<ApiUsage> {
+        let usage = ApiUsage {
+            key_id: admission.key.id.clone(),
+            timestamp: chrono::Utc::now().timestamp(),
+            model: model.to_string(),
+            prompt_tokens,
+            completion_tokens,
+        };
+        self.store.record_api_usage(&usage)?;
+        for hook in &self.hooks {
+            hook.on_usage(&admission.key, &usage);
+        }
+        Ok(usage)
+    }
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// 粗略估算文本的 token 数（约 4 个字符一个 token），用于准入前的配额检查和没有分词器时的记账
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rate_limit_quota_and_revocation() {
+        let store = Arc::new(StatsStore::open_in_memory().unwrap());
+        let manager = ApiKeyManager::new(store.clone()).with_hook(Arc::new(SubmissionQueueHook::new(store.clone())));
+        let issued = manager
+            .issue(
+                "ci",
+                ApiKeyLimits {
+                    requests_per_minute: 2,
+                    token_quota: 100,
+                    quota_period_secs: 3600,
+                    priority: PriorityClass::Batch,
+                },
+            )
+            .unwrap();
+        assert!(issued.secret.starts_with(KEY_PREFIX));
+        assert_ne!(store.api_keys().unwrap()[0].key_hash, issued.secret);
+        assert_eq!(manager.authenticate("sk-williw-wrong"), Err(ApiKeyError::Invalid));
+
+        let start = Instant::now();
+        let unix = chrono::Utc::now().timestamp();
+        let admission = manager.admit_at(&issued.secret, 10, start, unix).unwrap();
+        assert_eq!(admission.tokens_remaining, Some(100));
+        assert_eq!(admission.priority(), PriorityClass::Batch);
+        manager.admit_at(&issued.secret, 10, start, unix).unwrap();
+        assert!(matches!(
+            manager.admit_at(&issued.secret, 10, start, unix),
+            Err(ApiKeyError::RateLimited { retry_after_secs: 30 })
+        ));
+
+        // 用量落库并排入上报队列，之后超出配额的请求被拒绝
+        manager.record_usage(&admission, "gpt2", 40, 50).unwrap();
+        assert_eq!(store.pending_submissions().unwrap()[0].kind, "inference_usage");
+        let later = start + Duration::from_secs(60);
+        assert_eq!(
+            manager.admit_at(&issued.secret, 20, later, unix),
+            Err(ApiKeyError::QuotaExceeded { used: 90, quota: 100 })
+        );
+        assert_eq!(manager.admit_at(&issued.secret, 5, later, unix).unwrap().tokens_remaining, Some(10));
+
+        assert!(manager.revoke(&issued.record.id).unwrap());
+        assert_eq!(manager.authenticate(&issued.secret), Err(ApiKeyError::Revoked));
+    }
+}
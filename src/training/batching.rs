@@ -0,0 +1,272 @@
+//! 动态批处理
+//!
+//! 逐个请求做前向计算时算力利用率很低。批处理器把几毫秒窗口内到达的请求合并成一次
+//! [`BatchModel::generate_batch`]：第一个请求到达后开启窗口，批次装满、窗口结束或
+//! 批内最早的截止时间临近时立即执行；已过截止时间的请求不再参与计算。
+//! 批次上限按设备基准测试分数推导。
+
+use crate::device::DeviceScore;
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+/// 支持整批前向计算的模型
+pub trait BatchModel: Send + Sync + 'static {
+    type Request: Send + 'static;
+    type Response: Send + 'static;
+
+    /// 一次前向处理整批请求，返回与输入一一对应的结果
+    fn generate_batch(&self, requests: Vec<Self::Request>) -> Result<Vec<Self::Response>>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatcherConfig {
+    pub max_batch_size: usize,
+    /// 第一个请求到达后等待后续请求的窗口（毫秒）
+    pub window_ms: u64,
+    /// 排队请求上限，超过时直接拒绝
+    pub queue_capacity: usize,
+}
+
+impl Default for BatcherConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 8,
+            window_ms: 5,
+            queue_capacity: 1024,
+        }
+    }
+}
+
+impl BatcherConfig {
+    /// 按校准分推导批次上限：参考设备（1.0 分）为 8，按分数线性缩放，范围 1..=64
+    pub fn from_device_score(score: &DeviceScore) -> Self {
+        Self {
+            max_batch_size: ((score.score * 8.0).round() as usize).clamp(1, 64),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BatchError {
+    #[error("请求在进入批次前已超过截止时间")]
+    DeadlineExceeded,
+    #[error("批处理队列已满")]
+    QueueFull,
+    #[error("批处理器已关闭")]
+    Closed,
+    #[error("批量推理失败: {0}")]
+    Model(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatcherStats {
+    pub batches: u64,
+    /// 进入前向计算的请求数
+    pub requests: u64,
+    pub expired: u64,
+    pub failed: u64,
+    pub largest_batch: usize,
+}
+
+impl BatcherStats {
+    pub fn average_batch_size(&self) -> f64 {
+        if self.batches == 0 {
+            0.0
+        } else {
+            self.requests as f64 / self.batches as f64
+        }
+    }
+}
+
+struct Pending<M: BatchModel> {
+    request: M::Request,
+    deadline: Option<Instant>,
+    reply: oneshot::Sender<Result<M::Response, BatchError>>,
+}
+
+/// 动态批处理器，需在 tokio 运行时内创建
+pub struct DynamicBatcher<M: BatchModel> {
+    tx: mpsc::Sender<Pending<M>>,
+    stats: Arc<Mutex<BatcherStats>>,
+}
+
+impl<M: BatchModel> Clone for DynamicBatcher<M> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<M: BatchModel> DynamicBatcher<M> {
+    pub fn new(model: Arc<M>, config: BatcherConfig) -> Self {
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let stats = Arc::new(Mutex::new(BatcherStats::default()));
+        tokio::spawn(run_batcher(model, config, rx, stats.clone()));
+        Self { tx, stats }
+    }
+
+    pub fn stats(&self) -> BatcherStats {
+        self.stats.lock().clone()
+    }
+
+    /// 提交一个请求并等待结果；`timeout` 为请求的截止时间（从提交起算）
+    pub async fn submit(&self, request: M::Request, timeout: Option<Duration>) -> Result<M::Response, BatchError> {
+        let (reply, rx) = oneshot::channel();
+        let pending = Pending {
+            request,
+            deadline: timeout.map(|t| Instant::now() + t),
+            reply,
+        };
+        self.tx.try_send(pending).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => BatchError::QueueFull,
+            mpsc::error::TrySendError::Closed(_) => BatchError::Closed,
+        })?;
+        rx.await.map_err(|_| BatchError::Closed)?
+    }
+}
+
+async fn run_batcher<M: BatchModel>(
+    model: Arc<M>,
+    config: BatcherConfig,
+    mut rx: mpsc::Receiver<Pending<M>>,
+    stats: Arc<Mutex<BatcherStats>>,
+) {
+    let max_batch = config.max_batch_size.max(1);
+    let window = Duration::from_millis(config.window_ms);
+
+    while let Some(first) = rx.recv().await {
+        let mut close_at = Instant::now() + window;
+        if let Some(deadline) = first.deadline {
+            close_at = close_at.min(deadline);
+        }
+        let mut batch = vec![first];
+
+        // 收集窗口内到达的请求；批内有请求快到截止时间时提前关闭窗口
+        while batch.len() < max_batch {
+            match tokio::time::timeout_at(close_at, rx.recv()).await {
+                Ok(Some(pending)) => {
+                    if let Some(deadline) = pending.deadline {
+                        close_at = close_at.min(deadline);
+                    }
+                    batch.push(pending);
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        let now = Instant::now();
+        let (live, expired): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .partition(|p| p.deadline.is_none_or(|deadline| deadline > now));
+        if !expired.is_empty() {
+            stats.lock().expired += expired.len() as u64;
+            for pending in expired {
+                let _ = pending.reply.send(Err(BatchError::DeadlineExceeded));
+            }
+        }
+        if live.is_empty() {
+            continue;
+        }
+
+        let size = live.len();
+        let (requests, replies): (Vec<_>, Vec<_>) = live.into_iter().map(|p| (p.request, p.reply)).unzip();
+        let model = model.clone();
+        let outcome = tokio::task::spawn_blocking(move || model.generate_batch(requests))
+            .await
+            .map_err(|e| BatchError::Model(e.to_string()))
+            .and_then(|result| result.map_err(|e| BatchError::Model(e.to_string())))
+            .and_then(|responses| {
+                if responses.len() == size {
+                    Ok(responses)
+                } else {
+                    Err(BatchError::Model(format!("返回 {} 个结果，期望 {}", responses.len(), size)))
+                }
+            });
+
+        {
+            let mut stats = stats.lock();
+            stats.batches += 1;
+            stats.requests += size as u64;
+            stats.largest_batch = stats.largest_batch.max(size);
+            if outcome.is_err() {
+                stats.failed += size as u64;
+            }
+        }
+        match outcome {
+            Ok(responses) => {
+                for (reply, response) in replies.into_iter().zip(responses) {
+                    let _ = reply.send(Ok(response));
+                }
+            }
+            Err(e) => {
+                log::warn!("[批处理] {}", e);
+                for reply in replies {
+                    let _ = reply.send(Err(e.clone()));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Doubler {
+        batch_sizes: Mutex<Vec<usize>>,
+    }
+
+    impl BatchModel for Doubler {
+        type Request = u32;
+        type Response = u32;
+
+        fn generate_batch(&self, requests: Vec<u32>) -> Result<Vec<u32>> {
+            self.batch_sizes.lock().push(requests.len());
+            Ok(requests.into_iter().map(|r| r * 2).collect())
+        }
+    }
+
+    fn doubler() -> Arc<Doubler> {
+        Arc::new(Doubler {
+            batch_sizes: Mutex::new(Vec::new()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_coalesces_requests_within_window() {
+        let model = doubler();
+        let config = BatcherConfig {
+            max_batch_size: 4,
+            window_ms: 50,
+            ..Default::default()
+        };
+        let batcher = DynamicBatcher::new(model.clone(), config);
+        let results = futures::future::join_all((1..=5).map(|i| batcher.submit(i, None))).await;
+        let results: Vec<u32> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(results, vec![2, 4, 6, 8, 10]);
+        assert_eq!(*model.batch_sizes.lock(), vec![4, 1]);
+        assert_eq!(batcher.stats().average_batch_size(), 2.5);
+    }
+
+    #[tokio::test]
+    async fn test_expired_requests_are_skipped() {
+        let model = doubler();
+        let batcher = DynamicBatcher::new(model.clone(), BatcherConfig::default());
+        let (expired, live) = tokio::join!(
+            batcher.submit(1, Some(Duration::ZERO)),
+            batcher.submit(2, Some(Duration::from_secs(5)))
+        );
+        assert_eq!(expired, Err(BatchError::DeadlineExceeded));
+        assert_eq!(live, Ok(4));
+        assert_eq!(batcher.stats().expired, 1);
+    }
+}
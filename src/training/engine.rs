@@ -4,14 +4,86 @@
 
 use crate::config::AppConfig;
 use crate::types::{SparseUpdate, TensorSnapshot};
-use anyhow::Result;
+use crate::training::evaluation::EvalModel;
+use crate::training::pipeline::Sample;
+use anyhow::{anyhow, Result};
 use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 已完成训练任务的统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskCompletion {
+    pub task_id: String,
+    /// 任务清单哈希（由协调者分配的任务才有）
+    pub manifest_hash: Option<String>,
+    /// 训练所用数据集的清单哈希（配置了数据集清单时才有）
+    pub dataset_manifest_hash: Option<String>,
+    /// 最近一次留出集评估折算的质量评分（0~1，未启用评估时为空）
+    pub quality_score: Option<f64>,
+    /// 开始时间（Unix 秒）
+    pub started_at: i64,
+    /// 结束时间（Unix 秒）
+    pub finished_at: i64,
+    pub samples_processed: u64,
+    pub batches_processed: u64,
+    /// 任务期间的能耗估计（瓦时），仅在配置允许写入贡献记录时提供
+    pub energy_wh: Option<f64>,
+}
+
+impl TaskCompletion {
+    /// 任务持续时间（秒）
+    pub fn duration_seconds(&self) -> u64 {
+        (self.finished_at - self.started_at).max(0) as u64
+    }
+}
+
+/// 训练任务生命周期的接收方（如链上贡献提交）
+///
+/// 回调在训练循环中同步调用，实现方不应阻塞。
+pub trait TaskCompletionSink: Send + Sync {
+    /// 任务开始
+    fn task_started(&self, _task_id: &str, _manifest_hash: Option<&str>) {}
+
+    /// 任务完成
+    fn task_completed(&self, completion: &TaskCompletion);
+
+    /// 任务被中止（例如资源越界），不会产生完成记录
+    fn task_aborted(&self, _task_id: &str, _reason: &str) {}
+}
+
+/// 进行中的训练任务
+#[derive(Debug, Clone)]
+struct ActiveTask {
+    task_id: String,
+    manifest_hash: Option<String>,
+    started_at: i64,
+    energy_wh: f64,
+}
 
 /// 简化的训练引擎
-#[derive(Debug)]
 pub struct TrainingEngine {
     config: AppConfig,
     model_dim: usize,
+    active_task: Option<ActiveTask>,
+    task_sinks: Vec<Arc<dyn TaskCompletionSink>>,
+    samples_fed: u64,
+    batches_fed: u64,
+    dataset_manifest_hash: Option<String>,
+    quality_score: Option<f64>,
+}
+
+impl std::fmt::Debug for TrainingEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrainingEngine")
+            .field("model_dim", &self.model_dim)
+            .field("active_task", &self.active_task)
+            .field("task_sinks", &self.task_sinks.len())
+            .field("samples_fed", &self.samples_fed)
+            .field("batches_fed", &self.batches_fed)
+            .field("dataset_manifest_hash", &self.dataset_manifest_hash)
+            .field("quality_score", &self.quality_score)
+            .finish()
+    }
 }
 
 impl TrainingEngine {
@@ -20,8 +92,103 @@ impl TrainingEngine {
         Ok(Self {
             model_dim: 512, // 默认模型维度
             config,
+            active_task: None,
+            task_sinks: Vec::new(),
+            samples_fed: 0,
+            batches_fed: 0,
+            dataset_manifest_hash: None,
+            quality_score: None,
         })
     }
+
+    /// 注册任务生命周期接收方
+    pub fn add_task_sink(&mut self, sink: Arc<dyn TaskCompletionSink>) {
+        self.task_sinks.push(sink);
+    }
+
+    /// 开始训练任务
+    pub fn start_task(&mut self, task_id: &str, manifest_hash: Option<&str>) -> Result<()> {
+        if let Some(active) = &self.active_task {
+            return Err(anyhow!("任务 {} 正在进行中", active.task_id));
+        }
+        self.active_task = Some(ActiveTask {
+            task_id: task_id.to_string(),
+            manifest_hash: manifest_hash.map(str::to_string),
+            started_at: chrono::Utc::now().timestamp(),
+            energy_wh: 0.0,
+        });
+        for sink in &self.task_sinks {
+            sink.task_started(task_id, manifest_hash);
+        }
+        Ok(())
+    }
+
+    /// 完成当前训练任务，并通知所有接收方
+    pub fn complete_task(&mut self, samples_processed: u64, batches_processed: u64) -> Result<TaskCompletion> {
+        let active = self.active_task.take().ok_or_else(|| anyhow!("没有正在进行的任务"))?;
+        let completion = TaskCompletion {
+            task_id: active.task_id,
+            manifest_hash: active.manifest_hash,
+            dataset_manifest_hash: self.dataset_manifest_hash.clone(),
+            quality_score: self.quality_score,
+            started_at: active.started_at,
+            finished_at: chrono::Utc::now().timestamp(),
+            samples_processed,
+            batches_processed,
+            energy_wh: self.config.energy.include_in_contributions.then_some(active.energy_wh),
+        };
+        for sink in &self.task_sinks {
+            sink.task_completed(&completion);
+        }
+        Ok(completion)
+    }
+
+    /// 中止当前任务，返回被中止的任务 ID
+    pub fn abort_task(&mut self, reason: &str) -> Option<String> {
+        let active = self.active_task.take()?;
+        for sink in &self.task_sinks {
+            sink.task_aborted(&active.task_id, reason);
+        }
+        Some(active.task_id)
+    }
+
+    /// 累加当前任务的能耗估计（瓦时）
+    pub fn record_energy(&mut self, watt_hours: f64) {
+        if let Some(active) = &mut self.active_task {
+            active.energy_wh += watt_hours;
+        }
+    }
+
+    /// 设置已校验的数据集清单哈希，之后完成的任务都会带上它
+    pub fn set_dataset_manifest(&mut self, manifest_hash: Option<String>) {
+        self.dataset_manifest_hash = manifest_hash;
+    }
+
+    /// 更新评估得到的质量评分
+    pub fn set_quality_score(&mut self, score: f64) {
+        self.quality_score = Some(score.clamp(0.0, 1.0));
+    }
+
+    /// 接收预处理流水线产出的一个批次
+    pub fn feed_batch(&mut self, batch: &[Sample]) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        // 模拟训练：只记录喂入的数据量
+        self.samples_fed += batch.len() as u64;
+        self.batches_fed += 1;
+        Ok(())
+    }
+
+    /// 累计喂入的 (样本数, 批次数)
+    pub fn fed_totals(&self) -> (u64, u64) {
+        (self.samples_fed, self.batches_fed)
+    }
+
+    /// 当前进行中的任务 ID
+    pub fn active_task_id(&self) -> Option<&str> {
+        self.active_task.as_ref().map(|t| t.task_id.as_str())
+    }
     
     /// 获取模型维度
     pub fn model_dim(&self) -> usize {
@@ -86,3 +253,15 @@ impl TrainingEngine {
         Ok(())
     }
 }
+
+impl EvalModel for TrainingEngine {
+    fn predict(&self, input: &[f32], output_dim: usize) -> Vec<f32> {
+        // 模拟前向：输出输入均值
+        let mean = input.iter().sum::<f32>() / input.len().max(1) as f32;
+        vec![mean; output_dim]
+    }
+
+    fn save_checkpoint(&self, path: &std::path::Path) -> Result<()> {
+        self.save_checkpoint_structured(path)
+    }
+}
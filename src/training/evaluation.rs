@@ -0,0 +1,396 @@
+//! 留出集评估
+//!
+//! 训练期间定期在留出的数据分片上评估模型，计算损失、准确率和困惑度：
+//! - 留出样本在启动时一次性载入，经过与训练流水线相同的变换（数据增强除外）
+//! - 损失连续 `patience` 次没有改善超过 `min_delta` 时提前停止训练
+//! - 每次改善都保存一份最佳 checkpoint，只保留最近的 `keep_best` 份
+//! - 评估结果记入统计，并折算为贡献记录中的质量评分
+
+use super::loss::{CrossEntropy, LossFunction, MSE};
+use super::pipeline::{Sample, SourceConfig, TransformRegistry, TransformSpec};
+use anyhow::Result;
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// 评估使用的损失
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvalLoss {
+    /// 均方误差，适合回归目标
+    #[default]
+    Mse,
+    /// 交叉熵（预测先经 softmax），同时给出困惑度
+    CrossEntropy,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalConfig {
+    pub enabled: bool,
+    /// 留出数据分片
+    pub holdout: SourceConfig,
+    /// 最多载入的留出样本数
+    pub max_samples: usize,
+    /// 每隔多少个 tick 评估一次
+    pub interval_ticks: u64,
+    pub loss: EvalLoss,
+    /// 连续多少次评估没有改善后提前停止，0 表示不提前停止
+    pub patience: u32,
+    /// 损失至少下降多少才算改善
+    pub min_delta: f64,
+    /// 最佳 checkpoint 目录
+    pub checkpoint_dir: PathBuf,
+    /// 保留的最佳 checkpoint 份数
+    pub keep_best: usize,
+}
+
+impl Default for EvalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            holdout: SourceConfig::Synthetic {
+                input_dim: 64,
+                output_dim: 1,
+                seed: 7,
+                samples: 256,
+            },
+            max_samples: 1024,
+            interval_ticks: 50,
+            loss: EvalLoss::Mse,
+            patience: 5,
+            min_delta: 1e-4,
+            checkpoint_dir: PathBuf::from("checkpoints/best"),
+            keep_best: 3,
+        }
+    }
+}
+
+/// 可评估的模型
+pub trait EvalModel {
+    /// 前向计算，输出 `output_dim` 维预测
+    fn predict(&self, input: &[f32], output_dim: usize) -> Vec<f32>;
+
+    /// 保存 checkpoint
+    fn save_checkpoint(&self, path: &Path) -> Result<()>;
+}
+
+/// 一次评估的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalMetrics {
+    /// 第几次评估（从 1 开始）
+    pub evaluation: u64,
+    pub samples: usize,
+    pub loss: f64,
+    /// 多维目标按 argmax 比较，一维目标按四舍五入比较
+    pub accuracy: f64,
+    /// exp(交叉熵)，仅在使用交叉熵损失时给出
+    pub perplexity: Option<f64>,
+    /// 评估时间（Unix 秒）
+    pub evaluated_at: i64,
+}
+
+impl EvalMetrics {
+    /// 折算为 0~1 的质量评分：取准确率与 1/(1+loss) 的平均
+    pub fn quality_score(&self) -> f64 {
+        let loss_score = if self.loss.is_finite() { 1.0 / (1.0 + self.loss.max(0.0)) } else { 0.0 };
+        ((self.accuracy + loss_score) / 2.0).clamp(0.0, 1.0)
+    }
+}
+
+/// 提前停止判断
+#[derive(Debug, Clone)]
+pub struct EarlyStopping {
+    patience: u32,
+    min_delta: f64,
+    best: Option<f64>,
+    stale: u32,
+}
+
+impl EarlyStopping {
+    pub fn new(patience: u32, min_delta: f64) -> Self {
+        Self {
+            patience,
+            min_delta,
+            best: None,
+            stale: 0,
+        }
+    }
+
+    /// 记录一次评估损失，返回是否为新的最佳值
+    pub fn observe(&mut self, loss: f64) -> bool {
+        let improved = self.best.is_none_or(|best| loss < best - self.min_delta);
+        if improved {
+            self.best = Some(loss);
+            self.stale = 0;
+        } else {
+            self.stale += 1;
+        }
+        improved
+    }
+
+    pub fn should_stop(&self) -> bool {
+        self.patience > 0 && self.stale >= self.patience
+    }
+
+    pub fn best(&self) -> Option<f64> {
+        self.best
+    }
+}
+
+/// 一次评估后的处理结果
+#[derive(Debug, Clone)]
+pub struct EvalOutcome {
+    pub metrics: EvalMetrics,
+    /// 本次保存的最佳 checkpoint
+    pub checkpoint: Option<PathBuf>,
+    /// 应提前停止训练
+    pub stop: bool,
+}
+
+/// 留出集评估器
+pub struct Evaluator {
+    config: EvalConfig,
+    samples: Vec<Sample>,
+    early_stopping: EarlyStopping,
+    best_checkpoints: VecDeque<PathBuf>,
+    evaluations: u64,
+}
+
+impl Evaluator {
+    /// 载入留出样本并施加训练流水线的变换（跳过 `augment`，评估数据不应带随机扰动）；
+    /// 与流水线一样，读取或变换失败的样本直接丢弃
+    pub fn new(config: EvalConfig, transforms: &[TransformSpec], registry: &TransformRegistry) -> Result<Self> {
+        let transforms = transforms
+            .iter()
+            .filter(|spec| spec.kind != "augment")
+            .map(|spec| registry.build(spec))
+            .collect::<Result<Vec<_>>>()?;
+        let mut samples = Vec::new();
+        for sample in config.holdout.open()? {
+            if samples.len() >= config.max_samples {
+                break;
+            }
+            let mut sample = sample.ok();
+            for transform in &transforms {
+                sample = match sample {
+                    Some(sample) => transform.apply(sample).unwrap_or_else(|e| {
+                        log::debug!("[评估] {} 处理留出样本失败: {}", transform.name(), e);
+                        None
+                    }),
+                    None => break,
+                };
+            }
+            samples.extend(sample);
+        }
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("留出集没有可用样本"));
+        }
+        Ok(Self {
+            early_stopping: EarlyStopping::new(config.patience, config.min_delta),
+            config,
+            samples,
+            best_checkpoints: VecDeque::new(),
+            evaluations: 0,
+        })
+    }
+
+    pub fn config(&self) -> &EvalConfig {
+        &self.config
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// 已触发提前停止
+    pub fn stopped(&self) -> bool {
+        self.early_stopping.should_stop()
+    }
+
+    /// 在留出集上计算指标
+    pub fn evaluate(&self, model: &dyn EvalModel) -> EvalMetrics {
+        let mut total_loss = 0.0;
+        let mut correct = 0usize;
+        for sample in &self.samples {
+            let mut predicted = Array1::from_vec(model.predict(&sample.input, sample.target.len()));
+            let target = Array1::from_vec(sample.target.clone());
+            let loss = match self.config.loss {
+                EvalLoss::Mse => MSE.compute(&predicted, &target),
+                EvalLoss::CrossEntropy => {
+                    softmax(&mut predicted);
+                    CrossEntropy.compute(&predicted, &target)
+                }
+            };
+            total_loss += loss as f64;
+            if is_correct(&predicted, &target) {
+                correct += 1;
+            }
+        }
+        let count = self.samples.len();
+        let loss = total_loss / count as f64;
+        EvalMetrics {
+            evaluation: self.evaluations + 1,
+            samples: count,
+            loss,
+            accuracy: correct as f64 / count as f64,
+            perplexity: (self.config.loss == EvalLoss::CrossEntropy).then(|| loss.exp()),
+            evaluated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// 评估并更新提前停止状态；损失改善时保存最佳 checkpoint
+    pub fn run(&mut self, model: &dyn EvalModel) -> Result<EvalOutcome> {
+        let metrics = self.evaluate(model);
+        self.evaluations = metrics.evaluation;
+        let checkpoint = if self.early_stopping.observe(metrics.loss) {
+            Some(self.save_best(model, &metrics)?)
+        } else {
+            None
+        };
+        Ok(EvalOutcome {
+            metrics,
+            checkpoint,
+            stop: self.early_stopping.should_stop(),
+        })
+    }
+
+    /// 保留的最佳 checkpoint，最新的在最后
+    pub fn best_checkpoints(&self) -> impl Iterator<Item = &PathBuf> {
+        self.best_checkpoints.iter()
+    }
+
+    fn save_best(&mut self, model: &dyn EvalModel, metrics: &EvalMetrics) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.checkpoint_dir)?;
+        let path = self
+            .config
+            .checkpoint_dir
+            .join(format!("best-{:06}.ckpt", metrics.evaluation));
+        model.save_checkpoint(&path)?;
+        std::fs::write(path.with_extension("json"), serde_json::to_vec_pretty(metrics)?)?;
+        self.best_checkpoints.push_back(path.clone());
+        while self.best_checkpoints.len() > self.config.keep_best.max(1) {
+            if let Some(old) = self.best_checkpoints.pop_front() {
+                let _ = std::fs::remove_file(&old);
+                let _ = std::fs::remove_file(old.with_extension("json"));
+            }
+        }
+        Ok(path)
+    }
+}
+
+fn softmax(values: &mut Array1<f32>) {
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    values.mapv_inplace(|x| (x - max).exp());
+    let sum = values.sum();
+    if sum > 0.0 {
+        values.mapv_inplace(|x| x / sum);
+    }
+}
+
+fn is_correct(predicted: &Array1<f32>, target: &Array1<f32>) -> bool {
+    match target.len() {
+        0 => false,
+        1 => predicted.first().is_some_and(|p| p.round() == target[0].round()),
+        _ => argmax(predicted) == argmax(target),
+    }
+}
+
+fn argmax(values: &Array1<f32>) -> Option<usize> {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// 每次预测都按固定偏移输出目标类别附近的值，偏移随“训练”逐步变化
+    struct FakeModel {
+        bias: Cell<f32>,
+    }
+
+    impl EvalModel for FakeModel {
+        fn predict(&self, input: &[f32], output_dim: usize) -> Vec<f32> {
+            let mean = input.iter().sum::<f32>() / input.len().max(1) as f32;
+            vec![mean + self.bias.get(); output_dim]
+        }
+
+        fn save_checkpoint(&self, path: &Path) -> Result<()> {
+            std::fs::write(path, self.bias.get().to_le_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn evaluator(dir: &Path, patience: u32) -> Evaluator {
+        let config = EvalConfig {
+            enabled: true,
+            holdout: SourceConfig::Synthetic {
+                input_dim: 8,
+                output_dim: 1,
+                seed: 3,
+                samples: 32,
+            },
+            patience,
+            checkpoint_dir: dir.to_path_buf(),
+            keep_best: 2,
+            ..Default::default()
+        };
+        Evaluator::new(config, &[], &TransformRegistry::default()).unwrap()
+    }
+
+    #[test]
+    fn test_early_stopping_and_best_checkpoint_retention() {
+        let dir = std::env::temp_dir().join(format!("williw-eval-{}", uuid::Uuid::new_v4()));
+        let mut evaluator = evaluator(&dir, 2);
+        assert_eq!(evaluator.sample_count(), 32);
+        let model = FakeModel { bias: Cell::new(50.0) };
+
+        // 损失逐步下降：每次都保存最佳 checkpoint，只保留最近两份
+        for bias in [50.0, 20.0, 5.0] {
+            model.bias.set(bias);
+            let outcome = evaluator.run(&model).unwrap();
+            assert!(outcome.checkpoint.is_some());
+            assert!(!outcome.stop);
+        }
+        let kept: Vec<_> = evaluator.best_checkpoints().cloned().collect();
+        assert_eq!(kept.len(), 2);
+        assert!(kept.iter().all(|path| path.exists() && path.with_extension("json").exists()));
+        assert!(!dir.join("best-000001.ckpt").exists());
+
+        // 连续两次没有改善后提前停止
+        model.bias.set(80.0);
+        assert!(!evaluator.run(&model).unwrap().stop);
+        let outcome = evaluator.run(&model).unwrap();
+        assert!(outcome.checkpoint.is_none());
+        assert!(outcome.stop && evaluator.stopped());
+        assert_eq!(outcome.metrics.evaluation, 5);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cross_entropy_metrics() {
+        let dir = std::env::temp_dir().join(format!("williw-eval-{}", uuid::Uuid::new_v4()));
+        let mut evaluator = evaluator(&dir, 0);
+        evaluator.config.loss = EvalLoss::CrossEntropy;
+        evaluator.samples = vec![
+            Sample {
+                text: None,
+                input: vec![1.0],
+                target: vec![0.0, 1.0],
+            };
+            4
+        ];
+
+        // 均匀预测：交叉熵为 ln 2，困惑度为 2
+        let metrics = evaluator.evaluate(&FakeModel { bias: Cell::new(0.0) });
+        assert!((metrics.loss - std::f64::consts::LN_2).abs() < 1e-5);
+        assert!((metrics.perplexity.unwrap() - 2.0).abs() < 1e-4);
+        assert!(metrics.quality_score() > 0.0 && metrics.quality_score() <= 1.0);
+    }
+}
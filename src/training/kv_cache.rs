@@ -0,0 +1,522 @@
+//! 分页 KV 缓存
+//!
+//! 长提示词的 KV 缓存如果按最大上下文预留整块内存，小设备很快就会耗尽。这里按固定大小的块
+//! （默认 16 个 token）从一个有上限的物理块池中按需分配：
+//! - 每个序列持有块表（逻辑块 -> 物理块），注意力计算通过块表读取 K/V（paged attention）
+//! - 写满的块按前缀哈希（父块哈希 + 本块 token）登记，共享相同前缀的请求直接复用，引用计数管理
+//! - 不再被引用的已登记块留在 LRU 中等待复用，只在空闲块用完时才驱逐
+//! - 物理块数由 [`DeviceCapabilities`] 的内存推导
+
+use crate::device::{DeviceCapabilities, DeviceType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 决定每个 token 占用多少 KV 内存的模型形状
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KvModelShape {
+    pub layers: usize,
+    pub kv_heads: usize,
+    pub head_dim: usize,
+}
+
+impl KvModelShape {
+    /// 每层每个 token 的 K（或 V）宽度
+    pub fn kv_width(&self) -> usize {
+        self.kv_heads * self.head_dim
+    }
+
+    /// 每个 token 在所有层上的 K 和 V 占用（f32）
+    pub fn bytes_per_token(&self) -> usize {
+        2 * self.layers * self.kv_width() * std::mem::size_of::<f32>()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KvCacheConfig {
+    pub shape: KvModelShape,
+    /// 每块 token 数
+    pub block_tokens: usize,
+    /// 物理块总数
+    pub max_blocks: usize,
+}
+
+impl KvCacheConfig {
+    /// 按设备内存推导块数：手机/平板只拿出较小比例的内存给 KV 缓存
+    pub fn for_device(caps: &DeviceCapabilities, shape: KvModelShape, block_tokens: usize) -> Self {
+        let fraction = match caps.device_type {
+            DeviceType::Phone => 0.10,
+            DeviceType::Tablet => 0.15,
+            DeviceType::Desktop => 0.25,
+            DeviceType::Unknown => 0.10,
+        };
+        let budget_bytes = (caps.max_memory_mb as f64 * 1024.0 * 1024.0 * fraction) as usize;
+        let block_tokens = block_tokens.max(1);
+        let block_bytes = (block_tokens * shape.bytes_per_token()).max(1);
+        Self {
+            shape,
+            block_tokens,
+            max_blocks: (budget_bytes / block_bytes).max(1),
+        }
+    }
+
+    pub fn block_bytes(&self) -> usize {
+        self.block_tokens * self.shape.bytes_per_token()
+    }
+
+    /// 缓存最多容纳的 token 数
+    pub fn capacity_tokens(&self) -> usize {
+        self.max_blocks * self.block_tokens
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum KvCacheError {
+    #[error("KV 缓存块已用完（共 {total} 块）")]
+    OutOfBlocks { total: usize },
+    #[error("序列 {0} 不存在")]
+    UnknownSequence(u64),
+    #[error("序列 {0} 已存在")]
+    DuplicateSequence(u64),
+    #[error("位置 {position} 超出序列长度 {len}")]
+    PositionOutOfRange { position: usize, len: usize },
+    #[error("向量宽度 {actual} 与 KV 宽度 {expected} 不符")]
+    WidthMismatch { expected: usize, actual: usize },
+}
+
+/// 缓存指标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KvCacheMetrics {
+    pub total_blocks: usize,
+    /// 被序列引用的块
+    pub used_blocks: usize,
+    /// 未被引用但保留用于前缀复用的块
+    pub cached_blocks: usize,
+    pub free_blocks: usize,
+    /// 前缀命中（复用）的块数
+    pub prefix_hit_blocks: u64,
+    /// 提示词中需要重新计算的完整块数
+    pub prefix_miss_blocks: u64,
+    /// 为腾出空间而驱逐的缓存块数
+    pub evictions: u64,
+    pub allocation_failures: u64,
+}
+
+impl KvCacheMetrics {
+    pub fn prefix_hit_rate(&self) -> f64 {
+        let total = self.prefix_hit_blocks + self.prefix_miss_blocks;
+        if total == 0 {
+            0.0
+        } else {
+            self.prefix_hit_blocks as f64 / total as f64
+        }
+    }
+}
+
+/// 新序列的分配结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceAllocation {
+    /// 前缀复用的 token 数，这些位置的 K/V 已在缓存中，无需重新计算
+    pub reused_tokens: usize,
+    pub blocks: Vec<usize>,
+}
+
+struct Block {
+    ref_count: usize,
+    /// 写满并登记后的前缀哈希
+    prefix_hash: Option<blake3::Hash>,
+    /// LRU 序号（仅在未被引用时有意义）
+    lru_tick: u64,
+    /// 按 [层][token][K/V][宽度] 排列，首次使用时分配，之后随块复用
+    data: Vec<f32>,
+}
+
+struct Sequence {
+    blocks: Vec<usize>,
+    /// 当前未写满块中的 token
+    pending_tokens: Vec<u32>,
+    len: usize,
+    /// 最后一个已写满块的前缀哈希
+    last_hash: Option<blake3::Hash>,
+}
+
+/// 分页 KV 缓存
+pub struct PagedKvCache {
+    config: KvCacheConfig,
+    blocks: Vec<Block>,
+    free: Vec<usize>,
+    /// 前缀哈希 -> 物理块
+    prefix_index: HashMap<blake3::Hash, usize>,
+    /// 未被引用的已登记块：LRU 序号 -> 物理块
+    evictable: BTreeMap<u64, usize>,
+    sequences: HashMap<u64, Sequence>,
+    tick: u64,
+    metrics: KvCacheMetrics,
+}
+
+fn block_hash(parent: Option<&blake3::Hash>, tokens: &[u32]) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new();
+    if let Some(parent) = parent {
+        hasher.update(parent.as_bytes());
+    }
+    for token in tokens {
+        hasher.update(&token.to_le_bytes());
+    }
+    hasher.finalize()
+}
+
+impl PagedKvCache {
+    pub fn new(config: KvCacheConfig) -> Self {
+        let blocks = (0..config.max_blocks)
+            .map(|_| Block {
+                ref_count: 0,
+                prefix_hash: None,
+                lru_tick: 0,
+                data: Vec::new(),
+            })
+            .collect();
+        Self {
+            free: (0..config.max_blocks).rev().collect(),
+            blocks,
+            prefix_index: HashMap::new(),
+            evictable: BTreeMap::new(),
+            sequences: HashMap::new(),
+            tick: 0,
+            metrics: KvCacheMetrics::default(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &KvCacheConfig {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> KvCacheMetrics {
+        KvCacheMetrics {
+            total_blocks: self.config.max_blocks,
+            used_blocks: self.blocks.iter().filter(|b| b.ref_count > 0).count(),
+            cached_blocks: self.evictable.len(),
+            free_blocks: self.free.len(),
+            ..self.metrics.clone()
+        }
+    }
+
+    /// 在不驱逐被引用块的前提下还能分配的块数
+    pub fn available_blocks(&self) -> usize {
+        self.free.len() + self.evictable.len()
+    }
+
+    pub fn sequence_len(&self, seq_id: u64) -> Option<usize> {
+        self.sequences.get(&seq_id).map(|s| s.len)
+    }
+
+    pub fn block_table(&self, seq_id: u64) -> Option<&[usize]> {
+        self.sequences.get(&seq_id).map(|s| s.blocks.as_slice())
+    }
+
+    /// 为新序列分配提示词所需的块，尽量复用已缓存的相同前缀；
+    /// 调用方需为 `reused_tokens` 之后的位置写入 K/V
+    pub fn allocate_sequence(&mut self, seq_id: u64, prompt: &[u32]) -> Result<SequenceAllocation, KvCacheError> {
+        if self.sequences.contains_key(&seq_id) {
+            return Err(KvCacheError::DuplicateSequence(seq_id));
+        }
+        let block_tokens = self.config.block_tokens;
+        let mut sequence = Sequence {
+            blocks: Vec::new(),
+            pending_tokens: Vec::new(),
+            len: 0,
+            last_hash: None,
+        };
+
+        // 逐个完整块查找前缀缓存，遇到第一个未命中即停止
+        let full_blocks = prompt.len() / block_tokens;
+        let mut reused_blocks = 0;
+        for chunk in prompt.chunks(block_tokens).take(full_blocks) {
+            let hash = block_hash(sequence.last_hash.as_ref(), chunk);
+            let Some(&block) = self.prefix_index.get(&hash) else {
+                break;
+            };
+            self.retain(block);
+            sequence.blocks.push(block);
+            sequence.last_hash = Some(hash);
+            sequence.len += block_tokens;
+            reused_blocks += 1;
+        }
+        self.metrics.prefix_hit_blocks += reused_blocks as u64;
+        self.metrics.prefix_miss_blocks += (full_blocks - reused_blocks) as u64;
+        let reused_tokens = sequence.len;
+
+        // 剩余 token 逐个追加；失败时这些块的 K/V 永远不会被计算，撤销登记后归还
+        self.sequences.insert(seq_id, sequence);
+        for &token in &prompt[reused_tokens..] {
+            if let Err(e) = self.append_token(seq_id, token) {
+                let fresh = self.sequences[&seq_id].blocks[reused_blocks..].to_vec();
+                for block in fresh {
+                    if let Some(hash) = self.blocks[block].prefix_hash.take() {
+                        self.prefix_index.remove(&hash);
+                    }
+                }
+                self.free_sequence(seq_id);
+                return Err(e);
+            }
+        }
+        Ok(SequenceAllocation {
+            reused_tokens,
+            blocks: self.sequences[&seq_id].blocks.clone(),
+        })
+    }
+
+    /// 追加一个生成的 token，需要时分配新块；块写满后登记到前缀索引
+    pub fn append_token(&mut self, seq_id: u64, token: u32) -> Result<usize, KvCacheError> {
+        let block_tokens = self.config.block_tokens;
+        let needs_block = {
+            let sequence = self.sequences.get(&seq_id).ok_or(KvCacheError::UnknownSequence(seq_id))?;
+            sequence.len % block_tokens == 0
+        };
+        if needs_block {
+            let block = self.allocate_block()?;
+            self.sequences.get_mut(&seq_id).expect("已检查").blocks.push(block);
+        }
+
+        let sequence = self.sequences.get_mut(&seq_id).expect("已检查");
+        let position = sequence.len;
+        sequence.pending_tokens.push(token);
+        sequence.len += 1;
+        if sequence.pending_tokens.len() == block_tokens {
+            let hash = block_hash(sequence.last_hash.as_ref(), &sequence.pending_tokens);
+            sequence.pending_tokens.clear();
+            sequence.last_hash = Some(hash);
+            let block = *sequence.blocks.last().expect("刚写满的块");
+            // 相同前缀已有登记块时保留旧的，当前块照常使用
+            if !self.prefix_index.contains_key(&hash) {
+                self.prefix_index.insert(hash, block);
+                self.blocks[block].prefix_hash = Some(hash);
+            }
+        }
+        Ok(position)
+    }
+
+    /// 释放序列，已登记的块留作前缀缓存
+    pub fn free_sequence(&mut self, seq_id: u64) -> bool {
+        let Some(sequence) = self.sequences.remove(&seq_id) else {
+            return false;
+        };
+        for block in sequence.blocks {
+            self.release(block);
+        }
+        true
+    }
+
+    /// 写入某层某位置的 K/V
+    pub fn write_kv(&mut self, seq_id: u64, layer: usize, position: usize, key: &[f32], value: &[f32]) -> Result<(), KvCacheError> {
+        let (block, offset) = self.locate(seq_id, position)?;
+        let width = self.config.shape.kv_width();
+        for v in [key, value] {
+            if v.len() != width {
+                return Err(KvCacheError::WidthMismatch {
+                    expected: width,
+                    actual: v.len(),
+                });
+            }
+        }
+        let base = self.slot_offset(layer, offset);
+        let block_len = self.config.shape.layers * self.config.block_tokens * 2 * width;
+        let data = &mut self.blocks[block].data;
+        if data.len() != block_len {
+            data.resize(block_len, 0.0);
+        }
+        data[base..base + width].copy_from_slice(key);
+        data[base + width..base + 2 * width].copy_from_slice(value);
+        Ok(())
+    }
+
+    /// 分页注意力：`query` 为该层所有头拼接的查询向量，按块表读取序列前 `len` 个位置的 K/V，
+    /// 每个头独立做缩放点积注意力，返回拼接后的输出
+    pub fn paged_attention(&self, seq_id: u64, layer: usize, query: &[f32]) -> Result<Vec<f32>, KvCacheError> {
+        let sequence = self.sequences.get(&seq_id).ok_or(KvCacheError::UnknownSequence(seq_id))?;
+        let shape = self.config.shape;
+        let width = shape.kv_width();
+        if query.len() != width {
+            return Err(KvCacheError::WidthMismatch {
+                expected: width,
+                actual: query.len(),
+            });
+        }
+
+        let scale = 1.0 / (shape.head_dim as f32).sqrt();
+        let mut output = vec![0.0f32; width];
+        for head in 0..shape.kv_heads {
+            let range = head * shape.head_dim..(head + 1) * shape.head_dim;
+            let q = &query[range.clone()];
+            // 在线 softmax：一次遍历所有块，不需要把 K/V 拼成连续内存
+            let mut max_score = f32::NEG_INFINITY;
+            let mut denom = 0.0f32;
+            let mut acc = vec![0.0f32; shape.head_dim];
+            for position in 0..sequence.len {
+                let block = sequence.blocks[position / self.config.block_tokens];
+                let data = &self.blocks[block].data;
+                let base = self.slot_offset(layer, position % self.config.block_tokens);
+                if data.len() < base + 2 * width {
+                    continue;
+                }
+                let k = &data[base + range.start..base + range.end];
+                let v = &data[base + width + range.start..base + width + range.end];
+                let score = q.iter().zip(k).map(|(a, b)| a * b).sum::<f32>() * scale;
+                let new_max = max_score.max(score);
+                let correction = (max_score - new_max).exp();
+                let weight = (score - new_max).exp();
+                denom = denom * correction + weight;
+                for (a, x) in acc.iter_mut().zip(v) {
+                    *a = *a * correction + weight * x;
+                }
+                max_score = new_max;
+            }
+            if denom > 0.0 {
+                for (out, a) in output[range].iter_mut().zip(&acc) {
+                    *out = a / denom;
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    fn slot_offset(&self, layer: usize, offset_in_block: usize) -> usize {
+        let width = self.config.shape.kv_width();
+        (layer * self.config.block_tokens + offset_in_block) * 2 * width
+    }
+
+    fn locate(&self, seq_id: u64, position: usize) -> Result<(usize, usize), KvCacheError> {
+        let sequence = self.sequences.get(&seq_id).ok_or(KvCacheError::UnknownSequence(seq_id))?;
+        if position >= sequence.len {
+            return Err(KvCacheError::PositionOutOfRange {
+                position,
+                len: sequence.len,
+            });
+        }
+        let block_tokens = self.config.block_tokens;
+        Ok((sequence.blocks[position / block_tokens], position % block_tokens))
+    }
+
+    fn allocate_block(&mut self) -> Result<usize, KvCacheError> {
+        let block = match self.free.pop() {
+            Some(block) => block,
+            None => {
+                // 驱逐最久未使用的缓存块
+                let Some((_, block)) = self.evictable.pop_first() else {
+                    self.metrics.allocation_failures += 1;
+                    return Err(KvCacheError::OutOfBlocks {
+                        total: self.config.max_blocks,
+                    });
+                };
+                if let Some(hash) = self.blocks[block].prefix_hash.take() {
+                    self.prefix_index.remove(&hash);
+                }
+                self.metrics.evictions += 1;
+                block
+            }
+        };
+        self.blocks[block].ref_count = 1;
+        Ok(block)
+    }
+
+    fn retain(&mut self, block: usize) {
+        if self.blocks[block].ref_count == 0 {
+            self.evictable.remove(&self.blocks[block].lru_tick);
+        }
+        self.blocks[block].ref_count += 1;
+    }
+
+    fn release(&mut self, block: usize) {
+        let entry = &mut self.blocks[block];
+        entry.ref_count = entry.ref_count.saturating_sub(1);
+        if entry.ref_count > 0 {
+            return;
+        }
+        if entry.prefix_hash.is_some() {
+            self.tick += 1;
+            entry.lru_tick = self.tick;
+            self.evictable.insert(self.tick, block);
+        } else {
+            self.free.push(block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_blocks: usize) -> KvCacheConfig {
+        KvCacheConfig {
+            shape: KvModelShape {
+                layers: 1,
+                kv_heads: 2,
+                head_dim: 2,
+            },
+            block_tokens: 4,
+            max_blocks,
+        }
+    }
+
+    #[test]
+    fn test_prefix_reuse_and_eviction() {
+        let mut cache = PagedKvCache::new(config(4));
+        let system_prompt: Vec<u32> = (0..8).collect();
+
+        let first = cache.allocate_sequence(1, &[system_prompt.clone(), vec![100, 101]].concat()).unwrap();
+        assert_eq!(first.reused_tokens, 0);
+        assert_eq!(first.blocks.len(), 3);
+
+        // 共享前两个完整块，只为不同的尾部分配新块
+        let second = cache.allocate_sequence(2, &[system_prompt.clone(), vec![200]].concat()).unwrap();
+        assert_eq!(second.reused_tokens, 8);
+        assert_eq!(second.blocks[..2], first.blocks[..2]);
+        assert_eq!(cache.metrics().used_blocks, 4);
+        assert!(matches!(cache.allocate_sequence(3, &[7; 4]), Err(KvCacheError::OutOfBlocks { .. })));
+
+        // 释放后前缀块仍可复用，空间不足时才驱逐
+        cache.free_sequence(1);
+        cache.free_sequence(2);
+        let metrics = cache.metrics();
+        assert_eq!((metrics.cached_blocks, metrics.free_blocks), (2, 2));
+        assert_eq!(cache.allocate_sequence(4, &system_prompt).unwrap().reused_tokens, 8);
+        cache.allocate_sequence(5, &(50..62).collect::<Vec<_>>()).unwrap_err();
+        cache.free_sequence(4);
+        assert_eq!(cache.allocate_sequence(6, &(50..62).collect::<Vec<_>>()).unwrap().reused_tokens, 0);
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.evictions, 1);
+        assert_eq!(metrics.allocation_failures, 2);
+        assert_eq!(metrics.prefix_hit_blocks, 4);
+    }
+
+    #[test]
+    fn test_paged_attention_matches_dense() {
+        let mut cache = PagedKvCache::new(config(8));
+        cache.allocate_sequence(1, &(0..10).collect::<Vec<_>>()).unwrap();
+        let kv = |p: usize| -> (Vec<f32>, Vec<f32>) {
+            let k = (0..4).map(|i| ((p * 4 + i) as f32 * 0.37).sin()).collect();
+            let v = (0..4).map(|i| ((p * 4 + i) as f32 * 0.11).cos()).collect();
+            (k, v)
+        };
+        for p in 0..10 {
+            let (k, v) = kv(p);
+            cache.write_kv(1, 0, p, &k, &v).unwrap();
+        }
+        let query = [0.3, -0.2, 0.5, 0.1];
+        let paged = cache.paged_attention(1, 0, &query).unwrap();
+
+        for head in 0..2 {
+            let r = head * 2..head * 2 + 2;
+            let scores: Vec<f32> = (0..10)
+                .map(|p| query[r.clone()].iter().zip(&kv(p).0[r.clone()]).map(|(a, b)| a * b).sum::<f32>() / 2f32.sqrt())
+                .collect();
+            let max = scores.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let weights: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+            let total: f32 = weights.iter().sum();
+            for (d, i) in r.clone().enumerate() {
+                let dense: f32 = (0..10).map(|p| weights[p] * kv(p).1[r.start + d]).sum::<f32>() / total;
+                assert!((paged[i] - dense).abs() < 1e-5);
+            }
+        }
+    }
+}
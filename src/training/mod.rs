@@ -2,11 +2,33 @@ pub mod data;
 pub mod loss;
 pub mod optimizer;
 pub mod engine;
+pub mod serving;
+pub mod api_keys;
+pub mod batching;
+pub mod kv_cache;
+pub mod scheduler;
+pub mod speculative;
+pub mod vector_index;
+pub mod moe_router;
+pub mod shadow;
+pub mod pipeline;
+pub mod evaluation;
 // pub mod huggingface_loader;  // 暂时注释，文件位置问题
 
 pub use data::{TrainingData, SyntheticData, ArrayData};
 pub use loss::{LossFunction, MSE, CrossEntropy, MAE};
 pub use optimizer::{Optimizer, SGD};
-pub use engine::TrainingEngine;
+pub use engine::{TaskCompletion, TaskCompletionSink, TrainingEngine};
+pub use moe_router::{MoeRouter, NodeBatch};
+pub use serving::{ModelRouter, ServableModel, SwapConfig, SwapOutcome};
+pub use api_keys::{estimate_tokens, Admission, ApiKeyError, ApiKeyLimits, ApiKeyManager, IssuedApiKey, SubmissionQueueHook, UsageHook};
+pub use batching::{BatchError, BatchModel, BatcherConfig, BatcherStats, DynamicBatcher};
+pub use kv_cache::{KvCacheConfig, KvCacheError, KvCacheMetrics, KvModelShape, PagedKvCache, SequenceAllocation};
+pub use scheduler::{InferenceScheduler, PriorityClass, RunPermit, SchedulerConfig, SchedulerStats};
+pub use speculative::{SpeculativeConfig, SpeculativeDecoder, SpeculativePair, SpeculativeStats, TokenModel};
+pub use vector_index::{cosine_similarity, SearchHit, VectorIndex};
+pub use shadow::{ShadowAlgorithm, ShadowConfig, ShadowRunner, ShadowSummary};
+pub use evaluation::{EarlyStopping, EvalConfig, EvalLoss, EvalMetrics, EvalModel, EvalOutcome, Evaluator};
+pub use pipeline::{Pipeline, PipelineConfig, Sample, SourceConfig, StageMetrics, Transform, TransformRegistry, TransformSpec};
 // pub use huggingface_loader::{LlamaModelLoader, ModelLayer, ModelPartition, create_llama_32_1b_loader};
 
@@ -0,0 +1,227 @@
+//! MoE 门控路由
+//!
+//! 推理流水线在 MoE 块处根据门控输出为每个 token 选出 top-k 专家，
+//! 只把 token 批次转发给托管这些专家的节点（由切分方案的 `expert_assignments` 决定），
+//! 并统计每个专家的负载。
+
+use anyhow::{anyhow, Result};
+use model_splitter::{expert_hosts, ExpertAssignment, SplitPlan};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 发往某个专家的 token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpertDispatch {
+    pub expert_index: usize,
+    /// token 在批次中的位置
+    pub token_indices: Vec<usize>,
+    /// 对应的门控权重（top-k 内归一化）
+    pub gate_weights: Vec<f32>,
+}
+
+/// 发往单个节点的批次
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeBatch {
+    pub node_id: String,
+    pub moe_block: String,
+    pub experts: Vec<ExpertDispatch>,
+}
+
+/// 专家负载快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpertLoad {
+    pub moe_block: String,
+    pub expert_index: usize,
+    pub hosts: Vec<String>,
+    /// 累计路由的 token 数
+    pub tokens: u64,
+    /// 累计收到 token 的批次数
+    pub batches: u64,
+    /// 占该块所有 token 路由的比例
+    pub share: f64,
+}
+
+#[derive(Default, Clone, Copy)]
+struct LoadCounter {
+    tokens: u64,
+    batches: u64,
+}
+
+/// 门控感知路由器
+pub struct MoeRouter {
+    top_k: usize,
+    hosts: HashMap<ExpertAssignment, Vec<String>>,
+    expert_load: RwLock<HashMap<ExpertAssignment, LoadCounter>>,
+    node_tokens: RwLock<HashMap<String, u64>>,
+}
+
+impl MoeRouter {
+    pub fn new(plans: &HashMap<String, SplitPlan>, top_k: usize) -> Self {
+        Self {
+            top_k: top_k.max(1),
+            hosts: expert_hosts(plans),
+            expert_load: RwLock::new(HashMap::new()),
+            node_tokens: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 根据门控 logits（每个 token 一行）把批次拆分到节点
+    pub fn route(&self, moe_block: &str, gate_logits: &[Vec<f32>]) -> Result<Vec<NodeBatch>> {
+        let mut per_expert: BTreeMap<usize, ExpertDispatch> = BTreeMap::new();
+        for (token, logits) in gate_logits.iter().enumerate() {
+            for (expert_index, weight) in top_k_softmax(logits, self.top_k) {
+                let dispatch = per_expert.entry(expert_index).or_insert_with(|| ExpertDispatch {
+                    expert_index,
+                    token_indices: Vec::new(),
+                    gate_weights: Vec::new(),
+                });
+                dispatch.token_indices.push(token);
+                dispatch.gate_weights.push(weight);
+            }
+        }
+
+        let mut batches: BTreeMap<String, NodeBatch> = BTreeMap::new();
+        let mut expert_load = self.expert_load.write();
+        let mut node_tokens = self.node_tokens.write();
+        for (expert_index, dispatch) in per_expert {
+            let key = ExpertAssignment {
+                moe_block: moe_block.to_string(),
+                expert_index,
+            };
+            let hosts = self
+                .hosts
+                .get(&key)
+                .filter(|h| !h.is_empty())
+                .ok_or_else(|| anyhow!("专家 {}#{} 没有托管节点", moe_block, expert_index))?;
+            // 有多个副本时选累计负载最低的节点
+            let node = hosts
+                .iter()
+                .min_by_key(|n| node_tokens.get(*n).copied().unwrap_or(0))
+                .expect("hosts 非空")
+                .clone();
+
+            let tokens = dispatch.token_indices.len() as u64;
+            let counter = expert_load.entry(key).or_default();
+            counter.tokens += tokens;
+            counter.batches += 1;
+            *node_tokens.entry(node.clone()).or_default() += tokens;
+
+            batches
+                .entry(node.clone())
+                .or_insert_with(|| NodeBatch {
+                    node_id: node,
+                    moe_block: moe_block.to_string(),
+                    experts: Vec::new(),
+                })
+                .experts
+                .push(dispatch);
+        }
+        Ok(batches.into_values().collect())
+    }
+
+    /// 每个专家的负载
+    pub fn expert_loads(&self) -> Vec<ExpertLoad> {
+        let loads = self.expert_load.read();
+        let mut block_totals: HashMap<&str, u64> = HashMap::new();
+        for (key, counter) in loads.iter() {
+            *block_totals.entry(key.moe_block.as_str()).or_default() += counter.tokens;
+        }
+
+        let mut result: Vec<ExpertLoad> = self
+            .hosts
+            .iter()
+            .map(|(key, hosts)| {
+                let counter = loads.get(key).copied().unwrap_or_default();
+                let total = block_totals.get(key.moe_block.as_str()).copied().unwrap_or(0);
+                ExpertLoad {
+                    moe_block: key.moe_block.clone(),
+                    expert_index: key.expert_index,
+                    hosts: hosts.clone(),
+                    tokens: counter.tokens,
+                    batches: counter.batches,
+                    share: if total > 0 { counter.tokens as f64 / total as f64 } else { 0.0 },
+                }
+            })
+            .collect();
+        result.sort_by(|a, b| a.moe_block.cmp(&b.moe_block).then(a.expert_index.cmp(&b.expert_index)));
+        result
+    }
+
+    /// 某个块的负载不均衡度（最大专家负载 / 平均负载），1.0 表示完全均衡
+    pub fn load_imbalance(&self, moe_block: &str) -> f64 {
+        let loads: Vec<u64> = self
+            .expert_loads()
+            .into_iter()
+            .filter(|l| l.moe_block == moe_block)
+            .map(|l| l.tokens)
+            .collect();
+        let total: u64 = loads.iter().sum();
+        if loads.is_empty() || total == 0 {
+            return 1.0;
+        }
+        let mean = total as f64 / loads.len() as f64;
+        *loads.iter().max().unwrap() as f64 / mean
+    }
+}
+
+/// 选出 top-k 专家并在其中做 softmax 归一化
+fn top_k_softmax(logits: &[f32], k: usize) -> Vec<(usize, f32)> {
+    let mut indexed: Vec<(usize, f32)> = logits.iter().copied().enumerate().collect();
+    indexed.sort_by(|a, b| b.1.total_cmp(&a.1));
+    indexed.truncate(k);
+    let max = indexed.first().map(|(_, v)| *v).unwrap_or(0.0);
+    let exp: Vec<f32> = indexed.iter().map(|(_, v)| (v - max).exp()).collect();
+    let sum: f32 = exp.iter().sum();
+    indexed
+        .into_iter()
+        .zip(exp)
+        .map(|((i, _), e)| (i, e / sum))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(node: &str, experts: &[usize]) -> SplitPlan {
+        SplitPlan {
+            node_id: node.to_string(),
+            layer_names: Vec::new(),
+            total_compute: 0.0,
+            compute_utilization: 0.0,
+            expert_assignments: experts
+                .iter()
+                .map(|&i| ExpertAssignment {
+                    moe_block: "moe0".to_string(),
+                    expert_index: i,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_routes_tokens_only_to_selected_expert_hosts() {
+        let plans = HashMap::from([
+            ("node-a".to_string(), plan("node-a", &[0, 1])),
+            ("node-b".to_string(), plan("node-b", &[2, 3])),
+        ]);
+        let router = MoeRouter::new(&plans, 2);
+
+        // 两个 token 都选中专家 0 和 1，只有 node-a 收到批次
+        let batches = router
+            .route("moe0", &[vec![3.0, 2.0, 0.0, -1.0], vec![1.0, 4.0, 0.5, 0.0]])
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].node_id, "node-a");
+        assert_eq!(batches[0].experts[0].token_indices, vec![0, 1]);
+        let weight_sum: f32 = batches[0].experts.iter().map(|e| e.gate_weights[0]).sum();
+        assert!((weight_sum - 1.0).abs() < 1e-5);
+
+        let loads = router.expert_loads();
+        assert_eq!(loads[0].tokens, 2);
+        assert_eq!(loads[2].tokens, 0);
+        assert!((router.load_imbalance("moe0") - 2.0).abs() < 1e-9);
+        assert!(router.route("moe1", &[vec![1.0]]).is_err());
+    }
+}
@@ -0,0 +1,406 @@
+//! 数据预处理流水线
+//!
+//! 在 TOML 中按顺序声明变换（分词、归一化、增强、过滤），每个阶段在独立的后台线程上运行，
+//! 阶段之间用有界通道连接：下游处理不过来时上游自然阻塞，内存占用不随数据量增长。
+//! 最后一个阶段把样本组成批次，训练引擎按需取用；每个阶段统计吞吐和忙碌占比。
+//!
+//! ```toml
+//! [pipeline]
+//! enabled = true
+//! batch_size = 32
+//!
+//! [pipeline.source]
+//! kind = "jsonl"
+//! path = "data/train.jsonl"
+//!
+//! [[pipeline.transforms]]
+//! kind = "tokenize"
+//! max_len = 128
+//!
+//! [[pipeline.transforms]]
+//! kind = "filter"
+//! min_tokens = 4
+//! ```
+
+mod source;
+mod transforms;
+
+pub use source::SourceConfig;
+pub use transforms::{Transform, TransformFactory, TransformRegistry, TransformSpec};
+
+use anyhow::Result;
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// 流水线中流动的样本
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sample {
+    /// 原始文本（分词前）
+    pub text: Option<String>,
+    pub input: Vec<f32>,
+    pub target: Vec<f32>,
+}
+
+impl Sample {
+    /// 转为 [`TrainingData`](super::TrainingData) 使用的 (输入, 目标) 形式
+    pub fn into_arrays(self) -> (Array1<f32>, Array1<f32>) {
+        (Array1::from_vec(self.input), Array1::from_vec(self.target))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub enabled: bool,
+    pub source: SourceConfig,
+    /// 按顺序执行的变换
+    pub transforms: Vec<TransformSpec>,
+    pub batch_size: usize,
+    /// 阶段之间通道的容量（样本数）
+    pub channel_capacity: usize,
+    /// 遍历数据源的轮数，0 表示无限循环
+    pub epochs: u32,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source: SourceConfig::default(),
+            transforms: Vec::new(),
+            batch_size: 32,
+            channel_capacity: 256,
+            epochs: 1,
+        }
+    }
+}
+
+/// 阶段计数（后台线程更新）
+struct StageCounters {
+    name: String,
+    received: AtomicU64,
+    emitted: AtomicU64,
+    dropped: AtomicU64,
+    errors: AtomicU64,
+    busy_nanos: AtomicU64,
+}
+
+impl StageCounters {
+    fn new(name: &str) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.to_string(),
+            received: AtomicU64::new(0),
+            emitted: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            busy_nanos: AtomicU64::new(0),
+        })
+    }
+
+    fn add_busy(&self, started: Instant) {
+        self.busy_nanos.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// 单个阶段的指标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageMetrics {
+    pub name: String,
+    pub received: u64,
+    /// 输出的样本数（批次阶段为批次数）
+    pub emitted: u64,
+    /// 被过滤丢弃的样本数
+    pub dropped: u64,
+    /// 处理失败而丢弃的样本数
+    pub errors: u64,
+    /// 自启动以来的平均输出速率（每秒）
+    pub throughput_per_sec: f64,
+    /// 处理耗时占运行时间的比例，接近 1 说明该阶段是瓶颈
+    pub busy_ratio: f64,
+}
+
+/// 运行中的流水线，丢弃时通知后台线程退出
+pub struct Pipeline {
+    // 包一层 Mutex 让 Pipeline 可以在节点的异步任务间共享
+    batches: Mutex<Receiver<Vec<Sample>>>,
+    stages: Vec<Arc<StageCounters>>,
+    started: Instant,
+    stop: Arc<AtomicBool>,
+}
+
+impl Pipeline {
+    /// 按配置构建变换并启动后台线程；变换类型或参数无效时直接返回错误
+    pub fn start(config: &PipelineConfig, registry: &TransformRegistry) -> Result<Self> {
+        let transforms = config
+            .transforms
+            .iter()
+            .map(|spec| registry.build(spec))
+            .collect::<Result<Vec<_>>>()?;
+        let capacity = config.channel_capacity.max(1);
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut stages = Vec::new();
+
+        let (source_tx, mut upstream) = sync_channel::<Sample>(capacity);
+        let counters = StageCounters::new("source");
+        stages.push(Arc::clone(&counters));
+        spawn_stage("source", {
+            let source = config.source.clone();
+            let epochs = config.epochs;
+            let stop = Arc::clone(&stop);
+            move || run_source(source, epochs, source_tx, counters, stop)
+        })?;
+
+        for transform in transforms {
+            let (tx, rx) = sync_channel::<Sample>(capacity);
+            let counters = StageCounters::new(transform.name());
+            stages.push(Arc::clone(&counters));
+            let input = std::mem::replace(&mut upstream, rx);
+            spawn_stage(transform.name(), move || {
+                for sample in input.iter() {
+                    let started = Instant::now();
+                    counters.received.fetch_add(1, Ordering::Relaxed);
+                    let output = transform.apply(sample);
+                    counters.add_busy(started);
+                    match output {
+                        Ok(Some(sample)) => {
+                            counters.emitted.fetch_add(1, Ordering::Relaxed);
+                            if tx.send(sample).is_err() {
+                                return;
+                            }
+                        }
+                        Ok(None) => {
+                            counters.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Err(e) => {
+                            counters.errors.fetch_add(1, Ordering::Relaxed);
+                            log::debug!("[流水线] {} 处理样本失败: {}", counters.name, e);
+                        }
+                    }
+                }
+            })?;
+        }
+
+        // 批次通道按批计数，容量折算为批次数
+        let batch_size = config.batch_size.max(1);
+        let (batch_tx, batches) = sync_channel::<Vec<Sample>>((capacity / batch_size).max(1));
+        let counters = StageCounters::new("batch");
+        stages.push(Arc::clone(&counters));
+        spawn_stage("batch", move || {
+            let mut batch = Vec::with_capacity(batch_size);
+            for sample in upstream.iter() {
+                counters.received.fetch_add(1, Ordering::Relaxed);
+                batch.push(sample);
+                if batch.len() == batch_size {
+                    counters.emitted.fetch_add(1, Ordering::Relaxed);
+                    let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+                    if batch_tx.send(full).is_err() {
+                        return;
+                    }
+                }
+            }
+            // 数据源结束时发出不满的最后一批
+            if !batch.is_empty() {
+                counters.emitted.fetch_add(1, Ordering::Relaxed);
+                let _ = batch_tx.send(batch);
+            }
+        })?;
+
+        Ok(Self {
+            batches: Mutex::new(batches),
+            stages,
+            started: Instant::now(),
+            stop,
+        })
+    }
+
+    /// 取一个已就绪的批次，不等待
+    pub fn try_next_batch(&self) -> Option<Vec<Sample>> {
+        match self.batches.lock().unwrap().try_recv() {
+            Ok(batch) => Some(batch),
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// 等待下一个批次，数据全部处理完后返回 None
+    pub fn next_batch(&self) -> Option<Vec<Sample>> {
+        self.batches.lock().unwrap().recv().ok()
+    }
+
+    /// 各阶段的吞吐指标，按流水线顺序排列
+    pub fn metrics(&self) -> Vec<StageMetrics> {
+        let elapsed = self.started.elapsed().as_secs_f64().max(1e-3);
+        self.stages
+            .iter()
+            .map(|stage| {
+                let emitted = stage.emitted.load(Ordering::Relaxed);
+                StageMetrics {
+                    name: stage.name.clone(),
+                    received: stage.received.load(Ordering::Relaxed),
+                    emitted,
+                    dropped: stage.dropped.load(Ordering::Relaxed),
+                    errors: stage.errors.load(Ordering::Relaxed),
+                    throughput_per_sec: emitted as f64 / elapsed,
+                    busy_ratio: (stage.busy_nanos.load(Ordering::Relaxed) as f64 / 1e9 / elapsed).min(1.0),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        // 数据源线程检查停止标志；下游线程在通道断开后依次退出
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn spawn_stage(name: &str, run: impl FnOnce() + Send + 'static) -> Result<()> {
+    std::thread::Builder::new()
+        .name(format!("pipeline-{}", name))
+        .spawn(run)?;
+    Ok(())
+}
+
+fn run_source(
+    source: SourceConfig,
+    epochs: u32,
+    tx: SyncSender<Sample>,
+    counters: Arc<StageCounters>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut epoch = 0;
+    while epochs == 0 || epoch < epochs {
+        epoch += 1;
+        let samples = match source.open() {
+            Ok(samples) => samples,
+            Err(e) => {
+                log::warn!("[流水线] 打开数据源失败: {}", e);
+                return;
+            }
+        };
+        let mut empty = true;
+        for sample in samples {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let started = Instant::now();
+            match sample {
+                Ok(sample) => {
+                    empty = false;
+                    counters.emitted.fetch_add(1, Ordering::Relaxed);
+                    counters.add_busy(started);
+                    if tx.send(sample).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    counters.errors.fetch_add(1, Ordering::Relaxed);
+                    log::debug!("[流水线] 读取样本失败: {}", e);
+                }
+            }
+        }
+        // 空数据源无限循环会空转
+        if empty {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_pipeline_runs_transforms_in_order() {
+        let path = std::env::temp_dir().join(format!("williw-pipeline-{}.jsonl", uuid::Uuid::new_v4()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, r#"{{"text": "the quick brown fox", "target": [1.0]}}"#).unwrap();
+        writeln!(file, r#"{{"text": "hi", "target": [0.0]}}"#).unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(file, r#"{{"text": "jumps over the lazy dog", "target": [1.0]}}"#).unwrap();
+
+        let config: PipelineConfig = toml::from_str(&format!(
+            r#"
+            enabled = true
+            batch_size = 2
+            channel_capacity = 1
+            [source]
+            kind = "jsonl"
+            path = "{}"
+            [[transforms]]
+            kind = "tokenize"
+            max_len = 8
+            [[transforms]]
+            kind = "filter"
+            min_tokens = 3
+            [[transforms]]
+            kind = "normalize"
+            method = "l2"
+            "#,
+            path.display().to_string().replace('\\', "/")
+        ))
+        .unwrap();
+        let pipeline = Pipeline::start(&config, &TransformRegistry::default()).unwrap();
+
+        let batch = pipeline.next_batch().unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(batch.iter().all(|sample| sample.input.len() == 8));
+        let norm: f32 = batch[0].input.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert!(pipeline.next_batch().is_none());
+
+        let metrics = pipeline.metrics();
+        let names: Vec<&str> = metrics.iter().map(|stage| stage.name.as_str()).collect();
+        assert_eq!(names, ["source", "tokenize", "filter", "normalize", "batch"]);
+        assert_eq!((metrics[0].emitted, metrics[0].errors), (3, 1));
+        assert_eq!(metrics[2].dropped, 1);
+        assert_eq!(metrics[4].emitted, 1);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_unknown_transform_and_custom_registration() {
+        struct Double;
+        impl Transform for Double {
+            fn name(&self) -> &str {
+                "double"
+            }
+            fn apply(&self, mut sample: Sample) -> Result<Option<Sample>> {
+                sample.input.iter_mut().for_each(|x| *x *= 2.0);
+                Ok(Some(sample))
+            }
+        }
+
+        let mut config = PipelineConfig {
+            source: SourceConfig::Synthetic {
+                input_dim: 4,
+                output_dim: 1,
+                seed: 7,
+                samples: 5,
+            },
+            transforms: vec![TransformSpec {
+                kind: "double".to_string(),
+                params: toml::Table::new(),
+            }],
+            batch_size: 4,
+            ..Default::default()
+        };
+        let mut registry = TransformRegistry::default();
+        assert!(Pipeline::start(&config, &registry).is_err());
+
+        registry.register("double", |_| Ok(Box::new(Double)));
+        let pipeline = Pipeline::start(&config, &registry).unwrap();
+        let sizes: Vec<usize> = std::iter::from_fn(|| pipeline.next_batch()).map(|batch| batch.len()).collect();
+        assert_eq!(sizes, vec![4, 1]);
+
+        config.transforms[0].params.insert("method".to_string(), toml::Value::Integer(1));
+        config.transforms[0].kind = "normalize".to_string();
+        assert!(Pipeline::start(&config, &registry).is_err());
+    }
+}
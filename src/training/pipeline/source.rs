@@ -0,0 +1,72 @@
+//! 流水线数据源
+
+use super::Sample;
+use crate::training::{SyntheticData, TrainingData};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+/// 数据源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SourceConfig {
+    /// 每行一个 JSON 样本：`{"text": ..., "input": [...], "target": [...]}`，字段均可省略
+    Jsonl { path: PathBuf },
+    /// 合成线性数据（测试和演示用）
+    Synthetic {
+        input_dim: usize,
+        output_dim: usize,
+        seed: u64,
+        samples: usize,
+    },
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        SourceConfig::Synthetic {
+            input_dim: 64,
+            output_dim: 1,
+            seed: 42,
+            samples: 1024,
+        }
+    }
+}
+
+pub type SampleIter = Box<dyn Iterator<Item = Result<Sample>> + Send>;
+
+impl SourceConfig {
+    /// 打开数据源，每个 epoch 调用一次
+    pub fn open(&self) -> Result<SampleIter> {
+        match self {
+            SourceConfig::Jsonl { path } => {
+                let file = std::fs::File::open(path).map_err(|e| anyhow!("打开数据文件 {} 失败: {}", path.display(), e))?;
+                let lines = BufReader::new(file).lines().enumerate().filter_map(|(index, line)| match line {
+                    Ok(line) if line.trim().is_empty() => None,
+                    Ok(line) => Some(
+                        serde_json::from_str(&line).map_err(|e| anyhow!("第 {} 行解析失败: {}", index + 1, e)),
+                    ),
+                    Err(e) => Some(Err(e.into())),
+                });
+                Ok(Box::new(lines))
+            }
+            SourceConfig::Synthetic {
+                input_dim,
+                output_dim,
+                seed,
+                samples,
+            } => {
+                let mut data = SyntheticData::new(*input_dim, *output_dim, *seed);
+                Ok(Box::new((0..*samples).map_while(move |_| {
+                    data.next_sample().map(|(input, target)| {
+                        Ok(Sample {
+                            text: None,
+                            input: input.to_vec(),
+                            target: target.to_vec(),
+                        })
+                    })
+                })))
+            }
+        }
+    }
+}
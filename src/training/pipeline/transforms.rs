@@ -0,0 +1,270 @@
+//! 内置变换与变换注册表
+//!
+//! TOML 中的每个变换由 `kind` 选择实现，其余字段作为参数交给注册的工厂函数解析。
+//! 自定义变换实现 [`Transform`] 后用 [`TransformRegistry::register`] 注册即可在配置中使用。
+
+use super::Sample;
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 流水线中的一个变换
+pub trait Transform: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// 处理一个样本；返回 `Ok(None)` 表示丢弃该样本
+    fn apply(&self, sample: Sample) -> Result<Option<Sample>>;
+}
+
+/// 配置中的变换声明：`kind` 之外的字段为参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformSpec {
+    pub kind: String,
+    #[serde(flatten)]
+    pub params: toml::Table,
+}
+
+/// 由参数构造变换
+pub type TransformFactory = fn(&toml::Table) -> Result<Box<dyn Transform>>;
+
+/// 变换注册表，默认包含 `tokenize`、`normalize`、`augment`、`filter`
+pub struct TransformRegistry {
+    factories: HashMap<String, TransformFactory>,
+}
+
+impl Default for TransformRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            factories: HashMap::new(),
+        };
+        registry.register("tokenize", |params| Ok(Box::new(Tokenize(parse_params(params)?))));
+        registry.register("normalize", |params| Ok(Box::new(Normalize(parse_params(params)?))));
+        registry.register("augment", |params| Ok(Box::new(Augment::new(parse_params(params)?))));
+        registry.register("filter", |params| Ok(Box::new(Filter(parse_params(params)?))));
+        registry
+    }
+}
+
+impl TransformRegistry {
+    /// 注册（或替换）一种变换
+    pub fn register(&mut self, kind: &str, factory: TransformFactory) {
+        self.factories.insert(kind.to_string(), factory);
+    }
+
+    pub fn build(&self, spec: &TransformSpec) -> Result<Box<dyn Transform>> {
+        let factory = self
+            .factories
+            .get(&spec.kind)
+            .ok_or_else(|| anyhow!("未知的变换类型: {}", spec.kind))?;
+        factory(&spec.params).map_err(|e| anyhow!("变换 {} 参数无效: {}", spec.kind, e))
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: &toml::Table) -> Result<T> {
+    Ok(toml::Value::Table(params.clone()).try_into()?)
+}
+
+/// 分词参数
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct TokenizeParams {
+    vocab_size: u32,
+    /// 输出长度，不足时补 0，超出时截断
+    max_len: usize,
+    lowercase: bool,
+}
+
+impl Default for TokenizeParams {
+    fn default() -> Self {
+        Self {
+            vocab_size: 32_000,
+            max_len: 128,
+            lowercase: true,
+        }
+    }
+}
+
+/// 按空白切词并哈希到词表（ID 从 1 开始，0 为填充），结果写入 `input`
+struct Tokenize(TokenizeParams);
+
+impl Transform for Tokenize {
+    fn name(&self) -> &str {
+        "tokenize"
+    }
+
+    fn apply(&self, mut sample: Sample) -> Result<Option<Sample>> {
+        let text = sample.text.as_deref().ok_or_else(|| anyhow!("样本没有文本"))?;
+        let buckets = self.0.vocab_size.max(2) - 1;
+        let mut input: Vec<f32> = text
+            .split_whitespace()
+            .take(self.0.max_len)
+            .map(|word| {
+                let word = if self.0.lowercase { word.to_lowercase() } else { word.to_string() };
+                let hash = blake3::hash(word.as_bytes());
+                let id = u32::from_le_bytes(hash.as_bytes()[..4].try_into().expect("哈希长度固定"));
+                (1 + id % buckets) as f32
+            })
+            .collect();
+        input.resize(self.0.max_len, 0.0);
+        sample.input = input;
+        Ok(Some(sample))
+    }
+}
+
+/// 归一化方式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NormalizeMethod {
+    /// 减均值除标准差
+    #[default]
+    Zscore,
+    /// 缩放到 [0, 1]
+    Minmax,
+    /// 除以 L2 范数
+    L2,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct NormalizeParams {
+    method: NormalizeMethod,
+    epsilon: f32,
+}
+
+impl Default for NormalizeParams {
+    fn default() -> Self {
+        Self {
+            method: NormalizeMethod::Zscore,
+            epsilon: 1e-6,
+        }
+    }
+}
+
+/// 对 `input` 逐样本归一化
+struct Normalize(NormalizeParams);
+
+impl Transform for Normalize {
+    fn name(&self) -> &str {
+        "normalize"
+    }
+
+    fn apply(&self, mut sample: Sample) -> Result<Option<Sample>> {
+        let input = &mut sample.input;
+        if input.is_empty() {
+            return Ok(Some(sample));
+        }
+        let epsilon = self.0.epsilon;
+        match self.0.method {
+            NormalizeMethod::Zscore => {
+                let mean = input.iter().sum::<f32>() / input.len() as f32;
+                let variance = input.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / input.len() as f32;
+                let std = variance.sqrt().max(epsilon);
+                input.iter_mut().for_each(|x| *x = (*x - mean) / std);
+            }
+            NormalizeMethod::Minmax => {
+                let min = input.iter().copied().fold(f32::INFINITY, f32::min);
+                let max = input.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let range = (max - min).max(epsilon);
+                input.iter_mut().for_each(|x| *x = (*x - min) / range);
+            }
+            NormalizeMethod::L2 => {
+                let norm = input.iter().map(|x| x * x).sum::<f32>().sqrt().max(epsilon);
+                input.iter_mut().for_each(|x| *x /= norm);
+            }
+        }
+        Ok(Some(sample))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct AugmentParams {
+    /// 高斯噪声的标准差
+    noise_std: f32,
+    /// 每个元素被置零的概率
+    dropout: f32,
+    seed: u64,
+}
+
+/// 数据增强：对 `input` 加高斯噪声并随机置零
+struct Augment {
+    params: AugmentParams,
+    rng: Mutex<StdRng>,
+}
+
+impl Augment {
+    fn new(params: AugmentParams) -> Self {
+        let rng = Mutex::new(StdRng::seed_from_u64(params.seed));
+        Self { params, rng }
+    }
+}
+
+impl Transform for Augment {
+    fn name(&self) -> &str {
+        "augment"
+    }
+
+    fn apply(&self, mut sample: Sample) -> Result<Option<Sample>> {
+        let mut rng = self.rng.lock();
+        for x in sample.input.iter_mut() {
+            if self.params.dropout > 0.0 && rng.random::<f32>() < self.params.dropout {
+                *x = 0.0;
+                continue;
+            }
+            if self.params.noise_std > 0.0 {
+                // Box-Muller 生成标准正态分布
+                let (u1, u2) = (rng.random::<f32>().max(f32::MIN_POSITIVE), rng.random::<f32>());
+                let gaussian = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
+                *x += gaussian * self.params.noise_std;
+            }
+        }
+        Ok(Some(sample))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct FilterParams {
+    /// `input` 中非零元素（分词后即词数）的下限
+    min_tokens: usize,
+    max_tokens: Option<usize>,
+    /// 文本长度（字符）下限
+    min_text_chars: usize,
+    /// 丢弃含 NaN / 无穷值的样本
+    drop_non_finite: bool,
+}
+
+impl Default for FilterParams {
+    fn default() -> Self {
+        Self {
+            min_tokens: 1,
+            max_tokens: None,
+            min_text_chars: 0,
+            drop_non_finite: true,
+        }
+    }
+}
+
+/// 按长度和数值有效性丢弃样本
+struct Filter(FilterParams);
+
+impl Transform for Filter {
+    fn name(&self) -> &str {
+        "filter"
+    }
+
+    fn apply(&self, sample: Sample) -> Result<Option<Sample>> {
+        let tokens = sample.input.iter().filter(|x| **x != 0.0).count();
+        let text_chars = sample.text.as_deref().map_or(0, |text| text.chars().count());
+        let non_finite = sample.input.iter().chain(&sample.target).any(|x| !x.is_finite());
+        let keep = tokens >= self.0.min_tokens
+            && self.0.max_tokens.is_none_or(|max| tokens <= max)
+            && text_chars >= self.0.min_text_chars
+            && !(self.0.drop_non_finite && non_finite);
+        Ok(keep.then_some(sample))
+    }
+}
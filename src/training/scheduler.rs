@@ -0,0 +1,480 @@
+//! 推理请求的优先级与抢占调度
+//!
+//! - 优先级类别：交互式 > 标准 > 批处理，空闲执行槽总是先分给最高类别的等待请求
+//! - 租户公平：同一类别内选择虚拟时间（已占用执行时间 / 权重）最少的租户，单个租户无法占满队列
+//! - 抢占：长时间运行的请求在安全点调用 [`RunPermit::checkpoint`]，有更高类别的请求在等待时
+//!   让出执行槽并排回队首（进度由调用方保留），之后再恢复执行
+//! - 统计：各类别的队列深度、排队等待和端到端延迟分位数
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// 推理请求的优先级类别（按 API 密钥配置）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityClass {
+    Batch,
+    #[default]
+    Standard,
+    Interactive,
+}
+
+impl PriorityClass {
+    /// 从高到低
+    pub const ALL: [PriorityClass; 3] = [PriorityClass::Interactive, PriorityClass::Standard, PriorityClass::Batch];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriorityClass::Interactive => "interactive",
+            PriorityClass::Standard => "standard",
+            PriorityClass::Batch => "batch",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "interactive" => Some(PriorityClass::Interactive),
+            "standard" => Some(PriorityClass::Standard),
+            "batch" => Some(PriorityClass::Batch),
+            _ => None,
+        }
+    }
+}
+
+/// 调度配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// 同时执行的请求数
+    pub max_concurrent: usize,
+    /// 租户权重（未列出的为 1.0），权重越大分到的执行时间越多
+    pub tenant_weights: HashMap<String, f64>,
+    /// 每个类别保留的最近延迟样本数
+    pub latency_window: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            tenant_weights: HashMap::new(),
+            latency_window: 1024,
+        }
+    }
+}
+
+/// 延迟分位数（毫秒）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// 单个优先级类别的统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassStats {
+    pub class: PriorityClass,
+    pub queue_depth: usize,
+    pub running: usize,
+    /// 从提交到首次获得执行槽
+    pub wait: LatencyPercentiles,
+    /// 从提交到完成（含被抢占的时间）
+    pub latency: LatencyPercentiles,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchedulerStats {
+    /// 按优先级从高到低
+    pub classes: Vec<ClassStats>,
+    pub preemptions: u64,
+    pub completed: u64,
+}
+
+impl SchedulerStats {
+    pub fn class(&self, class: PriorityClass) -> &ClassStats {
+        self.classes.iter().find(|c| c.class == class).expect("包含所有类别")
+    }
+}
+
+struct Waiting {
+    submitted_at: Instant,
+    /// 被抢占后重新排队的请求不再计入等待延迟
+    resumed: bool,
+    grant: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct LatencySamples {
+    wait_ms: VecDeque<f64>,
+    latency_ms: VecDeque<f64>,
+}
+
+fn push_sample(samples: &mut VecDeque<f64>, value: Duration, window: usize) {
+    if samples.len() >= window.max(1) {
+        samples.pop_front();
+    }
+    samples.push_back(value.as_secs_f64() * 1000.0);
+}
+
+fn percentiles(samples: &VecDeque<f64>) -> LatencyPercentiles {
+    if samples.is_empty() {
+        return LatencyPercentiles::default();
+    }
+    let mut sorted: Vec<f64> = samples.iter().copied().collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = |p: f64| sorted[((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len()) - 1];
+    LatencyPercentiles {
+        samples: sorted.len(),
+        p50_ms: rank(0.50),
+        p95_ms: rank(0.95),
+        p99_ms: rank(0.99),
+    }
+}
+
+struct State {
+    config: SchedulerConfig,
+    free_slots: usize,
+    /// 类别 -> 租户 -> 等待队列
+    queues: BTreeMap<PriorityClass, HashMap<String, VecDeque<Waiting>>>,
+    /// 租户虚拟时间（秒）
+    vtime: HashMap<String, f64>,
+    running: HashMap<PriorityClass, usize>,
+    samples: HashMap<PriorityClass, LatencySamples>,
+    preemptions: u64,
+    completed: u64,
+}
+
+impl State {
+    fn is_backlogged(&self, tenant: &str) -> bool {
+        self.queues.values().any(|tenants| tenants.get(tenant).is_some_and(|q| !q.is_empty()))
+    }
+
+    fn enqueue(&mut self, tenant: &str, class: PriorityClass, submitted_at: Instant, resumed: bool) -> oneshot::Receiver<()> {
+        // 新进入排队的租户从当前最小虚拟时间起步，不能用空闲期攒下的额度插队
+        if !self.is_backlogged(tenant) {
+            let floor = self
+                .queues
+                .values()
+                .flat_map(|tenants| tenants.iter().filter(|(_, q)| !q.is_empty()).map(|(t, _)| t))
+                .filter_map(|t| self.vtime.get(t).copied())
+                .fold(None, |min: Option<f64>, v| Some(min.map_or(v, |m| m.min(v))));
+            let vtime = self.vtime.entry(tenant.to_string()).or_insert(0.0);
+            if let Some(floor) = floor {
+                *vtime = vtime.max(floor);
+            }
+        }
+
+        let (grant, rx) = oneshot::channel();
+        let waiting = Waiting {
+            submitted_at,
+            resumed,
+            grant,
+        };
+        let queue = self.queues.entry(class).or_default().entry(tenant.to_string()).or_default();
+        if resumed {
+            queue.push_front(waiting);
+        } else {
+            queue.push_back(waiting);
+        }
+        self.dispatch();
+        rx
+    }
+
+    /// 最高类别中虚拟时间最少的租户的队首请求
+    fn pick_next(&mut self) -> Option<(PriorityClass, Waiting)> {
+        for class in PriorityClass::ALL {
+            let Some(tenants) = self.queues.get_mut(&class) else {
+                continue;
+            };
+            let tenant = tenants
+                .iter()
+                .filter(|(_, q)| !q.is_empty())
+                .map(|(t, _)| (self.vtime.get(t).copied().unwrap_or(0.0), t))
+                .min_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)))
+                .map(|(_, t)| t.clone());
+            if let Some(tenant) = tenant {
+                let queue = tenants.get_mut(&tenant).expect("刚选出的租户");
+                let waiting = queue.pop_front().expect("队列非空");
+                if queue.is_empty() {
+                    tenants.remove(&tenant);
+                }
+                return Some((class, waiting));
+            }
+        }
+        None
+    }
+
+    fn dispatch(&mut self) {
+        while self.free_slots > 0 {
+            let Some((class, waiting)) = self.pick_next() else {
+                break;
+            };
+            let (submitted_at, resumed) = (waiting.submitted_at, waiting.resumed);
+            // 接收方已放弃（请求被取消）时跳过
+            if waiting.grant.send(()).is_err() {
+                continue;
+            }
+            self.free_slots -= 1;
+            *self.running.entry(class).or_default() += 1;
+            if !resumed {
+                let window = self.config.latency_window;
+                push_sample(&mut self.samples.entry(class).or_default().wait_ms, submitted_at.elapsed(), window);
+            }
+        }
+    }
+
+    fn charge(&mut self, tenant: &str, elapsed: Duration) {
+        let weight = self.config.tenant_weights.get(tenant).copied().unwrap_or(1.0).max(f64::EPSILON);
+        *self.vtime.entry(tenant.to_string()).or_insert(0.0) += elapsed.as_secs_f64() / weight;
+    }
+
+    fn release(&mut self, class: PriorityClass) {
+        self.free_slots += 1;
+        if let Some(running) = self.running.get_mut(&class) {
+            *running = running.saturating_sub(1);
+        }
+        self.dispatch();
+    }
+
+    fn higher_waiting(&self, class: PriorityClass) -> bool {
+        self.queues
+            .range(class..)
+            .any(|(c, tenants)| *c > class && tenants.values().any(|q| !q.is_empty()))
+    }
+}
+
+/// 等待执行槽；未收到授权前被丢弃（请求取消）时，若授权已发出则归还执行槽
+struct PendingGrant {
+    rx: Option<oneshot::Receiver<()>>,
+    state: Arc<Mutex<State>>,
+    class: PriorityClass,
+}
+
+impl PendingGrant {
+    async fn wait(mut self) {
+        if let Some(rx) = self.rx.as_mut() {
+            let _ = rx.await;
+        }
+        self.rx = None;
+    }
+}
+
+impl Drop for PendingGrant {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            if rx.try_recv().is_ok() {
+                self.state.lock().release(self.class);
+            }
+        }
+    }
+}
+
+/// 推理请求调度器
+#[derive(Clone)]
+pub struct InferenceScheduler {
+    state: Arc<Mutex<State>>,
+}
+
+impl InferenceScheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        let free_slots = config.max_concurrent.max(1);
+        Self {
+            state: Arc::new(Mutex::new(State {
+                config,
+                free_slots,
+                queues: BTreeMap::new(),
+                vtime: HashMap::new(),
+                running: HashMap::new(),
+                samples: HashMap::new(),
+                preemptions: 0,
+                completed: 0,
+            })),
+        }
+    }
+
+    /// 排队等待执行槽，返回的许可在丢弃时归还
+    pub async fn acquire(&self, tenant: &str, class: PriorityClass) -> RunPermit {
+        let submitted_at = Instant::now();
+        let rx = self.state.lock().enqueue(tenant, class, submitted_at, false);
+        PendingGrant {
+            rx: Some(rx),
+            state: self.state.clone(),
+            class,
+        }
+        .wait()
+        .await;
+        RunPermit {
+            state: self.state.clone(),
+            tenant: tenant.to_string(),
+            class,
+            submitted_at,
+            slice_started: Instant::now(),
+            holding: true,
+            preemptions: 0,
+        }
+    }
+
+    pub fn stats(&self) -> SchedulerStats {
+        let state = self.state.lock();
+        let classes = PriorityClass::ALL
+            .iter()
+            .map(|class| {
+                let samples = state.samples.get(class);
+                ClassStats {
+                    class: *class,
+                    queue_depth: state.queues.get(class).map_or(0, |tenants| tenants.values().map(VecDeque::len).sum()),
+                    running: state.running.get(class).copied().unwrap_or(0),
+                    wait: samples.map(|s| percentiles(&s.wait_ms)).unwrap_or_default(),
+                    latency: samples.map(|s| percentiles(&s.latency_ms)).unwrap_or_default(),
+                }
+            })
+            .collect();
+        SchedulerStats {
+            classes,
+            preemptions: state.preemptions,
+            completed: state.completed,
+        }
+    }
+}
+
+/// 执行许可
+pub struct RunPermit {
+    state: Arc<Mutex<State>>,
+    tenant: String,
+    class: PriorityClass,
+    submitted_at: Instant,
+    slice_started: Instant,
+    holding: bool,
+    preemptions: u32,
+}
+
+impl RunPermit {
+    pub fn class(&self) -> PriorityClass {
+        self.class
+    }
+
+    /// 本请求被抢占的次数
+    pub fn preemptions(&self) -> u32 {
+        self.preemptions
+    }
+
+    /// 安全点：有更高类别的请求等待时让出执行槽，重新获得后返回 true；否则立即返回 false
+    pub async fn checkpoint(&mut self) -> bool {
+        let rx = {
+            let mut state = self.state.lock();
+            if !state.higher_waiting(self.class) {
+                return false;
+            }
+            state.charge(&self.tenant, self.slice_started.elapsed());
+            state.preemptions += 1;
+            let rx = state.enqueue(&self.tenant, self.class, self.submitted_at, true);
+            state.release(self.class);
+            rx
+        };
+        self.holding = false;
+        self.preemptions += 1;
+        PendingGrant {
+            rx: Some(rx),
+            state: self.state.clone(),
+            class: self.class,
+        }
+        .wait()
+        .await;
+        self.holding = true;
+        self.slice_started = Instant::now();
+        true
+    }
+}
+
+impl Drop for RunPermit {
+    fn drop(&mut self) {
+        if !self.holding {
+            return;
+        }
+        let mut state = self.state.lock();
+        state.charge(&self.tenant, self.slice_started.elapsed());
+        state.completed += 1;
+        let window = state.config.latency_window;
+        push_sample(
+            &mut state.samples.entry(self.class).or_default().latency_ms,
+            self.submitted_at.elapsed(),
+            window,
+        );
+        state.release(self.class);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single_slot() -> InferenceScheduler {
+        InferenceScheduler::new(SchedulerConfig {
+            max_concurrent: 1,
+            ..Default::default()
+        })
+    }
+
+    async fn wait_queued(scheduler: &InferenceScheduler, class: PriorityClass, depth: usize) {
+        while scheduler.stats().class(class).queue_depth < depth {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interactive_preempts_batch() {
+        let scheduler = single_slot();
+        let mut batch = scheduler.acquire("tenant-a", PriorityClass::Batch).await;
+        assert!(!batch.checkpoint().await);
+
+        let other = scheduler.clone();
+        let interactive = tokio::spawn(async move {
+            let _permit = other.acquire("tenant-b", PriorityClass::Interactive).await;
+        });
+        wait_queued(&scheduler, PriorityClass::Interactive, 1).await;
+
+        // 让出执行槽，交互式请求完成后恢复
+        assert!(batch.checkpoint().await);
+        interactive.await.unwrap();
+        assert_eq!(batch.preemptions(), 1);
+
+        let stats = scheduler.stats();
+        assert_eq!(stats.preemptions, 1);
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.class(PriorityClass::Batch).running, 1);
+        assert_eq!(stats.class(PriorityClass::Interactive).latency.samples, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fair_share_across_tenants() {
+        let scheduler = single_slot();
+        let holder = scheduler.acquire("heavy", PriorityClass::Standard).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for tenant in ["heavy", "heavy", "heavy", "light"] {
+            let (scheduler, order) = (scheduler.clone(), order.clone());
+            tasks.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(tenant, PriorityClass::Standard).await;
+                order.lock().push(tenant);
+                tokio::time::sleep(Duration::from_millis(2)).await;
+            }));
+            wait_queued(&scheduler, PriorityClass::Standard, tasks.len()).await;
+        }
+
+        // heavy 已占用过执行时间，后到的 light 先于 heavy 排队中的请求执行
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        drop(holder);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(order.lock()[0], "light");
+        assert_eq!(scheduler.stats().class(PriorityClass::Standard).wait.samples, 5);
+    }
+}
@@ -0,0 +1,366 @@
+//! 推理服务的蓝绿模型切换
+//!
+//! 升级模型版本不需要停止推理：
+//! 1. 在内存预算内把新版本加载到旧版本旁边（绿）
+//! 2. 原子切换路由，新请求进入新版本
+//! 3. 等待旧版本的在途请求处理完（排空）
+//! 4. 观察期内新版本错误率超过阈值则自动回滚到旧版本，观察期结束后释放旧版本
+//!
+//! 内存不足以同时容纳两个版本时，由 `plan_staggered_swap` 把副本分批原地切换，
+//! 每批切换期间其余副本继续提供服务。
+
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 可被推理服务加载的模型
+pub trait ServableModel: Send + Sync {
+    /// 模型版本标识
+    fn version(&self) -> String;
+    /// 加载后占用的内存（MB）
+    fn memory_mb(&self) -> usize;
+    /// 执行一次推理
+    fn infer(&self, input: &[f32]) -> Result<Vec<f32>>;
+}
+
+/// 模型切换配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapConfig {
+    /// 推理服务可用的内存预算（MB）
+    pub memory_budget_mb: usize,
+    /// 排空在途请求的最长等待时间（毫秒）
+    pub drain_timeout_ms: u64,
+    /// 切换后的观察期（毫秒），期间保留旧版本用于回滚
+    pub observation_window_ms: u64,
+    /// 观察期内触发回滚的错误率
+    pub max_error_rate: f64,
+    /// 至少处理这么多请求后才判断错误率
+    pub min_requests_for_rollback: u64,
+}
+
+impl Default for SwapConfig {
+    fn default() -> Self {
+        Self {
+            memory_budget_mb: 4096,
+            drain_timeout_ms: 30_000,
+            observation_window_ms: 120_000,
+            max_error_rate: 0.05,
+            min_requests_for_rollback: 20,
+        }
+    }
+}
+
+/// 切换结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapOutcome {
+    /// 已切换，进入观察期
+    Switched { from: String, to: String, drained: bool },
+    /// 内存预算不足以同时加载两个版本，需要分批原地切换
+    InsufficientMemory { required_mb: usize, budget_mb: usize },
+}
+
+/// 路由事件（用于日志和监控）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RouterEvent {
+    Switched { from: String, to: String },
+    RolledBack { from: String, to: String, error_rate_ppm: u64 },
+    Committed { version: String },
+}
+
+struct ModelSlot<M> {
+    model: Arc<M>,
+    version: String,
+    in_flight: AtomicUsize,
+    requests: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl<M: ServableModel> ModelSlot<M> {
+    fn new(model: M) -> Self {
+        Self {
+            version: model.version(),
+            model: Arc::new(model),
+            in_flight: AtomicUsize::new(0),
+            requests: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        }
+    }
+
+    fn error_rate(&self) -> f64 {
+        let requests = self.requests.load(Ordering::Relaxed);
+        if requests == 0 {
+            return 0.0;
+        }
+        self.errors.load(Ordering::Relaxed) as f64 / requests as f64
+    }
+}
+
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// 切换后仍保留的旧版本
+struct Standby<M> {
+    slot: Arc<ModelSlot<M>>,
+    switched_at: Instant,
+}
+
+/// 模型路由器
+pub struct ModelRouter<M: ServableModel> {
+    config: SwapConfig,
+    active: RwLock<Arc<ModelSlot<M>>>,
+    standby: Mutex<Option<Standby<M>>>,
+    swapping: tokio::sync::Mutex<()>,
+    events: Mutex<Vec<RouterEvent>>,
+}
+
+impl<M: ServableModel> ModelRouter<M> {
+    pub fn new(config: SwapConfig, model: M) -> Self {
+        Self {
+            config,
+            active: RwLock::new(Arc::new(ModelSlot::new(model))),
+            standby: Mutex::new(None),
+            swapping: tokio::sync::Mutex::new(()),
+            events: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 当前提供服务的版本
+    pub fn active_version(&self) -> String {
+        self.active.read().version.clone()
+    }
+
+    /// 当前版本的在途请求数
+    pub fn in_flight(&self) -> usize {
+        self.active.read().in_flight.load(Ordering::Acquire)
+    }
+
+    /// 是否处于观察期（保留旧版本可回滚）
+    pub fn is_observing(&self) -> bool {
+        self.standby.lock().is_some()
+    }
+
+    pub fn events(&self) -> Vec<RouterEvent> {
+        self.events.lock().clone()
+    }
+
+    /// 路由一次推理请求
+    pub fn infer(&self, input: &[f32]) -> Result<Vec<f32>> {
+        let slot = self.active.read().clone();
+        slot.in_flight.fetch_add(1, Ordering::AcqRel);
+        let _guard = InFlightGuard(&slot.in_flight);
+
+        let result = slot.model.infer(input);
+        slot.requests.fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            slot.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.evaluate(&slot);
+        result
+    }
+
+    /// 蓝绿切换到新版本
+    pub async fn swap(&self, new_model: M) -> Result<SwapOutcome> {
+        let _swapping = self
+            .swapping
+            .try_lock()
+            .map_err(|_| anyhow!("已有模型切换正在进行"))?;
+        // 上一次切换仍在观察期时先确认，释放旧版本内存
+        self.commit();
+
+        let current = self.active.read().clone();
+        let required_mb = current.model.memory_mb() + new_model.memory_mb();
+        if required_mb > self.config.memory_budget_mb {
+            return Ok(SwapOutcome::InsufficientMemory {
+                required_mb,
+                budget_mb: self.config.memory_budget_mb,
+            });
+        }
+
+        let green = Arc::new(ModelSlot::new(new_model));
+        let (from, to) = (current.version.clone(), green.version.clone());
+        *self.active.write() = green;
+        *self.standby.lock() = Some(Standby {
+            slot: current.clone(),
+            switched_at: Instant::now(),
+        });
+        println!("[模型切换] {} -> {}，开始排空旧版本请求", from, to);
+        self.events.lock().push(RouterEvent::Switched {
+            from: from.clone(),
+            to: to.clone(),
+        });
+
+        let drained = drain(&current, Duration::from_millis(self.config.drain_timeout_ms)).await;
+        if !drained {
+            log::warn!(
+                "[模型切换] 旧版本 {} 排空超时，仍有 {} 个在途请求",
+                from,
+                current.in_flight.load(Ordering::Acquire)
+            );
+        }
+        Ok(SwapOutcome::Switched { from, to, drained })
+    }
+
+    /// 观察期结束后释放旧版本
+    pub fn commit(&self) -> bool {
+        let Some(standby) = self.standby.lock().take() else {
+            return false;
+        };
+        let version = self.active_version();
+        println!("[模型切换] 确认版本 {}，释放 {}", version, standby.slot.version);
+        self.events.lock().push(RouterEvent::Committed { version });
+        true
+    }
+
+    /// 手动回滚到旧版本
+    pub fn rollback(&self) -> Result<()> {
+        let standby = self
+            .standby
+            .lock()
+            .take()
+            .ok_or_else(|| anyhow!("没有可回滚的版本"))?;
+        let mut active = self.active.write();
+        let failed = std::mem::replace(&mut *active, standby.slot);
+        let error_rate_ppm = (failed.error_rate() * 1_000_000.0) as u64;
+        println!(
+            "[模型切换] 回滚 {} -> {}（错误率 {:.2}%）",
+            failed.version,
+            active.version,
+            failed.error_rate() * 100.0
+        );
+        self.events.lock().push(RouterEvent::RolledBack {
+            from: failed.version.clone(),
+            to: active.version.clone(),
+            error_rate_ppm,
+        });
+        Ok(())
+    }
+
+    /// 观察期内检查新版本健康状况
+    fn evaluate(&self, slot: &Arc<ModelSlot<M>>) {
+        // 请求可能在切换前进入旧版本，只评估当前生效的版本
+        if !Arc::ptr_eq(slot, &self.active.read()) {
+            return;
+        }
+        let expired = match self.standby.lock().as_ref() {
+            Some(standby) => standby.switched_at.elapsed() >= Duration::from_millis(self.config.observation_window_ms),
+            None => return,
+        };
+
+        let unhealthy = slot.requests.load(Ordering::Relaxed) >= self.config.min_requests_for_rollback
+            && slot.error_rate() > self.config.max_error_rate;
+        if unhealthy {
+            let _ = self.rollback();
+        } else if expired {
+            self.commit();
+        }
+    }
+}
+
+async fn drain<M>(slot: &ModelSlot<M>, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while slot.in_flight.load(Ordering::Acquire) > 0 {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    true
+}
+
+/// 内存不足时的分批原地切换计划：每批最多 `max_unavailable` 个副本下线，
+/// 且至少保留一个副本在线
+pub fn plan_staggered_swap(replicas: &[String], max_unavailable: usize) -> Vec<Vec<String>> {
+    if replicas.is_empty() {
+        return Vec::new();
+    }
+    let wave_size = max_unavailable.clamp(1, replicas.len().saturating_sub(1).max(1));
+    replicas.chunks(wave_size).map(|wave| wave.to_vec()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    struct TestModel {
+        version: &'static str,
+        memory_mb: usize,
+        failing: Arc<AtomicBool>,
+    }
+
+    impl ServableModel for TestModel {
+        fn version(&self) -> String {
+            self.version.to_string()
+        }
+
+        fn memory_mb(&self) -> usize {
+            self.memory_mb
+        }
+
+        fn infer(&self, input: &[f32]) -> Result<Vec<f32>> {
+            if self.failing.load(Ordering::Relaxed) {
+                return Err(anyhow!("推理失败"));
+            }
+            Ok(input.to_vec())
+        }
+    }
+
+    fn model(version: &'static str, failing: bool) -> TestModel {
+        TestModel {
+            version,
+            memory_mb: 100,
+            failing: Arc::new(AtomicBool::new(failing)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swap_then_rollback_on_error_spike() {
+        let config = SwapConfig {
+            memory_budget_mb: 250,
+            min_requests_for_rollback: 5,
+            ..Default::default()
+        };
+        let router = ModelRouter::new(config, model("v1", false));
+
+        let outcome = router.swap(model("v2", true)).await.unwrap();
+        assert!(matches!(outcome, SwapOutcome::Switched { drained: true, .. }));
+        assert_eq!(router.active_version(), "v2");
+
+        for _ in 0..5 {
+            assert!(router.infer(&[1.0]).is_err());
+        }
+        // 错误率超过阈值，自动回滚
+        assert_eq!(router.active_version(), "v1");
+        assert!(router.infer(&[1.0]).is_ok());
+        assert!(matches!(router.events().last(), Some(RouterEvent::RolledBack { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_swap_respects_memory_budget() {
+        let config = SwapConfig {
+            memory_budget_mb: 150,
+            ..Default::default()
+        };
+        let router = ModelRouter::new(config, model("v1", false));
+        let outcome = router.swap(model("v2", false)).await.unwrap();
+        assert_eq!(
+            outcome,
+            SwapOutcome::InsufficientMemory {
+                required_mb: 200,
+                budget_mb: 150
+            }
+        );
+        assert_eq!(router.active_version(), "v1");
+
+        let replicas: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(plan_staggered_swap(&replicas, 5).len(), 2);
+    }
+}
@@ -0,0 +1,415 @@
+//! 影子模式：在生产中验证新的聚合或路由算法
+//!
+//! 新算法（影子）与当前算法（主路）在同一输入上并行运行，
+//! 只有主路结果会被提交；影子的输出、错误甚至 panic 都不会影响主路。
+//! 每次运行记录两者的偏差指标，可导出为 NDJSON 供离线分析，
+//! 汇总报告用于决定影子算法是否可以转正。
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::stats::StatsStreamWriter;
+
+/// 可被影子化的算法
+pub trait ShadowAlgorithm<I: ?Sized, O>: Send + Sync {
+    fn name(&self) -> &str;
+    fn run(&self, input: &I) -> Result<O>;
+}
+
+/// 输出之间的偏差度量
+pub trait Divergence {
+    fn divergence(live: &Self, shadow: &Self) -> DivergenceMetrics;
+}
+
+/// 一次运行中主路与影子输出的偏差
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceMetrics {
+    /// 欧氏距离（向量输出）
+    pub l2_distance: f64,
+    /// 余弦相似度（向量输出，任一为零向量时为 1 或 0）
+    pub cosine_similarity: f64,
+    /// 最大逐元素绝对差（向量输出）
+    pub max_abs_diff: f64,
+    /// 输出是否完全一致
+    pub agrees: bool,
+}
+
+impl Divergence for Vec<f32> {
+    fn divergence(live: &Self, shadow: &Self) -> DivergenceMetrics {
+        if live.len() != shadow.len() {
+            return DivergenceMetrics {
+                l2_distance: f64::INFINITY,
+                cosine_similarity: 0.0,
+                max_abs_diff: f64::INFINITY,
+                agrees: false,
+            };
+        }
+        let (mut sq, mut dot, mut live_sq, mut shadow_sq, mut max_abs) = (0.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64);
+        for (a, b) in live.iter().zip(shadow) {
+            let (a, b) = (*a as f64, *b as f64);
+            sq += (a - b) * (a - b);
+            dot += a * b;
+            live_sq += a * a;
+            shadow_sq += b * b;
+            max_abs = max_abs.max((a - b).abs());
+        }
+        let cosine_similarity = if live_sq == 0.0 || shadow_sq == 0.0 {
+            if live_sq == shadow_sq { 1.0 } else { 0.0 }
+        } else {
+            dot / (live_sq.sqrt() * shadow_sq.sqrt())
+        };
+        DivergenceMetrics {
+            l2_distance: sq.sqrt(),
+            cosine_similarity,
+            max_abs_diff: max_abs,
+            agrees: max_abs == 0.0,
+        }
+    }
+}
+
+/// 路由类输出（节点或路径 ID 列表）：余弦相似度取集合的 Jaccard 系数
+impl Divergence for Vec<String> {
+    fn divergence(live: &Self, shadow: &Self) -> DivergenceMetrics {
+        let overlap = live.iter().filter(|id| shadow.contains(id)).count();
+        let union = live.len() + shadow.len() - overlap;
+        let jaccard = if union == 0 { 1.0 } else { overlap as f64 / union as f64 };
+        DivergenceMetrics {
+            l2_distance: 1.0 - jaccard,
+            cosine_similarity: jaccard,
+            max_abs_diff: (live.len() as f64 - shadow.len() as f64).abs(),
+            agrees: live == shadow,
+        }
+    }
+}
+
+/// 影子模式配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShadowConfig {
+    pub enabled: bool,
+    /// 每 N 次运行执行一次影子（1 表示每次都执行）
+    pub sample_every: u64,
+    /// 内存中保留的最近记录数
+    pub max_records: usize,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sample_every: 1,
+            max_records: 1024,
+        }
+    }
+}
+
+/// 单次影子运行记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowRecord {
+    pub run: u64,
+    pub timestamp: i64,
+    pub live: String,
+    pub shadow: String,
+    pub live_latency_us: u64,
+    pub shadow_latency_us: u64,
+    /// 影子失败时为 None
+    pub metrics: Option<DivergenceMetrics>,
+    pub shadow_error: Option<String>,
+}
+
+/// 影子算法的累计汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowSummary {
+    pub shadow: String,
+    pub runs: u64,
+    pub errors: u64,
+    pub agreements: u64,
+    pub mean_l2_distance: f64,
+    pub max_l2_distance: f64,
+    pub mean_cosine_similarity: f64,
+    pub mean_latency_ratio: f64,
+}
+
+impl ShadowSummary {
+    /// 是否满足转正条件：样本足够、无错误、平均余弦相似度达标
+    pub fn ready_for_promotion(&self, min_runs: u64, min_cosine_similarity: f64) -> bool {
+        self.runs >= min_runs && self.errors == 0 && self.mean_cosine_similarity >= min_cosine_similarity
+    }
+
+    fn record(&mut self, live_latency_us: u64, shadow_latency_us: u64, metrics: Option<&DivergenceMetrics>) {
+        self.runs += 1;
+        let Some(metrics) = metrics else {
+            self.errors += 1;
+            return;
+        };
+        let n = (self.runs - self.errors) as f64;
+        if metrics.agrees {
+            self.agreements += 1;
+        }
+        self.mean_l2_distance += (metrics.l2_distance - self.mean_l2_distance) / n;
+        self.max_l2_distance = self.max_l2_distance.max(metrics.l2_distance);
+        self.mean_cosine_similarity += (metrics.cosine_similarity - self.mean_cosine_similarity) / n;
+        let ratio = shadow_latency_us as f64 / live_latency_us.max(1) as f64;
+        self.mean_latency_ratio += (ratio - self.mean_latency_ratio) / n;
+    }
+}
+
+/// 影子运行器：执行主路算法并在同一输入上并行执行影子算法
+pub struct ShadowRunner<I: ?Sized, O> {
+    config: ShadowConfig,
+    live: Arc<dyn ShadowAlgorithm<I, O>>,
+    shadows: Vec<Arc<dyn ShadowAlgorithm<I, O>>>,
+    runs: Mutex<u64>,
+    records: Mutex<VecDeque<ShadowRecord>>,
+    summaries: Mutex<HashMap<String, ShadowSummary>>,
+}
+
+impl<I, O> ShadowRunner<I, O>
+where
+    I: Sync + ?Sized,
+    O: Divergence + Send,
+{
+    pub fn new(config: ShadowConfig, live: Arc<dyn ShadowAlgorithm<I, O>>) -> Self {
+        Self {
+            config,
+            live,
+            shadows: Vec::new(),
+            runs: Mutex::new(0),
+            records: Mutex::new(VecDeque::new()),
+            summaries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 添加一个影子算法
+    pub fn add_shadow(&mut self, shadow: Arc<dyn ShadowAlgorithm<I, O>>) {
+        self.shadows.push(shadow);
+    }
+
+    /// 运行主路算法并返回其结果；影子只用于记录偏差
+    pub fn run(&self, input: &I) -> Result<O> {
+        let run = {
+            let mut runs = self.runs.lock();
+            *runs += 1;
+            *runs
+        };
+        let sample = self.config.enabled
+            && !self.shadows.is_empty()
+            && run % self.config.sample_every.max(1) == 0;
+        if !sample {
+            return self.live.run(input);
+        }
+
+        let (live_result, live_latency_us, shadow_results) = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .shadows
+                .iter()
+                .map(|shadow| {
+                    scope.spawn(move || {
+                        let started = Instant::now();
+                        let result = catch_unwind(AssertUnwindSafe(|| shadow.run(input)))
+                            .unwrap_or_else(|_| Err(anyhow!("影子算法 panic")));
+                        (result, started.elapsed().as_micros() as u64)
+                    })
+                })
+                .collect();
+            let started = Instant::now();
+            let live_result = self.live.run(input);
+            let live_latency_us = started.elapsed().as_micros() as u64;
+            let shadow_results: Vec<_> = handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|_| (Err(anyhow!("影子线程异常退出")), 0)))
+                .collect();
+            (live_result, live_latency_us, shadow_results)
+        });
+
+        // 主路失败时没有可比较的基准，直接返回错误
+        let live_output = live_result?;
+        for (shadow, (result, shadow_latency_us)) in self.shadows.iter().zip(shadow_results) {
+            let (metrics, shadow_error) = match result {
+                Ok(output) => (Some(O::divergence(&live_output, &output)), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            self.summaries
+                .lock()
+                .entry(shadow.name().to_string())
+                .or_insert_with(|| ShadowSummary {
+                    shadow: shadow.name().to_string(),
+                    ..Default::default()
+                })
+                .record(live_latency_us, shadow_latency_us, metrics.as_ref());
+
+            let mut records = self.records.lock();
+            records.push_back(ShadowRecord {
+                run,
+                timestamp: chrono::Utc::now().timestamp_millis(),
+                live: self.live.name().to_string(),
+                shadow: shadow.name().to_string(),
+                live_latency_us,
+                shadow_latency_us,
+                metrics,
+                shadow_error,
+            });
+            while records.len() > self.config.max_records {
+                records.pop_front();
+            }
+        }
+        Ok(live_output)
+    }
+
+    /// 最近的影子运行记录
+    pub fn records(&self) -> Vec<ShadowRecord> {
+        self.records.lock().iter().cloned().collect()
+    }
+
+    /// 各影子算法的汇总，用于转正评估
+    pub fn summaries(&self) -> Vec<ShadowSummary> {
+        let mut summaries: Vec<_> = self.summaries.lock().values().cloned().collect();
+        summaries.sort_by(|a, b| a.shadow.cmp(&b.shadow));
+        summaries
+    }
+
+    /// 把内存中的记录追加导出为 NDJSON 并清空，返回导出的条数
+    pub fn export_records<P: AsRef<Path>>(&self, path: P) -> Result<u64> {
+        let records: Vec<_> = self.records.lock().drain(..).collect();
+        let mut writer = StatsStreamWriter::open(path)?;
+        for record in &records {
+            writer.write_record(record)?;
+        }
+        writer.flush()?;
+        Ok(writer.records_written())
+    }
+}
+
+/// 梯度聚合算法
+pub type Aggregator = dyn ShadowAlgorithm<[Vec<f32>], Vec<f32>>;
+
+fn check_updates(updates: &[Vec<f32>]) -> Result<usize> {
+    let dim = updates.first().map(|u| u.len()).ok_or_else(|| anyhow!("没有可聚合的更新"))?;
+    if updates.iter().any(|u| u.len() != dim) {
+        return Err(anyhow!("更新维度不一致"));
+    }
+    Ok(dim)
+}
+
+/// 逐元素平均（当前生产使用的聚合方式）
+pub struct MeanAggregator;
+
+impl ShadowAlgorithm<[Vec<f32>], Vec<f32>> for MeanAggregator {
+    fn name(&self) -> &str {
+        "mean"
+    }
+
+    fn run(&self, updates: &[Vec<f32>]) -> Result<Vec<f32>> {
+        let dim = check_updates(updates)?;
+        let mut out = vec![0.0f32; dim];
+        for update in updates {
+            for (o, v) in out.iter_mut().zip(update) {
+                *o += v;
+            }
+        }
+        let n = updates.len() as f32;
+        out.iter_mut().for_each(|o| *o /= n);
+        Ok(out)
+    }
+}
+
+/// 截尾均值：逐坐标去掉最大和最小的 `trim_ratio` 比例后取平均，抵御拜占庭节点
+pub struct TrimmedMeanAggregator {
+    pub trim_ratio: f32,
+}
+
+impl ShadowAlgorithm<[Vec<f32>], Vec<f32>> for TrimmedMeanAggregator {
+    fn name(&self) -> &str {
+        "trimmed_mean"
+    }
+
+    fn run(&self, updates: &[Vec<f32>]) -> Result<Vec<f32>> {
+        let dim = check_updates(updates)?;
+        let trim = ((updates.len() as f32 * self.trim_ratio.clamp(0.0, 0.49)) as usize).min((updates.len() - 1) / 2);
+        let mut column = Vec::with_capacity(updates.len());
+        Ok((0..dim)
+            .map(|i| {
+                column.clear();
+                column.extend(updates.iter().map(|u| u[i]));
+                column.sort_by(|a, b| a.total_cmp(b));
+                let kept = &column[trim..column.len() - trim];
+                kept.iter().sum::<f32>() / kept.len() as f32
+            })
+            .collect())
+    }
+}
+
+/// 逐坐标中位数
+pub struct CoordinateMedianAggregator;
+
+impl ShadowAlgorithm<[Vec<f32>], Vec<f32>> for CoordinateMedianAggregator {
+    fn name(&self) -> &str {
+        "coordinate_median"
+    }
+
+    fn run(&self, updates: &[Vec<f32>]) -> Result<Vec<f32>> {
+        let dim = check_updates(updates)?;
+        let mut column = Vec::with_capacity(updates.len());
+        Ok((0..dim)
+            .map(|i| {
+                column.clear();
+                column.extend(updates.iter().map(|u| u[i]));
+                column.sort_by(|a, b| a.total_cmp(b));
+                let mid = column.len() / 2;
+                if column.len() % 2 == 0 {
+                    (column[mid - 1] + column[mid]) / 2.0
+                } else {
+                    column[mid]
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Panicking;
+
+    impl ShadowAlgorithm<[Vec<f32>], Vec<f32>> for Panicking {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        fn run(&self, _updates: &[Vec<f32>]) -> Result<Vec<f32>> {
+            panic!("boom")
+        }
+    }
+
+    #[test]
+    fn test_shadow_does_not_affect_live_result() {
+        let mut runner: ShadowRunner<[Vec<f32>], Vec<f32>> =
+            ShadowRunner::new(ShadowConfig::default(), Arc::new(MeanAggregator));
+        runner.add_shadow(Arc::new(TrimmedMeanAggregator { trim_ratio: 0.25 }));
+        runner.add_shadow(Arc::new(Panicking));
+
+        // 一个拜占庭节点发送离群更新
+        let updates = vec![vec![1.0, 1.0], vec![1.0, 1.0], vec![1.0, 1.0], vec![100.0, -100.0]];
+        let committed = runner.run(&updates).unwrap();
+        assert_eq!(committed, MeanAggregator.run(&updates).unwrap());
+
+        let summaries = runner.summaries();
+        let panicking = summaries.iter().find(|s| s.shadow == "panicking").unwrap();
+        assert_eq!(panicking.errors, 1);
+        let trimmed = summaries.iter().find(|s| s.shadow == "trimmed_mean").unwrap();
+        assert!(trimmed.mean_l2_distance > 1.0);
+        assert!(!trimmed.ready_for_promotion(1, 0.99));
+
+        let path = std::env::temp_dir().join(format!("williw-shadow-{}.ndjson", std::process::id()));
+        assert_eq!(runner.export_records(&path).unwrap(), 2);
+        assert!(runner.records().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,351 @@
+//! 投机解码
+//!
+//! 小的草稿模型先自回归地提出 k 个 token，目标模型一次前向同时给出这 k+1 个位置的分布并逐个验证：
+//! 以 min(1, p/q) 的概率接受草稿 token，首个被拒绝的位置从 max(0, p - q) 归一化后的残差分布重新采样，
+//! 全部接受时再从目标分布多采样一个。输出分布与只用目标模型逐个采样一致，
+//! 加速取决于接受率以及两个模型单次前向的耗时比。
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 逐 token 生成的语言模型
+pub trait TokenModel: Send + Sync {
+    fn name(&self) -> String;
+
+    /// 下一个 token 的概率分布（长度为词表大小）
+    fn next_token_probs(&self, context: &[u32]) -> Result<Vec<f32>>;
+
+    /// 在 `context` 后依次追加 `proposal` 中的 token，返回每个位置下一个 token 的分布（共 `proposal.len() + 1` 个）。
+    /// 默认逐个调用 `next_token_probs`；能一次前向处理多个位置的模型应覆盖此方法，否则没有加速
+    fn score_continuation(&self, context: &[u32], proposal: &[u32]) -> Result<Vec<Vec<f32>>> {
+        let mut context = context.to_vec();
+        let mut dists = Vec::with_capacity(proposal.len() + 1);
+        for &token in proposal {
+            dists.push(self.next_token_probs(&context)?);
+            context.push(token);
+        }
+        dists.push(self.next_token_probs(&context)?);
+        Ok(dists)
+    }
+}
+
+/// 一组目标模型与草稿模型的搭配
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeculativePair {
+    /// 目标模型（名称或路径）
+    pub target: String,
+    /// 草稿模型（名称或路径），需与目标模型共用词表
+    pub draft: String,
+    /// 每轮提出的草稿 token 数
+    pub draft_tokens: usize,
+    /// 贪心验证：只接受与目标模型 argmax 一致的草稿 token
+    pub greedy: bool,
+    pub enabled: bool,
+}
+
+impl Default for SpeculativePair {
+    fn default() -> Self {
+        Self {
+            target: String::new(),
+            draft: String::new(),
+            draft_tokens: 4,
+            greedy: false,
+            enabled: true,
+        }
+    }
+}
+
+/// 投机解码配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeculativeConfig {
+    pub pairs: Vec<SpeculativePair>,
+}
+
+impl SpeculativeConfig {
+    /// 目标模型启用的草稿搭配
+    pub fn pair_for(&self, target: &str) -> Option<&SpeculativePair> {
+        self.pairs.iter().find(|pair| pair.enabled && pair.target == target)
+    }
+}
+
+/// 投机解码统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpeculativeStats {
+    pub target_passes: u64,
+    pub draft_passes: u64,
+    pub proposed_tokens: u64,
+    pub accepted_tokens: u64,
+    pub generated_tokens: u64,
+    pub target_time_ms: f64,
+    pub draft_time_ms: f64,
+}
+
+impl SpeculativeStats {
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.proposed_tokens == 0 {
+            0.0
+        } else {
+            self.accepted_tokens as f64 / self.proposed_tokens as f64
+        }
+    }
+
+    pub fn tokens_per_target_pass(&self) -> f64 {
+        if self.target_passes == 0 {
+            0.0
+        } else {
+            self.generated_tokens as f64 / self.target_passes as f64
+        }
+    }
+
+    /// 相对逐 token 解码的实测加速比：基线按每个 token 一次目标前向（取实测的平均单次耗时）估算，
+    /// 假设验证 k+1 个位置与生成一个 token 的前向耗时相近（访存受限时成立）
+    pub fn speedup(&self) -> f64 {
+        let elapsed = self.target_time_ms + self.draft_time_ms;
+        if self.target_passes == 0 || elapsed <= 0.0 {
+            return 1.0;
+        }
+        let baseline = self.generated_tokens as f64 * self.target_time_ms / self.target_passes as f64;
+        baseline / elapsed
+    }
+}
+
+fn prob(dist: &[f32], token: u32) -> f32 {
+    dist.get(token as usize).copied().unwrap_or(0.0).max(0.0)
+}
+
+fn argmax(dist: &[f32]) -> u32 {
+    dist.iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i as u32)
+        .unwrap_or(0)
+}
+
+/// 按（未必归一化的）权重采样
+fn sample<R: Rng + ?Sized>(weights: &[f32], rng: &mut R) -> u32 {
+    let total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+    if total <= 0.0 {
+        return argmax(weights);
+    }
+    let mut target = rng.random::<f32>() * total;
+    let mut last = 0;
+    for (i, w) in weights.iter().enumerate() {
+        if *w <= 0.0 {
+            continue;
+        }
+        last = i;
+        target -= w;
+        if target < 0.0 {
+            return i as u32;
+        }
+    }
+    last as u32
+}
+
+/// 草稿模型 + 目标模型的投机解码器
+pub struct SpeculativeDecoder<T: TokenModel, D: TokenModel> {
+    target: T,
+    draft: D,
+    pair: SpeculativePair,
+    stats: Mutex<SpeculativeStats>,
+}
+
+impl<T: TokenModel, D: TokenModel> SpeculativeDecoder<T, D> {
+    pub fn new(target: T, draft: D, pair: SpeculativePair) -> Self {
+        Self {
+            target,
+            draft,
+            pair,
+            stats: Mutex::new(SpeculativeStats::default()),
+        }
+    }
+
+    pub fn pair(&self) -> &SpeculativePair {
+        &self.pair
+    }
+
+    /// 累计统计
+    pub fn stats(&self) -> SpeculativeStats {
+        self.stats.lock().clone()
+    }
+
+    /// 生成最多 `max_new_tokens` 个 token，遇到 `eos` 停止（`eos` 包含在输出中）
+    pub fn generate<R: Rng + ?Sized>(
+        &self,
+        prompt: &[u32],
+        max_new_tokens: usize,
+        eos: Option<u32>,
+        rng: &mut R,
+    ) -> Result<Vec<u32>> {
+        let greedy = self.pair.greedy;
+        let mut context = prompt.to_vec();
+        let mut generated = Vec::new();
+        let mut round = SpeculativeStats::default();
+
+        'decode: while generated.len() < max_new_tokens {
+            let k = self.pair.draft_tokens.max(1).min(max_new_tokens - generated.len());
+
+            // 草稿模型自回归提出 k 个 token
+            let started = Instant::now();
+            let mut proposal = Vec::with_capacity(k);
+            let mut draft_dists = Vec::with_capacity(k);
+            let mut draft_context = context.clone();
+            for _ in 0..k {
+                let q = self.draft.next_token_probs(&draft_context)?;
+                let token = if greedy { argmax(&q) } else { sample(&q, rng) };
+                proposal.push(token);
+                draft_dists.push(q);
+                draft_context.push(token);
+                round.draft_passes += 1;
+                if Some(token) == eos {
+                    break;
+                }
+            }
+            round.draft_time_ms += started.elapsed().as_secs_f64() * 1000.0;
+
+            // 目标模型一次前向验证
+            let started = Instant::now();
+            let target_dists = self.target.score_continuation(&context, &proposal)?;
+            round.target_time_ms += started.elapsed().as_secs_f64() * 1000.0;
+            round.target_passes += 1;
+            if target_dists.len() != proposal.len() + 1 {
+                return Err(anyhow!(
+                    "目标模型 {} 返回 {} 个分布，期望 {}",
+                    self.target.name(),
+                    target_dists.len(),
+                    proposal.len() + 1
+                ));
+            }
+
+            let mut accepted = 0;
+            let mut correction = None;
+            for (i, &token) in proposal.iter().enumerate() {
+                let p = &target_dists[i];
+                let ok = if greedy {
+                    argmax(p) == token
+                } else {
+                    let q = prob(&draft_dists[i], token);
+                    q > 0.0 && rng.random::<f32>() < (prob(p, token) / q).min(1.0)
+                };
+                if ok {
+                    accepted += 1;
+                    continue;
+                }
+                correction = Some(if greedy {
+                    argmax(p)
+                } else {
+                    let residual: Vec<f32> = p
+                        .iter()
+                        .enumerate()
+                        .map(|(j, pj)| (pj - draft_dists[i].get(j).copied().unwrap_or(0.0)).max(0.0))
+                        .collect();
+                    if residual.iter().sum::<f32>() > 0.0 {
+                        sample(&residual, rng)
+                    } else {
+                        sample(p, rng)
+                    }
+                });
+                break;
+            }
+            let next = correction.unwrap_or_else(|| {
+                let p = &target_dists[proposal.len()];
+                if greedy {
+                    argmax(p)
+                } else {
+                    sample(p, rng)
+                }
+            });
+            round.proposed_tokens += proposal.len() as u64;
+            round.accepted_tokens += accepted as u64;
+
+            for token in proposal[..accepted].iter().copied().chain(std::iter::once(next)) {
+                if generated.len() >= max_new_tokens {
+                    break 'decode;
+                }
+                generated.push(token);
+                context.push(token);
+                if Some(token) == eos {
+                    break 'decode;
+                }
+            }
+        }
+
+        round.generated_tokens = generated.len() as u64;
+        let mut stats = self.stats.lock();
+        stats.target_passes += round.target_passes;
+        stats.draft_passes += round.draft_passes;
+        stats.proposed_tokens += round.proposed_tokens;
+        stats.accepted_tokens += round.accepted_tokens;
+        stats.generated_tokens += round.generated_tokens;
+        stats.target_time_ms += round.target_time_ms;
+        stats.draft_time_ms += round.draft_time_ms;
+        Ok(generated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    struct FnModel(fn(&[u32]) -> Vec<f32>);
+
+    impl TokenModel for FnModel {
+        fn name(&self) -> String {
+            "fn".to_string()
+        }
+
+        fn next_token_probs(&self, context: &[u32]) -> Result<Vec<f32>> {
+            Ok((self.0)(context))
+        }
+    }
+
+    fn counting(context: &[u32]) -> Vec<f32> {
+        let mut dist = vec![0.05; 8];
+        dist[(context.last().copied().unwrap_or(0) as usize + 1) % 8] = 0.65;
+        dist
+    }
+
+    #[test]
+    fn test_greedy_matches_target_with_fewer_passes() {
+        let pair = SpeculativePair {
+            draft_tokens: 3,
+            greedy: true,
+            ..Default::default()
+        };
+        let decoder = SpeculativeDecoder::new(FnModel(counting), FnModel(counting), pair);
+        let mut rng = StdRng::seed_from_u64(1);
+        let tokens = decoder.generate(&[0], 8, None, &mut rng).unwrap();
+        assert_eq!(tokens, vec![1, 2, 3, 4, 5, 6, 7, 0]);
+
+        // 草稿全部被接受，每轮验证产出 k+1 个 token
+        let stats = decoder.stats();
+        assert_eq!(stats.target_passes, 2);
+        assert_eq!(stats.acceptance_rate(), 1.0);
+        assert_eq!(stats.tokens_per_target_pass(), 4.0);
+    }
+
+    #[test]
+    fn test_sampling_preserves_target_distribution() {
+        let target = FnModel(|_| vec![0.6, 0.3, 0.1]);
+        let draft = FnModel(|_| vec![1.0 / 3.0; 3]);
+        let decoder = SpeculativeDecoder::new(target, draft, SpeculativePair::default());
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts = [0usize; 3];
+        let runs = 20_000;
+        for _ in 0..runs {
+            counts[decoder.generate(&[], 1, None, &mut rng).unwrap()[0] as usize] += 1;
+        }
+        for (count, expected) in counts.iter().zip([0.6, 0.3, 0.1]) {
+            assert!((*count as f64 / runs as f64 - expected).abs() < 0.02, "{counts:?}");
+        }
+        // 接受率 = Σ min(p, q)
+        assert!((decoder.stats().acceptance_rate() - (1.0 / 3.0 + 1.0 / 3.0 + 0.1)).abs() < 0.02);
+    }
+}
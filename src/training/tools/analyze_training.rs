@@ -4,12 +4,17 @@
 //! ```bash
 //! cargo run --bin analyze_training -- --input test_output
 //! ```
+//!
+//! 支持 `<node>_stats.json` 快照文件和 `<node>_stats.ndjson` 流式历史文件，
+//! NDJSON 文件逐行读取，内存占用与历史长度无关。
 
 use anyhow::Result;
 use clap::Parser;
 use serde_json::Value;
 use std::fs;
+use std::io::{BufWriter, Write};
 use std::path::PathBuf;
+use williw::stats::StatsStreamReader;
 
 #[derive(Parser, Debug)]
 #[command(name = "analyze_training")]
@@ -78,44 +83,60 @@ fn main() -> Result<()> {
         let entry = entry?;
         let path = entry.path();
         
-        if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-            if file_name.ends_with("_stats.json") {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(json) = serde_json::from_str::<Value>(&content) {
-                        let node_id = file_name.replace("_stats.json", "");
-                        
-                        let tick_count = json["tick_count"].as_u64().unwrap_or(0);
-                        let sparse_received = json["sparse_updates_received"].as_u64().unwrap_or(0);
-                        let dense_received = json["dense_snapshots_received"].as_u64().unwrap_or(0);
-                        let sparse_sent = json["sparse_updates_sent"].as_u64().unwrap_or(0);
-                        let dense_sent = json["dense_snapshots_sent"].as_u64().unwrap_or(0);
-                        let connected_peers = json["connected_peers"].as_u64().unwrap_or(0) as usize;
-                        let model_version = json["model_version"].as_u64().unwrap_or(0);
-                        let model_hash = json["model_hash"].as_str().unwrap_or("").to_string();
-                        let elapsed = json["start_time_secs"].as_u64().unwrap_or(0);
-
-                        total_interactions += sparse_received + dense_received + sparse_sent + dense_sent;
-                        total_sparse_updates += sparse_received + sparse_sent;
-                        total_dense_snapshots += dense_received + dense_sent;
-                        total_ticks += tick_count;
-                        total_peers += connected_peers;
-                        max_duration = max_duration.max(elapsed);
-
-                        nodes.push(NodeReport {
-                            node_id,
-                            tick_count,
-                            sparse_updates_received: sparse_received,
-                            dense_snapshots_received: dense_received,
-                            sparse_updates_sent: sparse_sent,
-                            dense_snapshots_sent: dense_sent,
-                            connected_peers,
-                            model_version,
-                            model_hash,
-                        });
-                    }
-                }
-            }
-        }
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        // 单个 JSON 快照或 NDJSON 流（流式读取，仅保留最后一条记录）
+        let (node_id, json) = if let Some(node_id) = file_name.strip_suffix("_stats.json") {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(json) = serde_json::from_str::<Value>(&content) else {
+                continue;
+            };
+            (node_id.to_string(), json)
+        } else if let Some(node_id) = file_name.strip_suffix("_stats.ndjson") {
+            let mut reader = StatsStreamReader::open(&path)?;
+            let mut latest = None;
+            let records = reader.for_each(|record: Value| latest = Some(record))?;
+            let Some(json) = latest else {
+                continue;
+            };
+            println!("  {}: 读取 {} 条历史记录", node_id, records);
+            (node_id.to_string(), json)
+        } else {
+            continue;
+        };
+
+        let tick_count = json["tick_count"].as_u64().unwrap_or(0);
+        let sparse_received = json["sparse_updates_received"].as_u64().unwrap_or(0);
+        let dense_received = json["dense_snapshots_received"].as_u64().unwrap_or(0);
+        let sparse_sent = json["sparse_updates_sent"].as_u64().unwrap_or(0);
+        let dense_sent = json["dense_snapshots_sent"].as_u64().unwrap_or(0);
+        let connected_peers = json["connected_peers"].as_u64().unwrap_or(0) as usize;
+        let model_version = json["model_version"].as_u64().unwrap_or(0);
+        let model_hash = json["model_hash"].as_str().unwrap_or("").to_string();
+        let elapsed = json["start_time_secs"].as_u64().unwrap_or(0);
+
+        total_interactions += sparse_received + dense_received + sparse_sent + dense_sent;
+        total_sparse_updates += sparse_received + sparse_sent;
+        total_dense_snapshots += dense_received + dense_sent;
+        total_ticks += tick_count;
+        total_peers += connected_peers;
+        max_duration = max_duration.max(elapsed);
+
+        nodes.push(NodeReport {
+            node_id,
+            tick_count,
+            sparse_updates_received: sparse_received,
+            dense_snapshots_received: dense_received,
+            sparse_updates_sent: sparse_sent,
+            dense_snapshots_sent: dense_sent,
+            connected_peers,
+            model_version,
+            model_hash,
+        });
     }
 
     let node_count = nodes.len();
@@ -138,8 +159,9 @@ fn main() -> Result<()> {
     };
 
     // 输出报告
-    let report_json = serde_json::to_string_pretty(&report)?;
-    fs::write(&args.output, report_json)?;
+    let mut writer = BufWriter::new(fs::File::create(&args.output)?);
+    serde_json::to_writer_pretty(&mut writer, &report)?;
+    writer.flush()?;
 
     println!("\n=== 训练分析报告 ===");
     println!("节点数量: {}", report.total_nodes);
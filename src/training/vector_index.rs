@@ -0,0 +1,202 @@
+//! 向量运算与内存向量索引
+//!
+//! 边缘节点做检索时向量规模不大，这里用扁平存储 + 暴力余弦相似度搜索：
+//! 写入时归一化，搜索只需点积，并用小顶堆只保留前 k 个结果。
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+pub fn norm(v: &[f32]) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// 原地 L2 归一化，零向量保持不变
+pub fn l2_normalize(v: &mut [f32]) {
+    let n = norm(v);
+    if n > 0.0 {
+        for x in v.iter_mut() {
+            *x /= n;
+        }
+    }
+}
+
+/// 余弦相似度，任一向量为零向量时返回 0
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let denom = norm(a) * norm(b);
+    if denom == 0.0 {
+        0.0
+    } else {
+        dot(a, b) / denom
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub score: f32,
+}
+
+/// 堆中按分数比较，分数相同时按 id 保证结果稳定
+struct Candidate<'a> {
+    score: f32,
+    id: &'a str,
+}
+
+impl PartialEq for Candidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate<'_> {}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate<'_> {
+    // 反序：BinaryHeap 堆顶是当前最差的候选
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.score.total_cmp(&self.score).then_with(|| self.id.cmp(other.id))
+    }
+}
+
+/// 内存向量索引（余弦相似度）
+#[derive(Debug, Clone)]
+pub struct VectorIndex {
+    dim: usize,
+    ids: Vec<String>,
+    /// 归一化后的向量，按行扁平存储
+    vectors: Vec<f32>,
+    positions: HashMap<String, usize>,
+}
+
+impl VectorIndex {
+    pub fn new(dim: usize) -> Self {
+        Self {
+            dim,
+            ids: Vec::new(),
+            vectors: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    fn check_dim(&self, v: &[f32]) -> Result<()> {
+        if v.len() != self.dim {
+            return Err(anyhow!("向量维度不匹配: 期望 {}, 实际 {}", self.dim, v.len()));
+        }
+        Ok(())
+    }
+
+    /// 插入向量，id 已存在时覆盖
+    pub fn insert(&mut self, id: impl Into<String>, vector: &[f32]) -> Result<()> {
+        self.check_dim(vector)?;
+        let mut normalized = vector.to_vec();
+        l2_normalize(&mut normalized);
+        let id = id.into();
+        match self.positions.get(&id) {
+            Some(&pos) => self.vectors[pos * self.dim..(pos + 1) * self.dim].copy_from_slice(&normalized),
+            None => {
+                self.positions.insert(id.clone(), self.ids.len());
+                self.ids.push(id);
+                self.vectors.extend_from_slice(&normalized);
+            }
+        }
+        Ok(())
+    }
+
+    /// 删除向量（与最后一行交换后截断）
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(pos) = self.positions.remove(id) else {
+            return false;
+        };
+        let last = self.ids.len() - 1;
+        if pos != last {
+            self.ids.swap(pos, last);
+            let (head, tail) = self.vectors.split_at_mut(last * self.dim);
+            head[pos * self.dim..(pos + 1) * self.dim].copy_from_slice(&tail[..self.dim]);
+            self.positions.insert(self.ids[pos].clone(), pos);
+        }
+        self.ids.pop();
+        self.vectors.truncate(last * self.dim);
+        true
+    }
+
+    /// 与 `query` 余弦相似度最高的 `k` 个向量，按分数降序
+    pub fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchHit>> {
+        self.check_dim(query)?;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let mut query = query.to_vec();
+        l2_normalize(&mut query);
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        for (row, id) in self.vectors.chunks_exact(self.dim.max(1)).zip(&self.ids) {
+            heap.push(Candidate {
+                score: dot(&query, row),
+                id,
+            });
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+        Ok(heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|c| SearchHit {
+                id: c.id.to_string(),
+                score: c.score,
+            })
+            .collect())
+    }
+
+    pub fn search_batch(&self, queries: &[Vec<f32>], k: usize) -> Result<Vec<Vec<SearchHit>>> {
+        queries.iter().map(|q| self.search(q, k)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_k_search_and_remove() {
+        let mut index = VectorIndex::new(3);
+        index.insert("x", &[1.0, 0.0, 0.0]).unwrap();
+        index.insert("y", &[0.0, 2.0, 0.0]).unwrap();
+        index.insert("xy", &[1.0, 1.0, 0.0]).unwrap();
+        index.insert("z", &[0.0, 0.0, 5.0]).unwrap();
+        assert!(index.insert("bad", &[1.0]).is_err());
+
+        let hits = index.search(&[3.0, 0.5, 0.0], 2).unwrap();
+        assert_eq!(hits.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(), vec!["x", "xy"]);
+        assert!((hits[0].score - cosine_similarity(&[3.0, 0.5, 0.0], &[1.0, 0.0, 0.0])).abs() < 1e-6);
+
+        assert!(index.remove("x"));
+        assert!(!index.remove("x"));
+        index.insert("z", &[0.9, 0.1, 0.0]).unwrap();
+        let hits = index.search(&[1.0, 0.0, 0.0], 1).unwrap();
+        assert_eq!((index.len(), hits[0].id.as_str()), (3, "z"));
+    }
+}
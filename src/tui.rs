@@ -0,0 +1,317 @@
+//! `williw top` 终端仪表盘
+//!
+//! 以独立进程只读打开节点的统计数据库（节点需以 `--stats-db` 启动），
+//! 定期刷新邻居、带宽、loss 曲线、本机 GPU/电池状态和最近的链上提交记录。
+//! 不与节点共享进程，避免节点日志输出打乱终端画面。
+
+use crate::args::TopArgs;
+use crate::device::{DeviceDetector, DeviceManager, GpuUsageInfo};
+use crate::stats::{PeerSample, StatsStore, SubmissionRecord, TickMetrics};
+use anyhow::{anyhow, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols;
+use ratatui::text::Line;
+use ratatui::widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, List, ListItem, Paragraph, Row, Sparkline, Table};
+use ratatui::Frame;
+use std::time::{Duration, Instant};
+
+/// loss 曲线和带宽图使用的最近 tick 数
+const HISTORY_TICKS: usize = 300;
+/// 事件列表展示的最近提交数
+const RECENT_EVENTS: usize = 20;
+/// GPU/电池状态的刷新间隔（检测较慢，不必每帧刷新）
+const DEVICE_REFRESH: Duration = Duration::from_secs(5);
+
+/// 一次刷新读取到的数据
+#[derive(Default)]
+struct Snapshot {
+    ticks: Vec<TickMetrics>,
+    peers_recorded_at: Option<i64>,
+    peers: Vec<PeerSample>,
+    pending: Vec<SubmissionRecord>,
+    submitted: Vec<SubmissionRecord>,
+    battery_level: Option<f32>,
+    is_charging: Option<bool>,
+    gpus: Vec<GpuUsageInfo>,
+}
+
+impl Snapshot {
+    fn load_stats(&mut self, store: &StatsStore) -> Result<()> {
+        self.ticks = store.recent_ticks(HISTORY_TICKS)?;
+        let (recorded_at, peers) = store.latest_peers()?;
+        self.peers_recorded_at = recorded_at;
+        self.peers = peers;
+        self.pending = store.pending_submissions()?;
+        self.submitted = store.recent_submitted(RECENT_EVENTS)?;
+        Ok(())
+    }
+
+    fn load_device(&mut self, devices: &DeviceManager) {
+        devices.refresh();
+        let capabilities = devices.get();
+        self.battery_level = capabilities.battery_level;
+        self.is_charging = capabilities.is_charging;
+        self.gpus = DeviceDetector::detect_gpu_usage();
+    }
+
+    /// 相邻 tick 之间的收发速率（字节/秒）
+    fn bandwidth_rates(&self) -> (Vec<u64>, Vec<u64>) {
+        self.ticks
+            .windows(2)
+            .map(|pair| {
+                let elapsed = (pair[1].timestamp - pair[0].timestamp).max(1) as u64;
+                (
+                    pair[1].bytes_sent.saturating_sub(pair[0].bytes_sent) / elapsed,
+                    pair[1].bytes_received.saturating_sub(pair[0].bytes_received) / elapsed,
+                )
+            })
+            .unzip()
+    }
+}
+
+pub fn run(args: TopArgs) -> Result<()> {
+    if !args.db.exists() {
+        return Err(anyhow!(
+            "统计数据库不存在: {}（节点需以 --stats-db 启动）",
+            args.db.display()
+        ));
+    }
+    let store = StatsStore::open(&args.db)?;
+    let devices = DeviceManager::new();
+    let refresh = Duration::from_millis(args.refresh_ms.max(100));
+
+    let mut snapshot = Snapshot::default();
+    snapshot.load_device(&devices);
+    let mut device_refreshed = Instant::now();
+
+    let mut terminal = ratatui::init();
+    let result = (|| -> Result<()> {
+        loop {
+            snapshot.load_stats(&store)?;
+            if device_refreshed.elapsed() >= DEVICE_REFRESH {
+                snapshot.load_device(&devices);
+                device_refreshed = Instant::now();
+            }
+            terminal.draw(|frame| draw(frame, &snapshot))?;
+
+            if event::poll(refresh)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    result
+}
+
+fn draw(frame: &mut Frame, snapshot: &Snapshot) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Percentage(45),
+            Constraint::Min(8),
+        ])
+        .split(frame.area());
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[1]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[2]);
+
+    draw_summary(frame, rows[0], snapshot);
+    draw_loss(frame, top[0], snapshot);
+    draw_bandwidth_and_device(frame, top[1], snapshot);
+    draw_peers(frame, bottom[0], snapshot);
+    draw_events(frame, bottom[1], snapshot);
+}
+
+fn draw_summary(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let text = match snapshot.ticks.last() {
+        Some(last) => format!(
+            "tick {}  loss {:.4}  准确率 {:.2}%  邻居 {}  样本 {}  待提交 {}    q/Esc 退出",
+            last.tick,
+            last.loss,
+            last.accuracy * 100.0,
+            last.connected_peers,
+            last.samples_processed,
+            snapshot.pending.len()
+        ),
+        None => "暂无 tick 数据，等待节点写入统计数据库…    q/Esc 退出".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("williw top")),
+        area,
+    );
+}
+
+fn draw_loss(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let points: Vec<(f64, f64)> = snapshot.ticks.iter().map(|t| (t.tick as f64, t.loss)).collect();
+    let (min_x, max_x) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (first.0, last.0.max(first.0 + 1.0)),
+        _ => (0.0, 1.0),
+    };
+    let max_y = points.iter().map(|p| p.1).fold(0.0_f64, f64::max).max(f64::EPSILON);
+
+    let dataset = Dataset::default()
+        .name("loss")
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Cyan))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(Block::default().borders(Borders::ALL).title("Loss 曲线"))
+        .x_axis(
+            Axis::default()
+                .title("tick")
+                .bounds([min_x, max_x])
+                .labels([format!("{:.0}", min_x), format!("{:.0}", max_x)]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, max_y])
+                .labels(["0".to_string(), format!("{:.3}", max_y)]),
+        );
+    frame.render_widget(chart, area);
+}
+
+fn draw_bandwidth_and_device(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+            Constraint::Percentage(40),
+        ])
+        .split(area);
+
+    let (sent, received) = snapshot.bandwidth_rates();
+    for (data, title, color, part) in [
+        (&sent, "上行", Color::Green, parts[0]),
+        (&received, "下行", Color::Yellow, parts[1]),
+    ] {
+        let current = data.last().copied().unwrap_or(0);
+        // 只展示放得下的最近部分
+        let width = part.width.saturating_sub(2) as usize;
+        let visible = &data[data.len().saturating_sub(width)..];
+        frame.render_widget(
+            Sparkline::default()
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("{} {:.1} KB/s", title, current as f64 / 1024.0)),
+                )
+                .data(visible)
+                .style(Style::default().fg(color)),
+            part,
+        );
+    }
+
+    let mut lines = vec![Line::from(match (snapshot.battery_level, snapshot.is_charging) {
+        (Some(level), Some(true)) => format!("电池: {:.0}%（充电中）", level),
+        (Some(level), _) => format!("电池: {:.0}%", level),
+        (None, _) => "电池: 无".to_string(),
+    })];
+    if snapshot.gpus.is_empty() {
+        lines.push(Line::from("GPU: 未检测到"));
+    }
+    for gpu in &snapshot.gpus {
+        let memory = match (gpu.memory_used_mb, gpu.memory_total_mb) {
+            (Some(used), Some(total)) => format!("  显存 {}/{}MB", used, total),
+            _ => String::new(),
+        };
+        let temperature = gpu
+            .temperature
+            .map(|t| format!("  {:.0}°C", t))
+            .unwrap_or_default();
+        lines.push(Line::from(format!(
+            "{}: {:.0}%{}{}",
+            gpu.gpu_name, gpu.usage_percent, memory, temperature
+        )));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("设备")),
+        parts[2],
+    );
+}
+
+fn draw_peers(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let title = match snapshot.peers_recorded_at {
+        Some(ts) => format!("邻居（{} 个，更新于 {}）", snapshot.peers.len(), format_time(ts)),
+        None => "邻居（暂无快照）".to_string(),
+    };
+    let rows = snapshot.peers.iter().map(|peer| {
+        Row::new(vec![
+            short_id(&peer.peer_id),
+            peer.role.clone(),
+            format!("{:.3}", peer.similarity),
+            peer.trust.clone().unwrap_or_else(|| "-".to_string()),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(18),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Min(8),
+        ],
+    )
+    .header(Row::new(vec!["节点", "角色", "相似度", "信任"]).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(table, area);
+}
+
+fn draw_events(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let pending = snapshot.pending.iter().map(|record| {
+        let status = match &record.last_error {
+            Some(error) => format!("重试 {} 次: {}", record.attempts, error),
+            None => "排队中".to_string(),
+        };
+        ListItem::new(format!(
+            "{} {} {} {}",
+            format_time(record.queued_at),
+            record.kind,
+            short_id(&record.id),
+            status
+        ))
+        .style(Style::default().fg(Color::Yellow))
+    });
+    let submitted = snapshot.submitted.iter().map(|record| {
+        ListItem::new(format!(
+            "{} {} {} 已上链 {}",
+            format_time(record.submitted_at.unwrap_or(record.queued_at)),
+            record.kind,
+            short_id(&record.id),
+            record.signature.as_deref().map(short_id).unwrap_or_default()
+        ))
+        .style(Style::default().fg(Color::Green))
+    });
+    let items: Vec<ListItem> = pending.chain(submitted).collect();
+    frame.render_widget(
+        List::new(items).block(Block::default().borders(Borders::ALL).title("奖励/链上提交")),
+        area,
+    );
+}
+
+fn short_id(id: &str) -> String {
+    if id.chars().count() <= 16 {
+        id.to_string()
+    } else {
+        format!("{}…", id.chars().take(15).collect::<String>())
+    }
+}
+
+fn format_time(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
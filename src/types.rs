@@ -195,4 +195,53 @@ pub enum GgbMessage {
         position: GeoPoint,
         sender: String,
     },
+    /// 应用层保活探测
+    Ping {
+        sender: String,
+        target: String,
+        nonce: u64,
+    },
+    /// 保活探测响应
+    Pong {
+        sender: String,
+        target: String,
+        nonce: u64,
+    },
+    /// 协调者下发的任务分配清单
+    TaskAssignment {
+        sender: String,
+        manifest: crate::task_manifest::SignedTaskManifest,
+    },
+    /// 密钥轮换公告（`sender` 为旧节点 ID）
+    KeyRotation {
+        sender: String,
+        rotation: crate::crypto::KeyRotation,
+    },
+    /// 节点能力记录（含构建信息）
+    Capability {
+        sender: String,
+        record: crate::build_info::SignedCapabilityRecord,
+    },
+    /// 计算分数共识轮的消息
+    ScoreConsensus {
+        sender: String,
+        message: crate::consensus::bft::SignedBftMessage,
+    },
+    /// 节点出示的链上质押证明，用于解除准入观察期
+    StakeAttestation {
+        sender: String,
+        attestation: crate::comms::StakeAttestation,
+    },
+    /// 远程签名请求或审批结果（如手机钱包审批交易）
+    RemoteSigning {
+        sender: String,
+        target: String,
+        message: crate::comms::SigningMessage,
+    },
+    /// 任务完成后的输出摘要，供协调者做冗余执行比较
+    TaskResult {
+        sender: String,
+        task_id: String,
+        output: Vec<f32>,
+    },
 }
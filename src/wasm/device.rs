@@ -0,0 +1,281 @@
+//! 浏览器设备能力检测
+//!
+//! 通过 navigator 上的标准 API 读取真实设备能力，页面和 Web Worker 中都可以使用：
+//! - `hardwareConcurrency`：CPU 核心数
+//! - `deviceMemory`：内存（Chromium 系，按 0.25/0.5/1/2/4/8 GB 取整）
+//! - `storage.estimate()`：可用存储配额
+//! - `getBattery()`：电量和充电状态（仅部分浏览器的页面上下文）
+//! - `connection`：网络类型（Network Information API）
+//! - `gpu`：是否支持 WebGPU
+//!
+//! 每项 API 都可能不存在或被权限拒绝，缺失时退回保守的默认值，
+//! 实际检测到的字段记录在 [`BrowserCapabilities::detected`] 中。
+
+use crate::device::{DeviceCapabilities, DeviceType, GpuComputeApi, NetworkType};
+use js_sys::{Function, Promise, Reflect};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// `deviceMemory` 不可用时假定的内存（MB）
+pub const FALLBACK_MEMORY_MB: u64 = 2048;
+/// `hardwareConcurrency` 不可用时假定的核心数
+pub const FALLBACK_CPU_CORES: u32 = 2;
+
+/// 浏览器中检测到的设备能力
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserCapabilities {
+    #[serde(flatten)]
+    pub capabilities: DeviceCapabilities,
+    /// 存储配额（MB）
+    pub storage_quota_mb: Option<u64>,
+    /// 已用存储（MB）
+    pub storage_usage_mb: Option<u64>,
+    /// 实际由浏览器 API 检测到的字段，其余字段为默认值
+    pub detected: Vec<String>,
+}
+
+/// JS 覆盖钩子返回的部分能力，未给出的字段保持检测值
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CapabilityOverride {
+    pub max_memory_mb: Option<u64>,
+    pub cpu_cores: Option<u32>,
+    pub has_gpu: Option<bool>,
+    pub network_type: Option<NetworkType>,
+    pub battery_level: Option<f32>,
+    pub is_charging: Option<bool>,
+    pub device_type: Option<DeviceType>,
+    pub storage_quota_mb: Option<u64>,
+    pub storage_usage_mb: Option<u64>,
+}
+
+impl CapabilityOverride {
+    pub fn apply(self, target: &mut BrowserCapabilities) {
+        let caps = &mut target.capabilities;
+        if let Some(value) = self.max_memory_mb {
+            caps.max_memory_mb = value;
+        }
+        if let Some(value) = self.cpu_cores {
+            caps.cpu_cores = value.max(1);
+        }
+        if let Some(value) = self.has_gpu {
+            caps.has_gpu = value;
+        }
+        if let Some(value) = self.network_type {
+            caps.network_type = value;
+        }
+        if let Some(value) = self.battery_level {
+            caps.battery_level = Some(value.clamp(0.0, 1.0));
+        }
+        if let Some(value) = self.is_charging {
+            caps.is_charging = Some(value);
+        }
+        if let Some(value) = self.device_type {
+            caps.device_type = value;
+        }
+        if self.storage_quota_mb.is_some() {
+            target.storage_quota_mb = self.storage_quota_mb;
+        }
+        if self.storage_usage_mb.is_some() {
+            target.storage_usage_mb = self.storage_usage_mb;
+        }
+    }
+}
+
+/// 由 Network Information API 推断网络类型
+///
+/// 浏览器不区分 4G 和 5G，蜂窝网络一律按 4G 处理；桌面浏览器通常不提供 `type`，
+/// 此时按有线网络处理，避免大文件下载被“仅 Wi-Fi”规则一直挡住。
+pub fn network_type_from(connection_type: Option<&str>, device_type: DeviceType) -> NetworkType {
+    match connection_type {
+        Some("wifi") | Some("ethernet") => NetworkType::WiFi,
+        Some("cellular") => NetworkType::Cellular4G,
+        Some(_) => NetworkType::Unknown,
+        None if device_type == DeviceType::Desktop => NetworkType::WiFi,
+        None => NetworkType::Unknown,
+    }
+}
+
+/// 由 User-Agent 推断设备类型（iPadOS 伪装成 Mac，需要结合触控点数判断）
+pub fn device_type_from(user_agent: &str, max_touch_points: i32) -> DeviceType {
+    if user_agent.contains("iPad") || (user_agent.contains("Macintosh") && max_touch_points > 1) {
+        DeviceType::Tablet
+    } else if user_agent.contains("iPhone") || user_agent.contains("Mobi") {
+        DeviceType::Phone
+    } else if user_agent.contains("Android") {
+        DeviceType::Tablet
+    } else {
+        DeviceType::Desktop
+    }
+}
+
+fn bytes_to_mb(bytes: f64) -> u64 {
+    (bytes / (1024.0 * 1024.0)) as u64
+}
+
+fn get(target: &JsValue, key: &str) -> Option<JsValue> {
+    Reflect::get(target, &JsValue::from_str(key))
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
+
+/// 页面中为 `Navigator`，Worker 中为 `WorkerNavigator`，这里用到的属性两者一致
+pub(crate) fn navigator() -> Option<web_sys::Navigator> {
+    get(&js_sys::global(), "navigator").map(JsCast::unchecked_into)
+}
+
+async fn resolve(promise: Promise) -> Option<JsValue> {
+    JsFuture::from(promise).await.ok()
+}
+
+async fn storage_estimate(navigator: &web_sys::Navigator) -> Option<(Option<u64>, Option<u64>)> {
+    get(navigator, "storage")?;
+    let estimate = resolve(navigator.storage().estimate().ok()?).await?;
+    let quota = get(&estimate, "quota").and_then(|v| v.as_f64()).map(bytes_to_mb);
+    let usage = get(&estimate, "usage").and_then(|v| v.as_f64()).map(bytes_to_mb);
+    Some((quota, usage))
+}
+
+async fn battery(navigator: &web_sys::Navigator) -> Option<(f32, bool)> {
+    // Firefox、Safari 和 Worker 中没有 getBattery
+    get(navigator, "getBattery")?;
+    let manager: web_sys::BatteryManager = resolve(navigator.get_battery().ok()?).await?.dyn_into().ok()?;
+    Some((manager.level() as f32, manager.charging()))
+}
+
+/// 检测当前浏览器环境的设备能力
+pub async fn detect() -> BrowserCapabilities {
+    let mut caps = DeviceCapabilities {
+        max_memory_mb: FALLBACK_MEMORY_MB,
+        cpu_cores: FALLBACK_CPU_CORES,
+        has_gpu: false,
+        cpu_architecture: "wasm32".to_string(),
+        gpu_compute_apis: Vec::new(),
+        has_tpu: None,
+        network_type: NetworkType::Unknown,
+        battery_level: None,
+        is_charging: None,
+        device_type: DeviceType::Unknown,
+    };
+    let mut result = BrowserCapabilities {
+        capabilities: caps.clone(),
+        storage_quota_mb: None,
+        storage_usage_mb: None,
+        detected: Vec::new(),
+    };
+    let Some(navigator) = navigator() else {
+        log::warn!("[WASM] 当前环境没有 navigator，使用默认设备能力");
+        return result;
+    };
+    let mut detected = Vec::new();
+
+    let cores = navigator.hardware_concurrency();
+    if cores >= 1.0 {
+        caps.cpu_cores = cores as u32;
+        detected.push("cpu_cores");
+    }
+    if let Some(memory_gb) = get(&navigator, "deviceMemory").and_then(|v| v.as_f64()) {
+        caps.max_memory_mb = (memory_gb * 1024.0) as u64;
+        detected.push("max_memory_mb");
+    }
+    if get(&navigator, "gpu").is_some() {
+        caps.has_gpu = true;
+        caps.gpu_compute_apis.push(GpuComputeApi::WebGPU);
+        detected.push("has_gpu");
+    }
+
+    let user_agent = navigator.user_agent().unwrap_or_default();
+    let touch_points = get(&navigator, "maxTouchPoints").and_then(|v| v.as_f64()).unwrap_or(0.0) as i32;
+    caps.device_type = device_type_from(&user_agent, touch_points);
+    detected.push("device_type");
+
+    let connection_type = get(&navigator, "connection")
+        .and_then(|connection| get(&connection, "type"))
+        .and_then(|v| v.as_string());
+    if connection_type.is_some() {
+        detected.push("network_type");
+    }
+    caps.network_type = network_type_from(connection_type.as_deref(), caps.device_type);
+
+    if let Some((level, charging)) = battery(&navigator).await {
+        caps.battery_level = Some(level);
+        caps.is_charging = Some(charging);
+        detected.push("battery_level");
+    }
+    if let Some((quota, usage)) = storage_estimate(&navigator).await {
+        result.storage_quota_mb = quota;
+        result.storage_usage_mb = usage;
+        detected.push("storage");
+    }
+
+    result.capabilities = caps;
+    result.detected = detected.into_iter().map(String::from).collect();
+    result
+}
+
+/// 调用 JS 覆盖钩子：以检测结果为参数，返回部分能力对象（可以是 Promise），
+/// 返回 `undefined` 或无法解析时保持检测结果不变
+pub async fn apply_override(hook: &Function, mut caps: BrowserCapabilities) -> BrowserCapabilities {
+    let Ok(arg) = serde_wasm_bindgen::to_value(&caps) else {
+        return caps;
+    };
+    let value = match hook.call1(&JsValue::NULL, &arg) {
+        Ok(value) => match value.dyn_into::<Promise>() {
+            Ok(promise) => resolve(promise).await,
+            Err(value) => Some(value),
+        },
+        Err(e) => {
+            log::warn!("[WASM] 设备能力覆盖钩子抛出异常: {:?}", e);
+            None
+        }
+    };
+    let Some(value) = value.filter(|v| !v.is_undefined() && !v.is_null()) else {
+        return caps;
+    };
+    match serde_wasm_bindgen::from_value::<CapabilityOverride>(value) {
+        Ok(overrides) => overrides.apply(&mut caps),
+        Err(e) => log::warn!("[WASM] 无法解析设备能力覆盖: {}", e),
+    }
+    caps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_and_device_type_inference() {
+        let iphone = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148";
+        let ipad_desktop_mode = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) Safari/605.1.15";
+        assert_eq!(device_type_from(iphone, 5), DeviceType::Phone);
+        assert_eq!(device_type_from(ipad_desktop_mode, 5), DeviceType::Tablet);
+        assert_eq!(device_type_from(ipad_desktop_mode, 0), DeviceType::Desktop);
+
+        assert_eq!(network_type_from(Some("cellular"), DeviceType::Phone), NetworkType::Cellular4G);
+        assert_eq!(network_type_from(Some("ethernet"), DeviceType::Desktop), NetworkType::WiFi);
+        assert_eq!(network_type_from(None, DeviceType::Desktop), NetworkType::WiFi);
+        assert_eq!(network_type_from(None, DeviceType::Phone), NetworkType::Unknown);
+    }
+
+    #[test]
+    fn test_override_keeps_unset_fields() {
+        let mut caps = BrowserCapabilities {
+            capabilities: DeviceCapabilities::default(),
+            storage_quota_mb: Some(1000),
+            storage_usage_mb: None,
+            detected: Vec::new(),
+        };
+        let cores = caps.capabilities.cpu_cores;
+        CapabilityOverride {
+            max_memory_mb: Some(512),
+            battery_level: Some(1.5),
+            ..Default::default()
+        }
+        .apply(&mut caps);
+        assert_eq!(caps.capabilities.max_memory_mb, 512);
+        assert_eq!(caps.capabilities.battery_level, Some(1.0));
+        assert_eq!(caps.capabilities.cpu_cores, cores);
+        assert_eq!(caps.storage_quota_mb, Some(1000));
+    }
+}
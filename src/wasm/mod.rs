@@ -0,0 +1,85 @@
+//! 浏览器端绑定（wasm-bindgen）
+//!
+//! `WilliwWasmApp` 是页面或 Web Worker 中的入口，设备能力检测见 [`device`]；
+//! 访客贡献算力的浏览器内训练见 [`trainer`]，梯度经 [`webrtc`] 交换；
+//! 多线程模式见 [`threads`]。
+
+pub mod device;
+pub mod threads;
+pub mod trainer;
+pub mod webrtc;
+#[cfg(feature = "webgpu")]
+pub mod webgpu;
+
+use crate::device::DeviceManager;
+use device::BrowserCapabilities;
+use js_sys::{Function, Promise};
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+#[wasm_bindgen(start)]
+pub fn wasm_start() {
+    console_error_panic_hook::set_once();
+}
+
+/// 浏览器中的 williw 应用
+#[wasm_bindgen]
+pub struct WilliwWasmApp {
+    devices: DeviceManager,
+    capability_override: RefCell<Option<Function>>,
+}
+
+#[wasm_bindgen]
+impl WilliwWasmApp {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WilliwWasmApp {
+        Self {
+            devices: DeviceManager::with_capabilities(Default::default()),
+            capability_override: RefCell::new(None),
+        }
+    }
+
+    /// 检测设备能力，返回 Promise
+    ///
+    /// 结果同时写入内部的设备管理器。注册了覆盖钩子时，钩子的返回值优先于检测结果。
+    #[wasm_bindgen(js_name = getDeviceCapabilities)]
+    pub fn get_device_capabilities(&self) -> Promise {
+        let devices = self.devices.clone();
+        let hook = self.capability_override.borrow().clone();
+        future_to_promise(async move {
+            let mut caps = device::detect().await;
+            if let Some(hook) = hook {
+                caps = device::apply_override(&hook, caps).await;
+            }
+            devices.set_capabilities(caps.capabilities.clone());
+            to_js(&caps)
+        })
+    }
+
+    /// 注册设备能力覆盖钩子：`(detected) => partial | Promise<partial> | undefined`
+    ///
+    /// 用于浏览器 API 不可用或不准确时由页面提供数值（例如从原生容器获取电量），
+    /// 传入 `undefined` 取消覆盖。
+    #[wasm_bindgen(js_name = setCapabilityOverride)]
+    pub fn set_capability_override(&self, hook: Option<Function>) {
+        *self.capability_override.borrow_mut() = hook;
+    }
+
+    /// 最近一次检测（或初始默认）的设备能力
+    #[wasm_bindgen(js_name = currentCapabilities)]
+    pub fn current_capabilities(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.devices.get())
+            .map_err(|e| JsValue::from_str(&format!("序列化失败: {}", e)))
+    }
+}
+
+impl Default for WilliwWasmApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_js(caps: &BrowserCapabilities) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(caps).map_err(|e| JsValue::from_str(&format!("序列化失败: {}", e)))
+}
@@ -0,0 +1,57 @@
+//! 浏览器多线程模式（`wasm-threads` 特性）
+//!
+//! 基于 wasm-bindgen-rayon，用 Web Worker 组成 rayon 线程池，线程数取 `hardwareConcurrency`。
+//! 线程池依赖 SharedArrayBuffer，页面必须处于跨源隔离状态
+//! （响应头 `Cross-Origin-Opener-Policy: same-origin` 与 `Cross-Origin-Embedder-Policy: require-corp`），
+//! 条件不满足时保持单线程，矩阵运算和批量加密自动走顺序路径。
+
+use crate::parallel;
+use js_sys::Reflect;
+use wasm_bindgen::prelude::*;
+
+/// 线程池的线程数上限
+#[cfg(feature = "wasm-threads")]
+const MAX_THREADS: u32 = 16;
+
+/// 也直接导出 `initThreadPool(n)`，便于需要自定义线程数的页面
+#[cfg(feature = "wasm-threads")]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+/// 页面是否满足共享内存的条件
+pub fn cross_origin_isolated() -> bool {
+    let global = js_sys::global();
+    let isolated = Reflect::get(&global, &"crossOriginIsolated".into())
+        .ok()
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    isolated && Reflect::has(&global, &"SharedArrayBuffer".into()).unwrap_or(false)
+}
+
+/// 初始化线程池，返回实际使用的线程数（1 表示单线程模式）
+#[wasm_bindgen(js_name = initThreads)]
+pub async fn init_threads() -> Result<u32, JsValue> {
+    if parallel::available() {
+        return Ok(rayon::current_num_threads() as u32);
+    }
+    #[cfg(feature = "wasm-threads")]
+    if cross_origin_isolated() {
+        let threads = super::device::navigator()
+            .map(|navigator| navigator.hardware_concurrency() as u32)
+            .unwrap_or(1)
+            .clamp(1, MAX_THREADS);
+        if threads > 1 {
+            wasm_bindgen_futures::JsFuture::from(init_thread_pool(threads as usize)).await?;
+            parallel::mark_thread_pool_ready();
+            log::info!("[WASM] 已启用 {} 线程的 Worker 线程池", threads);
+            return Ok(threads);
+        }
+    }
+    log::info!("[WASM] 页面未跨源隔离或构建未启用 wasm-threads，使用单线程模式");
+    Ok(1)
+}
+
+/// 是否处于多线程模式
+#[wasm_bindgen(js_name = threadsEnabled)]
+pub fn threads_enabled() -> bool {
+    parallel::available()
+}
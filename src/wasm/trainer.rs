@@ -0,0 +1,568 @@
+//! 浏览器内训练
+//!
+//! 访客页面（通常在 Web Worker 中）加载一个小模型分片，逐批做前向/反向计算并更新参数：
+//! - 有 WebGPU 时用计算着色器（见 [`super::webgpu`]），否则或失败时退回 CPU
+//! - 每步的梯度编码为二进制消息，经 WebRTC 数据通道（[`super::webrtc`]）与其他访客交换，
+//!   收到的同分片梯度按样本数加权合并后再更新
+//!
+//! 分片是一个线性层 `y = Wx + b`，损失为 MSE，与原生节点的线性模型一致。
+
+use crate::parallel;
+use crate::training::{LossFunction, Optimizer, MSE, SGD};
+use anyhow::{anyhow, bail, Result};
+use js_sys::{Float32Array, Object, Reflect, Uint8Array};
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+/// 梯度消息魔数
+const GRADIENT_MAGIC: &[u8; 4] = b"WGRD";
+const GRADIENT_FORMAT_VERSION: u8 = 1;
+/// 等待合并的梯度消息上限，超出时丢弃最旧的
+const MAX_PENDING_GRADIENTS: usize = 64;
+
+/// 模型分片：单个线性层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelShard {
+    pub shard_id: String,
+    pub input_dim: usize,
+    pub output_dim: usize,
+    /// 行主序权重，`output_dim × input_dim`
+    pub weights: Vec<f32>,
+    pub bias: Vec<f32>,
+    /// 分片版本，只有同一版本的梯度才会合并
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl ModelShard {
+    pub fn validate(&self) -> Result<()> {
+        if self.input_dim == 0 || self.output_dim == 0 {
+            bail!("分片 {} 的维度不能为 0", self.shard_id);
+        }
+        if Some(self.weights.len()) != self.input_dim.checked_mul(self.output_dim) || self.bias.len() != self.output_dim {
+            bail!(
+                "分片 {} 参数数量与维度 {}×{} 不一致",
+                self.shard_id,
+                self.output_dim,
+                self.input_dim
+            );
+        }
+        Ok(())
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        self.weights.len() + self.bias.len()
+    }
+
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        (0..self.output_dim)
+            .map(|o| {
+                let row = &self.weights[o * self.input_dim..(o + 1) * self.input_dim];
+                self.bias[o] + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>()
+            })
+            .collect()
+    }
+
+    /// 按批量数据检查输入长度，返回样本数
+    pub fn batch_size(&self, inputs: &[f32], targets: &[f32]) -> Result<usize> {
+        if inputs.is_empty() || inputs.len() % self.input_dim != 0 {
+            bail!("输入长度 {} 不是输入维度 {} 的整数倍", inputs.len(), self.input_dim);
+        }
+        let batch = inputs.len() / self.input_dim;
+        if targets.len() != batch * self.output_dim {
+            bail!("目标长度 {} 与批量 {}×{} 不一致", targets.len(), batch, self.output_dim);
+        }
+        Ok(batch)
+    }
+}
+
+/// 一个批量的梯度（已按批量取平均）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradients {
+    pub weights: Vec<f32>,
+    pub bias: Vec<f32>,
+    pub loss: f32,
+    pub samples: u32,
+}
+
+/// CPU 上的前向/反向计算（多线程模式下按样本和权重行并行）
+pub fn cpu_forward_backward(shard: &ModelShard, inputs: &[f32], targets: &[f32]) -> Result<Gradients> {
+    let batch = shard.batch_size(inputs, targets)?;
+    let (input_dim, output_dim) = (shard.input_dim, shard.output_dim);
+
+    let mut predictions = vec![0.0; batch * output_dim];
+    parallel::for_each_row(&mut predictions, output_dim, |b, row| {
+        row.copy_from_slice(&shard.forward(&inputs[b * input_dim..(b + 1) * input_dim]));
+    });
+
+    let mut loss = 0.0;
+    let mut output_grads = vec![0.0; batch * output_dim];
+    for b in 0..batch {
+        let range = b * output_dim..(b + 1) * output_dim;
+        let predicted = Array1::from(predictions[range.clone()].to_vec());
+        let target = Array1::from(targets[range.clone()].to_vec());
+        loss += MSE.compute(&predicted, &target);
+        output_grads[range].copy_from_slice(&MSE.gradient(&predicted, &target).to_vec());
+    }
+
+    // 按批量取平均
+    let scale = 1.0 / batch as f32;
+    let mut weights = vec![0.0; shard.weights.len()];
+    parallel::for_each_row(&mut weights, input_dim, |o, row| {
+        for b in 0..batch {
+            let g = output_grads[b * output_dim + o] * scale;
+            for (w, x) in row.iter_mut().zip(&inputs[b * input_dim..(b + 1) * input_dim]) {
+                *w += g * x;
+            }
+        }
+    });
+    let bias = (0..output_dim)
+        .map(|o| (0..batch).map(|b| output_grads[b * output_dim + o]).sum::<f32>() * scale)
+        .collect();
+
+    Ok(Gradients {
+        weights,
+        bias,
+        loss: loss * scale,
+        samples: batch as u32,
+    })
+}
+
+/// 在访客之间交换的梯度消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientMessage {
+    pub shard_id: String,
+    pub shard_version: u64,
+    pub step: u64,
+    pub samples: u32,
+    pub loss: f32,
+    /// 权重梯度在前、偏置梯度在后
+    pub values: Vec<f32>,
+}
+
+impl GradientMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let id = self.shard_id.as_bytes();
+        let mut out = Vec::with_capacity(4 + 1 + 2 + id.len() + 8 + 8 + 4 + 4 + 4 + self.values.len() * 4);
+        out.extend_from_slice(GRADIENT_MAGIC);
+        out.push(GRADIENT_FORMAT_VERSION);
+        out.extend_from_slice(&(id.len() as u16).to_le_bytes());
+        out.extend_from_slice(id);
+        out.extend_from_slice(&self.shard_version.to_le_bytes());
+        out.extend_from_slice(&self.step.to_le_bytes());
+        out.extend_from_slice(&self.samples.to_le_bytes());
+        out.extend_from_slice(&self.loss.to_le_bytes());
+        out.extend_from_slice(&(self.values.len() as u32).to_le_bytes());
+        for value in &self.values {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != GRADIENT_MAGIC {
+            bail!("不是梯度消息");
+        }
+        let format = reader.take(1)?[0];
+        if format != GRADIENT_FORMAT_VERSION {
+            bail!("不支持的梯度消息格式 {}", format);
+        }
+        let id_len = u16::from_le_bytes(reader.array()?) as usize;
+        let shard_id = String::from_utf8(reader.take(id_len)?.to_vec())?;
+        let shard_version = u64::from_le_bytes(reader.array()?);
+        let step = u64::from_le_bytes(reader.array()?);
+        let samples = u32::from_le_bytes(reader.array()?);
+        let loss = f32::from_le_bytes(reader.array()?);
+        let count = u32::from_le_bytes(reader.array()?) as usize;
+        let values = reader
+            .take(count * 4)?
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        Ok(Self {
+            shard_id,
+            shard_version,
+            step,
+            samples,
+            loss,
+            values,
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.offset + len;
+        let slice = self.bytes.get(self.offset..end).ok_or_else(|| anyhow!("梯度消息被截断"))?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+}
+
+/// 收集其他访客的梯度，在本地更新前合并
+#[derive(Debug, Default)]
+pub struct GradientAggregator {
+    pending: Vec<GradientMessage>,
+}
+
+impl GradientAggregator {
+    pub fn push(&mut self, message: GradientMessage) {
+        if self.pending.len() >= MAX_PENDING_GRADIENTS {
+            self.pending.remove(0);
+        }
+        self.pending.push(message);
+    }
+
+    /// 把与当前分片匹配、落后不超过 `max_staleness` 步的梯度按样本数加权合并进 `local`，
+    /// 返回合并的消息数；其余消息直接丢弃
+    pub fn merge_into(&mut self, local: &mut Gradients, shard: &ModelShard, step: u64, max_staleness: u64) -> usize {
+        let mut merged = 0;
+        let mut total = local.samples as f32;
+        let weight_len = local.weights.len();
+        for message in self.pending.drain(..) {
+            if message.shard_id != shard.shard_id
+                || message.shard_version != shard.version
+                || message.values.len() != shard.parameter_count()
+                || step.saturating_sub(message.step) > max_staleness
+                || message.samples == 0
+            {
+                continue;
+            }
+            let weight = message.samples as f32;
+            let keep = total / (total + weight);
+            let (weights, bias) = message.values.split_at(weight_len);
+            for (g, peer) in local.weights.iter_mut().zip(weights) {
+                *g = *g * keep + peer * (1.0 - keep);
+            }
+            for (g, peer) in local.bias.iter_mut().zip(bias) {
+                *g = *g * keep + peer * (1.0 - keep);
+            }
+            total += weight;
+            merged += 1;
+        }
+        merged
+    }
+}
+
+/// 训练配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrainerConfig {
+    pub learning_rate: f32,
+    /// 可用时优先用 WebGPU
+    pub prefer_gpu: bool,
+    /// 合并其他访客梯度时允许落后的最大步数
+    pub max_staleness: u64,
+}
+
+impl Default for TrainerConfig {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.01,
+            prefer_gpu: true,
+            max_staleness: 2,
+        }
+    }
+}
+
+enum Backend {
+    Cpu,
+    #[cfg(feature = "webgpu")]
+    Gpu(Rc<super::webgpu::WebGpuBackend>),
+}
+
+impl Backend {
+    fn name(&self) -> &'static str {
+        match self {
+            Backend::Cpu => "cpu",
+            #[cfg(feature = "webgpu")]
+            Backend::Gpu(_) => "webgpu",
+        }
+    }
+}
+
+struct TrainerState {
+    config: TrainerConfig,
+    shard: Option<ModelShard>,
+    optimizer: SGD,
+    backend: Backend,
+    step: u64,
+    peers: GradientAggregator,
+}
+
+impl TrainerState {
+    fn load_shard(&mut self, shard: ModelShard) -> Result<()> {
+        shard.validate()?;
+        self.shard = Some(shard);
+        self.optimizer.reset();
+        self.peers = GradientAggregator::default();
+        self.step = 0;
+        Ok(())
+    }
+
+    /// 合并其他访客的梯度并更新参数，返回本步要广播的消息和合并数
+    fn apply(&mut self, mut grads: Gradients) -> Result<(GradientMessage, usize)> {
+        let step = self.step;
+        let max_staleness = self.config.max_staleness;
+        let shard = self.shard.as_mut().ok_or_else(|| anyhow!("尚未加载模型分片"))?;
+        if grads.weights.len() != shard.weights.len() || grads.bias.len() != shard.bias.len() {
+            bail!("训练过程中模型分片已被替换");
+        }
+        let message = GradientMessage {
+            shard_id: shard.shard_id.clone(),
+            shard_version: shard.version,
+            step,
+            samples: grads.samples,
+            loss: grads.loss,
+            values: grads.weights.iter().chain(&grads.bias).copied().collect(),
+        };
+        let merged = self.peers.merge_into(&mut grads, shard, step, max_staleness);
+
+        let mut params = Array1::from_iter(shard.weights.iter().chain(&shard.bias).copied());
+        let gradients = Array1::from_iter(grads.weights.iter().chain(&grads.bias).copied());
+        self.optimizer.update(&mut params, &gradients);
+        let params = params.to_vec();
+        let (weights, bias) = params.split_at(shard.weights.len());
+        shard.weights.copy_from_slice(weights);
+        shard.bias.copy_from_slice(bias);
+        self.step += 1;
+        Ok((message, merged))
+    }
+}
+
+fn js_error(error: impl std::fmt::Display) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+/// 浏览器训练器，通常运行在 Web Worker 中
+#[wasm_bindgen]
+pub struct WasmTrainer {
+    state: Rc<RefCell<TrainerState>>,
+}
+
+#[wasm_bindgen]
+impl WasmTrainer {
+    /// 创建训练器；`prefer_gpu` 时尝试初始化 WebGPU，失败则使用 CPU
+    pub async fn create(config: JsValue) -> Result<WasmTrainer, JsValue> {
+        let config: TrainerConfig = if config.is_undefined() || config.is_null() {
+            TrainerConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config).map_err(|e| js_error(format!("配置解析失败: {}", e)))?
+        };
+        let backend = select_backend(&config).await;
+        log::info!("[WASM] 训练后端: {}", backend.name());
+        Ok(WasmTrainer {
+            state: Rc::new(RefCell::new(TrainerState {
+                optimizer: SGD::new(config.learning_rate),
+                config,
+                shard: None,
+                backend,
+                step: 0,
+                peers: GradientAggregator::default(),
+            })),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn backend(&self) -> String {
+        self.state.borrow().backend.name().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn step(&self) -> f64 {
+        self.state.borrow().step as f64
+    }
+
+    /// 加载模型分片对象（字段见 `ModelShard`），会重置优化器和步数
+    #[wasm_bindgen(js_name = loadShard)]
+    pub fn load_shard(&self, shard: JsValue) -> Result<(), JsValue> {
+        let shard: ModelShard = serde_wasm_bindgen::from_value(shard).map_err(js_error)?;
+        self.state.borrow_mut().load_shard(shard).map_err(js_error)
+    }
+
+    /// 从 URL 下载 JSON 格式的模型分片
+    #[wasm_bindgen(js_name = loadShardFromUrl)]
+    pub fn load_shard_from_url(&self, url: String) -> js_sys::Promise {
+        let state = self.state.clone();
+        future_to_promise(async move {
+            let fetch: js_sys::Function = Reflect::get(&js_sys::global(), &"fetch".into())?.dyn_into()?;
+            let response = JsFuture::from(fetch.call1(&JsValue::NULL, &url.into())?.dyn_into::<js_sys::Promise>()?).await?;
+            let ok = Reflect::get(&response, &"ok".into())?.as_bool().unwrap_or(false);
+            if !ok {
+                return Err(js_error("下载模型分片失败"));
+            }
+            let json: js_sys::Function = Reflect::get(&response, &"json".into())?.dyn_into()?;
+            let value = JsFuture::from(json.call0(&response)?.dyn_into::<js_sys::Promise>()?).await?;
+            let shard: ModelShard = serde_wasm_bindgen::from_value(value).map_err(js_error)?;
+            state.borrow_mut().load_shard(shard).map_err(js_error)?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// 导出当前参数（与 `loadShard` 的格式相同）
+    #[wasm_bindgen(js_name = exportShard)]
+    pub fn export_shard(&self) -> Result<JsValue, JsValue> {
+        let state = self.state.borrow();
+        let shard = state.shard.as_ref().ok_or_else(|| js_error("尚未加载模型分片"))?;
+        serde_wasm_bindgen::to_value(shard).map_err(js_error)
+    }
+
+    /// 接收其他访客广播的梯度消息，在下一步更新时合并
+    #[wasm_bindgen(js_name = receiveGradient)]
+    pub fn receive_gradient(&self, bytes: &[u8]) -> Result<(), JsValue> {
+        let message = GradientMessage::decode(bytes).map_err(js_error)?;
+        self.state.borrow_mut().peers.push(message);
+        Ok(())
+    }
+
+    /// 训练一步：`inputs` 为 `batch × input_dim`，`targets` 为 `batch × output_dim`（均为行主序）
+    ///
+    /// 返回 `{ step, loss, samples, backend, merged_peers, gradient }`，
+    /// 其中 `gradient` 为需要广播给其他访客的梯度消息（Uint8Array）。
+    /// 同一训练器上应等待上一步完成后再调用。
+    #[wasm_bindgen(js_name = trainStep)]
+    pub fn train_step(&self, inputs: Float32Array, targets: Float32Array) -> js_sys::Promise {
+        let state = self.state.clone();
+        let inputs = inputs.to_vec();
+        let targets = targets.to_vec();
+        future_to_promise(async move {
+            let shard = state.borrow().shard.clone().ok_or_else(|| js_error("尚未加载模型分片"))?;
+            let grads = compute(&state, &shard, &inputs, &targets).await.map_err(js_error)?;
+            let (message, merged) = state.borrow_mut().apply(grads).map_err(js_error)?;
+
+            let result = Object::new();
+            Reflect::set(&result, &"step".into(), &JsValue::from(message.step as f64))?;
+            Reflect::set(&result, &"loss".into(), &JsValue::from(message.loss))?;
+            Reflect::set(&result, &"samples".into(), &JsValue::from(message.samples))?;
+            Reflect::set(&result, &"backend".into(), &state.borrow().backend.name().into())?;
+            Reflect::set(&result, &"merged_peers".into(), &JsValue::from(merged as u32))?;
+            Reflect::set(&result, &"gradient".into(), &Uint8Array::from(message.encode().as_slice()))?;
+            Ok(result.into())
+        })
+    }
+}
+
+async fn select_backend(config: &TrainerConfig) -> Backend {
+    #[cfg(feature = "webgpu")]
+    if config.prefer_gpu {
+        match super::webgpu::WebGpuBackend::new().await {
+            Ok(gpu) => return Backend::Gpu(Rc::new(gpu)),
+            Err(e) => log::warn!("[WASM] WebGPU 不可用，使用 CPU: {}", e),
+        }
+    }
+    #[cfg(not(feature = "webgpu"))]
+    let _ = config;
+    Backend::Cpu
+}
+
+/// 在当前后端上计算梯度；GPU 出错时永久切换到 CPU 并重算
+async fn compute(state: &Rc<RefCell<TrainerState>>, shard: &ModelShard, inputs: &[f32], targets: &[f32]) -> Result<Gradients> {
+    #[cfg(feature = "webgpu")]
+    {
+        let gpu = match &state.borrow().backend {
+            Backend::Gpu(gpu) => Some(gpu.clone()),
+            Backend::Cpu => None,
+        };
+        if let Some(gpu) = gpu {
+            match gpu.forward_backward(shard, inputs, targets).await {
+                Ok(grads) => return Ok(grads),
+                Err(e) => {
+                    log::warn!("[WASM] WebGPU 计算失败，切换到 CPU: {}", e);
+                    state.borrow_mut().backend = Backend::Cpu;
+                }
+            }
+        }
+    }
+    #[cfg(not(feature = "webgpu"))]
+    let _ = state;
+    cpu_forward_backward(shard, inputs, targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard() -> ModelShard {
+        ModelShard {
+            shard_id: "layer-0".to_string(),
+            input_dim: 2,
+            output_dim: 1,
+            weights: vec![0.0, 0.0],
+            bias: vec![0.0],
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn test_cpu_training_reduces_loss() {
+        // y = 2a - b + 0.5
+        let inputs = [1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 2.0, 1.0];
+        let targets = [2.5, -0.5, 1.5, 3.5];
+        let mut state = TrainerState {
+            config: TrainerConfig::default(),
+            shard: None,
+            optimizer: SGD::new(0.1),
+            backend: Backend::Cpu,
+            step: 0,
+            peers: GradientAggregator::default(),
+        };
+        state.load_shard(shard()).unwrap();
+
+        let first = cpu_forward_backward(state.shard.as_ref().unwrap(), &inputs, &targets).unwrap();
+        for _ in 0..200 {
+            let grads = cpu_forward_backward(state.shard.as_ref().unwrap(), &inputs, &targets).unwrap();
+            state.apply(grads).unwrap();
+        }
+        let last = cpu_forward_backward(state.shard.as_ref().unwrap(), &inputs, &targets).unwrap();
+        assert!(last.loss < first.loss * 0.01);
+        assert_eq!(state.step, 200);
+        assert!(cpu_forward_backward(&shard(), &inputs[..3], &targets).is_err());
+    }
+
+    #[test]
+    fn test_gradient_message_roundtrip_and_merge() {
+        let message = GradientMessage {
+            shard_id: "layer-0".to_string(),
+            shard_version: 1,
+            step: 5,
+            samples: 3,
+            loss: 0.25,
+            values: vec![1.0, 1.0, 1.0],
+        };
+        let decoded = GradientMessage::decode(&message.encode()).unwrap();
+        assert_eq!(decoded, message);
+        assert!(GradientMessage::decode(&message.encode()[..10]).is_err());
+
+        let mut local = Gradients {
+            weights: vec![0.0, 0.0],
+            bias: vec![0.0],
+            loss: 0.0,
+            samples: 1,
+        };
+        let mut aggregator = GradientAggregator::default();
+        aggregator.push(decoded);
+        aggregator.push(GradientMessage { step: 1, ..message.clone() });
+        aggregator.push(GradientMessage {
+            shard_version: 2,
+            ..message
+        });
+        // 只有第一条满足版本和时效要求：按样本数 1:3 加权
+        assert_eq!(aggregator.merge_into(&mut local, &shard(), 6, 2), 1);
+        assert_eq!(local.weights, vec![0.75, 0.75]);
+        assert_eq!(local.bias, vec![0.75]);
+    }
+}
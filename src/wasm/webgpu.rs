@@ -0,0 +1,297 @@
+//! WebGPU 计算后端
+//!
+//! 用两个计算着色器完成线性层的一次训练计算：
+//! - `forward`：每个线程算一个 (样本, 输出) 的预测值和 MSE 输出梯度
+//! - `backward`：每个线程沿批量累加一个权重或偏置的梯度
+//!
+//! 结果读回后与 CPU 路径一样按批量取平均，可以直接交给优化器。
+
+use super::trainer::{Gradients, ModelShard};
+use anyhow::{anyhow, bail, Result};
+use wgpu::util::DeviceExt;
+
+const WORKGROUP_SIZE: u32 = 64;
+/// 单个维度上允许的最大工作组数（WebGPU 默认限制）
+const MAX_WORKGROUPS: u32 = 65535;
+
+const SHADER: &str = r#"
+struct Dims {
+    batch: u32,
+    input_dim: u32,
+    output_dim: u32,
+    _pad: u32,
+}
+
+@group(0) @binding(0) var<uniform> dims: Dims;
+@group(0) @binding(1) var<storage, read> weights: array<f32>;
+@group(0) @binding(2) var<storage, read> bias: array<f32>;
+@group(0) @binding(3) var<storage, read> inputs: array<f32>;
+@group(0) @binding(4) var<storage, read> targets: array<f32>;
+@group(0) @binding(5) var<storage, read_write> outputs: array<f32>;
+@group(0) @binding(6) var<storage, read_write> out_grad: array<f32>;
+@group(0) @binding(7) var<storage, read_write> param_grad: array<f32>;
+
+@compute @workgroup_size(64)
+fn forward(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= dims.batch * dims.output_dim) {
+        return;
+    }
+    let b = index / dims.output_dim;
+    let o = index % dims.output_dim;
+    var y = bias[o];
+    for (var i = 0u; i < dims.input_dim; i++) {
+        y += weights[o * dims.input_dim + i] * inputs[b * dims.input_dim + i];
+    }
+    outputs[index] = y;
+    out_grad[index] = 2.0 * (y - targets[index]) / f32(dims.output_dim * dims.batch);
+}
+
+// param_grad 中权重梯度在前、偏置梯度在后
+@compute @workgroup_size(64)
+fn backward(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    let weight_count = dims.output_dim * dims.input_dim;
+    if (index >= weight_count + dims.output_dim) {
+        return;
+    }
+    var g = 0.0;
+    if (index < weight_count) {
+        let o = index / dims.input_dim;
+        let i = index % dims.input_dim;
+        for (var b = 0u; b < dims.batch; b++) {
+            g += out_grad[b * dims.output_dim + o] * inputs[b * dims.input_dim + i];
+        }
+    } else {
+        let o = index - weight_count;
+        for (var b = 0u; b < dims.batch; b++) {
+            g += out_grad[b * dims.output_dim + o];
+        }
+    }
+    param_grad[index] = g;
+}
+"#;
+
+fn to_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn workgroups(threads: usize) -> Result<u32> {
+    let groups = (threads as u32).div_ceil(WORKGROUP_SIZE);
+    if threads > u32::MAX as usize || groups > MAX_WORKGROUPS {
+        bail!("分片过大，超出单次调度上限");
+    }
+    Ok(groups)
+}
+
+fn layout_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+pub struct WebGpuBackend {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    forward: wgpu::ComputePipeline,
+    backward: wgpu::ComputePipeline,
+}
+
+impl WebGpuBackend {
+    pub async fn new() -> Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::BROWSER_WEBGPU,
+            ..Default::default()
+        });
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| anyhow!("没有可用的 WebGPU 适配器"))?;
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("williw-trainer"),
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("williw-linear"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        use wgpu::BufferBindingType::{Storage, Uniform};
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("williw-linear"),
+            entries: &[
+                layout_entry(0, Uniform),
+                layout_entry(1, Storage { read_only: true }),
+                layout_entry(2, Storage { read_only: true }),
+                layout_entry(3, Storage { read_only: true }),
+                layout_entry(4, Storage { read_only: true }),
+                layout_entry(5, Storage { read_only: false }),
+                layout_entry(6, Storage { read_only: false }),
+                layout_entry(7, Storage { read_only: false }),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("williw-linear"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point,
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let forward = pipeline("forward");
+        let backward = pipeline("backward");
+        Ok(Self {
+            device,
+            queue,
+            layout,
+            forward,
+            backward,
+        })
+    }
+
+    pub async fn forward_backward(&self, shard: &ModelShard, inputs: &[f32], targets: &[f32]) -> Result<Gradients> {
+        let batch = shard.batch_size(inputs, targets)?;
+        let output_count = batch * shard.output_dim;
+        let param_count = shard.parameter_count();
+        let forward_groups = workgroups(output_count)?;
+        let backward_groups = workgroups(param_count)?;
+
+        let init = |label: &str, contents: &[u8], usage: wgpu::BufferUsages| {
+            self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage,
+            })
+        };
+        let output = |label: &str, len: usize| {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: (len * 4) as u64,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            })
+        };
+        let dims: Vec<u8> = [batch as u32, shard.input_dim as u32, shard.output_dim as u32, 0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let dims = init("dims", &dims, wgpu::BufferUsages::UNIFORM);
+        let weights = init("weights", &to_bytes(&shard.weights), wgpu::BufferUsages::STORAGE);
+        let bias = init("bias", &to_bytes(&shard.bias), wgpu::BufferUsages::STORAGE);
+        let inputs_buffer = init("inputs", &to_bytes(inputs), wgpu::BufferUsages::STORAGE);
+        let targets_buffer = init("targets", &to_bytes(targets), wgpu::BufferUsages::STORAGE);
+        let outputs = output("outputs", output_count);
+        let out_grad = output("out_grad", output_count);
+        let param_grad = output("param_grad", param_count);
+
+        let buffers = [
+            &dims,
+            &weights,
+            &bias,
+            &inputs_buffer,
+            &targets_buffer,
+            &outputs,
+            &out_grad,
+            &param_grad,
+        ];
+        let entries: Vec<wgpu::BindGroupEntry> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("williw-linear"),
+            layout: &self.layout,
+            entries: &entries,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("williw-train-step"),
+        });
+        for (pipeline, groups) in [(&self.forward, forward_groups), (&self.backward, backward_groups)] {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups, 1, 1);
+        }
+        self.queue.submit([encoder.finish()]);
+
+        let predicted = self.read(&outputs, output_count).await?;
+        let grads = self.read(&param_grad, param_count).await?;
+        let loss = predicted
+            .iter()
+            .zip(targets)
+            .map(|(y, t)| (y - t) * (y - t))
+            .sum::<f32>()
+            / output_count as f32;
+        let (weights, bias) = grads.split_at(shard.weights.len());
+        Ok(Gradients {
+            weights: weights.to_vec(),
+            bias: bias.to_vec(),
+            loss,
+            samples: batch as u32,
+        })
+    }
+
+    /// 把 GPU 缓冲区读回内存
+    async fn read(&self, source: &wgpu::Buffer, len: usize) -> Result<Vec<f32>> {
+        let size = (len * 4) as u64;
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("williw-readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_buffer_to_buffer(source, 0, &staging, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        // 浏览器中由事件循环驱动，这里只是兼容原生后端
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.await??;
+        let values = slice
+            .get_mapped_range()
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        staging.unmap();
+        Ok(values)
+    }
+}
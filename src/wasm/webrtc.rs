@@ -0,0 +1,116 @@
+//! WebRTC 梯度传输
+//!
+//! 页面负责信令和建立 `RTCPeerConnection`，把协商好的数据通道交给这里；
+//! 传输层只负责广播和接收二进制梯度消息。`RTCPeerConnection` 在 Web Worker 中不可用，
+//! 训练器运行在 Worker 时，由页面把 `trainStep` 返回的 `gradient` 转发过来广播，
+//! 收到的消息再通过 `postMessage` 交给 Worker 中的 `receiveGradient`。
+
+use js_sys::{ArrayBuffer, Function, Uint8Array};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{MessageEvent, RtcDataChannel, RtcDataChannelState, RtcDataChannelType};
+
+/// 单条消息的上限：部分浏览器的数据通道不支持更大的消息
+pub const MAX_MESSAGE_BYTES: usize = 256 * 1024;
+
+type MessageHandler = Closure<dyn FnMut(MessageEvent)>;
+
+/// 通过 WebRTC 数据通道在访客之间交换梯度
+#[wasm_bindgen]
+pub struct WebRtcGradientTransport {
+    channels: Vec<RtcDataChannel>,
+    listener: Rc<RefCell<Option<Function>>>,
+    // 回调需要和通道一样长寿
+    handlers: Vec<MessageHandler>,
+}
+
+#[wasm_bindgen]
+impl WebRtcGradientTransport {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WebRtcGradientTransport {
+        Self {
+            channels: Vec::new(),
+            listener: Rc::new(RefCell::new(None)),
+            handlers: Vec::new(),
+        }
+    }
+
+    /// 添加一条已建立（或正在建立）的数据通道
+    #[wasm_bindgen(js_name = addChannel)]
+    pub fn add_channel(&mut self, channel: RtcDataChannel) {
+        channel.set_binary_type(RtcDataChannelType::Arraybuffer);
+        let listener = self.listener.clone();
+        let handler = MessageHandler::new(move |event: MessageEvent| {
+            let Ok(buffer) = event.data().dyn_into::<ArrayBuffer>() else {
+                return;
+            };
+            if let Some(listener) = listener.borrow().as_ref() {
+                if let Err(e) = listener.call1(&JsValue::NULL, &Uint8Array::new(&buffer)) {
+                    log::warn!("[WASM] 梯度回调抛出异常: {:?}", e);
+                }
+            }
+        });
+        channel.set_onmessage(Some(handler.as_ref().unchecked_ref()));
+        self.channels.push(channel);
+        self.handlers.push(handler);
+    }
+
+    /// 注册收到梯度消息（Uint8Array）时的回调
+    #[wasm_bindgen(js_name = onGradient)]
+    pub fn on_gradient(&self, callback: Option<Function>) {
+        *self.listener.borrow_mut() = callback;
+    }
+
+    /// 向所有已打开的通道广播梯度消息，返回成功发送的通道数
+    pub fn broadcast(&mut self, bytes: &[u8]) -> Result<u32, JsValue> {
+        if bytes.len() > MAX_MESSAGE_BYTES {
+            return Err(JsValue::from_str(&format!(
+                "梯度消息 {} 字节超过数据通道上限 {} 字节，请使用更小的分片",
+                bytes.len(),
+                MAX_MESSAGE_BYTES
+            )));
+        }
+        self.prune_closed();
+        let mut sent = 0;
+        for channel in &self.channels {
+            if channel.ready_state() != RtcDataChannelState::Open {
+                continue;
+            }
+            match channel.send_with_u8_array(bytes) {
+                Ok(()) => sent += 1,
+                Err(e) => log::warn!("[WASM] 发送梯度失败（通道 {}）: {:?}", channel.label(), e),
+            }
+        }
+        Ok(sent)
+    }
+
+    /// 已打开的通道数
+    #[wasm_bindgen(js_name = peerCount)]
+    pub fn peer_count(&self) -> u32 {
+        self.channels
+            .iter()
+            .filter(|channel| channel.ready_state() == RtcDataChannelState::Open)
+            .count() as u32
+    }
+
+    /// 移除已关闭的通道
+    fn prune_closed(&mut self) {
+        let mut index = 0;
+        while index < self.channels.len() {
+            if self.channels[index].ready_state() == RtcDataChannelState::Closed {
+                self.channels[index].set_onmessage(None);
+                self.channels.remove(index);
+                self.handlers.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+impl Default for WebRtcGradientTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
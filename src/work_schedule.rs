@@ -0,0 +1,322 @@
+//! 后台重任务调度窗口
+//!
+//! 模型下载、梯度同步、分片传输等重任务只在用户配置的时间窗口内、且满足网络策略时执行：
+//! - 允许窗口（`windows`）：为空表示全天允许
+//! - 安静时段（`quiet_hours`）：优先于允许窗口，期间一律暂停
+//! - 仅 Wi-Fi（`wifi_only`）：列出的任务类型在移动网络或未知网络下暂停
+//!
+//! 一次性任务（下载、分片传输）通过 [`WorkScheduler::wait_for_window`] 排队，窗口打开后自动继续；
+//! 周期性任务（梯度同步）被拦下时直接跳过本轮，窗口打开后的下一轮自然恢复。
+
+use crate::device::{DeviceManager, NetworkType};
+use chrono::{Datelike, Local, NaiveDateTime, Timelike, Weekday};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// 受调度窗口约束的重任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkKind {
+    ModelDownload,
+    GradientSync,
+    ShardTransfer,
+}
+
+impl fmt::Display for WorkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WorkKind::ModelDownload => "模型下载",
+            WorkKind::GradientSync => "梯度同步",
+            WorkKind::ShardTransfer => "分片传输",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 本地时间窗口
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// 开始时间 `HH:MM`
+    pub start: String,
+    /// 结束时间 `HH:MM`，早于开始时间表示跨越午夜，等于开始时间表示全天
+    pub end: String,
+    /// 生效的星期（跨午夜的窗口按开始那天计算），为空表示每天
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+}
+
+impl TimeWindow {
+    pub fn new(start: impl Into<String>, end: impl Into<String>) -> Self {
+        Self {
+            start: start.into(),
+            end: end.into(),
+            days: Vec::new(),
+        }
+    }
+
+    /// 开始和结束时间（当天的分钟数）
+    pub fn bounds(&self) -> Result<(u32, u32), String> {
+        Ok((parse_minutes(&self.start)?, parse_minutes(&self.end)?))
+    }
+
+    /// `now` 是否落在窗口内，时间格式错误的窗口视为不匹配
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let Ok((start, end)) = self.bounds() else {
+            return false;
+        };
+        let minute = now.hour() * 60 + now.minute();
+        let today = now.weekday();
+        let day = if start == end {
+            Some(today)
+        } else if start < end {
+            (start..end).contains(&minute).then_some(today)
+        } else if minute >= start {
+            Some(today)
+        } else if minute < end {
+            Some(today.pred())
+        } else {
+            None
+        };
+        day.is_some_and(|day| self.days.is_empty() || self.days.contains(&day))
+    }
+}
+
+fn parse_minutes(value: &str) -> Result<u32, String> {
+    let (hour, minute) = value
+        .split_once(':')
+        .ok_or_else(|| format!("时间格式应为 HH:MM: {}", value))?;
+    let hour: u32 = hour.trim().parse().map_err(|_| format!("无效的小时: {}", value))?;
+    let minute: u32 = minute.trim().parse().map_err(|_| format!("无效的分钟: {}", value))?;
+    if hour > 23 || minute > 59 {
+        return Err(format!("时间超出范围: {}", value));
+    }
+    Ok(hour * 60 + minute)
+}
+
+/// 调度窗口配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkScheduleConfig {
+    pub enabled: bool,
+    /// 允许执行重任务的时间窗口，为空表示全天
+    pub windows: Vec<TimeWindow>,
+    /// 安静时段，期间暂停所有重任务
+    pub quiet_hours: Vec<TimeWindow>,
+    /// 只在 Wi-Fi 下执行的任务类型
+    pub wifi_only: Vec<WorkKind>,
+    /// 排队任务重新检查窗口的间隔（秒）
+    pub check_interval_secs: u64,
+}
+
+impl Default for WorkScheduleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            windows: Vec::new(),
+            quiet_hours: Vec::new(),
+            wifi_only: vec![WorkKind::ModelDownload, WorkKind::ShardTransfer],
+            check_interval_secs: 60,
+        }
+    }
+}
+
+/// 重任务被拦下的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkBlock {
+    QuietHours,
+    OutsideWindow,
+    /// 该任务类型仅允许在 Wi-Fi 下执行
+    WifiOnly(NetworkType),
+}
+
+impl fmt::Display for WorkBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkBlock::QuietHours => write!(f, "处于安静时段"),
+            WorkBlock::OutsideWindow => write!(f, "不在允许的时间窗口内"),
+            WorkBlock::WifiOnly(network) => write!(f, "仅允许 Wi-Fi，当前网络 {:?}", network),
+        }
+    }
+}
+
+impl WorkScheduleConfig {
+    /// 在给定网络和本地时间下该任务是否被拦下，返回 None 表示允许执行
+    pub fn blocked_at(&self, kind: WorkKind, network: NetworkType, now: NaiveDateTime) -> Option<WorkBlock> {
+        if !self.enabled {
+            return None;
+        }
+        if self.quiet_hours.iter().any(|w| w.contains(now)) {
+            return Some(WorkBlock::QuietHours);
+        }
+        if !self.windows.is_empty() && !self.windows.iter().any(|w| w.contains(now)) {
+            return Some(WorkBlock::OutsideWindow);
+        }
+        if network != NetworkType::WiFi && self.wifi_only.contains(&kind) {
+            return Some(WorkBlock::WifiOnly(network));
+        }
+        None
+    }
+}
+
+/// 等待窗口打开的任务
+#[derive(Debug, Clone)]
+pub struct PendingWork {
+    pub id: u64,
+    pub kind: WorkKind,
+    pub label: String,
+    pub queued_at: SystemTime,
+    pub reason: WorkBlock,
+}
+
+/// 重任务调度器，网络类型取自设备管理器（由节点定期刷新）
+pub struct WorkScheduler {
+    config: RwLock<WorkScheduleConfig>,
+    devices: DeviceManager,
+    pending: Mutex<Vec<PendingWork>>,
+    /// 各任务类型上一次检查的拦截原因，只在状态变化时打印
+    last_block: Mutex<HashMap<WorkKind, WorkBlock>>,
+    next_id: AtomicU64,
+}
+
+impl WorkScheduler {
+    pub fn new(config: WorkScheduleConfig, devices: DeviceManager) -> Self {
+        Self {
+            config: RwLock::new(config),
+            devices,
+            pending: Mutex::new(Vec::new()),
+            last_block: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn config(&self) -> WorkScheduleConfig {
+        self.config.read().clone()
+    }
+
+    /// 替换配置，排队中的任务在下一次检查时按新配置判断
+    pub fn set_config(&self, config: WorkScheduleConfig) {
+        *self.config.write() = config;
+    }
+
+    /// 按当前网络和本地时间检查该任务是否被拦下
+    pub fn blocked(&self, kind: WorkKind) -> Option<WorkBlock> {
+        let network = self.devices.get().network_type;
+        self.config.read().blocked_at(kind, network, Local::now().naive_local())
+    }
+
+    /// 周期性任务的检查：拦截状态变化时打印一次暂停或恢复
+    pub fn is_allowed(&self, kind: WorkKind) -> bool {
+        let block = self.blocked(kind);
+        let mut last = self.last_block.lock();
+        match block {
+            Some(reason) => {
+                if last.insert(kind, reason) != Some(reason) {
+                    println!("[工作时段] 暂停{}：{}", kind, reason);
+                }
+                false
+            }
+            None => {
+                if last.remove(&kind).is_some() {
+                    println!("[工作时段] 恢复{}", kind);
+                }
+                true
+            }
+        }
+    }
+
+    /// 正在排队等待窗口的任务
+    pub fn pending(&self) -> Vec<PendingWork> {
+        self.pending.lock().clone()
+    }
+
+    /// 一次性任务在执行前调用：被拦下时排队，直到窗口打开才返回
+    pub async fn wait_for_window(&self, kind: WorkKind, label: &str) {
+        let Some(reason) = self.blocked(kind) else {
+            return;
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let queued_at = SystemTime::now();
+        self.pending.lock().push(PendingWork {
+            id,
+            kind,
+            label: label.to_string(),
+            queued_at,
+            reason,
+        });
+        println!("[工作时段] {}「{}」已排队：{}", kind, label, reason);
+
+        loop {
+            let interval = self.config.read().check_interval_secs.max(1);
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            match self.blocked(kind) {
+                Some(reason) => {
+                    if let Some(work) = self.pending.lock().iter_mut().find(|w| w.id == id) {
+                        work.reason = reason;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        self.pending.lock().retain(|w| w.id != id);
+        println!(
+            "[工作时段] {}「{}」恢复执行（排队 {}s）",
+            kind,
+            label,
+            queued_at.elapsed().unwrap_or_default().as_secs()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-01-01 是星期一
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_overnight_window_and_quiet_hours() {
+        let config = WorkScheduleConfig {
+            enabled: true,
+            windows: vec![TimeWindow {
+                days: vec![Weekday::Mon],
+                ..TimeWindow::new("22:00", "06:00")
+            }],
+            quiet_hours: vec![TimeWindow::new("02:00", "03:00")],
+            ..Default::default()
+        };
+        let sync = |now| config.blocked_at(WorkKind::GradientSync, NetworkType::WiFi, now);
+        assert_eq!(sync(at(1, 23, 0)), None);
+        // 周一开始的窗口延续到周二凌晨
+        assert_eq!(sync(at(2, 5, 59)), None);
+        assert_eq!(sync(at(2, 2, 30)), Some(WorkBlock::QuietHours));
+        assert_eq!(sync(at(2, 12, 0)), Some(WorkBlock::OutsideWindow));
+        assert_eq!(sync(at(2, 23, 0)), Some(WorkBlock::OutsideWindow));
+    }
+
+    #[test]
+    fn test_wifi_only_applies_per_kind() {
+        let config = WorkScheduleConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let now = at(1, 12, 0);
+        assert_eq!(
+            config.blocked_at(WorkKind::ModelDownload, NetworkType::Cellular5G, now),
+            Some(WorkBlock::WifiOnly(NetworkType::Cellular5G))
+        );
+        assert_eq!(config.blocked_at(WorkKind::GradientSync, NetworkType::Cellular5G, now), None);
+        assert_eq!(config.blocked_at(WorkKind::ModelDownload, NetworkType::WiFi, now), None);
+
+        let disabled = WorkScheduleConfig::default();
+        assert_eq!(disabled.blocked_at(WorkKind::ModelDownload, NetworkType::Unknown, now), None);
+    }
+}
@@ -0,0 +1,227 @@
+//! 边缘接口的请求签名认证
+//!
+//! 节点用持久身份（[`NodeIdentity`](crate::crypto::identity::NodeIdentity)）对每个写请求签名：
+//! - 签名内容为 `方法|路径|时间戳|nonce|请求体 blake3`，通过请求头携带
+//! - 时间戳超出允许偏差或 nonce 重复的请求被拒绝，防止重放
+//! - 节点首次注册需先领取一次性挑战并在注册请求中签名，之后的请求使用登记的公钥验证
+//! - 每个节点按令牌桶限流
+//!
+//! 挑战、nonce 和令牌桶的状态保存在 [`super::storage`] 中。
+
+use crate::crypto::identity::{verify_signature, NodeIdentity};
+use crate::crypto::SolSignature;
+use serde::{Deserialize, Serialize};
+
+pub const HEADER_NODE: &str = "X-Williw-Node";
+pub const HEADER_TIMESTAMP: &str = "X-Williw-Timestamp";
+pub const HEADER_NONCE: &str = "X-Williw-Nonce";
+pub const HEADER_SIGNATURE: &str = "X-Williw-Signature";
+
+/// 允许的时钟偏差，nonce 也只需在这个窗口内保持唯一
+pub const MAX_CLOCK_SKEW_MS: u64 = 300_000;
+/// 注册挑战的有效期
+pub const CHALLENGE_TTL_MS: u64 = 60_000;
+
+/// 认证失败原因
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AuthError {
+    #[error("缺少签名请求头")]
+    Missing,
+
+    #[error("请求签名无效")]
+    BadSignature,
+
+    #[error("请求时间戳超出允许范围: {timestamp_ms}")]
+    Stale { timestamp_ms: u64 },
+
+    #[error("重复请求: nonce {nonce}")]
+    Replay { nonce: String },
+
+    #[error("节点未登记公钥，请先完成注册")]
+    UnknownNode,
+
+    #[error("注册挑战无效或已过期")]
+    BadChallenge,
+
+    #[error("签名节点与请求中的 node_id 不一致")]
+    NodeMismatch,
+
+    #[error("请求过于频繁，{retry_after_ms} 毫秒后重试")]
+    RateLimited { retry_after_ms: u64 },
+}
+
+impl AuthError {
+    /// 对应的 HTTP 状态码
+    pub fn status(&self) -> u16 {
+        match self {
+            AuthError::NodeMismatch => 403,
+            AuthError::RateLimited { .. } => 429,
+            _ => 401,
+        }
+    }
+}
+
+/// 从请求头解析出的签名信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestAuth {
+    /// 节点 ID（身份公钥 base58）
+    pub node_id: String,
+    /// 签名时间（Unix 毫秒）
+    pub timestamp_ms: u64,
+    pub nonce: String,
+    /// 签名（base58）
+    pub signature: String,
+}
+
+/// 待签名内容
+pub fn signing_payload(method: &str, path: &str, timestamp_ms: u64, nonce: &str, body: &[u8]) -> Vec<u8> {
+    format!(
+        "williw-request:{}:{}:{}:{}:{}",
+        method.to_ascii_uppercase(),
+        path,
+        timestamp_ms,
+        nonce,
+        blake3::hash(body).to_hex()
+    )
+    .into_bytes()
+}
+
+/// 节点侧：为请求生成签名头
+pub fn sign_request(
+    identity: &NodeIdentity,
+    method: &str,
+    path: &str,
+    body: &[u8],
+    timestamp_ms: u64,
+    nonce: &str,
+) -> RequestAuth {
+    let signature = identity.sign(&signing_payload(method, path, timestamp_ms, nonce, body));
+    RequestAuth {
+        node_id: signature.pubkey,
+        timestamp_ms,
+        nonce: nonce.to_string(),
+        signature: signature.signature,
+    }
+}
+
+impl RequestAuth {
+    /// 用登记的公钥验证签名和时间戳（nonce 去重由调用方负责）
+    pub fn verify(&self, public_key: &str, method: &str, path: &str, body: &[u8], now_ms: u64) -> Result<(), AuthError> {
+        if self.timestamp_ms.abs_diff(now_ms) > MAX_CLOCK_SKEW_MS {
+            return Err(AuthError::Stale {
+                timestamp_ms: self.timestamp_ms,
+            });
+        }
+        let signature = SolSignature {
+            pubkey: public_key.to_string(),
+            signature: self.signature.clone(),
+        };
+        let payload = signing_payload(method, path, self.timestamp_ms, &self.nonce, body);
+        if verify_signature(&payload, &signature) {
+            Ok(())
+        } else {
+            Err(AuthError::BadSignature)
+        }
+    }
+}
+
+/// 注册挑战
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistrationChallenge {
+    pub challenge: String,
+    pub expires_at_ms: u64,
+}
+
+/// 限流参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// 桶容量（允许的突发请求数）
+    pub burst: u32,
+    /// 每秒补充的令牌数
+    pub per_second: f64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            burst: 30,
+            per_second: 5.0,
+        }
+    }
+}
+
+/// 单个节点的令牌桶
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenBucket {
+    pub tokens: f64,
+    pub updated_ms: u64,
+}
+
+impl TokenBucket {
+    pub fn full(config: &RateLimitConfig, now_ms: u64) -> Self {
+        Self {
+            tokens: config.burst as f64,
+            updated_ms: now_ms,
+        }
+    }
+
+    /// 消耗一个令牌，不足时返回需要等待的毫秒数
+    pub fn take(&mut self, config: &RateLimitConfig, now_ms: u64) -> Result<(), AuthError> {
+        let elapsed = now_ms.saturating_sub(self.updated_ms) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * config.per_second).min(config.burst as f64);
+        self.updated_ms = now_ms.max(self.updated_ms);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let retry_after_ms = ((1.0 - self.tokens) / config.per_second.max(f64::EPSILON) * 1000.0).ceil() as u64;
+            Err(AuthError::RateLimited { retry_after_ms })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::node::MemoryKeystore;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_signed_request_verifies_only_unmodified() {
+        let identity = NodeIdentity::with_keystore(Arc::new(MemoryKeystore::default())).unwrap();
+        let body = br#"{"node_id":"n"}"#;
+        let now = 1_700_000_000_000;
+        let auth = sign_request(&identity, "post", "/api/nodes/heartbeat", body, now, "abc");
+        assert_eq!(auth.node_id, identity.node_id());
+
+        assert!(auth.verify(&auth.node_id, "POST", "/api/nodes/heartbeat", body, now + 1_000).is_ok());
+        assert_eq!(
+            auth.verify(&auth.node_id, "POST", "/api/nodes/heartbeat", b"{}", now),
+            Err(AuthError::BadSignature)
+        );
+        assert_eq!(
+            auth.verify(&auth.node_id, "POST", "/api/tasks/lease", body, now),
+            Err(AuthError::BadSignature)
+        );
+        assert!(matches!(
+            auth.verify(&auth.node_id, "POST", "/api/nodes/heartbeat", body, now + MAX_CLOCK_SKEW_MS + 1),
+            Err(AuthError::Stale { .. })
+        ));
+    }
+
+    #[test]
+    fn test_token_bucket_limits_bursts() {
+        let config = RateLimitConfig {
+            burst: 2,
+            per_second: 1.0,
+        };
+        let mut bucket = TokenBucket::full(&config, 0);
+        assert!(bucket.take(&config, 0).is_ok());
+        assert!(bucket.take(&config, 0).is_ok());
+        assert_eq!(
+            bucket.take(&config, 0),
+            Err(AuthError::RateLimited { retry_after_ms: 1_000 })
+        );
+        assert!(bucket.take(&config, 1_000).is_ok());
+    }
+}
@@ -0,0 +1,172 @@
+//! 边缘服务器：Cloudflare Worker 入口
+//!
+//! 对外提供节点注册、心跳、查询、任务分配和任务队列的 HTTP 接口，状态全部交给
+//! [`WorkerDurableObject`](super::storage::WorkerDurableObject) 维护。
+//! 写接口要求节点签名（见 [`super::auth`]），签名信息随请求转发给注册表实例验证。
+//! 注册和分配请求没有携带位置时，用 Cloudflare 提供的请求来源坐标补全。
+//! 节点可通过 `/api/nodes/stream` 建立 WebSocket 推送连接（见 [`super::push`]），
+//! 治理公告由持有 `ADMIN_TOKEN` 的管理端通过 `/api/governance/announce` 广播。
+
+use super::auth::{AuthError, RequestAuth, HEADER_NODE, HEADER_NONCE, HEADER_SIGNATURE, HEADER_TIMESTAMP};
+use super::registry::GeoLocation;
+use super::storage::{auth_error, AUTH_HEADER, LOCATION_HEADER, PUBLIC_PATH_HEADER};
+use worker::*;
+
+/// wrangler.toml 中注册表 Durable Object 的绑定名
+pub const REGISTRY_BINDING: &str = "NODE_REGISTRY";
+/// 全局唯一的注册表实例名
+const REGISTRY_INSTANCE: &str = "global";
+
+/// 需要签名的写接口及其在注册表实例中的内部路径
+fn signed_route(path: &str) -> Option<&'static str> {
+    match path {
+        "/api/nodes/register" => Some("/register"),
+        "/api/nodes/heartbeat" => Some("/heartbeat"),
+        "/api/tasks/assign" => Some("/assign"),
+        "/api/tasks/release" => Some("/release"),
+        "/api/tasks" => Some("/tasks"),
+        "/api/tasks/lease" => Some("/tasks/lease"),
+        "/api/tasks/complete" => Some("/tasks/complete"),
+        "/api/tasks/fail" => Some("/tasks/fail"),
+        _ => None,
+    }
+}
+
+pub struct EdgeServerWorker {
+    env: Env,
+}
+
+impl EdgeServerWorker {
+    pub fn new(env: Env) -> Self {
+        Self { env }
+    }
+
+    fn registry(&self) -> Result<Stub> {
+        self.env
+            .durable_object(REGISTRY_BINDING)?
+            .id_from_name(REGISTRY_INSTANCE)?
+            .get_stub()
+    }
+
+    pub async fn handle(&self, mut req: Request) -> Result<Response> {
+        let path = req.path();
+        match (req.method(), path.as_str()) {
+            (Method::Get, "/health") => Response::ok("ok"),
+            (Method::Get, "/api/nodes") => {
+                let query = req.url()?.query().map(|q| format!("?{q}")).unwrap_or_default();
+                self.forward(Method::Get, &format!("/nodes{query}"), Headers::new(), None).await
+            }
+            (Method::Get, "/api/tasks/dead") => self.forward(Method::Get, "/tasks/dead", Headers::new(), None).await,
+            (Method::Get, "/api/nodes/stream") => {
+                let auth = match request_auth(req.headers()) {
+                    Ok(auth) => auth,
+                    Err(e) => return auth_error(&e),
+                };
+                let headers = Headers::new();
+                headers.set(AUTH_HEADER, &serde_json::to_string(&auth)?)?;
+                headers.set(PUBLIC_PATH_HEADER, "/api/nodes/stream")?;
+                headers.set("Upgrade", &req.headers().get("Upgrade")?.unwrap_or_default())?;
+                let query = req.url()?.query().map(|q| format!("?{q}")).unwrap_or_default();
+                self.forward(Method::Get, &format!("/stream{query}"), headers, None).await
+            }
+            (Method::Post, "/api/governance/announce") => {
+                if !self.is_admin(&req)? {
+                    return Response::error("需要管理令牌", 401);
+                }
+                let body = req.bytes().await?;
+                self.forward(Method::Post, "/governance", Headers::new(), Some(body)).await
+            }
+            // 注册握手第一步：领取一次性挑战
+            (Method::Post, "/api/nodes/challenge") => {
+                let body = req.bytes().await?;
+                self.forward(Method::Post, "/challenge", Headers::new(), Some(body)).await
+            }
+            (Method::Post, public_path) => {
+                let Some(internal_path) = signed_route(public_path) else {
+                    return Response::error("Not Found", 404);
+                };
+                let auth = match request_auth(req.headers()) {
+                    Ok(auth) => auth,
+                    Err(e) => return auth_error(&e),
+                };
+                let headers = Headers::new();
+                headers.set(AUTH_HEADER, &serde_json::to_string(&auth)?)?;
+                headers.set(PUBLIC_PATH_HEADER, public_path)?;
+                if let Some(location) = request_location(&req) {
+                    headers.set(LOCATION_HEADER, &serde_json::to_string(&location)?)?;
+                }
+                // 请求体原样转发，签名覆盖的是原始字节
+                let body = req.bytes().await?;
+                self.forward(Method::Post, internal_path, headers, Some(body)).await
+            }
+            _ => Response::error("Not Found", 404),
+        }
+    }
+
+    /// 校验 `Authorization: Bearer <ADMIN_TOKEN>`
+    fn is_admin(&self, req: &Request) -> Result<bool> {
+        let Ok(token) = self.env.secret("ADMIN_TOKEN") else {
+            return Ok(false);
+        };
+        let expected = token.to_string();
+        let presented = req.headers().get("Authorization")?.unwrap_or_default();
+        let presented = presented.strip_prefix("Bearer ").unwrap_or_default();
+        // 逐字节比较全部内容，避免泄露匹配长度
+        Ok(!expected.is_empty()
+            && presented.len() == expected.len()
+            && presented
+                .bytes()
+                .zip(expected.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0)
+    }
+
+    /// 把请求转发到注册表实例的内部路径
+    async fn forward(&self, method: Method, path: &str, headers: Headers, body: Option<Vec<u8>>) -> Result<Response> {
+        let mut init = RequestInit::new();
+        init.with_method(method);
+        if let Some(body) = body {
+            headers.set("Content-Type", "application/json")?;
+            init.with_body(Some(js_sys::Uint8Array::from(body.as_slice()).into()));
+        }
+        init.with_headers(headers);
+        let request = Request::new_with_init(&format!("https://registry{path}"), &init)?;
+        self.registry()?.fetch_with_request(request).await
+    }
+}
+
+/// 从请求头读取节点签名
+fn request_auth(headers: &Headers) -> std::result::Result<RequestAuth, AuthError> {
+    let header = |name: &str| headers.get(name).ok().flatten().filter(|v| !v.is_empty());
+    let (Some(node_id), Some(timestamp), Some(nonce), Some(signature)) = (
+        header(HEADER_NODE),
+        header(HEADER_TIMESTAMP),
+        header(HEADER_NONCE),
+        header(HEADER_SIGNATURE),
+    ) else {
+        return Err(AuthError::Missing);
+    };
+    let timestamp_ms = timestamp.parse().map_err(|_| AuthError::Missing)?;
+    Ok(RequestAuth {
+        node_id,
+        timestamp_ms,
+        nonce,
+        signature,
+    })
+}
+
+/// Cloudflare 识别的请求来源坐标
+fn request_location(req: &Request) -> Option<GeoLocation> {
+    let cf = req.cf()?;
+    let (latitude, longitude) = cf.coordinates()?;
+    Some(GeoLocation {
+        latitude: latitude as f64,
+        longitude: longitude as f64,
+        country: cf.country(),
+    })
+}
+
+#[event(fetch)]
+pub async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
+    EdgeServerWorker::new(env).handle(req).await
+}
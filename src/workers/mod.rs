@@ -0,0 +1,25 @@
+//! Cloudflare Workers 边缘部署（`workers` 特性）
+//!
+//! - [`registry`]：节点注册表的核心逻辑，与平台无关
+//! - [`queue`]：任务队列的租约、重试与死信规则
+//! - [`auth`]：节点请求签名、防重放与限流
+//! - [`push`]：向节点实时推送事件的协议与重连策略
+//! - [`storage`]：Durable Object 实现的注册表
+//! - [`edge_server`]：Worker 入口与 HTTP 路由
+//!
+//! 使用 `worker-build --release --features workers` 构建，部署配置见仓库根目录的 `wrangler.toml`。
+
+pub mod auth;
+pub mod push;
+pub mod queue;
+pub mod registry;
+
+#[cfg(target_arch = "wasm32")]
+pub mod edge_server;
+#[cfg(target_arch = "wasm32")]
+pub mod storage;
+
+#[cfg(target_arch = "wasm32")]
+pub use edge_server::EdgeServerWorker;
+#[cfg(target_arch = "wasm32")]
+pub use storage::WorkerDurableObject;
@@ -0,0 +1,178 @@
+//! 边缘向节点的实时推送
+//!
+//! 节点通过签名的 WebSocket 连接 `/api/nodes/stream?since=<seq>` 订阅事件，
+//! 注册表实例用 Durable Object 的休眠 WebSocket 维持连接。事件按递增序号写入事件日志，
+//! 节点断线重连时带上最后收到的序号，服务端补发其后的事件，再发送 `replay_complete`。
+//! 日志只保留 [`EVENT_RETENTION_MS`]，缺口超出保留范围时发送 `replay_gap`，节点应通过 HTTP 接口重新同步。
+//!
+//! 这里定义协议消息和节点侧的重连/去重状态，连接本身由各平台的 WebSocket 客户端负责。
+
+use super::queue::QueuedTask;
+use super::registry::Assignment;
+use serde::{Deserialize, Serialize};
+
+/// 事件日志保留时长
+pub const EVENT_RETENTION_MS: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PushEventKind {
+    /// 任务已直接分配给该节点
+    TaskAssigned { assignment: Assignment },
+    /// 队列中有新任务可以领取
+    TaskAvailable { task: QueuedTask },
+    /// 任务已从该节点收回（租约超时或节点过期），节点应停止执行
+    TaskReassigned {
+        task_id: String,
+        previous_node_id: String,
+        reason: String,
+    },
+    /// 治理公告，原样转发
+    Governance { announcement: serde_json::Value },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushEvent {
+    pub seq: u64,
+    /// 目标节点，为空表示广播
+    #[serde(default)]
+    pub target: Option<String>,
+    pub created_at_ms: u64,
+    #[serde(flatten)]
+    pub kind: PushEventKind,
+}
+
+impl PushEvent {
+    /// 事件是否应投递给该节点
+    pub fn is_for(&self, node_id: &str) -> bool {
+        self.target.as_deref().is_none_or(|target| target == node_id)
+    }
+}
+
+/// 服务端发给节点的消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Event { event: PushEvent },
+    /// 补发结束，之后都是实时事件
+    ReplayComplete { last_seq: u64 },
+    /// 请求的起点早于日志中最早的事件
+    ReplayGap { oldest_seq: u64 },
+    Pong,
+}
+
+/// 节点发给服务端的消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Ping,
+}
+
+/// 节点侧的事件游标：记录最后处理的序号并丢弃重复事件
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventCursor {
+    last_seq: u64,
+}
+
+impl EventCursor {
+    pub fn new(last_seq: u64) -> Self {
+        Self { last_seq }
+    }
+
+    /// 重连时作为 `since` 参数
+    pub fn since(&self) -> u64 {
+        self.last_seq
+    }
+
+    /// 事件是新的则推进游标并返回 true；补发与实时推送重叠时旧事件返回 false
+    pub fn accept(&mut self, event: &PushEvent) -> bool {
+        if event.seq <= self.last_seq {
+            return false;
+        }
+        self.last_seq = event.seq;
+        true
+    }
+}
+
+/// 断线重连的指数退避
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// 随机抖动比例（0~1），避免大量节点同时重连
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 500,
+            max_delay_ms: 60_000,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// 第 `attempt` 次（从 0 开始）重连前的等待时间，`random` 取 [0, 1)
+    pub fn delay_ms(&self, attempt: u32, random: f64) -> u64 {
+        let base = self
+            .initial_delay_ms
+            .saturating_mul(1u64 << attempt.min(20))
+            .min(self.max_delay_ms);
+        let spread = base as f64 * self.jitter.clamp(0.0, 1.0);
+        (base as f64 - spread + 2.0 * spread * random.clamp(0.0, 1.0)).round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn governance(seq: u64, target: Option<&str>) -> PushEvent {
+        PushEvent {
+            seq,
+            target: target.map(str::to_string),
+            created_at_ms: 0,
+            kind: PushEventKind::Governance {
+                announcement: serde_json::json!({ "proposal": seq }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_cursor_drops_replayed_duplicates() {
+        let mut cursor = EventCursor::default();
+        assert!(cursor.accept(&governance(1, None)));
+        assert!(cursor.accept(&governance(3, Some("node-a"))));
+        assert!(!cursor.accept(&governance(2, None)));
+        assert!(!cursor.accept(&governance(3, None)));
+        assert_eq!(cursor.since(), 3);
+
+        assert!(governance(3, Some("node-a")).is_for("node-a"));
+        assert!(!governance(3, Some("node-a")).is_for("node-b"));
+        assert!(governance(1, None).is_for("node-b"));
+
+        let message = serde_json::to_value(ServerMessage::Event {
+            event: governance(5, None),
+        })
+        .unwrap();
+        assert_eq!(message["type"], "event");
+        assert_eq!(message["event"]["kind"], "governance");
+    }
+
+    #[test]
+    fn test_reconnect_backoff_is_capped() {
+        let policy = ReconnectPolicy {
+            initial_delay_ms: 1_000,
+            max_delay_ms: 8_000,
+            jitter: 0.0,
+        };
+        let delays: Vec<u64> = (0..5).map(|attempt| policy.delay_ms(attempt, 0.5)).collect();
+        assert_eq!(delays, vec![1_000, 2_000, 4_000, 8_000, 8_000]);
+
+        let jittered = ReconnectPolicy::default();
+        assert_eq!(jittered.delay_ms(0, 0.0), 400);
+        assert_eq!(jittered.delay_ms(0, 1.0), 600);
+    }
+}
@@ -0,0 +1,197 @@
+//! 任务队列核心逻辑
+//!
+//! 提交的任务先进入待领取状态；节点领取时获得带可见性超时的租约，
+//! 租约到期未完成或节点报告失败都计为一次尝试，未达上限则重新排队，否则移入死信列表。
+//! 持久化由 [`super::storage`] 完成。
+
+use super::registry::{GeoLocation, NodeRecord, TaskRequirements};
+use serde::{Deserialize, Serialize};
+
+/// 默认最大尝试次数
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// 默认租约可见性超时
+pub const DEFAULT_VISIBILITY_TIMEOUT_MS: u64 = 300_000;
+
+/// 队列配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueConfig {
+    pub max_attempts: u32,
+    pub visibility_timeout_ms: u64,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            visibility_timeout_ms: DEFAULT_VISIBILITY_TIMEOUT_MS,
+        }
+    }
+}
+
+/// 提交到队列的任务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskRequest {
+    pub task_id: String,
+    #[serde(default)]
+    pub requirements: TaskRequirements,
+    #[serde(default)]
+    pub location: Option<GeoLocation>,
+    /// 交给节点的任务内容，队列不解析
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    /// 覆盖队列默认的最大尝试次数
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TaskState {
+    Pending,
+    Leased { node_id: String, lease_expires_ms: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub request: TaskRequest,
+    #[serde(flatten)]
+    pub state: TaskState,
+    /// 已领取次数
+    pub attempts: u32,
+    pub enqueued_at_ms: u64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// 一次失败后的去向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOutcome {
+    Retry,
+    DeadLetter,
+}
+
+/// 死信列表中的任务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub task: QueuedTask,
+    pub dead_at_ms: u64,
+}
+
+impl QueuedTask {
+    pub fn new(request: TaskRequest, now_ms: u64) -> Self {
+        Self {
+            request,
+            state: TaskState::Pending,
+            attempts: 0,
+            enqueued_at_ms: now_ms,
+            last_error: None,
+        }
+    }
+
+    pub fn task_id(&self) -> &str {
+        &self.request.task_id
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state == TaskState::Pending
+    }
+
+    /// 当前租约的持有节点
+    pub fn leased_to(&self) -> Option<&str> {
+        match &self.state {
+            TaskState::Leased { node_id, .. } => Some(node_id),
+            TaskState::Pending => None,
+        }
+    }
+
+    pub fn lease_expired(&self, now_ms: u64) -> bool {
+        matches!(self.state, TaskState::Leased { lease_expires_ms, .. } if now_ms >= lease_expires_ms)
+    }
+
+    pub fn lease(&mut self, node_id: &str, now_ms: u64, visibility_timeout_ms: u64) {
+        self.attempts += 1;
+        self.state = TaskState::Leased {
+            node_id: node_id.to_string(),
+            lease_expires_ms: now_ms + visibility_timeout_ms,
+        };
+    }
+
+    /// 记录一次失败（包括租约超时），决定重新排队还是进入死信
+    pub fn fail(&mut self, reason: impl Into<String>, config: &QueueConfig) -> FailureOutcome {
+        self.last_error = Some(reason.into());
+        self.state = TaskState::Pending;
+        let max_attempts = self.request.max_attempts.unwrap_or(config.max_attempts).max(1);
+        if self.attempts >= max_attempts {
+            FailureOutcome::DeadLetter
+        } else {
+            FailureOutcome::Retry
+        }
+    }
+}
+
+/// 为节点挑选最早入队且资源要求匹配的待领取任务
+pub fn next_for_node<'a>(tasks: &'a [QueuedTask], node: &NodeRecord) -> Option<&'a QueuedTask> {
+    tasks
+        .iter()
+        .filter(|task| task.is_pending() && node.meets(&task.request.requirements))
+        .min_by_key(|task| task.enqueued_at_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(task_id: &str, requires_gpu: bool) -> TaskRequest {
+        TaskRequest {
+            task_id: task_id.to_string(),
+            requirements: TaskRequirements {
+                requires_gpu,
+                ..Default::default()
+            },
+            location: None,
+            payload: serde_json::Value::Null,
+            max_attempts: None,
+        }
+    }
+
+    #[test]
+    fn test_expired_lease_retries_then_dead_letters() {
+        let config = QueueConfig {
+            max_attempts: 2,
+            visibility_timeout_ms: 1_000,
+        };
+        let mut task = QueuedTask::new(request("t1", false), 0);
+
+        task.lease("node-a", 10, config.visibility_timeout_ms);
+        assert_eq!(task.leased_to(), Some("node-a"));
+        assert!(!task.lease_expired(500));
+        assert!(task.lease_expired(1_010));
+        assert_eq!(task.fail("租约超时", &config), FailureOutcome::Retry);
+        assert!(task.is_pending());
+
+        task.lease("node-b", 2_000, config.visibility_timeout_ms);
+        assert_eq!(task.fail("执行失败", &config), FailureOutcome::DeadLetter);
+        assert_eq!(task.last_error.as_deref(), Some("执行失败"));
+    }
+
+    #[test]
+    fn test_next_for_node_matches_requirements_in_order() {
+        let node: NodeRecord = serde_json::from_value(serde_json::json!({
+            "node_id": "cpu-node",
+            "location": { "latitude": 0.0, "longitude": 0.0 },
+            "capacity": { "cpu_cores": 4, "memory_mb": 4096 },
+            "registered_at_ms": 0,
+            "last_heartbeat_ms": 0
+        }))
+        .unwrap();
+        let mut leased = QueuedTask::new(request("leased", false), 0);
+        leased.lease("other", 0, 1_000);
+        let tasks = vec![
+            QueuedTask::new(request("first", false), 5),
+            leased,
+            QueuedTask::new(request("needs-gpu", true), 1),
+            QueuedTask::new(request("later", false), 9),
+        ];
+        assert_eq!(next_for_node(&tasks, &node).map(QueuedTask::task_id), Some("first"));
+    }
+}
@@ -0,0 +1,442 @@
+//! 节点注册表核心逻辑
+//!
+//! 与存储无关：记录结构、心跳存活判断、地理分桶和任务分配的选点规则都在这里，
+//! Durable Object（[`super::storage`]）只负责读写和事务边界。
+
+use serde::{Deserialize, Serialize};
+
+/// 默认心跳超时：超过该时间没有心跳的节点视为离线
+pub const DEFAULT_HEARTBEAT_TTL_MS: u64 = 90_000;
+/// 默认地理分桶大小（经纬度）
+pub const DEFAULT_BUCKET_DEGREES: f64 = 10.0;
+/// 地球平均半径
+const EARTH_RADIUS_KM: f64 = 6371.0;
+/// 距离得分减半的距离
+const DISTANCE_SCALE_KM: f64 = 2000.0;
+
+fn default_max_tasks() -> u32 {
+    1
+}
+
+fn default_min_cpu_cores() -> u32 {
+    1
+}
+
+/// 注册表配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegistryConfig {
+    pub heartbeat_ttl_ms: u64,
+    pub bucket_degrees: f64,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_ttl_ms: DEFAULT_HEARTBEAT_TTL_MS,
+            bucket_degrees: DEFAULT_BUCKET_DEGREES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeoLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default)]
+    pub country: Option<String>,
+}
+
+impl GeoLocation {
+    /// 两点间的大圆距离（haversine）
+    pub fn distance_km(&self, other: &GeoLocation) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// 节点可承担的计算资源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeCapacity {
+    pub cpu_cores: u32,
+    pub memory_mb: u64,
+    #[serde(default)]
+    pub has_gpu: bool,
+    /// 可同时执行的任务数
+    #[serde(default = "default_max_tasks")]
+    pub max_tasks: u32,
+}
+
+/// 节点注册请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeRegistration {
+    pub node_id: String,
+    pub location: GeoLocation,
+    pub capacity: NodeCapacity,
+    /// 节点对外地址（可选）
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+/// 注册表中保存的节点记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeRecord {
+    #[serde(flatten)]
+    pub registration: NodeRegistration,
+    pub registered_at_ms: u64,
+    pub last_heartbeat_ms: u64,
+    /// 已分配且未释放的任务数
+    #[serde(default)]
+    pub active_tasks: u32,
+}
+
+impl NodeRecord {
+    pub fn node_id(&self) -> &str {
+        &self.registration.node_id
+    }
+
+    pub fn is_alive(&self, now_ms: u64, ttl_ms: u64) -> bool {
+        now_ms.saturating_sub(self.last_heartbeat_ms) <= ttl_ms
+    }
+
+    pub fn has_capacity(&self) -> bool {
+        self.active_tasks < self.registration.capacity.max_tasks
+    }
+
+    /// 负载比例（0 表示空闲）
+    pub fn load(&self) -> f64 {
+        self.active_tasks as f64 / self.registration.capacity.max_tasks.max(1) as f64
+    }
+
+    pub fn meets(&self, requirements: &TaskRequirements) -> bool {
+        let capacity = &self.registration.capacity;
+        capacity.cpu_cores >= requirements.min_cpu_cores
+            && capacity.memory_mb >= requirements.min_memory_mb
+            && (capacity.has_gpu || !requirements.requires_gpu)
+    }
+}
+
+/// 心跳请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub node_id: String,
+    /// 节点自报的运行中任务数，给出时覆盖注册表的计数
+    #[serde(default)]
+    pub active_tasks: Option<u32>,
+}
+
+/// 地理分桶：按固定经纬度网格划分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GeoBucket {
+    pub lat: i32,
+    pub lon: i32,
+}
+
+impl GeoBucket {
+    pub fn of(location: &GeoLocation, degrees: f64) -> Self {
+        let lat = location.latitude.clamp(-90.0, 90.0);
+        let lon = location.longitude.clamp(-180.0, 180.0);
+        Self {
+            lat: (lat / degrees).floor() as i32,
+            lon: (lon / degrees).floor() as i32,
+        }
+    }
+
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.lat, self.lon)
+    }
+
+    /// 以自身为中心、半径 `ring` 格的所有分桶（经度方向环绕，纬度方向截断），自身在最前
+    pub fn around(&self, ring: i32, degrees: f64) -> Vec<GeoBucket> {
+        let lon_cells = (360.0 / degrees).ceil() as i32;
+        let min_lon = (-180.0 / degrees).floor() as i32;
+        let min_lat = (-90.0 / degrees).floor() as i32;
+        let max_lat = (90.0 / degrees).floor() as i32;
+        let mut buckets = vec![*self];
+        for d_lat in -ring..=ring {
+            for d_lon in -ring..=ring {
+                if d_lat == 0 && d_lon == 0 {
+                    continue;
+                }
+                let lat = self.lat + d_lat;
+                if lat < min_lat || lat > max_lat {
+                    continue;
+                }
+                let lon = (self.lon + d_lon - min_lon).rem_euclid(lon_cells) + min_lon;
+                let bucket = GeoBucket { lat, lon };
+                if !buckets.contains(&bucket) {
+                    buckets.push(bucket);
+                }
+            }
+        }
+        buckets
+    }
+}
+
+/// 任务对节点的资源要求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskRequirements {
+    #[serde(default = "default_min_cpu_cores")]
+    pub min_cpu_cores: u32,
+    #[serde(default)]
+    pub min_memory_mb: u64,
+    #[serde(default)]
+    pub requires_gpu: bool,
+}
+
+impl Default for TaskRequirements {
+    fn default() -> Self {
+        Self {
+            min_cpu_cores: default_min_cpu_cores(),
+            min_memory_mb: 0,
+            requires_gpu: false,
+        }
+    }
+}
+
+/// 节点匹配策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchingStrategy {
+    /// 只看负载
+    #[default]
+    LeastLoaded,
+    /// 综合距离、负载和资源余量
+    Geography,
+}
+
+/// 地理匹配各项得分的权重
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchWeights {
+    pub distance: f64,
+    pub load: f64,
+    pub capability: f64,
+}
+
+impl Default for MatchWeights {
+    fn default() -> Self {
+        Self {
+            distance: 0.6,
+            load: 0.3,
+            capability: 0.1,
+        }
+    }
+}
+
+/// 匹配得分明细，各项均在 0~1 之间，越大越好
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchScore {
+    /// 与参考位置的距离，没有参考位置时为空
+    pub distance_km: Option<f64>,
+    pub distance_score: f64,
+    pub load_score: f64,
+    pub capability_score: f64,
+    /// 加权总分（没有参考位置时不计距离项，其余权重按比例放大）
+    pub total: f64,
+}
+
+/// 任务分配请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssignRequest {
+    pub task_id: String,
+    #[serde(default)]
+    pub requirements: TaskRequirements,
+    /// 希望就近分配的位置，缺省时在全部节点中选择
+    #[serde(default)]
+    pub location: Option<GeoLocation>,
+    /// 任务数据所在位置，地理匹配时优先以此计算距离
+    #[serde(default)]
+    pub data_source: Option<GeoLocation>,
+    #[serde(default)]
+    pub strategy: MatchingStrategy,
+}
+
+impl AssignRequest {
+    /// 地理匹配的参考位置：数据源优先，其次是提交方
+    pub fn origin(&self) -> Option<&GeoLocation> {
+        self.data_source.as_ref().or(self.location.as_ref())
+    }
+}
+
+/// 分配结果（按任务 ID 幂等）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub task_id: String,
+    pub node_id: String,
+    pub assigned_at_ms: u64,
+    pub strategy: MatchingStrategy,
+    /// 地理匹配时的得分明细
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<MatchScore>,
+}
+
+/// 在候选节点中选出负载最低的存活节点，负载相同时选最近有心跳的
+pub fn select_node<'a>(
+    candidates: impl IntoIterator<Item = &'a NodeRecord>,
+    requirements: &TaskRequirements,
+    now_ms: u64,
+    ttl_ms: u64,
+) -> Option<&'a NodeRecord> {
+    candidates
+        .into_iter()
+        .filter(|node| node.is_alive(now_ms, ttl_ms) && node.has_capacity() && node.meets(requirements))
+        .min_by(|a, b| {
+            a.load()
+                .total_cmp(&b.load())
+                .then(b.last_heartbeat_ms.cmp(&a.last_heartbeat_ms))
+        })
+}
+
+/// 计算节点相对参考位置的匹配得分
+pub fn score_node(
+    node: &NodeRecord,
+    requirements: &TaskRequirements,
+    origin: Option<&GeoLocation>,
+    weights: &MatchWeights,
+) -> MatchScore {
+    let distance_km = origin.map(|origin| origin.distance_km(&node.registration.location));
+    let distance_score = distance_km.map_or(0.0, |d| 1.0 / (1.0 + d / DISTANCE_SCALE_KM));
+    let load_score = (1.0 - node.load()).clamp(0.0, 1.0);
+
+    // 资源余量：节点资源超出任务要求的比例
+    let capacity = &node.registration.capacity;
+    let headroom = |have: f64, need: f64| if have > 0.0 { (1.0 - need / have).clamp(0.0, 1.0) } else { 0.0 };
+    let capability_score = (headroom(capacity.cpu_cores as f64, requirements.min_cpu_cores as f64)
+        + headroom(capacity.memory_mb as f64, requirements.min_memory_mb as f64))
+        / 2.0;
+
+    let distance_weight = if distance_km.is_some() { weights.distance } else { 0.0 };
+    let weight_sum = distance_weight + weights.load + weights.capability;
+    let total = if weight_sum > 0.0 {
+        (distance_weight * distance_score + weights.load * load_score + weights.capability * capability_score)
+            / weight_sum
+    } else {
+        0.0
+    };
+    MatchScore {
+        distance_km,
+        distance_score,
+        load_score,
+        capability_score,
+        total,
+    }
+}
+
+/// 按地理匹配得分从高到低排列可用节点（已过滤离线、满载和不满足要求的节点）
+pub fn rank_nodes<'a>(
+    candidates: impl IntoIterator<Item = &'a NodeRecord>,
+    requirements: &TaskRequirements,
+    origin: Option<&GeoLocation>,
+    weights: &MatchWeights,
+    now_ms: u64,
+    ttl_ms: u64,
+) -> Vec<(&'a NodeRecord, MatchScore)> {
+    let mut ranked: Vec<_> = candidates
+        .into_iter()
+        .filter(|node| node.is_alive(now_ms, ttl_ms) && node.has_capacity() && node.meets(requirements))
+        .map(|node| (node, score_node(node, requirements, origin, weights)))
+        .collect();
+    ranked.sort_by(|(_, a), (_, b)| b.total.total_cmp(&a.total));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, latitude: f64, longitude: f64, heartbeat: u64, active_tasks: u32) -> NodeRecord {
+        NodeRecord {
+            registration: NodeRegistration {
+                node_id: id.to_string(),
+                location: GeoLocation {
+                    latitude,
+                    longitude,
+                    country: None,
+                },
+                capacity: NodeCapacity {
+                    cpu_cores: 4,
+                    memory_mb: 8192,
+                    has_gpu: false,
+                    max_tasks: 2,
+                },
+                endpoint: None,
+            },
+            registered_at_ms: 0,
+            last_heartbeat_ms: heartbeat,
+            active_tasks,
+        }
+    }
+
+    #[test]
+    fn test_geo_buckets_wrap_longitude() {
+        let tokyo = GeoLocation {
+            latitude: 35.6,
+            longitude: 139.7,
+            country: Some("JP".into()),
+        };
+        let bucket = GeoBucket::of(&tokyo, 10.0);
+        assert_eq!(bucket, GeoBucket { lat: 3, lon: 13 });
+        assert_eq!(bucket.around(1, 10.0).len(), 9);
+
+        // 日期变更线两侧互为邻居
+        let east = GeoBucket { lat: 0, lon: 17 };
+        assert!(east.around(1, 10.0).contains(&GeoBucket { lat: 0, lon: -18 }));
+        // 极地不越界
+        assert_eq!(GeoBucket { lat: 9, lon: 0 }.around(1, 10.0).len(), 6);
+    }
+
+    #[test]
+    fn test_select_node_prefers_alive_idle_nodes() {
+        let ttl = DEFAULT_HEARTBEAT_TTL_MS;
+        let now = 1_000_000;
+        let nodes = [
+            node("stale", 0.0, 0.0, now - ttl - 1, 0),
+            node("busy", 0.0, 0.0, now, 1),
+            node("full", 0.0, 0.0, now, 2),
+            node("idle", 0.0, 0.0, now - 10, 0),
+        ];
+        let chosen = select_node(&nodes, &TaskRequirements::default(), now, ttl).unwrap();
+        assert_eq!(chosen.node_id(), "idle");
+
+        let gpu = TaskRequirements {
+            requires_gpu: true,
+            ..Default::default()
+        };
+        assert!(select_node(&nodes, &gpu, now, ttl).is_none());
+    }
+
+    #[test]
+    fn test_geography_ranking_prefers_nearby_nodes() {
+        let shanghai = GeoLocation {
+            latitude: 31.23,
+            longitude: 121.47,
+            country: None,
+        };
+        let beijing = GeoLocation {
+            latitude: 39.9,
+            longitude: 116.4,
+            country: None,
+        };
+        let distance = shanghai.distance_km(&beijing);
+        assert!((distance - 1067.0).abs() < 10.0, "上海到北京约 1067 公里，实际 {}", distance);
+
+        let now = 1_000_000;
+        let nodes = [
+            node("frankfurt", 50.1, 8.7, now, 0),
+            node("hangzhou", 30.3, 120.2, now, 1),
+            node("tokyo", 35.7, 139.7, now, 0),
+        ];
+        let weights = MatchWeights::default();
+        let ranked = rank_nodes(&nodes, &TaskRequirements::default(), Some(&shanghai), &weights, now, 1_000);
+        let order: Vec<_> = ranked.iter().map(|(node, _)| node.node_id()).collect();
+        assert_eq!(order, vec!["hangzhou", "tokyo", "frankfurt"]);
+        assert!(ranked[0].1.distance_km.unwrap() < 200.0);
+
+        // 没有参考位置时只比较负载和资源
+        let ranked = rank_nodes(&nodes, &TaskRequirements::default(), None, &weights, now, 1_000);
+        assert_eq!(ranked.last().unwrap().0.node_id(), "hangzhou");
+        assert!(ranked[0].1.distance_km.is_none());
+    }
+}
@@ -0,0 +1,889 @@
+//! 基于 Durable Object 的节点注册表
+//!
+//! 所有节点共享一个注册表实例（见 [`super::edge_server`]），实例内请求串行执行，
+//! 存储读写都在同一次请求里完成，因此任务分配不会把同一任务分给两个节点，
+//! 也不会让节点超出 `max_tasks`。存储布局：
+//!
+//! - `node:{id}`：[`NodeRecord`]
+//! - `geo:{bucket}:{id}`：地理分桶索引，值为节点 ID
+//! - `assign:{task_id}`：[`Assignment`]
+//! - `task:{task_id}`：队列中的 [`QueuedTask`]
+//! - `dead:{task_id}`：死信 [`DeadLetter`]
+//! - `key:{id}`：完成注册握手的节点公钥；`challenge:{id}`、`nonce:{id}:{nonce}`、`rate:{id}`：认证状态
+//!
+//! - `event:{seq}`、`event_seq`：推送事件日志（见 [`super::push`]）
+//!
+//! 写请求在分发前按 [`super::auth`] 验证签名、防重放并限流。
+//! 节点的推送连接使用休眠 WebSocket，按节点 ID 打标签，实例休眠后连接仍然保持。
+//! 过期清理由 alarm 按心跳超时周期触发，离线节点及其未完成的分配一并删除，任务可重新分配；
+//! 队列任务的租约到期时 alarm 也会提前触发，把任务重新排队或移入死信。
+
+use super::auth::{AuthError, RateLimitConfig, RegistrationChallenge, RequestAuth, TokenBucket, CHALLENGE_TTL_MS, MAX_CLOCK_SKEW_MS};
+use super::push::{ClientMessage, PushEvent, PushEventKind, ServerMessage, EVENT_RETENTION_MS};
+use super::queue::{next_for_node, DeadLetter, FailureOutcome, QueueConfig, QueuedTask, TaskRequest, TaskState};
+use super::registry::{
+    rank_nodes, select_node, AssignRequest, Assignment, GeoBucket, GeoLocation, Heartbeat, MatchScore, MatchWeights,
+    MatchingStrategy, NodeRecord, NodeRegistration, RegistryConfig,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use wasm_bindgen::JsCast;
+use worker::*;
+
+/// 边缘入口转发请求时附带的内部请求头
+pub(super) const AUTH_HEADER: &str = "X-Williw-Auth";
+pub(super) const PUBLIC_PATH_HEADER: &str = "X-Williw-Path";
+pub(super) const LOCATION_HEADER: &str = "X-Williw-Location";
+
+const NODE_PREFIX: &str = "node:";
+const GEO_PREFIX: &str = "geo:";
+const ASSIGN_PREFIX: &str = "assign:";
+const TASK_PREFIX: &str = "task:";
+const DEAD_PREFIX: &str = "dead:";
+const KEY_PREFIX: &str = "key:";
+const CHALLENGE_PREFIX: &str = "challenge:";
+const NONCE_PREFIX: &str = "nonce:";
+const RATE_PREFIX: &str = "rate:";
+const EVENT_PREFIX: &str = "event:";
+const EVENT_SEQ_KEY: &str = "event_seq";
+
+/// 就近查找时向外扩展的分桶圈数
+const SEARCH_RING: i32 = 1;
+
+fn node_key(node_id: &str) -> String {
+    format!("{NODE_PREFIX}{node_id}")
+}
+
+fn geo_key(bucket: &GeoBucket, node_id: &str) -> String {
+    format!("{GEO_PREFIX}{}:{node_id}", bucket.key())
+}
+
+fn assign_key(task_id: &str) -> String {
+    format!("{ASSIGN_PREFIX}{task_id}")
+}
+
+fn task_key(task_id: &str) -> String {
+    format!("{TASK_PREFIX}{task_id}")
+}
+
+fn dead_key(task_id: &str) -> String {
+    format!("{DEAD_PREFIX}{task_id}")
+}
+
+fn nonce_key(node_id: &str, nonce: &str) -> String {
+    format!("{NONCE_PREFIX}{node_id}:{nonce}")
+}
+
+/// 序号补零，使存储按键排序即按序号排序
+fn event_key(seq: u64) -> String {
+    format!("{EVENT_PREFIX}{seq:020}")
+}
+
+fn now_ms() -> u64 {
+    Date::now().as_millis()
+}
+
+#[derive(Deserialize)]
+struct ChallengeRequest {
+    node_id: String,
+}
+
+#[derive(Deserialize)]
+struct ReleaseRequest {
+    task_id: String,
+}
+
+#[derive(Deserialize)]
+struct LeaseRequest {
+    node_id: String,
+}
+
+/// 节点完成或放弃租约
+#[derive(Deserialize)]
+struct LeaseResult {
+    task_id: String,
+    node_id: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+fn parse_var<T: std::str::FromStr>(env: &Env, name: &str) -> Option<T> {
+    env.var(name).ok().and_then(|v| v.to_string().parse().ok())
+}
+
+fn from_js<T: DeserializeOwned>(value: wasm_bindgen::JsValue) -> Result<T> {
+    serde_wasm_bindgen::from_value(value).map_err(|e| Error::RustError(e.to_string()))
+}
+
+/// 解析请求体；请求未携带位置时用边缘识别的来源坐标补全
+fn parse_body<T: DeserializeOwned>(body: &[u8], fallback_location: Option<&GeoLocation>) -> Result<T> {
+    let mut value: Value = serde_json::from_slice(body)?;
+    if let Some(location) = fallback_location {
+        if value.get("location").is_none_or(Value::is_null) {
+            value["location"] = serde_json::to_value(location)?;
+        }
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+pub(super) fn auth_error(error: &AuthError) -> Result<Response> {
+    let response = Response::error(error.to_string(), error.status())?;
+    match error {
+        AuthError::RateLimited { retry_after_ms } => {
+            let headers = Headers::new();
+            headers.set("Retry-After", &retry_after_ms.div_ceil(1000).to_string())?;
+            Ok(response.with_headers(headers))
+        }
+        _ => Ok(response),
+    }
+}
+
+/// 使用运行时的 `crypto.randomUUID()` 生成挑战
+fn random_challenge() -> Result<String> {
+    let crypto = js_sys::Reflect::get(&js_sys::global(), &"crypto".into())?;
+    let random_uuid: js_sys::Function = js_sys::Reflect::get(&crypto, &"randomUUID".into())?.dyn_into()?;
+    random_uuid
+        .call0(&crypto)?
+        .as_string()
+        .ok_or_else(|| Error::RustError("randomUUID 返回值无效".to_string()))
+}
+
+/// 节点注册表 Durable Object
+#[durable_object]
+pub struct WorkerDurableObject {
+    state: State,
+    config: RegistryConfig,
+    queue: QueueConfig,
+    rate_limit: RateLimitConfig,
+}
+
+impl DurableObject for WorkerDurableObject {
+    fn new(state: State, env: Env) -> Self {
+        let mut config = RegistryConfig::default();
+        if let Some(ttl) = parse_var::<u64>(&env, "HEARTBEAT_TTL_SECS") {
+            config.heartbeat_ttl_ms = ttl * 1000;
+        }
+        if let Some(degrees) = parse_var::<f64>(&env, "GEO_BUCKET_DEGREES").filter(|d| *d > 0.0) {
+            config.bucket_degrees = degrees;
+        }
+        let mut queue = QueueConfig::default();
+        if let Some(attempts) = parse_var::<u32>(&env, "TASK_MAX_ATTEMPTS") {
+            queue.max_attempts = attempts.max(1);
+        }
+        if let Some(timeout) = parse_var::<u64>(&env, "TASK_VISIBILITY_TIMEOUT_SECS") {
+            queue.visibility_timeout_ms = timeout * 1000;
+        }
+        let mut rate_limit = RateLimitConfig::default();
+        if let Some(burst) = parse_var::<u32>(&env, "RATE_LIMIT_BURST") {
+            rate_limit.burst = burst.max(1);
+        }
+        if let Some(per_second) = parse_var::<f64>(&env, "RATE_LIMIT_PER_SEC").filter(|r| *r > 0.0) {
+            rate_limit.per_second = per_second;
+        }
+        Self {
+            state,
+            config,
+            queue,
+            rate_limit,
+        }
+    }
+
+    async fn fetch(&self, req: Request) -> Result<Response> {
+        let path = req.path();
+        match req.method() {
+            Method::Get => self.query(&req, &path).await,
+            Method::Post => self.command(req, &path).await,
+            _ => Response::error("Method Not Allowed", 405),
+        }
+    }
+
+    async fn alarm(&self) -> Result<Response> {
+        let removed = self.sweep().await?;
+        let reclaimed = self.reclaim_expired_leases().await?;
+        self.schedule_sweep().await?;
+        Response::ok(format!("removed {removed} expired nodes, reclaimed {reclaimed} leases"))
+    }
+
+    async fn websocket_message(&self, ws: WebSocket, message: WebSocketIncomingMessage) -> Result<()> {
+        if let WebSocketIncomingMessage::String(text) = message {
+            match serde_json::from_str::<ClientMessage>(&text) {
+                Ok(ClientMessage::Ping) => ws.send(&ServerMessage::Pong)?,
+                Err(e) => console_log!("[推送] 忽略无法解析的节点消息: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn websocket_close(&self, ws: WebSocket, code: usize, reason: String, _was_clean: bool) -> Result<()> {
+        // 节点断开后由其自行重连并补发
+        let _ = ws.close(Some(code as u16), Some(reason));
+        Ok(())
+    }
+}
+
+impl WorkerDurableObject {
+    fn storage(&self) -> Storage {
+        self.state.storage()
+    }
+
+    /// 只读查询，不需要签名
+    async fn query(&self, req: &Request, path: &str) -> Result<Response> {
+        match path {
+            "/nodes" => {
+                let url = req.url()?;
+                let query = |name: &str| {
+                    url.query_pairs()
+                        .find(|(key, _)| key == name)
+                        .and_then(|(_, value)| value.parse::<f64>().ok())
+                };
+                let location = match (query("lat"), query("lon")) {
+                    (Some(latitude), Some(longitude)) => Some(GeoLocation {
+                        latitude,
+                        longitude,
+                        country: None,
+                    }),
+                    _ => None,
+                };
+                Response::from_json(&self.alive_nodes(location.as_ref()).await?)
+            }
+            "/tasks/dead" => Response::from_json(&self.dead_letters().await?),
+            "/stream" => self.open_stream(req, path).await,
+            _ => Response::error("Not Found", 404),
+        }
+    }
+
+    /// 读取并验证边缘入口转发的签名信息
+    async fn authorized(
+        &self,
+        req: &Request,
+        method: &str,
+        path: &str,
+        body: &[u8],
+    ) -> Result<std::result::Result<RequestAuth, AuthError>> {
+        let headers = req.headers();
+        let Some(auth) = headers
+            .get(AUTH_HEADER)?
+            .and_then(|value| serde_json::from_str::<RequestAuth>(&value).ok())
+        else {
+            return Ok(Err(AuthError::Missing));
+        };
+        let public_path = headers.get(PUBLIC_PATH_HEADER)?.unwrap_or_else(|| path.to_string());
+        if let Err(e) = self.authenticate(&auth, method, path, &public_path, body).await? {
+            console_log!("[认证] 拒绝节点 {} 的请求 {}: {}", auth.node_id, public_path, e);
+            return Ok(Err(e));
+        }
+        Ok(Ok(auth))
+    }
+
+    /// 建立推送连接，先补发 `since` 之后的事件
+    async fn open_stream(&self, req: &Request, path: &str) -> Result<Response> {
+        if req.headers().get("Upgrade")?.as_deref() != Some("websocket") {
+            return Response::error("需要 WebSocket 升级请求", 426);
+        }
+        let auth = match self.authorized(req, "GET", path, &[]).await? {
+            Ok(auth) => auth,
+            Err(e) => return auth_error(&e),
+        };
+        let since = req
+            .url()?
+            .query_pairs()
+            .find(|(key, _)| key == "since")
+            .and_then(|(_, value)| value.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let pair = WebSocketPair::new()?;
+        self.state.accept_websocket_with_tags(&pair.server, &[auth.node_id.as_str()]);
+
+        let events = self.events().await?;
+        if let Some(oldest) = events.first() {
+            if since + 1 < oldest.seq {
+                pair.server.send(&ServerMessage::ReplayGap { oldest_seq: oldest.seq })?;
+            }
+        }
+        let mut last_seq = since;
+        for event in events.into_iter().filter(|event| event.seq > since) {
+            last_seq = event.seq;
+            if event.is_for(&auth.node_id) {
+                pair.server.send(&ServerMessage::Event { event })?;
+            }
+        }
+        pair.server.send(&ServerMessage::ReplayComplete { last_seq })?;
+        Response::from_websocket(pair.client)
+    }
+
+    /// 写入事件日志并推送给在线的目标节点（`target` 为空时广播）
+    async fn publish(&self, target: Option<&str>, kind: PushEventKind) -> Result<PushEvent> {
+        let storage = self.storage();
+        let seq = storage.get::<u64>(EVENT_SEQ_KEY).await?.unwrap_or(0) + 1;
+        let event = PushEvent {
+            seq,
+            target: target.map(str::to_string),
+            created_at_ms: now_ms(),
+            kind,
+        };
+        storage.put(EVENT_SEQ_KEY, seq).await?;
+        storage.put(&event_key(seq), &event).await?;
+
+        let sockets = match target {
+            Some(node_id) => self.state.get_websockets_with_tag(node_id),
+            None => self.state.get_websockets(),
+        };
+        let message = ServerMessage::Event { event: event.clone() };
+        for socket in sockets {
+            // 发送失败的连接由节点重连后补发
+            let _ = socket.send(&message);
+        }
+        Ok(event)
+    }
+
+    async fn events(&self) -> Result<Vec<PushEvent>> {
+        let entries = self
+            .storage()
+            .list_with_options(ListOptions::new().prefix(EVENT_PREFIX))
+            .await?;
+        entries.values().into_iter().map(|value| from_js(value?)).collect()
+    }
+
+    /// 删除超过保留时长的事件
+    async fn prune_events(&self, now: u64) -> Result<()> {
+        let storage = self.storage();
+        for event in self.events().await? {
+            if now.saturating_sub(event.created_at_ms) <= EVENT_RETENTION_MS {
+                break;
+            }
+            storage.delete(&event_key(event.seq)).await?;
+        }
+        Ok(())
+    }
+
+    /// 写请求：除领取注册挑战外都必须通过签名认证
+    async fn command(&self, mut req: Request, path: &str) -> Result<Response> {
+        let body = req.bytes().await?;
+        if path == "/challenge" {
+            return self.issue_challenge(parse_body::<ChallengeRequest>(&body, None)?.node_id).await;
+        }
+
+        if path == "/governance" {
+            // 只有边缘入口校验过管理令牌后才会转发到这里
+            let announcement: Value = serde_json::from_slice(&body)?;
+            let event = self.publish(None, PushEventKind::Governance { announcement }).await?;
+            return Response::from_json(&event);
+        }
+
+        if let Err(e) = self.authorized(&req, "POST", path, &body).await? {
+            return auth_error(&e);
+        }
+        let location = req
+            .headers()
+            .get(LOCATION_HEADER)?
+            .and_then(|value| serde_json::from_str::<GeoLocation>(&value).ok());
+
+        match path {
+            "/register" => self.register(parse_body(&body, location.as_ref())?).await,
+            "/heartbeat" => self.heartbeat(parse_body(&body, None)?).await,
+            "/assign" => self.assign(parse_body(&body, location.as_ref())?).await,
+            "/release" => self.release(parse_body::<ReleaseRequest>(&body, None)?.task_id).await,
+            "/tasks" => self.submit(parse_body(&body, None)?).await,
+            "/tasks/lease" => self.lease(parse_body::<LeaseRequest>(&body, None)?.node_id).await,
+            "/tasks/complete" => self.complete(parse_body(&body, None)?).await,
+            "/tasks/fail" => self.fail(parse_body(&body, None)?).await,
+            _ => Response::error("Not Found", 404),
+        }
+    }
+
+    async fn issue_challenge(&self, node_id: String) -> Result<Response> {
+        if node_id.is_empty() {
+            return Response::error("node_id 不能为空", 400);
+        }
+        let challenge = RegistrationChallenge {
+            challenge: random_challenge()?,
+            expires_at_ms: now_ms() + CHALLENGE_TTL_MS,
+        };
+        self.storage()
+            .put(&format!("{CHALLENGE_PREFIX}{node_id}"), &challenge)
+            .await?;
+        Response::from_json(&challenge)
+    }
+
+    /// 验证签名、nonce、限流和 node_id 归属；注册请求额外校验挑战并登记公钥
+    async fn authenticate(
+        &self,
+        auth: &RequestAuth,
+        method: &str,
+        path: &str,
+        public_path: &str,
+        body: &[u8],
+    ) -> Result<std::result::Result<(), AuthError>> {
+        let now = now_ms();
+        let storage = self.storage();
+        let challenge_key = format!("{CHALLENGE_PREFIX}{}", auth.node_id);
+        let key_key = format!("{KEY_PREFIX}{}", auth.node_id);
+        let body_json: Option<Value> = serde_json::from_slice(body).ok();
+        let field = |name: &str| {
+            body_json
+                .as_ref()
+                .and_then(|v| v.get(name))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+
+        let public_key = if path == "/register" {
+            // 节点 ID 即身份公钥，能对挑战签名即证明持有私钥
+            let challenge = storage.get::<RegistrationChallenge>(&challenge_key).await?;
+            match challenge {
+                Some(c) if c.expires_at_ms > now && field("challenge").as_deref() == Some(c.challenge.as_str()) => {
+                    auth.node_id.clone()
+                }
+                _ => return Ok(Err(AuthError::BadChallenge)),
+            }
+        } else {
+            match storage.get::<String>(&key_key).await? {
+                Some(key) => key,
+                None => return Ok(Err(AuthError::UnknownNode)),
+            }
+        };
+        if let Err(e) = auth.verify(&public_key, method, public_path, body, now) {
+            return Ok(Err(e));
+        }
+
+        let nonce_key = nonce_key(&auth.node_id, &auth.nonce);
+        if storage.get::<u64>(&nonce_key).await?.is_some() {
+            return Ok(Err(AuthError::Replay {
+                nonce: auth.nonce.clone(),
+            }));
+        }
+        // 超出时钟偏差窗口后时间戳校验就会拒绝，nonce 记录可以清理
+        storage.put(&nonce_key, auth.timestamp_ms + MAX_CLOCK_SKEW_MS).await?;
+
+        let rate_key = format!("{RATE_PREFIX}{}", auth.node_id);
+        let mut bucket = storage
+            .get::<TokenBucket>(&rate_key)
+            .await?
+            .unwrap_or_else(|| TokenBucket::full(&self.rate_limit, now));
+        let limited = bucket.take(&self.rate_limit, now);
+        storage.put(&rate_key, &bucket).await?;
+        if let Err(e) = limited {
+            return Ok(Err(e));
+        }
+
+        if field("node_id").is_some_and(|node_id| node_id != auth.node_id) {
+            return Ok(Err(AuthError::NodeMismatch));
+        }
+        if path == "/register" {
+            storage.delete(&challenge_key).await?;
+            storage.put(&key_key, &public_key).await?;
+        }
+        Ok(Ok(()))
+    }
+
+    async fn load_node(&self, node_id: &str) -> Result<Option<NodeRecord>> {
+        self.storage().get(&node_key(node_id)).await
+    }
+
+    async fn register(&self, registration: NodeRegistration) -> Result<Response> {
+        if registration.node_id.is_empty() {
+            return Response::error("node_id 不能为空", 400);
+        }
+        let now = now_ms();
+        let storage = self.storage();
+        let bucket = GeoBucket::of(&registration.location, self.config.bucket_degrees);
+        let record = match self.load_node(&registration.node_id).await? {
+            Some(mut existing) => {
+                // 重新注册：位置变化时迁移分桶索引，保留已分配任务计数
+                let old_bucket = GeoBucket::of(&existing.registration.location, self.config.bucket_degrees);
+                if old_bucket != bucket {
+                    storage.delete(&geo_key(&old_bucket, &registration.node_id)).await?;
+                }
+                existing.registration = registration;
+                existing.last_heartbeat_ms = now;
+                existing
+            }
+            None => NodeRecord {
+                registration,
+                registered_at_ms: now,
+                last_heartbeat_ms: now,
+                active_tasks: 0,
+            },
+        };
+        storage.put(&node_key(record.node_id()), &record).await?;
+        storage.put(&geo_key(&bucket, record.node_id()), record.node_id()).await?;
+        self.ensure_sweep_scheduled().await?;
+        Response::from_json(&record)
+    }
+
+    async fn heartbeat(&self, heartbeat: Heartbeat) -> Result<Response> {
+        let Some(mut record) = self.load_node(&heartbeat.node_id).await? else {
+            // 已被清理的节点需要重新注册
+            return Response::error("节点未注册或已过期", 404);
+        };
+        record.last_heartbeat_ms = now_ms();
+        if let Some(active_tasks) = heartbeat.active_tasks {
+            record.active_tasks = active_tasks;
+        }
+        self.storage().put(&node_key(record.node_id()), &record).await?;
+        Response::from_json(&record)
+    }
+
+    /// 给定位置时返回所在分桶及相邻分桶的存活节点，否则返回全部存活节点
+    async fn alive_nodes(&self, location: Option<&GeoLocation>) -> Result<Vec<NodeRecord>> {
+        let now = now_ms();
+        let ttl = self.config.heartbeat_ttl_ms;
+        let nodes = match location {
+            Some(location) => {
+                let bucket = GeoBucket::of(location, self.config.bucket_degrees);
+                let mut nodes = Vec::new();
+                for bucket in bucket.around(SEARCH_RING, self.config.bucket_degrees) {
+                    nodes.extend(self.nodes_in_bucket(&bucket).await?);
+                }
+                nodes
+            }
+            None => self.all_nodes().await?,
+        };
+        Ok(nodes.into_iter().filter(|node| node.is_alive(now, ttl)).collect())
+    }
+
+    async fn nodes_in_bucket(&self, bucket: &GeoBucket) -> Result<Vec<NodeRecord>> {
+        let prefix = format!("{GEO_PREFIX}{}:", bucket.key());
+        let index = self.storage().list_with_options(ListOptions::new().prefix(&prefix)).await?;
+        let mut nodes = Vec::new();
+        for value in index.values() {
+            let node_id = value?.as_string().unwrap_or_default();
+            if let Some(record) = self.load_node(&node_id).await? {
+                nodes.push(record);
+            }
+        }
+        Ok(nodes)
+    }
+
+    async fn all_nodes(&self) -> Result<Vec<NodeRecord>> {
+        let entries = self
+            .storage()
+            .list_with_options(ListOptions::new().prefix(NODE_PREFIX))
+            .await?;
+        entries
+            .values()
+            .into_iter()
+            .map(|value| from_js(value?))
+            .collect()
+    }
+
+    async fn assign(&self, request: AssignRequest) -> Result<Response> {
+        let storage = self.storage();
+        let key = assign_key(&request.task_id);
+        // 同一任务重复请求时返回已有分配
+        if let Some(existing) = storage.get::<Assignment>(&key).await? {
+            return Response::from_json(&existing);
+        }
+
+        let now = now_ms();
+        let ttl = self.config.heartbeat_ttl_ms;
+        let origin = match request.strategy {
+            MatchingStrategy::Geography => request.origin(),
+            MatchingStrategy::LeastLoaded => request.location.as_ref(),
+        };
+        // 先在参考位置附近的分桶中选，没有合适节点再扩大到全部节点
+        let mut chosen = None;
+        if let Some(location) = origin {
+            let nearby = self.alive_nodes(Some(location)).await?;
+            chosen = self.choose(&nearby, &request, origin, now, ttl);
+        }
+        if chosen.is_none() {
+            let all = self.all_nodes().await?;
+            chosen = self.choose(&all, &request, origin, now, ttl);
+        }
+        let Some((mut node, score)) = chosen else {
+            return Response::error("没有满足要求的可用节点", 503);
+        };
+
+        node.active_tasks += 1;
+        let assignment = Assignment {
+            task_id: request.task_id,
+            node_id: node.node_id().to_string(),
+            assigned_at_ms: now,
+            strategy: request.strategy,
+            score,
+        };
+        storage.put(&node_key(node.node_id()), &node).await?;
+        storage.put(&key, &assignment).await?;
+        self.publish(
+            Some(&assignment.node_id),
+            PushEventKind::TaskAssigned {
+                assignment: assignment.clone(),
+            },
+        )
+        .await?;
+        Response::from_json(&assignment)
+    }
+
+    /// 按请求的匹配策略选出节点，地理匹配时附带得分明细
+    fn choose(
+        &self,
+        candidates: &[NodeRecord],
+        request: &AssignRequest,
+        origin: Option<&GeoLocation>,
+        now: u64,
+        ttl: u64,
+    ) -> Option<(NodeRecord, Option<MatchScore>)> {
+        match request.strategy {
+            MatchingStrategy::LeastLoaded => {
+                select_node(candidates, &request.requirements, now, ttl).map(|node| (node.clone(), None))
+            }
+            MatchingStrategy::Geography => {
+                rank_nodes(candidates, &request.requirements, origin, &MatchWeights::default(), now, ttl)
+                    .into_iter()
+                    .next()
+                    .map(|(node, score)| (node.clone(), Some(score)))
+            }
+        }
+    }
+
+    async fn release(&self, task_id: String) -> Result<Response> {
+        let storage = self.storage();
+        let key = assign_key(&task_id);
+        let Some(assignment) = storage.get::<Assignment>(&key).await? else {
+            return Response::error("任务没有分配记录", 404);
+        };
+        if let Some(mut node) = self.load_node(&assignment.node_id).await? {
+            node.active_tasks = node.active_tasks.saturating_sub(1);
+            storage.put(&node_key(node.node_id()), &node).await?;
+        }
+        storage.delete(&key).await?;
+        Response::from_json(&assignment)
+    }
+
+    /// 删除心跳超时的节点、索引及其分配，返回删除的节点数
+    async fn sweep(&self) -> Result<usize> {
+        let now = now_ms();
+        let ttl = self.config.heartbeat_ttl_ms;
+        let storage = self.storage();
+        let mut expired = Vec::new();
+        for node in self.all_nodes().await? {
+            if !node.is_alive(now, ttl) {
+                let bucket = GeoBucket::of(&node.registration.location, self.config.bucket_degrees);
+                storage.delete(&geo_key(&bucket, node.node_id())).await?;
+                storage.delete(&node_key(node.node_id())).await?;
+                // 过期节点需重新完成注册握手
+                storage.delete(&format!("{KEY_PREFIX}{}", node.node_id())).await?;
+                storage.delete(&format!("{RATE_PREFIX}{}", node.node_id())).await?;
+                expired.push(node.registration.node_id);
+            }
+        }
+        if !expired.is_empty() {
+            let assignments = storage.list_with_options(ListOptions::new().prefix(ASSIGN_PREFIX)).await?;
+            for value in assignments.values() {
+                let assignment: Assignment = from_js(value?)?;
+                if expired.contains(&assignment.node_id) {
+                    storage.delete(&assign_key(&assignment.task_id)).await?;
+                    self.publish(
+                        Some(&assignment.node_id),
+                        PushEventKind::TaskReassigned {
+                            task_id: assignment.task_id.clone(),
+                            previous_node_id: assignment.node_id.clone(),
+                            reason: "节点心跳超时".to_string(),
+                        },
+                    )
+                    .await?;
+                }
+            }
+            console_log!("[注册表] 清理 {} 个过期节点", expired.len());
+        }
+        self.sweep_auth_state(now).await?;
+        self.prune_events(now).await?;
+        Ok(expired.len())
+    }
+
+    /// 删除过期的 nonce 和注册挑战
+    async fn sweep_auth_state(&self, now: u64) -> Result<()> {
+        let storage = self.storage();
+        let nonces = storage.list_with_options(ListOptions::new().prefix(NONCE_PREFIX)).await?;
+        for entry in nonces.entries() {
+            let pair: js_sys::Array = entry?.dyn_into()?;
+            let expires_at_ms: u64 = from_js(pair.get(1))?;
+            if expires_at_ms <= now {
+                if let Some(key) = pair.get(0).as_string() {
+                    storage.delete(&key).await?;
+                }
+            }
+        }
+        let challenges = storage.list_with_options(ListOptions::new().prefix(CHALLENGE_PREFIX)).await?;
+        for entry in challenges.entries() {
+            let pair: js_sys::Array = entry?.dyn_into()?;
+            let challenge: RegistrationChallenge = from_js(pair.get(1))?;
+            if challenge.expires_at_ms <= now {
+                if let Some(key) = pair.get(0).as_string() {
+                    storage.delete(&key).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn submit(&self, request: TaskRequest) -> Result<Response> {
+        if request.task_id.is_empty() {
+            return Response::error("task_id 不能为空", 400);
+        }
+        let storage = self.storage();
+        let key = task_key(&request.task_id);
+        // 重复提交返回已有任务
+        if let Some(existing) = storage.get::<QueuedTask>(&key).await? {
+            return Response::from_json(&existing);
+        }
+        if storage.get::<DeadLetter>(&dead_key(&request.task_id)).await?.is_some() {
+            return Response::error("任务已进入死信列表", 409);
+        }
+        let task = QueuedTask::new(request, now_ms());
+        storage.put(&key, &task).await?;
+        self.publish(None, PushEventKind::TaskAvailable { task: task.clone() })
+            .await?;
+        Response::from_json(&task)
+    }
+
+    /// 节点领取一个匹配的任务；没有可领取的任务时返回 204
+    async fn lease(&self, node_id: String) -> Result<Response> {
+        let now = now_ms();
+        let Some(mut node) = self.load_node(&node_id).await? else {
+            return Response::error("节点未注册或已过期", 404);
+        };
+        if !node.is_alive(now, self.config.heartbeat_ttl_ms) || !node.has_capacity() {
+            return Ok(Response::empty()?.with_status(204));
+        }
+        self.reclaim_expired_leases().await?;
+        let tasks = self.queued_tasks().await?;
+        let Some(task) = next_for_node(&tasks, &node) else {
+            return Ok(Response::empty()?.with_status(204));
+        };
+        let mut task = task.clone();
+        task.lease(&node_id, now, self.queue.visibility_timeout_ms);
+        node.active_tasks += 1;
+
+        let storage = self.storage();
+        storage.put(&task_key(task.task_id()), &task).await?;
+        storage.put(&node_key(&node_id), &node).await?;
+        self.schedule_sweep().await?;
+        Response::from_json(&task)
+    }
+
+    async fn complete(&self, result: LeaseResult) -> Result<Response> {
+        let Some(task) = self.leased_task(&result).await? else {
+            return Response::error("任务不存在或租约不属于该节点", 409);
+        };
+        self.storage().delete(&task_key(&result.task_id)).await?;
+        self.release_node_slot(&result.node_id).await?;
+        Response::from_json(&task)
+    }
+
+    async fn fail(&self, result: LeaseResult) -> Result<Response> {
+        let Some(task) = self.leased_task(&result).await? else {
+            return Response::error("任务不存在或租约不属于该节点", 409);
+        };
+        let reason = result.error.unwrap_or_else(|| "节点报告失败".to_string());
+        self.release_node_slot(&result.node_id).await?;
+        let task = self.retry_or_dead_letter(task, reason).await?;
+        Response::from_json(&task)
+    }
+
+    /// 仅当任务当前由该节点持有租约时返回
+    async fn leased_task(&self, result: &LeaseResult) -> Result<Option<QueuedTask>> {
+        let task = self.storage().get::<QueuedTask>(&task_key(&result.task_id)).await?;
+        Ok(task.filter(|task| task.leased_to() == Some(result.node_id.as_str())))
+    }
+
+    async fn release_node_slot(&self, node_id: &str) -> Result<()> {
+        if let Some(mut node) = self.load_node(node_id).await? {
+            node.active_tasks = node.active_tasks.saturating_sub(1);
+            self.storage().put(&node_key(node_id), &node).await?;
+        }
+        Ok(())
+    }
+
+    async fn retry_or_dead_letter(&self, mut task: QueuedTask, reason: String) -> Result<QueuedTask> {
+        let storage = self.storage();
+        match task.fail(reason, &self.queue) {
+            FailureOutcome::Retry => {
+                storage.put(&task_key(task.task_id()), &task).await?;
+                self.publish(None, PushEventKind::TaskAvailable { task: task.clone() })
+                    .await?;
+            }
+            FailureOutcome::DeadLetter => {
+                console_log!("[任务队列] 任务 {} 尝试 {} 次后进入死信", task.task_id(), task.attempts);
+                storage.delete(&task_key(task.task_id())).await?;
+                let dead = DeadLetter {
+                    task: task.clone(),
+                    dead_at_ms: now_ms(),
+                };
+                storage.put(&dead_key(task.task_id()), &dead).await?;
+            }
+        }
+        Ok(task)
+    }
+
+    /// 处理租约到期的任务，返回处理的任务数
+    async fn reclaim_expired_leases(&self) -> Result<usize> {
+        let now = now_ms();
+        let mut reclaimed = 0;
+        for task in self.queued_tasks().await? {
+            if !task.lease_expired(now) {
+                continue;
+            }
+            if let Some(node_id) = task.leased_to() {
+                self.release_node_slot(node_id).await?;
+                self.publish(
+                    Some(node_id),
+                    PushEventKind::TaskReassigned {
+                        task_id: task.task_id().to_string(),
+                        previous_node_id: node_id.to_string(),
+                        reason: "租约超时".to_string(),
+                    },
+                )
+                .await?;
+            }
+            self.retry_or_dead_letter(task, "租约超时".to_string()).await?;
+            reclaimed += 1;
+        }
+        Ok(reclaimed)
+    }
+
+    async fn queued_tasks(&self) -> Result<Vec<QueuedTask>> {
+        let entries = self
+            .storage()
+            .list_with_options(ListOptions::new().prefix(TASK_PREFIX))
+            .await?;
+        entries.values().into_iter().map(|value| from_js(value?)).collect()
+    }
+
+    async fn dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        let entries = self
+            .storage()
+            .list_with_options(ListOptions::new().prefix(DEAD_PREFIX))
+            .await?;
+        let mut dead: Vec<DeadLetter> = entries
+            .values()
+            .into_iter()
+            .map(|value| from_js(value?))
+            .collect::<Result<_>>()?;
+        dead.sort_by_key(|letter| letter.dead_at_ms);
+        Ok(dead)
+    }
+
+    /// 下一次 alarm：心跳清理周期与最早到期的租约取较早者
+    async fn schedule_sweep(&self) -> Result<()> {
+        let now = now_ms();
+        let mut next = now + self.config.heartbeat_ttl_ms;
+        for task in self.queued_tasks().await? {
+            if let TaskState::Leased { lease_expires_ms, .. } = task.state {
+                next = next.min(lease_expires_ms);
+            }
+        }
+        let delay = next.saturating_sub(now).max(1);
+        self.storage().set_alarm(Duration::from_millis(delay)).await
+    }
+
+    async fn ensure_sweep_scheduled(&self) -> Result<()> {
+        if self.storage().get_alarm().await?.is_none() {
+            self.schedule_sweep().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+//! 计算分数 BFT 共识轮的多成员测试：正常路径、领导者崩溃和作恶领导者触发的视图切换
+//!
+//! 运行：`cargo test --features testkit --test bft_consensus`
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use williw::consensus::bft::SignedBftMessage;
+use williw::consensus::{BftConfig, BftRound, Committee, ScoreVector};
+use williw::crypto::NodeIdentity;
+use williw::testkit::node::MemoryKeystore;
+
+const EPOCH: u64 = 7;
+
+fn scores(factor: f64) -> ScoreVector {
+    let scores = (0..4).map(|i| (format!("worker-{}", i), (i + 1) as f64 * 10.0 * factor)).collect::<BTreeMap<_, _>>();
+    ScoreVector::new(EPOCH, scores)
+}
+
+/// 委员会成员集合，消息经过一个广播队列投递给所有未崩溃的成员
+struct Harness {
+    rounds: Vec<BftRound>,
+    crashed: Vec<bool>,
+    queue: VecDeque<(usize, SignedBftMessage)>,
+}
+
+impl Harness {
+    /// `local(i)` 给出按委员会顺序第 i 个成员观测到的分数
+    fn new(size: usize, local: impl Fn(usize) -> ScoreVector) -> Self {
+        let identities: Vec<Arc<NodeIdentity>> = (0..size)
+            .map(|_| Arc::new(NodeIdentity::with_keystore(Arc::new(MemoryKeystore::default())).unwrap()))
+            .collect();
+        let committee = Committee::new(identities.iter().map(|identity| identity.node_id()));
+        let mut ordered = identities;
+        ordered.sort_by_key(|identity| identity.node_id());
+        let rounds = ordered
+            .into_iter()
+            .enumerate()
+            .map(|(i, identity)| BftRound::new(BftConfig::default(), committee.clone(), identity, local(i)))
+            .collect();
+        Self {
+            rounds,
+            crashed: vec![false; size],
+            queue: VecDeque::new(),
+        }
+    }
+
+    fn leader_index(&self, view: u64) -> usize {
+        let committee = self.rounds[0].committee();
+        let leader = committee.leader(EPOCH, view);
+        committee.members().iter().position(|member| member == leader).unwrap()
+    }
+
+    fn send(&mut self, from: usize, messages: Vec<SignedBftMessage>) {
+        if !self.crashed[from] {
+            self.queue.extend(messages.into_iter().map(|message| (from, message)));
+        }
+    }
+
+    fn start(&mut self) {
+        for i in 0..self.rounds.len() {
+            let messages = self.rounds[i].start().unwrap();
+            self.send(i, messages);
+        }
+    }
+
+    fn timeout_all(&mut self) {
+        for i in 0..self.rounds.len() {
+            if !self.crashed[i] {
+                let messages = self.rounds[i].on_timeout().unwrap();
+                self.send(i, messages);
+            }
+        }
+    }
+
+    fn run(&mut self) {
+        while let Some((from, message)) = self.queue.pop_front() {
+            for to in 0..self.rounds.len() {
+                if to == from || self.crashed[to] {
+                    continue;
+                }
+                let replies = self.rounds[to].handle(message.clone()).unwrap();
+                self.send(to, replies);
+            }
+        }
+    }
+
+    fn live(&self) -> impl Iterator<Item = &BftRound> {
+        self.rounds.iter().zip(&self.crashed).filter(|(_, crashed)| !**crashed).map(|(round, _)| round)
+    }
+}
+
+#[test]
+fn test_committee_agrees_on_leader_vector() {
+    let mut harness = Harness::new(4, |i| scores(1.0 + i as f64 * 0.01));
+    harness.start();
+    harness.run();
+
+    let leader = harness.leader_index(0);
+    let expected = scores(1.0 + leader as f64 * 0.01);
+    for round in harness.live() {
+        let certificate = round.decided().expect("每个成员都应达成决定");
+        assert_eq!(certificate.view, 0);
+        assert_eq!(certificate.vector, expected);
+        assert!(certificate.verify(round.committee()));
+    }
+}
+
+#[test]
+fn test_leader_crash_triggers_view_change() {
+    let mut harness = Harness::new(4, |_| scores(1.0));
+    let leader = harness.leader_index(0);
+    harness.crashed[leader] = true;
+    harness.start();
+    harness.run();
+    assert!(harness.live().all(|round| round.decided().is_none()));
+
+    harness.timeout_all();
+    harness.run();
+    for round in harness.live() {
+        let certificate = round.decided().expect("视图切换后应达成决定");
+        assert_eq!(certificate.view, 1);
+        assert_eq!(certificate.vector, scores(1.0));
+        assert!(certificate.verify(round.committee()));
+    }
+}
+
+#[test]
+fn test_inflated_proposal_is_replaced_after_view_change() {
+    let size = 4;
+    // 视图 0 的领导者（按委员会顺序第 epoch % n 个）虚报分数，诚实成员拒绝投票
+    let leader = EPOCH as usize % size;
+    let mut harness = Harness::new(size, |i| if i == leader { scores(2.0) } else { scores(1.0) });
+    assert_eq!(harness.leader_index(0), leader);
+    harness.start();
+    harness.run();
+    assert!(harness.live().all(|round| round.decided().is_none()));
+
+    harness.timeout_all();
+    harness.run();
+    let certificate = harness.rounds[(leader + 1) % size].decided().expect("诚实领导者应推动决定");
+    assert_eq!(certificate.vector, scores(1.0));
+    assert!(certificate.view >= 1);
+}
@@ -0,0 +1,54 @@
+//! 多节点端到端模拟：节点流失、网络分区和慢节点场景
+//!
+//! 运行：`cargo test --features testkit --test multi_node_sim`
+
+#![cfg(feature = "testkit")]
+
+use williw::testkit::{ScenarioReport, ScenarioScript, SimCluster, SimClusterConfig, SolanaTestValidator};
+
+fn run_scenario(file: &str) -> ScenarioReport {
+    let path = format!("{}/tests/scenarios/{}", env!("CARGO_MANIFEST_DIR"), file);
+    let json = std::fs::read_to_string(&path).unwrap();
+    let script = ScenarioScript::from_json(&json).unwrap();
+    let config = SimClusterConfig {
+        nodes: script.nodes,
+        ..SimClusterConfig::default()
+    };
+    let report = SimCluster::spawn(config).unwrap().run(&script).unwrap();
+    println!("[测试套件] {:?}", report);
+    report
+}
+
+#[test]
+fn test_node_churn_reassigns_tasks() {
+    let report = run_scenario("node_churn.json");
+    assert_eq!(report.tasks_completed, report.tasks_submitted);
+    assert!(report.tasks_reassigned > 0, "下线节点的任务应被重新分配");
+    assert_eq!(report.online_nodes, 4);
+    assert!(report.final_divergence < 0.5, "离散度 {}", report.final_divergence);
+}
+
+#[test]
+fn test_network_partition_converges_after_heal() {
+    let report = run_scenario("network_partition.json");
+    assert_eq!(report.tasks_completed, report.tasks_submitted);
+    assert!(report.network.dropped > 0);
+    assert!(report.final_divergence < 0.5, "离散度 {}", report.final_divergence);
+}
+
+#[test]
+fn test_slow_peer_still_converges() {
+    let report = run_scenario("slow_peer.json");
+    assert_eq!(report.tasks_completed, report.tasks_submitted);
+    assert_eq!(report.rejected_messages, 0);
+    assert!(report.final_divergence < 0.5, "离散度 {}", report.final_divergence);
+}
+
+/// 需要本地安装 Solana CLI，未安装时跳过
+#[test]
+fn test_local_validator_starts() {
+    let Some(validator) = SolanaTestValidator::start(18899).unwrap() else {
+        return;
+    };
+    assert!(validator.rpc_url().ends_with(":18899"));
+}